@@ -7,4 +7,48 @@ fn main() {
     {
         println!("cargo:rustc-cfg=use_colorsync_cgdisplaycreateuuidfromdisplayid");
     }
+
+    // The X11 and Wayland backends are built on the free unixes with their feature, alias them
+    // to keep the gates of the code they share with the other platforms short
+    println!("cargo:rustc-check-cfg=cfg(x11_platform)");
+    println!("cargo:rustc-check-cfg=cfg(wayland_platform)");
+    let free_unix = std::env::var("CARGO_CFG_TARGET_OS").map_or(false, |os| {
+        matches!(
+            os.as_str(),
+            "linux" | "dragonfly" | "freebsd" | "openbsd" | "netbsd"
+        )
+    });
+    if free_unix && std::env::var_os("CARGO_FEATURE_X11").is_some() {
+        println!("cargo:rustc-cfg=x11_platform");
+    }
+    if free_unix && std::env::var_os("CARGO_FEATURE_WAYLAND").is_some() {
+        println!("cargo:rustc-cfg=wayland_platform");
+    }
+
+    // Generate the Wayland protocols which aren't part of `wayland-protocols`
+    #[cfg(feature = "wayland")]
+    {
+        let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+        if matches!(
+            target_os.as_str(),
+            "linux" | "dragonfly" | "freebsd" | "openbsd" | "netbsd"
+        ) {
+            let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+            wayland_scanner::generate_code(
+                "src/platform_impl/linux/wayland/protocols/blur.xml",
+                out_dir.join("blur_client_api.rs"),
+                wayland_scanner::Side::Client,
+            );
+            wayland_scanner::generate_code(
+                "src/platform_impl/linux/wayland/protocols/color-management-v1.xml",
+                out_dir.join("color_management_client_api.rs"),
+                wayland_scanner::Side::Client,
+            );
+            wayland_scanner::generate_code(
+                "src/platform_impl/linux/wayland/protocols/kde-output-device-v2.xml",
+                out_dir.join("kde_output_device_client_api.rs"),
+                wayland_scanner::Side::Client,
+            );
+        }
+    }
 }
@@ -0,0 +1,82 @@
+use simple_logger::SimpleLogger;
+use winit::{
+    clipboard::{ClipboardKind, ClipboardMimedContent, MimeType},
+    event::{ElementState, Event, KeyboardInput, StartCause, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+fn main() {
+    SimpleLogger::new().init().unwrap();
+    let event_loop = EventLoop::new();
+
+    let window = WindowBuilder::new()
+        .with_title("A fantastic window!")
+        .build(&event_loop)
+        .unwrap();
+
+    let mut copies = 0;
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+
+        match event {
+            Event::NewEvents(StartCause::Init) => {
+                eprintln!("Press \"c\" to copy text into the clipboard, and \"v\" to paste it.")
+            }
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::C),
+                            ..
+                        },
+                    ..
+                } => {
+                    copies += 1;
+                    let text = format!("Copied from winit {} time(s)", copies);
+                    match window
+                        .set_clipboard(ClipboardKind::Clipboard, ClipboardMimedContent::Text(text))
+                    {
+                        Ok(()) => println!("Copied!"),
+                        Err(err) => println!("Failed to copy: {}", err),
+                    }
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::V),
+                            ..
+                        },
+                    ..
+                } => {
+                    let picker = Box::new(|offered: &[MimeType]| {
+                        println!("Offered: {:?}", offered);
+                        offered
+                            .iter()
+                            .find(|&mime| *mime == MimeType::Text)
+                            .cloned()
+                    });
+                    if let Err(err) = window.read_clipboard(ClipboardKind::Clipboard, picker) {
+                        println!("Failed to paste: {}", err);
+                    }
+                }
+                WindowEvent::ClipboardData {
+                    content: Some(ClipboardMimedContent::Text(text)),
+                    ..
+                } => println!("Pasted: {:?}", text),
+                WindowEvent::ClipboardData { content, .. } => {
+                    println!("Nothing to paste: {:?}", content)
+                }
+                WindowEvent::ClipboardChanged { kind } => {
+                    println!("{:?} was changed by another application", kind)
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    });
+}
@@ -0,0 +1,35 @@
+use simple_logger::SimpleLogger;
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+fn main() {
+    SimpleLogger::new().init().unwrap();
+    let event_loop = EventLoop::new();
+
+    let _window = WindowBuilder::new()
+        .with_title("Press the buttons of a gamepad")
+        .build(&event_loop)
+        .unwrap();
+
+    if let Err(error) = event_loop.set_gamepads_enabled(true) {
+        eprintln!("The gamepads can't be enabled: {}", error);
+    }
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+
+        match event {
+            Event::GamepadEvent { gamepad_id, event } => {
+                println!("{:?}: {:?}", gamepad_id, event);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            _ => (),
+        }
+    });
+}
@@ -32,6 +32,13 @@ fn main() {
                         pos.y -= (y * pixels_per_line) as i32;
                         window.set_outer_position(pos)
                     }
+                    winit::event::MouseScrollDelta::Value120(x, y) => {
+                        println!("mouse wheel Value120: ({},{})", x, y);
+                        let mut pos = window.outer_position().unwrap();
+                        pos.x -= x;
+                        pos.y -= y;
+                        window.set_outer_position(pos)
+                    }
                     winit::event::MouseScrollDelta::PixelDelta(p) => {
                         println!("mouse wheel Pixel Delta: ({},{})", p.x, p.y);
                         let mut pos = window.outer_position().unwrap();
@@ -0,0 +1,48 @@
+// Limit this example to only compatible platforms.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn main() {
+    use simple_logger::SimpleLogger;
+    use winit::{
+        event::{Event, WindowEvent},
+        event_loop::EventLoop,
+        platform::run_async::EventLoopExtRunAsync,
+        window::WindowBuilder,
+    };
+
+    SimpleLogger::new().init().unwrap();
+    let mut event_loop: EventLoop<()> = EventLoop::new();
+
+    event_loop.run_async(|mut events| async move {
+        let window = events
+            .with_window_target(|target| {
+                WindowBuilder::new()
+                    .with_title("A fantastic window!")
+                    .build(target)
+            })
+            .unwrap();
+
+        loop {
+            match events.next_event().await {
+                Event::WindowEvent {
+                    event: WindowEvent::CloseRequested,
+                    window_id,
+                } if window_id == window.id() => break,
+                Event::WindowEvent { event, .. } => println!("{:?}", event),
+                _ => (),
+            }
+        }
+    });
+}
+
+#[cfg(any(target_os = "ios", target_os = "android", target_arch = "wasm32"))]
+fn main() {
+    println!("This platform doesn't support run_async.");
+}
@@ -15,7 +15,7 @@ fn main() {
     use winit::{
         event::{Event, WindowEvent},
         event_loop::{ControlFlow, EventLoop},
-        platform::run_return::EventLoopExtRunReturn,
+        platform::run_on_demand::EventLoopExtRunOnDemand,
         window::WindowBuilder,
     };
     let mut event_loop = EventLoop::new();
@@ -29,7 +29,7 @@ fn main() {
     let mut quit = false;
 
     while !quit {
-        event_loop.run_return(|event, _, control_flow| {
+        event_loop.run_on_demand(|event, _, control_flow| {
             *control_flow = ControlFlow::Wait;
 
             if let Event::WindowEvent { event, .. } = &event {
@@ -59,5 +59,5 @@ fn main() {
 
 #[cfg(any(target_os = "ios", target_os = "android", target_arch = "wasm32"))]
 fn main() {
-    println!("This platform doesn't support run_return.");
+    println!("This platform doesn't support run_on_demand.");
 }
@@ -0,0 +1,496 @@
+//! Types useful for interacting with the system clipboard.
+//!
+//! The clipboard can be set with [`Window::set_clipboard`] and
+//! [`EventLoopWindowTarget::set_clipboard`], and read with [`Window::read_clipboard`], which
+//! delivers the content through [`WindowEvent::ClipboardData`]. Changes made by other
+//! applications are reported with [`WindowEvent::ClipboardChanged`].
+//!
+//! Large data can be produced and consumed incrementally with a [`ClipboardProvider`] and a
+//! [`ClipboardConsumer`], see [`Window::set_clipboard_stream`] and
+//! [`Window::read_clipboard_stream`]. Content which is expensive to serialize can be rendered
+//! only once it's requested, see [`Window::set_clipboard_provider`].
+//!
+//! [`Window::set_clipboard`]: crate::window::Window::set_clipboard
+//! [`Window::read_clipboard`]: crate::window::Window::read_clipboard
+//! [`Window::set_clipboard_stream`]: crate::window::Window::set_clipboard_stream
+//! [`Window::read_clipboard_stream`]: crate::window::Window::read_clipboard_stream
+//! [`Window::set_clipboard_provider`]: crate::window::Window::set_clipboard_provider
+//! [`EventLoopWindowTarget::set_clipboard`]: crate::event_loop::EventLoopWindowTarget::set_clipboard
+//! [`WindowEvent::ClipboardData`]: crate::event::WindowEvent::ClipboardData
+//! [`WindowEvent::ClipboardChanged`]: crate::event::WindowEvent::ClipboardChanged
+
+use std::borrow::Cow;
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str;
+use std::sync::Mutex;
+
+/// The clipboard to operate on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ClipboardKind {
+    /// The regular clipboard, which is set by a copy action and read by a paste action.
+    Clipboard,
+
+    /// The primary selection, which is set by selecting text and usually pasted with the middle
+    /// mouse button.
+    ///
+    /// Only exists on X11 and Wayland.
+    Primary,
+}
+
+/// The kind of data stored in the clipboard.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MimeType {
+    /// UTF-8 encoded text.
+    Text,
+
+    /// PNG encoded image.
+    PngImage,
+
+    /// UTF-8 encoded HTML document or fragment.
+    Html,
+
+    /// Rich Text Format document.
+    Rtf,
+
+    /// A list of files.
+    FileList,
+
+    /// Data of a kind unknown to winit, identified by its MIME type, such as
+    /// `application/x-myapp-nodes`.
+    Other(String),
+}
+
+/// The mapping of the kinds of data to the MIME type strings, for the backends with a clipboard.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_arch = "wasm32",
+    x11_platform,
+    wayland_platform
+))]
+mod mime_names {
+    use super::MimeType;
+
+    impl MimeType {
+        /// The MIME type strings under which the data of this kind is exchanged, with the
+        /// preferred one coming first.
+        pub(crate) fn mime_types(&self) -> Vec<&str> {
+            match self {
+                MimeType::Text => vec![
+                    "text/plain;charset=utf-8",
+                    "UTF8_STRING",
+                    "text/plain",
+                    "STRING",
+                    "TEXT",
+                ],
+                MimeType::PngImage => vec!["image/png"],
+                MimeType::Html => vec!["text/html"],
+                MimeType::Rtf => vec!["text/rtf", "application/rtf"],
+                MimeType::FileList => vec!["text/uri-list"],
+                MimeType::Other(mime) => vec![mime],
+            }
+        }
+
+        /// The kind of data exchanged under the given MIME type string.
+        ///
+        /// Returns `None` for the strings which aren't MIME types, like the special targets of X11.
+        pub(crate) fn from_mime_type(mime: &str) -> Option<MimeType> {
+            let known = [
+                MimeType::Text,
+                MimeType::PngImage,
+                MimeType::Html,
+                MimeType::Rtf,
+                MimeType::FileList,
+            ]
+            .iter()
+            .find(|mime_type| mime_type.mime_types().contains(&mime))
+            .cloned();
+
+            match known {
+                Some(mime_type) => Some(mime_type),
+                None if mime.contains('/') => Some(MimeType::Other(mime.to_owned())),
+                None => None,
+            }
+        }
+
+        /// The kinds of data among the offered MIME type strings, in the order they were offered
+        /// and without duplicates.
+        #[allow(dead_code)] // Not used on every platform
+        pub(crate) fn from_mime_types<'a, I>(mimes: I) -> Vec<MimeType>
+        where
+            I: IntoIterator<Item = &'a str>,
+        {
+            let mut mime_types = Vec::new();
+            for mime_type in mimes.into_iter().filter_map(MimeType::from_mime_type) {
+                if !mime_types.contains(&mime_type) {
+                    mime_types.push(mime_type);
+                }
+            }
+            mime_types
+        }
+    }
+}
+
+impl MimeType {
+    /// The kind of data delivered when something is dropped into a window, which is a list of
+    /// files if one is offered, the first offered kind known to winit otherwise, and the first
+    /// offered kind if there's no known one.
+    #[allow(dead_code)] // Not used on every platform
+    pub(crate) fn preferred_drop_mime_type(offered: &[MimeType]) -> Option<MimeType> {
+        offered
+            .iter()
+            .find(|mime_type| **mime_type == MimeType::FileList)
+            .or_else(|| {
+                offered
+                    .iter()
+                    .find(|mime_type| !matches!(mime_type, MimeType::Other(_)))
+            })
+            .or_else(|| offered.first())
+            .cloned()
+    }
+}
+
+/// A callback picking the kind of data to read from the offered ones.
+///
+/// The offered kinds are ordered by the preference of the clipboard owner, when the platform
+/// provides such information. Returning `None` cancels the read.
+pub type MimePicker = Box<dyn FnOnce(&[MimeType]) -> Option<MimeType> + Send>;
+
+/// A piece of data stored in the clipboard along with its kind.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ClipboardMimedContent {
+    /// UTF-8 encoded text.
+    Text(String),
+
+    /// PNG encoded image.
+    PngImage(Vec<u8>),
+
+    /// UTF-8 encoded HTML document or fragment.
+    Html(String),
+
+    /// Rich Text Format document.
+    Rtf(String),
+
+    /// A list of files, such as the ones copied in a file manager.
+    FileList(Vec<PathBuf>),
+
+    /// Data of a kind unknown to winit, see [`MimeType::Other`].
+    Bytes { mime: String, data: Vec<u8> },
+}
+
+impl ClipboardMimedContent {
+    /// The kind of the content.
+    pub fn mime_type(&self) -> MimeType {
+        match self {
+            ClipboardMimedContent::Text(_) => MimeType::Text,
+            ClipboardMimedContent::PngImage(_) => MimeType::PngImage,
+            ClipboardMimedContent::Html(_) => MimeType::Html,
+            ClipboardMimedContent::Rtf(_) => MimeType::Rtf,
+            ClipboardMimedContent::FileList(_) => MimeType::FileList,
+            ClipboardMimedContent::Bytes { mime, .. } => MimeType::Other(mime.clone()),
+        }
+    }
+
+    /// Decodes the content of the given kind from the raw bytes.
+    pub(crate) fn from_bytes(mime_type: MimeType, data: Vec<u8>) -> Self {
+        let text = |data: Vec<u8>| match String::from_utf8(data) {
+            Ok(text) => text,
+            Err(err) => String::from_utf8_lossy(err.as_bytes()).into(),
+        };
+        match mime_type {
+            MimeType::Text => ClipboardMimedContent::Text(text(data)),
+            MimeType::PngImage => ClipboardMimedContent::PngImage(data),
+            MimeType::Html => ClipboardMimedContent::Html(text(data)),
+            MimeType::Rtf => ClipboardMimedContent::Rtf(text(data)),
+            MimeType::FileList => ClipboardMimedContent::FileList(decode_uri_list(&data)),
+            MimeType::Other(mime) => ClipboardMimedContent::Bytes { mime, data },
+        }
+    }
+
+    /// Reads the data of the given kind from the provider at once.
+    #[allow(dead_code)] // Not used on every platform
+    pub(crate) fn from_provider(
+        provider: &dyn ClipboardProvider,
+        mime_type: MimeType,
+    ) -> io::Result<Self> {
+        let mut data = Vec::new();
+        provider.write_to(&mime_type, &mut data)?;
+        Ok(ClipboardMimedContent::from_bytes(mime_type, data))
+    }
+
+    /// The raw bytes of the content.
+    ///
+    /// A list of files is encoded as a `text/uri-list`.
+    pub fn to_bytes(&self) -> Cow<'_, [u8]> {
+        match self {
+            ClipboardMimedContent::Text(text)
+            | ClipboardMimedContent::Html(text)
+            | ClipboardMimedContent::Rtf(text) => Cow::Borrowed(text.as_bytes()),
+            ClipboardMimedContent::PngImage(data) | ClipboardMimedContent::Bytes { data, .. } => {
+                Cow::Borrowed(data)
+            }
+            ClipboardMimedContent::FileList(paths) => Cow::Owned(encode_uri_list(paths)),
+        }
+    }
+}
+
+/// A source of the clipboard data, which produces the data once some application requests it.
+///
+/// Every [`ClipboardMimedContent`] is a provider of its own data.
+pub trait ClipboardProvider: Send + Sync {
+    /// The kinds of data offered, the preferred one first.
+    fn mime_types(&self) -> Vec<MimeType>;
+
+    /// Writes the data of the given kind, which is one of the offered ones, into `writer`.
+    ///
+    /// This is called each time some application requests the data, possibly on a
+    /// different thread.
+    fn write_to(&self, mime_type: &MimeType, writer: &mut dyn Write) -> io::Result<()>;
+
+    /// The data of the given kind, which is one of the offered ones, without encoding it.
+    ///
+    /// This is used to hand the data over within the application, like when it's dragged
+    /// between the windows of the application. Returning `None` falls back to
+    /// [`write_to`](Self::write_to), which is what the default implementation does.
+    fn content(&self, mime_type: &MimeType) -> Option<ClipboardMimedContent> {
+        let _ = mime_type;
+        None
+    }
+}
+
+impl ClipboardProvider for ClipboardMimedContent {
+    fn mime_types(&self) -> Vec<MimeType> {
+        vec![self.mime_type()]
+    }
+
+    fn write_to(&self, _mime_type: &MimeType, writer: &mut dyn Write) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    fn content(&self, _mime_type: &MimeType) -> Option<ClipboardMimedContent> {
+        Some(self.clone())
+    }
+}
+
+/// A provider rendering the content with a callback, once some application requests it.
+pub(crate) struct CallbackProvider<F> {
+    mime_types: Vec<MimeType>,
+    render: Mutex<F>,
+}
+
+impl<F> CallbackProvider<F> {
+    pub(crate) fn new(mime_types: Vec<MimeType>, render: F) -> Self {
+        Self {
+            mime_types,
+            render: Mutex::new(render),
+        }
+    }
+}
+
+impl<F> ClipboardProvider for CallbackProvider<F>
+where
+    F: FnMut(MimeType) -> ClipboardMimedContent + Send,
+{
+    fn mime_types(&self) -> Vec<MimeType> {
+        self.mime_types.clone()
+    }
+
+    fn write_to(&self, mime_type: &MimeType, writer: &mut dyn Write) -> io::Result<()> {
+        let content = (self.render.lock().unwrap())(mime_type.clone());
+        writer.write_all(&content.to_bytes())
+    }
+
+    fn content(&self, mime_type: &MimeType) -> Option<ClipboardMimedContent> {
+        Some((self.render.lock().unwrap())(mime_type.clone()))
+    }
+}
+
+/// A receiver of the clipboard data, which reads the data as it arrives.
+///
+/// It's implemented for closures taking the same arguments as [`ClipboardConsumer::consume`].
+pub trait ClipboardConsumer: Send {
+    /// Reads the data of the picked kind from the reader.
+    ///
+    /// Gets `None` if the clipboard was empty, none of the offered kinds of data was picked, or
+    /// the data couldn't be retrieved from its owner. This may be called on a different thread.
+    fn consume(self: Box<Self>, data: Option<(MimeType, &mut dyn Read)>);
+}
+
+impl<F> ClipboardConsumer for F
+where
+    F: FnOnce(Option<(MimeType, &mut dyn Read)>) + Send,
+{
+    fn consume(self: Box<Self>, data: Option<(MimeType, &mut dyn Read)>) {
+        (*self)(data)
+    }
+}
+
+/// Passes the data which was read at once to the consumer.
+pub(crate) fn consume_bytes(
+    consumer: Box<dyn ClipboardConsumer>,
+    data: Option<(MimeType, Vec<u8>)>,
+) {
+    match data {
+        Some((mime_type, data)) => consumer.consume(Some((mime_type, &mut Cursor::new(data)))),
+        None => consumer.consume(None),
+    }
+}
+
+/// Passes the content which was read at once to the consumer.
+#[allow(dead_code)] // Not used on every platform
+pub(crate) fn consume_content(
+    consumer: Box<dyn ClipboardConsumer>,
+    content: Option<ClipboardMimedContent>,
+) {
+    let data = content.map(|content| (content.mime_type(), content.to_bytes().into_owned()));
+    consume_bytes(consumer, data)
+}
+
+/// Encodes the paths as `file` URIs of a `text/uri-list`.
+fn encode_uri_list(paths: &[PathBuf]) -> Vec<u8> {
+    let mut list = Vec::new();
+    for path in paths {
+        list.extend_from_slice(b"file://");
+        for &byte in path_to_bytes(path).iter() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                    list.push(byte)
+                }
+                _ => list.extend_from_slice(format!("%{:02X}", byte).as_bytes()),
+            }
+        }
+        list.extend_from_slice(b"\r\n");
+    }
+    list
+}
+
+/// Decodes the paths of the `file` URIs of a `text/uri-list`, the other URIs are skipped.
+fn decode_uri_list(list: &[u8]) -> Vec<PathBuf> {
+    list.split(|&byte| byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.starts_with(b"#"))
+        .filter_map(|uri| {
+            let uri = uri.strip_prefix(b"file://")?;
+            // Skip the host, which is either empty or `localhost` for the local files.
+            let path = &uri[uri.iter().position(|&byte| byte == b'/')?..];
+            Some(path_from_bytes(percent_decode(path)))
+        })
+        .collect()
+}
+
+fn percent_decode(data: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(data.len());
+    let mut index = 0;
+    while index < data.len() {
+        let escaped = data
+            .get(index + 1..index + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok());
+        match (data[index], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    decoded
+}
+
+#[cfg(unix)]
+fn path_to_bytes(path: &Path) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn path_to_bytes(path: &Path) -> Cow<'_, [u8]> {
+    match path.to_string_lossy() {
+        Cow::Borrowed(path) => Cow::Borrowed(path.as_bytes()),
+        Cow::Owned(path) => Cow::Owned(path.into_bytes()),
+    }
+}
+
+#[cfg(unix)]
+fn path_from_bytes(path: Vec<u8>) -> PathBuf {
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt};
+    OsString::from_vec(path).into()
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(path: Vec<u8>) -> PathBuf {
+    String::from_utf8_lossy(&path).into_owned().into()
+}
+
+impl From<String> for ClipboardMimedContent {
+    fn from(text: String) -> Self {
+        ClipboardMimedContent::Text(text)
+    }
+}
+
+impl From<&str> for ClipboardMimedContent {
+    fn from(text: &str) -> Self {
+        ClipboardMimedContent::Text(text.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uri_list_is_encoded_per_line() {
+        let paths = [PathBuf::from("/tmp/a b"), PathBuf::from("/tmp/c~d_e-f.txt")];
+        assert_eq!(
+            encode_uri_list(&paths),
+            b"file:///tmp/a%20b\r\nfile:///tmp/c~d_e-f.txt\r\n".to_vec(),
+        );
+    }
+
+    #[test]
+    fn uri_list_skips_comments_and_other_uris() {
+        let list = b"# comment\r\n\
+            file:///tmp/a%20b\r\n\
+            https://example.com/c\r\n\
+            file://localhost/home/d\n\
+            file://host\r\n\
+            \r\n";
+        assert_eq!(
+            decode_uri_list(list),
+            vec![PathBuf::from("/tmp/a b"), PathBuf::from("/home/d")],
+        );
+    }
+
+    #[test]
+    fn percent_escapes_are_decoded() {
+        assert_eq!(percent_decode(b"a%41b%4a"), b"aAbJ".to_vec());
+        // The invalid and truncated escapes are kept as they are.
+        assert_eq!(percent_decode(b"%zz%4g"), b"%zz%4g".to_vec());
+        assert_eq!(percent_decode(b"a%4"), b"a%4".to_vec());
+        assert_eq!(percent_decode(b"a%"), b"a%".to_vec());
+        assert_eq!(percent_decode(b"%%41"), b"%A".to_vec());
+    }
+
+    #[test]
+    fn uri_list_round_trips() {
+        let paths = vec![
+            PathBuf::from("/tmp/with spaces/100%.txt"),
+            PathBuf::from("/tmp/ünïcode#?"),
+        ];
+        assert_eq!(decode_uri_list(&encode_uri_list(&paths)), paths);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn uri_list_round_trips_non_utf8() {
+        let paths = vec![path_from_bytes(b"/tmp/not \xFF utf-8".to_vec())];
+        assert_eq!(decode_uri_list(&encode_uri_list(&paths)), paths);
+    }
+}
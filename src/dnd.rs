@@ -0,0 +1,209 @@
+//! Types useful for drag and drop.
+//!
+//! A drag can be started with [`Window::start_drag`], its outcome is reported with
+//! [`WindowEvent::DragFinished`] or [`WindowEvent::DragCancelled`]. The dragged data is
+//! described the same way as the clipboard content, see the [`clipboard`](crate::clipboard)
+//! module.
+//!
+//! When the data is dropped into a window of the application it was dragged from, it's read
+//! straight from its provider, see [`ClipboardProvider::content`], and the
+//! [`WindowEvent::Drop`] tells the window the data was dragged from.
+//!
+//! [`Window::start_drag`]: crate::window::Window::start_drag
+//! [`WindowEvent::DragFinished`]: crate::event::WindowEvent::DragFinished
+//! [`WindowEvent::DragCancelled`]: crate::event::WindowEvent::DragCancelled
+//! [`WindowEvent::Drop`]: crate::event::WindowEvent::Drop
+//! [`ClipboardProvider::content`]: crate::clipboard::ClipboardProvider::content
+
+use std::sync::Arc;
+
+use crate::{
+    clipboard::{ClipboardMimedContent, ClipboardProvider, MimeType},
+    dpi::PhysicalPosition,
+    icon::{BadIcon, RgbaIcon},
+};
+
+/// What is done with the dragged data once it's dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DndAction {
+    /// The data is copied.
+    Copy,
+    /// The data is moved, the source is expected to remove it.
+    Move,
+    /// A link to the data is created.
+    Link,
+}
+
+bitflags! {
+    /// A set of [`DndAction`]s.
+    #[derive(Default)]
+    pub struct DndActions: u32 {
+        /// [`DndAction::Copy`].
+        const COPY = 1 << 0;
+        /// [`DndAction::Move`].
+        const MOVE = 1 << 1;
+        /// [`DndAction::Link`].
+        const LINK = 1 << 2;
+    }
+}
+
+impl DndActions {
+    /// Whether the given action is in the set.
+    pub fn contains_action(self, action: DndAction) -> bool {
+        self.contains(action.into())
+    }
+}
+
+impl From<DndAction> for DndActions {
+    fn from(action: DndAction) -> Self {
+        match action {
+            DndAction::Copy => DndActions::COPY,
+            DndAction::Move => DndActions::MOVE,
+            DndAction::Link => DndActions::LINK,
+        }
+    }
+}
+
+/// An image following the cursor during a drag.
+#[derive(Debug, Clone)]
+// Only read by the backends with drags
+#[cfg_attr(
+    not(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_arch = "wasm32",
+        x11_platform,
+        wayland_platform
+    )),
+    allow(dead_code)
+)]
+pub struct DragIcon {
+    pub(crate) image: RgbaIcon,
+    pub(crate) hotspot: PhysicalPosition<u32>,
+}
+
+impl DragIcon {
+    /// Creates an icon from 32bpp RGBA data.
+    ///
+    /// The `hotspot` is the point of the image which is placed under the cursor. The length of
+    /// `rgba` must be divisible by 4, and `width * height` must equal `rgba.len() / 4`.
+    /// Otherwise, this will return a `BadIcon` error.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<Self, BadIcon> {
+        Ok(DragIcon {
+            image: RgbaIcon::from_rgba(rgba, width, height)?,
+            hotspot,
+        })
+    }
+}
+
+/// The data of a drag started by the application.
+#[derive(Clone)]
+pub struct DragData {
+    pub(crate) provider: Arc<dyn ClipboardProvider>,
+    pub(crate) icon: Option<DragIcon>,
+}
+
+impl DragData {
+    /// Drags the given content.
+    pub fn new(content: ClipboardMimedContent) -> Self {
+        Self::from_provider(Arc::new(content))
+    }
+
+    /// Drags the data of the `provider`, which is only written once the drop target requests
+    /// it.
+    pub fn from_provider(provider: Arc<dyn ClipboardProvider>) -> Self {
+        Self {
+            provider,
+            icon: None,
+        }
+    }
+
+    /// Shows the `icon` under the cursor while the data is dragged.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The icon is only shown over the windows that support it, which are the
+    ///   ones of most applications.
+    /// - **X11:** The icon replaces the cursor, since XDND has no notion of the drag icon.
+    pub fn with_icon(mut self, icon: DragIcon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+}
+
+impl std::fmt::Debug for DragData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DragData")
+            .field("mime_types", &self.provider.mime_types())
+            .field("icon", &self.icon)
+            .finish()
+    }
+}
+
+/// A file dropped into a window, whose content is read through the handle.
+///
+/// Only the browsers hand out such files, see [`WindowEvent::DroppedFileHandle`]. The content
+/// of the larger files is read asynchronously with `FileHandleExtWebSys`.
+///
+/// [`WindowEvent::DroppedFileHandle`]: crate::event::WindowEvent::DroppedFileHandle
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileHandle {
+    pub(crate) name: String,
+    pub(crate) mime: String,
+    pub(crate) size: u64,
+    pub(crate) content: Option<Arc<[u8]>>,
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) file: web_sys::File,
+}
+
+impl FileHandle {
+    /// The size of the largest file whose content is read before the file is delivered.
+    pub const READ_AHEAD_LIMIT: u64 = 1 << 20;
+
+    /// The name of the file, without the path.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The MIME type of the file, guessed from its name, or an empty string if it's unknown.
+    pub fn mime(&self) -> &str {
+        &self.mime
+    }
+
+    /// The size of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The content of the file, if it's small enough to be read before the file is delivered.
+    ///
+    /// The files of up to [`FileHandle::READ_AHEAD_LIMIT`] bytes are read ahead.
+    pub fn content(&self) -> Option<&[u8]> {
+        self.content.as_deref()
+    }
+}
+
+/// The content delivered when the data dragged from a window of the application is dropped
+/// into one of its windows, which is read straight from the provider of the dragged data.
+#[allow(dead_code)] // Not used on every platform
+pub(crate) fn local_drop_content(
+    provider: &dyn ClipboardProvider,
+) -> Option<ClipboardMimedContent> {
+    let mime_type = MimeType::preferred_drop_mime_type(&provider.mime_types())?;
+    if let Some(content) = provider.content(&mime_type) {
+        return Some(content);
+    }
+    match ClipboardMimedContent::from_provider(provider, mime_type) {
+        Ok(content) => Some(content),
+        Err(err) => {
+            warn!("Failed to read the dragged data: {}", err);
+            None
+        }
+    }
+}
@@ -25,6 +25,16 @@ pub struct OsError {
     error: platform_impl::OsError,
 }
 
+/// The error type for when the native handle of a window is unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandleError {
+    /// The surface of the window is gone until the application is resumed.
+    Unavailable,
+    /// The window was destroyed by the system while it was still alive, such as along with its
+    /// parent.
+    Destroyed,
+}
+
 impl NotSupportedError {
     #[inline]
     #[allow(dead_code)]
@@ -77,6 +87,16 @@ impl fmt::Display for NotSupportedError {
     }
 }
 
+impl fmt::Display for HandleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            HandleError::Unavailable => f.pad("the surface of the window is unavailable"),
+            HandleError::Destroyed => f.pad("the window was destroyed"),
+        }
+    }
+}
+
 impl error::Error for OsError {}
 impl error::Error for ExternalError {}
 impl error::Error for NotSupportedError {}
+impl error::Error for HandleError {}
@@ -34,12 +34,19 @@
 //!
 //! [event_loop_run]: crate::event_loop::EventLoop::run
 use instant::Instant;
-use std::path::PathBuf;
+use std::{ops::Range, path::PathBuf, time::Duration};
 
 use crate::{
+    clipboard::{ClipboardKind, ClipboardMimedContent},
+    dnd::{DndAction, FileHandle},
     dpi::{PhysicalPosition, PhysicalSize},
+    event_loop::{SourceToken, TimerId},
+    gamepad::{GamepadEvent, GamepadId},
+    hotkey::HotkeyId,
+    keymap::KeyboardLayout,
+    monitor::{MonitorEvent, MonitorHandle},
     platform_impl,
-    window::{Theme, WindowId},
+    window::{EdgeFlags, Theme, WindowId},
 };
 
 /// Describes a generic event.
@@ -70,6 +77,56 @@ pub enum Event<'a, T: 'static> {
     /// Emitted when an event is sent from [`EventLoopProxy::send_event`](crate::event_loop::EventLoopProxy::send_event)
     UserEvent(T),
 
+    /// Emitted when an external event source registered with the event loop is ready.
+    ///
+    /// The event is emitted once per iteration of the event loop for as long as the source
+    /// stays ready, so the source doesn't have to be drained at once.
+    ///
+    /// See `EventLoopExtUnix::insert_source`, `EventLoopExtWindows::insert_source` and
+    /// `EventLoopExtMacOS::insert_source`.
+    ExternalSource(SourceToken),
+
+    /// Emitted when a timer set with
+    /// [`EventLoopWindowTarget::set_timer`](crate::event_loop::EventLoopWindowTarget::set_timer)
+    /// fires.
+    Timer(TimerId),
+
+    /// Emitted when a global hotkey registered with
+    /// [`EventLoopWindowTarget::register_global_hotkey`](crate::event_loop::EventLoopWindowTarget::register_global_hotkey)
+    /// is pressed, whether or not a window of the application has the focus.
+    ///
+    /// The focused window doesn't get the `KeyboardInput` of the press.
+    GlobalHotkey(HotkeyId),
+
+    /// Emitted when a gamepad is connected, disconnected or used, once the gamepads were enabled
+    /// with
+    /// [`EventLoopWindowTarget::set_gamepads_enabled`](crate::event_loop::EventLoopWindowTarget::set_gamepads_enabled).
+    ///
+    /// The gamepads are used whether or not a window of the application has the focus.
+    GamepadEvent {
+        gamepad_id: GamepadId,
+        event: GamepadEvent,
+    },
+
+    /// Emitted when a monitor changed, whether or not a window of the application is on it.
+    MonitorEvent {
+        monitor: MonitorHandle,
+        event: MonitorEvent,
+    },
+
+    /// Emitted when monitors were connected or disconnected.
+    ///
+    /// The removed monitors can only be compared against the handles kept before, they can't be
+    /// queried anymore.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Web:** Unsupported.
+    MonitorsChanged {
+        added: Vec<MonitorHandle>,
+        removed: Vec<MonitorHandle>,
+    },
+
     /// Emitted when the application has been suspended.
     Suspended,
 
@@ -127,6 +184,21 @@ impl<T: Clone> Clone for Event<'static, T> {
                 event: event.clone(),
             },
             UserEvent(event) => UserEvent(event.clone()),
+            ExternalSource(token) => ExternalSource(*token),
+            Timer(id) => Timer(*id),
+            GlobalHotkey(id) => GlobalHotkey(*id),
+            GamepadEvent { gamepad_id, event } => GamepadEvent {
+                gamepad_id: *gamepad_id,
+                event: event.clone(),
+            },
+            MonitorEvent { monitor, event } => MonitorEvent {
+                monitor: monitor.clone(),
+                event: event.clone(),
+            },
+            MonitorsChanged { added, removed } => MonitorsChanged {
+                added: added.clone(),
+                removed: removed.clone(),
+            },
             DeviceEvent { device_id, event } => DeviceEvent {
                 device_id: *device_id,
                 event: event.clone(),
@@ -147,6 +219,12 @@ impl<'a, T> Event<'a, T> {
         use self::Event::*;
         match self {
             UserEvent(_) => Err(self),
+            ExternalSource(token) => Ok(ExternalSource(token)),
+            Timer(id) => Ok(Timer(id)),
+            GlobalHotkey(id) => Ok(GlobalHotkey(id)),
+            GamepadEvent { gamepad_id, event } => Ok(GamepadEvent { gamepad_id, event }),
+            MonitorEvent { monitor, event } => Ok(MonitorEvent { monitor, event }),
+            MonitorsChanged { added, removed } => Ok(MonitorsChanged { added, removed }),
             WindowEvent { window_id, event } => Ok(WindowEvent { window_id, event }),
             DeviceEvent { device_id, event } => Ok(DeviceEvent { device_id, event }),
             NewEvents(cause) => Ok(NewEvents(cause)),
@@ -168,6 +246,12 @@ impl<'a, T> Event<'a, T> {
                 .to_static()
                 .map(|event| WindowEvent { window_id, event }),
             UserEvent(event) => Some(UserEvent(event)),
+            ExternalSource(token) => Some(ExternalSource(token)),
+            Timer(id) => Some(Timer(id)),
+            GlobalHotkey(id) => Some(GlobalHotkey(id)),
+            GamepadEvent { gamepad_id, event } => Some(GamepadEvent { gamepad_id, event }),
+            MonitorEvent { monitor, event } => Some(MonitorEvent { monitor, event }),
+            MonitorsChanged { added, removed } => Some(MonitorsChanged { added, removed }),
             DeviceEvent { device_id, event } => Some(DeviceEvent { device_id, event }),
             NewEvents(cause) => Some(NewEvents(cause)),
             MainEventsCleared => Some(MainEventsCleared),
@@ -227,6 +311,18 @@ pub enum WindowEvent<'a> {
     /// separately.
     DroppedFile(PathBuf),
 
+    /// A file has been dropped into the window, given as a handle its content can be read
+    /// through.
+    ///
+    /// When the user drops multiple files at once, this event will be emitted for each file
+    /// separately.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only sent on **Web**, where the dropped files have no path. Other platforms send
+    ///   [`WindowEvent::DroppedFile`] instead.
+    DroppedFileHandle(FileHandle),
+
     /// A file is being hovered over the window.
     ///
     /// When the user hovers multiple files at once, this event will be emitted for each file
@@ -270,6 +366,107 @@ pub enum WindowEvent<'a> {
     ///   issue, and it should get fixed - but it's the current state of the API.
     ModifiersChanged(ModifiersState),
 
+    /// The keyboard modifiers of a single keyboard have changed.
+    ///
+    /// Unlike [`WindowEvent::ModifiersChanged`], which merges all the keyboards, `modifiers` are
+    /// the ones held on the `device_id` keyboard alone, so that applications with a user per seat
+    /// or per keyboard can tell them apart. The keyboards whose modifiers are held are reported
+    /// when the window gains focus, and reported with no modifiers when it loses focus.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The devices are the seats, the keyboards of a seat being merged by the
+    ///   compositor.
+    /// - **X11:** The devices are the XInput 2 slave keyboards.
+    /// - **Windows:** The devices are the keyboards of the raw input.
+    /// - **macOS:** The system merges the keyboards, so the event mirrors `ModifiersChanged`.
+    /// - **iOS / Android / Web:** Unsupported.
+    DeviceModifiersChanged {
+        device_id: DeviceId,
+        modifiers: ModifiersState,
+    },
+
+    /// The user switched to another keyboard layout, or the keymap of the keyboard was changed.
+    ///
+    /// The event carries the layout which is used from now on, as
+    /// [`EventLoopWindowTarget::current_keyboard_layout`] would return it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Only sent to the window which has the keyboard focus.
+    /// - **macOS:** Sent to every window.
+    /// - **X11:** Sent to every window. Requires `libxkbcommon-x11`.
+    /// - **Wayland / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`EventLoopWindowTarget::current_keyboard_layout`]: crate::event_loop::EventLoopWindowTarget::current_keyboard_layout
+    KeyboardLayoutChanged(KeyboardLayout),
+
+    /// A dead key or a compose sequence started, went on or ended in the window which has the
+    /// keyboard focus.
+    ///
+    /// This is meant for showing the pending input, while the composed characters are still
+    /// delivered through [`WindowEvent::ReceivedCharacter`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Also reports the marked text of the input methods, which are handled as dead
+    ///   keys.
+    /// - **X11 / Wayland:** Follows the Compose file of the locale, as read by `libxkbcommon`,
+    ///   which doesn't see the sequences handled by the input methods themselves. Sent before the
+    ///   `KeyboardInput` of the key.
+    /// - **Windows / iOS / Android / Web:** Unsupported.
+    ComposeChanged(ComposeState),
+
+    /// The text the input method is composing in the window which has the keyboard focus
+    /// changed, along with the styles to draw it with.
+    ///
+    /// The composed text is committed through [`WindowEvent::ReceivedCharacter`], after which a
+    /// preedit with empty text clears the composition. The applications drawing the composition
+    /// themselves should underline and highlight the spans as the native applications do.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** `text-input-v3` doesn't carry any style, so the whole text comes as a
+    ///   single underlined span.
+    /// - **Windows:** The compositions of the Text Services Framework come as a single
+    ///   underlined span, while the ones of the IMM32 input methods follow their attributes, and
+    ///   the input method keeps drawing its own composition window.
+    /// - **X11:** Unsupported, as the input methods of XIM draw the composition themselves.
+    /// - **iOS / Android / Web:** Unsupported.
+    ImePreedit(ImePreedit),
+
+    /// The candidate window of the input method was shown or moved to the area, in client area
+    /// coordinates, or was hidden if `None`.
+    ///
+    /// Applications can use it to avoid showing their own popups under the candidate list.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Only reported for the input methods placing their candidate window under
+    ///   the IME window of the application, which some of the Text Services Framework ones don't.
+    /// - **macOS / X11 / Wayland:** Unsupported, as the system doesn't tell where it places the
+    ///   candidate window.
+    /// - **iOS / Android / Web:** Unsupported.
+    ImeCandidateArea(Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>),
+
+    /// The input method asks to delete the text around the cursor, given in bytes of the text
+    /// set with [`Window::set_ime_surrounding_text`](crate::window::Window::set_ime_surrounding_text).
+    ///
+    /// The lengths are counted from the ends of the selection when there is one, which is
+    /// deleted too. The text replacing the deleted one is then delivered through
+    /// [`WindowEvent::ReceivedCharacter`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Sent when the inserted text replaces the range of the surrounding text.
+    /// - **Windows:** Sent when an input method of the Text Services Framework edits the
+    ///   surrounding text, like when reconverting it.
+    /// - **X11 / iOS / Android / Web:** Unsupported.
+    ImeDeleteSurrounding {
+        before_length: usize,
+        after_length: usize,
+    },
+
     /// The cursor has moved on the window.
     CursorMoved {
         device_id: DeviceId,
@@ -283,16 +480,59 @@ pub enum WindowEvent<'a> {
     },
 
     /// The cursor has entered the window.
-    CursorEntered { device_id: DeviceId },
+    ///
+    /// `device_id` is the pointer which entered, each of the pointers of the multi-pointer
+    /// systems having one of its own.
+    CursorEntered {
+        device_id: DeviceId,
+        /// Where the cursor entered, in physical pixels relative to the top-left corner of the
+        /// window.
+        position: PhysicalPosition<f64>,
+    },
 
     /// The cursor has left the window.
-    CursorLeft { device_id: DeviceId },
+    ///
+    /// The pointer captured by a button pressed inside the window, or by
+    /// [`Window::capture_pointer`], leaves the window all the same, and its motion keeps coming as
+    /// `CursorMoved` events with positions outside of it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Web:** The captured pointer leaves the window once it's released.
+    ///
+    /// [`Window::capture_pointer`]: crate::window::Window::capture_pointer
+    CursorLeft {
+        device_id: DeviceId,
+        /// Where the cursor left, in physical pixels relative to the top-left corner of the
+        /// window, which is outside of the window when the platform knows it.
+        ///
+        /// ## Platform-specific
+        ///
+        /// - **Wayland:** The last position the cursor had on the window, as the compositors
+        ///   don't tell where it went.
+        position: PhysicalPosition<f64>,
+    },
 
     /// A mouse wheel movement or touchpad scroll occurred.
+    ///
+    /// The scrolls with a finger start with `TouchPhase::Started` and end with a
+    /// `TouchPhase::Ended` event with a delta of zero once the finger is lifted, which is where
+    /// kinetic scrolling starts.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** The system kinetic scrolling follows the end of the scroll, as
+    ///   `TouchPhase::Moved` events.
+    /// - **Windows:** The precision touchpads scroll by pixels through DirectManipulation, with
+    ///   their inertia following the end of the scroll as `TouchPhase::Moved` events. The wheels
+    ///   don't end their scrolls, which are always `TouchPhase::Moved`.
+    /// - **X11 / Web:** The scrolls don't end, their phase is always `TouchPhase::Moved`.
     MouseWheel {
         device_id: DeviceId,
         delta: MouseScrollDelta,
         phase: TouchPhase,
+        /// The kind of device the scroll came from, or `None` when the platform doesn't tell.
+        source: Option<AxisSource>,
         #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
         modifiers: ModifiersState,
     },
@@ -311,10 +551,74 @@ pub enum WindowEvent<'a> {
     /// At the moment, only supported on Apple forcetouch-capable macbooks.
     /// The parameters are: pressure level (value between 0 and 1 representing how hard the touchpad
     /// is being pressed) and stage (integer representing the click level).
+    ///
+    /// The presses reaching the stage 2 are the force clicks, like the ones opening the Quick
+    /// Look previews, and the stage goes back to 0 as the touchpad is released.
     TouchpadPressure {
         device_id: DeviceId,
         pressure: f32,
         stage: i64,
+        /// The progress of the press toward the next stage, from 0.0 to 1.0, or toward the
+        /// previous one, from 0.0 to -1.0, for the applications animating the transitions.
+        stage_transition: f32,
+    },
+
+    /// A pinch of two fingers or more on the touchpad, for zooming.
+    ///
+    /// The gesture goes through the phases of `GesturePhase`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the version 2.4 of the XInput extension.
+    /// - **Wayland:** Requires the `zwp_pointer_gestures_v1` protocol.
+    /// - **Windows:** Only the pinches of two fingers on the touch screens, since the pinches on
+    ///   the touchpads come as `MouseWheel` events with the Control key held.
+    /// - **iOS / Android / Web:** Unsupported.
+    PinchGesture {
+        device_id: DeviceId,
+        /// The change of the scale of the pinch since the previous event, positive when the
+        /// fingers spread apart, which adds up to the scale the pinch would give the content.
+        delta: f64,
+        phase: GesturePhase,
+    },
+
+    /// A rotation of two fingers or more on the touchpad.
+    ///
+    /// The gesture goes through the phases of `GesturePhase`. On most platforms, the rotations are
+    /// made along with the pinches, and their events come alongside each other.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the version 2.4 of the XInput extension.
+    /// - **Wayland:** Requires the `zwp_pointer_gestures_v1` protocol.
+    /// - **Windows:** Only the rotations of two fingers on the touch screens.
+    /// - **iOS / Android / Web:** Unsupported.
+    RotationGesture {
+        device_id: DeviceId,
+        /// The rotation since the previous event in degrees, positive counterclockwise.
+        delta: f32,
+        phase: GesturePhase,
+    },
+
+    /// A swipe of three fingers or more on the touchpad.
+    ///
+    /// The gesture goes through the phases of `GesturePhase`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the version 2.4 of the XInput extension.
+    /// - **Wayland:** Requires the `zwp_pointer_gestures_v1` protocol.
+    /// - **macOS:** Only the swipes of three fingers set up for the navigation in the system
+    ///   preferences, each as a single `GesturePhase::Updated` event of a delta of one in the
+    ///   direction of the swipe, between the start and the end.
+    /// - **Windows / iOS / Android / Web:** Unsupported.
+    SwipeGesture {
+        device_id: DeviceId,
+        /// The motion of the fingers since the previous event, in physical pixels.
+        delta: PhysicalPosition<f64>,
+        /// The number of fingers of the swipe.
+        fingers: u32,
+        phase: GesturePhase,
     },
 
     /// Motion on some analog axis. May report data redundant to other, more specific events.
@@ -327,6 +631,22 @@ pub enum WindowEvent<'a> {
     /// Touch event has been received
     Touch(Touch),
 
+    /// A pen has come close to the window, touched it, moved or left it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The pens of Windows Ink, which also send their `Touch` events, and whose id
+    ///   is the one of their pointer.
+    /// - **Wayland:** Requires the `zwp_tablet_manager_v2` protocol.
+    /// - **X11:** The devices with a pressure valuator, the eraser ones having a name ending
+    ///   with "eraser". The pens don't come close nor leave, and their id is the one of their
+    ///   device.
+    /// - **macOS:** The tablet events.
+    /// - **iOS:** The Apple Pencil, which also sends its `Touch` events. It doesn't come close
+    ///   nor leave, has no twist, eraser nor button, and its id is 0.
+    /// - **Android / Web:** Unsupported.
+    Pen(PenEvent),
+
     /// The window's scale factor has changed.
     ///
     /// The following user actions can cause DPI changes:
@@ -352,6 +672,230 @@ pub enum WindowEvent<'a> {
     ///
     /// At the moment this is only supported on Windows.
     ThemeChanged(Theme),
+
+    /// The content of the clipboard requested with
+    /// [`Window::read_clipboard`](crate::window::Window::read_clipboard).
+    ///
+    /// The content is `None` if the clipboard was empty, none of the offered kinds of data was
+    /// picked, or the data couldn't be retrieved from its owner.
+    ClipboardData {
+        kind: ClipboardKind,
+        content: Option<ClipboardMimedContent>,
+    },
+
+    /// The content of the clipboard was changed by another application.
+    ///
+    /// The event is sent to every window, and the new content can be read with
+    /// [`Window::read_clipboard`](crate::window::Window::read_clipboard).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the XFixes extension.
+    /// - **Wayland:** The compositor only shares the clipboard with the focused client, so the
+    ///   event is sent once one of the windows gains keyboard focus, even if the content didn't
+    ///   change while the focus was away.
+    /// - **macOS / iOS / Android / Web:** Unsupported.
+    ClipboardChanged { kind: ClipboardKind },
+
+    /// The data dragged from this window with
+    /// [`Window::start_drag`](crate::window::Window::start_drag) moved over a drop target
+    /// accepting it.
+    ///
+    /// The event is sent again whenever the data moves over another target accepting it, or the
+    /// action changes. The action is the one the data would be dropped with.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Unsupported, the drop targets aren't known to the source of the drag.
+    DragEntered { action: DndAction },
+
+    /// The cursor dragging the data from this window moved.
+    ///
+    /// The position is relative to the top-left corner of the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Unsupported, the cursor isn't known to the source of the drag.
+    DragMoved { position: PhysicalPosition<f64> },
+
+    /// The drag started from this window has ended without the data being dropped.
+    ///
+    /// The drag was either cancelled, or released where the data couldn't be accepted, or the
+    /// drop target failed to take the data.
+    DragCancelled,
+
+    /// The data dragged from this window was dropped.
+    ///
+    /// The action is the one taken by the drop target, so the data should only be removed from
+    /// the window for [`DndAction::Move`]. `outside` tells whether the data was dropped outside of
+    /// the windows of the application.
+    DragFinished { action: DndAction, outside: bool },
+
+    /// Data was dropped into the window.
+    ///
+    /// Unlike [`WindowEvent::DroppedFile`], this covers every kind of data, and is sent once per
+    /// drop. The content is a list of files if one was offered, the first offered kind known to
+    /// winit otherwise, and the first offered kind if there's no known one. For a list of files,
+    /// `DroppedFile` is still sent for each of the files.
+    ///
+    /// The position is the location of the cursor in the window when the data was dropped, and
+    /// the action is the one the data is dropped with.
+    ///
+    /// The source is the window the data was dragged from with
+    /// [`Window::start_drag`](crate::window::Window::start_drag), if it was dragged from a
+    /// window of the application. The content is then read straight from the provider of the
+    /// dragged data.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Web:** Unsupported.
+    Drop {
+        content: ClipboardMimedContent,
+        position: PhysicalPosition<f64>,
+        action: DndAction,
+        source: Option<WindowId>,
+    },
+
+    /// The user dragged the tab of the window out of its tab group, into a window of its own.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / Linux / iOS / Android / Web:** Unsupported, the windows having no tabs.
+    TabDetached,
+
+    /// The largest inner size the window is recommended to take changed, as it moved to another
+    /// monitor or the area of its monitor left for the windows changed.
+    ///
+    /// See [`Window::recommended_bounds`].
+    ///
+    /// [`Window::recommended_bounds`]: crate::window::Window::recommended_bounds
+    RecommendedBounds(PhysicalSize<u32>),
+
+    /// The window became fully hidden from the user, or shown again. The applications may stop
+    /// rendering while the window is occluded, as nothing they draw is seen.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The window is occluded when it's minimized or cloaked, as it's on another
+    ///   virtual desktop. The windows fully covered by other windows are not detected.
+    /// - **X11:** The window is occluded when it's unmapped, as it's hidden or minimized, or
+    ///   fully obscured as per `VisibilityNotify`. The compositors usually report their windows
+    ///   as never obscured.
+    /// - **Wayland:** Guessed from the frame callbacks, as the window is occluded when the
+    ///   compositor stops calling back while the window keeps being redrawn on
+    ///   [`RedrawRequested`](crate::event::Event::RedrawRequested).
+    /// - **macOS:** The window is occluded when no part of it is visible, as per its occlusion
+    ///   state.
+    /// - **iOS / Android / Web:** Unsupported.
+    Occluded(bool),
+
+    /// The edges the window is tiled on changed, as it was snapped to a side or a corner of its
+    /// monitor, or against other windows. The tiled edges are fixed by the window manager, so
+    /// the client side decorations are expected to square off their corners and leave out their
+    /// resize handles on them.
+    ///
+    /// An empty set is sent once the window isn't tiled anymore. The maximized windows aren't
+    /// reported as tiled.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Sent for the windows snapped to the edges of the work area of their
+    ///   monitor.
+    /// - **X11:** Only with the window managers setting `_GTK_EDGE_CONSTRAINTS`, such as Mutter.
+    /// - **macOS:** Sent for the windows in a split view, which are tiled on all their edges.
+    /// - **iOS / Android / Web:** Unsupported.
+    TiledStateChanged(EdgeFlags),
+
+    /// The window moved to the workspace of the given index, or `None` once it's visible on all
+    /// the workspaces, as set by the application or the user. See
+    /// [`Window::workspace`](crate::window::Window::workspace).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Only the moves to another virtual desktop are sent.
+    /// - **Wayland / macOS / iOS / Android / Web:** Unsupported.
+    WorkspaceChanged(Option<u32>),
+
+    /// The contents drawn on the last [`RedrawRequested`](crate::event::Event::RedrawRequested)
+    /// were shown on the screen at `time`. The applications may measure their latency with it,
+    /// and time their animations on the presentations rather than on the redraws.
+    ///
+    /// `refresh` is the duration until the next presentation, as the refresh period of the
+    /// monitor, when known. The frames which were dropped aren't reported.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Only with the Present extension, for the frames presented through it, such as
+    ///   by the drivers of OpenGL and Vulkan.
+    /// - **Wayland:** Only with the compositors supporting `wp_presentation`.
+    /// - **macOS / iOS:** Only for the frames notified with
+    ///   [`Window::pre_present_notify`](crate::window::Window::pre_present_notify), at the
+    ///   refresh of the display following the one they were presented before, as timed by a
+    ///   `CVDisplayLink` or a `CADisplayLink`.
+    /// - **Windows / Android / Web:** Unsupported.
+    Presented {
+        time: Instant,
+        refresh: Option<Duration>,
+        flags: PresentationFlags,
+    },
+
+    /// The native surface of the window became invalid. The renderers are expected to drop their
+    /// swapchains and stop drawing until [`SurfaceRestored`](WindowEvent::SurfaceRestored), as
+    /// between [`Suspended`](crate::event::Event::Suspended) and
+    /// [`Resumed`](crate::event::Event::Resumed) on Android.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Only sent once the session is disconnected from the console or from a
+    ///   remote desktop client, as the adapter drawing the windows changes along with it. The
+    ///   removals of the device, such as on the updates of the driver, are only reported by the
+    ///   graphics APIs, like with `DXGI_ERROR_DEVICE_REMOVED`.
+    /// - **Wayland:** Unsupported, the connection being lost along with the compositor.
+    /// - **X11 / macOS / iOS / Web:** Unsupported.
+    /// - **Android:** Unsupported, the surface being lost on
+    ///   [`Suspended`](crate::event::Event::Suspended).
+    SurfaceLost,
+
+    /// The native surface of the window is usable again after
+    /// [`SurfaceLost`](WindowEvent::SurfaceLost), and the renderers are expected to create their
+    /// swapchains anew.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Sent once the session is connected to the console or to a remote desktop
+    ///   client.
+    /// - **X11 / Wayland / macOS / iOS / Android / Web:** Unsupported.
+    SurfaceRestored,
+
+    /// The switch to the video mode of [`Fullscreen::Exclusive`] completed with `true`, or failed
+    /// with `false`, the window being then fullscreen in the video mode of the desktop. The video
+    /// mode of the desktop is restored once the window leaves exclusive fullscreen or is dropped,
+    /// which includes unwinding from a panic.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The video mode of the monitor is switched with XRandR.
+    /// - **Wayland:** The video mode is emulated, the buffers of its size being scaled to the output
+    ///   by the compositor. The switch fails without `wp_viewporter`.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`Fullscreen::Exclusive`]: crate::window::Fullscreen::Exclusive
+    VideoModeSwitched(bool),
+
+    /// The refresh rate of the monitor the window is on changed, in millihertz, as the user
+    /// switched the mode of the monitor or the window moved to a monitor with another refresh
+    /// rate. The frame pacers may then adjust their timings.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sent on the XRandR notifications, and once the window moves to another monitor.
+    /// - **Wayland:** Sent once the mode of an output the window is on changes, or the window
+    ///   enters another output.
+    /// - **Windows:** Sent on `WM_DISPLAYCHANGE`, and once the window moves to another monitor.
+    /// - **macOS:** Read from the `CVDisplayLink` of the screen once the screen parameters change,
+    ///   or the window moves to another screen.
+    /// - **iOS / Android / Web:** Unsupported.
+    RefreshRateChanged(u32),
 }
 
 impl Clone for WindowEvent<'static> {
@@ -363,6 +907,7 @@ impl Clone for WindowEvent<'static> {
             CloseRequested => CloseRequested,
             Destroyed => Destroyed,
             DroppedFile(file) => DroppedFile(file.clone()),
+            DroppedFileHandle(file) => DroppedFileHandle(file.clone()),
             HoveredFile(file) => HoveredFile(file.clone()),
             HoveredFileCancelled => HoveredFileCancelled,
             ReceivedCharacter(c) => ReceivedCharacter(*c),
@@ -378,6 +923,24 @@ impl Clone for WindowEvent<'static> {
             },
 
             ModifiersChanged(modifiers) => ModifiersChanged(modifiers.clone()),
+            DeviceModifiersChanged {
+                device_id,
+                modifiers,
+            } => DeviceModifiersChanged {
+                device_id: *device_id,
+                modifiers: *modifiers,
+            },
+            KeyboardLayoutChanged(layout) => KeyboardLayoutChanged(layout.clone()),
+            ComposeChanged(state) => ComposeChanged(state.clone()),
+            ImePreedit(preedit) => ImePreedit(preedit.clone()),
+            ImeCandidateArea(area) => ImeCandidateArea(*area),
+            ImeDeleteSurrounding {
+                before_length,
+                after_length,
+            } => ImeDeleteSurrounding {
+                before_length: *before_length,
+                after_length: *after_length,
+            },
             #[allow(deprecated)]
             CursorMoved {
                 device_id,
@@ -388,22 +951,32 @@ impl Clone for WindowEvent<'static> {
                 position: *position,
                 modifiers: *modifiers,
             },
-            CursorEntered { device_id } => CursorEntered {
+            CursorEntered {
+                device_id,
+                position,
+            } => CursorEntered {
                 device_id: *device_id,
+                position: *position,
             },
-            CursorLeft { device_id } => CursorLeft {
+            CursorLeft {
+                device_id,
+                position,
+            } => CursorLeft {
                 device_id: *device_id,
+                position: *position,
             },
             #[allow(deprecated)]
             MouseWheel {
                 device_id,
                 delta,
                 phase,
+                source,
                 modifiers,
             } => MouseWheel {
                 device_id: *device_id,
                 delta: *delta,
                 phase: *phase,
+                source: *source,
                 modifiers: *modifiers,
             },
             #[allow(deprecated)]
@@ -422,10 +995,41 @@ impl Clone for WindowEvent<'static> {
                 device_id,
                 pressure,
                 stage,
+                stage_transition,
             } => TouchpadPressure {
                 device_id: *device_id,
                 pressure: *pressure,
                 stage: *stage,
+                stage_transition: *stage_transition,
+            },
+            PinchGesture {
+                device_id,
+                delta,
+                phase,
+            } => PinchGesture {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+            },
+            RotationGesture {
+                device_id,
+                delta,
+                phase,
+            } => RotationGesture {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+            },
+            SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+            } => SwipeGesture {
+                device_id: *device_id,
+                delta: *delta,
+                fingers: *fingers,
+                phase: *phase,
             },
             AxisMotion {
                 device_id,
@@ -437,7 +1041,51 @@ impl Clone for WindowEvent<'static> {
                 value: *value,
             },
             Touch(touch) => Touch(*touch),
+            Pen(pen) => Pen(*pen),
             ThemeChanged(theme) => ThemeChanged(theme.clone()),
+            ClipboardData { kind, content } => ClipboardData {
+                kind: *kind,
+                content: content.clone(),
+            },
+            ClipboardChanged { kind } => ClipboardChanged { kind: *kind },
+            DragEntered { action } => DragEntered { action: *action },
+            DragMoved { position } => DragMoved {
+                position: *position,
+            },
+            DragCancelled => DragCancelled,
+            DragFinished { action, outside } => DragFinished {
+                action: *action,
+                outside: *outside,
+            },
+            Drop {
+                content,
+                position,
+                action,
+                source,
+            } => Drop {
+                content: content.clone(),
+                position: *position,
+                action: *action,
+                source: *source,
+            },
+            TabDetached => TabDetached,
+            RecommendedBounds(size) => RecommendedBounds(*size),
+            Occluded(occluded) => Occluded(*occluded),
+            TiledStateChanged(edges) => TiledStateChanged(*edges),
+            WorkspaceChanged(workspace) => WorkspaceChanged(*workspace),
+            Presented {
+                time,
+                refresh,
+                flags,
+            } => Presented {
+                time: *time,
+                refresh: *refresh,
+                flags: *flags,
+            },
+            SurfaceLost => SurfaceLost,
+            SurfaceRestored => SurfaceRestored,
+            VideoModeSwitched(switched) => VideoModeSwitched(*switched),
+            RefreshRateChanged(refresh_rate) => RefreshRateChanged(*refresh_rate),
             ScaleFactorChanged { .. } => {
                 unreachable!("Static event can't be about scale factor changing")
             }
@@ -454,6 +1102,7 @@ impl<'a> WindowEvent<'a> {
             CloseRequested => Some(CloseRequested),
             Destroyed => Some(Destroyed),
             DroppedFile(file) => Some(DroppedFile(file)),
+            DroppedFileHandle(file) => Some(DroppedFileHandle(file)),
             HoveredFile(file) => Some(HoveredFile(file)),
             HoveredFileCancelled => Some(HoveredFileCancelled),
             ReceivedCharacter(c) => Some(ReceivedCharacter(c)),
@@ -468,6 +1117,24 @@ impl<'a> WindowEvent<'a> {
                 is_synthetic,
             }),
             ModifiersChanged(modifiers) => Some(ModifiersChanged(modifiers)),
+            DeviceModifiersChanged {
+                device_id,
+                modifiers,
+            } => Some(DeviceModifiersChanged {
+                device_id,
+                modifiers,
+            }),
+            KeyboardLayoutChanged(layout) => Some(KeyboardLayoutChanged(layout)),
+            ComposeChanged(state) => Some(ComposeChanged(state)),
+            ImePreedit(preedit) => Some(ImePreedit(preedit)),
+            ImeCandidateArea(area) => Some(ImeCandidateArea(area)),
+            ImeDeleteSurrounding {
+                before_length,
+                after_length,
+            } => Some(ImeDeleteSurrounding {
+                before_length,
+                after_length,
+            }),
             #[allow(deprecated)]
             CursorMoved {
                 device_id,
@@ -478,18 +1145,32 @@ impl<'a> WindowEvent<'a> {
                 position,
                 modifiers,
             }),
-            CursorEntered { device_id } => Some(CursorEntered { device_id }),
-            CursorLeft { device_id } => Some(CursorLeft { device_id }),
+            CursorEntered {
+                device_id,
+                position,
+            } => Some(CursorEntered {
+                device_id,
+                position,
+            }),
+            CursorLeft {
+                device_id,
+                position,
+            } => Some(CursorLeft {
+                device_id,
+                position,
+            }),
             #[allow(deprecated)]
             MouseWheel {
                 device_id,
                 delta,
                 phase,
+                source,
                 modifiers,
             } => Some(MouseWheel {
                 device_id,
                 delta,
                 phase,
+                source,
                 modifiers,
             }),
             #[allow(deprecated)]
@@ -508,10 +1189,41 @@ impl<'a> WindowEvent<'a> {
                 device_id,
                 pressure,
                 stage,
+                stage_transition,
             } => Some(TouchpadPressure {
                 device_id,
                 pressure,
                 stage,
+                stage_transition,
+            }),
+            PinchGesture {
+                device_id,
+                delta,
+                phase,
+            } => Some(PinchGesture {
+                device_id,
+                delta,
+                phase,
+            }),
+            RotationGesture {
+                device_id,
+                delta,
+                phase,
+            } => Some(RotationGesture {
+                device_id,
+                delta,
+                phase,
+            }),
+            SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+            } => Some(SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
             }),
             AxisMotion {
                 device_id,
@@ -523,7 +1235,43 @@ impl<'a> WindowEvent<'a> {
                 value,
             }),
             Touch(touch) => Some(Touch(touch)),
+            Pen(pen) => Some(Pen(pen)),
             ThemeChanged(theme) => Some(ThemeChanged(theme)),
+            ClipboardData { kind, content } => Some(ClipboardData { kind, content }),
+            ClipboardChanged { kind } => Some(ClipboardChanged { kind }),
+            DragEntered { action } => Some(DragEntered { action }),
+            DragMoved { position } => Some(DragMoved { position }),
+            DragCancelled => Some(DragCancelled),
+            DragFinished { action, outside } => Some(DragFinished { action, outside }),
+            Drop {
+                content,
+                position,
+                action,
+                source,
+            } => Some(Drop {
+                content,
+                position,
+                action,
+                source,
+            }),
+            TabDetached => Some(TabDetached),
+            RecommendedBounds(size) => Some(RecommendedBounds(size)),
+            Occluded(occluded) => Some(Occluded(occluded)),
+            TiledStateChanged(edges) => Some(TiledStateChanged(edges)),
+            WorkspaceChanged(workspace) => Some(WorkspaceChanged(workspace)),
+            Presented {
+                time,
+                refresh,
+                flags,
+            } => Some(Presented {
+                time,
+                refresh,
+                flags,
+            }),
+            SurfaceLost => Some(SurfaceLost),
+            SurfaceRestored => Some(SurfaceRestored),
+            VideoModeSwitched(switched) => Some(VideoModeSwitched(switched)),
+            RefreshRateChanged(refresh_rate) => Some(RefreshRateChanged(refresh_rate)),
             ScaleFactorChanged { .. } => None,
         }
     }
@@ -548,6 +1296,64 @@ impl DeviceId {
     }
 }
 
+/// An input device connected to the system, as returned by
+/// [`EventLoopWindowTarget::input_devices`].
+///
+/// [`EventLoopWindowTarget::input_devices`]: crate::event_loop::EventLoopWindowTarget::input_devices
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputDevice {
+    /// The identifier of the device, which is the one of the events it sends.
+    pub id: DeviceId,
+    /// The name of the device, to show to the user.
+    pub name: String,
+    /// The USB vendor identifier of the device, when it has one.
+    pub vendor_id: Option<u16>,
+    /// The USB product identifier of the device, when it has one.
+    pub product_id: Option<u16>,
+    /// The kinds of input the device provides.
+    pub capabilities: DeviceCapabilities,
+    /// The resolution of the motion of the pointing device, in counts per inch, when the system
+    /// knows it. The deltas of its [`DeviceEvent::MouseMotion`] are in these counts.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The resolution of the horizontal axis the driver reports, which the drivers of
+    ///   the mice often leave out.
+    /// - **Windows / Wayland:** Always `None`.
+    pub resolution: Option<u32>,
+}
+
+bitflags! {
+    /// How the contents of a window were presented, as sent in [`WindowEvent::Presented`]. The
+    /// flags the platform doesn't know about are left out.
+    #[derive(Default)]
+    pub struct PresentationFlags: u32 {
+        /// The presentation was synchronized with the vertical blank of the monitor.
+        const VSYNC = 1 << 0;
+        /// The time of the presentation was taken from the clock of the display hardware.
+        const HW_CLOCK = 1 << 1;
+        /// The display hardware signaled the completion of the presentation.
+        const HW_COMPLETION = 1 << 2;
+        /// The buffer of the window was scanned out without being copied.
+        const ZERO_COPY = 1 << 3;
+    }
+}
+
+bitflags! {
+    /// A set of the kinds of input an [`InputDevice`] provides.
+    #[derive(Default)]
+    pub struct DeviceCapabilities: u32 {
+        /// The device has keys, such as a keyboard.
+        const KEYBOARD = 1 << 0;
+        /// The device moves the pointer, such as a mouse or a touchpad.
+        const POINTER = 1 << 1;
+        /// The device is a touch screen.
+        const TOUCH = 1 << 2;
+        /// The device is a pen tablet.
+        const PEN = 1 << 3;
+    }
+}
+
 /// Represents raw hardware events that are not associated with any particular window.
 ///
 /// Useful for interactions that diverge significantly from a conventional 2D GUI, such as 3D camera or first-person
@@ -558,12 +1364,29 @@ impl DeviceId {
 /// Note that these events are delivered regardless of input focus.
 #[derive(Clone, Debug, PartialEq)]
 pub enum DeviceEvent {
-    Added,
+    /// The device was plugged in, or was otherwise added by the system.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Only sent for the keyboards and the mice. It's also sent for the ones already
+    ///   connected when the event loop starts.
+    /// - **Wayland:** Sent for the seats, which are the devices of the events.
+    /// - **macOS / iOS / Android / Web:** Unsupported.
+    Added(InputDevice),
+    /// The device was unplugged, and its identifier isn't used afterwards.
     Removed,
 
     /// Change in physical position of a pointing device.
     ///
     /// This represents raw, unfiltered physical motion. Not to be confused with `WindowEvent::CursorMoved`.
+    ///
+    /// The motion is unaccelerated, and is sent by each of the devices, so the sensitivity can be
+    /// set for each of them, along with the [`InputDevice::resolution`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Sent by the seats, which merge the motion of their pointing devices.
+    /// - **macOS:** Sent for all the devices with the same identifier, and accelerated.
     MouseMotion {
         /// (x, y) change in position in unspecified units.
         ///
@@ -594,6 +1417,66 @@ pub enum DeviceEvent {
     Text {
         codepoint: char,
     },
+
+    /// A raw report of a HID device, which is only sent for the devices whose reports were
+    /// enabled with [`EventLoopWindowTarget::set_hid_reports_enabled`].
+    ///
+    /// [`EventLoopWindowTarget::set_hid_reports_enabled`]: crate::event_loop::EventLoopWindowTarget::set_hid_reports_enabled
+    HidReport {
+        /// The report, starting with its identifier when the device numbers its reports.
+        data: Vec<u8>,
+    },
+}
+
+/// The state of a dead key or compose sequence, as reported by [`WindowEvent::ComposeChanged`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ComposeState {
+    /// The sequence is in progress and waits for more keys, with the characters of the keys
+    /// pressed so far, such as `´` after the dead acute key.
+    Pending(String),
+    /// The sequence ended, producing the given characters.
+    Committed(String),
+    /// The sequence was cancelled, by a key which doesn't continue it or by the window losing
+    /// the focus.
+    Cancelled,
+}
+
+/// The text an input method is composing, as reported by [`WindowEvent::ImePreedit`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImePreedit {
+    /// The composed text, which is empty once the composition ended.
+    pub text: String,
+    /// The byte range of `text` the cursor spans, which is empty for a caret, or `None` if the
+    /// cursor is hidden.
+    pub cursor: Option<Range<usize>>,
+    /// The styles of the byte ranges of `text`, in order and without overlapping. The parts of
+    /// the text out of the spans are drawn as they are.
+    pub spans: Vec<ImePreeditSpan>,
+}
+
+/// A styled byte range of an [`ImePreedit`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImePreeditSpan {
+    pub range: Range<usize>,
+    pub underline: ImeUnderline,
+    /// Whether the span is highlighted, such as the clause being converted.
+    pub highlighted: bool,
+}
+
+/// How a span of an [`ImePreedit`] is underlined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImeUnderline {
+    None,
+    /// A thin line, for the text which is converted.
+    Single,
+    /// A thick line, for the clause being converted.
+    Thick,
+    /// A dotted line, for the text which is yet to be converted.
+    Dotted,
 }
 
 /// Describes a keyboard input event.
@@ -609,6 +1492,14 @@ pub struct KeyboardInput {
 
     pub state: ElementState,
 
+    /// Whether the press was repeated by the system because the key is held down.
+    ///
+    /// This is always `false` for the releases. The repeats are turned off for a window with
+    /// [`Window::set_key_repeat`].
+    ///
+    /// [`Window::set_key_repeat`]: crate::window::Window::set_key_repeat
+    pub repeat: bool,
+
     /// Identifies the semantic meaning of the key
     ///
     /// Use when the semantics of the key are more important than the physical location of the key, such as when
@@ -633,6 +1524,22 @@ pub enum TouchPhase {
     Cancelled,
 }
 
+/// The phase of a gesture of the touchpads or the touch screens, which is the same on every
+/// platform.
+///
+/// A gesture starts with a single `Started` event of a delta of zero, goes on with any number of
+/// `Updated` events, and ends with a single `Ended` event, or a `Cancelled` one when the platform
+/// takes it back, both of a delta of zero. The phases the platforms don't tell are synthesized,
+/// so that the gestures always start and end.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GesturePhase {
+    Started,
+    Updated,
+    Ended,
+    Cancelled,
+}
+
 /// Represents a touch event
 ///
 /// Every time the user touches the screen, a new `Start` event with an unique
@@ -648,12 +1555,22 @@ pub enum TouchPhase {
 ///
 /// A `Cancelled` event is emitted when the system has canceled tracking this
 /// touch, such as when the window loses focus, or on iOS if the user moves the
-/// device against their face.
+/// device against their face. The touches the system takes for a gesture of its
+/// own, like the swipes from the edges of the screen, or rejects as palms are
+/// cancelled too, so that every touch ends with either an `Ended` or a
+/// `Cancelled` event.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Touch {
     pub device_id: DeviceId,
     pub phase: TouchPhase,
     pub location: PhysicalPosition<f64>,
+    /// The time the touch was at `location`, which is earlier than the time the event is received
+    /// at, as the events are queued.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Android:** The time the event reached the event loop.
+    pub time: Instant,
     /// Describes how hard the screen was pressed. May be `None` if the platform
     /// does not support pressure sensitivity.
     ///
@@ -663,6 +1580,97 @@ pub struct Touch {
     pub force: Option<Force>,
     /// Unique identifier of a finger.
     pub id: u64,
+    /// Whether the platform took the contact for a palm resting on the screen, so that the
+    /// drawing applications can take back the stroke of the touch. The palms are told apart as
+    /// they come down or as they're cancelled, depending on the platform.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Only on Windows 7, from the start of the touches. The later versions cancel
+    ///   the palms without telling them apart.
+    /// - **Android:** Only on Android 13+, as the touches are cancelled.
+    /// - **X11 / Wayland / iOS:** Always `false`.
+    pub palm: bool,
+}
+
+/// Represents an event of a pen.
+///
+/// The pens come close with `PenPhase::Entered`, touch the surface with `PenPhase::Down` and lift
+/// off it with `PenPhase::Up` any number of times as they move, and leave with
+/// `PenPhase::Left`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PenEvent {
+    pub device_id: DeviceId,
+    pub phase: PenPhase,
+    /// The position of the tip, in physical pixels relative to the top-left corner of the window.
+    pub location: PhysicalPosition<f64>,
+    /// The time the pen was at `location`, which is earlier than the time the event is received
+    /// at, as the events are queued.
+    pub time: Instant,
+    /// The pressure of the tip, from 0.0 while the pen hovers to 1.0 at the most.
+    pub pressure: f64,
+    /// The tilt of the pen in degrees along the x and the y axes, from -90.0 to 90.0, towards the
+    /// right and the bottom of the window, or `None` when the pen doesn't tell.
+    pub tilt: Option<(f64, f64)>,
+    /// The rotation of the pen about its axis in degrees clockwise, from 0.0 to 360.0, or `None`
+    /// when the pen doesn't tell.
+    pub twist: Option<f64>,
+    /// Whether the pen is used as an eraser, which is its other end for most of the pens.
+    pub eraser: bool,
+    /// Whether the button on the barrel of the pen is held.
+    pub barrel_button: bool,
+    /// The id of the pen, which tells the pens apart. It's the serial of the hardware when the
+    /// platform knows it, which stays the same from a tablet to another.
+    pub tool_id: u64,
+    /// The kind of the tool, which is `PenTool::Eraser` whenever `eraser` is set.
+    pub tool: PenTool,
+    /// The serial of the hardware of the tool, when the tablet tells it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The serial of the transducer of the digitizers telling it over HID.
+    /// - **X11:** The serial of the Wacom tablets, from the `Wacom Serial IDs` property of their
+    ///   devices.
+    /// - **iOS:** Always `None`.
+    pub serial: Option<u64>,
+}
+
+/// Describes the kind of a tool of a tablet.
+///
+/// ## Platform-specific
+///
+/// - **Windows / iOS:** Only `Pen` and `Eraser`.
+/// - **X11:** From the name of the device, `Mouse` being the one of the Wacom cursors.
+/// - **macOS:** Only `Pen`, `Eraser` and `Mouse`, which is the one of the pucks.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenTool {
+    Pen,
+    Eraser,
+    Brush,
+    Pencil,
+    Airbrush,
+    Finger,
+    /// A mouse used on the tablet, such as a puck.
+    Mouse,
+    /// A mouse with a lens to aim at the tablet.
+    Lens,
+}
+
+/// Describes what a pen did.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PenPhase {
+    /// The pen came close to the window.
+    Entered,
+    /// The tip of the pen touched the surface.
+    Down,
+    /// The pen moved, or its pressure, its tilt, its twist or its buttons changed.
+    Moved,
+    /// The tip of the pen lifted off the surface.
+    Up,
+    /// The pen left the window, or went too far from it.
+    Left,
 }
 
 /// Describes the force of a touch event
@@ -745,6 +1753,24 @@ pub enum MouseButton {
     Left,
     Right,
     Middle,
+    /// The button going back, the side button nearest to the user on most mice.
+    ///
+    /// It's the first extended button (`XBUTTON1`) on Windows, and button 8 on X11.
+    Back,
+    /// The button going forward, the other side button on most mice.
+    ///
+    /// It's the second extended button (`XBUTTON2`) on Windows, and button 9 on X11.
+    Forward,
+    /// Any other button, numbered from 6 as the sixth button of the mouse on every platform, the
+    /// first five being the ones above.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Button `n` from 10 is `Other(n - 4)`, as buttons 4 to 7 scroll.
+    /// - **Wayland:** `BTN_FORWARD`, `BTN_BACK` and `BTN_TASK` are `Other(6)`, `Other(7)` and
+    ///   `Other(8)` like on X11, with the next codes continuing from there.
+    /// - **macOS / Web:** Button number `n` from 5 is `Other(n + 1)`, as they count from 0.
+    /// - **Windows:** The mice have no other buttons.
     Other(u16),
 }
 
@@ -765,6 +1791,44 @@ pub enum MouseScrollDelta {
     /// supported by the device (eg. a touchpad) and
     /// platform.
     PixelDelta(PhysicalPosition<f64>),
+    /// Amount in 120ths of the notches of a wheel to scroll in the horizontal and vertical
+    /// directions, with the same signs as `LineDelta`.
+    ///
+    /// A notch is a line, and high-resolution wheels scroll by fractions of a notch. Wheels are
+    /// reported so on the platforms having the high-resolution data, and as a `LineDelta`
+    /// elsewhere.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The notches are always whole, since the compositors give no
+    ///   high-resolution data to this version of the seats.
+    Value120(i32, i32),
+}
+
+impl MouseScrollDelta {
+    /// The amount in lines, which pixel deltas have none of.
+    pub fn lines(&self) -> Option<(f32, f32)> {
+        match *self {
+            MouseScrollDelta::LineDelta(x, y) => Some((x, y)),
+            MouseScrollDelta::PixelDelta(_) => None,
+            MouseScrollDelta::Value120(x, y) => Some((x as f32 / 120.0, y as f32 / 120.0)),
+        }
+    }
+}
+
+/// The kind of device a scroll came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AxisSource {
+    /// A wheel, scrolling by notches.
+    Wheel,
+    /// A wheel tilted sideways, scrolling horizontally.
+    WheelTilt,
+    /// A finger on a touchpad, which ends the scroll when it's lifted.
+    Finger,
+    /// A device moving in a continuous space without ending the scrolls, like a trackball or
+    /// scrolling by holding a button.
+    Continuous,
 }
 
 /// Symbolic name for a keyboard key.
@@ -970,6 +2034,35 @@ pub enum VirtualKeyCode {
     Cut,
 }
 
+impl VirtualKeyCode {
+    /// Returns the key at the position of `scancode` on a US keyboard, whatever the keyboard
+    /// layout.
+    ///
+    /// This is meant for storing the key bindings as physical keys, which are then shown with
+    /// [`EventLoopWindowTarget::physical_key_to_logical`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The scancodes of the extended keys are prefixed with `0xE0`, as the ones of
+    ///   `KeyboardInput`.
+    /// - **iOS / Android / Web:** Always returns `None`.
+    ///
+    /// [`EventLoopWindowTarget::physical_key_to_logical`]: crate::event_loop::EventLoopWindowTarget::physical_key_to_logical
+    #[inline]
+    pub fn from_scancode(scancode: ScanCode) -> Option<VirtualKeyCode> {
+        platform_impl::scancode_to_key(scancode)
+    }
+
+    /// Returns the scancode of the key at the position of `self` on a US keyboard, which is the
+    /// reverse of [`VirtualKeyCode::from_scancode`].
+    ///
+    /// Returns `None` for the keys which have no position on the keyboards of the platform.
+    #[inline]
+    pub fn to_scancode(self) -> Option<ScanCode> {
+        platform_impl::key_to_scancode(self)
+    }
+}
+
 impl ModifiersState {
     /// Returns `true` if the shift key is pressed.
     pub fn shift(&self) -> bool {
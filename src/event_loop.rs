@@ -10,10 +10,35 @@
 //! [event_loop_proxy]: crate::event_loop::EventLoopProxy
 //! [send_event]: crate::event_loop::EventLoopProxy::send_event
 use instant::Instant;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::mem;
 use std::ops::Deref;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 use std::{error, fmt};
 
-use crate::{event::Event, monitor::MonitorHandle, platform_impl};
+use crate::{
+    clipboard::{
+        CallbackProvider, ClipboardKind, ClipboardMimedContent, ClipboardProvider, MimeType,
+    },
+    dpi::{LogicalPosition, PhysicalPosition},
+    error::{ExternalError, OsError},
+    event::{DeviceId, Event, InputDevice, ModifiersState, ScanCode, VirtualKeyCode},
+    gamepad::GamepadId,
+    hotkey::{Accelerator, HotkeyId},
+    icon::{BadIcon, RgbaIcon},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
+    monitor::MonitorHandle,
+    platform_impl,
+    resampling::{Resampled, Resampler},
+    window::{CursorFrame, CustomCursor, Window, WindowBuilder},
+};
 
 /// Provides a way to retrieve events from the system and from the windows that were registered to
 /// the events loop.
@@ -31,6 +56,7 @@ use crate::{event::Event, monitor::MonitorHandle, platform_impl};
 ///
 pub struct EventLoop<T: 'static> {
     pub(crate) event_loop: platform_impl::EventLoop<T>,
+    pub(crate) proxy_channels: ProxyChannels<T>,
     pub(crate) _marker: ::std::marker::PhantomData<*mut ()>, // Not Send nor Sync
 }
 
@@ -64,8 +90,8 @@ impl<T> fmt::Debug for EventLoopWindowTarget<T> {
 ///
 /// ## Persistency
 /// Almost every change is persistent between multiple calls to the event loop closure within a
-/// given run loop. The only exception to this is `Exit` which, once set, cannot be unset. Changes
-/// are **not** persistent between multiple calls to `run_return` - issuing a new call will reset
+/// given run loop. The only exception to this is `ExitWithCode` which, once set, cannot be unset. Changes
+/// are **not** persistent between multiple calls to `run_on_demand` - issuing a new call will reset
 /// the control flow to `Poll`.
 ///
 /// [events_cleared]: crate::event::Event::RedrawEventsCleared
@@ -85,10 +111,30 @@ pub enum ControlFlow {
     /// When the current loop iteration finishes, suspend the thread until either another event
     /// arrives or the given time is reached.
     WaitUntil(Instant),
-    /// Send a `LoopDestroyed` event and stop the event loop. This variant is *sticky* - once set,
-    /// `control_flow` cannot be changed from `Exit`, and any future attempts to do so will result
-    /// in the `control_flow` parameter being reset to `Exit`.
-    Exit,
+    /// Send a `LoopDestroyed` event and stop the event loop, with the given exit code. This
+    /// variant is *sticky* - once set, `control_flow` cannot be changed from `ExitWithCode`, and
+    /// any future attempts to do so will result in the `control_flow` parameter being reset to
+    /// `ExitWithCode` with the same code.
+    ///
+    /// The exit code is the one the process exits with once `EventLoop::run` is over, and the one
+    /// returned by the `run_on_demand` and `pump_events` platform extensions.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The event loop also exits with the code 1 once the connection to the
+    ///   compositor fails, as on a protocol error.
+    /// - **iOS:** The event loop can't be exited, so this does nothing.
+    /// - **Web:** The exit code is ignored.
+    ExitWithCode(i32),
+}
+
+impl ControlFlow {
+    /// Send a `LoopDestroyed` event and stop the event loop, with an exit code of 0.
+    ///
+    /// This is `ExitWithCode(0)`, so a `match` on the control flow only catches the exits with the
+    /// code 0 with it. Use `ExitWithCode(_)` to catch all of them.
+    #[allow(non_upper_case_globals)]
+    pub const Exit: Self = Self::ExitWithCode(0);
 }
 
 impl Default for ControlFlow {
@@ -132,10 +178,34 @@ impl<T> EventLoop<T> {
     pub fn with_user_event() -> EventLoop<T> {
         EventLoop {
             event_loop: platform_impl::EventLoop::new(),
+            proxy_channels: Default::default(),
             _marker: ::std::marker::PhantomData,
         }
     }
 
+    /// Builds a secondary event loop on the calling thread, next to the event loop of the main
+    /// thread.
+    ///
+    /// The windows created through the secondary event loop belong to its thread, so heavy
+    /// windows such as video previews don't hold up the windows of the main thread. The
+    /// secondary event loop is driven with `run_return`, `run_on_demand` or `pump_events`, as
+    /// [`run`](EventLoop::run) exits the process once the event loop exits.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Every thread runs its own message loop.
+    /// - **X11:** The secondary event loop opens a connection to the X server of its own.
+    /// - **Wayland:** Unsupported, this returns `NotSupported` whenever the main event loop
+    ///   would run on Wayland.
+    /// - **macOS / iOS / Android / Web:** Unsupported, the windows only live on the main thread.
+    pub fn new_secondary() -> Result<EventLoop<T>, ExternalError> {
+        Ok(EventLoop {
+            event_loop: platform_impl::EventLoop::new_secondary()?,
+            proxy_channels: Default::default(),
+            _marker: ::std::marker::PhantomData,
+        })
+    }
+
     /// Hijacks the calling thread and initializes the winit event loop with the provided
     /// closure. Since the closure is `'static`, it must be a `move` closure if it needs to
     /// access any data from the calling context.
@@ -145,12 +215,16 @@ impl<T> EventLoop<T> {
     ///
     /// Any values not passed to this function will *not* be dropped.
     ///
+    /// Once the `control_flow` is set to `ControlFlow::ExitWithCode`, the process exits with the
+    /// given exit code after `LoopDestroyed` is handled.
+    ///
     /// [`ControlFlow`]: crate::event_loop::ControlFlow
     #[inline]
     pub fn run<F>(self, event_handler: F) -> !
     where
         F: 'static + FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
     {
+        let event_handler = self.proxy_channels.wrap_handler(event_handler);
         self.event_loop.run(event_handler)
     }
 
@@ -158,8 +232,49 @@ impl<T> EventLoop<T> {
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
         EventLoopProxy {
             event_loop_proxy: self.event_loop.create_proxy(),
+            window_requests: self
+                .proxy_channels
+                .window_requests(|| self.event_loop.create_wakeup()),
         }
     }
+
+    /// Creates a `BoundedEventLoopProxy`, which dispatches user events to the main event loop
+    /// through a channel holding up to `capacity` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn create_bounded_proxy(&self, capacity: usize) -> BoundedEventLoopProxy<T> {
+        assert!(
+            capacity > 0,
+            "the capacity of a bounded proxy can't be zero"
+        );
+        self.proxy_channels
+            .create(capacity, self.event_loop.create_wakeup())
+    }
+
+    /// Sets the resampling of the moves of the touches and the pens, or turns it off with `None`,
+    /// which is the default.
+    ///
+    /// The resampled moves reduce the stutter of the inking and the scrolling, as the windows
+    /// drawn at every `RedrawRequested` get one move of every touch and pen per frame, timed on
+    /// the refreshes of the display. They're interpolated from the times the platform gives the
+    /// moves, and their `time` is the one they're resampled at. The pressure, the tilt and the
+    /// other fields are the ones of the last move.
+    ///
+    /// The refreshes are timed by the [`WindowEvent::Presented`] of the windows, and the moves of
+    /// the windows without them are resampled at the end of the events instead.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Android:** The moves are timed as they reach the event loop.
+    /// - **Web:** The moves are timed as they reach the event loop, which only gets the queued
+    ///   events at the animation frames, so they're resampled coarsely.
+    ///
+    /// [`WindowEvent::Presented`]: crate::event::WindowEvent::Presented
+    pub fn set_motion_resampling(&self, resampling: Option<MotionResampling>) {
+        self.proxy_channels.set_resampling(resampling);
+    }
 }
 
 impl<T> Deref for EventLoop<T> {
@@ -171,6 +286,8 @@ impl<T> Deref for EventLoop<T> {
 
 impl<T> EventLoopWindowTarget<T> {
     /// Returns the list of all the monitors available on the system.
+    ///
+    /// An [`Event::MonitorsChanged`] is sent once monitors were connected or disconnected.
     #[inline]
     pub fn available_monitors(&self) -> impl Iterator<Item = MonitorHandle> {
         self.p
@@ -179,6 +296,327 @@ impl<T> EventLoopWindowTarget<T> {
             .map(|inner| MonitorHandle { inner })
     }
 
+    /// Returns the monitor on which the position, in the physical pixels of the full screen
+    /// area, is, along with the logical position relative to the top-left corner of that monitor.
+    ///
+    /// Returns `None` if the position isn't on any monitor.
+    ///
+    /// See [`MonitorHandle::to_local`] and [`MonitorHandle::to_global`] to convert the positions
+    /// of a given monitor.
+    #[inline]
+    pub fn monitor_from_position(
+        &self,
+        position: PhysicalPosition<i32>,
+    ) -> Option<(MonitorHandle, LogicalPosition<f64>)> {
+        self.available_monitors()
+            .find(|monitor| monitor.contains(position))
+            .map(|monitor| {
+                let local = monitor.to_local(position);
+                (monitor, local)
+            })
+    }
+
+    /// Sets the timer `id`, which emits an [`Event::Timer`] once it fires.
+    ///
+    /// Setting a timer again reschedules it, so several timers are only told apart by their
+    /// `id`. The timers keep running when the event loop waits, whatever the `ControlFlow`,
+    /// and a repeating timer which missed some of its intervals only fires once for them.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The timers have the resolution of the system timer, which is usually
+    ///   around 15 milliseconds.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`Event::Timer`]: crate::event::Event::Timer
+    #[inline]
+    pub fn set_timer(&self, id: TimerId, timer: Timer) -> Result<(), ExternalError> {
+        self.p.set_timer(id, timer)
+    }
+
+    /// Cancels the timer `id`, returning whether it was still going to fire.
+    #[inline]
+    pub fn cancel_timer(&self, id: TimerId) -> bool {
+        self.p.cancel_timer(id)
+    }
+
+    /// Sets whether `ControlFlow::WaitUntil` wakes the event loop up precisely at the requested
+    /// instant, instead of up to the resolution of the system timer later.
+    ///
+    /// This is meant for the applications pacing their frames, since the precise waits wake the
+    /// system up more often. They are disabled by default.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Waits with a high resolution waitable timer, which is available since
+    ///   Windows 10 version 1803. The older versions keep the resolution of the system timer.
+    /// - **macOS:** The run loop wakes up shortly before the deadline, then sleeps until it with
+    ///   `mach_wait_until`.
+    /// - **Linux:** Waits for a `timerfd` following the deadline. The BSDs already wait precisely.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_precise_wait(&self, precise: bool) -> Result<(), ExternalError> {
+        self.p.set_precise_wait(precise)
+    }
+
+    /// Sets whether the event loop handles the gamepads, delivering their events through
+    /// [`Event::GamepadEvent`].
+    ///
+    /// Once they're enabled, a `Connected` event is sent for every gamepad already connected.
+    /// When they're disabled, the gamepads are forgotten without being disconnected, and they're
+    /// connected again once the gamepads are enabled again. They are disabled by default.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Polls the XInput controllers every 8 milliseconds on a thread of its own,
+    ///   which only wakes the event loop up when they changed, and looks for the newly connected
+    ///   ones every second. XInput doesn't give the names of the controllers, nor has the `Mode`
+    ///   button.
+    /// - **macOS:** Polls the extended gamepads of the Game Controller framework every
+    ///   8 milliseconds. Before macOS 11.3, their input is only received while the application
+    ///   is active.
+    /// - **Linux:** Reads the evdev devices of `/dev/input` which have the buttons of a gamepad
+    ///   or of a joystick, which needs the permission to read them, and watches the directory for
+    ///   the gamepads plugged later. Their buttons are mapped following the gamepad layout of the
+    ///   kernel. The BSDs always return an [`ExternalError::NotSupported`].
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`Event::GamepadEvent`]: crate::event::Event::GamepadEvent
+    #[inline]
+    pub fn set_gamepads_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        self.p.set_gamepads_enabled(enabled)
+    }
+
+    /// Rumbles the gamepad for `duration`, with the strengths of its strong and weak motors,
+    /// which go from `0.0` to `1.0`. The strong motor is the low frequency one, usually on the
+    /// left, and the weak motor the high frequency one.
+    ///
+    /// A rumble replaces the previous one, so a rumble without strength stops it. The gamepads
+    /// which don't rumble return an [`ExternalError::NotSupported`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Sets the vibration of the XInput controller, which is stopped once the
+    ///   duration elapsed by the thread polling the controllers.
+    /// - **macOS:** Plays a continuous haptic event of Core Haptics, whose intensity is the one
+    ///   of the strongest motor and whose sharpness is the share of the weak motor. Needs
+    ///   macOS 11.
+    /// - **Linux:** Plays a rumble effect of the force feedback of the evdev device, which needs
+    ///   the permission to write to it. The durations are capped at about 65 seconds.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn rumble_gamepad(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        self.p.rumble_gamepad(gamepad_id, strong, weak, duration)
+    }
+
+    /// Performs the haptic feedback on the trackpads of the system, to acknowledge the gestures
+    /// and the moves of the user.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Performs the pattern of `NSHapticFeedbackManager` on the Force Touch
+    ///   trackpads, right away. The other trackpads don't give any feedback.
+    /// - **Windows / Linux / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn perform_haptic_feedback(&self, pattern: HapticPattern) -> Result<(), ExternalError> {
+        self.p.perform_haptic_feedback(pattern)
+    }
+
+    /// Returns the input devices connected to the system, whose identifiers are the ones of the
+    /// events they send.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Returns the keyboards, the mice and the HID devices of the raw input, whose
+    ///   identifiers are the ones of the `DeviceEvent`s. The window events identify all the
+    ///   devices with the same identifier, which isn't returned. The names are the ones the HID
+    ///   devices give, and are generic for the others.
+    /// - **X11:** Returns the master devices, which send the window events, along with the
+    ///   physical devices attached to them, which send the `DeviceEvent`s.
+    /// - **Wayland:** Returns the seats, which are the devices of all the events. The seats have
+    ///   neither vendor nor product identifiers, and their pens aren't reported.
+    /// - **macOS / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`], the
+    ///   events not identifying their devices.
+    #[inline]
+    pub fn input_devices(&self) -> Result<Vec<InputDevice>, ExternalError> {
+        self.p.input_devices()
+    }
+
+    /// Sets whether the raw reports of the HID device are delivered through
+    /// [`DeviceEvent::HidReport`], for the devices winit doesn't otherwise handle, such as the 3D
+    /// mice. The reports are delivered regardless of the focus. They are disabled by default.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Registers the usage of the device for the raw input, so the other devices
+    ///   of the same usage also send their input to the event loop, though only the reports of
+    ///   the enabled devices are delivered. The keyboards and the mice aren't HID devices there.
+    /// - **X11:** Reads the `hidraw` node of the device, which needs the permission to read it.
+    /// - **Wayland / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`], the devices of the events not being HID devices.
+    ///
+    /// [`DeviceEvent::HidReport`]: crate::event::DeviceEvent::HidReport
+    #[inline]
+    pub fn set_hid_reports_enabled(
+        &self,
+        device: DeviceId,
+        enabled: bool,
+    ) -> Result<(), ExternalError> {
+        self.p.set_hid_reports_enabled(device.0, enabled)
+    }
+
+    /// Returns the keyboard layouts installed on the system.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Returns the layouts of the keymap of the core keyboard, which are the ones the
+    ///   user switches between.
+    /// - **Wayland / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn keyboard_layouts(&self) -> Result<Vec<KeyboardLayout>, ExternalError> {
+        self.p.keyboard_layouts()
+    }
+
+    /// Returns the active keyboard layout.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Returns the layout of the thread of the event loop.
+    /// - **Wayland / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn current_keyboard_layout(&self) -> Result<KeyboardLayout, ExternalError> {
+        self.p.current_keyboard_layout()
+    }
+
+    /// Returns the characters the key at `scancode` produces with the active keyboard layout,
+    /// when it's pressed along with the `modifiers`.
+    ///
+    /// The scancodes are the ones of [`KeyboardInput::scancode`]. Returns `None` for the keys
+    /// which don't produce any character.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS:** The dead keys return their spacing character.
+    /// - **X11:** The dead keys return `None`.
+    /// - **Wayland / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`KeyboardInput::scancode`]: crate::event::KeyboardInput::scancode
+    #[inline]
+    pub fn scancode_to_text(
+        &self,
+        scancode: ScanCode,
+        modifiers: ModifiersState,
+    ) -> Result<Option<String>, ExternalError> {
+        self.p.scancode_to_text(scancode, modifiers)
+    }
+
+    /// Returns how the system repeats the keys which are held down, or `None` if the keys
+    /// don't repeat.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The speeds of the control panel are rounded to the milliseconds.
+    /// - **Wayland / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn key_repeat(&self) -> Result<Option<KeyRepeat>, ExternalError> {
+        self.p.key_repeat()
+    }
+
+    /// Returns the lock keys which are on, as the LEDs of the keyboard show them.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Only reports Caps Lock, as the keyboards of the Mac have no other lock key.
+    /// - **X11:** Reports the indicators of the core keyboard.
+    /// - **Wayland:** Reports the keyboards as of the last time a window of the application had
+    ///   the keyboard focus. Scroll Lock isn't reported.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        self.p.lock_keys()
+    }
+
+    /// Turns the `lock_keys` on or off, as if the user pressed them, leaving the other lock keys
+    /// as they are.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Synthesizes the presses of the keys, so the application receives them like
+    ///   the other applications do.
+    /// - **X11:** Locks the modifiers of Caps Lock and Num Lock, and sets the indicator of Scroll
+    ///   Lock, which usually locks no modifier.
+    /// - **macOS / Wayland / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_lock_keys(&self, lock_keys: LockKeys, on: bool) -> Result<(), ExternalError> {
+        self.p.set_lock_keys(lock_keys, on)
+    }
+
+    /// Returns the characters the physical key `key` produces with the active keyboard layout,
+    /// when it's pressed along with the `modifiers`.
+    ///
+    /// The physical keys are named after their position on a US keyboard, as the ones of
+    /// [`VirtualKeyCode::from_scancode`]. Returns `None` for the keys which don't produce any
+    /// character.
+    ///
+    /// ## Platform-specific
+    ///
+    /// See [`EventLoopWindowTarget::scancode_to_text`].
+    ///
+    /// [`VirtualKeyCode::from_scancode`]: crate::event::VirtualKeyCode::from_scancode
+    #[inline]
+    pub fn physical_key_to_logical(
+        &self,
+        key: VirtualKeyCode,
+        modifiers: ModifiersState,
+    ) -> Result<Option<String>, ExternalError> {
+        match key.to_scancode() {
+            Some(scancode) => self.scancode_to_text(scancode, modifiers),
+            None => Ok(None),
+        }
+    }
+
+    /// Registers `accelerator` as a global hotkey, whose presses are delivered through
+    /// [`Event::GlobalHotkey`] even when no window of the application has the focus.
+    ///
+    /// The key of the accelerator is looked up in the active keyboard layout when registering
+    /// the hotkey, and the hotkey stays on the same physical key afterwards. This fails if the
+    /// layout has no such key, or if the combination is already registered, by this application
+    /// or by another one. The hotkeys don't repeat while they're held down.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Registered with the Carbon hotkeys, which don't require the accessibility
+    ///   permission.
+    /// - **X11:** Grabs the key on the root window. The combination is registered whether or not
+    ///   Num Lock and Caps Lock are on.
+    /// - **Wayland:** Always returns an [`ExternalError::NotSupported`], as the compositors only
+    ///   offer the global shortcuts through the `GlobalShortcuts` desktop portal, which isn't
+    ///   implemented yet.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`Event::GlobalHotkey`]: crate::event::Event::GlobalHotkey
+    #[inline]
+    pub fn register_global_hotkey(
+        &self,
+        accelerator: Accelerator,
+    ) -> Result<HotkeyId, ExternalError> {
+        self.p.register_global_hotkey(accelerator)
+    }
+
+    /// Unregisters the global hotkey `id`, returning whether it was still registered.
+    #[inline]
+    pub fn unregister_global_hotkey(&self, id: HotkeyId) -> bool {
+        self.p.unregister_global_hotkey(id)
+    }
+
     /// Returns the primary monitor of the system.
     ///
     /// Returns `None` if it can't identify any monitor as a primary one.
@@ -190,17 +628,130 @@ impl<T> EventLoopWindowTarget<T> {
     pub fn primary_monitor(&self) -> Option<MonitorHandle> {
         self.p.primary_monitor()
     }
+
+    /// Sets the content of the clipboard of the given `kind`.
+    ///
+    /// The content is served to other applications for as long as the event loop is running,
+    /// or until some other application takes over the clipboard.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires a recent input event on any of the windows, like a key press.
+    ///   [`ClipboardKind::Primary`] requires the `zwp_primary_selection_device_manager_v1`
+    ///   protocol.
+    /// - **macOS / Windows:** The content is handed over to the system clipboard once some
+    ///   application requests it. [`ClipboardKind::Primary`] returns an
+    ///   [`ExternalError::NotSupported`].
+    /// - **Web:** Requires a secure context. Only text, HTML, PNG images and the custom
+    ///   formats prefixed with `web ` are supported. The clipboard is set asynchronously, errors
+    ///   are only logged. [`ClipboardKind::Primary`] returns an [`ExternalError::NotSupported`].
+    /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        content: ClipboardMimedContent,
+    ) -> Result<(), ExternalError> {
+        self.p.set_clipboard(kind, Arc::new(content))
+    }
+
+    /// Sets the content of the clipboard of the given `kind` to the data of the `provider`.
+    ///
+    /// See [`Window::set_clipboard_stream`](crate::window::Window::set_clipboard_stream) for
+    /// the details.
+    #[inline]
+    pub fn set_clipboard_stream(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        self.p.set_clipboard(kind, provider)
+    }
+
+    /// Sets the content of the clipboard of the given `kind`, which is only rendered by
+    /// `render` once some application requests the data of one of the `mime_types`.
+    ///
+    /// See [`Window::set_clipboard_provider`](crate::window::Window::set_clipboard_provider)
+    /// for the details.
+    #[inline]
+    pub fn set_clipboard_provider<F>(
+        &self,
+        kind: ClipboardKind,
+        mime_types: Vec<MimeType>,
+        render: F,
+    ) -> Result<(), ExternalError>
+    where
+        F: FnMut(MimeType) -> ClipboardMimedContent + Send + 'static,
+    {
+        let provider = CallbackProvider::new(mime_types, render);
+        self.p.set_clipboard(kind, Arc::new(provider))
+    }
+
+    /// Creates a cursor from an image of `width` by `height` pixels in RGBA order, with the
+    /// `hotspot` being the pixel of the image pointing at the position of the cursor.
+    ///
+    /// The hotspot is clamped to the image. The cursor is shown with
+    /// [`Window::set_cursor`](crate::window::Window::set_cursor).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android:** The cursor is never shown.
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        let image = RgbaIcon::from_rgba(rgba, width, height)?;
+        let hotspot = PhysicalPosition::new(
+            hotspot.x.min(width.saturating_sub(1)),
+            hotspot.y.min(height.saturating_sub(1)),
+        );
+        Ok(CustomCursor {
+            inner: self.p.create_custom_cursor(image, hotspot)?,
+        })
+    }
+
+    /// Creates a cursor cycling through the frames, each shown for its delay.
+    ///
+    /// The cursor is shown with [`Window::set_cursor`](crate::window::Window::set_cursor).
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The cursor is animated by the X server, as the animated Xcursor themes.
+    /// - **Windows:** The cursor is an animated cursor, as the `.ani` files, whose delays are
+    ///   rounded to the sixtieths of a second.
+    /// - **Wayland / macOS / Web:** The frames are changed by a timer of the event loop.
+    /// - **iOS / Android:** The cursor is never shown.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no frames.
+    #[inline]
+    pub fn create_animated_cursor(
+        &self,
+        frames: Vec<CursorFrame>,
+    ) -> Result<CustomCursor, BadIcon> {
+        assert!(!frames.is_empty(), "An animated cursor needs frames");
+        Ok(CustomCursor {
+            inner: self.p.create_animated_cursor(frames)?,
+        })
+    }
 }
 
 /// Used to send custom events to `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
     event_loop_proxy: platform_impl::EventLoopProxy<T>,
+    window_requests: Arc<WindowRequests>,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
     fn clone(&self) -> Self {
         Self {
             event_loop_proxy: self.event_loop_proxy.clone(),
+            window_requests: self.window_requests.clone(),
         }
     }
 }
@@ -214,6 +765,117 @@ impl<T: 'static> EventLoopProxy<T> {
     pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
         self.event_loop_proxy.send_event(event)
     }
+
+    /// Creates a window on the thread of the `EventLoop` from which this proxy was created,
+    /// returning a future which resolves to the window once it's created.
+    ///
+    /// The window is created by the event loop right after the next `NewEvents`, so the future
+    /// only resolves while it runs. It's meant to be awaited from another thread, since the
+    /// event loop doesn't poll the future itself.
+    ///
+    /// The future resolves to an `Err` if the window can't be created, or if the associated
+    /// `EventLoop` no longer exists.
+    pub fn create_window(&self, builder: WindowBuilder) -> CreateWindow {
+        let reply = Arc::new(Mutex::new(Reply {
+            result: None,
+            waker: None,
+        }));
+        let mut requests = self.window_requests.requests.lock().unwrap();
+        if requests.closed {
+            reply.lock().unwrap().result = Some(Err(CreateWindowError::EventLoopClosed));
+        } else {
+            requests.pending.push((builder, reply.clone()));
+            self.window_requests.wakeup.wake_up();
+        }
+        CreateWindow { reply }
+    }
+}
+
+/// The future returned by [`EventLoopProxy::create_window`].
+#[must_use = "the window is destroyed once it's dropped along with the future"]
+pub struct CreateWindow {
+    reply: Arc<Mutex<Reply>>,
+}
+
+impl Future for CreateWindow {
+    type Output = Result<Window, CreateWindowError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut reply = self.reply.lock().unwrap();
+        match reply.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                reply.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CreateWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("CreateWindow { .. }")
+    }
+}
+
+/// The error that is returned when a window requested with [`EventLoopProxy::create_window`]
+/// can't be created.
+#[derive(Debug)]
+pub enum CreateWindowError {
+    /// The `EventLoop` no longer exists.
+    EventLoopClosed,
+    /// The OS cannot perform the operation.
+    Os(OsError),
+}
+
+impl fmt::Display for CreateWindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CreateWindowError::EventLoopClosed => {
+                f.write_str("Tried to create a window with a closed `EventLoop`")
+            }
+            CreateWindowError::Os(err) => err.fmt(f),
+        }
+    }
+}
+
+impl error::Error for CreateWindowError {}
+
+/// The windows requested through the `EventLoopProxy`, which are created by the event loop.
+struct WindowRequests {
+    requests: Mutex<WindowRequestQueue>,
+    wakeup: platform_impl::EventLoopWakeup,
+}
+
+struct WindowRequestQueue {
+    pending: Vec<(WindowBuilder, Arc<Mutex<Reply>>)>,
+    closed: bool,
+}
+
+struct Reply {
+    result: Option<Result<Window, CreateWindowError>>,
+    /// Woken up once the result is there.
+    waker: Option<Waker>,
+}
+
+impl Reply {
+    fn resolve(reply: &Mutex<Reply>, result: Result<Window, CreateWindowError>) {
+        let mut reply = reply.lock().unwrap();
+        reply.result = Some(result);
+        if let Some(waker) = reply.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl WindowRequests {
+    fn close(&self) {
+        let mut requests = self.requests.lock().unwrap();
+        requests.closed = true;
+        for (_, reply) in requests.pending.drain(..) {
+            Reply::resolve(&reply, Err(CreateWindowError::EventLoopClosed));
+        }
+    }
 }
 
 impl<T: 'static> fmt::Debug for EventLoopProxy<T> {
@@ -222,6 +884,343 @@ impl<T: 'static> fmt::Debug for EventLoopProxy<T> {
     }
 }
 
+/// Used to send custom events to `EventLoop` through a bounded channel.
+///
+/// Unlike with the `EventLoopProxy`, the events sent before the event loop gets to them only
+/// wake it up once, and the channel holds up to the capacity given to
+/// [`EventLoop::create_bounded_proxy`], so the senders are held back once it's full. The events
+/// are handed over as `UserEvent`s right before `MainEventsCleared`, in the order they were
+/// sent.
+///
+/// The high priority events skip the line: they're handed over right after `NewEvents`, and they
+/// don't count towards the capacity, so they get through a full channel.
+///
+/// ## Platform-specific
+///
+/// - **Web:** The events sent by the event handler itself after `MainEventsCleared` are handed
+///   over once the event loop goes through another iteration.
+pub struct BoundedEventLoopProxy<T: 'static> {
+    channel: Arc<BoundedChannel<T>>,
+}
+
+impl<T: 'static> Clone for BoundedEventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<T: 'static> BoundedEventLoopProxy<T> {
+    /// Sends an event to the `EventLoop` from which this proxy was created, waiting for some
+    /// room in the channel if it's full.
+    ///
+    /// This must not be called from the thread of the event loop, which is the one making room
+    /// in the channel.
+    ///
+    /// Returns an `Err` if the associated `EventLoop` no longer exists.
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        let mut lanes = self.channel.lanes.lock().unwrap();
+        while !lanes.closed && lanes.normal.len() >= self.channel.capacity {
+            lanes = self.channel.room.wait(lanes).unwrap();
+        }
+        if lanes.closed {
+            return Err(EventLoopClosed(event));
+        }
+        lanes.normal.push_back(event);
+        drop(lanes);
+        self.channel.wake_up();
+        Ok(())
+    }
+
+    /// Sends an event to the `EventLoop` from which this proxy was created, if there's some
+    /// room in the channel.
+    ///
+    /// Returns an `Err` if the channel is full, or if the associated `EventLoop` no longer
+    /// exists.
+    pub fn try_send_event(&self, event: T) -> Result<(), TrySendError<T>> {
+        let mut lanes = self.channel.lanes.lock().unwrap();
+        if lanes.closed {
+            return Err(TrySendError::Closed(event));
+        }
+        if lanes.normal.len() >= self.channel.capacity {
+            return Err(TrySendError::Full(event));
+        }
+        lanes.normal.push_back(event);
+        drop(lanes);
+        self.channel.wake_up();
+        Ok(())
+    }
+
+    /// Sends a high priority event to the `EventLoop` from which this proxy was created, which
+    /// is handed over before the other events, whether the channel is full or not.
+    ///
+    /// Returns an `Err` if the associated `EventLoop` no longer exists.
+    pub fn send_high_priority_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        let mut lanes = self.channel.lanes.lock().unwrap();
+        if lanes.closed {
+            return Err(EventLoopClosed(event));
+        }
+        lanes.high.push_back(event);
+        drop(lanes);
+        self.channel.wake_up();
+        Ok(())
+    }
+}
+
+impl<T: 'static> fmt::Debug for BoundedEventLoopProxy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("BoundedEventLoopProxy { .. }")
+    }
+}
+
+/// The error that is returned when a `BoundedEventLoopProxy` can't send an event right away.
+/// Contains the original event given to `try_send_event`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TrySendError<T> {
+    /// The channel is full.
+    Full(T),
+    /// The `EventLoop` no longer exists.
+    Closed(T),
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("Tried to send an event to a full channel"),
+            TrySendError::Closed(_) => f.write_str("Tried to wake up a closed `EventLoop`"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> error::Error for TrySendError<T> {}
+
+struct BoundedChannel<T> {
+    lanes: Mutex<Lanes<T>>,
+    /// Notified once the event loop takes the events, or once it no longer exists.
+    room: Condvar,
+    capacity: usize,
+    /// Whether the event loop was woken up, and hasn't taken the events yet.
+    wakeup_pending: AtomicBool,
+    wakeup: platform_impl::EventLoopWakeup,
+}
+
+struct Lanes<T> {
+    normal: VecDeque<T>,
+    high: VecDeque<T>,
+    closed: bool,
+}
+
+impl<T> BoundedChannel<T> {
+    /// Wakes the event loop up, unless it's already woken up for the events sent earlier.
+    fn wake_up(&self) {
+        if !self.wakeup_pending.swap(true, Ordering::AcqRel) {
+            self.wakeup.wake_up();
+        }
+    }
+
+    fn take_events(&self, all: bool) -> (VecDeque<T>, VecDeque<T>) {
+        if all {
+            // The events sent from now on wake the event loop up again.
+            self.wakeup_pending.store(false, Ordering::Release);
+        }
+        let mut lanes = self.lanes.lock().unwrap();
+        let high = mem::take(&mut lanes.high);
+        let normal = if all {
+            mem::take(&mut lanes.normal)
+        } else {
+            VecDeque::new()
+        };
+        drop(lanes);
+        if !normal.is_empty() {
+            self.room.notify_all();
+        }
+        (high, normal)
+    }
+}
+
+/// The channels of the proxies created for an `EventLoop`, whose events and window requests are
+/// handled by the event handler given to the event loop, along with the resampling of the moves.
+pub(crate) struct ProxyChannels<T: 'static> {
+    list: Rc<Channels<T>>,
+}
+
+struct Channels<T> {
+    channels: RefCell<Vec<Arc<BoundedChannel<T>>>>,
+    /// The window requests of the `EventLoopProxy`, once one is created.
+    window_requests: RefCell<Option<Arc<WindowRequests>>>,
+    /// The resampler of the moves, when the resampling is on.
+    resampler: RefCell<Option<Resampler>>,
+}
+
+impl<T> Default for ProxyChannels<T> {
+    fn default() -> Self {
+        Self {
+            list: Rc::new(Channels {
+                channels: RefCell::new(Vec::new()),
+                window_requests: RefCell::new(None),
+                resampler: RefCell::new(None),
+            }),
+        }
+    }
+}
+
+impl<T> Drop for Channels<T> {
+    fn drop(&mut self) {
+        for channel in self.channels.get_mut().drain(..) {
+            channel.lanes.lock().unwrap().closed = true;
+            channel.room.notify_all();
+        }
+        if let Some(window_requests) = self.window_requests.get_mut() {
+            window_requests.close();
+        }
+    }
+}
+
+impl<T> ProxyChannels<T> {
+    fn create(
+        &self,
+        capacity: usize,
+        wakeup: platform_impl::EventLoopWakeup,
+    ) -> BoundedEventLoopProxy<T> {
+        let channel = Arc::new(BoundedChannel {
+            lanes: Mutex::new(Lanes {
+                normal: VecDeque::new(),
+                high: VecDeque::new(),
+                closed: false,
+            }),
+            room: Condvar::new(),
+            capacity,
+            wakeup_pending: AtomicBool::new(false),
+            wakeup,
+        });
+        self.list.channels.borrow_mut().push(channel.clone());
+        BoundedEventLoopProxy { channel }
+    }
+
+    fn window_requests(
+        &self,
+        wakeup: impl FnOnce() -> platform_impl::EventLoopWakeup,
+    ) -> Arc<WindowRequests> {
+        self.list
+            .window_requests
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                Arc::new(WindowRequests {
+                    requests: Mutex::new(WindowRequestQueue {
+                        pending: Vec::new(),
+                        closed: false,
+                    }),
+                    wakeup: wakeup(),
+                })
+            })
+            .clone()
+    }
+
+    fn set_resampling(&self, resampling: Option<MotionResampling>) {
+        *self.list.resampler.borrow_mut() = resampling.map(Resampler::new);
+    }
+
+    /// Wraps the event handler given to the event loop, so it also creates the windows requested
+    /// through the proxies, is handed the events of the bounded ones and gets the resampled moves.
+    pub(crate) fn wrap_handler<F>(
+        &self,
+        mut event_handler: F,
+    ) -> impl FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow)
+    where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let list = self.list.clone();
+        move |event, window_target, control_flow| match event {
+            Event::NewEvents(_) => {
+                event_handler(event, window_target, control_flow);
+                list.create_windows(window_target);
+                list.dispatch(false, window_target, control_flow, &mut event_handler);
+            }
+            Event::MainEventsCleared => {
+                list.dispatch(true, window_target, control_flow, &mut event_handler);
+                let resampled = match &mut *list.resampler.borrow_mut() {
+                    Some(resampler) => resampler.resample(Instant::now()),
+                    None => Vec::new(),
+                };
+                for (window_id, event) in resampled {
+                    let event = Event::WindowEvent { window_id, event };
+                    event_handler(event, window_target, control_flow);
+                }
+                event_handler(event, window_target, control_flow);
+            }
+            Event::WindowEvent { window_id, event } => {
+                let resampled = match &mut *list.resampler.borrow_mut() {
+                    Some(resampler) => resampler.push(window_id, &event),
+                    None => Resampled::Passed(None),
+                };
+                if let Resampled::Passed(flushed) = resampled {
+                    if let Some((window_id, event)) = flushed {
+                        let event = Event::WindowEvent { window_id, event };
+                        event_handler(event, window_target, control_flow);
+                    }
+                    let event = Event::WindowEvent { window_id, event };
+                    event_handler(event, window_target, control_flow);
+                }
+            }
+            event => event_handler(event, window_target, control_flow),
+        }
+    }
+}
+
+impl<T> Channels<T> {
+    fn create_windows(&self, window_target: &EventLoopWindowTarget<T>) {
+        let pending = match &*self.window_requests.borrow() {
+            Some(window_requests) => {
+                mem::take(&mut window_requests.requests.lock().unwrap().pending)
+            }
+            None => return,
+        };
+        for (builder, reply) in pending {
+            let result = builder.build(window_target).map_err(CreateWindowError::Os);
+            Reply::resolve(&reply, result);
+        }
+    }
+
+    /// Hands the high priority events over, followed by the other ones if `all` is set.
+    fn dispatch<F>(
+        &self,
+        all: bool,
+        window_target: &EventLoopWindowTarget<T>,
+        control_flow: &mut ControlFlow,
+        event_handler: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let channels = {
+            let mut channels = self.channels.borrow_mut();
+            if channels.is_empty() {
+                return;
+            }
+            // The channels of the proxies which are all gone can't get new events anymore.
+            channels.retain(|channel| {
+                Arc::strong_count(channel) > 1 || {
+                    let lanes = channel.lanes.lock().unwrap();
+                    !lanes.high.is_empty() || !lanes.normal.is_empty()
+                }
+            });
+            channels.clone()
+        };
+
+        let mut normal_events = Vec::new();
+        for channel in &channels {
+            let (high, normal) = channel.take_events(all);
+            for event in high {
+                event_handler(Event::UserEvent(event), window_target, control_flow);
+            }
+            normal_events.push(normal);
+        }
+        for event in normal_events.into_iter().flatten() {
+            event_handler(Event::UserEvent(event), window_target, control_flow);
+        }
+    }
+}
+
 /// The error that is returned when an `EventLoopProxy` attempts to wake up an `EventLoop` that
 /// no longer exists. Contains the original event given to `send_event`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -234,3 +1233,87 @@ impl<T> fmt::Display for EventLoopClosed<T> {
 }
 
 impl<T: fmt::Debug> error::Error for EventLoopClosed<T> {}
+
+/// Identifies an external event source registered with the event loop, which is handed back
+/// through [`Event::ExternalSource`] once the source is ready.
+///
+/// The value is chosen by the application when registering the source, and must be unique
+/// among the sources of an event loop.
+///
+/// [`Event::ExternalSource`]: crate::event::Event::ExternalSource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SourceToken(pub usize);
+
+bitflags! {
+    /// The readiness of an external event source the event loop waits for.
+    pub struct SourceInterest: u8 {
+        /// The source is ready to be read from.
+        const READABLE = 1 << 0;
+        /// The source is ready to be written to.
+        const WRITABLE = 1 << 1;
+    }
+}
+
+/// Identifies a timer set with [`EventLoopWindowTarget::set_timer`], which is handed back
+/// through [`Event::Timer`].
+///
+/// [`Event::Timer`]: crate::event::Event::Timer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimerId(pub usize);
+
+/// When a timer set with [`EventLoopWindowTarget::set_timer`] fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timer {
+    /// The timer fires once, at the given instant, or right away if it's already past.
+    At(Instant),
+    /// The timer fires repeatedly, at the given interval, starting one interval from now.
+    Every(Duration),
+}
+
+/// The haptic feedback performed with [`EventLoopWindowTarget::perform_haptic_feedback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HapticPattern {
+    /// A feedback without any particular meaning.
+    Generic,
+    /// The feedback of an element snapping into alignment, such as a guide.
+    Alignment,
+    /// The feedback of a change of level, such as the steps of a pressure.
+    LevelChange,
+}
+
+/// The resampling of the moves of the touches and the pens, set with
+/// [`EventLoop::set_motion_resampling`].
+///
+/// The moves are held until the end of the events of every iteration of the event loop, and a
+/// single move of every touch and pen is then sent before `MainEventsCleared`, at the position
+/// it had `latency` before the last refresh of the display, interpolated from its two last moves.
+/// When its last move is older than that, its position is predicted up to `max_prediction` ahead
+/// of it.
+///
+/// The last refresh is the one at the end of the events, one refresh period after another from
+/// the last [`WindowEvent::Presented`] of the window. The windows which weren't presented are
+/// resampled at the end of the events.
+///
+/// [`WindowEvent::Presented`]: crate::event::WindowEvent::Presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MotionResampling {
+    /// How long before the refresh the moves are resampled at, which lets them be interpolated
+    /// instead of predicted. Defaults to 5 milliseconds.
+    pub latency: Duration,
+    /// How far ahead of the last move the position may be predicted, which is never more than
+    /// half of the interval between the two last moves. Defaults to 8 milliseconds, and zero
+    /// turns the prediction off.
+    pub max_prediction: Duration,
+}
+
+impl Default for MotionResampling {
+    fn default() -> Self {
+        Self {
+            latency: Duration::from_millis(5),
+            max_prediction: Duration::from_millis(8),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Types useful for handling the gamepads.
+//!
+//! The gamepads are handled by the event loop once they're enabled with
+//! [`EventLoopWindowTarget::set_gamepads_enabled`], and their events are then delivered through
+//! [`Event::GamepadEvent`], along with the other events of the event loop.
+//!
+//! The buttons and the axes are mapped to the layout of the controllers of the consoles: the
+//! face buttons are named after their position, and the sticks go from `-1.0` to `1.0`, right and
+//! up being positive, while the triggers go from `0.0` to `1.0`.
+//!
+//! [`EventLoopWindowTarget::set_gamepads_enabled`]: crate::event_loop::EventLoopWindowTarget::set_gamepads_enabled
+//! [`Event::GamepadEvent`]: crate::event::Event::GamepadEvent
+
+use crate::event::ElementState;
+
+/// Identifies a gamepad, from its connection until its disconnection.
+///
+/// The identifiers are chosen by the event loop, and aren't reused, so a gamepad connected again
+/// gets a new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GamepadId(pub(crate) u32);
+
+impl GamepadId {
+    /// Returns a dummy `GamepadId`, useful for unit testing.
+    ///
+    /// # Safety
+    ///
+    /// The only guarantee made about the return value of this function is that
+    /// it will always be equal to itself and to future values returned by this function.
+    /// No other guarantees are made. This may be equal to a real `GamepadId`.
+    ///
+    /// **Passing this into a winit function will result in undefined behavior.**
+    pub const unsafe fn dummy() -> Self {
+        GamepadId(u32::MAX)
+    }
+}
+
+/// Describes an event from a gamepad.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GamepadEvent {
+    /// The gamepad was connected, or was already connected when the gamepads were enabled.
+    Connected {
+        /// The name of the gamepad, as the system reports it.
+        name: String,
+    },
+
+    /// The gamepad was disconnected. Its identifier isn't used afterwards.
+    Disconnected,
+
+    /// A button of the gamepad was pressed or released.
+    Button {
+        button: GamepadButton,
+        state: ElementState,
+    },
+
+    /// An axis of the gamepad moved.
+    ///
+    /// The sticks go from `-1.0` to `1.0`, right and up being positive, and the triggers go from
+    /// `0.0` to `1.0`.
+    Axis { axis: GamepadAxis, value: f64 },
+}
+
+/// A button of a gamepad.
+///
+/// The face buttons are named after their position: `South` is the A button of the Xbox
+/// controllers and the cross of the PlayStation ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    /// The left trigger, which is pressed past the threshold of the platform when the trigger is
+    /// analog.
+    LeftTrigger,
+    /// The right trigger, which is pressed past the threshold of the platform when the trigger is
+    /// analog.
+    RightTrigger,
+    /// The left one of the buttons in the middle, such as Back, Select, View or Share.
+    Select,
+    /// The right one of the buttons in the middle, such as Start, Menu or Options.
+    Start,
+    /// The button with the logo of the vendor, such as Guide, Home or PS.
+    Mode,
+    /// The press of the left stick.
+    LeftStick,
+    /// The press of the right stick.
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    /// Another button, with the code the platform gives it.
+    Other(u16),
+}
+
+/// An axis of a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+    /// Another axis, with the code the platform gives it.
+    Other(u16),
+}
@@ -0,0 +1,40 @@
+//! Types useful for registering the global hotkeys.
+//!
+//! A global hotkey is a key combination which is delivered to the application whichever
+//! application has the keyboard focus, such as the shortcut of a push-to-talk or of a screenshot
+//! tool. The hotkeys are registered with [`EventLoopWindowTarget::register_global_hotkey`], and
+//! their presses are delivered through [`Event::GlobalHotkey`].
+//!
+//! [`EventLoopWindowTarget::register_global_hotkey`]: crate::event_loop::EventLoopWindowTarget::register_global_hotkey
+//! [`Event::GlobalHotkey`]: crate::event::Event::GlobalHotkey
+
+use crate::event::{ModifiersState, VirtualKeyCode};
+
+/// A key combination, made of a key and of the modifiers which are held along with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Accelerator {
+    /// The modifiers which must be held, while the other ones must not.
+    pub modifiers: ModifiersState,
+    /// The key, as it's mapped with the keyboard layout used when registering the hotkey.
+    pub key: VirtualKeyCode,
+}
+
+impl Accelerator {
+    pub fn new(modifiers: ModifiersState, key: VirtualKeyCode) -> Accelerator {
+        Accelerator { modifiers, key }
+    }
+}
+
+/// Identifies a global hotkey registered with
+/// [`EventLoopWindowTarget::register_global_hotkey`], which is handed back through
+/// [`Event::GlobalHotkey`].
+///
+/// The identifiers are chosen by the event loop, and may be reused once their hotkey is
+/// unregistered.
+///
+/// [`EventLoopWindowTarget::register_global_hotkey`]: crate::event_loop::EventLoopWindowTarget::register_global_hotkey
+/// [`Event::GlobalHotkey`]: crate::event::Event::GlobalHotkey
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HotkeyId(pub(crate) usize);
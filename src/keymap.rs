@@ -0,0 +1,60 @@
+//! Types useful for introspecting the keyboard layouts and the key repeat.
+//!
+//! The layouts installed on the system are listed with
+//! [`EventLoopWindowTarget::keyboard_layouts`], and the active one is returned by
+//! [`EventLoopWindowTarget::current_keyboard_layout`]. The characters a key produces with the
+//! active layout are given by [`EventLoopWindowTarget::scancode_to_text`], to draw keyboard
+//! overlays or the bindings of remapping UIs, and by
+//! [`EventLoopWindowTarget::physical_key_to_logical`] for the bindings stored as physical keys.
+//! The key repeat settings of the system are returned by [`EventLoopWindowTarget::key_repeat`].
+//! The lock keys which are on are returned by [`EventLoopWindowTarget::lock_keys`], and turned on
+//! or off with [`EventLoopWindowTarget::set_lock_keys`].
+//!
+//! [`EventLoopWindowTarget::keyboard_layouts`]: crate::event_loop::EventLoopWindowTarget::keyboard_layouts
+//! [`EventLoopWindowTarget::current_keyboard_layout`]: crate::event_loop::EventLoopWindowTarget::current_keyboard_layout
+//! [`EventLoopWindowTarget::scancode_to_text`]: crate::event_loop::EventLoopWindowTarget::scancode_to_text
+//! [`EventLoopWindowTarget::physical_key_to_logical`]: crate::event_loop::EventLoopWindowTarget::physical_key_to_logical
+//! [`EventLoopWindowTarget::key_repeat`]: crate::event_loop::EventLoopWindowTarget::key_repeat
+//! [`EventLoopWindowTarget::lock_keys`]: crate::event_loop::EventLoopWindowTarget::lock_keys
+//! [`EventLoopWindowTarget::set_lock_keys`]: crate::event_loop::EventLoopWindowTarget::set_lock_keys
+
+use std::time::Duration;
+
+/// A keyboard layout installed on the system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyboardLayout {
+    /// The identifier of the layout, which stays the same across the sessions.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The input locale identifier, such as `04090409`.
+    /// - **macOS:** The identifier of the input source, such as `com.apple.keylayout.US`.
+    /// - **X11:** The name of the XKB layout, such as `English (US)`.
+    pub id: String,
+    /// The name of the layout, to show to the user.
+    pub name: String,
+}
+
+/// How the system repeats the keys which are held down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyRepeat {
+    /// The time a key is held down before it starts repeating.
+    pub delay: Duration,
+    /// The time between two repeats.
+    pub interval: Duration,
+}
+
+bitflags! {
+    /// A set of the lock keys, which stay on once pressed until they're pressed again.
+    #[derive(Default)]
+    pub struct LockKeys: u32 {
+        /// The "caps lock" key.
+        const CAPS_LOCK = 1 << 0;
+        /// The "num lock" key.
+        const NUM_LOCK = 1 << 1;
+        /// The "scroll lock" key.
+        const SCROLL_LOCK = 1 << 2;
+    }
+}
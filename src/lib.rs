@@ -37,7 +37,7 @@
 //! Winit no longer uses a `EventLoop::poll_events() -> impl Iterator<Event>`-based event loop
 //! model, since that can't be implemented properly on some platforms (e.g web, iOS) and works poorly on
 //! most other platforms. However, this model can be re-implemented to an extent with
-//! [`EventLoopExtRunReturn::run_return`]. See that method's documentation for more reasons about why
+//! [`EventLoopExtPumpEvents::pump_events`]. See that method's documentation for more reasons about why
 //! it's discouraged, beyond compatibility reasons.
 //!
 //!
@@ -98,8 +98,8 @@
 //! # Drawing on the window
 //!
 //! Winit doesn't directly provide any methods for drawing on a [`Window`]. However it allows you to
-//! retrieve the raw handle of the window (see the [`platform`] module and/or the
-//! [`raw_window_handle`] method), which in turn allows you to create an
+//! retrieve the handle of the window (see the [`platform`] module and/or the
+//! [`window_handle`] method), which in turn allows you to create an
 //! OpenGL/Vulkan/DirectX/Metal/etc. context that can be used to render graphics.
 //!
 //! Note that many platforms will display garbage data in the window's client area if the
@@ -109,7 +109,7 @@
 //! window visible only once you're ready to render into it.
 //!
 //! [`EventLoop`]: event_loop::EventLoop
-//! [`EventLoopExtRunReturn::run_return`]: ./platform/run_return/trait.EventLoopExtRunReturn.html#tymethod.run_return
+//! [`EventLoopExtPumpEvents::pump_events`]: ./platform/pump_events/trait.EventLoopExtPumpEvents.html#tymethod.pump_events
 //! [`EventLoop::new()`]: event_loop::EventLoop::new
 //! [event_loop_run]: event_loop::EventLoop::run
 //! [`ControlFlow`]: event_loop::ControlFlow
@@ -127,7 +127,7 @@
 //! [`UserEvent`]: event::Event::UserEvent
 //! [`LoopDestroyed`]: event::Event::LoopDestroyed
 //! [`platform`]: platform
-//! [`raw_window_handle`]: ./window/struct.Window.html#method.raw_window_handle
+//! [`window_handle`]: window::Window::window_handle
 
 #![deny(rust_2018_idioms)]
 #![deny(broken_intra_doc_links)]
@@ -147,14 +147,20 @@ extern crate bitflags;
 #[macro_use]
 extern crate objc;
 
+pub mod clipboard;
+pub mod dnd;
 pub mod dpi;
 #[macro_use]
 pub mod error;
 pub mod event;
 pub mod event_loop;
+pub mod gamepad;
+pub mod hotkey;
 mod icon;
+pub mod keymap;
 pub mod monitor;
 mod platform_impl;
+mod resampling;
 pub mod window;
 
 pub mod platform;
@@ -10,7 +10,7 @@
 //! [loop_get]: crate::event_loop::EventLoopWindowTarget::available_monitors
 //! [window_get]: crate::window::Window::available_monitors
 use crate::{
-    dpi::{PhysicalPosition, PhysicalSize},
+    dpi::{LogicalPosition, PhysicalPosition, PhysicalSize, Pixel},
     platform_impl,
 };
 
@@ -171,4 +171,442 @@ impl MonitorHandle {
     pub fn video_modes(&self) -> impl Iterator<Item = VideoMode> {
         self.inner.video_modes()
     }
+
+    /// Returns the color capabilities of the monitor, like whether it's currently outputting HDR.
+    ///
+    /// They're sent again through [`MonitorEvent::ColorChanged`] once they change.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Read from the DXGI outputs, which requires Windows 10. The headroom is
+    ///   unknown.
+    /// - **macOS:** Read from the EDR headroom of the screen, with `hdr` once it's above `1.0`.
+    ///   The luminance is unknown.
+    /// - **Wayland:** Requires the `wp_color_manager_v1` protocol, and is only known once the
+    ///   compositor described the output.
+    /// - **X11:** Read from the EDID of the output. `hdr` is always `false`, since the X server
+    ///   doesn't output HDR.
+    /// - **iOS / Android / Web:** Always returns the default capabilities.
+    #[inline]
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        self.inner.color_capabilities()
+    }
+
+    /// Returns whether the monitor supports a variable refresh rate, also known as adaptive sync,
+    /// so that the frames can be paced by the application instead of the refresh rate.
+    ///
+    /// See [`Window::is_vrr_active`](crate::window::Window::is_vrr_active) for whether a window
+    /// is presented with it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Whether DXGI allows presenting with tearing, which the variable refresh
+    ///   rate requires, so it's the same for all the monitors.
+    /// - **macOS:** Whether the refresh interval of the screen can vary, which requires macOS 12.
+    /// - **X11:** Read from the `vrr_capable` property of the output.
+    /// - **Wayland:** Requires KDE's `kde_output_device_v2` protocol.
+    /// - **iOS / Android / Web:** Always returns `false`.
+    #[inline]
+    pub fn supports_variable_refresh(&self) -> bool {
+        self.inner.supports_variable_refresh()
+    }
+
+    /// Returns the identity of the monitor, which stays the same across its connectors and the
+    /// reboots, unlike its name.
+    ///
+    /// Returns `None` if the monitor doesn't exist anymore or doesn't identify itself.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / X11:** Read from the EDID of the monitor.
+    /// - **macOS:** Read from the numbers of the display, so the model is unknown.
+    /// - **Wayland:** The manufacturer and the model are the ones given by the compositor, so the
+    ///   product code and the serial are unknown.
+    /// - **iOS / Android / Web:** Always returns `None`.
+    #[inline]
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        self.inner.identity()
+    }
+
+    /// Returns the work area of the monitor, which is the part of it left to the windows by the
+    /// taskbars, the docks and the panels, as its top-left corner and its size in the physical
+    /// pixels of the full screen area.
+    ///
+    /// The popups and the tool windows can be kept within it, so they aren't placed under a
+    /// taskbar.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The part of the `_NET_WORKAREA` of the current desktop on the monitor. It's a
+    ///   single area for all the monitors, so the panels of the other monitors may be left out
+    ///   of this one. Returns the whole monitor with the window managers not giving it.
+    /// - **macOS:** The visible frame of the screen, leaving out the menu bar and the dock.
+    /// - **Wayland / iOS / Android / Web:** Always returns the whole monitor.
+    #[inline]
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        self.inner.work_area()
+    }
+
+    /// Returns whether the position, in the physical pixels of the full screen area, is on this
+    /// monitor.
+    #[inline]
+    pub fn contains(&self, position: PhysicalPosition<i32>) -> bool {
+        let origin = self.position();
+        let size = self.size();
+        let (x, y) = (
+            i64::from(position.x) - i64::from(origin.x),
+            i64::from(position.y) - i64::from(origin.y),
+        );
+        x >= 0 && y >= 0 && x < i64::from(size.width) && y < i64::from(size.height)
+    }
+
+    /// Maps a position in the physical pixels of the full screen area to a logical position
+    /// relative to the top-left corner of this monitor, using its scale factor.
+    ///
+    /// The position doesn't have to be on this monitor, see [`MonitorHandle::contains`].
+    #[inline]
+    pub fn to_local(&self, position: PhysicalPosition<i32>) -> LogicalPosition<f64> {
+        to_local(position, self.position(), self.scale_factor())
+    }
+
+    /// Maps a logical position relative to the top-left corner of this monitor back to the
+    /// physical pixels of the full screen area.
+    ///
+    /// This is the inverse of [`MonitorHandle::to_local`].
+    #[inline]
+    pub fn to_global(&self, position: LogicalPosition<f64>) -> PhysicalPosition<i32> {
+        to_global(position, self.position(), self.scale_factor())
+    }
+
+    /// Converts a position on this monitor, in the physical pixels of the full screen area, to
+    /// the position at the same logical offset on the `target` monitor.
+    ///
+    /// This keeps, for instance, a window at the same place relative to the monitor once it's
+    /// moved to a monitor with another scale factor.
+    #[inline]
+    pub fn convert_position(
+        &self,
+        position: PhysicalPosition<i32>,
+        target: &MonitorHandle,
+    ) -> PhysicalPosition<i32> {
+        target.to_global(self.to_local(position))
+    }
+
+    /// Converts a size in the physical pixels of this monitor to the physical pixels of the
+    /// `target` monitor, so that it keeps the same logical size.
+    #[inline]
+    pub fn convert_size<P: Pixel>(
+        &self,
+        size: PhysicalSize<P>,
+        target: &MonitorHandle,
+    ) -> PhysicalSize<P> {
+        convert_size(size, self.scale_factor(), target.scale_factor())
+    }
+}
+
+/// Maps a position in the physical pixels of the full screen area to a logical position relative
+/// to the `origin` of a monitor.
+fn to_local(
+    position: PhysicalPosition<i32>,
+    origin: PhysicalPosition<i32>,
+    scale_factor: f64,
+) -> LogicalPosition<f64> {
+    PhysicalPosition::new(
+        f64::from(position.x) - f64::from(origin.x),
+        f64::from(position.y) - f64::from(origin.y),
+    )
+    .to_logical(scale_factor)
+}
+
+/// Maps a logical position relative to the `origin` of a monitor back to the physical pixels of
+/// the full screen area.
+fn to_global(
+    position: LogicalPosition<f64>,
+    origin: PhysicalPosition<i32>,
+    scale_factor: f64,
+) -> PhysicalPosition<i32> {
+    let offset = position.to_physical::<f64>(scale_factor);
+    PhysicalPosition::new(
+        f64::from(origin.x) + offset.x,
+        f64::from(origin.y) + offset.y,
+    )
+    .cast()
+}
+
+/// Converts a size in physical pixels between two scale factors, keeping its logical size.
+fn convert_size<P: Pixel>(
+    size: PhysicalSize<P>,
+    scale_factor: f64,
+    target_scale_factor: f64,
+) -> PhysicalSize<P> {
+    size.to_logical::<f64>(scale_factor)
+        .to_physical(target_scale_factor)
+}
+
+/// The identity of a monitor, so that applications can remember their layouts per monitor.
+///
+/// Can be acquired with [`MonitorHandle::identity`]. Two monitors of the same model only differ
+/// by their serial.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MonitorIdentity {
+    /// The manufacturer, as the 3 letters of its PNP ID, like `DEL`.
+    ///
+    /// On Wayland, it's the make given by the compositor instead.
+    pub manufacturer: String,
+    /// The name of the model, if known.
+    pub model: Option<String>,
+    /// The code of the model given by its manufacturer, if known.
+    pub product_code: Option<u16>,
+    /// The serial of the monitor, if known.
+    pub serial: Option<String>,
+}
+
+/// Describes a change of a monitor.
+///
+/// Sent through [`Event::MonitorEvent`](crate::event::Event::MonitorEvent).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MonitorEvent {
+    /// The color capabilities of the monitor changed, like once HDR was turned on or off.
+    ColorChanged(ColorCapabilities),
+}
+
+/// The color capabilities of a monitor, so that renderers can decide when to output HDR.
+///
+/// Can be acquired with [`MonitorHandle::color_capabilities`]. The default capabilities are the
+/// ones of an unknown SDR monitor.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ColorCapabilities {
+    /// Whether the monitor is currently outputting HDR, so that the content brighter than the SDR
+    /// white isn't clipped.
+    pub hdr: bool,
+    /// The bits per color component the monitor is driven with, if known.
+    pub bit_depth: Option<u8>,
+    /// The peak luminance of the monitor in nits, if known.
+    pub max_luminance: Option<f32>,
+    /// The ratio of the peak luminance to the luminance of the SDR white, if known.
+    pub headroom: Option<f32>,
+    /// The standard color primaries covered by the monitor, empty if unknown.
+    pub primaries: Vec<ColorPrimaries>,
+    /// The transfer functions the content can be output with, empty if unknown.
+    pub transfer_functions: Vec<TransferFunction>,
+}
+
+/// The standard color primaries, defining the gamut of a monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ColorPrimaries {
+    /// The primaries of sRGB and BT.709.
+    Srgb,
+    /// The primaries of Display P3, which are the ones of DCI-P3 with the white of sRGB.
+    DisplayP3,
+    /// The primaries of Adobe RGB.
+    AdobeRgb,
+    /// The primaries of BT.2020, which are the ones of the HDR content.
+    Bt2020,
+}
+
+/// The arithmetic of the gamuts, for the backends reading the primaries of the monitors.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    x11_platform,
+    wayland_platform,
+    test
+))]
+mod gamut {
+    use super::ColorPrimaries;
+
+    impl ColorPrimaries {
+        /// The red, green and blue primaries, and the white point, as CIE 1931 xy coordinates.
+        pub(crate) fn chromaticities(self) -> [(f32, f32); 4] {
+            match self {
+                ColorPrimaries::Srgb => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06), D65],
+                ColorPrimaries::DisplayP3 => [(0.68, 0.32), (0.265, 0.69), (0.15, 0.06), D65],
+                ColorPrimaries::AdobeRgb => [(0.64, 0.33), (0.21, 0.71), (0.15, 0.06), D65],
+                ColorPrimaries::Bt2020 => [(0.708, 0.292), (0.17, 0.797), (0.131, 0.046), D65],
+            }
+        }
+
+        /// Returns the standard primaries covered by the gamut of the given red, green and blue
+        /// primaries.
+        ///
+        /// The panels rarely cover a gamut entirely, so it's enough to cover 90% of the way from
+        /// the white to each of its primaries.
+        pub(crate) fn covered_by(
+            red: (f32, f32),
+            green: (f32, f32),
+            blue: (f32, f32),
+        ) -> Vec<Self> {
+            let sign = |p: (f32, f32), a: (f32, f32), b: (f32, f32)| {
+                (p.0 - b.0) * (a.1 - b.1) - (a.0 - b.0) * (p.1 - b.1)
+            };
+            let contains = |p: (f32, f32)| {
+                let d1 = sign(p, red, green);
+                let d2 = sign(p, green, blue);
+                let d3 = sign(p, blue, red);
+                let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+                let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+                !(negative && positive)
+            };
+
+            [
+                ColorPrimaries::Srgb,
+                ColorPrimaries::DisplayP3,
+                ColorPrimaries::AdobeRgb,
+                ColorPrimaries::Bt2020,
+            ]
+            .iter()
+            .copied()
+            .filter(|primaries| {
+                let [red, green, blue, white] = primaries.chromaticities();
+                [red, green, blue].iter().all(|primary| {
+                    let x = white.0 + (primary.0 - white.0) * 0.9;
+                    let y = white.1 + (primary.1 - white.1) * 0.9;
+                    contains((x, y))
+                })
+            })
+            .collect()
+        }
+    }
+
+    /// The white point of sRGB, BT.2020 and Display P3.
+    const D65: (f32, f32) = (0.3127, 0.329);
+}
+
+/// A transfer function the content can be output with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TransferFunction {
+    /// The transfer function of sRGB, which is the one of the SDR content.
+    Srgb,
+    /// The linear extended sRGB, like scRGB, where the values above `1.0` are brighter than the
+    /// SDR white.
+    ExtendedLinear,
+    /// The perceptual quantizer of SMPTE ST 2084, with absolute luminances up to 10000 nits.
+    Pq,
+    /// The hybrid log-gamma of ARIB STD-B67, with luminances relative to the peak.
+    Hlg,
+}
+
+/// The smallest rectangle containing all the monitors, as its position and size.
+///
+/// Returns `None` if there's no monitor.
+#[cfg(any(target_os = "windows", x11_platform))]
+pub(crate) fn union_rect<I: IntoIterator<Item = MonitorHandle>>(
+    monitors: I,
+) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    let mut rect: Option<(i64, i64, i64, i64)> = None;
+    for monitor in monitors {
+        let position = monitor.position();
+        let size = monitor.size();
+        let (left, top) = (i64::from(position.x), i64::from(position.y));
+        let (right, bottom) = (left + i64::from(size.width), top + i64::from(size.height));
+        rect = Some(match rect {
+            Some((l, t, r, b)) => (l.min(left), t.min(top), r.max(right), b.max(bottom)),
+            None => (left, top, right, bottom),
+        });
+    }
+    rect.map(|(left, top, right, bottom)| {
+        (
+            PhysicalPosition::new(left as i32, top as i32),
+            PhysicalSize::new((right - left) as u32, (bottom - top) as u32),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covered_by(primaries: ColorPrimaries) -> Vec<ColorPrimaries> {
+        let [red, green, blue, _] = primaries.chromaticities();
+        ColorPrimaries::covered_by(red, green, blue)
+    }
+
+    #[test]
+    fn primaries_cover_themselves() {
+        assert_eq!(covered_by(ColorPrimaries::Srgb), vec![ColorPrimaries::Srgb]);
+        assert_eq!(
+            covered_by(ColorPrimaries::DisplayP3),
+            vec![ColorPrimaries::Srgb, ColorPrimaries::DisplayP3],
+        );
+        assert_eq!(
+            covered_by(ColorPrimaries::Bt2020),
+            vec![
+                ColorPrimaries::Srgb,
+                ColorPrimaries::DisplayP3,
+                ColorPrimaries::AdobeRgb,
+                ColorPrimaries::Bt2020,
+            ],
+        );
+    }
+
+    #[test]
+    fn primaries_covered_by_most_of_the_gamut() {
+        // The primaries of the panel are a share of the way from the white to the ones of the
+        // gamut.
+        let shrunk = |primaries: ColorPrimaries, share: f32| {
+            let [red, green, blue, white] = primaries.chromaticities();
+            let shrink = |p: (f32, f32)| {
+                (
+                    white.0 + (p.0 - white.0) * share,
+                    white.1 + (p.1 - white.1) * share,
+                )
+            };
+            ColorPrimaries::covered_by(shrink(red), shrink(green), shrink(blue))
+        };
+        assert_eq!(
+            shrunk(ColorPrimaries::DisplayP3, 0.95),
+            vec![ColorPrimaries::Srgb, ColorPrimaries::DisplayP3],
+        );
+        assert_eq!(shrunk(ColorPrimaries::Srgb, 0.85), vec![]);
+    }
+
+    #[test]
+    fn positions_are_relative_to_the_monitor() {
+        let origin = PhysicalPosition::new(-1920, -200);
+        let position = PhysicalPosition::new(-1000, 100);
+        let local = to_local(position, origin, 2.0);
+        assert_eq!(local, LogicalPosition::new(460.0, 150.0));
+        assert_eq!(to_global(local, origin, 2.0), position);
+
+        // Left of and above the monitor.
+        let position = PhysicalPosition::new(-2020, -250);
+        let local = to_local(position, origin, 2.0);
+        assert_eq!(local, LogicalPosition::new(-50.0, -25.0));
+        assert_eq!(to_global(local, origin, 2.0), position);
+    }
+
+    #[test]
+    fn positions_are_converted_between_monitors() {
+        // A monitor at 2x on the left of one at 1x.
+        let (left, right) = (PhysicalPosition::new(-3840, 0), PhysicalPosition::new(0, 0));
+        let position = PhysicalPosition::new(-3640, 300);
+        let converted = to_global(to_local(position, left, 2.0), right, 1.0);
+        assert_eq!(converted, PhysicalPosition::new(100, 150));
+        // And back.
+        let converted = to_global(to_local(converted, right, 1.0), left, 2.0);
+        assert_eq!(converted, position);
+        // The fractional positions are rounded to the nearest pixel.
+        let converted = to_global(
+            to_local(PhysicalPosition::new(-3839, 1), left, 2.0),
+            right,
+            1.5,
+        );
+        assert_eq!(converted, PhysicalPosition::new(1, 1));
+    }
+
+    #[test]
+    fn sizes_keep_their_logical_size() {
+        let size = PhysicalSize::new(1600u32, 900);
+        assert_eq!(convert_size(size, 2.0, 1.0), PhysicalSize::new(800, 450));
+        assert_eq!(convert_size(size, 1.0, 1.5), PhysicalSize::new(2400, 1350));
+        assert_eq!(
+            convert_size(PhysicalSize::new(101u32, 33), 2.0, 1.0),
+            PhysicalSize::new(51, 17),
+        );
+    }
 }
@@ -0,0 +1,399 @@
+#![cfg(all(
+    feature = "headless",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )
+))]
+
+//! A backend without any display server, to test the window management logic of the
+//! applications in CI.
+//!
+//! The headless event loop is built with [`EventLoopExtHeadless::new_headless`], or by setting
+//! the `WINIT_UNIX_BACKEND` environment variable to `headless`. Its windows aren't shown
+//! anywhere, and there's no window manager: the windows only move, resize or get the focus when
+//! the application asks for it, and the input is synthesized with
+//! [`EventLoopWindowTargetExtHeadless`].
+//!
+//! The methods like [`inject_key`](EventLoopWindowTargetExtHeadless::inject_key) and
+//! [`inject_pointer_motion`](EventLoopWindowTargetExtHeadless::inject_pointer_motion) keep the
+//! state of the keyboard, the pointer and the touches, and send the events the real input would,
+//! such as the `ModifiersChanged` of the modifier keys or the `CursorEntered` of the windows the
+//! pointer moves onto. The toolkits can thus be tested end to end.
+
+use std::io;
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    error::NotSupportedError,
+    event::{
+        DeviceEvent, DeviceId, ElementState, MouseButton, MouseScrollDelta, ScanCode, TouchPhase,
+        VirtualKeyCode, WindowEvent,
+    },
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    monitor::{ColorCapabilities, MonitorIdentity},
+    platform_impl::{
+        EventLoop as LinuxEventLoop, EventLoopWindowTarget as LinuxEventLoopWindowTarget,
+        Window as LinuxWindow,
+    },
+    window::{Window, WindowId},
+};
+
+/// A monitor of the headless backend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualMonitor {
+    pub name: String,
+    pub size: PhysicalSize<u32>,
+    pub position: PhysicalPosition<i32>,
+    pub scale_factor: f64,
+    pub refresh_rate: u16,
+    pub color_capabilities: ColorCapabilities,
+    pub identity: Option<MonitorIdentity>,
+    pub variable_refresh: bool,
+    /// The part of the monitor left to the windows by the panels, the whole monitor if `None`.
+    pub work_area: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+}
+
+impl Default for VirtualMonitor {
+    fn default() -> Self {
+        VirtualMonitor {
+            name: "headless".into(),
+            size: PhysicalSize::new(1920, 1080),
+            position: PhysicalPosition::new(0, 0),
+            scale_factor: 1.0,
+            refresh_rate: 60,
+            color_capabilities: Default::default(),
+            identity: None,
+            variable_refresh: false,
+            work_area: None,
+        }
+    }
+}
+
+/// Additional methods on `EventLoop` to build a headless event loop.
+pub trait EventLoopExtHeadless {
+    /// Builds a new `EventLoop` that is forced to use the headless backend, with a single
+    /// default [`VirtualMonitor`].
+    ///
+    /// # Panics
+    ///
+    /// If called outside the main thread. To initialize a headless event loop outside
+    /// the main thread, use [`new_headless_any_thread`](#tymethod.new_headless_any_thread).
+    fn new_headless() -> Result<Self, io::Error>
+    where
+        Self: Sized;
+
+    /// Builds a new headless `EventLoop` on any thread.
+    ///
+    /// This method bypasses the cross-platform compatibility requirement
+    /// that `EventLoop` be created on the main thread.
+    fn new_headless_any_thread() -> Result<Self, io::Error>
+    where
+        Self: Sized;
+}
+
+impl<T> EventLoopExtHeadless for EventLoop<T> {
+    #[inline]
+    fn new_headless() -> Result<Self, io::Error> {
+        LinuxEventLoop::new_headless().map(wrap_ev)
+    }
+
+    #[inline]
+    fn new_headless_any_thread() -> Result<Self, io::Error> {
+        LinuxEventLoop::new_headless_any_thread().map(wrap_ev)
+    }
+}
+
+fn wrap_ev<T>(event_loop: LinuxEventLoop<T>) -> EventLoop<T> {
+    EventLoop {
+        event_loop,
+        proxy_channels: Default::default(),
+        _marker: std::marker::PhantomData,
+    }
+}
+
+/// Additional methods on `EventLoopWindowTarget` to drive the headless backend.
+///
+/// The methods return an error when the event loop doesn't use the headless backend.
+pub trait EventLoopWindowTargetExtHeadless {
+    /// True if the `EventLoopWindowTarget` uses the headless backend.
+    fn is_headless(&self) -> bool;
+
+    /// Replaces the monitors, the first one being the primary monitor.
+    ///
+    /// The windows keep their position and size. The monitors are told apart by their index, so
+    /// an [`Event::MonitorsChanged`](crate::event::Event::MonitorsChanged) is sent for the
+    /// monitors past the end of the previous list or of the new one, and a
+    /// [`MonitorEvent::ColorChanged`](crate::monitor::MonitorEvent::ColorChanged) is sent for
+    /// the ones whose color capabilities changed. A
+    /// [`WindowEvent::RefreshRateChanged`] is sent for the windows on a monitor with another
+    /// refresh rate than before.
+    fn set_virtual_monitors(&self, monitors: Vec<VirtualMonitor>) -> Result<(), NotSupportedError>;
+
+    /// The device sending the synthesized input.
+    fn headless_device_id(&self) -> Result<DeviceId, NotSupportedError>;
+
+    /// Sends the `event` to the window `window_id` in the next iteration of the event loop, as
+    /// if it came from the display server.
+    ///
+    /// The `Resized`, `Moved` and `Focused` events update the state of the window accordingly.
+    /// The repeated `KeyboardInput` presses are dropped if the window turned the key repeat off.
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent<'static>,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Sends the `event` in the next iteration of the event loop, from the device returned by
+    /// [`headless_device_id`](Self::headless_device_id).
+    fn inject_device_event(&self, event: DeviceEvent) -> Result<(), NotSupportedError>;
+
+    /// Presses or releases a key of the keyboard.
+    ///
+    /// The `KeyboardInput` goes to the window with the focus, preceded by a `ModifiersChanged`
+    /// when the key is a modifier key, and the `Key` device event is sent either way. Pressing a
+    /// held key repeats it, and releasing a key which isn't held does nothing. Pressing Caps Lock,
+    /// Num Lock or Scroll Lock toggles it in [`EventLoopWindowTarget::lock_keys`].
+    fn inject_key(
+        &self,
+        scancode: ScanCode,
+        virtual_keycode: Option<VirtualKeyCode>,
+        state: ElementState,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Sends the characters of `text` to the window with the focus, as `ReceivedCharacter`
+    /// events.
+    fn inject_text(&self, text: &str) -> Result<(), NotSupportedError>;
+
+    /// Moves the pointer to `position` on the window `window_id`.
+    ///
+    /// Moving the pointer onto another window sends it `CursorEntered` and the window it left
+    /// `CursorLeft`, while moving it on the same window sends the `MouseMotion` device event.
+    fn inject_pointer_motion(
+        &self,
+        window_id: WindowId,
+        position: PhysicalPosition<f64>,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Moves the pointer out of the windows, sending `CursorLeft` to the one it was on.
+    fn inject_pointer_leave(&self) -> Result<(), NotSupportedError>;
+
+    /// Presses or releases a button of the mouse, on the window under the pointer.
+    fn inject_mouse_button(
+        &self,
+        button: MouseButton,
+        state: ElementState,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Scrolls the wheel of the mouse, on the window under the pointer.
+    fn inject_mouse_wheel(&self, delta: MouseScrollDelta) -> Result<(), NotSupportedError>;
+
+    /// Starts, moves or ends the touch `id` at `location` on the window `window_id`.
+    ///
+    /// The touches stay on the window they started on, whichever `window_id` the later phases
+    /// give, and the phases of the touches which didn't start are dropped.
+    fn inject_touch(
+        &self,
+        window_id: WindowId,
+        id: u64,
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+    ) -> Result<(), NotSupportedError>;
+}
+
+impl<T> EventLoopWindowTargetExtHeadless for EventLoopWindowTarget<T> {
+    #[inline]
+    fn is_headless(&self) -> bool {
+        headless_target(&self.p).is_ok()
+    }
+
+    #[inline]
+    fn set_virtual_monitors(&self, monitors: Vec<VirtualMonitor>) -> Result<(), NotSupportedError> {
+        headless_target(&self.p)?.set_virtual_monitors(monitors);
+        Ok(())
+    }
+
+    #[inline]
+    fn headless_device_id(&self) -> Result<DeviceId, NotSupportedError> {
+        headless_target(&self.p)?;
+        Ok(DeviceId(crate::platform_impl::DeviceId::Headless(
+            crate::platform_impl::headless::DeviceId,
+        )))
+    }
+
+    #[inline]
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent<'static>,
+    ) -> Result<(), NotSupportedError> {
+        let target = headless_target(&self.p)?;
+        target.inject_window_event(headless_window_id(window_id)?, event);
+        Ok(())
+    }
+
+    #[inline]
+    fn inject_device_event(&self, event: DeviceEvent) -> Result<(), NotSupportedError> {
+        headless_target(&self.p)?.inject_device_event(event);
+        Ok(())
+    }
+
+    #[inline]
+    fn inject_key(
+        &self,
+        scancode: ScanCode,
+        virtual_keycode: Option<VirtualKeyCode>,
+        state: ElementState,
+    ) -> Result<(), NotSupportedError> {
+        headless_target(&self.p)?.inject_key(scancode, virtual_keycode, state);
+        Ok(())
+    }
+
+    #[inline]
+    fn inject_text(&self, text: &str) -> Result<(), NotSupportedError> {
+        headless_target(&self.p)?.inject_text(text);
+        Ok(())
+    }
+
+    #[inline]
+    fn inject_pointer_motion(
+        &self,
+        window_id: WindowId,
+        position: PhysicalPosition<f64>,
+    ) -> Result<(), NotSupportedError> {
+        let target = headless_target(&self.p)?;
+        target.inject_pointer_motion(headless_window_id(window_id)?, position);
+        Ok(())
+    }
+
+    #[inline]
+    fn inject_pointer_leave(&self) -> Result<(), NotSupportedError> {
+        headless_target(&self.p)?.inject_pointer_leave();
+        Ok(())
+    }
+
+    #[inline]
+    fn inject_mouse_button(
+        &self,
+        button: MouseButton,
+        state: ElementState,
+    ) -> Result<(), NotSupportedError> {
+        headless_target(&self.p)?.inject_mouse_button(button, state);
+        Ok(())
+    }
+
+    #[inline]
+    fn inject_mouse_wheel(&self, delta: MouseScrollDelta) -> Result<(), NotSupportedError> {
+        headless_target(&self.p)?.inject_mouse_wheel(delta);
+        Ok(())
+    }
+
+    #[inline]
+    fn inject_touch(
+        &self,
+        window_id: WindowId,
+        id: u64,
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+    ) -> Result<(), NotSupportedError> {
+        let target = headless_target(&self.p)?;
+        target.inject_touch(headless_window_id(window_id)?, id, phase, location);
+        Ok(())
+    }
+}
+
+fn headless_window_id(
+    window_id: WindowId,
+) -> Result<crate::platform_impl::headless::WindowId, NotSupportedError> {
+    match window_id.0 {
+        crate::platform_impl::WindowId::Headless(window_id) => Ok(window_id),
+        #[allow(unreachable_patterns)]
+        _ => Err(NotSupportedError::new()),
+    }
+}
+
+fn headless_target<T>(
+    target: &LinuxEventLoopWindowTarget<T>,
+) -> Result<&crate::platform_impl::headless::EventLoopWindowTarget<T>, NotSupportedError> {
+    match target {
+        LinuxEventLoopWindowTarget::Headless(target) => Ok(target),
+        #[cfg(any(feature = "x11", feature = "wayland"))]
+        _ => Err(NotSupportedError::new()),
+    }
+}
+
+/// Additional methods on `Window` to inspect the windows of the headless backend.
+///
+/// The methods return `None` when the window doesn't use the headless backend.
+pub trait WindowExtHeadless {
+    /// The title of the window.
+    fn headless_title(&self) -> Option<String>;
+
+    /// Whether the window is visible.
+    fn headless_is_visible(&self) -> Option<bool>;
+
+    /// Whether the window is minimized.
+    fn headless_is_minimized(&self) -> Option<bool>;
+
+    /// Whether the window is resizable.
+    fn headless_is_resizable(&self) -> Option<bool>;
+
+    /// Whether the window has decorations.
+    fn headless_is_decorated(&self) -> Option<bool>;
+
+    /// Whether the window is always on top.
+    fn headless_is_always_on_top(&self) -> Option<bool>;
+
+    /// Whether the window has the focus.
+    fn headless_is_focused(&self) -> Option<bool>;
+}
+
+macro_rules! headless_window {
+    ($window:expr, $w:ident => $x:expr) => {
+        match $window.window {
+            LinuxWindow::Headless(ref $w) => Some($x),
+            #[cfg(any(feature = "x11", feature = "wayland"))]
+            _ => None,
+        }
+    };
+}
+
+impl WindowExtHeadless for Window {
+    #[inline]
+    fn headless_title(&self) -> Option<String> {
+        headless_window!(self, w => w.title())
+    }
+
+    #[inline]
+    fn headless_is_visible(&self) -> Option<bool> {
+        headless_window!(self, w => w.is_visible())
+    }
+
+    #[inline]
+    fn headless_is_minimized(&self) -> Option<bool> {
+        headless_window!(self, w => w.is_minimized())
+    }
+
+    #[inline]
+    fn headless_is_resizable(&self) -> Option<bool> {
+        headless_window!(self, w => w.is_resizable())
+    }
+
+    #[inline]
+    fn headless_is_decorated(&self) -> Option<bool> {
+        headless_window!(self, w => w.is_decorated())
+    }
+
+    #[inline]
+    fn headless_is_always_on_top(&self) -> Option<bool> {
+        headless_window!(self, w => w.is_always_on_top())
+    }
+
+    #[inline]
+    fn headless_is_focused(&self) -> Option<bool> {
+        headless_window!(self, w => w.is_focused())
+    }
+}
@@ -1,10 +1,11 @@
 #![cfg(target_os = "macos")]
 
-use std::os::raw::c_void;
+use std::os::{raw::c_void, unix::io::RawFd};
 
 use crate::{
     dpi::LogicalSize,
-    event_loop::{EventLoop, EventLoopWindowTarget},
+    error::ExternalError,
+    event_loop::{EventLoop, EventLoopWindowTarget, SourceInterest, SourceToken},
     monitor::MonitorHandle,
     platform_impl::get_aux_state_mut,
     window::{Window, WindowBuilder},
@@ -184,7 +185,7 @@ pub trait EventLoopExtMacOS {
     /// `NSApplicationActivationPolicyRegular` by default.
     ///
     /// This function only takes effect if it's called before calling [`run`](crate::event_loop::EventLoop::run) or
-    /// [`run_return`](crate::platform::run_return::EventLoopExtRunReturn::run_return)
+    /// [`run_on_demand`](crate::platform::run_on_demand::EventLoopExtRunOnDemand::run_on_demand)
     fn set_activation_policy(&mut self, activation_policy: ActivationPolicy);
 
     /// Used to prevent a default menubar menu from getting created
@@ -193,8 +194,32 @@ pub trait EventLoopExtMacOS {
     ///
     /// This function only takes effect if it's called before calling
     /// [`run`](crate::event_loop::EventLoop::run) or
-    /// [`run_return`](crate::platform::run_return::EventLoopExtRunReturn::run_return)
+    /// [`run_on_demand`](crate::platform::run_on_demand::EventLoopExtRunOnDemand::run_on_demand)
     fn enable_default_menu_creation(&mut self, enable: bool);
+
+    /// Registers the file descriptor `fd` as an external event source, which is watched by the
+    /// main run loop.
+    ///
+    /// An [`Event::ExternalSource`] carrying the `token` is emitted on each iteration of the
+    /// event loop while `fd` is ready for any of the `interest`. The file descriptor stays
+    /// owned by the caller, and must stay open until the source is removed with
+    /// [`remove_source`](EventLoopExtMacOS::remove_source).
+    ///
+    /// Returns an error if the `token` is already used by another source, or if the `interest`
+    /// is empty.
+    ///
+    /// [`Event::ExternalSource`]: crate::event::Event::ExternalSource
+    fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError>;
+
+    /// Removes the external event source registered with the `token`.
+    ///
+    /// Returns an error if there's no source registered with the `token`.
+    fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError>;
 }
 impl<T> EventLoopExtMacOS for EventLoop<T> {
     #[inline]
@@ -210,6 +235,21 @@ impl<T> EventLoopExtMacOS for EventLoop<T> {
             get_aux_state_mut(&**self.event_loop.delegate).create_default_menu = enable;
         }
     }
+
+    #[inline]
+    fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError> {
+        self.event_loop.insert_source(fd, interest, token)
+    }
+
+    #[inline]
+    fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        self.event_loop.remove_source(token)
+    }
 }
 
 /// Additional methods on `MonitorHandle` that are specific to MacOS.
@@ -9,9 +9,13 @@
 //!  - `windows`
 //!  - `web`
 //!
-//! And the following platform-specific module:
+//! And the following platform-specific modules:
 //!
 //! - `run_return` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `pump_events` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `run_on_demand` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `run_async` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `headless` (available on `unix` with the `headless` feature)
 //!
 //! However only the module corresponding to the platform you're compiling to will be available.
 
@@ -21,5 +25,10 @@ pub mod macos;
 pub mod unix;
 pub mod windows;
 
+pub mod headless;
+
+pub mod pump_events;
+pub mod run_async;
+pub mod run_on_demand;
 pub mod run_return;
 pub mod web;
@@ -0,0 +1,81 @@
+#![cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+use std::time::Duration;
+
+use crate::{
+    event::Event,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+};
+
+/// The state of the event loop once [`EventLoopExtPumpEvents::pump_events`] returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PumpStatus {
+    /// The event loop is still running, and the events should keep being pumped.
+    Continue,
+    /// The `control_flow` was set to `ControlFlow::ExitWithCode`, and `LoopDestroyed` was
+    /// delivered. This holds the exit code.
+    ///
+    /// Pumping the events again starts the event loop over, beginning with
+    /// `NewEvents(StartCause::Init)`.
+    Exit(i32),
+}
+
+/// Additional methods on `EventLoop` to dispatch the events from a loop owned by the caller.
+pub trait EventLoopExtPumpEvents {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent;
+
+    /// Dispatches the pending events, waiting for them for up to `timeout`, and returns.
+    ///
+    /// This allows an application which runs its own loop to drive the `winit` event loop,
+    /// calling this function on each of its iterations. The first call sends
+    /// `NewEvents(StartCause::Init)`, every call goes through a whole iteration of the event
+    /// loop, from `NewEvents` to `RedrawEventsCleared`, and the wait is the shortest of the
+    /// `timeout` and the one requested through the `control_flow`, with a `timeout` of `None`
+    /// waiting as long as the `control_flow` requests. A `timeout` of zero never blocks.
+    ///
+    /// The `control_flow` is kept between the calls. Once it's set to `ControlFlow::ExitWithCode`,
+    /// `LoopDestroyed` is delivered, and [`PumpStatus::Exit`] is returned with the exit code.
+    ///
+    /// # Caveats
+    /// Just like with `run_on_demand`, the events are only dispatched from within this function.
+    /// Some OS operations, like resizing a window on Windows or macOS, run their own loop,
+    /// which keeps this function from returning until they're over.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** The application is only launched on the first call, and the `timeout` is
+    ///   checked between the events, so a late timer may delay the return.
+    fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        );
+}
+
+impl<T> EventLoopExtPumpEvents for EventLoop<T> {
+    type UserEvent = T;
+
+    fn pump_events<F>(&mut self, timeout: Option<Duration>, event_handler: F) -> PumpStatus
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        ),
+    {
+        let event_handler = self.proxy_channels.wrap_handler(event_handler);
+        self.event_loop.pump_events(timeout, event_handler)
+    }
+}
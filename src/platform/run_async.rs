@@ -0,0 +1,192 @@
+#![cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+use crate::{
+    event::Event,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    platform::run_on_demand::EventLoopExtRunOnDemand,
+    platform_impl,
+};
+
+/// Additional methods on `EventLoop` to drive a future with the events.
+pub trait EventLoopExtRunAsync {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent: 'static;
+
+    /// Runs the event loop until the future returned by `f` completes, handing the events over
+    /// to it through the [`EventStream`].
+    ///
+    /// The future is polled on the thread of the event loop, once it's woken up, or once an
+    /// event arrives while it waits for one. The event loop waits for as long as the future is
+    /// pending, and waking the future up from any thread wakes the event loop up too, so there's
+    /// no `ControlFlow` to choose. The future of an asynchronous runtime can be awaited as long
+    /// as the runtime itself runs on another thread.
+    ///
+    /// Just like with `run_on_demand`, the event loop can be run again afterwards, every run
+    /// starting with `NewEvents(StartCause::Init)`. If the event loop is destroyed before the
+    /// future completes, the future gets `LoopDestroyed` and is dropped.
+    ///
+    /// # Caveats
+    /// The events are only handed over while the event loop runs, see
+    /// [`EventLoopExtRunOnDemand::run_on_demand`] for the OS operations which block it.
+    fn run_async<F, Fut>(&mut self, f: F)
+    where
+        F: FnOnce(EventStream<Self::UserEvent>) -> Fut,
+        Fut: Future<Output = ()>;
+}
+
+impl<T> EventLoopExtRunAsync for EventLoop<T> {
+    type UserEvent = T;
+
+    fn run_async<F, Fut>(&mut self, f: F)
+    where
+        F: FnOnce(EventStream<Self::UserEvent>) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let shared = Rc::new(Shared {
+            events: RefCell::new(VecDeque::new()),
+            waiting: Cell::new(false),
+            window_target: Cell::new(None),
+        });
+        let loop_waker = Arc::new(LoopWaker {
+            // The future is polled for the first time by the first iteration.
+            woken: AtomicBool::new(true),
+            wakeup: self.event_loop.create_wakeup(),
+        });
+        let waker = Waker::from(loop_waker.clone());
+        let mut future = Box::pin(f(EventStream {
+            shared: shared.clone(),
+        }));
+        let mut done = false;
+
+        self.run_on_demand(|event, window_target, control_flow| {
+            if done {
+                return;
+            }
+            *control_flow = ControlFlow::Wait;
+
+            if let Some(event) = event.to_static() {
+                shared.events.borrow_mut().push_back(event);
+            }
+            let woken = loop_waker.woken.swap(false, Ordering::AcqRel);
+            if !woken && !shared.waiting.get() {
+                return;
+            }
+
+            shared.waiting.set(false);
+            shared.window_target.set(Some(window_target));
+            let poll = future.as_mut().poll(&mut Context::from_waker(&waker));
+            shared.window_target.set(None);
+
+            if poll.is_ready() {
+                done = true;
+                *control_flow = ControlFlow::Exit;
+            }
+        });
+    }
+}
+
+/// The events of the event loop, as handed over to the future run by
+/// [`EventLoopExtRunAsync::run_async`].
+pub struct EventStream<T: 'static> {
+    shared: Rc<Shared<T>>,
+}
+
+struct Shared<T: 'static> {
+    events: RefCell<VecDeque<Event<'static, T>>>,
+    /// Whether the future waits for the next event.
+    waiting: Cell<bool>,
+    /// The window target of the event loop, while the future is polled.
+    window_target: Cell<Option<*const EventLoopWindowTarget<T>>>,
+}
+
+impl<T> EventStream<T> {
+    /// Waits for the next event.
+    ///
+    /// `WindowEvent::ScaleFactorChanged` isn't handed over, since the new size of the window can
+    /// only be chosen while it's handled. The `WindowEvent::Resized` following it tells the new
+    /// size.
+    pub fn next_event(&mut self) -> NextEvent<'_, T> {
+        NextEvent { stream: self }
+    }
+
+    /// Takes the next event, if it has already arrived.
+    pub fn try_next_event(&mut self) -> Option<Event<'static, T>> {
+        self.shared.events.borrow_mut().pop_front()
+    }
+
+    /// Calls `f` with the window target of the event loop, to create the windows for example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it's called outside of the future run by `run_async`.
+    pub fn with_window_target<R>(&self, f: impl FnOnce(&EventLoopWindowTarget<T>) -> R) -> R {
+        let window_target = self
+            .shared
+            .window_target
+            .get()
+            .expect("the window target is only available to the future run by `run_async`");
+        // The window target outlives the polling of the future.
+        f(unsafe { &*window_target })
+    }
+}
+
+/// The future returned by [`EventStream::next_event`].
+pub struct NextEvent<'a, T: 'static> {
+    stream: &'a mut EventStream<T>,
+}
+
+impl<'a, T> Future for NextEvent<'a, T> {
+    type Output = Event<'static, T>;
+
+    fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+        let shared = &self.stream.shared;
+        match shared.events.borrow_mut().pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => {
+                // The future is polled again once the next event arrives.
+                shared.waiting.set(true);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Wakes the event loop up when the future is woken up.
+struct LoopWaker {
+    woken: AtomicBool,
+    wakeup: platform_impl::EventLoopWakeup,
+}
+
+impl Wake for LoopWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.woken.swap(true, Ordering::AcqRel) {
+            self.wakeup.wake_up();
+        }
+    }
+}
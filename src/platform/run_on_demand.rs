@@ -0,0 +1,64 @@
+#![cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "android",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+use crate::{
+    event::Event,
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+};
+
+/// Additional methods on `EventLoop` to run it several times.
+pub trait EventLoopExtRunOnDemand {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent;
+
+    /// Runs the `winit` event loop until `control_flow` is set to `ControlFlow::ExitWithCode`,
+    /// and returns the exit code.
+    ///
+    /// Unlike `run`, this function accepts non-`'static` (i.e. non-`move`) closures, and the
+    /// event loop can be run again afterwards, for as many times as needed. Every run starts
+    /// with `NewEvents(StartCause::Init)` and ends with `LoopDestroyed`, while the connection to
+    /// the display and the windows are kept between the runs. The `control_flow` is reset to
+    /// `Poll` once a new run starts.
+    ///
+    /// # Caveats
+    /// The events are only dispatched while the event loop runs, so the windows kept between the
+    /// runs don't respond to the user in the meantime. This function will not return on Windows
+    /// or macOS while a window is getting resized, and other OS operations may result in such
+    /// freezes, see [`EventLoopExtPumpEvents::pump_events`] for the limits of returning the
+    /// control to the caller.
+    ///
+    /// You are strongly encouraged to use `run`, unless the use of this is absolutely necessary.
+    ///
+    /// [`EventLoopExtPumpEvents::pump_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_events
+    fn run_on_demand<F>(&mut self, event_handler: F) -> i32
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        );
+}
+
+impl<T> EventLoopExtRunOnDemand for EventLoop<T> {
+    type UserEvent = T;
+
+    fn run_on_demand<F>(&mut self, event_handler: F) -> i32
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        ),
+    {
+        let event_handler = self.proxy_channels.wrap_handler(event_handler);
+        self.event_loop.run_return(event_handler)
+    }
+}
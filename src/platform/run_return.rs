@@ -15,6 +15,7 @@ use crate::{
 };
 
 /// Additional methods on `EventLoop` to return control flow to the caller.
+#[deprecated = "use `EventLoopExtRunOnDemand::run_on_demand` instead"]
 pub trait EventLoopExtRunReturn {
     /// A type provided by the user that can be passed through `Event::UserEvent`.
     type UserEvent;
@@ -22,7 +23,8 @@ pub trait EventLoopExtRunReturn {
     /// Initializes the `winit` event loop.
     ///
     /// Unlike `run`, this function accepts non-`'static` (i.e. non-`move`) closures and returns
-    /// control flow to the caller when `control_flow` is set to `ControlFlow::Exit`.
+    /// control flow to the caller when `control_flow` is set to `ControlFlow::ExitWithCode`,
+    /// along with the exit code.
     ///
     /// # Caveats
     /// Despite its appearance at first glance, this is *not* a perfect replacement for
@@ -33,7 +35,11 @@ pub trait EventLoopExtRunReturn {
     /// underlying OS APIs, which cannot be hidden by `winit` without severe stability repercussions.
     ///
     /// You are strongly encouraged to use `run`, unless the use of this is absolutely necessary.
-    fn run_return<F>(&mut self, event_handler: F)
+    ///
+    /// This is the same as [`EventLoopExtRunOnDemand::run_on_demand`], which replaces it.
+    ///
+    /// [`EventLoopExtRunOnDemand::run_on_demand`]: crate::platform::run_on_demand::EventLoopExtRunOnDemand::run_on_demand
+    fn run_return<F>(&mut self, event_handler: F) -> i32
     where
         F: FnMut(
             Event<'_, Self::UserEvent>,
@@ -42,10 +48,11 @@ pub trait EventLoopExtRunReturn {
         );
 }
 
+#[allow(deprecated)]
 impl<T> EventLoopExtRunReturn for EventLoop<T> {
     type UserEvent = T;
 
-    fn run_return<F>(&mut self, event_handler: F)
+    fn run_return<F>(&mut self, event_handler: F) -> i32
     where
         F: FnMut(
             Event<'_, Self::UserEvent>,
@@ -53,6 +60,7 @@ impl<T> EventLoopExtRunReturn for EventLoop<T> {
             &mut ControlFlow,
         ),
     {
+        let event_handler = self.proxy_channels.wrap_handler(event_handler);
         self.event_loop.run_return(event_handler)
     }
 }
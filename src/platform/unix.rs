@@ -6,12 +6,15 @@
     target_os = "openbsd"
 ))]
 
+#[cfg(any(feature = "x11", feature = "wayland"))]
 use std::os::raw;
+use std::os::unix::io::RawFd;
 #[cfg(feature = "x11")]
 use std::{ptr, sync::Arc};
 
 use crate::{
-    event_loop::{EventLoop, EventLoopWindowTarget},
+    error::ExternalError,
+    event_loop::{EventLoop, EventLoopWindowTarget, SourceInterest, SourceToken},
     monitor::MonitorHandle,
     window::{Window, WindowBuilder},
 };
@@ -20,9 +23,10 @@ use crate::{
 use crate::dpi::Size;
 #[cfg(feature = "x11")]
 use crate::platform_impl::x11::{ffi::XVisualInfo, XConnection};
+use crate::platform_impl::EventLoop as LinuxEventLoop;
+#[cfg(any(feature = "x11", feature = "wayland"))]
 use crate::platform_impl::{
-    EventLoop as LinuxEventLoop, EventLoopWindowTarget as LinuxEventLoopWindowTarget,
-    Window as LinuxWindow,
+    EventLoopWindowTarget as LinuxEventLoopWindowTarget, Window as LinuxWindow,
 };
 
 // TODO: stupid hack so that glutin can do its work
@@ -30,7 +34,10 @@ use crate::platform_impl::{
 #[cfg(feature = "x11")]
 pub use crate::platform_impl::x11;
 #[cfg(feature = "x11")]
-pub use crate::platform_impl::{x11::util::WindowType as XWindowType, XNotSupported};
+pub use crate::platform_impl::{
+    x11::util::{VisualPreference as XVisualPreference, WindowType as XWindowType},
+    XNotSupported,
+};
 
 /// Additional methods on `EventLoopWindowTarget` that are specific to Unix.
 pub trait EventLoopWindowTargetExtUnix {
@@ -66,7 +73,7 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     #[inline]
     #[cfg(feature = "x11")]
     fn is_x11(&self) -> bool {
-        !self.p.is_wayland()
+        self.p.is_x11()
     }
 
     #[inline]
@@ -75,7 +82,7 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>> {
         match self.p {
             LinuxEventLoopWindowTarget::X(ref e) => Some(e.x_connection().clone()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => None,
         }
     }
@@ -87,7 +94,7 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
             LinuxEventLoopWindowTarget::Wayland(ref p) => {
                 Some(p.display().get_display_ptr() as *mut _)
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => None,
         }
     }
@@ -142,11 +149,35 @@ pub trait EventLoopExtUnix {
     fn new_wayland_any_thread() -> Self
     where
         Self: Sized;
+
+    /// Registers the file descriptor `fd` as an external event source.
+    ///
+    /// An [`Event::ExternalSource`] carrying the `token` is emitted on each iteration of the
+    /// event loop while `fd` is ready for any of the `interest`. The file descriptor stays
+    /// owned by the caller, and must stay open until the source is removed with
+    /// [`remove_source`](#tymethod.remove_source).
+    ///
+    /// Returns an error if the `token` is already used by another source, or if the `interest`
+    /// is empty.
+    ///
+    /// [`Event::ExternalSource`]: crate::event::Event::ExternalSource
+    fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError>;
+
+    /// Removes the external event source registered with the `token`.
+    ///
+    /// Returns an error if there's no source registered with the `token`.
+    fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError>;
 }
 
 fn wrap_ev<T>(event_loop: LinuxEventLoop<T>) -> EventLoop<T> {
     EventLoop {
         event_loop,
+        proxy_channels: Default::default(),
         _marker: std::marker::PhantomData,
     }
 }
@@ -188,6 +219,21 @@ impl<T> EventLoopExtUnix for EventLoop<T> {
                 .expect("failed to open Wayland connection"),
         )
     }
+
+    #[inline]
+    fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError> {
+        self.event_loop.insert_source(fd, interest, token)
+    }
+
+    #[inline]
+    fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        self.event_loop.remove_source(token)
+    }
 }
 
 /// Additional methods on `Window` that are specific to Unix.
@@ -257,7 +303,7 @@ impl WindowExtUnix for Window {
     fn xlib_window(&self) -> Option<raw::c_ulong> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_window()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => None,
         }
     }
@@ -267,7 +313,7 @@ impl WindowExtUnix for Window {
     fn xlib_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_display()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => None,
         }
     }
@@ -277,7 +323,7 @@ impl WindowExtUnix for Window {
     fn xlib_screen_id(&self) -> Option<raw::c_int> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_screen_id()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => None,
         }
     }
@@ -288,7 +334,7 @@ impl WindowExtUnix for Window {
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_xconnection()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => None,
         }
     }
@@ -298,7 +344,7 @@ impl WindowExtUnix for Window {
     fn xcb_connection(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xcb_connection()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => None,
         }
     }
@@ -308,7 +354,7 @@ impl WindowExtUnix for Window {
     fn wayland_surface(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.surface().as_ref().c_ptr() as *mut _),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => None,
         }
     }
@@ -318,7 +364,7 @@ impl WindowExtUnix for Window {
     fn wayland_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.display().get_display_ptr() as *mut _),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => None,
         }
     }
@@ -328,7 +374,7 @@ impl WindowExtUnix for Window {
     fn set_wayland_theme<T: Theme>(&self, theme: T) {
         match self.window {
             LinuxWindow::Wayland(ref w) => w.set_theme(theme),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => {}
         }
     }
@@ -343,6 +389,15 @@ impl WindowExtUnix for Window {
 pub trait WindowBuilderExtUnix {
     #[cfg(feature = "x11")]
     fn with_x11_visual<T>(self, visual_infos: *const T) -> Self;
+    /// Build window with the visual of the preference, such as the 32-bit ARGB visual for
+    /// transparency, a 30-bit visual for deep color or the visual of an EGL or GLX
+    /// configuration. Defaults to `XVisualPreference::Default`, the visual given with
+    /// `with_x11_visual` taking precedence. Only relevant on X11.
+    ///
+    /// Building the window fails if there's no visual of the id on the screen, while the other
+    /// preferences fall back to the default visual.
+    #[cfg(feature = "x11")]
+    fn with_x11_visual_preference(self, preference: XVisualPreference) -> Self;
     #[cfg(feature = "x11")]
     fn with_x11_screen(self, screen_id: i32) -> Self;
 
@@ -385,6 +440,13 @@ impl WindowBuilderExtUnix for WindowBuilder {
         self
     }
 
+    #[inline]
+    #[cfg(feature = "x11")]
+    fn with_x11_visual_preference(mut self, preference: XVisualPreference) -> Self {
+        self.platform_specific.visual_preference = preference;
+        self
+    }
+
     #[inline]
     #[cfg(feature = "x11")]
     fn with_x11_screen(mut self, screen_id: i32) -> Self {
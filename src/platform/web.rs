@@ -5,9 +5,11 @@
 //! to retrieve the canvas from the Window. Alternatively, use the `WindowBuilderExtWebSys` trait
 //! to provide your own canvas.
 
+use crate::dnd::FileHandle;
 use crate::window::WindowBuilder;
 
-use web_sys::HtmlCanvasElement;
+use wasm_bindgen::JsValue;
+use web_sys::{File, HtmlCanvasElement};
 
 pub trait WindowExtWebSys {
     fn canvas(&self) -> HtmlCanvasElement;
@@ -20,6 +22,29 @@ pub trait WindowBuilderExtWebSys {
     fn with_canvas(self, canvas: Option<HtmlCanvasElement>) -> Self;
 }
 
+/// Additional methods on [`FileHandle`] to read the content of the dropped files.
+pub trait FileHandleExtWebSys {
+    /// The underlying `File` object.
+    fn file(&self) -> File;
+
+    /// Reads the whole content of the file, calling `on_read` once it's read.
+    ///
+    /// `on_read` is called right away if the content was read ahead, see
+    /// [`FileHandle::content`].
+    fn read<F>(&self, on_read: F)
+    where
+        F: FnOnce(Result<Vec<u8>, JsValue>) + 'static;
+
+    /// Reads the content of the file in chunks of up to `chunk_size` bytes, so large files don't
+    /// have to be held in memory at once.
+    ///
+    /// `on_chunk` is called with each of the chunks in order, then with `Ok(None)` once the end
+    /// of the file is reached. Nothing is read after an error.
+    fn read_chunks<F>(&self, chunk_size: usize, on_chunk: F)
+    where
+        F: FnMut(Result<Option<Vec<u8>>, JsValue>) + 'static;
+}
+
 impl WindowBuilderExtWebSys for WindowBuilder {
     fn with_canvas(mut self, canvas: Option<HtmlCanvasElement>) -> Self {
         self.platform_specific.canvas = canvas;
@@ -6,11 +6,13 @@ use std::path::Path;
 use libc;
 use winapi::shared::minwindef::WORD;
 use winapi::shared::windef::{HMENU, HWND};
+use winapi::um::winnt::HANDLE;
 
 use crate::{
     dpi::PhysicalSize,
+    error::ExternalError,
     event::DeviceId,
-    event_loop::EventLoop,
+    event_loop::{EventLoop, SourceToken},
     monitor::MonitorHandle,
     platform_impl::{EventLoop as WindowsEventLoop, Parent, WinIcon},
     window::{BadIcon, Icon, Theme, Window, WindowBuilder},
@@ -41,6 +43,25 @@ pub trait EventLoopExtWindows {
     fn new_dpi_unaware_any_thread() -> Self
     where
         Self: Sized;
+
+    /// Registers the waitable `handle` as an external event source.
+    ///
+    /// An [`Event::ExternalSource`] carrying the `token` is emitted each time `handle` is
+    /// signaled, and for as long as it stays signaled. The handle is waited for the same way as
+    /// with `WaitForSingleObject`, so an auto-reset event is reset once it's reported. The
+    /// handle stays owned by the caller, and must stay open until the source is removed with
+    /// [`remove_source`](EventLoopExtWindows::remove_source).
+    ///
+    /// Returns an error if the `token` is already used by another source, or if the `handle`
+    /// can't be waited for.
+    ///
+    /// [`Event::ExternalSource`]: crate::event::Event::ExternalSource
+    fn insert_source(&mut self, handle: HANDLE, token: SourceToken) -> Result<(), ExternalError>;
+
+    /// Removes the external event source registered with the `token`.
+    ///
+    /// Returns an error if there's no source registered with the `token`.
+    fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError>;
 }
 
 impl<T> EventLoopExtWindows for EventLoop<T> {
@@ -48,6 +69,7 @@ impl<T> EventLoopExtWindows for EventLoop<T> {
     fn new_any_thread() -> Self {
         EventLoop {
             event_loop: WindowsEventLoop::new_any_thread(),
+            proxy_channels: Default::default(),
             _marker: ::std::marker::PhantomData,
         }
     }
@@ -56,6 +78,7 @@ impl<T> EventLoopExtWindows for EventLoop<T> {
     fn new_dpi_unaware() -> Self {
         EventLoop {
             event_loop: WindowsEventLoop::new_dpi_unaware(),
+            proxy_channels: Default::default(),
             _marker: ::std::marker::PhantomData,
         }
     }
@@ -64,9 +87,20 @@ impl<T> EventLoopExtWindows for EventLoop<T> {
     fn new_dpi_unaware_any_thread() -> Self {
         EventLoop {
             event_loop: WindowsEventLoop::new_dpi_unaware_any_thread(),
+            proxy_channels: Default::default(),
             _marker: ::std::marker::PhantomData,
         }
     }
+
+    #[inline]
+    fn insert_source(&mut self, handle: HANDLE, token: SourceToken) -> Result<(), ExternalError> {
+        self.event_loop.insert_source(handle, token)
+    }
+
+    #[inline]
+    fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        self.event_loop.remove_source(token)
+    }
 }
 
 /// Additional methods on `Window` that are specific to Windows.
@@ -1,10 +1,18 @@
 #![cfg(target_os = "android")]
 
 use crate::{
+    clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker},
+    dnd::{DndActions, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error, event,
-    event_loop::{self, ControlFlow},
-    monitor, window,
+    event_loop::{self, ControlFlow, Timer, TimerId},
+    gamepad::GamepadId,
+    hotkey::{Accelerator, HotkeyId},
+    icon::{BadIcon, RgbaIcon},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
+    monitor,
+    platform::pump_events::PumpStatus,
+    window,
 };
 use ndk::{
     configuration::Configuration,
@@ -18,6 +26,9 @@ use std::{
     time::{Duration, Instant},
 };
 
+/// The flag of the motion events cancelling their pointers, which is only in the NDK of Android 13.
+const AMOTION_EVENT_FLAG_CANCELED: i32 = 0x20;
+
 lazy_static! {
     static ref CONFIG: RwLock<Configuration> = RwLock::new(Configuration::new());
     // If this is `Some()` a `Poll::Wake` is considered an `EventSource::Internal` with the event
@@ -66,14 +77,18 @@ pub struct EventLoop<T: 'static> {
     start_cause: event::StartCause,
     looper: ThreadLooper,
     running: bool,
+    /// The control flow set by the callback, which is kept between the calls to `pump_events`.
+    control_flow: ControlFlow,
+    /// Whether `NewEvents(Init)` was sent, and `LoopDestroyed` is yet to be.
+    loop_running: bool,
 }
 
 macro_rules! call_event_handler {
     ( $event_handler:expr, $window_target:expr, $cf:expr, $event:expr ) => {{
-        if $cf != ControlFlow::Exit {
-            $event_handler($event, $window_target, &mut $cf);
+        if let ControlFlow::ExitWithCode(code) = $cf {
+            $event_handler($event, $window_target, &mut ControlFlow::ExitWithCode(code));
         } else {
-            $event_handler($event, $window_target, &mut ControlFlow::Exit);
+            $event_handler($event, $window_target, &mut $cf);
         }
     }};
 }
@@ -92,299 +107,351 @@ impl<T: 'static> EventLoop<T> {
             start_cause: event::StartCause::Init,
             looper: ThreadLooper::for_thread().unwrap(),
             running: false,
+            control_flow: ControlFlow::default(),
+            loop_running: false,
         }
     }
 
+    pub fn new_secondary() -> Result<Self, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn run<F>(mut self, event_handler: F) -> !
     where
         F: 'static
             + FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
     {
-        self.run_return(event_handler);
-        ::std::process::exit(0);
+        let exit_code = self.run_return(event_handler);
+        ::std::process::exit(exit_code);
     }
 
-    pub fn run_return<F>(&mut self, mut event_handler: F)
+    pub fn run_return<F>(&mut self, mut event_handler: F) -> i32
     where
         F: FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
     {
-        let mut control_flow = ControlFlow::default();
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut event_handler) {
+                return code;
+            }
+        }
+    }
 
-        'event_loop: loop {
-            call_event_handler!(
-                event_handler,
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut event_handler: F) -> PumpStatus
+    where
+        F: FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
+            self.control_flow = ControlFlow::default();
+            self.start_cause = event::StartCause::Init;
+            self.single_iteration(&mut event_handler);
+        }
+
+        // The first iteration may have already asked to exit.
+        if !matches!(self.control_flow, ControlFlow::ExitWithCode(_)) {
+            self.poll_events_with_timeout(timeout);
+            self.single_iteration(&mut event_handler);
+        }
+
+        if let ControlFlow::ExitWithCode(code) = self.control_flow {
+            // The pending event is handled once the loop runs again.
+            self.first_event = poll(
+                self.looper
+                    .poll_once_timeout(Duration::from_millis(0))
+                    .unwrap(),
+            );
+            self.loop_running = false;
+            event_handler(
+                event::Event::LoopDestroyed,
                 self.window_target(),
-                control_flow,
-                event::Event::NewEvents(self.start_cause)
+                &mut ControlFlow::ExitWithCode(code),
             );
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
 
-            let mut redraw = false;
-            let mut resized = false;
+    /// Waits for an event for as long as both the control flow and the timeout allow.
+    fn poll_events_with_timeout(&mut self, timeout: Option<Duration>) {
+        let start = Instant::now();
+        let control_flow_timeout = match self.control_flow {
+            ControlFlow::Poll => Some(Duration::from_millis(0)),
+            ControlFlow::Wait => None,
+            ControlFlow::WaitUntil(instant) => Some(instant.saturating_duration_since(start)),
+            ControlFlow::ExitWithCode(_) => unreachable!(),
+        };
+        let timeout = match (control_flow_timeout, timeout) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
 
-            match self.first_event.take() {
-                Some(EventSource::Callback) => match ndk_glue::poll_events().unwrap() {
-                    Event::WindowCreated => {
-                        call_event_handler!(
-                            event_handler,
-                            self.window_target(),
-                            control_flow,
-                            event::Event::Resumed
-                        );
-                    }
-                    Event::WindowResized => resized = true,
-                    Event::WindowRedrawNeeded => redraw = true,
-                    Event::WindowDestroyed => {
+        self.first_event = match timeout {
+            Some(timeout) => poll(self.looper.poll_all_timeout(timeout).unwrap()),
+            None => poll(self.looper.poll_all().unwrap()),
+        };
+        self.start_cause = match self.control_flow {
+            ControlFlow::Poll => event::StartCause::Poll,
+            ControlFlow::WaitUntil(instant) if Instant::now() >= instant => {
+                event::StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: instant,
+                }
+            }
+            ControlFlow::WaitUntil(instant) => event::StartCause::WaitCancelled {
+                start,
+                requested_resume: Some(instant),
+            },
+            _ => event::StartCause::WaitCancelled {
+                start,
+                requested_resume: None,
+            },
+        };
+    }
+
+    fn single_iteration<F>(&mut self, event_handler: &mut F)
+    where
+        F: FnMut(event::Event<'_, T>, &event_loop::EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let mut control_flow = self.control_flow;
+
+        call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::NewEvents(self.start_cause)
+        );
+
+        let mut redraw = false;
+        let mut resized = false;
+
+        match self.first_event.take() {
+            Some(EventSource::Callback) => match ndk_glue::poll_events().unwrap() {
+                Event::WindowCreated => {
+                    call_event_handler!(
+                        event_handler,
+                        self.window_target(),
+                        control_flow,
+                        event::Event::Resumed
+                    );
+                }
+                Event::WindowResized => resized = true,
+                Event::WindowRedrawNeeded => redraw = true,
+                Event::WindowDestroyed => {
+                    call_event_handler!(
+                        event_handler,
+                        self.window_target(),
+                        control_flow,
+                        event::Event::Suspended
+                    );
+                }
+                Event::Pause => self.running = false,
+                Event::Resume => self.running = true,
+                Event::ConfigChanged => {
+                    let am = ndk_glue::native_activity().asset_manager();
+                    let config = Configuration::from_asset_manager(&am);
+                    let old_scale_factor = MonitorHandle.scale_factor();
+                    *CONFIG.write().unwrap() = config;
+                    let scale_factor = MonitorHandle.scale_factor();
+                    if (scale_factor - old_scale_factor).abs() < f64::EPSILON {
+                        let mut size = MonitorHandle.size();
+                        let event = event::Event::WindowEvent {
+                            window_id: window::WindowId(WindowId),
+                            event: event::WindowEvent::ScaleFactorChanged {
+                                new_inner_size: &mut size,
+                                scale_factor,
+                            },
+                        };
                         call_event_handler!(
                             event_handler,
                             self.window_target(),
                             control_flow,
-                            event::Event::Suspended
+                            event
                         );
                     }
-                    Event::Pause => self.running = false,
-                    Event::Resume => self.running = true,
-                    Event::ConfigChanged => {
-                        let am = ndk_glue::native_activity().asset_manager();
-                        let config = Configuration::from_asset_manager(&am);
-                        let old_scale_factor = MonitorHandle.scale_factor();
-                        *CONFIG.write().unwrap() = config;
-                        let scale_factor = MonitorHandle.scale_factor();
-                        if (scale_factor - old_scale_factor).abs() < f64::EPSILON {
-                            let mut size = MonitorHandle.size();
-                            let event = event::Event::WindowEvent {
-                                window_id: window::WindowId(WindowId),
-                                event: event::WindowEvent::ScaleFactorChanged {
-                                    new_inner_size: &mut size,
-                                    scale_factor,
-                                },
-                            };
-                            call_event_handler!(
-                                event_handler,
-                                self.window_target(),
-                                control_flow,
-                                event
-                            );
+                }
+                Event::WindowHasFocus => {
+                    call_event_handler!(
+                        event_handler,
+                        self.window_target(),
+                        control_flow,
+                        event::Event::WindowEvent {
+                            window_id: window::WindowId(WindowId),
+                            event: event::WindowEvent::Focused(true),
                         }
-                    }
-                    Event::WindowHasFocus => {
-                        call_event_handler!(
-                            event_handler,
-                            self.window_target(),
-                            control_flow,
-                            event::Event::WindowEvent {
-                                window_id: window::WindowId(WindowId),
-                                event: event::WindowEvent::Focused(true),
-                            }
-                        );
-                    }
-                    Event::WindowLostFocus => {
-                        call_event_handler!(
-                            event_handler,
-                            self.window_target(),
-                            control_flow,
-                            event::Event::WindowEvent {
-                                window_id: window::WindowId(WindowId),
-                                event: event::WindowEvent::Focused(false),
-                            }
-                        );
-                    }
-                    _ => {}
-                },
-                Some(EventSource::InputQueue) => {
-                    if let Some(input_queue) = ndk_glue::input_queue().as_ref() {
-                        while let Some(event) = input_queue.get_event() {
-                            if let Some(event) = input_queue.pre_dispatch(event) {
-                                let mut handled = true;
-                                let window_id = window::WindowId(WindowId);
-                                let device_id = event::DeviceId(DeviceId);
-                                match &event {
-                                    InputEvent::MotionEvent(motion_event) => {
-                                        let phase = match motion_event.action() {
-                                            MotionAction::Down | MotionAction::PointerDown => {
-                                                Some(event::TouchPhase::Started)
-                                            }
-                                            MotionAction::Up | MotionAction::PointerUp => {
-                                                Some(event::TouchPhase::Ended)
-                                            }
-                                            MotionAction::Move => Some(event::TouchPhase::Moved),
-                                            MotionAction::Cancel => {
-                                                Some(event::TouchPhase::Cancelled)
-                                            }
-                                            _ => {
-                                                handled = false;
-                                                None // TODO mouse events
-                                            }
+                    );
+                }
+                Event::WindowLostFocus => {
+                    call_event_handler!(
+                        event_handler,
+                        self.window_target(),
+                        control_flow,
+                        event::Event::WindowEvent {
+                            window_id: window::WindowId(WindowId),
+                            event: event::WindowEvent::Focused(false),
+                        }
+                    );
+                }
+                _ => {}
+            },
+            Some(EventSource::InputQueue) => {
+                if let Some(input_queue) = ndk_glue::input_queue().as_ref() {
+                    while let Some(event) = input_queue.get_event() {
+                        if let Some(event) = input_queue.pre_dispatch(event) {
+                            let mut handled = true;
+                            let window_id = window::WindowId(WindowId);
+                            let device_id = event::DeviceId(DeviceId);
+                            match &event {
+                                InputEvent::MotionEvent(motion_event) => {
+                                    // Since Android 13, the pointers rejected as palms come with
+                                    // the flag, on their `PointerUp` or on the `Cancel` of all
+                                    // the pointers.
+                                    let flags = unsafe {
+                                        ndk_sys::AMotionEvent_getFlags(motion_event.ptr().as_ptr())
+                                    };
+                                    let palm = flags & AMOTION_EVENT_FLAG_CANCELED != 0;
+                                    let action = motion_event.action();
+                                    let phase = match action {
+                                        MotionAction::Down | MotionAction::PointerDown => {
+                                            Some(event::TouchPhase::Started)
+                                        }
+                                        MotionAction::Up | MotionAction::PointerUp if palm => {
+                                            Some(event::TouchPhase::Cancelled)
+                                        }
+                                        MotionAction::Up | MotionAction::PointerUp => {
+                                            Some(event::TouchPhase::Ended)
+                                        }
+                                        MotionAction::Move => Some(event::TouchPhase::Moved),
+                                        MotionAction::Cancel => Some(event::TouchPhase::Cancelled),
+                                        _ => {
+                                            handled = false;
+                                            None // TODO mouse events
+                                        }
+                                    };
+                                    if let Some(phase) = phase {
+                                        let pointers: Box<
+                                            dyn Iterator<Item = ndk::event::Pointer<'_>>,
+                                        > = match action {
+                                            MotionAction::Down
+                                            | MotionAction::PointerDown
+                                            | MotionAction::Up
+                                            | MotionAction::PointerUp => Box::new(std::iter::once(
+                                                motion_event
+                                                    .pointer_at_index(motion_event.pointer_index()),
+                                            )),
+                                            _ => Box::new(motion_event.pointers()),
                                         };
-                                        if let Some(phase) = phase {
-                                            let pointers: Box<
-                                                dyn Iterator<Item = ndk::event::Pointer<'_>>,
-                                            > = match phase {
-                                                event::TouchPhase::Started
-                                                | event::TouchPhase::Ended => Box::new(
-                                                    std::iter::once(motion_event.pointer_at_index(
-                                                        motion_event.pointer_index(),
-                                                    )),
-                                                ),
-                                                event::TouchPhase::Moved
-                                                | event::TouchPhase::Cancelled => {
-                                                    Box::new(motion_event.pointers())
-                                                }
-                                            };
 
-                                            for pointer in pointers {
-                                                let location = PhysicalPosition {
-                                                    x: pointer.x() as _,
-                                                    y: pointer.y() as _,
-                                                };
-                                                let event = event::Event::WindowEvent {
-                                                    window_id,
-                                                    event: event::WindowEvent::Touch(
-                                                        event::Touch {
-                                                            device_id,
-                                                            phase,
-                                                            location,
-                                                            id: pointer.pointer_id() as u64,
-                                                            force: None,
-                                                        },
-                                                    ),
-                                                };
-                                                call_event_handler!(
-                                                    event_handler,
-                                                    self.window_target(),
-                                                    control_flow,
-                                                    event
-                                                );
-                                            }
+                                        let time = Instant::now();
+                                        for pointer in pointers {
+                                            let location = PhysicalPosition {
+                                                x: pointer.x() as _,
+                                                y: pointer.y() as _,
+                                            };
+                                            let event = event::Event::WindowEvent {
+                                                window_id,
+                                                event: event::WindowEvent::Touch(event::Touch {
+                                                    device_id,
+                                                    phase,
+                                                    location,
+                                                    time,
+                                                    id: pointer.pointer_id() as u64,
+                                                    force: None,
+                                                    palm: palm
+                                                        && phase == event::TouchPhase::Cancelled,
+                                                }),
+                                            };
+                                            call_event_handler!(
+                                                event_handler,
+                                                self.window_target(),
+                                                control_flow,
+                                                event
+                                            );
                                         }
                                     }
-                                    InputEvent::KeyEvent(key) => {
-                                        let state = match key.action() {
-                                            KeyAction::Down => event::ElementState::Pressed,
-                                            KeyAction::Up => event::ElementState::Released,
-                                            _ => event::ElementState::Released,
-                                        };
-                                        #[allow(deprecated)]
-                                        let event = event::Event::WindowEvent {
-                                            window_id,
-                                            event: event::WindowEvent::KeyboardInput {
-                                                device_id,
-                                                input: event::KeyboardInput {
-                                                    scancode: key.scan_code() as u32,
-                                                    state,
-                                                    virtual_keycode: None,
-                                                    modifiers: event::ModifiersState::default(),
-                                                },
-                                                is_synthetic: false,
+                                }
+                                InputEvent::KeyEvent(key) => {
+                                    let state = match key.action() {
+                                        KeyAction::Down => event::ElementState::Pressed,
+                                        KeyAction::Up => event::ElementState::Released,
+                                        _ => event::ElementState::Released,
+                                    };
+                                    #[allow(deprecated)]
+                                    let event = event::Event::WindowEvent {
+                                        window_id,
+                                        event: event::WindowEvent::KeyboardInput {
+                                            device_id,
+                                            input: event::KeyboardInput {
+                                                scancode: key.scan_code() as u32,
+                                                state,
+                                                repeat: key.repeat_count() > 0,
+                                                virtual_keycode: None,
+                                                modifiers: event::ModifiersState::default(),
                                             },
-                                        };
-                                        call_event_handler!(
-                                            event_handler,
-                                            self.window_target(),
-                                            control_flow,
-                                            event
-                                        );
-                                    }
-                                };
-                                input_queue.finish_event(event, handled);
-                            }
+                                            is_synthetic: false,
+                                        },
+                                    };
+                                    call_event_handler!(
+                                        event_handler,
+                                        self.window_target(),
+                                        control_flow,
+                                        event
+                                    );
+                                }
+                            };
+                            input_queue.finish_event(event, handled);
                         }
                     }
                 }
-                Some(EventSource::User) => {
-                    let mut user_queue = self.user_queue.lock().unwrap();
-                    while let Some(event) = user_queue.pop_front() {
-                        call_event_handler!(
-                            event_handler,
-                            self.window_target(),
-                            control_flow,
-                            event::Event::UserEvent(event)
-                        );
-                    }
+            }
+            Some(EventSource::User) => {
+                let mut user_queue = self.user_queue.lock().unwrap();
+                while let Some(event) = user_queue.pop_front() {
+                    call_event_handler!(
+                        event_handler,
+                        self.window_target(),
+                        control_flow,
+                        event::Event::UserEvent(event)
+                    );
                 }
-                Some(EventSource::Internal(internal)) => match internal {
-                    InternalEvent::RedrawRequested => redraw = true,
-                },
-                None => {}
             }
+            Some(EventSource::Internal(internal)) => match internal {
+                InternalEvent::RedrawRequested => redraw = true,
+            },
+            None => {}
+        }
 
-            call_event_handler!(
-                event_handler,
-                self.window_target(),
-                control_flow,
-                event::Event::MainEventsCleared
-            );
-
-            if resized && self.running {
-                let size = MonitorHandle.size();
-                let event = event::Event::WindowEvent {
-                    window_id: window::WindowId(WindowId),
-                    event: event::WindowEvent::Resized(size),
-                };
-                call_event_handler!(event_handler, self.window_target(), control_flow, event);
-            }
+        call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::MainEventsCleared
+        );
+
+        if resized && self.running {
+            let size = MonitorHandle.size();
+            let event = event::Event::WindowEvent {
+                window_id: window::WindowId(WindowId),
+                event: event::WindowEvent::Resized(size),
+            };
+            call_event_handler!(event_handler, self.window_target(), control_flow, event);
+        }
 
-            if redraw && self.running {
-                let event = event::Event::RedrawRequested(window::WindowId(WindowId));
-                call_event_handler!(event_handler, self.window_target(), control_flow, event);
-            }
+        if redraw && self.running {
+            let event = event::Event::RedrawRequested(window::WindowId(WindowId));
+            call_event_handler!(event_handler, self.window_target(), control_flow, event);
+        }
 
-            call_event_handler!(
-                event_handler,
-                self.window_target(),
-                control_flow,
-                event::Event::RedrawEventsCleared
-            );
+        call_event_handler!(
+            event_handler,
+            self.window_target(),
+            control_flow,
+            event::Event::RedrawEventsCleared
+        );
 
-            match control_flow {
-                ControlFlow::Exit => {
-                    self.first_event = poll(
-                        self.looper
-                            .poll_once_timeout(Duration::from_millis(0))
-                            .unwrap(),
-                    );
-                    self.start_cause = event::StartCause::WaitCancelled {
-                        start: Instant::now(),
-                        requested_resume: None,
-                    };
-                    break 'event_loop;
-                }
-                ControlFlow::Poll => {
-                    self.first_event = poll(
-                        self.looper
-                            .poll_all_timeout(Duration::from_millis(0))
-                            .unwrap(),
-                    );
-                    self.start_cause = event::StartCause::Poll;
-                }
-                ControlFlow::Wait => {
-                    self.first_event = poll(self.looper.poll_all().unwrap());
-                    self.start_cause = event::StartCause::WaitCancelled {
-                        start: Instant::now(),
-                        requested_resume: None,
-                    }
-                }
-                ControlFlow::WaitUntil(instant) => {
-                    let start = Instant::now();
-                    let duration = if instant <= start {
-                        Duration::default()
-                    } else {
-                        instant - start
-                    };
-                    self.first_event = poll(self.looper.poll_all_timeout(duration).unwrap());
-                    self.start_cause = if self.first_event.is_some() {
-                        event::StartCause::WaitCancelled {
-                            start,
-                            requested_resume: Some(instant),
-                        }
-                    } else {
-                        event::StartCause::ResumeTimeReached {
-                            start,
-                            requested_resume: instant,
-                        }
-                    }
-                }
-            }
-        }
+        self.control_flow = control_flow;
     }
 
     pub fn window_target(&self) -> &event_loop::EventLoopWindowTarget<T> {
@@ -397,6 +464,12 @@ impl<T: 'static> EventLoop<T> {
             looper: ForeignLooper::for_thread().expect("called from event loop thread"),
         }
     }
+
+    pub fn create_wakeup(&self) -> EventLoopWakeup {
+        EventLoopWakeup {
+            looper: ForeignLooper::for_thread().expect("called from event loop thread"),
+        }
+    }
 }
 
 pub struct EventLoopProxy<T: 'static> {
@@ -421,6 +494,16 @@ impl<T> Clone for EventLoopProxy<T> {
     }
 }
 
+pub struct EventLoopWakeup {
+    looper: ForeignLooper,
+}
+
+impl EventLoopWakeup {
+    pub fn wake_up(&self) {
+        self.looper.wake();
+    }
+}
+
 pub struct EventLoopWindowTarget<T: 'static> {
     _marker: std::marker::PhantomData<T>,
 }
@@ -437,6 +520,147 @@ impl<T: 'static> EventLoopWindowTarget<T> {
         v.push_back(MonitorHandle);
         v
     }
+
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn create_custom_cursor(
+        &self,
+        _image: RgbaIcon,
+        _hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor)
+    }
+
+    pub fn create_animated_cursor(
+        &self,
+        _frames: Vec<window::CursorFrame>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor)
+    }
+
+    pub fn set_timer(&self, _id: TimerId, _timer: Timer) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn cancel_timer(&self, _id: TimerId) -> bool {
+        false
+    }
+
+    pub fn set_precise_wait(&self, _precise: bool) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_gamepads_enabled(&self, _enabled: bool) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn rumble_gamepad(
+        &self,
+        _gamepad_id: GamepadId,
+        _strong: f64,
+        _weak: f64,
+        _duration: Duration,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn perform_haptic_feedback(
+        &self,
+        _pattern: event_loop::HapticPattern,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn input_devices(&self) -> Result<Vec<event::InputDevice>, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_hid_reports_enabled(
+        &self,
+        _device: DeviceId,
+        _enabled: bool,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn keyboard_layouts(&self) -> Result<Vec<KeyboardLayout>, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn current_keyboard_layout(&self) -> Result<KeyboardLayout, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn scancode_to_text(
+        &self,
+        _scancode: event::ScanCode,
+        _modifiers: event::ModifiersState,
+    ) -> Result<Option<String>, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn key_repeat(&self) -> Result<Option<KeyRepeat>, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn lock_keys(&self) -> Result<LockKeys, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_lock_keys(
+        &self,
+        _lock_keys: LockKeys,
+        _on: bool,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn register_global_hotkey(
+        &self,
+        _accelerator: Accelerator,
+    ) -> Result<HotkeyId, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn unregister_global_hotkey(&self, _id: HotkeyId) -> bool {
+        false
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -460,6 +684,34 @@ impl DeviceId {
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct PlatformSpecificWindowBuilderAttributes;
 
+/// A custom cursor, which is never shown as there's no cursor on Android.
+#[derive(Clone)]
+pub struct CustomCursor;
+
+/// A child surface, which is never created as there's a single native window on Android.
+pub struct ChildSurface;
+
+impl ChildSurface {
+    pub fn new<T: 'static>(
+        _el: &EventLoopWindowTarget<T>,
+        _parent: &Window,
+        _position: Position,
+        _size: Size,
+    ) -> Result<Self, error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_position(&self, _position: Position) {}
+
+    pub fn set_size(&self, _size: Size) {}
+
+    pub fn window_handle(&self) -> Result<raw_window_handle::RawWindowHandle, error::HandleError> {
+        Err(error::HandleError::Unavailable)
+    }
+}
+
 pub struct Window;
 
 impl Window {
@@ -482,6 +734,10 @@ impl Window {
         })
     }
 
+    pub fn is_vrr_active(&self) -> bool {
+        false
+    }
+
     pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
         let mut v = VecDeque::with_capacity(1);
         v.push_back(MonitorHandle);
@@ -503,6 +759,21 @@ impl Window {
         ForeignLooper::for_thread().unwrap().wake();
     }
 
+    pub fn set_vsync_redraws(&self, _vsync_redraws: bool) {}
+
+    pub fn pre_present_notify(&self) {}
+
+    #[cfg(feature = "software-present")]
+    pub fn present_pixels(
+        &self,
+        _pixels: &[u32],
+        _size: PhysicalSize<u32>,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, error::NotSupportedError> {
         Err(error::NotSupportedError::new())
     }
@@ -527,10 +798,16 @@ impl Window {
         MonitorHandle.size()
     }
 
+    pub fn recommended_bounds(&self) -> Option<PhysicalSize<u32>> {
+        None
+    }
+
     pub fn set_min_inner_size(&self, _: Option<Size>) {}
 
     pub fn set_max_inner_size(&self, _: Option<Size>) {}
 
+    pub fn set_aspect_ratio(&self, _: Option<window::Ratio>) {}
+
     pub fn set_title(&self, _title: &str) {}
 
     pub fn set_visible(&self, _visibility: bool) {}
@@ -555,18 +832,87 @@ impl Window {
 
     pub fn set_decorations(&self, _decorations: bool) {}
 
+    pub fn set_shadow(&self, _shadow: bool) {}
+
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_backdrop(&self, _backdrop: window::Backdrop) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn set_always_on_top(&self, _always_on_top: bool) {}
 
     pub fn set_window_icon(&self, _window_icon: Option<crate::icon::Icon>) {}
 
-    pub fn set_ime_position(&self, _position: Position) {}
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
+    pub fn set_ime_purpose(&self, _purpose: window::ImePurpose) {}
+
+    pub fn set_key_repeat(&self, _key_repeat: bool) {}
+
+    pub fn set_system_shortcuts_inhibited(
+        &self,
+        _inhibited: bool,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
 
     pub fn focus_window(&self) {}
 
     pub fn request_user_attention(&self, _request_type: Option<window::UserAttentionType>) {}
 
+    pub fn tab_group(&self) -> Option<String> {
+        None
+    }
+
+    pub fn set_tab_group(&self, _group: &str) {}
+
+    pub fn merge_into_tab_group(&self, _window: &Window) {}
+
+    pub fn select_next_tab(&self) {}
+
+    pub fn workspace(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn move_to_workspace(&self, _workspace: u32) {}
+
+    pub fn set_visible_on_all_workspaces(&self, _visible: bool) {}
+
+    pub fn set_modal(&self, _modal: bool) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_progress(&self, _state: window::ProgressState) {}
+
+    pub fn set_badge(&self, _count: Option<u64>) {}
+
     pub fn set_cursor_icon(&self, _: window::CursorIcon) {}
 
+    pub fn set_cursor(&self, _: CustomCursor) {}
+
+    pub fn set_cursor_theme(&self, _: &str, _: u32) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn cursor_theme(&self) -> Option<window::CursorTheme> {
+        None
+    }
+
     pub fn set_cursor_position(&self, _: Position) -> Result<(), error::ExternalError> {
         Err(error::ExternalError::NotSupported(
             error::NotSupportedError::new(),
@@ -579,23 +925,87 @@ impl Window {
         ))
     }
 
+    pub fn set_cursor_grab_area(
+        &self,
+        _: Option<(Position, Size)>,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_pointer_lock(&self, _: bool) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn capture_pointer(&self) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn release_pointer(&self) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn set_cursor_visible(&self, _: bool) {}
 
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn read_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _picker: MimePicker,
+        _consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn start_drag(
+        &self,
+        _data: DragData,
+        _actions: DndActions,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn drag_window(&self) -> Result<(), error::ExternalError> {
         Err(error::ExternalError::NotSupported(
             error::NotSupportedError::new(),
         ))
     }
 
-    pub fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
-        let a_native_window = if let Some(native_window) = ndk_glue::native_window().as_ref() {
-            unsafe { native_window.ptr().as_mut() as *mut _ as *mut _ }
-        } else {
-            panic!("Cannot get the native window, it's null and will always be null before Event::Resumed and after Event::Suspended. Make sure you only call this function between those events.");
+    pub fn window_handle(&self) -> Result<raw_window_handle::RawWindowHandle, error::HandleError> {
+        // The native window is only there between `Event::Resumed` and `Event::Suspended`.
+        let a_native_window = match ndk_glue::native_window().as_ref() {
+            Some(native_window) => unsafe { native_window.ptr().as_mut() as *mut _ as *mut _ },
+            None => return Err(error::HandleError::Unavailable),
         };
         let mut handle = raw_window_handle::android::AndroidHandle::empty();
         handle.a_native_window = a_native_window;
-        raw_window_handle::RawWindowHandle::Android(handle)
+        Ok(raw_window_handle::RawWindowHandle::Android(handle))
+    }
+
+    pub fn display_handle(&self) -> Result<raw_window_handle::RawWindowHandle, error::HandleError> {
+        Ok(raw_window_handle::RawWindowHandle::Android(
+            raw_window_handle::android::AndroidHandle::empty(),
+        ))
     }
 
     pub fn config(&self) -> Configuration {
@@ -619,6 +1029,14 @@ impl Display for OsError {
 
 pub(crate) use crate::icon::NoIcon as PlatformIcon;
 
+pub fn scancode_to_key(_scancode: event::ScanCode) -> Option<event::VirtualKeyCode> {
+    None
+}
+
+pub fn key_to_scancode(_key: event::VirtualKeyCode) -> Option<event::ScanCode> {
+    None
+}
+
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct MonitorHandle;
 
@@ -649,6 +1067,22 @@ impl MonitorHandle {
             .unwrap_or(1.0)
     }
 
+    pub fn color_capabilities(&self) -> monitor::ColorCapabilities {
+        monitor::ColorCapabilities::default()
+    }
+
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        (self.position(), self.size())
+    }
+
+    pub fn identity(&self) -> Option<monitor::MonitorIdentity> {
+        None
+    }
+
+    pub fn supports_variable_refresh(&self) -> bool {
+        false
+    }
+
     pub fn video_modes(&self) -> impl Iterator<Item = monitor::VideoMode> {
         let size = self.size().into();
         let mut v = Vec::new();
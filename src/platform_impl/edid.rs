@@ -0,0 +1,209 @@
+//! The parsing of the EDID of the monitors, which identifies them and describes their
+//! capabilities.
+// macOS only decodes the manufacturers of the displays.
+#![cfg_attr(target_os = "macos", allow(dead_code))]
+
+use crate::monitor::{ColorCapabilities, ColorPrimaries, MonitorIdentity, TransferFunction};
+
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+const BLOCK_LEN: usize = 128;
+
+/// The offsets of the 18 bytes descriptors of the base block.
+const DESCRIPTORS: [usize; 4] = [54, 72, 90, 108];
+const DESCRIPTOR_LEN: usize = 18;
+/// The tag of the display descriptor with the serial.
+const SERIAL_DESCRIPTOR: u8 = 0xff;
+/// The tag of the display descriptor with the name of the model.
+const NAME_DESCRIPTOR: u8 = 0xfc;
+
+/// The tag of the CTA-861 extension blocks.
+const CTA_EXTENSION: u8 = 0x02;
+/// The tag of the data blocks with an extended tag.
+const EXTENDED_TAG: u8 = 7;
+/// The extended tag of the HDR static metadata data block.
+const HDR_STATIC_METADATA: u8 = 6;
+
+// The electro-optical transfer functions of the HDR static metadata.
+const EOTF_ST2084: u8 = 1 << 2;
+const EOTF_HLG: u8 = 1 << 3;
+
+/// The identity and the capabilities of a monitor read from its EDID.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Edid {
+    /// The PNP ID of the manufacturer.
+    manufacturer: String,
+    product_code: u16,
+    /// The serial number, which is zero when unused.
+    serial_number: u32,
+    /// The serial of the serial descriptor, taking over the serial number.
+    serial: Option<String>,
+    /// The name of the model of the name descriptor.
+    name: Option<String>,
+    /// The bits per color component of the digital inputs, from EDID 1.4 on.
+    bit_depth: Option<u8>,
+    /// The red, green and blue primaries, as CIE 1931 xy coordinates.
+    chromaticities: Option<[(f32, f32); 3]>,
+    /// The electro-optical transfer functions of the HDR static metadata.
+    eotfs: u8,
+    /// The desired content max luminance of the HDR static metadata, in nits.
+    max_luminance: Option<f32>,
+}
+
+impl Edid {
+    /// Parses the base block of the EDID and its CTA-861 extensions.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < BLOCK_LEN || data[..HEADER.len()] != HEADER {
+            return None;
+        }
+
+        let mut edid = Edid {
+            manufacturer: manufacturer(u16::from_be_bytes([data[8], data[9]])),
+            product_code: u16::from_le_bytes([data[10], data[11]]),
+            serial_number: u32::from_le_bytes([data[12], data[13], data[14], data[15]]),
+            ..Edid::default()
+        };
+
+        for &offset in DESCRIPTORS.iter() {
+            let descriptor = &data[offset..offset + DESCRIPTOR_LEN];
+            // The display descriptors are the ones without a pixel clock.
+            if descriptor[..3] != [0, 0, 0] {
+                continue;
+            }
+            match descriptor[3] {
+                SERIAL_DESCRIPTOR => edid.serial = descriptor_text(descriptor),
+                NAME_DESCRIPTOR => edid.name = descriptor_text(descriptor),
+                _ => (),
+            }
+        }
+
+        // The depth is only given for the digital inputs of EDID 1.4.
+        let (version, revision) = (data[18], data[19]);
+        if data[20] & 0x80 != 0 && (version, revision) >= (1, 4) {
+            edid.bit_depth = match (data[20] >> 4) & 0x07 {
+                depth @ 1..=6 => Some(4 + 2 * depth),
+                _ => None,
+            };
+        }
+
+        // The 10 bits coordinates are split between their 2 low bits and their 8 high bits.
+        let coordinate = |high: u8, low: u8, shift: u8| {
+            let value = (u16::from(high) << 2) | u16::from((low >> shift) & 0x03);
+            f32::from(value) / 1024.0
+        };
+        let (red_green, blue_white) = (data[25], data[26]);
+        let red = (
+            coordinate(data[27], red_green, 6),
+            coordinate(data[28], red_green, 4),
+        );
+        let green = (
+            coordinate(data[29], red_green, 2),
+            coordinate(data[30], red_green, 0),
+        );
+        let blue = (
+            coordinate(data[31], blue_white, 6),
+            coordinate(data[32], blue_white, 4),
+        );
+        if [red, green, blue].iter().all(|&(x, y)| x > 0.0 && y > 0.0) {
+            edid.chromaticities = Some([red, green, blue]);
+        }
+
+        for extension in data[BLOCK_LEN..].chunks_exact(BLOCK_LEN) {
+            if extension[0] == CTA_EXTENSION {
+                edid.parse_cta_extension(extension);
+            }
+        }
+
+        Some(edid)
+    }
+
+    /// Parses the data blocks of a CTA-861 extension, which are followed by its detailed timings.
+    fn parse_cta_extension(&mut self, extension: &[u8]) {
+        let end = (extension[2] as usize).min(BLOCK_LEN - 1);
+        let mut offset = 4;
+        while offset < end {
+            let header = extension[offset];
+            let len = (header & 0x1f) as usize;
+            let block = match extension.get(offset + 1..offset + 1 + len) {
+                Some(block) if offset + 1 + len <= end => block,
+                _ => break,
+            };
+            offset += 1 + len;
+
+            if header >> 5 != EXTENDED_TAG || block.first() != Some(&HDR_STATIC_METADATA) {
+                continue;
+            }
+            if let Some(&eotfs) = block.get(1) {
+                self.eotfs = eotfs;
+            }
+            // The luminance is coded as `50 * 2 ^ (value / 32)`, zero being unknown.
+            self.max_luminance = block
+                .get(3)
+                .filter(|&&value| value != 0)
+                .map(|&value| 50.0 * 2f32.powf(f32::from(value) / 32.0));
+        }
+    }
+
+    pub fn identity(&self) -> MonitorIdentity {
+        // Some monitors leave the serial number as ones instead of zero.
+        let serial_number = match self.serial_number {
+            0 | 0x0101_0101 => None,
+            serial_number => Some(serial_number.to_string()),
+        };
+
+        MonitorIdentity {
+            manufacturer: self.manufacturer.clone(),
+            model: self.name.clone(),
+            product_code: Some(self.product_code),
+            serial: self.serial.clone().or(serial_number),
+        }
+    }
+
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        let primaries = match self.chromaticities {
+            Some([red, green, blue]) => ColorPrimaries::covered_by(red, green, blue),
+            None => Vec::new(),
+        };
+
+        // The monitors without HDR static metadata only take SDR.
+        let mut transfer_functions = vec![TransferFunction::Srgb];
+        if self.eotfs & EOTF_ST2084 != 0 {
+            transfer_functions.push(TransferFunction::Pq);
+        }
+        if self.eotfs & EOTF_HLG != 0 {
+            transfer_functions.push(TransferFunction::Hlg);
+        }
+
+        ColorCapabilities {
+            // The X server doesn't output HDR.
+            hdr: false,
+            bit_depth: self.bit_depth,
+            max_luminance: self.max_luminance,
+            headroom: None,
+            primaries,
+            transfer_functions,
+        }
+    }
+}
+
+/// Decodes the PNP ID of a manufacturer, which is 3 letters of 5 bits each, `A` being 1.
+pub fn manufacturer(id: u16) -> String {
+    [10, 5, 0]
+        .iter()
+        .map(|shift| (b'A' - 1 + ((id >> shift) & 0x1f) as u8) as char)
+        .collect()
+}
+
+/// The text of a display descriptor, ended by a line feed and padded with spaces.
+fn descriptor_text(descriptor: &[u8]) -> Option<String> {
+    let text = &descriptor[5..];
+    let end = text
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .unwrap_or(text.len());
+    let text = String::from_utf8_lossy(&text[..end]).trim().to_owned();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
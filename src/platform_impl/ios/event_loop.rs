@@ -4,17 +4,29 @@ use std::{
     fmt::{self, Debug},
     marker::PhantomData,
     mem, ptr,
-    sync::mpsc::{self, Receiver, Sender},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    time::Duration,
 };
 
 use crate::{
-    dpi::LogicalSize,
-    event::Event,
+    clipboard::{ClipboardKind, ClipboardProvider},
+    dpi::{LogicalSize, PhysicalPosition},
+    error::{ExternalError, NotSupportedError},
+    event::{Event, InputDevice, ModifiersState, ScanCode},
     event_loop::{
         ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget,
+        HapticPattern, Timer, TimerId,
     },
+    gamepad::GamepadId,
+    hotkey::{Accelerator, HotkeyId},
+    icon::{BadIcon, RgbaIcon},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
     monitor::MonitorHandle as RootMonitorHandle,
     platform::ios::Idiom,
+    window::CursorFrame,
 };
 
 use crate::platform_impl::platform::{
@@ -28,7 +40,7 @@ use crate::platform_impl::platform::{
         CFRunLoopSourceSignal, CFRunLoopWakeUp, NSStringRust, UIApplicationMain,
         UIUserInterfaceIdiom,
     },
-    monitor, view, MonitorHandle,
+    monitor, view, CustomCursor, DeviceId, MonitorHandle,
 };
 
 #[derive(Debug)]
@@ -63,6 +75,110 @@ impl<T: 'static> EventLoopWindowTarget<T> {
 
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn create_custom_cursor(
+        &self,
+        _image: RgbaIcon,
+        _hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor)
+    }
+
+    pub fn create_animated_cursor(
+        &self,
+        _frames: Vec<CursorFrame>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor)
+    }
+
+    pub fn set_timer(&self, _id: TimerId, _timer: Timer) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn cancel_timer(&self, _id: TimerId) -> bool {
+        false
+    }
+
+    pub fn set_precise_wait(&self, _precise: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_gamepads_enabled(&self, _enabled: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn rumble_gamepad(
+        &self,
+        _gamepad_id: GamepadId,
+        _strong: f64,
+        _weak: f64,
+        _duration: Duration,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn perform_haptic_feedback(&self, _pattern: HapticPattern) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn input_devices(&self) -> Result<Vec<InputDevice>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_hid_reports_enabled(
+        &self,
+        _device: DeviceId,
+        _enabled: bool,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn keyboard_layouts(&self) -> Result<Vec<KeyboardLayout>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn current_keyboard_layout(&self) -> Result<KeyboardLayout, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn scancode_to_text(
+        &self,
+        _scancode: ScanCode,
+        _modifiers: ModifiersState,
+    ) -> Result<Option<String>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn key_repeat(&self) -> Result<Option<KeyRepeat>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_lock_keys(&self, _lock_keys: LockKeys, _on: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn register_global_hotkey(
+        &self,
+        _accelerator: Accelerator,
+    ) -> Result<HotkeyId, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn unregister_global_hotkey(&self, _id: HotkeyId) -> bool {
+        false
+    }
 }
 
 pub struct EventLoop<T: 'static> {
@@ -99,6 +215,10 @@ impl<T: 'static> EventLoop<T> {
         }
     }
 
+    pub fn new_secondary() -> Result<Self, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn run<F>(self, event_handler: F) -> !
     where
         F: 'static + FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
@@ -131,6 +251,12 @@ impl<T: 'static> EventLoop<T> {
         EventLoopProxy::new(self.window_target.p.sender_to_clone.clone())
     }
 
+    pub fn create_wakeup(&self) -> EventLoopWakeup {
+        EventLoopWakeup {
+            source: unsafe { create_wakeup_source() },
+        }
+    }
+
     pub fn window_target(&self) -> &RootEventLoopWindowTarget<T> {
         &self.window_target
     }
@@ -166,23 +292,27 @@ impl<T> Drop for EventLoopProxy<T> {
     }
 }
 
+/// Creates a source waking up the event loop once it's signaled.
+unsafe fn create_wakeup_source() -> CFRunLoopSourceRef {
+    // just wake up the eventloop
+    extern "C" fn event_loop_proxy_handler(_: *mut c_void) {}
+
+    // adding a Source to the main CFRunLoop lets us wake it up and
+    // process user events through the normal OS EventLoop mechanisms.
+    let rl = CFRunLoopGetMain();
+    // we want all the members of context to be zero/null, except one
+    let mut context: CFRunLoopSourceContext = mem::zeroed();
+    context.perform = Some(event_loop_proxy_handler);
+    let source = CFRunLoopSourceCreate(ptr::null_mut(), CFIndex::max_value() - 1, &mut context);
+    CFRunLoopAddSource(rl, source, kCFRunLoopCommonModes);
+    CFRunLoopWakeUp(rl);
+    source
+}
+
 impl<T> EventLoopProxy<T> {
     fn new(sender: Sender<T>) -> EventLoopProxy<T> {
         unsafe {
-            // just wake up the eventloop
-            extern "C" fn event_loop_proxy_handler(_: *mut c_void) {}
-
-            // adding a Source to the main CFRunLoop lets us wake it up and
-            // process user events through the normal OS EventLoop mechanisms.
-            let rl = CFRunLoopGetMain();
-            // we want all the members of context to be zero/null, except one
-            let mut context: CFRunLoopSourceContext = mem::zeroed();
-            context.perform = Some(event_loop_proxy_handler);
-            let source =
-                CFRunLoopSourceCreate(ptr::null_mut(), CFIndex::max_value() - 1, &mut context);
-            CFRunLoopAddSource(rl, source, kCFRunLoopCommonModes);
-            CFRunLoopWakeUp(rl);
-
+            let source = create_wakeup_source();
             EventLoopProxy { sender, source }
         }
     }
@@ -201,6 +331,32 @@ impl<T> EventLoopProxy<T> {
     }
 }
 
+pub struct EventLoopWakeup {
+    source: CFRunLoopSourceRef,
+}
+
+unsafe impl Send for EventLoopWakeup {}
+unsafe impl Sync for EventLoopWakeup {}
+
+impl Drop for EventLoopWakeup {
+    fn drop(&mut self) {
+        unsafe {
+            CFRunLoopSourceInvalidate(self.source);
+            CFRelease(self.source as _);
+        }
+    }
+}
+
+impl EventLoopWakeup {
+    pub fn wake_up(&self) {
+        unsafe {
+            CFRunLoopSourceSignal(self.source);
+            let rl = CFRunLoopGetMain();
+            CFRunLoopWakeUp(rl);
+        }
+    }
+}
+
 fn setup_control_flow_observers() {
     unsafe {
         // begin is queued with the highest priority to ensure it is processed before other observers
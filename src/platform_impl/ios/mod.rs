@@ -77,14 +77,26 @@ mod window;
 
 use std::fmt;
 
+use crate::event::{ScanCode, VirtualKeyCode};
+
 pub use self::{
-    event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget},
+    event_loop::{EventLoop, EventLoopProxy, EventLoopWakeup, EventLoopWindowTarget},
     monitor::{MonitorHandle, VideoMode},
-    window::{PlatformSpecificWindowBuilderAttributes, Window, WindowId},
+    window::{
+        ChildSurface, CustomCursor, PlatformSpecificWindowBuilderAttributes, Window, WindowId,
+    },
 };
 
 pub(crate) use crate::icon::NoIcon as PlatformIcon;
 
+pub fn scancode_to_key(_scancode: ScanCode) -> Option<VirtualKeyCode> {
+    None
+}
+
+pub fn key_to_scancode(_key: VirtualKeyCode) -> Option<ScanCode> {
+    None
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DeviceId {
     uiscreen: ffi::id,
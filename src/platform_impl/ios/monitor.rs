@@ -6,7 +6,10 @@ use std::{
 
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
-    monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+    monitor::{
+        ColorCapabilities, MonitorHandle as RootMonitorHandle, MonitorIdentity,
+        VideoMode as RootVideoMode,
+    },
     platform_impl::platform::{
         app_state,
         ffi::{id, nil, CGFloat, CGRect, CGSize, NSInteger, NSUInteger},
@@ -237,6 +240,22 @@ impl Inner {
         }
     }
 
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        ColorCapabilities::default()
+    }
+
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        (self.position(), self.size())
+    }
+
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        None
+    }
+
+    pub fn supports_variable_refresh(&self) -> bool {
+        false
+    }
+
     pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
         let mut modes = BTreeSet::new();
         unsafe {
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
 use objc::{
     declare::ClassDecl,
@@ -7,14 +10,17 @@ use objc::{
 
 use crate::{
     dpi::PhysicalPosition,
-    event::{DeviceId as RootDeviceId, Event, Force, Touch, TouchPhase, WindowEvent},
+    event::{
+        DeviceId as RootDeviceId, Event, Force, PenEvent, PenPhase, PenTool, PresentationFlags,
+        Touch, TouchPhase, WindowEvent,
+    },
     platform::ios::MonitorHandleExtIOS,
     platform_impl::platform::{
         app_state::{self, OSCapabilities},
         event_loop::{self, EventProxy, EventWrapper},
         ffi::{
-            id, nil, CGFloat, CGPoint, CGRect, UIForceTouchCapability, UIInterfaceOrientationMask,
-            UIRectEdge, UITouchPhase, UITouchType,
+            id, kCFRunLoopCommonModes, nil, CGFloat, CGPoint, CGRect, UIForceTouchCapability,
+            UIInterfaceOrientationMask, UIRectEdge, UITouchPhase, UITouchType,
         },
         window::PlatformSpecificWindowBuilderAttributes,
         DeviceId,
@@ -214,6 +220,10 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
                 let touches_enum: id = msg_send![touches, objectEnumerator];
                 let mut touch_events = Vec::new();
                 let os_supports_force = app_state::os_capabilities().force_touch;
+                // The timestamps of the touches are in seconds since the system started.
+                let now = Instant::now();
+                let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+                let uptime: f64 = msg_send![process_info, systemUptime];
                 loop {
                     let touch: id = msg_send![touches_enum, nextObject];
                     if touch == nil {
@@ -248,6 +258,9 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
                         None
                     };
                     let touch_id = touch as u64;
+                    let timestamp: f64 = msg_send![touch, timestamp];
+                    let elapsed = Duration::from_secs_f64((uptime - timestamp).max(0.0));
+                    let time = now.checked_sub(elapsed).unwrap_or(now);
                     let phase: UITouchPhase = msg_send![touch, phase];
                     let phase = match phase {
                         UITouchPhase::Began => TouchPhase::Started,
@@ -271,10 +284,51 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
                             device_id: RootDeviceId(DeviceId { uiscreen }),
                             id: touch_id,
                             location: physical_location,
+                            time,
                             force,
                             phase,
+                            palm: false,
                         }),
                     }));
+                    if touch_type == UITouchType::Pencil {
+                        let force: CGFloat = msg_send![touch, force];
+                        let max_possible_force: CGFloat = msg_send![touch, maximumPossibleForce];
+                        // The altitude is the angle from the screen, and the azimuth the direction
+                        // the top of the pencil points to, both in radians.
+                        let altitude: CGFloat = msg_send![touch, altitudeAngle];
+                        let azimuth: CGFloat = msg_send![touch, azimuthAngleInView: object];
+                        let (altitude, azimuth) = (altitude as f64, azimuth as f64);
+                        let tilt = (
+                            (azimuth.cos() / altitude.tan()).atan().to_degrees(),
+                            (azimuth.sin() / altitude.tan()).atan().to_degrees(),
+                        );
+                        let pressure = if max_possible_force > 0.0 {
+                            (force / max_possible_force) as f64
+                        } else {
+                            0.0
+                        };
+                        touch_events.push(EventWrapper::StaticEvent(Event::WindowEvent {
+                            window_id: RootWindowId(window.into()),
+                            event: WindowEvent::Pen(PenEvent {
+                                device_id: RootDeviceId(DeviceId { uiscreen }),
+                                phase: match phase {
+                                    TouchPhase::Started => PenPhase::Down,
+                                    TouchPhase::Moved => PenPhase::Moved,
+                                    TouchPhase::Ended | TouchPhase::Cancelled => PenPhase::Up,
+                                },
+                                location: physical_location,
+                                time,
+                                pressure,
+                                tilt: Some(tilt),
+                                twist: None,
+                                eraser: false,
+                                barrel_button: false,
+                                tool_id: 0,
+                                tool: PenTool::Pen,
+                                serial: None,
+                            }),
+                        }));
+                    }
                 }
                 app_state::handle_nonuser_events(touch_events);
             }
@@ -538,12 +592,126 @@ pub unsafe fn create_window(
 
             msg_send![window, setScreen: uiscreen]
         }
+        Some(Fullscreen::Span(ref monitors)) => {
+            let uiscreen: id = match monitors.first() {
+                Some(monitor) => monitor.ui_screen() as id,
+                None => {
+                    let uiscreen: id = msg_send![window, screen];
+                    uiscreen
+                }
+            };
+
+            msg_send![window, setScreen: uiscreen]
+        }
         None => (),
     }
 
     window
 }
 
+// requires main thread
+unsafe fn get_display_link_target_class() -> &'static Class {
+    static mut CLASS: Option<&'static Class> = None;
+    if CLASS.is_none() {
+        // The frame presented before a refresh is shown on the next one, whose time is then sent
+        // as its `Presented`.
+        extern "C" fn refresh(object: &mut Object, _: Sel, display_link: id) {
+            unsafe {
+                let frame_latched: BOOL = *object.get_ivar("_frameLatched");
+                if frame_latched == YES {
+                    let window: id = *object.get_ivar("_window");
+                    // The timestamps of the display links are in seconds since the system
+                    // started.
+                    let now = Instant::now();
+                    let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+                    let uptime: f64 = msg_send![process_info, systemUptime];
+                    let timestamp: f64 = msg_send![display_link, timestamp];
+                    let duration: f64 = msg_send![display_link, duration];
+                    let elapsed = Duration::from_secs_f64((uptime - timestamp).max(0.0));
+                    app_state::handle_nonuser_event(EventWrapper::StaticEvent(
+                        Event::WindowEvent {
+                            window_id: RootWindowId(window.into()),
+                            event: WindowEvent::Presented {
+                                time: now.checked_sub(elapsed).unwrap_or(now),
+                                refresh: Some(Duration::from_secs_f64(duration))
+                                    .filter(|refresh| *refresh > Duration::from_secs(0)),
+                                flags: PresentationFlags::VSYNC,
+                            },
+                        },
+                    ));
+                }
+                let frame_presented: BOOL = *object.get_ivar("_framePresented");
+                object.set_ivar::<BOOL>("_frameLatched", frame_presented);
+                object.set_ivar::<BOOL>("_framePresented", NO);
+            }
+        }
+
+        let mut decl = ClassDecl::new("WinitDisplayLinkTarget", class!(NSObject))
+            .expect("Failed to declare class `WinitDisplayLinkTarget`");
+        decl.add_ivar::<id>("_window");
+        decl.add_ivar::<id>("_displayLink");
+        decl.add_ivar::<BOOL>("_framePresented");
+        decl.add_ivar::<BOOL>("_frameLatched");
+        decl.add_method(
+            sel!(refresh:),
+            refresh as extern "C" fn(&mut Object, Sel, id),
+        );
+
+        CLASS = Some(decl.register());
+    }
+    CLASS.unwrap()
+}
+
+// requires main thread
+/// Creates the `CADisplayLink` timing the presentations of the frames of the window, returning
+/// its target, which keeps it.
+pub unsafe fn create_display_link(window: id) -> id {
+    let class = get_display_link_target_class();
+
+    let target: id = msg_send![class, alloc];
+    assert!(
+        !target.is_null(),
+        "Failed to create `WinitDisplayLinkTarget` instance"
+    );
+    let target: id = msg_send![target, init];
+    assert!(
+        !target.is_null(),
+        "Failed to initialize `WinitDisplayLinkTarget` instance"
+    );
+    let display_link: id = msg_send![
+        class!(CADisplayLink),
+        displayLinkWithTarget: target
+        selector: sel!(refresh:)
+    ];
+    let () = msg_send![display_link, retain];
+    let run_loop: id = msg_send![class!(NSRunLoop), mainRunLoop];
+    let () = msg_send![
+        display_link,
+        addToRunLoop: run_loop
+        forMode: kCFRunLoopCommonModes as id
+    ];
+    (*target).set_ivar::<id>("_window", window);
+    (*target).set_ivar::<id>("_displayLink", display_link);
+    (*target).set_ivar::<BOOL>("_framePresented", NO);
+    (*target).set_ivar::<BOOL>("_frameLatched", NO);
+    target
+}
+
+// requires main thread
+pub unsafe fn display_link_frame_presented(target: id) {
+    (*target).set_ivar::<BOOL>("_framePresented", YES);
+}
+
+// requires main thread
+/// Stops the display link of the target, and releases both.
+pub unsafe fn release_display_link(target: id) {
+    let display_link: id = *(*target).get_ivar("_displayLink");
+    // The display link releases its target once invalidated.
+    let () = msg_send![display_link, invalidate];
+    let () = msg_send![display_link, release];
+    let () = msg_send![target, release];
+}
+
 pub fn create_delegate_class() {
     extern "C" fn did_finish_launching(_: &mut Object, _: Sel, _: id, _: id) -> BOOL {
         unsafe {
@@ -1,14 +1,18 @@
 use raw_window_handle::{ios::IOSHandle, RawWindowHandle};
 use std::{
+    cell::Cell,
     collections::VecDeque,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 
 use objc::runtime::{Class, Object, BOOL, NO, YES};
 
 use crate::{
+    clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker},
+    dnd::{DndActions, DragData},
     dpi::{self, LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
-    error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    error::{ExternalError, HandleError, NotSupportedError, OsError as RootOsError},
     event::{Event, WindowEvent},
     icon::Icon,
     monitor::MonitorHandle as RootMonitorHandle,
@@ -17,26 +21,58 @@ use crate::{
         app_state,
         event_loop::{self, EventProxy, EventWrapper},
         ffi::{
-            id, CGFloat, CGPoint, CGRect, CGSize, UIEdgeInsets, UIInterfaceOrientationMask,
-            UIRectEdge, UIScreenOverscanCompensation,
+            id, nil, CGFloat, CGPoint, CGRect, CGSize, NSInteger, UIEdgeInsets,
+            UIInterfaceOrientationMask, UIRectEdge, UIScreenOverscanCompensation,
         },
         monitor, view, EventLoopWindowTarget, MonitorHandle,
     },
     window::{
-        CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+        Backdrop, CursorIcon, CursorTheme, Fullscreen, ImePurpose, ProgressState, Ratio,
+        UserAttentionType, WindowAttributes, WindowId as RootWindowId,
     },
 };
 
+/// A custom cursor, which is never shown as there's no cursor on iOS.
+#[derive(Clone)]
+pub struct CustomCursor;
+
+/// A child surface, which is never created as it's unsupported on iOS.
+pub struct ChildSurface;
+
+impl ChildSurface {
+    pub fn new<T: 'static>(
+        _window_target: &EventLoopWindowTarget<T>,
+        _parent: &Window,
+        _position: Position,
+        _size: Size,
+    ) -> Result<Self, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_position(&self, _position: Position) {}
+
+    pub fn set_size(&self, _size: Size) {}
+
+    pub fn window_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        Err(HandleError::Unavailable)
+    }
+}
+
 pub struct Inner {
     pub window: id,
     pub view_controller: id,
     pub view: id,
     gl_or_metal_backed: bool,
+    /// The target of the display link timing the presentations, once a frame was presented.
+    display_link: Cell<id>,
 }
 
 impl Drop for Inner {
     fn drop(&mut self) {
         unsafe {
+            if self.display_link.get() != nil {
+                view::release_display_link(self.display_link.get());
+            }
             let () = msg_send![self.view, release];
             let () = msg_send![self.view_controller, release];
             let () = msg_send![self.window, release];
@@ -78,6 +114,29 @@ impl Inner {
         }
     }
 
+    pub fn set_vsync_redraws(&self, _vsync_redraws: bool) {
+        warn!("`Window::set_vsync_redraws` is ignored on iOS")
+    }
+
+    pub fn pre_present_notify(&self) {
+        unsafe {
+            if self.display_link.get() == nil {
+                self.display_link
+                    .set(view::create_display_link(self.window));
+            }
+            view::display_link_frame_presented(self.display_link.get());
+        }
+    }
+
+    #[cfg(feature = "software-present")]
+    pub fn present_pixels(
+        &self,
+        _pixels: &[u32],
+        _size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         unsafe {
             let safe_area = self.safe_area_screen_space();
@@ -143,6 +202,10 @@ impl Inner {
         }
     }
 
+    pub fn recommended_bounds(&self) -> Option<PhysicalSize<u32>> {
+        None
+    }
+
     pub fn set_inner_size(&self, _size: Size) {
         warn!("not clear what `Window::set_inner_size` means on iOS");
     }
@@ -155,6 +218,10 @@ impl Inner {
         warn!("`Window::set_max_inner_size` is ignored on iOS")
     }
 
+    pub fn set_aspect_ratio(&self, _ratio: Option<Ratio>) {
+        warn!("`Window::set_aspect_ratio` is ignored on iOS")
+    }
+
     pub fn set_resizable(&self, _resizable: bool) {
         warn!("`Window::set_resizable` is ignored on iOS")
     }
@@ -170,6 +237,18 @@ impl Inner {
         debug!("`Window::set_cursor_icon` ignored on iOS")
     }
 
+    pub fn set_cursor(&self, _cursor: CustomCursor) {
+        debug!("`Window::set_cursor` ignored on iOS")
+    }
+
+    pub fn set_cursor_theme(&self, _name: &str, _size: u32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        None
+    }
+
     pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
@@ -178,6 +257,25 @@ impl Inner {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
+    pub fn set_cursor_grab_area(
+        &self,
+        _area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_pointer_lock(&self, _lock: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn set_cursor_visible(&self, _visible: bool) {
         debug!("`Window::set_cursor_visible` is ignored on iOS")
     }
@@ -210,6 +308,11 @@ impl Inner {
                 Some(Fullscreen::Borderless(monitor)) => monitor
                     .unwrap_or_else(|| self.current_monitor_inner())
                     .ui_screen() as id,
+                Some(Fullscreen::Span(monitors)) => monitors
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| self.current_monitor_inner())
+                    .ui_screen() as id,
                 None => {
                     warn!("`Window::set_fullscreen(None)` ignored on iOS");
                     return;
@@ -259,6 +362,18 @@ impl Inner {
         warn!("`Window::set_decorations` is ignored on iOS")
     }
 
+    pub fn set_shadow(&self, _shadow: bool) {
+        warn!("`Window::set_shadow` is ignored on iOS")
+    }
+
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_backdrop(&self, _backdrop: Backdrop) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn set_always_on_top(&self, _always_on_top: bool) {
         warn!("`Window::set_always_on_top` is ignored on iOS")
     }
@@ -267,8 +382,45 @@ impl Inner {
         warn!("`Window::set_window_icon` is ignored on iOS")
     }
 
-    pub fn set_ime_position(&self, _position: Position) {
-        warn!("`Window::set_ime_position` is ignored on iOS")
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {
+        warn!("`Window::set_ime_cursor_area` is ignored on iOS")
+    }
+
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {
+        warn!("`Window::set_ime_surrounding_text` is ignored on iOS")
+    }
+
+    pub fn set_ime_purpose(&self, _purpose: ImePurpose) {
+        warn!("`Window::set_ime_purpose` is ignored on iOS")
+    }
+
+    pub fn set_key_repeat(&self, _key_repeat: bool) {
+        warn!("`Window::set_key_repeat` is ignored on iOS")
+    }
+
+    pub fn set_system_shortcuts_inhibited(&self, _inhibited: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn read_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _picker: MimePicker,
+        _consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn start_drag(&self, _data: DragData, _actions: DndActions) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
     pub fn focus_window(&self) {
@@ -279,6 +431,50 @@ impl Inner {
         warn!("`Window::request_user_attention` is ignored on iOS")
     }
 
+    pub fn tab_group(&self) -> Option<String> {
+        None
+    }
+
+    pub fn set_tab_group(&self, _group: &str) {
+        warn!("`Window::set_tab_group` is ignored on iOS")
+    }
+
+    pub fn merge_into_tab_group(&self, _window: &Inner) {
+        warn!("`Window::merge_into_tab_group` is ignored on iOS")
+    }
+
+    pub fn select_next_tab(&self) {
+        warn!("`Window::select_next_tab` is ignored on iOS")
+    }
+
+    pub fn workspace(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn move_to_workspace(&self, _workspace: u32) {
+        warn!("`Window::move_to_workspace` is ignored on iOS")
+    }
+
+    pub fn set_visible_on_all_workspaces(&self, _visible: bool) {
+        warn!("`Window::set_visible_on_all_workspaces` is ignored on iOS")
+    }
+
+    pub fn set_modal(&self, _modal: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_progress(&self, _state: ProgressState) {
+        warn!("`Window::set_progress` is ignored on iOS")
+    }
+
+    pub fn set_badge(&self, count: Option<u64>) {
+        unsafe {
+            let app: id = msg_send![class!(UIApplication), sharedApplication];
+            let number = count.unwrap_or(0) as NSInteger;
+            let () = msg_send![app, setApplicationIconBadgeNumber: number];
+        }
+    }
+
     // Allow directly accessing the current monitor internally without unwrapping.
     fn current_monitor_inner(&self) -> RootMonitorHandle {
         unsafe {
@@ -302,18 +498,26 @@ impl Inner {
         Some(RootMonitorHandle { inner: monitor })
     }
 
+    pub fn is_vrr_active(&self) -> bool {
+        false
+    }
+
     pub fn id(&self) -> WindowId {
         self.window.into()
     }
 
-    pub fn raw_window_handle(&self) -> RawWindowHandle {
+    pub fn window_handle(&self) -> Result<RawWindowHandle, HandleError> {
         let handle = IOSHandle {
             ui_window: self.window as _,
             ui_view: self.view as _,
             ui_view_controller: self.view_controller as _,
             ..IOSHandle::empty()
         };
-        RawWindowHandle::IOS(handle)
+        Ok(RawWindowHandle::IOS(handle))
+    }
+
+    pub fn display_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        Ok(RawWindowHandle::IOS(IOSHandle::empty()))
     }
 }
 
@@ -375,7 +579,10 @@ impl Window {
                     video_mode.video_mode.monitor.ui_screen() as id
                 }
                 Some(Fullscreen::Borderless(Some(ref monitor))) => monitor.inner.ui_screen(),
-                Some(Fullscreen::Borderless(None)) | None => {
+                Some(Fullscreen::Span(ref monitors)) if !monitors.is_empty() => {
+                    monitors[0].inner.ui_screen()
+                }
+                Some(Fullscreen::Borderless(None)) | Some(Fullscreen::Span(_)) | None => {
                     monitor::main_uiscreen().ui_screen() as id
                 }
             };
@@ -423,6 +630,7 @@ impl Window {
                     view_controller,
                     view,
                     gl_or_metal_backed,
+                    display_link: Cell::new(nil),
                 },
             };
             app_state::set_key_window(window);
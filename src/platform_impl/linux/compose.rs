@@ -0,0 +1,165 @@
+//! The tracking of the compose sequences, following the Compose file of the locale like the
+//! input methods of X11 and the keyboards of Wayland do.
+
+use std::{
+    env,
+    ffi::{CStr, CString, OsString},
+    os::{raw::c_char, unix::ffi::OsStringExt},
+    ptr,
+};
+
+use super::xkb::{self, XkbCommon, XKBCOMMON};
+use crate::event::ComposeState;
+
+pub struct Compose {
+    xkb: &'static XkbCommon,
+    context: *mut xkb::xkb_context,
+    table: *mut xkb::xkb_compose_table,
+    state: *mut xkb::xkb_compose_state,
+    /// The characters of the keys of the sequence in progress.
+    pending: String,
+}
+
+impl Compose {
+    pub fn new() -> Option<Compose> {
+        let xkb = XKBCOMMON.as_ref()?;
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(env::var_os)
+            .find(|locale| !locale.is_empty())
+            .unwrap_or_else(|| OsString::from("C"));
+        let locale = CString::new(locale.into_vec()).ok()?;
+
+        unsafe {
+            let context = (xkb.xkb_context_new)(0);
+            if context.is_null() {
+                return None;
+            }
+            // The compose state takes over the pointers as they're created, to free them on
+            // errors.
+            let mut compose = Compose {
+                xkb,
+                context,
+                table: ptr::null_mut(),
+                state: ptr::null_mut(),
+                pending: String::new(),
+            };
+            compose.table = (xkb.xkb_compose_table_new_from_locale)(context, locale.as_ptr(), 0);
+            if compose.table.is_null() {
+                return None;
+            }
+            compose.state = (xkb.xkb_compose_state_new)(compose.table, 0);
+            if compose.state.is_null() {
+                return None;
+            }
+            Some(compose)
+        }
+    }
+
+    /// Feeds the keysym of a pressed key, returning the new state of the sequence if the key
+    /// changed it.
+    pub fn feed(&mut self, keysym: u32) -> Option<ComposeState> {
+        unsafe {
+            // The modifiers are ignored, and don't affect the sequences.
+            if (self.xkb.xkb_compose_state_feed)(self.state, keysym)
+                != xkb::XKB_COMPOSE_FEED_ACCEPTED
+            {
+                return None;
+            }
+            match (self.xkb.xkb_compose_state_get_status)(self.state) {
+                xkb::XKB_COMPOSE_COMPOSING => {
+                    self.pending.push_str(&self.keysym_text(keysym));
+                    Some(ComposeState::Pending(self.pending.clone()))
+                }
+                xkb::XKB_COMPOSE_COMPOSED => {
+                    let mut buffer = [0 as c_char; 64];
+                    let len = (self.xkb.xkb_compose_state_get_utf8)(
+                        self.state,
+                        buffer.as_mut_ptr(),
+                        buffer.len(),
+                    );
+                    let text = if len > 0 {
+                        CStr::from_ptr(buffer.as_ptr())
+                            .to_string_lossy()
+                            .into_owned()
+                    } else {
+                        String::new()
+                    };
+                    self.reset();
+                    Some(ComposeState::Committed(text))
+                }
+                xkb::XKB_COMPOSE_CANCELLED => {
+                    self.reset();
+                    Some(ComposeState::Cancelled)
+                }
+                _ => None,
+            }
+        }
+    }
+
+    /// Cancels the sequence in progress, returning `Cancelled` if there was one.
+    pub fn cancel(&mut self) -> Option<ComposeState> {
+        let pending = !self.pending.is_empty();
+        self.reset();
+        if pending {
+            Some(ComposeState::Cancelled)
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        unsafe { (self.xkb.xkb_compose_state_reset)(self.state) };
+        self.pending.clear();
+    }
+
+    /// The characters shown for a key of a sequence, which are the spacing characters of the
+    /// dead keys.
+    fn keysym_text(&self, keysym: u32) -> String {
+        let spacing = match keysym {
+            0xfe50 => '`',
+            0xfe51 => '´',
+            0xfe52 => '^',
+            0xfe53 => '~',
+            0xfe54 => '¯',
+            0xfe55 => '˘',
+            0xfe56 => '˙',
+            0xfe57 => '¨',
+            0xfe58 => '˚',
+            0xfe59 => '˝',
+            0xfe5a => 'ˇ',
+            0xfe5b => '¸',
+            0xfe5c => '˛',
+            // The compose key.
+            0xff20 => '·',
+            _ => {
+                let mut buffer = [0 as c_char; 8];
+                let len = unsafe {
+                    (self.xkb.xkb_keysym_to_utf8)(keysym, buffer.as_mut_ptr(), buffer.len())
+                };
+                // The length includes the terminating null character.
+                if len <= 1 {
+                    return String::new();
+                }
+                return unsafe { CStr::from_ptr(buffer.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned();
+            }
+        };
+        spacing.to_string()
+    }
+}
+
+impl Drop for Compose {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.state.is_null() {
+                (self.xkb.xkb_compose_state_unref)(self.state);
+            }
+            if !self.table.is_null() {
+                (self.xkb.xkb_compose_table_unref)(self.table);
+            }
+            (self.xkb.xkb_context_unref)(self.context);
+        }
+    }
+}
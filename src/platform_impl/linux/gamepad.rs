@@ -0,0 +1,953 @@
+//! The gamepads, read from the evdev devices of `/dev/input`, whose buttons and axes are mapped
+//! following the gamepad layout of the kernel, from `Documentation/input/gamepad.rst`.
+//!
+//! The devices, the watch of the directory and a wake-up are gathered in an epoll, so the event
+//! loops only have to wait for its descriptor.
+
+use std::{
+    cell::{Cell, RefCell},
+    time::Duration,
+};
+
+#[cfg(target_os = "linux")]
+use std::{
+    ffi::{CStr, CString, OsStr, OsString},
+    fs, io, mem,
+    os::unix::ffi::OsStrExt,
+    os::unix::io::RawFd,
+};
+
+use crate::{
+    error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    gamepad::{GamepadEvent, GamepadId},
+};
+
+#[cfg(target_os = "linux")]
+use crate::{
+    event::ElementState,
+    gamepad::{GamepadAxis, GamepadButton},
+};
+
+#[derive(Default)]
+pub struct Gamepads {
+    /// Whether the epoll was registered with the poll of the event loop.
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    registered: Cell<bool>,
+    /// The epoll is kept once it's created, so the poll it is registered with keeps it.
+    epoll: RefCell<Option<Epoll>>,
+    next_id: Cell<u32>,
+}
+
+impl Gamepads {
+    pub fn set_enabled(
+        &self,
+        enabled: bool,
+        os_error: impl Fn(&'static str) -> RootOsError,
+    ) -> Result<(), ExternalError> {
+        if cfg!(not(target_os = "linux")) {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        let mut epoll = self.epoll.borrow_mut();
+        if !enabled {
+            if let Some(epoll) = epoll.as_mut() {
+                epoll.unwatch();
+            }
+            return Ok(());
+        }
+        if epoll.is_none() {
+            *epoll = Some(Epoll::new().map_err(|_| {
+                ExternalError::Os(os_error("Failed to create the epoll of the gamepads"))
+            })?);
+        }
+        epoll
+            .as_mut()
+            .unwrap()
+            .watch(&self.next_id)
+            .map_err(|_| ExternalError::Os(os_error("Failed to watch the input devices")))
+    }
+
+    /// The descriptor of the epoll, once the gamepads were enabled.
+    #[cfg(target_os = "linux")]
+    pub fn fd(&self) -> Option<RawFd> {
+        self.epoll.borrow().as_ref().map(|epoll| epoll.fd)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        None
+    }
+
+    /// Rumbles the gamepad, replacing its previous rumble.
+    #[cfg(target_os = "linux")]
+    pub fn rumble(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+        os_error: impl Fn(&'static str) -> RootOsError,
+    ) -> Result<(), ExternalError> {
+        let mut epoll = self.epoll.borrow_mut();
+        let device = epoll
+            .as_mut()
+            .and_then(|epoll| epoll.watch.as_mut())
+            .and_then(|watch| {
+                watch
+                    .devices
+                    .iter_mut()
+                    .find(|device| device.id == gamepad_id)
+            })
+            .ok_or_else(|| ExternalError::Os(os_error("The gamepad isn't connected")))?;
+        if !device.rumble {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        device
+            .rumble(strong, weak, duration)
+            .map_err(|_| ExternalError::Os(os_error("Failed to rumble the gamepad")))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn rumble(
+        &self,
+        _gamepad_id: GamepadId,
+        _strong: f64,
+        _weak: f64,
+        _duration: Duration,
+        _os_error: impl Fn(&'static str) -> RootOsError,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    pub fn is_registered(&self) -> bool {
+        self.registered.get()
+    }
+
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    pub fn set_registered(&self) {
+        self.registered.set(true);
+    }
+
+    /// Reads the devices which are ready and the changes of the directory, without waiting,
+    /// returning the events of the gamepads since the last call.
+    pub fn dispatch(&self) -> Vec<(GamepadId, GamepadEvent)> {
+        match self.epoll.borrow_mut().as_mut() {
+            Some(epoll) => epoll.dispatch(&self.next_id),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod ffi {
+    pub const EV_SYN: u16 = 0x00;
+    pub const EV_KEY: u16 = 0x01;
+    pub const EV_ABS: u16 = 0x03;
+    pub const EV_FF: u16 = 0x15;
+
+    pub const SYN_REPORT: u16 = 0;
+    pub const SYN_DROPPED: u16 = 3;
+
+    pub const BTN_JOYSTICK: u16 = 0x120;
+    pub const BTN_SOUTH: u16 = 0x130;
+    pub const BTN_EAST: u16 = 0x131;
+    pub const BTN_NORTH: u16 = 0x133;
+    pub const BTN_WEST: u16 = 0x134;
+    pub const BTN_TL: u16 = 0x136;
+    pub const BTN_TR: u16 = 0x137;
+    pub const BTN_TL2: u16 = 0x138;
+    pub const BTN_TR2: u16 = 0x139;
+    pub const BTN_SELECT: u16 = 0x13a;
+    pub const BTN_START: u16 = 0x13b;
+    pub const BTN_MODE: u16 = 0x13c;
+    pub const BTN_THUMBL: u16 = 0x13d;
+    pub const BTN_THUMBR: u16 = 0x13e;
+    /// The end of the buttons of the joysticks and of the gamepads, where the ones of the
+    /// digitizers start.
+    pub const BTN_DIGI: u16 = 0x140;
+    pub const BTN_DPAD_UP: u16 = 0x220;
+    pub const BTN_DPAD_DOWN: u16 = 0x221;
+    pub const BTN_DPAD_LEFT: u16 = 0x222;
+    pub const BTN_DPAD_RIGHT: u16 = 0x223;
+    pub const KEY_CNT: usize = 0x300;
+
+    pub const ABS_X: u16 = 0x00;
+    pub const ABS_Y: u16 = 0x01;
+    pub const ABS_Z: u16 = 0x02;
+    pub const ABS_RX: u16 = 0x03;
+    pub const ABS_RY: u16 = 0x04;
+    pub const ABS_RZ: u16 = 0x05;
+    pub const ABS_HAT0X: u16 = 0x10;
+    pub const ABS_HAT0Y: u16 = 0x11;
+    pub const ABS_HAT2X: u16 = 0x14;
+    pub const ABS_HAT2Y: u16 = 0x15;
+    pub const ABS_CNT: usize = 0x40;
+
+    pub const FF_RUMBLE: u16 = 0x50;
+    pub const FF_CNT: usize = 0x80;
+
+    // The ioctls are encoded as in `asm-generic/ioctl.h`, except for the direction bits of the
+    // architectures which have three of them.
+    const IOC_READ: u32 = 2;
+    #[cfg(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc",
+        target_arch = "sparc64"
+    ))]
+    const IOC_WRITE: u32 = 4;
+    #[cfg(not(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc",
+        target_arch = "sparc64"
+    )))]
+    const IOC_WRITE: u32 = 1;
+    #[cfg(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc",
+        target_arch = "sparc64"
+    ))]
+    const IOC_DIRSHIFT: u32 = 29;
+    #[cfg(not(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc",
+        target_arch = "sparc64"
+    )))]
+    const IOC_DIRSHIFT: u32 = 30;
+
+    const fn ior(nr: u32, size: usize) -> u32 {
+        IOC_READ << IOC_DIRSHIFT | (size as u32) << 16 | (b'E' as u32) << 8 | nr
+    }
+
+    const fn iow(nr: u32, size: usize) -> u32 {
+        IOC_WRITE << IOC_DIRSHIFT | (size as u32) << 16 | (b'E' as u32) << 8 | nr
+    }
+
+    pub const fn eviocgname(len: usize) -> u32 {
+        ior(0x06, len)
+    }
+
+    pub const fn eviocgkey(len: usize) -> u32 {
+        ior(0x18, len)
+    }
+
+    pub const fn eviocgbit(ev: u16, len: usize) -> u32 {
+        ior(0x20 + ev as u32, len)
+    }
+
+    pub const fn eviocgabs(abs: u16) -> u32 {
+        ior(
+            0x40 + abs as u32,
+            std::mem::size_of::<libc::input_absinfo>(),
+        )
+    }
+
+    pub const fn eviocsff() -> u32 {
+        iow(0x80, std::mem::size_of::<libc::ff_effect>())
+    }
+}
+
+#[cfg(target_os = "linux")]
+const INPUT_DIR: &str = "/dev/input";
+
+/// The threshold past which the analog triggers are pressed, as the one of XInput.
+#[cfg(target_os = "linux")]
+const TRIGGER_THRESHOLD: f64 = 30. / 255.;
+
+#[cfg(target_os = "linux")]
+struct Epoll {
+    fd: RawFd,
+    /// An eventfd, written to wake the event loop up when events are queued outside of the
+    /// dispatch, like when the gamepads are enabled.
+    wake: RawFd,
+    /// The watch of the directory, while the gamepads are enabled.
+    watch: Option<Watch>,
+}
+
+#[cfg(target_os = "linux")]
+impl Epoll {
+    fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let wake = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        if wake < 0 {
+            let error = io::Error::last_os_error();
+            unsafe { libc::close(fd) };
+            return Err(error);
+        }
+        let epoll = Epoll {
+            fd,
+            wake,
+            watch: None,
+        };
+        epoll.add(wake)?;
+        Ok(epoll)
+    }
+
+    fn add(&self, fd: RawFd) -> io::Result<()> {
+        let mut event = libc::epoll_event {
+            events: libc::EPOLLIN as u32,
+            u64: fd as u64,
+        };
+        if unsafe { libc::epoll_ctl(self.fd, libc::EPOLL_CTL_ADD, fd, &mut event) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Starts watching the directory, connecting the gamepads already there.
+    fn watch(&mut self, next_id: &Cell<u32>) -> io::Result<()> {
+        if self.watch.is_some() {
+            return Ok(());
+        }
+        let inotify = unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) };
+        if inotify < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut watch = Watch {
+            inotify,
+            devices: Vec::new(),
+            events: Vec::new(),
+        };
+        let dir = CString::new(INPUT_DIR).unwrap();
+        // The nodes may be created before their permissions are set.
+        let mask = libc::IN_CREATE | libc::IN_ATTRIB | libc::IN_DELETE;
+        if unsafe { libc::inotify_add_watch(inotify, dir.as_ptr(), mask) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        self.add(inotify)?;
+        watch.scan(self, next_id);
+        self.watch = Some(watch);
+        self.wake_up();
+        Ok(())
+    }
+
+    /// Stops watching the directory, closing the gamepads.
+    fn unwatch(&mut self) {
+        // Closing the descriptors removes them from the epoll.
+        self.watch = None;
+    }
+
+    fn wake_up(&self) {
+        let value = 1u64;
+        unsafe {
+            libc::write(
+                self.wake,
+                &value as *const u64 as *const libc::c_void,
+                mem::size_of::<u64>(),
+            )
+        };
+    }
+
+    fn dispatch(&mut self, next_id: &Cell<u32>) -> Vec<(GamepadId, GamepadEvent)> {
+        let mut ready = [libc::epoll_event { events: 0, u64: 0 }; 16];
+        loop {
+            let count =
+                unsafe { libc::epoll_wait(self.fd, ready.as_mut_ptr(), ready.len() as _, 0) };
+            if count <= 0 {
+                break;
+            }
+            let mut watch = self.watch.take();
+            for event in &ready[..count as usize] {
+                let fd = event.u64 as RawFd;
+                if fd == self.wake {
+                    let mut value = 0u64;
+                    unsafe {
+                        libc::read(
+                            self.wake,
+                            &mut value as *mut u64 as *mut libc::c_void,
+                            mem::size_of::<u64>(),
+                        )
+                    };
+                } else if let Some(watch) = watch.as_mut() {
+                    if fd == watch.inotify {
+                        watch.read_changes(self, next_id);
+                    } else {
+                        watch.read_device(fd);
+                    }
+                }
+            }
+            self.watch = watch;
+            if (count as usize) < ready.len() {
+                break;
+            }
+        }
+        match self.watch.as_mut() {
+            Some(watch) => mem::take(&mut watch.events),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Epoll {
+    fn drop(&mut self) {
+        self.watch = None;
+        unsafe {
+            libc::close(self.wake);
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// The epoll is never created on the BSDs, whose evdev devices aren't read.
+#[cfg(not(target_os = "linux"))]
+enum Epoll {}
+
+#[cfg(not(target_os = "linux"))]
+impl Epoll {
+    fn new() -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "The gamepads aren't supported",
+        ))
+    }
+
+    fn watch(&mut self, _next_id: &Cell<u32>) -> std::io::Result<()> {
+        match *self {}
+    }
+
+    fn unwatch(&mut self) {
+        match *self {}
+    }
+
+    fn dispatch(&mut self, _next_id: &Cell<u32>) -> Vec<(GamepadId, GamepadEvent)> {
+        match *self {}
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct Watch {
+    inotify: RawFd,
+    devices: Vec<Device>,
+    /// The events which weren't returned by the dispatch yet.
+    events: Vec<(GamepadId, GamepadEvent)>,
+}
+
+#[cfg(target_os = "linux")]
+impl Watch {
+    /// Connects the gamepads of the directory, in the order of their nodes.
+    fn scan(&mut self, epoll: &Epoll, next_id: &Cell<u32>) {
+        let mut nodes: Vec<_> = match fs::read_dir(INPUT_DIR) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name())
+                .filter(|node| is_event_node(node))
+                .collect(),
+            Err(_) => return,
+        };
+        nodes.sort_by_key(|node| {
+            let number = &node.as_bytes()[b"event".len()..];
+            (number.len(), number.to_vec())
+        });
+        for node in nodes {
+            self.connect(node, epoll, next_id);
+        }
+    }
+
+    fn connect(&mut self, node: OsString, epoll: &Epoll, next_id: &Cell<u32>) {
+        if self.devices.iter().any(|device| device.node == node) {
+            return;
+        }
+        let id = GamepadId(next_id.get());
+        let (device, name) = match Device::open(id, node) {
+            Some(device) => device,
+            None => return,
+        };
+        if epoll.add(device.fd).is_err() {
+            return;
+        }
+        next_id.set(next_id.get().wrapping_add(1));
+        self.events.push((id, GamepadEvent::Connected { name }));
+        self.devices.push(device);
+    }
+
+    fn disconnect(&mut self, index: usize) {
+        let device = self.devices.remove(index);
+        self.events.push((device.id, GamepadEvent::Disconnected));
+    }
+
+    /// Reads the changes of the directory, connecting and disconnecting the gamepads.
+    fn read_changes(&mut self, epoll: &Epoll, next_id: &Cell<u32>) {
+        // The buffer is aligned for the events.
+        let mut buffer = [0u32; 1024];
+        loop {
+            let len = unsafe {
+                libc::read(
+                    self.inotify,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    mem::size_of_val(&buffer),
+                )
+            };
+            if len <= 0 {
+                break;
+            }
+            let bytes =
+                unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, len as usize) };
+            let mut offset = 0;
+            while offset + mem::size_of::<libc::inotify_event>() <= bytes.len() {
+                let event: libc::inotify_event =
+                    unsafe { std::ptr::read_unaligned(bytes[offset..].as_ptr() as *const _) };
+                let name_start = offset + mem::size_of::<libc::inotify_event>();
+                let name_end = (name_start + event.len as usize).min(bytes.len());
+                offset = name_end;
+
+                if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                    self.scan(epoll, next_id);
+                    continue;
+                }
+                let name = &bytes[name_start..name_end];
+                let name = match name.iter().position(|&byte| byte == 0) {
+                    Some(end) => &name[..end],
+                    None => name,
+                };
+                let node = OsStr::from_bytes(name).to_owned();
+                if !is_event_node(&node) {
+                    continue;
+                }
+                if event.mask & libc::IN_DELETE != 0 {
+                    if let Some(index) = self.devices.iter().position(|device| device.node == node)
+                    {
+                        self.disconnect(index);
+                    }
+                } else {
+                    self.connect(node, epoll, next_id);
+                }
+            }
+        }
+    }
+
+    fn read_device(&mut self, fd: RawFd) {
+        let index = match self.devices.iter().position(|device| device.fd == fd) {
+            Some(index) => index,
+            None => return,
+        };
+        let mut buffer: [libc::input_event; 64] = unsafe { mem::zeroed() };
+        loop {
+            let len = unsafe {
+                libc::read(
+                    fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    mem::size_of_val(&buffer),
+                )
+            };
+            if len < 0 {
+                // The device is gone once it's unplugged, before its node is removed.
+                if io::Error::last_os_error().raw_os_error() == Some(libc::ENODEV) {
+                    self.disconnect(index);
+                }
+                return;
+            }
+            let count = len as usize / mem::size_of::<libc::input_event>();
+            if count == 0 {
+                return;
+            }
+            let device = &mut self.devices[index];
+            for event in &buffer[..count] {
+                device.handle(event, &mut self.events);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Watch {
+    fn drop(&mut self) {
+        self.devices.clear();
+        unsafe { libc::close(self.inotify) };
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn is_event_node(node: &OsStr) -> bool {
+    let node = node.as_bytes();
+    node.len() > b"event".len() && node.starts_with(b"event")
+}
+
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, PartialEq)]
+enum AxisKind {
+    Axis(GamepadAxis),
+    /// An axis of the hat of the d-pad, whose positions are the presses of its buttons.
+    HatX,
+    HatY,
+}
+
+#[cfg(target_os = "linux")]
+struct Axis {
+    code: u16,
+    kind: AxisKind,
+    minimum: i32,
+    maximum: i32,
+    /// The last value, either the normalized one or the one of the hat.
+    value: f64,
+}
+
+#[cfg(target_os = "linux")]
+struct Device {
+    id: GamepadId,
+    /// The name of the node in the directory.
+    node: OsString,
+    fd: RawFd,
+    axes: Vec<Axis>,
+    /// The bits of the keys which are pressed.
+    keys: [u8; ffi::KEY_CNT / 8],
+    /// Whether the triggers are only analog, so their presses are derived from their axes.
+    analog_triggers: bool,
+    /// Whether the left and the right triggers are pressed, when they're only analog.
+    triggers: [bool; 2],
+    /// Whether some events were dropped, so they're skipped until the next report.
+    dropped: bool,
+    /// Whether the device rumbles, which needs its node to be opened for writing.
+    rumble: bool,
+    /// The identifier of the rumble effect uploaded to the device, or `-1` before the first one.
+    effect: i16,
+}
+
+#[cfg(target_os = "linux")]
+impl Device {
+    /// Opens the node when it's a gamepad or a joystick, giving the device and its name.
+    fn open(id: GamepadId, node: OsString) -> Option<(Device, String)> {
+        let mut path = INPUT_DIR.as_bytes().to_vec();
+        path.push(b'/');
+        path.extend_from_slice(node.as_bytes());
+        let path = CString::new(path).ok()?;
+        let open =
+            |mode| unsafe { libc::open(path.as_ptr(), mode | libc::O_NONBLOCK | libc::O_CLOEXEC) };
+        // The gamepads are still read when they can't be written to.
+        let (fd, writable) = match open(libc::O_RDWR) {
+            fd if fd >= 0 => (fd, true),
+            _ => (open(libc::O_RDONLY), false),
+        };
+        if fd < 0 {
+            return None;
+        }
+        let mut device = Device {
+            id,
+            node,
+            fd,
+            axes: Vec::new(),
+            keys: [0; ffi::KEY_CNT / 8],
+            analog_triggers: false,
+            triggers: [false; 2],
+            dropped: false,
+            rumble: false,
+            effect: -1,
+        };
+
+        let mut key_bits = [0u8; ffi::KEY_CNT / 8];
+        let mut abs_bits = [0u8; ffi::ABS_CNT / 8];
+        let mut ff_bits = [0u8; ffi::FF_CNT / 8];
+        let mut name = [0u8; 256];
+        unsafe {
+            if libc::ioctl(
+                fd,
+                ffi::eviocgbit(ffi::EV_KEY, key_bits.len()) as _,
+                key_bits.as_mut_ptr(),
+            ) < 0
+            {
+                return None;
+            }
+            libc::ioctl(
+                fd,
+                ffi::eviocgbit(ffi::EV_ABS, abs_bits.len()) as _,
+                abs_bits.as_mut_ptr(),
+            );
+            libc::ioctl(
+                fd,
+                ffi::eviocgbit(ffi::EV_FF, ff_bits.len()) as _,
+                ff_bits.as_mut_ptr(),
+            );
+            libc::ioctl(
+                fd,
+                ffi::eviocgkey(device.keys.len()) as _,
+                device.keys.as_mut_ptr(),
+            );
+            libc::ioctl(fd, ffi::eviocgname(name.len() - 1) as _, name.as_mut_ptr());
+        }
+        // The mice, the keyboards and the digitizers don't have the buttons of the joysticks and
+        // of the gamepads.
+        if !(ffi::BTN_JOYSTICK..ffi::BTN_DIGI).any(|code| bit(&key_bits, code)) {
+            return None;
+        }
+
+        // The generic joysticks have their right stick on the Z axes, instead of the triggers.
+        let right_stick_on_rx = bit(&abs_bits, ffi::ABS_RX);
+        for code in 0..ffi::ABS_CNT as u16 {
+            if !bit(&abs_bits, code) {
+                continue;
+            }
+            let kind = match code {
+                ffi::ABS_X => AxisKind::Axis(GamepadAxis::LeftStickX),
+                ffi::ABS_Y => AxisKind::Axis(GamepadAxis::LeftStickY),
+                ffi::ABS_RX => AxisKind::Axis(GamepadAxis::RightStickX),
+                ffi::ABS_RY => AxisKind::Axis(GamepadAxis::RightStickY),
+                ffi::ABS_Z if right_stick_on_rx => AxisKind::Axis(GamepadAxis::LeftTrigger),
+                ffi::ABS_RZ if right_stick_on_rx => AxisKind::Axis(GamepadAxis::RightTrigger),
+                ffi::ABS_Z => AxisKind::Axis(GamepadAxis::RightStickX),
+                ffi::ABS_RZ => AxisKind::Axis(GamepadAxis::RightStickY),
+                ffi::ABS_HAT2Y => AxisKind::Axis(GamepadAxis::LeftTrigger),
+                ffi::ABS_HAT2X => AxisKind::Axis(GamepadAxis::RightTrigger),
+                ffi::ABS_HAT0X => AxisKind::HatX,
+                ffi::ABS_HAT0Y => AxisKind::HatY,
+                code => AxisKind::Axis(GamepadAxis::Other(code)),
+            };
+            let mut info: libc::input_absinfo = unsafe { mem::zeroed() };
+            if unsafe { libc::ioctl(fd, ffi::eviocgabs(code) as _, &mut info) } < 0 {
+                continue;
+            }
+            let mut axis = Axis {
+                code,
+                kind,
+                minimum: info.minimum,
+                maximum: info.maximum,
+                value: 0.,
+            };
+            axis.value = axis.normalize(info.value);
+            device.axes.push(axis);
+        }
+        let has_trigger_axes = device.axes.iter().any(|axis| {
+            axis.kind == AxisKind::Axis(GamepadAxis::LeftTrigger)
+                || axis.kind == AxisKind::Axis(GamepadAxis::RightTrigger)
+        });
+        device.analog_triggers = has_trigger_axes && !bit(&key_bits, ffi::BTN_TL2);
+        device.rumble = writable && bit(&ff_bits, ffi::FF_RUMBLE);
+
+        let name = unsafe { CStr::from_ptr(name.as_ptr() as *const libc::c_char) };
+        Some((device, name.to_string_lossy().into_owned()))
+    }
+
+    fn handle(&mut self, event: &libc::input_event, events: &mut Vec<(GamepadId, GamepadEvent)>) {
+        match (event.type_, event.code) {
+            (ffi::EV_SYN, ffi::SYN_DROPPED) => self.dropped = true,
+            (ffi::EV_SYN, ffi::SYN_REPORT) if self.dropped => {
+                self.dropped = false;
+                self.resync(events);
+            }
+            _ if self.dropped => (),
+            // The repeats of the keys are left out.
+            (ffi::EV_KEY, code) if event.value != 2 => self.key(code, event.value != 0, events),
+            (ffi::EV_ABS, code) => self.abs(code, event.value, events),
+            _ => (),
+        }
+    }
+
+    /// Uploads the rumble effect, replacing the previous one, and plays it.
+    fn rumble(&mut self, strong: f64, weak: f64, duration: Duration) -> io::Result<()> {
+        let magnitude = |value: f64| (value.clamp(0., 1.) * u16::MAX as f64) as u16;
+        let mut effect: libc::ff_effect = unsafe { mem::zeroed() };
+        effect.type_ = ffi::FF_RUMBLE;
+        effect.id = self.effect;
+        effect.replay.length = duration.as_millis().min(u16::MAX as u128) as u16;
+        let rumble = libc::ff_rumble_effect {
+            strong_magnitude: magnitude(strong),
+            weak_magnitude: magnitude(weak),
+        };
+        unsafe {
+            (effect.u.as_mut_ptr() as *mut libc::ff_rumble_effect).write(rumble);
+            if libc::ioctl(self.fd, ffi::eviocsff() as _, &mut effect) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        self.effect = effect.id;
+
+        let mut play: libc::input_event = unsafe { mem::zeroed() };
+        play.type_ = ffi::EV_FF;
+        play.code = effect.id as u16;
+        play.value = 1;
+        let len = unsafe {
+            libc::write(
+                self.fd,
+                &play as *const libc::input_event as *const libc::c_void,
+                mem::size_of::<libc::input_event>(),
+            )
+        };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Catches up with the state of the device, once some of its events were dropped.
+    fn resync(&mut self, events: &mut Vec<(GamepadId, GamepadEvent)>) {
+        let mut keys = [0u8; ffi::KEY_CNT / 8];
+        if unsafe { libc::ioctl(self.fd, ffi::eviocgkey(keys.len()) as _, keys.as_mut_ptr()) } >= 0
+        {
+            for code in 0..ffi::KEY_CNT as u16 {
+                let pressed = bit(&keys, code);
+                if pressed != bit(&self.keys, code) {
+                    self.key(code, pressed, events);
+                }
+            }
+        }
+        let codes: Vec<_> = self.axes.iter().map(|axis| axis.code).collect();
+        for code in codes {
+            let mut info: libc::input_absinfo = unsafe { mem::zeroed() };
+            if unsafe { libc::ioctl(self.fd, ffi::eviocgabs(code) as _, &mut info) } >= 0 {
+                self.abs(code, info.value, events);
+            }
+        }
+    }
+
+    fn key(&mut self, code: u16, pressed: bool, events: &mut Vec<(GamepadId, GamepadEvent)>) {
+        if code as usize >= ffi::KEY_CNT {
+            return;
+        }
+        let mask = 1 << (code % 8);
+        if pressed {
+            self.keys[code as usize / 8] |= mask;
+        } else {
+            self.keys[code as usize / 8] &= !mask;
+        }
+        self.button(button_from_code(code), pressed, events);
+    }
+
+    fn button(
+        &self,
+        button: GamepadButton,
+        pressed: bool,
+        events: &mut Vec<(GamepadId, GamepadEvent)>,
+    ) {
+        let state = if pressed {
+            ElementState::Pressed
+        } else {
+            ElementState::Released
+        };
+        events.push((self.id, GamepadEvent::Button { button, state }));
+    }
+
+    fn abs(&mut self, code: u16, value: i32, events: &mut Vec<(GamepadId, GamepadEvent)>) {
+        let index = match self.axes.iter().position(|axis| axis.code == code) {
+            Some(index) => index,
+            None => return,
+        };
+        let axis = &mut self.axes[index];
+        let kind = axis.kind;
+        match kind {
+            AxisKind::HatX | AxisKind::HatY => {
+                let (negative, positive) = match kind {
+                    AxisKind::HatX => (GamepadButton::DPadLeft, GamepadButton::DPadRight),
+                    _ => (GamepadButton::DPadUp, GamepadButton::DPadDown),
+                };
+                let previous = axis.value;
+                let value = value.signum() as f64;
+                if value == previous {
+                    return;
+                }
+                axis.value = value;
+                if previous < 0. {
+                    self.button(negative, false, events);
+                } else if previous > 0. {
+                    self.button(positive, false, events);
+                }
+                if value < 0. {
+                    self.button(negative, true, events);
+                } else if value > 0. {
+                    self.button(positive, true, events);
+                }
+            }
+            AxisKind::Axis(gamepad_axis) => {
+                let value = axis.normalize(value);
+                if value == axis.value {
+                    return;
+                }
+                axis.value = value;
+                events.push((
+                    self.id,
+                    GamepadEvent::Axis {
+                        axis: gamepad_axis,
+                        value,
+                    },
+                ));
+
+                let trigger = match gamepad_axis {
+                    GamepadAxis::LeftTrigger if self.analog_triggers => 0,
+                    GamepadAxis::RightTrigger if self.analog_triggers => 1,
+                    _ => return,
+                };
+                let pressed = value > TRIGGER_THRESHOLD;
+                if pressed != self.triggers[trigger] {
+                    self.triggers[trigger] = pressed;
+                    let button = match trigger {
+                        0 => GamepadButton::LeftTrigger,
+                        _ => GamepadButton::RightTrigger,
+                    };
+                    self.button(button, pressed, events);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Axis {
+    /// Maps the value to the range of the axis, the y axes of the sticks of the devices pointing
+    /// down.
+    fn normalize(&self, value: i32) -> f64 {
+        if let AxisKind::HatX | AxisKind::HatY = self.kind {
+            return value.signum() as f64;
+        }
+        let range = self.maximum as f64 - self.minimum as f64;
+        if range <= 0. {
+            return 0.;
+        }
+        let unit = ((value as f64 - self.minimum as f64) / range).clamp(0., 1.);
+        match self.kind {
+            AxisKind::Axis(GamepadAxis::LeftTrigger)
+            | AxisKind::Axis(GamepadAxis::RightTrigger) => unit,
+            AxisKind::Axis(GamepadAxis::LeftStickY) | AxisKind::Axis(GamepadAxis::RightStickY) => {
+                1. - 2. * unit
+            }
+            _ => 2. * unit - 1.,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn bit(bits: &[u8], code: u16) -> bool {
+    matches!(bits.get(code as usize / 8), Some(byte) if byte & (1 << (code % 8)) != 0)
+}
+
+#[cfg(target_os = "linux")]
+fn button_from_code(code: u16) -> GamepadButton {
+    match code {
+        ffi::BTN_SOUTH => GamepadButton::South,
+        ffi::BTN_EAST => GamepadButton::East,
+        ffi::BTN_NORTH => GamepadButton::North,
+        ffi::BTN_WEST => GamepadButton::West,
+        ffi::BTN_TL => GamepadButton::LeftBumper,
+        ffi::BTN_TR => GamepadButton::RightBumper,
+        ffi::BTN_TL2 => GamepadButton::LeftTrigger,
+        ffi::BTN_TR2 => GamepadButton::RightTrigger,
+        ffi::BTN_SELECT => GamepadButton::Select,
+        ffi::BTN_START => GamepadButton::Start,
+        ffi::BTN_MODE => GamepadButton::Mode,
+        ffi::BTN_THUMBL => GamepadButton::LeftStick,
+        ffi::BTN_THUMBR => GamepadButton::RightStick,
+        ffi::BTN_DPAD_UP => GamepadButton::DPadUp,
+        ffi::BTN_DPAD_DOWN => GamepadButton::DPadDown,
+        ffi::BTN_DPAD_LEFT => GamepadButton::DPadLeft,
+        ffi::BTN_DPAD_RIGHT => GamepadButton::DPadRight,
+        code => GamepadButton::Other(code),
+    }
+}
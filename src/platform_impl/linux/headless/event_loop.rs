@@ -0,0 +1,784 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    io,
+    marker::PhantomData,
+    mem,
+    os::unix::io::RawFd,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    clipboard::{ClipboardKind, ClipboardProvider},
+    dpi::PhysicalPosition,
+    error::{ExternalError, NotSupportedError},
+    event::{DeviceEvent, Event, ModifiersState, StartCause, WindowEvent},
+    event_loop::{
+        ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW, SourceInterest,
+        SourceToken, Timer, TimerId,
+    },
+    gamepad::GamepadId,
+    icon::{BadIcon, RgbaIcon},
+    monitor::{MonitorEvent, MonitorHandle as RootMonitorHandle},
+    platform::{headless::VirtualMonitor, pump_events::PumpStatus},
+    platform_impl::{
+        platform::{
+            gamepad::Gamepads, min_timeout, precise_wait::PreciseWait, sticky_exit_callback,
+            timer::Timers,
+        },
+        MonitorHandle as PlatformMonitorHandle, OsError,
+    },
+    window::CursorFrame,
+};
+
+use super::{
+    input::{modifiers_events, InputState},
+    make_did, make_wid,
+    window::WindowState,
+    CustomCursor, MonitorHandle, WindowId,
+};
+
+/// The state shared by the event loop and its windows, which may live on other threads.
+pub(super) struct Shared {
+    /// The events sent in the next iteration of the event loop.
+    pending_events: Mutex<VecDeque<PendingEvent>>,
+    wake_pipe: WakePipe,
+    monitors: Mutex<Vec<MonitorHandle>>,
+    windows: Mutex<HashMap<WindowId, Weak<Mutex<WindowState>>>>,
+    focused_window: Mutex<Option<WindowId>>,
+    pub(super) input: Mutex<InputState>,
+    next_window_id: AtomicU64,
+}
+
+pub(super) enum PendingEvent {
+    Window(WindowId, WindowEvent<'static>),
+    Device(DeviceEvent),
+    Monitor(MonitorHandle, MonitorEvent),
+    MonitorsChanged(Vec<MonitorHandle>, Vec<MonitorHandle>),
+    Redraw(WindowId),
+}
+
+impl Shared {
+    pub fn push_event(&self, event: PendingEvent) {
+        self.pending_events.lock().unwrap().push_back(event);
+        self.wake_pipe.wake_up();
+    }
+
+    pub fn monitors(&self) -> Vec<MonitorHandle> {
+        self.monitors.lock().unwrap().clone()
+    }
+
+    pub fn register_window(&self, state: &Arc<Mutex<WindowState>>) -> WindowId {
+        let id = WindowId(self.next_window_id.fetch_add(1, Ordering::Relaxed));
+        self.windows
+            .lock()
+            .unwrap()
+            .insert(id, Arc::downgrade(state));
+        id
+    }
+
+    pub fn unregister_window(&self, id: WindowId) {
+        self.windows.lock().unwrap().remove(&id);
+        let mut focused_window = self.focused_window.lock().unwrap();
+        if *focused_window == Some(id) {
+            *focused_window = None;
+        }
+    }
+
+    pub fn focused_window(&self) -> Option<WindowId> {
+        *self.focused_window.lock().unwrap()
+    }
+
+    /// Gives the focus to the window `id`, taking it from the window which had it.
+    pub fn focus_window(&self, id: WindowId) {
+        let mut focused_window = self.focused_window.lock().unwrap();
+        if *focused_window == Some(id) {
+            return;
+        }
+        if let Some(previous) = focused_window.replace(id) {
+            self.push_focus_events(previous, false);
+        }
+        self.push_focus_events(id, true);
+    }
+
+    /// Sends the `Focused` event along with the modifiers held, which leave the window losing the
+    /// focus and come to the one gaining it.
+    fn push_focus_events(&self, id: WindowId, focused: bool) {
+        let modifiers = self.input.lock().unwrap().modifiers();
+        if !modifiers.is_empty() {
+            let modifiers = if focused {
+                modifiers
+            } else {
+                ModifiersState::empty()
+            };
+            for event in modifiers_events(modifiers).iter().cloned() {
+                self.push_event(PendingEvent::Window(id, event));
+            }
+        }
+        self.push_event(PendingEvent::Window(id, WindowEvent::Focused(focused)));
+    }
+}
+
+/// A pipe waking up the poll of the event loop.
+struct WakePipe {
+    read: RawFd,
+    write: RawFd,
+}
+
+impl WakePipe {
+    fn new() -> io::Result<Self> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(WakePipe {
+            read: fds[0],
+            write: fds[1],
+        })
+    }
+
+    fn wake_up(&self) {
+        // The pipe is only full when the event loop already has to wake up.
+        unsafe { libc::write(self.write, [1u8].as_ptr() as *const _, 1) };
+    }
+
+    fn drain(&self) {
+        let mut buf = [0u8; 64];
+        while unsafe { libc::read(self.read, buf.as_mut_ptr() as *mut _, buf.len()) } > 0 {}
+    }
+}
+
+impl Drop for WakePipe {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.read);
+            libc::close(self.write);
+        }
+    }
+}
+
+pub struct EventLoopWindowTarget<T> {
+    pub(super) shared: Arc<Shared>,
+    /// The timers set by the user.
+    timers: RefCell<Timers>,
+    precise_wait: PreciseWait,
+    gamepads: Gamepads,
+    _marker: PhantomData<T>,
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    #[inline]
+    pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
+        self.shared.monitors().into_iter().collect()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<RootMonitorHandle> {
+        self.shared
+            .monitors()
+            .into_iter()
+            .next()
+            .map(|monitor| RootMonitorHandle {
+                inner: PlatformMonitorHandle::Headless(monitor),
+            })
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        _image: RgbaIcon,
+        _hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor)
+    }
+
+    #[inline]
+    pub fn create_animated_cursor(
+        &self,
+        _frames: Vec<CursorFrame>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor)
+    }
+
+    #[inline]
+    pub fn set_timer(&self, id: TimerId, timer: Timer) -> Result<(), ExternalError> {
+        // The poll waits for the timers once the callback returns.
+        self.timers.borrow_mut().set(id, timer);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn cancel_timer(&self, id: TimerId) -> bool {
+        self.timers.borrow_mut().cancel(id)
+    }
+
+    #[inline]
+    pub fn set_precise_wait(&self, precise: bool) -> Result<(), ExternalError> {
+        self.precise_wait.set_enabled(precise).map_err(|_| {
+            ExternalError::Os(os_error!(OsError::HeadlessMisc(
+                "Failed to create the timer of the precise waits"
+            )))
+        })
+    }
+
+    #[inline]
+    pub fn set_gamepads_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        self.gamepads
+            .set_enabled(enabled, |message| os_error!(OsError::HeadlessMisc(message)))
+    }
+
+    pub fn rumble_gamepad(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        self.gamepads
+            .rumble(gamepad_id, strong, weak, duration, |message| {
+                os_error!(OsError::HeadlessMisc(message))
+            })
+    }
+
+    pub fn set_virtual_monitors(&self, monitors: Vec<VirtualMonitor>) {
+        let monitors = monitors
+            .into_iter()
+            .enumerate()
+            .map(|(id, monitor)| MonitorHandle::new(id as u32, monitor))
+            .collect::<Vec<_>>();
+        let previous = mem::replace(&mut *self.shared.monitors.lock().unwrap(), monitors.clone());
+
+        // The monitors being told apart by their index, only the trailing ones come and go.
+        let added = monitors
+            .iter()
+            .skip(previous.len())
+            .cloned()
+            .collect::<Vec<_>>();
+        let removed = previous
+            .iter()
+            .skip(monitors.len())
+            .cloned()
+            .collect::<Vec<_>>();
+        if !added.is_empty() || !removed.is_empty() {
+            self.shared
+                .push_event(PendingEvent::MonitorsChanged(added, removed));
+        }
+
+        let windows = self.shared.windows.lock().unwrap().clone();
+        for (window_id, state) in windows {
+            let position = match state.upgrade() {
+                Some(state) => state.lock().unwrap().position,
+                None => continue,
+            };
+            let refresh_rate = |monitors: &[MonitorHandle]| {
+                MonitorHandle::at(monitors, position).map(MonitorHandle::refresh_rate_millihertz)
+            };
+            if let Some(new_refresh_rate) = refresh_rate(&monitors) {
+                if refresh_rate(&previous) != Some(new_refresh_rate) {
+                    let event = WindowEvent::RefreshRateChanged(new_refresh_rate);
+                    self.shared
+                        .push_event(PendingEvent::Window(window_id, event));
+                }
+            }
+        }
+
+        for monitor in monitors {
+            let capabilities = monitor.color_capabilities();
+            let changed = previous.iter().any(|previous| {
+                *previous == monitor && previous.color_capabilities() != capabilities
+            });
+            if changed {
+                let event = MonitorEvent::ColorChanged(capabilities);
+                self.shared
+                    .push_event(PendingEvent::Monitor(monitor, event));
+            }
+        }
+    }
+
+    pub fn inject_window_event(&self, window_id: WindowId, event: WindowEvent<'static>) {
+        let state = self
+            .shared
+            .windows
+            .lock()
+            .unwrap()
+            .get(&window_id)
+            .and_then(Weak::upgrade);
+        if let Some(state) = state {
+            let mut state = state.lock().unwrap();
+            match event {
+                WindowEvent::Resized(size) => state.size = size,
+                WindowEvent::Moved(position) => state.position = position,
+                WindowEvent::KeyboardInput { input, .. } if input.repeat && !state.key_repeat => {
+                    return
+                }
+                _ => (),
+            }
+        }
+        if let WindowEvent::Focused(focused) = event {
+            let mut focused_window = self.shared.focused_window.lock().unwrap();
+            if focused {
+                *focused_window = Some(window_id);
+            } else if *focused_window == Some(window_id) {
+                *focused_window = None;
+            }
+        }
+        self.shared
+            .push_event(PendingEvent::Window(window_id, event));
+    }
+
+    pub fn inject_device_event(&self, event: DeviceEvent) {
+        self.shared.push_event(PendingEvent::Device(event));
+    }
+}
+
+pub struct EventLoop<T: 'static> {
+    user_receiver: mpsc::Receiver<T>,
+    user_sender: mpsc::Sender<T>,
+    target: Rc<RootELW<T>>,
+    /// The control flow set by the callback, which is kept between the calls to `pump_events`.
+    control_flow: ControlFlow,
+    /// Whether `NewEvents(Init)` was sent, and `LoopDestroyed` is yet to be.
+    loop_running: bool,
+    /// The external sources registered by the user.
+    sources: HashMap<SourceToken, (RawFd, SourceInterest)>,
+    /// The external sources reported ready by the latest poll.
+    ready_sources: Vec<SourceToken>,
+}
+
+pub struct EventLoopProxy<T: 'static> {
+    user_sender: mpsc::Sender<T>,
+    shared: Arc<Shared>,
+}
+
+pub struct EventLoopWakeup {
+    shared: Arc<Shared>,
+}
+
+impl<T: 'static> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        EventLoopProxy {
+            user_sender: self.user_sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T: 'static> EventLoop<T> {
+    pub fn new() -> io::Result<EventLoop<T>> {
+        let shared = Arc::new(Shared {
+            pending_events: Mutex::new(VecDeque::new()),
+            wake_pipe: WakePipe::new()?,
+            monitors: Mutex::new(vec![MonitorHandle::new(0, VirtualMonitor::default())]),
+            windows: Mutex::new(HashMap::new()),
+            focused_window: Mutex::new(None),
+            input: Default::default(),
+            next_window_id: AtomicU64::new(1),
+        });
+        let (user_sender, user_receiver) = mpsc::channel();
+        let target = Rc::new(RootELW {
+            p: crate::platform_impl::EventLoopWindowTarget::Headless(EventLoopWindowTarget {
+                shared,
+                timers: Default::default(),
+                precise_wait: Default::default(),
+                gamepads: Default::default(),
+                _marker: PhantomData,
+            }),
+            _marker: PhantomData,
+        });
+
+        Ok(EventLoop {
+            user_receiver,
+            user_sender,
+            target,
+            control_flow: ControlFlow::default(),
+            loop_running: false,
+            sources: HashMap::new(),
+            ready_sources: Vec::new(),
+        })
+    }
+
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            user_sender: self.user_sender.clone(),
+            shared: get_target(&self.target).shared.clone(),
+        }
+    }
+
+    pub fn create_wakeup(&self) -> EventLoopWakeup {
+        EventLoopWakeup {
+            shared: get_target(&self.target).shared.clone(),
+        }
+    }
+
+    pub(crate) fn window_target(&self) -> &RootELW<T> {
+        &self.target
+    }
+
+    pub fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError> {
+        if self.sources.contains_key(&token) {
+            return Err(ExternalError::Os(os_error!(OsError::HeadlessMisc(
+                "The token of the source is already in use"
+            ))));
+        }
+        if interest.is_empty() {
+            return Err(ExternalError::Os(os_error!(OsError::HeadlessMisc(
+                "The source has no interest"
+            ))));
+        }
+        self.sources.insert(token, (fd, interest));
+        Ok(())
+    }
+
+    pub fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        self.sources.remove(&token).ok_or_else(|| {
+            ExternalError::Os(os_error!(OsError::HeadlessMisc(
+                "There's no source with this token"
+            )))
+        })?;
+        self.ready_sources.retain(|ready| *ready != token);
+        Ok(())
+    }
+
+    pub fn run_return<F>(&mut self, mut callback: F) -> i32
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                return code;
+            }
+        }
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
+            self.control_flow = ControlFlow::default();
+            self.single_iteration(&mut callback, StartCause::Init);
+        }
+
+        // The first iteration may have already asked to exit.
+        if !matches!(self.control_flow, ControlFlow::ExitWithCode(_)) {
+            self.poll_events_with_timeout(timeout, &mut callback);
+        }
+
+        if let ControlFlow::ExitWithCode(code) = self.control_flow {
+            self.loop_running = false;
+            callback(Event::LoopDestroyed, &self.target, &mut self.control_flow);
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
+
+    pub fn run<F>(mut self, callback: F) -> !
+    where
+        F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let exit_code = self.run_return(callback);
+        ::std::process::exit(exit_code);
+    }
+
+    /// Waits for the events for as long as both the control flow and the timeout allow, then
+    /// dispatches them.
+    fn poll_events_with_timeout<F>(&mut self, timeout: Option<Duration>, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let precise = get_target(&self.target).precise_wait.arm(self.control_flow);
+
+        let start = Instant::now();
+        let control_flow_timeout = match self.control_flow {
+            ControlFlow::Poll => Some(Duration::from_millis(0)),
+            ControlFlow::Wait => None,
+            // The timer of the precise waits wakes the poll up.
+            ControlFlow::WaitUntil(_) if precise => None,
+            ControlFlow::WaitUntil(deadline) => Some(deadline.saturating_duration_since(start)),
+            ControlFlow::ExitWithCode(_) => unreachable!(),
+        };
+        let timeout = min_timeout(control_flow_timeout, timeout);
+        let timer_timeout = get_target(&self.target)
+            .timers
+            .borrow()
+            .next_deadline()
+            .map(|deadline| deadline.saturating_duration_since(start));
+        let timeout = min_timeout(timer_timeout, timeout);
+        self.poll(timeout);
+
+        let cause = match self.control_flow {
+            ControlFlow::Poll => StartCause::Poll,
+            ControlFlow::WaitUntil(deadline) if Instant::now() >= deadline => {
+                StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: deadline,
+                }
+            }
+            ControlFlow::WaitUntil(deadline) => StartCause::WaitCancelled {
+                start,
+                requested_resume: Some(deadline),
+            },
+            _ => StartCause::WaitCancelled {
+                start,
+                requested_resume: None,
+            },
+        };
+        self.single_iteration(callback, cause);
+    }
+
+    /// Polls the wake pipe, the timer of the precise waits and the external sources, collecting
+    /// the ready sources.
+    fn poll(&mut self, timeout: Option<Duration>) {
+        let target = get_target(&self.target);
+        let shared = &target.shared;
+        let precise_wait_timer = target.precise_wait.timer();
+        let mut fds = vec![libc::pollfd {
+            fd: shared.wake_pipe.read,
+            events: libc::POLLIN,
+            revents: 0,
+        }];
+        if let Some(timer) = &precise_wait_timer {
+            fds.push(libc::pollfd {
+                fd: timer.fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            });
+        }
+        // The gamepads are read on each iteration, so the poll only has to wake up for them.
+        if let Some(fd) = target.gamepads.fd() {
+            fds.push(libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            });
+        }
+        let first_source = fds.len();
+        let tokens: Vec<_> = self.sources.keys().copied().collect();
+        for token in &tokens {
+            let (fd, interest) = self.sources[token];
+            let mut events = 0;
+            if interest.contains(SourceInterest::READABLE) {
+                events |= libc::POLLIN;
+            }
+            if interest.contains(SourceInterest::WRITABLE) {
+                events |= libc::POLLOUT;
+            }
+            fds.push(libc::pollfd {
+                fd,
+                events,
+                revents: 0,
+            });
+        }
+
+        // The timeout is rounded up, so the deadlines aren't missed by a fraction of a
+        // millisecond.
+        let timeout = match timeout {
+            Some(timeout) => {
+                let millis = (timeout + Duration::from_nanos(999_999)).as_millis();
+                millis.min(libc::c_int::MAX as u128) as libc::c_int
+            }
+            None => -1,
+        };
+        // An interrupted poll is just an early wakeup.
+        if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) } <= 0 {
+            return;
+        }
+
+        if fds[0].revents != 0 {
+            shared.wake_pipe.drain();
+        }
+        if let Some(timer) = &precise_wait_timer {
+            if fds[1].revents != 0 {
+                timer.clear();
+            }
+        }
+        for (token, fd) in tokens.into_iter().zip(&fds[first_source..]) {
+            if fd.revents != 0 && !self.ready_sources.contains(&token) {
+                self.ready_sources.push(token);
+            }
+        }
+    }
+
+    fn single_iteration<F>(&mut self, callback: &mut F, cause: StartCause)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let mut control_flow = self.control_flow;
+
+        sticky_exit_callback(
+            Event::NewEvents(cause),
+            &self.target,
+            &mut control_flow,
+            callback,
+        );
+
+        // The events are taken first, so the ones queued by the callback wait for the next
+        // iteration.
+        let mut redraw_requests = Vec::new();
+        let pending_events = mem::take(
+            &mut *get_target(&self.target)
+                .shared
+                .pending_events
+                .lock()
+                .unwrap(),
+        );
+        for event in pending_events {
+            let event = match event {
+                PendingEvent::Window(window_id, event) => Event::WindowEvent {
+                    window_id: make_wid(window_id),
+                    event,
+                },
+                PendingEvent::Device(event) => Event::DeviceEvent {
+                    device_id: make_did(),
+                    event,
+                },
+                PendingEvent::Monitor(monitor, event) => Event::MonitorEvent {
+                    monitor: RootMonitorHandle {
+                        inner: PlatformMonitorHandle::Headless(monitor),
+                    },
+                    event,
+                },
+                PendingEvent::MonitorsChanged(added, removed) => {
+                    let wrap = |monitor| RootMonitorHandle {
+                        inner: PlatformMonitorHandle::Headless(monitor),
+                    };
+                    Event::MonitorsChanged {
+                        added: added.into_iter().map(wrap).collect(),
+                        removed: removed.into_iter().map(wrap).collect(),
+                    }
+                }
+                PendingEvent::Redraw(window_id) => {
+                    if !redraw_requests.contains(&window_id) {
+                        redraw_requests.push(window_id);
+                    }
+                    continue;
+                }
+            };
+            sticky_exit_callback(event, &self.target, &mut control_flow, callback);
+        }
+
+        // Empty the user event buffer
+        while let Ok(event) = self.user_receiver.try_recv() {
+            sticky_exit_callback(
+                Event::UserEvent(event),
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+        // Send the fired timers
+        let fired = get_target(&self.target)
+            .timers
+            .borrow_mut()
+            .take_fired(Instant::now());
+        for id in fired {
+            sticky_exit_callback(Event::Timer(id), &self.target, &mut control_flow, callback);
+        }
+        // Send the events of the gamepads
+        let gamepad_events = get_target(&self.target).gamepads.dispatch();
+        for (gamepad_id, event) in gamepad_events {
+            sticky_exit_callback(
+                Event::GamepadEvent { gamepad_id, event },
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+        // Report the ready external sources, which the poll reports again for as long as
+        // they stay ready.
+        for token in mem::take(&mut self.ready_sources) {
+            sticky_exit_callback(
+                Event::ExternalSource(token),
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.target,
+            &mut control_flow,
+            callback,
+        );
+
+        // The redraws requested so far are sent in this iteration, including the ones requested
+        // while handling `MainEventsCleared`.
+        get_target(&self.target)
+            .shared
+            .pending_events
+            .lock()
+            .unwrap()
+            .retain(|event| match event {
+                PendingEvent::Redraw(window_id) => {
+                    if !redraw_requests.contains(window_id) {
+                        redraw_requests.push(*window_id);
+                    }
+                    false
+                }
+                _ => true,
+            });
+        for window_id in redraw_requests {
+            sticky_exit_callback(
+                Event::RedrawRequested(make_wid(window_id)),
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.target,
+            &mut control_flow,
+            callback,
+        );
+
+        self.control_flow = control_flow;
+    }
+}
+
+pub(super) fn get_target<T>(target: &RootELW<T>) -> &EventLoopWindowTarget<T> {
+    match target.p {
+        crate::platform_impl::EventLoopWindowTarget::Headless(ref target) => target,
+        #[cfg(any(feature = "x11", feature = "wayland"))]
+        _ => unreachable!(),
+    }
+}
+
+impl<T: 'static> EventLoopProxy<T> {
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.user_sender
+            .send(event)
+            .map_err(|mpsc::SendError(event)| EventLoopClosed(event))?;
+        self.shared.wake_pipe.wake_up();
+        Ok(())
+    }
+}
+
+impl EventLoopWakeup {
+    pub fn wake_up(&self) {
+        self.shared.wake_pipe.wake_up();
+    }
+}
@@ -0,0 +1,301 @@
+//! The synthesized input, which keeps the state of the keyboard, the pointer and the touches like
+//! a display server would, and sends the same events as the real input of the other backends.
+
+use std::{collections::HashMap, time::Instant};
+
+use crate::{
+    dpi::PhysicalPosition,
+    error::ExternalError,
+    event::{
+        DeviceEvent, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+        ScanCode, Touch, TouchPhase, VirtualKeyCode, WindowEvent,
+    },
+    keymap::LockKeys,
+};
+
+use super::{make_did, EventLoopWindowTarget, WindowId};
+
+#[derive(Default)]
+pub(super) struct InputState {
+    /// The keys held on the keyboard, with their virtual keycode.
+    pressed_keys: HashMap<ScanCode, Option<VirtualKeyCode>>,
+    /// The lock keys which are on, toggled by the presses of the keys.
+    lock_keys: LockKeys,
+    /// The window under the pointer, and the position of the pointer on it.
+    pointer: Option<(WindowId, PhysicalPosition<f64>)>,
+    /// The window each of the touches in progress started on.
+    touches: HashMap<u64, WindowId>,
+}
+
+impl InputState {
+    /// The modifiers of the modifier keys held on the keyboard.
+    pub fn modifiers(&self) -> ModifiersState {
+        self.pressed_keys
+            .values()
+            .filter_map(|&key| key.map(key_modifier))
+            .fold(ModifiersState::empty(), |modifiers, modifier| {
+                modifiers | modifier
+            })
+    }
+}
+
+fn key_modifier(key: VirtualKeyCode) -> ModifiersState {
+    match key {
+        VirtualKeyCode::LShift | VirtualKeyCode::RShift => ModifiersState::SHIFT,
+        VirtualKeyCode::LControl | VirtualKeyCode::RControl => ModifiersState::CTRL,
+        VirtualKeyCode::LAlt | VirtualKeyCode::RAlt => ModifiersState::ALT,
+        VirtualKeyCode::LWin | VirtualKeyCode::RWin => ModifiersState::LOGO,
+        _ => ModifiersState::empty(),
+    }
+}
+
+fn key_lock(key: VirtualKeyCode) -> LockKeys {
+    match key {
+        VirtualKeyCode::Capital => LockKeys::CAPS_LOCK,
+        VirtualKeyCode::Numlock => LockKeys::NUM_LOCK,
+        VirtualKeyCode::Scroll => LockKeys::SCROLL_LOCK,
+        _ => LockKeys::empty(),
+    }
+}
+
+/// The events telling a window of a change of the modifiers.
+pub(super) fn modifiers_events(modifiers: ModifiersState) -> [WindowEvent<'static>; 2] {
+    [
+        WindowEvent::ModifiersChanged(modifiers),
+        WindowEvent::DeviceModifiersChanged {
+            device_id: make_did(),
+            modifiers,
+        },
+    ]
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn inject_key(
+        &self,
+        scancode: ScanCode,
+        virtual_keycode: Option<VirtualKeyCode>,
+        state: ElementState,
+    ) {
+        let mut input = self.shared.input.lock().unwrap();
+        let old_modifiers = input.modifiers();
+        let repeat = match state {
+            ElementState::Pressed => {
+                let repeat = input
+                    .pressed_keys
+                    .insert(scancode, virtual_keycode)
+                    .is_some();
+                if let (Some(key), false) = (virtual_keycode, repeat) {
+                    input.lock_keys.toggle(key_lock(key));
+                }
+                repeat
+            }
+            ElementState::Released => {
+                if input.pressed_keys.remove(&scancode).is_none() {
+                    return;
+                }
+                false
+            }
+        };
+        let modifiers = input.modifiers();
+        drop(input);
+
+        #[allow(deprecated)]
+        let keyboard_input = KeyboardInput {
+            scancode,
+            state,
+            repeat,
+            virtual_keycode,
+            modifiers,
+        };
+        self.inject_device_event(DeviceEvent::Key(keyboard_input));
+        if let Some(window_id) = self.shared.focused_window() {
+            if modifiers != old_modifiers {
+                for event in modifiers_events(modifiers).iter().cloned() {
+                    self.inject_window_event(window_id, event);
+                }
+            }
+            self.inject_window_event(
+                window_id,
+                WindowEvent::KeyboardInput {
+                    device_id: make_did(),
+                    input: keyboard_input,
+                    is_synthetic: false,
+                },
+            );
+        }
+    }
+
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        Ok(self.shared.input.lock().unwrap().lock_keys)
+    }
+
+    pub fn set_lock_keys(&self, lock_keys: LockKeys, on: bool) -> Result<(), ExternalError> {
+        self.shared
+            .input
+            .lock()
+            .unwrap()
+            .lock_keys
+            .set(lock_keys, on);
+        Ok(())
+    }
+
+    pub fn inject_text(&self, text: &str) {
+        if let Some(window_id) = self.shared.focused_window() {
+            for c in text.chars() {
+                self.inject_window_event(window_id, WindowEvent::ReceivedCharacter(c));
+            }
+        }
+    }
+
+    pub fn inject_pointer_motion(&self, window_id: WindowId, position: PhysicalPosition<f64>) {
+        let previous = self
+            .shared
+            .input
+            .lock()
+            .unwrap()
+            .pointer
+            .replace((window_id, position));
+        match previous {
+            Some((previous_id, previous_position)) if previous_id == window_id => {
+                self.inject_device_event(DeviceEvent::MouseMotion {
+                    delta: (
+                        position.x - previous_position.x,
+                        position.y - previous_position.y,
+                    ),
+                });
+            }
+            _ => {
+                // The windows have no common space, so the pointer leaves where it was.
+                if let Some((previous_id, previous_position)) = previous {
+                    self.inject_window_event(
+                        previous_id,
+                        WindowEvent::CursorLeft {
+                            device_id: make_did(),
+                            position: previous_position,
+                        },
+                    );
+                }
+                self.inject_window_event(
+                    window_id,
+                    WindowEvent::CursorEntered {
+                        device_id: make_did(),
+                        position,
+                    },
+                );
+            }
+        }
+        #[allow(deprecated)]
+        self.inject_window_event(
+            window_id,
+            WindowEvent::CursorMoved {
+                device_id: make_did(),
+                position,
+                modifiers: self.shared.input.lock().unwrap().modifiers(),
+            },
+        );
+    }
+
+    pub fn inject_pointer_leave(&self) {
+        let previous = self.shared.input.lock().unwrap().pointer.take();
+        if let Some((window_id, position)) = previous {
+            self.inject_window_event(
+                window_id,
+                WindowEvent::CursorLeft {
+                    device_id: make_did(),
+                    position,
+                },
+            );
+        }
+    }
+
+    pub fn inject_mouse_button(&self, button: MouseButton, state: ElementState) {
+        // The ids of the buttons are the ones of X11.
+        let button_id = match button {
+            MouseButton::Left => 1,
+            MouseButton::Middle => 2,
+            MouseButton::Right => 3,
+            MouseButton::Back => 8,
+            MouseButton::Forward => 9,
+            MouseButton::Other(button) => button as u32 + 4,
+        };
+        self.inject_device_event(DeviceEvent::Button {
+            button: button_id,
+            state,
+        });
+        let input = self.shared.input.lock().unwrap();
+        let (window_id, modifiers) = match input.pointer {
+            Some((window_id, _)) => (window_id, input.modifiers()),
+            None => return,
+        };
+        drop(input);
+        #[allow(deprecated)]
+        self.inject_window_event(
+            window_id,
+            WindowEvent::MouseInput {
+                device_id: make_did(),
+                state,
+                button,
+                modifiers,
+            },
+        );
+    }
+
+    pub fn inject_mouse_wheel(&self, delta: MouseScrollDelta) {
+        self.inject_device_event(DeviceEvent::MouseWheel { delta });
+        let input = self.shared.input.lock().unwrap();
+        let (window_id, modifiers) = match input.pointer {
+            Some((window_id, _)) => (window_id, input.modifiers()),
+            None => return,
+        };
+        drop(input);
+        #[allow(deprecated)]
+        self.inject_window_event(
+            window_id,
+            WindowEvent::MouseWheel {
+                device_id: make_did(),
+                delta,
+                phase: TouchPhase::Moved,
+                source: None,
+                modifiers,
+            },
+        );
+    }
+
+    pub fn inject_touch(
+        &self,
+        window_id: WindowId,
+        id: u64,
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+    ) {
+        let mut input = self.shared.input.lock().unwrap();
+        // The touches stay on the window they started on.
+        let window_id = match phase {
+            TouchPhase::Started => {
+                input.touches.insert(id, window_id);
+                window_id
+            }
+            TouchPhase::Moved => match input.touches.get(&id) {
+                Some(&window_id) => window_id,
+                None => return,
+            },
+            TouchPhase::Ended | TouchPhase::Cancelled => match input.touches.remove(&id) {
+                Some(window_id) => window_id,
+                None => return,
+            },
+        };
+        drop(input);
+        self.inject_window_event(
+            window_id,
+            WindowEvent::Touch(Touch {
+                device_id: make_did(),
+                phase,
+                location,
+                time: Instant::now(),
+                force: None,
+                id,
+                palm: false,
+            }),
+        );
+    }
+}
@@ -0,0 +1,46 @@
+#![cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWakeup, EventLoopWindowTarget};
+pub use monitor::{MonitorHandle, VideoMode};
+pub use window::{ChildSurface, CustomCursor, Window};
+
+mod event_loop;
+mod input;
+mod monitor;
+mod window;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceId;
+
+impl DeviceId {
+    #[cfg(not(any(feature = "x11", feature = "wayland")))]
+    pub unsafe fn dummy() -> Self {
+        DeviceId
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowId(u64);
+
+impl WindowId {
+    #[cfg(not(any(feature = "x11", feature = "wayland")))]
+    pub unsafe fn dummy() -> Self {
+        WindowId(0)
+    }
+}
+
+#[inline]
+fn make_did() -> crate::event::DeviceId {
+    crate::event::DeviceId(crate::platform_impl::DeviceId::Headless(DeviceId))
+}
+
+#[inline]
+fn make_wid(id: WindowId) -> crate::window::WindowId {
+    crate::window::WindowId(crate::platform_impl::WindowId::Headless(id))
+}
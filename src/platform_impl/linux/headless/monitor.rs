@@ -0,0 +1,163 @@
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    monitor::{
+        ColorCapabilities, MonitorHandle as RootMonitorHandle, MonitorIdentity,
+        VideoMode as RootVideoMode,
+    },
+    platform::headless::VirtualMonitor,
+    platform_impl::{MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode},
+};
+
+/// A monitor set by the user, which is told apart from the others by its `id`.
+#[derive(Debug, Clone)]
+pub struct MonitorHandle {
+    id: u32,
+    monitor: Arc<VirtualMonitor>,
+}
+
+impl MonitorHandle {
+    pub(super) fn new(id: u32, monitor: VirtualMonitor) -> Self {
+        MonitorHandle {
+            id,
+            monitor: Arc::new(monitor),
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> Option<String> {
+        Some(self.monitor.name.clone())
+    }
+
+    #[inline]
+    pub fn native_identifier(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.monitor.size
+    }
+
+    #[inline]
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        self.monitor.position
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.monitor.scale_factor
+    }
+
+    #[inline]
+    pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
+        std::iter::once(RootVideoMode {
+            video_mode: PlatformVideoMode::Headless(VideoMode {
+                monitor: self.clone(),
+            }),
+        })
+    }
+
+    #[inline]
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        self.monitor.color_capabilities.clone()
+    }
+
+    pub fn supports_variable_refresh(&self) -> bool {
+        self.monitor.variable_refresh
+    }
+
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        self.monitor.identity.clone()
+    }
+
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        self.monitor
+            .work_area
+            .unwrap_or((self.monitor.position, self.monitor.size))
+    }
+
+    /// Whether the `position` is on the monitor.
+    pub(super) fn contains(&self, position: PhysicalPosition<i32>) -> bool {
+        let origin = self.monitor.position;
+        let size = self.monitor.size;
+        position.x >= origin.x
+            && position.y >= origin.y
+            && (position.x - origin.x) < size.width as i32
+            && (position.y - origin.y) < size.height as i32
+    }
+
+    /// The refresh rate of the monitor, in millihertz.
+    pub(super) fn refresh_rate_millihertz(&self) -> u32 {
+        u32::from(self.monitor.refresh_rate) * 1000
+    }
+
+    /// The monitor the `position` is on, which is the first monitor when it's on none of them.
+    pub(super) fn at(monitors: &[MonitorHandle], position: PhysicalPosition<i32>) -> Option<&Self> {
+        monitors
+            .iter()
+            .find(|monitor| monitor.contains(position))
+            .or_else(|| monitors.first())
+    }
+}
+
+impl PartialEq for MonitorHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for MonitorHandle {}
+
+impl PartialOrd for MonitorHandle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MonitorHandle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl Hash for MonitorHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// The only video mode of a monitor, matching its size and refresh rate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoMode {
+    monitor: MonitorHandle,
+}
+
+impl VideoMode {
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.monitor.size()
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u16 {
+        32
+    }
+
+    #[inline]
+    pub fn refresh_rate(&self) -> u16 {
+        self.monitor.monitor.refresh_rate
+    }
+
+    #[inline]
+    pub fn monitor(&self) -> RootMonitorHandle {
+        RootMonitorHandle {
+            inner: PlatformMonitorHandle::Headless(self.monitor.clone()),
+        }
+    }
+}
@@ -0,0 +1,526 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use raw_window_handle::{unix::XlibHandle, RawWindowHandle};
+
+use crate::{
+    clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker},
+    dnd::{DndActions, DragData},
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
+    error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    event::WindowEvent,
+    monitor::MonitorHandle as RootMonitorHandle,
+    platform_impl::{
+        MonitorHandle as PlatformMonitorHandle, PlatformSpecificWindowBuilderAttributes,
+    },
+    window::{Backdrop, CursorIcon, CursorTheme, Fullscreen, ImePurpose, WindowAttributes},
+};
+
+use super::{
+    event_loop::{PendingEvent, Shared},
+    EventLoopWindowTarget, MonitorHandle, WindowId,
+};
+
+/// The state of a window, which only changes when the application or the injected events
+/// change it.
+pub(super) struct WindowState {
+    pub title: String,
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub min_size: Option<Size>,
+    pub max_size: Option<Size>,
+    pub visible: bool,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub maximized: bool,
+    pub minimized: bool,
+    pub always_on_top: bool,
+    pub fullscreen: Option<Fullscreen>,
+    pub key_repeat: bool,
+    /// The workspace the window is on, `None` standing for all of them.
+    pub workspace: Option<u32>,
+}
+
+/// A custom cursor, which is never shown as there's no display to show it on.
+#[derive(Clone)]
+pub struct CustomCursor;
+
+/// A child surface, which has nothing to render to either.
+pub struct ChildSurface;
+
+impl ChildSurface {
+    #[inline]
+    pub fn new(_parent: &Window, _position: Position, _size: Size) -> Result<Self, ExternalError> {
+        Ok(ChildSurface)
+    }
+
+    #[inline]
+    pub fn set_position(&self, _position: Position) {}
+
+    #[inline]
+    pub fn set_size(&self, _size: Size) {}
+
+    #[inline]
+    pub fn window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Xlib(XlibHandle::empty())
+    }
+}
+
+pub struct Window {
+    id: WindowId,
+    shared: Arc<Shared>,
+    state: Arc<Mutex<WindowState>>,
+}
+
+impl Window {
+    pub fn new<T>(
+        window_target: &EventLoopWindowTarget<T>,
+        attributes: WindowAttributes,
+        _: PlatformSpecificWindowBuilderAttributes,
+    ) -> Result<Self, RootOsError> {
+        let shared = window_target.shared.clone();
+        let monitor = shared.monitors().into_iter().next();
+        let scale_factor = monitor
+            .as_ref()
+            .map(MonitorHandle::scale_factor)
+            .unwrap_or(1.0);
+
+        let size = attributes
+            .inner_size
+            .unwrap_or_else(|| LogicalSize::new(800, 600).into())
+            .to_physical(scale_factor);
+        let position = match attributes.position {
+            Some(position) => position.to_physical(scale_factor),
+            None => monitor
+                .as_ref()
+                .map(MonitorHandle::position)
+                .unwrap_or_else(|| PhysicalPosition::new(0, 0)),
+        };
+        let mut state = WindowState {
+            title: attributes.title,
+            position,
+            size,
+            min_size: attributes.min_inner_size,
+            max_size: attributes.max_inner_size,
+            visible: attributes.visible,
+            resizable: attributes.resizable,
+            decorations: attributes.decorations,
+            maximized: attributes.maximized,
+            minimized: false,
+            always_on_top: attributes.always_on_top,
+            fullscreen: attributes.fullscreen,
+            key_repeat: attributes.key_repeat,
+            workspace: Some(0),
+        };
+        state.size = clamp_size(&state, size, scale_factor);
+
+        let state = Arc::new(Mutex::new(state));
+        let id = shared.register_window(&state);
+        Ok(Window { id, shared, state })
+    }
+
+    #[inline]
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
+
+    #[inline]
+    pub fn title(&self) -> String {
+        self.state.lock().unwrap().title.clone()
+    }
+
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.state.lock().unwrap().visible
+    }
+
+    #[inline]
+    pub fn is_minimized(&self) -> bool {
+        self.state.lock().unwrap().minimized
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.state.lock().unwrap().maximized
+    }
+
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.state.lock().unwrap().resizable
+    }
+
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.state.lock().unwrap().decorations
+    }
+
+    #[inline]
+    pub fn is_always_on_top(&self) -> bool {
+        self.state.lock().unwrap().always_on_top
+    }
+
+    #[inline]
+    pub fn is_focused(&self) -> bool {
+        self.shared.focused_window() == Some(self.id)
+    }
+
+    #[inline]
+    pub fn set_title(&self, title: &str) {
+        self.state.lock().unwrap().title = title.to_owned();
+    }
+
+    #[inline]
+    pub fn set_visible(&self, visible: bool) {
+        self.state.lock().unwrap().visible = visible;
+    }
+
+    #[inline]
+    pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.state.lock().unwrap().position)
+    }
+
+    #[inline]
+    pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        // There are no decorations drawn around the windows.
+        self.outer_position()
+    }
+
+    #[inline]
+    pub fn set_outer_position(&self, position: Position) {
+        let position = position.to_physical(self.scale_factor());
+        let mut state = self.state.lock().unwrap();
+        if state.position != position {
+            state.position = position;
+            self.push_event(WindowEvent::Moved(position));
+        }
+    }
+
+    #[inline]
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.state.lock().unwrap().size
+    }
+
+    #[inline]
+    pub fn outer_size(&self) -> PhysicalSize<u32> {
+        self.inner_size()
+    }
+
+    #[inline]
+    pub fn set_inner_size(&self, size: Size) {
+        let scale_factor = self.scale_factor();
+        let mut state = self.state.lock().unwrap();
+        let size = clamp_size(&state, size.to_physical(scale_factor), scale_factor);
+        self.resize(&mut state, size);
+    }
+
+    #[inline]
+    pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
+        let scale_factor = self.scale_factor();
+        let mut state = self.state.lock().unwrap();
+        state.min_size = dimensions;
+        let size = clamp_size(&state, state.size, scale_factor);
+        self.resize(&mut state, size);
+    }
+
+    #[inline]
+    pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
+        let scale_factor = self.scale_factor();
+        let mut state = self.state.lock().unwrap();
+        state.max_size = dimensions;
+        let size = clamp_size(&state, state.size, scale_factor);
+        self.resize(&mut state, size);
+    }
+
+    #[inline]
+    pub fn set_resizable(&self, resizable: bool) {
+        self.state.lock().unwrap().resizable = resizable;
+    }
+
+    #[inline]
+    pub fn set_cursor_icon(&self, _cursor: CursorIcon) {}
+
+    #[inline]
+    pub fn set_cursor(&self, _cursor: CustomCursor) {}
+
+    #[inline]
+    pub fn set_cursor_theme(&self, _name: &str, _size: u32) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    /// There are no cursors without a display.
+    #[inline]
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        None
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_grab_area(
+        &self,
+        _area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_pointer_lock(&self, _lock: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_visible(&self, _visible: bool) {}
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _data: DragData, _actions: DndActions) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.monitor()
+            .as_ref()
+            .map(MonitorHandle::scale_factor)
+            .unwrap_or(1.0)
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        self.state.lock().unwrap().maximized = maximized;
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        self.state.lock().unwrap().minimized = minimized;
+    }
+
+    #[inline]
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.state.lock().unwrap().fullscreen.clone()
+    }
+
+    #[inline]
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        self.state.lock().unwrap().fullscreen = fullscreen;
+    }
+
+    #[inline]
+    pub fn workspace(&self) -> Option<u32> {
+        self.state.lock().unwrap().workspace
+    }
+
+    #[inline]
+    pub fn move_to_workspace(&self, workspace: u32) {
+        self.set_workspace(Some(workspace));
+    }
+
+    /// There's a single current workspace, the first one, which the window is kept on.
+    #[inline]
+    pub fn set_visible_on_all_workspaces(&self, visible: bool) {
+        if visible {
+            self.set_workspace(None);
+        } else if self.workspace().is_none() {
+            self.set_workspace(Some(0));
+        }
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorations: bool) {
+        self.state.lock().unwrap().decorations = decorations;
+    }
+
+    #[inline]
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        self.state.lock().unwrap().always_on_top = always_on_top;
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_backdrop(&self, _backdrop: Backdrop) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
+    #[inline]
+    pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
+
+    #[inline]
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        self.state.lock().unwrap().key_repeat = key_repeat;
+    }
+
+    #[inline]
+    pub fn set_system_shortcuts_inhibited(&self, _inhibited: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn read_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _picker: MimePicker,
+        _consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn focus_window(&self) {
+        self.shared.focus_window(self.id);
+    }
+
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.shared.push_event(PendingEvent::Redraw(self.id));
+    }
+
+    #[inline]
+    pub fn set_vsync_redraws(&self, _vsync_redraws: bool) {}
+
+    #[inline]
+    pub fn pre_present_notify(&self) {}
+
+    #[cfg(feature = "software-present")]
+    #[inline]
+    pub fn present_pixels(
+        &self,
+        _pixels: &[u32],
+        _size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn current_monitor(&self) -> Option<RootMonitorHandle> {
+        self.monitor().map(|monitor| RootMonitorHandle {
+            inner: PlatformMonitorHandle::Headless(monitor),
+        })
+    }
+
+    /// The variable refresh rate is active for the fullscreen windows, like on most systems.
+    #[inline]
+    pub fn is_vrr_active(&self) -> bool {
+        self.fullscreen().is_some()
+            && self
+                .monitor()
+                .map_or(false, |monitor| monitor.supports_variable_refresh())
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
+        self.shared.monitors().into_iter().collect()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<RootMonitorHandle> {
+        self.shared
+            .monitors()
+            .into_iter()
+            .next()
+            .map(|monitor| RootMonitorHandle {
+                inner: PlatformMonitorHandle::Headless(monitor),
+            })
+    }
+
+    /// There's nothing to render to, so the handle is empty.
+    #[inline]
+    pub fn window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Xlib(XlibHandle::empty())
+    }
+
+    /// The monitor the window is on, which is the primary monitor when it's on none of them.
+    fn monitor(&self) -> Option<MonitorHandle> {
+        let position = self.state.lock().unwrap().position;
+        MonitorHandle::at(&self.shared.monitors(), position).cloned()
+    }
+
+    fn resize(&self, state: &mut WindowState, size: PhysicalSize<u32>) {
+        if state.size != size {
+            state.size = size;
+            self.push_event(WindowEvent::Resized(size));
+        }
+    }
+
+    fn set_workspace(&self, workspace: Option<u32>) {
+        let mut state = self.state.lock().unwrap();
+        if state.workspace != workspace {
+            state.workspace = workspace;
+            drop(state);
+            self.push_event(WindowEvent::WorkspaceChanged(workspace));
+        }
+    }
+
+    fn push_event(&self, event: WindowEvent<'static>) {
+        self.shared.push_event(PendingEvent::Window(self.id, event));
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        self.shared.unregister_window(self.id);
+        self.push_event(WindowEvent::Destroyed);
+    }
+}
+
+/// Clamps the `size` to the minimum and maximum sizes of the window.
+fn clamp_size(
+    state: &WindowState,
+    size: PhysicalSize<u32>,
+    scale_factor: f64,
+) -> PhysicalSize<u32> {
+    let mut size = size;
+    if let Some(min_size) = state.min_size {
+        let min_size: PhysicalSize<u32> = min_size.to_physical(scale_factor);
+        size.width = size.width.max(min_size.width);
+        size.height = size.height.max(min_size.height);
+    }
+    if let Some(max_size) = state.max_size {
+        let max_size: PhysicalSize<u32> = max_size.to_physical(scale_factor);
+        size.width = size.width.min(max_size.width);
+        size.height = size.height.min(max_size.height);
+    }
+    size
+}
@@ -0,0 +1,220 @@
+//! The progress and the count of the application shown on its launcher icon, with the
+//! `com.canonical.Unity.LauncherEntry` D-Bus API the docks implement. `libdbus` is loaded at
+//! runtime, like the other libraries.
+
+#![allow(non_camel_case_types)]
+
+use std::{
+    env,
+    ffi::{CStr, CString},
+    mem,
+    os::raw::{c_char, c_int, c_uint, c_void},
+    path::PathBuf,
+    ptr,
+    sync::Mutex,
+};
+
+use crate::window::ProgressState;
+
+pub enum DBusConnection {}
+pub enum DBusMessage {}
+
+/// The iterator appending the arguments of a message, which is larger than `DBusMessageIter`.
+#[repr(C)]
+pub struct DBusMessageIter {
+    _private: [*mut c_void; 16],
+}
+
+const DBUS_BUS_SESSION: c_int = 0;
+const DBUS_TYPE_STRING: c_int = b's' as c_int;
+const DBUS_TYPE_INT64: c_int = b'x' as c_int;
+const DBUS_TYPE_DOUBLE: c_int = b'd' as c_int;
+const DBUS_TYPE_BOOLEAN: c_int = b'b' as c_int;
+const DBUS_TYPE_ARRAY: c_int = b'a' as c_int;
+const DBUS_TYPE_VARIANT: c_int = b'v' as c_int;
+const DBUS_TYPE_DICT_ENTRY: c_int = b'e' as c_int;
+
+library! {
+    DBus, b"libdbus-1.so.3\0", b"libdbus-1.so\0";
+    fn dbus_threads_init_default() -> c_uint;
+    fn dbus_bus_get(c_int, *mut c_void) -> *mut DBusConnection;
+    fn dbus_connection_set_exit_on_disconnect(*mut DBusConnection, c_uint) -> ();
+    fn dbus_connection_send(*mut DBusConnection, *mut DBusMessage, *mut u32) -> c_uint;
+    fn dbus_connection_flush(*mut DBusConnection) -> ();
+    fn dbus_message_new_signal(*const c_char, *const c_char, *const c_char) -> *mut DBusMessage;
+    fn dbus_message_unref(*mut DBusMessage) -> ();
+    fn dbus_message_iter_init_append(*mut DBusMessage, *mut DBusMessageIter) -> ();
+    fn dbus_message_iter_append_basic(*mut DBusMessageIter, c_int, *const c_void) -> c_uint;
+    fn dbus_message_iter_open_container(
+        *mut DBusMessageIter,
+        c_int,
+        *const c_char,
+        *mut DBusMessageIter
+    ) -> c_uint;
+    fn dbus_message_iter_close_container(*mut DBusMessageIter, *mut DBusMessageIter) -> c_uint;
+}
+
+lazy_static! {
+    /// The connection to the session bus, made once the entry is first updated.
+    static ref CONNECTION: Option<Mutex<Connection>> = Connection::open();
+}
+
+/// A property of the launcher entry.
+enum Value {
+    Boolean(bool),
+    Int64(i64),
+    Double(f64),
+}
+
+struct Connection {
+    dbus: DBus,
+    connection: *mut DBusConnection,
+}
+
+// The threads of `libdbus` are initialized before the connection is made.
+unsafe impl Send for Connection {}
+
+impl Connection {
+    fn open() -> Option<Mutex<Self>> {
+        let dbus = DBus::open()?;
+        unsafe {
+            (dbus.dbus_threads_init_default)();
+            let connection = (dbus.dbus_bus_get)(DBUS_BUS_SESSION, ptr::null_mut());
+            if connection.is_null() {
+                return None;
+            }
+            // The process is kept when the bus goes away.
+            (dbus.dbus_connection_set_exit_on_disconnect)(connection, 0);
+            Some(Mutex::new(Connection { dbus, connection }))
+        }
+    }
+
+    /// Sends the `Update` signal of the entry of the application.
+    unsafe fn update(&self, app_uri: &CStr, properties: &[(&[u8], Value)]) {
+        let dbus = &self.dbus;
+        let message = (dbus.dbus_message_new_signal)(
+            b"/com/canonical/unity/launcherentry/winit\0".as_ptr() as *const c_char,
+            b"com.canonical.Unity.LauncherEntry\0".as_ptr() as *const c_char,
+            b"Update\0".as_ptr() as *const c_char,
+        );
+        if message.is_null() {
+            return;
+        }
+
+        let mut args: DBusMessageIter = mem::zeroed();
+        (dbus.dbus_message_iter_init_append)(message, &mut args);
+        let app_uri = app_uri.as_ptr();
+        (dbus.dbus_message_iter_append_basic)(
+            &mut args,
+            DBUS_TYPE_STRING,
+            &app_uri as *const _ as *const c_void,
+        );
+
+        let mut array: DBusMessageIter = mem::zeroed();
+        (dbus.dbus_message_iter_open_container)(
+            &mut args,
+            DBUS_TYPE_ARRAY,
+            b"{sv}\0".as_ptr() as *const c_char,
+            &mut array,
+        );
+        for (name, value) in properties {
+            let mut entry: DBusMessageIter = mem::zeroed();
+            (dbus.dbus_message_iter_open_container)(
+                &mut array,
+                DBUS_TYPE_DICT_ENTRY,
+                ptr::null(),
+                &mut entry,
+            );
+            let name = name.as_ptr() as *const c_char;
+            (dbus.dbus_message_iter_append_basic)(
+                &mut entry,
+                DBUS_TYPE_STRING,
+                &name as *const _ as *const c_void,
+            );
+
+            let mut variant: DBusMessageIter = mem::zeroed();
+            let signature: &[u8] = match value {
+                Value::Boolean(_) => b"b\0",
+                Value::Int64(_) => b"x\0",
+                Value::Double(_) => b"d\0",
+            };
+            (dbus.dbus_message_iter_open_container)(
+                &mut entry,
+                DBUS_TYPE_VARIANT,
+                signature.as_ptr() as *const c_char,
+                &mut variant,
+            );
+            match *value {
+                Value::Boolean(value) => {
+                    let value = value as c_uint;
+                    (dbus.dbus_message_iter_append_basic)(
+                        &mut variant,
+                        DBUS_TYPE_BOOLEAN,
+                        &value as *const _ as *const c_void,
+                    )
+                }
+                Value::Int64(value) => (dbus.dbus_message_iter_append_basic)(
+                    &mut variant,
+                    DBUS_TYPE_INT64,
+                    &value as *const _ as *const c_void,
+                ),
+                Value::Double(value) => (dbus.dbus_message_iter_append_basic)(
+                    &mut variant,
+                    DBUS_TYPE_DOUBLE,
+                    &value as *const _ as *const c_void,
+                ),
+            };
+            (dbus.dbus_message_iter_close_container)(&mut entry, &mut variant);
+            (dbus.dbus_message_iter_close_container)(&mut array, &mut entry);
+        }
+        (dbus.dbus_message_iter_close_container)(&mut args, &mut array);
+
+        (dbus.dbus_connection_send)(self.connection, message, ptr::null_mut());
+        (dbus.dbus_connection_flush)(self.connection);
+        (dbus.dbus_message_unref)(message);
+    }
+}
+
+/// The URI of the desktop entry of the application, which is the one it was launched from, or
+/// the one named after its executable.
+fn app_uri() -> Option<CString> {
+    let entry = env::var_os("GIO_LAUNCHED_DESKTOP_FILE")
+        .map(PathBuf::from)
+        .or_else(|| env::current_exe().ok())?;
+    let name = entry.file_stem()?.to_str()?;
+    CString::new(format!("application://{}.desktop", name)).ok()
+}
+
+/// Updates the properties of the entry of the application.
+fn update(properties: &[(&[u8], Value)]) {
+    let connection = match *CONNECTION {
+        Some(ref connection) => connection.lock().unwrap(),
+        None => return,
+    };
+    if let Some(app_uri) = app_uri() {
+        unsafe { connection.update(&app_uri, properties) };
+    }
+}
+
+pub fn set_progress(state: ProgressState) {
+    // The entries have no indeterminate progress, and show the errors by asking for attention.
+    let (visible, progress, urgent) = match state {
+        ProgressState::None | ProgressState::Indeterminate => (false, 0.0, false),
+        ProgressState::Normal(progress) | ProgressState::Paused(progress) => {
+            (true, progress, false)
+        }
+        ProgressState::Error(progress) => (true, progress, true),
+    };
+    update(&[
+        (b"progress\0", Value::Double(progress.clamp(0., 1.))),
+        (b"progress-visible\0", Value::Boolean(visible)),
+        (b"urgent\0", Value::Boolean(urgent)),
+    ]);
+}
+
+pub fn set_badge(count: Option<u64>) {
+    update(&[
+        (b"count\0", Value::Int64(count.unwrap_or(0) as i64)),
+        (b"count-visible\0", Value::Boolean(count.is_some())),
+    ]);
+}
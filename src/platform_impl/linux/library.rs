@@ -0,0 +1,51 @@
+//! The loading of the libraries at runtime, like the X11 and Wayland ones, which are only
+//! needed once the platform is used.
+
+/// Declares the functions of a library, which are loaded with `open`, from the first of the
+/// names which is found.
+macro_rules! library {
+    (
+        $name:ident, $($lib:expr),+;
+        $(fn $func:ident($($arg:ty),*) -> $ret:ty;)*
+    ) => {
+        pub struct $name {
+            $(pub $func: unsafe extern "C" fn($($arg),*) -> $ret,)*
+        }
+
+        impl $name {
+            fn open() -> Option<Self> {
+                unsafe {
+                    let libs: &[&[u8]] = &[$($lib),+];
+                    let handle = libs.iter().find_map(|lib| {
+                        let handle = ::libc::dlopen(
+                            lib.as_ptr() as *const ::std::os::raw::c_char,
+                            ::libc::RTLD_LAZY | ::libc::RTLD_LOCAL,
+                        );
+                        if handle.is_null() {
+                            None
+                        } else {
+                            Some(handle)
+                        }
+                    })?;
+                    // The library is never closed, as the functions are kept for the whole
+                    // process.
+                    Some($name {
+                        $($func: {
+                            let symbol = ::libc::dlsym(
+                                handle,
+                                concat!(stringify!($func), "\0").as_ptr() as *const ::std::os::raw::c_char,
+                            );
+                            if symbol.is_null() {
+                                return None;
+                            }
+                            ::std::mem::transmute::<
+                                *mut ::std::os::raw::c_void,
+                                unsafe extern "C" fn($($arg),*) -> $ret,
+                            >(symbol)
+                        },)*
+                    })
+                }
+            }
+        }
+    };
+}
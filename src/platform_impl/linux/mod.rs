@@ -6,45 +6,86 @@
     target_os = "openbsd"
 ))]
 
-#[cfg(all(not(feature = "x11"), not(feature = "wayland")))]
-compile_error!("Please select a feature to build for unix: `x11`, `wayland`");
+#[cfg(all(
+    not(feature = "x11"),
+    not(feature = "wayland"),
+    not(feature = "headless")
+))]
+compile_error!("Please select a feature to build for unix: `x11`, `wayland`, `headless`");
 
 #[cfg(feature = "wayland")]
 use std::error::Error;
-use std::{collections::VecDeque, env, fmt};
+#[cfg(feature = "headless")]
+use std::io;
+use std::{collections::VecDeque, env, fmt, os::unix::io::RawFd, sync::Arc, time::Duration};
 #[cfg(feature = "x11")]
-use std::{ffi::CStr, mem::MaybeUninit, os::raw::*, sync::Arc};
+use std::{ffi::CStr, mem::MaybeUninit, os::raw::*, sync::Weak};
 
 #[cfg(feature = "x11")]
 use parking_lot::Mutex;
 use raw_window_handle::RawWindowHandle;
 
+pub use self::scancode::{key_to_scancode, scancode_to_key};
 #[cfg(feature = "x11")]
 pub use self::x11::XNotSupported;
 #[cfg(feature = "x11")]
-use self::x11::{ffi::XVisualInfo, util::WindowType as XWindowType, XConnection, XError};
+use self::x11::{
+    ffi::XVisualInfo,
+    util::{VisualPreference as XVisualPreference, WindowType as XWindowType},
+    XConnection, XError,
+};
 use crate::{
+    clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker},
+    dnd::{DndActions, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
-    error::{ExternalError, NotSupportedError, OsError as RootOsError},
-    event::Event,
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
-    icon::Icon,
-    monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
-    window::{CursorIcon, Fullscreen, UserAttentionType, WindowAttributes},
+    error::{ExternalError, HandleError, NotSupportedError, OsError as RootOsError},
+    event::{Event, InputDevice, ModifiersState, ScanCode},
+    event_loop::{
+        ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW, HapticPattern,
+        SourceInterest, SourceToken, Timer, TimerId,
+    },
+    gamepad::GamepadId,
+    hotkey::{Accelerator, HotkeyId},
+    icon::{BadIcon, Icon, RgbaIcon},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
+    monitor::{
+        ColorCapabilities, MonitorHandle as RootMonitorHandle, MonitorIdentity,
+        VideoMode as RootVideoMode,
+    },
+    platform::pump_events::PumpStatus,
+    window::{
+        Backdrop, CursorFrame, CursorIcon, CursorTheme, Fullscreen, ImePurpose, ProgressState,
+        Ratio, UserAttentionType, WindowAttributes,
+    },
 };
 
 pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
 
+#[macro_use]
+mod library;
+
+#[cfg(any(feature = "x11", feature = "wayland"))]
+mod compose;
+mod gamepad;
+#[cfg(feature = "headless")]
+pub mod headless;
+mod launcher_entry;
+mod precise_wait;
+mod scancode;
+#[cfg(any(feature = "x11", feature = "headless"))]
+mod timer;
 #[cfg(feature = "wayland")]
 pub mod wayland;
 #[cfg(feature = "x11")]
 pub mod x11;
+#[cfg(any(feature = "x11", feature = "wayland"))]
+mod xkb;
 
 /// Environment variable specifying which backend should be used on unix platform.
 ///
-/// Legal values are x11 and wayland. If this variable is set only the named backend
+/// Legal values are x11, wayland and headless. If this variable is set only the named backend
 /// will be tried by winit. If it is not set, winit will try to connect to a wayland connection,
-/// and if it fails will fallback on x11.
+/// and if it fails will fallback on x11. The headless backend is only used when it's asked for.
 ///
 /// If this variable is set with any other value, winit will panic.
 const BACKEND_PREFERENCE_ENV_VAR: &str = "WINIT_UNIX_BACKEND";
@@ -54,6 +95,8 @@ pub struct PlatformSpecificWindowBuilderAttributes {
     #[cfg(feature = "x11")]
     pub visual_infos: Option<XVisualInfo>,
     #[cfg(feature = "x11")]
+    pub visual_preference: XVisualPreference,
+    #[cfg(feature = "x11")]
     pub screen_id: Option<i32>,
     #[cfg(feature = "x11")]
     pub resize_increments: Option<Size>,
@@ -77,6 +120,8 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
             #[cfg(feature = "x11")]
             visual_infos: None,
             #[cfg(feature = "x11")]
+            visual_preference: XVisualPreference::Default,
+            #[cfg(feature = "x11")]
             screen_id: None,
             #[cfg(feature = "x11")]
             resize_increments: None,
@@ -96,10 +141,15 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
     }
 }
 
+unsafe impl Send for PlatformSpecificWindowBuilderAttributes {}
+unsafe impl Sync for PlatformSpecificWindowBuilderAttributes {}
+
 #[cfg(feature = "x11")]
 lazy_static! {
     pub static ref X11_BACKEND: Mutex<Result<Arc<XConnection>, XNotSupported>> =
         Mutex::new(XConnection::new(Some(x_error_callback)).map(Arc::new));
+    /// The connections of the secondary event loops, for the error handler to find them.
+    static ref X11_SECONDARY_CONNECTIONS: Mutex<Vec<Weak<XConnection>>> = Default::default();
 }
 
 #[derive(Debug, Clone)]
@@ -110,6 +160,8 @@ pub enum OsError {
     XMisc(&'static str),
     #[cfg(feature = "wayland")]
     WaylandMisc(&'static str),
+    #[cfg(feature = "headless")]
+    HeadlessMisc(&'static str),
 }
 
 impl fmt::Display for OsError {
@@ -121,6 +173,8 @@ impl fmt::Display for OsError {
             OsError::XMisc(ref e) => _f.pad(e),
             #[cfg(feature = "wayland")]
             OsError::WaylandMisc(ref e) => _f.pad(e),
+            #[cfg(feature = "headless")]
+            OsError::HeadlessMisc(ref e) => _f.pad(e),
         }
     }
 }
@@ -130,6 +184,18 @@ pub enum Window {
     X(x11::Window),
     #[cfg(feature = "wayland")]
     Wayland(wayland::Window),
+    #[cfg(feature = "headless")]
+    Headless(headless::Window),
+}
+
+#[derive(Clone)]
+pub enum CustomCursor {
+    #[cfg(feature = "x11")]
+    X(x11::CustomCursor),
+    #[cfg(feature = "wayland")]
+    Wayland(wayland::CustomCursor),
+    #[cfg(feature = "headless")]
+    Headless(headless::CustomCursor),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -138,6 +204,8 @@ pub enum WindowId {
     X(x11::WindowId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::WindowId),
+    #[cfg(feature = "headless")]
+    Headless(headless::WindowId),
 }
 
 impl WindowId {
@@ -146,6 +214,8 @@ impl WindowId {
         return WindowId::Wayland(wayland::WindowId::dummy());
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return WindowId::X(x11::WindowId::dummy());
+        #[cfg(all(not(feature = "wayland"), not(feature = "x11")))]
+        return WindowId::Headless(headless::WindowId::dummy());
     }
 }
 
@@ -155,6 +225,8 @@ pub enum DeviceId {
     X(x11::DeviceId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::DeviceId),
+    #[cfg(feature = "headless")]
+    Headless(headless::DeviceId),
 }
 
 impl DeviceId {
@@ -163,6 +235,8 @@ impl DeviceId {
         return DeviceId::Wayland(wayland::DeviceId::dummy());
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return DeviceId::X(x11::DeviceId::dummy());
+        #[cfg(all(not(feature = "wayland"), not(feature = "x11")))]
+        return DeviceId::Headless(headless::DeviceId::dummy());
     }
 }
 
@@ -172,6 +246,8 @@ pub enum MonitorHandle {
     X(x11::MonitorHandle),
     #[cfg(feature = "wayland")]
     Wayland(wayland::MonitorHandle),
+    #[cfg(feature = "headless")]
+    Headless(headless::MonitorHandle),
 }
 
 /// `x11_or_wayland!(match expr; Enum(foo) => foo.something())`
@@ -180,6 +256,7 @@ pub enum MonitorHandle {
 /// match self {
 ///    Enum::X(foo) => foo.something(),
 ///    Enum::Wayland(foo) => foo.something(),
+///    Enum::Headless(foo) => foo.something(),
 /// }
 /// ```
 /// The result can be converted to another enum by adding `; as AnotherEnum`
@@ -190,6 +267,8 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $enum2::X($x),
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $enum2::Wayland($x),
+            #[cfg(feature = "headless")]
+            $enum::Headless($($c1)*) => $enum2::Headless($x),
         }
     };
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr) => {
@@ -198,6 +277,8 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $x,
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $x,
+            #[cfg(feature = "headless")]
+            $enum::Headless($($c1)*) => $x,
         }
     };
 }
@@ -232,6 +313,26 @@ impl MonitorHandle {
     pub fn video_modes(&self) -> Box<dyn Iterator<Item = RootVideoMode>> {
         x11_or_wayland!(match self; MonitorHandle(m) => Box::new(m.video_modes()))
     }
+
+    #[inline]
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.color_capabilities())
+    }
+
+    #[inline]
+    pub fn supports_variable_refresh(&self) -> bool {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.supports_variable_refresh())
+    }
+
+    #[inline]
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.identity())
+    }
+
+    #[inline]
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        x11_or_wayland!(match self; MonitorHandle(m) => m.work_area())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -240,6 +341,8 @@ pub enum VideoMode {
     X(x11::VideoMode),
     #[cfg(feature = "wayland")]
     Wayland(wayland::VideoMode),
+    #[cfg(feature = "headless")]
+    Headless(headless::VideoMode),
 }
 
 impl VideoMode {
@@ -280,6 +383,10 @@ impl Window {
             EventLoopWindowTarget::X(ref window_target) => {
                 x11::Window::new(window_target, attribs, pl_attribs).map(Window::X)
             }
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref window_target) => {
+                headless::Window::new(window_target, attribs, pl_attribs).map(Window::Headless)
+            }
         }
     }
 
@@ -323,6 +430,16 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.outer_size())
     }
 
+    #[inline]
+    pub fn recommended_bounds(&self) -> Option<PhysicalSize<u32>> {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref w) => w.recommended_bounds(),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn set_inner_size(&self, size: Size) {
         x11_or_wayland!(match self; Window(w) => w.set_inner_size(size))
@@ -338,6 +455,18 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_max_inner_size(dimensions))
     }
 
+    #[inline]
+    pub fn set_aspect_ratio(&self, _ratio: Option<Ratio>) {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref w) => w.set_aspect_ratio(_ratio),
+            #[cfg(feature = "wayland")]
+            &Window::Wayland(ref w) => w.set_aspect_ratio(_ratio),
+            #[cfg(feature = "headless")]
+            _ => (),
+        }
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
         x11_or_wayland!(match self; Window(w) => w.set_resizable(resizable))
@@ -348,11 +477,59 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_cursor_icon(cursor))
     }
 
+    #[inline]
+    pub fn set_cursor(&self, cursor: CustomCursor) {
+        match (self, cursor) {
+            #[cfg(feature = "x11")]
+            (Window::X(window), CustomCursor::X(cursor)) => window.set_cursor(cursor),
+            #[cfg(feature = "wayland")]
+            (Window::Wayland(window), CustomCursor::Wayland(cursor)) => window.set_cursor(cursor),
+            #[cfg(feature = "headless")]
+            (Window::Headless(window), CustomCursor::Headless(cursor)) => window.set_cursor(cursor),
+            // The cursor comes from another event loop.
+            #[allow(unreachable_patterns)]
+            _ => (),
+        }
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.set_cursor_theme(name, size))
+    }
+
+    #[inline]
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        x11_or_wayland!(match self; Window(window) => window.cursor_theme())
+    }
+
     #[inline]
     pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
         x11_or_wayland!(match self; Window(window) => window.set_cursor_grab(grab))
     }
 
+    #[inline]
+    pub fn set_cursor_grab_area(
+        &self,
+        area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.set_cursor_grab_area(area))
+    }
+
+    #[inline]
+    pub fn set_pointer_lock(&self, lock: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.set_pointer_lock(lock))
+    }
+
+    #[inline]
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.capture_pointer())
+    }
+
+    #[inline]
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.release_pointer())
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         x11_or_wayland!(match self; Window(window) => window.set_cursor_visible(visible))
@@ -363,6 +540,11 @@ impl Window {
         x11_or_wayland!(match self; Window(window) => window.drag_window())
     }
 
+    #[inline]
+    pub fn start_drag(&self, data: DragData, actions: DndActions) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.start_drag(data, actions))
+    }
+
     #[inline]
     pub fn scale_factor(&self) -> f64 {
         x11_or_wayland!(match self; Window(w) => w.scale_factor() as f64)
@@ -380,8 +562,13 @@ impl Window {
 
     #[inline]
     pub fn is_maximized(&self) -> bool {
-        // TODO: Not implemented
-        false
+        match self {
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.is_maximized(),
+            // TODO: Not implemented
+            #[cfg(any(feature = "x11", feature = "wayland"))]
+            _ => false,
+        }
     }
 
     #[inline]
@@ -404,11 +591,33 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_decorations(decorations))
     }
 
+    #[inline]
+    pub fn set_shadow(&self, _shadow: bool) {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref w) => w.set_shadow(_shadow),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => (),
+        }
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_opacity(opacity))
+    }
+
+    #[inline]
+    pub fn set_backdrop(&self, backdrop: Backdrop) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_backdrop(backdrop))
+    }
+
     #[inline]
     pub fn set_always_on_top(&self, _always_on_top: bool) {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref w) => w.set_always_on_top(_always_on_top),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.set_always_on_top(_always_on_top),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -419,14 +628,53 @@ impl Window {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref w) => w.set_window_icon(_window_icon),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => (),
         }
     }
 
     #[inline]
-    pub fn set_ime_position(&self, position: Position) {
-        x11_or_wayland!(match self; Window(w) => w.set_ime_position(position))
+    pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_cursor_area(position, size))
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_surrounding_text(text, cursor, anchor))
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_purpose(purpose))
+    }
+
+    #[inline]
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        x11_or_wayland!(match self; Window(w) => w.set_key_repeat(key_repeat))
+    }
+
+    #[inline]
+    pub fn set_system_shortcuts_inhibited(&self, inhibited: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_system_shortcuts_inhibited(inhibited))
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_clipboard(kind, provider))
+    }
+
+    #[inline]
+    pub fn read_clipboard(
+        &self,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.read_clipboard(kind, picker, consumer))
     }
 
     #[inline]
@@ -434,6 +682,8 @@ impl Window {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref w) => w.focus_window(),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.focus_window(),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -442,16 +692,108 @@ impl Window {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref w) => w.request_user_attention(_request_type),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => (),
+        }
+    }
+
+    #[inline]
+    pub fn tab_group(&self) -> Option<String> {
+        None
+    }
+
+    #[inline]
+    pub fn set_tab_group(&self, _group: &str) {}
+
+    #[inline]
+    pub fn merge_into_tab_group(&self, _window: &Window) {}
+
+    #[inline]
+    pub fn select_next_tab(&self) {}
+
+    #[inline]
+    pub fn workspace(&self) -> Option<u32> {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref w) => w.workspace(),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.workspace(),
+            #[cfg(feature = "wayland")]
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn move_to_workspace(&self, _workspace: u32) {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref w) => w.move_to_workspace(_workspace),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.move_to_workspace(_workspace),
             #[cfg(feature = "wayland")]
             _ => (),
         }
     }
 
+    #[inline]
+    pub fn set_visible_on_all_workspaces(&self, _visible: bool) {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref w) => w.set_visible_on_all_workspaces(_visible),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref w) => w.set_visible_on_all_workspaces(_visible),
+            #[cfg(feature = "wayland")]
+            _ => (),
+        }
+    }
+
+    #[inline]
+    pub fn set_progress(&self, state: ProgressState) {
+        launcher_entry::set_progress(state)
+    }
+
+    #[inline]
+    pub fn set_badge(&self, count: Option<u64>) {
+        launcher_entry::set_badge(count)
+    }
+
+    #[inline]
+    pub fn set_modal(&self, _modal: bool) -> Result<(), ExternalError> {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref w) => w.set_modal(_modal),
+            // Wayland has no modal windows, and the owners can't be set, see
+            // `wayland::Window::new`.
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
     #[inline]
     pub fn request_redraw(&self) {
         x11_or_wayland!(match self; Window(w) => w.request_redraw())
     }
 
+    #[inline]
+    pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+        x11_or_wayland!(match self; Window(w) => w.set_vsync_redraws(vsync_redraws))
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        x11_or_wayland!(match self; Window(w) => w.pre_present_notify())
+    }
+
+    #[cfg(feature = "software-present")]
+    #[inline]
+    pub fn present_pixels(
+        &self,
+        pixels: &[u32],
+        size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.present_pixels(pixels, size))
+    }
+
     #[inline]
     pub fn current_monitor(&self) -> Option<RootMonitorHandle> {
         match self {
@@ -469,6 +811,8 @@ impl Window {
                     inner: current_monitor,
                 })
             }
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref window) => window.current_monitor(),
         }
     }
 
@@ -487,6 +831,12 @@ impl Window {
                 .into_iter()
                 .map(MonitorHandle::Wayland)
                 .collect(),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref window) => window
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -502,26 +852,126 @@ impl Window {
             }
             #[cfg(feature = "wayland")]
             &Window::Wayland(ref window) => window.primary_monitor(),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref window) => window.primary_monitor(),
+        }
+    }
+
+    #[inline]
+    pub fn is_vrr_active(&self) -> bool {
+        x11_or_wayland!(match self; Window(w) => w.is_vrr_active())
+    }
+
+    #[inline]
+    pub fn window_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref window) => window.window_handle().map(RawWindowHandle::Xlib),
+            #[cfg(feature = "wayland")]
+            &Window::Wayland(ref window) => Ok(RawWindowHandle::Wayland(window.window_handle())),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref window) => Ok(window.window_handle()),
+        }
+    }
+
+    #[inline]
+    pub fn display_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        match self {
+            #[cfg(feature = "x11")]
+            &Window::X(ref window) => Ok(RawWindowHandle::Xlib(window.display_handle())),
+            #[cfg(feature = "wayland")]
+            &Window::Wayland(ref window) => Ok(RawWindowHandle::Wayland(window.display_handle())),
+            #[cfg(feature = "headless")]
+            &Window::Headless(ref window) => Ok(window.window_handle()),
+        }
+    }
+}
+
+pub enum ChildSurface {
+    #[cfg(feature = "x11")]
+    X(x11::ChildSurface),
+    #[cfg(feature = "wayland")]
+    Wayland(wayland::ChildSurface),
+    #[cfg(feature = "headless")]
+    Headless(headless::ChildSurface),
+}
+
+impl ChildSurface {
+    #[inline]
+    pub fn new<T>(
+        window_target: &EventLoopWindowTarget<T>,
+        parent: &Window,
+        position: Position,
+        size: Size,
+    ) -> Result<Self, ExternalError> {
+        match (window_target, parent) {
+            #[cfg(feature = "x11")]
+            (EventLoopWindowTarget::X(_), Window::X(parent)) => {
+                x11::ChildSurface::new(parent, position, size).map(ChildSurface::X)
+            }
+            #[cfg(feature = "wayland")]
+            (EventLoopWindowTarget::Wayland(window_target), Window::Wayland(parent)) => {
+                wayland::ChildSurface::new(window_target, parent, position, size)
+                    .map(ChildSurface::Wayland)
+            }
+            #[cfg(feature = "headless")]
+            (EventLoopWindowTarget::Headless(_), Window::Headless(parent)) => {
+                headless::ChildSurface::new(parent, position, size).map(ChildSurface::Headless)
+            }
+            // The window comes from another event loop.
+            #[allow(unreachable_patterns)]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
         }
     }
 
-    pub fn raw_window_handle(&self) -> RawWindowHandle {
+    #[inline]
+    pub fn set_position(&self, position: Position) {
+        x11_or_wayland!(match self; ChildSurface(s) => s.set_position(position))
+    }
+
+    #[inline]
+    pub fn set_size(&self, size: Size) {
+        x11_or_wayland!(match self; ChildSurface(s) => s.set_size(size))
+    }
+
+    #[inline]
+    pub fn window_handle(&self) -> Result<RawWindowHandle, HandleError> {
         match self {
             #[cfg(feature = "x11")]
-            &Window::X(ref window) => RawWindowHandle::Xlib(window.raw_window_handle()),
+            &ChildSurface::X(ref surface) => surface.window_handle().map(RawWindowHandle::Xlib),
             #[cfg(feature = "wayland")]
-            &Window::Wayland(ref window) => RawWindowHandle::Wayland(window.raw_window_handle()),
+            &ChildSurface::Wayland(ref surface) => {
+                Ok(RawWindowHandle::Wayland(surface.window_handle()))
+            }
+            #[cfg(feature = "headless")]
+            &ChildSurface::Headless(ref surface) => Ok(surface.window_handle()),
         }
     }
 }
 
+#[cfg(feature = "wayland")]
+fn is_wayland_session() -> bool {
+    env::var_os("WAYLAND_DISPLAY").is_some() || env::var_os("WAYLAND_SOCKET").is_some()
+}
+
 #[cfg(feature = "x11")]
 unsafe extern "C" fn x_error_callback(
     display: *mut x11::ffi::Display,
     event: *mut x11::ffi::XErrorEvent,
 ) -> c_int {
+    // The secondary connections are looked up first, so the error handler doesn't connect
+    // to the X server for the main event loop.
+    let secondary_xconn = X11_SECONDARY_CONNECTIONS
+        .lock()
+        .iter()
+        .filter_map(Weak::upgrade)
+        .find(|xconn| xconn.display == display);
     let xconn_lock = X11_BACKEND.lock();
-    if let Ok(ref xconn) = *xconn_lock {
+    let xconn = match secondary_xconn {
+        Some(xconn) => Some(xconn),
+        None => xconn_lock.as_ref().ok().cloned(),
+    };
+    if let Some(ref xconn) = xconn {
         // `assume_init` is safe here because the array consists of `MaybeUninit` values,
         // which do not require initialization.
         let mut buf: [MaybeUninit<c_char>; 1024] = MaybeUninit::uninit().assume_init();
@@ -553,6 +1003,8 @@ pub enum EventLoop<T: 'static> {
     Wayland(wayland::EventLoop<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoop<T>),
+    #[cfg(feature = "headless")]
+    Headless(headless::EventLoop<T>),
 }
 
 pub enum EventLoopProxy<T: 'static> {
@@ -560,6 +1012,8 @@ pub enum EventLoopProxy<T: 'static> {
     X(x11::EventLoopProxy<T>),
     #[cfg(feature = "wayland")]
     Wayland(wayland::EventLoopProxy<T>),
+    #[cfg(feature = "headless")]
+    Headless(headless::EventLoopProxy<T>),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -568,6 +1022,21 @@ impl<T: 'static> Clone for EventLoopProxy<T> {
     }
 }
 
+pub enum EventLoopWakeup {
+    #[cfg(feature = "x11")]
+    X(x11::EventLoopWakeup),
+    #[cfg(feature = "wayland")]
+    Wayland(wayland::EventLoopWakeup),
+    #[cfg(feature = "headless")]
+    Headless(headless::EventLoopWakeup),
+}
+
+impl EventLoopWakeup {
+    pub fn wake_up(&self) {
+        x11_or_wayland!(match self; EventLoopWakeup(wakeup) => wakeup.wake_up())
+    }
+}
+
 impl<T: 'static> EventLoop<T> {
     pub fn new() -> EventLoop<T> {
         assert_is_main_thread("new_any_thread");
@@ -593,8 +1062,15 @@ impl<T: 'static> EventLoop<T> {
                     #[cfg(not(feature = "wayland"))]
                     panic!("wayland feature is not enabled");
                 }
+                "headless" => {
+                    #[cfg(feature = "headless")]
+                    return EventLoop::new_headless_any_thread()
+                        .expect("Failed to initialize the headless backend");
+                    #[cfg(not(feature = "headless"))]
+                    panic!("headless feature is not enabled");
+                }
                 _ => panic!(
-                    "Unknown environment variable value for {}, try one of `x11`,`wayland`",
+                    "Unknown environment variable value for {}, try one of `x11`,`wayland`,`headless`",
                     BACKEND_PREFERENCE_ENV_VAR,
                 ),
             }
@@ -623,6 +1099,49 @@ impl<T: 'static> EventLoop<T> {
         );
     }
 
+    /// The secondary event loops are supported on X11, with a connection of their own, and on
+    /// the headless backend.
+    pub fn new_secondary() -> Result<EventLoop<T>, ExternalError> {
+        match env::var(BACKEND_PREFERENCE_ENV_VAR).as_deref() {
+            #[cfg(feature = "x11")]
+            Ok("x11") => EventLoop::new_x11_secondary(),
+            #[cfg(feature = "headless")]
+            Ok("headless") => EventLoop::new_headless_any_thread().map_err(|_| {
+                ExternalError::Os(os_error!(OsError::HeadlessMisc(
+                    "Failed to initialize the headless backend"
+                )))
+            }),
+            Ok(_) => Err(ExternalError::NotSupported(NotSupportedError::new())),
+            // The main event loop runs on Wayland whenever there's a compositor to connect to.
+            #[cfg(feature = "wayland")]
+            Err(_) if is_wayland_session() => {
+                Err(ExternalError::NotSupported(NotSupportedError::new()))
+            }
+            #[cfg(feature = "x11")]
+            Err(_) => EventLoop::new_x11_secondary(),
+            #[allow(unreachable_patterns)]
+            Err(_) => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[cfg(feature = "x11")]
+    fn new_x11_secondary() -> Result<EventLoop<T>, ExternalError> {
+        let xconn = XConnection::new(Some(x_error_callback))
+            .map(Arc::new)
+            .map_err(|_| {
+                ExternalError::Os(os_error!(OsError::XMisc(
+                    "Failed to connect to the X server"
+                )))
+            })?;
+
+        let mut secondary_xconns = X11_SECONDARY_CONNECTIONS.lock();
+        secondary_xconns.retain(|xconn| xconn.strong_count() > 0);
+        secondary_xconns.push(Arc::downgrade(&xconn));
+        drop(secondary_xconns);
+
+        Ok(EventLoop::X(x11::EventLoop::new(xconn)))
+    }
+
     #[cfg(feature = "wayland")]
     pub fn new_wayland() -> Result<EventLoop<T>, Box<dyn Error>> {
         assert_is_main_thread("new_wayland_any_thread");
@@ -652,17 +1171,53 @@ impl<T: 'static> EventLoop<T> {
         Ok(EventLoop::X(x11::EventLoop::new(xconn)))
     }
 
+    #[cfg(feature = "headless")]
+    pub fn new_headless() -> Result<EventLoop<T>, io::Error> {
+        assert_is_main_thread("new_headless_any_thread");
+
+        EventLoop::new_headless_any_thread()
+    }
+
+    #[cfg(feature = "headless")]
+    pub fn new_headless_any_thread() -> Result<EventLoop<T>, io::Error> {
+        headless::EventLoop::new().map(EventLoop::Headless)
+    }
+
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.create_proxy(); as EventLoopProxy)
     }
 
-    pub fn run_return<F>(&mut self, callback: F)
+    pub fn create_wakeup(&self) -> EventLoopWakeup {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.create_wakeup(); as EventLoopWakeup)
+    }
+
+    pub fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.insert_source(fd, interest, token))
+    }
+
+    pub fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.remove_source(token))
+    }
+
+    pub fn run_return<F>(&mut self, callback: F) -> i32
     where
         F: FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.run_return(callback))
     }
 
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, callback: F) -> PumpStatus
+    where
+        F: FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.pump_events(timeout, callback))
+    }
+
     pub fn run<F>(self, callback: F) -> !
     where
         F: 'static + FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
@@ -686,15 +1241,29 @@ pub enum EventLoopWindowTarget<T> {
     Wayland(wayland::EventLoopWindowTarget<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoopWindowTarget<T>),
+    #[cfg(feature = "headless")]
+    Headless(headless::EventLoopWindowTarget<T>),
 }
 
 impl<T> EventLoopWindowTarget<T> {
     #[inline]
+    #[cfg(feature = "wayland")]
     pub fn is_wayland(&self) -> bool {
         match *self {
             #[cfg(feature = "wayland")]
             EventLoopWindowTarget::Wayland(_) => true,
+            #[cfg(any(feature = "x11", feature = "headless"))]
+            _ => false,
+        }
+    }
+
+    #[inline]
+    #[cfg(feature = "x11")]
+    pub fn is_x11(&self) -> bool {
+        match *self {
             #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(_) => true,
+            #[cfg(any(feature = "wayland", feature = "headless"))]
             _ => false,
         }
     }
@@ -715,6 +1284,12 @@ impl<T> EventLoopWindowTarget<T> {
                 .into_iter()
                 .map(MonitorHandle::X)
                 .collect(),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -730,6 +1305,188 @@ impl<T> EventLoopWindowTarget<T> {
                     inner: primary_monitor,
                 })
             }
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.primary_monitor(),
+        }
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.set_clipboard(kind, provider))
+    }
+
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        image: RgbaIcon,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(
+            x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.create_custom_cursor(image, hotspot)?; as CustomCursor),
+        )
+    }
+
+    #[inline]
+    pub fn create_animated_cursor(
+        &self,
+        frames: Vec<CursorFrame>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(
+            x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.create_animated_cursor(frames)?; as CustomCursor),
+        )
+    }
+
+    #[inline]
+    pub fn set_timer(&self, id: TimerId, timer: Timer) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.set_timer(id, timer))
+    }
+
+    #[inline]
+    pub fn cancel_timer(&self, id: TimerId) -> bool {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.cancel_timer(id))
+    }
+
+    #[inline]
+    pub fn set_precise_wait(&self, precise: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.set_precise_wait(precise))
+    }
+
+    #[inline]
+    pub fn set_gamepads_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.set_gamepads_enabled(enabled))
+    }
+
+    #[inline]
+    pub fn rumble_gamepad(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.rumble_gamepad(gamepad_id, strong, weak, duration))
+    }
+
+    #[inline]
+    pub fn perform_haptic_feedback(&self, _pattern: HapticPattern) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn input_devices(&self) -> Result<Vec<InputDevice>, ExternalError> {
+        match *self {
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => Ok(evlp.input_devices()),
+            #[cfg(feature = "wayland")]
+            EventLoopWindowTarget::Wayland(ref evlp) => Ok(evlp.input_devices()),
+            #[cfg(feature = "headless")]
+            EventLoopWindowTarget::Headless(_) => {
+                Err(ExternalError::NotSupported(NotSupportedError::new()))
+            }
+        }
+    }
+
+    #[inline]
+    #[cfg_attr(not(feature = "x11"), allow(unused_variables))]
+    pub fn set_hid_reports_enabled(
+        &self,
+        device: DeviceId,
+        enabled: bool,
+    ) -> Result<(), ExternalError> {
+        match (self, device) {
+            #[cfg(feature = "x11")]
+            (EventLoopWindowTarget::X(ref evlp), DeviceId::X(device)) => {
+                evlp.set_hid_reports_enabled(device, enabled)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[inline]
+    pub fn keyboard_layouts(&self) -> Result<Vec<KeyboardLayout>, ExternalError> {
+        match *self {
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.keyboard_layouts(),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[inline]
+    pub fn current_keyboard_layout(&self) -> Result<KeyboardLayout, ExternalError> {
+        match *self {
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.current_keyboard_layout(),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[inline]
+    pub fn scancode_to_text(
+        &self,
+        scancode: ScanCode,
+        modifiers: ModifiersState,
+    ) -> Result<Option<String>, ExternalError> {
+        #[cfg(not(feature = "x11"))]
+        let _ = (scancode, modifiers);
+        match *self {
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.scancode_to_text(scancode, modifiers),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[inline]
+    pub fn key_repeat(&self) -> Result<Option<KeyRepeat>, ExternalError> {
+        match *self {
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.key_repeat(),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[inline]
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.lock_keys())
+    }
+
+    #[inline]
+    pub fn set_lock_keys(&self, lock_keys: LockKeys, on: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.set_lock_keys(lock_keys, on))
+    }
+
+    #[inline]
+    pub fn register_global_hotkey(
+        &self,
+        accelerator: Accelerator,
+    ) -> Result<HotkeyId, ExternalError> {
+        #[cfg(not(feature = "x11"))]
+        let _ = accelerator;
+        match *self {
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.register_global_hotkey(accelerator),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[inline]
+    pub fn unregister_global_hotkey(&self, id: HotkeyId) -> bool {
+        #[cfg(not(feature = "x11"))]
+        let _ = id;
+        match *self {
+            #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(ref evlp) => evlp.unregister_global_hotkey(id),
+            #[cfg(any(feature = "wayland", feature = "headless"))]
+            _ => false,
         }
     }
 }
@@ -742,10 +1499,10 @@ fn sticky_exit_callback<T, F>(
 ) where
     F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
 {
-    // make ControlFlow::Exit sticky by providing a dummy
-    // control flow reference if it is already Exit.
-    let mut dummy = ControlFlow::Exit;
-    let cf = if *control_flow == ControlFlow::Exit {
+    // make ControlFlow::ExitWithCode sticky by providing a dummy
+    // control flow reference if it is already exiting.
+    let mut dummy = *control_flow;
+    let cf = if let ControlFlow::ExitWithCode(_) = *control_flow {
         &mut dummy
     } else {
         control_flow
@@ -754,6 +1511,52 @@ fn sticky_exit_callback<T, F>(
     callback(evt, target, cf)
 }
 
+/// The shortest of the timeouts, where `None` means waiting indefinitely.
+fn min_timeout(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// The instant of the time of the clock, such as a presentation timestamp, from how long ago it
+/// was as per the clock. `None` if the clock can't be read.
+#[cfg(any(feature = "x11", feature = "wayland"))]
+fn instant_from_clock(clock_id: libc::clockid_t, time: Duration) -> Option<std::time::Instant> {
+    let now = std::time::Instant::now();
+    let mut clock_now: libc::timespec = unsafe { std::mem::zeroed() };
+    if unsafe { libc::clock_gettime(clock_id, &mut clock_now) } != 0 {
+        return None;
+    }
+    let clock_now = Duration::new(clock_now.tv_sec as u64, clock_now.tv_nsec as u32);
+    // The presentations of the frames may be predicted a bit ahead of time.
+    match clock_now.checked_sub(time) {
+        Some(elapsed) => now.checked_sub(elapsed),
+        None => now.checked_add(time - clock_now),
+    }
+}
+
+/// The instant of the timestamp of an input event, in milliseconds of the monotonic clock
+/// wrapping at 32 bits, as the X server and the compositors give them. The timestamps which
+/// aren't from that clock give the current instant instead.
+#[cfg(any(feature = "x11", feature = "wayland"))]
+fn instant_from_event_time(time: u32) -> std::time::Instant {
+    // The events are never queued that long, unlike the timestamps of the other clocks.
+    const MAX_EVENT_AGE: Duration = Duration::from_secs(1);
+
+    let now = std::time::Instant::now();
+    let mut clock_now: libc::timespec = unsafe { std::mem::zeroed() };
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut clock_now) } != 0 {
+        return now;
+    }
+    let clock_now = clock_now.tv_sec as u64 * 1000 + clock_now.tv_nsec as u64 / 1_000_000;
+    let elapsed = Duration::from_millis((clock_now as u32).wrapping_sub(time) as u64);
+    if elapsed > MAX_EVENT_AGE {
+        return now;
+    }
+    now.checked_sub(elapsed).unwrap_or(now)
+}
+
 fn assert_is_main_thread(suggested_method: &str) {
     if !is_main_thread() {
         panic!(
@@ -0,0 +1,160 @@
+//! The precise waits of `ControlFlow::WaitUntil`, which wake the event loop up with a timerfd
+//! instead of the millisecond timeout of the poll.
+
+use std::{
+    cell::{Cell, RefCell},
+    io,
+    rc::Rc,
+    time::Instant,
+};
+
+#[cfg(target_os = "linux")]
+use std::{mem, os::unix::io::RawFd, ptr};
+
+use crate::event_loop::ControlFlow;
+
+#[derive(Default)]
+pub struct PreciseWait {
+    enabled: Cell<bool>,
+    /// Whether the timer was registered with the poll of the event loop.
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    registered: Cell<bool>,
+    /// The timer is kept once it's created, so the poll it is registered with keeps it.
+    timer: RefCell<Option<Rc<TimerFd>>>,
+}
+
+impl PreciseWait {
+    pub fn set_enabled(&self, enabled: bool) -> io::Result<()> {
+        if enabled && self.timer.borrow().is_none() {
+            *self.timer.borrow_mut() = TimerFd::new()?.map(Rc::new);
+        }
+        self.enabled.set(enabled);
+        Ok(())
+    }
+
+    /// The timer, once the precise waits were enabled.
+    pub fn timer(&self) -> Option<Rc<TimerFd>> {
+        self.timer.borrow().clone()
+    }
+
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    pub fn is_registered(&self) -> bool {
+        self.registered.get()
+    }
+
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    pub fn set_registered(&self) {
+        self.registered.set(true);
+    }
+
+    /// Arms the timer for the deadline of the `control_flow`, returning whether it wakes the
+    /// event loop up, so the poll doesn't need to time out.
+    pub fn arm(&self, control_flow: ControlFlow) -> bool {
+        let timer = match self.timer() {
+            Some(timer) => timer,
+            None => return false,
+        };
+        let deadline = match control_flow {
+            ControlFlow::WaitUntil(deadline) if self.enabled.get() => Some(deadline),
+            _ => None,
+        };
+        // The poll times out as usual if the timer can't be armed.
+        timer.set_deadline(deadline).is_ok() && deadline.is_some()
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub struct TimerFd {
+    fd: RawFd,
+}
+
+#[cfg(target_os = "linux")]
+impl TimerFd {
+    fn new() -> io::Result<Option<Self>> {
+        let fd = unsafe {
+            libc::timerfd_create(
+                libc::CLOCK_MONOTONIC,
+                libc::TFD_CLOEXEC | libc::TFD_NONBLOCK,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Some(TimerFd { fd }))
+    }
+
+    pub fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Arms the timer for the `deadline`, or disarms it.
+    fn set_deadline(&self, deadline: Option<Instant>) -> io::Result<()> {
+        let mut spec: libc::itimerspec = unsafe { mem::zeroed() };
+        let mut flags = 0;
+        if let Some(deadline) = deadline {
+            // `Instant` is based on the monotonic clock, which the timer follows. Taking the
+            // time of the clock last can only make the timer fire late, not early.
+            let delay = deadline.saturating_duration_since(Instant::now());
+            let mut now: libc::timespec = unsafe { mem::zeroed() };
+            unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) };
+
+            let mut nanos = now.tv_nsec + delay.subsec_nanos() as libc::c_long;
+            let mut secs = now.tv_sec + delay.as_secs() as libc::time_t;
+            if nanos >= 1_000_000_000 {
+                nanos -= 1_000_000_000;
+                secs += 1;
+            }
+            spec.it_value = libc::timespec {
+                tv_sec: secs,
+                tv_nsec: nanos,
+            };
+            flags = libc::TFD_TIMER_ABSTIME;
+        }
+        if unsafe { libc::timerfd_settime(self.fd, flags, &spec, ptr::null_mut()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Acknowledges the expiration of the timer, once the poll reported it.
+    pub fn clear(&self) {
+        let mut expirations = 0u64;
+        unsafe {
+            libc::read(
+                self.fd,
+                &mut expirations as *mut u64 as *mut libc::c_void,
+                mem::size_of::<u64>(),
+            )
+        };
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for TimerFd {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+/// The BSDs poll with kqueue, whose timeouts are already precise, so there's no timer.
+#[cfg(not(target_os = "linux"))]
+pub enum TimerFd {}
+
+#[cfg(not(target_os = "linux"))]
+impl TimerFd {
+    fn new() -> io::Result<Option<Self>> {
+        Ok(None)
+    }
+
+    pub fn fd(&self) -> std::os::unix::io::RawFd {
+        match *self {}
+    }
+
+    fn set_deadline(&self, _deadline: Option<Instant>) -> io::Result<()> {
+        match *self {}
+    }
+
+    pub fn clear(&self) {
+        match *self {}
+    }
+}
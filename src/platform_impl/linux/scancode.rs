@@ -0,0 +1,167 @@
+//! The physical keys of the scancodes, which are the evdev keycodes on X11 and Wayland alike.
+
+use crate::event::{ScanCode, VirtualKeyCode};
+
+/// The keys at the position of the scancodes on a US keyboard.
+const SCANCODES: &[(ScanCode, VirtualKeyCode)] = &[
+    (1, VirtualKeyCode::Escape),
+    (2, VirtualKeyCode::Key1),
+    (3, VirtualKeyCode::Key2),
+    (4, VirtualKeyCode::Key3),
+    (5, VirtualKeyCode::Key4),
+    (6, VirtualKeyCode::Key5),
+    (7, VirtualKeyCode::Key6),
+    (8, VirtualKeyCode::Key7),
+    (9, VirtualKeyCode::Key8),
+    (10, VirtualKeyCode::Key9),
+    (11, VirtualKeyCode::Key0),
+    (12, VirtualKeyCode::Minus),
+    (13, VirtualKeyCode::Equals),
+    (14, VirtualKeyCode::Back),
+    (15, VirtualKeyCode::Tab),
+    (16, VirtualKeyCode::Q),
+    (17, VirtualKeyCode::W),
+    (18, VirtualKeyCode::E),
+    (19, VirtualKeyCode::R),
+    (20, VirtualKeyCode::T),
+    (21, VirtualKeyCode::Y),
+    (22, VirtualKeyCode::U),
+    (23, VirtualKeyCode::I),
+    (24, VirtualKeyCode::O),
+    (25, VirtualKeyCode::P),
+    (26, VirtualKeyCode::LBracket),
+    (27, VirtualKeyCode::RBracket),
+    (28, VirtualKeyCode::Return),
+    (29, VirtualKeyCode::LControl),
+    (30, VirtualKeyCode::A),
+    (31, VirtualKeyCode::S),
+    (32, VirtualKeyCode::D),
+    (33, VirtualKeyCode::F),
+    (34, VirtualKeyCode::G),
+    (35, VirtualKeyCode::H),
+    (36, VirtualKeyCode::J),
+    (37, VirtualKeyCode::K),
+    (38, VirtualKeyCode::L),
+    (39, VirtualKeyCode::Semicolon),
+    (40, VirtualKeyCode::Apostrophe),
+    (41, VirtualKeyCode::Grave),
+    (42, VirtualKeyCode::LShift),
+    (43, VirtualKeyCode::Backslash),
+    (44, VirtualKeyCode::Z),
+    (45, VirtualKeyCode::X),
+    (46, VirtualKeyCode::C),
+    (47, VirtualKeyCode::V),
+    (48, VirtualKeyCode::B),
+    (49, VirtualKeyCode::N),
+    (50, VirtualKeyCode::M),
+    (51, VirtualKeyCode::Comma),
+    (52, VirtualKeyCode::Period),
+    (53, VirtualKeyCode::Slash),
+    (54, VirtualKeyCode::RShift),
+    (55, VirtualKeyCode::NumpadMultiply),
+    (56, VirtualKeyCode::LAlt),
+    (57, VirtualKeyCode::Space),
+    (58, VirtualKeyCode::Capital),
+    (59, VirtualKeyCode::F1),
+    (60, VirtualKeyCode::F2),
+    (61, VirtualKeyCode::F3),
+    (62, VirtualKeyCode::F4),
+    (63, VirtualKeyCode::F5),
+    (64, VirtualKeyCode::F6),
+    (65, VirtualKeyCode::F7),
+    (66, VirtualKeyCode::F8),
+    (67, VirtualKeyCode::F9),
+    (68, VirtualKeyCode::F10),
+    (69, VirtualKeyCode::Numlock),
+    (70, VirtualKeyCode::Scroll),
+    (71, VirtualKeyCode::Numpad7),
+    (72, VirtualKeyCode::Numpad8),
+    (73, VirtualKeyCode::Numpad9),
+    (74, VirtualKeyCode::NumpadSubtract),
+    (75, VirtualKeyCode::Numpad4),
+    (76, VirtualKeyCode::Numpad5),
+    (77, VirtualKeyCode::Numpad6),
+    (78, VirtualKeyCode::NumpadAdd),
+    (79, VirtualKeyCode::Numpad1),
+    (80, VirtualKeyCode::Numpad2),
+    (81, VirtualKeyCode::Numpad3),
+    (82, VirtualKeyCode::Numpad0),
+    (83, VirtualKeyCode::NumpadDecimal),
+    (86, VirtualKeyCode::OEM102),
+    (87, VirtualKeyCode::F11),
+    (88, VirtualKeyCode::F12),
+    (89, VirtualKeyCode::AbntC1),
+    (92, VirtualKeyCode::Convert),
+    (93, VirtualKeyCode::Kana),
+    (94, VirtualKeyCode::NoConvert),
+    (96, VirtualKeyCode::NumpadEnter),
+    (97, VirtualKeyCode::RControl),
+    (98, VirtualKeyCode::NumpadDivide),
+    (99, VirtualKeyCode::Snapshot),
+    (100, VirtualKeyCode::RAlt),
+    (102, VirtualKeyCode::Home),
+    (103, VirtualKeyCode::Up),
+    (104, VirtualKeyCode::PageUp),
+    (105, VirtualKeyCode::Left),
+    (106, VirtualKeyCode::Right),
+    (107, VirtualKeyCode::End),
+    (108, VirtualKeyCode::Down),
+    (109, VirtualKeyCode::PageDown),
+    (110, VirtualKeyCode::Insert),
+    (111, VirtualKeyCode::Delete),
+    (113, VirtualKeyCode::Mute),
+    (114, VirtualKeyCode::VolumeDown),
+    (115, VirtualKeyCode::VolumeUp),
+    (116, VirtualKeyCode::Power),
+    (117, VirtualKeyCode::NumpadEquals),
+    (119, VirtualKeyCode::Pause),
+    (121, VirtualKeyCode::NumpadComma),
+    (124, VirtualKeyCode::Yen),
+    (125, VirtualKeyCode::LWin),
+    (126, VirtualKeyCode::RWin),
+    (127, VirtualKeyCode::Apps),
+    (133, VirtualKeyCode::Copy),
+    (135, VirtualKeyCode::Paste),
+    (137, VirtualKeyCode::Cut),
+    (140, VirtualKeyCode::Calculator),
+    (142, VirtualKeyCode::Sleep),
+    (143, VirtualKeyCode::Wake),
+    (155, VirtualKeyCode::Mail),
+    (157, VirtualKeyCode::MyComputer),
+    (158, VirtualKeyCode::WebBack),
+    (159, VirtualKeyCode::WebForward),
+    (163, VirtualKeyCode::NextTrack),
+    (164, VirtualKeyCode::PlayPause),
+    (165, VirtualKeyCode::PrevTrack),
+    (166, VirtualKeyCode::MediaStop),
+    (172, VirtualKeyCode::WebHome),
+    (173, VirtualKeyCode::WebRefresh),
+    (183, VirtualKeyCode::F13),
+    (184, VirtualKeyCode::F14),
+    (185, VirtualKeyCode::F15),
+    (186, VirtualKeyCode::F16),
+    (187, VirtualKeyCode::F17),
+    (188, VirtualKeyCode::F18),
+    (189, VirtualKeyCode::F19),
+    (190, VirtualKeyCode::F20),
+    (191, VirtualKeyCode::F21),
+    (192, VirtualKeyCode::F22),
+    (193, VirtualKeyCode::F23),
+    (194, VirtualKeyCode::F24),
+    (217, VirtualKeyCode::WebSearch),
+    (226, VirtualKeyCode::MediaSelect),
+];
+
+pub fn scancode_to_key(scancode: ScanCode) -> Option<VirtualKeyCode> {
+    SCANCODES
+        .iter()
+        .find(|&&(code, _)| code == scancode)
+        .map(|&(_, key)| key)
+}
+
+pub fn key_to_scancode(key: VirtualKeyCode) -> Option<ScanCode> {
+    SCANCODES
+        .iter()
+        .find(|&&(_, code_key)| code_key == key)
+        .map(|&(scancode, _)| scancode)
+}
@@ -0,0 +1,71 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::event_loop::{Timer, TimerId};
+
+/// The timers set by the user, which bound the time the poll waits for.
+#[derive(Default)]
+pub struct Timers {
+    timers: HashMap<TimerId, ScheduledTimer>,
+}
+
+struct ScheduledTimer {
+    deadline: Instant,
+    /// The interval of the repeating timers.
+    interval: Option<Duration>,
+}
+
+impl Timers {
+    pub fn set(&mut self, id: TimerId, timer: Timer) {
+        let scheduled = match timer {
+            Timer::At(deadline) => ScheduledTimer {
+                deadline,
+                interval: None,
+            },
+            Timer::Every(interval) => ScheduledTimer {
+                deadline: Instant::now() + interval,
+                interval: Some(interval),
+            },
+        };
+        self.timers.insert(id, scheduled);
+    }
+
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        self.timers.remove(&id).is_some()
+    }
+
+    /// The instant the next timer fires at.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.values().map(|timer| timer.deadline).min()
+    }
+
+    /// Takes the timers which fired by `now`, in the order they fired, rescheduling the
+    /// repeating ones.
+    pub fn take_fired(&mut self, now: Instant) -> Vec<TimerId> {
+        let mut fired: Vec<_> = self
+            .timers
+            .iter()
+            .filter(|(_, timer)| timer.deadline <= now)
+            .map(|(id, timer)| (timer.deadline, *id))
+            .collect();
+        fired.sort();
+
+        for (_, id) in &fired {
+            let timer = self.timers.get_mut(id).unwrap();
+            match timer.interval {
+                // The missed intervals are skipped, so the timer only fires once for them.
+                Some(interval) if interval > Duration::from_secs(0) => {
+                    let missed = (now - timer.deadline).as_nanos() / interval.as_nanos() + 1;
+                    timer.deadline += Duration::from_nanos((interval.as_nanos() * missed) as u64);
+                }
+                Some(_) => timer.deadline = now,
+                None => {
+                    self.timers.remove(id);
+                }
+            }
+        }
+        fired.into_iter().map(|(_, id)| id).collect()
+    }
+}
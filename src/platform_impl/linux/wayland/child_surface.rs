@@ -0,0 +1,113 @@
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
+use sctk::reexports::client::protocol::wl_subsurface::WlSubsurface;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Display;
+use sctk::reexports::protocols::viewporter::client::wp_viewport::WpViewport;
+use sctk::reexports::protocols::viewporter::client::wp_viewporter::WpViewporter;
+
+use raw_window_handle::unix::WaylandHandle;
+
+use crate::dpi::{LogicalPosition, LogicalSize, Position, Size};
+use crate::error::{ExternalError, NotSupportedError};
+
+use super::{EventLoopWindowTarget, Window};
+
+/// A subsurface of a window, which is committed independently of it.
+pub struct ChildSurface {
+    /// The Wayland display.
+    display: Display,
+
+    /// The surface of the window, whose scale factor the positions and sizes are in.
+    parent: WlSurface,
+
+    /// The underlying wl_surface.
+    surface: WlSurface,
+
+    /// The role of the surface.
+    subsurface: WlSubsurface,
+
+    /// The viewport scaling the buffers to the size of the surface, if the compositor has one.
+    viewport: Option<WpViewport>,
+}
+
+impl ChildSurface {
+    pub fn new<T>(
+        event_loop_window_target: &EventLoopWindowTarget<T>,
+        parent: &Window,
+        position: Position,
+        size: Size,
+    ) -> Result<Self, ExternalError> {
+        let env = &event_loop_window_target.env;
+        let subcompositor = env
+            .get_global::<WlSubcompositor>()
+            .ok_or_else(|| ExternalError::NotSupported(NotSupportedError::new()))?;
+
+        let surface = env.create_surface().detach();
+        let subsurface = subcompositor
+            .get_subsurface(&surface, parent.surface())
+            .detach();
+        // The child is presented to without waiting for the commits of the window.
+        subsurface.set_desync();
+
+        // The input over the child goes to the window, with an empty input region.
+        let region = env.require_global::<WlCompositor>().create_region();
+        surface.set_input_region(Some(&region.detach()));
+        region.destroy();
+
+        let viewport = env
+            .get_global::<WpViewporter>()
+            .map(|viewporter| viewporter.get_viewport(&surface).detach());
+
+        let child_surface = Self {
+            display: event_loop_window_target.display.clone(),
+            parent: parent.surface().clone(),
+            surface,
+            subsurface,
+            viewport,
+        };
+        child_surface.set_position(position);
+        child_surface.set_size(size);
+
+        Ok(child_surface)
+    }
+
+    #[inline]
+    pub fn set_position(&self, position: Position) {
+        // The position is applied with the next commit of the window.
+        let position: LogicalPosition<i32> = position.to_logical(self.scale_factor());
+        self.subsurface.set_position(position.x, position.y);
+    }
+
+    #[inline]
+    pub fn set_size(&self, size: Size) {
+        // Without a viewport, the size is the one of the buffers attached.
+        if let Some(viewport) = self.viewport.as_ref() {
+            let size: LogicalSize<u32> = size.to_logical(self.scale_factor());
+            viewport.set_destination(size.width.max(1) as i32, size.height.max(1) as i32);
+        }
+    }
+
+    #[inline]
+    pub fn window_handle(&self) -> WaylandHandle {
+        WaylandHandle {
+            display: self.display.get_display_ptr() as *mut _,
+            surface: self.surface.as_ref().c_ptr() as *mut _,
+            ..WaylandHandle::empty()
+        }
+    }
+
+    fn scale_factor(&self) -> f64 {
+        sctk::get_surface_scale_factor(&self.parent) as f64
+    }
+}
+
+impl Drop for ChildSurface {
+    fn drop(&mut self) {
+        if let Some(viewport) = self.viewport.take() {
+            viewport.destroy();
+        }
+        self.subsurface.destroy();
+        self.surface.destroy();
+    }
+}
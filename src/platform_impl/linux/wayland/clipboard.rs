@@ -0,0 +1,453 @@
+//! Clipboard handling.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Arc;
+use std::thread;
+
+use sctk::data_device::{DataSourceEvent, ReadPipe, WritePipe};
+use sctk::environment::Environment;
+use sctk::primary_selection::PrimarySelectionSourceEvent;
+use sctk::reexports::calloop::channel::Sender;
+use sctk::reexports::client::protocol::wl_data_device_manager::{
+    DndAction as WlDndAction, WlDataDeviceManager,
+};
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::shm::MemPool;
+
+use crate::clipboard::{
+    ClipboardConsumer, ClipboardKind, ClipboardMimedContent, ClipboardProvider, MimePicker,
+    MimeType,
+};
+use crate::dnd::{DndAction, DndActions, DragData, DragIcon};
+use crate::error::{ExternalError, NotSupportedError};
+use crate::event::WindowEvent;
+use crate::platform_impl::OsError;
+
+use super::cursor::image_surface;
+use super::env::WinitEnv;
+use super::event_loop::WinitState;
+use super::{EventLoopWindowTarget, WindowId};
+
+pub struct ClipboardManager {
+    /// Environment to create data sources.
+    env: Environment<WinitEnv>,
+
+    /// The seat with the latest input event and the serial of that event.
+    ///
+    /// Setting the selection requires a serial of a recent input event.
+    latest_serial: Option<(WlSeat, u32)>,
+
+    /// Sender of the content read for the given window back to the event loop.
+    content_sender: Sender<(WindowId, ClipboardKind, Option<ClipboardMimedContent>)>,
+
+    /// The source of each selection currently owned by the application.
+    ///
+    /// The compositor reports the selections set by the application as well, so this is used
+    /// to tell them apart from the ones set by other clients.
+    owned_sources: HashMap<ClipboardKind, u32>,
+
+    /// The identifier of the latest created source.
+    latest_source: u32,
+
+    /// The window the ongoing drag was started from, along with the provider of the dragged
+    /// data.
+    local_drag: Option<(WindowId, Arc<dyn ClipboardProvider>)>,
+
+    /// Whether the data of the ongoing drag was dropped into one of our windows.
+    dropped_inside: bool,
+}
+
+impl ClipboardManager {
+    pub fn new(
+        env: Environment<WinitEnv>,
+        content_sender: Sender<(WindowId, ClipboardKind, Option<ClipboardMimedContent>)>,
+    ) -> Self {
+        Self {
+            env,
+            latest_serial: None,
+            content_sender,
+            owned_sources: HashMap::new(),
+            latest_source: 0,
+            local_drag: None,
+            dropped_inside: false,
+        }
+    }
+
+    /// Whether the selection is set by the application.
+    pub fn owns_selection(&self, kind: ClipboardKind) -> bool {
+        self.owned_sources.contains_key(&kind)
+    }
+
+    /// Forget about the selection source, since it was replaced.
+    fn source_cancelled(&mut self, kind: ClipboardKind, source: u32) {
+        if self.owned_sources.get(&kind) == Some(&source) {
+            self.owned_sources.remove(&kind);
+        }
+    }
+
+    /// The window the ongoing drag was started from, along with the provider of the dragged
+    /// data, which is read directly when dropped into our windows.
+    pub fn local_drag(&self) -> Option<(WindowId, Arc<dyn ClipboardProvider>)> {
+        self.local_drag
+            .as_ref()
+            .map(|(window_id, provider)| (*window_id, Arc::clone(provider)))
+    }
+
+    /// Remember that the data of the ongoing drag was dropped into one of our windows.
+    pub fn set_dropped_inside(&mut self) {
+        self.dropped_inside = true;
+    }
+
+    /// Remember the serial of the input event received on the given seat.
+    pub fn set_latest_serial(&mut self, seat: WlSeat, serial: u32) {
+        self.latest_serial = Some((seat, serial));
+    }
+
+    pub fn set_clipboard(
+        &mut self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        let supported = match kind {
+            ClipboardKind::Clipboard => self.env.get_global::<WlDataDeviceManager>().is_some(),
+            ClipboardKind::Primary => self.env.get_primary_selection_manager().is_some(),
+        };
+        if !supported {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let (seat, serial) = match self.latest_serial.as_ref() {
+            Some(latest_serial) => latest_serial,
+            None => {
+                return Err(ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "no input event to take the clipboard ownership with."
+                ))))
+            }
+        };
+
+        let mime_types = provider
+            .mime_types()
+            .iter()
+            .flat_map(|mime_type| mime_type.mime_types())
+            .map(|mime| mime.to_string())
+            .collect();
+
+        self.latest_source = self.latest_source.wrapping_add(1);
+        let source_id = self.latest_source;
+
+        let result = match kind {
+            ClipboardKind::Clipboard => {
+                let source = self.env.new_data_source(
+                    mime_types,
+                    move |event, mut dispatch_data| match event {
+                        DataSourceEvent::Send { mime_type, pipe } => {
+                            write_content(pipe, &provider, &mime_type)
+                        }
+                        DataSourceEvent::Cancelled => {
+                            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                            winit_state.clipboard.source_cancelled(kind, source_id);
+                        }
+                        _ => (),
+                    },
+                );
+                let source = Some(source);
+                self.env
+                    .with_data_device(seat, |device| device.set_selection(&source, *serial))
+            }
+            ClipboardKind::Primary => {
+                let source = self.env.new_primary_selection_source(
+                    mime_types,
+                    move |event, mut dispatch_data| match event {
+                        PrimarySelectionSourceEvent::Send { mime_type, pipe } => {
+                            write_content(pipe, &provider, &mime_type)
+                        }
+                        PrimarySelectionSourceEvent::Cancelled => {
+                            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                            winit_state.clipboard.source_cancelled(kind, source_id);
+                        }
+                    },
+                );
+                let source = Some(source);
+                self.env
+                    .with_primary_selection(seat, |device| device.set_selection(&source, *serial))
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                self.owned_sources.insert(kind, source_id);
+                Ok(())
+            }
+            Err(_) => Err(ExternalError::Os(os_error!(OsError::WaylandMisc(
+                "failed to access the data device."
+            )))),
+        }
+    }
+
+    /// Read the clipboard content for the given window.
+    ///
+    /// The content is passed to the consumer, or delivered through the event loop once read if
+    /// there's no consumer. `None` is delivered in case the content couldn't be read.
+    pub fn read_clipboard(
+        &self,
+        window_id: WindowId,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) {
+        let consumer = consumer.unwrap_or_else(|| {
+            let content_sender = self.content_sender.clone();
+            Box::new(move |data: Option<(MimeType, &mut dyn Read)>| {
+                let content = data.and_then(|(mime_type, reader)| {
+                    let mut data = Vec::new();
+                    match reader.read_to_end(&mut data) {
+                        Ok(_) => Some(ClipboardMimedContent::from_bytes(mime_type, data)),
+                        Err(err) => {
+                            warn!("Failed to read the clipboard: {}", err);
+                            None
+                        }
+                    }
+                });
+                let _ = content_sender.send((window_id, kind, content));
+            })
+        });
+
+        let seat = match self.latest_serial.as_ref() {
+            Some((seat, _)) => seat,
+            None => return consumer.consume(None),
+        };
+
+        let mut received = None;
+        let _ = match kind {
+            ClipboardKind::Clipboard => self.env.with_data_device(seat, |device| {
+                received = device.with_selection(|offer| {
+                    let offer = offer?;
+                    let mimes = offer.with_mime_types(|mimes| mimes.to_vec());
+                    receive_offer(mimes, picker, |mime| offer.receive(mime))
+                });
+            }),
+            ClipboardKind::Primary => self.env.with_primary_selection(seat, |device| {
+                received = device.with_selection(|offer| {
+                    let offer = offer?;
+                    let mimes = offer.with_mime_types(|mimes| mimes.to_vec());
+                    receive_offer(mimes, picker, |mime| offer.receive(mime))
+                });
+            }),
+        };
+
+        let (mime_type, mut pipe) = match received {
+            Some(received) => received,
+            None => return consumer.consume(None),
+        };
+
+        // Read the data on a separate thread, since the data is only sent once the request
+        // reaches the owner, and the owner could be slow.
+        thread::spawn(move || consumer.consume(Some((mime_type, &mut pipe))));
+    }
+}
+
+impl ClipboardManager {
+    /// Start dragging the data from the surface of the given window, with the implicit grab of the
+    /// given serial on the pointer of the seat.
+    pub fn start_drag(
+        &mut self,
+        window_id: WindowId,
+        surface: &WlSurface,
+        seat: &WlSeat,
+        serial: u32,
+        data: DragData,
+        actions: DndActions,
+    ) -> Result<(), ExternalError> {
+        let version = match self.env.get_global::<WlDataDeviceManager>() {
+            Some(manager) => manager.as_ref().version(),
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
+
+        let provider = data.provider;
+        let local_provider = Arc::clone(&provider);
+        // The pool is kept along with the surface, until the drag is over.
+        let mut icon = data.icon.and_then(|icon| self.icon_surface(&icon));
+        let icon_surface = icon.as_ref().map(|(surface, _)| surface.clone());
+        let mime_types = provider
+            .mime_types()
+            .iter()
+            .flat_map(|mime_type| mime_type.mime_types())
+            .map(|mime| mime.to_string())
+            .collect();
+
+        // The sources before the version 3 don't negotiate the action, and always copy the data.
+        let default_action = if version >= 3 {
+            None
+        } else {
+            Some(DndAction::Copy)
+        };
+        // The latest action chosen by the drop target, which is the one to take once finished.
+        let mut chosen_action = default_action;
+        // Whether the drop target under the pointer accepts the data.
+        let mut accepted = false;
+        // The action the drop target was last reported to accept the data with.
+        let mut entered_action = None;
+        let source = self
+            .env
+            .new_data_source(mime_types, move |event, mut dispatch_data| {
+                if let DataSourceEvent::Send { mime_type, pipe } = event {
+                    return write_content(pipe, &provider, &mime_type);
+                }
+
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                let event = match event {
+                    DataSourceEvent::Target { mime_type } => {
+                        accepted = mime_type.is_some();
+                        None
+                    }
+                    DataSourceEvent::Action { action } => {
+                        chosen_action = from_wayland_action(action);
+                        None
+                    }
+                    DataSourceEvent::Finished => Some(WindowEvent::DragFinished {
+                        action: chosen_action.unwrap_or(DndAction::Copy),
+                        outside: !winit_state.clipboard.dropped_inside,
+                    }),
+                    DataSourceEvent::Cancelled => Some(WindowEvent::DragCancelled),
+                    _ => return,
+                };
+
+                match event {
+                    Some(event) => {
+                        if let Some((surface, _)) = icon.take() {
+                            surface.destroy();
+                        }
+
+                        winit_state.clipboard.local_drag = None;
+                        winit_state.event_sink.push_window_event(event, window_id);
+                    }
+                    // Tell about the target accepting the data, or the action changing.
+                    None => {
+                        let action = chosen_action.filter(|_| accepted);
+                        if action != entered_action {
+                            entered_action = action;
+                            if let Some(action) = action {
+                                winit_state.event_sink.push_window_event(
+                                    WindowEvent::DragEntered { action },
+                                    window_id,
+                                );
+                            }
+                        }
+                    }
+                }
+            });
+
+        let actions = to_wayland_actions(actions);
+        self.env
+            .with_data_device(seat, |device| {
+                device.start_drag(
+                    surface,
+                    Some(source),
+                    actions,
+                    icon_surface.as_ref(),
+                    serial,
+                )
+            })
+            .map_err(|_| {
+                ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "failed to access the data device."
+                )))
+            })?;
+
+        self.local_drag = Some((window_id, local_provider));
+        self.dropped_inside = false;
+        Ok(())
+    }
+
+    /// Create the surface showing the icon under the pointer during a drag.
+    fn icon_surface(&self, icon: &DragIcon) -> Option<(WlSurface, MemPool)> {
+        // The offset moves the hotspot of the image under the pointer.
+        let offset = (-(icon.hotspot.x as i32), -(icon.hotspot.y as i32));
+        match image_surface(&self.env, &icon.image, offset) {
+            Ok(icon_surface) => Some(icon_surface),
+            Err(err) => {
+                warn!("Failed to create the drag icon: {}", err);
+                None
+            }
+        }
+    }
+}
+
+fn to_wayland_actions(actions: DndActions) -> WlDndAction {
+    let mut wayland_actions = WlDndAction::None;
+    if actions.contains(DndActions::COPY) {
+        wayland_actions |= WlDndAction::Copy;
+    }
+    if actions.contains(DndActions::MOVE) {
+        wayland_actions |= WlDndAction::Move;
+    }
+    wayland_actions
+}
+
+pub fn from_wayland_action(action: WlDndAction) -> Option<DndAction> {
+    if action.contains(WlDndAction::Move) {
+        Some(DndAction::Move)
+    } else if action.contains(WlDndAction::Copy) {
+        Some(DndAction::Copy)
+    } else {
+        None
+    }
+}
+
+/// Write the data requested under the given MIME type into the pipe of the receiving client.
+fn write_content(mut pipe: WritePipe, provider: &Arc<dyn ClipboardProvider>, mime: &str) {
+    let mime_type = match provider
+        .mime_types()
+        .into_iter()
+        .find(|mime_type| mime_type.mime_types().contains(&mime))
+    {
+        Some(mime_type) => mime_type,
+        None => return,
+    };
+
+    // Write the data on a separate thread, so we won't block the event loop
+    // if the receiving end is slow.
+    let provider = provider.clone();
+    thread::spawn(move || {
+        if let Err(err) = provider.write_to(&mime_type, &mut pipe) {
+            warn!("Failed to write the clipboard: {}", err);
+        }
+    });
+}
+
+/// Pick the kind of data to receive among the offered MIME types and request it.
+fn receive_offer<F>(
+    mimes: Vec<String>,
+    picker: MimePicker,
+    receive: F,
+) -> Option<(MimeType, ReadPipe)>
+where
+    F: FnOnce(String) -> Result<ReadPipe, ()>,
+{
+    let offered = MimeType::from_mime_types(mimes.iter().map(String::as_str));
+
+    let mime_type = picker(&offered).filter(|mime_type| offered.contains(mime_type))?;
+    let mime = mime_type
+        .mime_types()
+        .into_iter()
+        .find(|mime| mimes.iter().any(|offered| offered == mime))?
+        .to_owned();
+
+    receive(mime).ok().map(|pipe| (mime_type, pipe))
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        self.state
+            .borrow_mut()
+            .clipboard
+            .set_clipboard(kind, provider)
+    }
+}
@@ -0,0 +1,236 @@
+//! Custom cursors, drawn into the surfaces the pointers show over the windows.
+
+use std::error::Error;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sctk::environment::Environment;
+use sctk::reexports::calloop::timer::{Timeout, Timer, TimerHandle};
+use sctk::reexports::calloop::LoopHandle;
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_shm::{Format, WlShm};
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::seat::pointer::{ThemeManager, ThemeSpec, ThemedPointer};
+use sctk::shm::MemPool;
+
+use crate::dpi::PhysicalPosition;
+use crate::icon::{BadIcon, RgbaIcon};
+use crate::window::CursorFrame;
+
+use super::env::WinitEnv;
+use super::event_loop::WinitState;
+use super::{EventLoopWindowTarget, WindowId};
+
+/// The frames of a custom cursor, which every window draws into surfaces of its own since the
+/// surfaces and the pools belong to the event loop thread.
+#[derive(Clone)]
+pub struct CustomCursor {
+    frames: Arc<[CursorFrame]>,
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        image: RgbaIcon,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        let frame = CursorFrame {
+            image,
+            hotspot,
+            delay: Duration::from_secs(0),
+        };
+        Ok(CustomCursor {
+            frames: Arc::new([frame]),
+        })
+    }
+
+    #[inline]
+    pub fn create_animated_cursor(
+        &self,
+        frames: Vec<CursorFrame>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor {
+            frames: frames.into(),
+        })
+    }
+}
+
+/// The timer showing the next frames of the animated cursors of the windows.
+pub fn cursor_timer(
+    loop_handle: &LoopHandle<WinitState>,
+) -> Result<TimerHandle<WindowId>, Box<dyn Error>> {
+    let timer = Timer::new()?;
+    let handle = timer.handle();
+    loop_handle.insert_source(timer, |window_id, _, winit_state| {
+        if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+            window_handle.next_cursor_frame();
+        }
+    })?;
+    Ok(handle)
+}
+
+struct FrameSurface {
+    surface: WlSurface,
+    hotspot: PhysicalPosition<u32>,
+    delay: Duration,
+    _pool: MemPool,
+}
+
+/// The surfaces showing the frames of a custom cursor, with the timeout of the current frame
+/// when the cursor is animated.
+pub struct CursorSurface {
+    frames: Vec<FrameSurface>,
+    current: usize,
+    timer: TimerHandle<WindowId>,
+    window_id: WindowId,
+    timeout: Option<Timeout>,
+}
+
+impl CursorSurface {
+    pub fn new(
+        env: &Environment<WinitEnv>,
+        cursor: &CustomCursor,
+        timer: &TimerHandle<WindowId>,
+        window_id: WindowId,
+    ) -> io::Result<Self> {
+        let frames = cursor
+            .frames
+            .iter()
+            .map(|frame| {
+                let (surface, pool) = image_surface(env, &frame.image, (0, 0))?;
+                Ok(FrameSurface {
+                    surface,
+                    hotspot: frame.hotspot,
+                    delay: frame.delay,
+                    _pool: pool,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let mut cursor_surface = Self {
+            frames,
+            current: 0,
+            timer: timer.clone(),
+            window_id,
+            timeout: None,
+        };
+        cursor_surface.schedule();
+        Ok(cursor_surface)
+    }
+
+    /// The surface of the current frame, with its hotspot.
+    pub fn surface(&self) -> (&WlSurface, PhysicalPosition<u32>) {
+        let frame = &self.frames[self.current];
+        (&frame.surface, frame.hotspot)
+    }
+
+    /// Moves on to the next frame, once the timeout of the current one went off.
+    pub fn next_frame(&mut self) {
+        self.current = (self.current + 1) % self.frames.len();
+        self.schedule();
+    }
+
+    fn schedule(&mut self) {
+        if self.frames.len() > 1 {
+            let delay = self.frames[self.current].delay;
+            self.timeout = Some(self.timer.add_timeout(delay, self.window_id));
+        }
+    }
+}
+
+impl Drop for CursorSurface {
+    fn drop(&mut self) {
+        if let Some(timeout) = self.timeout.take() {
+            self.timer.cancel_timeout(&timeout);
+        }
+        for frame in self.frames.iter() {
+            frame.surface.destroy();
+        }
+    }
+}
+
+/// The cursor theme of the seats and the size of its cursors in logical pixels, which come from
+/// `XCURSOR_THEME` and `XCURSOR_SIZE` as for the theme manager.
+pub fn system_cursor_theme() -> (String, u32) {
+    let theme = std::env::var("XCURSOR_THEME").unwrap_or_else(|_| "default".into());
+    let size = std::env::var("XCURSOR_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(24);
+    (theme, size)
+}
+
+/// The cursor theme of a window, replacing the theme of the seats for the pointers over it.
+pub struct WindowCursorTheme {
+    theme_manager: ThemeManager,
+    /// The pointers themed by the theme, each with a cursor surface of its own.
+    pointers: Vec<ThemedPointer>,
+}
+
+impl WindowCursorTheme {
+    /// Loads the theme, the size of whose cursors is in logical pixels.
+    pub fn new(env: &Environment<WinitEnv>, name: &str, size: u32) -> Self {
+        let theme_manager = ThemeManager::init(
+            ThemeSpec::Precise { name, size },
+            env.require_global::<WlCompositor>(),
+            env.require_global::<WlShm>(),
+        );
+        Self {
+            theme_manager,
+            pointers: Vec::new(),
+        }
+    }
+
+    /// The pointer, themed by the theme.
+    pub fn themed_pointer(&mut self, pointer: &WlPointer) -> &ThemedPointer {
+        let position = self.pointers.iter().position(|p| **p == *pointer);
+        let position = position.unwrap_or_else(|| {
+            let themed_pointer = self.theme_manager.theme_pointer(pointer.clone());
+            self.pointers.push(themed_pointer);
+            self.pointers.len() - 1
+        });
+        &self.pointers[position]
+    }
+}
+
+/// Create a surface showing the image, with the pool holding its pixels.
+///
+/// The offset is the one of the buffer attached to the surface.
+pub fn image_surface(
+    env: &Environment<WinitEnv>,
+    image: &RgbaIcon,
+    offset: (i32, i32),
+) -> io::Result<(WlSurface, MemPool)> {
+    let mut pool = env.create_simple_pool(|_| {})?;
+    let stride = image.width as usize * 4;
+    pool.resize(stride * image.height as usize)?;
+
+    // The format is little-endian and uses premultiplied alpha.
+    for (pixel, rgba) in pool
+        .mmap()
+        .chunks_exact_mut(4)
+        .zip(image.rgba.chunks_exact(4))
+    {
+        let alpha = rgba[3] as u32;
+        pixel[0] = (rgba[2] as u32 * alpha / 255) as u8;
+        pixel[1] = (rgba[1] as u32 * alpha / 255) as u8;
+        pixel[2] = (rgba[0] as u32 * alpha / 255) as u8;
+        pixel[3] = rgba[3];
+    }
+
+    let buffer = pool.buffer(
+        0,
+        image.width as i32,
+        image.height as i32,
+        stride as i32,
+        Format::Argb8888,
+    );
+    let surface = env.create_surface().detach();
+    surface.attach(Some(&buffer), offset.0, offset.1);
+    surface.damage(0, 0, image.width as i32, image.height as i32);
+    surface.commit();
+
+    Ok((surface, pool))
+}
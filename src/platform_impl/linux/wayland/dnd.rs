@@ -0,0 +1,360 @@
+//! Drag and drop handling, for the data dropped into the windows.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::io::FromRawFd;
+use std::sync::Arc;
+use std::thread;
+
+use sctk::reexports::calloop::channel::Sender;
+use sctk::reexports::client::protocol::wl_data_device::Event as DataDeviceEvent;
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction as WlDndAction;
+use sctk::reexports::client::protocol::wl_data_offer::{Event as DataOfferEvent, WlDataOffer};
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Main;
+
+use crate::clipboard::{ClipboardMimedContent, ClipboardProvider, MimeType};
+use crate::dnd::DndAction;
+use crate::dpi::LogicalPosition;
+use crate::event::WindowEvent;
+use crate::platform_impl::platform::WindowId as PlatformWindowId;
+use crate::window::WindowId as RootWindowId;
+
+use super::clipboard;
+use super::event_loop::WinitState;
+use super::WindowId;
+
+/// What the source tells about the data offered to the application.
+#[derive(Default)]
+struct OfferData {
+    mime_types: Vec<String>,
+    /// The action chosen by the compositor.
+    action: Option<WlDndAction>,
+}
+
+/// Keep track of the kinds of data and the actions the offer comes with.
+pub fn init_offer(offer: &Main<WlDataOffer>) {
+    offer
+        .as_ref()
+        .user_data()
+        .set(|| RefCell::new(OfferData::default()));
+    offer.quick_assign(|offer, event, _| {
+        let offer_data = match offer.as_ref().user_data().get::<RefCell<OfferData>>() {
+            Some(offer_data) => offer_data,
+            None => return,
+        };
+        let mut offer_data = offer_data.borrow_mut();
+        match event {
+            DataOfferEvent::Offer { mime_type } => offer_data.mime_types.push(mime_type),
+            DataOfferEvent::Action { dnd_action } => offer_data.action = Some(dnd_action),
+            _ => (),
+        }
+    });
+}
+
+/// The data dragged over one of the windows.
+struct DropOffer {
+    /// The identifier telling the content read for this offer apart from the other ones.
+    id: u32,
+    offer: WlDataOffer,
+    surface: WlSurface,
+    window_id: WindowId,
+    /// The latest position of the pointer on the surface.
+    position: LogicalPosition<f64>,
+    /// The content, once it's read.
+    content: Option<ClipboardMimedContent>,
+    /// Whether the data was dropped, and is delivered once it's read.
+    dropped: bool,
+    /// The window the data is dragged from, if it's dragged from one of our windows.
+    source: Option<WindowId>,
+}
+
+pub struct DndManager {
+    /// The data currently dragged over the windows, or dropped and still being read.
+    ///
+    /// It's boxed, since it's only there during the drags.
+    current: Option<Box<DropOffer>>,
+
+    /// Sender of the content read for the given offer back to the event loop.
+    content_sender: Sender<(u32, Option<ClipboardMimedContent>)>,
+
+    /// The identifier of the latest offer.
+    latest_offer: u32,
+}
+
+impl DndManager {
+    pub fn new(content_sender: Sender<(u32, Option<ClipboardMimedContent>)>) -> Self {
+        Self {
+            current: None,
+            content_sender,
+            latest_offer: 0,
+        }
+    }
+
+    /// Accept the data dragged over the window, and start reading it.
+    ///
+    /// The data is read right away, so the files are known while they're hovered. The data
+    /// dragged from our windows is read straight from its provider.
+    fn enter(
+        &mut self,
+        offer: WlDataOffer,
+        serial: u32,
+        surface: WlSurface,
+        window_id: WindowId,
+        position: LogicalPosition<f64>,
+        local_drag: Option<(WindowId, Arc<dyn ClipboardProvider>)>,
+    ) {
+        let mimes = offer
+            .as_ref()
+            .user_data()
+            .get::<RefCell<OfferData>>()
+            .map(|offer_data| offer_data.borrow().mime_types.clone())
+            .unwrap_or_default();
+        let offered = MimeType::from_mime_types(mimes.iter().map(String::as_str));
+        let mime = MimeType::preferred_drop_mime_type(&offered).and_then(|mime_type| {
+            let mime = mime_type
+                .mime_types()
+                .into_iter()
+                .find(|mime| mimes.iter().any(|offered| offered == mime))?
+                .to_owned();
+            Some((mime_type, mime))
+        });
+        let (mime_type, mime) = match mime {
+            Some(mime) => mime,
+            None => {
+                offer.accept(serial, None);
+                offer.destroy();
+                return;
+            }
+        };
+
+        offer.accept(serial, Some(mime.clone()));
+        if offer.as_ref().version() >= 3 {
+            offer.set_actions(WlDndAction::Copy | WlDndAction::Move, WlDndAction::Copy);
+        }
+
+        self.latest_offer = self.latest_offer.wrapping_add(1);
+        let id = self.latest_offer;
+        let content_sender = self.content_sender.clone();
+        let source = local_drag.as_ref().map(|(source, _)| *source);
+        if let Some((_, provider)) = local_drag {
+            let content = crate::dnd::local_drop_content(&*provider);
+            let _ = content_sender.send((id, content));
+        } else {
+            match receive(&offer, mime) {
+                // Read the data on a separate thread, since the source could be slow.
+                Some(mut pipe) => {
+                    thread::spawn(move || {
+                        let mut data = Vec::new();
+                        let content = match pipe.read_to_end(&mut data) {
+                            Ok(_) => Some(ClipboardMimedContent::from_bytes(mime_type, data)),
+                            Err(err) => {
+                                warn!("Failed to read the dropped data: {}", err);
+                                None
+                            }
+                        };
+                        let _ = content_sender.send((id, content));
+                    });
+                }
+                None => {
+                    let _ = content_sender.send((id, None));
+                }
+            }
+        }
+
+        self.current = Some(Box::new(DropOffer {
+            id,
+            offer,
+            surface,
+            window_id,
+            position,
+            content: None,
+            dropped: false,
+            source,
+        }));
+    }
+}
+
+/// Create a pipe and request the data of the given MIME type to be written into it.
+fn receive(offer: &WlDataOffer, mime: String) -> Option<File> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        warn!("Failed to create a pipe for the dropped data");
+        return None;
+    }
+
+    offer.receive(mime, fds[1]);
+    // The writing end is duplicated once the request is sent.
+    unsafe {
+        libc::close(fds[1]);
+        Some(File::from_raw_fd(fds[0]))
+    }
+}
+
+/// Handle the drag and drop events of a data device.
+pub fn handle_device_event(winit_state: &mut WinitState, event: DataDeviceEvent) {
+    match event {
+        DataDeviceEvent::Enter {
+            serial,
+            surface,
+            x,
+            y,
+            id,
+        } => {
+            leave(winit_state);
+
+            let offer = match id {
+                Some(offer) => offer,
+                None => return,
+            };
+            let window_id = super::make_wid(&surface);
+            if !winit_state.window_map.contains_key(&window_id) {
+                offer.accept(serial, None);
+                offer.destroy();
+                return;
+            }
+
+            let position = LogicalPosition::new(x, y);
+            let local_drag = winit_state.clipboard.local_drag();
+            winit_state
+                .dnd
+                .enter(offer, serial, surface, window_id, position, local_drag);
+        }
+        DataDeviceEvent::Motion { x, y, .. } => {
+            if let Some(current) = winit_state.dnd.current.as_mut() {
+                if !current.dropped {
+                    current.position = LogicalPosition::new(x, y);
+                }
+            }
+        }
+        DataDeviceEvent::Leave => leave(winit_state),
+        DataDeviceEvent::Drop => {
+            let ready = match winit_state.dnd.current.as_mut() {
+                Some(current) if !current.dropped => {
+                    current.dropped = true;
+                    current.content.is_some()
+                }
+                _ => false,
+            };
+            if ready {
+                deliver(winit_state);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Handle the content read for the given offer.
+pub fn content_received(
+    winit_state: &mut WinitState,
+    id: u32,
+    content: Option<ClipboardMimedContent>,
+) {
+    let current = match winit_state.dnd.current.as_mut() {
+        Some(current) if current.id == id => current,
+        _ => return,
+    };
+
+    let content = match content {
+        Some(content) => content,
+        None => {
+            // There's nothing to deliver, so the drop is over.
+            if current.dropped {
+                let current = winit_state.dnd.current.take().unwrap();
+                current.offer.destroy();
+            }
+            return;
+        }
+    };
+
+    if let ClipboardMimedContent::FileList(paths) = &content {
+        for path in paths {
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::HoveredFile(path.clone()), current.window_id);
+        }
+    }
+    current.content = Some(content);
+
+    if current.dropped {
+        deliver(winit_state);
+    }
+}
+
+/// Forget about the data dragged away from the window, unless it's already dropped.
+fn leave(winit_state: &mut WinitState) {
+    match winit_state.dnd.current.as_ref() {
+        Some(current) if !current.dropped => (),
+        _ => return,
+    }
+
+    let current = winit_state.dnd.current.take().unwrap();
+    current.offer.destroy();
+    winit_state
+        .event_sink
+        .push_window_event(WindowEvent::HoveredFileCancelled, current.window_id);
+}
+
+/// Deliver the dropped content, and finish the drop.
+fn deliver(winit_state: &mut WinitState) {
+    let current = match winit_state.dnd.current.take() {
+        Some(current) => current,
+        None => return,
+    };
+    let content = match current.content {
+        Some(content) => content,
+        None => return current.offer.destroy(),
+    };
+
+    let chosen_action = current
+        .offer
+        .as_ref()
+        .user_data()
+        .get::<RefCell<OfferData>>()
+        .and_then(|offer_data| offer_data.borrow().action);
+    let version = current.offer.as_ref().version();
+    // The offers before the version 3 don't negotiate the action, and always copy the data.
+    let action = if version >= 3 {
+        chosen_action
+            .and_then(clipboard::from_wayland_action)
+            .unwrap_or(DndAction::Copy)
+    } else {
+        DndAction::Copy
+    };
+
+    if let ClipboardMimedContent::FileList(paths) = &content {
+        for path in paths {
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::DroppedFile(path.clone()), current.window_id);
+        }
+    }
+
+    if current.source.is_some() {
+        winit_state.clipboard.set_dropped_inside();
+    }
+
+    let scale_factor = sctk::get_surface_scale_factor(&current.surface) as f64;
+    let position = current.position.to_physical(scale_factor);
+    winit_state.event_sink.push_window_event(
+        WindowEvent::Drop {
+            content,
+            position,
+            action,
+            source: current
+                .source
+                .map(|source| RootWindowId(PlatformWindowId::Wayland(source))),
+        },
+        current.window_id,
+    );
+
+    // Finishing is only allowed once an action is chosen.
+    let action_chosen = chosen_action
+        .filter(|action| action.intersects(WlDndAction::Copy | WlDndAction::Move))
+        .is_some();
+    if version >= 3 && action_chosen {
+        current.offer.finish();
+    }
+    current.offer.destroy();
+}
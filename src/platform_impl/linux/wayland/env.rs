@@ -1,21 +1,48 @@
 //! SCTK environment setup.
 
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
 use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_registry::WlRegistry;
 use sctk::reexports::protocols::unstable::xdg_shell::v6::client::zxdg_shell_v6::ZxdgShellV6;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::protocols::unstable::xdg_decoration::v1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1;
 use sctk::reexports::client::protocol::wl_shell::WlShell;
 use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
-use sctk::reexports::client::{Attached, DispatchData};
+use sctk::reexports::client::{Attached, DispatchData, Main};
 use sctk::reexports::client::protocol::wl_shm::WlShm;
 use sctk::reexports::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
+use sctk::reexports::protocols::unstable::keyboard_shortcuts_inhibit::v1::client::zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+use sctk::reexports::protocols::misc::gtk_primary_selection::client::gtk_primary_selection_device_manager::GtkPrimarySelectionDeviceManager;
+use sctk::reexports::protocols::viewporter::client::wp_viewporter::WpViewporter;
+use sctk::reexports::protocols::presentation_time::client::wp_presentation::{self, WpPresentation};
+
+use super::protocols::blur::org_kde_kwin_blur_manager::OrgKdeKwinBlurManager;
+use super::protocols::color_management::wp_color_manager_v1::{self, WpColorManagerV1};
+use super::protocols::kde_output_device::kde_output_device_v2::{
+    self, Capability, KdeOutputDeviceV2, VrrPolicy,
+};
+
+use super::output;
+use crate::monitor::TransferFunction;
 
-use sctk::environment::{Environment, SimpleGlobal};
+use sctk::data_device::{DataDevice, DataDeviceHandler, DataDeviceHandling, DndEvent};
+use sctk::environment::{Environment, GlobalHandler, MultiGlobalHandler, SimpleGlobal};
 use sctk::output::{OutputHandler, OutputHandling, OutputInfo, OutputStatusListener};
+use sctk::primary_selection::{
+    PrimarySelectionDevice, PrimarySelectionDeviceManager, PrimarySelectionHandler,
+    PrimarySelectionHandling,
+};
 use sctk::seat::{SeatData, SeatHandler, SeatHandling, SeatListener};
 use sctk::shell::{Shell, ShellHandler, ShellHandling};
 use sctk::shm::ShmHandler;
@@ -24,18 +51,43 @@ use sctk::shm::ShmHandler;
 #[derive(Debug, Clone, Copy)]
 pub struct WindowingFeatures {
     cursor_grab: bool,
+    pointer_lock: bool,
+    shortcuts_inhibit: bool,
+    blur: bool,
 }
 
 impl WindowingFeatures {
     /// Create `WindowingFeatures` based on the presented interfaces.
     pub fn new(env: &Environment<WinitEnv>) -> Self {
         let cursor_grab = env.get_global::<ZwpPointerConstraintsV1>().is_some();
-        Self { cursor_grab }
+        let pointer_lock = cursor_grab && env.get_global::<ZwpRelativePointerManagerV1>().is_some();
+        let shortcuts_inhibit = env
+            .get_global::<ZwpKeyboardShortcutsInhibitManagerV1>()
+            .is_some();
+        let blur = env.get_global::<OrgKdeKwinBlurManager>().is_some();
+        Self {
+            cursor_grab,
+            pointer_lock,
+            shortcuts_inhibit,
+            blur,
+        }
     }
 
     pub fn cursor_grab(&self) -> bool {
         self.cursor_grab
     }
+
+    pub fn pointer_lock(&self) -> bool {
+        self.pointer_lock
+    }
+
+    pub fn shortcuts_inhibit(&self) -> bool {
+        self.shortcuts_inhibit
+    }
+
+    pub fn blur(&self) -> bool {
+        self.blur
+    }
 }
 
 sctk::environment!(WinitEnv,
@@ -49,11 +101,22 @@ sctk::environment!(WinitEnv,
         ZxdgDecorationManagerV1 => decoration_manager,
         ZwpRelativePointerManagerV1 => relative_pointer_manager,
         ZwpPointerConstraintsV1 => pointer_constraints,
+        ZwpPointerGesturesV1 => pointer_gestures,
         ZwpTextInputManagerV3 => text_input_manager,
+        ZwpTabletManagerV2 => tablet_manager,
+        ZwpKeyboardShortcutsInhibitManagerV1 => shortcuts_inhibit_manager,
+        OrgKdeKwinBlurManager => blur_manager,
+        WpViewporter => viewporter,
+        WpColorManagerV1 => color_manager,
+        WpPresentation => presentation,
+        WlDataDeviceManager => data_device_manager,
+        ZwpPrimarySelectionDeviceManagerV1 => primary_selection_manager,
+        GtkPrimarySelectionDeviceManager => primary_selection_manager,
     ],
     multis = [
         WlSeat => seats,
         WlOutput => outputs,
+        KdeOutputDeviceV2 => output_devices,
     ]
 );
 
@@ -63,6 +126,8 @@ pub struct WinitEnv {
 
     outputs: OutputHandler,
 
+    output_devices: OutputDeviceHandler,
+
     shm: ShmHandler,
 
     compositor: SimpleGlobal<WlCompositor>,
@@ -75,9 +140,27 @@ pub struct WinitEnv {
 
     pointer_constraints: SimpleGlobal<ZwpPointerConstraintsV1>,
 
+    pointer_gestures: SimpleGlobal<ZwpPointerGesturesV1>,
+
     text_input_manager: SimpleGlobal<ZwpTextInputManagerV3>,
 
+    tablet_manager: SimpleGlobal<ZwpTabletManagerV2>,
+
+    shortcuts_inhibit_manager: SimpleGlobal<ZwpKeyboardShortcutsInhibitManagerV1>,
+
+    blur_manager: SimpleGlobal<OrgKdeKwinBlurManager>,
+
+    viewporter: SimpleGlobal<WpViewporter>,
+
+    color_manager: ColorManagerHandler,
+
+    presentation: PresentationHandler,
+
     decoration_manager: SimpleGlobal<ZxdgDecorationManagerV1>,
+
+    data_device_manager: DataDeviceHandler,
+
+    primary_selection_manager: PrimarySelectionHandler,
 }
 
 impl WinitEnv {
@@ -85,8 +168,15 @@ impl WinitEnv {
         // Output tracking for available_monitors, etc.
         let outputs = OutputHandler::new();
 
+        // Adaptive sync of the outputs of KWin.
+        let output_devices = OutputDeviceHandler::default();
+
         // Keyboard/Pointer/Touch input.
-        let seats = SeatHandler::new();
+        let mut seats = SeatHandler::new();
+
+        // Clipboard handling.
+        let data_device_manager = DataDeviceHandler::init(&mut seats);
+        let primary_selection_manager = PrimarySelectionHandler::init(&mut seats);
 
         // Essential globals.
         let shm = ShmHandler::new();
@@ -106,12 +196,34 @@ impl WinitEnv {
         // Pointer grab functionality.
         let pointer_constraints = SimpleGlobal::new();
 
+        // Touchpad gestures.
+        let pointer_gestures = SimpleGlobal::new();
+
         // IME handling.
         let text_input_manager = SimpleGlobal::new();
 
+        // Pens of the graphics tablets.
+        let tablet_manager = SimpleGlobal::new();
+
+        // Inhibition of the compositor shortcuts.
+        let shortcuts_inhibit_manager = SimpleGlobal::new();
+
+        // Blur behind the surfaces.
+        let blur_manager = SimpleGlobal::new();
+
+        // Scaling of the buffers of the emulated video modes.
+        let viewporter = SimpleGlobal::new();
+
+        // Color capabilities of the outputs.
+        let color_manager = ColorManagerHandler::default();
+
+        // Feedback of the presentation of the frames.
+        let presentation = PresentationHandler::default();
+
         Self {
             seats,
             outputs,
+            output_devices,
             shm,
             compositor,
             subcompositor,
@@ -119,11 +231,186 @@ impl WinitEnv {
             decoration_manager,
             relative_pointer_manager,
             pointer_constraints,
+            pointer_gestures,
             text_input_manager,
+            tablet_manager,
+            shortcuts_inhibit_manager,
+            blur_manager,
+            viewporter,
+            color_manager,
+            presentation,
+            data_device_manager,
+            primary_selection_manager,
         }
     }
 }
 
+impl WinitEnv {
+    /// The transfer functions supported by the color manager, which are sent once it's bound.
+    pub fn color_transfer_functions(&self) -> Rc<RefCell<Vec<TransferFunction>>> {
+        self.color_manager.transfer_functions.clone()
+    }
+
+    /// The clock of the timestamps of the presentations, which is sent once the presentation
+    /// is bound.
+    pub fn presentation_clock(&self) -> Rc<Cell<Option<u32>>> {
+        self.presentation.clock_id.clone()
+    }
+
+    /// The outputs of KWin, which are updated once they're done.
+    pub fn output_devices(&self) -> Arc<Mutex<Vec<OutputDevice>>> {
+        self.output_devices.devices.clone()
+    }
+}
+
+/// The color manager, which sends the transfer functions it supports once it's bound.
+#[derive(Default)]
+pub struct ColorManagerHandler {
+    color_manager: Option<Attached<WpColorManagerV1>>,
+    transfer_functions: Rc<RefCell<Vec<TransferFunction>>>,
+}
+
+impl GlobalHandler<WpColorManagerV1> for ColorManagerHandler {
+    fn created(
+        &mut self,
+        registry: Attached<WlRegistry>,
+        id: u32,
+        version: u32,
+        _: DispatchData<'_>,
+    ) {
+        let color_manager = registry.bind::<WpColorManagerV1>(version.min(1), id);
+        let transfer_functions = self.transfer_functions.clone();
+        color_manager.quick_assign(move |_, event, _| {
+            if let wp_color_manager_v1::Event::SupportedTfNamed { tf } = event {
+                let mut transfer_functions = transfer_functions.borrow_mut();
+                match output::transfer_function(tf) {
+                    Some(tf) if !transfer_functions.contains(&tf) => transfer_functions.push(tf),
+                    _ => (),
+                }
+            }
+        });
+        self.color_manager = Some(color_manager.into());
+    }
+
+    fn get(&self) -> Option<Attached<WpColorManagerV1>> {
+        self.color_manager.clone()
+    }
+}
+
+/// The presentation, which sends the clock of its timestamps once it's bound.
+#[derive(Default)]
+pub struct PresentationHandler {
+    presentation: Option<Attached<WpPresentation>>,
+    clock_id: Rc<Cell<Option<u32>>>,
+}
+
+impl GlobalHandler<WpPresentation> for PresentationHandler {
+    fn created(
+        &mut self,
+        registry: Attached<WlRegistry>,
+        id: u32,
+        _version: u32,
+        _: DispatchData<'_>,
+    ) {
+        let presentation = registry.bind::<WpPresentation>(1, id);
+        let clock_id = self.clock_id.clone();
+        presentation.quick_assign(move |_, event, _| {
+            if let wp_presentation::Event::ClockId { clk_id } = event {
+                clock_id.set(Some(clk_id));
+            }
+        });
+        self.presentation = Some(presentation.into());
+    }
+
+    fn get(&self) -> Option<Attached<WpPresentation>> {
+        self.presentation.clone()
+    }
+}
+
+/// The adaptive sync of an output of KWin, as per `kde_output_device_v2`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputDevice {
+    /// The name of the global of the device.
+    id: u32,
+    /// The location, make and model, which tell the `WlOutput` of the device.
+    pub location: (i32, i32),
+    pub make: String,
+    pub model: String,
+    pub vrr_capable: bool,
+    pub vrr_policy: Option<VrrPolicy>,
+}
+
+/// The outputs of KWin, which is the only compositor telling their adaptive sync.
+#[derive(Default)]
+pub struct OutputDeviceHandler {
+    proxies: Vec<(u32, Main<KdeOutputDeviceV2>)>,
+    devices: Arc<Mutex<Vec<OutputDevice>>>,
+}
+
+impl MultiGlobalHandler<KdeOutputDeviceV2> for OutputDeviceHandler {
+    fn created(
+        &mut self,
+        registry: Attached<WlRegistry>,
+        id: u32,
+        _version: u32,
+        _: DispatchData<'_>,
+    ) {
+        // The later versions only add events.
+        let proxy = registry.bind::<KdeOutputDeviceV2>(1, id);
+        let devices = self.devices.clone();
+        let mut pending = OutputDevice {
+            id,
+            location: (0, 0),
+            make: String::new(),
+            model: String::new(),
+            vrr_capable: false,
+            vrr_policy: None,
+        };
+        proxy.quick_assign(move |_, event, _| match event {
+            kde_output_device_v2::Event::Geometry {
+                x, y, make, model, ..
+            } => {
+                pending.location = (x, y);
+                pending.make = make;
+                pending.model = model;
+            }
+            // The modes have nothing to do with the adaptive sync.
+            kde_output_device_v2::Event::Mode { mode } => mode.quick_assign(|_, _, _| {}),
+            kde_output_device_v2::Event::Capabilities { flags } => {
+                pending.vrr_capable =
+                    Capability::from_bits_truncate(flags).contains(Capability::Vrr);
+            }
+            kde_output_device_v2::Event::VrrPolicy { vrr_policy } => {
+                pending.vrr_policy = VrrPolicy::from_raw(vrr_policy);
+            }
+            kde_output_device_v2::Event::Done => {
+                let mut devices = devices.lock().unwrap();
+                match devices.iter_mut().find(|device| device.id == id) {
+                    Some(device) => *device = pending.clone(),
+                    None => devices.push(pending.clone()),
+                }
+            }
+            _ => (),
+        });
+        self.proxies.push((id, proxy));
+    }
+
+    fn removed(&mut self, id: u32, _: DispatchData<'_>) {
+        self.proxies.retain(|(proxy_id, _)| *proxy_id != id);
+        self.devices
+            .lock()
+            .unwrap()
+            .retain(|device| device.id != id);
+    }
+
+    fn get_all(&self) -> Vec<Attached<KdeOutputDeviceV2>> {
+        self.proxies
+            .iter()
+            .map(|(_, proxy)| proxy.clone().into())
+            .collect()
+    }
+}
+
 impl ShellHandling for WinitEnv {
     fn get_shell(&self) -> Option<Shell> {
         self.shell.get_shell()
@@ -147,3 +434,32 @@ impl OutputHandling for WinitEnv {
         self.outputs.listen(f)
     }
 }
+
+impl DataDeviceHandling for WinitEnv {
+    fn set_callback<F: FnMut(WlSeat, DndEvent<'_>, DispatchData<'_>) + 'static>(
+        &mut self,
+        callback: F,
+    ) -> Result<(), ()> {
+        self.data_device_manager.set_callback(callback)
+    }
+
+    fn with_device<F: FnOnce(&DataDevice)>(&self, seat: &WlSeat, f: F) -> Result<(), ()> {
+        self.data_device_manager.with_device(seat, f)
+    }
+}
+
+impl PrimarySelectionHandling for WinitEnv {
+    fn with_primary_selection<F: FnOnce(&PrimarySelectionDevice)>(
+        &self,
+        seat: &WlSeat,
+        f: F,
+    ) -> Result<(), ()> {
+        self.primary_selection_manager
+            .with_primary_selection(seat, f)
+    }
+
+    fn get_primary_selection_manager(&self) -> Option<PrimarySelectionDeviceManager> {
+        self.primary_selection_manager
+            .get_primary_selection_manager()
+    }
+}
@@ -1,6 +1,7 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::error::Error;
+use std::os::unix::io::RawFd;
 use std::process;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
@@ -10,18 +11,31 @@ use sctk::reexports::client::protocol::wl_shm::WlShm;
 use sctk::reexports::client::Display;
 
 use sctk::reexports::calloop;
+use sctk::reexports::calloop::generic::{Fd, Generic};
+use sctk::reexports::calloop::timer::TimerHandle;
 
 use sctk::environment::Environment;
 use sctk::seat::pointer::{ThemeManager, ThemeSpec};
 use sctk::WaylandSource;
 
+use crate::error::ExternalError;
 use crate::event::{Event, StartCause, WindowEvent};
-use crate::event_loop::{ControlFlow, EventLoopWindowTarget as RootEventLoopWindowTarget};
-use crate::platform_impl::platform::sticky_exit_callback;
-
+use crate::event_loop::{
+    ControlFlow, EventLoopWindowTarget as RootEventLoopWindowTarget, SourceInterest, SourceToken,
+};
+use crate::keymap::LockKeys;
+use crate::platform::pump_events::PumpStatus;
+use crate::platform_impl::platform::{
+    gamepad::Gamepads, min_timeout, precise_wait::PreciseWait, sticky_exit_callback, OsError,
+};
+
+use super::clipboard::ClipboardManager;
+use super::cursor;
+use super::dnd::{self, DndManager};
 use super::env::{WindowingFeatures, WinitEnv};
 use super::output::OutputManager;
 use super::seat::SeatManager;
+use super::timer::Timers;
 use super::window::shim::{self, WindowUpdate};
 use super::{DeviceId, WindowId};
 
@@ -29,10 +43,10 @@ mod proxy;
 mod sink;
 mod state;
 
-pub use proxy::EventLoopProxy;
+pub use proxy::{EventLoopProxy, EventLoopWakeup};
 pub use state::WinitState;
 
-use sink::EventSink;
+pub use sink::EventSink;
 
 pub struct EventLoopWindowTarget<T> {
     /// Wayland display.
@@ -65,6 +79,18 @@ pub struct EventLoopWindowTarget<T> {
     /// multiple similar themes.
     pub theme_manager: ThemeManager,
 
+    /// The timers set by the user.
+    pub timers: Timers,
+
+    /// The timer of the animated cursors of the windows.
+    pub cursor_timer: TimerHandle<WindowId>,
+
+    /// The precise waits of `ControlFlow::WaitUntil`.
+    pub precise_wait: PreciseWait,
+
+    /// The gamepads, once they're enabled.
+    pub gamepads: Gamepads,
+
     _marker: std::marker::PhantomData<T>,
 }
 
@@ -84,11 +110,30 @@ pub struct EventLoop<T: 'static> {
     /// Wayland source of events.
     wayland_source: Rc<calloop::Source<WaylandSource>>,
 
+    /// The external sources registered by the user.
+    external_sources: HashMap<SourceToken, calloop::Source<Generic<Fd>>>,
+
+    /// The external sources which are ready.
+    ready_sources: Rc<RefCell<Vec<SourceToken>>>,
+
     /// Window target.
     window_target: RootEventLoopWindowTarget<T>,
 
     /// Output manager.
     _seat_manager: SeatManager,
+
+    /// The control flow set by the callback, which is kept between the calls to `pump_events`.
+    control_flow: ControlFlow,
+
+    /// Whether `NewEvents(Init)` was sent, and `LoopDestroyed` is yet to be.
+    loop_running: bool,
+
+    /// The window updates taken from the state on each iteration.
+    window_updates: Vec<(WindowId, WindowUpdate)>,
+
+    /// The window events taken from the sink on each iteration, which are delivered without
+    /// holding a borrow of the state.
+    event_sink_back_buffer: Vec<Event<'static, ()>>,
 }
 
 impl<T: 'static> EventLoop<T> {
@@ -101,6 +146,9 @@ impl<T: 'static> EventLoop<T> {
         // Setup environment.
         let env = Environment::new(&display_proxy, &mut event_queue, WinitEnv::new())?;
 
+        // Bind the primary selection manager, since it's bound lazily.
+        let _ = env.get_primary_selection_manager();
+
         // Create event loop.
         let event_loop = calloop::EventLoop::<WinitState>::new()?;
         // Build windowing features.
@@ -133,6 +181,30 @@ impl<T: 'static> EventLoop<T> {
                 }
             })?;
 
+        // A source of the clipboard content read on separate threads.
+        let (clipboard_sender, clipboard_channel) = calloop::channel::channel();
+        event_loop.handle().insert_source(
+            clipboard_channel,
+            move |event, _, winit_state: &mut WinitState| {
+                if let calloop::channel::Event::Msg((window_id, kind, content)) = event {
+                    winit_state
+                        .event_sink
+                        .push_window_event(WindowEvent::ClipboardData { kind, content }, window_id);
+                }
+            },
+        )?;
+
+        // A source of the dropped content read on separate threads.
+        let (dnd_sender, dnd_channel) = calloop::channel::channel();
+        event_loop.handle().insert_source(
+            dnd_channel,
+            move |event, _, winit_state: &mut WinitState| {
+                if let calloop::channel::Event::Msg((id, content)) = event {
+                    dnd::content_received(winit_state, id, content);
+                }
+            },
+        )?;
+
         // An event's loop awakener to wake up for window events from winit's windows.
         let (event_loop_awakener, event_loop_awakener_source) = calloop::ping::make_ping()?;
 
@@ -144,10 +216,16 @@ impl<T: 'static> EventLoop<T> {
             },
         )?;
 
+        // The timers set by the user.
+        let timers = Timers::new(&event_loop.handle())?;
+        let cursor_timer = cursor::cursor_timer(&event_loop.handle())?;
+
         let event_loop_handle = event_loop.handle();
         let window_map = HashMap::new();
         let event_sink = EventSink::new();
         let window_updates = HashMap::new();
+        let clipboard = ClipboardManager::new(env.clone(), clipboard_sender);
+        let dnd = DndManager::new(dnd_sender);
 
         // Create event loop window target.
         let event_loop_window_target = EventLoopWindowTarget {
@@ -157,6 +235,10 @@ impl<T: 'static> EventLoop<T> {
                 window_map,
                 event_sink,
                 window_updates,
+                clipboard,
+                dnd,
+                fired_timers: Vec::new(),
+                lock_keys: LockKeys::empty(),
             }),
             event_loop_handle,
             output_manager,
@@ -164,6 +246,10 @@ impl<T: 'static> EventLoop<T> {
             wayland_source: wayland_source.clone(),
             windowing_features,
             theme_manager,
+            timers,
+            cursor_timer,
+            precise_wait: Default::default(),
+            gamepads: Default::default(),
             _marker: std::marker::PhantomData,
         };
 
@@ -175,6 +261,12 @@ impl<T: 'static> EventLoop<T> {
             wayland_source,
             _seat_manager: seat_manager,
             user_events_sender,
+            external_sources: HashMap::new(),
+            ready_sources: Rc::new(RefCell::new(Vec::new())),
+            control_flow: ControlFlow::default(),
+            loop_running: false,
+            window_updates: Vec::new(),
+            event_sink_back_buffer: Vec::new(),
             window_target: RootEventLoopWindowTarget {
                 p: crate::platform_impl::EventLoopWindowTarget::Wayland(event_loop_window_target),
                 _marker: std::marker::PhantomData,
@@ -184,316 +276,482 @@ impl<T: 'static> EventLoop<T> {
         Ok(event_loop)
     }
 
+    pub fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError> {
+        if self.external_sources.contains_key(&token) {
+            return Err(ExternalError::Os(os_error!(OsError::WaylandMisc(
+                "The token of the source is already in use"
+            ))));
+        }
+        let interest = match (
+            interest.contains(SourceInterest::READABLE),
+            interest.contains(SourceInterest::WRITABLE),
+        ) {
+            (true, true) => calloop::Interest::Both,
+            (true, false) => calloop::Interest::Readable,
+            (false, true) => calloop::Interest::Writable,
+            (false, false) => {
+                return Err(ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "The source has no interest"
+                ))))
+            }
+        };
+
+        // The source is level-triggered, so it's reported on each dispatch while it's ready.
+        let ready_sources = self.ready_sources.clone();
+        let source = self
+            .event_loop
+            .handle()
+            .insert_source(
+                Generic::from_fd(fd, interest, calloop::Mode::Level),
+                move |_, _, _| {
+                    let mut ready_sources = ready_sources.borrow_mut();
+                    if !ready_sources.contains(&token) {
+                        ready_sources.push(token);
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|_| {
+                ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "Failed to register the source"
+                )))
+            })?;
+        self.external_sources.insert(token, source);
+        Ok(())
+    }
+
+    pub fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        let source = self.external_sources.remove(&token).ok_or_else(|| {
+            ExternalError::Os(os_error!(OsError::WaylandMisc(
+                "There's no source with this token"
+            )))
+        })?;
+        self.ready_sources
+            .borrow_mut()
+            .retain(|ready| *ready != token);
+        self.event_loop.handle().remove(source);
+        Ok(())
+    }
+
     pub fn run<F>(mut self, callback: F) -> !
     where
         F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow) + 'static,
     {
-        self.run_return(callback);
-        process::exit(0)
+        let exit_code = self.run_return(callback);
+        process::exit(exit_code)
     }
 
-    pub fn run_return<F>(&mut self, mut callback: F)
+    pub fn run_return<F>(&mut self, mut callback: F) -> i32
     where
         F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
     {
-        // Send pending events to the server.
-        let _ = self.display.flush();
-
-        let mut control_flow = ControlFlow::default();
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                return code;
+            }
+        }
+    }
 
-        let pending_user_events = self.pending_user_events.clone();
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
+            self.control_flow = ControlFlow::default();
 
-        callback(
-            Event::NewEvents(StartCause::Init),
-            &self.window_target,
-            &mut control_flow,
-        );
+            // Send pending events to the server.
+            let _ = self.display.flush();
 
-        let mut window_updates: Vec<(WindowId, WindowUpdate)> = Vec::new();
-        let mut event_sink_back_buffer = Vec::new();
+            self.single_iteration(&mut callback, StartCause::Init);
+        }
 
-        // NOTE We break on errors from dispatches, since if we've got protocol error
+        // NOTE We exit on errors from dispatches, since if we've got protocol error
         // libwayland-client/wayland-rs will inform us anyway, but crashing downstream is not
-        // really an option. Instead we inform that the event loop got destroyed. We may
-        // communicate an error that something was terminated, but winit doesn't provide us
-        // with an API to do that via some event.
-        loop {
-            // Handle pending user events. We don't need back buffer, since we can't dispatch
-            // user events indirectly via callback to the user.
-            for user_event in pending_user_events.borrow_mut().drain(..) {
-                sticky_exit_callback(
-                    Event::UserEvent(user_event),
-                    &self.window_target,
-                    &mut control_flow,
-                    &mut callback,
-                );
-            }
-
-            // Process 'new' pending updates.
-            self.with_state(|state| {
-                window_updates.clear();
-                window_updates.extend(
-                    state
-                        .window_updates
-                        .iter_mut()
-                        .map(|(wid, window_update)| (*wid, window_update.take())),
-                );
-            });
+        // really an option. Instead we inform that the event loop got destroyed, with the exit
+        // code 1 so that the failure isn't taken for a clean exit.
+        //
+        // The first iteration may have already asked to exit.
+        if !matches!(self.control_flow, ControlFlow::ExitWithCode(_))
+            && self
+                .poll_events_with_timeout(timeout, &mut callback)
+                .is_err()
+        {
+            self.control_flow = ControlFlow::ExitWithCode(1);
+        }
 
-            for (window_id, window_update) in window_updates.iter_mut() {
-                if let Some(scale_factor) = window_update.scale_factor.map(|f| f as f64) {
-                    let mut physical_size = self.with_state(|state| {
-                        let window_handle = state.window_map.get(&window_id).unwrap();
-                        let mut size = window_handle.size.lock().unwrap();
+        if let ControlFlow::ExitWithCode(code) = self.control_flow {
+            self.loop_running = false;
+            callback(
+                Event::LoopDestroyed,
+                &self.window_target,
+                &mut self.control_flow,
+            );
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
 
-                        // Update the new logical size if it was changed.
-                        let window_size = window_update.size.unwrap_or(*size);
-                        *size = window_size;
+    /// Waits for the events for as long as both the control flow and the timeout allow, then
+    /// dispatches them.
+    fn poll_events_with_timeout<F>(
+        &mut self,
+        timeout: Option<Duration>,
+        callback: &mut F,
+    ) -> std::io::Result<()>
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        // During the run of the user callback, some other code monitoring and reading the
+        // Wayland socket may have been run (mesa for example does this with vsync), if that
+        // is the case, some events may have been enqueued in our event queue.
+        //
+        // If some messages are there, the event loop needs to behave as if it was instantly
+        // woken up by messages arriving from the Wayland socket, to avoid delaying the
+        // dispatch of these events until we're woken up again.
+        let instant_wakeup = {
+            let handle = self.event_loop.handle();
+            let source = self.wayland_source.clone();
+            let dispatched = handle.with_source(&source, |wayland_source| {
+                let queue = wayland_source.queue();
+                self.with_state(|state| queue.dispatch_pending(state, |_, _, _| unimplemented!()))
+            })?;
 
-                        window_size.to_physical(scale_factor)
-                    });
+            dispatched > 0
+        };
 
-                    sticky_exit_callback(
-                        Event::WindowEvent {
-                            window_id: crate::window::WindowId(
-                                crate::platform_impl::WindowId::Wayland(*window_id),
-                            ),
-                            event: WindowEvent::ScaleFactorChanged {
-                                scale_factor,
-                                new_inner_size: &mut physical_size,
-                            },
-                        },
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+        let precise = match &self.window_target.p {
+            crate::platform_impl::EventLoopWindowTarget::Wayland(window_target) => {
+                window_target.precise_wait.is_registered()
+                    && window_target.precise_wait.arm(self.control_flow)
+            }
+            #[cfg(any(feature = "x11", feature = "headless"))]
+            _ => unreachable!(),
+        };
 
-                    // We don't update size on a window handle since we'll do that later
-                    // when handling size update.
-                    let new_logical_size = physical_size.to_logical(scale_factor);
-                    window_update.size = Some(new_logical_size);
-                }
+        let start = Instant::now();
+        let control_flow_timeout = match self.control_flow {
+            ControlFlow::Poll => Some(Duration::from_millis(0)),
+            ControlFlow::Wait => None,
+            // The timer of the precise waits wakes the event loop up.
+            ControlFlow::WaitUntil(_) if precise => None,
+            ControlFlow::WaitUntil(deadline) => Some(deadline.saturating_duration_since(start)),
+            ControlFlow::ExitWithCode(_) => unreachable!(),
+        };
+        let timeout = if instant_wakeup {
+            Some(Duration::from_millis(0))
+        } else {
+            min_timeout(control_flow_timeout, timeout)
+        };
 
-                if let Some(size) = window_update.size.take() {
-                    let physical_size = self.with_state(|state| {
-                        let window_handle = state.window_map.get_mut(&window_id).unwrap();
-                        let mut window_size = window_handle.size.lock().unwrap();
-
-                        // Always issue resize event on scale factor change.
-                        let physical_size =
-                            if window_update.scale_factor.is_none() && *window_size == size {
-                                // The size hasn't changed, don't inform downstream about that.
-                                None
-                            } else {
-                                *window_size = size;
-                                let scale_factor =
-                                    sctk::get_surface_scale_factor(&window_handle.window.surface());
-                                let physical_size = size.to_physical(scale_factor as f64);
-                                Some(physical_size)
-                            };
-
-                        // We still perform all of those resize related logic even if the size
-                        // hasn't changed, since GNOME relies on `set_geometry` calls after
-                        // configures.
-                        window_handle.window.resize(size.width, size.height);
-                        window_handle.window.refresh();
-
-                        // Mark that refresh isn't required, since we've done it right now.
-                        window_update.refresh_frame = false;
-
-                        physical_size
-                    });
-
-                    if let Some(physical_size) = physical_size {
-                        sticky_exit_callback(
-                            Event::WindowEvent {
-                                window_id: crate::window::WindowId(
-                                    crate::platform_impl::WindowId::Wayland(*window_id),
-                                ),
-                                event: WindowEvent::Resized(physical_size),
-                            },
-                            &self.window_target,
-                            &mut control_flow,
-                            &mut callback,
-                        );
-                    }
-                }
+        self.loop_dispatch(timeout)?;
 
-                if window_update.close_window {
-                    sticky_exit_callback(
-                        Event::WindowEvent {
-                            window_id: crate::window::WindowId(
-                                crate::platform_impl::WindowId::Wayland(*window_id),
-                            ),
-                            event: WindowEvent::CloseRequested,
-                        },
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+        let cause = match self.control_flow {
+            ControlFlow::Poll => StartCause::Poll,
+            ControlFlow::WaitUntil(deadline) if Instant::now() >= deadline => {
+                StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: deadline,
                 }
             }
+            ControlFlow::WaitUntil(deadline) => StartCause::WaitCancelled {
+                start,
+                requested_resume: Some(deadline),
+            },
+            _ => StartCause::WaitCancelled {
+                start,
+                requested_resume: None,
+            },
+        };
+        self.single_iteration(callback, cause);
 
-            // The purpose of the back buffer and that swap is to not hold borrow_mut when
-            // we're doing callback to the user, since we can double borrow if the user decides
-            // to create a window in one of those callbacks.
-            self.with_state(|state| {
-                std::mem::swap(
-                    &mut event_sink_back_buffer,
-                    &mut state.event_sink.window_events,
-                )
-            });
-
-            // Handle pending window events.
-            for event in event_sink_back_buffer.drain(..) {
-                let event = event.map_nonuser_event().unwrap();
-                sticky_exit_callback(event, &self.window_target, &mut control_flow, &mut callback);
-            }
+        Ok(())
+    }
+
+    fn single_iteration<F>(&mut self, callback: &mut F, cause: StartCause)
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let mut control_flow = self.control_flow;
+        let pending_user_events = self.pending_user_events.clone();
+        let mut window_updates = std::mem::take(&mut self.window_updates);
+        let mut event_sink_back_buffer = std::mem::take(&mut self.event_sink_back_buffer);
 
-            // Send events cleared.
+        sticky_exit_callback(
+            Event::NewEvents(cause),
+            &self.window_target,
+            &mut control_flow,
+            callback,
+        );
+
+        // Handle pending user events. We don't need back buffer, since we can't dispatch
+        // user events indirectly via callback to the user.
+        for user_event in pending_user_events.borrow_mut().drain(..) {
             sticky_exit_callback(
-                Event::MainEventsCleared,
+                Event::UserEvent(user_event),
                 &self.window_target,
                 &mut control_flow,
-                &mut callback,
+                callback,
             );
+        }
 
-            // Handle RedrawRequested events.
-            for (window_id, window_update) in window_updates.iter() {
-                // Handle refresh of the frame.
-                if window_update.refresh_frame {
-                    self.with_state(|state| {
-                        let window_handle = state.window_map.get_mut(&window_id).unwrap();
-                        window_handle.window.refresh();
-                        if !window_update.redraw_requested {
-                            window_handle.window.surface().commit();
-                        }
-                    });
-                }
+        // Send the fired timers.
+        let fired_timers = self.with_state(|state| std::mem::take(&mut state.fired_timers));
+        for id in fired_timers {
+            sticky_exit_callback(
+                Event::Timer(id),
+                &self.window_target,
+                &mut control_flow,
+                callback,
+            );
+        }
 
-                // Handle redraw request.
-                if window_update.redraw_requested {
-                    sticky_exit_callback(
-                        Event::RedrawRequested(crate::window::WindowId(
-                            crate::platform_impl::WindowId::Wayland(*window_id),
-                        )),
-                        &self.window_target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
-                }
+        // Send the events of the gamepads.
+        let gamepad_events = match &self.window_target.p {
+            crate::platform_impl::EventLoopWindowTarget::Wayland(window_target) => {
+                window_target.gamepads.dispatch()
             }
-
-            // Send RedrawEventCleared.
+            #[cfg(any(feature = "x11", feature = "headless"))]
+            _ => unreachable!(),
+        };
+        for (gamepad_id, event) in gamepad_events {
             sticky_exit_callback(
-                Event::RedrawEventsCleared,
+                Event::GamepadEvent { gamepad_id, event },
                 &self.window_target,
                 &mut control_flow,
-                &mut callback,
+                callback,
             );
+        }
 
-            // Send pending events to the server.
-            let _ = self.display.flush();
+        // Report the ready external sources.
+        let ready_sources = std::mem::take(&mut *self.ready_sources.borrow_mut());
+        for token in ready_sources {
+            sticky_exit_callback(
+                Event::ExternalSource(token),
+                &self.window_target,
+                &mut control_flow,
+                callback,
+            );
+        }
 
-            // During the run of the user callback, some other code monitoring and reading the
-            // Wayland socket may have been run (mesa for example does this with vsync), if that
-            // is the case, some events may have been enqueued in our event queue.
-            //
-            // If some messages are there, the event loop needs to behave as if it was instantly
-            // woken up by messages arriving from the Wayland socket, to avoid delaying the
-            // dispatch of these events until we're woken up again.
-            let instant_wakeup = {
-                let handle = self.event_loop.handle();
-                let source = self.wayland_source.clone();
-                let dispatched = handle.with_source(&source, |wayland_source| {
-                    let queue = wayland_source.queue();
-                    self.with_state(|state| {
-                        queue.dispatch_pending(state, |_, _, _| unimplemented!())
-                    })
+        // Process 'new' pending updates.
+        self.with_state(|state| {
+            window_updates.clear();
+            window_updates.extend(
+                state
+                    .window_updates
+                    .iter_mut()
+                    .map(|(wid, window_update)| (*wid, window_update.take())),
+            );
+        });
+
+        for (window_id, window_update) in window_updates.iter_mut() {
+            if let Some(scale_factor) = window_update.scale_factor.map(|f| f as f64) {
+                let (mut physical_size, window_size, has_video_mode) = self.with_state(|state| {
+                    let window_handle = state.window_map.get(window_id).unwrap();
+                    let mut size = window_handle.size.lock().unwrap();
+
+                    // Update the new logical size if it was changed.
+                    let window_size = window_update.size.unwrap_or(*size);
+                    *size = window_size;
+
+                    (
+                        window_handle.buffer_size(window_size, scale_factor),
+                        window_size,
+                        window_handle.has_video_mode(),
+                    )
                 });
 
-                if let Ok(dispatched) = dispatched {
-                    dispatched > 0
-                } else {
-                    break;
-                }
-            };
-
-            match control_flow {
-                ControlFlow::Exit => break,
-                ControlFlow::Poll => {
-                    // Non-blocking dispatch.
-                    let timeout = Duration::from_millis(0);
-                    if self.loop_dispatch(Some(timeout)).is_err() {
-                        break;
-                    }
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(
+                            crate::platform_impl::WindowId::Wayland(*window_id),
+                        ),
+                        event: WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size: &mut physical_size,
+                        },
+                    },
+                    &self.window_target,
+                    &mut control_flow,
+                    callback,
+                );
 
-                    callback(
-                        Event::NewEvents(StartCause::Poll),
-                        &self.window_target,
-                        &mut control_flow,
-                    );
-                }
-                ControlFlow::Wait => {
-                    let timeout = if instant_wakeup {
-                        Some(Duration::from_millis(0))
-                    } else {
+                // We don't update size on a window handle since we'll do that later
+                // when handling size update. The size of the buffers of the video mode is kept.
+                let new_logical_size = match has_video_mode {
+                    true => window_size,
+                    false => physical_size.to_logical(scale_factor),
+                };
+                window_update.size = Some(new_logical_size);
+            }
+
+            if let Some(size) = window_update.size.take() {
+                let physical_size = self.with_state(|state| {
+                    let window_handle = state.window_map.get_mut(window_id).unwrap();
+                    let mut window_size = window_handle.size.lock().unwrap();
+
+                    // Always issue resize event on scale factor change, or once the buffers
+                    // changed size.
+                    let buffers_resized = window_handle.take_buffers_resized();
+                    let physical_size = if window_update.scale_factor.is_none()
+                        && *window_size == size
+                        && !buffers_resized
+                    {
+                        // The size hasn't changed, don't inform downstream about that.
                         None
+                    } else {
+                        *window_size = size;
+                        let scale_factor =
+                            sctk::get_surface_scale_factor(window_handle.window.surface());
+                        let physical_size = window_handle.buffer_size(size, scale_factor as f64);
+                        Some(physical_size)
                     };
 
-                    if self.loop_dispatch(timeout).is_err() {
-                        break;
-                    }
+                    // We still perform all of those resize related logic even if the size
+                    // hasn't changed, since GNOME relies on `set_geometry` calls after
+                    // configures.
+                    window_handle.set_viewport_destination(size);
+                    window_handle.window.resize(size.width, size.height);
+                    window_handle.window.refresh();
+
+                    // Mark that refresh isn't required, since we've done it right now.
+                    window_update.refresh_frame = false;
 
-                    callback(
-                        Event::NewEvents(StartCause::WaitCancelled {
-                            start: Instant::now(),
-                            requested_resume: None,
-                        }),
+                    physical_size
+                });
+
+                if let Some(physical_size) = physical_size {
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: crate::window::WindowId(
+                                crate::platform_impl::WindowId::Wayland(*window_id),
+                            ),
+                            event: WindowEvent::Resized(physical_size),
+                        },
                         &self.window_target,
                         &mut control_flow,
+                        callback,
                     );
                 }
-                ControlFlow::WaitUntil(deadline) => {
-                    let start = Instant::now();
+            }
 
-                    // Compute the amount of time we'll block for.
-                    let duration = if deadline > start && !instant_wakeup {
-                        deadline - start
-                    } else {
-                        Duration::from_millis(0)
-                    };
+            if window_update.close_window {
+                sticky_exit_callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(
+                            crate::platform_impl::WindowId::Wayland(*window_id),
+                        ),
+                        event: WindowEvent::CloseRequested,
+                    },
+                    &self.window_target,
+                    &mut control_flow,
+                    callback,
+                );
+            }
+        }
 
-                    if self.loop_dispatch(Some(duration)).is_err() {
-                        break;
-                    }
+        // The surfaces aren't told once the mode of their output changes, so the refresh rates
+        // are checked once per dispatch.
+        self.with_state(|state| {
+            for (window_id, window_handle) in state.window_map.iter() {
+                if let Some(refresh_rate) = window_handle.refresh_rate_changed() {
+                    state.event_sink.push_window_event(
+                        WindowEvent::RefreshRateChanged(refresh_rate),
+                        *window_id,
+                    );
+                }
+            }
+        });
+
+        // The purpose of the back buffer and that swap is to not hold borrow_mut when
+        // we're doing callback to the user, since we can double borrow if the user decides
+        // to create a window in one of those callbacks.
+        self.with_state(|state| {
+            std::mem::swap(
+                &mut event_sink_back_buffer,
+                &mut state.event_sink.window_events,
+            )
+        });
+
+        // Handle pending window events.
+        for event in event_sink_back_buffer.drain(..) {
+            let event = event.map_nonuser_event().unwrap();
+            sticky_exit_callback(event, &self.window_target, &mut control_flow, callback);
+        }
 
-                    let now = Instant::now();
-
-                    if now < deadline {
-                        callback(
-                            Event::NewEvents(StartCause::WaitCancelled {
-                                start,
-                                requested_resume: Some(deadline),
-                            }),
-                            &self.window_target,
-                            &mut control_flow,
-                        )
-                    } else {
-                        callback(
-                            Event::NewEvents(StartCause::ResumeTimeReached {
-                                start,
-                                requested_resume: deadline,
-                            }),
-                            &self.window_target,
-                            &mut control_flow,
-                        )
+        // Send events cleared.
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.window_target,
+            &mut control_flow,
+            callback,
+        );
+
+        // Handle RedrawRequested events.
+        for (window_id, window_update) in window_updates.iter() {
+            // Handle refresh of the frame.
+            if window_update.refresh_frame {
+                self.with_state(|state| {
+                    let window_handle = state.window_map.get_mut(window_id).unwrap();
+                    window_handle.window.refresh();
+                    if !window_update.redraw_requested {
+                        window_handle.window.surface().commit();
                     }
+                });
+            }
+
+            // Handle redraw request.
+            if window_update.redraw_requested {
+                let occluded = self.with_state(|state| match state.window_map.get(window_id) {
+                    Some(window_handle) => window_handle.before_redraw(*window_id),
+                    None => false,
+                });
+                if occluded {
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: crate::window::WindowId(
+                                crate::platform_impl::WindowId::Wayland(*window_id),
+                            ),
+                            event: WindowEvent::Occluded(true),
+                        },
+                        &self.window_target,
+                        &mut control_flow,
+                        callback,
+                    );
                 }
+
+                sticky_exit_callback(
+                    Event::RedrawRequested(crate::window::WindowId(
+                        crate::platform_impl::WindowId::Wayland(*window_id),
+                    )),
+                    &self.window_target,
+                    &mut control_flow,
+                    callback,
+                );
             }
         }
 
-        callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+        // Send RedrawEventCleared.
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.window_target,
+            &mut control_flow,
+            callback,
+        );
+
+        // Send pending events to the server.
+        let _ = self.display.flush();
+
+        self.window_updates = window_updates;
+        self.event_sink_back_buffer = event_sink_back_buffer;
+        self.control_flow = control_flow;
     }
 
     #[inline]
@@ -501,6 +759,18 @@ impl<T: 'static> EventLoop<T> {
         EventLoopProxy::new(self.user_events_sender.clone())
     }
 
+    pub fn create_wakeup(&self) -> EventLoopWakeup {
+        let event_loop_awakener = match &self.window_target.p {
+            crate::platform_impl::EventLoopWindowTarget::Wayland(window_target) => {
+                window_target.event_loop_awakener.clone()
+            }
+            #[cfg(any(feature = "x11", feature = "headless"))]
+            _ => unreachable!(),
+        };
+
+        EventLoopWakeup::new(event_loop_awakener)
+    }
+
     #[inline]
     pub fn window_target(&self) -> &RootEventLoopWindowTarget<T> {
         &self.window_target
@@ -511,7 +781,7 @@ impl<T: 'static> EventLoop<T> {
             crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
                 window_target.state.get_mut()
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => unreachable!(),
         };
 
@@ -526,7 +796,7 @@ impl<T: 'static> EventLoop<T> {
             crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
                 window_target.state.get_mut()
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "headless"))]
             _ => unreachable!(),
         };
 
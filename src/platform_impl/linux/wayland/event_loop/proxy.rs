@@ -3,6 +3,7 @@
 use std::sync::mpsc::SendError;
 
 use sctk::reexports::calloop::channel::Sender;
+use sctk::reexports::calloop::ping::Ping;
 
 use crate::event_loop::EventLoopClosed;
 
@@ -30,3 +31,21 @@ impl<T: 'static> EventLoopProxy<T> {
             .map_err(|SendError(error)| EventLoopClosed(error))
     }
 }
+
+/// A handle that can be sent across the threads and used to wake up the `EventLoop`, without
+/// sending any event.
+pub struct EventLoopWakeup {
+    event_loop_awakener: Ping,
+}
+
+impl EventLoopWakeup {
+    pub fn new(event_loop_awakener: Ping) -> Self {
+        Self {
+            event_loop_awakener,
+        }
+    }
+
+    pub fn wake_up(&self) {
+        self.event_loop_awakener.ping();
+    }
+}
@@ -1,9 +1,14 @@
 //! An event loop's sink to deliver events from the Wayland event callbacks.
 
 use crate::event::{DeviceEvent, DeviceId as RootDeviceId, Event, WindowEvent};
-use crate::platform_impl::platform::{DeviceId as PlatformDeviceId, WindowId as PlatformWindowId};
+use crate::monitor::{MonitorEvent, MonitorHandle as RootMonitorHandle};
+use crate::platform_impl::platform::{
+    DeviceId as PlatformDeviceId, MonitorHandle as PlatformMonitorHandle,
+    WindowId as PlatformWindowId,
+};
 use crate::window::WindowId as RootWindowId;
 
+use super::super::MonitorHandle;
 use super::{DeviceId, WindowId};
 
 /// An event loop's sink to deliver events from the Wayland event callbacks
@@ -26,6 +31,31 @@ impl EventSink {
         });
     }
 
+    /// Add new monitor event to a queue.
+    pub fn push_monitor_event(&mut self, event: MonitorEvent, monitor: MonitorHandle) {
+        self.window_events.push(Event::MonitorEvent {
+            event,
+            monitor: RootMonitorHandle {
+                inner: PlatformMonitorHandle::Wayland(monitor),
+            },
+        });
+    }
+
+    /// Add the connected and disconnected monitors to a queue.
+    pub fn push_monitors_changed(
+        &mut self,
+        added: Vec<MonitorHandle>,
+        removed: Vec<MonitorHandle>,
+    ) {
+        let wrap = |monitor| RootMonitorHandle {
+            inner: PlatformMonitorHandle::Wayland(monitor),
+        };
+        self.window_events.push(Event::MonitorsChanged {
+            added: added.into_iter().map(wrap).collect(),
+            removed: removed.into_iter().map(wrap).collect(),
+        });
+    }
+
     /// Add new window event to a queue.
     pub fn push_window_event(&mut self, event: WindowEvent<'static>, window_id: WindowId) {
         self.window_events.push(Event::WindowEvent {
@@ -3,6 +3,10 @@
 use std::collections::HashMap;
 
 use super::EventSink;
+use crate::event_loop::TimerId;
+use crate::keymap::LockKeys;
+use crate::platform_impl::wayland::clipboard::ClipboardManager;
+use crate::platform_impl::wayland::dnd::DndManager;
 use crate::platform_impl::wayland::window::shim::{WindowHandle, WindowUpdate};
 use crate::platform_impl::wayland::WindowId;
 
@@ -22,4 +26,16 @@ pub struct WinitState {
     /// and requests from winit's windows are being forwarded to them either via
     /// `WindowUpdate` or buffer on the associated with it `WindowHandle`.
     pub window_map: HashMap<WindowId, WindowHandle>,
+
+    /// Clipboard manager that owns the selections set by the application.
+    pub clipboard: ClipboardManager,
+
+    /// Drag and drop manager tracking the data dropped into the windows.
+    pub dnd: DndManager,
+
+    /// The timers which fired during the dispatch.
+    pub fired_timers: Vec<TimerId>,
+
+    /// The lock keys which are on, as of the latest modifiers of the keyboards.
+    pub lock_keys: LockKeys,
 }
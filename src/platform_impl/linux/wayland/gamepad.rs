@@ -0,0 +1,54 @@
+//! The gamepads, with their epoll registered with calloop.
+
+use sctk::reexports::calloop;
+use sctk::reexports::calloop::generic::Generic;
+
+use std::time::Duration;
+
+use crate::error::ExternalError;
+use crate::gamepad::GamepadId;
+use crate::platform_impl::platform::OsError;
+
+use super::event_loop::EventLoopWindowTarget;
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn set_gamepads_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        self.gamepads
+            .set_enabled(enabled, |message| os_error!(OsError::WaylandMisc(message)))?;
+
+        if self.gamepads.is_registered() {
+            return Ok(());
+        }
+        let fd = match self.gamepads.fd() {
+            Some(fd) => fd,
+            None => return Ok(()),
+        };
+        // The epoll is kept once it's created, and so is the source. The gamepads are read on
+        // each iteration of the event loop, so the source only wakes it up.
+        self.event_loop_handle
+            .insert_source(
+                Generic::from_fd(fd, calloop::Interest::Readable, calloop::Mode::Level),
+                |_, _, _| Ok(()),
+            )
+            .map_err(|_| {
+                ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "Failed to register the epoll of the gamepads"
+                )))
+            })?;
+        self.gamepads.set_registered();
+        Ok(())
+    }
+
+    pub fn rumble_gamepad(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        self.gamepads
+            .rumble(gamepad_id, strong, weak, duration, |message| {
+                os_error!(OsError::WaylandMisc(message))
+            })
+    }
+}
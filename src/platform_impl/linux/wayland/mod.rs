@@ -6,24 +6,35 @@
     target_os = "openbsd"
 ))]
 
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 
-pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
+pub use child_surface::ChildSurface;
+pub use cursor::CustomCursor;
+pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWakeup, EventLoopWindowTarget};
 pub use output::{MonitorHandle, VideoMode};
 pub use window::Window;
 
+mod child_surface;
+mod clipboard;
+mod cursor;
+mod dnd;
 mod env;
 mod event_loop;
+mod gamepad;
 mod output;
+mod precise_wait;
+mod protocols;
 mod seat;
+mod timer;
 mod window;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct DeviceId;
+pub struct DeviceId(u32);
 
 impl DeviceId {
     pub unsafe fn dummy() -> Self {
-        DeviceId
+        DeviceId(0)
     }
 }
 
@@ -40,3 +51,9 @@ impl WindowId {
 fn make_wid(surface: &WlSurface) -> WindowId {
     WindowId(surface.as_ref().c_ptr() as usize)
 }
+
+/// The devices are told apart by their seats, as the compositor merges the devices of a seat.
+#[inline]
+fn make_did(seat: &WlSeat) -> DeviceId {
+    DeviceId(seat.as_ref().id())
+}
@@ -1,20 +1,31 @@
+use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
 use sctk::reexports::client::protocol::wl_output::WlOutput;
-use sctk::reexports::client::Display;
+use sctk::reexports::client::{Attached, Display};
 
 use sctk::environment::Environment;
 use sctk::output::OutputStatusListener;
 
 use crate::dpi::{PhysicalPosition, PhysicalSize};
-use crate::monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode};
+use crate::monitor::{
+    ColorCapabilities, ColorPrimaries, MonitorEvent, MonitorHandle as RootMonitorHandle,
+    MonitorIdentity, TransferFunction, VideoMode as RootVideoMode,
+};
 use crate::platform_impl::platform::{
     MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode,
 };
 
-use super::env::WinitEnv;
-use super::event_loop::EventLoopWindowTarget;
+use super::env::{OutputDevice, WinitEnv};
+use super::event_loop::{EventLoopWindowTarget, WinitState};
+use super::protocols::color_management::wp_color_management_output_v1::{
+    self, WpColorManagementOutputV1,
+};
+use super::protocols::color_management::wp_color_manager_v1::WpColorManagerV1;
+use super::protocols::color_management::wp_image_description_info_v1;
+use super::protocols::color_management::wp_image_description_v1;
 
 /// Output manager.
 pub struct OutputManager {
@@ -26,7 +37,8 @@ pub struct OutputManager {
 
 impl OutputManager {
     pub fn new(env: &Environment<WinitEnv>) -> Self {
-        let handle = OutputManagerHandle::new();
+        let handle = OutputManagerHandle::new(env.with_inner(|env| env.output_devices()));
+        let color_manager = ColorManager::new(env);
 
         // Handle existing outputs.
         for output in env.get_all_outputs() {
@@ -37,16 +49,26 @@ impl OutputManager {
             }
 
             // The output is present and unusable, add it to the output manager manager.
-            handle.add_output(output);
+            handle.add_output(output, &color_manager);
         }
 
         let handle_for_listener = handle.clone();
 
-        let output_listener = env.listen_for_outputs(move |output, info, _| {
-            if info.obsolete {
-                handle_for_listener.remove_output(output)
+        let output_listener = env.listen_for_outputs(move |output, info, mut dispatch_data| {
+            let (added, removed) = if info.obsolete {
+                (None, handle_for_listener.remove_output(output))
             } else {
-                handle_for_listener.add_output(output)
+                (handle_for_listener.add_output(output, &color_manager), None)
+            };
+
+            if added.is_none() && removed.is_none() {
+                return;
+            }
+            if let Some(winit_state) = dispatch_data.get::<WinitState>() {
+                winit_state.event_sink.push_monitors_changed(
+                    added.into_iter().collect(),
+                    removed.into_iter().collect(),
+                );
             }
         });
 
@@ -65,30 +87,57 @@ impl OutputManager {
 #[derive(Debug, Clone)]
 pub struct OutputManagerHandle {
     outputs: Arc<Mutex<VecDeque<MonitorHandle>>>,
+
+    /// The outputs of KWin, which the monitors look their adaptive sync up in.
+    output_devices: Arc<Mutex<Vec<OutputDevice>>>,
 }
 
 impl OutputManagerHandle {
-    fn new() -> Self {
+    fn new(output_devices: Arc<Mutex<Vec<OutputDevice>>>) -> Self {
         let outputs = Arc::new(Mutex::new(VecDeque::new()));
-        Self { outputs }
+        Self {
+            outputs,
+            output_devices,
+        }
     }
 
-    /// Handle addition of the output.
-    fn add_output(&self, output: WlOutput) {
+    /// Handle addition of the output, returning the monitor when it wasn't known yet.
+    fn add_output(&self, output: WlOutput, color_manager: &ColorManager) -> Option<MonitorHandle> {
         let mut outputs = self.outputs.lock().unwrap();
         let position = outputs.iter().position(|handle| handle.proxy == output);
-        if position.is_none() {
-            outputs.push_back(MonitorHandle::new(output));
+        if position.is_some() {
+            return None;
         }
+
+        let mut monitor = MonitorHandle::new(output);
+        monitor.output_devices = self.output_devices.clone();
+        monitor.color_output = color_manager.get_output(&monitor);
+        outputs.push_back(monitor.clone());
+        Some(monitor)
     }
 
-    /// Handle removal of the output.
-    fn remove_output(&self, output: WlOutput) {
+    /// Handle removal of the output, returning the removed monitor.
+    fn remove_output(&self, output: WlOutput) -> Option<MonitorHandle> {
         let mut outputs = self.outputs.lock().unwrap();
-        let position = outputs.iter().position(|handle| handle.proxy == output);
-        if let Some(position) = position {
-            outputs.remove(position);
+        let position = outputs.iter().position(|handle| handle.proxy == output)?;
+        let mut monitor = outputs.remove(position)?;
+        if let Some(color_output) = monitor.color_output.take() {
+            color_output.destroy();
         }
+        Some(monitor)
+    }
+
+    /// Get the observed monitor of the output, which knows its color capabilities.
+    pub fn monitor(&self, output: WlOutput) -> MonitorHandle {
+        let outputs = self.outputs.lock().unwrap();
+        outputs
+            .iter()
+            .find(|handle| handle.proxy == output)
+            .cloned()
+            .unwrap_or_else(|| MonitorHandle {
+                output_devices: self.output_devices.clone(),
+                ..MonitorHandle::new(output)
+            })
     }
 
     /// Get all observed outputs.
@@ -100,6 +149,15 @@ impl OutputManagerHandle {
 #[derive(Clone, Debug)]
 pub struct MonitorHandle {
     pub(crate) proxy: WlOutput,
+
+    /// The color management of the output, when the compositor has it.
+    color_output: Option<WpColorManagementOutputV1>,
+
+    /// The color capabilities, once the compositor described the output.
+    color_capabilities: Arc<Mutex<ColorCapabilities>>,
+
+    /// The outputs of KWin, to look the adaptive sync of the output up.
+    output_devices: Arc<Mutex<Vec<OutputDevice>>>,
 }
 
 impl PartialEq for MonitorHandle {
@@ -131,7 +189,12 @@ impl std::hash::Hash for MonitorHandle {
 impl MonitorHandle {
     #[inline]
     pub(crate) fn new(proxy: WlOutput) -> Self {
-        Self { proxy }
+        Self {
+            proxy,
+            color_output: None,
+            color_capabilities: Default::default(),
+            output_devices: Default::default(),
+        }
     }
 
     #[inline]
@@ -188,6 +251,253 @@ impl MonitorHandle {
             }),
         })
     }
+
+    #[inline]
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        self.color_capabilities.lock().unwrap().clone()
+    }
+
+    #[inline]
+    pub fn supports_variable_refresh(&self) -> bool {
+        self.output_device()
+            .map_or(false, |device| device.vrr_capable)
+    }
+
+    /// The output of KWin with the same location, make and model.
+    pub(crate) fn output_device(&self) -> Option<OutputDevice> {
+        let output_devices = self.output_devices.lock().unwrap();
+        sctk::output::with_output_info(&self.proxy, |info| {
+            output_devices
+                .iter()
+                .find(|device| {
+                    device.location == info.location
+                        && device.make == info.make
+                        && device.model == info.model
+                })
+                .cloned()
+        })
+        .flatten()
+    }
+
+    /// The compositors don't tell the work area, which is the whole output.
+    #[inline]
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        (self.position(), self.size())
+    }
+
+    #[inline]
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        sctk::output::with_output_info(&self.proxy, |info| MonitorIdentity {
+            manufacturer: info.make.clone(),
+            model: Some(info.model.clone()),
+            product_code: None,
+            serial: None,
+        })
+    }
+}
+
+/// The color manager, which describes the color capabilities of the outputs.
+struct ColorManager {
+    color_manager: Option<Attached<WpColorManagerV1>>,
+
+    /// The transfer functions supported by the compositor.
+    transfer_functions: Rc<RefCell<Vec<TransferFunction>>>,
+}
+
+impl ColorManager {
+    fn new(env: &Environment<WinitEnv>) -> Self {
+        Self {
+            color_manager: env.get_global::<WpColorManagerV1>(),
+            transfer_functions: env.with_inner(|env| env.color_transfer_functions()),
+        }
+    }
+
+    /// Get the color management of the output, which describes it again once it changes.
+    fn get_output(&self, monitor: &MonitorHandle) -> Option<WpColorManagementOutputV1> {
+        let color_output = self.color_manager.as_ref()?.get_output(&monitor.proxy);
+        describe_output(&color_output, monitor, &self.transfer_functions);
+
+        let monitor = monitor.clone();
+        let transfer_functions = self.transfer_functions.clone();
+        color_output.quick_assign(move |color_output, event, _| match event {
+            wp_color_management_output_v1::Event::ImageDescriptionChanged => {
+                describe_output(&color_output, &monitor, &transfer_functions);
+            }
+        });
+
+        Some(color_output.detach())
+    }
+}
+
+/// Request the image description of the output, and update the color capabilities of the
+/// monitor once it's received.
+fn describe_output(
+    color_output: &WpColorManagementOutputV1,
+    monitor: &MonitorHandle,
+    transfer_functions: &Rc<RefCell<Vec<TransferFunction>>>,
+) {
+    let image_description = color_output.get_image_description();
+
+    let monitor = monitor.clone();
+    let transfer_functions = transfer_functions.clone();
+    image_description.quick_assign(move |image_description, event, _| {
+        match event {
+            wp_image_description_v1::Event::Ready { .. } => {
+                let info = image_description.get_information();
+                let monitor = monitor.clone();
+                let transfer_functions = transfer_functions.clone();
+                let mut description = OutputDescription::default();
+                info.quick_assign(move |_, event, mut dispatch_data| {
+                    if !description.handle_event(event) {
+                        return;
+                    }
+
+                    let capabilities = description.color_capabilities(&transfer_functions.borrow());
+                    let mut color_capabilities = monitor.color_capabilities.lock().unwrap();
+                    if *color_capabilities == capabilities {
+                        return;
+                    }
+                    *color_capabilities = capabilities.clone();
+                    drop(color_capabilities);
+
+                    if let Some(winit_state) = dispatch_data.get::<WinitState>() {
+                        let event = MonitorEvent::ColorChanged(capabilities);
+                        winit_state
+                            .event_sink
+                            .push_monitor_event(event, monitor.clone());
+                    }
+                });
+            }
+            // The output can't be described, so its capabilities stay unknown.
+            wp_image_description_v1::Event::Failed { .. } => (),
+        }
+        image_description.destroy();
+    });
+}
+
+/// The image description of an output, as it's received.
+#[derive(Default)]
+struct OutputDescription {
+    primaries: Option<Vec<ColorPrimaries>>,
+    target_primaries: Option<Vec<ColorPrimaries>>,
+    transfer_function: Option<TransferFunction>,
+    max_luminance: Option<u32>,
+    reference_luminance: Option<u32>,
+    target_max_luminance: Option<u32>,
+}
+
+impl OutputDescription {
+    /// Handle an event of the information, returning whether it's complete.
+    fn handle_event(&mut self, event: wp_image_description_info_v1::Event) -> bool {
+        use wp_image_description_info_v1::Event;
+
+        // The coordinates are given in millionths.
+        let coordinates = |x: i32, y: i32| (x as f32 / 1_000_000.0, y as f32 / 1_000_000.0);
+        match event {
+            Event::Done => return true,
+            Event::Primaries {
+                r_x,
+                r_y,
+                g_x,
+                g_y,
+                b_x,
+                b_y,
+                ..
+            } => {
+                self.primaries = Some(ColorPrimaries::covered_by(
+                    coordinates(r_x, r_y),
+                    coordinates(g_x, g_y),
+                    coordinates(b_x, b_y),
+                ))
+            }
+            Event::PrimariesNamed { primaries } => {
+                self.primaries = Some(named_primaries(primaries))
+            }
+            Event::TargetPrimaries {
+                r_x,
+                r_y,
+                g_x,
+                g_y,
+                b_x,
+                b_y,
+                ..
+            } => {
+                self.target_primaries = Some(ColorPrimaries::covered_by(
+                    coordinates(r_x, r_y),
+                    coordinates(g_x, g_y),
+                    coordinates(b_x, b_y),
+                ))
+            }
+            Event::TfNamed { tf } => self.transfer_function = transfer_function(tf),
+            Event::Luminances {
+                max_lum,
+                reference_lum,
+                ..
+            } => {
+                self.max_luminance = Some(max_lum);
+                self.reference_luminance = Some(reference_lum);
+            }
+            Event::TargetLuminance { max_lum, .. } => self.target_max_luminance = Some(max_lum),
+            // The ICC profiles aren't read, and the other events describe the content.
+            _ => (),
+        }
+
+        false
+    }
+
+    fn color_capabilities(&self, transfer_functions: &[TransferFunction]) -> ColorCapabilities {
+        // The target luminance defaults to the luminance of the primary color volume.
+        let max_luminance = self.target_max_luminance.or(self.max_luminance);
+        let headroom = match (max_luminance, self.reference_luminance) {
+            (Some(max_luminance), Some(reference)) if reference > 0 => {
+                Some(max_luminance as f32 / reference as f32)
+            }
+            _ => None,
+        };
+        let hdr = match self.transfer_function {
+            Some(TransferFunction::Pq) | Some(TransferFunction::Hlg) => true,
+            _ => headroom.map_or(false, |headroom| headroom > 1.0),
+        };
+
+        ColorCapabilities {
+            hdr,
+            bit_depth: None,
+            max_luminance: max_luminance.map(|luminance| luminance as f32),
+            headroom,
+            primaries: self
+                .target_primaries
+                .clone()
+                .or_else(|| self.primaries.clone())
+                .unwrap_or_default(),
+            transfer_functions: transfer_functions.to_vec(),
+        }
+    }
+}
+
+/// The standard primaries covered by the named primaries of the color manager.
+fn named_primaries(primaries: u32) -> Vec<ColorPrimaries> {
+    let primaries = match primaries {
+        1 => ColorPrimaries::Srgb,
+        6 => ColorPrimaries::Bt2020,
+        // DCI-P3 only differs from Display P3 by its white.
+        8 | 9 => ColorPrimaries::DisplayP3,
+        10 => ColorPrimaries::AdobeRgb,
+        _ => return Vec::new(),
+    };
+    let [red, green, blue, _] = primaries.chromaticities();
+    ColorPrimaries::covered_by(red, green, blue)
+}
+
+/// The transfer function of the named transfer function of the color manager.
+pub fn transfer_function(tf: u32) -> Option<TransferFunction> {
+    match tf {
+        // The gamma 2.2 is how most of the monitors decode sRGB.
+        2 | 9 => Some(TransferFunction::Srgb),
+        5 => Some(TransferFunction::ExtendedLinear),
+        11 => Some(TransferFunction::Pq),
+        13 => Some(TransferFunction::Hlg),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
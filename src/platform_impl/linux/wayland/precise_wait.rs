@@ -0,0 +1,47 @@
+//! The precise waits of `ControlFlow::WaitUntil`, with the timer registered with calloop.
+
+use sctk::reexports::calloop;
+use sctk::reexports::calloop::generic::Generic;
+
+use crate::error::ExternalError;
+use crate::platform_impl::platform::OsError;
+
+use super::event_loop::EventLoopWindowTarget;
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn set_precise_wait(&self, precise: bool) -> Result<(), ExternalError> {
+        self.precise_wait.set_enabled(precise).map_err(|_| {
+            ExternalError::Os(os_error!(OsError::WaylandMisc(
+                "Failed to create the timer of the precise waits"
+            )))
+        })?;
+
+        if self.precise_wait.is_registered() {
+            return Ok(());
+        }
+        let timer = match self.precise_wait.timer() {
+            Some(timer) => timer,
+            None => return Ok(()),
+        };
+        // The timer is kept once it's created, and so is the source.
+        self.event_loop_handle
+            .insert_source(
+                Generic::from_fd(
+                    timer.fd(),
+                    calloop::Interest::Readable,
+                    calloop::Mode::Level,
+                ),
+                move |_, _, _| {
+                    timer.clear();
+                    Ok(())
+                },
+            )
+            .map_err(|_| {
+                ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "Failed to register the timer of the precise waits"
+                )))
+            })?;
+        self.precise_wait.set_registered();
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! Wayland protocols which aren't part of `wayland-protocols`, generated by the build script.
+
+#![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+#![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+#![allow(missing_docs, clippy::all)]
+#![allow(unknown_lints, static_mut_refs)]
+
+/// The blur behind the surfaces of KWin.
+pub mod blur {
+    pub(crate) use wayland_client::protocol::{wl_region, wl_surface};
+    pub(crate) use wayland_client::sys;
+    pub(crate) use wayland_client::{AnonymousObject, Attached, Main, Proxy, ProxyMap};
+    pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+    pub(crate) use wayland_commons::smallvec;
+    pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
+    pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+    include!(concat!(env!("OUT_DIR"), "/blur_client_api.rs"));
+}
+
+/// The color management of the surfaces and the outputs.
+pub mod color_management {
+    pub(crate) use wayland_client::protocol::{wl_output, wl_surface};
+    pub(crate) use wayland_client::sys;
+    pub(crate) use wayland_client::{AnonymousObject, Attached, Main, Proxy, ProxyMap};
+    pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+    pub(crate) use wayland_commons::smallvec;
+    pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
+    pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+    include!(concat!(env!("OUT_DIR"), "/color_management_client_api.rs"));
+}
+
+/// The outputs of KWin, which tell their adaptive sync policy.
+pub mod kde_output_device {
+    pub(crate) use wayland_client::sys;
+    pub(crate) use wayland_client::{AnonymousObject, Attached, Main, Proxy, ProxyMap};
+    pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+    pub(crate) use wayland_commons::smallvec;
+    pub(crate) use wayland_commons::wire::{Argument, ArgumentType, Message, MessageDesc};
+    pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+    include!(concat!(env!("OUT_DIR"), "/kde_output_device_client_api.rs"));
+}
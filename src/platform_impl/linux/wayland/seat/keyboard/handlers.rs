@@ -5,8 +5,10 @@ use sctk::reexports::client::protocol::wl_keyboard::KeyState;
 use sctk::seat::keyboard::Event as KeyboardEvent;
 
 use crate::event::{ElementState, KeyboardInput, ModifiersState, WindowEvent};
+use crate::keymap::LockKeys;
+use crate::platform_impl::platform::compose::Compose;
+use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::platform_impl::wayland::{self, DeviceId};
 
 use super::keymap;
 use super::KeyboardInner;
@@ -18,10 +20,20 @@ pub(super) fn handle_keyboard(
     winit_state: &mut WinitState,
 ) {
     let event_sink = &mut winit_state.event_sink;
+    let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+        wayland::make_did(&inner.seat),
+    ));
     match event {
-        KeyboardEvent::Enter { surface, .. } => {
+        KeyboardEvent::Enter {
+            surface, serial, ..
+        } => {
             let window_id = wayland::make_wid(&surface);
 
+            // Keep the serial to be able to set the clipboard.
+            winit_state
+                .clipboard
+                .set_latest_serial(inner.seat.clone(), serial);
+
             // Window gained focus.
             event_sink.push_window_event(WindowEvent::Focused(true), window_id);
 
@@ -29,6 +41,18 @@ pub(super) fn handle_keyboard(
             if let Some(modifiers) = inner.pending_modifers_state.take() {
                 *inner.modifiers_state.borrow_mut() = modifiers;
                 event_sink.push_window_event(WindowEvent::ModifiersChanged(modifiers), window_id);
+                event_sink.push_window_event(
+                    WindowEvent::DeviceModifiersChanged {
+                        device_id,
+                        modifiers,
+                    },
+                    window_id,
+                );
+            }
+
+            // Inhibit the shortcuts of this seat if the window asked so.
+            if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                window_handle.keyboard_entered(inner.seat.clone());
             }
 
             inner.target_window_id = Some(window_id);
@@ -36,12 +60,28 @@ pub(super) fn handle_keyboard(
         KeyboardEvent::Leave { surface, .. } => {
             let window_id = wayland::make_wid(&surface);
 
+            if let Some(state) = inner.compose.as_mut().and_then(Compose::cancel) {
+                event_sink.push_window_event(WindowEvent::ComposeChanged(state), window_id);
+            }
+
             // Notify that no modifiers are being pressed.
             if !inner.modifiers_state.borrow().is_empty() {
                 event_sink.push_window_event(
                     WindowEvent::ModifiersChanged(ModifiersState::empty()),
                     window_id,
                 );
+                event_sink.push_window_event(
+                    WindowEvent::DeviceModifiersChanged {
+                        device_id,
+                        modifiers: ModifiersState::empty(),
+                    },
+                    window_id,
+                );
+            }
+
+            // The shortcuts of this seat are given back to the compositor.
+            if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                window_handle.keyboard_left(&inner.seat);
             }
 
             // Window lost focus.
@@ -55,8 +95,13 @@ pub(super) fn handle_keyboard(
             keysym,
             state,
             utf8,
+            serial,
             ..
         } => {
+            winit_state
+                .clipboard
+                .set_latest_serial(inner.seat.clone(), serial);
+
             let window_id = match inner.target_window_id {
                 Some(window_id) => window_id,
                 None => return,
@@ -68,16 +113,26 @@ pub(super) fn handle_keyboard(
                 _ => unreachable!(),
             };
 
+            if state == ElementState::Pressed {
+                let compose_state = inner
+                    .compose
+                    .as_mut()
+                    .and_then(|compose| compose.feed(keysym));
+                if let Some(compose_state) = compose_state {
+                    event_sink
+                        .push_window_event(WindowEvent::ComposeChanged(compose_state), window_id);
+                }
+            }
+
             let virtual_keycode = keymap::keysym_to_vkey(keysym);
 
             event_sink.push_window_event(
                 #[allow(deprecated)]
                 WindowEvent::KeyboardInput {
-                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
-                    )),
+                    device_id,
                     input: KeyboardInput {
                         state,
+                        repeat: false,
                         scancode: rawkey,
                         virtual_keycode,
                         modifiers: *inner.modifiers_state.borrow(),
@@ -109,16 +164,21 @@ pub(super) fn handle_keyboard(
                 None => return,
             };
 
+            if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                if !window_handle.key_repeat.get() {
+                    return;
+                }
+            }
+
             let virtual_keycode = keymap::keysym_to_vkey(keysym);
 
             event_sink.push_window_event(
                 #[allow(deprecated)]
                 WindowEvent::KeyboardInput {
-                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
-                    )),
+                    device_id,
                     input: KeyboardInput {
                         state: ElementState::Pressed,
+                        repeat: true,
                         scancode: rawkey,
                         virtual_keycode,
                         modifiers: *inner.modifiers_state.borrow(),
@@ -135,11 +195,19 @@ pub(super) fn handle_keyboard(
             }
         }
         KeyboardEvent::Modifiers { modifiers } => {
+            winit_state.lock_keys = LockKeys::from(modifiers);
             let modifiers = ModifiersState::from(modifiers);
             if let Some(window_id) = inner.target_window_id {
                 *inner.modifiers_state.borrow_mut() = modifiers;
 
                 event_sink.push_window_event(WindowEvent::ModifiersChanged(modifiers), window_id);
+                event_sink.push_window_event(
+                    WindowEvent::DeviceModifiersChanged {
+                        device_id,
+                        modifiers,
+                    },
+                    window_id,
+                );
             } else {
                 // Compositor must send modifiers after wl_keyboard::enter, however certain
                 // compositors are still sending it before, so stash such events and send
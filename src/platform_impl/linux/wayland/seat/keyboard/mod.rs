@@ -11,8 +11,11 @@ use sctk::reexports::calloop::{LoopHandle, Source};
 
 use sctk::seat::keyboard::{self, RepeatSource};
 
+use crate::error::{ExternalError, NotSupportedError};
 use crate::event::ModifiersState;
-use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::keymap::LockKeys;
+use crate::platform_impl::platform::compose::Compose;
+use crate::platform_impl::wayland::event_loop::{EventLoopWindowTarget, WinitState};
 use crate::platform_impl::wayland::WindowId;
 
 mod handlers;
@@ -34,7 +37,7 @@ impl Keyboard {
         loop_handle: LoopHandle<WinitState>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
     ) -> Option<Self> {
-        let mut inner = KeyboardInner::new(modifiers_state);
+        let mut inner = KeyboardInner::new(seat.detach(), modifiers_state);
         let keyboard_data = keyboard::map_keyboard_repeat(
             loop_handle.clone(),
             &seat,
@@ -69,6 +72,9 @@ impl Drop for Keyboard {
 }
 
 struct KeyboardInner {
+    /// The seat this keyboard belongs to.
+    seat: WlSeat,
+
     /// Currently focused surface.
     target_window_id: Option<WindowId>,
 
@@ -81,14 +87,19 @@ struct KeyboardInner {
 
     /// Current state of modifiers keys.
     modifiers_state: Rc<RefCell<ModifiersState>>,
+
+    /// The compose sequence in progress, followed along with the one of the keyboard.
+    compose: Option<Compose>,
 }
 
 impl KeyboardInner {
-    fn new(modifiers_state: Rc<RefCell<ModifiersState>>) -> Self {
+    fn new(seat: WlSeat, modifiers_state: Rc<RefCell<ModifiersState>>) -> Self {
         Self {
+            seat,
             target_window_id: None,
             pending_modifers_state: None,
             modifiers_state,
+            compose: Compose::new(),
         }
     }
 }
@@ -103,3 +114,23 @@ impl From<keyboard::ModifiersState> for ModifiersState {
         wl_mods
     }
 }
+
+impl From<keyboard::ModifiersState> for LockKeys {
+    fn from(mods: keyboard::ModifiersState) -> LockKeys {
+        let mut lock_keys = LockKeys::empty();
+        lock_keys.set(LockKeys::CAPS_LOCK, mods.caps_lock);
+        lock_keys.set(LockKeys::NUM_LOCK, mods.num_lock);
+        lock_keys
+    }
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        Ok(self.state.borrow().lock_keys)
+    }
+
+    pub fn set_lock_keys(&self, _lock_keys: LockKeys, _on: bool) -> Result<(), ExternalError> {
+        // The compositor owns the state of the keyboards.
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+}
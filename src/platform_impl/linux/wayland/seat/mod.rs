@@ -5,8 +5,12 @@ use std::rc::Rc;
 
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
 
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::Attached;
 
@@ -16,16 +20,21 @@ use sctk::seat::pointer::ThemeManager;
 use sctk::seat::{SeatData, SeatListener};
 
 use super::env::WinitEnv;
-use super::event_loop::WinitState;
-use crate::event::ModifiersState;
+use super::event_loop::{EventLoopWindowTarget, EventSink, WinitState};
+use crate::event::{DeviceCapabilities, DeviceEvent, DeviceId, InputDevice, ModifiersState};
+use crate::platform_impl;
 
 mod keyboard;
 pub mod pointer;
+mod selection;
+mod tablet;
 pub mod text_input;
 mod touch;
 
 use keyboard::Keyboard;
 use pointer::Pointers;
+use selection::SelectionObserver;
+use tablet::Tablet;
 use text_input::TextInput;
 use touch::Touch;
 
@@ -40,17 +49,7 @@ impl SeatManager {
         loop_handle: LoopHandle<WinitState>,
         theme_manager: ThemeManager,
     ) -> Self {
-        let relative_pointer_manager = env.get_global::<ZwpRelativePointerManagerV1>();
-        let pointer_constraints = env.get_global::<ZwpPointerConstraintsV1>();
-        let text_input_manager = env.get_global::<ZwpTextInputManagerV3>();
-
-        let mut inner = SeatManagerInner::new(
-            theme_manager,
-            relative_pointer_manager,
-            pointer_constraints,
-            text_input_manager,
-            loop_handle,
-        );
+        let mut inner = SeatManagerInner::new(theme_manager, SeatGlobals::new(env), loop_handle);
 
         // Handle existing seats.
         for seat in env.get_all_seats() {
@@ -59,11 +58,12 @@ impl SeatManager {
                 None => continue,
             };
 
-            inner.process_seat_update(&seat, &seat_data);
+            inner.process_seat_update(&seat, &seat_data, None);
         }
 
-        let seat_listener = env.listen_for_seats(move |seat, seat_data, _| {
-            inner.process_seat_update(&seat, &seat_data);
+        let seat_listener = env.listen_for_seats(move |seat, seat_data, mut dispatch_data| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            inner.process_seat_update(&seat, seat_data, Some(&mut winit_state.event_sink));
         });
 
         Self {
@@ -72,23 +72,90 @@ impl SeatManager {
     }
 }
 
-/// Inner state of the seat manager.
-struct SeatManagerInner {
-    /// Currently observed seats.
-    seats: Vec<SeatInfo>,
+impl<T> EventLoopWindowTarget<T> {
+    /// The seats, since the events are identified by their seat.
+    pub fn input_devices(&self) -> Vec<InputDevice> {
+        self.env
+            .get_all_seats()
+            .iter()
+            .filter_map(|seat| {
+                sctk::seat::with_seat_data(seat, |seat_data| {
+                    if seat_data.defunct {
+                        None
+                    } else {
+                        Some(seat_device(seat, seat_data))
+                    }
+                })
+                .flatten()
+            })
+            .collect()
+    }
+}
 
-    /// Loop handle.
-    loop_handle: LoopHandle<WinitState>,
+fn seat_device(seat: &WlSeat, seat_data: &SeatData) -> InputDevice {
+    let mut capabilities = DeviceCapabilities::empty();
+    capabilities.set(DeviceCapabilities::KEYBOARD, seat_data.has_keyboard);
+    capabilities.set(DeviceCapabilities::POINTER, seat_data.has_pointer);
+    capabilities.set(DeviceCapabilities::TOUCH, seat_data.has_touch);
+    InputDevice {
+        id: DeviceId(platform_impl::DeviceId::Wayland(super::make_did(seat))),
+        name: seat_data.name.clone(),
+        vendor_id: None,
+        product_id: None,
+        capabilities,
+        resolution: None,
+    }
+}
 
+/// The globals of the optional protocols the seats use, if the compositor advertises them.
+struct SeatGlobals {
     /// Relative pointer manager.
     relative_pointer_manager: Option<Attached<ZwpRelativePointerManagerV1>>,
 
     /// Pointer constraints.
     pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
 
+    /// Pointer gestures.
+    pointer_gestures: Option<Attached<ZwpPointerGesturesV1>>,
+
     /// Text input manager.
     text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
 
+    /// Tablet manager.
+    tablet_manager: Option<Attached<ZwpTabletManagerV2>>,
+
+    /// Data device manager.
+    data_device_manager: Option<Attached<WlDataDeviceManager>>,
+
+    /// Primary selection manager.
+    primary_selection_manager: Option<Attached<ZwpPrimarySelectionDeviceManagerV1>>,
+}
+
+impl SeatGlobals {
+    fn new(env: &Environment<WinitEnv>) -> Self {
+        Self {
+            relative_pointer_manager: env.get_global::<ZwpRelativePointerManagerV1>(),
+            pointer_constraints: env.get_global::<ZwpPointerConstraintsV1>(),
+            pointer_gestures: env.get_global::<ZwpPointerGesturesV1>(),
+            text_input_manager: env.get_global::<ZwpTextInputManagerV3>(),
+            tablet_manager: env.get_global::<ZwpTabletManagerV2>(),
+            data_device_manager: env.get_global::<WlDataDeviceManager>(),
+            primary_selection_manager: env.get_global::<ZwpPrimarySelectionDeviceManagerV1>(),
+        }
+    }
+}
+
+/// Inner state of the seat manager.
+struct SeatManagerInner {
+    /// Currently observed seats.
+    seats: Vec<SeatInfo>,
+
+    /// Loop handle.
+    loop_handle: LoopHandle<WinitState>,
+
+    /// The globals of the optional protocols.
+    globals: SeatGlobals,
+
     /// A theme manager.
     theme_manager: ThemeManager,
 }
@@ -96,26 +163,44 @@ struct SeatManagerInner {
 impl SeatManagerInner {
     fn new(
         theme_manager: ThemeManager,
-        relative_pointer_manager: Option<Attached<ZwpRelativePointerManagerV1>>,
-        pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
-        text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
+        globals: SeatGlobals,
         loop_handle: LoopHandle<WinitState>,
     ) -> Self {
         Self {
             seats: Vec::new(),
             loop_handle,
-            relative_pointer_manager,
-            pointer_constraints,
-            text_input_manager,
+            globals,
             theme_manager,
         }
     }
 
-    /// Handle seats update from the `SeatListener`.
-    pub fn process_seat_update(&mut self, seat: &Attached<WlSeat>, seat_data: &SeatData) {
+    /// Handle seats update from the `SeatListener`, sending the seats added and removed to the
+    /// event sink, which isn't there for the initial seats.
+    pub fn process_seat_update(
+        &mut self,
+        seat: &Attached<WlSeat>,
+        seat_data: &SeatData,
+        event_sink: Option<&mut EventSink>,
+    ) {
         let detached_seat = seat.detach();
 
         let position = self.seats.iter().position(|si| si.seat == detached_seat);
+        if let Some(event_sink) = event_sink {
+            let device_id = super::make_did(&detached_seat);
+            if seat_data.defunct && position.is_some() {
+                event_sink.push_device_event(DeviceEvent::Removed, device_id);
+            } else if !seat_data.defunct && position.is_none() {
+                let device = seat_device(&detached_seat, seat_data);
+                event_sink.push_device_event(DeviceEvent::Added(device), device_id);
+            }
+        }
+        if seat_data.defunct {
+            // The seat is gone for good, along with its devices.
+            if let Some(index) = position {
+                self.seats.remove(index);
+            }
+            return;
+        }
         let index = position.unwrap_or_else(|| {
             self.seats.push(SeatInfo::new(detached_seat));
             self.seats.len() - 1
@@ -129,8 +214,9 @@ impl SeatManagerInner {
                 seat_info.pointer = Some(Pointers::new(
                     &seat,
                     &self.theme_manager,
-                    &self.relative_pointer_manager,
-                    &self.pointer_constraints,
+                    &self.globals.relative_pointer_manager,
+                    &self.globals.pointer_constraints,
+                    &self.globals.pointer_gestures,
                     seat_info.modifiers_state.clone(),
                 ));
             }
@@ -161,13 +247,35 @@ impl SeatManagerInner {
         }
 
         // Handle text input.
-        if let Some(text_input_manager) = self.text_input_manager.as_ref() {
+        if let Some(text_input_manager) = self.globals.text_input_manager.as_ref() {
             if seat_data.defunct {
                 seat_info.text_input = None;
             } else if seat_info.text_input.is_none() {
                 seat_info.text_input = Some(TextInput::new(&seat, &text_input_manager));
             }
         }
+
+        // Handle tablets.
+        if let Some(tablet_manager) = self.globals.tablet_manager.as_ref() {
+            if seat_data.defunct {
+                seat_info.tablet = None;
+            } else if seat_info.tablet.is_none() {
+                seat_info.tablet = Some(Tablet::new(seat, tablet_manager));
+            }
+        }
+
+        // Handle selection changes.
+        if let Some(data_device_manager) = self.globals.data_device_manager.as_ref() {
+            if seat_data.defunct {
+                seat_info.selection = None;
+            } else if seat_info.selection.is_none() {
+                seat_info.selection = Some(SelectionObserver::new(
+                    seat,
+                    data_device_manager,
+                    self.globals.primary_selection_manager.as_deref(),
+                ));
+            }
+        }
     }
 }
 
@@ -188,6 +296,12 @@ struct SeatInfo {
     /// Text input handling aka IME.
     text_input: Option<TextInput>,
 
+    /// The pens of the graphics tablets.
+    tablet: Option<Tablet>,
+
+    /// Selection changes observing.
+    selection: Option<SelectionObserver>,
+
     /// The current state of modifiers observed in keyboard handler.
     ///
     /// We keep modifiers state on a seat, since it's being used by pointer events as well.
@@ -202,6 +316,8 @@ impl SeatInfo {
             pointer: None,
             touch: None,
             text_input: None,
+            tablet: None,
+            selection: None,
             modifiers_state: Rc::new(RefCell::new(ModifiersState::default())),
         }
     }
@@ -7,14 +7,19 @@ use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1};
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
 
-use crate::event::{ModifiersState, TouchPhase};
+use crate::dpi::PhysicalPosition;
+use crate::event::{AxisSource, ModifiersState, TouchPhase};
 
 /// A data being used by pointer handlers.
 pub(super) struct PointerData {
     /// Winit's surface the pointer is currently over.
     pub surface: Option<WlSurface>,
 
+    /// The latest position of the pointer on the surface.
+    pub position: PhysicalPosition<f64>,
+
     /// Current modifiers state.
     ///
     /// This refers a state of modifiers from `WlKeyboard` on
@@ -26,6 +31,8 @@ pub(super) struct PointerData {
 
     pub confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
 
+    pub locked_pointer: Rc<RefCell<Option<ZwpLockedPointerV1>>>,
+
     /// A latest event serial.
     pub latest_serial: Rc<Cell<u32>>,
 
@@ -36,13 +43,16 @@ pub(super) struct PointerData {
 impl PointerData {
     pub fn new(
         confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+        locked_pointer: Rc<RefCell<Option<ZwpLockedPointerV1>>>,
         pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
     ) -> Self {
         Self {
             surface: None,
+            position: PhysicalPosition::new(0.0, 0.0),
             latest_serial: Rc::new(Cell::new(0)),
             confined_pointer,
+            locked_pointer,
             modifiers_state,
             pointer_constraints,
             axis_data: AxisData::new(),
@@ -59,8 +69,14 @@ pub(super) struct AxisData {
     /// A buffer for `PixelDelta` event.
     pub axis_buffer: Option<(f32, f32)>,
 
-    /// A buffer for `LineDelta` event.
-    pub axis_discrete_buffer: Option<(f32, f32)>,
+    /// A buffer for `Value120` event, in notches.
+    pub axis_discrete_buffer: Option<(i32, i32)>,
+
+    /// The source of the axis events of the frame.
+    pub axis_source: Option<AxisSource>,
+
+    /// Whether the scroll stopped in the frame.
+    pub axis_stopped: bool,
 }
 
 impl AxisData {
@@ -69,6 +85,21 @@ impl AxisData {
             axis_state: TouchPhase::Ended,
             axis_buffer: None,
             axis_discrete_buffer: None,
+            axis_source: None,
+            axis_stopped: false,
         }
     }
 }
+
+/// The gesture in progress on a pointer.
+#[derive(Default)]
+pub(super) struct GestureData {
+    /// The surface the gesture started on.
+    pub surface: Option<WlSurface>,
+
+    /// The number of fingers of the gesture.
+    pub fingers: u32,
+
+    /// The scale of the pinch at the previous event, relative to its start.
+    pub scale: f64,
+}
@@ -6,22 +6,28 @@ use std::rc::Rc;
 use sctk::reexports::client::protocol::wl_pointer::{self, Event as PointerEvent};
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_v1::Event as RelativePointerEvent;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_pinch_v1::Event as PinchEvent;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_swipe_v1::Event as SwipeEvent;
 
 use sctk::seat::pointer::ThemedPointer;
 
-use crate::dpi::LogicalPosition;
+use crate::dpi::{LogicalPosition, PhysicalPosition};
 use crate::event::{
-    DeviceEvent, ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
+    AxisSource, DeviceEvent, ElementState, GesturePhase, MouseButton, MouseScrollDelta, TouchPhase,
+    WindowEvent,
 };
+use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::platform_impl::wayland::{self, DeviceId};
 
-use super::{PointerData, WinitPointer};
+use super::{GestureData, PointerData, WinitPointer};
 
 // These values are comming from <linux/input-event-codes.h>.
 const BTN_LEFT: u32 = 0x110;
 const BTN_RIGHT: u32 = 0x111;
 const BTN_MIDDLE: u32 = 0x112;
+const BTN_SIDE: u32 = 0x113;
+const BTN_EXTRA: u32 = 0x114;
+const BTN_FORWARD: u32 = 0x115;
 
 #[inline]
 pub(super) fn handle_pointer(
@@ -33,6 +39,7 @@ pub(super) fn handle_pointer(
 ) {
     let event_sink = &mut winit_state.event_sink;
     let mut pointer_data = pointer_data.borrow_mut();
+    let device_id = wayland::make_did(&seat);
     match event {
         PointerEvent::Enter {
             surface,
@@ -59,27 +66,31 @@ pub(super) fn handle_pointer(
             let winit_pointer = WinitPointer {
                 pointer,
                 confined_pointer: Rc::downgrade(&pointer_data.confined_pointer),
+                locked_pointer: Rc::downgrade(&pointer_data.locked_pointer),
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
+                modifiers_state: pointer_data.modifiers_state.clone(),
                 seat,
             };
             window_handle.pointer_entered(winit_pointer);
 
+            let position = LogicalPosition::new(surface_x, surface_y).to_physical(scale_factor);
+            pointer_data.position = position;
+
             event_sink.push_window_event(
                 WindowEvent::CursorEntered {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
+                        device_id,
                     )),
+                    position,
                 },
                 window_id,
             );
 
-            let position = LogicalPosition::new(surface_x, surface_y).to_physical(scale_factor);
-
             event_sink.push_window_event(
                 WindowEvent::CursorMoved {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
+                        device_id,
                     )),
                     position,
                     modifiers: *pointer_data.modifiers_state.borrow(),
@@ -102,8 +113,10 @@ pub(super) fn handle_pointer(
             let winit_pointer = WinitPointer {
                 pointer,
                 confined_pointer: Rc::downgrade(&pointer_data.confined_pointer),
+                locked_pointer: Rc::downgrade(&pointer_data.locked_pointer),
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
+                modifiers_state: pointer_data.modifiers_state.clone(),
                 seat,
             };
             window_handle.pointer_left(winit_pointer);
@@ -111,8 +124,9 @@ pub(super) fn handle_pointer(
             event_sink.push_window_event(
                 WindowEvent::CursorLeft {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
+                        device_id,
                     )),
+                    position: pointer_data.position,
                 },
                 window_id,
             );
@@ -131,11 +145,12 @@ pub(super) fn handle_pointer(
 
             let scale_factor = sctk::get_surface_scale_factor(&surface) as f64;
             let position = LogicalPosition::new(surface_x, surface_y).to_physical(scale_factor);
+            pointer_data.position = position;
 
             event_sink.push_window_event(
                 WindowEvent::CursorMoved {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
+                        device_id,
                     )),
                     position,
                     modifiers: *pointer_data.modifiers_state.borrow(),
@@ -150,6 +165,9 @@ pub(super) fn handle_pointer(
             ..
         } => {
             pointer_data.latest_serial.replace(serial);
+            winit_state
+                .clipboard
+                .set_latest_serial(seat.clone(), serial);
             let window_id = match pointer_data.surface.as_ref().map(wayland::make_wid) {
                 Some(window_id) => window_id,
                 None => return,
@@ -165,13 +183,16 @@ pub(super) fn handle_pointer(
                 BTN_LEFT => MouseButton::Left,
                 BTN_RIGHT => MouseButton::Right,
                 BTN_MIDDLE => MouseButton::Middle,
-                button => MouseButton::Other(button as u16),
+                BTN_SIDE => MouseButton::Back,
+                BTN_EXTRA => MouseButton::Forward,
+                // Numbered like on X11, where `BTN_FORWARD` is button 10.
+                button => MouseButton::Other((button.saturating_sub(BTN_FORWARD) + 6) as u16),
             };
 
             event_sink.push_window_event(
                 WindowEvent::MouseInput {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
+                        device_id,
                     )),
                     state,
                     button,
@@ -205,10 +226,11 @@ pub(super) fn handle_pointer(
                 event_sink.push_window_event(
                     WindowEvent::MouseWheel {
                         device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                            DeviceId,
+                            device_id,
                         )),
                         delta: MouseScrollDelta::PixelDelta(delta),
                         phase: TouchPhase::Moved,
+                        source: None,
                         modifiers: *pointer_data.modifiers_state.borrow(),
                     },
                     window_id,
@@ -234,12 +256,12 @@ pub(super) fn handle_pointer(
             let (mut x, mut y) = pointer_data
                 .axis_data
                 .axis_discrete_buffer
-                .unwrap_or((0., 0.));
+                .unwrap_or((0, 0));
 
             match axis {
                 // Wayland vertical sign convention is the inverse of winit.
-                wl_pointer::Axis::VerticalScroll => y -= discrete as f32,
-                wl_pointer::Axis::HorizontalScroll => x += discrete as f32,
+                wl_pointer::Axis::VerticalScroll => y -= discrete,
+                wl_pointer::Axis::HorizontalScroll => x += discrete,
                 _ => unreachable!(),
             }
 
@@ -250,13 +272,23 @@ pub(super) fn handle_pointer(
                 _ => TouchPhase::Started,
             }
         }
-        PointerEvent::AxisSource { .. } => (),
+        PointerEvent::AxisSource { axis_source } => {
+            pointer_data.axis_data.axis_source = match axis_source {
+                wl_pointer::AxisSource::Wheel => Some(AxisSource::Wheel),
+                wl_pointer::AxisSource::WheelTilt => Some(AxisSource::WheelTilt),
+                wl_pointer::AxisSource::Finger => Some(AxisSource::Finger),
+                wl_pointer::AxisSource::Continuous => Some(AxisSource::Continuous),
+                _ => None,
+            };
+        }
         PointerEvent::AxisStop { .. } => {
-            pointer_data.axis_data.axis_state = TouchPhase::Ended;
+            pointer_data.axis_data.axis_stopped = true;
         }
         PointerEvent::Frame => {
             let axis_buffer = pointer_data.axis_data.axis_buffer.take();
             let axis_discrete_buffer = pointer_data.axis_data.axis_discrete_buffer.take();
+            let axis_source = pointer_data.axis_data.axis_source.take();
+            let axis_stopped = std::mem::replace(&mut pointer_data.axis_data.axis_stopped, false);
 
             let surface = match pointer_data.surface.as_ref() {
                 Some(surface) => surface,
@@ -264,42 +296,193 @@ pub(super) fn handle_pointer(
             };
             let window_id = wayland::make_wid(&surface);
 
-            let window_event = if let Some((x, y)) = axis_discrete_buffer {
-                WindowEvent::MouseWheel {
-                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
-                    )),
-                    delta: MouseScrollDelta::LineDelta(x, y),
-                    phase: pointer_data.axis_data.axis_state,
-                    modifiers: *pointer_data.modifiers_state.borrow(),
-                }
+            let delta = if let Some((x, y)) = axis_discrete_buffer {
+                Some(MouseScrollDelta::Value120(x * 120, y * 120))
             } else if let Some((x, y)) = axis_buffer {
                 let scale_factor = sctk::get_surface_scale_factor(&surface) as f64;
                 let delta = LogicalPosition::new(x, y).to_physical(scale_factor);
-
-                WindowEvent::MouseWheel {
-                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
-                    )),
-                    delta: MouseScrollDelta::PixelDelta(delta),
-                    phase: pointer_data.axis_data.axis_state,
-                    modifiers: *pointer_data.modifiers_state.borrow(),
-                }
+                Some(MouseScrollDelta::PixelDelta(delta))
             } else {
-                return;
+                None
             };
 
-            event_sink.push_window_event(window_event, window_id);
+            if let Some(delta) = delta {
+                event_sink.push_window_event(
+                    WindowEvent::MouseWheel {
+                        device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                            device_id,
+                        )),
+                        delta,
+                        phase: pointer_data.axis_data.axis_state,
+                        source: axis_source,
+                        modifiers: *pointer_data.modifiers_state.borrow(),
+                    },
+                    window_id,
+                );
+            }
+
+            // The stop of the scroll is an event of its own, without any delta.
+            if axis_stopped && pointer_data.axis_data.axis_state != TouchPhase::Ended {
+                pointer_data.axis_data.axis_state = TouchPhase::Ended;
+                event_sink.push_window_event(
+                    WindowEvent::MouseWheel {
+                        device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                            device_id,
+                        )),
+                        delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 0.0)),
+                        phase: TouchPhase::Ended,
+                        source: axis_source,
+                        modifiers: *pointer_data.modifiers_state.borrow(),
+                    },
+                    window_id,
+                );
+            }
         }
         _ => (),
     }
 }
 
 #[inline]
-pub(super) fn handle_relative_pointer(event: RelativePointerEvent, winit_state: &mut WinitState) {
-    if let RelativePointerEvent::RelativeMotion { dx, dy, .. } = event {
-        winit_state
-            .event_sink
-            .push_device_event(DeviceEvent::MouseMotion { delta: (dx, dy) }, DeviceId)
+pub(super) fn handle_relative_pointer(
+    event: RelativePointerEvent,
+    winit_state: &mut WinitState,
+    seat: &WlSeat,
+) {
+    if let RelativePointerEvent::RelativeMotion {
+        dx_unaccel,
+        dy_unaccel,
+        ..
+    } = event
+    {
+        winit_state.event_sink.push_device_event(
+            DeviceEvent::MouseMotion {
+                delta: (dx_unaccel, dy_unaccel),
+            },
+            wayland::make_did(seat),
+        )
     }
 }
+
+#[inline]
+pub(super) fn handle_pinch_gesture(
+    event: PinchEvent,
+    gesture_data: &mut GestureData,
+    winit_state: &mut WinitState,
+    seat: &WlSeat,
+) {
+    let (surface, scale_delta, rotation, phase) = match event {
+        PinchEvent::Begin { surface, .. } => {
+            gesture_data.surface = Some(surface.clone());
+            gesture_data.scale = 1.;
+            (surface, 0., 0., GesturePhase::Started)
+        }
+        PinchEvent::Update {
+            scale, rotation, ..
+        } => {
+            let surface = match gesture_data.surface.as_ref() {
+                Some(surface) => surface.clone(),
+                None => return,
+            };
+            let scale_delta = scale - gesture_data.scale;
+            gesture_data.scale = scale;
+            // The rotation is clockwise.
+            (surface, scale_delta, -rotation, GesturePhase::Updated)
+        }
+        PinchEvent::End { cancelled, .. } => {
+            let surface = match gesture_data.surface.take() {
+                Some(surface) => surface,
+                None => return,
+            };
+            let phase = if cancelled != 0 {
+                GesturePhase::Cancelled
+            } else {
+                GesturePhase::Ended
+            };
+            (surface, 0., 0., phase)
+        }
+        _ => return,
+    };
+
+    let window_id = wayland::make_wid(&surface);
+    if !winit_state.window_map.contains_key(&window_id) {
+        return;
+    }
+
+    let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+        wayland::make_did(seat),
+    ));
+    let event_sink = &mut winit_state.event_sink;
+    event_sink.push_window_event(
+        WindowEvent::PinchGesture {
+            device_id,
+            delta: scale_delta,
+            phase,
+        },
+        window_id,
+    );
+    event_sink.push_window_event(
+        WindowEvent::RotationGesture {
+            device_id,
+            delta: rotation as f32,
+            phase,
+        },
+        window_id,
+    );
+}
+
+#[inline]
+pub(super) fn handle_swipe_gesture(
+    event: SwipeEvent,
+    gesture_data: &mut GestureData,
+    winit_state: &mut WinitState,
+    seat: &WlSeat,
+) {
+    let (surface, delta, phase) = match event {
+        SwipeEvent::Begin {
+            surface, fingers, ..
+        } => {
+            gesture_data.surface = Some(surface.clone());
+            gesture_data.fingers = fingers;
+            (surface, (0., 0.), GesturePhase::Started)
+        }
+        SwipeEvent::Update { dx, dy, .. } => {
+            let surface = match gesture_data.surface.as_ref() {
+                Some(surface) => surface.clone(),
+                None => return,
+            };
+            (surface, (dx, dy), GesturePhase::Updated)
+        }
+        SwipeEvent::End { cancelled, .. } => {
+            let surface = match gesture_data.surface.take() {
+                Some(surface) => surface,
+                None => return,
+            };
+            let phase = if cancelled != 0 {
+                GesturePhase::Cancelled
+            } else {
+                GesturePhase::Ended
+            };
+            (surface, (0., 0.), phase)
+        }
+        _ => return,
+    };
+
+    let window_id = wayland::make_wid(&surface);
+    if !winit_state.window_map.contains_key(&window_id) {
+        return;
+    }
+
+    let scale_factor = sctk::get_surface_scale_factor(&surface) as f64;
+    let delta = LogicalPosition::new(delta.0, delta.1).to_physical(scale_factor);
+    winit_state.event_sink.push_window_event(
+        WindowEvent::SwipeGesture {
+            device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                wayland::make_did(seat),
+            )),
+            delta,
+            fingers: gesture_data.fingers,
+            phase,
+        },
+        window_id,
+    );
+}
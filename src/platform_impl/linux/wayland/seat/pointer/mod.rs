@@ -4,6 +4,7 @@ use std::cell::{Cell, RefCell};
 use std::rc::{Rc, Weak};
 
 use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_region::WlRegion;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
@@ -11,18 +12,24 @@ use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_rela
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1, Lifetime};
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1;
 
 use sctk::seat::pointer::{ThemeManager, ThemedPointer};
 use sctk::window::{ConceptFrame, Window};
 
+use crate::dpi::{LogicalPosition, PhysicalPosition};
 use crate::event::ModifiersState;
+use crate::platform_impl::wayland::cursor::WindowCursorTheme;
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::window::CursorIcon;
 
 mod data;
 mod handlers;
 
-use data::PointerData;
+use data::{GestureData, PointerData};
 
 /// A proxy to Wayland pointer, which serves requests from a `WindowHandle`.
 pub struct WinitPointer {
@@ -34,9 +41,15 @@ pub struct WinitPointer {
     /// Cursor to handle confine requests.
     confined_pointer: Weak<RefCell<Option<ZwpConfinedPointerV1>>>,
 
+    /// Cursor to handle lock requests.
+    locked_pointer: Weak<RefCell<Option<ZwpLockedPointerV1>>>,
+
     /// Latest observed serial in pointer events.
     latest_serial: Rc<Cell<u32>>,
 
+    /// Current modifiers state of the seat.
+    modifiers_state: Rc<RefCell<ModifiersState>>,
+
     /// Seat.
     seat: WlSeat,
 }
@@ -54,15 +67,19 @@ impl WinitPointer {
     ///
     /// Providing `None` will hide the cursor.
     pub fn set_cursor(&self, cursor_icon: Option<CursorIcon>) {
-        let cursor_icon = match cursor_icon {
-            Some(cursor_icon) => cursor_icon,
-            None => {
-                // Hide the cursor.
-                (*self.pointer).set_cursor(self.latest_serial.get(), None, 0, 0);
-                return;
-            }
-        };
+        match cursor_icon {
+            Some(cursor_icon) => self.set_themed_cursor(&self.pointer, cursor_icon),
+            // Hide the cursor.
+            None => (*self.pointer).set_cursor(self.latest_serial.get(), None, 0, 0),
+        }
+    }
+
+    /// Set the cursor icon from the cursor theme of a window, instead of the one of the seat.
+    pub fn set_cursor_from_theme(&self, theme: &mut WindowCursorTheme, cursor_icon: CursorIcon) {
+        self.set_themed_cursor(theme.themed_pointer(&self.pointer), cursor_icon);
+    }
 
+    fn set_themed_cursor(&self, pointer: &ThemedPointer, cursor_icon: CursorIcon) {
         let cursors: &[&str] = match cursor_icon {
             CursorIcon::Alias => &["link"],
             CursorIcon::Arrow => &["arrow"],
@@ -108,14 +125,24 @@ impl WinitPointer {
 
         let serial = Some(self.latest_serial.get());
         for cursor in cursors {
-            if self.pointer.set_cursor(cursor, serial).is_ok() {
+            if pointer.set_cursor(cursor, serial).is_ok() {
                 break;
             }
         }
     }
 
-    /// Confine the pointer to a surface.
-    pub fn confine(&self, surface: &WlSurface) {
+    /// Set the cursor to the surface showing a custom cursor.
+    pub fn set_custom_cursor(&self, surface: &WlSurface, hotspot: PhysicalPosition<u32>) {
+        (*self.pointer).set_cursor(
+            self.latest_serial.get(),
+            Some(surface),
+            hotspot.x as i32,
+            hotspot.y as i32,
+        );
+    }
+
+    /// Confine the pointer to a surface, or to a region of it.
+    pub fn confine(&self, surface: &WlSurface, region: Option<&WlRegion>) {
         let pointer_constraints = match &self.pointer_constraints {
             Some(pointer_constraints) => pointer_constraints,
             None => return,
@@ -131,6 +158,7 @@ impl WinitPointer {
             &pointer_constraints,
             &surface,
             &*self.pointer,
+            region,
         ));
     }
 
@@ -149,9 +177,74 @@ impl WinitPointer {
         }
     }
 
+    /// Lock the pointer in place on a surface.
+    pub fn lock(&self, surface: &WlSurface) {
+        let pointer_constraints = match &self.pointer_constraints {
+            Some(pointer_constraints) => pointer_constraints,
+            None => return,
+        };
+
+        let locked_pointer = match self.locked_pointer.upgrade() {
+            Some(locked_pointer) => locked_pointer,
+            // A pointer is gone.
+            None => return,
+        };
+
+        *locked_pointer.borrow_mut() = Some(init_locked_pointer(
+            pointer_constraints,
+            surface,
+            &self.pointer,
+        ));
+    }
+
+    /// Hints the position of the pointer on the surface it's locked on, if it's locked.
+    pub fn set_cursor_position_hint(&self, position: LogicalPosition<f64>) {
+        let locked_pointer = match self.locked_pointer.upgrade() {
+            Some(locked_pointer) => locked_pointer,
+            // A pointer is gone.
+            None => return,
+        };
+
+        let locked_pointer = locked_pointer.borrow();
+
+        if let Some(locked_pointer) = locked_pointer.as_ref() {
+            locked_pointer.set_cursor_position_hint(position.x, position.y);
+        }
+    }
+
+    /// Tries to unlock the pointer if the current pointer is locked.
+    pub fn unlock(&self) {
+        let locked_pointer = match self.locked_pointer.upgrade() {
+            Some(locked_pointer) => locked_pointer,
+            // A pointer is gone.
+            None => return,
+        };
+
+        let mut locked_pointer = locked_pointer.borrow_mut();
+
+        if let Some(locked_pointer) = locked_pointer.take() {
+            locked_pointer.destroy();
+        }
+    }
+
     pub fn drag_window(&self, window: &Window<ConceptFrame>) {
         window.start_interactive_move(&self.seat, self.latest_serial.get());
     }
+
+    /// The seat of the pointer.
+    pub fn seat(&self) -> &WlSeat {
+        &self.seat
+    }
+
+    /// The latest observed serial in pointer events.
+    pub fn latest_serial(&self) -> u32 {
+        self.latest_serial.get()
+    }
+
+    /// The current modifiers state of the seat.
+    pub fn modifiers(&self) -> ModifiersState {
+        *self.modifiers_state.borrow()
+    }
 }
 
 /// A pointer wrapper for easy releasing and managing pointers.
@@ -164,6 +257,15 @@ pub(super) struct Pointers {
 
     /// Confined pointer.
     confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+
+    /// Locked pointer.
+    locked_pointer: Rc<RefCell<Option<ZwpLockedPointerV1>>>,
+
+    /// Pinch gestures handler.
+    pinch_gesture: Option<ZwpPointerGesturePinchV1>,
+
+    /// Swipe gestures handler.
+    swipe_gesture: Option<ZwpPointerGestureSwipeV1>,
 }
 
 impl Pointers {
@@ -172,11 +274,14 @@ impl Pointers {
         theme_manager: &ThemeManager,
         relative_pointer_manager: &Option<Attached<ZwpRelativePointerManagerV1>>,
         pointer_constraints: &Option<Attached<ZwpPointerConstraintsV1>>,
+        pointer_gestures: &Option<Attached<ZwpPointerGesturesV1>>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
     ) -> Self {
         let confined_pointer = Rc::new(RefCell::new(None));
+        let locked_pointer = Rc::new(RefCell::new(None));
         let pointer_data = Rc::new(RefCell::new(PointerData::new(
             confined_pointer.clone(),
+            locked_pointer.clone(),
             pointer_constraints.clone(),
             modifiers_state,
         )));
@@ -196,17 +301,25 @@ impl Pointers {
         );
 
         // Setup relative_pointer if it's available.
-        let relative_pointer = match relative_pointer_manager.as_ref() {
-            Some(relative_pointer_manager) => {
-                Some(init_relative_pointer(&relative_pointer_manager, &*pointer))
-            }
-            None => None,
-        };
+        let relative_pointer = relative_pointer_manager
+            .as_ref()
+            .map(|manager| init_relative_pointer(manager, &pointer, seat.detach()));
+
+        // Setup gestures if they're available.
+        let pinch_gesture = pointer_gestures
+            .as_ref()
+            .map(|manager| init_pinch_gesture(manager, &pointer, seat.detach()));
+        let swipe_gesture = pointer_gestures
+            .as_ref()
+            .map(|manager| init_swipe_gesture(manager, &pointer, seat.detach()));
 
         Self {
             pointer,
             relative_pointer,
             confined_pointer,
+            locked_pointer,
+            pinch_gesture,
+            swipe_gesture,
         }
     }
 }
@@ -223,6 +336,19 @@ impl Drop for Pointers {
             confined_pointer.destroy();
         }
 
+        // Drop locked pointer.
+        if let Some(locked_pointer) = self.locked_pointer.borrow_mut().take() {
+            locked_pointer.destroy();
+        }
+
+        // Drop gestures.
+        if let Some(pinch_gesture) = self.pinch_gesture.take() {
+            pinch_gesture.destroy();
+        }
+        if let Some(swipe_gesture) = self.swipe_gesture.take() {
+            swipe_gesture.destroy();
+        }
+
         // Drop the pointer itself in case it's possible.
         if self.pointer.as_ref().version() >= 3 {
             self.pointer.release();
@@ -233,25 +359,74 @@ impl Drop for Pointers {
 pub(super) fn init_relative_pointer(
     relative_pointer_manager: &ZwpRelativePointerManagerV1,
     pointer: &WlPointer,
+    seat: WlSeat,
 ) -> ZwpRelativePointerV1 {
     let relative_pointer = relative_pointer_manager.get_relative_pointer(&*pointer);
     relative_pointer.quick_assign(move |_, event, mut dispatch_data| {
         let winit_state = dispatch_data.get::<WinitState>().unwrap();
-        handlers::handle_relative_pointer(event, winit_state);
+        handlers::handle_relative_pointer(event, winit_state, &seat);
     });
 
     relative_pointer.detach()
 }
 
+pub(super) fn init_pinch_gesture(
+    pointer_gestures: &ZwpPointerGesturesV1,
+    pointer: &WlPointer,
+    seat: WlSeat,
+) -> ZwpPointerGesturePinchV1 {
+    let pinch_gesture = pointer_gestures.get_pinch_gesture(pointer);
+    let mut gesture_data = GestureData::default();
+    pinch_gesture.quick_assign(move |_, event, mut dispatch_data| {
+        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+        handlers::handle_pinch_gesture(event, &mut gesture_data, winit_state, &seat);
+    });
+
+    pinch_gesture.detach()
+}
+
+pub(super) fn init_swipe_gesture(
+    pointer_gestures: &ZwpPointerGesturesV1,
+    pointer: &WlPointer,
+    seat: WlSeat,
+) -> ZwpPointerGestureSwipeV1 {
+    let swipe_gesture = pointer_gestures.get_swipe_gesture(pointer);
+    let mut gesture_data = GestureData::default();
+    swipe_gesture.quick_assign(move |_, event, mut dispatch_data| {
+        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+        handlers::handle_swipe_gesture(event, &mut gesture_data, winit_state, &seat);
+    });
+
+    swipe_gesture.detach()
+}
+
 pub(super) fn init_confined_pointer(
     pointer_constraints: &Attached<ZwpPointerConstraintsV1>,
     surface: &WlSurface,
     pointer: &WlPointer,
+    region: Option<&WlRegion>,
 ) -> ZwpConfinedPointerV1 {
-    let confined_pointer =
-        pointer_constraints.confine_pointer(surface, pointer, None, Lifetime::Persistent.to_raw());
+    let confined_pointer = pointer_constraints.confine_pointer(
+        surface,
+        pointer,
+        region,
+        Lifetime::Persistent.to_raw(),
+    );
 
     confined_pointer.quick_assign(move |_, _, _| {});
 
     confined_pointer.detach()
 }
+
+pub(super) fn init_locked_pointer(
+    pointer_constraints: &Attached<ZwpPointerConstraintsV1>,
+    surface: &WlSurface,
+    pointer: &WlPointer,
+) -> ZwpLockedPointerV1 {
+    let locked_pointer =
+        pointer_constraints.lock_pointer(surface, pointer, None, Lifetime::Persistent.to_raw());
+
+    locked_pointer.quick_assign(move |_, _, _| {});
+
+    locked_pointer.detach()
+}
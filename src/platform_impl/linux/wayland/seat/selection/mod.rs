@@ -0,0 +1,103 @@
+//! Observing the selections of a seat.
+
+use sctk::reexports::client::protocol::wl_data_device::{Event as DataDeviceEvent, WlDataDevice};
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use sctk::reexports::client::protocol::wl_data_offer::WlDataOffer;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::Attached;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_v1::{
+    Event as PrimarySelectionDeviceEvent, ZwpPrimarySelectionDeviceV1,
+};
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_offer_v1::ZwpPrimarySelectionOfferV1;
+
+use crate::clipboard::ClipboardKind;
+use crate::event::WindowEvent;
+use crate::platform_impl::wayland::dnd;
+use crate::platform_impl::wayland::event_loop::WinitState;
+
+/// Data devices notifying the windows about the selection changes on a seat.
+///
+/// The devices of `sctk` keep track of the selection offers, but don't tell when they change,
+/// so the devices here are used to observe the changes. The data device also receives the
+/// data dropped into the windows.
+pub struct SelectionObserver {
+    data_device: Attached<WlDataDevice>,
+    primary_selection_device: Option<Attached<ZwpPrimarySelectionDeviceV1>>,
+}
+
+impl SelectionObserver {
+    pub fn new(
+        seat: &Attached<WlSeat>,
+        data_device_manager: &WlDataDeviceManager,
+        primary_selection_manager: Option<&ZwpPrimarySelectionDeviceManagerV1>,
+    ) -> Self {
+        let data_device = data_device_manager.get_data_device(seat);
+        let mut selection: Option<WlDataOffer> = None;
+        data_device.quick_assign(move |_, event, mut dispatch_data| match event {
+            DataDeviceEvent::DataOffer { id } => dnd::init_offer(&id),
+            DataDeviceEvent::Selection { id } => {
+                if let Some(offer) = std::mem::replace(&mut selection, id) {
+                    offer.destroy();
+                }
+
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                selection_changed(winit_state, ClipboardKind::Clipboard);
+            }
+            event => {
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                dnd::handle_device_event(winit_state, event);
+            }
+        });
+
+        let primary_selection_device = primary_selection_manager.map(|manager| {
+            let device = manager.get_device(seat);
+            let mut selection: Option<ZwpPrimarySelectionOfferV1> = None;
+            device.quick_assign(move |_, event, mut dispatch_data| match event {
+                PrimarySelectionDeviceEvent::DataOffer { offer } => {
+                    offer.quick_assign(|_, _, _| {})
+                }
+                PrimarySelectionDeviceEvent::Selection { id } => {
+                    if let Some(offer) = std::mem::replace(&mut selection, id) {
+                        offer.destroy();
+                    }
+
+                    let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                    selection_changed(winit_state, ClipboardKind::Primary);
+                }
+                _ => (),
+            });
+            device.into()
+        });
+
+        Self {
+            data_device: data_device.into(),
+            primary_selection_device,
+        }
+    }
+}
+
+impl Drop for SelectionObserver {
+    fn drop(&mut self) {
+        if self.data_device.as_ref().version() >= 2 {
+            self.data_device.release();
+        }
+
+        if let Some(primary_selection_device) = self.primary_selection_device.as_ref() {
+            primary_selection_device.destroy();
+        }
+    }
+}
+
+/// Notify all the windows about the selection change, unless the application changed it itself.
+fn selection_changed(winit_state: &mut WinitState, kind: ClipboardKind) {
+    if winit_state.clipboard.owns_selection(kind) {
+        return;
+    }
+
+    for window_id in winit_state.window_map.keys() {
+        winit_state
+            .event_sink
+            .push_window_event(WindowEvent::ClipboardChanged { kind }, *window_id);
+    }
+}
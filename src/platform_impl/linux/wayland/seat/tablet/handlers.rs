@@ -0,0 +1,130 @@
+//! Handlers for the tablet tool events.
+
+use sctk::reexports::client::Main;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_tool_v2::{
+    ButtonState, Event as ToolEvent, Type, ZwpTabletToolV2,
+};
+
+use crate::dpi::LogicalPosition;
+use crate::event::{PenEvent, PenPhase, PenTool, WindowEvent};
+
+use crate::platform_impl::platform::instant_from_event_time;
+use crate::platform_impl::wayland;
+use crate::platform_impl::wayland::event_loop::WinitState;
+
+use super::TabletToolInner;
+
+/// The button on the barrel of the pens, as `BTN_STYLUS` of `linux/input-event-codes.h`.
+const BTN_STYLUS: u32 = 0x14b;
+
+/// Handle ZwpTabletToolV2 events.
+#[inline]
+pub(super) fn handle_tool(
+    tool: &Main<ZwpTabletToolV2>,
+    event: ToolEvent,
+    inner: &mut TabletToolInner,
+    winit_state: &mut WinitState,
+) {
+    match event {
+        ToolEvent::Type { tool_type } => {
+            inner.tool = match tool_type {
+                Type::Eraser => PenTool::Eraser,
+                Type::Brush => PenTool::Brush,
+                Type::Pencil => PenTool::Pencil,
+                Type::Airbrush => PenTool::Airbrush,
+                Type::Finger => PenTool::Finger,
+                Type::Mouse => PenTool::Mouse,
+                Type::Lens => PenTool::Lens,
+                _ => PenTool::Pen,
+            };
+        }
+        ToolEvent::HardwareSerial {
+            hardware_serial_hi,
+            hardware_serial_lo,
+        } => {
+            let serial = (hardware_serial_hi as u64) << 32 | hardware_serial_lo as u64;
+            inner.tool_id = serial;
+            inner.serial = Some(serial);
+        }
+        ToolEvent::Removed => {
+            tool.destroy();
+        }
+        ToolEvent::ProximityIn { surface, .. } => {
+            let window_id = wayland::make_wid(&surface);
+            if !winit_state.window_map.contains_key(&window_id) {
+                return;
+            }
+            inner.surface = Some(surface);
+            inner.phases.push(PenPhase::Entered);
+        }
+        ToolEvent::ProximityOut => inner.phases.push(PenPhase::Left),
+        ToolEvent::Down { .. } => inner.phases.push(PenPhase::Down),
+        ToolEvent::Up => inner.phases.push(PenPhase::Up),
+        ToolEvent::Motion { x, y } => {
+            inner.position = LogicalPosition::new(x, y);
+            inner.moved = true;
+        }
+        ToolEvent::Pressure { pressure } => {
+            inner.pressure = pressure as f64 / 65535.;
+            inner.moved = true;
+        }
+        ToolEvent::Tilt { tilt_x, tilt_y } => {
+            inner.tilt = Some((tilt_x, tilt_y));
+            inner.moved = true;
+        }
+        ToolEvent::Rotation { degrees } => {
+            inner.twist = Some(degrees);
+            inner.moved = true;
+        }
+        ToolEvent::Button { button, state, .. } if button == BTN_STYLUS => {
+            inner.barrel_button = state == ButtonState::Pressed;
+            inner.moved = true;
+        }
+        ToolEvent::Frame { time } => {
+            let surface = match inner.surface.as_ref() {
+                Some(surface) => surface,
+                None => {
+                    inner.phases.clear();
+                    inner.moved = false;
+                    return;
+                }
+            };
+            let window_id = wayland::make_wid(surface);
+            let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+
+            let mut phases = std::mem::take(&mut inner.phases);
+            if phases.is_empty() && inner.moved {
+                phases.push(PenPhase::Moved);
+            }
+            inner.moved = false;
+
+            let time = instant_from_event_time(time);
+            for &phase in phases.iter() {
+                let event = PenEvent {
+                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                        wayland::make_did(&inner.seat),
+                    )),
+                    phase,
+                    location: inner.position.to_physical(scale_factor),
+                    time,
+                    pressure: inner.pressure,
+                    tilt: inner.tilt,
+                    twist: inner.twist,
+                    eraser: inner.tool == PenTool::Eraser,
+                    barrel_button: inner.barrel_button,
+                    tool_id: inner.tool_id,
+                    tool: inner.tool,
+                    serial: inner.serial,
+                };
+                winit_state
+                    .event_sink
+                    .push_window_event(WindowEvent::Pen(event), window_id);
+            }
+
+            if phases.last() == Some(&PenPhase::Left) {
+                inner.surface = None;
+            }
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,133 @@
+//! Graphics tablets handling.
+
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{Attached, Main};
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_pad_group_v2::{
+    Event as PadGroupEvent, ZwpTabletPadGroupV2,
+};
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_pad_v2::Event as PadEvent;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_seat_v2::{
+    Event as TabletSeatEvent, ZwpTabletSeatV2,
+};
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_tool_v2::ZwpTabletToolV2;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_v2::Event as TabletEvent;
+
+use crate::dpi::LogicalPosition;
+use crate::event::{PenPhase, PenTool};
+
+use crate::platform_impl::wayland::event_loop::WinitState;
+
+mod handlers;
+
+/// The tablets of a seat, whose tools send the events of the pens.
+pub struct Tablet {
+    tablet_seat: ZwpTabletSeatV2,
+}
+
+impl Tablet {
+    pub fn new(seat: &Attached<WlSeat>, tablet_manager: &ZwpTabletManagerV2) -> Self {
+        let tablet_seat = tablet_manager.get_tablet_seat(seat);
+        let seat = seat.detach();
+
+        tablet_seat.quick_assign(move |_, event, _| match event {
+            TabletSeatEvent::TabletAdded { id } => {
+                id.quick_assign(|tablet, event, _| {
+                    if let TabletEvent::Removed = event {
+                        tablet.destroy();
+                    }
+                });
+            }
+            TabletSeatEvent::ToolAdded { id } => init_tool(id, seat.clone()),
+            // The pads don't send the events of the pens, but their objects have to be handled.
+            TabletSeatEvent::PadAdded { id } => {
+                id.quick_assign(|pad, event, _| match event {
+                    PadEvent::Group { pad_group } => init_pad_group(pad_group),
+                    PadEvent::Removed => pad.destroy(),
+                    _ => (),
+                });
+            }
+            _ => (),
+        });
+
+        Self {
+            tablet_seat: tablet_seat.detach(),
+        }
+    }
+}
+
+impl Drop for Tablet {
+    fn drop(&mut self) {
+        self.tablet_seat.destroy();
+    }
+}
+
+fn init_tool(tool: Main<ZwpTabletToolV2>, seat: WlSeat) {
+    let mut inner = TabletToolInner::new(seat, tool.as_ref().id() as u64);
+    tool.quick_assign(move |tool, event, mut dispatch_data| {
+        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+        handlers::handle_tool(&tool, event, &mut inner, winit_state);
+    });
+}
+
+fn init_pad_group(pad_group: Main<ZwpTabletPadGroupV2>) {
+    pad_group.quick_assign(|_, event, _| match event {
+        PadGroupEvent::Ring { ring } => ring.quick_assign(|_, _, _| {}),
+        PadGroupEvent::Strip { strip } => strip.quick_assign(|_, _, _| {}),
+        _ => (),
+    });
+}
+
+/// The state of a tool, which its events update until the end of their frame.
+pub(super) struct TabletToolInner {
+    /// The seat this tool belongs to.
+    seat: WlSeat,
+
+    /// The hardware serial of the tool, or the id of its object when it has none.
+    tool_id: u64,
+
+    /// The kind of the tool.
+    tool: PenTool,
+
+    /// The hardware serial of the tool, when it has one.
+    serial: Option<u64>,
+
+    /// The surface the tool is over.
+    surface: Option<WlSurface>,
+
+    /// The phases of the current frame, other than the moves.
+    phases: Vec<PenPhase>,
+
+    /// Whether the tool moved or changed in the current frame.
+    moved: bool,
+
+    position: LogicalPosition<f64>,
+
+    pressure: f64,
+
+    tilt: Option<(f64, f64)>,
+
+    twist: Option<f64>,
+
+    barrel_button: bool,
+}
+
+impl TabletToolInner {
+    fn new(seat: WlSeat, tool_id: u64) -> Self {
+        Self {
+            seat,
+            tool_id,
+            tool: PenTool::Pen,
+            serial: None,
+            surface: None,
+            phases: Vec::new(),
+            moved: false,
+            position: LogicalPosition::new(0., 0.),
+            pressure: 0.,
+            tilt: None,
+            twist: None,
+            barrel_button: false,
+        }
+    }
+}
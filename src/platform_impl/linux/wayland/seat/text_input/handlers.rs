@@ -5,9 +5,9 @@ use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input
     Event as TextInputEvent, ZwpTextInputV3,
 };
 
-use crate::event::WindowEvent;
-use crate::platform_impl::wayland;
+use crate::event::{ImePreedit, ImePreeditSpan, ImeUnderline, WindowEvent};
 use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::{self, WindowId};
 
 use super::{TextInputHandler, TextInputInner};
 
@@ -21,6 +21,11 @@ pub(super) fn handle_text_input(
     let event_sink = &mut winit_state.event_sink;
     match event {
         TextInputEvent::Enter { surface } => {
+            // The previous surface is left first if the compositor didn't tell.
+            if let Some(window_id) = inner.target_window_id.take() {
+                leave_window(&text_input, inner, window_id, winit_state);
+            }
+
             let window_id = wayland::make_wid(&surface);
 
             let window_handle = match winit_state.window_map.get_mut(&window_id) {
@@ -29,11 +34,8 @@ pub(super) fn handle_text_input(
             };
             inner.target_window_id = Some(window_id);
 
-            // Enable text input on that surface.
-            text_input.enable();
-            text_input.commit();
-
-            // Notify a window we're currently over about text input handler.
+            // Notify a window we're currently over about text input handler, which enables
+            // the text input on that surface with the state of the window.
             let text_input_handler = TextInputHandler {
                 text_input: text_input.detach(),
             };
@@ -42,37 +44,121 @@ pub(super) fn handle_text_input(
         TextInputEvent::Leave { surface } => {
             // Always issue a disable.
             text_input.disable();
-            text_input.commit();
-
-            let window_id = wayland::make_wid(&surface);
-
-            let window_handle = match winit_state.window_map.get_mut(&window_id) {
-                Some(window_handle) => window_handle,
-                None => return,
-            };
+            super::commit(&text_input);
 
             inner.target_window_id = None;
-
-            // Remove text input handler from the window we're leaving.
-            let text_input_handler = TextInputHandler {
-                text_input: text_input.detach(),
-            };
-            window_handle.text_input_left(text_input_handler);
+            leave_window(&text_input, inner, wayland::make_wid(&surface), winit_state);
         }
         TextInputEvent::CommitString { text } => {
             // Update currenly commited string.
             inner.commit_string = text;
         }
-        TextInputEvent::Done { .. } => {
-            let (window_id, text) = match (inner.target_window_id, inner.commit_string.take()) {
-                (Some(window_id), Some(text)) => (window_id, text),
-                _ => return,
+        TextInputEvent::DeleteSurroundingText {
+            before_length,
+            after_length,
+        } => {
+            inner.delete_surrounding = Some((before_length as usize, after_length as usize));
+        }
+        TextInputEvent::PreeditString {
+            text,
+            cursor_begin,
+            cursor_end,
+        } => {
+            inner.preedit = text.map(|text| preedit(text, cursor_begin, cursor_end));
+        }
+        TextInputEvent::Done { serial } => {
+            let commit_string = inner.commit_string.take();
+            // The lengths are relative to the surrounding text the compositor knew of, which
+            // isn't the one of the window anymore when it missed some of the commits.
+            let delete_surrounding = inner
+                .delete_surrounding
+                .take()
+                .filter(|_| super::is_current_serial(&text_input, serial));
+            let preedit = inner
+                .preedit
+                .take()
+                .filter(|preedit| !preedit.text.is_empty());
+            let window_id = match inner.target_window_id {
+                Some(window_id) => window_id,
+                None => return,
             };
 
-            for ch in text.chars() {
-                event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
+            // The shown preedit is removed before the surrounding text is deleted and the text is
+            // committed, and replaced after.
+            if inner.preedit_shown && (commit_string.is_some() || preedit.is_none()) {
+                inner.preedit_shown = false;
+                event_sink
+                    .push_window_event(WindowEvent::ImePreedit(ImePreedit::default()), window_id);
+            }
+
+            if let Some((before_length, after_length)) = delete_surrounding {
+                event_sink.push_window_event(
+                    WindowEvent::ImeDeleteSurrounding {
+                        before_length,
+                        after_length,
+                    },
+                    window_id,
+                );
+            }
+
+            if let Some(text) = commit_string {
+                for ch in text.chars() {
+                    event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
+                }
+            }
+
+            if let Some(preedit) = preedit {
+                inner.preedit_shown = true;
+                event_sink.push_window_event(WindowEvent::ImePreedit(preedit), window_id);
             }
         }
         _ => (),
     }
 }
+
+/// Forgets the pending input of the window the text input left, clearing its preedit.
+fn leave_window(
+    text_input: &ZwpTextInputV3,
+    inner: &mut TextInputInner,
+    window_id: WindowId,
+    winit_state: &mut WinitState,
+) {
+    inner.commit_string = None;
+    inner.delete_surrounding = None;
+    inner.preedit = None;
+    if inner.preedit_shown {
+        inner.preedit_shown = false;
+        winit_state
+            .event_sink
+            .push_window_event(WindowEvent::ImePreedit(ImePreedit::default()), window_id);
+    }
+
+    // Remove text input handler from the window we're leaving.
+    if let Some(window_handle) = winit_state.window_map.get_mut(&window_id) {
+        let text_input_handler = TextInputHandler {
+            text_input: text_input.clone(),
+        };
+        window_handle.text_input_left(text_input_handler);
+    }
+}
+
+/// The preedit of `preedit_string`, whose cursor is hidden when both its ends are -1.
+fn preedit(text: String, cursor_begin: i32, cursor_end: i32) -> ImePreedit {
+    let len = text.len();
+    let cursor = if cursor_begin == -1 && cursor_end == -1 {
+        None
+    } else {
+        let begin = (cursor_begin.max(0) as usize).min(len);
+        let end = (cursor_end.max(0) as usize).min(len);
+        Some(begin.min(end)..begin.max(end))
+    };
+    ImePreedit {
+        spans: vec![ImePreeditSpan {
+            range: 0..len,
+            underline: ImeUnderline::Single,
+            highlighted: false,
+        }],
+        text,
+        cursor,
+    }
+}
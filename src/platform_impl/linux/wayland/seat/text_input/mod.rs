@@ -1,10 +1,16 @@
+use std::cell::Cell;
+
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::Attached;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
-use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_v3::ZwpTextInputV3;
+use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_v3::{
+    ContentHint, ContentPurpose, ZwpTextInputV3,
+};
 
+use crate::event::ImePreedit;
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::platform_impl::wayland::WindowId;
+use crate::window::ImePurpose;
 
 mod handlers;
 
@@ -15,11 +21,131 @@ pub struct TextInputHandler {
 }
 
 impl TextInputHandler {
+    /// Enables the text input on the surface it entered, along with the state of the window,
+    /// which the compositor resets on every enable.
+    #[inline]
+    pub fn enable(&self, state: &ImeState) {
+        self.text_input.enable();
+        self.set_state(state);
+    }
+
+    /// Sends the state of the window, and commits it.
     #[inline]
-    pub fn set_ime_position(&self, x: i32, y: i32) {
-        self.text_input.set_cursor_rectangle(x, y, 0, 0);
-        self.text_input.commit();
+    pub fn set_state(&self, state: &ImeState) {
+        if let Some((x, y, width, height)) = state.cursor_area {
+            self.text_input.set_cursor_rectangle(x, y, width, height);
+        }
+        if let Some((ref text, cursor, anchor)) = state.surrounding_text {
+            let (text, cursor, anchor) = trim_surrounding_text(text, cursor, anchor);
+            self.text_input
+                .set_surrounding_text(text.to_owned(), cursor as i32, anchor as i32);
+        }
+        if let Some(purpose) = state.purpose {
+            let (hint, purpose) = content_type(purpose);
+            self.text_input.set_content_type(hint, purpose);
+        }
+        commit(&self.text_input);
+    }
+}
+
+/// The state of the text input of a window, kept to be sent again on every enable.
+#[derive(Default)]
+pub struct ImeState {
+    /// The cursor rectangle, as its position and size.
+    pub cursor_area: Option<(i32, i32, i32, i32)>,
+
+    /// The surrounding text, with the offsets of the cursor and the anchor.
+    pub surrounding_text: Option<(String, usize, usize)>,
+
+    /// The purpose of the text being edited.
+    pub purpose: Option<ImePurpose>,
+}
+
+/// The data of a text input, shared by all of its proxies.
+#[derive(Default)]
+struct TextInputData {
+    /// The number of commit requests, which the serial of `done` is compared with.
+    commits: Cell<u32>,
+}
+
+/// Commits the pending state of the text input, counting the commit.
+fn commit(text_input: &ZwpTextInputV3) {
+    if let Some(data) = text_input.as_ref().user_data().get::<TextInputData>() {
+        data.commits.set(data.commits.get().wrapping_add(1));
     }
+    text_input.commit();
+}
+
+/// Whether the compositor knew of the latest state of the text input when it sent `done` with
+/// the serial.
+fn is_current_serial(text_input: &ZwpTextInputV3, serial: u32) -> bool {
+    match text_input.as_ref().user_data().get::<TextInputData>() {
+        Some(data) => data.commits.get() == serial,
+        None => true,
+    }
+}
+
+fn content_type(purpose: ImePurpose) -> (ContentHint, ContentPurpose) {
+    match purpose {
+        ImePurpose::Normal => (ContentHint::None, ContentPurpose::Normal),
+        ImePurpose::Password => (
+            ContentHint::HiddenText | ContentHint::SensitiveData,
+            ContentPurpose::Password,
+        ),
+        ImePurpose::Pin => (
+            ContentHint::HiddenText | ContentHint::SensitiveData,
+            ContentPurpose::Pin,
+        ),
+        ImePurpose::Digits => (ContentHint::None, ContentPurpose::Digits),
+        ImePurpose::Number => (ContentHint::None, ContentPurpose::Number),
+        ImePurpose::Phone => (ContentHint::None, ContentPurpose::Phone),
+        ImePurpose::Email => (ContentHint::None, ContentPurpose::Email),
+        ImePurpose::Url => (ContentHint::None, ContentPurpose::Url),
+        ImePurpose::Name => (ContentHint::AutoCapitalization, ContentPurpose::Name),
+        ImePurpose::Terminal => (ContentHint::None, ContentPurpose::Terminal),
+    }
+}
+
+/// The most bytes of surrounding text `text-input-v3` allows in a request.
+const MAX_SURROUNDING_TEXT: usize = 4000;
+
+/// Trims the text to the bytes around the cursor which fit in a request, keeping the anchor
+/// when it still fits.
+fn trim_surrounding_text(text: &str, cursor: usize, anchor: usize) -> (&str, usize, usize) {
+    let floor = |mut index: usize| {
+        index = index.min(text.len());
+        while !text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    };
+    let cursor = floor(cursor);
+    let anchor = floor(anchor);
+    if text.len() <= MAX_SURROUNDING_TEXT {
+        return (text, cursor, anchor);
+    }
+
+    let (low, high) = (cursor.min(anchor), cursor.max(anchor));
+    let (low, high) = if high - low <= MAX_SURROUNDING_TEXT {
+        (low, high)
+    } else {
+        (cursor, cursor)
+    };
+    // The selection is centered in the trimmed text, which doesn't go past the ends of the text.
+    let margin = MAX_SURROUNDING_TEXT - (high - low);
+    let start = low
+        .saturating_sub(margin / 2)
+        .min(text.len() - MAX_SURROUNDING_TEXT);
+    let (start, end) = match (floor(start), floor(start + MAX_SURROUNDING_TEXT)) {
+        (_, end) if end < high => (low, high),
+        (start, end) => (start, end),
+    };
+    let anchor = if anchor >= start && anchor <= end {
+        anchor
+    } else {
+        cursor
+    };
+    (&text[start..end], cursor - start, anchor - start)
 }
 
 /// A wrapper around text input to automatically destroy the object on `Drop`.
@@ -30,6 +156,7 @@ pub struct TextInput {
 impl TextInput {
     pub fn new(seat: &Attached<WlSeat>, text_input_manager: &ZwpTextInputManagerV3) -> Self {
         let text_input = text_input_manager.get_text_input(seat);
+        text_input.as_ref().user_data().set(TextInputData::default);
         let mut text_input_inner = TextInputInner::new();
         text_input.quick_assign(move |text_input, event, mut dispatch_data| {
             let winit_state = dispatch_data.get::<WinitState>().unwrap();
@@ -54,6 +181,15 @@ struct TextInputInner {
 
     /// Pending string to commit.
     commit_string: Option<String>,
+
+    /// Pending lengths of the surrounding text to delete before and after the cursor.
+    delete_surrounding: Option<(usize, usize)>,
+
+    /// Pending preedit, which replaces the shown one on `done`.
+    preedit: Option<ImePreedit>,
+
+    /// Whether the focused window shows a preedit, to clear it.
+    preedit_shown: bool,
 }
 
 impl TextInputInner {
@@ -61,6 +197,9 @@ impl TextInputInner {
         Self {
             target_window_id: None,
             commit_string: None,
+            delete_surrounding: None,
+            preedit: None,
+            preedit_shown: false,
         }
     }
 }
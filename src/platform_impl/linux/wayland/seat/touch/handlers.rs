@@ -1,12 +1,15 @@
 //! Various handlers for touch events.
 
+use std::time::Instant;
+
 use sctk::reexports::client::protocol::wl_touch::Event as TouchEvent;
 
 use crate::dpi::LogicalPosition;
 use crate::event::{TouchPhase, WindowEvent};
 
+use crate::platform_impl::platform::instant_from_event_time;
+use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::platform_impl::wayland::{self, DeviceId};
 
 use super::{TouchInner, TouchPoint};
 
@@ -21,7 +24,12 @@ pub(super) fn handle_touch(
 
     match event {
         TouchEvent::Down {
-            surface, id, x, y, ..
+            time,
+            surface,
+            id,
+            x,
+            y,
+            ..
         } => {
             let window_id = wayland::make_wid(&surface);
             if !winit_state.window_map.contains_key(&window_id) {
@@ -34,12 +42,14 @@ pub(super) fn handle_touch(
             event_sink.push_window_event(
                 WindowEvent::Touch(crate::event::Touch {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
+                        wayland::make_did(&inner.seat),
                     )),
                     phase: TouchPhase::Started,
                     location: position.to_physical(scale_factor),
+                    time: instant_from_event_time(time),
                     force: None, // TODO
                     id: id as u64,
+                    palm: false,
                 }),
                 window_id,
             );
@@ -48,9 +58,10 @@ pub(super) fn handle_touch(
                 .touch_points
                 .push(TouchPoint::new(surface, position, id));
         }
-        TouchEvent::Up { id, .. } => {
-            let touch_point = match inner.touch_points.iter().find(|p| p.id == id) {
-                Some(touch_point) => touch_point,
+        TouchEvent::Up { time, id, .. } => {
+            // The touch is over, so that the cancel of the seat doesn't end it again.
+            let touch_point = match inner.touch_points.iter().position(|p| p.id == id) {
+                Some(index) => inner.touch_points.remove(index),
                 None => return,
             };
 
@@ -61,17 +72,19 @@ pub(super) fn handle_touch(
             event_sink.push_window_event(
                 WindowEvent::Touch(crate::event::Touch {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
+                        wayland::make_did(&inner.seat),
                     )),
                     phase: TouchPhase::Ended,
                     location,
+                    time: instant_from_event_time(time),
                     force: None, // TODO
                     id: id as u64,
+                    palm: false,
                 }),
                 window_id,
             );
         }
-        TouchEvent::Motion { id, x, y, .. } => {
+        TouchEvent::Motion { time, id, x, y } => {
             let touch_point = match inner.touch_points.iter_mut().find(|p| p.id == id) {
                 Some(touch_point) => touch_point,
                 None => return,
@@ -86,18 +99,21 @@ pub(super) fn handle_touch(
             event_sink.push_window_event(
                 WindowEvent::Touch(crate::event::Touch {
                     device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
+                        wayland::make_did(&inner.seat),
                     )),
                     phase: TouchPhase::Moved,
                     location,
+                    time: instant_from_event_time(time),
                     force: None, // TODO
                     id: id as u64,
+                    palm: false,
                 }),
                 window_id,
             );
         }
         TouchEvent::Frame => (),
         TouchEvent::Cancel => {
+            let time = Instant::now();
             for touch_point in inner.touch_points.drain(..) {
                 let scale_factor = sctk::get_surface_scale_factor(&touch_point.surface) as f64;
                 let location = touch_point.position.to_physical(scale_factor);
@@ -106,12 +122,14 @@ pub(super) fn handle_touch(
                 event_sink.push_window_event(
                     WindowEvent::Touch(crate::event::Touch {
                         device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                            DeviceId,
+                            wayland::make_did(&inner.seat),
                         )),
                         phase: TouchPhase::Cancelled,
                         location,
+                        time,
                         force: None, // TODO
                         id: touch_point.id as u64,
+                        palm: false,
                     }),
                     window_id,
                 );
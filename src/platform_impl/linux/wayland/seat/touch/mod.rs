@@ -20,7 +20,7 @@ pub struct Touch {
 impl Touch {
     pub fn new(seat: &Attached<WlSeat>) -> Self {
         let touch = seat.get_touch();
-        let mut inner = TouchInner::new();
+        let mut inner = TouchInner::new(seat.detach());
 
         touch.quick_assign(move |_, event, mut dispatch_data| {
             let winit_state = dispatch_data.get::<WinitState>().unwrap();
@@ -43,13 +43,17 @@ impl Drop for Touch {
 
 /// The data used by touch handlers.
 pub(super) struct TouchInner {
+    /// The seat this touch belongs to.
+    seat: WlSeat,
+
     /// Current touch points.
     touch_points: Vec<TouchPoint>,
 }
 
 impl TouchInner {
-    fn new() -> Self {
+    fn new(seat: WlSeat) -> Self {
         Self {
+            seat,
             touch_points: Vec::new(),
         }
     }
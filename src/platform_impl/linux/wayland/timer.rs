@@ -0,0 +1,89 @@
+//! The timers set by the user, backed by a calloop timer.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use sctk::reexports::calloop::timer::{Timeout, Timer as CalloopTimer, TimerHandle};
+use sctk::reexports::calloop::LoopHandle;
+
+use crate::error::ExternalError;
+use crate::event_loop::{Timer, TimerId};
+
+use super::event_loop::{EventLoopWindowTarget, WinitState};
+
+struct ScheduledTimer {
+    timeout: Timeout,
+    /// The interval of the repeating timers.
+    interval: Option<Duration>,
+}
+
+pub struct Timers {
+    handle: TimerHandle<TimerId>,
+    scheduled: Rc<RefCell<HashMap<TimerId, ScheduledTimer>>>,
+}
+
+impl Timers {
+    pub fn new(loop_handle: &LoopHandle<WinitState>) -> Result<Self, Box<dyn Error>> {
+        let timer = CalloopTimer::new()?;
+        let handle = timer.handle();
+        let scheduled = Rc::new(RefCell::new(HashMap::<TimerId, ScheduledTimer>::new()));
+
+        let scheduled_clone = scheduled.clone();
+        loop_handle.insert_source(timer, move |id, handle, winit_state| {
+            let mut scheduled = scheduled_clone.borrow_mut();
+            match scheduled.get_mut(&id) {
+                Some(timer) => match timer.interval {
+                    Some(interval) => timer.timeout = handle.add_timeout(interval, id),
+                    None => {
+                        scheduled.remove(&id);
+                    }
+                },
+                // The timer was cancelled.
+                None => return,
+            }
+            winit_state.fired_timers.push(id);
+        })?;
+
+        Ok(Self { handle, scheduled })
+    }
+
+    fn set(&self, id: TimerId, timer: Timer) {
+        let (delay, interval) = match timer {
+            Timer::At(deadline) => (deadline.saturating_duration_since(Instant::now()), None),
+            Timer::Every(interval) => (interval, Some(interval)),
+        };
+
+        let mut scheduled = self.scheduled.borrow_mut();
+        if let Some(timer) = scheduled.remove(&id) {
+            self.handle.cancel_timeout(&timer.timeout);
+        }
+        let timeout = self.handle.add_timeout(delay, id);
+        scheduled.insert(id, ScheduledTimer { timeout, interval });
+    }
+
+    fn cancel(&self, id: TimerId) -> bool {
+        match self.scheduled.borrow_mut().remove(&id) {
+            Some(timer) => {
+                self.handle.cancel_timeout(&timer.timeout);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    #[inline]
+    pub fn set_timer(&self, id: TimerId, timer: Timer) -> Result<(), ExternalError> {
+        self.timers.set(id, timer);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn cancel_timer(&self, id: TimerId) -> bool {
+        self.timers.cancel(id)
+    }
+}
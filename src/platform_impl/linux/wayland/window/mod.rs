@@ -1,7 +1,9 @@
 use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
 
+use sctk::reexports::client::protocol::wl_output::WlOutput;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Display;
 
@@ -13,24 +15,32 @@ use sctk::window::{
 
 use raw_window_handle::unix::WaylandHandle;
 
+use crate::clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker};
+use crate::dnd::{DndActions, DragData};
 use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
-use crate::monitor::MonitorHandle as RootMonitorHandle;
+use crate::event::WindowEvent;
+use crate::monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode};
 use crate::platform::unix::{ARGBColor as LocalARGBColor, Button, ButtonState, Element, Theme};
 use crate::platform_impl::{
     MonitorHandle as PlatformMonitorHandle, OsError,
-    PlatformSpecificWindowBuilderAttributes as PlatformAttributes,
+    PlatformSpecificWindowBuilderAttributes as PlatformAttributes, VideoMode as PlatformVideoMode,
+};
+use crate::window::{
+    Backdrop, CursorIcon, CursorTheme, EdgeFlags, Fullscreen, ImePurpose, Ratio, TitlebarStyle,
+    WindowAttributes,
 };
-use crate::window::{CursorIcon, Fullscreen, WindowAttributes};
 
+use super::cursor::{self, CustomCursor};
 use super::env::WindowingFeatures;
 use super::event_loop::WinitState;
-use super::output::{MonitorHandle, OutputManagerHandle};
+use super::output::{MonitorHandle, OutputManagerHandle, VideoMode};
+use super::protocols::kde_output_device::kde_output_device_v2::VrrPolicy;
 use super::{EventLoopWindowTarget, WindowId};
 
 pub mod shim;
 
-use shim::{WindowHandle, WindowRequest, WindowUpdate};
+use shim::{FrameCallbacks, WindowHandle, WindowRequest, WindowUpdate};
 
 pub struct Window {
     /// Window id.
@@ -54,11 +64,29 @@ pub struct Window {
     /// Fullscreen state.
     fullscreen: Arc<AtomicBool>,
 
+    /// The video mode emulated while the window is exclusive fullscreen.
+    video_mode: Arc<Mutex<Option<VideoMode>>>,
+
+    /// The ratio the sizes configured while the window is resized are shrunk to.
+    aspect_ratio: Arc<Mutex<Option<Ratio>>>,
+
     /// Available windowing features.
     windowing_features: WindowingFeatures,
 
     /// Requests that SCTK window should perform.
     window_requests: Arc<Mutex<Vec<WindowRequest>>>,
+
+    /// The cursor theme and size set for the window, empty and zero for the ones of the seats.
+    cursor_theme: Mutex<(String, u32)>,
+
+    /// Whether the pointer lock was requested, which the cursor position hints need.
+    pointer_locked: AtomicBool,
+
+    /// The frame callbacks, shared with the handle of the window.
+    frame_callbacks: FrameCallbacks,
+
+    /// The thread of the event loop, the only one the frame callbacks can be requested from.
+    event_loop_thread: ThreadId,
 }
 
 impl Window {
@@ -67,6 +95,14 @@ impl Window {
         attributes: WindowAttributes,
         platform_attributes: PlatformAttributes,
     ) -> Result<Self, RootOsError> {
+        // The owner would be set with `xdg_toplevel::set_parent`, but the toolkit keeps the
+        // toplevels of the windows too.
+        if attributes.owner.is_some() {
+            return Err(os_error!(OsError::WaylandMisc(
+                "owned windows are not supported on Wayland."
+            )));
+        }
+
         let surface = event_loop_window_target
             .env
             .create_surface_with_scale_callback(move |scale, surface, mut dispatch_data| {
@@ -80,7 +116,14 @@ impl Window {
                 window_update.scale_factor = Some(scale);
                 window_update.redraw_requested = true;
 
-                surface.set_buffer_scale(scale);
+                // The buffers of the video mode are in its pixels.
+                let has_video_mode = winit_state
+                    .window_map
+                    .get(&window_id)
+                    .map_or(false, |window_handle| window_handle.has_video_mode());
+                if !has_video_mode {
+                    surface.set_buffer_scale(scale);
+                }
             })
             .detach();
 
@@ -89,6 +132,9 @@ impl Window {
         let window_id = super::make_wid(&surface);
         let fullscreen = Arc::new(AtomicBool::new(false));
         let fullscreen_clone = fullscreen.clone();
+        let aspect_ratio = Arc::new(Mutex::new(None::<Ratio>));
+        let aspect_ratio_clone = aspect_ratio.clone();
+        let mut tiled_edges = EdgeFlags::empty();
 
         let (width, height) = attributes
             .inner_size
@@ -116,9 +162,47 @@ impl Window {
                             let is_fullscreen = states.contains(&State::Fullscreen);
                             fullscreen_clone.store(is_fullscreen, Ordering::Relaxed);
 
+                            if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                                let had_video_mode = window_handle.has_video_mode();
+                                if window_handle.configure_video_mode(is_fullscreen) {
+                                    winit_state.event_sink.push_window_event(
+                                        WindowEvent::VideoModeSwitched(true),
+                                        window_id,
+                                    );
+                                } else if had_video_mode && !window_handle.has_video_mode() {
+                                    // The buffers are resized even if the surface isn't.
+                                    window_update.size = Some(*window_handle.size.lock().unwrap());
+                                }
+                            }
+
+                            let edges = [
+                                (State::TiledTop, EdgeFlags::TOP),
+                                (State::TiledBottom, EdgeFlags::BOTTOM),
+                                (State::TiledLeft, EdgeFlags::LEFT),
+                                (State::TiledRight, EdgeFlags::RIGHT),
+                            ]
+                            .iter()
+                            .filter(|(state, _)| states.contains(state))
+                            .fold(EdgeFlags::empty(), |edges, &(_, edge)| edges | edge);
+                            if edges != tiled_edges {
+                                tiled_edges = edges;
+                                winit_state.event_sink.push_window_event(
+                                    WindowEvent::TiledStateChanged(edges),
+                                    window_id,
+                                );
+                            }
+
                             window_update.refresh_frame = true;
                             window_update.redraw_requested = true;
                             if let Some((w, h)) = new_size {
+                                // The sizes configured while resizing are the largest ones the
+                                // window can take.
+                                let (w, h) = match *aspect_ratio_clone.lock().unwrap() {
+                                    Some(ratio) if states.contains(&State::Resizing) => {
+                                        ratio.fit(w, h)
+                                    }
+                                    _ => (w, h),
+                                };
                                 window_update.size = Some(LogicalSize::new(w, h));
                             }
                         }
@@ -131,10 +215,20 @@ impl Window {
             .map_err(|_| os_error!(OsError::WaylandMisc("failed to create window.")))?;
 
         // Set decorations.
-        if attributes.decorations {
-            window.set_decorate(Decorations::FollowServer);
-        } else {
-            window.set_decorate(Decorations::None);
+        match attributes.titlebar_style {
+            _ if !attributes.decorations => window.set_decorate(Decorations::None),
+            // The content is drawn in place of the title bar, which the frame can't leave out.
+            TitlebarStyle::Hidden | TitlebarStyle::ExtendContent => {
+                window.set_decorate(Decorations::None)
+            }
+            TitlebarStyle::Transparent => {
+                window.set_frame_config(ConceptConfig {
+                    title_font: None,
+                    ..ConceptConfig::default()
+                });
+                window.set_decorate(Decorations::FollowServer);
+            }
+            TitlebarStyle::Visible => window.set_decorate(Decorations::FollowServer),
         }
 
         // Min dimensions.
@@ -161,20 +255,16 @@ impl Window {
         window.set_resizable(attributes.resizable);
         window.set_title(attributes.title);
 
-        // Set fullscreen/maximized if so was requested.
+        // Set fullscreen/maximized if so was requested, the exclusive fullscreen being set once
+        // the window has a handle.
+        let mut exclusive_video_mode = None;
         match attributes.fullscreen {
-            Some(Fullscreen::Exclusive(_)) => {
-                warn!("`Fullscreen::Exclusive` is ignored on Wayland")
-            }
+            Some(Fullscreen::Exclusive(video_mode)) => exclusive_video_mode = Some(video_mode),
             Some(Fullscreen::Borderless(monitor)) => {
-                let monitor =
-                    monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
-                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
-                    });
-
-                window.set_fullscreen(monitor.as_ref());
+                window.set_fullscreen(monitor_output(monitor).as_ref());
+            }
+            Some(Fullscreen::Span(monitors)) => {
+                window.set_fullscreen(monitor_output(monitors.into_iter().next()).as_ref());
             }
             None => {
                 if attributes.maximized {
@@ -192,9 +282,23 @@ impl Window {
 
         let window_id = super::make_wid(&surface);
         let window_requests = Arc::new(Mutex::new(Vec::with_capacity(64)));
+        let video_mode = Arc::new(Mutex::new(None));
+        let frame_callbacks = FrameCallbacks::default();
+        if let Some(request) = exclusive_video_mode.and_then(exclusive_fullscreen_request) {
+            window_requests.lock().unwrap().push(request);
+        }
 
         // Create a handle that performs all the requests on underlying sctk a window.
-        let window_handle = WindowHandle::new(window, size.clone(), window_requests.clone());
+        let window_handle = WindowHandle::new(
+            window,
+            size.clone(),
+            window_requests.clone(),
+            event_loop_window_target.env.clone(),
+            (event_loop_window_target.cursor_timer.clone(), window_id),
+            video_mode.clone(),
+            frame_callbacks.clone(),
+        );
+        window_handle.key_repeat.set(attributes.key_repeat);
 
         let mut winit_state = event_loop_window_target.state.borrow_mut();
 
@@ -235,7 +339,13 @@ impl Window {
             window_requests,
             event_loop_awakener: event_loop_window_target.event_loop_awakener.clone(),
             fullscreen,
+            video_mode,
+            aspect_ratio,
             windowing_features,
+            cursor_theme: Mutex::new((String::new(), 0)),
+            pointer_locked: AtomicBool::new(false),
+            frame_callbacks,
+            event_loop_thread: thread::current().id(),
         };
 
         Ok(window)
@@ -276,6 +386,11 @@ impl Window {
     }
 
     pub fn inner_size(&self) -> PhysicalSize<u32> {
+        // The buffers of the video mode are scaled to the surface.
+        if let Some(ref video_mode) = *self.video_mode.lock().unwrap() {
+            return video_mode.size;
+        }
+
         self.size
             .lock()
             .unwrap()
@@ -289,6 +404,45 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+        let vsync_redraws_request = WindowRequest::VsyncRedraws(vsync_redraws);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(vsync_redraws_request);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        // The frame callbacks are assigned where the events are dispatched, so the ones of the
+        // frames presented from the other threads are still requested before the redraws.
+        if thread::current().id() != self.event_loop_thread {
+            return;
+        }
+        self.frame_callbacks
+            .present_notified
+            .store(true, Ordering::Relaxed);
+        shim::request_frame_callback(&self.surface, self.window_id, &self.frame_callbacks.pending);
+    }
+
+    #[cfg(feature = "software-present")]
+    #[inline]
+    pub fn present_pixels(
+        &self,
+        pixels: &[u32],
+        size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        let present_pixels_request = WindowRequest::PresentPixels(pixels.to_vec(), size);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(present_pixels_request);
+        self.event_loop_awakener.ping();
+        Ok(())
+    }
+
     #[inline]
     pub fn outer_size(&self) -> PhysicalSize<u32> {
         self.size
@@ -332,6 +486,11 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_aspect_ratio(&self, ratio: Option<Ratio>) {
+        *self.aspect_ratio.lock().unwrap() = ratio;
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
         let resizeable_request = WindowRequest::Resizeable(resizable);
@@ -377,6 +536,12 @@ impl Window {
 
     #[inline]
     pub fn fullscreen(&self) -> Option<Fullscreen> {
+        if let Some(ref video_mode) = *self.video_mode.lock().unwrap() {
+            return Some(Fullscreen::Exclusive(RootVideoMode {
+                video_mode: PlatformVideoMode::Wayland(video_mode.clone()),
+            }));
+        }
+
         if self.fullscreen.load(Ordering::Relaxed) {
             let current_monitor = self.current_monitor().map(|monitor| RootMonitorHandle {
                 inner: PlatformMonitorHandle::Wayland(monitor),
@@ -391,19 +556,18 @@ impl Window {
     #[inline]
     pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
         let fullscreen_request = match fullscreen {
-            Some(Fullscreen::Exclusive(_)) => {
-                warn!("`Fullscreen::Exclusive` is ignored on Wayland");
-                return;
+            Some(Fullscreen::Exclusive(video_mode)) => {
+                match exclusive_fullscreen_request(video_mode) {
+                    Some(request) => request,
+                    None => return,
+                }
             }
             Some(Fullscreen::Borderless(monitor)) => {
-                let monitor =
-                    monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
-                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
-                    });
-
-                WindowRequest::Fullscreen(monitor)
+                WindowRequest::Fullscreen(monitor_output(monitor))
+            }
+            // The compositors only fullscreen the windows on a single output.
+            Some(Fullscreen::Span(monitors)) => {
+                WindowRequest::Fullscreen(monitor_output(monitors.into_iter().next()))
             }
             None => WindowRequest::UnsetFullscreen,
         };
@@ -548,6 +712,49 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_cursor(&self, cursor: CustomCursor) {
+        let custom_cursor_request = WindowRequest::CustomCursor(cursor);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(custom_cursor_request);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) -> Result<(), ExternalError> {
+        *self.cursor_theme.lock().unwrap() = (name.to_owned(), size);
+        let theme = if name.is_empty() && size == 0 {
+            None
+        } else {
+            let (system_name, system_size) = cursor::system_cursor_theme();
+            let name = if name.is_empty() {
+                system_name
+            } else {
+                name.to_owned()
+            };
+            Some((name, if size == 0 { system_size } else { size }))
+        };
+        let cursor_theme_request = WindowRequest::CursorTheme(theme);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(cursor_theme_request);
+        self.event_loop_awakener.ping();
+        Ok(())
+    }
+
+    #[inline]
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        let (name, size) = self.cursor_theme.lock().unwrap().clone();
+        let (system_name, system_size) = cursor::system_cursor_theme();
+        Some(CursorTheme {
+            name: Some(if name.is_empty() { system_name } else { name }),
+            size: if size == 0 { system_size } else { size },
+        })
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         let cursor_visible_request = WindowRequest::ShowCursor(visible);
@@ -575,17 +782,97 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_cursor_position(&self, _: Position) -> Result<(), ExternalError> {
-        // XXX This is possible if the locked pointer is being used. We don't have any
-        // API for that right now, but it could be added in
-        // https://github.com/rust-windowing/winit/issues/1677.
-        //
-        // This function is essential for the locked pointer API.
-        //
-        // See pointer-constraints-unstable-v1.xml.
+    pub fn set_cursor_grab_area(
+        &self,
+        area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        if !self.windowing_features.cursor_grab() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let scale_factor = self.scale_factor() as f64;
+        let area = area.map(|(position, size)| {
+            (
+                position.to_logical(scale_factor),
+                size.to_logical(scale_factor),
+            )
+        });
+        let cursor_grab_area_request = WindowRequest::CursorGrabArea(area);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(cursor_grab_area_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_pointer_lock(&self, lock: bool) -> Result<(), ExternalError> {
+        if !self.windowing_features.pointer_lock() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        self.pointer_locked.store(lock, Ordering::Relaxed);
+        let pointer_lock_request = WindowRequest::LockPointer(lock);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(pointer_lock_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        // The compositors have no protocol for the opacity of the whole surface.
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_backdrop(&self, backdrop: Backdrop) -> Result<(), ExternalError> {
+        if !self.windowing_features.blur() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let blur_request = WindowRequest::Blur(backdrop != Backdrop::None);
+        self.window_requests.lock().unwrap().push(blur_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        // Only the implicit grabs of the presses of the buttons exist.
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
+    #[inline]
+    pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+        // The clients can't move the cursor, only hint where the locked pointers are, for the
+        // compositor to put the cursor there once they're unlocked.
+        if !self.windowing_features.pointer_lock() || !self.pointer_locked.load(Ordering::Relaxed) {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let position = position.to_logical(self.scale_factor() as f64);
+        let cursor_position_request = WindowRequest::CursorPositionHint(position);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(cursor_position_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
     #[inline]
     pub fn drag_window(&self) -> Result<(), ExternalError> {
         let drag_window_request = WindowRequest::DragWindow;
@@ -599,15 +886,104 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_ime_position(&self, position: Position) {
+    pub fn start_drag(&self, data: DragData, actions: DndActions) -> Result<(), ExternalError> {
+        let start_drag_request = WindowRequest::StartDrag(data, actions);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(start_drag_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
         let scale_factor = self.scale_factor() as f64;
         let position = position.to_logical(scale_factor);
-        let ime_position_request = WindowRequest::IMEPosition(position);
+        let size = size.to_logical(scale_factor);
+        let ime_cursor_area_request = WindowRequest::IMECursorArea(position, size);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(ime_cursor_area_request);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        let surrounding_text_request = WindowRequest::IMESurroundingText(text, cursor, anchor);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(surrounding_text_request);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        let ime_purpose_request = WindowRequest::IMEPurpose(purpose);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(ime_purpose_request);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        let key_repeat_request = WindowRequest::KeyRepeat(key_repeat);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(key_repeat_request);
+        self.event_loop_awakener.ping();
+    }
+
+    #[inline]
+    pub fn set_system_shortcuts_inhibited(&self, inhibited: bool) -> Result<(), ExternalError> {
+        if !self.windowing_features.shortcuts_inhibit() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let inhibit_shortcuts_request = WindowRequest::InhibitShortcuts(inhibited);
         self.window_requests
             .lock()
             .unwrap()
-            .push(ime_position_request);
+            .push(inhibit_shortcuts_request);
         self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        let clipboard_request = WindowRequest::Clipboard(kind, provider);
+        self.window_requests.lock().unwrap().push(clipboard_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn read_clipboard(
+        &self,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), ExternalError> {
+        let read_clipboard_request = WindowRequest::ReadClipboard(kind, picker, consumer);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(read_clipboard_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
     }
 
     #[inline]
@@ -623,7 +999,26 @@ impl Window {
     #[inline]
     pub fn current_monitor(&self) -> Option<MonitorHandle> {
         let output = sctk::get_surface_outputs(&self.surface).last()?.clone();
-        Some(MonitorHandle::new(output))
+        Some(self.output_manager_handle.monitor(output))
+    }
+
+    /// KWin enables the adaptive sync of the outputs either always or for their fullscreen
+    /// windows.
+    #[inline]
+    pub fn is_vrr_active(&self) -> bool {
+        let device = match self
+            .current_monitor()
+            .and_then(|monitor| monitor.output_device())
+        {
+            Some(device) if device.vrr_capable => device,
+            _ => return false,
+        };
+
+        match device.vrr_policy {
+            Some(VrrPolicy::Always) => true,
+            Some(VrrPolicy::Automatic) => self.fullscreen().is_some(),
+            Some(VrrPolicy::Never) | None => false,
+        }
     }
 
     #[inline]
@@ -637,7 +1032,7 @@ impl Window {
     }
 
     #[inline]
-    pub fn raw_window_handle(&self) -> WaylandHandle {
+    pub fn window_handle(&self) -> WaylandHandle {
         let display = self.display.get_display_ptr() as *mut _;
         let surface = self.surface.as_ref().c_ptr() as *mut _;
 
@@ -647,6 +1042,14 @@ impl Window {
             ..WaylandHandle::empty()
         }
     }
+
+    #[inline]
+    pub fn display_handle(&self) -> WaylandHandle {
+        WaylandHandle {
+            display: self.display.get_display_ptr() as *mut _,
+            ..WaylandHandle::empty()
+        }
+    }
 }
 
 impl From<LocalARGBColor> for ARGBColor {
@@ -666,3 +1069,27 @@ impl Drop for Window {
         self.event_loop_awakener.ping();
     }
 }
+
+/// The output of the monitor, if it's a Wayland one.
+fn monitor_output(monitor: Option<RootMonitorHandle>) -> Option<WlOutput> {
+    monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
+        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
+        #[cfg(feature = "x11")]
+        PlatformMonitorHandle::X(_) => None,
+        #[cfg(feature = "headless")]
+        PlatformMonitorHandle::Headless(_) => None,
+    })
+}
+
+/// The request of the exclusive fullscreen with the video mode, which is emulated on its output.
+fn exclusive_fullscreen_request(video_mode: RootVideoMode) -> Option<WindowRequest> {
+    match video_mode.video_mode {
+        PlatformVideoMode::Wayland(video_mode) => {
+            Some(WindowRequest::ExclusiveFullscreen(video_mode))
+        }
+        #[cfg(feature = "x11")]
+        PlatformVideoMode::X(_) => None,
+        #[cfg(feature = "headless")]
+        PlatformVideoMode::Headless(_) => None,
+    }
+}
@@ -1,27 +1,56 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use sctk::reexports::client::protocol::wl_callback;
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
-
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+#[cfg(feature = "software-present")]
+use sctk::reexports::client::protocol::wl_shm::Format;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::environment::Environment;
+use sctk::reexports::calloop::timer::TimerHandle;
+use sctk::reexports::client::Attached;
+use sctk::reexports::protocols::unstable::keyboard_shortcuts_inhibit::v1::client::zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1;
+use sctk::reexports::protocols::unstable::keyboard_shortcuts_inhibit::v1::client::zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1;
+use sctk::reexports::protocols::presentation_time::client::wp_presentation::WpPresentation;
+use sctk::reexports::protocols::presentation_time::client::wp_presentation_feedback;
+use sctk::reexports::protocols::viewporter::client::wp_viewport::WpViewport;
+use sctk::reexports::protocols::viewporter::client::wp_viewporter::WpViewporter;
+
+#[cfg(feature = "software-present")]
+use sctk::shm::DoubleMemPool;
 use sctk::window::{ConceptConfig, ConceptFrame, Decorations, Window};
 
-use crate::dpi::{LogicalPosition, LogicalSize};
+use crate::clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker};
+use crate::dnd::{DndActions, DragData};
+use crate::dpi::{LogicalPosition, LogicalSize, PhysicalSize};
 
-use crate::event::WindowEvent;
+use crate::event::{PresentationFlags, WindowEvent};
+use crate::platform_impl::platform::instant_from_clock;
+use crate::platform_impl::wayland::cursor::{CursorSurface, CustomCursor, WindowCursorTheme};
+use crate::platform_impl::wayland::env::WinitEnv;
 use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::output::VideoMode;
+use crate::platform_impl::wayland::protocols::blur::org_kde_kwin_blur::OrgKdeKwinBlur;
+use crate::platform_impl::wayland::protocols::blur::org_kde_kwin_blur_manager::OrgKdeKwinBlurManager;
 use crate::platform_impl::wayland::seat::pointer::WinitPointer;
-use crate::platform_impl::wayland::seat::text_input::TextInputHandler;
+use crate::platform_impl::wayland::seat::text_input::{ImeState, TextInputHandler};
 use crate::platform_impl::wayland::WindowId;
-use crate::window::CursorIcon;
+use crate::window::{CursorIcon, ImePurpose};
 
 /// A request to SCTK window from Winit window.
-#[derive(Debug, Clone)]
 pub enum WindowRequest {
     /// Set fullscreen.
     ///
     /// Passing `None` will set it on the current monitor.
     Fullscreen(Option<WlOutput>),
 
+    /// Set fullscreen on the output of the video mode, emulating it.
+    ExclusiveFullscreen(VideoMode),
+
     /// Unset fullscreen.
     UnsetFullscreen,
 
@@ -31,12 +60,30 @@ pub enum WindowRequest {
     /// Change the cursor icon.
     NewCursorIcon(CursorIcon),
 
+    /// Show a custom cursor instead of the cursor icon.
+    CustomCursor(CustomCursor),
+
+    /// Load the cursor icons from the theme with the size, or from the theme of the seats.
+    CursorTheme(Option<(String, u32)>),
+
     /// Grab cursor.
     GrabCursor(bool),
 
+    /// Set the area the grabbed cursor is confined to, or the whole surface with `None`.
+    CursorGrabArea(Option<(LogicalPosition<i32>, LogicalSize<i32>)>),
+
+    /// Lock the pointer in place.
+    LockPointer(bool),
+
+    /// Hint the position of the locked pointers on the surface.
+    CursorPositionHint(LogicalPosition<f64>),
+
     /// Drag window.
     DragWindow,
 
+    /// Start dragging the data from the window.
+    StartDrag(DragData, DndActions),
+
     /// Maximize the window.
     Maximize(bool),
 
@@ -61,12 +108,44 @@ pub enum WindowRequest {
     /// New frame size.
     FrameSize(LogicalSize<u32>),
 
-    /// Set IME window position.
-    IMEPosition(LogicalPosition<u32>),
+    /// Set the area the IME window is placed around.
+    IMECursorArea(LogicalPosition<u32>, LogicalSize<u32>),
+
+    /// Set the text around the cursor, with the offsets of the cursor and the anchor.
+    IMESurroundingText(String, usize, usize),
+
+    /// Set the purpose of the text being edited.
+    IMEPurpose(ImePurpose),
+
+    /// Send the repeated key presses to the window or not.
+    KeyRepeat(bool),
+
+    /// Inhibit the compositor shortcuts or not.
+    InhibitShortcuts(bool),
+
+    /// Blur what's behind the window or not.
+    Blur(bool),
+
+    /// Set the clipboard content.
+    Clipboard(ClipboardKind, Arc<dyn ClipboardProvider>),
+
+    /// Read the clipboard content, passing it to the consumer if any.
+    ReadClipboard(
+        ClipboardKind,
+        MimePicker,
+        Option<Box<dyn ClipboardConsumer>>,
+    ),
 
     /// Redraw was requested.
     Redraw,
 
+    /// Hold the requested redraws until the frame callbacks or not.
+    VsyncRedraws(bool),
+
+    /// Attach the pixels of the size to the surface.
+    #[cfg(feature = "software-present")]
+    PresentPixels(Vec<u32>, PhysicalSize<u32>),
+
     /// A new theme for a concept frame was requested.
     Theme(ConceptConfig),
 
@@ -127,6 +206,10 @@ impl WindowUpdate {
     }
 }
 
+/// How long the frame callback stays pending, while the window is redrawn, before the window is
+/// thought to be occluded.
+const OCCLUSION_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// A handle to perform operations on SCTK window
 /// and react to events.
 pub struct WindowHandle {
@@ -142,17 +225,119 @@ pub struct WindowHandle {
     /// Current cursor icon.
     pub cursor_icon: Cell<CursorIcon>,
 
+    /// The surfaces of the custom cursor shown instead of the cursor icon.
+    custom_cursor: RefCell<Option<CursorSurface>>,
+
+    /// The timer of the animated cursors, and the id of the window its timeouts are for.
+    cursor_timer: (TimerHandle<WindowId>, WindowId),
+
+    /// The cursor theme set for the window, replacing the one of the seats.
+    cursor_theme: RefCell<Option<WindowCursorTheme>>,
+
     /// Visible cursor or not.
     cursor_visible: Cell<bool>,
 
     /// Cursor confined to the surface.
     confined: Cell<bool>,
 
+    /// The area of the surface the cursor is confined to, instead of the whole surface.
+    confine_area: Cell<Option<(LogicalPosition<i32>, LogicalSize<i32>)>>,
+
+    /// Environment to create the regions of the confined pointers and the cursor surfaces.
+    env: Environment<WinitEnv>,
+
+    /// Pointer locked in place on the surface.
+    locked: Cell<bool>,
+
+    /// Whether the repeated key presses are sent to the window.
+    pub key_repeat: Cell<bool>,
+
     /// Pointers over the current surface.
     pointers: Vec<WinitPointer>,
 
     /// Text inputs on the current surface.
     text_inputs: Vec<TextInputHandler>,
+
+    /// The state of the text inputs, sent to them whenever they're enabled.
+    ime_state: RefCell<ImeState>,
+
+    /// Compositor shortcuts inhibited while the window has keyboard focus.
+    shortcuts_inhibited: Cell<bool>,
+
+    /// Seats with keyboard focus on the current surface, along with their inhibitors.
+    keyboard_seats: RefCell<Vec<(WlSeat, Option<ZwpKeyboardShortcutsInhibitorV1>)>>,
+
+    /// The blur behind the surface, while it's blurred.
+    blur: RefCell<Option<OrgKdeKwinBlur>>,
+
+    /// The globals of the optional protocols the window uses.
+    globals: WindowGlobals,
+
+    /// The viewport scaling the buffers of the size of the video mode to the surface, while the
+    /// window is exclusive fullscreen.
+    viewport: RefCell<Option<WpViewport>>,
+
+    /// The video mode emulated for the window.
+    video_mode: Arc<Mutex<Option<VideoMode>>>,
+
+    /// Whether the compositor is yet to configure the window fullscreen with the video mode.
+    video_mode_pending: Cell<bool>,
+
+    /// Whether the buffers changed size since the last resize, as the video mode changed.
+    buffers_resized: Cell<bool>,
+
+    /// The frame callbacks, shared with the window.
+    frame_callbacks: FrameCallbacks,
+
+    /// Whether the compositor stopped calling back, as the window isn't shown.
+    occluded: Cell<bool>,
+
+    /// Whether the requested redraws wait for the pending frame callback.
+    vsync_redraws: Cell<bool>,
+
+    /// Whether a requested redraw waits for the pending frame callback.
+    redraw_held: Cell<bool>,
+
+    /// The refresh rate of the output the window was last on, in millihertz.
+    refresh_rate: Cell<Option<u32>>,
+
+    /// The pools of the buffers of the pixels presented in software, once they were.
+    #[cfg(feature = "software-present")]
+    pixel_pools: RefCell<Option<DoubleMemPool>>,
+}
+
+/// The globals of the optional protocols the windows use, if the compositor advertises them.
+struct WindowGlobals {
+    /// Manager of the shortcuts inhibitors.
+    shortcuts_inhibit_manager: Option<Attached<ZwpKeyboardShortcutsInhibitManagerV1>>,
+
+    /// Manager of the blur behind the surfaces.
+    blur_manager: Option<Attached<OrgKdeKwinBlurManager>>,
+
+    /// Scaler of the buffers of the surfaces.
+    viewporter: Option<Attached<WpViewporter>>,
+}
+
+impl WindowGlobals {
+    fn new(env: &Environment<WinitEnv>) -> Self {
+        Self {
+            shortcuts_inhibit_manager: env.get_global::<ZwpKeyboardShortcutsInhibitManagerV1>(),
+            blur_manager: env.get_global::<OrgKdeKwinBlurManager>(),
+            viewporter: env.get_global::<WpViewporter>(),
+        }
+    }
+}
+
+/// The frame callbacks of a window, which both the window and its handle request.
+#[derive(Clone, Default)]
+pub struct FrameCallbacks {
+    /// The frame callback pending since it was requested, with the number of redraws dispatched
+    /// since.
+    pub pending: Arc<Mutex<Option<(Instant, u32)>>>,
+
+    /// Whether the application requests the frame callbacks right before presenting, rather
+    /// than them being requested before the redraws.
+    pub present_notified: Arc<AtomicBool>,
 }
 
 impl WindowHandle {
@@ -160,19 +345,159 @@ impl WindowHandle {
         window: Window<ConceptFrame>,
         size: Arc<Mutex<LogicalSize<u32>>>,
         pending_window_requests: Arc<Mutex<Vec<WindowRequest>>>,
+        env: Environment<WinitEnv>,
+        cursor_timer: (TimerHandle<WindowId>, WindowId),
+        video_mode: Arc<Mutex<Option<VideoMode>>>,
+        frame_callbacks: FrameCallbacks,
     ) -> Self {
+        let globals = WindowGlobals::new(&env);
         Self {
             window,
             size,
             pending_window_requests,
             cursor_icon: Cell::new(CursorIcon::Default),
+            custom_cursor: RefCell::new(None),
+            cursor_timer,
+            cursor_theme: RefCell::new(None),
             confined: Cell::new(false),
+            confine_area: Cell::new(None),
+            env,
+            locked: Cell::new(false),
             cursor_visible: Cell::new(true),
+            key_repeat: Cell::new(true),
             pointers: Vec::new(),
             text_inputs: Vec::new(),
+            ime_state: RefCell::new(ImeState::default()),
+            shortcuts_inhibited: Cell::new(false),
+            keyboard_seats: RefCell::new(Vec::new()),
+            blur: RefCell::new(None),
+            globals,
+            viewport: RefCell::new(None),
+            video_mode,
+            video_mode_pending: Cell::new(false),
+            buffers_resized: Cell::new(false),
+            frame_callbacks,
+            occluded: Cell::new(false),
+            vsync_redraws: Cell::new(false),
+            redraw_held: Cell::new(false),
+            refresh_rate: Cell::new(None),
+            #[cfg(feature = "software-present")]
+            pixel_pools: RefCell::new(None),
         }
     }
 
+    /// Returns the refresh rate of the output the window is on, in millihertz, once it changed
+    /// since the last call, as the mode of the output changed or the window entered another one.
+    pub fn refresh_rate_changed(&self) -> Option<u32> {
+        let output = sctk::get_surface_outputs(self.window.surface())
+            .last()?
+            .clone();
+        let refresh_rate = sctk::output::with_output_info(&output, |info| {
+            info.modes
+                .iter()
+                .find(|mode| mode.is_current)
+                .map(|mode| mode.refresh_rate)
+        })
+        .flatten()
+        .filter(|&refresh_rate| refresh_rate > 0)? as u32;
+
+        // The refresh rate of the first output the window enters isn't a change.
+        match self.refresh_rate.replace(Some(refresh_rate)) {
+            Some(last_refresh_rate) if last_refresh_rate != refresh_rate => Some(refresh_rate),
+            _ => None,
+        }
+    }
+
+    /// Requests a frame callback along with the redraw about to be dispatched, unless one is
+    /// still pending or the application requests them itself. Returns whether the window became
+    /// occluded, as the compositor stopped calling back while the window kept being redrawn.
+    pub fn before_redraw(&self, window_id: WindowId) -> bool {
+        self.request_presentation_feedback(window_id);
+
+        let mut frame_callback = self.frame_callbacks.pending.lock().unwrap();
+        if let Some((requested, redraws)) = *frame_callback {
+            *frame_callback = Some((requested, redraws + 1));
+            let starved = redraws >= 2 && requested.elapsed() >= OCCLUSION_TIMEOUT;
+            return starved && !self.occluded.replace(true);
+        }
+
+        drop(frame_callback);
+
+        if !self
+            .frame_callbacks
+            .present_notified
+            .load(Ordering::Relaxed)
+        {
+            request_frame_callback(
+                self.window.surface(),
+                window_id,
+                &self.frame_callbacks.pending,
+            );
+        }
+        false
+    }
+
+    /// Holds the requested redraw until the pending frame callback, if the redraws are
+    /// synchronized with them. Returns whether it's held.
+    fn hold_redraw(&self) -> bool {
+        let held =
+            self.vsync_redraws.get() && self.frame_callbacks.pending.lock().unwrap().is_some();
+        if held {
+            self.redraw_held.set(true);
+        }
+        held
+    }
+
+    /// Requests the feedback of the presentation of the frame drawn on the redraw about to be
+    /// dispatched, which is sent as `Presented` once the compositor showed it.
+    fn request_presentation_feedback(&self, window_id: WindowId) {
+        let presentation = match self.env.get_global::<WpPresentation>() {
+            Some(presentation) => presentation,
+            None => return,
+        };
+        let clock_id = match self.env.with_inner(|env| env.presentation_clock()).get() {
+            Some(clock_id) => clock_id,
+            None => return,
+        };
+
+        let feedback = presentation.feedback(self.window.surface());
+        feedback.quick_assign(move |_, event, mut dispatch_data| {
+            // The discarded frames aren't reported.
+            if let wp_presentation_feedback::Event::Presented {
+                tv_sec_hi,
+                tv_sec_lo,
+                tv_nsec,
+                refresh,
+                flags,
+                ..
+            } = event
+            {
+                let seconds = (u64::from(tv_sec_hi) << 32) | u64::from(tv_sec_lo);
+                let time = match instant_from_clock(
+                    clock_id as libc::clockid_t,
+                    Duration::new(seconds, tv_nsec),
+                ) {
+                    Some(time) => time,
+                    None => return,
+                };
+                // The refresh is zero for the outputs with a variable refresh rate.
+                let refresh = if refresh > 0 {
+                    Some(Duration::from_nanos(u64::from(refresh)))
+                } else {
+                    None
+                };
+                let event = WindowEvent::Presented {
+                    time,
+                    refresh,
+                    flags: PresentationFlags::from_bits_truncate(flags),
+                };
+
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                winit_state.event_sink.push_window_event(event, window_id);
+            }
+        });
+    }
+
     pub fn set_cursor_grab(&self, grab: bool) {
         // The new requested state matches the current confine status, return.
         if self.confined.get() == grab {
@@ -182,11 +507,72 @@ impl WindowHandle {
         self.confined.replace(grab);
 
         for pointer in self.pointers.iter() {
-            if self.confined.get() {
-                let surface = self.window.surface();
-                pointer.confine(&surface);
-            } else {
-                pointer.unconfine();
+            self.apply_pointer_constraint(pointer);
+        }
+    }
+
+    pub fn set_cursor_grab_area(&self, area: Option<(LogicalPosition<i32>, LogicalSize<i32>)>) {
+        self.confine_area.replace(area);
+
+        // The region of a confined pointer is only given when it's created.
+        if self.confined.get() {
+            for pointer in self.pointers.iter() {
+                self.apply_pointer_constraint(pointer);
+            }
+        }
+    }
+
+    pub fn set_pointer_lock(&self, lock: bool) {
+        // The new requested state matches the current lock status, return.
+        if self.locked.get() == lock {
+            return;
+        }
+
+        self.locked.replace(lock);
+
+        for pointer in self.pointers.iter() {
+            self.apply_pointer_constraint(pointer);
+        }
+
+        // The cursor is hidden while the pointer is locked.
+        self.refresh_cursor();
+    }
+
+    /// Hints the position of the locked pointers on the surface, where the compositor puts the
+    /// cursor once they're unlocked. Returns `false` when the pointers aren't locked.
+    pub fn set_cursor_position_hint(&self, position: LogicalPosition<f64>) -> bool {
+        if !self.locked.get() {
+            return false;
+        }
+
+        for pointer in self.pointers.iter() {
+            pointer.set_cursor_position_hint(position);
+        }
+
+        // The hint is applied along with the state of the surface.
+        self.window.surface().commit();
+        true
+    }
+
+    /// Constrains the pointer as requested, the lock taking over the confinement since a pointer
+    /// can only have one constraint on a surface.
+    fn apply_pointer_constraint(&self, pointer: &WinitPointer) {
+        pointer.unconfine();
+        pointer.unlock();
+
+        let surface = self.window.surface();
+        if self.locked.get() {
+            pointer.lock(surface);
+        } else if self.confined.get() {
+            let region = self.confine_area.get().map(|(position, size)| {
+                let region = self.env.require_global::<WlCompositor>().create_region();
+                region.add(position.x, position.y, size.width, size.height);
+                region.detach()
+            });
+            pointer.confine(surface, region.as_ref());
+            // The region is copied by the request.
+            if let Some(region) = region {
+                region.destroy();
             }
         }
     }
@@ -196,15 +582,12 @@ impl WindowHandle {
         let position = self.pointers.iter().position(|p| *p == pointer);
 
         if position.is_none() {
-            if self.confined.get() {
-                let surface = self.window.surface();
-                pointer.confine(&surface);
-            }
+            self.apply_pointer_constraint(&pointer);
             self.pointers.push(pointer);
         }
 
         // Apply the current cursor style.
-        self.set_cursor_visible(self.cursor_visible.get());
+        self.refresh_cursor();
     }
 
     /// Pointer left the window.
@@ -214,14 +597,14 @@ impl WindowHandle {
         if let Some(position) = position {
             let pointer = self.pointers.remove(position);
 
-            // Drop the confined pointer.
-            if self.confined.get() {
-                pointer.unconfine();
-            }
+            // Drop the confined or locked pointer.
+            pointer.unconfine();
+            pointer.unlock();
         }
     }
 
     pub fn text_input_entered(&mut self, text_input: TextInputHandler) {
+        text_input.enable(&self.ime_state.borrow());
         if self
             .text_inputs
             .iter()
@@ -238,37 +621,290 @@ impl WindowHandle {
         }
     }
 
-    pub fn set_ime_position(&self, position: LogicalPosition<u32>) {
+    pub fn set_shortcuts_inhibited(&self, inhibited: bool) {
+        // The new requested state matches the current inhibit status, return.
+        if self.shortcuts_inhibited.replace(inhibited) == inhibited {
+            return;
+        }
+
+        let surface = self.window.surface();
+        for (seat, inhibitor) in self.keyboard_seats.borrow_mut().iter_mut() {
+            if inhibited {
+                *inhibitor =
+                    inhibit_shortcuts(&self.globals.shortcuts_inhibit_manager, surface, seat);
+            } else if let Some(inhibitor) = inhibitor.take() {
+                inhibitor.destroy();
+            }
+        }
+    }
+
+    /// Attaches the pixels to the surface from the pool the compositor isn't reading, dropping
+    /// them while it reads both.
+    #[cfg(feature = "software-present")]
+    pub fn present_pixels(&self, pixels: &[u32], size: PhysicalSize<u32>) {
+        let mut pixel_pools = self.pixel_pools.borrow_mut();
+        if pixel_pools.is_none() {
+            match self.env.create_double_pool(|_| {}) {
+                Ok(pools) => *pixel_pools = Some(pools),
+                Err(err) => {
+                    warn!("Failed to create the pools of the pixels: {}", err);
+                    return;
+                }
+            }
+        }
+        let pool = match pixel_pools.as_mut().unwrap().pool() {
+            Some(pool) => pool,
+            None => {
+                debug!("Dropped the pixels, as the compositor reads both buffers");
+                return;
+            }
+        };
+
+        let stride = size.width as usize * 4;
+        if let Err(err) = pool.resize(stride * size.height as usize) {
+            warn!("Failed to resize the pool of the pixels: {}", err);
+            return;
+        }
+        // The format is little-endian, as the pixels are.
+        for (pixel, rgb) in pool.mmap().chunks_exact_mut(4).zip(pixels) {
+            pixel.copy_from_slice(&rgb.to_le_bytes());
+        }
+
+        let buffer = pool.buffer(
+            0,
+            size.width as i32,
+            size.height as i32,
+            stride as i32,
+            Format::Xrgb8888,
+        );
+        let surface = self.window.surface();
+        surface.attach(Some(&buffer), 0, 0);
+        surface.damage(0, 0, i32::MAX, i32::MAX);
+        surface.commit();
+    }
+
+    pub fn set_blur(&self, blurred: bool) {
+        let manager = match self.globals.blur_manager.as_ref() {
+            Some(manager) => manager,
+            None => return,
+        };
+
+        let surface = self.window.surface();
+        let mut blur = self.blur.borrow_mut();
+        if blurred {
+            if blur.is_some() {
+                return;
+            }
+
+            // The blur without a region is behind the whole surface.
+            let new_blur = manager.create(surface).detach();
+            new_blur.commit();
+            *blur = Some(new_blur);
+        } else if let Some(blur) = blur.take() {
+            manager.unset(surface);
+            blur.release();
+        } else {
+            return;
+        }
+
+        // The blur is applied along with the state of the surface.
+        surface.commit();
+    }
+
+    /// Scales the buffers of the size of the video mode to the surface, which the compositor
+    /// makes fullscreen. Returns `false` when the buffers can't be scaled.
+    pub fn enter_video_mode(&self, video_mode: VideoMode) -> bool {
+        let viewporter = match self.globals.viewporter.as_ref() {
+            Some(viewporter) => viewporter,
+            None => {
+                self.leave_video_mode();
+                return false;
+            }
+        };
+
+        let surface = self.window.surface();
+        let mut viewport = self.viewport.borrow_mut();
+        if viewport.is_none() {
+            *viewport = Some(viewporter.get_viewport(surface).detach());
+        }
+
+        // The buffers are in the pixels of the video mode, whatever the scale of the surface.
+        surface.set_buffer_scale(1);
+        *self.video_mode.lock().unwrap() = Some(video_mode);
+        self.video_mode_pending.set(true);
+        self.buffers_resized.set(true);
+        true
+    }
+
+    /// Stops scaling the buffers of the size of the video mode. Returns whether they were scaled.
+    pub fn leave_video_mode(&self) -> bool {
+        let viewport = match self.viewport.borrow_mut().take() {
+            Some(viewport) => viewport,
+            None => return false,
+        };
+
+        viewport.destroy();
+        let surface = self.window.surface();
+        surface.set_buffer_scale(sctk::get_surface_scale_factor(surface));
+        *self.video_mode.lock().unwrap() = None;
+        self.video_mode_pending.set(false);
+        self.buffers_resized.set(true);
+        true
+    }
+
+    /// Whether the buffers are scaled from the size of the video mode.
+    pub fn has_video_mode(&self) -> bool {
+        self.viewport.borrow().is_some()
+    }
+
+    /// Follows the fullscreen state configured by the compositor, leaving the video mode once the
+    /// window isn't fullscreen anymore. Returns whether the window was first configured
+    /// fullscreen with the video mode.
+    pub fn configure_video_mode(&self, fullscreen: bool) -> bool {
+        if !self.has_video_mode() {
+            return false;
+        }
+
+        if fullscreen {
+            self.video_mode_pending.replace(false)
+        } else {
+            if !self.video_mode_pending.get() {
+                self.leave_video_mode();
+            }
+            false
+        }
+    }
+
+    /// The physical size of the buffers of the surface of the logical `size`, which is the size
+    /// of the video mode while it's emulated.
+    pub fn buffer_size(&self, size: LogicalSize<u32>, scale_factor: f64) -> PhysicalSize<u32> {
+        match *self.video_mode.lock().unwrap() {
+            Some(ref video_mode) if self.has_video_mode() => video_mode.size,
+            _ => size.to_physical(scale_factor),
+        }
+    }
+
+    /// Returns whether the buffers changed size since the last call, as the video mode changed.
+    pub fn take_buffers_resized(&self) -> bool {
+        self.buffers_resized.replace(false)
+    }
+
+    /// Scales the buffers of the size of the video mode to the logical `size` of the surface.
+    pub fn set_viewport_destination(&self, size: LogicalSize<u32>) {
+        if let Some(viewport) = self.viewport.borrow().as_ref() {
+            viewport.set_destination(size.width as i32, size.height as i32);
+        }
+    }
+
+    /// Keyboard focus entered the window.
+    pub fn keyboard_entered(&self, seat: WlSeat) {
+        let mut keyboard_seats = self.keyboard_seats.borrow_mut();
+        if keyboard_seats.iter().any(|(s, _)| *s == seat) {
+            return;
+        }
+
+        let inhibitor = if self.shortcuts_inhibited.get() {
+            let surface = self.window.surface();
+            inhibit_shortcuts(&self.globals.shortcuts_inhibit_manager, surface, &seat)
+        } else {
+            None
+        };
+        keyboard_seats.push((seat, inhibitor));
+    }
+
+    /// Keyboard focus left the window.
+    pub fn keyboard_left(&self, seat: &WlSeat) {
+        let mut keyboard_seats = self.keyboard_seats.borrow_mut();
+        let position = keyboard_seats.iter().position(|(s, _)| s == seat);
+
+        if let Some(position) = position {
+            // Drop the inhibitor, since the seat could gain focus on the window again.
+            if let (_, Some(inhibitor)) = keyboard_seats.remove(position) {
+                inhibitor.destroy();
+            }
+        }
+    }
+
+    pub fn set_ime_cursor_area(&self, position: LogicalPosition<u32>, size: LogicalSize<u32>) {
         // XXX This won't fly unless user will have a way to request IME window per seat, since
         // the ime windows will be overlapping, but winit doesn't expose API to specify for
         // which seat we're setting IME position.
         let (x, y) = (position.x as i32, position.y as i32);
+        let (width, height) = (size.width as i32, size.height as i32);
+        self.ime_state.borrow_mut().cursor_area = Some((x, y, width, height));
+        self.send_ime_state();
+    }
+
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        self.ime_state.borrow_mut().surrounding_text = Some((text, cursor, anchor));
+        self.send_ime_state();
+    }
+
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        self.ime_state.borrow_mut().purpose = Some(purpose);
+        self.send_ime_state();
+    }
+
+    fn send_ime_state(&self) {
+        let ime_state = self.ime_state.borrow();
         for text_input in self.text_inputs.iter() {
-            text_input.set_ime_position(x, y);
+            text_input.set_state(&ime_state);
         }
     }
 
     pub fn set_cursor_visible(&self, visible: bool) {
         self.cursor_visible.replace(visible);
-        let cursor_icon = match visible {
-            true => Some(self.cursor_icon.get()),
-            false => None,
-        };
-
-        for pointer in self.pointers.iter() {
-            pointer.set_cursor(cursor_icon)
-        }
+        self.refresh_cursor();
     }
 
     pub fn set_cursor_icon(&self, cursor_icon: CursorIcon) {
         self.cursor_icon.replace(cursor_icon);
+        self.custom_cursor.replace(None);
+        self.refresh_cursor();
+    }
 
-        if !self.cursor_visible.get() {
-            return;
+    pub fn set_custom_cursor(&self, cursor: CustomCursor) {
+        let (ref timer, window_id) = self.cursor_timer;
+        match CursorSurface::new(&self.env, &cursor, timer, window_id) {
+            Ok(cursor_surface) => {
+                self.custom_cursor.replace(Some(cursor_surface));
+                self.refresh_cursor();
+            }
+            Err(err) => warn!("Failed to create the custom cursor: {}", err),
+        }
+    }
+
+    /// Loads the cursor icons from the theme, or from the one of the seats when it's `None`.
+    pub fn set_cursor_theme(&self, theme: Option<(String, u32)>) {
+        let theme = theme.map(|(name, size)| WindowCursorTheme::new(&self.env, &name, size));
+        self.cursor_theme.replace(theme);
+        self.refresh_cursor();
+    }
+
+    /// Shows the next frame of the animated custom cursor.
+    pub fn next_cursor_frame(&self) {
+        if let Some(ref mut cursor) = *self.custom_cursor.borrow_mut() {
+            cursor.next_frame();
         }
+        self.refresh_cursor();
+    }
 
+    /// Show the custom cursor or the cursor icon on the pointers, or no cursor when it's hidden
+    /// or the pointer is locked.
+    fn refresh_cursor(&self) {
+        let visible = self.cursor_visible.get() && !self.locked.get();
+        let custom_cursor = self.custom_cursor.borrow();
+        let mut cursor_theme = self.cursor_theme.borrow_mut();
         for pointer in self.pointers.iter() {
-            pointer.set_cursor(Some(cursor_icon));
+            match (&*custom_cursor, &mut *cursor_theme) {
+                _ if !visible => pointer.set_cursor(None),
+                (Some(cursor), _) => {
+                    let (surface, hotspot) = cursor.surface();
+                    pointer.set_custom_cursor(surface, hotspot);
+                }
+                (None, Some(theme)) => pointer.set_cursor_from_theme(theme, self.cursor_icon.get()),
+                (None, None) => pointer.set_cursor(Some(self.cursor_icon.get())),
+            }
         }
     }
 
@@ -279,6 +915,81 @@ impl WindowHandle {
     }
 }
 
+impl Drop for WindowHandle {
+    fn drop(&mut self) {
+        for (_, inhibitor) in self.keyboard_seats.get_mut().drain(..) {
+            if let Some(inhibitor) = inhibitor {
+                inhibitor.destroy();
+            }
+        }
+
+        if let Some(blur) = self.blur.get_mut().take() {
+            blur.release();
+        }
+
+        if let Some(viewport) = self.viewport.get_mut().take() {
+            viewport.destroy();
+        }
+    }
+}
+
+/// Resizes the buffers of the window along with its surface, which keeps its size.
+#[inline]
+fn resize_buffers(window_handle: &WindowHandle, window_update: &mut WindowUpdate) {
+    if window_update.size.is_none() {
+        window_update.size = Some(*window_handle.size.lock().unwrap());
+    }
+    window_update.redraw_requested = true;
+}
+
+#[inline]
+fn inhibit_shortcuts(
+    manager: &Option<Attached<ZwpKeyboardShortcutsInhibitManagerV1>>,
+    surface: &WlSurface,
+    seat: &WlSeat,
+) -> Option<ZwpKeyboardShortcutsInhibitorV1> {
+    let manager = manager.as_ref()?;
+    Some(manager.inhibit_shortcuts(surface, seat).detach())
+}
+
+/// Requests the frame callback of the surface, unless one is pending already.
+///
+/// The callback is assigned on the thread dispatching the events, which is the one of the event
+/// loop.
+pub fn request_frame_callback(
+    surface: &WlSurface,
+    window_id: WindowId,
+    frame_callback: &Mutex<Option<(Instant, u32)>>,
+) {
+    let mut frame_callback = frame_callback.lock().unwrap();
+    if frame_callback.is_some() {
+        return;
+    }
+
+    let callback = surface.frame();
+    callback.quick_assign(move |_, event, mut dispatch_data| {
+        if let wl_callback::Event::Done { .. } = event {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            let window_handle = match winit_state.window_map.get(&window_id) {
+                Some(window_handle) => window_handle,
+                None => return,
+            };
+            *window_handle.frame_callbacks.pending.lock().unwrap() = None;
+            if window_handle.redraw_held.replace(false) {
+                if let Some(window_update) = winit_state.window_updates.get_mut(&window_id) {
+                    window_update.redraw_requested = true;
+                }
+            }
+            if window_handle.occluded.replace(false) {
+                winit_state
+                    .event_sink
+                    .push_window_event(WindowEvent::Occluded(false), window_id);
+            }
+        }
+    });
+    *frame_callback = Some((Instant::now(), 0));
+}
+
 #[inline]
 pub fn handle_window_requests(winit_state: &mut WinitState) {
     let window_map = &mut winit_state.window_map;
@@ -291,9 +1002,27 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
         for request in requests.drain(..) {
             match request {
                 WindowRequest::Fullscreen(fullscreen) => {
+                    if window_handle.leave_video_mode() {
+                        resize_buffers(window_handle, window_updates.get_mut(window_id).unwrap());
+                    }
                     window_handle.window.set_fullscreen(fullscreen.as_ref());
                 }
+                WindowRequest::ExclusiveFullscreen(video_mode) => {
+                    let output = video_mode.monitor.proxy.clone();
+                    let switched = window_handle.enter_video_mode(video_mode);
+                    resize_buffers(window_handle, window_updates.get_mut(window_id).unwrap());
+                    if !switched {
+                        warn!("Failed to emulate the video mode: no `wp_viewporter`");
+                        winit_state
+                            .event_sink
+                            .push_window_event(WindowEvent::VideoModeSwitched(false), *window_id);
+                    }
+                    window_handle.window.set_fullscreen(Some(&output));
+                }
                 WindowRequest::UnsetFullscreen => {
+                    if window_handle.leave_video_mode() {
+                        resize_buffers(window_handle, window_updates.get_mut(window_id).unwrap());
+                    }
                     window_handle.window.unset_fullscreen();
                 }
                 WindowRequest::ShowCursor(show_cursor) => {
@@ -302,15 +1031,94 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                 WindowRequest::NewCursorIcon(cursor_icon) => {
                     window_handle.set_cursor_icon(cursor_icon);
                 }
-                WindowRequest::IMEPosition(position) => {
-                    window_handle.set_ime_position(position);
+                WindowRequest::CustomCursor(cursor) => {
+                    window_handle.set_custom_cursor(cursor);
+                }
+                WindowRequest::CursorTheme(theme) => {
+                    window_handle.set_cursor_theme(theme);
+                }
+                WindowRequest::IMECursorArea(position, size) => {
+                    window_handle.set_ime_cursor_area(position, size);
+                }
+                WindowRequest::IMESurroundingText(text, cursor, anchor) => {
+                    window_handle.set_ime_surrounding_text(text, cursor, anchor);
+                }
+                WindowRequest::IMEPurpose(purpose) => {
+                    window_handle.set_ime_purpose(purpose);
+                }
+                WindowRequest::KeyRepeat(key_repeat) => {
+                    window_handle.key_repeat.set(key_repeat);
+                }
+                WindowRequest::InhibitShortcuts(inhibited) => {
+                    window_handle.set_shortcuts_inhibited(inhibited);
+                }
+                WindowRequest::Blur(blurred) => {
+                    window_handle.set_blur(blurred);
+                }
+                WindowRequest::Clipboard(kind, provider) => {
+                    if let Err(err) = winit_state.clipboard.set_clipboard(kind, provider) {
+                        warn!("Failed to set the clipboard: {}", err);
+                    }
+                }
+                WindowRequest::ReadClipboard(kind, picker, consumer) => {
+                    winit_state
+                        .clipboard
+                        .read_clipboard(*window_id, kind, picker, consumer);
                 }
                 WindowRequest::GrabCursor(grab) => {
                     window_handle.set_cursor_grab(grab);
                 }
+                WindowRequest::CursorGrabArea(area) => {
+                    window_handle.set_cursor_grab_area(area);
+                }
+                WindowRequest::LockPointer(lock) => {
+                    window_handle.set_pointer_lock(lock);
+                }
+                WindowRequest::CursorPositionHint(position) => {
+                    if !window_handle.set_cursor_position_hint(position) {
+                        continue;
+                    }
+
+                    // The locked pointers are where they were hinted for the window, since the
+                    // compositor doesn't send their motion.
+                    let surface = window_handle.window.surface();
+                    let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+                    let position = position.to_physical(scale_factor);
+                    for pointer in window_handle.pointers.iter() {
+                        let device_id = crate::platform_impl::wayland::make_did(pointer.seat());
+                        winit_state.event_sink.push_window_event(
+                            WindowEvent::CursorMoved {
+                                device_id: crate::event::DeviceId(
+                                    crate::platform_impl::DeviceId::Wayland(device_id),
+                                ),
+                                position,
+                                modifiers: pointer.modifiers(),
+                            },
+                            *window_id,
+                        );
+                    }
+                }
                 WindowRequest::DragWindow => {
                     window_handle.drag_window();
                 }
+                WindowRequest::StartDrag(data, actions) => {
+                    let pointer = match window_handle.pointers.first() {
+                        Some(pointer) => pointer,
+                        None => {
+                            warn!("Failed to start the drag: no pointer to drag with");
+                            continue;
+                        }
+                    };
+
+                    let surface = window_handle.window.surface();
+                    let (seat, serial) = (pointer.seat(), pointer.latest_serial());
+                    if let Err(err) = winit_state
+                        .clipboard
+                        .start_drag(*window_id, surface, seat, serial, data, actions)
+                    {
+                        warn!("Failed to start the drag: {}", err);
+                    }
+                }
                 WindowRequest::Maximize(maximize) => {
                     if maximize {
                         window_handle.window.set_maximized();
@@ -370,8 +1178,22 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                     window_update.refresh_frame = true;
                 }
                 WindowRequest::Redraw => {
-                    let window_update = window_updates.get_mut(&window_id).unwrap();
-                    window_update.redraw_requested = true;
+                    if !window_handle.hold_redraw() {
+                        let window_update = window_updates.get_mut(window_id).unwrap();
+                        window_update.redraw_requested = true;
+                    }
+                }
+                WindowRequest::VsyncRedraws(vsync_redraws) => {
+                    window_handle.vsync_redraws.set(vsync_redraws);
+                    // The held redraw isn't left waiting.
+                    if !vsync_redraws && window_handle.redraw_held.replace(false) {
+                        let window_update = window_updates.get_mut(window_id).unwrap();
+                        window_update.redraw_requested = true;
+                    }
+                }
+                #[cfg(feature = "software-present")]
+                WindowRequest::PresentPixels(pixels, size) => {
+                    window_handle.present_pixels(&pixels, size);
                 }
                 WindowRequest::Theme(concept_config) => {
                     window_handle.window.set_frame_config(concept_config);
@@ -0,0 +1,111 @@
+use std::{mem, os::raw::*, sync::Arc};
+
+use raw_window_handle::unix::XlibHandle;
+
+use super::{ffi, UnownedWindow, Window};
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize, Position, Size},
+    error::{ExternalError, HandleError},
+    platform_impl::OsError,
+};
+
+/// A child window of a window, which the events select nothing of so they go to its parent.
+pub struct ChildSurface {
+    // The parent is kept alive, as it tells whether the child was destroyed along with it.
+    parent: Arc<UnownedWindow>,
+    xwindow: ffi::Window,
+}
+
+impl ChildSurface {
+    pub fn new(parent: &Window, position: Position, size: Size) -> Result<Self, ExternalError> {
+        let parent = Arc::clone(&parent.0);
+        let xconn = &parent.xconn;
+        let scale_factor = parent.scale_factor();
+        let position: PhysicalPosition<i32> = position.to_physical(scale_factor);
+        let size: PhysicalSize<u32> = size.to_physical(scale_factor);
+
+        let xwindow = unsafe {
+            // The visual and the colormap are the ones of the parent.
+            let mut set_win_attr: ffi::XSetWindowAttributes = mem::zeroed();
+            let xwindow = (xconn.xlib.XCreateWindow)(
+                xconn.display,
+                parent.id().0,
+                position.x as c_int,
+                position.y as c_int,
+                size.width.max(1) as c_uint,
+                size.height.max(1) as c_uint,
+                0,
+                ffi::CopyFromParent,
+                ffi::InputOutput as c_uint,
+                ffi::CopyFromParent as *mut ffi::Visual,
+                0,
+                &mut set_win_attr,
+            );
+            (xconn.xlib.XMapWindow)(xconn.display, xwindow);
+            xwindow
+        };
+        if let Err(err) = xconn.check_errors() {
+            unsafe { (xconn.xlib.XDestroyWindow)(xconn.display, xwindow) };
+            let _ = xconn.check_errors();
+            return Err(ExternalError::Os(os_error!(OsError::XError(err))));
+        }
+
+        Ok(ChildSurface { parent, xwindow })
+    }
+
+    #[inline]
+    pub fn set_position(&self, position: Position) {
+        let xconn = &self.parent.xconn;
+        let position: PhysicalPosition<i32> = position.to_physical(self.parent.scale_factor());
+        unsafe {
+            (xconn.xlib.XMoveWindow)(
+                xconn.display,
+                self.xwindow,
+                position.x as c_int,
+                position.y as c_int,
+            );
+        }
+        xconn
+            .flush_requests()
+            .expect("Failed to call `XMoveWindow`");
+    }
+
+    #[inline]
+    pub fn set_size(&self, size: Size) {
+        let xconn = &self.parent.xconn;
+        let size: PhysicalSize<u32> = size.to_physical(self.parent.scale_factor());
+        unsafe {
+            (xconn.xlib.XResizeWindow)(
+                xconn.display,
+                self.xwindow,
+                size.width.max(1) as c_uint,
+                size.height.max(1) as c_uint,
+            );
+        }
+        xconn
+            .flush_requests()
+            .expect("Failed to call `XResizeWindow`");
+    }
+
+    #[inline]
+    pub fn window_handle(&self) -> Result<XlibHandle, HandleError> {
+        // The child is destroyed along with its parent.
+        let parent_handle = self.parent.window_handle()?;
+        Ok(XlibHandle {
+            window: self.xwindow,
+            ..parent_handle
+        })
+    }
+}
+
+impl Drop for ChildSurface {
+    fn drop(&mut self) {
+        let xconn = &self.parent.xconn;
+        unsafe {
+            (xconn.xlib.XDestroyWindow)(xconn.display, self.xwindow);
+            // The child is already gone if its parent was destroyed, which is a `BadWindow`
+            // error we don't care about.
+            let _ = xconn.check_errors();
+        }
+    }
+}
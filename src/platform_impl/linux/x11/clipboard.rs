@@ -0,0 +1,823 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    mem,
+    os::raw::*,
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::clipboard::{
+    self, ClipboardConsumer, ClipboardKind, ClipboardMimedContent, ClipboardProvider, MimePicker,
+    MimeType,
+};
+
+use super::{ffi, util, XConnection, XError};
+
+#[derive(Debug)]
+pub struct ClipboardAtoms {
+    pub clipboard: ffi::Atom,
+    pub primary: ffi::Atom,
+    pub targets: ffi::Atom,
+    /// The type of the property announcing a transfer in chunks.
+    pub incr: ffi::Atom,
+    /// The property on the requestor window the selections are transferred through.
+    pub transfer: ffi::Atom,
+}
+
+impl ClipboardAtoms {
+    pub fn new(xconn: &Arc<XConnection>) -> Result<Self, XError> {
+        let names = [
+            b"CLIPBOARD\0".as_ptr() as *mut c_char,
+            b"PRIMARY\0".as_ptr() as *mut c_char,
+            b"TARGETS\0".as_ptr() as *mut c_char,
+            b"INCR\0".as_ptr() as *mut c_char,
+            b"_WINIT_SELECTION\0".as_ptr() as *mut c_char,
+        ];
+        let atoms = unsafe { xconn.get_atoms(&names) }?;
+        Ok(ClipboardAtoms {
+            clipboard: atoms[0],
+            primary: atoms[1],
+            targets: atoms[2],
+            incr: atoms[3],
+            transfer: atoms[4],
+        })
+    }
+
+    /// The selection backing the given clipboard.
+    pub fn selection(&self, kind: ClipboardKind) -> ffi::Atom {
+        match kind {
+            ClipboardKind::Clipboard => self.clipboard,
+            ClipboardKind::Primary => self.primary,
+        }
+    }
+
+    /// The clipboard backed by the given selection.
+    pub fn kind(&self, selection: ffi::Atom) -> Option<ClipboardKind> {
+        if selection == self.clipboard {
+            Some(ClipboardKind::Clipboard)
+        } else if selection == self.primary {
+            Some(ClipboardKind::Primary)
+        } else {
+            None
+        }
+    }
+}
+
+/// A selection read which is waiting for the owner to reply.
+struct PendingRead {
+    selection: ffi::Atom,
+    state: ReadState,
+    /// The consumer of the data, or `None` if the data is delivered through an event.
+    consumer: Option<Box<dyn ClipboardConsumer>>,
+}
+
+enum ReadState {
+    /// Waiting for the list of the targets offered by the owner.
+    Targets(MimePicker),
+    /// Waiting for the data of the picked kind.
+    Data(MimeType),
+    /// Receiving the data of the picked kind in chunks, with the `INCR` mechanism. Each chunk
+    /// is announced with a `PropertyNotify`, see `handle_property_notify`.
+    Incr(MimeType, Vec<u8>),
+}
+
+/// The size of the header of a `ChangeProperty` request, which counts towards the maximum size
+/// of the requests.
+const CHANGE_PROPERTY_HEADER_SIZE: usize = 24;
+
+/// The time after which the requestor is assumed to have given up on an `INCR` transfer it
+/// stopped deleting the chunks of.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A selection sent in chunks with the `INCR` mechanism, each one once the requestor deleted
+/// the previous one.
+struct IncrTransfer {
+    /// The target the selection was converted to.
+    target: ffi::Atom,
+    /// The chunk to send first, which was taken to know that the data doesn't fit into a single
+    /// request.
+    pending: Option<Vec<u8>>,
+    /// The chunks written by the provider, see `ChunkWriter`.
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    /// The events of the requestor window selected before the transfer, which are restored
+    /// once it's finished.
+    event_mask: c_long,
+    /// The last time a chunk was sent.
+    updated: Instant,
+}
+
+/// A writer handing the data of a selection over in chunks, waiting for the previous chunk to be
+/// sent before it fills the next one.
+struct ChunkWriter {
+    chunk: Vec<u8>,
+    chunk_size: usize,
+    sender: SyncSender<io::Result<Vec<u8>>>,
+}
+
+impl ChunkWriter {
+    fn send(&mut self) -> io::Result<()> {
+        let chunk = mem::replace(&mut self.chunk, Vec::with_capacity(self.chunk_size));
+        self.sender
+            .send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "The transfer was abandoned"))
+    }
+
+    /// Hands the last chunk over, which is smaller than the others.
+    fn finish(&mut self) -> io::Result<()> {
+        if self.chunk.is_empty() {
+            Ok(())
+        } else {
+            self.send()
+        }
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.chunk_size - self.chunk.len());
+        self.chunk.extend_from_slice(&buf[..len]);
+        if self.chunk.len() == self.chunk_size {
+            self.send()?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The owner of the selections set by the application.
+///
+/// Selections are owned by a hidden window, so they outlive the windows of the
+/// application and are served for as long as the event loop is running.
+pub struct Clipboard {
+    xconn: Arc<XConnection>,
+    pub atoms: ClipboardAtoms,
+    /// The window owning the selections.
+    pub window: ffi::Window,
+    /// The provider of the content of each selection owned by `window`.
+    contents: Mutex<HashMap<ffi::Atom, Arc<dyn ClipboardProvider>>>,
+    /// The selection reads of each requestor window.
+    reads: Mutex<HashMap<ffi::Window, PendingRead>>,
+    /// The `INCR` transfers by the requestor window and the property they're sent through.
+    transfers: Mutex<HashMap<(ffi::Window, ffi::Atom), IncrTransfer>>,
+    /// The size of the largest chunk of data sent in a single request.
+    chunk_size: usize,
+    /// The first event of the XFixes extension, used to track the selection owners.
+    ///
+    /// `None` if the extension isn't available.
+    xfixes_event_base: Option<c_int>,
+}
+
+unsafe impl Send for Clipboard {}
+unsafe impl Sync for Clipboard {}
+
+impl Clipboard {
+    pub fn new(xconn: Arc<XConnection>, root: ffi::Window) -> Result<Self, XError> {
+        let atoms = ClipboardAtoms::new(&xconn)?;
+        let window =
+            unsafe { (xconn.xlib.XCreateSimpleWindow)(xconn.display, root, 0, 0, 1, 1, 0, 0, 0) };
+        xconn.check_errors()?;
+
+        let xfixes_event_base = xconn.xfixes.as_ref().and_then(|xfixes| unsafe {
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if (xfixes.XFixesQueryExtension)(xconn.display, &mut event_base, &mut error_base)
+                == ffi::False
+            {
+                return None;
+            }
+
+            let mask = ffi::XFixesSetSelectionOwnerNotifyMask
+                | ffi::XFixesSelectionWindowDestroyNotifyMask
+                | ffi::XFixesSelectionClientCloseNotifyMask;
+            for &selection in &[atoms.clipboard, atoms.primary] {
+                (xfixes.XFixesSelectSelectionInput)(xconn.display, window, selection, mask);
+            }
+            Some(event_base)
+        });
+        xconn.check_errors()?;
+
+        // The size is in units of 4 bytes.
+        let max_request_size = unsafe { (xconn.xlib.XMaxRequestSize)(xconn.display) } as usize;
+        let chunk_size = max_request_size * 4 - CHANGE_PROPERTY_HEADER_SIZE;
+
+        Ok(Clipboard {
+            xconn,
+            atoms,
+            window,
+            contents: Default::default(),
+            reads: Default::default(),
+            transfers: Default::default(),
+            chunk_size,
+            xfixes_event_base,
+        })
+    }
+
+    /// Takes the ownership of the selection backing the clipboard and serves the content of
+    /// `provider` for it.
+    pub fn set(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), XError> {
+        self.own_selection(self.atoms.selection(kind), provider)
+    }
+
+    /// Takes the ownership of the selection and serves the content of `provider` for it.
+    pub fn own_selection(
+        &self,
+        selection: ffi::Atom,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), XError> {
+        self.contents.lock().insert(selection, provider);
+
+        let owner = unsafe {
+            (self.xconn.xlib.XSetSelectionOwner)(
+                self.xconn.display,
+                selection,
+                self.window,
+                ffi::CurrentTime,
+            );
+            (self.xconn.xlib.XGetSelectionOwner)(self.xconn.display, selection)
+        };
+        self.xconn.check_errors()?;
+
+        if owner != self.window {
+            self.contents.lock().remove(&selection);
+            return Err(XError {
+                description: "Failed to take the selection ownership".into(),
+                error_code: 0,
+                request_code: 0,
+                minor_code: 0,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Replies to another client requesting the content of the selection we own.
+    pub fn handle_selection_request(&self, request: &ffi::XSelectionRequestEvent) {
+        // Obsolete clients may not provide the property, in which case the target
+        // is used as a property.
+        let property = if request.property == 0 {
+            request.target
+        } else {
+            request.property
+        };
+
+        let provider = self.contents.lock().get(&request.selection).cloned();
+        let converted = match provider {
+            Some(provider) if request.target == self.atoms.targets => {
+                let mut targets = vec![self.atoms.targets];
+                for mime_type in provider.mime_types() {
+                    targets.extend(self.mime_targets(&mime_type));
+                }
+                self.xconn
+                    .change_property(
+                        request.requestor,
+                        property,
+                        ffi::XA_ATOM,
+                        util::PropMode::Replace,
+                        &targets,
+                    )
+                    .queue();
+                true
+            }
+            Some(provider) => {
+                let mime_type = provider
+                    .mime_types()
+                    .into_iter()
+                    .find(|mime_type| self.mime_targets(mime_type).contains(&request.target));
+                match mime_type {
+                    Some(mime_type) => self.send_selection(
+                        request.requestor,
+                        property,
+                        request.target,
+                        provider,
+                        mime_type,
+                    ),
+                    None => false,
+                }
+            }
+            None => false,
+        };
+
+        let mut notify = ffi::XSelectionEvent {
+            type_: ffi::SelectionNotify,
+            serial: 0,
+            send_event: ffi::True,
+            display: self.xconn.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: if converted { property } else { 0 },
+            time: request.time,
+        };
+
+        unsafe {
+            (self.xconn.xlib.XSendEvent)(
+                self.xconn.display,
+                request.requestor,
+                ffi::False,
+                ffi::NoEventMask,
+                &mut notify as *mut ffi::XSelectionEvent as *mut ffi::XEvent,
+            );
+        }
+
+        if let Err(err) = self.xconn.flush_requests() {
+            warn!("Failed to reply to the selection request: {}", err);
+        }
+    }
+
+    /// Sends the data of the given kind to the requestor, in chunks with the `INCR` mechanism
+    /// if it doesn't fit into a single request. Returns whether the data is being sent.
+    ///
+    /// The provider writes the data on a separate thread, which is only a chunk ahead of the
+    /// requestor, so large data is never entirely in memory.
+    fn send_selection(
+        &self,
+        requestor: ffi::Window,
+        property: ffi::Atom,
+        target: ffi::Atom,
+        provider: Arc<dyn ClipboardProvider>,
+        mime_type: MimeType,
+    ) -> bool {
+        // The requestors which went away without finishing their transfers are forgotten.
+        self.transfers
+            .lock()
+            .retain(|_, transfer| transfer.updated.elapsed() < TRANSFER_TIMEOUT);
+
+        let (sender, chunks) = mpsc::sync_channel(1);
+        let chunk_size = self.chunk_size;
+        thread::spawn(move || {
+            let mut writer = ChunkWriter {
+                chunk: Vec::with_capacity(chunk_size),
+                chunk_size,
+                sender,
+            };
+            let result = provider
+                .write_to(&mime_type, &mut writer)
+                .and_then(|()| writer.finish());
+            if let Err(err) = result {
+                let _ = writer.sender.send(Err(err));
+            }
+        });
+
+        let chunk = match chunks.recv() {
+            Ok(Ok(chunk)) => chunk,
+            Ok(Err(err)) => {
+                warn!("Failed to write the selection: {}", err);
+                return false;
+            }
+            // Nothing was written.
+            Err(_) => Vec::new(),
+        };
+        // Only the last chunk is smaller than the others.
+        if chunk.len() < self.chunk_size {
+            self.xconn
+                .change_property(requestor, property, target, util::PropMode::Replace, &chunk)
+                .queue();
+            return true;
+        }
+
+        // The requestor starts the transfer by deleting the property, which holds a lower bound
+        // of the size of the data.
+        let event_mask = unsafe {
+            let mut attributes = mem::zeroed();
+            (self.xconn.xlib.XGetWindowAttributes)(self.xconn.display, requestor, &mut attributes);
+            (self.xconn.xlib.XSelectInput)(
+                self.xconn.display,
+                requestor,
+                attributes.your_event_mask | ffi::PropertyChangeMask,
+            );
+            attributes.your_event_mask
+        };
+        self.xconn
+            .change_property(
+                requestor,
+                property,
+                self.atoms.incr,
+                util::PropMode::Replace,
+                &[chunk.len() as c_ulong],
+            )
+            .queue();
+        let transfer = IncrTransfer {
+            target,
+            pending: Some(chunk),
+            chunks,
+            event_mask,
+            updated: Instant::now(),
+        };
+        self.transfers
+            .lock()
+            .insert((requestor, property), transfer);
+        true
+    }
+
+    /// Sends the next chunk of an `INCR` transfer, once the requestor deleted the previous one.
+    fn send_next_chunk(&self, requestor: ffi::Window, property: ffi::Atom) {
+        let mut transfers = self.transfers.lock();
+        let transfer = match transfers.get_mut(&(requestor, property)) {
+            Some(transfer) => transfer,
+            None => return,
+        };
+
+        let chunk = match transfer.pending.take() {
+            Some(chunk) => Ok(Ok(chunk)),
+            None => transfer.chunks.recv(),
+        };
+        match chunk {
+            Ok(Ok(chunk)) => {
+                self.xconn
+                    .change_property(
+                        requestor,
+                        property,
+                        transfer.target,
+                        util::PropMode::Replace,
+                        &chunk,
+                    )
+                    .queue();
+                transfer.updated = Instant::now();
+            }
+            // The transfer ends with an empty chunk.
+            Err(_) => {
+                self.xconn
+                    .change_property::<c_uchar>(
+                        requestor,
+                        property,
+                        transfer.target,
+                        util::PropMode::Replace,
+                        &[],
+                    )
+                    .queue();
+                let transfer = transfers.remove(&(requestor, property)).unwrap();
+                self.end_transfer(requestor, transfer);
+            }
+            // The requestor is left to time out, since there's no way to abort the transfer.
+            Ok(Err(err)) => {
+                warn!("Failed to write the selection: {}", err);
+                let transfer = transfers.remove(&(requestor, property)).unwrap();
+                self.end_transfer(requestor, transfer);
+            }
+        }
+
+        if let Err(err) = self.xconn.flush_requests() {
+            warn!("Failed to send the selection: {}", err);
+        }
+    }
+
+    /// Stops following the properties of the requestor window of the finished transfer.
+    fn end_transfer(&self, requestor: ffi::Window, transfer: IncrTransfer) {
+        unsafe {
+            (self.xconn.xlib.XSelectInput)(self.xconn.display, requestor, transfer.event_mask);
+        }
+    }
+
+    /// Requests the content of the clipboard on behalf of the `requestor` window.
+    ///
+    /// The targets offered by the owner are requested first, and the data of the target
+    /// chosen by `picker` once they arrive, see `handle_selection_notify`.
+    pub fn read(
+        &self,
+        requestor: ffi::Window,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), XError> {
+        let selection = self.atoms.selection(kind);
+        let read = PendingRead {
+            selection,
+            state: ReadState::Targets(picker),
+            consumer,
+        };
+        self.reads.lock().insert(requestor, read);
+        self.convert_selection(requestor, selection, self.atoms.targets)
+    }
+
+    /// Advances the read of the selection the `SelectionNotify` replies to.
+    ///
+    /// Returns the content along with the clipboard it was read from once the read is finished,
+    /// or `None` if the read is still in progress, the data was passed to the consumer of the
+    /// read, or the event isn't related to the clipboard.
+    pub fn handle_selection_notify(
+        &self,
+        notify: &ffi::XSelectionEvent,
+    ) -> Option<(ClipboardKind, Option<ClipboardMimedContent>)> {
+        let mut reads = self.reads.lock();
+        match reads.get(&notify.requestor) {
+            Some(read) if read.selection == notify.selection => (),
+            _ => return None,
+        }
+        let read = reads.remove(&notify.requestor).unwrap();
+        drop(reads);
+
+        let data = match self.advance_read(notify, read.selection, read.state) {
+            Ok(data) => data,
+            Err(state) => {
+                let read = PendingRead { state, ..read };
+                self.reads.lock().insert(notify.requestor, read);
+                return None;
+            }
+        };
+        self.finish_read(read.selection, read.consumer, data)
+    }
+
+    /// Advances the `INCR` transfers, sending the next chunk once the requestor deleted the
+    /// property, and receiving the next chunk once the owner replaced it.
+    ///
+    /// Returns the content along with the clipboard it was read from once the last chunk is
+    /// received, like `handle_selection_notify`.
+    pub fn handle_property_notify(
+        &self,
+        xev: &ffi::XPropertyEvent,
+    ) -> Option<(ClipboardKind, Option<ClipboardMimedContent>)> {
+        if xev.state == ffi::PropertyDelete {
+            self.send_next_chunk(xev.window, xev.atom);
+            return None;
+        }
+        if xev.atom != self.atoms.transfer || xev.state != ffi::PropertyNewValue {
+            return None;
+        }
+
+        let mut reads = self.reads.lock();
+        match reads.get(&xev.window) {
+            Some(PendingRead {
+                state: ReadState::Incr(..),
+                ..
+            }) => (),
+            _ => return None,
+        }
+        let read = reads.remove(&xev.window).unwrap();
+        drop(reads);
+
+        let (mime_type, mut data) = match read.state {
+            ReadState::Incr(mime_type, data) => (mime_type, data),
+            _ => unreachable!(),
+        };
+        // Deleting the property asks the owner for the next chunk.
+        let chunk = match self.take_property::<c_uchar>(xev.window, ffi::AnyPropertyType as _) {
+            Some(chunk) => chunk,
+            None => return self.finish_read(read.selection, read.consumer, None),
+        };
+        if let Err(err) = self.xconn.flush_requests() {
+            warn!("Failed to request the next chunk of the selection: {}", err);
+            return self.finish_read(read.selection, read.consumer, None);
+        }
+
+        // The transfer ends with an empty chunk.
+        if chunk.is_empty() {
+            return self.finish_read(read.selection, read.consumer, Some((mime_type, data)));
+        }
+        data.extend_from_slice(&chunk);
+        let read = PendingRead {
+            state: ReadState::Incr(mime_type, data),
+            ..read
+        };
+        self.reads.lock().insert(xev.window, read);
+        None
+    }
+
+    /// Passes the data of the finished read to its consumer, or returns it along with the
+    /// clipboard it was read from if it has none.
+    fn finish_read(
+        &self,
+        selection: ffi::Atom,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+        data: Option<(MimeType, Vec<u8>)>,
+    ) -> Option<(ClipboardKind, Option<ClipboardMimedContent>)> {
+        let kind = self.atoms.kind(selection)?;
+        match consumer {
+            Some(consumer) => {
+                clipboard::consume_bytes(consumer, data);
+                None
+            }
+            None => Some((
+                kind,
+                data.map(|(mime_type, data)| ClipboardMimedContent::from_bytes(mime_type, data)),
+            )),
+        }
+    }
+
+    /// Returns the data once the read is finished, or the next state of the read if it's
+    /// still in progress.
+    fn advance_read(
+        &self,
+        notify: &ffi::XSelectionEvent,
+        selection: ffi::Atom,
+        state: ReadState,
+    ) -> Result<Option<(MimeType, Vec<u8>)>, ReadState> {
+        // The owner refused to convert the selection, or there's no owner at all.
+        if notify.property == 0 {
+            return Ok(None);
+        }
+
+        match state {
+            ReadState::Targets(picker) => {
+                let targets = match self.take_property::<ffi::Atom>(notify.requestor, ffi::XA_ATOM)
+                {
+                    Some(targets) => targets,
+                    None => return Ok(None),
+                };
+                let mut offered = Vec::new();
+                for &target in &targets {
+                    if let Some(mime_type) = self
+                        .xconn
+                        .get_atom_name(target)
+                        .ok()
+                        .and_then(|name| MimeType::from_mime_type(&name))
+                    {
+                        if !offered.contains(&mime_type) {
+                            offered.push(mime_type);
+                        }
+                    }
+                }
+
+                let mime_type = match picker(&offered) {
+                    Some(mime_type) if offered.contains(&mime_type) => mime_type,
+                    _ => return Ok(None),
+                };
+                let target = self
+                    .mime_targets(&mime_type)
+                    .into_iter()
+                    .find(|target| targets.contains(target))
+                    .unwrap();
+
+                match self.convert_selection(notify.requestor, selection, target) {
+                    Ok(()) => Err(ReadState::Data(mime_type)),
+                    Err(err) => {
+                        warn!("Failed to request the selection: {}", err);
+                        Ok(None)
+                    }
+                }
+            }
+            ReadState::Data(mime_type) => {
+                // The data which doesn't fit into a single request is announced with a property
+                // of the `INCR` type, holding a lower bound of its size. Deleting it starts the
+                // transfer of the chunks.
+                if self
+                    .xconn
+                    .get_property::<c_ulong>(notify.requestor, self.atoms.transfer, self.atoms.incr)
+                    .is_ok()
+                {
+                    self.take_property::<c_ulong>(notify.requestor, self.atoms.incr);
+                    return match self.xconn.flush_requests() {
+                        Ok(()) => Err(ReadState::Incr(mime_type, Vec::new())),
+                        Err(err) => {
+                            warn!("Failed to start the transfer of the selection: {}", err);
+                            Ok(None)
+                        }
+                    };
+                }
+
+                let data = self
+                    .take_property::<c_uchar>(notify.requestor, ffi::AnyPropertyType as ffi::Atom);
+                Ok(data.map(|data| (mime_type, data)))
+            }
+            // The chunks are announced with `PropertyNotify`, not with this event.
+            ReadState::Incr(..) => Err(state),
+        }
+    }
+
+    /// Drops the content of the selection, since some other client took its ownership.
+    pub fn handle_selection_clear(&self, clear: &ffi::XSelectionClearEvent) {
+        self.contents.lock().remove(&clear.selection);
+    }
+
+    /// Stops serving the content of the selection, which is no longer needed.
+    pub fn release_selection(&self, selection: ffi::Atom) {
+        self.contents.lock().remove(&selection);
+    }
+
+    /// Returns the clipboard whose selection was taken by another client, or whose owner
+    /// went away, if the event is an XFixes selection notification.
+    pub fn handle_xfixes_event(&self, xev: &ffi::XEvent) -> Option<ClipboardKind> {
+        let event_base = self.xfixes_event_base?;
+        if xev.get_type() != event_base + ffi::XFixesSelectionNotify {
+            return None;
+        }
+
+        let notify =
+            unsafe { &*(xev as *const ffi::XEvent as *const ffi::XFixesSelectionNotifyEvent) };
+        // We've taken the selection ourselves.
+        if notify.owner == self.window {
+            return None;
+        }
+
+        self.atoms.kind(notify.selection)
+    }
+
+    /// The targets the data of the given kind is exchanged under, the preferred one first.
+    ///
+    /// The MIME types with a NUL byte, which can't be atom names, are skipped.
+    pub fn mime_targets(&self, mime_type: &MimeType) -> Vec<ffi::Atom> {
+        mime_type
+            .mime_types()
+            .into_iter()
+            .filter_map(|mime| std::ffi::CString::new(mime).ok())
+            .map(|name| self.xconn.get_atom(name))
+            .collect()
+    }
+
+    fn convert_selection(
+        &self,
+        requestor: ffi::Window,
+        selection: ffi::Atom,
+        target: ffi::Atom,
+    ) -> Result<(), XError> {
+        unsafe {
+            (self.xconn.xlib.XConvertSelection)(
+                self.xconn.display,
+                selection,
+                target,
+                self.atoms.transfer,
+                requestor,
+                ffi::CurrentTime,
+            );
+        }
+        self.xconn.flush_requests()
+    }
+
+    /// Reads the transferred selection from the `requestor` window and deletes it, so the
+    /// property can be used for the next transfer.
+    ///
+    fn take_property<T: util::Formattable>(
+        &self,
+        requestor: ffi::Window,
+        property_type: ffi::Atom,
+    ) -> Option<Vec<T>> {
+        let data = self
+            .xconn
+            .get_property(requestor, self.atoms.transfer, property_type);
+        unsafe {
+            (self.xconn.xlib.XDeleteProperty)(self.xconn.display, requestor, self.atoms.transfer);
+        }
+        data.ok()
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xconn.xlib.XDestroyWindow)(self.xconn.display, self.window);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_is_written_in_chunks() {
+        let (sender, chunks) = mpsc::sync_channel(4);
+        let mut writer = ChunkWriter {
+            chunk: Vec::new(),
+            chunk_size: 4,
+            sender,
+        };
+        writer.write_all(b"0123456789").unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        let chunks: Vec<_> = chunks.iter().map(Result::unwrap).collect();
+        assert_eq!(chunks, [&b"0123"[..], b"4567", b"89"]);
+    }
+
+    #[test]
+    fn no_empty_chunk_is_written_last() {
+        let (sender, chunks) = mpsc::sync_channel(4);
+        let mut writer = ChunkWriter {
+            chunk: Vec::new(),
+            chunk_size: 4,
+            sender,
+        };
+        writer.write_all(b"01234567").unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        assert_eq!(chunks.iter().count(), 2);
+    }
+
+    #[test]
+    fn abandoned_transfer_fails_the_write() {
+        let (sender, chunks) = mpsc::sync_channel(4);
+        let mut writer = ChunkWriter {
+            chunk: Vec::new(),
+            chunk_size: 4,
+            sender,
+        };
+        drop(chunks);
+
+        let err = writer.write_all(b"01234567").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+}
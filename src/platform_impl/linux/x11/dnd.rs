@@ -6,9 +6,14 @@ use std::{
     sync::Arc,
 };
 
+use parking_lot::Mutex;
 use percent_encoding::percent_decode;
 
-use super::{ffi, util, XConnection, XError};
+use super::{clipboard::Clipboard, ffi, util, XConnection, XError};
+use crate::{
+    clipboard::{ClipboardMimedContent, ClipboardProvider, MimeType},
+    dnd::{DndAction, DndActions, DragData},
+};
 
 #[derive(Debug)]
 pub struct DndAtoms {
@@ -18,12 +23,13 @@ pub struct DndAtoms {
     pub drop: ffi::Atom,
     pub position: ffi::Atom,
     pub status: ffi::Atom,
-    pub action_private: ffi::Atom,
     pub selection: ffi::Atom,
     pub finished: ffi::Atom,
     pub type_list: ffi::Atom,
-    pub uri_list: ffi::Atom,
     pub none: ffi::Atom,
+    pub action_copy: ffi::Atom,
+    pub action_move: ffi::Atom,
+    pub action_link: ffi::Atom,
 }
 
 impl DndAtoms {
@@ -35,12 +41,13 @@ impl DndAtoms {
             b"XdndDrop\0".as_ptr() as *mut c_char,
             b"XdndPosition\0".as_ptr() as *mut c_char,
             b"XdndStatus\0".as_ptr() as *mut c_char,
-            b"XdndActionPrivate\0".as_ptr() as *mut c_char,
             b"XdndSelection\0".as_ptr() as *mut c_char,
             b"XdndFinished\0".as_ptr() as *mut c_char,
             b"XdndTypeList\0".as_ptr() as *mut c_char,
-            b"text/uri-list\0".as_ptr() as *mut c_char,
             b"None\0".as_ptr() as *mut c_char,
+            b"XdndActionCopy\0".as_ptr() as *mut c_char,
+            b"XdndActionMove\0".as_ptr() as *mut c_char,
+            b"XdndActionLink\0".as_ptr() as *mut c_char,
         ];
         let atoms = unsafe { xconn.get_atoms(&names) }?;
         Ok(DndAtoms {
@@ -50,12 +57,13 @@ impl DndAtoms {
             drop: atoms[3],
             position: atoms[4],
             status: atoms[5],
-            action_private: atoms[6],
-            selection: atoms[7],
-            finished: atoms[8],
-            type_list: atoms[9],
-            uri_list: atoms[10],
-            none: atoms[11],
+            selection: atoms[6],
+            finished: atoms[7],
+            type_list: atoms[8],
+            none: atoms[9],
+            action_copy: atoms[10],
+            action_move: atoms[11],
+            action_link: atoms[12],
         })
     }
 }
@@ -95,8 +103,17 @@ pub struct Dnd {
     pub type_list: Option<Vec<c_ulong>>,
     // Populated by XdndPosition event handler
     pub source_window: Option<c_ulong>,
+    /// The kind of data requested from the source, along with the target it's requested under.
+    pub requested: Option<(MimeType, ffi::Atom)>,
+    /// The latest position of the cursor, in root window coordinates.
+    pub position: (i32, i32),
+    /// The action requested by the source.
+    pub action: DndAction,
     // Populated by SelectionNotify event handler (triggered by XdndPosition event handler)
     pub result: Option<Result<Vec<PathBuf>, DndDataParseError>>,
+    pub content: Option<ClipboardMimedContent>,
+    /// The window the data is dragged from, if it's dragged from one of our windows.
+    pub local_source: Option<ffi::Window>,
 }
 
 impl Dnd {
@@ -108,7 +125,12 @@ impl Dnd {
             version: None,
             type_list: None,
             source_window: None,
+            requested: None,
+            position: (0, 0),
+            action: DndAction::Copy,
             result: None,
+            content: None,
+            local_source: None,
         })
     }
 
@@ -116,7 +138,53 @@ impl Dnd {
         self.version = None;
         self.type_list = None;
         self.source_window = None;
+        self.requested = None;
+        self.position = (0, 0);
+        self.action = DndAction::Copy;
         self.result = None;
+        self.content = None;
+        self.local_source = None;
+    }
+
+    /// Picks the kind of data to request among the offered types, see `WindowEvent::Drop`.
+    pub fn pick_target(&self) -> Option<(MimeType, ffi::Atom)> {
+        let targets = self
+            .type_list
+            .as_ref()?
+            .iter()
+            .filter_map(|&atom| {
+                let name = self.xconn.get_atom_name(atom).ok()?;
+                Some((atom, name))
+            })
+            .collect::<Vec<_>>();
+        let offered = MimeType::from_mime_types(targets.iter().map(|(_, name)| name.as_str()));
+        let mime_type = MimeType::preferred_drop_mime_type(&offered)?;
+        let target = mime_type.mime_types().into_iter().find_map(|mime| {
+            targets
+                .iter()
+                .find(|(_, name)| name == mime)
+                .map(|(atom, _)| *atom)
+        })?;
+        Some((mime_type, target))
+    }
+
+    /// The action requested with the given atom, which is a copy for the unknown ones.
+    pub fn atom_action(&self, atom: ffi::Atom) -> DndAction {
+        if atom == self.atoms.action_move {
+            DndAction::Move
+        } else if atom == self.atoms.action_link {
+            DndAction::Link
+        } else {
+            DndAction::Copy
+        }
+    }
+
+    fn action_atom(&self, action: DndAction) -> ffi::Atom {
+        match action {
+            DndAction::Copy => self.atoms.action_copy,
+            DndAction::Move => self.atoms.action_move,
+            DndAction::Link => self.atoms.action_link,
+        }
     }
 
     pub unsafe fn send_status(
@@ -126,7 +194,7 @@ impl Dnd {
         state: DndState,
     ) -> Result<(), XError> {
         let (accepted, action) = match state {
-            DndState::Accepted => (1, self.atoms.action_private as c_long),
+            DndState::Accepted => (1, self.action_atom(self.action) as c_long),
             DndState::Rejected => (0, self.atoms.none as c_long),
         };
         self.xconn
@@ -147,7 +215,7 @@ impl Dnd {
         state: DndState,
     ) -> Result<(), XError> {
         let (accepted, action) = match state {
-            DndState::Accepted => (1, self.atoms.action_private as c_long),
+            DndState::Accepted => (1, self.action_atom(self.action) as c_long),
             DndState::Rejected => (0, self.atoms.none as c_long),
         };
         self.xconn
@@ -169,11 +237,11 @@ impl Dnd {
             .get_property(source_window, self.atoms.type_list, ffi::XA_ATOM)
     }
 
-    pub unsafe fn convert_selection(&self, window: c_ulong, time: c_ulong) {
+    pub unsafe fn convert_selection(&self, window: c_ulong, target: ffi::Atom, time: c_ulong) {
         (self.xconn.xlib.XConvertSelection)(
             self.xconn.display,
             self.atoms.selection,
-            self.atoms.uri_list,
+            target,
             self.atoms.selection,
             window,
             time,
@@ -184,8 +252,11 @@ impl Dnd {
         &self,
         window: c_ulong,
     ) -> Result<Vec<c_uchar>, util::GetPropertyError> {
-        self.xconn
-            .get_property(window, self.atoms.selection, self.atoms.uri_list)
+        self.xconn.get_property(
+            window,
+            self.atoms.selection,
+            ffi::AnyPropertyType as ffi::Atom,
+        )
     }
 
     pub fn parse_data(&self, data: &mut Vec<c_uchar>) -> Result<Vec<PathBuf>, DndDataParseError> {
@@ -217,3 +288,364 @@ impl Dnd {
         }
     }
 }
+
+/// What the drop targets tell about the drag started by the application.
+pub enum DragProgress {
+    /// The target under the cursor accepts the data with the given action, which differs from
+    /// the previous one.
+    Accepted(DndAction),
+    /// The drag is over, and the target took the data with the given action, if any.
+    Finished {
+        target: ffi::Window,
+        action: Option<DndAction>,
+    },
+}
+
+/// The latest version of the XDND protocol we support as a drag source.
+const XDND_VERSION: c_long = 5;
+
+/// A drag started by the application.
+struct Drag {
+    /// The window the drag was started from.
+    origin: ffi::Window,
+    /// The targets the data is offered under.
+    targets: Vec<ffi::Atom>,
+    /// The provider of the data, which is read directly when dropped into our windows.
+    provider: Arc<dyn ClipboardProvider>,
+    /// The action requested from the drop targets.
+    action: ffi::Atom,
+    /// The window under the cursor which takes part in the protocol, if any.
+    target: Option<DragTarget>,
+    /// The latest cursor position along with its time, which is sent once the target replied
+    /// to the previous one.
+    pending_position: Option<(c_long, c_long, ffi::Time)>,
+    /// Whether the data was dropped, and the target is yet to finish the transfer.
+    dropped: bool,
+}
+
+struct DragTarget {
+    window: ffi::Window,
+    version: c_long,
+    /// Whether the target is yet to reply to the latest `XdndPosition`.
+    awaiting_status: bool,
+    /// The action the target accepts the data with, if it accepts the data at all.
+    accepted_action: Option<DndAction>,
+}
+
+/// The source side of the XDND protocol, for the drags started by the application.
+///
+/// The hidden clipboard window acts as the source window, and the data is served through the
+/// `XdndSelection` owned by it. The cursor is tracked through the implicit grab of the button
+/// which started the drag, so the drop happens once the button is released.
+pub struct DragSource {
+    xconn: Arc<XConnection>,
+    atoms: DndAtoms,
+    clipboard: Arc<Clipboard>,
+    root: ffi::Window,
+    drag: Mutex<Option<Drag>>,
+}
+
+impl DragSource {
+    pub fn new(
+        xconn: Arc<XConnection>,
+        clipboard: Arc<Clipboard>,
+        root: ffi::Window,
+    ) -> Result<Self, XError> {
+        let atoms = DndAtoms::new(&xconn)?;
+        Ok(DragSource {
+            xconn,
+            atoms,
+            clipboard,
+            root,
+            drag: Default::default(),
+        })
+    }
+
+    /// The window the drop targets reply to.
+    pub fn window(&self) -> ffi::Window {
+        self.clipboard.window
+    }
+
+    /// Starts dragging the data from the `origin` window.
+    pub fn start(
+        &self,
+        origin: ffi::Window,
+        data: DragData,
+        actions: DndActions,
+    ) -> Result<(), XError> {
+        let error = |description: &str| XError {
+            description: description.into(),
+            error_code: 0,
+            request_code: 0,
+            minor_code: 0,
+        };
+
+        let mut drag = self.drag.lock();
+        if drag.is_some() {
+            return Err(error("A drag is already in progress"));
+        }
+
+        // There's no way to negotiate the action before the drop, so the action which loses the
+        // least is requested.
+        let action = if actions.contains(DndActions::COPY) {
+            self.atoms.action_copy
+        } else if actions.contains(DndActions::MOVE) {
+            self.atoms.action_move
+        } else if actions.contains(DndActions::LINK) {
+            self.atoms.action_link
+        } else {
+            return Err(error("No drag and drop action is allowed"));
+        };
+
+        let mut targets = Vec::new();
+        for mime_type in data.provider.mime_types() {
+            targets.extend(self.clipboard.mime_targets(&mime_type));
+        }
+
+        let provider = Arc::clone(&data.provider);
+        self.clipboard
+            .own_selection(self.atoms.selection, data.provider)?;
+        // Only three targets fit into `XdndEnter`, the rest are listed in a property.
+        if targets.len() > 3 {
+            self.xconn
+                .change_property(
+                    self.window(),
+                    self.atoms.type_list,
+                    ffi::XA_ATOM,
+                    util::PropMode::Replace,
+                    &targets,
+                )
+                .flush()?;
+        }
+
+        *drag = Some(Drag {
+            origin,
+            targets,
+            provider,
+            action,
+            target: None,
+            pending_position: None,
+            dropped: false,
+        });
+        Ok(())
+    }
+
+    /// The window the ongoing drag was started from, along with the provider of the dragged
+    /// data.
+    pub fn local_drag(&self) -> Option<(ffi::Window, Arc<dyn ClipboardProvider>)> {
+        self.drag
+            .lock()
+            .as_ref()
+            .map(|drag| (drag.origin, Arc::clone(&drag.provider)))
+    }
+
+    /// Follows the cursor, which moved to the given root coordinates.
+    ///
+    /// Returns the window the drag was started from if the drag is ongoing.
+    pub fn handle_motion(&self, x: f64, y: f64, time: ffi::Time) -> Option<ffi::Window> {
+        let mut drag = self.drag.lock();
+        let drag = match drag.as_mut() {
+            Some(drag) if !drag.dropped => drag,
+            _ => return None,
+        };
+
+        let (x, y) = (x as c_long, y as c_long);
+        let window = self.find_target(x, y);
+        if drag.target.as_ref().map(|target| target.window) != window.map(|(window, _)| window) {
+            if let Some(target) = drag.target.take() {
+                self.send(&target, self.atoms.leave, [0; 4]);
+            }
+
+            if let Some((window, version)) = window {
+                let target = DragTarget {
+                    window,
+                    version,
+                    awaiting_status: false,
+                    accepted_action: None,
+                };
+                let more_types = (drag.targets.len() > 3) as c_long;
+                let types = &drag.targets;
+                let type_at = |index: usize| types.get(index).cloned().unwrap_or(0) as c_long;
+                self.send(
+                    &target,
+                    self.atoms.enter,
+                    [
+                        (version << 24) | more_types,
+                        type_at(0),
+                        type_at(1),
+                        type_at(2),
+                    ],
+                );
+                drag.target = Some(target);
+            }
+        }
+
+        drag.pending_position = Some((x, y, time));
+        self.send_pending_position(drag);
+        Some(drag.origin)
+    }
+
+    /// Drops the data once the button holding the drag is released.
+    ///
+    /// Returns the window the drag was started from if the drag is over right away, since
+    /// there's no target accepting the data.
+    pub fn handle_release(&self, time: ffi::Time) -> Option<ffi::Window> {
+        let mut drag_lock = self.drag.lock();
+        let drag = match drag_lock.as_mut() {
+            Some(drag) if !drag.dropped => drag,
+            _ => return None,
+        };
+
+        match drag.target.as_ref() {
+            Some(target) if target.accepted_action.is_some() => {
+                self.send(target, self.atoms.drop, [0, time as c_long, 0, 0]);
+                drag.dropped = true;
+                None
+            }
+            target => {
+                if let Some(target) = target {
+                    self.send(target, self.atoms.leave, [0; 4]);
+                }
+                let drag = drag_lock.take().unwrap();
+                self.clipboard.release_selection(self.atoms.selection);
+                Some(drag.origin)
+            }
+        }
+    }
+
+    /// Handles the replies of the drop targets.
+    ///
+    /// Returns the window the drag was started from along with what the target told about the
+    /// drag.
+    pub fn handle_client_message(
+        &self,
+        message: &ffi::XClientMessageEvent,
+    ) -> Option<(ffi::Window, DragProgress)> {
+        let mut drag_lock = self.drag.lock();
+        let drag = drag_lock.as_mut()?;
+        let target = drag.target.as_mut()?;
+        if message.data.get_long(0) as ffi::Window != target.window {
+            return None;
+        }
+
+        if message.message_type == self.atoms.status {
+            let accepted = message.data.get_long(1) & 1 == 1;
+            target.awaiting_status = false;
+            let accepted_action = if accepted {
+                let action = message.data.get_long(4) as ffi::Atom;
+                // Targets not telling the action accept the requested one.
+                let requested = drag.action;
+                self.action(action).or_else(|| self.action(requested))
+            } else {
+                None
+            };
+            let changed = accepted_action != target.accepted_action;
+            target.accepted_action = accepted_action;
+
+            if !drag.dropped {
+                self.send_pending_position(drag);
+            }
+            match accepted_action {
+                Some(action) if changed => Some((drag.origin, DragProgress::Accepted(action))),
+                _ => None,
+            }
+        } else if message.message_type == self.atoms.finished && drag.dropped {
+            // Only the targets of version 5 tell whether the drop succeeded, and which action
+            // was taken.
+            let action = if target.version < 5 {
+                target.accepted_action
+            } else if message.data.get_long(1) & 1 == 1 {
+                let action = message.data.get_long(2) as ffi::Atom;
+                self.action(action).or(target.accepted_action)
+            } else {
+                None
+            };
+
+            let target = target.window;
+            let drag = drag_lock.take().unwrap();
+            self.clipboard.release_selection(self.atoms.selection);
+            Some((drag.origin, DragProgress::Finished { target, action }))
+        } else {
+            None
+        }
+    }
+
+    fn send_pending_position(&self, drag: &mut Drag) {
+        let target = match drag.target.as_mut() {
+            Some(target) if !target.awaiting_status => target,
+            _ => return,
+        };
+
+        if let Some((x, y, time)) = drag.pending_position.take() {
+            let position = (x << 16) | (y & 0xffff);
+            self.send(
+                target,
+                self.atoms.position,
+                [0, position, time as c_long, drag.action as c_long],
+            );
+            target.awaiting_status = true;
+        }
+    }
+
+    /// Sends the message to the target, with the source window prepended to the data.
+    fn send(&self, target: &DragTarget, message_type: ffi::Atom, data: [c_long; 4]) {
+        let data = [self.window() as c_long, data[0], data[1], data[2], data[3]];
+        let result = self
+            .xconn
+            .send_client_msg(target.window, target.window, message_type, None, data)
+            .flush();
+        if let Err(err) = result {
+            warn!("Failed to send the drag and drop message: {}", err);
+        }
+    }
+
+    /// Finds the window under the given root coordinates taking part in the protocol, along
+    /// with the protocol version to use.
+    fn find_target(&self, x: c_long, y: c_long) -> Option<(ffi::Window, c_long)> {
+        let mut window = self.root;
+        loop {
+            let mut child = 0;
+            let (mut child_x, mut child_y) = (0, 0);
+            unsafe {
+                (self.xconn.xlib.XTranslateCoordinates)(
+                    self.xconn.display,
+                    self.root,
+                    window,
+                    x as c_int,
+                    y as c_int,
+                    &mut child_x,
+                    &mut child_y,
+                    &mut child,
+                );
+            }
+            if child == 0 {
+                return None;
+            }
+            window = child;
+
+            let version =
+                self.xconn
+                    .get_property::<c_ulong>(window, self.atoms.aware, ffi::XA_ATOM);
+            if let Some(&version) = version.ok().as_ref().and_then(|version| version.first()) {
+                // The protocol is only usable since the version 3.
+                return if version >= 3 {
+                    Some((window, (version as c_long).min(XDND_VERSION)))
+                } else {
+                    None
+                };
+            }
+        }
+    }
+
+    fn action(&self, atom: ffi::Atom) -> Option<DndAction> {
+        if atom == self.atoms.action_copy {
+            Some(DndAction::Copy)
+        } else if atom == self.atoms.action_move {
+            Some(DndAction::Move)
+        } else if atom == self.atoms.action_link {
+            Some(DndAction::Link)
+        } else {
+            None
+        }
+    }
+}
@@ -1,23 +1,38 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, slice, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    slice,
+    sync::Arc,
+    time::Duration,
+};
 
-use libc::{c_char, c_int, c_long, c_uint, c_ulong};
+use libc::{c_char, c_int, c_long, c_short, c_uint, c_ulong};
 
 use parking_lot::MutexGuard;
 
 use super::{
-    events, ffi, get_xtarget, mkdid, mkwid, monitor, util, Device, DeviceId, DeviceInfo, Dnd,
-    DndState, GenericEventCookie, ImeReceiver, ScrollOrientation, UnownedWindow, WindowId,
-    XExtension,
+    events, ffi, get_xtarget, keymap::Keymap, mkdid, mkwid, monitor, util, Device, DeviceId,
+    DeviceInfo, Dnd, DndState, DragProgress, GenericEventCookie, ImeReceiver, ScrollOrientation,
+    UnownedWindow, WindowId, XExtension,
 };
 
 use util::modifiers::{ModifierKeyState, ModifierKeymap};
 
 use crate::{
+    clipboard::{ClipboardMimedContent, MimeType},
     dpi::{PhysicalPosition, PhysicalSize},
     event::{
-        DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState, TouchPhase, WindowEvent,
+        DeviceEvent, ElementState, Event, GesturePhase, KeyboardInput, ModifiersState, PenPhase,
+        PresentationFlags, TouchPhase, WindowEvent,
     },
     event_loop::EventLoopWindowTarget as RootELW,
+    keymap::KeyboardLayout,
+    monitor::{MonitorEvent, MonitorHandle as RootMonitorHandle},
+    platform_impl::{
+        platform::{compose::Compose, instant_from_clock, instant_from_event_time},
+        MonitorHandle as PlatformMonitorHandle,
+    },
 };
 
 /// The X11 documentation states: "Keycodes lie in the inclusive range [8,255]".
@@ -27,14 +42,27 @@ pub(super) struct EventProcessor<T: 'static> {
     pub(super) dnd: Dnd,
     pub(super) ime_receiver: ImeReceiver,
     pub(super) randr_event_offset: c_int,
+    pub(super) xkb_event_type: Option<c_int>,
+    // The layout reported last, to send `KeyboardLayoutChanged` only when it actually changes
+    pub(super) keyboard_layout: Option<KeyboardLayout>,
     pub(super) devices: RefCell<HashMap<DeviceId, Device>>,
     pub(super) xi2ext: XExtension,
+    // The major opcode of the Present extension, if the server has it
+    pub(super) present_opcode: Option<c_int>,
     pub(super) target: Rc<RootELW<T>>,
     pub(super) mod_keymap: ModifierKeymap,
     pub(super) device_mod_state: ModifierKeyState,
+    // The modifiers held on each of the slave keyboards, tracked from their raw events
+    pub(super) keyboard_mod_states: HashMap<c_int, ModifierKeyState>,
+    // The keys held down, to tell the repeated presses apart
+    pub(super) held_keys: HashSet<ffi::KeyCode>,
+    // The compose sequence in progress, if libxkbcommon is available
+    pub(super) compose: Option<Compose>,
     // Number of touch events currently in progress
     pub(super) num_touch: u32,
     pub(super) first_touch: Option<u64>,
+    // The scale of the pinch in progress at its previous event, relative to its start
+    pub(super) pinch_scale: f64,
     // Currently focused window belonging to this process
     pub(super) active_window: Option<ffi::Window>,
 }
@@ -74,6 +102,44 @@ impl<T: 'static> EventProcessor<T> {
         result
     }
 
+    /// The `Presented` event of the completion of a presentation, unless it wasn't the one of a
+    /// redraw or it was skipped.
+    fn presented_event(
+        &self,
+        xev: &ffi::XPresentCompleteNotifyEvent,
+    ) -> Option<WindowEvent<'static>> {
+        // The completions of `XPresentNotifyMSC` aren't frames.
+        if c_int::from(xev.kind) != ffi::PresentCompleteKindPixmap
+            || c_int::from(xev.mode) == ffi::PresentCompleteModeSkip
+        {
+            return None;
+        }
+        let refresh_rate = self.with_window(xev.window, |window| {
+            window
+                .shared_state
+                .lock()
+                .last_monitor
+                .refresh_rate_millihertz()
+        })?;
+
+        // The timestamps are the ones of the vertical blanks, in microseconds.
+        let time = instant_from_clock(libc::CLOCK_MONOTONIC, Duration::from_micros(xev.ust))?;
+        let refresh = refresh_rate
+            .filter(|&refresh_rate| refresh_rate > 0)
+            .map(|refresh_rate| Duration::from_nanos(1_000_000_000_000 / u64::from(refresh_rate)));
+        // Only the flips are known to be timed by the display hardware.
+        let flags = if c_int::from(xev.mode) == ffi::PresentCompleteModeFlip {
+            PresentationFlags::all()
+        } else {
+            PresentationFlags::empty()
+        };
+        Some(WindowEvent::Presented {
+            time,
+            refresh,
+            flags,
+        })
+    }
+
     fn window_exists(&self, window_id: ffi::Window) -> bool {
         self.with_window(window_id, |_| ()).is_some()
     }
@@ -117,6 +183,25 @@ impl<T: 'static> EventProcessor<T> {
         F: FnMut(Event<'_, T>),
     {
         let wt = get_xtarget(&self.target);
+
+        // The compose sequences are followed before the input method filters their keys out.
+        if xev.get_type() == ffi::KeyPress {
+            let xkev: &mut ffi::XKeyEvent = xev.as_mut();
+            if xkev.keycode != 0 && xkev.window != wt.root {
+                let keysym = wt.xconn.lookup_keysym(xkev);
+                let state = self
+                    .compose
+                    .as_mut()
+                    .and_then(|compose| compose.feed(keysym as u32));
+                if let Some(state) = state {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(xkev.window),
+                        event: WindowEvent::ComposeChanged(state),
+                    });
+                }
+            }
+        }
+
         // XFilterEvent tells us when an event has been discarded by the input method.
         // Specifically, this involves all of the KeyPress events in compose/pre-edit sequences,
         // along with an extra copy of the KeyRelease events. This also prevents backspace and
@@ -170,6 +255,9 @@ impl<T: 'static> EventProcessor<T> {
 
                     self.mod_keymap.reset_from_x_connection(&wt.xconn);
                     self.device_mod_state.update_keymap(&self.mod_keymap);
+                    for keyboard_mod_state in self.keyboard_mod_states.values_mut() {
+                        keyboard_mod_state.update_keymap(&self.mod_keymap);
+                    }
                 }
             }
 
@@ -179,7 +267,31 @@ impl<T: 'static> EventProcessor<T> {
                 let window = client_msg.window;
                 let window_id = mkwid(window);
 
-                if client_msg.data.get_long(0) as ffi::Atom == wt.wm_delete_window {
+                if window == wt.drag_source.window() {
+                    let event = match wt.drag_source.handle_client_message(client_msg) {
+                        Some((origin, DragProgress::Accepted(action))) => {
+                            Some((origin, WindowEvent::DragEntered { action }))
+                        }
+                        Some((origin, DragProgress::Finished { target, action })) => {
+                            self.with_window(origin, |window| window.end_drag());
+                            let event = match action {
+                                Some(action) => WindowEvent::DragFinished {
+                                    action,
+                                    outside: self.with_window(target, |_| ()).is_none(),
+                                },
+                                None => WindowEvent::DragCancelled,
+                            };
+                            Some((origin, event))
+                        }
+                        None => None,
+                    };
+                    if let Some((origin, event)) = event {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(origin),
+                            event,
+                        });
+                    }
+                } else if client_msg.data.get_long(0) as ffi::Atom == wt.wm_delete_window {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::CloseRequested,
@@ -212,7 +324,7 @@ impl<T: 'static> EventProcessor<T> {
                         self.dnd.type_list = Some(more_types);
                     }
                 } else if client_msg.message_type == self.dnd.atoms.position {
-                    // This event occurs every time the mouse moves while a file's being dragged
+                    // This event occurs every time the mouse moves while something's being dragged
                     // over our window. We emit HoveredFile in response; while the macOS backend
                     // does that upon a drag entering, XDND doesn't have access to the actual drop
                     // data until this event. For parity with other platforms, we only emit
@@ -222,31 +334,51 @@ impl<T: 'static> EventProcessor<T> {
 
                     let source_window = client_msg.data.get_long(0) as c_ulong;
 
-                    // Equivalent to `(x << shift) | y`
-                    // where `shift = mem::size_of::<c_short>() * 8`
+                    // Equivalent to `(x << 16) | y`.
                     // Note that coordinates are in "desktop space", not "window space"
                     // (in X11 parlance, they're root window coordinates)
-                    //let packed_coordinates = client_msg.data.get_long(2);
-                    //let shift = mem::size_of::<libc::c_short>() * 8;
-                    //let x = packed_coordinates >> shift;
-                    //let y = packed_coordinates & !(x << shift);
+                    let packed_coordinates = client_msg.data.get_long(2);
+                    let x = (packed_coordinates >> 16) as c_short as i32;
+                    let y = packed_coordinates as c_short as i32;
+                    self.dnd.position = (x, y);
 
                     // By our own state flow, `version` should never be `None` at this point.
                     let version = self.dnd.version.unwrap_or(5);
 
-                    // Action is specified in versions 2 and up, though we don't need it anyway.
-                    //let action = client_msg.data.get_long(4);
+                    // Action is specified in versions 2 and up.
+                    if version >= 2 {
+                        let action = client_msg.data.get_long(4) as ffi::Atom;
+                        self.dnd.action = self.dnd.atom_action(action);
+                    }
 
-                    let accepted = if let Some(ref type_list) = self.dnd.type_list {
-                        type_list.contains(&self.dnd.atoms.uri_list)
-                    } else {
-                        false
-                    };
+                    if self.dnd.requested.is_none() {
+                        self.dnd.requested = self.dnd.pick_target();
+                    }
 
-                    if accepted {
+                    if let Some((_, target)) = self.dnd.requested {
                         self.dnd.source_window = Some(source_window);
+                        // The data dragged from our windows is read straight from its provider.
+                        let local_drag = if source_window == wt.drag_source.window() {
+                            wt.drag_source.local_drag()
+                        } else {
+                            None
+                        };
+                        if let (None, Some((origin, provider))) = (&self.dnd.content, local_drag) {
+                            let content = crate::dnd::local_drop_content(&*provider);
+                            if let Some(ClipboardMimedContent::FileList(paths)) = &content {
+                                for path in paths {
+                                    callback(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::HoveredFile(path.clone()),
+                                    });
+                                }
+                                self.dnd.result = Some(Ok(paths.clone()));
+                            }
+                            self.dnd.content = content;
+                            self.dnd.local_source = Some(origin);
+                        }
                         unsafe {
-                            if self.dnd.result.is_none() {
+                            if self.dnd.content.is_none() && self.dnd.local_source.is_none() {
                                 let time = if version >= 1 {
                                     client_msg.data.get_long(3) as c_ulong
                                 } else {
@@ -254,7 +386,7 @@ impl<T: 'static> EventProcessor<T> {
                                     ffi::CurrentTime
                                 };
                                 // This results in the `SelectionNotify` event below
-                                self.dnd.convert_selection(window, time);
+                                self.dnd.convert_selection(window, target, time);
                             }
                             self.dnd
                                 .send_status(window, source_window, DndState::Accepted)
@@ -279,6 +411,26 @@ impl<T: 'static> EventProcessor<T> {
                                 });
                             }
                         }
+                        if let Some(content) = self.dnd.content.take() {
+                            // The position is reported relative to the root window.
+                            let (x, y) = self.dnd.position;
+                            let position = match wt.xconn.translate_coords(window, wt.root) {
+                                Ok(origin) => (x - origin.x_rel_root, y - origin.y_rel_root),
+                                Err(_) => (x, y),
+                            };
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::Drop {
+                                    content,
+                                    position: PhysicalPosition::new(
+                                        position.0 as f64,
+                                        position.1 as f64,
+                                    ),
+                                    action: self.dnd.action,
+                                    source: self.dnd.local_source.map(mkwid),
+                                },
+                            });
+                        }
                         (source_window, DndState::Accepted)
                     } else {
                         // `source_window` won't be part of our DND state if we already rejected the drop in our
@@ -309,22 +461,51 @@ impl<T: 'static> EventProcessor<T> {
 
                 if xsel.property == self.dnd.atoms.selection {
                     let mut result = None;
+                    let mut content = None;
 
                     // This is where we receive data from drag and drop
-                    if let Ok(mut data) = unsafe { self.dnd.read_data(window) } {
-                        let parse_result = self.dnd.parse_data(&mut data);
-                        if let Ok(ref path_list) = parse_result {
-                            for path in path_list {
-                                callback(Event::WindowEvent {
-                                    window_id,
-                                    event: WindowEvent::HoveredFile(path.clone()),
-                                });
+                    if let (Ok(mut data), Some((mime_type, _))) = (
+                        unsafe { self.dnd.read_data(window) },
+                        self.dnd.requested.clone(),
+                    ) {
+                        if mime_type == MimeType::FileList {
+                            let parse_result = self.dnd.parse_data(&mut data);
+                            if let Ok(ref path_list) = parse_result {
+                                for path in path_list {
+                                    callback(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::HoveredFile(path.clone()),
+                                    });
+                                }
                             }
+                            result = Some(parse_result);
                         }
-                        result = Some(parse_result);
+                        content = Some(ClipboardMimedContent::from_bytes(mime_type, data));
                     }
 
                     self.dnd.result = result;
+                    self.dnd.content = content;
+                } else if let Some((kind, content)) = wt.clipboard.handle_selection_notify(xsel) {
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::ClipboardData { kind, content },
+                    });
+                }
+            }
+
+            ffi::SelectionRequest => {
+                let request: &ffi::XSelectionRequestEvent = xev.as_ref();
+
+                if request.owner == wt.clipboard.window {
+                    wt.clipboard.handle_selection_request(request);
+                }
+            }
+
+            ffi::SelectionClear => {
+                let clear: &ffi::XSelectionClearEvent = xev.as_ref();
+
+                if clear.window == wt.clipboard.window {
+                    wt.clipboard.handle_selection_clear(clear);
                 }
             }
 
@@ -394,6 +575,7 @@ impl<T: 'static> EventProcessor<T> {
                         if moved {
                             // Temporarily unlock shared state to prevent deadlock
                             MutexGuard::unlocked(&mut shared_state_lock, || {
+                                window.refresh_pointer_barriers();
                                 callback(Event::WindowEvent {
                                     window_id,
                                     event: WindowEvent::Moved(outer.into()),
@@ -413,6 +595,8 @@ impl<T: 'static> EventProcessor<T> {
                             .unwrap_or_else(|| (xev.width as u32, xev.height as u32));
 
                         let last_scale_factor = shared_state_lock.last_monitor.scale_factor;
+                        let last_refresh_rate =
+                            shared_state_lock.last_monitor.refresh_rate_millihertz();
                         let new_scale_factor = {
                             let window_rect = util::AaRect::new(new_outer_position, new_inner_size);
                             let monitor = wt.xconn.get_monitor_for_window(Some(window_rect));
@@ -446,6 +630,8 @@ impl<T: 'static> EventProcessor<T> {
                                         new_inner_size: &mut new_inner_size,
                                     },
                                 });
+                                // The cursors of the themes set for the window follow its scale.
+                                window.refresh_cursor();
                             });
 
                             if new_inner_size != old_inner_size {
@@ -459,6 +645,19 @@ impl<T: 'static> EventProcessor<T> {
                                 resized = true;
                             }
                         }
+
+                        let new_refresh_rate =
+                            shared_state_lock.last_monitor.refresh_rate_millihertz();
+                        if let Some(refresh_rate) =
+                            new_refresh_rate.filter(|_| new_refresh_rate != last_refresh_rate)
+                        {
+                            MutexGuard::unlocked(&mut shared_state_lock, || {
+                                callback(Event::WindowEvent {
+                                    window_id,
+                                    event: WindowEvent::RefreshRateChanged(refresh_rate),
+                                });
+                            });
+                        }
                     }
 
                     // This is a hack to ensure that the DPI adjusted resize is actually applied on all WMs. KWin
@@ -474,15 +673,32 @@ impl<T: 'static> EventProcessor<T> {
                         }
                     }
 
-                    if resized {
-                        // Drop the shared state lock to prevent deadlock
-                        drop(shared_state_lock);
+                    // The bounds change along with the monitor and the frame of the window.
+                    let new_bounds =
+                        window
+                            .recommended_bounds_inner(&shared_state_lock)
+                            .filter(|&bounds| {
+                                util::maybe_change(
+                                    &mut shared_state_lock.recommended_bounds,
+                                    bounds,
+                                )
+                            });
+
+                    // Drop the shared state lock to prevent deadlock
+                    drop(shared_state_lock);
 
+                    if resized {
                         callback(Event::WindowEvent {
                             window_id,
                             event: WindowEvent::Resized(new_inner_size.into()),
                         });
                     }
+                    if let Some(bounds) = new_bounds {
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::RecommendedBounds(bounds.into()),
+                        });
+                    }
                 }
             }
 
@@ -509,6 +725,7 @@ impl<T: 'static> EventProcessor<T> {
 
                 // In the event that the window's been destroyed without being dropped first, we
                 // cleanup again here.
+                self.with_window(window, |window| window.set_destroyed());
                 wt.windows.borrow_mut().remove(&WindowId(window));
 
                 // Since all XIM stuff needs to happen from the same thread, we destroy the input
@@ -528,7 +745,66 @@ impl<T: 'static> EventProcessor<T> {
                 let xev: &ffi::XVisibilityEvent = xev.as_ref();
                 let xwindow = xev.window;
 
-                self.with_window(xwindow, |window| window.visibility_notify());
+                let occluded = xev.state == ffi::VisibilityFullyObscured;
+                let changed = self.with_window(xwindow, |window| {
+                    window.visibility_notify();
+                    window.update_occlusion(occluded)
+                });
+                if changed == Some(true) {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(xwindow),
+                        event: WindowEvent::Occluded(occluded),
+                    });
+                }
+            }
+
+            ffi::PropertyNotify => {
+                let xev: &ffi::XPropertyEvent = xev.as_ref();
+                let xwindow = xev.window;
+
+                let constraints_atom =
+                    unsafe { wt.xconn.get_atom_unchecked(b"_GTK_EDGE_CONSTRAINTS\0") };
+                let desktop_atom = unsafe { wt.xconn.get_atom_unchecked(b"_NET_WM_DESKTOP\0") };
+                if let Some((kind, content)) = wt.clipboard.handle_property_notify(xev) {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(xwindow),
+                        event: WindowEvent::ClipboardData { kind, content },
+                    });
+                } else if xev.atom == constraints_atom {
+                    let edges = self
+                        .with_window(xwindow, |window| window.update_tiled_edges())
+                        .flatten();
+                    if let Some(edges) = edges {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(xwindow),
+                            event: WindowEvent::TiledStateChanged(edges),
+                        });
+                    }
+                } else if xev.atom == desktop_atom {
+                    let workspace = self
+                        .with_window(xwindow, |window| window.update_workspace())
+                        .flatten();
+                    if let Some(workspace) = workspace {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(xwindow),
+                            event: WindowEvent::WorkspaceChanged(workspace),
+                        });
+                    }
+                }
+            }
+
+            ffi::UnmapNotify => {
+                let xev: &ffi::XUnmapEvent = xev.as_ref();
+                let xwindow = xev.window;
+
+                // The windows are unmapped once they're hidden or minimized.
+                let changed = self.with_window(xwindow, |window| window.update_occlusion(true));
+                if changed == Some(true) {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(xwindow),
+                        event: WindowEvent::Occluded(true),
+                    });
+                }
             }
 
             ffi::Expose => {
@@ -568,6 +844,31 @@ impl<T: 'static> EventProcessor<T> {
                 // When a compose sequence or IME pre-edit is finished, it ends in a KeyPress with
                 // a keycode of 0.
                 if keycode != 0 {
+                    // The repeats are presses without releases, as the auto repeat is detectable.
+                    let repeat = if state == Pressed {
+                        !self.held_keys.insert(keycode as ffi::KeyCode)
+                    } else {
+                        self.held_keys.remove(&(keycode as ffi::KeyCode));
+                        false
+                    };
+                    // The presses of the global hotkeys are reported to the root window.
+                    if window == wt.root {
+                        if state == Pressed && !repeat {
+                            let hotkey = wt.hotkeys.borrow().find(keycode, xkev.state);
+                            if let Some(id) = hotkey {
+                                callback(Event::GlobalHotkey(id));
+                            }
+                        }
+                        return;
+                    }
+                    if repeat
+                        && !self
+                            .with_window(window, |window| window.key_repeat())
+                            .unwrap_or(true)
+                    {
+                        return;
+                    }
+
                     let scancode = keycode - KEYCODE_OFFSET as u32;
                     let keysym = wt.xconn.lookup_keysym(xkev);
                     let virtual_keycode = events::keysym_to_element(keysym as c_uint);
@@ -586,6 +887,7 @@ impl<T: 'static> EventProcessor<T> {
                             device_id,
                             input: KeyboardInput {
                                 state,
+                                repeat,
                                 scancode,
                                 virtual_keycode,
                                 modifiers,
@@ -619,18 +921,34 @@ impl<T: 'static> EventProcessor<T> {
                     return;
                 };
                 let xev = &guard.cookie;
+                if self.present_opcode == Some(xev.extension) {
+                    if xev.evtype == ffi::PresentCompleteNotify {
+                        let xev: &ffi::XPresentCompleteNotifyEvent =
+                            unsafe { &*(xev.data as *const _) };
+                        if c_int::from(xev.kind) == ffi::PresentCompleteKindNotifyMSC {
+                            // The vertical blank the held redraw waited for.
+                            self.with_window(xev.window, |window| window.release_redraw());
+                        } else if let Some(event) = self.presented_event(xev) {
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(xev.window),
+                                event,
+                            });
+                        }
+                    }
+                    return;
+                }
                 if self.xi2ext.opcode != xev.extension {
                     return;
                 }
 
                 use crate::event::{
                     ElementState::{Pressed, Released},
-                    MouseButton::{Left, Middle, Other, Right},
+                    MouseButton::{Back, Forward, Left, Middle, Other, Right},
                     MouseScrollDelta::LineDelta,
                     Touch,
                     WindowEvent::{
-                        AxisMotion, CursorEntered, CursorLeft, CursorMoved, Focused, MouseInput,
-                        MouseWheel,
+                        AxisMotion, CursorEntered, CursorLeft, CursorMoved, DragCancelled,
+                        DragMoved, Focused, MouseInput, MouseWheel,
                     },
                 };
 
@@ -698,6 +1016,7 @@ impl<T: 'static> EventProcessor<T> {
                                                 _ => unreachable!(),
                                             },
                                             phase: TouchPhase::Moved,
+                                            source: None,
                                             modifiers,
                                         },
                                     });
@@ -709,11 +1028,58 @@ impl<T: 'static> EventProcessor<T> {
                                 event: MouseInput {
                                     device_id,
                                     state,
-                                    button: Other(x as u16),
+                                    button: match x {
+                                        8 => Back,
+                                        9 => Forward,
+                                        // Buttons 4 to 7 scroll, so button 10 is the sixth one.
+                                        x => Other(x.saturating_sub(4) as u16),
+                                    },
                                     modifiers,
                                 },
                             }),
                         }
+
+                        // The pens press the first button with their tip, and the second one with
+                        // their barrel button.
+                        let pen_event = self
+                            .devices
+                            .borrow_mut()
+                            .get_mut(&DeviceId(xev.sourceid))
+                            .and_then(|device| device.pen.as_mut())
+                            .and_then(|pen| {
+                                pen.update(&xev.valuators);
+                                let phase = match (xev.detail, state) {
+                                    (1, Pressed) => PenPhase::Down,
+                                    (1, Released) => PenPhase::Up,
+                                    (2, state) => {
+                                        pen.barrel_button = state == Pressed;
+                                        PenPhase::Moved
+                                    }
+                                    _ => return None,
+                                };
+                                let location = PhysicalPosition::new(xev.event_x, xev.event_y);
+                                pen.window = Some((xev.event, location));
+                                let time = instant_from_event_time(xev.time as u32);
+                                let tool_id = xev.sourceid as u64;
+                                Some(pen.event(device_id, phase, location, time, tool_id))
+                            });
+                        if let Some(pen_event) = pen_event {
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::Pen(pen_event),
+                            });
+                        }
+
+                        // The drag is held by the implicit grab of the button, which is over.
+                        if state == Released && !(4..=7).contains(&xev.detail) {
+                            if let Some(origin) = wt.drag_source.handle_release(xev.time) {
+                                self.with_window(origin, |window| window.end_drag());
+                                callback(Event::WindowEvent {
+                                    window_id: mkwid(origin),
+                                    event: DragCancelled,
+                                });
+                            }
+                        }
                     }
                     ffi::XI_Motion => {
                         let xev: &ffi::XIDeviceEvent = unsafe { &*(xev.data as *const _) };
@@ -721,11 +1087,32 @@ impl<T: 'static> EventProcessor<T> {
                         let window_id = mkwid(xev.event);
                         let new_cursor_pos = (xev.event_x, xev.event_y);
 
+                        // The cursor is grabbed by the window the drag was started from.
+                        let drag_origin = wt
+                            .drag_source
+                            .handle_motion(xev.root_x, xev.root_y, xev.time);
+                        if drag_origin == Some(xev.event) {
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: DragMoved {
+                                    position: PhysicalPosition::new(xev.event_x, xev.event_y),
+                                },
+                            });
+                        }
+
                         let modifiers = ModifiersState::from_x11(&xev.mods);
                         update_modifiers!(modifiers, None);
 
                         let cursor_moved = self.with_window(xev.event, |window| {
                             let mut shared_state_lock = window.shared_state.lock();
+                            if let Some((x, y)) = shared_state_lock.locked_cursor_pos {
+                                // The locked cursor is put back in place, and doesn't move.
+                                drop(shared_state_lock);
+                                if new_cursor_pos != (x, y) {
+                                    let _ = window.set_cursor_position_physical(x as i32, y as i32);
+                                }
+                                return false;
+                            }
                             util::maybe_change(&mut shared_state_lock.cursor_pos, new_cursor_pos)
                         });
                         if cursor_moved == Some(true) {
@@ -783,6 +1170,7 @@ impl<T: 'static> EventProcessor<T> {
                                                     }
                                                 },
                                                 phase: TouchPhase::Moved,
+                                                source: None,
                                                 modifiers,
                                             },
                                         });
@@ -799,6 +1187,27 @@ impl<T: 'static> EventProcessor<T> {
                                     value = unsafe { value.offset(1) };
                                 }
                             }
+
+                            if let Some(pen) = physical_device.pen.as_mut() {
+                                pen.update(&xev.valuators);
+                                let location = PhysicalPosition::new(xev.event_x, xev.event_y);
+                                let time = instant_from_event_time(xev.time as u32);
+                                let tool_id = xev.sourceid as u64;
+                                pen.window = Some((xev.event, location));
+                                let mut phases = vec![PenPhase::Moved];
+                                if pen.entering {
+                                    pen.entering = false;
+                                    phases.insert(0, PenPhase::Entered);
+                                }
+                                for phase in phases {
+                                    events.push(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::Pen(
+                                            pen.event(device_id, phase, location, time, tool_id),
+                                        ),
+                                    });
+                                }
+                            }
                         }
                         for event in events {
                             callback(event);
@@ -829,14 +1238,23 @@ impl<T: 'static> EventProcessor<T> {
                             }
                         }
 
-                        if self.window_exists(xev.event) {
+                        // The grabs cross into their window wherever the pointer is, and back out
+                        // once they're over, without the pointer moving.
+                        let grab_crossing =
+                            xev.mode == ffi::XINotifyGrab || xev.mode == ffi::XINotifyUngrab;
+                        if self.window_exists(xev.event) && !grab_crossing {
+                            let position = PhysicalPosition::new(xev.event_x, xev.event_y);
+
+                            // The device is the master pointer, of which the multi-pointer
+                            // systems have several.
                             callback(Event::WindowEvent {
                                 window_id,
-                                event: CursorEntered { device_id },
+                                event: CursorEntered {
+                                    device_id,
+                                    position,
+                                },
                             });
 
-                            let position = PhysicalPosition::new(xev.event_x, xev.event_y);
-
                             // The mods field on this event isn't actually populated, so query the
                             // pointer device. In the future, we can likely remove this round-trip by
                             // relying on `Xkb` for modifier values.
@@ -866,11 +1284,14 @@ impl<T: 'static> EventProcessor<T> {
                         // Leave, FocusIn, and FocusOut can be received by a window that's already
                         // been destroyed, which the user presumably doesn't want to deal with.
                         let window_closed = !self.window_exists(xev.event);
-                        if !window_closed {
+                        let grab_crossing =
+                            xev.mode == ffi::XINotifyGrab || xev.mode == ffi::XINotifyUngrab;
+                        if !window_closed && !grab_crossing {
                             callback(Event::WindowEvent {
                                 window_id: mkwid(xev.event),
                                 event: CursorLeft {
                                     device_id: mkdid(xev.deviceid),
+                                    position: PhysicalPosition::new(xev.event_x, xev.event_y),
                                 },
                             });
                         }
@@ -890,6 +1311,12 @@ impl<T: 'static> EventProcessor<T> {
                         if self.active_window != Some(xev.event) {
                             self.active_window = Some(xev.event);
 
+                            if let Some(Err(err)) = self
+                                .with_window(xev.event, |window| window.update_keyboard_grab(true))
+                            {
+                                warn!("Failed to inhibit the system shortcuts: {}", err);
+                            }
+
                             let window_id = mkwid(xev.event);
                             let position = PhysicalPosition::new(xev.event_x, xev.event_y);
 
@@ -905,6 +1332,19 @@ impl<T: 'static> EventProcessor<T> {
                                 });
                             }
 
+                            for (&keyboard, keyboard_mod_state) in &self.keyboard_mod_states {
+                                let modifiers = keyboard_mod_state.modifiers();
+                                if !modifiers.is_empty() {
+                                    callback(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::DeviceModifiersChanged {
+                                            device_id: mkdid(keyboard),
+                                            modifiers,
+                                        },
+                                    });
+                                }
+                            }
+
                             // The deviceid for this event is for a keyboard instead of a pointer,
                             // so we have to do a little extra work.
                             let pointer_id = self
@@ -930,6 +1370,7 @@ impl<T: 'static> EventProcessor<T> {
                                 ElementState::Pressed,
                                 &self.mod_keymap,
                                 &mut self.device_mod_state,
+                                &mut self.held_keys,
                                 &mut callback,
                             );
                         }
@@ -947,6 +1388,19 @@ impl<T: 'static> EventProcessor<T> {
                         if self.active_window.take() == Some(xev.event) {
                             let window_id = mkwid(xev.event);
 
+                            if let Some(Err(err)) = self
+                                .with_window(xev.event, |window| window.update_keyboard_grab(false))
+                            {
+                                warn!("Failed to release the keyboard grab: {}", err);
+                            }
+
+                            if let Some(state) = self.compose.as_mut().and_then(Compose::cancel) {
+                                callback(Event::WindowEvent {
+                                    window_id,
+                                    event: WindowEvent::ComposeChanged(state),
+                                });
+                            }
+
                             // Issue key release events for all pressed keys
                             Self::handle_pressed_keys(
                                 &wt,
@@ -954,6 +1408,7 @@ impl<T: 'static> EventProcessor<T> {
                                 ElementState::Released,
                                 &self.mod_keymap,
                                 &mut self.device_mod_state,
+                                &mut self.held_keys,
                                 &mut callback,
                             );
 
@@ -962,6 +1417,20 @@ impl<T: 'static> EventProcessor<T> {
                                 event: WindowEvent::ModifiersChanged(ModifiersState::empty()),
                             });
 
+                            // The raw events keep tracking the keyboards, so only the window is
+                            // told that their modifiers are gone.
+                            for (&keyboard, keyboard_mod_state) in &self.keyboard_mod_states {
+                                if !keyboard_mod_state.modifiers().is_empty() {
+                                    callback(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::DeviceModifiersChanged {
+                                            device_id: mkdid(keyboard),
+                                            modifiers: ModifiersState::empty(),
+                                        },
+                                    });
+                                }
+                            }
+
                             callback(Event::WindowEvent {
                                 window_id,
                                 event: Focused(false),
@@ -1004,13 +1473,92 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id: mkdid(xev.deviceid),
                                     phase,
                                     location,
+                                    time: instant_from_event_time(xev.time as u32),
                                     force: None, // TODO
                                     id,
+                                    palm: false,
                                 }),
                             })
                         }
                     }
 
+                    ffi::XI_GesturePinchBegin
+                    | ffi::XI_GesturePinchUpdate
+                    | ffi::XI_GesturePinchEnd => {
+                        let xev: &ffi::XIGesturePinchEvent = unsafe { &*(xev.data as *const _) };
+                        let window_id = mkwid(xev.event);
+                        let device_id = mkdid(xev.deviceid);
+                        let (scale_delta, rotation, phase) = match xev.evtype {
+                            ffi::XI_GesturePinchBegin => {
+                                self.pinch_scale = 1.;
+                                (0., 0., GesturePhase::Started)
+                            }
+                            ffi::XI_GesturePinchUpdate => {
+                                let scale_delta = xev.scale - self.pinch_scale;
+                                self.pinch_scale = xev.scale;
+                                // The rotation is clockwise.
+                                (scale_delta, -xev.delta_angle, GesturePhase::Updated)
+                            }
+                            ffi::XI_GesturePinchEnd
+                                if xev.flags & ffi::XIGesturePinchEventCancelled != 0 =>
+                            {
+                                (0., 0., GesturePhase::Cancelled)
+                            }
+                            ffi::XI_GesturePinchEnd => (0., 0., GesturePhase::Ended),
+                            _ => unreachable!(),
+                        };
+                        if self.window_exists(xev.event) {
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::PinchGesture {
+                                    device_id,
+                                    delta: scale_delta,
+                                    phase,
+                                },
+                            });
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::RotationGesture {
+                                    device_id,
+                                    delta: rotation as f32,
+                                    phase,
+                                },
+                            });
+                        }
+                    }
+
+                    ffi::XI_GestureSwipeBegin
+                    | ffi::XI_GestureSwipeUpdate
+                    | ffi::XI_GestureSwipeEnd => {
+                        let xev: &ffi::XIGestureSwipeEvent = unsafe { &*(xev.data as *const _) };
+                        let window_id = mkwid(xev.event);
+                        let (delta, phase) = match xev.evtype {
+                            ffi::XI_GestureSwipeBegin => ((0., 0.), GesturePhase::Started),
+                            ffi::XI_GestureSwipeUpdate => {
+                                ((xev.delta_x, xev.delta_y), GesturePhase::Updated)
+                            }
+                            ffi::XI_GestureSwipeEnd
+                                if xev.flags & ffi::XIGestureSwipeEventCancelled != 0 =>
+                            {
+                                ((0., 0.), GesturePhase::Cancelled)
+                            }
+                            ffi::XI_GestureSwipeEnd => ((0., 0.), GesturePhase::Ended),
+                            _ => unreachable!(),
+                        };
+                        if self.window_exists(xev.event) {
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::SwipeGesture {
+                                    device_id: mkdid(xev.deviceid),
+                                    delta: PhysicalPosition::new(delta.0, delta.1),
+                                    // The detail is the number of touches.
+                                    fingers: xev.detail as u32,
+                                    phase,
+                                },
+                            });
+                        }
+                    }
+
                     ffi::XI_RawButtonPress | ffi::XI_RawButtonRelease => {
                         let xev: &ffi::XIRawEvent = unsafe { &*(xev.data as *const _) };
                         if xev.flags & ffi::XIPointerEmulated == 0 {
@@ -1105,6 +1653,7 @@ impl<T: 'static> EventProcessor<T> {
                                 scancode: scancode as u32,
                                 virtual_keycode,
                                 state,
+                                repeat: false,
                                 modifiers,
                             }),
                         });
@@ -1128,6 +1677,59 @@ impl<T: 'static> EventProcessor<T> {
                                     });
                                 }
                             }
+
+                            let keyboard_mod_state =
+                                self.keyboard_mod_states.entry(xev.sourceid).or_default();
+                            let modifiers = keyboard_mod_state.modifiers();
+                            keyboard_mod_state.key_event(state, keycode as ffi::KeyCode, modifier);
+                            let new_modifiers = keyboard_mod_state.modifiers();
+
+                            if modifiers != new_modifiers {
+                                if let Some(window_id) = self.active_window {
+                                    callback(Event::WindowEvent {
+                                        window_id: mkwid(window_id),
+                                        event: WindowEvent::DeviceModifiersChanged {
+                                            device_id,
+                                            modifiers: new_modifiers,
+                                        },
+                                    });
+                                }
+                            }
+                        }
+                    }
+
+                    ffi::XI_PropertyEvent => {
+                        let xev: &ffi::XIPropertyEvent = unsafe { &*(xev.data as *const _) };
+                        // The Wacom driver tells the tools coming in and out of proximity there.
+                        let serial_ids =
+                            unsafe { wt.xconn.get_atom_unchecked(b"Wacom Serial IDs\0") };
+                        if xev.property != serial_ids {
+                            return;
+                        }
+                        let ids = match wt.xconn.get_device_property_u32(xev.deviceid, serial_ids) {
+                            Some(ids) => ids,
+                            None => return,
+                        };
+                        let left = self
+                            .devices
+                            .borrow_mut()
+                            .get_mut(&DeviceId(xev.deviceid))
+                            .and_then(|device| {
+                                let pen = device.pen.as_mut()?;
+                                let (window, location) = pen.update_serial_ids(&ids)?;
+                                // The pens send their events through their master pointer.
+                                let device_id = mkdid(device.attachment);
+                                let time = instant_from_event_time(xev.time as u32);
+                                let tool_id = xev.deviceid as u64;
+                                let phase = PenPhase::Left;
+                                let event = pen.event(device_id, phase, location, time, tool_id);
+                                Some((window, event))
+                            });
+                        if let Some((window, event)) = left {
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event: WindowEvent::Pen(event),
+                            });
                         }
                     }
 
@@ -1138,18 +1740,27 @@ impl<T: 'static> EventProcessor<T> {
                         {
                             if 0 != info.flags & (ffi::XISlaveAdded | ffi::XIMasterAdded) {
                                 self.init_device(info.deviceid);
-                                callback(Event::DeviceEvent {
-                                    device_id: mkdid(info.deviceid),
-                                    event: DeviceEvent::Added,
-                                });
+                                // The device may already be gone again.
+                                let device =
+                                    DeviceInfo::get(&wt.xconn, info.deviceid).and_then(|device| {
+                                        Some(Device::input_device(&wt.xconn, device.first()?))
+                                    });
+                                if let Some(device) = device {
+                                    callback(Event::DeviceEvent {
+                                        device_id: mkdid(info.deviceid),
+                                        event: DeviceEvent::Added(device),
+                                    });
+                                }
                             } else if 0 != info.flags & (ffi::XISlaveRemoved | ffi::XIMasterRemoved)
                             {
                                 callback(Event::DeviceEvent {
                                     device_id: mkdid(info.deviceid),
                                     event: DeviceEvent::Removed,
                                 });
+                                wt.hid_reports.remove(info.deviceid);
                                 let mut devices = self.devices.borrow_mut();
                                 devices.remove(&DeviceId(info.deviceid));
+                                self.keyboard_mod_states.remove(&info.deviceid);
                             }
                         }
                     }
@@ -1158,12 +1769,53 @@ impl<T: 'static> EventProcessor<T> {
                 }
             }
             _ => {
-                if event_type == self.randr_event_offset {
-                    // In the future, it would be quite easy to emit monitor hotplug events.
+                if let Some(kind) = wt.clipboard.handle_xfixes_event(xev) {
+                    // The clipboard is shared by all the windows, so notify each of them.
+                    let window_ids = wt.windows.borrow().keys().copied().collect::<Vec<_>>();
+                    for window_id in window_ids {
+                        callback(Event::WindowEvent {
+                            window_id: crate::window::WindowId(
+                                crate::platform_impl::platform::WindowId::X(window_id),
+                            ),
+                            event: WindowEvent::ClipboardChanged { kind },
+                        });
+                    }
+                } else if Some(event_type) == self.xkb_event_type {
+                    let xkb_event = unsafe { &*(xev as *const _ as *const ffi::XkbAnyEvent) };
+                    let layout_may_change = match xkb_event.xkb_type {
+                        ffi::XkbStateNotify => {
+                            let state =
+                                unsafe { &*(xev as *const _ as *const ffi::XkbStateNotifyEvent) };
+                            state.changed & ffi::XkbGroupStateMask as c_uint != 0
+                        }
+                        ffi::XkbNewKeyboardNotify | ffi::XkbMapNotify => true,
+                        _ => false,
+                    };
+                    if layout_may_change {
+                        let layout = Keymap::new(&wt.xconn)
+                            .ok()
+                            .and_then(|keymap| keymap.current_layout())
+                            .filter(|layout| self.keyboard_layout.as_ref() != Some(layout));
+                        if let Some(layout) = layout {
+                            self.keyboard_layout = Some(layout.clone());
+                            // The layout is shared by all the windows, so notify each of them.
+                            let window_ids =
+                                wt.windows.borrow().keys().copied().collect::<Vec<_>>();
+                            for window_id in window_ids {
+                                callback(Event::WindowEvent {
+                                    window_id: mkwid(window_id.0),
+                                    event: WindowEvent::KeyboardLayoutChanged(layout.clone()),
+                                });
+                            }
+                        }
+                    }
+                } else if event_type == self.randr_event_offset
+                    || event_type == self.randr_event_offset + ffi::RRNotify
+                {
                     let prev_list = monitor::invalidate_cached_monitor_list();
                     if let Some(prev_list) = prev_list {
                         let new_list = wt.xconn.available_monitors();
-                        for new_monitor in new_list {
+                        for new_monitor in &new_list {
                             prev_list
                                 .iter()
                                 .find(|prev_monitor| prev_monitor.name == new_monitor.name)
@@ -1214,8 +1866,70 @@ impl<T: 'static> EventProcessor<T> {
                                             }
                                         }
                                     }
+                                    if new_monitor.refresh_rate_millihertz()
+                                        != prev_monitor.refresh_rate_millihertz()
+                                    {
+                                        let refresh_rate = new_monitor.refresh_rate_millihertz();
+                                        for (window_id, window) in wt.windows.borrow().iter() {
+                                            let window = match window.upgrade() {
+                                                Some(window) => window,
+                                                None => continue,
+                                            };
+                                            // The window keeps the monitor it's on up to date.
+                                            let mut shared_state = window.shared_state.lock();
+                                            if shared_state.last_monitor.name != new_monitor.name {
+                                                continue;
+                                            }
+                                            shared_state.last_monitor = new_monitor.clone();
+                                            drop(shared_state);
+
+                                            if let Some(refresh_rate) = refresh_rate {
+                                                callback(Event::WindowEvent {
+                                                    window_id: mkwid(window_id.0),
+                                                    event: WindowEvent::RefreshRateChanged(
+                                                        refresh_rate,
+                                                    ),
+                                                });
+                                            }
+                                        }
+                                    }
+                                    if new_monitor.color_capabilities
+                                        != prev_monitor.color_capabilities
+                                    {
+                                        callback(Event::MonitorEvent {
+                                            monitor: RootMonitorHandle {
+                                                inner: PlatformMonitorHandle::X(
+                                                    new_monitor.clone(),
+                                                ),
+                                            },
+                                            event: MonitorEvent::ColorChanged(
+                                                new_monitor.color_capabilities(),
+                                            ),
+                                        });
+                                    }
                                 });
                         }
+
+                        // The CRTCs are reused by the other outputs, so only their names tell
+                        // the monitors apart.
+                        let missing_from =
+                            |list: &[monitor::MonitorHandle],
+                             monitors: &[monitor::MonitorHandle]| {
+                                monitors
+                                    .iter()
+                                    .filter(|monitor| {
+                                        !list.iter().any(|other| other.name == monitor.name)
+                                    })
+                                    .map(|monitor| RootMonitorHandle {
+                                        inner: PlatformMonitorHandle::X(monitor.clone()),
+                                    })
+                                    .collect::<Vec<_>>()
+                            };
+                        let added = missing_from(&prev_list, &new_list);
+                        let removed = missing_from(&new_list, &prev_list);
+                        if !added.is_empty() || !removed.is_empty() {
+                            callback(Event::MonitorsChanged { added, removed });
+                        }
                     }
                 }
             }
@@ -1235,12 +1949,16 @@ impl<T: 'static> EventProcessor<T> {
         state: ElementState,
         mod_keymap: &ModifierKeymap,
         device_mod_state: &mut ModifierKeyState,
+        held_keys: &mut HashSet<ffi::KeyCode>,
         callback: &mut F,
     ) where
         F: FnMut(Event<'_, T>),
     {
         let device_id = mkdid(util::VIRTUAL_CORE_KEYBOARD);
         let modifiers = device_mod_state.modifiers();
+        if state == ElementState::Released {
+            held_keys.clear();
+        }
 
         // Update modifiers state and emit key events based on which keys are currently pressed.
         for keycode in wt
@@ -1253,6 +1971,9 @@ impl<T: 'static> EventProcessor<T> {
             let keysym = wt.xconn.keycode_to_keysym(keycode);
             let virtual_keycode = events::keysym_to_element(keysym as c_uint);
 
+            if state == ElementState::Pressed {
+                held_keys.insert(keycode as ffi::KeyCode);
+            }
             if let Some(modifier) = mod_keymap.get_modifier(keycode as ffi::KeyCode) {
                 device_mod_state.key_event(
                     ElementState::Pressed,
@@ -1269,6 +1990,7 @@ impl<T: 'static> EventProcessor<T> {
                     input: KeyboardInput {
                         scancode,
                         state,
+                        repeat: false,
                         virtual_keycode,
                         modifiers,
                     },
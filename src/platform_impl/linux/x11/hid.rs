@@ -0,0 +1,260 @@
+//! The raw reports of the HID devices, read from their `hidraw` nodes, which are found from the
+//! evdev nodes the server reads the devices from.
+//!
+//! The nodes are gathered in an epoll, so the event loop only has to wait for its descriptor.
+
+use std::{
+    cell::{Cell, RefCell},
+    os::raw::c_int,
+};
+
+#[cfg(target_os = "linux")]
+use std::{
+    ffi::CString,
+    fs, io,
+    os::unix::{ffi::OsStrExt, io::RawFd},
+    path::{Path, PathBuf},
+};
+
+use super::XConnection;
+use crate::error::{ExternalError, NotSupportedError};
+
+#[cfg(target_os = "linux")]
+use crate::platform_impl::OsError;
+
+/// The size of the largest reports, as `HID_MAX_BUFFER_SIZE`.
+#[cfg(target_os = "linux")]
+const MAX_REPORT_SIZE: usize = 16384;
+
+#[derive(Default)]
+pub struct HidReports {
+    /// Whether the epoll was registered with the poll of the event loop.
+    registered: Cell<bool>,
+    /// The epoll is kept once it's created, so the poll it is registered with keeps it.
+    epoll: RefCell<Option<Epoll>>,
+}
+
+impl HidReports {
+    #[cfg(target_os = "linux")]
+    pub fn set_enabled(
+        &self,
+        xconn: &XConnection,
+        device: c_int,
+        enabled: bool,
+    ) -> Result<(), ExternalError> {
+        let mut epoll = self.epoll.borrow_mut();
+        if !enabled {
+            if let Some(epoll) = epoll.as_mut() {
+                epoll.remove(device);
+            }
+            return Ok(());
+        }
+        if let Some(epoll) = epoll.as_ref() {
+            if epoll.devices.iter().any(|&(enabled, _)| enabled == device) {
+                return Ok(());
+            }
+        }
+        let node = hidraw_node(xconn, device)
+            .ok_or_else(|| ExternalError::NotSupported(NotSupportedError::new()))?;
+        let node = CString::new(node.as_os_str().as_bytes()).unwrap();
+        let fd = unsafe {
+            libc::open(
+                node.as_ptr(),
+                libc::O_RDONLY | libc::O_NONBLOCK | libc::O_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(ExternalError::Os(os_error!(OsError::XMisc(
+                "Failed to open the hidraw node of the device"
+            ))));
+        }
+        if epoll.is_none() {
+            match Epoll::new() {
+                Ok(new_epoll) => *epoll = Some(new_epoll),
+                Err(_) => {
+                    unsafe { libc::close(fd) };
+                    return Err(ExternalError::Os(os_error!(OsError::XMisc(
+                        "Failed to create the epoll of the HID reports"
+                    ))));
+                }
+            }
+        }
+        epoll.as_mut().unwrap().add(device, fd).map_err(|_| {
+            ExternalError::Os(os_error!(OsError::XMisc(
+                "Failed to poll the hidraw node of the device"
+            )))
+        })
+    }
+
+    /// The `hidraw` nodes are only on Linux.
+    #[cfg(not(target_os = "linux"))]
+    pub fn set_enabled(
+        &self,
+        _xconn: &XConnection,
+        _device: c_int,
+        _enabled: bool,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    /// Stops reading the device, which was removed.
+    pub fn remove(&self, device: c_int) {
+        if let Some(epoll) = self.epoll.borrow_mut().as_mut() {
+            epoll.remove(device);
+        }
+    }
+
+    /// The descriptor of the epoll, once a device was enabled.
+    #[cfg(target_os = "linux")]
+    pub fn fd(&self) -> Option<RawFd> {
+        self.epoll.borrow().as_ref().map(|epoll| epoll.fd)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        None
+    }
+
+    pub fn is_registered(&self) -> bool {
+        self.registered.get()
+    }
+
+    pub fn set_registered(&self) {
+        self.registered.set(true);
+    }
+
+    /// Reads the devices which are ready, without waiting, returning their reports.
+    pub fn dispatch(&self) -> Vec<(c_int, Vec<u8>)> {
+        match self.epoll.borrow_mut().as_mut() {
+            Some(epoll) => epoll.dispatch(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// The `hidraw` node of the HID device the evdev node of the device belongs to.
+#[cfg(target_os = "linux")]
+fn hidraw_node(xconn: &XConnection, device: c_int) -> Option<PathBuf> {
+    let property = unsafe { xconn.get_atom_unchecked(b"Device Node\0") };
+    let node = xconn.get_device_property_string(device, property)?;
+    let name = Path::new(&node).file_name()?;
+    // The input device of the evdev node is a child of the HID device, along with its hidraw
+    // device.
+    let dir = Path::new("/sys/class/input")
+        .join(name)
+        .join("device/device/hidraw");
+    let hidraw = fs::read_dir(dir).ok()?.filter_map(Result::ok).next()?;
+    Some(Path::new("/dev").join(hidraw.file_name()))
+}
+
+#[cfg(target_os = "linux")]
+struct Epoll {
+    fd: RawFd,
+    /// The enabled devices, with their `hidraw` nodes.
+    devices: Vec<(c_int, RawFd)>,
+}
+
+#[cfg(target_os = "linux")]
+impl Epoll {
+    fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Epoll {
+            fd,
+            devices: Vec::new(),
+        })
+    }
+
+    /// Adds the node of the device, which is closed if it can't be polled.
+    fn add(&mut self, device: c_int, fd: RawFd) -> io::Result<()> {
+        let mut event = libc::epoll_event {
+            events: libc::EPOLLIN as u32,
+            u64: fd as u64,
+        };
+        if unsafe { libc::epoll_ctl(self.fd, libc::EPOLL_CTL_ADD, fd, &mut event) } < 0 {
+            let error = io::Error::last_os_error();
+            unsafe { libc::close(fd) };
+            return Err(error);
+        }
+        self.devices.push((device, fd));
+        Ok(())
+    }
+
+    fn remove(&mut self, device: c_int) {
+        self.devices.retain(|&(enabled, fd)| {
+            if enabled == device {
+                // Closing the descriptor removes it from the epoll.
+                unsafe { libc::close(fd) };
+            }
+            enabled != device
+        });
+    }
+
+    fn dispatch(&mut self) -> Vec<(c_int, Vec<u8>)> {
+        let mut reports = Vec::new();
+        let mut ready = [libc::epoll_event { events: 0, u64: 0 }; 16];
+        let mut buffer = vec![0u8; MAX_REPORT_SIZE];
+        loop {
+            let count =
+                unsafe { libc::epoll_wait(self.fd, ready.as_mut_ptr(), ready.len() as _, 0) };
+            if count <= 0 {
+                break;
+            }
+            for event in &ready[..count as usize] {
+                let fd = event.u64 as RawFd;
+                let device = match self.devices.iter().find(|&&(_, node)| node == fd) {
+                    Some(&(device, _)) => device,
+                    None => continue,
+                };
+                // Each read gives a single report.
+                loop {
+                    let read = unsafe {
+                        libc::read(fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len())
+                    };
+                    if read > 0 {
+                        reports.push((device, buffer[..read as usize].to_vec()));
+                        continue;
+                    }
+                    let error = io::Error::last_os_error();
+                    if read < 0 && error.kind() == io::ErrorKind::WouldBlock {
+                        break;
+                    }
+                    // The device was unplugged.
+                    self.remove(device);
+                    break;
+                }
+            }
+            if (count as usize) < ready.len() {
+                break;
+            }
+        }
+        reports
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Epoll {
+    fn drop(&mut self) {
+        for &(_, fd) in &self.devices {
+            unsafe { libc::close(fd) };
+        }
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+/// The epoll is never created on the BSDs, which have no `hidraw` nodes.
+#[cfg(not(target_os = "linux"))]
+enum Epoll {}
+
+#[cfg(not(target_os = "linux"))]
+impl Epoll {
+    fn remove(&mut self, _device: c_int) {
+        match *self {}
+    }
+
+    fn dispatch(&mut self) -> Vec<(c_int, Vec<u8>)> {
+        match *self {}
+    }
+}
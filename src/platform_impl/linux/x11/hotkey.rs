@@ -0,0 +1,124 @@
+//! The global hotkeys, which are passive grabs of their keys on the root window.
+
+use std::{
+    collections::HashMap,
+    os::raw::{c_int, c_uint},
+};
+
+use super::{events, ffi, XConnection};
+use crate::{
+    error::ExternalError,
+    event::ModifiersState,
+    hotkey::{Accelerator, HotkeyId},
+    platform_impl::platform::OsError,
+};
+
+/// The modifiers the hotkeys are grabbed with, which leave out Caps Lock and Num Lock.
+const HOTKEY_MASK: c_uint = ffi::ShiftMask | ffi::ControlMask | ffi::Mod1Mask | ffi::Mod4Mask;
+
+/// The states of Caps Lock and Num Lock, as each of them needs a grab of its own.
+const LOCK_MASKS: [c_uint; 4] = [
+    0,
+    ffi::LockMask,
+    ffi::Mod2Mask,
+    ffi::LockMask | ffi::Mod2Mask,
+];
+
+#[derive(Default)]
+pub struct Hotkeys {
+    next_id: usize,
+    /// The keycode and the modifier mask of each of the hotkeys.
+    grabs: HashMap<HotkeyId, (c_int, c_uint)>,
+}
+
+impl Hotkeys {
+    pub fn register(
+        &mut self,
+        xconn: &XConnection,
+        root: ffi::Window,
+        accelerator: Accelerator,
+    ) -> Result<HotkeyId, ExternalError> {
+        let keycode = (8..=255)
+            .find(|&keycode| {
+                let keysym = xconn.keycode_to_keysym(keycode);
+                events::keysym_to_element(keysym as c_uint) == Some(accelerator.key)
+            })
+            .ok_or_else(|| {
+                ExternalError::Os(os_error!(OsError::XMisc(
+                    "The keyboard layout has no such key"
+                )))
+            })? as c_int;
+        let mask = modifier_mask(accelerator.modifiers);
+        if self.grabs.values().any(|&grab| grab == (keycode, mask)) {
+            return Err(ExternalError::Os(os_error!(OsError::XMisc(
+                "The hotkey is already registered"
+            ))));
+        }
+
+        for &lock_mask in &LOCK_MASKS {
+            unsafe {
+                (xconn.xlib.XGrabKey)(
+                    xconn.display,
+                    keycode,
+                    mask | lock_mask,
+                    root,
+                    ffi::False,
+                    ffi::GrabModeAsync,
+                    ffi::GrabModeAsync,
+                );
+            }
+        }
+        // The grab fails with `BadAccess` when another client has grabbed the combination.
+        if let Err(err) = xconn.sync_with_server() {
+            ungrab(xconn, root, keycode, mask);
+            let _ = xconn.sync_with_server();
+            return Err(ExternalError::Os(os_error!(OsError::XError(err))));
+        }
+
+        let id = HotkeyId(self.next_id);
+        self.next_id += 1;
+        self.grabs.insert(id, (keycode, mask));
+        Ok(id)
+    }
+
+    pub fn unregister(&mut self, xconn: &XConnection, root: ffi::Window, id: HotkeyId) -> bool {
+        match self.grabs.remove(&id) {
+            Some((keycode, mask)) => {
+                ungrab(xconn, root, keycode, mask);
+                let _ = xconn.flush_requests();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The hotkey a key press on the root window is for.
+    pub fn find(&self, keycode: c_uint, state: c_uint) -> Option<HotkeyId> {
+        let mask = state & HOTKEY_MASK;
+        self.grabs
+            .iter()
+            .find(|&(_, &grab)| grab == (keycode as c_int, mask))
+            .map(|(&id, _)| id)
+    }
+}
+
+fn modifier_mask(modifiers: ModifiersState) -> c_uint {
+    let mut mask = 0;
+    for &(modifier, modifier_mask) in &[
+        (ModifiersState::SHIFT, ffi::ShiftMask),
+        (ModifiersState::CTRL, ffi::ControlMask),
+        (ModifiersState::ALT, ffi::Mod1Mask),
+        (ModifiersState::LOGO, ffi::Mod4Mask),
+    ] {
+        if modifiers.contains(modifier) {
+            mask |= modifier_mask;
+        }
+    }
+    mask
+}
+
+fn ungrab(xconn: &XConnection, root: ffi::Window, keycode: c_int, mask: c_uint) {
+    for &lock_mask in &LOCK_MASKS {
+        unsafe { (xconn.xlib.XUngrabKey)(xconn.display, keycode, mask | lock_mask, root) };
+    }
+}
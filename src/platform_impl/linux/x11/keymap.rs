@@ -0,0 +1,177 @@
+//! The introspection of the keyboard layouts, through the keymap of the core keyboard.
+
+use std::{ffi::CStr, os::raw::c_char, ptr};
+
+use super::XConnection;
+use crate::{
+    error::ExternalError,
+    event::{ModifiersState, ScanCode},
+    keymap::KeyboardLayout,
+    platform_impl::platform::{
+        xkb::{self, XkbCommon, XKBCOMMON, XKBCOMMON_X11},
+        OsError,
+    },
+};
+
+/// The X11 keycodes are the evdev ones, shifted by 8.
+const KEYCODE_OFFSET: u32 = 8;
+
+/// The keymap of the core keyboard, along with its state, as it is when it's created.
+pub struct Keymap {
+    xkb: &'static XkbCommon,
+    context: *mut xkb::xkb_context,
+    keymap: *mut xkb::xkb_keymap,
+    state: *mut xkb::xkb_state,
+}
+
+impl Keymap {
+    pub fn new(xconn: &XConnection) -> Result<Keymap, ExternalError> {
+        let (xkb, xkb_x11) = match (XKBCOMMON.as_ref(), XKBCOMMON_X11.as_ref()) {
+            (Some(xkb), Some(xkb_x11)) => (xkb, xkb_x11),
+            _ => return Err(keymap_error("Failed to load libxkbcommon-x11")),
+        };
+
+        unsafe {
+            let connection =
+                (xconn.xlib_xcb.XGetXCBConnection)(xconn.display) as *mut xkb::xcb_connection_t;
+            let extension = (xkb_x11.xkb_x11_setup_xkb_extension)(
+                connection,
+                xkb::XKB_X11_MIN_MAJOR_XKB_VERSION,
+                xkb::XKB_X11_MIN_MINOR_XKB_VERSION,
+                0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if extension == 0 {
+                return Err(keymap_error("Failed to set up the XKB extension"));
+            }
+            let device = (xkb_x11.xkb_x11_get_core_keyboard_device_id)(connection);
+            if device < 0 {
+                return Err(keymap_error("Failed to find the core keyboard"));
+            }
+
+            let context = (xkb.xkb_context_new)(0);
+            if context.is_null() {
+                return Err(keymap_error("Failed to create the XKB context"));
+            }
+            // The keymap takes over the pointers as they're created, to free them on errors.
+            let mut keymap = Keymap {
+                xkb,
+                context,
+                keymap: ptr::null_mut(),
+                state: ptr::null_mut(),
+            };
+            keymap.keymap =
+                (xkb_x11.xkb_x11_keymap_new_from_device)(context, connection, device, 0);
+            if keymap.keymap.is_null() {
+                return Err(keymap_error(
+                    "Failed to get the keymap of the core keyboard",
+                ));
+            }
+            keymap.state =
+                (xkb_x11.xkb_x11_state_new_from_device)(keymap.keymap, connection, device);
+            if keymap.state.is_null() {
+                return Err(keymap_error("Failed to get the state of the core keyboard"));
+            }
+            Ok(keymap)
+        }
+    }
+
+    pub fn layouts(&self) -> Vec<KeyboardLayout> {
+        let count = unsafe { (self.xkb.xkb_keymap_num_layouts)(self.keymap) };
+        (0..count).filter_map(|index| self.layout(index)).collect()
+    }
+
+    pub fn current_layout(&self) -> Option<KeyboardLayout> {
+        self.layout(self.current_layout_index())
+    }
+
+    /// The characters the key at `scancode` produces with the current layout and `modifiers`.
+    pub fn key_text(&self, scancode: ScanCode, modifiers: ModifiersState) -> Option<String> {
+        let mut mods = 0;
+        for &(modifier, name) in &[
+            (ModifiersState::SHIFT, &b"Shift\0"[..]),
+            (ModifiersState::CTRL, b"Control\0"),
+            (ModifiersState::ALT, b"Mod1\0"),
+            (ModifiersState::LOGO, b"Mod4\0"),
+        ] {
+            if !modifiers.contains(modifier) {
+                continue;
+            }
+            let index = unsafe {
+                (self.xkb.xkb_keymap_mod_get_index)(self.keymap, name.as_ptr() as *const c_char)
+            };
+            if index != xkb::XKB_MOD_INVALID && index < 32 {
+                mods |= 1 << index;
+            }
+        }
+
+        unsafe {
+            // A state of its own, so the modifiers which are held right now don't count.
+            let state = (self.xkb.xkb_state_new)(self.keymap);
+            if state.is_null() {
+                return None;
+            }
+            (self.xkb.xkb_state_update_mask)(state, mods, 0, 0, 0, 0, self.current_layout_index());
+            let mut buffer = [0 as c_char; 64];
+            let len = (self.xkb.xkb_state_key_get_utf8)(
+                state,
+                scancode + KEYCODE_OFFSET,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+            );
+            (self.xkb.xkb_state_unref)(state);
+            if len <= 0 {
+                return None;
+            }
+            Some(
+                CStr::from_ptr(buffer.as_ptr())
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        }
+    }
+
+    fn current_layout_index(&self) -> u32 {
+        unsafe {
+            (self.xkb.xkb_state_serialize_layout)(self.state, xkb::XKB_STATE_LAYOUT_EFFECTIVE)
+        }
+    }
+
+    fn layout(&self, index: u32) -> Option<KeyboardLayout> {
+        if index == xkb::XKB_LAYOUT_INVALID {
+            return None;
+        }
+        let name = unsafe { (self.xkb.xkb_keymap_layout_get_name)(self.keymap, index) };
+        if name.is_null() {
+            return None;
+        }
+        let name = unsafe { CStr::from_ptr(name) }
+            .to_string_lossy()
+            .into_owned();
+        Some(KeyboardLayout {
+            id: name.clone(),
+            name,
+        })
+    }
+}
+
+impl Drop for Keymap {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.state.is_null() {
+                (self.xkb.xkb_state_unref)(self.state);
+            }
+            if !self.keymap.is_null() {
+                (self.xkb.xkb_keymap_unref)(self.keymap);
+            }
+            (self.xkb.xkb_context_unref)(self.context);
+        }
+    }
+}
+
+fn keymap_error(message: &'static str) -> ExternalError {
+    ExternalError::Os(os_error!(OsError::XMisc(message)))
+}
@@ -6,29 +6,36 @@
     target_os = "openbsd"
 ))]
 
+mod child_surface;
+mod clipboard;
 mod dnd;
 mod event_processor;
 mod events;
 pub mod ffi;
+mod hid;
+mod hotkey;
 mod ime;
+mod keymap;
 mod monitor;
 pub mod util;
 mod window;
 mod xdisplay;
 
 pub use self::{
+    child_surface::ChildSurface,
     monitor::{MonitorHandle, VideoMode},
+    util::CustomCursor,
     window::UnownedWindow,
     xdisplay::{XConnection, XError, XNotSupported},
 };
 
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
     ffi::CStr,
     mem::{self, MaybeUninit},
     ops::Deref,
-    os::raw::*,
+    os::{raw::*, unix::io::RawFd},
     ptr,
     rc::Rc,
     slice,
@@ -48,21 +55,49 @@ use mio_misc::{
 };
 
 use self::{
-    dnd::{Dnd, DndState},
+    clipboard::Clipboard,
+    dnd::{Dnd, DndState, DragProgress, DragSource},
     event_processor::EventProcessor,
+    hid::HidReports,
+    hotkey::Hotkeys,
     ime::{Ime, ImeCreationError, ImeReceiver, ImeSender},
+    keymap::Keymap,
     util::modifiers::ModifierKeymap,
 };
 use crate::{
-    error::OsError as RootOsError,
-    event::{Event, StartCause},
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
-    platform_impl::{platform::sticky_exit_callback, PlatformSpecificWindowBuilderAttributes},
-    window::WindowAttributes,
+    clipboard::{ClipboardKind, ClipboardProvider},
+    dpi::PhysicalPosition,
+    error::{ExternalError, OsError as RootOsError},
+    event::{
+        DeviceCapabilities, DeviceId as RootDeviceId, Event, InputDevice, ModifiersState, PenEvent,
+        PenPhase, PenTool, ScanCode, StartCause,
+    },
+    event_loop::{
+        ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW, SourceInterest,
+        SourceToken, Timer, TimerId,
+    },
+    gamepad::GamepadId,
+    hotkey::{Accelerator, HotkeyId},
+    icon::{BadIcon, RgbaIcon},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
+    platform::pump_events::PumpStatus,
+    platform_impl::{
+        platform::{
+            compose::Compose, gamepad::Gamepads, min_timeout, precise_wait::PreciseWait,
+            sticky_exit_callback, timer::Timers,
+        },
+        OsError, PlatformSpecificWindowBuilderAttributes,
+    },
+    window::{CursorFrame, WindowAttributes},
 };
 
 const X_TOKEN: Token = Token(0);
 const USER_REDRAW_TOKEN: Token = Token(1);
+const PRECISE_WAIT_TOKEN: Token = Token(2);
+const GAMEPADS_TOKEN: Token = Token(3);
+const HID_REPORTS_TOKEN: Token = Token(4);
+/// The first token given to the external sources.
+const FIRST_SOURCE_TOKEN: usize = 5;
 
 pub struct EventLoopWindowTarget<T> {
     xconn: Arc<XConnection>,
@@ -73,6 +108,22 @@ pub struct EventLoopWindowTarget<T> {
     ime: RefCell<Ime>,
     windows: RefCell<HashMap<WindowId, Weak<UnownedWindow>>>,
     redraw_sender: Sender<WindowId>,
+    /// Sends whether the windows switched to the video mode of their exclusive fullscreen.
+    video_mode_sender: Sender<(WindowId, bool)>,
+    clipboard: Arc<Clipboard>,
+    drag_source: Arc<DragSource>,
+    timers: RefCell<Timers>,
+    precise_wait: PreciseWait,
+    gamepads: Gamepads,
+    /// The HID devices whose raw reports are read.
+    hid_reports: HidReports,
+    hotkeys: RefCell<Hotkeys>,
+    /// The position of the window last cascaded with `Placement::Cascade`.
+    last_cascaded: Cell<Option<PhysicalPosition<i32>>>,
+    /// Whether the server has the touchpad gestures of XInput 2.4.
+    xi2_gestures: bool,
+    /// Whether the server has the Present extension.
+    present: bool,
     _marker: ::std::marker::PhantomData<T>,
 }
 
@@ -80,15 +131,40 @@ pub struct EventLoop<T: 'static> {
     poll: Poll,
     event_processor: EventProcessor<T>,
     redraw_channel: Receiver<WindowId>,
+    video_mode_channel: Receiver<(WindowId, bool)>,
     user_channel: Receiver<T>,
     user_sender: Sender<T>,
+    /// Wakes up the poll, sharing the token with the user and the redraw channels.
+    waker: Arc<Waker>,
     target: Rc<RootELW<T>>,
+    events: Events,
+    /// The control flow set by the callback, which is kept between the calls to `pump_events`.
+    control_flow: ControlFlow,
+    /// Whether `NewEvents(Init)` was sent, and `LoopDestroyed` is yet to be.
+    loop_running: bool,
+    /// The external sources registered by the user.
+    sources: HashMap<SourceToken, ExternalSource>,
+    /// The external sources reported ready by the latest poll.
+    ready_sources: Vec<SourceToken>,
+    /// The token to register the next external source with.
+    next_source_token: usize,
+}
+
+/// An external source, as it's registered with the poll.
+struct ExternalSource {
+    fd: RawFd,
+    interest: Interest,
+    token: Token,
 }
 
 pub struct EventLoopProxy<T: 'static> {
     user_sender: Sender<T>,
 }
 
+pub struct EventLoopWakeup {
+    waker: Arc<Waker>,
+}
+
 impl<T: 'static> Clone for EventLoopProxy<T> {
     fn clone(&self) -> Self {
         EventLoopProxy {
@@ -108,6 +184,14 @@ impl<T: 'static> EventLoop<T> {
         let dnd = Dnd::new(Arc::clone(&xconn))
             .expect("Failed to call XInternAtoms when initializing drag and drop");
 
+        let clipboard = Clipboard::new(Arc::clone(&xconn), root)
+            .map(Arc::new)
+            .expect("Failed to create the clipboard window");
+
+        let drag_source = DragSource::new(Arc::clone(&xconn), Arc::clone(&clipboard), root)
+            .map(Arc::new)
+            .expect("Failed to call XInternAtoms when initializing drag and drop");
+
         let (ime_sender, ime_receiver) = mpsc::channel();
         // Input methods will open successfully without setting the locale, but it won't be
         // possible to actually commit pre-edit sequences.
@@ -143,6 +227,29 @@ impl<T: 'static> EventLoop<T> {
         let randr_event_offset = xconn
             .select_xrandr_input(root)
             .expect("Failed to query XRandR extension");
+        // The cached monitors are the ones the hotplugged monitors are told apart from.
+        xconn.available_monitors();
+
+        // The layouts are the groups of the keymap, so watch both to report the layout changes.
+        let xkb_event_type = xconn.query_xkb_event_type();
+        if xkb_event_type.is_some() {
+            if let Some(flusher) = xconn.select_xkb_events(
+                util::XKB_USE_CORE_KBD,
+                ffi::XkbNewKeyboardNotifyMask | ffi::XkbMapNotifyMask,
+            ) {
+                flusher.queue();
+            }
+            if let Some(flusher) = xconn.select_xkb_event_details(
+                util::XKB_USE_CORE_KBD,
+                ffi::XkbStateNotify as libc::c_uint,
+                ffi::XkbGroupStateMask,
+            ) {
+                flusher.queue();
+            }
+        }
+        let keyboard_layout = Keymap::new(&xconn)
+            .ok()
+            .and_then(|keymap| keymap.current_layout());
 
         let xi2ext = unsafe {
             let mut ext = XExtension::default();
@@ -162,9 +269,29 @@ impl<T: 'static> EventLoop<T> {
             ext
         };
 
-        unsafe {
+        // The frames presented through the Present extension are reported as presented.
+        let present_opcode = xconn.xpresent.as_ref().and_then(|xpresent| unsafe {
+            let mut opcode = 0;
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if (xpresent.XPresentQueryExtension)(
+                xconn.display,
+                &mut opcode,
+                &mut event_base,
+                &mut error_base,
+            ) == ffi::True
+            {
+                Some(opcode)
+            } else {
+                None
+            }
+        });
+
+        // The version given is the highest one we handle, the server answering with the one
+        // it has if it's older.
+        let xi2_gestures = unsafe {
             let mut xinput_major_ver = ffi::XI_2_Major;
-            let mut xinput_minor_ver = ffi::XI_2_Minor;
+            let mut xinput_minor_ver = ffi::XI_2_4_Minor;
             if (xconn.xinput2.XIQueryVersion)(
                 xconn.display,
                 &mut xinput_major_ver,
@@ -176,7 +303,8 @@ impl<T: 'static> EventLoop<T> {
                     xinput_major_ver, xinput_minor_ver,
                 );
             }
-        }
+            (xinput_major_ver, xinput_minor_ver) >= (ffi::XI_2_Major, ffi::XI_2_4_Minor)
+        };
 
         xconn.update_cached_wm_info(root);
 
@@ -185,7 +313,7 @@ impl<T: 'static> EventLoop<T> {
 
         let poll = Poll::new().unwrap();
         let waker = Arc::new(Waker::new(poll.registry(), USER_REDRAW_TOKEN).unwrap());
-        let queue = Arc::new(NotificationQueue::new(waker));
+        let queue = Arc::new(NotificationQueue::new(waker.clone()));
 
         poll.registry()
             .register(&mut SourceFd(&xconn.x11_fd), X_TOKEN, Interest::READABLE)
@@ -193,7 +321,9 @@ impl<T: 'static> EventLoop<T> {
 
         let (user_sender, user_channel) = channel(queue.clone(), NotificationId::gen_next());
 
-        let (redraw_sender, redraw_channel) = channel(queue, NotificationId::gen_next());
+        let (redraw_sender, redraw_channel) = channel(queue.clone(), NotificationId::gen_next());
+
+        let (video_mode_sender, video_mode_channel) = channel(queue, NotificationId::gen_next());
 
         let target = Rc::new(RootELW {
             p: super::EventLoopWindowTarget::X(EventLoopWindowTarget {
@@ -206,6 +336,17 @@ impl<T: 'static> EventLoop<T> {
                 wm_delete_window,
                 net_wm_ping,
                 redraw_sender,
+                video_mode_sender,
+                clipboard,
+                drag_source,
+                timers: Default::default(),
+                precise_wait: Default::default(),
+                gamepads: Default::default(),
+                hid_reports: Default::default(),
+                hotkeys: Default::default(),
+                last_cascaded: Cell::new(None),
+                xi2_gestures,
+                present: present_opcode.is_some(),
             }),
             _marker: ::std::marker::PhantomData,
         });
@@ -215,12 +356,19 @@ impl<T: 'static> EventLoop<T> {
             dnd,
             devices: Default::default(),
             randr_event_offset,
+            xkb_event_type,
+            keyboard_layout,
             ime_receiver,
             xi2ext,
+            present_opcode,
             mod_keymap,
             device_mod_state: Default::default(),
+            keyboard_mod_states: Default::default(),
+            held_keys: Default::default(),
+            compose: Compose::new(),
             num_touch: 0,
             first_touch: None,
+            pinch_scale: 1.,
             active_window: None,
         };
 
@@ -228,7 +376,11 @@ impl<T: 'static> EventLoop<T> {
         // (The request buffer is flushed during `init_device`)
         get_xtarget(&target)
             .xconn
-            .select_xinput_events(root, ffi::XIAllDevices, ffi::XI_HierarchyChangedMask)
+            .select_xinput_events(
+                root,
+                ffi::XIAllDevices,
+                (ffi::XI_HierarchyChangedMask | ffi::XI_PropertyEventMask).into(),
+            )
             .queue();
 
         event_processor.init_device(ffi::XIAllDevices);
@@ -236,10 +388,18 @@ impl<T: 'static> EventLoop<T> {
         let result = EventLoop {
             poll,
             redraw_channel,
+            video_mode_channel,
             user_channel,
             user_sender,
+            waker,
             event_processor,
             target,
+            events: Events::with_capacity(8),
+            control_flow: ControlFlow::default(),
+            loop_running: false,
+            sources: HashMap::new(),
+            ready_sources: Vec::new(),
+            next_source_token: FIRST_SOURCE_TOKEN,
         };
 
         result
@@ -251,139 +411,369 @@ impl<T: 'static> EventLoop<T> {
         }
     }
 
+    pub fn create_wakeup(&self) -> EventLoopWakeup {
+        EventLoopWakeup {
+            waker: self.waker.clone(),
+        }
+    }
+
     pub(crate) fn window_target(&self) -> &RootELW<T> {
         &self.target
     }
 
-    pub fn run_return<F>(&mut self, mut callback: F)
+    pub fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError> {
+        if self.sources.contains_key(&token) {
+            return Err(ExternalError::Os(os_error!(OsError::XMisc(
+                "The token of the source is already in use"
+            ))));
+        }
+        let interest = match (
+            interest.contains(SourceInterest::READABLE),
+            interest.contains(SourceInterest::WRITABLE),
+        ) {
+            (true, true) => Interest::READABLE | Interest::WRITABLE,
+            (true, false) => Interest::READABLE,
+            (false, true) => Interest::WRITABLE,
+            (false, false) => {
+                return Err(ExternalError::Os(os_error!(OsError::XMisc(
+                    "The source has no interest"
+                ))))
+            }
+        };
+
+        let poll_token = Token(self.next_source_token);
+        self.poll
+            .registry()
+            .register(&mut SourceFd(&fd), poll_token, interest)
+            .map_err(|_| {
+                ExternalError::Os(os_error!(OsError::XMisc("Failed to register the source")))
+            })?;
+        self.next_source_token += 1;
+        self.sources.insert(
+            token,
+            ExternalSource {
+                fd,
+                interest,
+                token: poll_token,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        let source = self.sources.remove(&token).ok_or_else(|| {
+            ExternalError::Os(os_error!(OsError::XMisc(
+                "There's no source with this token"
+            )))
+        })?;
+        self.ready_sources.retain(|ready| *ready != token);
+        self.poll
+            .registry()
+            .deregister(&mut SourceFd(&source.fd))
+            .map_err(|_| {
+                ExternalError::Os(os_error!(OsError::XMisc("Failed to deregister the source")))
+            })
+    }
+
+    pub fn run_return<F>(&mut self, mut callback: F) -> i32
     where
         F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
-        let mut control_flow = ControlFlow::default();
-        let mut events = Events::with_capacity(8);
-        let mut cause = StartCause::Init;
-
         loop {
-            sticky_exit_callback(
-                crate::event::Event::NewEvents(cause),
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut callback) {
+                return code;
+            }
+        }
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
+            self.control_flow = ControlFlow::default();
+            self.single_iteration(&mut callback, StartCause::Init);
+        }
+
+        // The first iteration may have already asked to exit.
+        if !matches!(self.control_flow, ControlFlow::ExitWithCode(_)) {
+            self.poll_events_with_timeout(timeout, &mut callback);
+        }
+
+        if let ControlFlow::ExitWithCode(code) = self.control_flow {
+            self.loop_running = false;
+            callback(
+                crate::event::Event::LoopDestroyed,
                 &self.target,
-                &mut control_flow,
-                &mut callback,
+                &mut self.control_flow,
             );
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
 
-            // Process all pending events
-            self.drain_events(&mut callback, &mut control_flow);
-
-            // Empty the user event buffer
+    /// Waits for the events for as long as both the control flow and the timeout allow, then
+    /// dispatches them.
+    fn poll_events_with_timeout<F>(&mut self, timeout: Option<Duration>, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let precise_wait = &get_xtarget(&self.target).precise_wait;
+        if let Some(timer) = precise_wait.timer() {
+            if !precise_wait.is_registered()
+                && self
+                    .poll
+                    .registry()
+                    .register(
+                        &mut SourceFd(&timer.fd()),
+                        PRECISE_WAIT_TOKEN,
+                        Interest::READABLE,
+                    )
+                    .is_ok()
             {
-                while let Ok(event) = self.user_channel.try_recv() {
-                    sticky_exit_callback(
-                        crate::event::Event::UserEvent(event),
-                        &self.target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
-                }
+                precise_wait.set_registered();
             }
-            // send MainEventsCleared
+        }
+        let precise = precise_wait.is_registered() && precise_wait.arm(self.control_flow);
+        // The gamepads are read on each iteration, so the poll only has to wake up for them.
+        let gamepads = &get_xtarget(&self.target).gamepads;
+        if let Some(fd) = gamepads.fd() {
+            if !gamepads.is_registered()
+                && self
+                    .poll
+                    .registry()
+                    .register(&mut SourceFd(&fd), GAMEPADS_TOKEN, Interest::READABLE)
+                    .is_ok()
             {
-                sticky_exit_callback(
-                    crate::event::Event::MainEventsCleared,
-                    &self.target,
-                    &mut control_flow,
-                    &mut callback,
-                );
+                gamepads.set_registered();
             }
-            // Empty the redraw requests
+        }
+        let hid_reports = &get_xtarget(&self.target).hid_reports;
+        if let Some(fd) = hid_reports.fd() {
+            if !hid_reports.is_registered()
+                && self
+                    .poll
+                    .registry()
+                    .register(&mut SourceFd(&fd), HID_REPORTS_TOKEN, Interest::READABLE)
+                    .is_ok()
             {
-                let mut windows = HashSet::new();
+                hid_reports.set_registered();
+            }
+        }
 
-                while let Ok(window_id) = self.redraw_channel.try_recv() {
-                    windows.insert(window_id);
+        let start = Instant::now();
+        let control_flow_timeout = match self.control_flow {
+            ControlFlow::Poll => Some(Duration::from_millis(0)),
+            ControlFlow::Wait => None,
+            // The timer of the precise waits wakes the poll up.
+            ControlFlow::WaitUntil(_) if precise => None,
+            ControlFlow::WaitUntil(deadline) => Some(deadline.saturating_duration_since(start)),
+            ControlFlow::ExitWithCode(_) => unreachable!(),
+        };
+        let timeout = min_timeout(control_flow_timeout, timeout);
+        let timer_timeout = get_xtarget(&self.target)
+            .timers
+            .borrow()
+            .next_deadline()
+            .map(|deadline| deadline.saturating_duration_since(start));
+        let timeout = min_timeout(timer_timeout, timeout);
+
+        // If the XConnection already contains buffered events, we don't
+        // need to wait for data on the socket, only to check the external sources.
+        let timeout = if self.event_processor.poll() {
+            Some(Duration::from_millis(0))
+        } else {
+            timeout
+        };
+        self.poll.poll(&mut self.events, timeout).unwrap();
+        for event in self.events.iter() {
+            let token = event.token();
+            if token == PRECISE_WAIT_TOKEN {
+                if let Some(timer) = precise_wait.timer() {
+                    timer.clear();
                 }
+            }
+            if token.0 < FIRST_SOURCE_TOKEN {
+                continue;
+            }
+            if let Some((source_token, _)) = self
+                .sources
+                .iter()
+                .find(|(_, source)| source.token == token)
+            {
+                if !self.ready_sources.contains(source_token) {
+                    self.ready_sources.push(*source_token);
+                }
+            }
+        }
+        self.events.clear();
 
-                for window_id in windows {
-                    let window_id = crate::window::WindowId(super::WindowId::X(window_id));
-                    sticky_exit_callback(
-                        Event::RedrawRequested(window_id),
-                        &self.target,
-                        &mut control_flow,
-                        &mut callback,
-                    );
+        let cause = match self.control_flow {
+            ControlFlow::Poll => StartCause::Poll,
+            ControlFlow::WaitUntil(deadline) if Instant::now() >= deadline => {
+                StartCause::ResumeTimeReached {
+                    start,
+                    requested_resume: deadline,
                 }
             }
-            // send RedrawEventsCleared
-            {
+            ControlFlow::WaitUntil(deadline) => StartCause::WaitCancelled {
+                start,
+                requested_resume: Some(deadline),
+            },
+            _ => StartCause::WaitCancelled {
+                start,
+                requested_resume: None,
+            },
+        };
+        self.single_iteration(callback, cause);
+    }
+
+    fn single_iteration<F>(&mut self, callback: &mut F, cause: StartCause)
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let mut control_flow = self.control_flow;
+
+        sticky_exit_callback(
+            crate::event::Event::NewEvents(cause),
+            &self.target,
+            &mut control_flow,
+            callback,
+        );
+
+        // Process all pending events
+        self.drain_events(callback, &mut control_flow);
+
+        // Empty the user event buffer
+        {
+            while let Ok(event) = self.user_channel.try_recv() {
                 sticky_exit_callback(
-                    crate::event::Event::RedrawEventsCleared,
+                    crate::event::Event::UserEvent(event),
                     &self.target,
                     &mut control_flow,
-                    &mut callback,
+                    callback,
                 );
             }
-
-            let start = Instant::now();
-            let (deadline, timeout);
-
-            match control_flow {
-                ControlFlow::Exit => break,
-                ControlFlow::Poll => {
-                    cause = StartCause::Poll;
-                    deadline = None;
-                    timeout = Some(Duration::from_millis(0));
-                }
-                ControlFlow::Wait => {
-                    cause = StartCause::WaitCancelled {
-                        start,
-                        requested_resume: None,
-                    };
-                    deadline = None;
-                    timeout = None;
-                }
-                ControlFlow::WaitUntil(wait_deadline) => {
-                    cause = StartCause::ResumeTimeReached {
-                        start,
-                        requested_resume: wait_deadline,
-                    };
-                    timeout = if wait_deadline > start {
-                        Some(wait_deadline - start)
-                    } else {
-                        Some(Duration::from_millis(0))
-                    };
-                    deadline = Some(wait_deadline);
-                }
+        }
+        // Report the switches to the video modes of exclusive fullscreen
+        while let Ok((window_id, switched)) = self.video_mode_channel.try_recv() {
+            sticky_exit_callback(
+                crate::event::Event::WindowEvent {
+                    window_id: mkwid(window_id.0),
+                    event: crate::event::WindowEvent::VideoModeSwitched(switched),
+                },
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+        // Send the fired timers
+        let fired = get_xtarget(&self.target)
+            .timers
+            .borrow_mut()
+            .take_fired(Instant::now());
+        for id in fired {
+            sticky_exit_callback(
+                crate::event::Event::Timer(id),
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+        // Send the events of the gamepads
+        let gamepad_events = get_xtarget(&self.target).gamepads.dispatch();
+        for (gamepad_id, event) in gamepad_events {
+            sticky_exit_callback(
+                crate::event::Event::GamepadEvent { gamepad_id, event },
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+        // Send the reports of the HID devices
+        let hid_reports = get_xtarget(&self.target).hid_reports.dispatch();
+        for (device, data) in hid_reports {
+            sticky_exit_callback(
+                crate::event::Event::DeviceEvent {
+                    device_id: mkdid(device),
+                    event: crate::event::DeviceEvent::HidReport { data },
+                },
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+        // Report the ready external sources
+        for token in mem::take(&mut self.ready_sources) {
+            sticky_exit_callback(
+                crate::event::Event::ExternalSource(token),
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+            // The poll only reports the changes of the readiness, so the source is registered
+            // again to be reported for as long as it stays ready.
+            if let Some(source) = self.sources.get(&token) {
+                let _ = self.poll.registry().reregister(
+                    &mut SourceFd(&source.fd),
+                    source.token,
+                    source.interest,
+                );
             }
+        }
+        // send MainEventsCleared
+        {
+            sticky_exit_callback(
+                crate::event::Event::MainEventsCleared,
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
+        // Empty the redraw requests
+        {
+            let mut windows = HashSet::new();
 
-            // If the XConnection already contains buffered events, we don't
-            // need to wait for data on the socket.
-            if !self.event_processor.poll() {
-                self.poll.poll(&mut events, timeout).unwrap();
-                events.clear();
+            while let Ok(window_id) = self.redraw_channel.try_recv() {
+                windows.insert(window_id);
             }
 
-            let wait_cancelled = deadline.map_or(false, |deadline| Instant::now() < deadline);
-
-            if wait_cancelled {
-                cause = StartCause::WaitCancelled {
-                    start,
-                    requested_resume: deadline,
-                };
+            for window_id in windows {
+                let window_id = crate::window::WindowId(super::WindowId::X(window_id));
+                sticky_exit_callback(
+                    Event::RedrawRequested(window_id),
+                    &self.target,
+                    &mut control_flow,
+                    callback,
+                );
             }
         }
+        // send RedrawEventsCleared
+        {
+            sticky_exit_callback(
+                crate::event::Event::RedrawEventsCleared,
+                &self.target,
+                &mut control_flow,
+                callback,
+            );
+        }
 
-        callback(
-            crate::event::Event::LoopDestroyed,
-            &self.target,
-            &mut control_flow,
-        );
+        self.control_flow = control_flow;
     }
 
     pub fn run<F>(mut self, callback: F) -> !
     where
         F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
-        self.run_return(callback);
-        ::std::process::exit(0);
+        let exit_code = self.run_return(callback);
+        ::std::process::exit(exit_code);
     }
 
     fn drain_events<F>(&mut self, callback: &mut F, control_flow: &mut ControlFlow)
@@ -421,7 +811,7 @@ impl<T: 'static> EventLoop<T> {
 pub(crate) fn get_xtarget<T>(target: &RootELW<T>) -> &EventLoopWindowTarget<T> {
     match target.p {
         super::EventLoopWindowTarget::X(ref target) => target,
-        #[cfg(feature = "wayland")]
+        #[cfg(any(feature = "wayland", feature = "headless"))]
         _ => unreachable!(),
     }
 }
@@ -432,6 +822,164 @@ impl<T> EventLoopWindowTarget<T> {
     pub fn x_connection(&self) -> &Arc<XConnection> {
         &self.xconn
     }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        self.clipboard
+            .set(kind, provider)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub(crate) fn create_custom_cursor(
+        &self,
+        image: RgbaIcon,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        CustomCursor::new(&self.xconn, &image, hotspot)
+    }
+
+    #[inline]
+    pub fn create_animated_cursor(
+        &self,
+        frames: Vec<CursorFrame>,
+    ) -> Result<CustomCursor, BadIcon> {
+        CustomCursor::new_animated(&self.xconn, &frames)
+    }
+
+    #[inline]
+    pub fn set_timer(&self, id: TimerId, timer: Timer) -> Result<(), ExternalError> {
+        // The poll waits for the timers once the callback returns.
+        self.timers.borrow_mut().set(id, timer);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn cancel_timer(&self, id: TimerId) -> bool {
+        self.timers.borrow_mut().cancel(id)
+    }
+
+    #[inline]
+    pub fn set_precise_wait(&self, precise: bool) -> Result<(), ExternalError> {
+        self.precise_wait.set_enabled(precise).map_err(|_| {
+            ExternalError::Os(os_error!(OsError::XMisc(
+                "Failed to create the timer of the precise waits"
+            )))
+        })
+    }
+
+    #[inline]
+    pub fn set_gamepads_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        self.gamepads
+            .set_enabled(enabled, |message| os_error!(OsError::XMisc(message)))
+    }
+
+    pub fn rumble_gamepad(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        self.gamepads
+            .rumble(gamepad_id, strong, weak, duration, |message| {
+                os_error!(OsError::XMisc(message))
+            })
+    }
+
+    pub fn input_devices(&self) -> Vec<InputDevice> {
+        let devices = match DeviceInfo::get(&self.xconn, ffi::XIAllDevices) {
+            Some(devices) => devices,
+            None => return Vec::new(),
+        };
+        devices
+            .iter()
+            .filter(|info| info.enabled == ffi::True)
+            .map(|info| Device::input_device(&self.xconn, info))
+            .collect()
+    }
+
+    pub fn set_hid_reports_enabled(
+        &self,
+        device: DeviceId,
+        enabled: bool,
+    ) -> Result<(), ExternalError> {
+        self.hid_reports.set_enabled(&self.xconn, device.0, enabled)
+    }
+
+    #[inline]
+    pub fn keyboard_layouts(&self) -> Result<Vec<KeyboardLayout>, ExternalError> {
+        Ok(Keymap::new(&self.xconn)?.layouts())
+    }
+
+    #[inline]
+    pub fn current_keyboard_layout(&self) -> Result<KeyboardLayout, ExternalError> {
+        Keymap::new(&self.xconn)?.current_layout().ok_or_else(|| {
+            ExternalError::Os(os_error!(OsError::XMisc("The core keyboard has no layout")))
+        })
+    }
+
+    #[inline]
+    pub fn scancode_to_text(
+        &self,
+        scancode: ScanCode,
+        modifiers: ModifiersState,
+    ) -> Result<Option<String>, ExternalError> {
+        Ok(Keymap::new(&self.xconn)?.key_text(scancode, modifiers))
+    }
+
+    #[inline]
+    pub fn key_repeat(&self) -> Result<Option<KeyRepeat>, ExternalError> {
+        let enabled = self
+            .xconn
+            .is_auto_repeat_enabled()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+        if !enabled {
+            return Ok(None);
+        }
+        let (delay, interval) = self.xconn.auto_repeat_rate().ok_or_else(|| {
+            ExternalError::Os(os_error!(OsError::XMisc("`XkbGetAutoRepeatRate` failed")))
+        })?;
+        Ok(Some(KeyRepeat {
+            delay: Duration::from_millis(delay as u64),
+            interval: Duration::from_millis(interval as u64),
+        }))
+    }
+
+    #[inline]
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        self.xconn
+            .lock_keys()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn set_lock_keys(&self, lock_keys: LockKeys, on: bool) -> Result<(), ExternalError> {
+        self.xconn
+            .set_lock_keys(lock_keys, on)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn register_global_hotkey(
+        &self,
+        accelerator: Accelerator,
+    ) -> Result<HotkeyId, ExternalError> {
+        self.hotkeys
+            .borrow_mut()
+            .register(&self.xconn, self.root, accelerator)
+    }
+
+    #[inline]
+    pub fn unregister_global_hotkey(&self, id: HotkeyId) -> bool {
+        self.hotkeys
+            .borrow_mut()
+            .unregister(&self.xconn, self.root, id)
+    }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
@@ -446,6 +994,12 @@ impl<T: 'static> EventLoopProxy<T> {
     }
 }
 
+impl EventLoopWakeup {
+    pub fn wake_up(&self) {
+        let _ = self.waker.wake();
+    }
+}
+
 struct DeviceInfo<'a> {
     xconn: &'a XConnection,
     info: *const ffi::XIDeviceInfo,
@@ -533,6 +1087,10 @@ impl Drop for Window {
     fn drop(&mut self) {
         let window = self.deref();
         let xconn = &window.xconn;
+        // The pointer barriers belong to the connection, and outlive the window otherwise.
+        let _ = window.set_cursor_grab_area(None);
+        // The video mode is kept by the server once the client is gone.
+        window.restore_desktop_video_mode();
         unsafe {
             (xconn.xlib.XDestroyWindow)(xconn.display, window.id().0);
             // If the window was somehow already destroyed, we'll get a `BadWindow` error, which we don't care about.
@@ -590,6 +1148,8 @@ fn mkdid(w: c_int) -> crate::event::DeviceId {
 struct Device {
     name: String,
     scroll_axes: Vec<(i32, ScrollAxis)>,
+    /// The axes of the pen, when the device is one.
+    pen: Option<PenAxes>,
     // For master devices, this is the paired device (pointer <-> keyboard).
     // For slave devices, this is the master.
     attachment: c_int,
@@ -608,10 +1168,132 @@ enum ScrollOrientation {
     Horizontal,
 }
 
+/// The valuators of a pen, with their last values since the events only carry the changed ones.
+#[derive(Debug, Copy, Clone)]
+struct PenAxes {
+    pressure: PenAxis,
+    tilt_x: Option<PenAxis>,
+    tilt_y: Option<PenAxis>,
+    tool: PenTool,
+    barrel_button: bool,
+    /// The serial of the tool in proximity, from the `Wacom Serial IDs` property of the device.
+    serial: Option<u64>,
+    /// Whether the tool came in proximity, which is sent along with its next motion.
+    entering: bool,
+    /// The window the pen last moved over, with its position there.
+    window: Option<(ffi::Window, PhysicalPosition<f64>)>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct PenAxis {
+    number: i32,
+    min: f64,
+    max: f64,
+    value: f64,
+}
+
+/// The tool of the device of a pen, from the names the drivers give to the devices of the tools.
+fn pen_tool(name: &str) -> PenTool {
+    let name = name.to_lowercase();
+    if name.ends_with("eraser") {
+        PenTool::Eraser
+    } else if name.ends_with("cursor") {
+        PenTool::Mouse
+    } else {
+        PenTool::Pen
+    }
+}
+
+impl PenAxis {
+    fn new(info: &ffi::XIValuatorClassInfo) -> Self {
+        PenAxis {
+            number: info.number,
+            min: info.min,
+            max: info.max,
+            value: info.value,
+        }
+    }
+}
+
+impl PenAxes {
+    /// Updates the values of the axes from the valuators of an event.
+    fn update(&mut self, valuators: &ffi::XIValuatorState) {
+        let mask = unsafe { slice::from_raw_parts(valuators.mask, valuators.mask_len as usize) };
+        let mut value = valuators.values;
+        for i in 0..valuators.mask_len * 8 {
+            if ffi::XIMaskIsSet(mask, i) {
+                let mut axes = [
+                    Some(&mut self.pressure),
+                    self.tilt_x.as_mut(),
+                    self.tilt_y.as_mut(),
+                ];
+                for axis in axes.iter_mut().flatten() {
+                    if axis.number == i {
+                        axis.value = unsafe { *value };
+                    }
+                }
+                value = unsafe { value.offset(1) };
+            }
+        }
+    }
+
+    fn event(
+        &self,
+        device_id: RootDeviceId,
+        phase: PenPhase,
+        location: PhysicalPosition<f64>,
+        time: Instant,
+        tool_id: u64,
+    ) -> PenEvent {
+        let pressure = &self.pressure;
+        let pressure = if pressure.max > pressure.min {
+            ((pressure.value - pressure.min) / (pressure.max - pressure.min)).clamp(0., 1.)
+        } else {
+            0.
+        };
+        // The tilt axes of the drivers count degrees.
+        let tilt = match (self.tilt_x, self.tilt_y) {
+            (Some(x), Some(y)) => Some((x.value.clamp(-90., 90.), y.value.clamp(-90., 90.))),
+            _ => None,
+        };
+        PenEvent {
+            device_id,
+            phase,
+            location,
+            time,
+            pressure,
+            tilt,
+            twist: None,
+            eraser: self.tool == PenTool::Eraser,
+            barrel_button: self.barrel_button,
+            tool_id,
+            tool: self.tool,
+            serial: self.serial,
+        }
+    }
+
+    /// Updates the tool in proximity from the `Wacom Serial IDs` property of the device, giving
+    /// the window the pen left when it's out of proximity.
+    fn update_serial_ids(&mut self, ids: &[u32]) -> Option<(ffi::Window, PhysicalPosition<f64>)> {
+        // The items are the id of the tablet, the serial and the id of the previous tool, and the
+        // serial and the id of the tool in proximity, whose serial is 0 once it left.
+        let serial = *ids.get(3)?;
+        if serial != 0 {
+            self.entering = self.serial != Some(serial as u64) || self.window.is_none();
+            self.serial = Some(serial as u64);
+            None
+        } else {
+            self.entering = false;
+            self.window.take()
+        }
+    }
+}
+
 impl Device {
     fn new<T: 'static>(el: &EventProcessor<T>, info: &ffi::XIDeviceInfo) -> Self {
         let name = unsafe { CStr::from_ptr(info.name).to_string_lossy() };
         let mut scroll_axes = Vec::new();
+        let mut pen_axes = (None, None, None);
 
         let wt = get_xtarget(&el.target);
 
@@ -624,7 +1306,7 @@ impl Device {
                 | ffi::XI_RawKeyReleaseMask;
             // The request buffer is flushed when we poll for events
             wt.xconn
-                .select_xinput_events(wt.root, info.deviceid, mask)
+                .select_xinput_events(wt.root, info.deviceid, mask.into())
                 .queue();
 
             // Identify scroll axes
@@ -648,14 +1330,48 @@ impl Device {
                             },
                         ));
                     }
+                    ffi::XIValuatorClass => {
+                        let info = unsafe {
+                            mem::transmute::<&ffi::XIAnyClassInfo, &ffi::XIValuatorClassInfo>(class)
+                        };
+                        let axis = Some(PenAxis::new(info));
+                        let label = info.label;
+                        unsafe {
+                            if label == wt.xconn.get_atom_unchecked(b"Abs Pressure\0") {
+                                pen_axes.0 = axis;
+                            } else if label == wt.xconn.get_atom_unchecked(b"Abs Tilt X\0") {
+                                pen_axes.1 = axis;
+                            } else if label == wt.xconn.get_atom_unchecked(b"Abs Tilt Y\0") {
+                                pen_axes.2 = axis;
+                            }
+                        }
+                    }
                     _ => {}
                 }
             }
         }
 
+        // The pens are the pointers with a pressure, whose drivers add a device for the eraser.
+        let pen = match pen_axes {
+            (Some(pressure), tilt_x, tilt_y) if info._use != ffi::XISlaveKeyboard => {
+                Some(PenAxes {
+                    pressure,
+                    tilt_x,
+                    tilt_y,
+                    tool: pen_tool(&name),
+                    barrel_button: false,
+                    serial: None,
+                    entering: false,
+                    window: None,
+                })
+            }
+            _ => None,
+        };
+
         let mut device = Device {
             name: name.into_owned(),
             scroll_axes,
+            pen,
             attachment: info.attachment,
         };
         device.reset_scroll_position(info);
@@ -685,6 +1401,94 @@ impl Device {
         }
     }
 
+    fn input_device(xconn: &XConnection, info: &ffi::XIDeviceInfo) -> InputDevice {
+        let name = unsafe { CStr::from_ptr(info.name) }
+            .to_string_lossy()
+            .into_owned();
+        // The property is set by the drivers of the physical devices.
+        let product_id = unsafe { xconn.get_atom_unchecked(b"Device Product ID\0") };
+        let (vendor_id, product_id) = match xconn
+            .get_device_property_u32(info.deviceid, product_id)
+            .as_deref()
+        {
+            Some(&[vendor, product, ..]) => (Some(vendor as u16), Some(product as u16)),
+            _ => (None, None),
+        };
+        InputDevice {
+            id: mkdid(info.deviceid),
+            name,
+            vendor_id,
+            product_id,
+            capabilities: Device::capabilities(xconn, info),
+            resolution: Device::resolution(info),
+        }
+    }
+
+    /// The resolution of the horizontal axis, which the servers report in units per meter.
+    fn resolution(info: &ffi::XIDeviceInfo) -> Option<u32> {
+        if !Device::physical_device(info) {
+            return None;
+        }
+        Device::classes(info).iter().find_map(|&class_ptr| {
+            let class = unsafe { &*class_ptr };
+            if class._type != ffi::XIValuatorClass {
+                return None;
+            }
+            let info =
+                unsafe { mem::transmute::<&ffi::XIAnyClassInfo, &ffi::XIValuatorClassInfo>(class) };
+            // The drivers which don't know the resolution report 0 or 1.
+            if info.number != 0 || info.resolution <= 1 {
+                return None;
+            }
+            Some((info.resolution as f64 * 0.0254).round() as u32)
+        })
+    }
+
+    /// The kinds of input of the device, from its classes.
+    fn capabilities(xconn: &XConnection, info: &ffi::XIDeviceInfo) -> DeviceCapabilities {
+        let mut capabilities = DeviceCapabilities::empty();
+        match info._use {
+            ffi::XIMasterKeyboard | ffi::XISlaveKeyboard => {
+                return DeviceCapabilities::KEYBOARD;
+            }
+            ffi::XIMasterPointer => return DeviceCapabilities::POINTER,
+            _ => (),
+        }
+        let pressure = unsafe { xconn.get_atom_unchecked(b"Abs Pressure\0") };
+        for class_ptr in Device::classes(info) {
+            let class = unsafe { &**class_ptr };
+            match class._type {
+                ffi::XIKeyClass if info._use == ffi::XIFloatingSlave => {
+                    capabilities |= DeviceCapabilities::KEYBOARD;
+                }
+                ffi::XIButtonClass => capabilities |= DeviceCapabilities::POINTER,
+                ffi::XIValuatorClass => {
+                    let info = unsafe {
+                        mem::transmute::<&ffi::XIAnyClassInfo, &ffi::XIValuatorClassInfo>(class)
+                    };
+                    if info.label == pressure {
+                        capabilities |= DeviceCapabilities::PEN;
+                    }
+                }
+                ffi::XITouchClass => {
+                    let info = unsafe {
+                        mem::transmute::<&ffi::XIAnyClassInfo, &ffi::XITouchClassInfo>(class)
+                    };
+                    // The dependent touches are the ones of the touchpads, which move the pointer.
+                    if info.mode == ffi::XIDirectTouch {
+                        capabilities |= DeviceCapabilities::TOUCH;
+                    }
+                }
+                _ => {}
+            }
+        }
+        // The pens and the touch screens also emulate a pointer.
+        if capabilities.intersects(DeviceCapabilities::PEN | DeviceCapabilities::TOUCH) {
+            capabilities.remove(DeviceCapabilities::POINTER);
+        }
+        capabilities
+    }
+
     #[inline]
     fn physical_device(info: &ffi::XIDeviceInfo) -> bool {
         info._use == ffi::XISlaveKeyboard
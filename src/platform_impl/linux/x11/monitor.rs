@@ -11,8 +11,13 @@ use super::{
 };
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
-    monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
-    platform_impl::{MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode},
+    monitor::{
+        ColorCapabilities, MonitorHandle as RootMonitorHandle, MonitorIdentity,
+        VideoMode as RootVideoMode,
+    },
+    platform_impl::{
+        MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode, X11_BACKEND,
+    },
 };
 
 // Used for testing. This should always be committed as false.
@@ -78,6 +83,14 @@ pub struct MonitorHandle {
     pub(crate) rect: util::AaRect,
     /// Supported video modes on this monitor
     video_modes: Vec<VideoMode>,
+    /// The color capabilities read from the EDID
+    pub(crate) color_capabilities: ColorCapabilities,
+    /// The identity read from the EDID, boxed to keep the handles small in the events
+    identity: Option<Box<MonitorIdentity>>,
+    /// If the output supports the variable refresh rate
+    vrr_capable: bool,
+    /// The refresh rate of the current mode, in millihertz
+    refresh_rate: Option<u32>,
 }
 
 impl PartialEq for MonitorHandle {
@@ -114,9 +127,16 @@ impl MonitorHandle {
         crtc: *mut XRRCrtcInfo,
         primary: bool,
     ) -> Option<Self> {
-        let (name, scale_factor, video_modes) = unsafe { xconn.get_output_info(resources, crtc)? };
+        let util::OutputInfo {
+            name,
+            scale_factor,
+            video_modes,
+            edid,
+            vrr_capable,
+        } = unsafe { xconn.get_output_info(resources, crtc)? };
         let dimensions = unsafe { ((*crtc).width as u32, (*crtc).height as u32) };
         let position = unsafe { ((*crtc).x as i32, (*crtc).y as i32) };
+        let refresh_rate = unsafe { util::crtc_refresh_rate(resources, crtc) };
         let rect = util::AaRect::new(position, dimensions);
         Some(MonitorHandle {
             id,
@@ -127,6 +147,13 @@ impl MonitorHandle {
             primary,
             rect,
             video_modes,
+            color_capabilities: edid
+                .as_ref()
+                .map(|edid| edid.color_capabilities())
+                .unwrap_or_default(),
+            identity: edid.map(|edid| Box::new(edid.identity())),
+            vrr_capable,
+            refresh_rate,
         })
     }
 
@@ -140,6 +167,10 @@ impl MonitorHandle {
             primary: true,
             rect: util::AaRect::new((0, 0), (1, 1)),
             video_modes: Vec::new(),
+            color_capabilities: Default::default(),
+            identity: None,
+            vrr_capable: false,
+            refresh_rate: None,
         }
     }
 
@@ -180,6 +211,42 @@ impl MonitorHandle {
             }
         })
     }
+
+    #[inline]
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        self.color_capabilities.clone()
+    }
+
+    #[inline]
+    pub fn supports_variable_refresh(&self) -> bool {
+        self.vrr_capable
+    }
+
+    #[inline]
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        self.identity.as_deref().cloned()
+    }
+
+    /// The part of the work area on the monitor, which is read again as the panels may change it.
+    #[inline]
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        let work_area = match X11_BACKEND.lock().as_ref() {
+            Ok(xconn) => {
+                let root = unsafe { (xconn.xlib.XDefaultRootWindow)(xconn.display) };
+                xconn.get_work_area(root)
+            }
+            Err(_) => None,
+        };
+        work_area
+            .and_then(|work_area| work_area.get_overlapping_rect(&self.rect))
+            .map(|(position, size)| (position.into(), size.into()))
+            .unwrap_or_else(|| (self.position(), self.size()))
+    }
+
+    #[inline]
+    pub(crate) fn refresh_rate_millihertz(&self) -> Option<u32> {
+        self.refresh_rate
+    }
 }
 
 impl XConnection {
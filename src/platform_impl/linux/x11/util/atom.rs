@@ -68,4 +68,14 @@ impl XConnection {
         );*/
         Ok(atoms)
     }
+
+    pub fn get_atom_name(&self, atom: ffi::Atom) -> Result<String, XError> {
+        unsafe {
+            let name = (self.xlib.XGetAtomName)(self.display, atom);
+            self.check_errors()?;
+            let string = CStr::from_ptr(name).to_string_lossy().into_owned();
+            (self.xlib.XFree)(name as *mut _);
+            Ok(string)
+        }
+    }
 }
@@ -1,18 +1,126 @@
-use crate::window::CursorIcon;
+use std::{
+    ffi::{CStr, CString},
+    io, slice,
+    sync::Arc,
+};
+
+use crate::{
+    dpi::PhysicalPosition,
+    icon::{BadIcon, RgbaIcon},
+    window::{CursorFrame, CursorIcon},
+};
 
 use super::*;
 
+/// A cursor created from an image, freed along with its last clone.
+#[derive(Clone)]
+pub struct CustomCursor {
+    inner: Arc<CursorHandle>,
+}
+
+struct CursorHandle {
+    xconn: Arc<XConnection>,
+    cursor: ffi::Cursor,
+}
+
+impl CustomCursor {
+    pub(crate) fn new(
+        xconn: &Arc<XConnection>,
+        image: &RgbaIcon,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        let cursor = xconn
+            .create_image_cursor(image, (hotspot.x, hotspot.y))
+            .ok_or_else(|| {
+                BadIcon::OsError(io::Error::other(
+                    "Failed to create the cursor from the image",
+                ))
+            })?;
+        Ok(CustomCursor::from_raw(xconn, cursor))
+    }
+
+    /// Creates a cursor which the X server animates, as the cursors of the animated themes.
+    pub(crate) fn new_animated(
+        xconn: &Arc<XConnection>,
+        frames: &[CursorFrame],
+    ) -> Result<CustomCursor, BadIcon> {
+        let cursor = xconn.create_animated_cursor(frames).ok_or_else(|| {
+            BadIcon::OsError(io::Error::other(
+                "Failed to create the cursor from the frames",
+            ))
+        })?;
+        Ok(CustomCursor::from_raw(xconn, cursor))
+    }
+
+    fn from_raw(xconn: &Arc<XConnection>, cursor: ffi::Cursor) -> CustomCursor {
+        CustomCursor {
+            inner: Arc::new(CursorHandle {
+                xconn: xconn.clone(),
+                cursor,
+            }),
+        }
+    }
+}
+
+impl Drop for CursorHandle {
+    fn drop(&mut self) {
+        // The windows keep using the cursor after it's freed.
+        unsafe { (self.xconn.xlib.XFreeCursor)(self.xconn.display, self.cursor) };
+    }
+}
+
 impl XConnection {
     pub fn set_cursor_icon(&self, window: ffi::Window, cursor: Option<CursorIcon>) {
         let cursor = *self
             .cursor_cache
             .lock()
             .entry(cursor)
-            .or_insert_with(|| self.get_cursor(cursor));
+            .or_insert_with(|| self.get_cursor(cursor, None));
 
         self.update_cursor(window, cursor);
     }
 
+    /// Sets the cursor icon of the window, loaded from the theme at the size in pixels instead
+    /// of the theme of the display.
+    pub fn set_themed_cursor_icon(
+        &self,
+        window: ffi::Window,
+        cursor: CursorIcon,
+        theme: &str,
+        size: u32,
+    ) {
+        let xcursor = *self
+            .themed_cursor_cache
+            .lock()
+            .entry((cursor, theme.to_owned(), size))
+            .or_insert_with(|| match CString::new(theme) {
+                Ok(theme) => self.get_cursor(Some(cursor), Some((&theme, size as c_int))),
+                Err(_) => 0,
+            });
+
+        // The cursors missing from the theme come from the one of the display.
+        if xcursor == 0 {
+            self.set_cursor_icon(window, Some(cursor));
+        } else {
+            self.update_cursor(window, xcursor);
+        }
+    }
+
+    /// The cursor theme of the display and the size of its cursors in pixels, which come from
+    /// `XCURSOR_THEME` and `XCURSOR_SIZE` or the `Xcursor` resources.
+    pub fn cursor_theme(&self) -> (String, u32) {
+        unsafe {
+            let theme = (self.xcursor.XcursorGetTheme)(self.display);
+            let theme = if theme.is_null() {
+                "default".to_owned()
+            } else {
+                CStr::from_ptr(theme).to_string_lossy().into_owned()
+            };
+            let size = (self.xcursor.XcursorGetDefaultSize)(self.display);
+            (theme, size.max(0) as u32)
+        }
+    }
+
     fn create_empty_cursor(&self) -> ffi::Cursor {
         let data = 0;
         let pixmap = unsafe {
@@ -44,15 +152,31 @@ impl XConnection {
         }
     }
 
-    fn load_cursor(&self, name: &[u8]) -> ffi::Cursor {
+    /// Loads the cursor from the theme and at the size given, or from the ones of the display.
+    fn load_cursor(&self, name: &[u8], theme: Option<(&CStr, c_int)>) -> ffi::Cursor {
+        let name = name.as_ptr() as *const c_char;
         unsafe {
-            (self.xcursor.XcursorLibraryLoadCursor)(self.display, name.as_ptr() as *const c_char)
+            let (theme, size) = match theme {
+                Some(theme) => theme,
+                None => return (self.xcursor.XcursorLibraryLoadCursor)(self.display, name),
+            };
+            let images = (self.xcursor.XcursorLibraryLoadImages)(name, theme.as_ptr(), size);
+            if images.is_null() {
+                return 0;
+            }
+            let xcursor = (self.xcursor.XcursorImagesLoadCursor)(self.display, images);
+            (self.xcursor.XcursorImagesDestroy)(images);
+            xcursor
         }
     }
 
-    fn load_first_existing_cursor(&self, names: &[&[u8]]) -> ffi::Cursor {
+    fn load_first_existing_cursor(
+        &self,
+        names: &[&[u8]],
+        theme: Option<(&CStr, c_int)>,
+    ) -> ffi::Cursor {
         for name in names.iter() {
-            let xcursor = self.load_cursor(name);
+            let xcursor = self.load_cursor(name, theme);
             if xcursor != 0 {
                 return xcursor;
             }
@@ -60,15 +184,15 @@ impl XConnection {
         0
     }
 
-    fn get_cursor(&self, cursor: Option<CursorIcon>) -> ffi::Cursor {
+    fn get_cursor(&self, cursor: Option<CursorIcon>, theme: Option<(&CStr, c_int)>) -> ffi::Cursor {
         let cursor = match cursor {
             Some(cursor) => cursor,
             None => return self.create_empty_cursor(),
         };
 
-        let load = |name: &[u8]| self.load_cursor(name);
+        let load = |name: &[u8]| self.load_cursor(name, theme);
 
-        let loadn = |names: &[&[u8]]| self.load_first_existing_cursor(names);
+        let loadn = |names: &[&[u8]]| self.load_first_existing_cursor(names, theme);
 
         // Try multiple names in some cases where the name
         // differs on the desktop environments or themes.
@@ -119,6 +243,109 @@ impl XConnection {
         }
     }
 
+    /// Shows the image in place of the cursor over the window, until the cursor is set again.
+    pub fn set_custom_cursor(&self, window: ffi::Window, cursor: &CustomCursor) {
+        self.update_cursor(window, cursor.inner.cursor);
+    }
+
+    pub(crate) fn set_cursor_image(
+        &self,
+        window: ffi::Window,
+        image: &RgbaIcon,
+        hotspot: (u32, u32),
+    ) {
+        match self.create_image_cursor(image, hotspot) {
+            Some(cursor) => unsafe {
+                self.update_cursor(window, cursor);
+                // The window keeps using the cursor after it's freed.
+                (self.xlib.XFreeCursor)(self.display, cursor);
+            },
+            None => warn!("Failed to create the cursor from the image"),
+        }
+    }
+
+    /// Creates a cursor showing the image, which the caller has to free.
+    fn create_image_cursor(&self, image: &RgbaIcon, hotspot: (u32, u32)) -> Option<ffi::Cursor> {
+        unsafe {
+            let xcursor_image = self.xcursor_image(image, hotspot, 0);
+            if xcursor_image.is_null() {
+                return None;
+            }
+            let cursor = (self.xcursor.XcursorImageLoadCursor)(self.display, xcursor_image);
+            (self.xcursor.XcursorImageDestroy)(xcursor_image);
+            if cursor == 0 {
+                None
+            } else {
+                Some(cursor)
+            }
+        }
+    }
+
+    /// Creates a cursor cycling through the frames, which the caller has to free.
+    fn create_animated_cursor(&self, frames: &[CursorFrame]) -> Option<ffi::Cursor> {
+        unsafe {
+            let xcursor_images = (self.xcursor.XcursorImagesCreate)(frames.len() as c_int);
+            if xcursor_images.is_null() {
+                return None;
+            }
+            // The images added so far are destroyed along with the list.
+            for frame in frames {
+                let delay = frame.delay.as_millis().min(u32::MAX as u128) as u32;
+                let xcursor_image =
+                    self.xcursor_image(&frame.image, (frame.hotspot.x, frame.hotspot.y), delay);
+                if xcursor_image.is_null() {
+                    (self.xcursor.XcursorImagesDestroy)(xcursor_images);
+                    return None;
+                }
+                let index = (*xcursor_images).nimage as usize;
+                *(*xcursor_images).images.add(index) = xcursor_image;
+                (*xcursor_images).nimage += 1;
+            }
+
+            let cursor = (self.xcursor.XcursorImagesLoadCursor)(self.display, xcursor_images);
+            (self.xcursor.XcursorImagesDestroy)(xcursor_images);
+            if cursor == 0 {
+                None
+            } else {
+                Some(cursor)
+            }
+        }
+    }
+
+    /// Creates an Xcursor image of the image, shown for the delay in milliseconds when it's a
+    /// frame of an animated cursor.
+    unsafe fn xcursor_image(
+        &self,
+        image: &RgbaIcon,
+        hotspot: (u32, u32),
+        delay: u32,
+    ) -> *mut ffi::XcursorImage {
+        let xcursor_image =
+            (self.xcursor.XcursorImageCreate)(image.width as c_int, image.height as c_int);
+        if xcursor_image.is_null() {
+            return xcursor_image;
+        }
+
+        // The hotspot has to be inside the image.
+        (*xcursor_image).xhot = hotspot.0.min(image.width.saturating_sub(1));
+        (*xcursor_image).yhot = hotspot.1.min(image.height.saturating_sub(1));
+        (*xcursor_image).delay = delay;
+        let pixels = slice::from_raw_parts_mut(
+            (*xcursor_image).pixels,
+            (image.width * image.height) as usize,
+        );
+        // The pixels are ARGB with premultiplied alpha.
+        for (pixel, rgba) in pixels.iter_mut().zip(image.rgba.chunks_exact(4)) {
+            let alpha = rgba[3] as u32;
+            let premultiply = |channel: u8| channel as u32 * alpha / 255;
+            *pixel = (alpha << 24)
+                | (premultiply(rgba[0]) << 16)
+                | (premultiply(rgba[1]) << 8)
+                | premultiply(rgba[2]);
+        }
+        xcursor_image
+    }
+
     fn update_cursor(&self, window: ffi::Window, cursor: ffi::Cursor) {
         unsafe {
             (self.xlib.XDefineCursor)(self.display, window, cursor);
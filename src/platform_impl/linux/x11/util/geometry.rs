@@ -27,7 +27,7 @@ impl AaRect {
         x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
     }
 
-    pub fn get_overlapping_area(&self, other: &Self) -> i64 {
+    pub fn get_overlapping_size(&self, other: &Self) -> (u32, u32) {
         let x_overlap = cmp::max(
             0,
             cmp::min(self.x + self.width, other.x + other.width) - cmp::max(self.x, other.x),
@@ -36,7 +36,21 @@ impl AaRect {
             0,
             cmp::min(self.y + self.height, other.y + other.height) - cmp::max(self.y, other.y),
         );
-        x_overlap * y_overlap
+        (x_overlap as u32, y_overlap as u32)
+    }
+
+    pub fn get_overlapping_rect(&self, other: &Self) -> Option<((i32, i32), (u32, u32))> {
+        let (width, height) = self.get_overlapping_size(other);
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let (x, y) = (cmp::max(self.x, other.x), cmp::max(self.y, other.y));
+        Some(((x as i32, y as i32), (width, height)))
+    }
+
+    pub fn get_overlapping_area(&self, other: &Self) -> i64 {
+        let (x_overlap, y_overlap) = self.get_overlapping_size(other);
+        x_overlap as i64 * y_overlap as i64
     }
 }
 
@@ -216,6 +230,36 @@ impl XConnection {
         })
     }
 
+    /// The area of the current desktop left for the windows by the panels and the docks, which
+    /// spans all the monitors.
+    pub fn get_work_area(&self, root: ffi::Window) -> Option<AaRect> {
+        let work_area_atom = unsafe { self.get_atom_unchecked(b"_NET_WORKAREA\0") };
+        let desktop_atom = unsafe { self.get_atom_unchecked(b"_NET_CURRENT_DESKTOP\0") };
+
+        if !hint_is_supported(work_area_atom) {
+            return None;
+        }
+
+        // There's a work area for each desktop.
+        let desktop = self
+            .get_property::<c_ulong>(root, desktop_atom, ffi::XA_CARDINAL)
+            .ok()
+            .and_then(|desktop| desktop.first().cloned())
+            .unwrap_or(0) as usize;
+        let work_areas: Vec<c_ulong> = self
+            .get_property(root, work_area_atom, ffi::XA_CARDINAL)
+            .ok()?;
+
+        work_areas
+            .get(desktop * 4..desktop * 4 + 4)
+            .map(|work_area| {
+                AaRect::new(
+                    (work_area[0] as i32, work_area[1] as i32),
+                    (work_area[2] as u32, work_area[3] as u32),
+                )
+            })
+    }
+
     pub fn is_top_level(&self, window: ffi::Window, root: ffi::Window) -> Option<bool> {
         let client_list_atom = unsafe { self.get_atom_unchecked(b"_NET_CLIENT_LIST\0") };
 
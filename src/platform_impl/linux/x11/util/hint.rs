@@ -136,6 +136,10 @@ impl MotifHints {
         }
     }
 
+    pub fn decorations(&self) -> bool {
+        self.hints.flags & mwm::MWM_HINTS_DECORATIONS == 0 || self.hints.decorations != 0
+    }
+
     pub fn set_decorations(&mut self, decorations: bool) {
         self.hints.flags |= mwm::MWM_HINTS_DECORATIONS;
         self.hints.decorations = decorations as c_ulong;
@@ -249,6 +253,21 @@ impl<'a> NormalHints<'a> {
         }
     }
 
+    pub fn set_aspect(&mut self, aspect: Option<(u32, u32)>) {
+        if let Some((width, height)) = aspect {
+            self.size_hints.flags |= ffi::PAspect;
+            // The same minimum and maximum aspect keep the ratio
+            let aspect = ffi::AspectRatio {
+                x: width as c_int,
+                y: height as c_int,
+            };
+            self.size_hints.min_aspect = aspect;
+            self.size_hints.max_aspect = aspect;
+        } else {
+            self.size_hints.flags &= !ffi::PAspect;
+        }
+    }
+
     pub fn set_base_size(&mut self, base_size: Option<(u32, u32)>) {
         if let Some((base_width, base_height)) = base_size {
             self.size_hints.flags |= ffi::PBaseSize;
@@ -5,6 +5,8 @@ use crate::event::ModifiersState;
 
 pub const VIRTUAL_CORE_POINTER: c_int = 2;
 pub const VIRTUAL_CORE_KEYBOARD: c_int = 3;
+/// The device spec of the core keyboard in the Xkb requests.
+pub const XKB_USE_CORE_KBD: c_uint = 0x0100;
 
 // A base buffer size of 1kB uses a negligible amount of RAM while preventing us from having to
 // re-allocate (and make another round-trip) in the *vast* majority of cases.
@@ -59,11 +61,12 @@ impl<'a> Drop for PointerState<'a> {
 }
 
 impl XConnection {
+    /// Selects the events of the mask, which is wide enough for the gestures of XInput 2.4.
     pub fn select_xinput_events(
         &self,
         window: c_ulong,
         device_id: c_int,
-        mask: i32,
+        mask: i64,
     ) -> Flusher<'_> {
         let mut event_mask = ffi::XIEventMask {
             deviceid: device_id,
@@ -81,7 +84,6 @@ impl XConnection {
         Flusher::new(self)
     }
 
-    #[allow(dead_code)]
     pub fn select_xkb_events(&self, device_id: c_uint, mask: c_ulong) -> Option<Flusher<'_>> {
         let status = unsafe { (self.xlib.XkbSelectEvents)(self.display, device_id, mask, mask) };
         if status == ffi::True {
@@ -91,6 +93,110 @@ impl XConnection {
         }
     }
 
+    pub fn select_xkb_event_details(
+        &self,
+        device_id: c_uint,
+        event_type: c_uint,
+        mask: c_ulong,
+    ) -> Option<Flusher<'_>> {
+        let status = unsafe {
+            (self.xlib.XkbSelectEventDetails)(self.display, device_id, event_type, mask, mask)
+        };
+        if status == ffi::True {
+            Some(Flusher::new(self))
+        } else {
+            None
+        }
+    }
+
+    /// The type of all the Xkb events, or `None` if the server lacks the extension.
+    pub fn query_xkb_event_type(&self) -> Option<c_int> {
+        let (mut opcode, mut event_type, mut error_type) = (0, 0, 0);
+        let (mut major, mut minor) = (1, 0);
+        let status = unsafe {
+            (self.xlib.XkbQueryExtension)(
+                self.display,
+                &mut opcode,
+                &mut event_type,
+                &mut error_type,
+                &mut major,
+                &mut minor,
+            )
+        };
+        if status == ffi::True {
+            Some(event_type)
+        } else {
+            None
+        }
+    }
+
+    /// The 32-bit items of the property of the device, such as the ones set by its driver.
+    pub fn get_device_property_u32(
+        &self,
+        device_id: c_int,
+        property: ffi::Atom,
+    ) -> Option<Vec<u32>> {
+        self.get_device_property(device_id, property, 32, |data, num_items| {
+            // Unlike the properties of the windows, the 32-bit items of XInput aren't longs.
+            unsafe { slice::from_raw_parts(data as *const u32, num_items) }.to_vec()
+        })
+    }
+
+    /// The string property of the device, such as the node the server reads it from.
+    pub fn get_device_property_string(
+        &self,
+        device_id: c_int,
+        property: ffi::Atom,
+    ) -> Option<String> {
+        self.get_device_property(device_id, property, 8, |data, num_items| {
+            let bytes = unsafe { slice::from_raw_parts(data, num_items) };
+            // The strings may hold their terminating nul.
+            let bytes = bytes.split(|&byte| byte == 0).next().unwrap_or_default();
+            String::from_utf8_lossy(bytes).into_owned()
+        })
+    }
+
+    fn get_device_property<T>(
+        &self,
+        device_id: c_int,
+        property: ffi::Atom,
+        format: c_int,
+        read: impl FnOnce(*const c_uchar, usize) -> T,
+    ) -> Option<T> {
+        let mut type_return = 0;
+        let mut format_return = 0;
+        let mut num_items = 0;
+        let mut bytes_after = 0;
+        let mut data = ptr::null_mut();
+        let status = unsafe {
+            (self.xinput2.XIGetProperty)(
+                self.display,
+                device_id,
+                property,
+                0,
+                // The length is in 4-byte units.
+                64,
+                ffi::False,
+                ffi::AnyPropertyType as c_ulong,
+                &mut type_return,
+                &mut format_return,
+                &mut num_items,
+                &mut bytes_after,
+                &mut data,
+            )
+        };
+        if status != ffi::Success as c_int || data.is_null() {
+            return None;
+        }
+        let items = if format_return == format {
+            Some(read(data, num_items as usize))
+        } else {
+            None
+        };
+        unsafe { (self.xlib.XFree)(data as _) };
+        items
+    }
+
     pub fn query_pointer(
         &self,
         window: ffi::Window,
@@ -1,6 +1,14 @@
-use std::{iter::Enumerate, ptr, slice::Iter};
+use std::{iter::Enumerate, mem, ptr, slice::Iter};
 
 use super::*;
+use crate::keymap::LockKeys;
+
+/// The names of the indicators of the lock keys, with the keysyms of the keys.
+const LOCK_INDICATORS: [(LockKeys, &[u8], c_uint); 3] = [
+    (LockKeys::CAPS_LOCK, b"Caps Lock\0", ffi::XK_Caps_Lock),
+    (LockKeys::NUM_LOCK, b"Num Lock\0", ffi::XK_Num_Lock),
+    (LockKeys::SCROLL_LOCK, b"Scroll Lock\0", ffi::XK_Scroll_Lock),
+];
 
 pub struct Keymap {
     keys: [u8; 32],
@@ -85,6 +93,87 @@ impl XConnection {
 
         Keymap { keys }
     }
+
+    /// Whether the keys repeat when they're held down.
+    pub fn is_auto_repeat_enabled(&self) -> Result<bool, XError> {
+        let mut state: ffi::XKeyboardState = unsafe { mem::zeroed() };
+        unsafe {
+            (self.xlib.XGetKeyboardControl)(self.display, &mut state);
+        }
+        self.check_errors()?;
+        Ok(state.global_auto_repeat != ffi::AutoRepeatModeOff)
+    }
+
+    /// The lock keys whose indicators are on, on the core keyboard.
+    pub fn lock_keys(&self) -> Result<LockKeys, XError> {
+        let mut lock_keys = LockKeys::empty();
+        for &(lock_key, name, _) in &LOCK_INDICATORS {
+            let atom = unsafe { self.get_atom_unchecked(name) };
+            let mut on = ffi::False;
+            let found = unsafe {
+                (self.xlib.XkbGetNamedIndicator)(
+                    self.display,
+                    atom,
+                    ptr::null_mut(),
+                    &mut on,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                )
+            };
+            lock_keys.set(lock_key, found == ffi::True && on == ffi::True);
+        }
+        self.check_errors()?;
+        Ok(lock_keys)
+    }
+
+    /// Turns the lock keys on or off, locking their modifiers, or setting their indicators for
+    /// the keys which lock no modifier, such as Scroll Lock usually.
+    pub fn set_lock_keys(&self, lock_keys: LockKeys, on: bool) -> Result<(), XError> {
+        for &(lock_key, name, keysym) in &LOCK_INDICATORS {
+            if !lock_keys.contains(lock_key) {
+                continue;
+            }
+            unsafe {
+                let mask = (self.xlib.XkbKeysymToModifiers)(self.display, keysym as c_ulong);
+                if mask != 0 {
+                    (self.xlib.XkbLockModifiers)(
+                        self.display,
+                        XKB_USE_CORE_KBD,
+                        mask,
+                        if on { mask } else { 0 },
+                    );
+                } else {
+                    (self.xlib.XkbSetNamedIndicator)(
+                        self.display,
+                        self.get_atom_unchecked(name),
+                        ffi::True,
+                        on as c_int,
+                        ffi::False,
+                        ptr::null_mut(),
+                    );
+                }
+            }
+        }
+        self.sync_with_server()
+    }
+
+    /// The delay and the interval of the repeats of the core keyboard, in milliseconds.
+    pub fn auto_repeat_rate(&self) -> Option<(c_uint, c_uint)> {
+        let (mut delay, mut interval) = (0, 0);
+        let status = unsafe {
+            (self.xlib.XkbGetAutoRepeatRate)(
+                self.display,
+                XKB_USE_CORE_KBD,
+                &mut delay,
+                &mut interval,
+            )
+        };
+        if status == ffi::True {
+            Some((delay, interval))
+        } else {
+            None
+        }
+    }
 }
 
 fn first_bit(b: u8) -> u8 {
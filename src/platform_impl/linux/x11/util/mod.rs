@@ -12,15 +12,21 @@ mod input;
 pub mod keys;
 mod memory;
 pub mod modifiers;
+#[cfg(feature = "software-present")]
+mod pixels;
 mod randr;
+mod visual;
 mod window_property;
 mod wm;
 
 pub use self::{
-    atom::*, client_msg::*, format::*, geometry::*, hint::*, icon::*, input::*, memory::*,
-    randr::*, window_property::*, wm::*,
+    atom::*, client_msg::*, cursor::CustomCursor, format::*, geometry::*, hint::*, icon::*,
+    input::*, memory::*, randr::*, visual::*, window_property::*, wm::*,
 };
 
+#[cfg(feature = "software-present")]
+pub use self::pixels::PixelBuffer;
+
 use std::{
     mem::{self, MaybeUninit},
     ops::BitAnd,
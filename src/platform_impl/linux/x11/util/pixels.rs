@@ -0,0 +1,223 @@
+// The pixels presented in software are put in the window through the shared memory of the
+// MIT-SHM extension, or sent through the connection when the server can't attach it, as over
+// the network.
+
+use std::{slice, sync::Arc};
+
+use super::*;
+
+/// The pixels presented in a window, from the image in the shared memory if any.
+pub struct PixelBuffer {
+    xconn: Arc<XConnection>,
+    window: ffi::Window,
+    gc: ffi::GC,
+    visual: *mut ffi::Visual,
+    depth: c_int,
+    // Whether the server could attach the shared memory so far
+    shm: bool,
+    shm_image: Option<ShmImage>,
+}
+
+struct ShmImage {
+    info: ffi::XShmSegmentInfo,
+    image: *mut ffi::XImage,
+    size: (u32, u32),
+}
+
+// The pointers are only used behind the lock of the window.
+unsafe impl Send for PixelBuffer {}
+
+impl PixelBuffer {
+    /// Creates the buffer of the window, if its visual has the `0x00RRGGBB` layout of the
+    /// pixels.
+    pub fn new(xconn: &Arc<XConnection>, window: ffi::Window) -> Option<PixelBuffer> {
+        let attributes = unsafe {
+            let mut attributes = MaybeUninit::<ffi::XWindowAttributes>::uninit();
+            if (xconn.xlib.XGetWindowAttributes)(xconn.display, window, attributes.as_mut_ptr())
+                == 0
+            {
+                return None;
+            }
+            attributes.assume_init()
+        };
+
+        let visual = unsafe { &*attributes.visual };
+        if (attributes.depth != 24 && attributes.depth != 32)
+            || visual.red_mask != 0xff0000
+            || visual.green_mask != 0xff00
+            || visual.blue_mask != 0xff
+        {
+            return None;
+        }
+
+        let gc = unsafe { (xconn.xlib.XCreateGC)(xconn.display, window, 0, ptr::null_mut()) };
+        let shm = xconn.xext.as_ref().map_or(false, |xext| unsafe {
+            (xext.XShmQueryExtension)(xconn.display) != ffi::False
+        });
+
+        Some(PixelBuffer {
+            xconn: Arc::clone(xconn),
+            window,
+            gc,
+            visual: attributes.visual,
+            depth: attributes.depth,
+            shm,
+            shm_image: None,
+        })
+    }
+
+    /// Puts the pixels at the top-left corner of the window.
+    pub fn present(&mut self, pixels: &[u32], size: (u32, u32)) -> Result<(), XError> {
+        // The pixels are opaque in the windows with an alpha channel.
+        let alpha = if self.depth == 32 { 0xff000000 } else { 0 };
+
+        if self.shm {
+            match self.shm_image(size) {
+                Some(image) => unsafe {
+                    let stride = (*image).bytes_per_line as usize / 4;
+                    let data = slice::from_raw_parts_mut(
+                        (*image).data as *mut u32,
+                        stride * size.1 as usize,
+                    );
+                    for (row, rgb_row) in data
+                        .chunks_exact_mut(stride)
+                        .zip(pixels.chunks_exact(size.0 as usize))
+                    {
+                        for (pixel, &rgb) in row.iter_mut().zip(rgb_row) {
+                            *pixel = rgb | alpha;
+                        }
+                    }
+                    let xext = self.xconn.xext.as_ref().unwrap();
+                    (xext.XShmPutImage)(
+                        self.xconn.display,
+                        self.window,
+                        self.gc,
+                        image,
+                        0,
+                        0,
+                        0,
+                        0,
+                        size.0,
+                        size.1,
+                        ffi::False,
+                    );
+                    // The segment is written again once the server is done reading it.
+                    return self.xconn.sync_with_server();
+                },
+                None => {
+                    debug!("Failed to attach the shared memory, sending the pixels instead");
+                    self.shm = false;
+                }
+            }
+        }
+
+        let mut data: Vec<u32> = pixels.iter().map(|&rgb| rgb | alpha).collect();
+        unsafe {
+            let image = (self.xconn.xlib.XCreateImage)(
+                self.xconn.display,
+                self.visual,
+                self.depth as c_uint,
+                ffi::ZPixmap,
+                0,
+                data.as_mut_ptr() as *mut c_char,
+                size.0,
+                size.1,
+                32,
+                0,
+            );
+            if image.is_null() {
+                return self.xconn.check_errors();
+            }
+            (self.xconn.xlib.XPutImage)(
+                self.xconn.display,
+                self.window,
+                self.gc,
+                image,
+                0,
+                0,
+                0,
+                0,
+                size.0,
+                size.1,
+            );
+            // The pixels are freed with the vector rather than with the image.
+            (*image).data = ptr::null_mut();
+            (self.xconn.xlib.XDestroyImage)(image);
+        }
+        self.xconn.flush_requests()
+    }
+
+    /// The image in the shared memory of the size, created again when the size changed.
+    fn shm_image(&mut self, size: (u32, u32)) -> Option<*mut ffi::XImage> {
+        match self.shm_image {
+            Some(ref shm_image) if shm_image.size == size => return Some(shm_image.image),
+            _ => (),
+        }
+        if let Some(shm_image) = self.shm_image.take() {
+            unsafe { self.destroy_shm_image(shm_image) };
+        }
+
+        let xext = self.xconn.xext.as_ref()?;
+        unsafe {
+            let mut info: ffi::XShmSegmentInfo = mem::zeroed();
+            let image = (xext.XShmCreateImage)(
+                self.xconn.display,
+                self.visual,
+                self.depth as c_uint,
+                ffi::ZPixmap,
+                ptr::null_mut(),
+                &mut info,
+                size.0,
+                size.1,
+            );
+            if image.is_null() {
+                return None;
+            }
+
+            let len = (*image).bytes_per_line as usize * size.1 as usize;
+            info.shmid = libc::shmget(libc::IPC_PRIVATE, len, libc::IPC_CREAT | 0o600);
+            if info.shmid == -1 {
+                (self.xconn.xlib.XDestroyImage)(image);
+                return None;
+            }
+            info.shmaddr = libc::shmat(info.shmid, ptr::null(), 0) as *mut c_char;
+            // The segment is removed once both the client and the server detached it.
+            let attached = info.shmaddr as isize != -1
+                && (xext.XShmAttach)(self.xconn.display, &mut info) != ffi::False
+                && self.xconn.sync_with_server().is_ok();
+            libc::shmctl(info.shmid, libc::IPC_RMID, ptr::null_mut());
+            if !attached {
+                if info.shmaddr as isize != -1 {
+                    libc::shmdt(info.shmaddr as *const _);
+                }
+                (self.xconn.xlib.XDestroyImage)(image);
+                return None;
+            }
+
+            (*image).data = info.shmaddr;
+            self.shm_image = Some(ShmImage { info, image, size });
+            Some(image)
+        }
+    }
+
+    unsafe fn destroy_shm_image(&self, mut shm_image: ShmImage) {
+        let xext = self.xconn.xext.as_ref().unwrap();
+        (xext.XShmDetach)(self.xconn.display, &mut shm_image.info);
+        libc::shmdt(shm_image.info.shmaddr as *const _);
+        // The data is the shared memory, which isn't freed with the image.
+        (*shm_image.image).data = ptr::null_mut();
+        (self.xconn.xlib.XDestroyImage)(shm_image.image);
+    }
+}
+
+impl Drop for PixelBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(shm_image) = self.shm_image.take() {
+                self.destroy_shm_image(shm_image);
+            }
+            (self.xconn.xlib.XFreeGC)(self.xconn.display, self.gc);
+        }
+        let _ = self.xconn.flush_requests();
+    }
+}
@@ -1,10 +1,25 @@
 use std::{env, slice, str::FromStr};
 
 use super::{
-    ffi::{CurrentTime, RRCrtc, RRMode, Success, XRRCrtcInfo, XRRScreenResources},
+    ffi::{
+        CurrentTime, RRCrtc, RRMode, RROutput, Success, XRRCrtcInfo, XRRModeInfo,
+        XRRScreenResources,
+    },
     *,
 };
-use crate::{dpi::validate_scale_factor, platform_impl::platform::x11::VideoMode};
+use crate::{
+    dpi::validate_scale_factor,
+    platform_impl::{edid::Edid, platform::x11::VideoMode},
+};
+
+/// The properties of the output a CRTC drives.
+pub struct OutputInfo {
+    pub name: String,
+    pub scale_factor: f64,
+    pub video_modes: Vec<VideoMode>,
+    pub edid: Option<Edid>,
+    pub vrr_capable: bool,
+}
 
 /// Represents values of `WINIT_HIDPI_FACTOR`.
 pub enum EnvVarDPI {
@@ -30,6 +45,33 @@ pub fn calc_dpi_factor(
     dpi_factor
 }
 
+/// The refresh rate of the mode in millihertz, or `0` if it's unknown.
+fn mode_refresh_rate(mode: &XRRModeInfo) -> u64 {
+    if mode.dotClock > 0 && mode.hTotal > 0 && mode.vTotal > 0 {
+        mode.dotClock * 1000 / (mode.hTotal as u64 * mode.vTotal as u64)
+    } else {
+        0
+    }
+}
+
+/// Returns the refresh rate in millihertz of the mode the CRTC currently outputs.
+///
+/// # Safety
+///
+/// `resources` and `crtc` must be valid, with `crtc` being one of the CRTCs of `resources`.
+pub unsafe fn crtc_refresh_rate(
+    resources: *mut XRRScreenResources,
+    crtc: *mut XRRCrtcInfo,
+) -> Option<u32> {
+    let resource_modes = slice::from_raw_parts((*resources).modes, (*resources).nmode as usize);
+    resource_modes
+        .iter()
+        .find(|mode| mode.id == (*crtc).mode)
+        .map(mode_refresh_rate)
+        .filter(|&refresh_rate| refresh_rate > 0)
+        .map(|refresh_rate| refresh_rate as u32)
+}
+
 impl XConnection {
     // Retrieve DPI from Xft.dpi property
     pub unsafe fn get_xft_dpi(&self) -> Option<f64> {
@@ -53,9 +95,9 @@ impl XConnection {
         &self,
         resources: *mut XRRScreenResources,
         crtc: *mut XRRCrtcInfo,
-    ) -> Option<(String, f64, Vec<VideoMode>)> {
-        let output_info =
-            (self.xrandr.XRRGetOutputInfo)(self.display, resources, *(*crtc).outputs.offset(0));
+    ) -> Option<OutputInfo> {
+        let output = *(*crtc).outputs.offset(0);
+        let output_info = (self.xrandr.XRRGetOutputInfo)(self.display, resources, output);
         if output_info.is_null() {
             // When calling `XRRGetOutputInfo` on a virtual monitor (versus a physical display)
             // it's possible for it to return null.
@@ -77,11 +119,7 @@ impl XConnection {
             // modes in the array in XRRScreenResources
             .filter(|x| output_modes.iter().any(|id| x.id == *id))
             .map(|x| {
-                let refresh_rate = if x.dotClock > 0 && x.hTotal > 0 && x.vTotal > 0 {
-                    x.dotClock as u64 * 1000 / (x.hTotal as u64 * x.vTotal as u64)
-                } else {
-                    0
-                };
+                let refresh_rate = mode_refresh_rate(x);
 
                 VideoMode {
                     size: (x.width, x.height),
@@ -157,8 +195,71 @@ impl XConnection {
             }
         };
 
+        // The EDID is 128 bytes long per block, with up to 3 extensions in practice.
+        let edid = self
+            .get_output_property::<c_uchar>(output, b"EDID\0", 128)
+            .and_then(|edid| Edid::parse(&edid));
+        // The drivers supporting the variable refresh rate tell it through this property.
+        let vrr_capable = self
+            .get_output_property::<c_long>(output, b"vrr_capable\0", 1)
+            .and_then(|vrr_capable| vrr_capable.first().copied())
+            == Some(1);
+
         (self.xrandr.XRRFreeOutputInfo)(output_info);
-        Some((name, scale_factor, modes))
+        Some(OutputInfo {
+            name,
+            scale_factor,
+            video_modes: modes,
+            edid,
+            vrr_capable,
+        })
+    }
+
+    /// Returns the property of the output, when the driver exposes it with the format of `T`.
+    ///
+    /// The length is in 32 bits units.
+    ///
+    /// # Safety
+    ///
+    /// `output` must be an output of the display, and `property` must be nul-terminated.
+    pub unsafe fn get_output_property<T: Formattable>(
+        &self,
+        output: RROutput,
+        property: &[u8],
+        length: c_long,
+    ) -> Option<Vec<T>> {
+        let property_atom = self.get_atom_unchecked(property);
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut nitems = 0;
+        let mut bytes_after = 0;
+        let mut data = ptr::null_mut();
+        let status = (self.xrandr.XRRGetOutputProperty)(
+            self.display,
+            output,
+            property_atom,
+            0,
+            length,
+            ffi::False,
+            ffi::False,
+            ffi::AnyPropertyType as ffi::Atom,
+            &mut actual_type,
+            &mut actual_format,
+            &mut nitems,
+            &mut bytes_after,
+            &mut data,
+        );
+        if status != Success as c_int || data.is_null() {
+            return None;
+        }
+
+        let value = if actual_format == T::FORMAT as c_int && nitems > 0 {
+            Some(slice::from_raw_parts(data as *const T, nitems as usize).to_vec())
+        } else {
+            None
+        };
+        (self.xlib.XFree)(data as _);
+        value
     }
     pub fn set_crtc_config(&self, crtc_id: RRCrtc, mode_id: RRMode) -> Result<(), ()> {
         unsafe {
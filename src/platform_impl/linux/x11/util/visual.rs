@@ -0,0 +1,60 @@
+use super::*;
+
+/// The visual an X window is created with, which sets the depth of its pixels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum VisualPreference {
+    /// The 32-bit ARGB visual for the transparent windows, and the visual of the root window for
+    /// the other ones.
+    Default,
+    /// A 32-bit visual with an alpha channel, which the compositing managers blend the window
+    /// with.
+    Transparent,
+    /// A 30-bit visual, with 10 bits per channel.
+    DeepColor,
+    /// The visual of the id, as the one of the configurations chosen with EGL or GLX.
+    Id(ffi::VisualID),
+}
+
+impl XConnection {
+    /// Finds a true color visual of the depth on the screen.
+    pub fn match_visual(&self, screen: c_int, depth: c_int) -> Option<ffi::XVisualInfo> {
+        unsafe {
+            let mut visual_info = MaybeUninit::<ffi::XVisualInfo>::uninit();
+            let status = (self.xlib.XMatchVisualInfo)(
+                self.display,
+                screen,
+                depth,
+                ffi::TrueColor,
+                visual_info.as_mut_ptr(),
+            );
+            if status == 0 {
+                None
+            } else {
+                Some(visual_info.assume_init())
+            }
+        }
+    }
+
+    /// Finds the visual of the id on the screen.
+    pub fn visual_from_id(&self, screen: c_int, id: ffi::VisualID) -> Option<ffi::XVisualInfo> {
+        unsafe {
+            let mut template: ffi::XVisualInfo = mem::zeroed();
+            template.visualid = id;
+            template.screen = screen;
+            let mut count = 0;
+            let visual_infos = (self.xlib.XGetVisualInfo)(
+                self.display,
+                ffi::VisualIDMask | ffi::VisualScreenMask,
+                &mut template,
+                &mut count,
+            );
+            let visual_infos = XSmartPointer::new(self, visual_infos)?;
+            if count == 0 {
+                None
+            } else {
+                Some(*visual_infos)
+            }
+        }
+    }
+}
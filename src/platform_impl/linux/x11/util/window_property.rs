@@ -75,7 +75,9 @@ impl XConnection {
                     return Err(GetPropertyError::XError(e));
                 }
 
-                if actual_type != property_type {
+                if property_type != ffi::AnyPropertyType as ffi::Atom
+                    && actual_type != property_type
+                {
                     return Err(GetPropertyError::TypeMismatch(actual_type));
                 }
 
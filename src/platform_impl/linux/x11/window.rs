@@ -14,22 +14,35 @@ use mio_misc::channel::Sender;
 use parking_lot::Mutex;
 
 use crate::{
+    clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker},
+    dnd::{DndActions, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
-    error::{ExternalError, NotSupportedError, OsError as RootOsError},
-    monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+    error::{ExternalError, HandleError, NotSupportedError, OsError as RootOsError},
+    monitor::{self, MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
     platform_impl::{
         x11::{ime::ImeContextCreationError, MonitorHandle as X11MonitorHandle},
         MonitorHandle as PlatformMonitorHandle, OsError, PlatformSpecificWindowBuilderAttributes,
-        VideoMode as PlatformVideoMode,
+        VideoMode as PlatformVideoMode, WindowId as PlatformWindowId,
     },
-    window::{CursorIcon, Fullscreen, Icon, UserAttentionType, WindowAttributes},
+    window::{
+        Backdrop, CursorIcon, CursorTheme, EdgeFlags, Fullscreen, Icon, ImePurpose, Placement,
+        Ratio, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    },
+};
+
+use super::{
+    clipboard::Clipboard, dnd::DragSource, ffi, util, CustomCursor, EventLoopWindowTarget,
+    ImeSender, WindowId, XConnection, XError,
 };
 
-use super::{ffi, util, EventLoopWindowTarget, ImeSender, WindowId, XConnection, XError};
+// The `_NET_WM_DESKTOP` of the windows visible on all the desktops
+const ALL_DESKTOPS: u32 = 0xFFFF_FFFF;
 
 #[derive(Debug)]
 pub struct SharedState {
     pub cursor_pos: Option<(f64, f64)>,
+    // Where the cursor is put back while the pointer is locked
+    pub locked_cursor_pos: Option<(f64, f64)>,
     pub size: Option<(u32, u32)>,
     pub position: Option<(i32, i32)>,
     pub inner_position: Option<(i32, i32)>,
@@ -41,6 +54,8 @@ pub struct SharedState {
     pub desired_fullscreen: Option<Option<Fullscreen>>,
     // Used to restore position after exiting fullscreen
     pub restore_position: Option<(i32, i32)>,
+    // Used to restore the size and the decorations after spanning the monitors
+    pub restore_span: Option<((u32, u32), bool)>,
     // Used to restore video mode after exiting fullscreen
     pub desktop_video_mode: Option<(ffi::RRCrtc, ffi::RRMode)>,
     pub frame_extents: Option<util::FrameExtentsHeuristic>,
@@ -49,6 +64,14 @@ pub struct SharedState {
     pub resize_increments: Option<Size>,
     pub base_size: Option<Size>,
     pub visibility: Visibility,
+    // The bounds last sent with `RecommendedBounds`
+    pub recommended_bounds: Option<(u32, u32)>,
+    // Whether the window was last sent as occluded
+    pub occluded: bool,
+    // The edges last sent with `TiledStateChanged`
+    pub tiled_edges: EdgeFlags,
+    // The workspace last sent with `WorkspaceChanged`, once the window manager set it
+    pub workspace: Option<Option<u32>>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -72,6 +95,7 @@ impl SharedState {
             visibility,
 
             cursor_pos: None,
+            locked_cursor_pos: None,
             size: None,
             position: None,
             inner_position: None,
@@ -80,12 +104,17 @@ impl SharedState {
             fullscreen: None,
             desired_fullscreen: None,
             restore_position: None,
+            restore_span: None,
             desktop_video_mode: None,
             frame_extents: None,
             min_inner_size: None,
             max_inner_size: None,
             resize_increments: None,
             base_size: None,
+            recommended_bounds: None,
+            occluded: false,
+            tiled_edges: EdgeFlags::empty(),
+            workspace: None,
         })
     }
 }
@@ -99,11 +128,39 @@ pub struct UnownedWindow {
     root: ffi::Window,           // never changes
     screen_id: i32,              // never changes
     cursor: Mutex<CursorIcon>,
+    // Shown instead of the cursor icon until the icon is set again
+    custom_cursor: Mutex<Option<CustomCursor>>,
+    // The theme and the logical size of the cursor icons, empty and zero for the ones of the display
+    cursor_theme: Mutex<(String, u32)>,
     cursor_grabbed: Mutex<bool>,
     cursor_visible: Mutex<bool>,
+    // The pointer is grabbed while it's locked, whether the cursor is grabbed or not
+    pointer_locked: Mutex<bool>,
+    // The pointer is grabbed while it's captured, unless the cursor is grabbed or the pointer locked
+    pointer_captured: Mutex<bool>,
+    // The area of the window the grabbed cursor is confined to, by the pointer barriers around it
+    cursor_grab_area: Mutex<Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>>,
+    pointer_barriers: Mutex<Vec<ffi::PointerBarrier>>,
+    key_repeat: Mutex<bool>,
+    // Whether the server has the Present extension, which times the vsync redraws
+    present: bool, // never changes
+    vsync_redraws: Mutex<bool>,
+    // Whether a requested redraw waits for the vertical blank Present is to notify
+    redraw_held: Mutex<bool>,
+    // The pixels presented in software, once they were
+    #[cfg(feature = "software-present")]
+    pixel_buffer: Mutex<Option<util::PixelBuffer>>,
+    // The keyboard is grabbed while the window has focus, so the window manager gets no keys
+    shortcuts_inhibited: Mutex<bool>,
+    keyboard_grabbed: Mutex<bool>,
     ime_sender: Mutex<ImeSender>,
     pub shared_state: Mutex<SharedState>,
     redraw_sender: Sender<WindowId>,
+    video_mode_sender: Sender<(WindowId, bool)>,
+    clipboard: Arc<Clipboard>,
+    drag_source: Arc<DragSource>,
+    // Whether the server destroyed the window before it was dropped
+    destroyed: Mutex<bool>,
 }
 
 impl UnownedWindow {
@@ -174,15 +231,65 @@ impl UnownedWindow {
             dimensions
         };
 
+        // The windows without a position are placed as requested
+        let position = match window_attrs.placement {
+            Some(placement)
+                if position.is_none()
+                    && window_attrs.fullscreen.is_none()
+                    && !window_attrs.maximized =>
+            {
+                let owner = match window_attrs.owner {
+                    Some(RootWindowId(PlatformWindowId::X(owner))) => Some(owner.0),
+                    _ => None,
+                };
+                Some(Self::placed_position(
+                    event_loop,
+                    placement,
+                    owner,
+                    dimensions.into(),
+                    &guessed_monitor,
+                    scale_factor,
+                ))
+            }
+            _ => position,
+        };
+
         let screen_id = match pl_attribs.screen_id {
             Some(id) => id,
             None => unsafe { (xconn.xlib.XDefaultScreen)(xconn.display) },
         };
 
+        let visual_infos = match (pl_attribs.visual_infos, pl_attribs.visual_preference) {
+            (Some(visual_infos), _) => Some(visual_infos),
+            (None, util::VisualPreference::Default) if window_attrs.transparent => {
+                xconn.match_visual(screen_id, 32)
+            }
+            (None, util::VisualPreference::Default) => None,
+            (None, util::VisualPreference::Transparent) => {
+                let visual_infos = xconn.match_visual(screen_id, 32);
+                if visual_infos.is_none() {
+                    warn!("The screen has no 32-bit visual, using the default visual");
+                }
+                visual_infos
+            }
+            (None, util::VisualPreference::DeepColor) => {
+                let visual_infos = xconn.match_visual(screen_id, 30);
+                if visual_infos.is_none() {
+                    warn!("The screen has no 30-bit visual, using the default visual");
+                }
+                visual_infos
+            }
+            (None, util::VisualPreference::Id(id)) => Some(
+                xconn
+                    .visual_from_id(screen_id, id)
+                    .ok_or_else(|| os_error!(OsError::XMisc("The visual isn't on the screen")))?,
+            ),
+        };
+
         // creating
         let mut set_win_attr = {
             let mut swa: ffi::XSetWindowAttributes = unsafe { mem::zeroed() };
-            swa.colormap = if let Some(vi) = pl_attribs.visual_infos {
+            swa.colormap = if let Some(vi) = visual_infos {
                 unsafe {
                     let visual = vi.visual;
                     (xconn.xlib.XCreateColormap)(xconn.display, root, visual, ffi::AllocNone)
@@ -193,6 +300,7 @@ impl UnownedWindow {
             swa.event_mask = ffi::ExposureMask
                 | ffi::StructureNotifyMask
                 | ffi::VisibilityChangeMask
+                | ffi::PropertyChangeMask
                 | ffi::KeyPressMask
                 | ffi::KeyReleaseMask
                 | ffi::KeymapStateMask
@@ -220,20 +328,12 @@ impl UnownedWindow {
                 dimensions.0 as c_uint,
                 dimensions.1 as c_uint,
                 0,
-                match pl_attribs.visual_infos {
+                match visual_infos {
                     Some(vi) => vi.depth,
                     None => ffi::CopyFromParent,
                 },
                 ffi::InputOutput as c_uint,
-                // TODO: If window wants transparency and `visual_infos` is None,
-                // we need to find our own visual which has an `alphaMask` which
-                // is > 0, like we do in glutin.
-                //
-                // It is non obvious which masks, if any, we should pass to
-                // `XGetVisualInfo`. winit doesn't receive any info about what
-                // properties the user wants. Users should consider choosing the
-                // visual themselves as glutin does.
-                match pl_attribs.visual_infos {
+                match visual_infos {
                     Some(vi) => vi.visual,
                     None => ffi::CopyFromParent as *mut ffi::Visual,
                 },
@@ -249,10 +349,28 @@ impl UnownedWindow {
             screen_id,
             cursor: Default::default(),
             cursor_grabbed: Mutex::new(false),
+            pointer_captured: Mutex::new(false),
+            custom_cursor: Mutex::new(None),
+            cursor_theme: Mutex::new((String::new(), 0)),
             cursor_visible: Mutex::new(true),
+            pointer_locked: Mutex::new(false),
+            cursor_grab_area: Mutex::new(None),
+            pointer_barriers: Mutex::new(Vec::new()),
+            key_repeat: Mutex::new(window_attrs.key_repeat),
+            present: event_loop.present,
+            vsync_redraws: Mutex::new(false),
+            redraw_held: Mutex::new(false),
+            #[cfg(feature = "software-present")]
+            pixel_buffer: Mutex::new(None),
+            shortcuts_inhibited: Mutex::new(false),
+            keyboard_grabbed: Mutex::new(false),
             ime_sender: Mutex::new(event_loop.ime_sender.clone()),
             shared_state: SharedState::new(guessed_monitor, window_attrs.visible),
             redraw_sender: event_loop.redraw_sender.clone(),
+            video_mode_sender: event_loop.video_mode_sender.clone(),
+            clipboard: Arc::clone(&event_loop.clipboard),
+            drag_source: Arc::clone(&event_loop.drag_source),
+            destroyed: Mutex::new(false),
         };
 
         // Title must be set before mapping. Some tiling window managers (i.e. i3) use the window
@@ -262,6 +380,9 @@ impl UnownedWindow {
         window
             .set_decorations_inner(window_attrs.decorations)
             .queue();
+        if !window_attrs.shadow {
+            window.set_shadow_inner(false).queue();
+        }
 
         {
             // Enable drag and drop (TODO: extend API to make this toggleable)
@@ -365,6 +486,13 @@ impl UnownedWindow {
                 xconn.set_normal_hints(window.xwindow, normal_hints).queue();
             }
 
+            // The window manager keeps the transient windows above their owner
+            if let Some(RootWindowId(PlatformWindowId::X(owner))) = window_attrs.owner {
+                unsafe {
+                    (xconn.xlib.XSetTransientForHint)(xconn.display, window.xwindow, owner.0);
+                }
+            }
+
             // Set window icons
             if let Some(icon) = window_attrs.window_icon {
                 window.set_icon_inner(icon).queue();
@@ -417,12 +545,33 @@ impl UnownedWindow {
                     | ffi::XI_TouchBeginMask
                     | ffi::XI_TouchUpdateMask
                     | ffi::XI_TouchEndMask;
+                let mut mask = i64::from(mask);
+                if event_loop.xi2_gestures {
+                    mask |= ffi::XI_GesturePinchBeginMask
+                        | ffi::XI_GesturePinchUpdateMask
+                        | ffi::XI_GesturePinchEndMask
+                        | ffi::XI_GestureSwipeBeginMask
+                        | ffi::XI_GestureSwipeUpdateMask
+                        | ffi::XI_GestureSwipeEndMask;
+                }
                 mask
             };
             xconn
                 .select_xinput_events(window.xwindow, ffi::XIAllMasterDevices, mask)
                 .queue();
 
+            // Select the completions of the presentations, for the frames drawn through Present
+            // and the vertical blanks the redraws wait for
+            if let Some(xpresent) = xconn.xpresent.as_ref().filter(|_| event_loop.present) {
+                unsafe {
+                    (xpresent.XPresentSelectInput)(
+                        xconn.display,
+                        window.xwindow,
+                        ffi::PresentCompleteNotifyMask as c_uint,
+                    );
+                }
+            }
+
             {
                 let result = event_loop.ime.borrow_mut().create_context(window.xwindow);
                 if let Err(err) = result {
@@ -465,6 +614,85 @@ impl UnownedWindow {
             .map_err(|x_err| os_error!(OsError::XError(x_err)))
     }
 
+    // Returns the position of the window of `size` placed within the work area of the monitor of
+    // its owner, or of the pointer
+    fn placed_position<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        placement: Placement,
+        owner: Option<ffi::Window>,
+        size: PhysicalSize<u32>,
+        pointer_monitor: &X11MonitorHandle,
+        scale_factor: f64,
+    ) -> PhysicalPosition<i32> {
+        let xconn = &event_loop.xconn;
+        let root = event_loop.root;
+
+        // The window managers not giving the work area leave the whole monitor
+        let work_area = xconn.get_work_area(root);
+        let bounds = |monitor: &X11MonitorHandle| {
+            work_area
+                .as_ref()
+                .and_then(|work_area| work_area.get_overlapping_rect(&monitor.rect))
+                .map(|(position, size)| (position.into(), size.into()))
+                .unwrap_or_else(|| (monitor.position(), monitor.size()))
+        };
+        let center_of = |(position, size): (PhysicalPosition<i32>, PhysicalSize<u32>)| {
+            PhysicalPosition::new(
+                position.x + size.width as i32 / 2,
+                position.y + size.height as i32 / 2,
+            )
+        };
+
+        let owner_rect = owner.and_then(|owner| {
+            let coords = xconn.translate_coords(owner, root).ok()?;
+            let geometry = xconn.get_geometry(owner).ok()?;
+            Some((
+                PhysicalPosition::new(coords.x_rel_root, coords.y_rel_root),
+                PhysicalSize::new(geometry.width, geometry.height),
+            ))
+        });
+        match (placement, owner_rect) {
+            (Placement::CenterOnParent, Some(owner_rect)) => {
+                let center = center_of(owner_rect);
+                let monitor = xconn
+                    .available_monitors()
+                    .into_iter()
+                    .find(|monitor| {
+                        monitor
+                            .rect
+                            .contains_point(center.x as i64, center.y as i64)
+                    })
+                    .unwrap_or_else(|| pointer_monitor.clone());
+                Placement::center_on(center, size, bounds(&monitor))
+            }
+            (Placement::AtCursor, _) => {
+                let bounds = bounds(pointer_monitor);
+                let center = xconn
+                    .query_pointer(root, util::VIRTUAL_CORE_POINTER)
+                    .map(|pointer| {
+                        PhysicalPosition::new(pointer.root_x as i32, pointer.root_y as i32)
+                    })
+                    .unwrap_or_else(|_| center_of(bounds));
+                Placement::center_on(center, size, bounds)
+            }
+            (Placement::Cascade, _) => {
+                let step = (32.0 * scale_factor) as i32;
+                let position = Placement::cascade_from(
+                    event_loop.last_cascaded.get(),
+                    step,
+                    size,
+                    bounds(pointer_monitor),
+                );
+                event_loop.last_cascaded.set(Some(position));
+                position
+            }
+            _ => {
+                let bounds = bounds(pointer_monitor);
+                Placement::center_on(center_of(bounds), size, bounds)
+            }
+        }
+    }
+
     fn set_pid(&self) -> Option<util::Flusher<'_>> {
         let pid_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_PID\0") };
         let client_machine_atom = unsafe { self.xconn.get_atom_unchecked(b"WM_CLIENT_MACHINE\0") };
@@ -594,40 +822,42 @@ impl UnownedWindow {
         }
         shared_state_lock.fullscreen = fullscreen.clone();
 
-        match (&old_fullscreen, &fullscreen) {
-            // Store the desktop video mode before entering exclusive
-            // fullscreen, so we can restore it upon exit, as XRandR does not
-            // provide a mechanism to set this per app-session or restore this
-            // to the desktop video mode as macOS and Windows do
-            (
-                &None,
-                &Some(Fullscreen::Exclusive(RootVideoMode {
-                    video_mode: PlatformVideoMode::X(ref video_mode),
-                })),
-            )
-            | (
-                &Some(Fullscreen::Borderless(_)),
-                &Some(Fullscreen::Exclusive(RootVideoMode {
-                    video_mode: PlatformVideoMode::X(ref video_mode),
-                })),
-            ) => {
-                let monitor = video_mode.monitor.as_ref().unwrap();
-                shared_state_lock.desktop_video_mode =
-                    Some((monitor.id, self.xconn.get_crtc_mode(monitor.id)));
+        // Restore the desktop video mode upon leaving exclusive fullscreen, or its monitor, as
+        // XRandR does not provide a mechanism to set this per app-session or restore this to
+        // the desktop video mode as macOS and Windows do
+        let new_crtc = match fullscreen {
+            Some(Fullscreen::Exclusive(RootVideoMode {
+                video_mode: PlatformVideoMode::X(ref video_mode),
+            })) => video_mode.monitor.as_ref().map(|monitor| monitor.id),
+            _ => None,
+        };
+        if let Some((crtc, mode)) = shared_state_lock.desktop_video_mode {
+            if new_crtc != Some(crtc) {
+                shared_state_lock.desktop_video_mode = None;
+                if self.xconn.set_crtc_config(crtc, mode).is_err() {
+                    warn!("Failed to restore the desktop video mode");
+                }
             }
-            // Restore desktop video mode upon exiting exclusive fullscreen
-            (&Some(Fullscreen::Exclusive(_)), &None)
-            | (&Some(Fullscreen::Exclusive(_)), &Some(Fullscreen::Borderless(_))) => {
-                let (monitor_id, mode_id) = shared_state_lock.desktop_video_mode.take().unwrap();
-                self.xconn
-                    .set_crtc_config(monitor_id, mode_id)
-                    .expect("failed to restore desktop video mode");
+        }
+        // Store the desktop video mode before entering exclusive fullscreen, so we can restore
+        // it upon exit
+        if let Some(crtc) = new_crtc {
+            if shared_state_lock.desktop_video_mode.is_none() {
+                shared_state_lock.desktop_video_mode = Some((crtc, self.xconn.get_crtc_mode(crtc)));
             }
-            _ => (),
         }
 
         drop(shared_state_lock);
 
+        // The window is restored as it was before spanning the monitors, once it stops.
+        let spanned = matches!(old_fullscreen, Some(Fullscreen::Span(_)));
+        if spanned && !matches!(fullscreen, Some(Fullscreen::Span(_))) {
+            if let Some((size, decorations)) = self.shared_state.lock().restore_span.take() {
+                self.set_decorations_inner(decorations).queue();
+                self.set_inner_size_physical(size.0, size.1);
+            }
+        }
+
         match fullscreen {
             None => {
                 let flusher = self.set_fullscreen_hint(false);
@@ -638,6 +868,34 @@ impl UnownedWindow {
                 }
                 Some(flusher)
             }
+            Some(Fullscreen::Span(monitors)) => {
+                let monitors = if monitors.is_empty() {
+                    self.available_monitors()
+                        .into_iter()
+                        .map(|monitor| RootMonitorHandle {
+                            inner: PlatformMonitorHandle::X(monitor),
+                        })
+                        .collect()
+                } else {
+                    monitors
+                };
+                let (position, size) = monitor::union_rect(monitors)?;
+
+                if !spanned {
+                    let window_position = self.outer_position_physical();
+                    let window_size = self.inner_size_physical();
+                    let decorations = self.xconn.get_motif_hints(self.xwindow).decorations();
+                    let mut shared_state_lock = self.shared_state.lock();
+                    shared_state_lock.restore_position = Some(window_position);
+                    shared_state_lock.restore_span = Some((window_size, decorations));
+                }
+
+                // The fullscreen state would keep the window on a single monitor.
+                self.set_fullscreen_hint(false).queue();
+                self.set_position_inner(position.x, position.y).queue();
+                self.set_inner_size_physical(size.width, size.height);
+                Some(self.set_decorations_inner(false))
+            }
             Some(fullscreen) => {
                 let (video_mode, monitor) = match fullscreen {
                     Fullscreen::Exclusive(RootVideoMode {
@@ -647,7 +905,8 @@ impl UnownedWindow {
                         inner: PlatformMonitorHandle::X(monitor),
                     })) => (None, monitor),
                     Fullscreen::Borderless(None) => (None, self.current_monitor()),
-                    #[cfg(feature = "wayland")]
+                    Fullscreen::Span(_) => unreachable!(),
+                    #[cfg(any(feature = "wayland", feature = "headless"))]
                     _ => unreachable!(),
                 };
 
@@ -682,9 +941,25 @@ impl UnownedWindow {
                     // mode higher than the current desktop video mode (I'm sure
                     // this will make someone unhappy, but it's very unusual for
                     // games to want to do this anyway).
-                    self.xconn
+                    let switched = self
+                        .xconn
                         .set_crtc_config(monitor.id, video_mode.native_mode)
-                        .expect("failed to set video mode");
+                        .is_ok();
+                    if !switched {
+                        // The window is left fullscreen in the desktop video mode.
+                        warn!("Failed to switch to the video mode of the exclusive fullscreen");
+                        let mut shared_state_lock = self.shared_state.lock();
+                        if let Some((crtc, mode)) = shared_state_lock.desktop_video_mode.take() {
+                            let _ = self.xconn.set_crtc_config(crtc, mode);
+                        }
+                        shared_state_lock.fullscreen =
+                            Some(Fullscreen::Borderless(Some(RootMonitorHandle {
+                                inner: PlatformMonitorHandle::X(monitor.clone()),
+                            })));
+                    }
+                    let _ = self
+                        .video_mode_sender
+                        .send((WindowId(self.xwindow), switched));
                 }
 
                 let window_position = self.outer_position_physical();
@@ -697,6 +972,15 @@ impl UnownedWindow {
         }
     }
 
+    /// Restores the desktop video mode, if the window switched the video mode of its monitor.
+    pub(crate) fn restore_desktop_video_mode(&self) {
+        if let Some((crtc, mode)) = self.shared_state.lock().desktop_video_mode.take() {
+            if self.xconn.set_crtc_config(crtc, mode).is_err() {
+                warn!("Failed to restore the desktop video mode");
+            }
+        }
+    }
+
     #[inline]
     pub fn fullscreen(&self) -> Option<Fullscreen> {
         let shared_state = self.shared_state.lock();
@@ -717,6 +1001,51 @@ impl UnownedWindow {
         }
     }
 
+    // Returns whether the occlusion changed
+    pub(crate) fn update_occlusion(&self, occluded: bool) -> bool {
+        let mut shared_state = self.shared_state.lock();
+        let changed = shared_state.occluded != occluded;
+        shared_state.occluded = occluded;
+        changed
+    }
+
+    // Returns the tiled edges if they changed, as per the `_GTK_EDGE_CONSTRAINTS` set by the
+    // window manager
+    pub(crate) fn update_tiled_edges(&self) -> Option<EdgeFlags> {
+        let constraints_atom = unsafe { self.xconn.get_atom_unchecked(b"_GTK_EDGE_CONSTRAINTS\0") };
+        let constraints = self
+            .xconn
+            .get_property::<c_ulong>(self.xwindow, constraints_atom, ffi::XA_CARDINAL)
+            .ok()
+            .and_then(|constraints| constraints.first().cloned())
+            .unwrap_or(0);
+
+        // Each edge has a bit for whether it's tiled, followed by one for whether it's resizable.
+        let mut edges = EdgeFlags::empty();
+        edges.set(EdgeFlags::TOP, constraints & (1 << 0) != 0);
+        edges.set(EdgeFlags::RIGHT, constraints & (1 << 2) != 0);
+        edges.set(EdgeFlags::BOTTOM, constraints & (1 << 4) != 0);
+        edges.set(EdgeFlags::LEFT, constraints & (1 << 6) != 0);
+
+        let mut shared_state = self.shared_state.lock();
+        if shared_state.tiled_edges == edges {
+            return None;
+        }
+        shared_state.tiled_edges = edges;
+        Some(edges)
+    }
+
+    // Returns the workspace if it changed, as per the `_NET_WM_DESKTOP` set by the window manager
+    pub(crate) fn update_workspace(&self) -> Option<Option<u32>> {
+        // The property is removed once the window is hidden, which doesn't change its workspace.
+        let workspace = self.workspace_hint()?;
+        let mut shared_state = self.shared_state.lock();
+        match shared_state.workspace.replace(workspace) {
+            Some(previous) if previous != workspace => Some(workspace),
+            _ => None,
+        }
+    }
+
     // Called by EventProcessor when a VisibilityNotify event is received
     pub(crate) fn visibility_notify(&self) {
         let mut shared_state = self.shared_state.lock();
@@ -750,6 +1079,21 @@ impl UnownedWindow {
         self.xconn.primary_monitor()
     }
 
+    // The drivers only enable the variable refresh rate for the fullscreen windows with the
+    // `_VARIABLE_REFRESH` set by Mesa
+    pub fn is_vrr_active(&self) -> bool {
+        if self.fullscreen().is_none() || !self.current_monitor().supports_variable_refresh() {
+            return false;
+        }
+
+        let vrr_atom = unsafe { self.xconn.get_atom_unchecked(b"_VARIABLE_REFRESH\0") };
+        self.xconn
+            .get_property::<c_ulong>(self.xwindow, vrr_atom, ffi::XA_CARDINAL)
+            .ok()
+            .and_then(|vrr| vrr.first().copied())
+            == Some(1)
+    }
+
     fn set_minimized_inner(&self, minimized: bool) -> util::Flusher<'_> {
         unsafe {
             if minimized {
@@ -865,6 +1209,146 @@ impl UnownedWindow {
             .expect("Failed to set always-on-top state");
     }
 
+    fn set_shadow_inner(&self, shadow: bool) -> util::Flusher<'_> {
+        // Followed by the compositors of the Compton lineage, such as picom
+        let shadow_atom = unsafe { self.xconn.get_atom_unchecked(b"_COMPTON_SHADOW\0") };
+        self.xconn.change_property(
+            self.xwindow,
+            shadow_atom,
+            ffi::XA_CARDINAL,
+            util::PropMode::Replace,
+            &[shadow as util::Cardinal],
+        )
+    }
+
+    #[inline]
+    pub fn set_shadow(&self, shadow: bool) {
+        self.set_shadow_inner(shadow)
+            .flush()
+            .expect("Failed to set shadow state");
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        let opacity_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_WINDOW_OPACITY\0") };
+        if opacity >= 1.0 {
+            // The opaque windows go without the property, which the compositors may unredirect
+            unsafe {
+                (self.xconn.xlib.XDeleteProperty)(self.xconn.display, self.xwindow, opacity_atom);
+            }
+            util::Flusher::new(&self.xconn)
+        } else {
+            let opacity = (opacity as f64 * u32::MAX as f64) as util::Cardinal;
+            self.xconn.change_property(
+                self.xwindow,
+                opacity_atom,
+                ffi::XA_CARDINAL,
+                util::PropMode::Replace,
+                &[opacity],
+            )
+        }
+        .flush()
+        .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn set_backdrop(&self, backdrop: Backdrop) -> Result<(), ExternalError> {
+        // Followed by KWin, an empty region being the whole window
+        let blur_atom = unsafe {
+            self.xconn
+                .get_atom_unchecked(b"_KDE_NET_WM_BLUR_BEHIND_REGION\0")
+        };
+        if backdrop == Backdrop::None {
+            unsafe {
+                (self.xconn.xlib.XDeleteProperty)(self.xconn.display, self.xwindow, blur_atom);
+            }
+            util::Flusher::new(&self.xconn)
+        } else {
+            self.xconn.change_property::<util::Cardinal>(
+                self.xwindow,
+                blur_atom,
+                ffi::XA_CARDINAL,
+                util::PropMode::Replace,
+                &[],
+            )
+        }
+        .flush()
+        .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn set_modal(&self, modal: bool) -> Result<(), ExternalError> {
+        let modal_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_STATE_MODAL\0") };
+        self.set_netwm(modal.into(), (modal_atom as c_long, 0, 0, 0))
+            .flush()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    // The workspace of `_NET_WM_DESKTOP`, `None` standing for all the workspaces, if it's set
+    fn workspace_hint(&self) -> Option<Option<u32>> {
+        let desktop_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_DESKTOP\0") };
+        let desktop = self
+            .xconn
+            .get_property::<c_ulong>(self.xwindow, desktop_atom, ffi::XA_CARDINAL)
+            .ok()
+            .and_then(|desktop| desktop.first().cloned())? as u32;
+        Some(Some(desktop).filter(|&desktop| desktop != ALL_DESKTOPS))
+    }
+
+    fn set_workspace_hint(&self, desktop: u32) -> util::Flusher<'_> {
+        let desktop_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_DESKTOP\0") };
+        if self.shared_state.lock().visibility == Visibility::No {
+            // The window manager reads the property once the window is mapped.
+            self.xconn.change_property(
+                self.xwindow,
+                desktop_atom,
+                ffi::XA_CARDINAL,
+                util::PropMode::Replace,
+                &[desktop as util::Cardinal],
+            )
+        } else {
+            self.xconn.send_client_msg(
+                self.xwindow,
+                self.root,
+                desktop_atom,
+                Some(ffi::SubstructureRedirectMask | ffi::SubstructureNotifyMask),
+                // The request comes from an application.
+                [desktop as c_long, 1, 0, 0, 0],
+            )
+        }
+    }
+
+    #[inline]
+    pub fn workspace(&self) -> Option<u32> {
+        self.workspace_hint().flatten()
+    }
+
+    #[inline]
+    pub fn move_to_workspace(&self, workspace: u32) {
+        self.set_workspace_hint(workspace)
+            .flush()
+            .expect("Failed to move the window to the workspace");
+    }
+
+    #[inline]
+    pub fn set_visible_on_all_workspaces(&self, visible: bool) {
+        let desktop = if visible {
+            ALL_DESKTOPS
+        } else if self.workspace_hint() == Some(None) {
+            let desktop_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_CURRENT_DESKTOP\0") };
+            self.xconn
+                .get_property::<c_ulong>(self.root, desktop_atom, ffi::XA_CARDINAL)
+                .ok()
+                .and_then(|desktop| desktop.first().cloned())
+                .unwrap_or(0) as u32
+        } else {
+            return;
+        };
+        self.set_workspace_hint(desktop)
+            .flush()
+            .expect("Failed to set the window visible on all the workspaces");
+    }
+
     fn set_icon_inner(&self, icon: Icon) -> util::Flusher<'_> {
         let icon_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_ICON\0") };
         let data = icon.to_cardinals();
@@ -1034,6 +1518,39 @@ impl UnownedWindow {
         }
     }
 
+    pub(crate) fn recommended_bounds_inner(
+        &self,
+        shared_state: &SharedState,
+    ) -> Option<(u32, u32)> {
+        let monitor = &shared_state.last_monitor;
+        if monitor.is_dummy() {
+            return None;
+        }
+
+        // The window managers not giving the work area leave the whole monitor
+        let (width, height) = match self.xconn.get_work_area(self.root) {
+            Some(work_area) => work_area.get_overlapping_size(&monitor.rect),
+            None => monitor.size().into(),
+        };
+        let (frame_width, frame_height) = match shared_state.frame_extents {
+            Some(ref frame_extents) => frame_extents.inner_size_to_outer(0, 0),
+            None => (0, 0),
+        };
+        Some((
+            width.saturating_sub(frame_width),
+            height.saturating_sub(frame_height),
+        ))
+    }
+
+    #[inline]
+    pub fn recommended_bounds(&self) -> Option<PhysicalSize<u32>> {
+        if self.shared_state.lock().frame_extents.is_none() {
+            self.update_cached_frame_extents();
+        }
+        self.recommended_bounds_inner(&self.shared_state.lock())
+            .map(Into::into)
+    }
+
     pub(crate) fn set_inner_size_physical(&self, width: u32, height: u32) {
         unsafe {
             (self.xconn.xlib.XResizeWindow)(
@@ -1091,6 +1608,13 @@ impl UnownedWindow {
         self.set_max_inner_size_physical(physical_dimensions);
     }
 
+    #[inline]
+    pub fn set_aspect_ratio(&self, ratio: Option<Ratio>) {
+        let aspect = ratio.map(|ratio| (ratio.width(), ratio.height()));
+        self.update_normal_hints(|normal_hints| normal_hints.set_aspect(aspect))
+            .expect("Failed to call `XSetWMNormalHints`");
+    }
+
     pub(crate) fn adjust_for_dpi(
         &self,
         old_scale_factor: f64,
@@ -1184,62 +1708,78 @@ impl UnownedWindow {
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
         let old_cursor = replace(&mut *self.cursor.lock(), cursor);
-        if cursor != old_cursor && *self.cursor_visible.lock() {
-            self.xconn.set_cursor_icon(self.xwindow, Some(cursor));
+        let was_custom = self.custom_cursor.lock().take().is_some();
+        if cursor != old_cursor || was_custom {
+            self.refresh_cursor();
         }
     }
 
+    #[inline]
+    pub fn set_cursor(&self, cursor: CustomCursor) {
+        *self.custom_cursor.lock() = Some(cursor);
+        self.refresh_cursor();
+    }
+
+    fn grab_pointer(&self) -> Result<(), ExternalError> {
+        let result = unsafe {
+            (self.xconn.xlib.XGrabPointer)(
+                self.xconn.display,
+                self.xwindow,
+                ffi::True,
+                (ffi::ButtonPressMask
+                    | ffi::ButtonReleaseMask
+                    | ffi::EnterWindowMask
+                    | ffi::LeaveWindowMask
+                    | ffi::PointerMotionMask
+                    | ffi::PointerMotionHintMask
+                    | ffi::Button1MotionMask
+                    | ffi::Button2MotionMask
+                    | ffi::Button3MotionMask
+                    | ffi::Button4MotionMask
+                    | ffi::Button5MotionMask
+                    | ffi::ButtonMotionMask
+                    | ffi::KeymapStateMask) as c_uint,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                self.xwindow,
+                0,
+                ffi::CurrentTime,
+            )
+        };
+
+        match result {
+            ffi::GrabSuccess => Ok(()),
+            ffi::AlreadyGrabbed => {
+                Err("Cursor could not be grabbed: already grabbed by another client")
+            }
+            ffi::GrabInvalidTime => Err("Cursor could not be grabbed: invalid time"),
+            ffi::GrabNotViewable => Err("Cursor could not be grabbed: grab location not viewable"),
+            ffi::GrabFrozen => Err("Cursor could not be grabbed: frozen by another client"),
+            _ => unreachable!(),
+        }
+        .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
     #[inline]
     pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
         let mut grabbed_lock = self.cursor_grabbed.lock();
         if grab == *grabbed_lock {
             return Ok(());
         }
+        if *self.pointer_locked.lock() {
+            // The lock holds the grab, which is left to it.
+            *grabbed_lock = grab;
+            return self.update_pointer_barriers(grab);
+        }
         unsafe {
             // We ungrab before grabbing to prevent passive grabs from causing `AlreadyGrabbed`.
             // Therefore, this is common to both codepaths.
             (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
         }
         let result = if grab {
-            let result = unsafe {
-                (self.xconn.xlib.XGrabPointer)(
-                    self.xconn.display,
-                    self.xwindow,
-                    ffi::True,
-                    (ffi::ButtonPressMask
-                        | ffi::ButtonReleaseMask
-                        | ffi::EnterWindowMask
-                        | ffi::LeaveWindowMask
-                        | ffi::PointerMotionMask
-                        | ffi::PointerMotionHintMask
-                        | ffi::Button1MotionMask
-                        | ffi::Button2MotionMask
-                        | ffi::Button3MotionMask
-                        | ffi::Button4MotionMask
-                        | ffi::Button5MotionMask
-                        | ffi::ButtonMotionMask
-                        | ffi::KeymapStateMask) as c_uint,
-                    ffi::GrabModeAsync,
-                    ffi::GrabModeAsync,
-                    self.xwindow,
-                    0,
-                    ffi::CurrentTime,
-                )
-            };
-
-            match result {
-                ffi::GrabSuccess => Ok(()),
-                ffi::AlreadyGrabbed => {
-                    Err("Cursor could not be grabbed: already grabbed by another client")
-                }
-                ffi::GrabInvalidTime => Err("Cursor could not be grabbed: invalid time"),
-                ffi::GrabNotViewable => {
-                    Err("Cursor could not be grabbed: grab location not viewable")
-                }
-                ffi::GrabFrozen => Err("Cursor could not be grabbed: frozen by another client"),
-                _ => unreachable!(),
-            }
-            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+            self.grab_pointer()
+        } else if *self.pointer_captured.lock() {
+            self.capture_grab()
         } else {
             self.xconn
                 .flush_requests()
@@ -1248,7 +1788,298 @@ impl UnownedWindow {
         if result.is_ok() {
             *grabbed_lock = grab;
         }
-        result
+        result.and_then(|()| self.update_pointer_barriers(grab))
+    }
+
+    #[inline]
+    pub fn set_cursor_grab_area(
+        &self,
+        area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        if !self.pointer_barriers_supported() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        let scale_factor = self.scale_factor();
+        let grabbed_lock = self.cursor_grabbed.lock();
+        *self.cursor_grab_area.lock() = area.map(|(position, size)| {
+            (
+                position.to_physical(scale_factor),
+                size.to_physical(scale_factor),
+            )
+        });
+        self.update_pointer_barriers(*grabbed_lock)
+    }
+
+    fn pointer_barriers_supported(&self) -> bool {
+        let xfixes = match self.xconn.xfixes.as_ref() {
+            Some(xfixes) => xfixes,
+            None => return false,
+        };
+        // The pointer barriers came with the version 5.
+        let mut major = 5;
+        let mut minor = 0;
+        unsafe {
+            (xfixes.XFixesQueryVersion)(self.xconn.display, &mut major, &mut minor) != ffi::False
+                && major >= 5
+        }
+    }
+
+    /// Puts back the pointer barriers around the area of the grab after the window moved.
+    pub(super) fn refresh_pointer_barriers(&self) {
+        let grabbed_lock = self.cursor_grabbed.lock();
+        let _ = self.update_pointer_barriers(*grabbed_lock);
+    }
+
+    /// Destroys the pointer barriers, and surrounds the area of the grab with new ones while the
+    /// cursor is grabbed.
+    fn update_pointer_barriers(&self, grabbed: bool) -> Result<(), ExternalError> {
+        let xfixes = match self.xconn.xfixes.as_ref() {
+            Some(xfixes) => xfixes,
+            None => return Ok(()),
+        };
+        let mut barriers = self.pointer_barriers.lock();
+        for barrier in barriers.drain(..) {
+            unsafe { (xfixes.XFixesDestroyPointerBarrier)(self.xconn.display, barrier) };
+        }
+
+        let area = *self.cursor_grab_area.lock();
+        if let (Some((position, size)), true) = (area, grabbed) {
+            // The barriers would keep the cursor out of the area otherwise.
+            let pointer = self
+                .xconn
+                .query_pointer(self.xwindow, util::VIRTUAL_CORE_POINTER)
+                .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+            let (x, y) = (pointer.win_x as i32, pointer.win_y as i32);
+            let (width, height) = (size.width as i32, size.height as i32);
+            if x < position.x
+                || y < position.y
+                || x >= position.x + width
+                || y >= position.y + height
+            {
+                self.set_cursor_position_physical(position.x + width / 2, position.y + height / 2)?;
+            }
+
+            // The barriers are in the coordinates of the root window.
+            let origin = self
+                .xconn
+                .translate_coords(self.xwindow, self.root)
+                .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+            let left = origin.x_rel_root + position.x;
+            let top = origin.y_rel_root + position.y;
+            let right = left + width;
+            let bottom = top + height;
+            let lines = [
+                (left, top, right, top),
+                (left, bottom, right, bottom),
+                (left, top, left, bottom),
+                (right, top, right, bottom),
+            ];
+            for &(x1, y1, x2, y2) in lines.iter() {
+                // No direction is allowed to cross them, for every device.
+                let barrier = unsafe {
+                    (xfixes.XFixesCreatePointerBarrier)(
+                        self.xconn.display,
+                        self.root,
+                        x1,
+                        y1,
+                        x2,
+                        y2,
+                        0,
+                        0,
+                        ptr::null_mut(),
+                    )
+                };
+                barriers.push(barrier);
+            }
+        }
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn set_pointer_lock(&self, lock: bool) -> Result<(), ExternalError> {
+        // Locked in the same order as `set_cursor_grab` does.
+        let grabbed_lock = self.cursor_grabbed.lock();
+        let mut locked_lock = self.pointer_locked.lock();
+        if lock == *locked_lock {
+            return Ok(());
+        }
+        let grabbed = *grabbed_lock;
+        if lock {
+            let pointer = self
+                .xconn
+                .query_pointer(self.xwindow, util::VIRTUAL_CORE_POINTER)
+                .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+            if !grabbed {
+                unsafe {
+                    (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
+                }
+                self.grab_pointer()?;
+            }
+            // The event processor puts the cursor back here whenever it moves, while the raw
+            // motion keeps coming as `DeviceEvent::MouseMotion`.
+            self.shared_state.lock().locked_cursor_pos = Some((pointer.win_x, pointer.win_y));
+            *locked_lock = true;
+        } else {
+            self.shared_state.lock().locked_cursor_pos = None;
+            if !grabbed {
+                unsafe {
+                    (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
+                }
+                if *self.pointer_captured.lock() {
+                    self.capture_grab()?;
+                }
+            }
+            *locked_lock = false;
+        }
+        drop(locked_lock);
+        drop(grabbed_lock);
+        self.refresh_cursor();
+        Ok(())
+    }
+
+    /// Grabs the pointer without confining it, for the window to get its events wherever it is.
+    fn capture_grab(&self) -> Result<(), ExternalError> {
+        let mut mask = ffi::XI_MotionMask
+            | ffi::XI_ButtonPressMask
+            | ffi::XI_ButtonReleaseMask
+            | ffi::XI_EnterMask
+            | ffi::XI_LeaveMask;
+        let mut event_mask = ffi::XIEventMask {
+            deviceid: util::VIRTUAL_CORE_POINTER,
+            mask: &mut mask as *mut _ as *mut c_uchar,
+            mask_len: mem::size_of_val(&mask) as c_int,
+        };
+        let result = unsafe {
+            (self.xconn.xinput2.XIGrabDevice)(
+                self.xconn.display,
+                util::VIRTUAL_CORE_POINTER,
+                self.xwindow,
+                ffi::CurrentTime,
+                0,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                ffi::True,
+                &mut event_mask,
+            )
+        };
+        match result {
+            ffi::GrabSuccess => Ok(()),
+            ffi::AlreadyGrabbed => {
+                Err("Pointer could not be captured: already grabbed by another client")
+            }
+            ffi::GrabNotViewable => Err("Pointer could not be captured: window not viewable"),
+            _ => Err("Pointer could not be captured"),
+        }
+        .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
+    #[inline]
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        // Locked in the same order as `set_pointer_lock` does.
+        let grabbed_lock = self.cursor_grabbed.lock();
+        let locked_lock = self.pointer_locked.lock();
+        let mut captured_lock = self.pointer_captured.lock();
+        if *captured_lock {
+            return Ok(());
+        }
+        // The grab of the cursor or of the locked pointer gets the events already.
+        if !*grabbed_lock && !*locked_lock {
+            self.capture_grab()?;
+        }
+        *captured_lock = true;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
+        let grabbed_lock = self.cursor_grabbed.lock();
+        let locked_lock = self.pointer_locked.lock();
+        let mut captured_lock = self.pointer_captured.lock();
+        if !*captured_lock {
+            return Ok(());
+        }
+        if !*grabbed_lock && !*locked_lock {
+            unsafe {
+                (self.xconn.xinput2.XIUngrabDevice)(
+                    self.xconn.display,
+                    util::VIRTUAL_CORE_POINTER,
+                    ffi::CurrentTime,
+                );
+            }
+        }
+        *captured_lock = false;
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    /// Shows the custom cursor or the cursor icon of the window, or none when it's hidden or the
+    /// pointer is locked.
+    pub(crate) fn refresh_cursor(&self) {
+        if !*self.cursor_visible.lock() || *self.pointer_locked.lock() {
+            self.xconn.set_cursor_icon(self.xwindow, None);
+        } else if let Some(ref cursor) = *self.custom_cursor.lock() {
+            self.xconn.set_custom_cursor(self.xwindow, cursor);
+        } else {
+            let cursor = *self.cursor.lock();
+            match self.themed_cursor() {
+                Some((theme, size)) => {
+                    self.xconn
+                        .set_themed_cursor_icon(self.xwindow, cursor, &theme, size)
+                }
+                None => self.xconn.set_cursor_icon(self.xwindow, Some(cursor)),
+            }
+        }
+    }
+
+    /// The theme of the cursor icons and their size in pixels, when they aren't the ones of the
+    /// display.
+    fn themed_cursor(&self) -> Option<(String, u32)> {
+        let (theme, size) = self.cursor_theme.lock().clone();
+        if theme.is_empty() && size == 0 {
+            return None;
+        }
+        let (display_theme, display_size) = self.xconn.cursor_theme();
+        let theme = if theme.is_empty() {
+            display_theme
+        } else {
+            theme
+        };
+        let size = if size == 0 {
+            display_size
+        } else {
+            (size as f64 * self.scale_factor()).round() as u32
+        };
+        Some((theme, size))
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) -> Result<(), ExternalError> {
+        *self.cursor_theme.lock() = (name.to_owned(), size);
+        self.refresh_cursor();
+        Ok(())
+    }
+
+    #[inline]
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        let (theme, size) = self.cursor_theme.lock().clone();
+        let (display_theme, display_size) = self.xconn.cursor_theme();
+        // The size of the display is in pixels.
+        let size = if size == 0 {
+            (display_size as f64 / self.scale_factor()).round() as u32
+        } else {
+            size
+        };
+        Some(CursorTheme {
+            name: Some(if theme.is_empty() {
+                display_theme
+            } else {
+                theme
+            }),
+            size,
+        })
     }
 
     #[inline]
@@ -1257,14 +2088,9 @@ impl UnownedWindow {
         if visible == *visible_lock {
             return;
         }
-        let cursor = if visible {
-            Some(*self.cursor.lock())
-        } else {
-            None
-        };
         *visible_lock = visible;
         drop(visible_lock);
-        self.xconn.set_cursor_icon(self.xwindow, cursor);
+        self.refresh_cursor();
     }
 
     #[inline]
@@ -1283,8 +2109,10 @@ impl UnownedWindow {
 
     #[inline]
     pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
-        let (x, y) = position.to_physical::<i32>(self.scale_factor()).into();
-        self.set_cursor_position_physical(x, y)
+        // The position is in the pixel it falls into, since rounding it could put the cursor
+        // out of the window at the fractional scale factors.
+        let position = position.to_physical::<f64>(self.scale_factor());
+        self.set_cursor_position_physical(position.x.floor() as i32, position.y.floor() as i32)
     }
 
     pub fn drag_window(&self) -> Result<(), ExternalError> {
@@ -1302,11 +2130,18 @@ impl UnownedWindow {
         let mut grabbed_lock = self.cursor_grabbed.lock();
         unsafe {
             (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
+            // The window manager takes the pointer for the move, from the capture as well.
+            (self.xconn.xinput2.XIUngrabDevice)(
+                self.xconn.display,
+                util::VIRTUAL_CORE_POINTER,
+                ffi::CurrentTime,
+            );
         }
         self.xconn
             .flush_requests()
             .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
         *grabbed_lock = false;
+        *self.pointer_captured.lock() = false;
 
         // we keep the lock until we are done
         self.xconn
@@ -1335,9 +2170,135 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn set_ime_position(&self, spot: Position) {
-        let (x, y) = spot.to_physical::<i32>(self.scale_factor()).into();
-        self.set_ime_position_physical(x, y);
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        self.clipboard
+            .set(kind, provider)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn read_clipboard(
+        &self,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), ExternalError> {
+        self.clipboard
+            .read(self.xwindow, kind, picker, consumer)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn start_drag(&self, data: DragData, actions: DndActions) -> Result<(), ExternalError> {
+        // XDND has no notion of the drag icon, so the icon replaces the cursor instead. The
+        // cursor of the window is shown during the implicit grab holding the drag.
+        let icon = data.icon.clone();
+        self.drag_source
+            .start(self.xwindow, data, actions)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+        if let Some(icon) = icon {
+            let hotspot = (icon.hotspot.x, icon.hotspot.y);
+            self.xconn
+                .set_cursor_image(self.xwindow, &icon.image, hotspot);
+        }
+        Ok(())
+    }
+
+    /// Restores the cursor replaced by the drag icon.
+    pub fn end_drag(&self) {
+        self.refresh_cursor();
+    }
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+        let (x, y) = position.to_physical::<i32>(self.scale_factor()).into();
+        let height = size.to_physical::<i32>(self.scale_factor()).height;
+        // The candidate window is shown below the spot, which is the baseline of the text.
+        self.set_ime_position_physical(x, y + height);
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {
+        // XIM asks for the text through the string conversion callbacks, which no input method
+        // in use relies on.
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(&self, _purpose: ImePurpose) {
+        // XIM has no notion of the purpose of the text.
+    }
+
+    #[inline]
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        *self.key_repeat.lock() = key_repeat;
+    }
+
+    #[inline]
+    pub fn key_repeat(&self) -> bool {
+        *self.key_repeat.lock()
+    }
+
+    #[inline]
+    pub fn set_system_shortcuts_inhibited(&self, inhibited: bool) -> Result<(), ExternalError> {
+        *self.shortcuts_inhibited.lock() = inhibited;
+        let focused = unsafe {
+            let mut focus = 0;
+            let mut revert_to = 0;
+            (self.xconn.xlib.XGetInputFocus)(self.xconn.display, &mut focus, &mut revert_to);
+            focus == self.xwindow
+        };
+        self.update_keyboard_grab(focused)
+    }
+
+    /// Grabs the keyboard if the window has focus and inhibits the shortcuts, releasing it
+    /// otherwise.
+    pub(crate) fn update_keyboard_grab(&self, focused: bool) -> Result<(), ExternalError> {
+        let grab = focused && *self.shortcuts_inhibited.lock();
+        let mut grabbed_lock = self.keyboard_grabbed.lock();
+        if grab == *grabbed_lock {
+            return Ok(());
+        }
+        let result = if grab {
+            let result = unsafe {
+                (self.xconn.xlib.XGrabKeyboard)(
+                    self.xconn.display,
+                    self.xwindow,
+                    ffi::True,
+                    ffi::GrabModeAsync,
+                    ffi::GrabModeAsync,
+                    ffi::CurrentTime,
+                )
+            };
+
+            match result {
+                ffi::GrabSuccess => Ok(()),
+                ffi::AlreadyGrabbed => {
+                    Err("Keyboard could not be grabbed: already grabbed by another client")
+                }
+                ffi::GrabInvalidTime => Err("Keyboard could not be grabbed: invalid time"),
+                ffi::GrabNotViewable => {
+                    Err("Keyboard could not be grabbed: grab location not viewable")
+                }
+                ffi::GrabFrozen => Err("Keyboard could not be grabbed: frozen by another client"),
+                _ => unreachable!(),
+            }
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+        } else {
+            unsafe {
+                (self.xconn.xlib.XUngrabKeyboard)(self.xconn.display, ffi::CurrentTime);
+            }
+            self.xconn
+                .flush_requests()
+                .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+        };
+        if result.is_ok() {
+            *grabbed_lock = grab;
+        }
+        result
     }
 
     #[inline]
@@ -1399,15 +2360,93 @@ impl UnownedWindow {
 
     #[inline]
     pub fn request_redraw(&self) {
-        self.redraw_sender.send(WindowId(self.xwindow)).unwrap();
+        if !self.hold_redraw() {
+            self.redraw_sender.send(WindowId(self.xwindow)).unwrap();
+        }
     }
 
     #[inline]
-    pub fn raw_window_handle(&self) -> XlibHandle {
-        XlibHandle {
+    pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+        *self.vsync_redraws.lock() = vsync_redraws;
+        // The held redraw isn't left waiting.
+        if !vsync_redraws {
+            self.release_redraw();
+        }
+    }
+
+    /// Holds the requested redraw until the next vertical blank, if the redraws are synchronized
+    /// with them. Returns whether it's held.
+    fn hold_redraw(&self) -> bool {
+        let xpresent = match self.xconn.xpresent.as_ref() {
+            Some(xpresent) if self.present && *self.vsync_redraws.lock() => xpresent,
+            _ => return false,
+        };
+        let mut redraw_held = self.redraw_held.lock();
+        if !*redraw_held {
+            // The next vertical blank is the first one whose counter is a multiple of 1.
+            unsafe {
+                (xpresent.XPresentNotifyMSC)(self.xconn.display, self.xwindow, 0, 0, 1, 0);
+            }
+            if let Err(err) = self.xconn.flush_requests() {
+                warn!("Failed to wait for the vertical blank: {:?}", err);
+                return false;
+            }
+            *redraw_held = true;
+        }
+        true
+    }
+
+    /// Sends the redraw held until the vertical blank, if any.
+    pub(super) fn release_redraw(&self) {
+        if replace(&mut *self.redraw_held.lock(), false) {
+            self.redraw_sender.send(WindowId(self.xwindow)).unwrap();
+        }
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        // Currently a no-op, the redraws are synchronized through Present
+    }
+
+    #[cfg(feature = "software-present")]
+    pub fn present_pixels(
+        &self,
+        pixels: &[u32],
+        size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        let mut pixel_buffer = self.pixel_buffer.lock();
+        if pixel_buffer.is_none() {
+            *pixel_buffer = util::PixelBuffer::new(&self.xconn, self.xwindow);
+        }
+        pixel_buffer
+            .as_mut()
+            .ok_or_else(|| ExternalError::NotSupported(NotSupportedError::new()))?
+            .present(pixels, size.into())
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn window_handle(&self) -> Result<XlibHandle, HandleError> {
+        if *self.destroyed.lock() {
+            return Err(HandleError::Destroyed);
+        }
+        Ok(XlibHandle {
             window: self.xwindow,
             display: self.xconn.display as _,
             ..XlibHandle::empty()
+        })
+    }
+
+    #[inline]
+    pub fn display_handle(&self) -> XlibHandle {
+        XlibHandle {
+            display: self.xconn.display as _,
+            ..XlibHandle::empty()
         }
     }
+
+    /// Marks the window as destroyed by the server, along with its parent.
+    pub(super) fn set_destroyed(&self) {
+        *self.destroyed.lock() = true;
+    }
 }
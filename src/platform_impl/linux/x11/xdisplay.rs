@@ -18,10 +18,18 @@ pub struct XConnection {
     pub xinput2: ffi::XInput2,
     pub xlib_xcb: ffi::Xlib_xcb,
     pub xrender: ffi::Xrender,
+    /// Exposes XFixes functions, which aren't available everywhere
+    pub xfixes: Option<ffi::Xfixes>,
+    /// Exposes the Present functions, which aren't available everywhere
+    pub xpresent: Option<ffi::Xpresent>,
+    /// Exposes the MIT-SHM functions, which aren't available everywhere
+    pub xext: Option<ffi::Xext>,
     pub display: *mut ffi::Display,
     pub x11_fd: c_int,
     pub latest_error: Mutex<Option<XError>>,
     pub cursor_cache: Mutex<HashMap<Option<CursorIcon>, ffi::Cursor>>,
+    /// The cursors loaded from the themes set for the windows, by theme and size.
+    pub themed_cursor_cache: Mutex<HashMap<(CursorIcon, String, u32), ffi::Cursor>>,
 }
 
 unsafe impl Send for XConnection {}
@@ -40,6 +48,9 @@ impl XConnection {
         let xinput2 = ffi::XInput2::open()?;
         let xlib_xcb = ffi::Xlib_xcb::open()?;
         let xrender = ffi::Xrender::open()?;
+        let xfixes = ffi::Xfixes::open().ok();
+        let xpresent = ffi::Xpresent::open().ok();
+        let xext = ffi::Xext::open().ok();
 
         unsafe { (xlib.XInitThreads)() };
         unsafe { (xlib.XSetErrorHandler)(error_handler) };
@@ -64,10 +75,14 @@ impl XConnection {
             xinput2,
             xlib_xcb,
             xrender,
+            xfixes,
+            xpresent,
+            xext,
             display,
             x11_fd: fd,
             latest_error: Mutex::new(None),
             cursor_cache: Default::default(),
+            themed_cursor_cache: Default::default(),
         })
     }
 
@@ -0,0 +1,82 @@
+//! The bindings of `libxkbcommon` and `libxkbcommon-x11`, which are loaded at runtime like the
+//! other X11 and Wayland libraries.
+
+#![allow(non_camel_case_types)]
+// The keymaps are only introspected on X11.
+#![cfg_attr(not(feature = "x11"), allow(dead_code))]
+
+use std::os::raw::{c_char, c_int};
+
+pub enum xkb_context {}
+pub enum xkb_keymap {}
+pub enum xkb_state {}
+pub enum xkb_compose_table {}
+pub enum xkb_compose_state {}
+#[cfg(feature = "x11")]
+pub enum xcb_connection_t {}
+
+pub const XKB_LAYOUT_INVALID: u32 = 0xffff_ffff;
+pub const XKB_MOD_INVALID: u32 = 0xffff_ffff;
+pub const XKB_STATE_LAYOUT_EFFECTIVE: c_int = 1 << 7;
+pub const XKB_COMPOSE_FEED_ACCEPTED: c_int = 1;
+pub const XKB_COMPOSE_COMPOSING: c_int = 1;
+pub const XKB_COMPOSE_COMPOSED: c_int = 2;
+pub const XKB_COMPOSE_CANCELLED: c_int = 3;
+#[cfg(feature = "x11")]
+pub const XKB_X11_MIN_MAJOR_XKB_VERSION: u16 = 1;
+#[cfg(feature = "x11")]
+pub const XKB_X11_MIN_MINOR_XKB_VERSION: u16 = 0;
+
+library! {
+    XkbCommon, b"libxkbcommon.so.0\0", b"libxkbcommon.so\0";
+    fn xkb_context_new(c_int) -> *mut xkb_context;
+    fn xkb_context_unref(*mut xkb_context) -> ();
+    fn xkb_keymap_unref(*mut xkb_keymap) -> ();
+    fn xkb_keymap_num_layouts(*mut xkb_keymap) -> u32;
+    fn xkb_keymap_layout_get_name(*mut xkb_keymap, u32) -> *const c_char;
+    fn xkb_keymap_mod_get_index(*mut xkb_keymap, *const c_char) -> u32;
+    fn xkb_state_new(*mut xkb_keymap) -> *mut xkb_state;
+    fn xkb_state_unref(*mut xkb_state) -> ();
+    fn xkb_state_update_mask(*mut xkb_state, u32, u32, u32, u32, u32, u32) -> c_int;
+    fn xkb_state_serialize_layout(*mut xkb_state, c_int) -> u32;
+    fn xkb_state_key_get_utf8(*mut xkb_state, u32, *mut c_char, usize) -> c_int;
+    fn xkb_keysym_to_utf8(u32, *mut c_char, usize) -> c_int;
+    fn xkb_compose_table_new_from_locale(*mut xkb_context, *const c_char, c_int)
+        -> *mut xkb_compose_table;
+    fn xkb_compose_table_unref(*mut xkb_compose_table) -> ();
+    fn xkb_compose_state_new(*mut xkb_compose_table, c_int) -> *mut xkb_compose_state;
+    fn xkb_compose_state_unref(*mut xkb_compose_state) -> ();
+    fn xkb_compose_state_feed(*mut xkb_compose_state, u32) -> c_int;
+    fn xkb_compose_state_reset(*mut xkb_compose_state) -> ();
+    fn xkb_compose_state_get_status(*mut xkb_compose_state) -> c_int;
+    fn xkb_compose_state_get_utf8(*mut xkb_compose_state, *mut c_char, usize) -> c_int;
+}
+
+#[cfg(feature = "x11")]
+library! {
+    XkbCommonX11, b"libxkbcommon-x11.so.0\0", b"libxkbcommon-x11.so\0";
+    fn xkb_x11_setup_xkb_extension(
+        *mut xcb_connection_t,
+        u16,
+        u16,
+        c_int,
+        *mut u16,
+        *mut u16,
+        *mut u8,
+        *mut u8
+    ) -> c_int;
+    fn xkb_x11_get_core_keyboard_device_id(*mut xcb_connection_t) -> i32;
+    fn xkb_x11_keymap_new_from_device(*mut xkb_context, *mut xcb_connection_t, i32, c_int)
+        -> *mut xkb_keymap;
+    fn xkb_x11_state_new_from_device(*mut xkb_keymap, *mut xcb_connection_t, i32)
+        -> *mut xkb_state;
+}
+
+lazy_static! {
+    pub static ref XKBCOMMON: Option<XkbCommon> = XkbCommon::open();
+}
+
+#[cfg(feature = "x11")]
+lazy_static! {
+    pub static ref XKBCOMMON_X11: Option<XkbCommonX11> = XkbCommonX11::open();
+}
@@ -1,4 +1,9 @@
-use crate::{platform::macos::ActivationPolicy, platform_impl::platform::app_state::AppState};
+use crate::{
+    event::Event,
+    monitor::{ColorCapabilities, MonitorEvent, MonitorHandle as RootMonitorHandle},
+    platform::macos::ActivationPolicy,
+    platform_impl::platform::{app_state::AppState, event::EventWrapper, monitor},
+};
 
 use cocoa::base::id;
 use objc::{
@@ -7,6 +12,7 @@ use objc::{
 };
 use std::{
     cell::{RefCell, RefMut},
+    mem,
     os::raw::c_void,
 };
 
@@ -19,6 +25,10 @@ pub struct AuxDelegateState {
     pub activation_policy: ActivationPolicy,
 
     pub create_default_menu: bool,
+
+    /// The monitors and their color capabilities, to tell which ones changed along with the
+    /// parameters of the screens.
+    pub monitors: Vec<(monitor::MonitorHandle, ColorCapabilities)>,
 }
 
 pub struct AppDelegateClass(pub *const Class);
@@ -37,6 +47,10 @@ lazy_static! {
             sel!(applicationDidFinishLaunching:),
             did_finish_launching as extern "C" fn(&Object, Sel, id),
         );
+        decl.add_method(
+            sel!(applicationDidChangeScreenParameters:),
+            did_change_screen_parameters as extern "C" fn(&Object, Sel, id),
+        );
         decl.add_ivar::<*mut c_void>(AUX_DELEGATE_STATE_NAME);
 
         AppDelegateClass(decl.register())
@@ -59,6 +73,7 @@ extern "C" fn new(class: &Class, _: Sel) -> id {
             Box::into_raw(Box::new(RefCell::new(AuxDelegateState {
                 activation_policy: ActivationPolicy::Regular,
                 create_default_menu: true,
+                monitors: Vec::new(),
             }))) as *mut c_void,
         );
         this
@@ -76,6 +91,62 @@ extern "C" fn dealloc(this: &Object, _: Sel) {
 
 extern "C" fn did_finish_launching(this: &Object, _: Sel, _: id) {
     trace!("Triggered `applicationDidFinishLaunching`");
+    let mut aux_state = unsafe { get_aux_state_mut(this) };
+    aux_state.monitors = monitor::available_monitors()
+        .into_iter()
+        .map(|monitor| {
+            let capabilities = monitor.color_capabilities();
+            (monitor, capabilities)
+        })
+        .collect();
+    drop(aux_state);
     AppState::launched(this);
     trace!("Completed `applicationDidFinishLaunching`");
 }
+
+extern "C" fn did_change_screen_parameters(this: &Object, _: Sel, _: id) {
+    trace!("Triggered `applicationDidChangeScreenParameters`");
+    let mut aux_state = unsafe { get_aux_state_mut(this) };
+    let monitors: Vec<_> = monitor::available_monitors()
+        .into_iter()
+        .map(|monitor| {
+            let capabilities = monitor.color_capabilities();
+            (monitor, capabilities)
+        })
+        .collect();
+    let previous = mem::replace(&mut aux_state.monitors, monitors.clone());
+    drop(aux_state);
+
+    let missing_from =
+        |list: &[(monitor::MonitorHandle, ColorCapabilities)],
+         monitors: &[(monitor::MonitorHandle, ColorCapabilities)]| {
+            monitors
+                .iter()
+                .filter(|(monitor, _)| !list.iter().any(|(other, _)| other == monitor))
+                .map(|(monitor, _)| RootMonitorHandle {
+                    inner: monitor.clone(),
+                })
+                .collect::<Vec<_>>()
+        };
+    let added = missing_from(&previous, &monitors);
+    let removed = missing_from(&monitors, &previous);
+    if !added.is_empty() || !removed.is_empty() {
+        AppState::queue_event(EventWrapper::StaticEvent(Event::MonitorsChanged {
+            added,
+            removed,
+        }));
+    }
+
+    for (monitor, capabilities) in monitors {
+        let changed = previous
+            .iter()
+            .any(|(other, previous)| *other == monitor && *previous != capabilities);
+        if changed {
+            AppState::queue_event(EventWrapper::StaticEvent(Event::MonitorEvent {
+                monitor: RootMonitorHandle { inner: monitor },
+                event: MonitorEvent::ColorChanged(capabilities),
+            }));
+        }
+    }
+    trace!("Completed `applicationDidChangeScreenParameters`");
+}
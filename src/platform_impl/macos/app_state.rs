@@ -62,7 +62,8 @@ pub trait EventHandler: Debug {
 
 struct EventLoopHandler<T: 'static> {
     callback: Weak<RefCell<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>>,
-    will_exit: bool,
+    /// The exit code, once the control flow has been set to `ExitWithCode`.
+    exit_code: Option<i32>,
     window_target: Rc<RootWindowTarget<T>>,
 }
 
@@ -99,24 +100,28 @@ impl<T> EventHandler for EventLoopHandler<T> {
     fn handle_nonuser_event(&mut self, event: Event<'_, Never>, control_flow: &mut ControlFlow) {
         self.with_callback(|this, mut callback| {
             (callback)(event.userify(), &this.window_target, control_flow);
-            this.will_exit |= *control_flow == ControlFlow::Exit;
-            if this.will_exit {
-                *control_flow = ControlFlow::Exit;
+            if let ControlFlow::ExitWithCode(code) = *control_flow {
+                this.exit_code.get_or_insert(code);
+            }
+            if let Some(code) = this.exit_code {
+                *control_flow = ControlFlow::ExitWithCode(code);
             }
         });
     }
 
     fn handle_user_events(&mut self, control_flow: &mut ControlFlow) {
         self.with_callback(|this, mut callback| {
-            let mut will_exit = this.will_exit;
+            let mut exit_code = this.exit_code;
             for event in this.window_target.p.receiver.try_iter() {
                 (callback)(Event::UserEvent(event), &this.window_target, control_flow);
-                will_exit |= *control_flow == ControlFlow::Exit;
-                if will_exit {
-                    *control_flow = ControlFlow::Exit;
+                if let ControlFlow::ExitWithCode(code) = *control_flow {
+                    exit_code.get_or_insert(code);
+                }
+                if let Some(code) = exit_code {
+                    *control_flow = ControlFlow::ExitWithCode(code);
                 }
             }
-            this.will_exit = will_exit;
+            this.exit_code = exit_code;
         });
     }
 }
@@ -126,6 +131,11 @@ struct Handler {
     ready: AtomicBool,
     in_callback: AtomicBool,
     dialog_is_closing: AtomicBool,
+    /// Whether the application is stopped once an iteration of the event loop is over, for
+    /// `pump_events`.
+    pumping: AtomicBool,
+    /// Whether `NewEvents` was sent since the events started being pumped.
+    woken_up: AtomicBool,
     control_flow: Mutex<ControlFlow>,
     control_flow_prev: Mutex<ControlFlow>,
     start_time: Mutex<Option<Instant>>,
@@ -159,8 +169,15 @@ impl Handler {
         self.ready.store(true, Ordering::Release);
     }
 
+    fn exit_code(&self) -> Option<i32> {
+        match *self.control_flow.lock().unwrap() {
+            ControlFlow::ExitWithCode(code) => Some(code),
+            _ => None,
+        }
+    }
+
     fn should_exit(&self) -> bool {
-        *self.control_flow.lock().unwrap() == ControlFlow::Exit
+        self.exit_code().is_some()
     }
 
     fn get_control_flow_and_update_prev(&self) -> ControlFlow {
@@ -175,6 +192,14 @@ impl Handler {
         (old, new)
     }
 
+    fn is_pumping(&self) -> bool {
+        self.pumping.load(Ordering::Acquire)
+    }
+
+    fn set_woken_up(&self) {
+        self.woken_up.store(true, Ordering::Release);
+    }
+
     fn get_start_time(&self) -> Option<Instant> {
         *self.start_time.lock().unwrap()
     }
@@ -268,11 +293,64 @@ impl AppState {
     ) {
         *HANDLER.callback.lock().unwrap() = Some(Box::new(EventLoopHandler {
             callback,
-            will_exit: false,
+            exit_code: None,
             window_target,
         }));
     }
 
+    /// Forgets about the callback, so it's not called once the events stop being pumped.
+    pub fn clear_callback() {
+        HANDLER.callback.lock().unwrap().take();
+    }
+
+    pub fn is_launched() -> bool {
+        HANDLER.is_ready()
+    }
+
+    pub fn should_exit() -> bool {
+        HANDLER.should_exit()
+    }
+
+    pub fn exit_code() -> Option<i32> {
+        HANDLER.exit_code()
+    }
+
+    pub fn set_precise_wait(precise: bool) {
+        HANDLER.waker().set_precise(precise);
+    }
+
+    /// Starts the event loop over once it has exited, since the application is only launched
+    /// once.
+    pub fn restart() {
+        *HANDLER.control_flow.lock().unwrap() = ControlFlow::default();
+        *HANDLER.control_flow_prev.lock().unwrap() = ControlFlow::default();
+        HANDLER.waker().start();
+        HANDLER.set_in_callback(true);
+        HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::NewEvents(
+            StartCause::Init,
+        )));
+        HANDLER.set_in_callback(false);
+        HANDLER.set_woken_up();
+    }
+
+    /// Stops the application once the next iteration of the event loop is over, waking it up by
+    /// the `deadline` at the latest.
+    pub fn start_pumping(deadline: Option<Instant>) {
+        HANDLER.woken_up.store(false, Ordering::Release);
+        HANDLER.pumping.store(true, Ordering::Release);
+        if let Some(deadline) = deadline {
+            match *HANDLER.control_flow.lock().unwrap() {
+                ControlFlow::Wait => HANDLER.waker().start_at(deadline),
+                ControlFlow::WaitUntil(instant) => HANDLER.waker().start_at(instant.min(deadline)),
+                ControlFlow::Poll | ControlFlow::ExitWithCode(_) => (),
+            }
+        }
+    }
+
+    pub fn stop_pumping() {
+        HANDLER.pumping.store(false, Ordering::Release);
+    }
+
     pub fn exit() {
         HANDLER.set_in_callback(true);
         HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::LoopDestroyed));
@@ -301,6 +379,7 @@ impl AppState {
             StartCause::Init,
         )));
         HANDLER.set_in_callback(false);
+        HANDLER.set_woken_up();
     }
 
     pub fn wakeup(panic_info: Weak<PanicInfo>) {
@@ -330,11 +409,12 @@ impl AppState {
                     }
                 }
             }
-            ControlFlow::Exit => StartCause::Poll, //panic!("unexpected `ControlFlow::Exit`"),
+            ControlFlow::ExitWithCode(_) => StartCause::Poll, //panic!("unexpected `ControlFlow::Exit`"),
         };
         HANDLER.set_in_callback(true);
         HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::NewEvents(cause)));
         HANDLER.set_in_callback(false);
+        HANDLER.set_woken_up();
     }
 
     // This is called from multiple threads at present
@@ -374,6 +454,7 @@ impl AppState {
         if panic_info.is_panicking() || !HANDLER.is_ready() {
             return;
         }
+        let mut stopped = false;
         if !HANDLER.get_in_callback() {
             HANDLER.set_in_callback(true);
             HANDLER.handle_user_events();
@@ -388,6 +469,22 @@ impl AppState {
             }
             HANDLER.handle_nonuser_event(EventWrapper::StaticEvent(Event::RedrawEventsCleared));
             HANDLER.set_in_callback(false);
+
+            // The events are pumped once a whole iteration, starting with `NewEvents`, is over.
+            if HANDLER.is_pumping()
+                && HANDLER.woken_up.swap(false, Ordering::AcqRel)
+                && !HANDLER.should_exit()
+            {
+                HANDLER.pumping.store(false, Ordering::Release);
+                stopped = true;
+                unsafe {
+                    let app: id = NSApp();
+                    autoreleasepool(|| {
+                        let () = msg_send![app, stop: nil];
+                        post_dummy_event(app);
+                    });
+                }
+            }
         }
         if HANDLER.should_exit() {
             unsafe {
@@ -429,8 +526,9 @@ impl AppState {
         }
         HANDLER.update_start_time();
         match HANDLER.get_old_and_new_control_flow() {
-            (ControlFlow::Exit, _) | (_, ControlFlow::Exit) => (),
-            (old, new) if old == new => (),
+            (ControlFlow::ExitWithCode(_), _) | (_, ControlFlow::ExitWithCode(_)) => (),
+            // The waker may have been started for the timeout of `pump_events`.
+            (old, new) if old == new && !stopped => (),
             (_, ControlFlow::Wait) => HANDLER.waker().stop(),
             (_, ControlFlow::WaitUntil(instant)) => HANDLER.waker().start_at(instant),
             (_, ControlFlow::Poll) => HANDLER.waker().start(),
@@ -0,0 +1,94 @@
+use cocoa::{
+    appkit::NSWindow,
+    base::id,
+    foundation::{NSPoint, NSRect, NSSize, NSUInteger},
+};
+use raw_window_handle::{macos::MacOSHandle, RawWindowHandle};
+
+use crate::{
+    dpi::{LogicalPosition, LogicalSize, Position, Size},
+    error::{ExternalError, HandleError},
+    platform_impl::platform::{
+        util::{self, IdRef},
+        EventLoopWindowTarget, OsError, Window,
+    },
+};
+
+/// A subview of the view of a window, which passes the events up to it.
+pub struct ChildSurface {
+    ns_window: IdRef,
+    ns_view: IdRef,
+}
+
+unsafe impl Send for ChildSurface {}
+unsafe impl Sync for ChildSurface {}
+
+impl ChildSurface {
+    pub fn new<T: 'static>(
+        _window_target: &EventLoopWindowTarget<T>,
+        parent: &Window,
+        position: Position,
+        size: Size,
+    ) -> Result<Self, ExternalError> {
+        let scale_factor = parent.scale_factor();
+        let position: LogicalPosition<f64> = position.to_logical(scale_factor);
+        let size: LogicalSize<f64> = size.to_logical(scale_factor);
+
+        unsafe {
+            let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(size.width, size.height));
+            let ns_view: id = msg_send![class!(NSView), alloc];
+            let ns_view = IdRef::new(msg_send![ns_view, initWithFrame: frame])
+                .non_nil()
+                .ok_or_else(|| {
+                    ExternalError::Os(os_error!(OsError::CreationError(
+                        "Couldn't create `NSView`"
+                    )))
+                })?;
+            // `NSViewMinYMargin`, as the view of the window isn't flipped and the child keeps its
+            // distance to the top as the window is resized.
+            let () = msg_send![*ns_view, setAutoresizingMask: 8 as NSUInteger];
+            let () = msg_send![*parent.ns_view, addSubview: *ns_view];
+            util::set_subview_frame(*ns_view, Some(position), None);
+
+            Ok(ChildSurface {
+                ns_window: IdRef::retain(*parent.ns_window),
+                ns_view,
+            })
+        }
+    }
+
+    #[inline]
+    pub fn set_position(&self, position: Position) {
+        let scale_factor = self.scale_factor();
+        let position = position.to_logical(scale_factor);
+        unsafe { util::set_subview_frame_async(*self.ns_view, Some(position), None) };
+    }
+
+    #[inline]
+    pub fn set_size(&self, size: Size) {
+        let scale_factor = self.scale_factor();
+        let size = size.to_logical(scale_factor);
+        unsafe { util::set_subview_frame_async(*self.ns_view, None, Some(size)) };
+    }
+
+    #[inline]
+    pub fn window_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        let handle = MacOSHandle {
+            ns_window: *self.ns_window as *mut _,
+            ns_view: *self.ns_view as *mut _,
+            ..MacOSHandle::empty()
+        };
+        Ok(RawWindowHandle::MacOS(handle))
+    }
+
+    fn scale_factor(&self) -> f64 {
+        unsafe { NSWindow::backingScaleFactor(*self.ns_window) as _ }
+    }
+}
+
+impl Drop for ChildSurface {
+    fn drop(&mut self) {
+        let ns_view = IdRef::retain(*self.ns_view);
+        unsafe { util::remove_from_superview_async(ns_view) };
+    }
+}
@@ -0,0 +1,361 @@
+use std::{
+    os::raw::c_void,
+    path::PathBuf,
+    slice, str,
+    sync::{Arc, Mutex},
+};
+
+use cocoa::{
+    base::{id, nil, NO, YES},
+    foundation::{NSArray, NSInteger, NSString, NSUInteger},
+};
+use objc::{
+    declare::ClassDecl,
+    rc::autoreleasepool,
+    runtime::{Class, Object, Sel, BOOL},
+};
+
+use crate::{
+    clipboard::{ClipboardKind, ClipboardMimedContent, ClipboardProvider, MimePicker, MimeType},
+    error::{ExternalError, NotSupportedError},
+    platform_impl::platform::{
+        util::{self, IdRef},
+        OsError,
+    },
+};
+
+// The values of `NSPasteboardTypeString`, `NSPasteboardTypePNG`, `NSPasteboardTypeHTML`
+// and `NSPasteboardTypeRTF`.
+const PASTEBOARD_TYPE_STRING: &str = "public.utf8-plain-text";
+const PASTEBOARD_TYPE_PNG: &str = "public.png";
+const PASTEBOARD_TYPE_HTML: &str = "public.html";
+const PASTEBOARD_TYPE_RTF: &str = "public.rtf";
+// The type of a property list with the paths of the files, which is deprecated, but is still
+// the simplest way to exchange the files.
+const PASTEBOARD_TYPE_FILENAMES: &str = "NSFilenamesPboardType";
+
+fn pasteboard_type_mime_type(pasteboard_type: &str) -> Option<MimeType> {
+    match pasteboard_type {
+        PASTEBOARD_TYPE_STRING => Some(MimeType::Text),
+        PASTEBOARD_TYPE_PNG => Some(MimeType::PngImage),
+        PASTEBOARD_TYPE_HTML => Some(MimeType::Html),
+        PASTEBOARD_TYPE_RTF => Some(MimeType::Rtf),
+        PASTEBOARD_TYPE_FILENAMES => Some(MimeType::FileList),
+        // Custom types are placed under their MIME type.
+        _ => MimeType::from_mime_type(pasteboard_type),
+    }
+}
+
+fn mime_type_pasteboard_type(mime_type: &MimeType) -> &str {
+    match mime_type {
+        MimeType::Text => PASTEBOARD_TYPE_STRING,
+        MimeType::PngImage => PASTEBOARD_TYPE_PNG,
+        MimeType::Html => PASTEBOARD_TYPE_HTML,
+        MimeType::Rtf => PASTEBOARD_TYPE_RTF,
+        MimeType::FileList => PASTEBOARD_TYPE_FILENAMES,
+        MimeType::Other(mime) => mime,
+    }
+}
+
+/// Places the content of `provider` into the general pasteboard, with the data of each offered
+/// kind only rendered once some application requests it.
+pub fn set_clipboard(
+    kind: ClipboardKind,
+    provider: Arc<dyn ClipboardProvider>,
+) -> Result<(), ExternalError> {
+    // There's no primary selection on macOS.
+    if kind != ClipboardKind::Clipboard {
+        return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+
+    let pasteboard_types = provider
+        .mime_types()
+        .iter()
+        .map(|mime_type| util::ns_string_id_ref(mime_type_pasteboard_type(mime_type)))
+        .collect::<Vec<_>>();
+
+    let written = autoreleasepool(|| unsafe {
+        let data_provider: id = msg_send![DATA_PROVIDER_CLASS.0, new];
+        let data_provider = IdRef::new(data_provider);
+        let provider_ptr = Box::into_raw(Box::new(provider));
+        (**data_provider).set_ivar("winitProvider", provider_ptr as *mut c_void);
+
+        let pasteboard_types = pasteboard_types
+            .iter()
+            .map(|pasteboard_type| **pasteboard_type)
+            .collect::<Vec<id>>();
+        let pasteboard_types = NSArray::arrayWithObjects(nil, &pasteboard_types);
+        let item: id = msg_send![class!(NSPasteboardItem), new];
+        let item: id = msg_send![item, autorelease];
+        let provided: BOOL = msg_send![item,
+            setDataProvider:*data_provider
+            forTypes:pasteboard_types
+        ];
+        if provided == NO {
+            return false;
+        }
+
+        let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+        let _: NSInteger = msg_send![pasteboard, clearContents];
+        let written: BOOL =
+            msg_send![pasteboard, writeObjects: NSArray::arrayWithObject(nil, item)];
+        if written == YES {
+            *CLIPBOARD_DATA_PROVIDER.lock().unwrap() = Some(DataProvider(data_provider));
+        }
+        written == YES
+    });
+
+    if written {
+        Ok(())
+    } else {
+        Err(ExternalError::Os(os_error!(OsError::PasteboardError(
+            "failed to write to the pasteboard"
+        ))))
+    }
+}
+
+/// The data provider of the content of the general pasteboard, which is kept until the
+/// pasteboard is done with it.
+struct DataProvider(IdRef);
+unsafe impl Send for DataProvider {}
+
+lazy_static! {
+    static ref CLIPBOARD_DATA_PROVIDER: Mutex<Option<DataProvider>> = Mutex::new(None);
+}
+
+struct DataProviderClass(*const Class);
+unsafe impl Send for DataProviderClass {}
+unsafe impl Sync for DataProviderClass {}
+
+lazy_static! {
+    static ref DATA_PROVIDER_CLASS: DataProviderClass = unsafe {
+        let superclass = class!(NSObject);
+        let mut decl = ClassDecl::new("WinitPasteboardDataProvider", superclass).unwrap();
+
+        decl.add_method(
+            sel!(dealloc),
+            data_provider_dealloc as extern "C" fn(&Object, Sel),
+        );
+        decl.add_method(
+            sel!(pasteboard:item:provideDataForType:),
+            provide_data_for_type as extern "C" fn(&Object, Sel, id, id, id),
+        );
+        decl.add_method(
+            sel!(pasteboardFinishedWithDataProvider:),
+            pasteboard_finished_with_data_provider as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_ivar::<*mut c_void>("winitProvider");
+
+        DataProviderClass(decl.register())
+    };
+}
+
+unsafe fn data_provider_provider<'a>(this: &'a Object) -> &'a Arc<dyn ClipboardProvider> {
+    let provider_ptr: *mut c_void = *this.get_ivar("winitProvider");
+    &*(provider_ptr as *const Arc<dyn ClipboardProvider>)
+}
+
+extern "C" fn data_provider_dealloc(this: &Object, _sel: Sel) {
+    unsafe {
+        let provider_ptr: *mut c_void = *this.get_ivar("winitProvider");
+        if !provider_ptr.is_null() {
+            drop(Box::from_raw(
+                provider_ptr as *mut Arc<dyn ClipboardProvider>,
+            ));
+        }
+        let _: () = msg_send![super(this, class!(NSObject)), dealloc];
+    }
+}
+
+extern "C" fn provide_data_for_type(
+    this: &Object,
+    _sel: Sel,
+    _pasteboard: id,
+    item: id,
+    pasteboard_type: id,
+) {
+    unsafe {
+        let provider = data_provider_provider(this);
+        let pasteboard_type = ns_string_to_string(pasteboard_type);
+        let mime_type = provider
+            .mime_types()
+            .into_iter()
+            .find(|mime_type| mime_type_pasteboard_type(mime_type) == pasteboard_type);
+        let content = match mime_type {
+            Some(mime_type) => ClipboardMimedContent::from_provider(&**provider, mime_type),
+            None => return,
+        };
+        match content {
+            Ok(content) => {
+                write_content(item, content);
+            }
+            Err(err) => warn!("Failed to render the clipboard content: {}", err),
+        }
+    }
+}
+
+extern "C" fn pasteboard_finished_with_data_provider(this: &Object, _sel: Sel, _pasteboard: id) {
+    // The provider is released once the content of the pasteboard is replaced, after the
+    // pasteboard is done calling it.
+    let mut data_provider = CLIPBOARD_DATA_PROVIDER.lock().unwrap();
+    let finished = match *data_provider {
+        Some(DataProvider(ref current)) => **current == this as *const Object as id,
+        None => false,
+    };
+    if !finished {
+        return;
+    }
+    if let Some(DataProvider(current)) = data_provider.take() {
+        unsafe {
+            let _: id = msg_send![*current, retain];
+            let _: id = msg_send![*current, autorelease];
+        }
+    }
+}
+
+/// Renders the data of every kind offered by `provider`.
+pub fn collect_contents(
+    provider: &dyn ClipboardProvider,
+) -> Result<Vec<ClipboardMimedContent>, ExternalError> {
+    provider
+        .mime_types()
+        .into_iter()
+        .map(|mime_type| ClipboardMimedContent::from_provider(provider, mime_type))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| {
+            warn!("Failed to collect the clipboard content: {}", err);
+            ExternalError::Os(os_error!(OsError::PasteboardError(
+                "failed to collect the content"
+            )))
+        })
+}
+
+/// Creates an autoreleased pasteboard item holding the contents, which is what the dragged data
+/// is written with.
+pub unsafe fn pasteboard_item(contents: Vec<ClipboardMimedContent>) -> Option<id> {
+    let item: id = msg_send![class!(NSPasteboardItem), new];
+    let item: id = msg_send![item, autorelease];
+
+    // The item has the same methods to write the data as the pasteboard itself.
+    let written = contents
+        .into_iter()
+        .all(|content| write_content(item, content));
+    if written {
+        Some(item)
+    } else {
+        None
+    }
+}
+
+unsafe fn write_content(pasteboard: id, content: ClipboardMimedContent) -> bool {
+    let pasteboard_type = util::ns_string_id_ref(mime_type_pasteboard_type(&content.mime_type()));
+    let written: BOOL = match content {
+        ClipboardMimedContent::Text(text) => {
+            let string = util::ns_string_id_ref(&text);
+            msg_send![pasteboard, setString:*string forType:*pasteboard_type]
+        }
+        ClipboardMimedContent::FileList(paths) => {
+            let paths = paths
+                .iter()
+                .map(|path| util::ns_string_id_ref(&path.to_string_lossy()))
+                .collect::<Vec<_>>();
+            let paths = paths.iter().map(|path| **path).collect::<Vec<id>>();
+            let paths = NSArray::arrayWithObjects(nil, &paths);
+            msg_send![pasteboard, setPropertyList:paths forType:*pasteboard_type]
+        }
+        content => {
+            let data = content.to_bytes();
+            let data: id = msg_send![class!(NSData),
+                dataWithBytes:data.as_ptr() as *const c_void
+                length:data.len() as NSUInteger
+            ];
+            msg_send![pasteboard, setData:data forType:*pasteboard_type]
+        }
+    };
+
+    written == YES
+}
+
+/// Reads the content of the kind chosen by `picker` from the general pasteboard.
+pub fn read_clipboard(
+    kind: ClipboardKind,
+    picker: MimePicker,
+) -> Result<Option<ClipboardMimedContent>, ExternalError> {
+    if kind != ClipboardKind::Clipboard {
+        return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+
+    let content = autoreleasepool(|| unsafe {
+        let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+        read_pasteboard(pasteboard, picker)
+    });
+
+    Ok(content)
+}
+
+/// Reads the content of the kind chosen by `picker` from the pasteboard.
+pub unsafe fn read_pasteboard(pasteboard: id, picker: MimePicker) -> Option<ClipboardMimedContent> {
+    let types: id = msg_send![pasteboard, types];
+    if types == nil {
+        return None;
+    }
+
+    // The types are ordered by the owner's preference.
+    let mut offered = Vec::new();
+    let count: NSUInteger = msg_send![types, count];
+    for index in 0..count {
+        let pasteboard_type: id = msg_send![types, objectAtIndex: index];
+        let pasteboard_type = slice::from_raw_parts(
+            pasteboard_type.UTF8String() as *const u8,
+            pasteboard_type.len(),
+        );
+        match str::from_utf8(pasteboard_type)
+            .ok()
+            .and_then(pasteboard_type_mime_type)
+        {
+            Some(mime_type) if !offered.contains(&mime_type) => offered.push(mime_type),
+            _ => (),
+        }
+    }
+
+    let mime_type = picker(&offered).filter(|mime_type| offered.contains(mime_type))?;
+    let pasteboard_type = util::ns_string_id_ref(mime_type_pasteboard_type(&mime_type));
+    match mime_type {
+        MimeType::Text => {
+            let string: id = msg_send![pasteboard, stringForType:*pasteboard_type];
+            if string == nil {
+                return None;
+            }
+            Some(ClipboardMimedContent::Text(ns_string_to_string(string)))
+        }
+        MimeType::FileList => {
+            let paths: id = msg_send![pasteboard, propertyListForType:*pasteboard_type];
+            if paths == nil {
+                return None;
+            }
+            let count: NSUInteger = msg_send![paths, count];
+            let paths = (0..count)
+                .map(|index| {
+                    let path: id = msg_send![paths, objectAtIndex: index];
+                    PathBuf::from(ns_string_to_string(path))
+                })
+                .collect();
+            Some(ClipboardMimedContent::FileList(paths))
+        }
+        mime_type => {
+            let data: id = msg_send![pasteboard, dataForType:*pasteboard_type];
+            if data == nil {
+                return None;
+            }
+            let bytes: *const c_void = msg_send![data, bytes];
+            let length: NSUInteger = msg_send![data, length];
+            let data = slice::from_raw_parts(bytes as *const u8, length as usize).to_vec();
+            Some(ClipboardMimedContent::from_bytes(mime_type, data))
+        }
+    }
+}
+
+unsafe fn ns_string_to_string(string: id) -> String {
+    let bytes = slice::from_raw_parts(string.UTF8String() as *const u8, string.len());
+    String::from_utf8_lossy(bytes).into_owned()
+}
@@ -7,7 +7,9 @@ use cocoa::{
 
 use crate::{
     dpi::LogicalSize,
-    event::{ElementState, Event, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent},
+    event::{
+        ElementState, Event, KeyboardInput, ModifiersState, ScanCode, VirtualKeyCode, WindowEvent,
+    },
     platform_impl::platform::{
         util::{IdRef, Never},
         DEVICE_ID,
@@ -89,6 +91,19 @@ pub fn char_to_keycode(c: char) -> Option<VirtualKeyCode> {
     })
 }
 
+pub fn scancode_to_key(scancode: ScanCode) -> Option<VirtualKeyCode> {
+    if scancode > c_ushort::MAX as ScanCode {
+        return None;
+    }
+    scancode_to_keycode(scancode as c_ushort)
+}
+
+pub fn key_to_scancode(key: VirtualKeyCode) -> Option<ScanCode> {
+    (0..0x80)
+        .find(|&scancode| scancode_to_keycode(scancode) == Some(key))
+        .map(ScanCode::from)
+}
+
 pub fn scancode_to_keycode(scancode: c_ushort) -> Option<VirtualKeyCode> {
     Some(match scancode {
         0x00 => VirtualKeyCode::A,
@@ -292,6 +307,7 @@ pub unsafe fn modifier_event(
             device_id: DEVICE_ID,
             input: KeyboardInput {
                 state,
+                repeat: false,
                 scancode: scancode as _,
                 virtual_keycode,
                 modifiers: event_mods(ns_event),
@@ -1,14 +1,15 @@
 use std::{
     any::Any,
     cell::{Cell, RefCell},
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     marker::PhantomData,
     mem,
-    os::raw::c_void,
+    os::{raw::c_void, unix::io::RawFd},
     panic::{catch_unwind, resume_unwind, RefUnwindSafe, UnwindSafe},
     process, ptr,
     rc::{Rc, Weak},
-    sync::mpsc,
+    sync::{mpsc, Arc},
+    time::{Duration, Instant},
 };
 
 use cocoa::{
@@ -19,17 +20,36 @@ use cocoa::{
 use objc::rc::autoreleasepool;
 
 use crate::{
-    event::Event,
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget},
+    clipboard::{ClipboardKind, ClipboardProvider},
+    dpi::PhysicalPosition,
+    error::{ExternalError, NotSupportedError},
+    event::{Event, InputDevice, ModifiersState, ScanCode},
+    event_loop::{
+        ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget, HapticPattern,
+        SourceInterest, SourceToken, Timer, TimerId,
+    },
+    gamepad::GamepadId,
+    hotkey::{Accelerator, HotkeyId},
+    icon::{BadIcon, RgbaIcon},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
     monitor::MonitorHandle as RootMonitorHandle,
+    platform::pump_events::PumpStatus,
     platform_impl::platform::{
         app::APP_CLASS,
         app_delegate::APP_DELEGATE_CLASS,
         app_state::AppState,
+        clipboard,
+        event::EventWrapper,
+        ffi,
+        gamepad::Gamepads,
+        hotkey::Hotkeys,
+        keymap,
         monitor::{self, MonitorHandle},
         observer::*,
-        util::IdRef,
+        util::{CustomCursor, IdRef},
+        DeviceId, OsError,
     },
+    window::CursorFrame,
 };
 
 #[derive(Default)]
@@ -63,12 +83,87 @@ impl PanicInfo {
 pub struct EventLoopWindowTarget<T: 'static> {
     pub sender: mpsc::Sender<T>, // this is only here to be cloned elsewhere
     pub receiver: mpsc::Receiver<T>,
+    /// The timers set by the user.
+    timers: RefCell<HashMap<TimerId, RunLoopTimer>>,
+    /// The global hotkeys registered by the user.
+    hotkeys: RefCell<Hotkeys>,
+    /// The gamepads, polled while they're enabled.
+    gamepads: Gamepads,
 }
 
 impl<T> Default for EventLoopWindowTarget<T> {
     fn default() -> Self {
         let (sender, receiver) = mpsc::channel();
-        EventLoopWindowTarget { sender, receiver }
+        EventLoopWindowTarget {
+            sender,
+            receiver,
+            timers: Default::default(),
+            hotkeys: Default::default(),
+            gamepads: Default::default(),
+        }
+    }
+}
+
+/// A timer set by the user, scheduled on the main run loop.
+struct RunLoopTimer {
+    timer: CFRunLoopTimerRef,
+    /// The info passed to the callback, which must outlive the timer.
+    _id: Box<TimerId>,
+}
+
+impl RunLoopTimer {
+    fn new(id: TimerId, timer: Timer) -> Result<Self, ExternalError> {
+        extern "C" fn timer_fired(_: CFRunLoopTimerRef, info: *mut c_void) {
+            let id = unsafe { *(info as *const TimerId) };
+            // The event is delivered once the run loop is done with the other sources.
+            AppState::queue_event(EventWrapper::StaticEvent(Event::Timer(id)));
+        }
+
+        let (delay, interval) = match timer {
+            Timer::At(deadline) => (deadline.saturating_duration_since(Instant::now()), 0.0),
+            Timer::Every(interval) => (interval, interval.as_secs_f64()),
+        };
+        let id = Box::new(id);
+        unsafe {
+            let mut context = CFRunLoopTimerContext {
+                version: 0,
+                info: &*id as *const TimerId as *mut c_void,
+                retain: None,
+                release: None,
+                copyDescription: None,
+            };
+            let timer = CFRunLoopTimerCreate(
+                ptr::null_mut(),
+                CFAbsoluteTimeGetCurrent() + delay.as_secs_f64(),
+                interval,
+                0,
+                0,
+                timer_fired,
+                &mut context,
+            );
+            if timer.is_null() {
+                return Err(ExternalError::Os(os_error!(OsError::CreationError(
+                    "Failed to create the timer"
+                ))));
+            }
+            CFRunLoopAddTimer(CFRunLoopGetMain(), timer, kCFRunLoopCommonModes);
+            Ok(RunLoopTimer { timer, _id: id })
+        }
+    }
+
+    /// Whether the timer is still going to fire, since the timers firing once are invalidated
+    /// once they fire.
+    fn is_valid(&self) -> bool {
+        unsafe { CFRunLoopTimerIsValid(self.timer) != ffi::FALSE }
+    }
+}
+
+impl Drop for RunLoopTimer {
+    fn drop(&mut self) {
+        unsafe {
+            CFRunLoopTimerInvalidate(self.timer);
+            CFRelease(self.timer as _);
+        }
     }
 }
 
@@ -83,6 +178,130 @@ impl<T: 'static> EventLoopWindowTarget<T> {
         let monitor = monitor::primary_monitor();
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        clipboard::set_clipboard(kind, provider)
+    }
+
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        image: RgbaIcon,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor::new(&image, hotspot))
+    }
+
+    pub fn create_animated_cursor(
+        &self,
+        frames: Vec<CursorFrame>,
+    ) -> Result<CustomCursor, BadIcon> {
+        Ok(CustomCursor::new_animated(&frames))
+    }
+
+    pub fn set_timer(&self, id: TimerId, timer: Timer) -> Result<(), ExternalError> {
+        // Setting the timer again replaces it, which drops and invalidates the previous one.
+        let timer = RunLoopTimer::new(id, timer)?;
+        self.timers.borrow_mut().insert(id, timer);
+        Ok(())
+    }
+
+    pub fn cancel_timer(&self, id: TimerId) -> bool {
+        match self.timers.borrow_mut().remove(&id) {
+            Some(timer) => timer.is_valid(),
+            None => false,
+        }
+    }
+
+    pub fn set_precise_wait(&self, precise: bool) -> Result<(), ExternalError> {
+        AppState::set_precise_wait(precise);
+        Ok(())
+    }
+
+    pub fn set_gamepads_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        self.gamepads.set_enabled(enabled)
+    }
+
+    pub fn rumble_gamepad(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        self.gamepads.rumble(gamepad_id, strong, weak, duration)
+    }
+
+    pub fn perform_haptic_feedback(&self, pattern: HapticPattern) -> Result<(), ExternalError> {
+        // `NSHapticFeedbackPattern`
+        let pattern: isize = match pattern {
+            HapticPattern::Generic => 0,
+            HapticPattern::Alignment => 1,
+            HapticPattern::LevelChange => 2,
+        };
+        unsafe {
+            let performer: id = msg_send![class!(NSHapticFeedbackManager), defaultPerformer];
+            // `NSHapticFeedbackPerformanceTimeNow`
+            let () = msg_send![performer, performFeedbackPattern: pattern performanceTime: 1usize];
+        }
+        Ok(())
+    }
+
+    pub fn input_devices(&self) -> Result<Vec<InputDevice>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_hid_reports_enabled(
+        &self,
+        _device: DeviceId,
+        _enabled: bool,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn keyboard_layouts(&self) -> Result<Vec<KeyboardLayout>, ExternalError> {
+        Ok(keymap::keyboard_layouts())
+    }
+
+    pub fn current_keyboard_layout(&self) -> Result<KeyboardLayout, ExternalError> {
+        Ok(keymap::current_keyboard_layout())
+    }
+
+    pub fn scancode_to_text(
+        &self,
+        scancode: ScanCode,
+        modifiers: ModifiersState,
+    ) -> Result<Option<String>, ExternalError> {
+        Ok(keymap::scancode_to_text(scancode, modifiers))
+    }
+
+    pub fn key_repeat(&self) -> Result<Option<KeyRepeat>, ExternalError> {
+        Ok(Some(keymap::key_repeat()))
+    }
+
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        Ok(keymap::lock_keys())
+    }
+
+    pub fn set_lock_keys(&self, _lock_keys: LockKeys, _on: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn register_global_hotkey(
+        &self,
+        accelerator: Accelerator,
+    ) -> Result<HotkeyId, ExternalError> {
+        self.hotkeys.borrow_mut().register(accelerator)
+    }
+
+    pub fn unregister_global_hotkey(&self, id: HotkeyId) -> bool {
+        self.hotkeys.borrow_mut().unregister(id)
+    }
 }
 
 pub struct EventLoop<T: 'static> {
@@ -98,6 +317,36 @@ pub struct EventLoop<T: 'static> {
     /// into a strong reference in order to call the callback but then the
     /// strong reference should be dropped as soon as possible.
     _callback: Option<Rc<RefCell<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>>>,
+
+    /// Whether `NewEvents(Init)` was sent, and `LoopDestroyed` is yet to be.
+    loop_running: bool,
+
+    /// The external sources registered by the user.
+    external_sources: HashMap<SourceToken, ExternalSource>,
+}
+
+/// A file descriptor registered as an external source, along with its run loop source.
+struct ExternalSource {
+    descriptor: CFFileDescriptorRef,
+    source: CFRunLoopSourceRef,
+    /// The info passed to the callback, which must outlive the descriptor.
+    _info: Box<ExternalSourceInfo>,
+}
+
+struct ExternalSourceInfo {
+    token: SourceToken,
+    callback_types: CFOptionFlags,
+}
+
+impl Drop for ExternalSource {
+    fn drop(&mut self) {
+        unsafe {
+            // Invalidating the descriptor removes its source from the run loop.
+            CFFileDescriptorInvalidate(self.descriptor);
+            CFRelease(self.source as _);
+            CFRelease(self.descriptor as _);
+        }
+    }
 }
 
 impl<T> EventLoop<T> {
@@ -129,9 +378,15 @@ impl<T> EventLoop<T> {
             }),
             panic_info,
             _callback: None,
+            loop_running: false,
+            external_sources: HashMap::new(),
         }
     }
 
+    pub fn new_secondary() -> Result<Self, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn window_target(&self) -> &RootWindowTarget<T> {
         &self.window_target
     }
@@ -140,11 +395,11 @@ impl<T> EventLoop<T> {
     where
         F: 'static + FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
     {
-        self.run_return(callback);
-        process::exit(0);
+        let exit_code = self.run_return(callback);
+        process::exit(exit_code);
     }
 
-    pub fn run_return<F>(&mut self, callback: F)
+    pub fn run_return<F>(&mut self, callback: F) -> i32
     where
         F: FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
     {
@@ -171,18 +426,174 @@ impl<T> EventLoop<T> {
             mem::drop(callback);
 
             AppState::set_callback(weak_cb, Rc::clone(&self.window_target));
+            // The application is only launched once, so the later runs start over by themselves.
+            if AppState::is_launched() && !self.loop_running {
+                AppState::restart();
+            }
             let () = msg_send![app, run];
 
             if let Some(panic) = self.panic_info.take() {
                 resume_unwind(panic);
             }
+            let exit_code = AppState::exit_code().unwrap_or(0);
             AppState::exit();
+            self.loop_running = false;
+            exit_code
+        })
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
+    {
+        // The callback is forgotten before returning, so it doesn't outlive this call.
+        let callback = unsafe {
+            mem::transmute::<
+                Rc<RefCell<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>>,
+                Rc<RefCell<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>>,
+            >(Rc::new(RefCell::new(callback)))
+        };
+
+        self._callback = Some(Rc::clone(&callback));
+
+        let status = autoreleasepool(|| unsafe {
+            let app = NSApp();
+            assert_ne!(app, nil);
+
+            let weak_cb: Weak<_> = Rc::downgrade(&callback);
+            mem::drop(callback);
+
+            AppState::set_callback(weak_cb, Rc::clone(&self.window_target));
+            // The application sends `NewEvents(Init)` once it's launched, by the first call.
+            if AppState::is_launched() && !self.loop_running {
+                AppState::restart();
+            }
+            self.loop_running = true;
+
+            AppState::start_pumping(timeout.map(|timeout| Instant::now() + timeout));
+            let () = msg_send![app, run];
+            AppState::stop_pumping();
+
+            if let Some(panic) = self.panic_info.take() {
+                resume_unwind(panic);
+            }
+
+            if let Some(exit_code) = AppState::exit_code() {
+                AppState::exit();
+                self.loop_running = false;
+                PumpStatus::Exit(exit_code)
+            } else {
+                AppState::clear_callback();
+                PumpStatus::Continue
+            }
         });
+
+        self._callback = None;
+        status
     }
 
     pub fn create_proxy(&self) -> Proxy<T> {
         Proxy::new(self.window_target.p.sender.clone())
     }
+
+    pub fn create_wakeup(&self) -> EventLoopWakeup {
+        EventLoopWakeup::new()
+    }
+
+    pub fn insert_source(
+        &mut self,
+        fd: RawFd,
+        interest: SourceInterest,
+        token: SourceToken,
+    ) -> Result<(), ExternalError> {
+        if self.external_sources.contains_key(&token) {
+            return Err(ExternalError::Os(os_error!(OsError::SourceError(
+                "The token of the source is already in use"
+            ))));
+        }
+        let mut callback_types = 0;
+        if interest.contains(SourceInterest::READABLE) {
+            callback_types |= kCFFileDescriptorReadCallBack;
+        }
+        if interest.contains(SourceInterest::WRITABLE) {
+            callback_types |= kCFFileDescriptorWriteCallBack;
+        }
+        if callback_types == 0 {
+            return Err(ExternalError::Os(os_error!(OsError::SourceError(
+                "The source has no interest"
+            ))));
+        }
+
+        let info = Box::new(ExternalSourceInfo {
+            token,
+            callback_types,
+        });
+        unsafe {
+            let context = CFFileDescriptorContext {
+                version: 0,
+                info: &*info as *const ExternalSourceInfo as *mut c_void,
+                retain: None,
+                release: None,
+                copyDescription: None,
+            };
+            let descriptor = CFFileDescriptorCreate(
+                ptr::null_mut(),
+                fd,
+                ffi::FALSE,
+                external_source_callback,
+                &context,
+            );
+            if descriptor.is_null() {
+                return Err(ExternalError::Os(os_error!(OsError::SourceError(
+                    "Failed to create the file descriptor object"
+                ))));
+            }
+            let source = CFFileDescriptorCreateRunLoopSource(ptr::null_mut(), descriptor, 0);
+            if source.is_null() {
+                CFFileDescriptorInvalidate(descriptor);
+                CFRelease(descriptor as _);
+                return Err(ExternalError::Os(os_error!(OsError::SourceError(
+                    "Failed to create the run loop source"
+                ))));
+            }
+            CFRunLoopAddSource(CFRunLoopGetMain(), source, kCFRunLoopCommonModes);
+            CFFileDescriptorEnableCallBacks(descriptor, callback_types);
+
+            self.external_sources.insert(
+                token,
+                ExternalSource {
+                    descriptor,
+                    source,
+                    _info: info,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    pub fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        self.external_sources
+            .remove(&token)
+            .map(drop)
+            .ok_or_else(|| {
+                ExternalError::Os(os_error!(OsError::SourceError(
+                    "There's no source with this token"
+                )))
+            })
+    }
+}
+
+extern "C" fn external_source_callback(
+    descriptor: CFFileDescriptorRef,
+    _: CFOptionFlags,
+    info: *mut c_void,
+) {
+    let info = unsafe { &*(info as *const ExternalSourceInfo) };
+    // The event is delivered once the run loop is done with the other sources.
+    AppState::queue_event(EventWrapper::StaticEvent(Event::ExternalSource(info.token)));
+    // The callbacks are only called once, so they're enabled again to be called on each
+    // iteration for as long as the file descriptor stays ready.
+    unsafe { CFFileDescriptorEnableCallBacks(descriptor, info.callback_types) };
 }
 
 #[inline]
@@ -256,22 +667,26 @@ impl<T> Clone for Proxy<T> {
     }
 }
 
+/// Creates a source waking up the event loop once it's signaled.
+unsafe fn create_wakeup_source() -> CFRunLoopSourceRef {
+    // just wake up the eventloop
+    extern "C" fn event_loop_proxy_handler(_: *mut c_void) {}
+
+    // adding a Source to the main CFRunLoop lets us wake it up and
+    // process user events through the normal OS EventLoop mechanisms.
+    let rl = CFRunLoopGetMain();
+    let mut context: CFRunLoopSourceContext = mem::zeroed();
+    context.perform = Some(event_loop_proxy_handler);
+    let source = CFRunLoopSourceCreate(ptr::null_mut(), CFIndex::max_value() - 1, &mut context);
+    CFRunLoopAddSource(rl, source, kCFRunLoopCommonModes);
+    CFRunLoopWakeUp(rl);
+    source
+}
+
 impl<T> Proxy<T> {
     fn new(sender: mpsc::Sender<T>) -> Self {
         unsafe {
-            // just wake up the eventloop
-            extern "C" fn event_loop_proxy_handler(_: *mut c_void) {}
-
-            // adding a Source to the main CFRunLoop lets us wake it up and
-            // process user events through the normal OS EventLoop mechanisms.
-            let rl = CFRunLoopGetMain();
-            let mut context: CFRunLoopSourceContext = mem::zeroed();
-            context.perform = Some(event_loop_proxy_handler);
-            let source =
-                CFRunLoopSourceCreate(ptr::null_mut(), CFIndex::max_value() - 1, &mut context);
-            CFRunLoopAddSource(rl, source, kCFRunLoopCommonModes);
-            CFRunLoopWakeUp(rl);
-
+            let source = create_wakeup_source();
             Proxy { sender, source }
         }
     }
@@ -289,3 +704,34 @@ impl<T> Proxy<T> {
         Ok(())
     }
 }
+
+pub struct EventLoopWakeup {
+    source: CFRunLoopSourceRef,
+}
+
+unsafe impl Send for EventLoopWakeup {}
+unsafe impl Sync for EventLoopWakeup {}
+
+impl Drop for EventLoopWakeup {
+    fn drop(&mut self) {
+        unsafe {
+            CFRelease(self.source as _);
+        }
+    }
+}
+
+impl EventLoopWakeup {
+    fn new() -> Self {
+        EventLoopWakeup {
+            source: unsafe { create_wakeup_source() },
+        }
+    }
+
+    pub fn wake_up(&self) {
+        unsafe {
+            CFRunLoopSourceSignal(self.source);
+            let rl = CFRunLoopGetMain();
+            CFRunLoopWakeUp(rl);
+        }
+    }
+}
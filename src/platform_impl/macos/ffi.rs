@@ -17,6 +17,12 @@ use objc;
 
 pub const NSNotFound: NSInteger = NSInteger::max_value();
 
+pub const NSTabletPointEventSubtype: i16 = 1;
+pub const NSPointingDeviceTypeCursor: NSUInteger = 2;
+pub const NSPointingDeviceTypeEraser: NSUInteger = 3;
+pub const NSPenLowerSideMask: NSUInteger = 1 << 1;
+pub const NSPenUpperSideMask: NSUInteger = 1 << 2;
+
 #[repr(C)]
 pub struct NSRange {
     pub location: NSUInteger,
@@ -218,3 +224,172 @@ extern "C" {
     pub fn CGDisplayModeRetain(mode: CGDisplayModeRef);
     pub fn CGDisplayModeRelease(mode: CGDisplayModeRef);
 }
+
+pub type TISInputSourceRef = *mut libc::c_void;
+pub type UniCharCount = libc::c_ulong;
+
+pub const kUCKeyActionDown: u16 = 0;
+pub const kUCKeyTranslateNoDeadKeysMask: u32 = 1 << 0;
+
+pub const cmdKey: u32 = 1 << 8;
+pub const shiftKey: u32 = 1 << 9;
+pub const optionKey: u32 = 1 << 11;
+pub const controlKey: u32 = 1 << 12;
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    pub static kTISPropertyInputSourceID: CFStringRef;
+    pub static kTISPropertyInputSourceType: CFStringRef;
+    pub static kTISPropertyLocalizedName: CFStringRef;
+    pub static kTISPropertyUnicodeKeyLayoutData: CFStringRef;
+    pub static kTISTypeKeyboardLayout: CFStringRef;
+
+    pub fn TISCreateInputSourceList(
+        properties: CFDictionaryRef,
+        includeAllInstalled: Boolean,
+    ) -> CFArrayRef;
+    pub fn TISCopyCurrentKeyboardLayoutInputSource() -> TISInputSourceRef;
+    pub fn TISGetInputSourceProperty(
+        inputSource: TISInputSourceRef,
+        propertyKey: CFStringRef,
+    ) -> *mut libc::c_void;
+    pub fn UCKeyTranslate(
+        keyLayoutPtr: *const u8,
+        virtualKeyCode: u16,
+        keyAction: u16,
+        modifierKeyState: u32,
+        keyboardType: u32,
+        keyTranslateOptions: u32,
+        deadKeyState: *mut u32,
+        maxStringLength: UniCharCount,
+        actualStringLength: *mut UniCharCount,
+        unicodeString: *mut u16,
+    ) -> i32;
+    pub fn LMGetKbdType() -> u8;
+}
+
+pub type OSStatus = i32;
+pub type EventTargetRef = *mut libc::c_void;
+pub type EventHandlerRef = *mut libc::c_void;
+pub type EventHandlerCallRef = *mut libc::c_void;
+pub type EventRef = *mut libc::c_void;
+pub type EventHotKeyRef = *mut libc::c_void;
+pub type EventHandlerUPP = Option<
+    unsafe extern "C" fn(
+        handler: EventHandlerCallRef,
+        event: EventRef,
+        user_data: *mut libc::c_void,
+    ) -> OSStatus,
+>;
+
+#[repr(C)]
+pub struct EventTypeSpec {
+    pub eventClass: u32,
+    pub eventKind: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventHotKeyID {
+    pub signature: u32,
+    pub id: u32,
+}
+
+pub const noErr: OSStatus = 0;
+pub const eventHotKeyExistsErr: OSStatus = -9878;
+/// `'keyb'`
+pub const kEventClassKeyboard: u32 = 0x6b65_7962;
+pub const kEventHotKeyPressed: u32 = 5;
+/// `'----'`
+pub const kEventParamDirectObject: u32 = 0x2d2d_2d2d;
+/// `'hkid'`
+pub const typeEventHotKeyID: u32 = 0x686b_6964;
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    pub fn GetApplicationEventTarget() -> EventTargetRef;
+    pub fn InstallEventHandler(
+        inTarget: EventTargetRef,
+        inHandler: EventHandlerUPP,
+        inNumTypes: libc::c_ulong,
+        inList: *const EventTypeSpec,
+        inUserData: *mut libc::c_void,
+        outRef: *mut EventHandlerRef,
+    ) -> OSStatus;
+    pub fn RemoveEventHandler(inHandlerRef: EventHandlerRef) -> OSStatus;
+    pub fn GetEventParameter(
+        inEvent: EventRef,
+        inName: u32,
+        inDesiredType: u32,
+        outActualType: *mut u32,
+        inBufferSize: libc::c_ulong,
+        outActualSize: *mut libc::c_ulong,
+        outData: *mut libc::c_void,
+    ) -> OSStatus;
+    pub fn RegisterEventHotKey(
+        inHotKeyCode: u32,
+        inHotKeyModifiers: u32,
+        inHotKeyID: EventHotKeyID,
+        inTarget: EventTargetRef,
+        inOptions: u32,
+        outRef: *mut EventHotKeyRef,
+    ) -> OSStatus;
+    pub fn UnregisterEventHotKey(inHotKey: EventHotKeyRef) -> OSStatus;
+}
+
+pub type CGEventTapProxy = *mut libc::c_void;
+pub type CGEventRef = *mut libc::c_void;
+pub type CGEventMask = u64;
+pub type CFMachPortRef = *mut libc::c_void;
+pub type CGEventTapCallBack = unsafe extern "C" fn(
+    proxy: CGEventTapProxy,
+    eventType: u32,
+    event: CGEventRef,
+    userInfo: *mut libc::c_void,
+) -> CGEventRef;
+
+pub const kCGSessionEventTap: u32 = 1;
+pub const kCGHeadInsertEventTap: u32 = 0;
+pub const kCGEventTapOptionDefault: u32 = 0;
+pub const kCGEventKeyDown: u32 = 10;
+pub const kCGEventKeyUp: u32 = 11;
+pub const kCGEventFlagsChanged: u32 = 12;
+pub const kCGEventTapDisabledByTimeout: u32 = 0xFFFF_FFFE;
+pub const kCGEventTapDisabledByUserInput: u32 = 0xFFFF_FFFF;
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    pub fn CGEventTapCreate(
+        tap: u32,
+        place: u32,
+        options: u32,
+        eventsOfInterest: CGEventMask,
+        callback: CGEventTapCallBack,
+        userInfo: *mut libc::c_void,
+    ) -> CFMachPortRef;
+    pub fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
+}
+
+pub const NSUnderlineStyleNone: NSInteger = 0x00;
+pub const NSUnderlineStyleSingle: NSInteger = 0x01;
+pub const NSUnderlineStyleThick: NSInteger = 0x02;
+pub const NSUnderlineStyleDouble: NSInteger = 0x09;
+pub const NSUnderlinePatternDot: NSInteger = 0x0100;
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    pub static NSUnderlineStyleAttributeName: id;
+    pub static NSAllRomanInputSourcesLocaleIdentifier: id;
+
+    pub fn NSBitsPerSampleFromDepth(depth: NSWindowDepth) -> NSInteger;
+}
+
+pub type NSWindowDepth = i32;
+
+pub const NSDisplayGamutP3: NSInteger = 2;
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    pub fn EnableSecureEventInput() -> OSStatus;
+    pub fn DisableSecureEventInput() -> OSStatus;
+}
@@ -0,0 +1,506 @@
+//! The gamepads, which are the extended gamepads of the Game Controller framework, polled on a
+//! timer of the main run loop, and rumbled with the haptic engines of Core Haptics.
+
+use std::{
+    cell::RefCell,
+    os::raw::{c_char, c_void},
+    ptr,
+    time::Duration,
+};
+
+use cocoa::base::{id, nil, YES};
+use objc::runtime::{Sel, BOOL, NO};
+
+use crate::{
+    error::{ExternalError, NotSupportedError},
+    event::{ElementState, Event},
+    gamepad::{GamepadAxis, GamepadButton, GamepadEvent, GamepadId},
+    platform_impl::platform::{app_state::AppState, event::EventWrapper, observer::*, OsError},
+};
+
+#[link(name = "GameController", kind = "framework")]
+extern "C" {}
+
+/// The interval of the polls of the controllers, in seconds.
+const POLL_INTERVAL: f64 = 0.008;
+
+/// Core Haptics, which is only loaded once a gamepad rumbles.
+const CORE_HAPTICS: &[u8] = b"/System/Library/Frameworks/CoreHaptics.framework/CoreHaptics\0";
+
+#[derive(Default)]
+pub struct Gamepads {
+    timer: RefCell<Option<CFRunLoopTimerRef>>,
+    /// The info passed to the callback of the timer, which must outlive it.
+    controllers: Box<RefCell<Controllers>>,
+}
+
+#[derive(Default)]
+struct Controllers {
+    next_id: u32,
+    /// The connected controllers, which are retained, with their last state.
+    connected: Vec<(id, GamepadId, State)>,
+    /// The haptic engines of the controllers which rumbled.
+    haptics: Vec<Haptics>,
+}
+
+struct Haptics {
+    controller: id,
+    /// The retained haptic engine.
+    engine: id,
+    /// The retained player of the last rumble, which is stopped by the next one.
+    player: id,
+}
+
+#[derive(Default, PartialEq)]
+struct State {
+    buttons: [bool; BUTTONS.len()],
+    axes: [f64; AXES.len()],
+}
+
+const BUTTONS: [GamepadButton; 17] = [
+    GamepadButton::South,
+    GamepadButton::East,
+    GamepadButton::West,
+    GamepadButton::North,
+    GamepadButton::LeftBumper,
+    GamepadButton::RightBumper,
+    GamepadButton::LeftTrigger,
+    GamepadButton::RightTrigger,
+    GamepadButton::Select,
+    GamepadButton::Start,
+    GamepadButton::Mode,
+    GamepadButton::LeftStick,
+    GamepadButton::RightStick,
+    GamepadButton::DPadUp,
+    GamepadButton::DPadDown,
+    GamepadButton::DPadLeft,
+    GamepadButton::DPadRight,
+];
+
+const AXES: [GamepadAxis; 6] = [
+    GamepadAxis::LeftStickX,
+    GamepadAxis::LeftStickY,
+    GamepadAxis::RightStickX,
+    GamepadAxis::RightStickY,
+    GamepadAxis::LeftTrigger,
+    GamepadAxis::RightTrigger,
+];
+
+impl Gamepads {
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        let mut timer = self.timer.borrow_mut();
+        if !enabled {
+            if let Some(timer) = timer.take() {
+                unsafe {
+                    CFRunLoopTimerInvalidate(timer);
+                    CFRelease(timer as _);
+                }
+            }
+            self.controllers.borrow_mut().forget();
+            return Ok(());
+        }
+        if timer.is_some() {
+            return Ok(());
+        }
+
+        extern "C" fn poll(_: CFRunLoopTimerRef, info: *mut c_void) {
+            let controllers = unsafe { &*(info as *const RefCell<Controllers>) };
+            let events = unsafe { controllers.borrow_mut().poll() };
+            for (gamepad_id, event) in events {
+                // The events are delivered once the run loop is done with the other sources.
+                AppState::queue_event(EventWrapper::StaticEvent(Event::GamepadEvent {
+                    gamepad_id,
+                    event,
+                }));
+            }
+        }
+
+        unsafe {
+            // The controllers are only used in the background since macOS 11.3.
+            let class = class!(GCController);
+            let sel = sel!(setShouldMonitorBackgroundEvents:);
+            let responds: BOOL = msg_send![class, respondsToSelector: sel];
+            if responds != NO {
+                let () = msg_send![class, setShouldMonitorBackgroundEvents: YES];
+            }
+
+            let mut context = CFRunLoopTimerContext {
+                version: 0,
+                info: &*self.controllers as *const RefCell<Controllers> as *mut c_void,
+                retain: None,
+                release: None,
+                copyDescription: None,
+            };
+            let new_timer = CFRunLoopTimerCreate(
+                ptr::null_mut(),
+                CFAbsoluteTimeGetCurrent(),
+                POLL_INTERVAL,
+                0,
+                0,
+                poll,
+                &mut context,
+            );
+            if new_timer.is_null() {
+                return Err(ExternalError::Os(os_error!(OsError::CreationError(
+                    "Failed to create the timer of the gamepads"
+                ))));
+            }
+            CFRunLoopAddTimer(CFRunLoopGetMain(), new_timer, kCFRunLoopCommonModes);
+            *timer = Some(new_timer);
+        }
+        Ok(())
+    }
+
+    pub fn rumble(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        let mut controllers = self.controllers.borrow_mut();
+        let controller = controllers
+            .connected
+            .iter()
+            .find(|&&(_, connected, _)| connected == gamepad_id)
+            .map(|&(controller, _, _)| controller)
+            .ok_or_else(|| {
+                ExternalError::Os(os_error!(OsError::GamepadError(
+                    "The gamepad isn't connected"
+                )))
+            })?;
+        unsafe {
+            let haptics = controllers.haptics(controller)?;
+            haptics.play(strong, weak, duration).ok_or_else(|| {
+                ExternalError::Os(os_error!(OsError::GamepadError(
+                    "Failed to rumble the gamepad"
+                )))
+            })
+        }
+    }
+}
+
+impl Drop for Gamepads {
+    fn drop(&mut self) {
+        let _ = self.set_enabled(false);
+    }
+}
+
+impl Controllers {
+    /// Compares the controllers with the ones of the last poll, giving the events of their
+    /// changes.
+    unsafe fn poll(&mut self) -> Vec<(GamepadId, GamepadEvent)> {
+        let mut events = Vec::new();
+        let controllers: id = msg_send![class!(GCController), controllers];
+        let count: usize = msg_send![controllers, count];
+        let mut current = Vec::with_capacity(count);
+        for index in 0..count {
+            let controller: id = msg_send![controllers, objectAtIndex: index];
+            let gamepad: id = msg_send![controller, extendedGamepad];
+            if gamepad != nil {
+                current.push((controller, gamepad));
+            }
+        }
+
+        let mut index = 0;
+        while index < self.connected.len() {
+            let (controller, gamepad_id, _) = self.connected[index];
+            if current.iter().any(|&(current, _)| current == controller) {
+                index += 1;
+            } else {
+                events.push((gamepad_id, GamepadEvent::Disconnected));
+                self.release_haptics(controller);
+                let () = msg_send![controller, release];
+                self.connected.remove(index);
+            }
+        }
+
+        for (controller, gamepad) in current {
+            let state = read_state(gamepad);
+            let index = self
+                .connected
+                .iter()
+                .position(|&(connected, _, _)| connected == controller);
+            let index = match index {
+                Some(index) => index,
+                None => {
+                    let gamepad_id = GamepadId(self.next_id);
+                    self.next_id = self.next_id.wrapping_add(1);
+                    events.push((
+                        gamepad_id,
+                        GamepadEvent::Connected {
+                            name: controller_name(controller),
+                        },
+                    ));
+                    let () = msg_send![controller, retain];
+                    self.connected
+                        .push((controller, gamepad_id, State::default()));
+                    self.connected.len() - 1
+                }
+            };
+            let (_, gamepad_id, previous) = &mut self.connected[index];
+            let gamepad_id = *gamepad_id;
+            if state == *previous {
+                continue;
+            }
+            for (index, &button) in BUTTONS.iter().enumerate() {
+                if state.buttons[index] != previous.buttons[index] {
+                    let state = if state.buttons[index] {
+                        ElementState::Pressed
+                    } else {
+                        ElementState::Released
+                    };
+                    events.push((gamepad_id, GamepadEvent::Button { button, state }));
+                }
+            }
+            for (index, &axis) in AXES.iter().enumerate() {
+                if state.axes[index] != previous.axes[index] {
+                    let value = state.axes[index];
+                    events.push((gamepad_id, GamepadEvent::Axis { axis, value }));
+                }
+            }
+            *previous = state;
+        }
+        events
+    }
+
+    /// Forgets the controllers, without disconnecting them.
+    fn forget(&mut self) {
+        self.haptics.clear();
+        for (controller, _, _) in self.connected.drain(..) {
+            let () = unsafe { msg_send![controller, release] };
+        }
+    }
+
+    /// The haptics of the controller, whose engine is created on its first rumble.
+    unsafe fn haptics(&mut self, controller: id) -> Result<&mut Haptics, ExternalError> {
+        if let Some(index) = self
+            .haptics
+            .iter()
+            .position(|haptics| haptics.controller == controller)
+        {
+            return Ok(&mut self.haptics[index]);
+        }
+        // The haptics of the controllers need macOS 11.
+        let responds: BOOL = msg_send![controller, respondsToSelector: sel!(haptics)];
+        if responds == NO || !load_core_haptics() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        let haptics: id = msg_send![controller, haptics];
+        let locality = symbol(b"GCHapticsLocalityDefault\0");
+        if haptics == nil || locality == nil {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        let engine: id = msg_send![haptics, createEngineWithLocality: locality];
+        if engine == nil {
+            return Err(ExternalError::Os(os_error!(OsError::GamepadError(
+                "Failed to create the haptic engine of the gamepad"
+            ))));
+        }
+        let () = msg_send![engine, retain];
+        self.haptics.push(Haptics {
+            controller,
+            engine,
+            player: nil,
+        });
+        Ok(self.haptics.last_mut().unwrap())
+    }
+
+    fn release_haptics(&mut self, controller: id) {
+        self.haptics
+            .retain(|haptics| haptics.controller != controller);
+    }
+}
+
+impl Haptics {
+    /// Plays a continuous event, replacing the last one.
+    unsafe fn play(&mut self, strong: f64, weak: f64, duration: Duration) -> Option<()> {
+        let (strong, weak) = (strong.clamp(0., 1.), weak.clamp(0., 1.));
+        // The weak motor is the high frequency one, which makes the rumble sharper.
+        let sharpness = if strong + weak > 0. {
+            weak / (strong + weak)
+        } else {
+            0.
+        };
+        let event_type = symbol(b"CHHapticEventTypeHapticContinuous\0");
+        let intensity_id = symbol(b"CHHapticEventParameterIDHapticIntensity\0");
+        let sharpness_id = symbol(b"CHHapticEventParameterIDHapticSharpness\0");
+        if event_type == nil || intensity_id == nil || sharpness_id == nil {
+            return None;
+        }
+
+        // The engine is stopped by the system while the application is inactive.
+        let mut error: id = nil;
+        let started: BOOL = msg_send![self.engine, startAndReturnError: &mut error];
+        if started == NO {
+            return None;
+        }
+
+        let parameter = |parameter_id: id, value: f64| -> id {
+            let parameter: id = msg_send![class!(CHHapticEventParameter), alloc];
+            let parameter: id =
+                msg_send![parameter, initWithParameterID: parameter_id value: value as f32];
+            msg_send![parameter, autorelease]
+        };
+        let parameters = [
+            parameter(intensity_id, strong.max(weak)),
+            parameter(sharpness_id, sharpness),
+        ];
+        let parameters: id = msg_send![
+            class!(NSArray),
+            arrayWithObjects: parameters.as_ptr()
+            count: parameters.len()
+        ];
+        let event: id = msg_send![class!(CHHapticEvent), alloc];
+        let event: id = msg_send![
+            event,
+            initWithEventType: event_type
+            parameters: parameters
+            relativeTime: 0f64
+            duration: duration.as_secs_f64()
+        ];
+        let event: id = msg_send![event, autorelease];
+        let events: id = msg_send![class!(NSArray), arrayWithObject: event];
+        let no_parameters: id = msg_send![class!(NSArray), array];
+        let pattern: id = msg_send![class!(CHHapticPattern), alloc];
+        let pattern: id = msg_send![
+            pattern,
+            initWithEvents: events
+            parameters: no_parameters
+            error: &mut error
+        ];
+        if pattern == nil {
+            return None;
+        }
+        let pattern: id = msg_send![pattern, autorelease];
+        let player: id = msg_send![self.engine, createPlayerWithPattern: pattern error: &mut error];
+        if player == nil {
+            return None;
+        }
+
+        self.stop();
+        let () = msg_send![player, retain];
+        self.player = player;
+        // `CHHapticTimeImmediate`
+        let started: BOOL = msg_send![player, startAtTime: 0f64 error: &mut error];
+        if started == NO {
+            return None;
+        }
+        Some(())
+    }
+
+    unsafe fn stop(&mut self) {
+        if self.player != nil {
+            let mut error: id = nil;
+            let _: BOOL = msg_send![self.player, stopAtTime: 0f64 error: &mut error];
+            let () = msg_send![self.player, release];
+            self.player = nil;
+        }
+    }
+}
+
+impl Drop for Haptics {
+    fn drop(&mut self) {
+        unsafe {
+            self.stop();
+            let () = msg_send![self.engine, stopWithCompletionHandler: nil];
+            let () = msg_send![self.engine, release];
+        }
+    }
+}
+
+/// Loads Core Haptics, whose classes are looked up by the messages.
+fn load_core_haptics() -> bool {
+    let handle = unsafe {
+        libc::dlopen(
+            CORE_HAPTICS.as_ptr() as *const c_char,
+            libc::RTLD_LAZY | libc::RTLD_GLOBAL,
+        )
+    };
+    // The handle is kept, the framework staying loaded.
+    !handle.is_null()
+}
+
+/// The string constant of the loaded frameworks, or `nil` when it's not there.
+unsafe fn symbol(name: &[u8]) -> id {
+    let symbol = libc::dlsym(libc::RTLD_DEFAULT, name.as_ptr() as *const c_char) as *const id;
+    if symbol.is_null() {
+        nil
+    } else {
+        *symbol
+    }
+}
+
+unsafe fn controller_name(controller: id) -> String {
+    let name: id = msg_send![controller, vendorName];
+    if name == nil {
+        return "Gamepad".to_owned();
+    }
+    let utf8: *const c_char = msg_send![name, UTF8String];
+    std::ffi::CStr::from_ptr(utf8)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The element of the gamepad, or `nil` when the version of the framework doesn't have it.
+unsafe fn element(gamepad: id, sel: Sel) -> id {
+    let responds: BOOL = msg_send![gamepad, respondsToSelector: sel];
+    if responds == NO {
+        return nil;
+    }
+    msg_send![gamepad, performSelector: sel]
+}
+
+unsafe fn pressed(button: id) -> bool {
+    if button == nil {
+        return false;
+    }
+    let pressed: BOOL = msg_send![button, isPressed];
+    pressed != NO
+}
+
+unsafe fn value(input: id) -> f64 {
+    let value: f32 = msg_send![input, value];
+    value as f64
+}
+
+unsafe fn read_state(gamepad: id) -> State {
+    let dpad: id = msg_send![gamepad, dpad];
+    let left_stick: id = msg_send![gamepad, leftThumbstick];
+    let right_stick: id = msg_send![gamepad, rightThumbstick];
+    let left_trigger: id = msg_send![gamepad, leftTrigger];
+    let right_trigger: id = msg_send![gamepad, rightTrigger];
+    let buttons: [id; BUTTONS.len()] = [
+        msg_send![gamepad, buttonA],
+        msg_send![gamepad, buttonB],
+        msg_send![gamepad, buttonX],
+        msg_send![gamepad, buttonY],
+        msg_send![gamepad, leftShoulder],
+        msg_send![gamepad, rightShoulder],
+        left_trigger,
+        right_trigger,
+        element(gamepad, sel!(buttonOptions)),
+        element(gamepad, sel!(buttonMenu)),
+        element(gamepad, sel!(buttonHome)),
+        element(gamepad, sel!(leftThumbstickButton)),
+        element(gamepad, sel!(rightThumbstickButton)),
+        msg_send![dpad, up],
+        msg_send![dpad, down],
+        msg_send![dpad, left],
+        msg_send![dpad, right],
+    ];
+    let mut state = State::default();
+    for (pressed_state, &button) in state.buttons.iter_mut().zip(buttons.iter()) {
+        *pressed_state = pressed(button);
+    }
+    // The y axes of the thumbsticks already point up.
+    state.axes = [
+        value(msg_send![left_stick, xAxis]),
+        value(msg_send![left_stick, yAxis]),
+        value(msg_send![right_stick, xAxis]),
+        value(msg_send![right_stick, yAxis]),
+        value(left_trigger),
+        value(right_trigger),
+    ];
+    state
+}
@@ -0,0 +1,154 @@
+//! The global hotkeys, which are the Carbon hotkeys of the application event target.
+
+use std::{collections::HashMap, mem, os::raw::c_void, ptr};
+
+use crate::{
+    error::ExternalError,
+    event::{Event, ModifiersState},
+    hotkey::{Accelerator, HotkeyId},
+    platform_impl::platform::{
+        app_state::AppState,
+        event::{scancode_to_keycode, EventWrapper},
+        ffi, OsError,
+    },
+};
+
+/// The signature of the hotkeys of winit, `'wnit'`.
+const HOTKEY_SIGNATURE: u32 = 0x776e_6974;
+
+pub struct Hotkeys {
+    next_id: u32,
+    hotkeys: HashMap<HotkeyId, ffi::EventHotKeyRef>,
+    /// The handler of the hotkey presses, which is installed along with the first hotkey.
+    handler: ffi::EventHandlerRef,
+}
+
+impl Default for Hotkeys {
+    fn default() -> Self {
+        Hotkeys {
+            next_id: 0,
+            hotkeys: HashMap::new(),
+            handler: ptr::null_mut(),
+        }
+    }
+}
+
+impl Hotkeys {
+    pub fn register(&mut self, accelerator: Accelerator) -> Result<HotkeyId, ExternalError> {
+        let keycode = (0..0x80)
+            .find(|&scancode| scancode_to_keycode(scancode) == Some(accelerator.key))
+            .ok_or_else(|| hotkey_error("The keyboard layout has no such key"))?;
+
+        let mut modifiers = 0;
+        for &(modifier, key) in &[
+            (ModifiersState::SHIFT, ffi::shiftKey),
+            (ModifiersState::CTRL, ffi::controlKey),
+            (ModifiersState::ALT, ffi::optionKey),
+            (ModifiersState::LOGO, ffi::cmdKey),
+        ] {
+            if accelerator.modifiers.contains(modifier) {
+                modifiers |= key;
+            }
+        }
+
+        unsafe {
+            if self.handler.is_null() {
+                let event_type = ffi::EventTypeSpec {
+                    eventClass: ffi::kEventClassKeyboard,
+                    eventKind: ffi::kEventHotKeyPressed,
+                };
+                let status = ffi::InstallEventHandler(
+                    ffi::GetApplicationEventTarget(),
+                    Some(hotkey_pressed),
+                    1,
+                    &event_type,
+                    ptr::null_mut(),
+                    &mut self.handler,
+                );
+                if status != ffi::noErr {
+                    self.handler = ptr::null_mut();
+                    return Err(hotkey_error("Failed to install the handler of the hotkeys"));
+                }
+            }
+
+            let id = self.next_id;
+            let mut hotkey = ptr::null_mut();
+            let status = ffi::RegisterEventHotKey(
+                keycode as u32,
+                modifiers,
+                ffi::EventHotKeyID {
+                    signature: HOTKEY_SIGNATURE,
+                    id,
+                },
+                ffi::GetApplicationEventTarget(),
+                0,
+                &mut hotkey,
+            );
+            match status {
+                ffi::noErr => (),
+                ffi::eventHotKeyExistsErr => {
+                    return Err(hotkey_error("The hotkey is already registered"))
+                }
+                _ => return Err(hotkey_error("Failed to register the hotkey")),
+            }
+
+            self.next_id = self.next_id.wrapping_add(1);
+            let id = HotkeyId(id as usize);
+            self.hotkeys.insert(id, hotkey);
+            Ok(id)
+        }
+    }
+
+    pub fn unregister(&mut self, id: HotkeyId) -> bool {
+        match self.hotkeys.remove(&id) {
+            Some(hotkey) => {
+                unsafe { ffi::UnregisterEventHotKey(hotkey) };
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Drop for Hotkeys {
+    fn drop(&mut self) {
+        unsafe {
+            for (_, hotkey) in self.hotkeys.drain() {
+                ffi::UnregisterEventHotKey(hotkey);
+            }
+            if !self.handler.is_null() {
+                ffi::RemoveEventHandler(self.handler);
+            }
+        }
+    }
+}
+
+extern "C" fn hotkey_pressed(
+    _: ffi::EventHandlerCallRef,
+    event: ffi::EventRef,
+    _: *mut c_void,
+) -> ffi::OSStatus {
+    let mut hotkey = ffi::EventHotKeyID::default();
+    let status = unsafe {
+        ffi::GetEventParameter(
+            event,
+            ffi::kEventParamDirectObject,
+            ffi::typeEventHotKeyID,
+            ptr::null_mut(),
+            mem::size_of::<ffi::EventHotKeyID>() as _,
+            ptr::null_mut(),
+            &mut hotkey as *mut _ as *mut c_void,
+        )
+    };
+    if status == ffi::noErr && hotkey.signature == HOTKEY_SIGNATURE {
+        // The event is delivered once the run loop is done with the other sources.
+        AppState::queue_event(EventWrapper::StaticEvent(Event::GlobalHotkey(HotkeyId(
+            hotkey.id as usize,
+        ))));
+    }
+    ffi::noErr
+}
+
+fn hotkey_error(message: &'static str) -> ExternalError {
+    ExternalError::Os(os_error!(OsError::HotkeyError(message)))
+}
@@ -0,0 +1,137 @@
+//! The introspection of the keyboard layouts, which are the keyboard input sources of macOS.
+
+use std::time::Duration;
+
+use cocoa::{appkit::NSEventModifierFlags, foundation::NSUInteger};
+use core_foundation::{
+    array::{CFArrayGetCount, CFArrayGetValueAtIndex},
+    base::{CFEqual, CFRelease, TCFType},
+    data::{CFDataGetBytePtr, CFDataRef},
+    string::{CFString, CFStringRef},
+};
+
+use crate::{
+    event::{ModifiersState, ScanCode},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
+    platform_impl::platform::ffi,
+};
+
+/// The keyboard layouts the user enabled.
+pub fn keyboard_layouts() -> Vec<KeyboardLayout> {
+    unsafe {
+        let sources = ffi::TISCreateInputSourceList(std::ptr::null(), ffi::FALSE);
+        if sources.is_null() {
+            return Vec::new();
+        }
+        let layouts = (0..CFArrayGetCount(sources))
+            .map(|index| CFArrayGetValueAtIndex(sources, index) as ffi::TISInputSourceRef)
+            .filter(|&source| {
+                let kind = ffi::TISGetInputSourceProperty(source, ffi::kTISPropertyInputSourceType);
+                !kind.is_null() && CFEqual(kind as _, ffi::kTISTypeKeyboardLayout as _) != 0
+            })
+            .map(|source| layout(source))
+            .collect();
+        CFRelease(sources as _);
+        layouts
+    }
+}
+
+pub fn current_keyboard_layout() -> KeyboardLayout {
+    unsafe {
+        let source = ffi::TISCopyCurrentKeyboardLayoutInputSource();
+        let layout = layout(source);
+        CFRelease(source as _);
+        layout
+    }
+}
+
+/// The characters the key at `scancode` produces with the current layout and `modifiers`.
+pub fn scancode_to_text(scancode: ScanCode, modifiers: ModifiersState) -> Option<String> {
+    let mut modifier_state = 0;
+    for &(modifier, key) in &[
+        (ModifiersState::SHIFT, ffi::shiftKey),
+        (ModifiersState::CTRL, ffi::controlKey),
+        (ModifiersState::ALT, ffi::optionKey),
+        (ModifiersState::LOGO, ffi::cmdKey),
+    ] {
+        if modifiers.contains(modifier) {
+            modifier_state |= key;
+        }
+    }
+
+    unsafe {
+        let source = ffi::TISCopyCurrentKeyboardLayoutInputSource();
+        let layout_data =
+            ffi::TISGetInputSourceProperty(source, ffi::kTISPropertyUnicodeKeyLayoutData)
+                as CFDataRef;
+        let text = if layout_data.is_null() {
+            None
+        } else {
+            let mut dead_key_state = 0;
+            let mut text = [0u16; 8];
+            let mut len = 0;
+            // The dead keys give their spacing character instead of waiting for the next key.
+            let status = ffi::UCKeyTranslate(
+                CFDataGetBytePtr(layout_data),
+                scancode as u16,
+                ffi::kUCKeyActionDown,
+                (modifier_state >> 8) & 0xff,
+                ffi::LMGetKbdType() as u32,
+                ffi::kUCKeyTranslateNoDeadKeysMask,
+                &mut dead_key_state,
+                text.len() as ffi::UniCharCount,
+                &mut len,
+                text.as_mut_ptr(),
+            );
+            if status == 0 && len > 0 {
+                Some(String::from_utf16_lossy(
+                    &text[..(len as usize).min(text.len())],
+                ))
+            } else {
+                None
+            }
+        };
+        CFRelease(source as _);
+        text
+    }
+}
+
+/// The repeat settings of the keyboard preferences.
+pub fn key_repeat() -> KeyRepeat {
+    unsafe {
+        // The `NSTimeInterval`s are in seconds.
+        let delay: f64 = msg_send![class!(NSEvent), keyRepeatDelay];
+        let interval: f64 = msg_send![class!(NSEvent), keyRepeatInterval];
+        KeyRepeat {
+            delay: Duration::from_secs_f64(delay.max(0.0)),
+            interval: Duration::from_secs_f64(interval.max(0.0)),
+        }
+    }
+}
+
+/// The lock keys which are on, which is only Caps Lock on the keyboards of the Mac.
+pub fn lock_keys() -> LockKeys {
+    let flags: NSUInteger = unsafe { msg_send![class!(NSEvent), modifierFlags] };
+    let flags = NSEventModifierFlags::from_bits_truncate(flags);
+    let mut lock_keys = LockKeys::empty();
+    lock_keys.set(
+        LockKeys::CAPS_LOCK,
+        flags.contains(NSEventModifierFlags::NSAlphaShiftKeyMask),
+    );
+    lock_keys
+}
+
+unsafe fn layout(source: ffi::TISInputSourceRef) -> KeyboardLayout {
+    let property = |key| {
+        let value = ffi::TISGetInputSourceProperty(source, key) as CFStringRef;
+        if value.is_null() {
+            String::new()
+        } else {
+            CFString::wrap_under_get_rule(value).to_string()
+        }
+    };
+    KeyboardLayout {
+        id: property(ffi::kTISPropertyInputSourceID),
+        name: property(ffi::kTISPropertyLocalizedName),
+    }
+}
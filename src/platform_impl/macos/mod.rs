@@ -3,12 +3,18 @@
 mod app;
 mod app_delegate;
 mod app_state;
+mod child_surface;
+mod clipboard;
 mod event;
 mod event_loop;
 mod ffi;
+mod gamepad;
+mod hotkey;
+mod keymap;
 mod menu;
 mod monitor;
 mod observer;
+mod shortcuts;
 mod util;
 mod view;
 mod window;
@@ -18,8 +24,11 @@ use std::{fmt, ops::Deref, sync::Arc};
 
 pub use self::{
     app_delegate::{get_aux_state_mut, AuxDelegateState},
-    event_loop::{EventLoop, EventLoopWindowTarget, Proxy as EventLoopProxy},
+    child_surface::ChildSurface,
+    event::{key_to_scancode, scancode_to_key},
+    event_loop::{EventLoop, EventLoopWakeup, EventLoopWindowTarget, Proxy as EventLoopProxy},
     monitor::{MonitorHandle, VideoMode},
+    util::CustomCursor,
     window::{Id as WindowId, PlatformSpecificWindowBuilderAttributes, UnownedWindow},
 };
 use crate::{
@@ -51,6 +60,11 @@ pub struct Window {
 pub enum OsError {
     CGError(core_graphics::base::CGError),
     CreationError(&'static str),
+    PasteboardError(&'static str),
+    SourceError(&'static str),
+    HotkeyError(&'static str),
+    EventTapError(&'static str),
+    GamepadError(&'static str),
 }
 
 unsafe impl Send for Window {}
@@ -80,6 +94,11 @@ impl fmt::Display for OsError {
         match self {
             OsError::CGError(e) => f.pad(&format!("CGError {}", e)),
             OsError::CreationError(e) => f.pad(e),
+            OsError::PasteboardError(e) => f.pad(e),
+            OsError::SourceError(e) => f.pad(e),
+            OsError::HotkeyError(e) => f.pad(e),
+            OsError::EventTapError(e) => f.pad(e),
+            OsError::GamepadError(e) => f.pad(e),
         }
     }
 }
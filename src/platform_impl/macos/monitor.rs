@@ -3,12 +3,16 @@ use std::{collections::VecDeque, fmt};
 use super::{ffi, util};
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
-    monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
+    monitor::{
+        ColorCapabilities, ColorPrimaries, MonitorHandle as RootMonitorHandle, MonitorIdentity,
+        TransferFunction, VideoMode as RootVideoMode,
+    },
+    platform_impl::edid,
 };
 use cocoa::{
-    appkit::NSScreen,
-    base::{id, nil},
-    foundation::NSUInteger,
+    appkit::{CGFloat, NSScreen},
+    base::{id, nil, BOOL, NO},
+    foundation::{NSTimeInterval, NSUInteger},
 };
 use core_foundation::{
     array::{CFArrayGetCount, CFArrayGetValueAtIndex},
@@ -223,6 +227,23 @@ impl MonitorHandle {
         unsafe { NSScreen::backingScaleFactor(screen) as f64 }
     }
 
+    /// The nominal refresh rate of the display link of the display, in millihertz.
+    pub(crate) fn refresh_rate_millihertz(&self) -> Option<u32> {
+        unsafe {
+            let mut display_link = std::ptr::null_mut();
+            if CVDisplayLinkCreateWithCGDisplay(self.0, &mut display_link) != kCVReturnSuccess {
+                return None;
+            }
+            let time = CVDisplayLinkGetNominalOutputVideoRefreshPeriod(display_link);
+            CVDisplayLinkRelease(display_link);
+
+            if time.flags & kCVTimeIsIndefinite != 0 || time.timeValue <= 0 {
+                return None;
+            }
+            Some((time.timeScale as i64 * 1000 / time.timeValue) as u32)
+        }
+    }
+
     pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
         let cv_refresh_rate = unsafe {
             let mut display_link = std::ptr::null_mut();
@@ -297,6 +318,126 @@ impl MonitorHandle {
         }
     }
 
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        let screen = match self.ns_screen() {
+            Some(screen) => screen,
+            None => return Default::default(),
+        };
+        unsafe {
+            // The potential headroom is from macOS 10.15 on, and stays at 1.0 without EDR.
+            let sel = sel!(maximumPotentialExtendedDynamicRangeColorComponentValue);
+            let responds: BOOL = msg_send![screen, respondsToSelector: sel];
+            let headroom: CGFloat = if responds != NO {
+                msg_send![
+                    screen,
+                    maximumPotentialExtendedDynamicRangeColorComponentValue
+                ]
+            } else {
+                1.0
+            };
+            let hdr = headroom > 1.0;
+
+            let depth: ffi::NSWindowDepth = msg_send![screen, depth];
+            let bit_depth = ffi::NSBitsPerSampleFromDepth(depth);
+
+            // The gamuts are given from macOS 10.12 on.
+            let mut primaries = vec![ColorPrimaries::Srgb];
+            let sel = sel!(canRepresentDisplayGamut:);
+            let responds: BOOL = msg_send![screen, respondsToSelector: sel];
+            if responds != NO {
+                let p3: BOOL = msg_send![screen, canRepresentDisplayGamut: ffi::NSDisplayGamutP3];
+                if p3 != NO {
+                    primaries.push(ColorPrimaries::DisplayP3);
+                }
+            }
+
+            // The EDR content is extended linear sRGB, and the PQ and HLG content is mapped to it.
+            let mut transfer_functions = vec![TransferFunction::Srgb];
+            if hdr {
+                transfer_functions.extend_from_slice(&[
+                    TransferFunction::ExtendedLinear,
+                    TransferFunction::Pq,
+                    TransferFunction::Hlg,
+                ]);
+            }
+
+            ColorCapabilities {
+                hdr,
+                bit_depth: Some(bit_depth as u8).filter(|&bit_depth| bit_depth > 0),
+                max_luminance: None,
+                headroom: Some(headroom as f32),
+                primaries,
+                transfer_functions,
+            }
+        }
+    }
+
+    pub fn supports_variable_refresh(&self) -> bool {
+        let screen = match self.ns_screen() {
+            Some(screen) => screen,
+            None => return false,
+        };
+        unsafe {
+            // The refresh intervals are from macOS 12 on, and only differ with adaptive sync.
+            let sel = sel!(minimumRefreshInterval);
+            let responds: BOOL = msg_send![screen, respondsToSelector: sel];
+            if responds == NO {
+                return false;
+            }
+            let minimum: NSTimeInterval = msg_send![screen, minimumRefreshInterval];
+            let maximum: NSTimeInterval = msg_send![screen, maximumRefreshInterval];
+            maximum > minimum
+        }
+    }
+
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        let screen = match self.ns_screen() {
+            Some(screen) => screen,
+            None => return (self.position(), self.size()),
+        };
+        let scale_factor = self.scale_factor();
+        unsafe {
+            // The visible frame leaves out the menu bar and the dock, with the Y axis of Cocoa
+            // going up, so it's placed against the bounds of the display from its insets.
+            let frame = NSScreen::frame(screen);
+            let visible_frame = NSScreen::visibleFrame(screen);
+            let bounds = CGDisplayBounds(self.native_identifier());
+            let left = visible_frame.origin.x - frame.origin.x;
+            let top = (frame.origin.y + frame.size.height)
+                - (visible_frame.origin.y + visible_frame.size.height);
+            (
+                PhysicalPosition::from_logical::<_, f64>(
+                    (bounds.origin.x + left, bounds.origin.y + top),
+                    scale_factor,
+                ),
+                PhysicalSize::from_logical::<_, f64>(
+                    (visible_frame.size.width, visible_frame.size.height),
+                    scale_factor,
+                ),
+            )
+        }
+    }
+
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        // The numbers are the ones of the EDID, with the displays which are gone having none.
+        const VENDOR_UNKNOWN: u32 = 0x756e_6b6e;
+        let display = CGDisplay::new(self.0);
+        let vendor = display.vendor_number();
+        if vendor == 0 || vendor == VENDOR_UNKNOWN {
+            return None;
+        }
+
+        let serial = display.serial_number();
+        Some(MonitorIdentity {
+            manufacturer: edid::manufacturer(vendor as u16),
+            model: None,
+            product_code: Some(display.model_number() as u16),
+            serial: Some(serial)
+                .filter(|&serial| serial != 0)
+                .map(|serial| serial.to_string()),
+        })
+    }
+
     pub(crate) fn ns_screen(&self) -> Option<id> {
         unsafe {
             let uuid = ffi::CGDisplayCreateUUIDFromDisplayID(self.0);
@@ -1,10 +1,11 @@
 use std::{
     self,
+    cell::Cell,
     os::raw::*,
     panic::{AssertUnwindSafe, UnwindSafe},
     ptr,
     rc::Weak,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use crate::platform_impl::platform::{
@@ -46,6 +47,7 @@ extern "C" {
     pub fn CFRunLoopAddTimer(rl: CFRunLoopRef, timer: CFRunLoopTimerRef, mode: CFRunLoopMode);
     pub fn CFRunLoopTimerSetNextFireDate(timer: CFRunLoopTimerRef, fireDate: CFAbsoluteTime);
     pub fn CFRunLoopTimerInvalidate(time: CFRunLoopTimerRef);
+    pub fn CFRunLoopTimerIsValid(timer: CFRunLoopTimerRef) -> ffi::Boolean;
 
     pub fn CFRunLoopSourceCreate(
         allocator: CFAllocatorRef,
@@ -57,6 +59,28 @@ extern "C" {
     pub fn CFRunLoopSourceInvalidate(source: CFRunLoopSourceRef);
     pub fn CFRunLoopSourceSignal(source: CFRunLoopSourceRef);
 
+    pub fn CFFileDescriptorCreate(
+        allocator: CFAllocatorRef,
+        fd: CFFileDescriptorNativeDescriptor,
+        closeOnInvalidate: ffi::Boolean,
+        callout: CFFileDescriptorCallBack,
+        context: *const CFFileDescriptorContext,
+    ) -> CFFileDescriptorRef;
+    pub fn CFFileDescriptorEnableCallBacks(f: CFFileDescriptorRef, callBackTypes: CFOptionFlags);
+    pub fn CFFileDescriptorCreateRunLoopSource(
+        allocator: CFAllocatorRef,
+        f: CFFileDescriptorRef,
+        order: CFIndex,
+    ) -> CFRunLoopSourceRef;
+    pub fn CFFileDescriptorInvalidate(f: CFFileDescriptorRef);
+
+    pub fn CFMachPortCreateRunLoopSource(
+        allocator: CFAllocatorRef,
+        port: ffi::CFMachPortRef,
+        order: CFIndex,
+    ) -> CFRunLoopSourceRef;
+    pub fn CFMachPortInvalidate(port: ffi::CFMachPortRef);
+
     pub fn CFAbsoluteTimeGetCurrent() -> CFAbsoluteTime;
     pub fn CFRelease(cftype: *const c_void);
 }
@@ -74,6 +98,9 @@ pub enum CFRunLoopSource {}
 pub type CFRunLoopSourceRef = *mut CFRunLoopSource;
 pub enum CFString {}
 pub type CFStringRef = *const CFString;
+pub enum CFFileDescriptor {}
+pub type CFFileDescriptorRef = *mut CFFileDescriptor;
+pub type CFFileDescriptorNativeDescriptor = c_int;
 
 pub type CFHashCode = c_ulong;
 pub type CFIndex = c_long;
@@ -92,11 +119,28 @@ pub const kCFRunLoopAfterWaiting: CFRunLoopActivity = 1 << 6;
 #[allow(non_upper_case_globals)]
 pub const kCFRunLoopExit: CFRunLoopActivity = 1 << 7;
 
+#[allow(non_upper_case_globals)]
+pub const kCFFileDescriptorReadCallBack: CFOptionFlags = 1 << 0;
+#[allow(non_upper_case_globals)]
+pub const kCFFileDescriptorWriteCallBack: CFOptionFlags = 1 << 1;
+
 pub type CFRunLoopObserverCallBack =
     extern "C" fn(observer: CFRunLoopObserverRef, activity: CFRunLoopActivity, info: *mut c_void);
 pub type CFRunLoopTimerCallBack = extern "C" fn(timer: CFRunLoopTimerRef, info: *mut c_void);
+pub type CFFileDescriptorCallBack =
+    extern "C" fn(f: CFFileDescriptorRef, callBackTypes: CFOptionFlags, info: *mut c_void);
 
-pub enum CFRunLoopTimerContext {}
+/// This mirrors the struct with the same name from Core Foundation.
+/// https://developer.apple.com/documentation/corefoundation/cfrunlooptimercontext?language=objc
+#[allow(non_snake_case)]
+#[repr(C)]
+pub struct CFRunLoopTimerContext {
+    pub version: CFIndex,
+    pub info: *mut c_void,
+    pub retain: Option<extern "C" fn(info: *const c_void) -> *const c_void>,
+    pub release: Option<extern "C" fn(info: *const c_void)>,
+    pub copyDescription: Option<extern "C" fn(info: *const c_void) -> CFStringRef>,
+}
 
 /// This mirrors the struct with the same name from Core Foundation.
 /// https://developer.apple.com/documentation/corefoundation/cfrunloopobservercontext?language=objc
@@ -125,6 +169,18 @@ pub struct CFRunLoopSourceContext {
     pub perform: Option<extern "C" fn(*mut c_void)>,
 }
 
+/// This mirrors the struct with the same name from Core Foundation.
+/// https://developer.apple.com/documentation/corefoundation/cffiledescriptorcontext?language=objc
+#[allow(non_snake_case)]
+#[repr(C)]
+pub struct CFFileDescriptorContext {
+    pub version: CFIndex,
+    pub info: *mut c_void,
+    pub retain: Option<extern "C" fn(info: *mut c_void) -> *mut c_void>,
+    pub release: Option<extern "C" fn(info: *mut c_void)>,
+    pub copyDescription: Option<extern "C" fn(info: *mut c_void) -> CFStringRef>,
+}
+
 unsafe fn control_flow_handler<F>(panic_info: *mut c_void, f: F)
 where
     F: FnOnce(Weak<PanicInfo>) + UnwindSafe,
@@ -240,6 +296,10 @@ pub fn setup_control_flow_observers(panic_info: Weak<PanicInfo>) {
 
 pub struct EventLoopWaker {
     timer: CFRunLoopTimerRef,
+    /// Whether the timer fires a little early, to wait for the rest of the time more precisely.
+    precise: bool,
+    /// The deadline the timer waits for once it fired, read by its callback.
+    precise_deadline: Box<Cell<Option<Instant>>>,
 }
 
 impl Drop for EventLoopWaker {
@@ -253,8 +313,21 @@ impl Drop for EventLoopWaker {
 
 impl Default for EventLoopWaker {
     fn default() -> EventLoopWaker {
-        extern "C" fn wakeup_main_loop(_timer: CFRunLoopTimerRef, _info: *mut c_void) {}
+        extern "C" fn wakeup_main_loop(_timer: CFRunLoopTimerRef, info: *mut c_void) {
+            let precise_deadline = unsafe { &*(info as *const Cell<Option<Instant>>) };
+            if let Some(deadline) = precise_deadline.take() {
+                wait_until(deadline);
+            }
+        }
+        let precise_deadline = Box::new(Cell::new(None));
         unsafe {
+            let mut context = CFRunLoopTimerContext {
+                version: 0,
+                info: &*precise_deadline as *const Cell<Option<Instant>> as *mut c_void,
+                retain: None,
+                release: None,
+                copyDescription: None,
+            };
             // Create a timer with a 0.1µs interval (1ns does not work) to mimic polling.
             // It is initially setup with a first fire time really far into the
             // future, but that gets changed to fire immediately in did_finish_launching
@@ -265,20 +338,30 @@ impl Default for EventLoopWaker {
                 0,
                 0,
                 wakeup_main_loop,
-                ptr::null_mut(),
+                &mut context,
             );
             CFRunLoopAddTimer(CFRunLoopGetMain(), timer, kCFRunLoopCommonModes);
-            EventLoopWaker { timer }
+            EventLoopWaker {
+                timer,
+                precise: false,
+                precise_deadline,
+            }
         }
     }
 }
 
 impl EventLoopWaker {
+    pub fn set_precise(&mut self, precise: bool) {
+        self.precise = precise;
+    }
+
     pub fn stop(&mut self) {
+        self.precise_deadline.set(None);
         unsafe { CFRunLoopTimerSetNextFireDate(self.timer, std::f64::MAX) }
     }
 
     pub fn start(&mut self) {
+        self.precise_deadline.set(None);
         unsafe { CFRunLoopTimerSetNextFireDate(self.timer, std::f64::MIN) }
     }
 
@@ -287,9 +370,17 @@ impl EventLoopWaker {
         if now >= instant {
             self.start();
         } else {
+            let mut duration = instant - now;
+            if self.precise {
+                // The run loop tends to wake up late, so the timer fires early and its callback
+                // waits for the rest of the time.
+                duration = duration.saturating_sub(PRECISE_WAIT_MARGIN);
+                self.precise_deadline.set(Some(instant));
+            } else {
+                self.precise_deadline.set(None);
+            }
             unsafe {
                 let current = CFAbsoluteTimeGetCurrent();
-                let duration = instant - now;
                 let fsecs =
                     duration.subsec_nanos() as f64 / 1_000_000_000.0 + duration.as_secs() as f64;
                 CFRunLoopTimerSetNextFireDate(self.timer, current + fsecs)
@@ -297,3 +388,51 @@ impl EventLoopWaker {
         }
     }
 }
+
+/// How early the timer fires in the precise waits.
+const PRECISE_WAIT_MARGIN: Duration = Duration::from_millis(1);
+
+#[repr(C)]
+struct MachTimebaseInfo {
+    numer: u32,
+    denom: u32,
+}
+
+extern "C" {
+    fn mach_absolute_time() -> u64;
+    fn mach_timebase_info(info: *mut MachTimebaseInfo) -> c_int;
+    fn mach_wait_until(deadline: u64) -> c_int;
+}
+
+/// Blocks the thread until the `deadline`, with the precision of the mach clock.
+fn wait_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline <= now {
+        return;
+    }
+    let nanos = (deadline - now).as_nanos() as u64;
+    unsafe {
+        let mut timebase = MachTimebaseInfo { numer: 0, denom: 0 };
+        if mach_timebase_info(&mut timebase) != 0 || timebase.numer == 0 {
+            return;
+        }
+        let ticks = nanos * timebase.denom as u64 / timebase.numer as u64;
+        mach_wait_until(mach_absolute_time() + ticks);
+    }
+}
+
+/// The instant of the time of the host in the ticks of the mach clock, as in the timestamps of
+/// the display links.
+pub fn host_time_instant(host_time: u64) -> Instant {
+    let now = Instant::now();
+    unsafe {
+        let mut timebase = MachTimebaseInfo { numer: 0, denom: 0 };
+        if mach_timebase_info(&mut timebase) != 0 || timebase.denom == 0 {
+            return now;
+        }
+        let ticks = mach_absolute_time().saturating_sub(host_time);
+        let nanos = u128::from(ticks) * u128::from(timebase.numer) / u128::from(timebase.denom);
+        now.checked_sub(Duration::from_nanos(nanos as u64))
+            .unwrap_or(now)
+    }
+}
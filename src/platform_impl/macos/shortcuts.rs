@@ -0,0 +1,130 @@
+//! The inhibition of the system shortcuts, which is an event tap of the session handing the keys
+//! to the application ahead of the system while its key window inhibits them.
+
+use std::{os::raw::c_void, ptr, sync::Mutex};
+
+use cocoa::{
+    appkit::NSApp,
+    base::{id, nil, BOOL, NO},
+};
+
+use crate::{
+    error::ExternalError,
+    platform_impl::platform::{
+        ffi,
+        observer::{
+            kCFRunLoopCommonModes, CFMachPortCreateRunLoopSource, CFMachPortInvalidate, CFRelease,
+            CFRunLoopAddSource, CFRunLoopGetMain, CFRunLoopSourceInvalidate, CFRunLoopSourceRef,
+        },
+        OsError,
+    },
+};
+
+struct ShortcutsTap {
+    tap: ffi::CFMachPortRef,
+    source: CFRunLoopSourceRef,
+    /// The windows inhibiting the shortcuts.
+    windows: Vec<usize>,
+}
+
+unsafe impl Send for ShortcutsTap {}
+
+lazy_static! {
+    static ref SHORTCUTS_TAP: Mutex<ShortcutsTap> = Mutex::new(ShortcutsTap {
+        tap: ptr::null_mut(),
+        source: ptr::null_mut(),
+        windows: Vec::new(),
+    });
+}
+
+/// Sets whether the window inhibits the shortcuts, creating the tap along with the first window
+/// and removing it along with the last one.
+pub fn set_shortcuts_inhibited(ns_window: id, inhibited: bool) -> Result<(), ExternalError> {
+    let mut shortcuts_tap = SHORTCUTS_TAP.lock().unwrap();
+    let window = ns_window as usize;
+    let position = shortcuts_tap.windows.iter().position(|&w| w == window);
+    match (inhibited, position) {
+        (true, None) => unsafe {
+            if shortcuts_tap.tap.is_null() {
+                let events = (1 << ffi::kCGEventKeyDown)
+                    | (1 << ffi::kCGEventKeyUp)
+                    | (1 << ffi::kCGEventFlagsChanged);
+                // Fails unless the application is trusted for accessibility.
+                let tap = ffi::CGEventTapCreate(
+                    ffi::kCGSessionEventTap,
+                    ffi::kCGHeadInsertEventTap,
+                    ffi::kCGEventTapOptionDefault,
+                    events,
+                    tap_callback,
+                    ptr::null_mut(),
+                );
+                if tap.is_null() {
+                    return Err(ExternalError::Os(os_error!(OsError::EventTapError(
+                        "Failed to create the event tap, which needs the accessibility permission"
+                    ))));
+                }
+                let source = CFMachPortCreateRunLoopSource(ptr::null_mut(), tap, 0);
+                CFRunLoopAddSource(CFRunLoopGetMain(), source, kCFRunLoopCommonModes);
+                shortcuts_tap.tap = tap;
+                shortcuts_tap.source = source;
+            }
+            shortcuts_tap.windows.push(window);
+        },
+        (false, Some(position)) => {
+            shortcuts_tap.windows.remove(position);
+            if shortcuts_tap.windows.is_empty() {
+                unsafe {
+                    CFMachPortInvalidate(shortcuts_tap.tap);
+                    CFRunLoopSourceInvalidate(shortcuts_tap.source);
+                    CFRelease(shortcuts_tap.source as *const c_void);
+                    CFRelease(shortcuts_tap.tap as *const c_void);
+                }
+                shortcuts_tap.tap = ptr::null_mut();
+                shortcuts_tap.source = ptr::null_mut();
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+unsafe extern "C" fn tap_callback(
+    _: ffi::CGEventTapProxy,
+    event_type: u32,
+    event: ffi::CGEventRef,
+    _: *mut c_void,
+) -> ffi::CGEventRef {
+    if event_type == ffi::kCGEventTapDisabledByTimeout
+        || event_type == ffi::kCGEventTapDisabledByUserInput
+    {
+        // The system turns the tap off when the run loop doesn't answer in time.
+        let shortcuts_tap = SHORTCUTS_TAP.lock().unwrap();
+        if !shortcuts_tap.tap.is_null() {
+            ffi::CGEventTapEnable(shortcuts_tap.tap, true);
+        }
+        return event;
+    }
+
+    let app = NSApp();
+    let active: BOOL = msg_send![app, isActive];
+    let key_window: id = msg_send![app, keyWindow];
+    if active == NO || key_window == nil {
+        return event;
+    }
+    let inhibited = SHORTCUTS_TAP
+        .lock()
+        .unwrap()
+        .windows
+        .contains(&(key_window as usize));
+    if !inhibited {
+        return event;
+    }
+
+    // The application gets the key before the system could take it for a shortcut.
+    let ns_event: id = msg_send![class!(NSEvent), eventWithCGEvent: event];
+    if ns_event == nil {
+        return event;
+    }
+    let _: () = msg_send![app, sendEvent: ns_event];
+    ptr::null_mut()
+}
@@ -6,15 +6,21 @@ use std::{
 use cocoa::{
     appkit::{CGFloat, NSScreen, NSWindow, NSWindowStyleMask},
     base::{id, nil},
-    foundation::{NSPoint, NSSize, NSString},
+    foundation::{NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger},
 };
+#[cfg(feature = "software-present")]
+use core_graphics::image::CGImage;
 use dispatch::Queue;
 use objc::rc::autoreleasepool;
-use objc::runtime::NO;
+use objc::runtime::{BOOL, NO, YES};
 
 use crate::{
-    dpi::LogicalSize,
-    platform_impl::platform::{ffi, util::IdRef, window::SharedState},
+    dpi::{LogicalPosition, LogicalSize},
+    platform_impl::platform::{
+        ffi,
+        util::{self, IdRef},
+        window::SharedState,
+    },
 };
 
 // Unsafe wrapper type that allows us to dispatch things that aren't Send.
@@ -205,6 +211,166 @@ pub unsafe fn set_title_async(ns_window: id, title: String) {
     });
 }
 
+unsafe fn set_visual_effect(ns_view: id, material: Option<NSInteger>) {
+    // The content view draws on its own layer, over the views it contains, so the effect view is
+    // placed behind it in the frame view instead.
+    let frame_view: id = msg_send![ns_view, superview];
+    if frame_view == nil {
+        return;
+    }
+
+    let identifier = util::ns_string_id_ref("WinitVisualEffectView");
+    let subviews: id = msg_send![frame_view, subviews];
+    let count: NSUInteger = msg_send![subviews, count];
+    let effect_view = (0..count)
+        .map(|index| -> id { msg_send![subviews, objectAtIndex: index] })
+        .find(|&view| {
+            let view_identifier: id = msg_send![view, identifier];
+            let is_equal: BOOL = msg_send![view_identifier, isEqualToString: *identifier];
+            view_identifier != nil && is_equal == YES
+        });
+
+    match (material, effect_view) {
+        (Some(material), Some(effect_view)) => {
+            let () = msg_send![effect_view, setMaterial: material];
+        }
+        (Some(material), None) => {
+            let frame: NSRect = msg_send![ns_view, frame];
+            let effect_view: id = msg_send![class!(NSVisualEffectView), alloc];
+            let effect_view: id = msg_send![effect_view, initWithFrame: frame];
+            let () = msg_send![effect_view, setIdentifier: *identifier];
+            // `NSViewWidthSizable | NSViewHeightSizable`
+            let () = msg_send![effect_view, setAutoresizingMask: 18 as NSUInteger];
+            // `NSVisualEffectBlendingModeBehindWindow`
+            let () = msg_send![effect_view, setBlendingMode: 0 as NSInteger];
+            // `NSVisualEffectStateActive`, as the effect would be gone while the window is
+            // inactive otherwise.
+            let () = msg_send![effect_view, setState: 1 as NSInteger];
+            let () = msg_send![effect_view, setMaterial: material];
+            // `NSWindowBelow`
+            let () = msg_send![
+                frame_view,
+                addSubview: effect_view
+                positioned: -1 as NSInteger
+                relativeTo: ns_view
+            ];
+            let () = msg_send![effect_view, release];
+        }
+        (None, Some(effect_view)) => {
+            let () = msg_send![effect_view, removeFromSuperview];
+        }
+        (None, None) => (),
+    }
+}
+
+// The views can only be changed from the main thread.
+pub unsafe fn set_visual_effect_async(ns_view: id, material: Option<NSInteger>) {
+    let ns_view = MainThreadSafe(ns_view);
+    Queue::main().exec_async(move || {
+        set_visual_effect(*ns_view, material);
+    });
+}
+
+#[cfg(feature = "software-present")]
+unsafe fn set_pixels(ns_view: id, image: &CGImage, scale_factor: CGFloat) {
+    let () = msg_send![ns_view, setWantsLayer: YES];
+    let layer: id = msg_send![ns_view, layer];
+
+    let name = util::ns_string_id_ref("WinitPixelsLayer");
+    let sublayers: id = msg_send![layer, sublayers];
+    let count: NSUInteger = if sublayers != nil {
+        msg_send![sublayers, count]
+    } else {
+        0
+    };
+    let pixels_layer = (0..count)
+        .map(|index| -> id { msg_send![sublayers, objectAtIndex: index] })
+        .find(|&sublayer| {
+            let sublayer_name: id = msg_send![sublayer, name];
+            let is_equal: BOOL = msg_send![sublayer_name, isEqualToString: *name];
+            sublayer_name != nil && is_equal == YES
+        });
+
+    let pixels_layer = match pixels_layer {
+        Some(pixels_layer) => pixels_layer,
+        None => {
+            let bounds: NSRect = msg_send![layer, bounds];
+            let pixels_layer: id = msg_send![class!(CALayer), new];
+            let () = msg_send![pixels_layer, setName: *name];
+            let () = msg_send![pixels_layer, setFrame: bounds];
+            // `kCALayerWidthSizable | kCALayerHeightSizable`
+            let () = msg_send![pixels_layer, setAutoresizingMask: 18 as u32];
+            // `kCAGravityTopLeft`, as the pixels aren't scaled to the layer.
+            let gravity = util::ns_string_id_ref("topLeft");
+            let () = msg_send![pixels_layer, setContentsGravity: *gravity];
+            let () = msg_send![layer, addSublayer: pixels_layer];
+            let () = msg_send![pixels_layer, release];
+            pixels_layer
+        }
+    };
+    let () = msg_send![pixels_layer, setContentsScale: scale_factor];
+    let () = msg_send![pixels_layer, setContents: image.as_ptr() as id];
+}
+
+// The layers of the views are only changed from the main thread, where the changes are
+// committed.
+#[cfg(feature = "software-present")]
+pub unsafe fn set_pixels_async(ns_view: id, image: CGImage, scale_factor: CGFloat) {
+    let ns_view = MainThreadSafe(ns_view);
+    let image = MainThreadSafe(image);
+    Queue::main().exec_async(move || {
+        set_pixels(*ns_view, &image, scale_factor);
+    });
+}
+
+// Sets the top-left corner or the size of the frame of the subview, keeping the other.
+pub unsafe fn set_subview_frame(
+    ns_view: id,
+    position: Option<LogicalPosition<f64>>,
+    size: Option<LogicalSize<f64>>,
+) {
+    let superview: id = msg_send![ns_view, superview];
+    if superview == nil {
+        return;
+    }
+    let bounds: NSRect = msg_send![superview, bounds];
+    let frame: NSRect = msg_send![ns_view, frame];
+
+    // The superview isn't flipped, so the frame is positioned from the bottom.
+    let top = bounds.size.height - frame.origin.y - frame.size.height;
+    let (x, top) = position.map_or((frame.origin.x, top), |position| {
+        (position.x as CGFloat, position.y as CGFloat)
+    });
+    let size = size.map_or(frame.size, |size| {
+        NSSize::new(size.width as CGFloat, size.height as CGFloat)
+    });
+    let frame = NSRect::new(
+        NSPoint::new(x, bounds.size.height - top - size.height),
+        size,
+    );
+    let () = msg_send![ns_view, setFrame: frame];
+}
+
+// The views can only be changed from the main thread.
+pub unsafe fn set_subview_frame_async(
+    ns_view: id,
+    position: Option<LogicalPosition<f64>>,
+    size: Option<LogicalSize<f64>>,
+) {
+    let ns_view = MainThreadSafe(ns_view);
+    Queue::main().exec_async(move || {
+        set_subview_frame(*ns_view, position, size);
+    });
+}
+
+// The view is kept alive through the `IdRef` until it's removed.
+pub unsafe fn remove_from_superview_async(ns_view: IdRef) {
+    let ns_view = MainThreadSafe(ns_view);
+    Queue::main().exec_async(move || {
+        let () = msg_send![**ns_view, removeFromSuperview];
+    });
+}
+
 // `close:` is thread-safe, but we want the event to be triggered from the main
 // thread. Though, it's a good idea to look into that more...
 //
@@ -1,17 +1,71 @@
 use cocoa::{
     appkit::NSImage,
     base::{id, nil},
-    foundation::{NSDictionary, NSPoint, NSString},
+    foundation::{NSDictionary, NSInteger, NSPoint, NSSize, NSString},
 };
-use objc::{runtime::Sel, runtime::NO};
-use std::cell::RefCell;
+use objc::{runtime::Sel, runtime::NO, runtime::YES};
+use std::{cell::RefCell, ptr, slice, sync::Arc, time::Duration};
 
-use crate::window::CursorIcon;
+use crate::{
+    dpi::PhysicalPosition,
+    icon::RgbaIcon,
+    window::{CursorFrame, CursorIcon},
+};
+
+use super::IdRef;
 
 pub enum Cursor {
     Native(&'static str),
     Undocumented(&'static str),
     WebKit(&'static str),
+    /// A custom cursor, with the frame shown when it's animated.
+    Custom(CustomCursor, usize),
+}
+
+/// A cursor created from images, released along with its last clone.
+#[derive(Clone)]
+pub struct CustomCursor {
+    /// The cursors of the frames, with their delays.
+    frames: Arc<Vec<(IdRef, Duration)>>,
+}
+
+unsafe impl Send for CustomCursor {}
+unsafe impl Sync for CustomCursor {}
+
+impl CustomCursor {
+    pub fn new(image: &RgbaIcon, hotspot: PhysicalPosition<u32>) -> Self {
+        let frame = unsafe { ns_cursor(image, hotspot) };
+        CustomCursor {
+            frames: Arc::new(vec![(frame, Duration::from_secs(0))]),
+        }
+    }
+
+    pub fn new_animated(frames: &[CursorFrame]) -> Self {
+        let frames = frames
+            .iter()
+            .map(|frame| unsafe { (ns_cursor(&frame.image, frame.hotspot), frame.delay) })
+            .collect();
+        CustomCursor {
+            frames: Arc::new(frames),
+        }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn delay(&self, frame: usize) -> Duration {
+        self.frames[frame].1
+    }
+}
+
+unsafe fn ns_cursor(image: &RgbaIcon, hotspot: PhysicalPosition<u32>) -> IdRef {
+    // The image is sized in points, like the hotspot.
+    let ns_image = image_from_rgba(image, 1.0);
+    let hotspot = NSPoint::new(hotspot.x as f64, hotspot.y as f64);
+    let ns_cursor: id = msg_send![class!(NSCursor), alloc];
+    let ns_cursor: id = msg_send![ns_cursor, initWithImage:ns_image hotSpot:hotspot];
+    IdRef::new(ns_cursor)
 }
 
 impl From<CursorIcon> for Cursor {
@@ -97,10 +151,51 @@ impl Cursor {
                 msg_send![class, performSelector: sel]
             }
             Cursor::WebKit(cursor_name) => load_webkit_cursor(cursor_name),
+            Cursor::Custom(cursor, frame) => *cursor.frames[*frame].0,
         }
     }
 }
 
+/// Creates an autoreleased image showing the pixels, sized in points.
+pub unsafe fn image_from_rgba(image: &RgbaIcon, scale_factor: f64) -> id {
+    let color_space = IdRef::new(NSString::alloc(nil).init_str("NSDeviceRGBColorSpace"));
+    let planes: *mut *mut u8 = ptr::null_mut();
+    let bitmap: id = msg_send![class!(NSBitmapImageRep), alloc];
+    let bitmap: id = msg_send![bitmap,
+        initWithBitmapDataPlanes:planes
+        pixelsWide:image.width as NSInteger
+        pixelsHigh:image.height as NSInteger
+        bitsPerSample:8 as NSInteger
+        samplesPerPixel:4 as NSInteger
+        hasAlpha:YES
+        isPlanar:NO
+        colorSpaceName:*color_space
+        bytesPerRow:(image.width * 4) as NSInteger
+        bitsPerPixel:32 as NSInteger
+    ];
+    let bitmap = IdRef::new(bitmap);
+
+    // The representation expects the alpha to be premultiplied.
+    let data: *mut u8 = msg_send![*bitmap, bitmapData];
+    let pixels = slice::from_raw_parts_mut(data, image.rgba.len());
+    for (pixel, rgba) in pixels.chunks_exact_mut(4).zip(image.rgba.chunks_exact(4)) {
+        let alpha = rgba[3] as u32;
+        pixel[0] = (rgba[0] as u32 * alpha / 255) as u8;
+        pixel[1] = (rgba[1] as u32 * alpha / 255) as u8;
+        pixel[2] = (rgba[2] as u32 * alpha / 255) as u8;
+        pixel[3] = rgba[3];
+    }
+
+    let size = NSSize::new(
+        image.width as f64 / scale_factor,
+        image.height as f64 / scale_factor,
+    );
+    let ns_image: id = msg_send![class!(NSImage), alloc];
+    let ns_image: id = msg_send![ns_image, initWithSize: size];
+    let _: () = msg_send![ns_image, addRepresentation: *bitmap];
+    msg_send![ns_image, autorelease]
+}
+
 // Note that loading `busybutclickable` with this code won't animate the frames;
 // instead you'll just get them all in a column.
 pub unsafe fn load_webkit_cursor(cursor_name: &str) -> id {
@@ -4,12 +4,15 @@ use std::{
     os::raw::*,
     slice, str,
     sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
 };
 
 use cocoa::{
-    appkit::{NSApp, NSEvent, NSEventModifierFlags, NSEventPhase, NSView, NSWindow},
+    appkit::{
+        CGFloat, NSApp, NSEvent, NSEventModifierFlags, NSEventPhase, NSEventType, NSView, NSWindow,
+    },
     base::{id, nil},
-    foundation::{NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger},
+    foundation::{NSArray, NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger},
 };
 use objc::{
     declare::ClassDecl,
@@ -17,10 +20,13 @@ use objc::{
 };
 
 use crate::{
-    dpi::LogicalPosition,
+    clipboard::ClipboardProvider,
+    dnd::{DndAction, DndActions, DragIcon},
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition},
     event::{
-        DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState, MouseButton,
-        MouseScrollDelta, TouchPhase, VirtualKeyCode, WindowEvent,
+        AxisSource, ComposeState, DeviceEvent, ElementState, Event, GesturePhase, ImePreedit,
+        ImePreeditSpan, ImeUnderline, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+        PenEvent, PenPhase, PenTool, TouchPhase, VirtualKeyCode, WindowEvent,
     },
     platform_impl::platform::{
         app_state::AppState,
@@ -29,15 +35,18 @@ use crate::{
             scancode_to_keycode, EventWrapper,
         },
         ffi::*,
+        keymap,
         util::{self, IdRef},
         window::get_window_id,
         DEVICE_ID,
     },
-    window::WindowId,
+    window::{ImePurpose, WindowId},
 };
 
 pub struct CursorState {
     pub visible: bool,
+    /// The cursor is hidden while the pointer is locked.
+    pub locked: bool,
     pub cursor: util::Cursor,
 }
 
@@ -45,6 +54,7 @@ impl Default for CursorState {
     fn default() -> Self {
         Self {
             visible: true,
+            locked: false,
             cursor: Default::default(),
         }
     }
@@ -53,11 +63,33 @@ impl Default for CursorState {
 pub(super) struct ViewState {
     ns_window: id,
     pub cursor_state: Arc<Mutex<CursorState>>,
-    ime_spot: Option<(f64, f64)>,
+    /// The area of the text being edited, in screen coordinates.
+    ime_area: Option<NSRect>,
+    /// The text around the cursor set by the application.
+    surrounding_text: Option<SurroundingText>,
+    /// Whether the text being edited is a password, which turns on the secure keyboard entry
+    /// while the window is key.
+    secure_input: bool,
+    secure_input_enabled: bool,
     raw_characters: Option<String>,
     is_key_down: bool,
+    /// Whether the repeated key presses are sent to the window.
+    key_repeat: bool,
     pub(super) modifiers: ModifiersState,
     tracking_rect: Option<NSInteger>,
+    /// The operations allowed for the drag started from the view.
+    drag_operations: NSUInteger,
+    /// The provider of the data dragged from the view, which is read directly when dropped
+    /// into our windows.
+    drag_provider: Option<Arc<dyn ClipboardProvider>>,
+    /// Whether the data dragged from the view was dropped into one of our windows.
+    drag_dropped_inside: bool,
+    /// The kind of the pen close to the tablet, with its unique id, which only the proximity
+    /// events tell.
+    tablet_tool: (PenTool, u64),
+    /// Whether a pinch and a rotation are in progress, to start the gestures and end them once.
+    pinch_in_progress: bool,
+    rotation_in_progress: bool,
 }
 
 impl ViewState {
@@ -66,17 +98,57 @@ impl ViewState {
     }
 }
 
+/// The text around the cursor, with the cursor and the anchor in bytes.
+struct SurroundingText {
+    text: String,
+    cursor: usize,
+    anchor: usize,
+}
+
+impl SurroundingText {
+    /// The offset in UTF-16 code units, which the ranges of AppKit count, of the byte offset.
+    fn utf16_offset(&self, offset: usize) -> NSUInteger {
+        self.text[..offset].encode_utf16().count() as NSUInteger
+    }
+
+    /// The byte offset of the offset in UTF-16 code units.
+    fn byte_offset(&self, offset: NSUInteger) -> usize {
+        let mut utf16_offset = 0;
+        for (index, c) in self.text.char_indices() {
+            if utf16_offset >= offset as usize {
+                return index;
+            }
+            utf16_offset += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    fn selection(&self) -> (usize, usize) {
+        (self.cursor.min(self.anchor), self.cursor.max(self.anchor))
+    }
+}
+
 pub fn new_view(ns_window: id) -> (IdRef, Weak<Mutex<CursorState>>) {
     let cursor_state = Default::default();
     let cursor_access = Arc::downgrade(&cursor_state);
     let state = ViewState {
         ns_window,
         cursor_state,
-        ime_spot: None,
+        ime_area: None,
+        surrounding_text: None,
+        secure_input: false,
+        secure_input_enabled: false,
         raw_characters: None,
         is_key_down: false,
+        key_repeat: true,
         modifiers: Default::default(),
         tracking_rect: None,
+        drag_operations: NS_DRAG_OPERATION_NONE,
+        drag_provider: None,
+        drag_dropped_inside: false,
+        tablet_tool: (PenTool::Pen, 0),
+        pinch_in_progress: false,
+        rotation_in_progress: false,
     };
     unsafe {
         // This is free'd in `dealloc`
@@ -89,17 +161,219 @@ pub fn new_view(ns_window: id) -> (IdRef, Weak<Mutex<CursorState>>) {
     }
 }
 
-pub unsafe fn set_ime_position(ns_view: id, input_context: id, x: f64, y: f64) {
+// The values of `NSDragOperation`.
+const NS_DRAG_OPERATION_NONE: NSUInteger = 0;
+const NS_DRAG_OPERATION_COPY: NSUInteger = 1;
+const NS_DRAG_OPERATION_LINK: NSUInteger = 2;
+const NS_DRAG_OPERATION_MOVE: NSUInteger = 16;
+
+fn actions_operations(actions: DndActions) -> NSUInteger {
+    let mut operations = NS_DRAG_OPERATION_NONE;
+    if actions.contains(DndActions::COPY) {
+        operations |= NS_DRAG_OPERATION_COPY;
+    }
+    if actions.contains(DndActions::MOVE) {
+        operations |= NS_DRAG_OPERATION_MOVE;
+    }
+    if actions.contains(DndActions::LINK) {
+        operations |= NS_DRAG_OPERATION_LINK;
+    }
+    operations
+}
+
+fn operation_action(operation: NSUInteger) -> Option<DndAction> {
+    if operation & NS_DRAG_OPERATION_MOVE != 0 {
+        Some(DndAction::Move)
+    } else if operation & NS_DRAG_OPERATION_COPY != 0 {
+        Some(DndAction::Copy)
+    } else if operation & NS_DRAG_OPERATION_LINK != 0 {
+        Some(DndAction::Link)
+    } else {
+        None
+    }
+}
+
+/// Starts dragging the pasteboard item from the view at the location of the current mouse
+/// event.
+///
+/// Returns `false` if there's no mouse event to start the drag with.
+pub unsafe fn start_drag(
+    ns_view: id,
+    item: id,
+    provider: Arc<dyn ClipboardProvider>,
+    icon: Option<&DragIcon>,
+    actions: DndActions,
+) -> bool {
+    let event: id = msg_send![NSApp(), currentEvent];
+    if event == nil {
+        return false;
+    }
+    let event_type = event.eventType();
+    let is_mouse_event = matches!(
+        event_type,
+        NSEventType::NSLeftMouseDown
+            | NSEventType::NSLeftMouseDragged
+            | NSEventType::NSRightMouseDown
+            | NSEventType::NSRightMouseDragged
+            | NSEventType::NSOtherMouseDown
+            | NSEventType::NSOtherMouseDragged
+    );
+    if !is_mouse_event {
+        return false;
+    }
+
+    let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("winitState");
+    let state = &mut *(state_ptr as *mut ViewState);
+    state.drag_operations = actions_operations(actions);
+    state.drag_provider = Some(provider);
+    state.drag_dropped_inside = false;
+
+    // The dragging frame is required even without an icon, it's sized after the icon otherwise.
+    let location = ns_view.convertPoint_fromView_(event.locationInWindow(), nil);
+    let (frame, contents) = match icon {
+        Some(icon) => {
+            let scale_factor = state.get_scale_factor();
+            let contents = util::image_from_rgba(&icon.image, scale_factor);
+            let size = NSSize::new(
+                icon.image.width as f64 / scale_factor,
+                icon.image.height as f64 / scale_factor,
+            );
+            // The view isn't flipped, so the frame starts at the bottom left corner of the
+            // icon, while the hotspot is relative to the top left one.
+            let origin = NSPoint::new(
+                location.x - icon.hotspot.x as f64 / scale_factor,
+                location.y + icon.hotspot.y as f64 / scale_factor - size.height,
+            );
+            (NSRect::new(origin, size), contents)
+        }
+        None => (NSRect::new(location, NSSize::new(1.0, 1.0)), nil),
+    };
+    let dragging_item: id = msg_send![class!(NSDraggingItem), alloc];
+    let dragging_item: id = msg_send![dragging_item, initWithPasteboardWriter: item];
+    let dragging_item: id = msg_send![dragging_item, autorelease];
+    let _: () = msg_send![dragging_item, setDraggingFrame:frame contents:contents];
+
+    let items = NSArray::arrayWithObject(nil, dragging_item);
+    let session: id = msg_send![ns_view,
+        beginDraggingSessionWithItems:items
+        event:event
+        source:ns_view
+    ];
+    if session == nil {
+        state.drag_provider = None;
+    }
+    session != nil
+}
+
+/// The state of the view the data is dragged from, if the source of the drag is one of our
+/// views.
+unsafe fn source_state<'a>(source: id) -> Option<&'a mut ViewState> {
+    if source == nil {
+        return None;
+    }
+    let is_view: BOOL = msg_send![source, isKindOfClass: VIEW_CLASS.0];
+    if is_view == NO {
+        return None;
+    }
+
+    let state_ptr: *mut c_void = *(*source).get_ivar("winitState");
+    Some(&mut *(state_ptr as *mut ViewState))
+}
+
+/// The window the data is dragged from along with the provider of the data, if the source of
+/// the drag is one of our views.
+pub unsafe fn local_drag(source: id) -> Option<(WindowId, Arc<dyn ClipboardProvider>)> {
+    let state = source_state(source)?;
+    let provider = state.drag_provider.clone()?;
+    Some((WindowId(get_window_id(state.ns_window)), provider))
+}
+
+/// Remembers that the data was dropped into one of our windows, if the source of the drag is
+/// one of our views.
+pub unsafe fn set_dropped_inside(source: id) {
+    if let Some(state) = source_state(source) {
+        state.drag_dropped_inside = true;
+    }
+}
+
+pub unsafe fn set_ime_cursor_area(
+    ns_view: id,
+    input_context: id,
+    position: LogicalPosition<f64>,
+    size: LogicalSize<f64>,
+) {
     let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("winitState");
     let state = &mut *(state_ptr as *mut ViewState);
     let content_rect =
         NSWindow::contentRectForFrameRect_(state.ns_window, NSWindow::frame(state.ns_window));
     let base_x = content_rect.origin.x as f64;
     let base_y = (content_rect.origin.y + content_rect.size.height) as f64;
-    state.ime_spot = Some((base_x + x, base_y - y));
+    // The origin of the rectangles of the screen is at their bottom left.
+    state.ime_area = Some(NSRect::new(
+        NSPoint::new(base_x + position.x, base_y - position.y - size.height),
+        NSSize::new(size.width, size.height),
+    ));
     let _: () = msg_send![input_context, invalidateCharacterCoordinates];
 }
 
+pub unsafe fn set_ime_surrounding_text(ns_view: id, text: String, cursor: usize, anchor: usize) {
+    let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("winitState");
+    let state = &mut *(state_ptr as *mut ViewState);
+    let floor = |mut offset: usize| {
+        offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    };
+    let (cursor, anchor) = (floor(cursor), floor(anchor));
+    state.surrounding_text = Some(SurroundingText {
+        text,
+        cursor,
+        anchor,
+    });
+}
+
+pub unsafe fn set_ime_purpose(ns_view: id, input_context: id, purpose: ImePurpose) {
+    let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("winitState");
+    let state = &mut *(state_ptr as *mut ViewState);
+    state.secure_input = match purpose {
+        ImePurpose::Password | ImePurpose::Pin => true,
+        _ => false,
+    };
+    // The input methods can't see the passwords, so only the Roman input sources are allowed.
+    let locales = if state.secure_input {
+        NSArray::arrayWithObject(nil, NSAllRomanInputSourcesLocaleIdentifier)
+    } else {
+        nil
+    };
+    let _: () = msg_send![input_context, setAllowedInputSourceLocales: locales];
+    let is_key: BOOL = msg_send![state.ns_window, isKeyWindow];
+    update_secure_input(ns_view, is_key == YES);
+}
+
+/// Turns the secure keyboard entry on while the window is key and edits a password, as it keeps
+/// the other applications from reading the keys.
+pub unsafe fn update_secure_input(ns_view: id, is_key: bool) {
+    let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("winitState");
+    let state = &mut *(state_ptr as *mut ViewState);
+    let enabled = state.secure_input && is_key;
+    if enabled != state.secure_input_enabled {
+        state.secure_input_enabled = enabled;
+        if enabled {
+            EnableSecureEventInput();
+        } else {
+            DisableSecureEventInput();
+        }
+    }
+}
+
+pub unsafe fn set_key_repeat(ns_view: id, key_repeat: bool) {
+    let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("winitState");
+    let state = &mut *(state_ptr as *mut ViewState);
+    state.key_repeat = key_repeat;
+}
+
 struct ViewClass(*const Class);
 unsafe impl Send for ViewClass {}
 unsafe impl Sync for ViewClass {}
@@ -239,10 +513,26 @@ lazy_static! {
             sel!(scrollWheel:),
             scroll_wheel as extern "C" fn(&Object, Sel, id),
         );
+        decl.add_method(
+            sel!(tabletProximity:),
+            tablet_proximity as extern "C" fn(&Object, Sel, id),
+        );
         decl.add_method(
             sel!(pressureChangeWithEvent:),
             pressure_change_with_event as extern "C" fn(&Object, Sel, id),
         );
+        decl.add_method(
+            sel!(magnifyWithEvent:),
+            magnify_with_event as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(rotateWithEvent:),
+            rotate_with_event as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(swipeWithEvent:),
+            swipe_with_event as extern "C" fn(&Object, Sel, id),
+        );
         decl.add_method(
             sel!(_wantsKeyDownForEvent:),
             wants_key_down_for_event as extern "C" fn(&Object, Sel, id) -> BOOL,
@@ -255,14 +545,33 @@ lazy_static! {
             sel!(frameDidChange:),
             frame_did_change as extern "C" fn(&Object, Sel, id),
         );
+        decl.add_method(
+            sel!(keyboardSelectionDidChange:),
+            keyboard_selection_did_change as extern "C" fn(&Object, Sel, id),
+        );
         decl.add_method(
             sel!(acceptsFirstMouse:),
             accepts_first_mouse as extern "C" fn(&Object, Sel, id) -> BOOL,
         );
+        decl.add_method(
+            sel!(draggingSession:sourceOperationMaskForDraggingContext:),
+            dragging_session_source_operation_mask
+                as extern "C" fn(&Object, Sel, id, NSInteger) -> NSUInteger,
+        );
+        decl.add_method(
+            sel!(draggingSession:movedToPoint:),
+            dragging_session_moved as extern "C" fn(&Object, Sel, id, NSPoint),
+        );
+        decl.add_method(
+            sel!(draggingSession:endedAtPoint:operation:),
+            dragging_session_ended as extern "C" fn(&Object, Sel, id, NSPoint, NSUInteger),
+        );
         decl.add_ivar::<*mut c_void>("winitState");
         decl.add_ivar::<id>("markedText");
         let protocol = Protocol::get("NSTextInputClient").unwrap();
         decl.add_protocol(&protocol);
+        let protocol = Protocol::get("NSDraggingSource").unwrap();
+        decl.add_protocol(&protocol);
         ViewClass(decl.register())
     };
 }
@@ -272,7 +581,13 @@ extern "C" fn dealloc(this: &Object, _sel: Sel) {
         let state: *mut c_void = *this.get_ivar("winitState");
         let marked_text: id = *this.get_ivar("markedText");
         let _: () = msg_send![marked_text, release];
-        Box::from_raw(state as *mut ViewState);
+        let notification_center: &Object = msg_send![class!(NSNotificationCenter), defaultCenter];
+        let _: () = msg_send![notification_center, removeObserver: this];
+        let state = Box::from_raw(state as *mut ViewState);
+        // The secure keyboard entry is counted, and stays on as long as it isn't turned off.
+        if state.secure_input_enabled {
+            DisableSecureEventInput();
+        }
     }
 }
 
@@ -297,6 +612,19 @@ extern "C" fn init_with_winit(this: &Object, _sel: Sel, state: *mut c_void) -> i
                 name: notification_name
                 object: this
             ];
+
+            // Posted by the input context of whichever view has the keyboard focus.
+            let notification_name = IdRef::new(
+                NSString::alloc(nil)
+                    .init_str("NSTextInputContextKeyboardSelectionDidChangeNotification"),
+            );
+            let _: () = msg_send![
+                notification_center,
+                addObserver: this
+                selector: sel!(keyboardSelectionDidChange:)
+                name: notification_name
+                object: nil
+            ];
         }
         this
     }
@@ -345,6 +673,20 @@ extern "C" fn frame_did_change(this: &Object, _sel: Sel, _event: id) {
     }
 }
 
+extern "C" fn keyboard_selection_did_change(this: &Object, _sel: Sel, _notification: id) {
+    trace!("Triggered `keyboardSelectionDidChange`");
+    unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
+        AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+            window_id: WindowId(get_window_id(state.ns_window)),
+            event: WindowEvent::KeyboardLayoutChanged(keymap::current_keyboard_layout()),
+        }));
+    }
+    trace!("Completed `keyboardSelectionDidChange`");
+}
+
 extern "C" fn draw_rect(this: &Object, _sel: Sel, rect: NSRect) {
     unsafe {
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
@@ -375,7 +717,7 @@ extern "C" fn reset_cursor_rects(this: &Object, _sel: Sel) {
 
         let bounds: NSRect = msg_send![this, bounds];
         let cursor_state = state.cursor_state.lock().unwrap();
-        let cursor = if cursor_state.visible {
+        let cursor = if cursor_state.visible && !cursor_state.locked {
             cursor_state.cursor.load()
         } else {
             util::invisible_cursor()
@@ -410,22 +752,38 @@ extern "C" fn marked_range(this: &Object, _sel: Sel) -> NSRange {
     }
 }
 
-extern "C" fn selected_range(_this: &Object, _sel: Sel) -> NSRange {
+extern "C" fn selected_range(this: &Object, _sel: Sel) -> NSRange {
     trace!("Triggered `selectedRange`");
+    let range = unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &*(state_ptr as *const ViewState);
+        match state.surrounding_text {
+            Some(ref surrounding_text) => {
+                let (start, end) = surrounding_text.selection();
+                let start = surrounding_text.utf16_offset(start);
+                NSRange::new(start, surrounding_text.utf16_offset(end) - start)
+            }
+            None => util::EMPTY_RANGE,
+        }
+    };
     trace!("Completed `selectedRange`");
-    util::EMPTY_RANGE
+    range
 }
 
 extern "C" fn set_marked_text(
     this: &mut Object,
     _sel: Sel,
     string: id,
-    _selected_range: NSRange,
+    selected_range: NSRange,
     _replacement_range: NSRange,
 ) {
     trace!("Triggered `setMarkedText`");
     unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
         let marked_text_ref: &mut id = this.get_mut_ivar("markedText");
+        let had_marked_text = (*marked_text_ref).length() > 0;
         let _: () = msg_send![(*marked_text_ref), release];
         let marked_text = NSMutableAttributedString::alloc(nil);
         let has_attr = msg_send![string, isKindOfClass: class!(NSAttributedString)];
@@ -435,6 +793,30 @@ extern "C" fn set_marked_text(
             marked_text.initWithString(string);
         };
         *marked_text_ref = marked_text;
+
+        // The dead keys are marked with their spacing character until the next key.
+        let text = input_string(string);
+        let compose_state = if !text.is_empty() {
+            Some(ComposeState::Pending(text))
+        } else if had_marked_text {
+            Some(ComposeState::Cancelled)
+        } else {
+            None
+        };
+        if let Some(compose_state) = compose_state {
+            AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+                window_id: WindowId(get_window_id(state.ns_window)),
+                event: WindowEvent::ComposeChanged(compose_state),
+            }));
+        }
+
+        let preedit = marked_text_preedit(string, selected_range);
+        if !preedit.text.is_empty() || had_marked_text {
+            AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+                window_id: WindowId(get_window_id(state.ns_window)),
+                event: WindowEvent::ImePreedit(preedit),
+            }));
+        }
     }
     trace!("Completed `setMarkedText`");
 }
@@ -442,7 +824,21 @@ extern "C" fn set_marked_text(
 extern "C" fn unmark_text(this: &Object, _sel: Sel) {
     trace!("Triggered `unmarkText`");
     unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
         let marked_text: id = *this.get_ivar("markedText");
+        if marked_text.length() > 0 {
+            // The marked text is discarded, rather than inserted.
+            AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+                window_id: WindowId(get_window_id(state.ns_window)),
+                event: WindowEvent::ComposeChanged(ComposeState::Cancelled),
+            }));
+            AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+                window_id: WindowId(get_window_id(state.ns_window)),
+                event: WindowEvent::ImePreedit(ImePreedit::default()),
+            }));
+        }
         let mutable_string = marked_text.mutableString();
         let _: () = msg_send![mutable_string, setString:""];
         let input_context: id = msg_send![this, inputContext];
@@ -451,6 +847,79 @@ extern "C" fn unmark_text(this: &Object, _sel: Sel) {
     trace!("Completed `unmarkText`");
 }
 
+/// The preedit of the marked text, following the underlines the input method gives to its
+/// clauses, the selected range being the clause being converted.
+unsafe fn marked_text_preedit(string: id, selected_range: NSRange) -> ImePreedit {
+    let text = input_string(string);
+    // The ranges of AppKit count the UTF-16 code units.
+    let mut utf16_to_byte = Vec::with_capacity(text.len() + 1);
+    for (index, c) in text.char_indices() {
+        for _ in 0..c.len_utf16() {
+            utf16_to_byte.push(index);
+        }
+    }
+    utf16_to_byte.push(text.len());
+    let length = utf16_to_byte.len() - 1;
+    let byte = |index: usize| utf16_to_byte[index.min(length)];
+
+    let location = selected_range.location as usize;
+    let selected = byte(location)..byte(location.saturating_add(selected_range.length as usize));
+    let has_attr: BOOL = msg_send![string, isKindOfClass: class!(NSAttributedString)];
+    let mut spans = Vec::new();
+    let mut index = 0;
+    while index < length {
+        let mut range = util::EMPTY_RANGE;
+        let underline = if has_attr == YES {
+            let value: id = msg_send![
+                string,
+                attribute: NSUnderlineStyleAttributeName
+                atIndex: index as NSUInteger
+                effectiveRange: &mut range
+            ];
+            if value != nil {
+                let style: NSInteger = msg_send![value, integerValue];
+                underline_style(style)
+            } else {
+                ImeUnderline::Single
+            }
+        } else {
+            ImeUnderline::Single
+        };
+        let end = if range.length > 0 {
+            (range.location as usize + range.length as usize).min(length)
+        } else {
+            length
+        };
+        let span = byte(index)..byte(end);
+        spans.push(ImePreeditSpan {
+            highlighted: !selected.is_empty()
+                && span.start >= selected.start
+                && span.end <= selected.end,
+            range: span,
+            underline,
+        });
+        index = end.max(index + 1);
+    }
+
+    ImePreedit {
+        text,
+        cursor: Some(selected),
+        spans,
+    }
+}
+
+fn underline_style(style: NSInteger) -> ImeUnderline {
+    if style == NSUnderlineStyleNone {
+        ImeUnderline::None
+    } else if style & NSUnderlinePatternDot != 0 {
+        ImeUnderline::Dotted
+    } else if style & 0xff == NSUnderlineStyleThick || style & 0xff == NSUnderlineStyleDouble {
+        ImeUnderline::Thick
+    } else {
+        ImeUnderline::Single
+    }
+}
+
 extern "C" fn valid_attributes_for_marked_text(_this: &Object, _sel: Sel) -> id {
     trace!("Triggered `validAttributesForMarkedText`");
     trace!("Completed `validAttributesForMarkedText`");
@@ -458,14 +927,36 @@ extern "C" fn valid_attributes_for_marked_text(_this: &Object, _sel: Sel) -> id
 }
 
 extern "C" fn attributed_substring_for_proposed_range(
-    _this: &Object,
+    this: &Object,
     _sel: Sel,
-    _range: NSRange,
-    _actual_range: *mut c_void, // *mut NSRange
+    range: NSRange,
+    actual_range: *mut c_void, // *mut NSRange
 ) -> id {
     trace!("Triggered `attributedSubstringForProposedRange`");
+    let substring = unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &*(state_ptr as *const ViewState);
+        match state.surrounding_text {
+            Some(ref surrounding_text) => {
+                // The range is clamped to the text the application gave.
+                let start = surrounding_text.byte_offset(range.location);
+                let end = surrounding_text.byte_offset(range.location.saturating_add(range.length));
+                if !actual_range.is_null() {
+                    let location = surrounding_text.utf16_offset(start);
+                    *(actual_range as *mut NSRange) =
+                        NSRange::new(location, surrounding_text.utf16_offset(end) - location);
+                }
+                let string = NSString::alloc(nil).init_str(&surrounding_text.text[start..end]);
+                let substring: id = msg_send![class!(NSAttributedString), alloc];
+                let substring: id = msg_send![substring, initWithString: string];
+                let _: () = msg_send![string, release];
+                msg_send![substring, autorelease]
+            }
+            None => nil,
+        }
+    };
     trace!("Completed `attributedSubstringForProposedRange`");
-    nil
+    substring
 }
 
 extern "C" fn character_index_for_point(_this: &Object, _sel: Sel, _point: NSPoint) -> NSUInteger {
@@ -484,44 +975,75 @@ extern "C" fn first_rect_for_character_range(
         trace!("Triggered `firstRectForCharacterRange`");
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
         let state = &mut *(state_ptr as *mut ViewState);
-        let (x, y) = state.ime_spot.unwrap_or_else(|| {
+        let rect = state.ime_area.unwrap_or_else(|| {
             let content_rect = NSWindow::contentRectForFrameRect_(
                 state.ns_window,
                 NSWindow::frame(state.ns_window),
             );
             let x = content_rect.origin.x;
             let y = util::bottom_left_to_top_left(content_rect);
-            (x, y)
+            NSRect::new(NSPoint::new(x as _, y as _), NSSize::new(0.0, 0.0))
         });
         trace!("Completed `firstRectForCharacterRange`");
-        NSRect::new(NSPoint::new(x as _, y as _), NSSize::new(0.0, 0.0))
+        rect
     }
 }
 
-extern "C" fn insert_text(this: &Object, _sel: Sel, string: id, _replacement_range: NSRange) {
+extern "C" fn insert_text(this: &Object, _sel: Sel, string: id, replacement_range: NSRange) {
     trace!("Triggered `insertText`");
     unsafe {
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
         let state = &mut *(state_ptr as *mut ViewState);
 
-        let has_attr = msg_send![string, isKindOfClass: class!(NSAttributedString)];
-        let characters = if has_attr {
-            // This is a *mut NSAttributedString
-            msg_send![string, string]
-        } else {
-            // This is already a *mut NSString
-            string
-        };
-
-        let slice =
-            slice::from_raw_parts(characters.UTF8String() as *const c_uchar, characters.len());
-        let string = str::from_utf8_unchecked(slice);
+        let string = input_string(string);
         state.is_key_down = true;
 
         // We don't need this now, but it's here if that changes.
         //let event: id = msg_send![NSApp(), currentEvent];
 
-        let mut events = VecDeque::with_capacity(characters.len());
+        let mut events = VecDeque::with_capacity(string.len() + 2);
+
+        // Inserting the text ends the sequence of the marked text.
+        let marked_text: id = *this.get_ivar("markedText");
+        if marked_text.length() > 0 {
+            let mutable_string = marked_text.mutableString();
+            let _: () = msg_send![mutable_string, setString:""];
+            events.push_back(EventWrapper::StaticEvent(Event::WindowEvent {
+                window_id: WindowId(get_window_id(state.ns_window)),
+                event: WindowEvent::ComposeChanged(ComposeState::Committed(string.clone())),
+            }));
+            events.push_back(EventWrapper::StaticEvent(Event::WindowEvent {
+                window_id: WindowId(get_window_id(state.ns_window)),
+                event: WindowEvent::ImePreedit(ImePreedit::default()),
+            }));
+        }
+
+        // The text replaces the range of the surrounding text, which has to contain the
+        // selection to be deleted around it.
+        if let Some(ref surrounding_text) = state.surrounding_text {
+            if replacement_range.location != NSNotFound as NSUInteger {
+                let start = surrounding_text.byte_offset(replacement_range.location);
+                let end = surrounding_text.byte_offset(
+                    replacement_range
+                        .location
+                        .saturating_add(replacement_range.length),
+                );
+                let (selection_start, selection_end) = surrounding_text.selection();
+                if start <= selection_start
+                    && end >= selection_end
+                    && (start, end) != (selection_start, selection_end)
+                {
+                    events.push_back(EventWrapper::StaticEvent(Event::WindowEvent {
+                        window_id: WindowId(get_window_id(state.ns_window)),
+                        event: WindowEvent::ImeDeleteSurrounding {
+                            before_length: selection_start - start,
+                            after_length: end - selection_end,
+                        },
+                    }));
+                }
+            }
+        }
+
         for character in string.chars().filter(|c| !is_corporate_character(*c)) {
             events.push_back(EventWrapper::StaticEvent(Event::WindowEvent {
                 window_id: WindowId(get_window_id(state.ns_window)),
@@ -534,6 +1056,21 @@ extern "C" fn insert_text(this: &Object, _sel: Sel, string: id, _replacement_ran
     trace!("Completed `insertText`");
 }
 
+/// The characters of a `NSString`, or of the string of a `NSAttributedString`.
+unsafe fn input_string(string: id) -> String {
+    let has_attr = msg_send![string, isKindOfClass: class!(NSAttributedString)];
+    let characters = if has_attr {
+        // This is a *mut NSAttributedString
+        msg_send![string, string]
+    } else {
+        // This is already a *mut NSString
+        string
+    };
+
+    let slice = slice::from_raw_parts(characters.UTF8String() as *const c_uchar, characters.len());
+    str::from_utf8_unchecked(slice).to_owned()
+}
+
 extern "C" fn do_command_by_selector(this: &Object, _sel: Sel, command: Sel) {
     trace!("Triggered `doCommandBySelector`");
     // Basically, we're sent this message whenever a keyboard event that doesn't generate a "human readable" character
@@ -637,6 +1174,13 @@ fn update_potentially_stale_modifiers(state: &mut ViewState, event: id) {
             window_id: WindowId(get_window_id(state.ns_window)),
             event: WindowEvent::ModifiersChanged(state.modifiers),
         }));
+        AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+            window_id: WindowId(get_window_id(state.ns_window)),
+            event: WindowEvent::DeviceModifiersChanged {
+                device_id: DEVICE_ID,
+                modifiers: state.modifiers,
+            },
+        }));
     }
 }
 
@@ -654,6 +1198,9 @@ extern "C" fn key_down(this: &Object, _sel: Sel, event: id) {
         let virtual_keycode = retrieve_keycode(event);
 
         let is_repeat = msg_send![event, isARepeat];
+        if is_repeat && !state.key_repeat {
+            return;
+        }
 
         update_potentially_stale_modifiers(state, event);
 
@@ -664,6 +1211,7 @@ extern "C" fn key_down(this: &Object, _sel: Sel, event: id) {
                 device_id: DEVICE_ID,
                 input: KeyboardInput {
                     state: ElementState::Pressed,
+                    repeat: is_repeat,
                     scancode,
                     virtual_keycode,
                     modifiers: event_mods(event),
@@ -719,6 +1267,7 @@ extern "C" fn key_up(this: &Object, _sel: Sel, event: id) {
                 device_id: DEVICE_ID,
                 input: KeyboardInput {
                     state: ElementState::Released,
+                    repeat: false,
                     scancode,
                     virtual_keycode,
                     modifiers: event_mods(event),
@@ -789,6 +1338,13 @@ extern "C" fn flags_changed(this: &Object, _sel: Sel, event: id) {
             window_id,
             event: WindowEvent::ModifiersChanged(state.modifiers),
         }));
+        AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+            window_id,
+            event: WindowEvent::DeviceModifiersChanged {
+                device_id: DEVICE_ID,
+                modifiers: state.modifiers,
+            },
+        }));
     }
     trace!("Completed `flagsChanged`");
 }
@@ -838,6 +1394,7 @@ extern "C" fn cancel_operation(this: &Object, _sel: Sel, _sender: id) {
                 device_id: DEVICE_ID,
                 input: KeyboardInput {
                     state: ElementState::Pressed,
+                    repeat: false,
                     scancode: scancode as _,
                     virtual_keycode,
                     modifiers: event_mods(event),
@@ -875,11 +1432,13 @@ fn mouse_click(this: &Object, event: id, button: MouseButton, button_state: Elem
 extern "C" fn mouse_down(this: &Object, _sel: Sel, event: id) {
     mouse_motion(this, event);
     mouse_click(this, event, MouseButton::Left, ElementState::Pressed);
+    tablet_point(this, event, PenPhase::Down);
 }
 
 extern "C" fn mouse_up(this: &Object, _sel: Sel, event: id) {
     mouse_motion(this, event);
     mouse_click(this, event, MouseButton::Left, ElementState::Released);
+    tablet_point(this, event, PenPhase::Up);
 }
 
 extern "C" fn right_mouse_down(this: &Object, _sel: Sel, event: id) {
@@ -894,12 +1453,33 @@ extern "C" fn right_mouse_up(this: &Object, _sel: Sel, event: id) {
 
 extern "C" fn other_mouse_down(this: &Object, _sel: Sel, event: id) {
     mouse_motion(this, event);
-    mouse_click(this, event, MouseButton::Middle, ElementState::Pressed);
+    mouse_click(
+        this,
+        event,
+        other_mouse_button(event),
+        ElementState::Pressed,
+    );
 }
 
 extern "C" fn other_mouse_up(this: &Object, _sel: Sel, event: id) {
     mouse_motion(this, event);
-    mouse_click(this, event, MouseButton::Middle, ElementState::Released);
+    mouse_click(
+        this,
+        event,
+        other_mouse_button(event),
+        ElementState::Released,
+    );
+}
+
+/// The button of the events of the buttons other than the left and right ones, which count from 0.
+fn other_mouse_button(event: id) -> MouseButton {
+    let button_number: NSInteger = unsafe { msg_send![event, buttonNumber] };
+    match button_number {
+        3 => MouseButton::Back,
+        4 => MouseButton::Forward,
+        n if n >= 5 => MouseButton::Other((n + 1) as u16),
+        _ => MouseButton::Middle,
+    }
 }
 
 fn mouse_motion(this: &Object, event: id) {
@@ -947,10 +1527,12 @@ fn mouse_motion(this: &Object, event: id) {
 
 extern "C" fn mouse_moved(this: &Object, _sel: Sel, event: id) {
     mouse_motion(this, event);
+    tablet_point(this, event, PenPhase::Moved);
 }
 
 extern "C" fn mouse_dragged(this: &Object, _sel: Sel, event: id) {
     mouse_motion(this, event);
+    tablet_point(this, event, PenPhase::Moved);
 }
 
 extern "C" fn right_mouse_dragged(this: &Object, _sel: Sel, event: id) {
@@ -961,7 +1543,36 @@ extern "C" fn other_mouse_dragged(this: &Object, _sel: Sel, event: id) {
     mouse_motion(this, event);
 }
 
-extern "C" fn mouse_entered(this: &Object, _sel: Sel, _event: id) {
+/// The position of the mouse of the event in the view, whose origin is at the top.
+unsafe fn view_position(this: &Object, event: id, scale_factor: f64) -> PhysicalPosition<f64> {
+    window_point_position(this, event.locationInWindow(), scale_factor)
+}
+
+/// The instant of the timestamp of the event, which is in seconds since the system started.
+unsafe fn event_instant(event: id) -> Instant {
+    let now = Instant::now();
+    let timestamp = event.timestamp();
+    let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+    let uptime: f64 = msg_send![process_info, systemUptime];
+    let elapsed = Duration::from_secs_f64((uptime - timestamp).max(0.0));
+    now.checked_sub(elapsed).unwrap_or(now)
+}
+
+/// The position in the view of the point in the window.
+unsafe fn window_point_position(
+    this: &Object,
+    window_point: NSPoint,
+    scale_factor: f64,
+) -> PhysicalPosition<f64> {
+    let view: id = this as *const _ as *mut _;
+    let view_point = view.convertPoint_fromView_(window_point, nil);
+    let view_rect = NSView::frame(view);
+    let x = view_point.x as f64;
+    let y = view_rect.size.height as f64 - view_point.y as f64;
+    LogicalPosition::new(x, y).to_physical(scale_factor)
+}
+
+extern "C" fn mouse_entered(this: &Object, _sel: Sel, event: id) {
     trace!("Triggered `mouseEntered`");
     unsafe {
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
@@ -971,6 +1582,7 @@ extern "C" fn mouse_entered(this: &Object, _sel: Sel, _event: id) {
             window_id: WindowId(get_window_id(state.ns_window)),
             event: WindowEvent::CursorEntered {
                 device_id: DEVICE_ID,
+                position: view_position(this, event, state.get_scale_factor()),
             },
         };
 
@@ -979,7 +1591,7 @@ extern "C" fn mouse_entered(this: &Object, _sel: Sel, _event: id) {
     trace!("Completed `mouseEntered`");
 }
 
-extern "C" fn mouse_exited(this: &Object, _sel: Sel, _event: id) {
+extern "C" fn mouse_exited(this: &Object, _sel: Sel, event: id) {
     trace!("Triggered `mouseExited`");
     unsafe {
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
@@ -989,6 +1601,7 @@ extern "C" fn mouse_exited(this: &Object, _sel: Sel, _event: id) {
             window_id: WindowId(get_window_id(state.ns_window)),
             event: WindowEvent::CursorLeft {
                 device_id: DEVICE_ID,
+                position: view_position(this, event, state.get_scale_factor()),
             },
         };
 
@@ -1006,14 +1619,17 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
         let state = &mut *(state_ptr as *mut ViewState);
 
-        let delta = {
+        let (delta, source) = {
             // macOS horizontal sign convention is the inverse of winit.
             let (x, y) = (event.scrollingDeltaX() * -1.0, event.scrollingDeltaY());
             if event.hasPreciseScrollingDeltas() == YES {
                 let delta = LogicalPosition::new(x, y).to_physical(state.get_scale_factor());
-                MouseScrollDelta::PixelDelta(delta)
+                (MouseScrollDelta::PixelDelta(delta), AxisSource::Finger)
             } else {
-                MouseScrollDelta::LineDelta(x as f32, y as f32)
+                (
+                    MouseScrollDelta::LineDelta(x as f32, y as f32),
+                    AxisSource::Wheel,
+                )
             }
         };
         let phase = match event.phase() {
@@ -1040,6 +1656,7 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
                 device_id: DEVICE_ID,
                 delta,
                 phase,
+                source: Some(source),
                 modifiers: event_mods(event),
             },
         };
@@ -1061,6 +1678,7 @@ extern "C" fn pressure_change_with_event(this: &Object, _sel: Sel, event: id) {
 
         let pressure = event.pressure();
         let stage = event.stage();
+        let stage_transition: CGFloat = msg_send![event, stageTransition];
 
         let window_event = Event::WindowEvent {
             window_id: WindowId(get_window_id(state.ns_window)),
@@ -1068,6 +1686,7 @@ extern "C" fn pressure_change_with_event(this: &Object, _sel: Sel, event: id) {
                 device_id: DEVICE_ID,
                 pressure,
                 stage,
+                stage_transition: stage_transition as f32,
             },
         };
 
@@ -1076,6 +1695,214 @@ extern "C" fn pressure_change_with_event(this: &Object, _sel: Sel, event: id) {
     trace!("Completed `pressureChangeWithEvent`");
 }
 
+/// Sends the event of the pen when the mouse event comes from a tablet.
+fn tablet_point(this: &Object, event: id, phase: PenPhase) {
+    unsafe {
+        let subtype: i16 = msg_send![event, subtype];
+        if subtype != NSTabletPointEventSubtype {
+            return;
+        }
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
+        // The tilt goes from -1.0 to 1.0, with the y axis pointing up.
+        let tilt: NSPoint = msg_send![event, tilt];
+        let rotation: f32 = msg_send![event, rotation];
+        let buttons: NSUInteger = msg_send![event, buttonMask];
+        let (tool, tool_id) = state.tablet_tool;
+
+        let window_event = Event::WindowEvent {
+            window_id: WindowId(get_window_id(state.ns_window)),
+            event: WindowEvent::Pen(PenEvent {
+                device_id: DEVICE_ID,
+                phase,
+                location: view_position(this, event, state.get_scale_factor()),
+                time: event_instant(event),
+                pressure: event.pressure() as f64,
+                tilt: Some((tilt.x as f64 * 90.0, -tilt.y as f64 * 90.0)),
+                twist: Some(rotation as f64),
+                eraser: tool == PenTool::Eraser,
+                barrel_button: buttons & (NSPenLowerSideMask | NSPenUpperSideMask) != 0,
+                tool_id,
+                tool,
+                serial: tablet_serial(tool_id),
+            }),
+        };
+
+        AppState::queue_event(EventWrapper::StaticEvent(window_event));
+    }
+}
+
+/// The unique id of the tools is their serial, which the tablets without one leave at 0.
+fn tablet_serial(tool_id: u64) -> Option<u64> {
+    match tool_id {
+        0 => None,
+        serial => Some(serial),
+    }
+}
+
+extern "C" fn tablet_proximity(this: &Object, _sel: Sel, event: id) {
+    trace!("Triggered `tabletProximity`");
+    unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
+        let entering: BOOL = msg_send![event, isEnteringProximity];
+        let device_type: NSUInteger = msg_send![event, pointingDeviceType];
+        let tool_id: u64 = msg_send![event, uniqueID];
+        let tool = match device_type {
+            NSPointingDeviceTypeEraser => PenTool::Eraser,
+            NSPointingDeviceTypeCursor => PenTool::Mouse,
+            _ => PenTool::Pen,
+        };
+        state.tablet_tool = (tool, tool_id);
+
+        // The proximity events have no location, so the pen is where the mouse is.
+        let window_point: NSPoint = msg_send![state.ns_window, mouseLocationOutsideOfEventStream];
+        let window_event = Event::WindowEvent {
+            window_id: WindowId(get_window_id(state.ns_window)),
+            event: WindowEvent::Pen(PenEvent {
+                device_id: DEVICE_ID,
+                phase: if entering == YES {
+                    PenPhase::Entered
+                } else {
+                    PenPhase::Left
+                },
+                location: window_point_position(this, window_point, state.get_scale_factor()),
+                time: event_instant(event),
+                pressure: 0.0,
+                tilt: None,
+                twist: None,
+                eraser: tool == PenTool::Eraser,
+                barrel_button: false,
+                tool_id,
+                tool,
+                serial: tablet_serial(tool_id),
+            }),
+        };
+
+        AppState::queue_event(EventWrapper::StaticEvent(window_event));
+    }
+    trace!("Completed `tabletProximity`");
+}
+
+/// The phases to send for the event of a gesture, starting the gestures which come without a
+/// start, and leaving out the ones which only may begin. The deltas go into `Updated` events,
+/// which the starts and the ends carrying one are followed or preceded by.
+fn gesture_phases(event: id, in_progress: &mut bool, has_delta: bool) -> Vec<GesturePhase> {
+    let mut phases = Vec::new();
+    let phase = match unsafe { event.phase() } {
+        NSEventPhase::NSEventPhaseMayBegin => return phases,
+        NSEventPhase::NSEventPhaseBegan => GesturePhase::Started,
+        NSEventPhase::NSEventPhaseEnded => GesturePhase::Ended,
+        NSEventPhase::NSEventPhaseCancelled => GesturePhase::Cancelled,
+        _ => GesturePhase::Updated,
+    };
+    let ending = matches!(phase, GesturePhase::Ended | GesturePhase::Cancelled);
+    if !*in_progress {
+        if ending {
+            return phases;
+        }
+        phases.push(GesturePhase::Started);
+        *in_progress = true;
+    }
+    if phase == GesturePhase::Updated || has_delta {
+        phases.push(GesturePhase::Updated);
+    }
+    if ending {
+        phases.push(phase);
+        *in_progress = false;
+    }
+    phases
+}
+
+extern "C" fn magnify_with_event(this: &Object, _sel: Sel, event: id) {
+    trace!("Triggered `magnifyWithEvent`");
+    unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
+        let magnification: f64 = msg_send![event, magnification];
+
+        let phases = gesture_phases(event, &mut state.pinch_in_progress, magnification != 0.0);
+        for phase in phases {
+            let window_event = Event::WindowEvent {
+                window_id: WindowId(get_window_id(state.ns_window)),
+                event: WindowEvent::PinchGesture {
+                    device_id: DEVICE_ID,
+                    delta: match phase {
+                        GesturePhase::Updated => magnification,
+                        _ => 0.0,
+                    },
+                    phase,
+                },
+            };
+            AppState::queue_event(EventWrapper::StaticEvent(window_event));
+        }
+    }
+    trace!("Completed `magnifyWithEvent`");
+}
+
+extern "C" fn rotate_with_event(this: &Object, _sel: Sel, event: id) {
+    trace!("Triggered `rotateWithEvent`");
+    unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
+        // The rotation is counterclockwise.
+        let rotation: f32 = msg_send![event, rotation];
+
+        let phases = gesture_phases(event, &mut state.rotation_in_progress, rotation != 0.0);
+        for phase in phases {
+            let window_event = Event::WindowEvent {
+                window_id: WindowId(get_window_id(state.ns_window)),
+                event: WindowEvent::RotationGesture {
+                    device_id: DEVICE_ID,
+                    delta: match phase {
+                        GesturePhase::Updated => rotation,
+                        _ => 0.0,
+                    },
+                    phase,
+                },
+            };
+            AppState::queue_event(EventWrapper::StaticEvent(window_event));
+        }
+    }
+    trace!("Completed `rotateWithEvent`");
+}
+
+extern "C" fn swipe_with_event(this: &Object, _sel: Sel, event: id) {
+    trace!("Triggered `swipeWithEvent`");
+    unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
+        // The swipes come whole, with the deltas towards their opposite direction, so they're
+        // sent with the phases of the other platforms.
+        let delta = PhysicalPosition::new(-event.deltaX() as f64, -event.deltaY() as f64);
+        let window_id = WindowId(get_window_id(state.ns_window));
+        let zero = PhysicalPosition::new(0.0, 0.0);
+        let swipes = [
+            (zero, GesturePhase::Started),
+            (delta, GesturePhase::Updated),
+            (zero, GesturePhase::Ended),
+        ];
+        for &(delta, phase) in swipes.iter() {
+            let window_event = Event::WindowEvent {
+                window_id,
+                event: WindowEvent::SwipeGesture {
+                    device_id: DEVICE_ID,
+                    delta,
+                    fingers: 3,
+                    phase,
+                },
+            };
+            AppState::queue_event(EventWrapper::StaticEvent(window_event));
+        }
+    }
+    trace!("Completed `swipeWithEvent`");
+}
+
 // Allows us to receive Ctrl-Tab and Ctrl-Esc.
 // Note that this *doesn't* help with any missing Cmd inputs.
 // https://github.com/chromium/chromium/blob/a86a8a6bcfa438fa3ac2eba6f02b3ad1f8e0756f/ui/views/cocoa/bridged_content_view.mm#L816
@@ -1086,3 +1913,73 @@ extern "C" fn wants_key_down_for_event(_this: &Object, _sel: Sel, _event: id) ->
 extern "C" fn accepts_first_mouse(_this: &Object, _sel: Sel, _event: id) -> BOOL {
     YES
 }
+
+extern "C" fn dragging_session_source_operation_mask(
+    this: &Object,
+    _sel: Sel,
+    _session: id,
+    _context: NSInteger,
+) -> NSUInteger {
+    unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+        state.drag_operations
+    }
+}
+
+extern "C" fn dragging_session_moved(this: &Object, _sel: Sel, _session: id, point: NSPoint) {
+    trace!("Triggered `draggingSession:movedToPoint:`");
+    unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+
+        let view: id = this as *const _ as *mut _;
+        // The point is in screen coordinates.
+        let screen_rect = NSRect::new(point, NSSize::new(0.0, 0.0));
+        let window_rect: NSRect = msg_send![state.ns_window, convertRectFromScreen: screen_rect];
+        let view_point = view.convertPoint_fromView_(window_rect.origin, nil);
+        let view_rect = NSView::frame(view);
+
+        let x = view_point.x as f64;
+        let y = view_rect.size.height as f64 - view_point.y as f64;
+        let window_event = Event::WindowEvent {
+            window_id: WindowId(get_window_id(state.ns_window)),
+            event: WindowEvent::DragMoved {
+                position: LogicalPosition::new(x, y).to_physical(state.get_scale_factor()),
+            },
+        };
+
+        AppState::queue_event(EventWrapper::StaticEvent(window_event));
+    }
+    trace!("Completed `draggingSession:movedToPoint:`");
+}
+
+extern "C" fn dragging_session_ended(
+    this: &Object,
+    _sel: Sel,
+    _session: id,
+    _point: NSPoint,
+    operation: NSUInteger,
+) {
+    trace!("Triggered `draggingSession:endedAtPoint:operation:`");
+    unsafe {
+        let state_ptr: *mut c_void = *this.get_ivar("winitState");
+        let state = &mut *(state_ptr as *mut ViewState);
+        state.drag_provider = None;
+
+        let event = match operation_action(operation) {
+            Some(action) => WindowEvent::DragFinished {
+                action,
+                outside: !state.drag_dropped_inside,
+            },
+            None => WindowEvent::DragCancelled,
+        };
+        let window_event = Event::WindowEvent {
+            window_id: WindowId(get_window_id(state.ns_window)),
+            event,
+        };
+
+        AppState::queue_event(EventWrapper::StaticEvent(window_event));
+    }
+    trace!("Completed `draggingSession:endedAtPoint:operation:`");
+}
@@ -1,27 +1,42 @@
 use raw_window_handle::{macos::MacOSHandle, RawWindowHandle};
 use std::{
+    cell::Cell,
     collections::VecDeque,
     f64,
-    os::raw::c_void,
+    ffi::CStr,
+    os::raw::{c_char, c_void},
+    ptr,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex, Weak,
     },
+    time::Duration,
 };
 
 use crate::{
+    clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker},
+    dnd::{DndActions, DragData},
     dpi::{
         LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size, Size::Logical,
     },
-    error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    error::{ExternalError, HandleError, NotSupportedError, OsError as RootOsError},
+    event::{Event, PresentationFlags, WindowEvent},
     icon::Icon,
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
     platform::macos::WindowExtMacOS,
     platform_impl::platform::{
         app_state::AppState,
         app_state::INTERRUPT_EVENT_LOOP_EXIT,
+        clipboard,
+        event::EventWrapper,
         ffi,
         monitor::{self, MonitorHandle, VideoMode},
+        observer::{
+            host_time_instant, kCFRunLoopCommonModes, CFAbsoluteTimeGetCurrent, CFRelease,
+            CFRunLoopAddTimer, CFRunLoopGetMain, CFRunLoopTimerContext, CFRunLoopTimerCreate,
+            CFRunLoopTimerInvalidate, CFRunLoopTimerRef, CFRunLoopTimerSetNextFireDate,
+        },
+        shortcuts,
         util::{self, IdRef},
         view::CursorState,
         view::{self, new_view},
@@ -29,7 +44,8 @@ use crate::{
         OsError,
     },
     window::{
-        CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+        Backdrop, CursorIcon, CursorTheme, Fullscreen, ImePurpose, Placement, ProgressState, Ratio,
+        TitlebarStyle, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
     },
 };
 use cocoa::{
@@ -38,15 +54,33 @@ use cocoa::{
         NSRequestUserAttentionType, NSScreen, NSView, NSWindow, NSWindowButton, NSWindowStyleMask,
     },
     base::{id, nil},
-    foundation::{NSDictionary, NSPoint, NSRect, NSSize},
+    foundation::{NSDictionary, NSPoint, NSRect, NSSize, NSUInteger},
 };
 use core_graphics::display::{CGDisplay, CGDisplayMode};
+#[cfg(feature = "software-present")]
+use core_graphics::{
+    base::{kCGBitmapByteOrder32Little, kCGImageAlphaNoneSkipFirst, kCGRenderingIntentDefault},
+    color_space::CGColorSpace,
+    data_provider::CGDataProvider,
+    image::CGImage,
+};
+use core_video_sys::{
+    kCVReturnSuccess, CVDisplayLinkCreateWithActiveCGDisplays, CVDisplayLinkRef,
+    CVDisplayLinkRelease, CVDisplayLinkSetOutputCallback, CVDisplayLinkStart, CVDisplayLinkStop,
+    CVOptionFlags, CVReturn, CVTimeStamp,
+};
+use dispatch::Queue;
 use objc::{
     declare::ClassDecl,
     rc::autoreleasepool,
     runtime::{Class, Object, Sel, BOOL, NO, YES},
 };
 
+thread_local! {
+    /// The top left point the next window placed with `Placement::Cascade` is cascaded from.
+    static CASCADE_POINT: Cell<NSPoint> = Cell::new(NSPoint::new(0.0, 0.0));
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Id(pub usize);
 
@@ -62,6 +96,82 @@ pub fn get_window_id(window_cocoa_id: id) -> Id {
     Id(window_cocoa_id as *const Object as _)
 }
 
+// The monitor of the `NSScreen`.
+unsafe fn screen_monitor(screen: id) -> MonitorHandle {
+    let desc = NSScreen::deviceDescription(screen);
+    let key = util::ns_string_id_ref("NSScreenNumber");
+    let value = NSDictionary::valueForKey_(desc, *key);
+    let display_id = msg_send![value, unsignedIntegerValue];
+    MonitorHandle::new(display_id)
+}
+
+// Shows the progress with a bar over the icon of the dock tile, which is then drawn by the
+// content view.
+unsafe fn set_dock_progress(state: ProgressState) {
+    let dock_tile: id = msg_send![NSApp(), dockTile];
+    let progress = match state {
+        ProgressState::None => {
+            let () = msg_send![dock_tile, setContentView: nil];
+            let () = msg_send![dock_tile, display];
+            return;
+        }
+        ProgressState::Indeterminate => None,
+        ProgressState::Normal(progress)
+        | ProgressState::Paused(progress)
+        | ProgressState::Error(progress) => Some(progress),
+    };
+    let mut content_view: id = msg_send![dock_tile, contentView];
+    if content_view == nil {
+        let size: NSSize = msg_send![dock_tile, size];
+        let image_view: id = msg_send![class!(NSImageView), alloc];
+        content_view = msg_send![
+            image_view,
+            initWithFrame: NSRect::new(NSPoint::new(0.0, 0.0), size)
+        ];
+        let icon: id = msg_send![NSApp(), applicationIconImage];
+        let () = msg_send![content_view, setImage: icon];
+
+        let indicator: id = msg_send![class!(NSProgressIndicator), alloc];
+        let indicator: id = msg_send![
+            indicator,
+            initWithFrame: NSRect::new(
+                NSPoint::new(0.0, 0.0),
+                NSSize::new(size.width, size.height / 8.0)
+            )
+        ];
+        // `NSProgressIndicatorStyleBar`
+        let () = msg_send![indicator, setStyle: 0usize];
+        let () = msg_send![indicator, setMinValue: 0.0f64];
+        let () = msg_send![indicator, setMaxValue: 1.0f64];
+        let () = msg_send![content_view, addSubview: indicator];
+        let () = msg_send![indicator, release];
+
+        let () = msg_send![dock_tile, setContentView: content_view];
+        let () = msg_send![content_view, release];
+    }
+    let subviews: id = msg_send![content_view, subviews];
+    let indicator: id = msg_send![subviews, firstObject];
+    match progress {
+        Some(progress) => {
+            let () = msg_send![indicator, setIndeterminate: NO];
+            let () = msg_send![indicator, setDoubleValue: progress];
+        }
+        None => {
+            let () = msg_send![indicator, setIndeterminate: YES];
+        }
+    }
+    let () = msg_send![dock_tile, display];
+}
+
+// Finds the window of the identifier among the windows of the application, if it's still open.
+unsafe fn find_window(window_id: Id) -> Option<id> {
+    let windows: id = msg_send![NSApp(), windows];
+    let count: usize = msg_send![windows, count];
+    (0..count)
+        .map(|index: usize| -> id { msg_send![windows, objectAtIndex: index] })
+        .find(|&window| get_window_id(window) == window_id)
+}
+
 #[derive(Clone)]
 pub struct PlatformSpecificWindowBuilderAttributes {
     pub movable_by_window_background: bool,
@@ -128,6 +238,10 @@ fn create_window(
                 let monitor_screen = monitor.ns_screen();
                 Some(monitor_screen.unwrap_or(appkit::NSScreen::mainScreen(nil)))
             }
+            Some(Fullscreen::Span(ref monitors)) => {
+                let monitor_screen = monitors.first().and_then(|m| m.inner.ns_screen());
+                Some(monitor_screen.unwrap_or(appkit::NSScreen::mainScreen(nil)))
+            }
             Some(Fullscreen::Borderless(None)) => Some(appkit::NSScreen::mainScreen(nil)),
             None => None,
         };
@@ -180,7 +294,11 @@ fn create_window(
             masks &= !NSWindowStyleMask::NSResizableWindowMask;
         }
 
-        if pl_attrs.fullsize_content_view {
+        // The style of the title bar adds to the one of the platform specific attributes.
+        let titlebar_style = attrs.titlebar_style;
+        let content_extended = titlebar_style == TitlebarStyle::Hidden
+            || titlebar_style == TitlebarStyle::ExtendContent;
+        if pl_attrs.fullsize_content_view || content_extended {
             masks |= NSWindowStyleMask::NSFullSizeContentViewWindowMask;
         }
 
@@ -197,13 +315,13 @@ fn create_window(
             ns_window.setTitle_(*title);
             ns_window.setAcceptsMouseMovedEvents_(YES);
 
-            if pl_attrs.titlebar_transparent {
+            if pl_attrs.titlebar_transparent || titlebar_style != TitlebarStyle::Visible {
                 ns_window.setTitlebarAppearsTransparent_(YES);
             }
-            if pl_attrs.title_hidden {
+            if pl_attrs.title_hidden || titlebar_style != TitlebarStyle::Visible {
                 ns_window.setTitleVisibility_(appkit::NSWindowTitleVisibility::NSWindowTitleHidden);
             }
-            if pl_attrs.titlebar_buttons_hidden {
+            if pl_attrs.titlebar_buttons_hidden || titlebar_style == TitlebarStyle::Hidden {
                 for titlebar_button in &[
                     NSWindowButton::NSWindowFullScreenButton,
                     NSWindowButton::NSWindowMiniaturizeButton,
@@ -233,7 +351,7 @@ fn create_window(
                 }
             }
 
-            if !pl_attrs.has_shadow {
+            if !pl_attrs.has_shadow || !attrs.shadow {
                 ns_window.setHasShadow_(NO);
             }
             if attrs.position.is_none() {
@@ -317,6 +435,11 @@ pub struct UnownedWindow {
     pub shared_state: Arc<Mutex<SharedState>>,
     decorations: AtomicBool,
     cursor_state: Weak<Mutex<CursorState>>,
+    /// The timer showing the frames of the animated custom cursor.
+    cursor_animation: Mutex<Option<CursorAnimation>>,
+    /// The display link the requested redraws wait for while they're synchronized with it, and
+    /// the presentations of the frames are timed on.
+    display_link: Mutex<Option<DisplayLink>>,
     pub inner_rect: Option<PhysicalSize<u32>>,
 }
 
@@ -345,6 +468,7 @@ impl UnownedWindow {
         unsafe {
             ns_window.setContentView_(*ns_view);
             ns_window.setInitialFirstResponder_(*ns_view);
+            view::set_key_repeat(*ns_view, win_attribs.key_repeat);
         }
 
         let input_context = unsafe { util::create_input_context(*ns_view) };
@@ -384,6 +508,10 @@ impl UnownedWindow {
         let maximized = win_attribs.maximized;
         let visible = win_attribs.visible;
         let decorations = win_attribs.decorations;
+        let owner = win_attribs.owner;
+        let placement = win_attribs
+            .placement
+            .filter(|_| win_attribs.position.is_none() && fullscreen.is_none() && !maximized);
         let inner_rect = win_attribs
             .inner_size
             .map(|size| size.to_physical(scale_factor));
@@ -395,11 +523,18 @@ impl UnownedWindow {
             shared_state: Arc::new(Mutex::new(win_attribs.into())),
             decorations: AtomicBool::new(decorations),
             cursor_state,
+            cursor_animation: Mutex::new(None),
+            display_link: Mutex::new(None),
             inner_rect,
         });
 
         let delegate = new_delegate(&window, fullscreen.is_some());
 
+        let owner = owner.and_then(|owner| unsafe { find_window(owner.0) });
+        if let Some(placement) = placement {
+            unsafe { window.place_window(placement, owner) };
+        }
+
         // Set fullscreen mode after we setup everything
         window.set_fullscreen(fullscreen);
 
@@ -415,9 +550,86 @@ impl UnownedWindow {
             window.set_maximized(maximized);
         }
 
+        // The child windows move along with their parent, and stay above it.
+        if let Some(owner) = owner {
+            unsafe {
+                // `NSWindowAbove`
+                let () = msg_send![owner, addChildWindow: *window.ns_window ordered: 1isize];
+            }
+        }
+
         Ok((window, delegate))
     }
 
+    // Places the window within the visible frame of the screen of its owner, or of the cursor.
+    unsafe fn place_window(&self, placement: Placement, owner: Option<id>) {
+        let center_of = |rect: NSRect| {
+            NSPoint::new(
+                rect.origin.x + rect.size.width / 2.0,
+                rect.origin.y + rect.size.height / 2.0,
+            )
+        };
+        let (screen, center): (id, NSPoint) = match (placement, owner) {
+            (Placement::CenterOnParent, Some(owner)) => {
+                (msg_send![owner, screen], center_of(NSWindow::frame(owner)))
+            }
+            (Placement::AtCursor, _) => {
+                let cursor: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+                let screens = NSScreen::screens(nil);
+                let count: NSUInteger = msg_send![screens, count];
+                let screen = (0..count)
+                    .map(|index| -> id { msg_send![screens, objectAtIndex: index] })
+                    .find(|&screen| {
+                        let frame = NSScreen::frame(screen);
+                        cursor.x >= frame.origin.x
+                            && cursor.x < frame.origin.x + frame.size.width
+                            && cursor.y >= frame.origin.y
+                            && cursor.y < frame.origin.y + frame.size.height
+                    })
+                    .unwrap_or_else(|| NSWindow::screen(*self.ns_window));
+                (screen, cursor)
+            }
+            (Placement::Cascade, _) => {
+                // Each window is cascaded from the point returned for the last one.
+                CASCADE_POINT.with(|point| {
+                    let next: NSPoint =
+                        msg_send![*self.ns_window, cascadeTopLeftFromPoint: point.get()];
+                    point.set(next);
+                });
+                return;
+            }
+            // The window is centered on its screen once it's created.
+            _ => return,
+        };
+        if screen == nil {
+            return;
+        }
+
+        // The visible frame leaves out the menu bar and the dock
+        let bounds = NSScreen::visibleFrame(screen);
+        let frame = NSWindow::frame(*self.ns_window);
+        let keep = |center: f64, length: f64, start: f64, bounds_length: f64| {
+            (center - length / 2.0)
+                .min(start + bounds_length - length)
+                .max(start)
+        };
+        let origin = NSPoint::new(
+            keep(
+                center.x,
+                frame.size.width,
+                bounds.origin.x,
+                bounds.size.width,
+            ),
+            keep(
+                center.y,
+                frame.size.height,
+                bounds.origin.y,
+                bounds.size.height,
+            ),
+        );
+        let () = msg_send![*self.ns_window, setFrameOrigin: origin];
+    }
+
     fn set_style_mask_async(&self, mask: NSWindowStyleMask) {
         unsafe { util::set_style_mask_async(*self.ns_window, *self.ns_view, mask) };
     }
@@ -444,9 +656,74 @@ impl UnownedWindow {
     }
 
     pub fn request_redraw(&self) {
+        if let Some(ref display_link) = *self.display_link.lock().unwrap() {
+            if display_link.vsync_redraws {
+                display_link.hold_redraw();
+                return;
+            }
+        }
         AppState::queue_redraw(RootWindowId(self.id()));
     }
 
+    #[inline]
+    pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+        let mut display_link = self.display_link.lock().unwrap();
+        if vsync_redraws {
+            if display_link.is_none() {
+                *display_link = DisplayLink::new(self.id());
+            }
+            if let Some(ref mut display_link) = *display_link {
+                display_link.vsync_redraws = true;
+            }
+        } else if let Some(ref mut display_link) = *display_link {
+            display_link.vsync_redraws = false;
+            // The held redraw isn't left waiting.
+            if display_link.take_held_redraw() {
+                AppState::queue_redraw(RootWindowId(self.id()));
+            }
+        }
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        let mut display_link = self.display_link.lock().unwrap();
+        if display_link.is_none() {
+            *display_link = DisplayLink::new(self.id());
+        }
+        if let Some(ref display_link) = *display_link {
+            display_link.frame_presented();
+        }
+    }
+
+    #[cfg(feature = "software-present")]
+    pub fn present_pixels(
+        &self,
+        pixels: &[u32],
+        size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        // The pixels are in the little-endian `xRGB` layout.
+        let data: Vec<u8> = pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_le_bytes())
+            .collect();
+        let image = CGImage::new(
+            size.width as usize,
+            size.height as usize,
+            8,
+            32,
+            size.width as usize * 4,
+            &CGColorSpace::create_device_rgb(),
+            kCGBitmapByteOrder32Little | kCGImageAlphaNoneSkipFirst,
+            &CGDataProvider::from_buffer(Arc::new(data)),
+            false,
+            kCGRenderingIntentDefault,
+        );
+        unsafe {
+            util::set_pixels_async(*self.ns_view, image, self.scale_factor() as CGFloat);
+        }
+        Ok(())
+    }
+
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         let frame_rect = unsafe { NSWindow::frame(*self.ns_window) };
         let position = LogicalPosition::new(
@@ -495,6 +772,24 @@ impl UnownedWindow {
         logical.to_physical(scale_factor)
     }
 
+    pub fn recommended_bounds(&self) -> Option<PhysicalSize<u32>> {
+        unsafe {
+            let screen = NSWindow::screen(*self.ns_window);
+            if screen == nil {
+                return None;
+            }
+            // The visible frame leaves out the menu bar and the dock
+            let content_rect =
+                NSWindow::contentRectForFrameRect_(*self.ns_window, NSScreen::visibleFrame(screen));
+            let logical: LogicalSize<f64> = (
+                content_rect.size.width as f64,
+                content_rect.size.height as f64,
+            )
+                .into();
+            Some(logical.to_physical(self.scale_factor()))
+        }
+    }
+
     #[inline]
     pub fn set_inner_size(&self, size: Size) {
         unsafe {
@@ -525,6 +820,22 @@ impl UnownedWindow {
         }
     }
 
+    pub fn set_aspect_ratio(&self, ratio: Option<Ratio>) {
+        unsafe {
+            match ratio {
+                Some(ratio) => {
+                    let ratio = NSSize::new(ratio.width() as CGFloat, ratio.height() as CGFloat);
+                    let _: () = msg_send![*self.ns_window, setContentAspectRatio: ratio];
+                }
+                // The resize increments replace the aspect ratio
+                None => {
+                    let increments = NSSize::new(1.0, 1.0);
+                    let _: () = msg_send![*self.ns_window, setContentResizeIncrements: increments];
+                }
+            }
+        }
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
         let fullscreen = {
@@ -546,7 +857,32 @@ impl UnownedWindow {
     }
 
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        let cursor = util::Cursor::from(cursor);
+        self.set_ns_cursor(util::Cursor::from(cursor));
+    }
+
+    #[inline]
+    pub fn set_cursor(&self, cursor: util::CustomCursor) {
+        self.set_ns_cursor(util::Cursor::Custom(cursor, 0));
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, _name: &str, _size: u32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        None
+    }
+
+    fn set_ns_cursor(&self, cursor: util::Cursor) {
+        let animation = match cursor {
+            util::Cursor::Custom(ref cursor, _) if cursor.frame_count() > 1 => {
+                CursorAnimation::new(self, cursor.delay(0))
+            }
+            _ => None,
+        };
+        *self.cursor_animation.lock().unwrap() = animation;
         if let Some(cursor_access) = self.cursor_state.upgrade() {
             cursor_access.lock().unwrap().cursor = cursor;
         }
@@ -564,6 +900,40 @@ impl UnownedWindow {
             .map_err(|status| ExternalError::Os(os_error!(OsError::CGError(status))))
     }
 
+    #[inline]
+    pub fn set_cursor_grab_area(
+        &self,
+        _area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_pointer_lock(&self, lock: bool) -> Result<(), ExternalError> {
+        // The mouse keeps giving its deltas while the cursor stays in place.
+        CGDisplay::associate_mouse_and_mouse_cursor_position(!lock)
+            .map_err(|status| ExternalError::Os(os_error!(OsError::CGError(status))))?;
+        if let Some(cursor_access) = self.cursor_state.upgrade() {
+            cursor_access.lock().unwrap().locked = lock;
+        }
+        unsafe {
+            let _: () = msg_send![*self.ns_window,
+                invalidateCursorRectsForView:*self.ns_view
+            ];
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         if let Some(cursor_access) = self.cursor_state.upgrade() {
@@ -714,6 +1084,13 @@ impl UnownedWindow {
 
     #[inline]
     pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        // Spanning several monitors isn't supported, so fall back to the
+        // borderless fullscreen on the first one.
+        let fullscreen = fullscreen.map(|fullscreen| match fullscreen {
+            Fullscreen::Span(monitors) => Fullscreen::Borderless(monitors.into_iter().next()),
+            fullscreen => fullscreen,
+        });
+
         trace!("Locked shared state in `set_fullscreen`");
         let mut shared_state_lock = self.shared_state.lock().unwrap();
         if shared_state_lock.is_simple_fullscreen {
@@ -749,6 +1126,7 @@ impl UnownedWindow {
                 Fullscreen::Exclusive(RootVideoMode {
                     video_mode: VideoMode { ref monitor, .. },
                 }) => monitor.clone(),
+                Fullscreen::Span(_) => unreachable!(),
             }
             .ns_screen()
             .unwrap();
@@ -925,6 +1303,37 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn set_shadow(&self, shadow: bool) {
+        self.set_has_shadow(shadow);
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        unsafe {
+            let () = msg_send![*self.ns_window, setAlphaValue: opacity as CGFloat];
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_backdrop(&self, backdrop: Backdrop) -> Result<(), ExternalError> {
+        // The values of `NSVisualEffectMaterial`
+        let material = match backdrop {
+            Backdrop::None => None,
+            // `NSVisualEffectMaterialUnderWindowBackground`
+            Backdrop::Blur => Some(21),
+            // `NSVisualEffectMaterialHUDWindow`
+            Backdrop::Acrylic => Some(13),
+            // `NSVisualEffectMaterialWindowBackground`
+            Backdrop::Mica => Some(12),
+            // `NSVisualEffectMaterialSidebar`
+            Backdrop::Vibrancy => Some(7),
+        };
+        unsafe { util::set_visual_effect_async(*self.ns_view, material) };
+        Ok(())
+    }
+
     #[inline]
     pub fn set_always_on_top(&self, always_on_top: bool) {
         let level = if always_on_top {
@@ -948,19 +1357,96 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn set_ime_position(&self, spot: Position) {
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        clipboard::set_clipboard(kind, provider)
+    }
+
+    #[inline]
+    pub fn read_clipboard(
+        &self,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), ExternalError> {
+        let content = clipboard::read_clipboard(kind, picker)?;
+        if let Some(consumer) = consumer {
+            crate::clipboard::consume_content(consumer, content);
+            return Ok(());
+        }
+
+        let window_id = RootWindowId(self.id());
+        // Events can only be queued from the main thread.
+        Queue::main().exec_async(move || {
+            AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+                window_id,
+                event: WindowEvent::ClipboardData { kind, content },
+            }));
+        });
+        Ok(())
+    }
+
+    #[inline]
+    pub fn start_drag(&self, data: DragData, actions: DndActions) -> Result<(), ExternalError> {
+        let contents = clipboard::collect_contents(&*data.provider)?;
+        let started = autoreleasepool(|| unsafe {
+            match clipboard::pasteboard_item(contents) {
+                Some(item) => view::start_drag(
+                    *self.ns_view,
+                    item,
+                    Arc::clone(&data.provider),
+                    data.icon.as_ref(),
+                    actions,
+                ),
+                None => false,
+            }
+        });
+
+        if started {
+            Ok(())
+        } else {
+            Err(ExternalError::Os(os_error!(OsError::PasteboardError(
+                "failed to start the drag"
+            ))))
+        }
+    }
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
         let scale_factor = self.scale_factor();
-        let logical_spot = spot.to_logical(scale_factor);
         unsafe {
-            view::set_ime_position(
+            view::set_ime_cursor_area(
                 *self.ns_view,
                 *self.input_context,
-                logical_spot.x,
-                logical_spot.y,
+                position.to_logical(scale_factor),
+                size.to_logical(scale_factor),
             );
         }
     }
 
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        unsafe { view::set_ime_surrounding_text(*self.ns_view, text, cursor, anchor) }
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        unsafe { view::set_ime_purpose(*self.ns_view, *self.input_context, purpose) }
+    }
+
+    #[inline]
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        unsafe { view::set_key_repeat(*self.ns_view, key_repeat) }
+    }
+
+    #[inline]
+    pub fn set_system_shortcuts_inhibited(&self, inhibited: bool) -> Result<(), ExternalError> {
+        shortcuts::set_shortcuts_inhibited(*self.ns_window, inhibited)
+    }
+
     #[inline]
     pub fn focus_window(&self) {
         let is_minimized: BOOL = unsafe { msg_send![*self.ns_window, isMiniaturized] };
@@ -989,17 +1475,110 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn tab_group(&self) -> Option<String> {
+        unsafe {
+            let identifier: id = msg_send![*self.ns_window, tabbingIdentifier];
+            if identifier == nil {
+                return None;
+            }
+            let utf8: *const c_char = msg_send![identifier, UTF8String];
+            Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+        }
+    }
+
+    #[inline]
+    pub fn set_tab_group(&self, group: &str) {
+        unsafe {
+            let identifier = util::ns_string_id_ref(group);
+            let () = msg_send![*self.ns_window, setTabbingIdentifier: *identifier];
+        }
+    }
+
+    #[inline]
+    pub fn merge_into_tab_group(&self, window: &UnownedWindow) {
+        unsafe {
+            let identifier: id = msg_send![*window.ns_window, tabbingIdentifier];
+            let () = msg_send![*self.ns_window, setTabbingIdentifier: identifier];
+            // `NSWindowAbove`
+            let () = msg_send![*window.ns_window, addTabbedWindow: *self.ns_window ordered: 1isize];
+        }
+    }
+
+    #[inline]
+    pub fn select_next_tab(&self) {
+        unsafe {
+            let () = msg_send![*self.ns_window, selectNextTab: nil];
+        }
+    }
+
+    #[inline]
+    pub fn workspace(&self) -> Option<u32> {
+        None
+    }
+
+    #[inline]
+    pub fn move_to_workspace(&self, _workspace: u32) {}
+
+    #[inline]
+    pub fn set_visible_on_all_workspaces(&self, visible: bool) {
+        // `NSWindowCollectionBehaviorCanJoinAllSpaces`
+        const CAN_JOIN_ALL_SPACES: NSUInteger = 1 << 0;
+
+        unsafe {
+            let behavior: NSUInteger = msg_send![*self.ns_window, collectionBehavior];
+            let behavior = if visible {
+                behavior | CAN_JOIN_ALL_SPACES
+            } else {
+                behavior & !CAN_JOIN_ALL_SPACES
+            };
+            let () = msg_send![*self.ns_window, setCollectionBehavior: behavior];
+        }
+    }
+
+    #[inline]
+    pub fn set_progress(&self, state: ProgressState) {
+        unsafe { set_dock_progress(state) };
+    }
+
+    #[inline]
+    pub fn set_badge(&self, count: Option<u64>) {
+        unsafe {
+            let dock_tile: id = msg_send![NSApp(), dockTile];
+            let label = count.map(|count| util::ns_string_id_ref(&count.to_string()));
+            let () =
+                msg_send![dock_tile, setBadgeLabel: label.as_ref().map_or(nil, |label| **label)];
+        }
+    }
+
+    #[inline]
+    pub fn set_modal(&self, modal: bool) -> Result<(), ExternalError> {
+        unsafe {
+            // The owner takes the window as a sheet instead of a child window while it's modal.
+            if modal {
+                let owner: id = msg_send![*self.ns_window, parentWindow];
+                if owner != nil {
+                    let () = msg_send![owner, removeChildWindow: *self.ns_window];
+                    let () = msg_send![owner, beginSheet: *self.ns_window completionHandler: nil];
+                }
+            } else {
+                let owner: id = msg_send![*self.ns_window, sheetParent];
+                if owner != nil {
+                    let () = msg_send![owner, endSheet: *self.ns_window];
+                    // `NSWindowAbove`
+                    let () = msg_send![owner, addChildWindow: *self.ns_window ordered: 1isize];
+                }
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     // Allow directly accessing the current monitor internally without unwrapping.
     pub(crate) fn current_monitor_inner(&self) -> RootMonitorHandle {
         unsafe {
-            let screen: id = msg_send![*self.ns_window, screen];
-            let desc = NSScreen::deviceDescription(screen);
-            let key = util::ns_string_id_ref("NSScreenNumber");
-            let value = NSDictionary::valueForKey_(desc, *key);
-            let display_id = msg_send![value, unsignedIntegerValue];
             RootMonitorHandle {
-                inner: MonitorHandle::new(display_id),
+                inner: screen_monitor(msg_send![*self.ns_window, screen]),
             }
         }
     }
@@ -1021,13 +1600,28 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn raw_window_handle(&self) -> RawWindowHandle {
+    pub fn is_vrr_active(&self) -> bool {
+        // The adaptive sync is only used for the fullscreen windows.
+        self.fullscreen().is_some()
+            && self
+                .current_monitor_inner()
+                .inner
+                .supports_variable_refresh()
+    }
+
+    #[inline]
+    pub fn window_handle(&self) -> Result<RawWindowHandle, HandleError> {
         let handle = MacOSHandle {
             ns_window: *self.ns_window as *mut _,
             ns_view: *self.ns_view as *mut _,
             ..MacOSHandle::empty()
         };
-        RawWindowHandle::MacOS(handle)
+        Ok(RawWindowHandle::MacOS(handle))
+    }
+
+    #[inline]
+    pub fn display_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        Ok(RawWindowHandle::MacOS(MacOSHandle::empty()))
     }
 }
 
@@ -1145,9 +1739,208 @@ impl WindowExtMacOS for UnownedWindow {
     }
 }
 
+/// A timer of the main run loop showing the next frame of the animated cursor of a window, once
+/// the delay of the current frame is over.
+struct CursorAnimation {
+    timer: CFRunLoopTimerRef,
+    /// The info passed to the callback, which must outlive the timer.
+    _target: Box<AnimationTarget>,
+}
+
+struct AnimationTarget {
+    ns_window: id,
+    ns_view: id,
+    cursor_state: Weak<Mutex<CursorState>>,
+}
+
+impl CursorAnimation {
+    fn new(window: &UnownedWindow, delay: Duration) -> Option<Self> {
+        extern "C" fn next_frame(timer: CFRunLoopTimerRef, info: *mut c_void) {
+            let target = unsafe { &*(info as *const AnimationTarget) };
+            let cursor_state = match target.cursor_state.upgrade() {
+                Some(cursor_state) => cursor_state,
+                None => return,
+            };
+            if let util::Cursor::Custom(ref cursor, ref mut frame) =
+                cursor_state.lock().unwrap().cursor
+            {
+                *frame = (*frame + 1) % cursor.frame_count();
+                let delay = cursor.delay(*frame).as_secs_f64();
+                unsafe { CFRunLoopTimerSetNextFireDate(timer, CFAbsoluteTimeGetCurrent() + delay) };
+            }
+            unsafe {
+                let _: () = msg_send![target.ns_window,
+                    invalidateCursorRectsForView:target.ns_view
+                ];
+            }
+        }
+
+        // The timer repeats, and the callback moves its next fire date along with the frames.
+        let target = Box::new(AnimationTarget {
+            ns_window: *window.ns_window,
+            ns_view: *window.ns_view,
+            cursor_state: window.cursor_state.clone(),
+        });
+        unsafe {
+            let mut context = CFRunLoopTimerContext {
+                version: 0,
+                info: &*target as *const AnimationTarget as *mut c_void,
+                retain: None,
+                release: None,
+                copyDescription: None,
+            };
+            let timer = CFRunLoopTimerCreate(
+                ptr::null_mut(),
+                CFAbsoluteTimeGetCurrent() + delay.as_secs_f64(),
+                delay.as_secs_f64(),
+                0,
+                0,
+                next_frame,
+                &mut context,
+            );
+            if timer.is_null() {
+                warn!("Failed to create the timer of the animated cursor");
+                return None;
+            }
+            CFRunLoopAddTimer(CFRunLoopGetMain(), timer, kCFRunLoopCommonModes);
+            Some(CursorAnimation {
+                timer,
+                _target: target,
+            })
+        }
+    }
+}
+
+impl Drop for CursorAnimation {
+    fn drop(&mut self) {
+        unsafe {
+            CFRunLoopTimerInvalidate(self.timer);
+            CFRelease(self.timer as _);
+        }
+    }
+}
+
+/// A display link of the active displays releasing the redraw held for the window on every
+/// refresh, from its own thread.
+///
+/// The frame presented before a refresh is shown on the next one, whose time is then sent as its
+/// `Presented`.
+struct DisplayLink {
+    display_link: CVDisplayLinkRef,
+    /// The context passed to the callback, which must outlive the display link.
+    target: Box<DisplayLinkTarget>,
+    vsync_redraws: bool,
+}
+
+struct DisplayLinkTarget {
+    window_id: Id,
+    redraw_held: AtomicBool,
+    /// A frame was presented since the last refresh.
+    frame_presented: AtomicBool,
+    /// A frame presented before the last refresh is shown on the next one.
+    frame_latched: AtomicBool,
+}
+
+impl DisplayLink {
+    fn new(window_id: Id) -> Option<Self> {
+        extern "C" fn refresh(
+            _display_link: CVDisplayLinkRef,
+            now: *const CVTimeStamp,
+            _output_time: *const CVTimeStamp,
+            _flags_in: CVOptionFlags,
+            _flags_out: *mut CVOptionFlags,
+            context: *mut c_void,
+        ) -> CVReturn {
+            let target = unsafe { &*(context as *const DisplayLinkTarget) };
+            if target.frame_latched.swap(false, Ordering::AcqRel) {
+                let now = unsafe { &*now };
+                let refresh = if now.videoTimeScale > 0 && now.videoRefreshPeriod > 0 {
+                    Some(Duration::from_secs_f64(
+                        now.videoRefreshPeriod as f64 / now.videoTimeScale as f64,
+                    ))
+                } else {
+                    None
+                };
+                let window_id = RootWindowId(target.window_id);
+                let time = host_time_instant(now.hostTime);
+                // Events can only be queued from the main thread.
+                Queue::main().exec_async(move || {
+                    AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::Presented {
+                            time,
+                            refresh,
+                            flags: PresentationFlags::VSYNC,
+                        },
+                    }));
+                });
+            }
+            if target.frame_presented.swap(false, Ordering::AcqRel) {
+                target.frame_latched.store(true, Ordering::Release);
+            }
+            if target.redraw_held.swap(false, Ordering::AcqRel) {
+                AppState::queue_redraw(RootWindowId(target.window_id));
+            }
+            kCVReturnSuccess
+        }
+
+        let target = Box::new(DisplayLinkTarget {
+            window_id,
+            redraw_held: AtomicBool::new(false),
+            frame_presented: AtomicBool::new(false),
+            frame_latched: AtomicBool::new(false),
+        });
+        unsafe {
+            let mut display_link = ptr::null_mut();
+            if CVDisplayLinkCreateWithActiveCGDisplays(&mut display_link) != kCVReturnSuccess {
+                warn!("Failed to create the display link of the window");
+                return None;
+            }
+            CVDisplayLinkSetOutputCallback(
+                display_link,
+                refresh,
+                &*target as *const DisplayLinkTarget as *mut c_void,
+            );
+            if CVDisplayLinkStart(display_link) != kCVReturnSuccess {
+                warn!("Failed to start the display link of the window");
+                CVDisplayLinkRelease(display_link);
+                return None;
+            }
+            Some(DisplayLink {
+                display_link,
+                target,
+                vsync_redraws: false,
+            })
+        }
+    }
+
+    fn hold_redraw(&self) {
+        self.target.redraw_held.store(true, Ordering::Release);
+    }
+
+    fn take_held_redraw(&self) -> bool {
+        self.target.redraw_held.swap(false, Ordering::AcqRel)
+    }
+
+    fn frame_presented(&self) {
+        self.target.frame_presented.store(true, Ordering::Release);
+    }
+}
+
+impl Drop for DisplayLink {
+    fn drop(&mut self) {
+        // Stopping waits for the callback, so the target isn't used anymore.
+        unsafe {
+            CVDisplayLinkStop(self.display_link);
+            CVDisplayLinkRelease(self.display_link);
+        }
+    }
+}
+
 impl Drop for UnownedWindow {
     fn drop(&mut self) {
         trace!("Dropping `UnownedWindow` ({:?})", self as *mut _);
+        let _ = shortcuts::set_shortcuts_inhibited(*self.ns_window, false);
         // Close the window if it has not yet been closed.
         if *self.ns_window != nil {
             unsafe { util::close_async(self.ns_window.clone()) };
@@ -1,13 +1,15 @@
 use std::{
-    f64,
+    f64, mem,
     os::raw::c_void,
     sync::{atomic::Ordering, Arc, Weak},
 };
 
 use cocoa::{
-    appkit::{self, NSApplicationPresentationOptions, NSView, NSWindow},
+    appkit::{
+        self, NSApplicationPresentationOptions, NSScreen, NSView, NSWindow, NSWindowStyleMask,
+    },
     base::{id, nil},
-    foundation::NSUInteger,
+    foundation::{NSPoint, NSString, NSUInteger},
 };
 use objc::{
     declare::ClassDecl,
@@ -16,17 +18,21 @@ use objc::{
 };
 
 use crate::{
-    dpi::{LogicalPosition, LogicalSize},
+    clipboard::MimeType,
+    dnd::DndAction,
+    dpi::{LogicalPosition, LogicalSize, PhysicalSize},
     event::{Event, ModifiersState, WindowEvent},
     platform_impl::platform::{
         app_state::AppState,
         app_state::INTERRUPT_EVENT_LOOP_EXIT,
+        clipboard,
         event::{EventProxy, EventWrapper},
         util::{self, IdRef},
-        view::ViewState,
+        view::{self, ViewState},
         window::{get_window_id, UnownedWindow},
+        DEVICE_ID,
     },
-    window::{Fullscreen, WindowId},
+    window::{EdgeFlags, Fullscreen, WindowId},
 };
 
 pub struct WindowDelegateState {
@@ -47,11 +53,22 @@ pub struct WindowDelegateState {
 
     // Used to prevent redundant events.
     previous_scale_factor: f64,
+    previous_bounds: Option<PhysicalSize<u32>>,
+    previous_tiled_edges: EdgeFlags,
+    previous_refresh_rate: Option<u32>,
+
+    // The tab group and its number of windows, used to tell when the window is dragged out of
+    // its group, which gives it a group of its own.
+    previous_tab_group: (id, usize),
 }
 
 impl WindowDelegateState {
     pub fn new(window: &Arc<UnownedWindow>, initial_fullscreen: bool) -> Self {
         let scale_factor = window.scale_factor();
+        let refresh_rate = window
+            .current_monitor_inner()
+            .inner
+            .refresh_rate_millihertz();
         let mut delegate_state = WindowDelegateState {
             ns_window: window.ns_window.clone(),
             ns_view: window.ns_view.clone(),
@@ -59,7 +76,12 @@ impl WindowDelegateState {
             initial_fullscreen,
             previous_position: None,
             previous_scale_factor: scale_factor,
+            previous_bounds: None,
+            previous_tiled_edges: EdgeFlags::empty(),
+            previous_refresh_rate: refresh_rate,
+            previous_tab_group: (nil, 0),
         };
+        delegate_state.previous_tab_group = delegate_state.tab_group();
 
         if scale_factor != 1.0 {
             delegate_state.emit_static_scale_factor_changed_event();
@@ -119,6 +141,87 @@ impl WindowDelegateState {
         }
     }
 
+    fn emit_recommended_bounds_event(&mut self) {
+        let bounds = self
+            .with_window(|window| window.recommended_bounds())
+            .flatten();
+        if let Some(bounds) = bounds {
+            if self.previous_bounds != Some(bounds) {
+                self.previous_bounds = Some(bounds);
+                self.emit_event(WindowEvent::RecommendedBounds(bounds));
+            }
+        }
+    }
+
+    fn emit_refresh_rate_event(&mut self) {
+        let refresh_rate = self
+            .with_window(|window| {
+                window
+                    .current_monitor_inner()
+                    .inner
+                    .refresh_rate_millihertz()
+            })
+            .flatten();
+        if let Some(refresh_rate) = refresh_rate {
+            if self.previous_refresh_rate != Some(refresh_rate) {
+                self.previous_refresh_rate = Some(refresh_rate);
+                self.emit_event(WindowEvent::RefreshRateChanged(refresh_rate));
+            }
+        }
+    }
+
+    fn emit_tiled_state_event(&mut self) {
+        let edges = self.tiled_edges();
+        if self.previous_tiled_edges != edges {
+            self.previous_tiled_edges = edges;
+            self.emit_event(WindowEvent::TiledStateChanged(edges));
+        }
+    }
+
+    /// The window in a split view is full screen while taking only a part of its screen, and is
+    /// tiled on all its edges, against the screen and the other window.
+    fn tiled_edges(&self) -> EdgeFlags {
+        unsafe {
+            let style_mask = self.ns_window.styleMask();
+            let screen = NSWindow::screen(*self.ns_window);
+            if !style_mask.contains(NSWindowStyleMask::NSFullScreenWindowMask) || screen == nil {
+                return EdgeFlags::empty();
+            }
+            let frame = NSWindow::frame(*self.ns_window);
+            if frame.size.width < NSScreen::frame(screen).size.width {
+                EdgeFlags::all()
+            } else {
+                EdgeFlags::empty()
+            }
+        }
+    }
+
+    fn emit_tab_detached_event(&mut self) {
+        let tab_group = self.tab_group();
+        let (previous_group, previous_count) =
+            mem::replace(&mut self.previous_tab_group, tab_group);
+        // The windows merged into another group join it instead of being alone in a new one.
+        if tab_group.0 != previous_group && previous_count > 1 && tab_group.1 == 1 {
+            self.emit_event(WindowEvent::TabDetached);
+        }
+    }
+
+    fn tab_group(&self) -> (id, usize) {
+        unsafe {
+            let responds: BOOL = msg_send![*self.ns_window, respondsToSelector: sel!(tabGroup)];
+            if responds == NO {
+                return (nil, 0);
+            }
+            let group: id = msg_send![*self.ns_window, tabGroup];
+            if group == nil {
+                return (nil, 0);
+            }
+            let windows: id = msg_send![group, windows];
+            let count: NSUInteger = msg_send![windows, count];
+            (group, count as usize)
+        }
+    }
+
     fn get_scale_factor(&self) -> f64 {
         (unsafe { NSWindow::backingScaleFactor(*self.ns_window) }) as f64
     }
@@ -170,6 +273,18 @@ lazy_static! {
             sel!(windowDidMove:),
             window_did_move as extern "C" fn(&Object, Sel, id),
         );
+        decl.add_method(
+            sel!(windowDidChangeScreen:),
+            window_did_change_screen as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(screenParametersDidChange:),
+            screen_parameters_did_change as extern "C" fn(&Object, Sel, id),
+        );
+        decl.add_method(
+            sel!(windowDidChangeOcclusionState:),
+            window_did_change_occlusion_state as extern "C" fn(&Object, Sel, id),
+        );
         decl.add_method(
             sel!(windowDidChangeBackingProperties:),
             window_did_change_backing_properties as extern "C" fn(&Object, Sel, id),
@@ -246,6 +361,10 @@ fn with_state<F: FnOnce(&mut WindowDelegateState) -> T, T>(this: &Object, callba
 }
 
 extern "C" fn dealloc(this: &Object, _sel: Sel) {
+    unsafe {
+        let notification_center: &Object = msg_send![class!(NSNotificationCenter), defaultCenter];
+        let _: () = msg_send![notification_center, removeObserver: this];
+    }
     with_state(this, |state| unsafe {
         Box::from_raw(state as *mut WindowDelegateState);
     });
@@ -259,6 +378,20 @@ extern "C" fn init_with_winit(this: &Object, _sel: Sel, state: *mut c_void) -> i
             with_state(&*this, |state| {
                 let () = msg_send![*state.ns_window, setDelegate: this];
             });
+
+            // The modes of the screens change without the windows being told.
+            let notification_center: &Object =
+                msg_send![class!(NSNotificationCenter), defaultCenter];
+            let notification_name = IdRef::new(
+                NSString::alloc(nil).init_str("NSApplicationDidChangeScreenParametersNotification"),
+            );
+            let _: () = msg_send![
+                notification_center,
+                addObserver: this
+                selector: sel!(screenParametersDidChange:)
+                name: notification_name
+                object: nil
+            ];
         }
         this
     }
@@ -290,6 +423,10 @@ extern "C" fn window_did_resize(this: &Object, _: Sel, _: id) {
     with_state(this, |state| {
         state.emit_resize_event();
         state.emit_move_event();
+        state.emit_tab_detached_event();
+        // The decorations of the window may have changed along with its size.
+        state.emit_recommended_bounds_event();
+        state.emit_tiled_state_event();
     });
     trace!("Completed `windowDidResize:`");
 }
@@ -299,10 +436,39 @@ extern "C" fn window_did_move(this: &Object, _: Sel, _: id) {
     trace!("Triggered `windowDidMove:`");
     with_state(this, |state| {
         state.emit_move_event();
+        state.emit_tab_detached_event();
     });
     trace!("Completed `windowDidMove:`");
 }
 
+extern "C" fn window_did_change_screen(this: &Object, _: Sel, _: id) {
+    trace!("Triggered `windowDidChangeScreen:`");
+    with_state(this, |state| {
+        state.emit_recommended_bounds_event();
+        state.emit_refresh_rate_event();
+    });
+    trace!("Completed `windowDidChangeScreen:`");
+}
+
+extern "C" fn screen_parameters_did_change(this: &Object, _: Sel, _: id) {
+    trace!("Triggered `screenParametersDidChange:`");
+    with_state(this, |state| {
+        state.emit_refresh_rate_event();
+    });
+    trace!("Completed `screenParametersDidChange:`");
+}
+
+extern "C" fn window_did_change_occlusion_state(this: &Object, _: Sel, _: id) {
+    trace!("Triggered `windowDidChangeOcclusionState:`");
+    with_state(this, |state| {
+        // `NSWindowOcclusionStateVisible`
+        let state_visible: NSUInteger = 1 << 1;
+        let occlusion_state: NSUInteger = unsafe { msg_send![*state.ns_window, occlusionState] };
+        state.emit_event(WindowEvent::Occluded(occlusion_state & state_visible == 0));
+    });
+    trace!("Completed `windowDidChangeOcclusionState:`");
+}
+
 extern "C" fn window_did_change_backing_properties(this: &Object, _: Sel, _: id) {
     trace!("Triggered `windowDidChangeBackingProperties:`");
     with_state(this, |state| {
@@ -317,6 +483,8 @@ extern "C" fn window_did_become_key(this: &Object, _: Sel, _: id) {
         // TODO: center the cursor if the window had mouse grab when it
         // lost focus
         state.emit_event(WindowEvent::Focused(true));
+        unsafe { view::update_secure_input(*state.ns_view, true) };
+        state.emit_tab_detached_event();
     });
     trace!("Completed `windowDidBecomeKey:`");
 }
@@ -345,8 +513,13 @@ extern "C" fn window_did_resign_key(this: &Object, _: Sel, _: id) {
         if !view_state.modifiers.is_empty() {
             view_state.modifiers = ModifiersState::empty();
             state.emit_event(WindowEvent::ModifiersChanged(view_state.modifiers));
+            state.emit_event(WindowEvent::DeviceModifiersChanged {
+                device_id: DEVICE_ID,
+                modifiers: view_state.modifiers,
+            });
         }
 
+        unsafe { view::update_secure_input(*state.ns_view, false) };
         state.emit_event(WindowEvent::Focused(false));
     });
     trace!("Completed `windowDidResignKey:`");
@@ -411,6 +584,44 @@ extern "C" fn perform_drag_operation(this: &Object, _: Sel, sender: id) -> BOOL
         }
     }
 
+    // The data dragged from our windows is read straight from its provider.
+    let source: id = unsafe { msg_send![sender, draggingSource] };
+    let local_drag = unsafe { view::local_drag(source) };
+    let content = match local_drag.as_ref() {
+        Some((_, provider)) => {
+            unsafe { view::set_dropped_inside(source) };
+            crate::dnd::local_drop_content(&**provider)
+        }
+        None => {
+            let picker =
+                Box::new(|offered: &[MimeType]| MimeType::preferred_drop_mime_type(offered));
+            unsafe { clipboard::read_pasteboard(pb, picker) }
+        }
+    };
+    if let Some(content) = content {
+        // The location is in the window coordinates, with the origin at the bottom left.
+        let location: NSPoint = unsafe { msg_send![sender, draggingLocation] };
+        with_state(this, |state| {
+            let (view_point, view_rect) = unsafe {
+                (
+                    state.ns_view.convertPoint_fromView_(location, nil),
+                    NSView::frame(*state.ns_view),
+                )
+            };
+            let x = view_point.x as f64;
+            let y = view_rect.size.height as f64 - view_point.y as f64;
+            let position = LogicalPosition::new(x, y).to_physical(state.get_scale_factor());
+            // The drops are accepted as copies, since `YES` returned from `draggingEntered:` is
+            // the value of `NSDragOperationCopy`.
+            state.emit_event(WindowEvent::Drop {
+                content,
+                position,
+                action: DndAction::Copy,
+                source: local_drag.map(|(source, _)| source),
+            });
+        });
+    }
+
     trace!("Completed `performDragOperation:`");
     YES
 }
@@ -449,7 +660,7 @@ extern "C" fn window_will_enter_fullscreen(this: &Object, _: Sel, _: id) {
                 // `window_will_enter_fullscreen` was triggered and we're already
                 // in fullscreen, so we must've reached here by `set_fullscreen`
                 // as it updates the state
-                Some(Fullscreen::Borderless(_)) => (),
+                Some(Fullscreen::Borderless(_)) | Some(Fullscreen::Span(_)) => (),
                 // Otherwise, we must've reached fullscreen by the user clicking
                 // on the green fullscreen button. Update state!
                 None => {
@@ -540,7 +751,9 @@ extern "C" fn window_did_exit_fullscreen(this: &Object, _: Sel, _: id) {
             if let Some(target_fullscreen) = target_fullscreen {
                 window.set_fullscreen(target_fullscreen);
             }
-        })
+        });
+        // The window left the split view it may have been in.
+        state.emit_tiled_state_event();
     });
     trace!("Completed `windowDidExitFullscreen:`");
 }
@@ -1,5 +1,21 @@
 pub use self::platform::*;
 
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    all(
+        feature = "x11",
+        any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )
+    )
+))]
+mod edid;
+
 #[cfg(target_os = "windows")]
 #[path = "windows/mod.rs"]
 mod platform;
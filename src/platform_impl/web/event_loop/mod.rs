@@ -3,10 +3,11 @@ mod runner;
 mod state;
 mod window_target;
 
-pub use self::proxy::Proxy;
+pub use self::proxy::{Proxy, Wakeup};
 pub use self::window_target::WindowTarget;
 
 use super::{backend, device, window};
+use crate::error::{ExternalError, NotSupportedError};
 use crate::event::Event;
 use crate::event_loop as root;
 
@@ -26,6 +27,10 @@ impl<T> EventLoop<T> {
         }
     }
 
+    pub fn new_secondary() -> Result<Self, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn run<F>(self, mut event_handler: F) -> !
     where
         F: 'static + FnMut(Event<'_, T>, &root::EventLoopWindowTarget<T>, &mut root::ControlFlow),
@@ -52,6 +57,10 @@ impl<T> EventLoop<T> {
         self.elw.p.proxy()
     }
 
+    pub fn create_wakeup(&self) -> Wakeup {
+        Wakeup::new(self.elw.p.runner.clone())
+    }
+
     pub fn window_target(&self) -> &root::EventLoopWindowTarget<T> {
         &self.elw
     }
@@ -2,6 +2,8 @@ use super::runner;
 use crate::event::Event;
 use crate::event_loop::EventLoopClosed;
 
+use std::iter;
+
 pub struct Proxy<T: 'static> {
     runner: runner::Shared<T>,
 }
@@ -24,3 +26,20 @@ impl<T: 'static> Clone for Proxy<T> {
         }
     }
 }
+
+pub struct Wakeup {
+    wake_up: Box<dyn Fn()>,
+}
+
+impl Wakeup {
+    pub fn new<T: 'static>(runner: runner::Shared<T>) -> Self {
+        Wakeup {
+            // Running the event loop without any event goes through a whole iteration.
+            wake_up: Box::new(move || runner.send_events(iter::empty())),
+        }
+    }
+
+    pub fn wake_up(&self) {
+        (self.wake_up)()
+    }
+}
@@ -1,15 +1,24 @@
 use super::{super::monitor, backend, device, proxy::Proxy, runner, window};
-use crate::dpi::{PhysicalSize, Size};
+use crate::clipboard::{ClipboardKind, ClipboardProvider};
+use crate::dpi::{PhysicalPosition, PhysicalSize, Size};
+use crate::error::{ExternalError, NotSupportedError};
 use crate::event::{
-    DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, TouchPhase, WindowEvent,
+    DeviceEvent, DeviceId, ElementState, Event, InputDevice, KeyboardInput, ModifiersState,
+    ScanCode, TouchPhase, WindowEvent,
 };
-use crate::event_loop::ControlFlow;
+use crate::event_loop::{ControlFlow, HapticPattern, Timer, TimerId};
+use crate::gamepad::GamepadId;
+use crate::hotkey::{Accelerator, HotkeyId};
+use crate::icon::{BadIcon, RgbaIcon};
+use crate::keymap::{KeyRepeat, KeyboardLayout, LockKeys};
 use crate::monitor::MonitorHandle as RootMH;
-use crate::window::{Theme, WindowId};
+use crate::window::{CursorFrame, Theme, WindowId};
 use std::cell::RefCell;
 use std::clone::Clone;
 use std::collections::{vec_deque::IntoIter as VecDequeIter, VecDeque};
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 
 pub struct WindowTarget<T: 'static> {
     pub(crate) runner: runner::Shared<T>,
@@ -68,7 +77,7 @@ impl<T> WindowTarget<T> {
         });
 
         let runner = self.runner.clone();
-        canvas.on_keyboard_press(move |scancode, virtual_keycode, modifiers| {
+        canvas.on_keyboard_press(move |scancode, virtual_keycode, modifiers, repeat| {
             #[allow(deprecated)]
             runner.send_event(Event::WindowEvent {
                 window_id: WindowId(id),
@@ -77,6 +86,7 @@ impl<T> WindowTarget<T> {
                     input: KeyboardInput {
                         scancode,
                         state: ElementState::Pressed,
+                        repeat,
                         virtual_keycode,
                         modifiers,
                     },
@@ -95,6 +105,7 @@ impl<T> WindowTarget<T> {
                     input: KeyboardInput {
                         scancode,
                         state: ElementState::Released,
+                        repeat: false,
                         virtual_keycode,
                         modifiers,
                     },
@@ -112,21 +123,23 @@ impl<T> WindowTarget<T> {
         });
 
         let runner = self.runner.clone();
-        canvas.on_cursor_leave(move |pointer_id| {
+        canvas.on_cursor_leave(move |pointer_id, position| {
             runner.send_event(Event::WindowEvent {
                 window_id: WindowId(id),
                 event: WindowEvent::CursorLeft {
                     device_id: DeviceId(device::Id(pointer_id)),
+                    position,
                 },
             });
         });
 
         let runner = self.runner.clone();
-        canvas.on_cursor_enter(move |pointer_id| {
+        canvas.on_cursor_enter(move |pointer_id, position| {
             runner.send_event(Event::WindowEvent {
                 window_id: WindowId(id),
                 event: WindowEvent::CursorEntered {
                     device_id: DeviceId(device::Id(pointer_id)),
+                    position,
                 },
             });
         });
@@ -196,6 +209,7 @@ impl<T> WindowTarget<T> {
                     device_id: DeviceId(device::Id(pointer_id)),
                     delta,
                     phase: TouchPhase::Moved,
+                    source: None,
                     modifiers,
                 },
             });
@@ -231,6 +245,17 @@ impl<T> WindowTarget<T> {
             runner.request_redraw(WindowId(id));
         });
 
+        let runner = self.runner.clone();
+        canvas.on_drop(move |files| {
+            let runner = runner.clone();
+            backend::read_dropped_files(files, move |files| {
+                runner.send_events(files.into_iter().map(|file| Event::WindowEvent {
+                    window_id: WindowId(id),
+                    event: WindowEvent::DroppedFileHandle(file),
+                }));
+            });
+        });
+
         let runner = self.runner.clone();
         canvas.on_dark_mode(move |is_dark_mode| {
             let theme = if is_dark_mode {
@@ -254,4 +279,108 @@ impl<T> WindowTarget<T> {
             inner: monitor::Handle,
         })
     }
+
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        window::set_clipboard(kind, &*provider)
+    }
+
+    pub fn create_custom_cursor(
+        &self,
+        image: RgbaIcon,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<window::CustomCursor, BadIcon> {
+        window::CustomCursor::new(&image, hotspot)
+    }
+
+    pub fn create_animated_cursor(
+        &self,
+        frames: Vec<CursorFrame>,
+    ) -> Result<window::CustomCursor, BadIcon> {
+        window::CustomCursor::new_animated(&frames)
+    }
+
+    pub fn set_timer(&self, _id: TimerId, _timer: Timer) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn cancel_timer(&self, _id: TimerId) -> bool {
+        false
+    }
+
+    pub fn set_precise_wait(&self, _precise: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_gamepads_enabled(&self, _enabled: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn rumble_gamepad(
+        &self,
+        _gamepad_id: GamepadId,
+        _strong: f64,
+        _weak: f64,
+        _duration: Duration,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn perform_haptic_feedback(&self, _pattern: HapticPattern) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn input_devices(&self) -> Result<Vec<InputDevice>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_hid_reports_enabled(
+        &self,
+        _device: device::Id,
+        _enabled: bool,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn keyboard_layouts(&self) -> Result<Vec<KeyboardLayout>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn current_keyboard_layout(&self) -> Result<KeyboardLayout, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn scancode_to_text(
+        &self,
+        _scancode: ScanCode,
+        _modifiers: ModifiersState,
+    ) -> Result<Option<String>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn key_repeat(&self) -> Result<Option<KeyRepeat>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_lock_keys(&self, _lock_keys: LockKeys, _on: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn register_global_hotkey(
+        &self,
+        _accelerator: Accelerator,
+    ) -> Result<HotkeyId, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn unregister_global_hotkey(&self, _id: HotkeyId) -> bool {
+        false
+    }
 }
@@ -29,16 +29,27 @@ mod backend;
 pub use self::device::Id as DeviceId;
 pub use self::error::OsError;
 pub use self::event_loop::{
-    EventLoop, Proxy as EventLoopProxy, WindowTarget as EventLoopWindowTarget,
+    EventLoop, Proxy as EventLoopProxy, Wakeup as EventLoopWakeup,
+    WindowTarget as EventLoopWindowTarget,
 };
 pub use self::monitor::{Handle as MonitorHandle, Mode as VideoMode};
 pub use self::window::{
-    Id as WindowId, PlatformSpecificBuilderAttributes as PlatformSpecificWindowBuilderAttributes,
-    Window,
+    ChildSurface, CustomCursor, Id as WindowId,
+    PlatformSpecificBuilderAttributes as PlatformSpecificWindowBuilderAttributes, Window,
 };
 
+use crate::event::{ScanCode, VirtualKeyCode};
+
 pub(crate) use crate::icon::NoIcon as PlatformIcon;
 
+pub fn scancode_to_key(_scancode: ScanCode) -> Option<VirtualKeyCode> {
+    None
+}
+
+pub fn key_to_scancode(_key: VirtualKeyCode) -> Option<ScanCode> {
+    None
+}
+
 #[derive(Clone, Copy)]
 pub(crate) struct ScaleChangeArgs {
     old_scale: f64,
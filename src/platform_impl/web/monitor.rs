@@ -1,5 +1,5 @@
 use crate::dpi::{PhysicalPosition, PhysicalSize};
-use crate::monitor::{MonitorHandle, VideoMode};
+use crate::monitor::{ColorCapabilities, MonitorHandle, MonitorIdentity, VideoMode};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Handle;
@@ -24,6 +24,22 @@ impl Handle {
         }
     }
 
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        ColorCapabilities::default()
+    }
+
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        (self.position(), self.size())
+    }
+
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        None
+    }
+
+    pub fn supports_variable_refresh(&self) -> bool {
+        false
+    }
+
     pub fn video_modes(&self) -> impl Iterator<Item = VideoMode> {
         std::iter::empty()
     }
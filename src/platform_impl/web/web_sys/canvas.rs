@@ -11,7 +11,7 @@ use std::rc::Rc;
 
 use wasm_bindgen::{closure::Closure, JsCast};
 use web_sys::{
-    AddEventListenerOptions, Event, FocusEvent, HtmlCanvasElement, KeyboardEvent,
+    AddEventListenerOptions, DragEvent, Event, File, FocusEvent, HtmlCanvasElement, KeyboardEvent,
     MediaQueryListEvent, MouseEvent, WheelEvent,
 };
 
@@ -29,6 +29,8 @@ pub struct Canvas {
     on_mouse_wheel: Option<EventListenerHandle<dyn FnMut(WheelEvent)>>,
     on_fullscreen_change: Option<EventListenerHandle<dyn FnMut(Event)>>,
     on_dark_mode: Option<MediaQueryListHandle>,
+    on_drag_over: Option<EventListenerHandle<dyn FnMut(DragEvent)>>,
+    on_drop: Option<EventListenerHandle<dyn FnMut(DragEvent)>>,
     mouse_state: MouseState,
 }
 
@@ -36,6 +38,7 @@ struct Common {
     /// Note: resizing the HTMLCanvasElement should go through `backend::set_canvas_size` to ensure the DPI factor is maintained.
     raw: HtmlCanvasElement,
     wants_fullscreen: Rc<RefCell<bool>>,
+    wants_pointer_lock: Rc<RefCell<bool>>,
 }
 
 impl Canvas {
@@ -76,6 +79,7 @@ impl Canvas {
             common: Common {
                 raw: canvas,
                 wants_fullscreen: Rc::new(RefCell::new(false)),
+                wants_pointer_lock: Rc::new(RefCell::new(false)),
             },
             on_blur: None,
             on_focus: None,
@@ -85,6 +89,8 @@ impl Canvas {
             on_mouse_wheel: None,
             on_fullscreen_change: None,
             on_dark_mode: None,
+            on_drag_over: None,
+            on_drop: None,
             mouse_state,
         })
     }
@@ -153,7 +159,7 @@ impl Canvas {
 
     pub fn on_keyboard_press<F>(&mut self, mut handler: F)
     where
-        F: 'static + FnMut(ScanCode, Option<VirtualKeyCode>, ModifiersState),
+        F: 'static + FnMut(ScanCode, Option<VirtualKeyCode>, ModifiersState, bool),
     {
         self.on_keyboard_press = Some(self.common.add_user_event(
             "keydown",
@@ -173,6 +179,7 @@ impl Canvas {
                     event::scan_code(&event),
                     event::virtual_key_code(&event),
                     event::keyboard_modifiers(&event),
+                    event.repeat(),
                 );
             },
         ));
@@ -199,7 +206,7 @@ impl Canvas {
 
     pub fn on_cursor_leave<F>(&mut self, handler: F)
     where
-        F: 'static + FnMut(i32),
+        F: 'static + FnMut(i32, PhysicalPosition<f64>),
     {
         match &mut self.mouse_state {
             MouseState::HasPointerEvent(h) => h.on_cursor_leave(&self.common, handler),
@@ -209,7 +216,7 @@ impl Canvas {
 
     pub fn on_cursor_enter<F>(&mut self, handler: F)
     where
-        F: 'static + FnMut(i32),
+        F: 'static + FnMut(i32, PhysicalPosition<f64>),
     {
         match &mut self.mouse_state {
             MouseState::HasPointerEvent(h) => h.on_cursor_enter(&self.common, handler),
@@ -281,6 +288,31 @@ impl Canvas {
         self.on_dark_mode = MediaQueryListHandle::new("(prefers-color-scheme: dark)", closure);
     }
 
+    pub fn on_drop<F>(&mut self, mut handler: F)
+    where
+        F: 'static + FnMut(Vec<File>),
+    {
+        // The drop is only allowed over the elements cancelling the `dragover` events.
+        self.on_drag_over = Some(self.common.add_event("dragover", |event: DragEvent| {
+            if let Some(data_transfer) = event.data_transfer() {
+                if data_transfer.types().includes(&"Files".into(), 0) {
+                    event.prevent_default();
+                    data_transfer.set_drop_effect("copy");
+                }
+            }
+        }));
+        self.on_drop = Some(self.common.add_event("drop", move |event: DragEvent| {
+            let files = match event.data_transfer().and_then(|data| data.files()) {
+                Some(files) => files,
+                None => return,
+            };
+            // Keep the browser from opening the files.
+            event.prevent_default();
+            let files = (0..files.length()).filter_map(|index| files.get(index));
+            handler(files.collect());
+        }));
+    }
+
     pub fn request_fullscreen(&self) {
         self.common.request_fullscreen()
     }
@@ -289,6 +321,14 @@ impl Canvas {
         self.common.is_fullscreen()
     }
 
+    pub fn request_pointer_lock(&self) {
+        self.common.request_pointer_lock()
+    }
+
+    pub fn exit_pointer_lock(&self) {
+        self.common.exit_pointer_lock()
+    }
+
     pub fn remove_listeners(&mut self) {
         self.on_focus = None;
         self.on_blur = None;
@@ -298,6 +338,8 @@ impl Canvas {
         self.on_mouse_wheel = None;
         self.on_fullscreen_change = None;
         self.on_dark_mode = None;
+        self.on_drag_over = None;
+        self.on_drop = None;
         match &mut self.mouse_state {
             MouseState::HasPointerEvent(h) => h.remove_listeners(),
             MouseState::NoPointerEvent(h) => h.remove_listeners(),
@@ -343,6 +385,7 @@ impl Common {
         F: 'static + FnMut(E),
     {
         let wants_fullscreen = self.wants_fullscreen.clone();
+        let wants_pointer_lock = self.wants_pointer_lock.clone();
         let canvas = self.raw.clone();
 
         self.add_event(event_name, move |event: E| {
@@ -354,6 +397,10 @@ impl Common {
                     .expect("Failed to enter fullscreen");
                 *wants_fullscreen.borrow_mut() = false;
             }
+            if *wants_pointer_lock.borrow() {
+                canvas.request_pointer_lock();
+                *wants_pointer_lock.borrow_mut() = false;
+            }
         })
     }
 
@@ -370,6 +417,7 @@ impl Common {
         F: 'static + FnMut(MouseEvent),
     {
         let wants_fullscreen = self.wants_fullscreen.clone();
+        let wants_pointer_lock = self.wants_pointer_lock.clone();
         let canvas = self.raw.clone();
         let window = web_sys::window().expect("Failed to obtain window");
 
@@ -382,6 +430,10 @@ impl Common {
                     .expect("Failed to enter fullscreen");
                 *wants_fullscreen.borrow_mut() = false;
             }
+            if *wants_pointer_lock.borrow() {
+                canvas.request_pointer_lock();
+                *wants_pointer_lock.borrow_mut() = false;
+            }
         }) as Box<dyn FnMut(_)>);
 
         let listener = EventListenerHandle::with_options(
@@ -401,6 +453,19 @@ impl Common {
     pub fn is_fullscreen(&self) -> bool {
         super::is_fullscreen(&self.raw)
     }
+
+    // Like the fullscreen, the lock is only granted along with an input of the user.
+    pub fn request_pointer_lock(&self) {
+        *self.wants_pointer_lock.borrow_mut() = true;
+    }
+
+    pub fn exit_pointer_lock(&self) {
+        *self.wants_pointer_lock.borrow_mut() = false;
+        let window = web_sys::window().expect("Failed to obtain window");
+        let document = window.document().expect("Failed to obtain document");
+
+        document.exit_pointer_lock();
+    }
 }
 
 enum MouseState {
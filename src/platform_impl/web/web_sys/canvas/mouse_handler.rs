@@ -15,7 +15,7 @@ pub(super) struct MouseHandler {
     on_mouse_move: Option<EventListenerHandle<dyn FnMut(MouseEvent)>>,
     on_mouse_press: Option<EventListenerHandle<dyn FnMut(MouseEvent)>>,
     on_mouse_release: Option<EventListenerHandle<dyn FnMut(MouseEvent)>>,
-    on_mouse_leave_handler: Rc<RefCell<Option<Box<dyn FnMut(i32)>>>>,
+    on_mouse_leave_handler: Rc<RefCell<Option<Box<dyn FnMut(i32, PhysicalPosition<f64>)>>>>,
     mouse_capture_state: Rc<RefCell<MouseCaptureState>>,
 }
 
@@ -40,35 +40,47 @@ impl MouseHandler {
     }
     pub fn on_cursor_leave<F>(&mut self, canvas_common: &super::Common, handler: F)
     where
-        F: 'static + FnMut(i32),
+        F: 'static + FnMut(i32, PhysicalPosition<f64>),
     {
         *self.on_mouse_leave_handler.borrow_mut() = Some(Box::new(handler));
         let on_mouse_leave_handler = self.on_mouse_leave_handler.clone();
         let mouse_capture_state = self.mouse_capture_state.clone();
-        self.on_mouse_leave = Some(canvas_common.add_event("mouseout", move |_: MouseEvent| {
-            // If the mouse is being captured, it is always considered
-            // to be "within" the the canvas, until the capture has been
-            // released, therefore we don't send cursor leave events.
-            if *mouse_capture_state.borrow() != MouseCaptureState::Captured {
-                if let Some(handler) = on_mouse_leave_handler.borrow_mut().as_mut() {
-                    handler(0);
+        self.on_mouse_leave = Some(canvas_common.add_event(
+            "mouseout",
+            move |event: MouseEvent| {
+                // If the mouse is being captured, it is always considered
+                // to be "within" the the canvas, until the capture has been
+                // released, therefore we don't send cursor leave events.
+                if *mouse_capture_state.borrow() != MouseCaptureState::Captured {
+                    if let Some(handler) = on_mouse_leave_handler.borrow_mut().as_mut() {
+                        handler(
+                            0,
+                            event::mouse_position(&event).to_physical(super::super::scale_factor()),
+                        );
+                    }
                 }
-            }
-        }));
+            },
+        ));
     }
 
     pub fn on_cursor_enter<F>(&mut self, canvas_common: &super::Common, mut handler: F)
     where
-        F: 'static + FnMut(i32),
+        F: 'static + FnMut(i32, PhysicalPosition<f64>),
     {
         let mouse_capture_state = self.mouse_capture_state.clone();
-        self.on_mouse_enter = Some(canvas_common.add_event("mouseover", move |_: MouseEvent| {
-            // We don't send cursor leave events when the mouse is being
-            // captured, therefore we do the same with cursor enter events.
-            if *mouse_capture_state.borrow() != MouseCaptureState::Captured {
-                handler(0);
-            }
-        }));
+        self.on_mouse_enter = Some(canvas_common.add_event(
+            "mouseover",
+            move |event: MouseEvent| {
+                // We don't send cursor leave events when the mouse is being
+                // captured, therefore we do the same with cursor enter events.
+                if *mouse_capture_state.borrow() != MouseCaptureState::Captured {
+                    handler(
+                        0,
+                        event::mouse_position(&event).to_physical(super::super::scale_factor()),
+                    );
+                }
+            },
+        ));
     }
 
     pub fn on_mouse_release<F>(&mut self, canvas_common: &super::Common, mut handler: F)
@@ -102,6 +114,9 @@ impl MouseHandler {
                     event::mouse_button(&event),
                     event::mouse_modifiers(&event),
                 );
+                // The event is of the window, so `offsetX`/`offsetY` aren't
+                // relative to the canvas.
+                let position = event::mouse_position_by_client(&event, &canvas);
                 if event
                     .target()
                     .map_or(false, |target| target != EventTarget::from(canvas))
@@ -110,7 +125,7 @@ impl MouseHandler {
                     // cursor is being captured, we instead send it after
                     // the capture has been released.
                     if let Some(handler) = on_mouse_leave_handler.borrow_mut().as_mut() {
-                        handler(0);
+                        handler(0, position.to_physical(super::super::scale_factor()));
                     }
                 }
                 if event.buttons() == 0 {
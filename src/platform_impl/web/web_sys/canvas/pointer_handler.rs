@@ -27,24 +27,30 @@ impl PointerHandler {
 
     pub fn on_cursor_leave<F>(&mut self, canvas_common: &super::Common, mut handler: F)
     where
-        F: 'static + FnMut(i32),
+        F: 'static + FnMut(i32, PhysicalPosition<f64>),
     {
         self.on_cursor_leave = Some(canvas_common.add_event(
             "pointerout",
             move |event: PointerEvent| {
-                handler(event.pointer_id());
+                handler(
+                    event.pointer_id(),
+                    event::mouse_position(&event).to_physical(super::super::scale_factor()),
+                );
             },
         ));
     }
 
     pub fn on_cursor_enter<F>(&mut self, canvas_common: &super::Common, mut handler: F)
     where
-        F: 'static + FnMut(i32),
+        F: 'static + FnMut(i32, PhysicalPosition<f64>),
     {
         self.on_cursor_enter = Some(canvas_common.add_event(
             "pointerover",
             move |event: PointerEvent| {
-                handler(event.pointer_id());
+                handler(
+                    event.pointer_id(),
+                    event::mouse_position(&event).to_physical(super::super::scale_factor()),
+                );
             },
         ));
     }
@@ -0,0 +1,178 @@
+//! Access to the clipboard through the asynchronous Clipboard API.
+//!
+//! The API is permission-gated and isn't part of the stable `web-sys` bindings yet, so its
+//! methods are looked up dynamically.
+
+use crate::clipboard::MimeType;
+
+use js_sys::{Array, Function, Object, Reflect, Uint8Array};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag};
+
+/// The MIME type the data of the given kind is exchanged with browsers under.
+///
+/// Browsers only support a few kinds of data, along with the custom formats whose MIME types
+/// are prefixed with `web `.
+fn web_mime(mime_type: &MimeType) -> Option<&str> {
+    match mime_type {
+        MimeType::Text => Some("text/plain"),
+        MimeType::Html => Some("text/html"),
+        MimeType::PngImage => Some("image/png"),
+        MimeType::Other(mime) if mime.starts_with("web ") => Some(mime),
+        _ => None,
+    }
+}
+
+/// Whether the data of the given kind can be placed into the clipboard.
+pub fn is_clipboard_mime_type_supported(mime_type: &MimeType) -> bool {
+    web_mime(mime_type).is_some()
+}
+
+/// The `navigator.clipboard` object, which is only available in secure contexts.
+fn clipboard() -> Option<Object> {
+    let window = web_sys::window().expect("Failed to obtain window");
+    Reflect::get(&window.navigator(), &JsValue::from_str("clipboard"))
+        .ok()
+        .and_then(|clipboard| clipboard.dyn_into().ok())
+}
+
+/// Whether the page can access the clipboard at all.
+pub fn is_clipboard_supported() -> bool {
+    let has_item =
+        Reflect::has(&js_sys::global(), &JsValue::from_str("ClipboardItem")).unwrap_or(false);
+    has_item && clipboard().is_some()
+}
+
+pub(super) fn call_method(target: &JsValue, name: &str, args: &Array) -> Result<JsValue, JsValue> {
+    let method: Function = Reflect::get(target, &JsValue::from_str(name))?.dyn_into()?;
+    method.apply(target, args)
+}
+
+/// Calls `handler` with the outcome of the promise once it settles.
+pub(super) fn when_settled<F>(promise: Result<JsValue, JsValue>, handler: F)
+where
+    F: FnOnce(Result<JsValue, JsValue>) + 'static,
+{
+    let promise = match promise {
+        Ok(promise) => promise,
+        Err(err) => return handler(Err(err)),
+    };
+
+    // `Promise.allSettled` always fulfills, so a single callback covers both of the outcomes.
+    let settled = Reflect::get(&js_sys::global(), &JsValue::from_str("Promise"))
+        .and_then(|promise_class| call_method(&promise_class, "allSettled", &Array::of1(&promise)));
+    let settled = match settled {
+        Ok(settled) => settled,
+        Err(err) => return handler(Err(err)),
+    };
+
+    let callback = Closure::once_into_js(move |results: JsValue| {
+        let result = Array::from(&results).get(0);
+        let fulfilled = Reflect::get(&result, &JsValue::from_str("status"))
+            .ok()
+            .and_then(|status| status.as_string())
+            .map_or(false, |status| status == "fulfilled");
+        if fulfilled {
+            handler(Reflect::get(&result, &JsValue::from_str("value")))
+        } else {
+            let reason = Reflect::get(&result, &JsValue::from_str("reason"));
+            handler(Err(reason.unwrap_or(JsValue::UNDEFINED)))
+        }
+    });
+    let _ = call_method(&settled, "then", &Array::of1(&callback));
+}
+
+/// Places the data into the clipboard.
+///
+/// Returns an error if the write couldn't be started. The failures of the write itself, like
+/// the user denying the permission, are only logged, since they're reported asynchronously.
+pub fn write_clipboard(data: Vec<(MimeType, Vec<u8>)>) -> Result<(), JsValue> {
+    let clipboard = clipboard().ok_or_else(|| JsValue::from_str("no clipboard access"))?;
+
+    let record = Object::new();
+    for (mime_type, data) in &data {
+        let mime = match web_mime(mime_type) {
+            Some(mime) => mime,
+            None => continue,
+        };
+
+        let mut options = BlobPropertyBag::new();
+        options.type_(mime);
+        let parts = Array::of1(&Uint8Array::from(data.as_slice()));
+        let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+        Reflect::set(&record, &JsValue::from_str(mime), &blob)?;
+    }
+
+    let item_class: Function =
+        Reflect::get(&js_sys::global(), &JsValue::from_str("ClipboardItem"))?.dyn_into()?;
+    let item = Reflect::construct(&item_class, &Array::of1(&record))?;
+
+    let written = call_method(&clipboard, "write", &Array::of1(&Array::of1(&item)));
+    when_settled(written, |result| {
+        if let Err(err) = result {
+            warn!("Failed to write the clipboard: {:?}", err);
+        }
+    });
+
+    Ok(())
+}
+
+/// Reads the data of the kind chosen by `picker` from the clipboard.
+///
+/// `on_read` is called with `None` in case the data couldn't be read.
+pub fn read_clipboard<P, F>(picker: P, on_read: F)
+where
+    P: FnOnce(&[MimeType]) -> Option<MimeType> + 'static,
+    F: FnOnce(Option<(MimeType, Vec<u8>)>) + 'static,
+{
+    let clipboard = match clipboard() {
+        Some(clipboard) => clipboard,
+        None => return on_read(None),
+    };
+
+    let items = call_method(&clipboard, "read", &Array::new());
+    when_settled(items, move |items| {
+        // Only the first item is read, since the clipboard holds a single item on every
+        // platform but iOS.
+        let item = match items {
+            Ok(items) => Array::from(&items).get(0),
+            Err(err) => {
+                warn!("Failed to read the clipboard: {:?}", err);
+                return on_read(None);
+            }
+        };
+
+        let mimes = Reflect::get(&item, &JsValue::from_str("types"))
+            .map(|mimes| Array::from(&mimes))
+            .unwrap_or_else(|_| Array::new())
+            .iter()
+            .filter_map(|mime| mime.as_string())
+            .collect::<Vec<_>>();
+        let offered = MimeType::from_mime_types(mimes.iter().map(String::as_str));
+
+        let mime_type = match picker(&offered).filter(|mime_type| offered.contains(mime_type)) {
+            Some(mime_type) => mime_type,
+            None => return on_read(None),
+        };
+        let mime = match mime_type
+            .mime_types()
+            .into_iter()
+            .find(|mime| mimes.iter().any(|offered| offered == mime))
+        {
+            Some(mime) => mime.to_owned(),
+            None => return on_read(None),
+        };
+
+        let blob = call_method(&item, "getType", &Array::of1(&JsValue::from_str(&mime)));
+        when_settled(blob, move |blob| {
+            let buffer = blob.and_then(|blob| call_method(&blob, "arrayBuffer", &Array::new()));
+            when_settled(buffer, move |buffer| match buffer {
+                Ok(buffer) => on_read(Some((mime_type, Uint8Array::new(&buffer).to_vec()))),
+                Err(err) => {
+                    warn!("Failed to read the clipboard: {:?}", err);
+                    on_read(None)
+                }
+            });
+        });
+    });
+}
@@ -9,7 +9,9 @@ pub fn mouse_button(event: &MouseEvent) -> MouseButton {
         0 => MouseButton::Left,
         1 => MouseButton::Middle,
         2 => MouseButton::Right,
-        i => MouseButton::Other((i - 3).try_into().expect("very large mouse button value")),
+        3 => MouseButton::Back,
+        4 => MouseButton::Forward,
+        i => MouseButton::Other((i + 1).try_into().expect("very large mouse button value")),
     }
 }
 
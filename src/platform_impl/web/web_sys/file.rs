@@ -0,0 +1,131 @@
+//! Reading the files dropped into the canvas.
+
+use super::clipboard::{call_method, when_settled};
+use crate::dnd::FileHandle;
+use crate::platform::web::FileHandleExtWebSys;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::{Array, Uint8Array};
+use wasm_bindgen::JsValue;
+use web_sys::{Blob, File};
+
+/// Reads the whole content of the blob.
+fn read_blob<F>(blob: &Blob, on_read: F)
+where
+    F: FnOnce(Result<Vec<u8>, JsValue>) + 'static,
+{
+    let buffer = call_method(blob, "arrayBuffer", &Array::new());
+    when_settled(buffer, move |buffer| {
+        on_read(buffer.map(|buffer| Uint8Array::new(&buffer).to_vec()))
+    });
+}
+
+fn read_chunk<F>(blob: Blob, offset: f64, chunk_size: f64, mut on_chunk: F)
+where
+    F: FnMut(Result<Option<Vec<u8>>, JsValue>) + 'static,
+{
+    if offset >= blob.size() {
+        return on_chunk(Ok(None));
+    }
+
+    let end = (offset + chunk_size).min(blob.size());
+    let chunk = match blob.slice_with_f64_and_f64(offset, end) {
+        Ok(chunk) => chunk,
+        Err(err) => return on_chunk(Err(err)),
+    };
+    read_blob(&chunk, move |data| match data {
+        Ok(data) => {
+            on_chunk(Ok(Some(data)));
+            read_chunk(blob, end, chunk_size, on_chunk);
+        }
+        Err(err) => on_chunk(Err(err)),
+    });
+}
+
+fn file_handle(file: File, content: Option<Vec<u8>>) -> FileHandle {
+    FileHandle {
+        name: file.name(),
+        mime: file.type_(),
+        size: file.size() as u64,
+        content: content.map(Into::into),
+        file,
+    }
+}
+
+/// Turns the dropped files into handles, once the content of the small ones is read.
+///
+/// `on_read` is called with the handles in the order of the files.
+pub fn read_dropped_files<F>(files: Vec<File>, on_read: F)
+where
+    F: FnOnce(Vec<FileHandle>) + 'static,
+{
+    let is_small = |file: &File| file.size() as u64 <= FileHandle::READ_AHEAD_LIMIT;
+    let (small, large): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .enumerate()
+        .partition(|(_, file)| is_small(file));
+    let mut handles: Vec<_> = (0..small.len() + large.len()).map(|_| None).collect();
+    for (index, file) in large {
+        handles[index] = Some(file_handle(file, None));
+    }
+    if small.is_empty() {
+        return on_read(handles.into_iter().flatten().collect());
+    }
+
+    struct Pending<F> {
+        handles: Vec<Option<FileHandle>>,
+        remaining: usize,
+        on_read: Option<F>,
+    }
+
+    let pending = Rc::new(RefCell::new(Pending {
+        handles,
+        remaining: small.len(),
+        on_read: Some(on_read),
+    }));
+    for (index, file) in small {
+        let pending = pending.clone();
+        let blob: Blob = file.clone().into();
+        read_blob(&blob, move |content| {
+            let content = content
+                .map_err(|err| warn!("Failed to read the dropped file: {:?}", err))
+                .ok();
+            let mut pending = pending.borrow_mut();
+            pending.handles[index] = Some(file_handle(file, content));
+            pending.remaining -= 1;
+            if pending.remaining == 0 {
+                let handles = pending.handles.drain(..).flatten().collect();
+                if let Some(on_read) = pending.on_read.take() {
+                    drop(pending);
+                    on_read(handles);
+                }
+            }
+        });
+    }
+}
+
+impl FileHandleExtWebSys for FileHandle {
+    fn file(&self) -> File {
+        self.file.clone()
+    }
+
+    fn read<F>(&self, on_read: F)
+    where
+        F: FnOnce(Result<Vec<u8>, JsValue>) + 'static,
+    {
+        if let Some(content) = self.content() {
+            return on_read(Ok(content.to_vec()));
+        }
+        read_blob(&self.file, on_read)
+    }
+
+    fn read_chunks<F>(&self, chunk_size: usize, on_chunk: F)
+    where
+        F: FnMut(Result<Option<Vec<u8>>, JsValue>) + 'static,
+    {
+        let chunk_size = chunk_size.max(1) as f64;
+        read_chunk(self.file.clone().into(), 0.0, chunk_size, on_chunk)
+    }
+}
@@ -1,19 +1,28 @@
 mod canvas;
+mod clipboard;
 mod event;
 mod event_handle;
+mod file;
 mod media_query_handle;
 mod scaling;
 mod timeout;
 
 pub use self::canvas::Canvas;
+pub use self::clipboard::{
+    is_clipboard_mime_type_supported, is_clipboard_supported, read_clipboard, write_clipboard,
+};
+pub use self::file::read_dropped_files;
 pub use self::scaling::ScaleChangeDetector;
 pub use self::timeout::{AnimationFrameRequest, Timeout};
 
 use crate::dpi::{LogicalSize, Size};
+use crate::icon::RgbaIcon;
 use crate::platform::web::WindowExtWebSys;
 use crate::window::Window;
-use wasm_bindgen::closure::Closure;
-use web_sys::{window, BeforeUnloadEvent, Element, HtmlCanvasElement};
+use wasm_bindgen::{closure::Closure, Clamped, JsCast, JsValue};
+use web_sys::{
+    window, BeforeUnloadEvent, CanvasRenderingContext2d, Element, HtmlCanvasElement, ImageData,
+};
 
 pub fn throw(msg: &str) {
     wasm_bindgen::throw_str(msg);
@@ -101,6 +110,26 @@ pub fn set_canvas_style_property(raw: &HtmlCanvasElement, property: &str, value:
         .expect(&format!("Failed to set {}", property));
 }
 
+/// The `data:` URL of a PNG of the image, drawn by a canvas of its size.
+pub fn image_data_url(image: &RgbaIcon) -> Result<String, JsValue> {
+    let window = window().expect("Failed to obtain window");
+    let document = window.document().expect("Failed to obtain document");
+    let canvas: HtmlCanvasElement = document.create_element("canvas")?.unchecked_into();
+    canvas.set_width(image.width);
+    canvas.set_height(image.height);
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or("The canvas has no 2D context")?
+        .unchecked_into();
+    let data = ImageData::new_with_u8_clamped_array_and_sh(
+        Clamped(&image.rgba[..]),
+        image.width,
+        image.height,
+    )?;
+    context.put_image_data(&data, 0.0, 0.0)?;
+    canvas.to_data_url()
+}
+
 pub fn is_fullscreen(canvas: &HtmlCanvasElement) -> bool {
     let window = window().expect("Failed to obtain window");
     let document = window.document().expect("Failed to obtain document");
@@ -1,27 +1,98 @@
+use crate::clipboard::{
+    self, ClipboardConsumer, ClipboardKind, ClipboardMimedContent, ClipboardProvider, MimePicker,
+};
+use crate::dnd::{DndActions, DragData};
 use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
-use crate::error::{ExternalError, NotSupportedError, OsError as RootOE};
+use crate::error::{ExternalError, HandleError, NotSupportedError, OsError as RootOE};
 use crate::event;
-use crate::icon::Icon;
+use crate::icon::{BadIcon, Icon, RgbaIcon};
 use crate::monitor::MonitorHandle as RootMH;
 use crate::window::{
-    CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWI,
+    Backdrop, CursorFrame, CursorIcon, CursorTheme, Fullscreen, ImePurpose, ProgressState, Ratio,
+    UserAttentionType, WindowAttributes, WindowId as RootWI,
 };
 
 use raw_window_handle::web::WebHandle;
 
-use super::{backend, monitor, EventLoopWindowTarget};
+use super::{backend, monitor, EventLoopWindowTarget, OsError};
 
+use std::borrow::Cow;
 use std::cell::{Ref, RefCell};
 use std::collections::vec_deque::IntoIter as VecDequeIter;
 use std::collections::VecDeque;
-use std::rc::Rc;
+use std::io;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A child surface, which is never created as it's unsupported on the web.
+pub struct ChildSurface;
+
+impl ChildSurface {
+    pub fn new<T>(
+        _target: &EventLoopWindowTarget<T>,
+        _parent: &Window,
+        _position: Position,
+        _size: Size,
+    ) -> Result<Self, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_position(&self, _position: Position) {}
+
+    pub fn set_size(&self, _size: Size) {}
+
+    pub fn window_handle(&self) -> Result<raw_window_handle::RawWindowHandle, HandleError> {
+        Err(HandleError::Unavailable)
+    }
+}
+
+/// A cursor made of images, as the values of the CSS `cursor` property showing its frames with
+/// their delays.
+#[derive(Clone)]
+pub struct CustomCursor {
+    frames: Arc<Vec<(String, Duration)>>,
+}
+
+impl CustomCursor {
+    pub fn new(image: &RgbaIcon, hotspot: PhysicalPosition<u32>) -> Result<Self, BadIcon> {
+        let frame = (cursor_css(image, hotspot)?, Duration::from_secs(0));
+        Ok(CustomCursor {
+            frames: Arc::new(vec![frame]),
+        })
+    }
+
+    pub fn new_animated(frames: &[CursorFrame]) -> Result<Self, BadIcon> {
+        let frames = frames
+            .iter()
+            .map(|frame| Ok((cursor_css(&frame.image, frame.hotspot)?, frame.delay)))
+            .collect::<Result<_, BadIcon>>()?;
+        Ok(CustomCursor {
+            frames: Arc::new(frames),
+        })
+    }
+}
+
+fn cursor_css(image: &RgbaIcon, hotspot: PhysicalPosition<u32>) -> Result<String, BadIcon> {
+    let url = backend::image_data_url(image).map_err(|err| {
+        BadIcon::OsError(io::Error::other(format!(
+            "Failed to draw the cursor: {:?}",
+            err
+        )))
+    })?;
+    // The browsers fall back to the default cursor when the image is too large for them.
+    Ok(format!("url({}) {} {}, auto", url, hotspot.x, hotspot.y))
+}
 
 pub struct Window {
     canvas: Rc<RefCell<backend::Canvas>>,
-    previous_pointer: RefCell<&'static str>,
+    previous_pointer: Rc<RefCell<Cow<'static, str>>>,
+    /// The timeout showing the next frame of the animated custom cursor.
+    cursor_animation: Rc<RefCell<Option<backend::Timeout>>>,
     id: Id,
     register_redraw_request: Box<dyn Fn()>,
     resize_notify_fn: Box<dyn Fn(PhysicalSize<u32>)>,
+    clipboard_data_fn: Rc<dyn Fn(ClipboardKind, Option<ClipboardMimedContent>)>,
     destroy_fn: Option<Box<dyn FnOnce()>>,
 }
 
@@ -50,15 +121,25 @@ impl Window {
             });
         });
 
+        let runner = target.runner.clone();
+        let clipboard_data_fn = Rc::new(move |kind, content| {
+            runner.send_event(event::Event::WindowEvent {
+                window_id: RootWI(id),
+                event: event::WindowEvent::ClipboardData { kind, content },
+            });
+        });
+
         let runner = target.runner.clone();
         let destroy_fn = Box::new(move || runner.notify_destroy_window(RootWI(id)));
 
         let window = Window {
             canvas,
-            previous_pointer: RefCell::new("auto"),
+            previous_pointer: Rc::new(RefCell::new(Cow::Borrowed("auto"))),
+            cursor_animation: Rc::new(RefCell::new(None)),
             id,
             register_redraw_request,
             resize_notify_fn,
+            clipboard_data_fn,
             destroy_fn: Some(destroy_fn),
         };
 
@@ -93,6 +174,23 @@ impl Window {
         (self.register_redraw_request)();
     }
 
+    pub fn set_vsync_redraws(&self, _vsync_redraws: bool) {
+        // Currently a no-op, `ControlFlow::Poll` already runs on the animation frames
+    }
+
+    pub fn pre_present_notify(&self) {
+        // Currently a no-op, the canvas is presented once the animation frame returns
+    }
+
+    #[cfg(feature = "software-present")]
+    pub fn present_pixels(
+        &self,
+        _pixels: &[u32],
+        _size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         Ok(self
             .canvas
@@ -126,6 +224,12 @@ impl Window {
         self.inner_size()
     }
 
+    #[inline]
+    pub fn recommended_bounds(&self) -> Option<PhysicalSize<u32>> {
+        // Intentionally `None`: the canvas is laid out by the page
+        None
+    }
+
     #[inline]
     pub fn set_inner_size(&self, size: Size) {
         let old_size = self.inner_size();
@@ -146,6 +250,11 @@ impl Window {
         // Intentionally a no-op: users can't resize canvas elements
     }
 
+    #[inline]
+    pub fn set_aspect_ratio(&self, _ratio: Option<Ratio>) {
+        // Intentionally a no-op: users can't resize canvas elements
+    }
+
     #[inline]
     pub fn set_resizable(&self, _resizable: bool) {
         // Intentionally a no-op: users can't resize canvas elements
@@ -197,10 +306,32 @@ impl Window {
             CursorIcon::ColResize => "col-resize",
             CursorIcon::RowResize => "row-resize",
         };
-        *self.previous_pointer.borrow_mut() = text;
+        self.cursor_animation.borrow_mut().take();
+        *self.previous_pointer.borrow_mut() = Cow::Borrowed(text);
         backend::set_canvas_style_property(self.canvas.borrow().raw(), "cursor", text);
     }
 
+    #[inline]
+    pub fn set_cursor(&self, cursor: CustomCursor) {
+        show_cursor_frame(
+            &self.canvas,
+            &self.previous_pointer,
+            &Rc::downgrade(&self.cursor_animation),
+            cursor,
+            0,
+        );
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, _name: &str, _size: u32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        None
+    }
+
     #[inline]
     pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
@@ -211,6 +342,35 @@ impl Window {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
+    #[inline]
+    pub fn set_cursor_grab_area(
+        &self,
+        _area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_pointer_lock(&self, lock: bool) -> Result<(), ExternalError> {
+        let canvas = self.canvas.borrow();
+        if lock {
+            canvas.request_pointer_lock();
+        } else {
+            canvas.exit_pointer_lock();
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         if !visible {
@@ -218,7 +378,7 @@ impl Window {
         } else {
             self.canvas
                 .borrow()
-                .set_attribute("cursor", *self.previous_pointer.borrow());
+                .set_attribute("cursor", &self.previous_pointer.borrow());
         }
     }
 
@@ -266,6 +426,21 @@ impl Window {
         // Intentionally a no-op, no canvas decorations
     }
 
+    #[inline]
+    pub fn set_shadow(&self, _shadow: bool) {
+        // Intentionally a no-op, no window shadows
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_backdrop(&self, _backdrop: Backdrop) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_always_on_top(&self, _always_on_top: bool) {
         // Intentionally a no-op, no window ordering
@@ -277,10 +452,72 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_ime_position(&self, _position: Position) {
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {
         // Currently a no-op as it does not seem there is good support for this on web
     }
 
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
+    #[inline]
+    pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
+
+    #[inline]
+    pub fn set_key_repeat(&self, _key_repeat: bool) {
+        // Currently a no-op, the repeats are still flagged in the `KeyboardInput`s
+    }
+
+    #[inline]
+    pub fn set_system_shortcuts_inhibited(&self, _inhibited: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        set_clipboard(kind, &*provider)
+    }
+
+    #[inline]
+    pub fn read_clipboard(
+        &self,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), ExternalError> {
+        // There's no primary selection on the web.
+        if kind != ClipboardKind::Clipboard {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        if !backend::is_clipboard_supported() {
+            return Err(ExternalError::Os(os_error!(OsError(
+                "the clipboard is only accessible in secure contexts".to_owned()
+            ))));
+        }
+
+        // The content is read asynchronously, and delivered through the event loop.
+        let clipboard_data_fn = self.clipboard_data_fn.clone();
+        backend::read_clipboard(picker, move |data| match consumer {
+            Some(consumer) => clipboard::consume_bytes(consumer, data),
+            None => {
+                let content = data
+                    .map(|(mime_type, data)| ClipboardMimedContent::from_bytes(mime_type, data));
+                clipboard_data_fn(kind, content)
+            }
+        });
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _data: DragData, _actions: DndActions) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn focus_window(&self) {
         // Currently a no-op as it does not seem there is good support for this on web
@@ -291,6 +528,42 @@ impl Window {
         // Currently an intentional no-op
     }
 
+    #[inline]
+    pub fn tab_group(&self) -> Option<String> {
+        None
+    }
+
+    #[inline]
+    pub fn set_tab_group(&self, _group: &str) {}
+
+    #[inline]
+    pub fn merge_into_tab_group(&self, _window: &Window) {}
+
+    #[inline]
+    pub fn select_next_tab(&self) {}
+
+    #[inline]
+    pub fn workspace(&self) -> Option<u32> {
+        None
+    }
+
+    #[inline]
+    pub fn move_to_workspace(&self, _workspace: u32) {}
+
+    #[inline]
+    pub fn set_visible_on_all_workspaces(&self, _visible: bool) {}
+
+    #[inline]
+    pub fn set_modal(&self, _modal: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_progress(&self, _state: ProgressState) {}
+
+    #[inline]
+    pub fn set_badge(&self, _count: Option<u64>) {}
+
     #[inline]
     // Allow directly accessing the current monitor internally without unwrapping.
     fn current_monitor_inner(&self) -> RootMH {
@@ -316,22 +589,72 @@ impl Window {
         })
     }
 
+    #[inline]
+    pub fn is_vrr_active(&self) -> bool {
+        false
+    }
+
     #[inline]
     pub fn id(&self) -> Id {
         return self.id;
     }
 
     #[inline]
-    pub fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
+    pub fn window_handle(&self) -> Result<raw_window_handle::RawWindowHandle, HandleError> {
         let handle = WebHandle {
             id: self.id.0,
             ..WebHandle::empty()
         };
 
-        raw_window_handle::RawWindowHandle::Web(handle)
+        Ok(raw_window_handle::RawWindowHandle::Web(handle))
+    }
+
+    #[inline]
+    pub fn display_handle(&self) -> Result<raw_window_handle::RawWindowHandle, HandleError> {
+        Ok(raw_window_handle::RawWindowHandle::Web(WebHandle::empty()))
     }
 }
 
+/// Shows the frame of the custom cursor, and the next frame once its delay is over while the
+/// window is still there.
+fn show_cursor_frame(
+    canvas: &Rc<RefCell<backend::Canvas>>,
+    previous_pointer: &Rc<RefCell<Cow<'static, str>>>,
+    cursor_animation: &Weak<RefCell<Option<backend::Timeout>>>,
+    cursor: CustomCursor,
+    frame: usize,
+) {
+    let animation = match cursor_animation.upgrade() {
+        Some(animation) => animation,
+        None => return,
+    };
+    let (ref css, delay) = cursor.frames[frame];
+    backend::set_canvas_style_property(canvas.borrow().raw(), "cursor", css);
+    *previous_pointer.borrow_mut() = Cow::Owned(css.clone());
+
+    let timeout = if cursor.frames.len() > 1 {
+        let canvas = canvas.clone();
+        let previous_pointer = previous_pointer.clone();
+        let cursor_animation = cursor_animation.clone();
+        let next = (frame + 1) % cursor.frames.len();
+        Some(backend::Timeout::new(
+            move || {
+                show_cursor_frame(
+                    &canvas,
+                    &previous_pointer,
+                    &cursor_animation,
+                    cursor.clone(),
+                    next,
+                )
+            },
+            delay,
+        ))
+    } else {
+        None
+    };
+    *animation.borrow_mut() = timeout;
+}
+
 impl Drop for Window {
     fn drop(&mut self) {
         if let Some(destroy_fn) = self.destroy_fn.take() {
@@ -353,3 +676,37 @@ impl Id {
 pub struct PlatformSpecificBuilderAttributes {
     pub(crate) canvas: Option<backend::RawCanvasType>,
 }
+
+/// Places the content of `provider` into the clipboard.
+///
+/// The data is collected right away, since the browser takes all of it at once.
+pub(crate) fn set_clipboard(
+    kind: ClipboardKind,
+    provider: &dyn ClipboardProvider,
+) -> Result<(), ExternalError> {
+    // There's no primary selection on the web.
+    if kind != ClipboardKind::Clipboard {
+        return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+
+    let mime_types = provider
+        .mime_types()
+        .into_iter()
+        .filter(backend::is_clipboard_mime_type_supported)
+        .collect::<Vec<_>>();
+    if mime_types.is_empty() {
+        return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+
+    let mut data = Vec::new();
+    for mime_type in mime_types {
+        let mut bytes = Vec::new();
+        provider
+            .write_to(&mime_type, &mut bytes)
+            .map_err(|err| ExternalError::Os(os_error!(OsError(err.to_string()))))?;
+        data.push((mime_type, bytes));
+    }
+
+    backend::write_clipboard(data)
+        .map_err(|err| ExternalError::Os(os_error!(OsError(format!("{:?}", err)))))
+}
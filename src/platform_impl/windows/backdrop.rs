@@ -0,0 +1,102 @@
+//! The backdrops of the windows, which are the system backdrops of DWM from Windows 11 on, and
+//! the accents of the windows from Windows 10 on.
+
+use std::mem;
+
+use winapi::{
+    shared::{
+        minwindef::{BOOL, DWORD},
+        windef::HWND,
+    },
+    um::dwmapi,
+};
+
+use crate::{
+    platform_impl::platform::{dark_mode::WIN10_BUILD_VERSION, util},
+    window::Backdrop,
+};
+
+// FIXME: These definitions are missing from winapi.
+const DWMWA_SYSTEMBACKDROP_TYPE: DWORD = 38;
+const DWMWA_MICA_EFFECT: DWORD = 1029;
+const DWMSBT_AUTO: DWORD = 0;
+const DWMSBT_MAINWINDOW: DWORD = 2;
+const DWMSBT_TRANSIENTWINDOW: DWORD = 3;
+
+const WCA_ACCENT_POLICY: DWORD = 19;
+const ACCENT_DISABLED: DWORD = 0;
+const ACCENT_ENABLE_BLURBEHIND: DWORD = 3;
+const ACCENT_ENABLE_ACRYLICBLURBEHIND: DWORD = 4;
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct ACCENT_POLICY {
+    AccentState: DWORD,
+    AccentFlags: DWORD,
+    GradientColor: DWORD,
+    AnimationId: DWORD,
+}
+
+/// Sets the backdrop of the window, the ones the system doesn't have falling back to the blur.
+///
+/// Returns whether it's a system backdrop, which is drawn behind the frame, or `None` if the
+/// system has no backdrop at all.
+pub fn set_backdrop(hwnd: HWND, backdrop: Backdrop) -> Option<bool> {
+    let build = match *WIN10_BUILD_VERSION {
+        Some(build) => build,
+        None if backdrop == Backdrop::None => return Some(false),
+        None => return None,
+    };
+
+    // The system backdrops are from Windows 11 22H2 on, with only the mica before
+    let system_backdrop = match backdrop {
+        Backdrop::Mica if build >= 22000 => Some(DWMSBT_MAINWINDOW),
+        Backdrop::Acrylic if build >= 22621 => Some(DWMSBT_TRANSIENTWINDOW),
+        _ => None,
+    };
+    let accent = match backdrop {
+        Backdrop::None => ACCENT_DISABLED,
+        _ if system_backdrop.is_some() => ACCENT_DISABLED,
+        // From Windows 10 1803 on
+        Backdrop::Acrylic if build >= 17134 => ACCENT_ENABLE_ACRYLICBLURBEHIND,
+        _ => ACCENT_ENABLE_BLURBEHIND,
+    };
+
+    unsafe {
+        if build >= 22621 {
+            set_window_attribute(
+                hwnd,
+                DWMWA_SYSTEMBACKDROP_TYPE,
+                system_backdrop.unwrap_or(DWMSBT_AUTO),
+            );
+        } else if build >= 22000 {
+            set_window_attribute(hwnd, DWMWA_MICA_EFFECT, system_backdrop.is_some() as BOOL);
+        }
+
+        let mut policy = ACCENT_POLICY {
+            AccentState: accent,
+            AccentFlags: 0,
+            // The acrylic is invisible without a tint, given in ABGR.
+            GradientColor: match accent {
+                ACCENT_ENABLE_ACRYLICBLURBEHIND => 0x4020_2020,
+                _ => 0,
+            },
+            AnimationId: 0,
+        };
+        let accented = util::set_window_composition_attribute(hwnd, WCA_ACCENT_POLICY, &mut policy);
+        if !accented && accent != ACCENT_DISABLED {
+            return None;
+        }
+    }
+
+    Some(system_backdrop.is_some())
+}
+
+unsafe fn set_window_attribute<T>(hwnd: HWND, attribute: DWORD, value: T) {
+    dwmapi::DwmSetWindowAttribute(
+        hwnd,
+        attribute,
+        &value as *const T as _,
+        mem::size_of::<T>() as DWORD,
+    );
+}
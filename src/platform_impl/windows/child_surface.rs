@@ -0,0 +1,183 @@
+#![cfg(target_os = "windows")]
+
+use std::{ffi::OsStr, io, mem, os::windows::ffi::OsStrExt, ptr};
+
+use raw_window_handle::{windows::WindowsHandle, RawWindowHandle};
+use winapi::{
+    shared::{
+        minwindef::{LPARAM, LRESULT, UINT, WPARAM},
+        windef::HWND,
+    },
+    um::{libloaderapi, winuser},
+};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize, Position, Size},
+    error::{ExternalError, HandleError},
+    platform_impl::platform::{
+        dpi::{dpi_to_scale_factor, hwnd_dpi},
+        event_loop::{EventLoopWindowTarget, DESTROY_MSG_ID},
+        window::Window,
+    },
+};
+
+lazy_static! {
+    static ref CHILD_SURFACE_WINDOW_CLASS: Vec<u16> = unsafe {
+        let class_name: Vec<_> = OsStr::new("Winit Child Surface")
+            .encode_wide()
+            .chain(Some(0).into_iter())
+            .collect();
+
+        let class = winuser::WNDCLASSEXW {
+            cbSize: mem::size_of::<winuser::WNDCLASSEXW>() as UINT,
+            style: 0,
+            lpfnWndProc: Some(child_surface_proc),
+            cbClsExtra: 0,
+            cbWndExtra: 0,
+            hInstance: libloaderapi::GetModuleHandleW(ptr::null()),
+            hIcon: ptr::null_mut(),
+            hCursor: ptr::null_mut(),
+            // The background is left to what's presented to the child.
+            hbrBackground: ptr::null_mut(),
+            lpszMenuName: ptr::null(),
+            lpszClassName: class_name.as_ptr(),
+            hIconSm: ptr::null_mut(),
+        };
+
+        winuser::RegisterClassExW(&class);
+
+        class_name
+    };
+}
+
+/// A child window of a window, which lets the input through to its parent.
+pub struct ChildSurface {
+    parent: HWND,
+    hwnd: HWND,
+}
+
+unsafe impl Send for ChildSurface {}
+unsafe impl Sync for ChildSurface {}
+
+impl ChildSurface {
+    pub fn new<T: 'static>(
+        _event_loop: &EventLoopWindowTarget<T>,
+        parent: &Window,
+        position: Position,
+        size: Size,
+    ) -> Result<Self, ExternalError> {
+        let scale_factor = parent.scale_factor();
+        let position: PhysicalPosition<i32> = position.to_physical(scale_factor);
+        let size: PhysicalSize<u32> = size.to_physical(scale_factor);
+
+        let hwnd = unsafe {
+            winuser::CreateWindowExW(
+                0,
+                CHILD_SURFACE_WINDOW_CLASS.as_ptr(),
+                ptr::null(),
+                winuser::WS_CHILD | winuser::WS_VISIBLE | winuser::WS_CLIPSIBLINGS,
+                position.x,
+                position.y,
+                size.width as i32,
+                size.height as i32,
+                parent.hwnd(),
+                ptr::null_mut(),
+                libloaderapi::GetModuleHandleW(ptr::null()),
+                ptr::null_mut(),
+            )
+        };
+        if hwnd.is_null() {
+            return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+        }
+
+        Ok(ChildSurface {
+            parent: parent.hwnd(),
+            hwnd,
+        })
+    }
+
+    #[inline]
+    pub fn set_position(&self, position: Position) {
+        let position: PhysicalPosition<i32> = position.to_physical(self.scale_factor());
+        unsafe {
+            winuser::SetWindowPos(
+                self.hwnd,
+                ptr::null_mut(),
+                position.x,
+                position.y,
+                0,
+                0,
+                winuser::SWP_ASYNCWINDOWPOS
+                    | winuser::SWP_NOZORDER
+                    | winuser::SWP_NOSIZE
+                    | winuser::SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    #[inline]
+    pub fn set_size(&self, size: Size) {
+        let size: PhysicalSize<u32> = size.to_physical(self.scale_factor());
+        unsafe {
+            winuser::SetWindowPos(
+                self.hwnd,
+                ptr::null_mut(),
+                0,
+                0,
+                size.width as i32,
+                size.height as i32,
+                winuser::SWP_ASYNCWINDOWPOS
+                    | winuser::SWP_NOZORDER
+                    | winuser::SWP_NOMOVE
+                    | winuser::SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    #[inline]
+    pub fn window_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        // The child is destroyed along with its parent.
+        if unsafe { winuser::IsWindow(self.hwnd) } == 0 {
+            return Err(HandleError::Destroyed);
+        }
+        let hinstance = unsafe { winuser::GetWindowLongPtrW(self.hwnd, winuser::GWLP_HINSTANCE) };
+        let handle = WindowsHandle {
+            hwnd: self.hwnd as *mut _,
+            hinstance: hinstance as *mut _,
+            ..WindowsHandle::empty()
+        };
+        Ok(RawWindowHandle::Windows(handle))
+    }
+
+    /// The scale factor of the parent, which the child is on.
+    fn scale_factor(&self) -> f64 {
+        dpi_to_scale_factor(unsafe { hwnd_dpi(self.parent) })
+    }
+}
+
+impl Drop for ChildSurface {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            // The window must be destroyed from the same thread that created it.
+            winuser::PostMessageW(self.hwnd, *DESTROY_MSG_ID, 0, 0);
+        }
+    }
+}
+
+unsafe extern "system" fn child_surface_proc(
+    window: HWND,
+    msg: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        // The input over the child goes to its parent.
+        winuser::WM_NCHITTEST => winuser::HTTRANSPARENT as LRESULT,
+        _ if msg == *DESTROY_MSG_ID => {
+            winuser::DestroyWindow(window);
+            0
+        }
+        _ => winuser::DefWindowProcW(window, msg, wparam, lparam),
+    }
+}
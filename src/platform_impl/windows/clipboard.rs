@@ -0,0 +1,460 @@
+#![cfg(target_os = "windows")]
+
+use std::{
+    ffi::{OsStr, OsString},
+    io, mem,
+    os::windows::ffi::{OsStrExt, OsStringExt},
+    path::PathBuf,
+    ptr, slice, str,
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use winapi::{
+    ctypes::c_int,
+    shared::{
+        minwindef::{BOOL, DWORD, HGLOBAL, TRUE, UINT},
+        windef::{HWND, POINT},
+    },
+    um::{processthreadsapi, winbase, winuser},
+};
+
+use crate::{
+    clipboard::{ClipboardKind, ClipboardMimedContent, ClipboardProvider, MimePicker, MimeType},
+    error::{ExternalError, NotSupportedError},
+};
+
+lazy_static! {
+    // The format used by browsers and image editors to exchange PNG images.
+    static ref CF_PNG: UINT = register_format("PNG");
+    // The format of HTML, which is UTF-8 encoded HTML prefixed with a header describing
+    // where the fragment is.
+    static ref CF_HTML: UINT = register_format("HTML Format");
+    static ref CF_RTF: UINT = register_format("Rich Text Format");
+    // The provider of the content placed into the clipboard, along with the window owning it, as
+    // `HWND`s aren't `Send`.
+    static ref CLIPBOARD_PROVIDER: Mutex<Option<(usize, Arc<dyn ClipboardProvider>)>> =
+        Mutex::new(None);
+}
+
+fn register_format(name: &str) -> UINT {
+    let name = OsStr::new(name)
+        .encode_wide()
+        .chain(Some(0).into_iter())
+        .collect::<Vec<_>>();
+    unsafe { winuser::RegisterClipboardFormatW(name.as_ptr()) }
+}
+
+pub fn format_mime_type(format: UINT) -> Option<MimeType> {
+    if format == winuser::CF_UNICODETEXT {
+        Some(MimeType::Text)
+    } else if format == *CF_PNG {
+        Some(MimeType::PngImage)
+    } else if format == *CF_HTML {
+        Some(MimeType::Html)
+    } else if format == *CF_RTF {
+        Some(MimeType::Rtf)
+    } else if format == winuser::CF_HDROP {
+        Some(MimeType::FileList)
+    } else {
+        // Custom formats are registered under their MIME type.
+        let mut name = [0u16; 256];
+        let len = unsafe {
+            winuser::GetClipboardFormatNameW(format, name.as_mut_ptr(), name.len() as c_int)
+        };
+        if len <= 0 {
+            return None;
+        }
+        MimeType::from_mime_type(&String::from_utf16_lossy(&name[..len as usize]))
+    }
+}
+
+pub fn mime_type_format(mime_type: &MimeType) -> UINT {
+    match mime_type {
+        MimeType::Text => winuser::CF_UNICODETEXT,
+        MimeType::PngImage => *CF_PNG,
+        MimeType::Html => *CF_HTML,
+        MimeType::Rtf => *CF_RTF,
+        MimeType::FileList => winuser::CF_HDROP,
+        MimeType::Other(mime) => register_format(mime),
+    }
+}
+
+/// The header of the `CF_HTML` format with the byte offsets of the document and the fragment.
+///
+/// The offsets are padded, so the length of the header doesn't depend on them.
+fn cf_html_header(
+    start_html: usize,
+    end_html: usize,
+    start_fragment: usize,
+    end_fragment: usize,
+) -> String {
+    format!(
+        "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\n\
+         StartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+        start_html, end_html, start_fragment, end_fragment,
+    )
+}
+
+/// Wraps the HTML fragment into the `CF_HTML` format.
+fn encode_cf_html(html: &str) -> Vec<u8> {
+    const PREFIX: &str = "<html><body>\r\n<!--StartFragment-->";
+    const SUFFIX: &str = "<!--EndFragment-->\r\n</body></html>";
+
+    let start_html = cf_html_header(0, 0, 0, 0).len();
+    let start_fragment = start_html + PREFIX.len();
+    let end_fragment = start_fragment + html.len();
+    let end_html = end_fragment + SUFFIX.len();
+
+    let mut data = cf_html_header(start_html, end_html, start_fragment, end_fragment).into_bytes();
+    data.extend_from_slice(PREFIX.as_bytes());
+    data.extend_from_slice(html.as_bytes());
+    data.extend_from_slice(SUFFIX.as_bytes());
+    data.push(0);
+    data
+}
+
+/// Extracts the HTML fragment from the `CF_HTML` format, or the whole document if the
+/// fragment isn't marked.
+fn decode_cf_html(data: &[u8]) -> String {
+    let offset = |key: &str| {
+        data.split(|&byte| byte == b'\n').find_map(|line| {
+            let line = str::from_utf8(line).ok()?.trim_end();
+            if line.starts_with(key) {
+                line[key.len()..].parse::<usize>().ok()
+            } else {
+                None
+            }
+        })
+    };
+
+    let html = match (offset("StartFragment:"), offset("EndFragment:")) {
+        (Some(start), Some(end)) if start <= end && end <= data.len() => &data[start..end],
+        _ => match (offset("StartHTML:"), offset("EndHTML:")) {
+            (Some(start), Some(end)) if start <= end && end <= data.len() => &data[start..end],
+            _ => data,
+        },
+    };
+    String::from_utf8_lossy(html).into_owned()
+}
+
+/// The header of `CF_HDROP`, which isn't defined by winapi.
+#[repr(C)]
+#[allow(non_snake_case)]
+struct DROPFILES {
+    pFiles: DWORD,
+    pt: POINT,
+    fNC: BOOL,
+    fWide: BOOL,
+}
+
+/// Encodes the paths into the `CF_HDROP` format, which is `DROPFILES` followed by the
+/// null-terminated paths, with the list itself terminated by an additional null.
+fn encode_hdrop(paths: &[PathBuf]) -> Vec<u8> {
+    let header = DROPFILES {
+        pFiles: mem::size_of::<DROPFILES>() as DWORD,
+        pt: POINT { x: 0, y: 0 },
+        fNC: 0,
+        fWide: TRUE,
+    };
+    let mut data = unsafe {
+        slice::from_raw_parts(
+            &header as *const DROPFILES as *const u8,
+            mem::size_of::<DROPFILES>(),
+        )
+    }
+    .to_vec();
+
+    for path in paths {
+        for c in path.as_os_str().encode_wide().chain(Some(0).into_iter()) {
+            data.extend_from_slice(&c.to_ne_bytes());
+        }
+    }
+    data.extend_from_slice(&0u16.to_ne_bytes());
+    data
+}
+
+fn decode_hdrop(data: &[u8]) -> Vec<PathBuf> {
+    if data.len() < mem::size_of::<DROPFILES>() {
+        return Vec::new();
+    }
+    let header = unsafe { ptr::read_unaligned(data.as_ptr() as *const DROPFILES) };
+    let files = data.get(header.pFiles as usize..).unwrap_or_default();
+
+    if header.fWide != 0 {
+        let files = files
+            .chunks_exact(2)
+            .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>();
+        files
+            .split(|&c| c == 0)
+            .take_while(|path| !path.is_empty())
+            .map(|path| OsString::from_wide(path).into())
+            .collect()
+    } else {
+        files
+            .split(|&c| c == 0)
+            .take_while(|path| !path.is_empty())
+            .map(|path| String::from_utf8_lossy(path).into_owned().into())
+            .collect()
+    }
+}
+
+/// Places the content of `provider` into the clipboard, with `hwnd` becoming the clipboard
+/// owner.
+///
+/// The data of each offered kind is only rendered once some application requests it, see
+/// `WM_RENDERFORMAT`.
+pub fn set_clipboard(
+    hwnd: HWND,
+    kind: ClipboardKind,
+    provider: Arc<dyn ClipboardProvider>,
+) -> Result<(), ExternalError> {
+    // There's no primary selection on Windows.
+    if kind != ClipboardKind::Clipboard {
+        return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+
+    unsafe {
+        if winuser::OpenClipboard(hwnd) == 0 {
+            return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+        }
+
+        let result = set_clipboard_formats(&*provider);
+        // The provider is replaced after the previous owner was told the clipboard was emptied,
+        // and before the data can be requested.
+        if result.is_ok() {
+            *CLIPBOARD_PROVIDER.lock() = Some((hwnd as usize, provider));
+        }
+        winuser::CloseClipboard();
+        result
+    }
+}
+
+unsafe fn set_clipboard_formats(provider: &dyn ClipboardProvider) -> Result<(), ExternalError> {
+    if winuser::EmptyClipboard() == 0 {
+        return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+    }
+
+    // Without any data, the formats are rendered on request.
+    for mime_type in provider.mime_types() {
+        winuser::SetClipboardData(mime_type_format(&mime_type), ptr::null_mut());
+    }
+
+    Ok(())
+}
+
+/// Renders the data of the requested format into the open clipboard, on `WM_RENDERFORMAT`.
+pub fn render_format(hwnd: HWND, format: UINT) {
+    let provider = match *CLIPBOARD_PROVIDER.lock() {
+        Some((owner, ref provider)) if owner == hwnd as usize => Arc::clone(provider),
+        _ => return,
+    };
+    let mime_type = provider
+        .mime_types()
+        .into_iter()
+        .find(|mime_type| mime_type_format(mime_type) == format);
+    let content = match mime_type {
+        Some(mime_type) => ClipboardMimedContent::from_provider(&*provider, mime_type),
+        None => return,
+    };
+
+    match content {
+        Ok(content) => {
+            let (format, data) = content_format_data(content);
+            if let Err(err) = unsafe { set_clipboard_format_data(format, &data) } {
+                warn!("Failed to render the clipboard data: {}", err);
+            }
+        }
+        Err(err) => warn!("Failed to render the clipboard data: {}", err),
+    }
+}
+
+/// Renders the data of every format, on `WM_RENDERALLFORMATS`, which is sent once the clipboard
+/// owner is destroyed.
+pub fn render_all_formats(hwnd: HWND) {
+    let formats = match *CLIPBOARD_PROVIDER.lock() {
+        Some((owner, ref provider)) if owner == hwnd as usize => provider
+            .mime_types()
+            .iter()
+            .map(mime_type_format)
+            .collect::<Vec<_>>(),
+        _ => return,
+    };
+
+    unsafe {
+        if winuser::OpenClipboard(hwnd) == 0 {
+            return;
+        }
+        // The clipboard may have been emptied in the meantime.
+        if winuser::GetClipboardOwner() == hwnd {
+            for format in formats {
+                render_format(hwnd, format);
+            }
+        }
+        winuser::CloseClipboard();
+    }
+
+    clear_provider(hwnd);
+}
+
+/// Drops the provider of the content placed by `hwnd`, on `WM_DESTROYCLIPBOARD`.
+pub fn clear_provider(hwnd: HWND) {
+    let mut provider = CLIPBOARD_PROVIDER.lock();
+    if matches!(*provider, Some((owner, _)) if owner == hwnd as usize) {
+        *provider = None;
+    }
+}
+
+/// The format and the data the content is placed into the clipboard with.
+pub fn content_format_data(content: ClipboardMimedContent) -> (UINT, Vec<u8>) {
+    match content {
+        ClipboardMimedContent::Text(text) => {
+            let data = OsStr::new(&text)
+                .encode_wide()
+                .chain(Some(0).into_iter())
+                .flat_map(|c| c.to_ne_bytes().to_vec())
+                .collect::<Vec<u8>>();
+            (winuser::CF_UNICODETEXT, data)
+        }
+        ClipboardMimedContent::PngImage(data) => (*CF_PNG, data),
+        ClipboardMimedContent::Html(html) => (*CF_HTML, encode_cf_html(&html)),
+        ClipboardMimedContent::Rtf(rtf) => {
+            let mut data = rtf.into_bytes();
+            data.push(0);
+            (*CF_RTF, data)
+        }
+        ClipboardMimedContent::FileList(paths) => (winuser::CF_HDROP, encode_hdrop(&paths)),
+        ClipboardMimedContent::Bytes { mime, data } => (register_format(&mime), data),
+    }
+}
+
+unsafe fn set_clipboard_format_data(format: UINT, data: &[u8]) -> Result<(), ExternalError> {
+    let handle = global_alloc_data(data).map_err(|err| ExternalError::Os(os_error!(err)))?;
+
+    // The system owns the memory once the data was set.
+    if winuser::SetClipboardData(format, handle).is_null() {
+        winbase::GlobalFree(handle);
+        return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+    }
+
+    Ok(())
+}
+
+/// Copies the data into a newly allocated global memory object, which is what the data is
+/// exchanged with through both the clipboard and OLE.
+pub unsafe fn global_alloc_data(data: &[u8]) -> Result<HGLOBAL, io::Error> {
+    let handle = winbase::GlobalAlloc(winbase::GMEM_MOVEABLE, data.len());
+    if handle.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    let buffer = winbase::GlobalLock(handle) as *mut u8;
+    if buffer.is_null() {
+        let err = io::Error::last_os_error();
+        winbase::GlobalFree(handle);
+        return Err(err);
+    }
+    ptr::copy_nonoverlapping(data.as_ptr(), buffer, data.len());
+    winbase::GlobalUnlock(handle);
+
+    Ok(handle)
+}
+
+/// Reads the content of the kind chosen by `picker` from the clipboard.
+pub fn read_clipboard(
+    hwnd: HWND,
+    kind: ClipboardKind,
+    picker: MimePicker,
+) -> Result<Option<ClipboardMimedContent>, ExternalError> {
+    if kind != ClipboardKind::Clipboard {
+        return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+
+    unsafe {
+        if winuser::OpenClipboard(hwnd) == 0 {
+            return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+        }
+
+        let content = read_clipboard_data(picker);
+        winuser::CloseClipboard();
+        Ok(content)
+    }
+}
+
+unsafe fn read_clipboard_data(picker: MimePicker) -> Option<ClipboardMimedContent> {
+    // The formats are enumerated in the order they were placed into the clipboard, which is
+    // the order of the owner's preference.
+    let mut offered = Vec::new();
+    let mut format = 0;
+    loop {
+        format = winuser::EnumClipboardFormats(format);
+        if format == 0 {
+            break;
+        }
+        match format_mime_type(format) {
+            Some(mime_type) if !offered.contains(&mime_type) => offered.push(mime_type),
+            _ => (),
+        }
+    }
+
+    let mime_type = picker(&offered).filter(|mime_type| offered.contains(mime_type))?;
+
+    let handle = winuser::GetClipboardData(mime_type_format(&mime_type));
+    if handle.is_null() {
+        return None;
+    }
+
+    global_content(mime_type, handle)
+}
+
+/// Decodes the content of the given kind from the global memory object holding its data.
+pub unsafe fn global_content(
+    mime_type: MimeType,
+    handle: HGLOBAL,
+) -> Option<ClipboardMimedContent> {
+    let buffer = winbase::GlobalLock(handle) as *const u8;
+    if buffer.is_null() {
+        return None;
+    }
+    let data = slice::from_raw_parts(buffer, winbase::GlobalSize(handle)).to_vec();
+    winbase::GlobalUnlock(handle);
+
+    let content = match mime_type {
+        MimeType::Text => {
+            let text = data
+                .chunks_exact(2)
+                .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+                .take_while(|&c| c != 0)
+                .collect::<Vec<_>>();
+            ClipboardMimedContent::Text(String::from_utf16_lossy(&text))
+        }
+        MimeType::PngImage => ClipboardMimedContent::PngImage(data),
+        MimeType::Html => {
+            let data = data.split(|&byte| byte == 0).next().unwrap_or_default();
+            ClipboardMimedContent::Html(decode_cf_html(data))
+        }
+        MimeType::Rtf => {
+            let data = data.split(|&byte| byte == 0).next().unwrap_or_default();
+            ClipboardMimedContent::from_bytes(MimeType::Rtf, data.to_vec())
+        }
+        MimeType::FileList => ClipboardMimedContent::FileList(decode_hdrop(&data)),
+        mime_type @ MimeType::Other(_) => ClipboardMimedContent::from_bytes(mime_type, data),
+    };
+
+    Some(content)
+}
+
+/// Whether the clipboard content was placed by a window of this process.
+pub fn is_owned_by_current_process() -> bool {
+    unsafe {
+        let owner = winuser::GetClipboardOwner();
+        if owner.is_null() {
+            return false;
+        }
+
+        let mut process_id = 0;
+        winuser::GetWindowThreadProcessId(owner, &mut process_id);
+        process_id == processthreadsapi::GetCurrentProcessId()
+    }
+}
@@ -5,19 +5,18 @@ use std::os::windows::ffi::OsStrExt;
 
 use winapi::{
     shared::{
-        basetsd::SIZE_T,
         minwindef::{BOOL, DWORD, FALSE, UINT, ULONG, WORD},
-        ntdef::{LPSTR, NTSTATUS, NT_SUCCESS, PVOID, WCHAR},
+        ntdef::{LPSTR, NTSTATUS, NT_SUCCESS, WCHAR},
         windef::HWND,
         winerror::S_OK,
     },
     um::{libloaderapi, uxtheme, winuser},
 };
 
-use crate::window::Theme;
+use crate::{platform_impl::platform::util, window::Theme};
 
 lazy_static! {
-    static ref WIN10_BUILD_VERSION: Option<DWORD> = {
+    pub(crate) static ref WIN10_BUILD_VERSION: Option<DWORD> = {
         // FIXME: RtlGetVersion is a documented windows API,
         // should be part of winapi!
 
@@ -104,44 +103,17 @@ pub fn try_theme(hwnd: HWND, preferred_theme: Option<Theme>) -> Theme {
 fn set_dark_mode_for_window(hwnd: HWND, is_dark_mode: bool) -> bool {
     // Uses Windows undocumented API SetWindowCompositionAttribute,
     // as seen in win32-darkmode example linked at top of file.
+    const WCA_USEDARKMODECOLORS: DWORD = 26;
 
-    type SetWindowCompositionAttribute =
-        unsafe extern "system" fn(HWND, *mut WINDOWCOMPOSITIONATTRIBDATA) -> BOOL;
+    // SetWindowCompositionAttribute needs a bigbool (i32), not bool.
+    let mut is_dark_mode_bigbool = is_dark_mode as BOOL;
 
-    #[allow(non_snake_case)]
-    type WINDOWCOMPOSITIONATTRIB = u32;
-    const WCA_USEDARKMODECOLORS: WINDOWCOMPOSITIONATTRIB = 26;
-
-    #[allow(non_snake_case)]
-    #[repr(C)]
-    struct WINDOWCOMPOSITIONATTRIBDATA {
-        Attrib: WINDOWCOMPOSITIONATTRIB,
-        pvData: PVOID,
-        cbData: SIZE_T,
-    }
-
-    lazy_static! {
-        static ref SET_WINDOW_COMPOSITION_ATTRIBUTE: Option<SetWindowCompositionAttribute> =
-            get_function!("user32.dll", SetWindowCompositionAttribute);
-    }
-
-    if let Some(set_window_composition_attribute) = *SET_WINDOW_COMPOSITION_ATTRIBUTE {
-        unsafe {
-            // SetWindowCompositionAttribute needs a bigbool (i32), not bool.
-            let mut is_dark_mode_bigbool = is_dark_mode as BOOL;
-
-            let mut data = WINDOWCOMPOSITIONATTRIBDATA {
-                Attrib: WCA_USEDARKMODECOLORS,
-                pvData: &mut is_dark_mode_bigbool as *mut _ as _,
-                cbData: std::mem::size_of_val(&is_dark_mode_bigbool) as _,
-            };
-
-            let status = set_window_composition_attribute(hwnd, &mut data as *mut _);
-
-            status != FALSE
-        }
-    } else {
-        false
+    unsafe {
+        util::set_window_composition_attribute(
+            hwnd,
+            WCA_USEDARKMODECOLORS,
+            &mut is_dark_mode_bigbool,
+        )
     }
 }
 
@@ -0,0 +1,679 @@
+//! The scrolls of the precision touchpads through DirectManipulation, which follows the fingers
+//! on a viewport of its own to give the scrolls in pixels, along with their inertia, instead of
+//! the wheel messages.
+
+use std::{
+    cell::Cell,
+    ptr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use winapi::{
+    ctypes::c_void,
+    shared::{
+        basetsd::UINT_PTR,
+        guiddef::{IsEqualIID, GUID, REFIID},
+        minwindef::{BOOL, DWORD, FALSE, UINT, ULONG},
+        windef::{HWND, RECT},
+        winerror::{E_NOINTERFACE, E_POINTER, S_OK},
+        wtypesbase::CLSCTX_INPROC_SERVER,
+    },
+    um::{
+        combaseapi,
+        unknwnbase::{IUnknown, IUnknownVtbl},
+        winnt::HRESULT,
+        winuser,
+    },
+    Interface,
+};
+
+use crate::{
+    dpi::PhysicalPosition,
+    event::{AxisSource, Event, MouseScrollDelta, TouchPhase, WindowEvent},
+    platform_impl::platform::{event, window, WindowId, DEVICE_ID},
+    window::WindowId as RootWindowId,
+};
+
+/// The message hit testing the contacts of the touchpads on the windows DirectManipulation is
+/// activated for.
+pub const DM_POINTERHITTEST: UINT = 0x0250;
+
+/// The timer of the window updating the viewport while its contacts move.
+pub const UPDATE_TIMER_ID: UINT_PTR = 0x444d;
+
+/// The interval of the updates, which is about the one of the refresh of the displays.
+const UPDATE_INTERVAL: UINT = 16;
+
+/// The updates without a scroll after a contact, after which the contact is taken as one which
+/// isn't scrolling.
+const MAX_IDLE_UPDATES: u32 = 30;
+
+const PT_TOUCHPAD: UINT = 5;
+
+type DirectManipulationStatus = i32;
+type DirectManipulationConfiguration = i32;
+type DirectManipulationViewportOptions = i32;
+
+const DIRECTMANIPULATION_RUNNING: DirectManipulationStatus = 3;
+const DIRECTMANIPULATION_INERTIA: DirectManipulationStatus = 4;
+const DIRECTMANIPULATION_READY: DirectManipulationStatus = 5;
+
+const DIRECTMANIPULATION_CONFIGURATION_INTERACTION: DirectManipulationConfiguration = 0x1;
+const DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_X: DirectManipulationConfiguration = 0x2;
+const DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_Y: DirectManipulationConfiguration = 0x4;
+const DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_INERTIA: DirectManipulationConfiguration = 0x20;
+const DIRECTMANIPULATION_CONFIGURATION_RAILS_X: DirectManipulationConfiguration = 0x100;
+const DIRECTMANIPULATION_CONFIGURATION_RAILS_Y: DirectManipulationConfiguration = 0x200;
+
+const DIRECTMANIPULATION_VIEWPORT_OPTIONS_MANUALUPDATE: DirectManipulationViewportOptions = 0x2;
+
+/// The size of the viewport, in the middle of a content large enough for the scrolls to never
+/// reach its edges, as the viewport goes back to the middle after every scroll.
+const VIEWPORT_SIZE: i32 = 1000;
+const CONTENT_SIZE: i32 = 1_000_000;
+
+const CLSID_DIRECT_MANIPULATION_MANAGER: GUID = GUID {
+    Data1: 0x54E211B6,
+    Data2: 0x3650,
+    Data3: 0x4F75,
+    Data4: [0x83, 0x34, 0xFA, 0x35, 0x95, 0x98, 0xE1, 0xC5],
+};
+const IID_IDIRECT_MANIPULATION_MANAGER: GUID = GUID {
+    Data1: 0xFBF5D3B4,
+    Data2: 0x70C7,
+    Data3: 0x4163,
+    Data4: [0x93, 0x22, 0x5A, 0x6F, 0x66, 0x0D, 0x6F, 0xBC],
+};
+const IID_IDIRECT_MANIPULATION_UPDATE_MANAGER: GUID = GUID {
+    Data1: 0xB0AE62FD,
+    Data2: 0xBE34,
+    Data3: 0x46E7,
+    Data4: [0x9C, 0xAA, 0xD3, 0x61, 0xFA, 0xCB, 0xB9, 0xCC],
+};
+const IID_IDIRECT_MANIPULATION_VIEWPORT: GUID = GUID {
+    Data1: 0x28B85A3D,
+    Data2: 0x60A0,
+    Data3: 0x48BD,
+    Data4: [0x9B, 0xA1, 0x5C, 0xE8, 0xD9, 0xEA, 0x3A, 0x6D],
+};
+const IID_IDIRECT_MANIPULATION_CONTENT: GUID = GUID {
+    Data1: 0xB89962CB,
+    Data2: 0x3D89,
+    Data3: 0x442B,
+    Data4: [0xBB, 0x58, 0x50, 0x98, 0xFA, 0x0F, 0x9F, 0x16],
+};
+const IID_IDIRECT_MANIPULATION_VIEWPORT_EVENT_HANDLER: GUID = GUID {
+    Data1: 0x952121DA,
+    Data2: 0xD69F,
+    Data3: 0x45F9,
+    Data4: [0xB0, 0xF9, 0xF2, 0x39, 0x44, 0x32, 0x1A, 0x6D],
+};
+
+type GetPointerType = unsafe extern "system" fn(pointerId: UINT, pointerType: *mut UINT) -> BOOL;
+
+lazy_static! {
+    static ref GET_POINTER_TYPE: Option<GetPointerType> =
+        get_function!("user32.dll", GetPointerType);
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct IDirectManipulationManagerVtbl {
+    parent: IUnknownVtbl,
+    Activate:
+        unsafe extern "system" fn(This: *mut IDirectManipulationManager, window: HWND) -> HRESULT,
+    Deactivate:
+        unsafe extern "system" fn(This: *mut IDirectManipulationManager, window: HWND) -> HRESULT,
+    // `RegisterHitTestTarget` and `ProcessInput`, which aren't used for the touchpads.
+    _RegisterHitTestTarget: usize,
+    _ProcessInput: usize,
+    GetUpdateManager: unsafe extern "system" fn(
+        This: *mut IDirectManipulationManager,
+        riid: REFIID,
+        object: *mut *mut c_void,
+    ) -> HRESULT,
+    CreateViewport: unsafe extern "system" fn(
+        This: *mut IDirectManipulationManager,
+        frameInfo: *mut IUnknown,
+        window: HWND,
+        riid: REFIID,
+        object: *mut *mut c_void,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct IDirectManipulationManager {
+    lpVtbl: *const IDirectManipulationManagerVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct IDirectManipulationUpdateManagerVtbl {
+    parent: IUnknownVtbl,
+    // `RegisterWaitHandleCallback` and `UnregisterWaitHandleCallback`, which aren't used.
+    _RegisterWaitHandleCallback: usize,
+    _UnregisterWaitHandleCallback: usize,
+    Update: unsafe extern "system" fn(
+        This: *mut IDirectManipulationUpdateManager,
+        frameInfo: *mut IUnknown,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct IDirectManipulationUpdateManager {
+    lpVtbl: *const IDirectManipulationUpdateManagerVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct IDirectManipulationViewportVtbl {
+    parent: IUnknownVtbl,
+    Enable: unsafe extern "system" fn(This: *mut IDirectManipulationViewport) -> HRESULT,
+    // `Disable`, which isn't used as the viewport is abandoned along with the window.
+    _Disable: usize,
+    SetContact: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewport,
+        pointerId: UINT,
+    ) -> HRESULT,
+    // `ReleaseContact`, `ReleaseAllContacts`, `GetStatus`, `GetTag`, `SetTag` and
+    // `GetViewportRect`, which aren't used.
+    _ReleaseContact: usize,
+    _ReleaseAllContacts: usize,
+    _GetStatus: usize,
+    _GetTag: usize,
+    _SetTag: usize,
+    _GetViewportRect: usize,
+    SetViewportRect: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewport,
+        viewport: *const RECT,
+    ) -> HRESULT,
+    ZoomToRect: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewport,
+        left: f32,
+        top: f32,
+        right: f32,
+        bottom: f32,
+        animate: BOOL,
+    ) -> HRESULT,
+    // `SetViewportTransform` and `SyncDisplayTransform`, which aren't used.
+    _SetViewportTransform: usize,
+    _SyncDisplayTransform: usize,
+    GetPrimaryContent: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewport,
+        riid: REFIID,
+        object: *mut *mut c_void,
+    ) -> HRESULT,
+    // `AddContent` and `RemoveContent`, which aren't used.
+    _AddContent: usize,
+    _RemoveContent: usize,
+    SetViewportOptions: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewport,
+        options: DirectManipulationViewportOptions,
+    ) -> HRESULT,
+    // `AddConfiguration` and `RemoveConfiguration`, which aren't used.
+    _AddConfiguration: usize,
+    _RemoveConfiguration: usize,
+    ActivateConfiguration: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewport,
+        configuration: DirectManipulationConfiguration,
+    ) -> HRESULT,
+    // `SetManualGesture` and `SetChaining`, which aren't used.
+    _SetManualGesture: usize,
+    _SetChaining: usize,
+    AddEventHandler: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewport,
+        window: HWND,
+        eventHandler: *mut IDirectManipulationViewportEventHandler,
+        cookie: *mut DWORD,
+    ) -> HRESULT,
+    RemoveEventHandler:
+        unsafe extern "system" fn(This: *mut IDirectManipulationViewport, cookie: DWORD) -> HRESULT,
+    // `SetInputMode`, `SetUpdateMode` and `Stop`, which aren't used.
+    _SetInputMode: usize,
+    _SetUpdateMode: usize,
+    _Stop: usize,
+    Abandon: unsafe extern "system" fn(This: *mut IDirectManipulationViewport) -> HRESULT,
+}
+
+#[repr(C)]
+struct IDirectManipulationViewport {
+    lpVtbl: *const IDirectManipulationViewportVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct IDirectManipulationContentVtbl {
+    parent: IUnknownVtbl,
+    // `GetContentRect`, which isn't used.
+    _GetContentRect: usize,
+    SetContentRect: unsafe extern "system" fn(
+        This: *mut IDirectManipulationContent,
+        contentSize: *const RECT,
+    ) -> HRESULT,
+    // `GetViewport`, `GetTag`, `SetTag` and `GetOutputTransform`, which aren't used.
+    _GetViewport: usize,
+    _GetTag: usize,
+    _SetTag: usize,
+    _GetOutputTransform: usize,
+    GetContentTransform: unsafe extern "system" fn(
+        This: *mut IDirectManipulationContent,
+        matrix: *mut f32,
+        pointCount: DWORD,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct IDirectManipulationContent {
+    lpVtbl: *const IDirectManipulationContentVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct IDirectManipulationViewportEventHandlerVtbl {
+    parent: IUnknownVtbl,
+    OnViewportStatusChanged: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewportEventHandler,
+        viewport: *mut IDirectManipulationViewport,
+        current: DirectManipulationStatus,
+        previous: DirectManipulationStatus,
+    ) -> HRESULT,
+    OnViewportUpdated: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewportEventHandler,
+        viewport: *mut IDirectManipulationViewport,
+    ) -> HRESULT,
+    OnContentUpdated: unsafe extern "system" fn(
+        This: *mut IDirectManipulationViewportEventHandler,
+        viewport: *mut IDirectManipulationViewport,
+        content: *mut IDirectManipulationContent,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct IDirectManipulationViewportEventHandler {
+    lpVtbl: *const IDirectManipulationViewportEventHandlerVtbl,
+}
+
+#[repr(C)]
+struct ViewportEventHandlerData {
+    interface: IDirectManipulationViewportEventHandler,
+    refcount: AtomicUsize,
+    window: HWND,
+    send_event: Box<dyn Fn(Event<'static, ()>)>,
+    status: Cell<DirectManipulationStatus>,
+    /// The translation of the content at its previous update.
+    translation: Cell<(f32, f32)>,
+    /// Whether the viewport has to go back to the middle of the content, once a scroll is over.
+    reset_pending: Cell<bool>,
+    /// The updates since the last contact, while the viewport is ready.
+    idle_updates: Cell<u32>,
+}
+
+impl ViewportEventHandlerData {
+    unsafe fn send_scroll(&self, delta: (f32, f32), phase: TouchPhase) {
+        #[allow(deprecated)]
+        (self.send_event)(Event::WindowEvent {
+            window_id: RootWindowId(WindowId(self.window)),
+            event: WindowEvent::MouseWheel {
+                device_id: DEVICE_ID,
+                delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(
+                    delta.0 as f64,
+                    delta.1 as f64,
+                )),
+                phase,
+                source: Some(AxisSource::Finger),
+                modifiers: event::get_key_mods(),
+            },
+        });
+    }
+}
+
+/// The DirectManipulation viewport of a window, which the contacts of the touchpads go to.
+pub struct DirectManipulation {
+    window: HWND,
+    manager: *mut IDirectManipulationManager,
+    update_manager: *mut IDirectManipulationUpdateManager,
+    viewport: *mut IDirectManipulationViewport,
+    handler: *mut ViewportEventHandlerData,
+    /// The cookie of the event handler, which is 0 when it wasn't added.
+    cookie: DWORD,
+}
+
+#[allow(non_snake_case)]
+impl DirectManipulation {
+    /// Activates DirectManipulation for the window, or returns `None` if it isn't available, in
+    /// which case the touchpads scroll with the wheel messages.
+    pub unsafe fn new(
+        window: HWND,
+        send_event: Box<dyn Fn(Event<'static, ()>)>,
+    ) -> Option<DirectManipulation> {
+        window::com_initialized();
+        let handler = Box::new(ViewportEventHandlerData {
+            interface: IDirectManipulationViewportEventHandler {
+                lpVtbl: &VIEWPORT_EVENT_HANDLER_VTBL
+                    as *const IDirectManipulationViewportEventHandlerVtbl,
+            },
+            refcount: AtomicUsize::new(1),
+            window,
+            send_event,
+            status: Cell::new(DIRECTMANIPULATION_READY),
+            translation: Cell::new((0.0, 0.0)),
+            reset_pending: Cell::new(false),
+            idle_updates: Cell::new(0),
+        });
+        // The viewport releases what it got so far if it fails midway.
+        let mut direct_manipulation = DirectManipulation {
+            window,
+            manager: ptr::null_mut(),
+            update_manager: ptr::null_mut(),
+            viewport: ptr::null_mut(),
+            handler: Box::into_raw(handler),
+            cookie: 0,
+        };
+
+        let hr = combaseapi::CoCreateInstance(
+            &CLSID_DIRECT_MANIPULATION_MANAGER,
+            ptr::null_mut(),
+            CLSCTX_INPROC_SERVER,
+            &IID_IDIRECT_MANIPULATION_MANAGER,
+            &mut direct_manipulation.manager as *mut _ as *mut _,
+        );
+        if hr != S_OK || direct_manipulation.manager.is_null() {
+            // DirectManipulation is only there since Windows 8.
+            debug!("Failed to create the DirectManipulation manager: {:#x}", hr);
+            direct_manipulation.manager = ptr::null_mut();
+            return None;
+        }
+        let manager = direct_manipulation.manager;
+        let hr = ((*(*manager).lpVtbl).GetUpdateManager)(
+            manager,
+            &IID_IDIRECT_MANIPULATION_UPDATE_MANAGER,
+            &mut direct_manipulation.update_manager as *mut _ as *mut _,
+        );
+        if hr != S_OK || direct_manipulation.update_manager.is_null() {
+            warn!(
+                "Failed to get the DirectManipulation update manager: {:#x}",
+                hr
+            );
+            return None;
+        }
+        let hr = ((*(*manager).lpVtbl).CreateViewport)(
+            manager,
+            ptr::null_mut(),
+            window,
+            &IID_IDIRECT_MANIPULATION_VIEWPORT,
+            &mut direct_manipulation.viewport as *mut _ as *mut _,
+        );
+        if hr != S_OK || direct_manipulation.viewport.is_null() {
+            warn!(
+                "Failed to create the DirectManipulation viewport: {:#x}",
+                hr
+            );
+            return None;
+        }
+
+        let viewport = direct_manipulation.viewport;
+        let vtbl = &*(*viewport).lpVtbl;
+        let configuration = DIRECTMANIPULATION_CONFIGURATION_INTERACTION
+            | DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_X
+            | DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_Y
+            | DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_INERTIA
+            | DIRECTMANIPULATION_CONFIGURATION_RAILS_X
+            | DIRECTMANIPULATION_CONFIGURATION_RAILS_Y;
+        let viewport_rect = RECT {
+            left: 0,
+            top: 0,
+            right: VIEWPORT_SIZE,
+            bottom: VIEWPORT_SIZE,
+        };
+        let content_rect = RECT {
+            left: 0,
+            top: 0,
+            right: CONTENT_SIZE,
+            bottom: CONTENT_SIZE,
+        };
+        let mut content: *mut IDirectManipulationContent = ptr::null_mut();
+        let hr = (vtbl.ActivateConfiguration)(viewport, configuration);
+        let hr = hr_and(hr, || {
+            (vtbl.SetViewportOptions)(viewport, DIRECTMANIPULATION_VIEWPORT_OPTIONS_MANUALUPDATE)
+        });
+        let hr = hr_and(hr, || (vtbl.SetViewportRect)(viewport, &viewport_rect));
+        let hr = hr_and(hr, || {
+            (vtbl.GetPrimaryContent)(
+                viewport,
+                &IID_IDIRECT_MANIPULATION_CONTENT,
+                &mut content as *mut _ as *mut _,
+            )
+        });
+        let hr = hr_and(hr, || {
+            let hr = ((*(*content).lpVtbl).SetContentRect)(content, &content_rect);
+            (*(content as *mut IUnknown)).Release();
+            hr
+        });
+        if hr != S_OK {
+            warn!(
+                "Failed to configure the DirectManipulation viewport: {:#x}",
+                hr
+            );
+            return None;
+        }
+        direct_manipulation.center_viewport();
+
+        let hr = (vtbl.AddEventHandler)(
+            viewport,
+            window,
+            direct_manipulation.handler as *mut IDirectManipulationViewportEventHandler,
+            &mut direct_manipulation.cookie,
+        );
+        if hr != S_OK {
+            warn!(
+                "Failed to add the DirectManipulation event handler: {:#x}",
+                hr
+            );
+            direct_manipulation.cookie = 0;
+            return None;
+        }
+        let hr = hr_and(((*(*manager).lpVtbl).Activate)(manager, window), || {
+            (vtbl.Enable)(viewport)
+        });
+        if hr != S_OK {
+            warn!("Failed to activate DirectManipulation: {:#x}", hr);
+            return None;
+        }
+        Some(direct_manipulation)
+    }
+
+    /// Gives the contact to the viewport when it's on a touchpad, on `DM_POINTERHITTEST`.
+    pub unsafe fn hit_test(&self, pointer_id: UINT) {
+        let get_pointer_type = match *GET_POINTER_TYPE {
+            Some(get_pointer_type) => get_pointer_type,
+            None => return,
+        };
+        let mut pointer_type = 0;
+        if get_pointer_type(pointer_id, &mut pointer_type) == 0 || pointer_type != PT_TOUCHPAD {
+            return;
+        }
+        if ((*(*self.viewport).lpVtbl).SetContact)(self.viewport, pointer_id) == S_OK {
+            (*self.handler).idle_updates.set(0);
+            winuser::SetTimer(self.window, UPDATE_TIMER_ID, UPDATE_INTERVAL, None);
+        }
+    }
+
+    /// Updates the viewport, on the timer running while it has contacts.
+    pub unsafe fn update(&self) {
+        ((*(*self.update_manager).lpVtbl).Update)(self.update_manager, ptr::null_mut());
+        let handler = &*self.handler;
+        if handler.status.get() != DIRECTMANIPULATION_READY {
+            return;
+        }
+        if handler.reset_pending.replace(false) {
+            self.center_viewport();
+            // The content moves back while the viewport is ready, which doesn't scroll.
+            ((*(*self.update_manager).lpVtbl).Update)(self.update_manager, ptr::null_mut());
+            winuser::KillTimer(self.window, UPDATE_TIMER_ID);
+        } else {
+            let idle_updates = handler.idle_updates.get() + 1;
+            handler.idle_updates.set(idle_updates);
+            if idle_updates > MAX_IDLE_UPDATES {
+                winuser::KillTimer(self.window, UPDATE_TIMER_ID);
+            }
+        }
+    }
+
+    unsafe fn center_viewport(&self) {
+        let start = ((CONTENT_SIZE - VIEWPORT_SIZE) / 2) as f32;
+        let end = start + VIEWPORT_SIZE as f32;
+        ((*(*self.viewport).lpVtbl).ZoomToRect)(self.viewport, start, start, end, end, FALSE);
+    }
+
+    // Implement IUnknown
+    unsafe extern "system" fn QueryInterface(
+        this: *mut IUnknown,
+        riid: REFIID,
+        ppvObject: *mut *mut c_void,
+    ) -> HRESULT {
+        if ppvObject.is_null() {
+            return E_POINTER;
+        }
+        let riid = &*riid;
+        if !IsEqualIID(riid, &IUnknown::uuidof())
+            && !IsEqualIID(riid, &IID_IDIRECT_MANIPULATION_VIEWPORT_EVENT_HANDLER)
+        {
+            *ppvObject = ptr::null_mut();
+            return E_NOINTERFACE;
+        }
+        Self::AddRef(this);
+        *ppvObject = this as *mut c_void;
+        S_OK
+    }
+
+    unsafe extern "system" fn AddRef(this: *mut IUnknown) -> ULONG {
+        let handler = Self::from_interface(this);
+        let count = handler.refcount.fetch_add(1, Ordering::Release) + 1;
+        count as ULONG
+    }
+
+    unsafe extern "system" fn Release(this: *mut IUnknown) -> ULONG {
+        let handler = Self::from_interface(this);
+        let count = handler.refcount.fetch_sub(1, Ordering::Release) - 1;
+        if count == 0 {
+            // Destroy the underlying data
+            drop(Box::from_raw(
+                handler as *const ViewportEventHandlerData as *mut ViewportEventHandlerData,
+            ));
+        }
+        count as ULONG
+    }
+
+    // Implement IDirectManipulationViewportEventHandler
+    unsafe extern "system" fn OnViewportStatusChanged(
+        this: *mut IDirectManipulationViewportEventHandler,
+        _viewport: *mut IDirectManipulationViewport,
+        current: DirectManipulationStatus,
+        previous: DirectManipulationStatus,
+    ) -> HRESULT {
+        let handler = Self::from_interface(this);
+        handler.status.set(current);
+        match current {
+            DIRECTMANIPULATION_RUNNING => handler.send_scroll((0.0, 0.0), TouchPhase::Started),
+            // The fingers are lifted, and the inertia follows as moves like on macOS.
+            DIRECTMANIPULATION_INERTIA if previous == DIRECTMANIPULATION_RUNNING => {
+                handler.send_scroll((0.0, 0.0), TouchPhase::Ended)
+            }
+            DIRECTMANIPULATION_READY => {
+                if previous == DIRECTMANIPULATION_RUNNING {
+                    handler.send_scroll((0.0, 0.0), TouchPhase::Ended);
+                }
+                handler.reset_pending.set(true);
+            }
+            _ => (),
+        }
+        S_OK
+    }
+
+    unsafe extern "system" fn OnViewportUpdated(
+        _this: *mut IDirectManipulationViewportEventHandler,
+        _viewport: *mut IDirectManipulationViewport,
+    ) -> HRESULT {
+        S_OK
+    }
+
+    unsafe extern "system" fn OnContentUpdated(
+        this: *mut IDirectManipulationViewportEventHandler,
+        _viewport: *mut IDirectManipulationViewport,
+        content: *mut IDirectManipulationContent,
+    ) -> HRESULT {
+        let handler = Self::from_interface(this);
+        let mut matrix = [0.0f32; 6];
+        let hr = ((*(*content).lpVtbl).GetContentTransform)(
+            content,
+            matrix.as_mut_ptr(),
+            matrix.len() as DWORD,
+        );
+        if hr != S_OK {
+            return hr;
+        }
+        let (x, y) = (matrix[4], matrix[5]);
+        let (previous_x, previous_y) = handler.translation.replace((x, y));
+        match handler.status.get() {
+            DIRECTMANIPULATION_RUNNING | DIRECTMANIPULATION_INERTIA => {
+                // The content follows the fingers, while the scrolls to the right are positive.
+                let delta = (previous_x - x, y - previous_y);
+                if delta != (0.0, 0.0) {
+                    handler.send_scroll(delta, TouchPhase::Moved);
+                }
+            }
+            _ => (),
+        }
+        S_OK
+    }
+
+    unsafe fn from_interface<'a, InterfaceT>(
+        this: *mut InterfaceT,
+    ) -> &'a ViewportEventHandlerData {
+        &*(this as *const ViewportEventHandlerData)
+    }
+}
+
+impl Drop for DirectManipulation {
+    fn drop(&mut self) {
+        unsafe {
+            winuser::KillTimer(self.window, UPDATE_TIMER_ID);
+            if !self.viewport.is_null() {
+                let vtbl = &*(*self.viewport).lpVtbl;
+                if self.cookie != 0 {
+                    (vtbl.RemoveEventHandler)(self.viewport, self.cookie);
+                }
+                (vtbl.Abandon)(self.viewport);
+                (*(self.viewport as *mut IUnknown)).Release();
+            }
+            if !self.update_manager.is_null() {
+                (*(self.update_manager as *mut IUnknown)).Release();
+            }
+            if !self.manager.is_null() {
+                ((*(*self.manager).lpVtbl).Deactivate)(self.manager, self.window);
+                (*(self.manager as *mut IUnknown)).Release();
+            }
+            DirectManipulation::Release(self.handler as *mut IUnknown);
+        }
+    }
+}
+
+/// The result of the call following a successful one.
+fn hr_and(hr: HRESULT, next: impl FnOnce() -> HRESULT) -> HRESULT {
+    match hr {
+        S_OK => next(),
+        hr => hr,
+    }
+}
+
+static VIEWPORT_EVENT_HANDLER_VTBL: IDirectManipulationViewportEventHandlerVtbl =
+    IDirectManipulationViewportEventHandlerVtbl {
+        parent: IUnknownVtbl {
+            QueryInterface: DirectManipulation::QueryInterface,
+            AddRef: DirectManipulation::AddRef,
+            Release: DirectManipulation::Release,
+        },
+        OnViewportStatusChanged: DirectManipulation::OnViewportStatusChanged,
+        OnViewportUpdated: DirectManipulation::OnViewportUpdated,
+        OnContentUpdated: DirectManipulation::OnContentUpdated,
+    };
@@ -1,12 +1,14 @@
 #![allow(non_snake_case, unused_unsafe)]
 
-use std::sync::Once;
+use std::{ptr, sync::Once};
 
 use crate::platform_impl::platform::util::{
-    ENABLE_NON_CLIENT_DPI_SCALING, GET_DPI_FOR_MONITOR, GET_DPI_FOR_WINDOW, SET_PROCESS_DPI_AWARE,
-    SET_PROCESS_DPI_AWARENESS, SET_PROCESS_DPI_AWARENESS_CONTEXT,
+    ENABLE_NON_CLIENT_DPI_SCALING, GET_DPI_FOR_MONITOR, GET_DPI_FOR_WINDOW,
+    GET_SYSTEM_METRICS_FOR_DPI, SET_PROCESS_DPI_AWARE, SET_PROCESS_DPI_AWARENESS,
+    SET_PROCESS_DPI_AWARENESS_CONTEXT,
 };
 use winapi::{
+    ctypes::c_int,
     shared::{
         minwindef::FALSE,
         windef::{DPI_AWARENESS_CONTEXT, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE, HMONITOR, HWND},
@@ -75,6 +77,41 @@ pub fn dpi_to_scale_factor(dpi: u32) -> f64 {
     dpi as f64 / BASE_DPI as f64
 }
 
+/// The size of the system cursors in logical pixels.
+pub fn system_cursor_size() -> u32 {
+    unsafe {
+        if let Some(GetSystemMetricsForDpi) = *GET_SYSTEM_METRICS_FOR_DPI {
+            // We are on Windows 10 Anniversary Update (1607) or later.
+            GetSystemMetricsForDpi(winuser::SM_CXCURSOR, BASE_DPI) as u32
+        } else {
+            // The metrics are for the DPI of the system.
+            let hdc = winuser::GetDC(ptr::null_mut());
+            let dpi = match hdc.is_null() {
+                true => BASE_DPI,
+                false => GetDeviceCaps(hdc, LOGPIXELSX) as u32,
+            };
+            winuser::ReleaseDC(ptr::null_mut(), hdc);
+            winuser::GetSystemMetrics(winuser::SM_CXCURSOR) as u32 * BASE_DPI / dpi.max(1)
+        }
+    }
+}
+
+/// A metric of the system at the DPI of the window.
+pub unsafe fn system_metric(hwnd: HWND, index: c_int) -> i32 {
+    if let Some(GetSystemMetricsForDpi) = *GET_SYSTEM_METRICS_FOR_DPI {
+        // We are on Windows 10 Anniversary Update (1607) or later.
+        GetSystemMetricsForDpi(index, hwnd_dpi(hwnd))
+    } else {
+        // The metrics are for the DPI of the system.
+        winuser::GetSystemMetrics(index)
+    }
+}
+
+/// The height of the resize frame of the window, which is above its title bar.
+pub unsafe fn frame_thickness(hwnd: HWND) -> i32 {
+    system_metric(hwnd, winuser::SM_CYSIZEFRAME) + system_metric(hwnd, winuser::SM_CXPADDEDBORDER)
+}
+
 pub unsafe fn hwnd_dpi(hwnd: HWND) -> u32 {
     let hdc = winuser::GetDC(hwnd);
     if hdc.is_null() {
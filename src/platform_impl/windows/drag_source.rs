@@ -0,0 +1,795 @@
+//! Dragging data out of the windows with OLE.
+
+use std::{
+    cell::Cell,
+    mem, ptr, slice,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use winapi::{
+    ctypes::c_void,
+    shared::{
+        guiddef::{IsEqualIID, GUID, REFIID},
+        minwindef::{BOOL, DWORD, HGLOBAL, LPARAM, MAKELONG, ULONG, WPARAM},
+        windef::{COLORREF, HBITMAP, HWND, POINT, SIZE},
+        winerror::{
+            DATA_S_SAMEFORMATETC, DRAGDROP_S_CANCEL, DRAGDROP_S_DROP, DRAGDROP_S_USEDEFAULTCURSORS,
+            DV_E_FORMATETC, DV_E_TYMED, E_NOINTERFACE, E_NOTIMPL, E_OUTOFMEMORY, E_POINTER,
+            OLE_E_ADVISENOTSUPPORTED, S_FALSE, S_OK,
+        },
+        wtypes::{CLIPFORMAT, DVASPECT_CONTENT},
+        wtypesbase::CLSCTX_INPROC_SERVER,
+    },
+    um::{
+        combaseapi,
+        objidl::{
+            IAdviseSink, IDataObject, IDataObjectVtbl, IEnumFORMATETC, IEnumFORMATETCVtbl,
+            IEnumSTATDATA, FORMATETC, STGMEDIUM, TYMED_HGLOBAL,
+        },
+        ole2,
+        oleidl::{
+            IDropSource, IDropSourceVtbl, DROPEFFECT_COPY, DROPEFFECT_LINK, DROPEFFECT_MOVE,
+            DROPEFFECT_NONE,
+        },
+        unknwnbase::{IUnknown, IUnknownVtbl},
+        winbase, wingdi,
+        winnt::{HRESULT, LONG},
+        winuser::{self, MK_LBUTTON, MK_MBUTTON, MK_RBUTTON},
+    },
+    Interface,
+};
+
+use crate::{
+    clipboard::{ClipboardMimedContent, ClipboardProvider, MimeType},
+    dnd::{DndAction, DndActions, DragIcon},
+    platform_impl::platform::{clipboard, event_loop},
+};
+
+/// The effects the drop target is allowed to choose among.
+pub fn actions_effects(actions: DndActions) -> DWORD {
+    let mut effects = DROPEFFECT_NONE;
+    if actions.contains(DndActions::COPY) {
+        effects |= DROPEFFECT_COPY;
+    }
+    if actions.contains(DndActions::MOVE) {
+        effects |= DROPEFFECT_MOVE;
+    }
+    if actions.contains(DndActions::LINK) {
+        effects |= DROPEFFECT_LINK;
+    }
+    effects
+}
+
+/// The action taken by the drop target, `None` if the drag was cancelled.
+pub fn effect_action(effect: DWORD) -> Option<DndAction> {
+    if effect & DROPEFFECT_MOVE != 0 {
+        Some(DndAction::Move)
+    } else if effect & DROPEFFECT_COPY != 0 {
+        Some(DndAction::Copy)
+    } else if effect & DROPEFFECT_LINK != 0 {
+        Some(DndAction::Link)
+    } else {
+        None
+    }
+}
+
+/// Hands out the object if it implements the requested interface.
+unsafe fn query_interface(
+    this: *mut IUnknown,
+    iid: &GUID,
+    riid: REFIID,
+    object: *mut *mut c_void,
+) -> HRESULT {
+    if object.is_null() {
+        return E_POINTER;
+    }
+    if IsEqualIID(&*riid, iid) || IsEqualIID(&*riid, &IUnknown::uuidof()) {
+        (*(*this).lpVtbl).AddRef(this);
+        *object = this as *mut c_void;
+        S_OK
+    } else {
+        *object = ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+/// The formats the data is offered in.
+fn formats(provider: &dyn ClipboardProvider) -> Vec<(CLIPFORMAT, MimeType)> {
+    provider
+        .mime_types()
+        .into_iter()
+        .map(|mime_type| {
+            (
+                clipboard::mime_type_format(&mime_type) as CLIPFORMAT,
+                mime_type,
+            )
+        })
+        .collect()
+}
+
+fn format_etc(format: CLIPFORMAT) -> FORMATETC {
+    FORMATETC {
+        cfFormat: format,
+        ptd: ptr::null(),
+        dwAspect: DVASPECT_CONTENT,
+        lindex: -1,
+        tymed: TYMED_HGLOBAL,
+    }
+}
+
+#[repr(C)]
+pub struct DragDataObjectData {
+    pub interface: IDataObject,
+    refcount: AtomicUsize,
+    // The window the data is dragged from.
+    source: HWND,
+    provider: Arc<dyn ClipboardProvider>,
+    formats: Vec<(CLIPFORMAT, MimeType)>,
+    // The data placed into the object with `SetData`, which is how the drag image helper
+    // passes the image to the drop targets.
+    stored: Vec<(CLIPFORMAT, Vec<u8>)>,
+    // Whether the data was dropped into one of our windows.
+    dropped_inside: Cell<bool>,
+}
+
+/// The data being dragged, which is only rendered once the drop target requests it.
+pub struct DragDataObject {
+    pub data: *mut DragDataObjectData,
+}
+
+#[allow(non_snake_case)]
+impl DragDataObject {
+    pub fn new(source: HWND, provider: Arc<dyn ClipboardProvider>) -> DragDataObject {
+        let data = Box::new(DragDataObjectData {
+            interface: IDataObject {
+                lpVtbl: &DATA_OBJECT_VTBL as *const IDataObjectVtbl,
+            },
+            refcount: AtomicUsize::new(1),
+            source,
+            formats: formats(&*provider),
+            provider,
+            stored: Vec::new(),
+            dropped_inside: Cell::new(false),
+        });
+        DragDataObject {
+            data: Box::into_raw(data),
+        }
+    }
+
+    // Implement IUnknown
+    pub unsafe extern "system" fn QueryInterface(
+        this: *mut IUnknown,
+        riid: REFIID,
+        ppvObject: *mut *mut c_void,
+    ) -> HRESULT {
+        query_interface(this, &IDataObject::uuidof(), riid, ppvObject)
+    }
+
+    pub unsafe extern "system" fn AddRef(this: *mut IUnknown) -> ULONG {
+        let data_object = Self::from_interface(this);
+        let count = data_object.refcount.fetch_add(1, Ordering::Release) + 1;
+        count as ULONG
+    }
+
+    pub unsafe extern "system" fn Release(this: *mut IUnknown) -> ULONG {
+        let data_object = Self::from_interface(this);
+        let count = data_object.refcount.fetch_sub(1, Ordering::Release) - 1;
+        if count == 0 {
+            // Destroy the underlying data
+            Box::from_raw(data_object as *mut DragDataObjectData);
+        }
+        count as ULONG
+    }
+
+    pub unsafe extern "system" fn GetData(
+        this: *mut IDataObject,
+        pformatetcIn: *const FORMATETC,
+        pmedium: *mut STGMEDIUM,
+    ) -> HRESULT {
+        let data_object = Self::from_interface(this);
+        let data = match data_object.data(pformatetcIn) {
+            Ok(data) => data,
+            Err(err) => return err,
+        };
+        let handle = match clipboard::global_alloc_data(&data) {
+            Ok(handle) => handle,
+            Err(_) => return E_OUTOFMEMORY,
+        };
+
+        // The drop target releases the memory once done with it, since there's no object to
+        // release it through.
+        (*pmedium).tymed = TYMED_HGLOBAL;
+        (*pmedium).u = handle as *mut _;
+        (*pmedium).pUnkForRelease = ptr::null_mut();
+
+        S_OK
+    }
+
+    pub unsafe extern "system" fn GetDataHere(
+        _this: *mut IDataObject,
+        _pformatetc: *const FORMATETC,
+        _pmedium: *mut STGMEDIUM,
+    ) -> HRESULT {
+        E_NOTIMPL
+    }
+
+    pub unsafe extern "system" fn QueryGetData(
+        this: *mut IDataObject,
+        pformatetc: *const FORMATETC,
+    ) -> HRESULT {
+        let data_object = Self::from_interface(this);
+        match data_object.format(pformatetc) {
+            Ok(_) => S_OK,
+            Err(err) => err,
+        }
+    }
+
+    pub unsafe extern "system" fn GetCanonicalFormatEtc(
+        _this: *mut IDataObject,
+        _pformatectIn: *const FORMATETC,
+        pformatetcOut: *mut FORMATETC,
+    ) -> HRESULT {
+        (*pformatetcOut).ptd = ptr::null();
+        DATA_S_SAMEFORMATETC
+    }
+
+    pub unsafe extern "system" fn SetData(
+        this: *mut IDataObject,
+        pformatetc: *const FORMATETC,
+        pmedium: *const STGMEDIUM,
+        fRelease: BOOL,
+    ) -> HRESULT {
+        let data_object = Self::from_interface(this);
+        if (*pformatetc).tymed != TYMED_HGLOBAL || (*pmedium).tymed != TYMED_HGLOBAL {
+            return E_NOTIMPL;
+        }
+
+        // The union holding the handle is declared as a pointer to it.
+        let handle = (*pmedium).u as HGLOBAL;
+        let buffer = winbase::GlobalLock(handle) as *const u8;
+        if buffer.is_null() {
+            return E_OUTOFMEMORY;
+        }
+        let data = slice::from_raw_parts(buffer, winbase::GlobalSize(handle)).to_vec();
+        winbase::GlobalUnlock(handle);
+        if fRelease != 0 {
+            ole2::ReleaseStgMedium(pmedium as *mut STGMEDIUM);
+        }
+
+        let format = (*pformatetc).cfFormat;
+        data_object.stored.retain(|(stored, _)| *stored != format);
+        data_object.stored.push((format, data));
+        S_OK
+    }
+
+    pub unsafe extern "system" fn EnumFormatEtc(
+        this: *mut IDataObject,
+        dwDirection: DWORD,
+        ppenumFormatEtc: *mut *mut IEnumFORMATETC,
+    ) -> HRESULT {
+        use winapi::um::objidl::DATADIR_GET;
+
+        if dwDirection != DATADIR_GET {
+            return E_NOTIMPL;
+        }
+
+        let data_object = Self::from_interface(this);
+        let formats = data_object
+            .formats
+            .iter()
+            .map(|(format, _)| *format)
+            .chain(data_object.stored.iter().map(|(format, _)| *format))
+            .map(format_etc)
+            .collect();
+        *ppenumFormatEtc = FormatEnumerator::new(formats, 0);
+        S_OK
+    }
+
+    pub unsafe extern "system" fn DAdvise(
+        _this: *mut IDataObject,
+        _pformatetc: *const FORMATETC,
+        _advf: DWORD,
+        _pAdvSink: *const IAdviseSink,
+        _pdwConnection: *mut DWORD,
+    ) -> HRESULT {
+        OLE_E_ADVISENOTSUPPORTED
+    }
+
+    pub unsafe extern "system" fn DUnadvise(
+        _this: *mut IDataObject,
+        _dwConnection: DWORD,
+    ) -> HRESULT {
+        OLE_E_ADVISENOTSUPPORTED
+    }
+
+    pub unsafe extern "system" fn EnumDAdvise(
+        _this: *mut IDataObject,
+        _ppenumAdvise: *const *const IEnumSTATDATA,
+    ) -> HRESULT {
+        OLE_E_ADVISENOTSUPPORTED
+    }
+
+    unsafe fn from_interface<'a, InterfaceT>(this: *mut InterfaceT) -> &'a mut DragDataObjectData {
+        &mut *(this as *mut _)
+    }
+}
+
+impl DragDataObjectData {
+    /// The requested format, if the data is available in it.
+    unsafe fn format(&self, format: *const FORMATETC) -> Result<CLIPFORMAT, HRESULT> {
+        let format = &*format;
+        if format.tymed & TYMED_HGLOBAL == 0 {
+            return Err(DV_E_TYMED);
+        }
+        if format.dwAspect != DVASPECT_CONTENT {
+            return Err(DV_E_FORMATETC);
+        }
+        let offered = self
+            .formats
+            .iter()
+            .any(|(offered, _)| *offered == format.cfFormat);
+        let stored = self
+            .stored
+            .iter()
+            .any(|(stored, _)| *stored == format.cfFormat);
+        if offered || stored {
+            Ok(format.cfFormat)
+        } else {
+            Err(DV_E_FORMATETC)
+        }
+    }
+
+    /// The data in the requested format, which is rendered if it's not stored in the object.
+    unsafe fn data(&self, format: *const FORMATETC) -> Result<Vec<u8>, HRESULT> {
+        let format = self.format(format)?;
+        if let Some((_, data)) = self.stored.iter().find(|(stored, _)| *stored == format) {
+            return Ok(data.clone());
+        }
+
+        let mime_type = self
+            .formats
+            .iter()
+            .find(|(offered, _)| *offered == format)
+            .map(|(_, mime_type)| mime_type.clone())
+            .ok_or(DV_E_FORMATETC)?;
+        match ClipboardMimedContent::from_provider(&*self.provider, mime_type) {
+            Ok(content) => Ok(clipboard::content_format_data(content).1),
+            Err(err) => {
+                warn!("Failed to render the dragged data: {}", err);
+                Err(E_OUTOFMEMORY)
+            }
+        }
+    }
+}
+
+impl Drop for DragDataObject {
+    fn drop(&mut self) {
+        unsafe {
+            DragDataObject::Release(self.data as *mut IUnknown);
+        }
+    }
+}
+
+impl DragDataObject {
+    /// Whether the data was dropped into one of our windows.
+    pub fn dropped_inside(&self) -> bool {
+        unsafe { (*self.data).dropped_inside.get() }
+    }
+}
+
+/// The data of the object, if it's the one of a drag started by the application.
+///
+/// The drag runs on the thread of the window it's started from, so the drop targets of the
+/// windows on that thread get the very same object.
+unsafe fn local_data<'a>(data_object: *const IDataObject) -> Option<&'a DragDataObjectData> {
+    if (*data_object).lpVtbl != &DATA_OBJECT_VTBL as *const IDataObjectVtbl {
+        return None;
+    }
+    Some(&*(data_object as *const DragDataObjectData))
+}
+
+/// The window the data is dragged from along with the provider of the data, if the data object
+/// is the one of a drag started by the application.
+pub unsafe fn local_drag(
+    data_object: *const IDataObject,
+) -> Option<(HWND, Arc<dyn ClipboardProvider>)> {
+    let data_object = local_data(data_object)?;
+    Some((data_object.source, Arc::clone(&data_object.provider)))
+}
+
+/// Remembers that the data of the object was dropped into one of our windows, if it's the one
+/// of a drag started by the application.
+pub unsafe fn set_dropped_inside(data_object: *const IDataObject) {
+    if let Some(data_object) = local_data(data_object) {
+        data_object.dropped_inside.set(true);
+    }
+}
+
+static DATA_OBJECT_VTBL: IDataObjectVtbl = IDataObjectVtbl {
+    parent: IUnknownVtbl {
+        QueryInterface: DragDataObject::QueryInterface,
+        AddRef: DragDataObject::AddRef,
+        Release: DragDataObject::Release,
+    },
+    GetData: DragDataObject::GetData,
+    GetDataHere: DragDataObject::GetDataHere,
+    QueryGetData: DragDataObject::QueryGetData,
+    GetCanonicalFormatEtc: DragDataObject::GetCanonicalFormatEtc,
+    SetData: DragDataObject::SetData,
+    EnumFormatEtc: DragDataObject::EnumFormatEtc,
+    DAdvise: DragDataObject::DAdvise,
+    DUnadvise: DragDataObject::DUnadvise,
+    EnumDAdvise: DragDataObject::EnumDAdvise,
+};
+
+/// The enumerator of the formats the dragged data is offered in.
+#[repr(C)]
+struct FormatEnumerator {
+    interface: IEnumFORMATETC,
+    refcount: AtomicUsize,
+    formats: Vec<FORMATETC>,
+    position: usize,
+}
+
+#[allow(non_snake_case)]
+impl FormatEnumerator {
+    fn new(formats: Vec<FORMATETC>, position: usize) -> *mut IEnumFORMATETC {
+        let enumerator = Box::new(FormatEnumerator {
+            interface: IEnumFORMATETC {
+                lpVtbl: &FORMAT_ENUMERATOR_VTBL as *const IEnumFORMATETCVtbl,
+            },
+            refcount: AtomicUsize::new(1),
+            formats,
+            position,
+        });
+        Box::into_raw(enumerator) as *mut IEnumFORMATETC
+    }
+
+    unsafe extern "system" fn QueryInterface(
+        this: *mut IUnknown,
+        riid: REFIID,
+        ppvObject: *mut *mut c_void,
+    ) -> HRESULT {
+        query_interface(this, &IEnumFORMATETC::uuidof(), riid, ppvObject)
+    }
+
+    unsafe extern "system" fn AddRef(this: *mut IUnknown) -> ULONG {
+        let enumerator = Self::from_interface(this);
+        let count = enumerator.refcount.fetch_add(1, Ordering::Release) + 1;
+        count as ULONG
+    }
+
+    unsafe extern "system" fn Release(this: *mut IUnknown) -> ULONG {
+        let enumerator = Self::from_interface(this);
+        let count = enumerator.refcount.fetch_sub(1, Ordering::Release) - 1;
+        if count == 0 {
+            Box::from_raw(enumerator as *mut FormatEnumerator);
+        }
+        count as ULONG
+    }
+
+    unsafe extern "system" fn Next(
+        this: *mut IEnumFORMATETC,
+        celt: ULONG,
+        rgelt: *mut FORMATETC,
+        pceltFetched: *mut ULONG,
+    ) -> HRESULT {
+        let enumerator = Self::from_interface(this);
+        let remaining = &enumerator.formats[enumerator.position..];
+        let fetched = remaining.len().min(celt as usize);
+        ptr::copy_nonoverlapping(remaining.as_ptr(), rgelt, fetched);
+        enumerator.position += fetched;
+
+        if !pceltFetched.is_null() {
+            *pceltFetched = fetched as ULONG;
+        }
+        if fetched == celt as usize {
+            S_OK
+        } else {
+            S_FALSE
+        }
+    }
+
+    unsafe extern "system" fn Skip(this: *mut IEnumFORMATETC, celt: ULONG) -> HRESULT {
+        let enumerator = Self::from_interface(this);
+        let skipped = (enumerator.formats.len() - enumerator.position).min(celt as usize);
+        enumerator.position += skipped;
+        if skipped == celt as usize {
+            S_OK
+        } else {
+            S_FALSE
+        }
+    }
+
+    unsafe extern "system" fn Reset(this: *mut IEnumFORMATETC) -> HRESULT {
+        Self::from_interface(this).position = 0;
+        S_OK
+    }
+
+    unsafe extern "system" fn Clone(
+        this: *mut IEnumFORMATETC,
+        ppenum: *mut *mut IEnumFORMATETC,
+    ) -> HRESULT {
+        let enumerator = Self::from_interface(this);
+        *ppenum = FormatEnumerator::new(enumerator.formats.clone(), enumerator.position);
+        S_OK
+    }
+
+    unsafe fn from_interface<'a, InterfaceT>(this: *mut InterfaceT) -> &'a mut FormatEnumerator {
+        &mut *(this as *mut _)
+    }
+}
+
+static FORMAT_ENUMERATOR_VTBL: IEnumFORMATETCVtbl = IEnumFORMATETCVtbl {
+    parent: IUnknownVtbl {
+        QueryInterface: FormatEnumerator::QueryInterface,
+        AddRef: FormatEnumerator::AddRef,
+        Release: FormatEnumerator::Release,
+    },
+    Next: FormatEnumerator::Next,
+    Skip: FormatEnumerator::Skip,
+    Reset: FormatEnumerator::Reset,
+    Clone: FormatEnumerator::Clone,
+};
+
+#[repr(C)]
+pub struct DragSourceData {
+    pub interface: IDropSource,
+    refcount: AtomicUsize,
+    // The window the data is dragged from, which is told about the progress of the drag.
+    window: HWND,
+    // The action the drop target under the cursor accepts the data with.
+    action: Option<DndAction>,
+    // The latest position of the cursor in the window.
+    position: Option<(i32, i32)>,
+}
+
+/// The source of the drag, which tells when the drag is over.
+pub struct DragSource {
+    pub data: *mut DragSourceData,
+}
+
+#[allow(non_snake_case)]
+impl DragSource {
+    pub fn new(window: HWND) -> DragSource {
+        let data = Box::new(DragSourceData {
+            interface: IDropSource {
+                lpVtbl: &DROP_SOURCE_VTBL as *const IDropSourceVtbl,
+            },
+            refcount: AtomicUsize::new(1),
+            window,
+            action: None,
+            position: None,
+        });
+        DragSource {
+            data: Box::into_raw(data),
+        }
+    }
+
+    // Implement IUnknown
+    pub unsafe extern "system" fn QueryInterface(
+        this: *mut IUnknown,
+        riid: REFIID,
+        ppvObject: *mut *mut c_void,
+    ) -> HRESULT {
+        query_interface(this, &IDropSource::uuidof(), riid, ppvObject)
+    }
+
+    pub unsafe extern "system" fn AddRef(this: *mut IUnknown) -> ULONG {
+        let drag_source = Self::from_interface(this);
+        let count = drag_source.refcount.fetch_add(1, Ordering::Release) + 1;
+        count as ULONG
+    }
+
+    pub unsafe extern "system" fn Release(this: *mut IUnknown) -> ULONG {
+        let drag_source = Self::from_interface(this);
+        let count = drag_source.refcount.fetch_sub(1, Ordering::Release) - 1;
+        if count == 0 {
+            // Destroy the underlying data
+            Box::from_raw(drag_source as *mut DragSourceData);
+        }
+        count as ULONG
+    }
+
+    pub unsafe extern "system" fn QueryContinueDrag(
+        _this: *mut IDropSource,
+        fEscapePressed: BOOL,
+        grfKeyState: DWORD,
+    ) -> HRESULT {
+        if fEscapePressed != 0 {
+            DRAGDROP_S_CANCEL
+        } else if grfKeyState & (MK_LBUTTON | MK_MBUTTON | MK_RBUTTON) as DWORD == 0 {
+            // The data is dropped once the button the drag was started with is released.
+            DRAGDROP_S_DROP
+        } else {
+            S_OK
+        }
+    }
+
+    // Called whenever the cursor moves, with the effect the drop target under it chose. The
+    // window is told about the drag through the messages dispatched by the modal loop.
+    pub unsafe extern "system" fn GiveFeedback(this: *mut IDropSource, dwEffect: DWORD) -> HRESULT {
+        let drag_source = Self::from_interface(this);
+
+        let action = effect_action(dwEffect);
+        if action != drag_source.action {
+            drag_source.action = action;
+            if action.is_some() {
+                winuser::PostMessageW(
+                    drag_source.window,
+                    *event_loop::DRAG_ENTERED_MSG_ID,
+                    dwEffect as WPARAM,
+                    0,
+                );
+            }
+        }
+
+        let mut point: POINT = mem::zeroed();
+        if winuser::GetCursorPos(&mut point) != 0 {
+            winuser::ScreenToClient(drag_source.window, &mut point);
+            let position = Some((point.x, point.y));
+            if position != drag_source.position {
+                drag_source.position = position;
+                winuser::PostMessageW(
+                    drag_source.window,
+                    *event_loop::DRAG_MOVED_MSG_ID,
+                    0,
+                    MAKELONG(point.x as u16, point.y as u16) as LPARAM,
+                );
+            }
+        }
+
+        DRAGDROP_S_USEDEFAULTCURSORS
+    }
+
+    unsafe fn from_interface<'a, InterfaceT>(this: *mut InterfaceT) -> &'a mut DragSourceData {
+        &mut *(this as *mut _)
+    }
+}
+
+impl Drop for DragSource {
+    fn drop(&mut self) {
+        unsafe {
+            DragSource::Release(self.data as *mut IUnknown);
+        }
+    }
+}
+
+static DROP_SOURCE_VTBL: IDropSourceVtbl = IDropSourceVtbl {
+    parent: IUnknownVtbl {
+        QueryInterface: DragSource::QueryInterface,
+        AddRef: DragSource::AddRef,
+        Release: DragSource::Release,
+    },
+    QueryContinueDrag: DragSource::QueryContinueDrag,
+    GiveFeedback: DragSource::GiveFeedback,
+};
+
+// The drag image helper isn't declared by `winapi`.
+const CLSID_DRAG_DROP_HELPER: GUID = GUID {
+    Data1: 0x4657278A,
+    Data2: 0x411B,
+    Data3: 0x11D2,
+    Data4: [0x83, 0x9A, 0x00, 0xC0, 0x4F, 0xD9, 0x18, 0xD0],
+};
+const IID_IDRAG_SOURCE_HELPER: GUID = GUID {
+    Data1: 0xDE5BF786,
+    Data2: 0x477A,
+    Data3: 0x11D2,
+    Data4: [0x83, 0x9D, 0x00, 0xC0, 0x4F, 0xD9, 0x18, 0xD0],
+};
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct SHDRAGIMAGE {
+    sizeDragImage: SIZE,
+    ptOffset: POINT,
+    hbmpDragImage: HBITMAP,
+    crColorKey: COLORREF,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct IDragSourceHelperVtbl {
+    parent: IUnknownVtbl,
+    InitializeFromBitmap: unsafe extern "system" fn(
+        This: *mut IDragSourceHelper,
+        pshdi: *mut SHDRAGIMAGE,
+        pDataObject: *mut IDataObject,
+    ) -> HRESULT,
+    InitializeFromWindow: unsafe extern "system" fn(
+        This: *mut IDragSourceHelper,
+        hwnd: HWND,
+        ppt: *mut POINT,
+        pDataObject: *mut IDataObject,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct IDragSourceHelper {
+    lpVtbl: *const IDragSourceHelperVtbl,
+}
+
+/// Creates a bitmap holding the image in the premultiplied BGRA layout the shell expects.
+unsafe fn icon_bitmap(icon: &DragIcon) -> Option<HBITMAP> {
+    let image = &icon.image;
+    let mut info: wingdi::BITMAPINFO = mem::zeroed();
+    info.bmiHeader.biSize = mem::size_of::<wingdi::BITMAPINFOHEADER>() as DWORD;
+    info.bmiHeader.biWidth = image.width as LONG;
+    // A negative height makes the bitmap top-down, like the image.
+    info.bmiHeader.biHeight = -(image.height as LONG);
+    info.bmiHeader.biPlanes = 1;
+    info.bmiHeader.biBitCount = 32;
+    info.bmiHeader.biCompression = wingdi::BI_RGB;
+
+    let mut bits = ptr::null_mut();
+    let bitmap = wingdi::CreateDIBSection(
+        ptr::null_mut(),
+        &info,
+        wingdi::DIB_RGB_COLORS,
+        &mut bits,
+        ptr::null_mut(),
+        0,
+    );
+    if bitmap.is_null() || bits.is_null() {
+        return None;
+    }
+
+    let pixels = slice::from_raw_parts_mut(bits as *mut u8, image.rgba.len());
+    for (pixel, rgba) in pixels.chunks_exact_mut(4).zip(image.rgba.chunks_exact(4)) {
+        let alpha = rgba[3] as u32;
+        pixel[0] = (rgba[2] as u32 * alpha / 255) as u8;
+        pixel[1] = (rgba[1] as u32 * alpha / 255) as u8;
+        pixel[2] = (rgba[0] as u32 * alpha / 255) as u8;
+        pixel[3] = rgba[3];
+    }
+
+    Some(bitmap)
+}
+
+/// Attaches the icon to the dragged data, so the drag image helper shows it under the cursor.
+pub unsafe fn set_drag_icon(data_object: *mut IDataObject, icon: &DragIcon) {
+    let mut helper: *mut IDragSourceHelper = ptr::null_mut();
+    let hr = combaseapi::CoCreateInstance(
+        &CLSID_DRAG_DROP_HELPER,
+        ptr::null_mut(),
+        CLSCTX_INPROC_SERVER,
+        &IID_IDRAG_SOURCE_HELPER,
+        &mut helper as *mut _ as *mut _,
+    );
+    if hr != S_OK || helper.is_null() {
+        warn!("Failed to create the drag image helper: {:#x}", hr);
+        return;
+    }
+
+    match icon_bitmap(icon) {
+        Some(bitmap) => {
+            let mut drag_image = SHDRAGIMAGE {
+                sizeDragImage: SIZE {
+                    cx: icon.image.width as LONG,
+                    cy: icon.image.height as LONG,
+                },
+                ptOffset: POINT {
+                    x: icon.hotspot.x as LONG,
+                    y: icon.hotspot.y as LONG,
+                },
+                hbmpDragImage: bitmap,
+                // No color is transparent, the alpha channel is used instead.
+                crColorKey: 0xFFFFFFFF,
+            };
+            let hr =
+                ((*(*helper).lpVtbl).InitializeFromBitmap)(helper, &mut drag_image, data_object);
+            // The helper takes the ownership of the bitmap only on success.
+            if hr != S_OK {
+                warn!("Failed to set the drag image: {:#x}", hr);
+                wingdi::DeleteObject(bitmap as _);
+            }
+        }
+        None => warn!("Failed to create the drag image bitmap"),
+    }
+
+    ((*(*helper).lpVtbl).parent.Release)(helper as *mut IUnknown);
+}
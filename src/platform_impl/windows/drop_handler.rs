@@ -1,5 +1,6 @@
 use std::{
     ffi::OsString,
+    mem,
     os::windows::ffi::OsStringExt,
     path::PathBuf,
     ptr,
@@ -10,21 +11,33 @@ use winapi::{
     ctypes::c_void,
     shared::{
         guiddef::REFIID,
-        minwindef::{DWORD, UINT, ULONG},
-        windef::{HWND, POINTL},
-        winerror::S_OK,
+        minwindef::{DWORD, HGLOBAL, UINT, ULONG},
+        windef::{HWND, POINT, POINTL},
+        winerror::{SUCCEEDED, S_OK},
+        wtypes::{CLIPFORMAT, DVASPECT_CONTENT},
     },
     um::{
-        objidl::IDataObject,
-        oleidl::{IDropTarget, IDropTargetVtbl, DROPEFFECT_COPY, DROPEFFECT_NONE},
+        combaseapi,
+        objidl::{IDataObject, DATADIR_GET, FORMATETC, STGMEDIUM, TYMED_HGLOBAL},
+        ole2,
+        oleidl::{
+            IDropTarget, IDropTargetVtbl, DROPEFFECT_COPY, DROPEFFECT_LINK, DROPEFFECT_MOVE,
+            DROPEFFECT_NONE,
+        },
         shellapi, unknwnbase,
         winnt::HRESULT,
+        winuser,
     },
 };
 
-use crate::platform_impl::platform::WindowId;
+use crate::platform_impl::platform::{clipboard, drag_source, WindowId};
 
-use crate::{event::Event, window::WindowId as SuperWindowId};
+use crate::{
+    clipboard::{ClipboardMimedContent, MimeType},
+    dpi::PhysicalPosition,
+    event::Event,
+    window::WindowId as SuperWindowId,
+};
 
 #[repr(C)]
 pub struct FileDropHandlerData {
@@ -33,6 +46,8 @@ pub struct FileDropHandlerData {
     window: HWND,
     send_event: Box<dyn Fn(Event<'static, ()>)>,
     cursor_effect: DWORD,
+    // Whether the hovered data is of a kind we can read, and is to be sent with `Drop`.
+    content_is_valid: bool,
     hovered_is_valid: bool, /* If the currently hovered item is not valid there must not be any `HoveredFileCancelled` emitted */
 }
 
@@ -51,6 +66,7 @@ impl FileDropHandler {
             window,
             send_event,
             cursor_effect: DROPEFFECT_NONE,
+            content_is_valid: false,
             hovered_is_valid: false,
         });
         FileDropHandler {
@@ -101,8 +117,16 @@ impl FileDropHandler {
             });
         });
         drop_handler.hovered_is_valid = hdrop.is_some();
-        drop_handler.cursor_effect = if drop_handler.hovered_is_valid {
-            DROPEFFECT_COPY
+        drop_handler.content_is_valid = Self::drop_mime_type(pDataObj).is_some();
+        drop_handler.cursor_effect = if drop_handler.content_is_valid {
+            // Copying is preferred, since the source keeps the data then.
+            if *pdwEffect & DROPEFFECT_COPY != 0 {
+                DROPEFFECT_COPY
+            } else if *pdwEffect & DROPEFFECT_MOVE != 0 {
+                DROPEFFECT_MOVE
+            } else {
+                *pdwEffect & DROPEFFECT_LINK
+            }
         } else {
             DROPEFFECT_NONE
         };
@@ -140,10 +164,10 @@ impl FileDropHandler {
         this: *mut IDropTarget,
         pDataObj: *const IDataObject,
         _grfKeyState: DWORD,
-        _pt: *const POINTL,
-        _pdwEffect: *mut DWORD,
+        pt: *const POINTL,
+        pdwEffect: *mut DWORD,
     ) -> HRESULT {
-        use crate::event::WindowEvent::DroppedFile;
+        use crate::event::WindowEvent::{self, DroppedFile};
         let drop_handler = Self::from_interface(this);
         let hdrop = Self::iterate_filenames(pDataObj, |filename| {
             drop_handler.send_event(Event::WindowEvent {
@@ -155,6 +179,34 @@ impl FileDropHandler {
             shellapi::DragFinish(hdrop);
         }
 
+        let action = drag_source::effect_action(drop_handler.cursor_effect);
+        // The data dragged from our windows is read straight from its provider.
+        let local_drag = drag_source::local_drag(pDataObj);
+        drag_source::set_dropped_inside(pDataObj);
+        let content = match local_drag.as_ref() {
+            Some((_, provider)) => crate::dnd::local_drop_content(&**provider),
+            None => Self::drop_mime_type(pDataObj)
+                .and_then(|mime_type| Self::read_content(pDataObj, mime_type)),
+        };
+        if let (Some(content), Some(action)) = (content, action) {
+            // The point is in screen coordinates.
+            let mut point = POINT {
+                x: (*pt).x,
+                y: (*pt).y,
+            };
+            winuser::ScreenToClient(drop_handler.window, &mut point);
+            drop_handler.send_event(Event::WindowEvent {
+                window_id: SuperWindowId(WindowId(drop_handler.window)),
+                event: WindowEvent::Drop {
+                    content,
+                    position: PhysicalPosition::new(point.x as f64, point.y as f64),
+                    action,
+                    source: local_drag.map(|(source, _)| SuperWindowId(WindowId(source))),
+                },
+            });
+        }
+        *pdwEffect = drop_handler.cursor_effect;
+
         S_OK
     }
 
@@ -162,6 +214,54 @@ impl FileDropHandler {
         &mut *(this as *mut _)
     }
 
+    /// The kind of data to read from the dropped data, see `WindowEvent::Drop`.
+    unsafe fn drop_mime_type(data_obj: *const IDataObject) -> Option<MimeType> {
+        let mut enumerator = ptr::null_mut();
+        if (*data_obj).EnumFormatEtc(DATADIR_GET, &mut enumerator) != S_OK || enumerator.is_null() {
+            return None;
+        }
+
+        let mut offered = Vec::new();
+        let mut format: FORMATETC = mem::zeroed();
+        while (*enumerator).Next(1, &mut format, ptr::null_mut()) == S_OK {
+            if !format.ptd.is_null() {
+                combaseapi::CoTaskMemFree(format.ptd as *mut c_void);
+            }
+            if format.tymed & TYMED_HGLOBAL == 0 {
+                continue;
+            }
+            match clipboard::format_mime_type(format.cfFormat as UINT) {
+                Some(mime_type) if !offered.contains(&mime_type) => offered.push(mime_type),
+                _ => (),
+            }
+        }
+        (*enumerator).Release();
+
+        MimeType::preferred_drop_mime_type(&offered)
+    }
+
+    unsafe fn read_content(
+        data_obj: *const IDataObject,
+        mime_type: MimeType,
+    ) -> Option<ClipboardMimedContent> {
+        let mut format = FORMATETC {
+            cfFormat: clipboard::mime_type_format(&mime_type) as CLIPFORMAT,
+            ptd: ptr::null(),
+            dwAspect: DVASPECT_CONTENT,
+            lindex: -1,
+            tymed: TYMED_HGLOBAL,
+        };
+
+        let mut medium: STGMEDIUM = mem::zeroed();
+        if !SUCCEEDED((*data_obj).GetData(&mut format, &mut medium)) {
+            return None;
+        }
+        // The union holding the handle is declared as a pointer to it.
+        let content = clipboard::global_content(mime_type, medium.u as HGLOBAL);
+        ole2::ReleaseStgMedium(&mut medium);
+        content
+    }
+
     unsafe fn iterate_filenames<F>(
         data_obj: *const IDataObject,
         callback: F,
@@ -170,15 +270,8 @@ impl FileDropHandler {
         F: Fn(PathBuf),
     {
         use winapi::{
-            shared::{
-                winerror::{DV_E_FORMATETC, SUCCEEDED},
-                wtypes::{CLIPFORMAT, DVASPECT_CONTENT},
-            },
-            um::{
-                objidl::{FORMATETC, TYMED_HGLOBAL},
-                shellapi::DragQueryFileW,
-                winuser::CF_HDROP,
-            },
+            shared::winerror::DV_E_FORMATETC,
+            um::{shellapi::DragQueryFileW, winuser::CF_HDROP},
         };
 
         let mut drop_format = FORMATETC {
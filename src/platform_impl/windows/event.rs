@@ -85,6 +85,21 @@ impl From<ModifiersStateSide> for ModifiersState {
     }
 }
 
+/// The modifier of the sides a key is, for the keys told apart by `handle_extended_keys`.
+pub fn vkey_to_modifier_side(vkey: c_int) -> Option<ModifiersStateSide> {
+    match vkey {
+        winuser::VK_LSHIFT => Some(ModifiersStateSide::LSHIFT),
+        winuser::VK_RSHIFT => Some(ModifiersStateSide::RSHIFT),
+        winuser::VK_LCONTROL => Some(ModifiersStateSide::LCTRL),
+        winuser::VK_RCONTROL => Some(ModifiersStateSide::RCTRL),
+        winuser::VK_LMENU => Some(ModifiersStateSide::LALT),
+        winuser::VK_RMENU => Some(ModifiersStateSide::RALT),
+        winuser::VK_LWIN => Some(ModifiersStateSide::LLOGO),
+        winuser::VK_RWIN => Some(ModifiersStateSide::RLOGO),
+        _ => None,
+    }
+}
+
 pub fn get_pressed_keys() -> impl Iterator<Item = c_int> {
     let mut keyboard_state = vec![0u8; 256];
     unsafe { winuser::GetKeyboardState(keyboard_state.as_mut_ptr()) };
@@ -398,6 +413,12 @@ pub fn process_key_params(
         .map(|(vkey, scancode)| (scancode, vkey_to_winit_vkey(vkey)))
 }
 
+/// Whether the key of a `WM_KEYDOWN` or `WM_CHAR` message was already down, which makes it a
+/// repeat.
+pub fn is_repeat(lparam: LPARAM) -> bool {
+    (lparam & 0x40000000) != 0
+}
+
 // This is needed as windows doesn't properly distinguish
 // some virtual key codes for different keyboard layouts
 fn map_text_keys(win_virtual_key: i32) -> Option<VirtualKeyCode> {
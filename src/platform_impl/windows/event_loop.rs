@@ -1,11 +1,16 @@
 #![allow(non_snake_case)]
 
+mod external_source;
+mod gamepad;
+mod hid;
+mod hotkey;
 mod runner;
+mod timer;
 
 use parking_lot::Mutex;
 use std::{
-    cell::Cell,
-    collections::VecDeque,
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
     marker::PhantomData,
     mem, panic, ptr,
     rc::Rc,
@@ -20,35 +25,67 @@ use winapi::shared::basetsd::{DWORD_PTR, UINT_PTR};
 
 use winapi::{
     shared::{
-        minwindef::{BOOL, DWORD, HIWORD, INT, LOWORD, LPARAM, LRESULT, UINT, WORD, WPARAM},
-        windef::{HWND, POINT, RECT},
+        minwindef::{
+            BOOL, DWORD, FALSE, HIWORD, HKL, INT, LOWORD, LPARAM, LRESULT, UINT, WORD, WPARAM,
+        },
+        ntdef::LARGE_INTEGER,
+        windef::{HWINEVENTHOOK, HWND, POINT, RECT},
         windowsx, winerror,
     },
     um::{
-        commctrl, libloaderapi, ole2, processthreadsapi, winbase,
-        winnt::{HANDLE, LONG, LPCSTR, SHORT},
-        winuser,
+        commctrl, dwmapi, handleapi, libloaderapi, ole2, processthreadsapi, synchapi, sysinfoapi,
+        winbase,
+        winnt::{self, HANDLE, LONG, LPCSTR, SHORT},
+        winuser, wtsapi32,
     },
 };
 
 use crate::{
+    clipboard::{ClipboardKind, ClipboardMimedContent, ClipboardProvider},
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{DeviceEvent, Event, Force, KeyboardInput, Touch, TouchPhase, WindowEvent},
-    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
-    monitor::MonitorHandle as RootMonitorHandle,
+    error::{ExternalError, NotSupportedError},
+    event::{
+        DeviceEvent, Event, Force, InputDevice, KeyboardInput, ModifiersState, PenEvent, PenPhase,
+        PenTool, ScanCode, Touch, TouchPhase, WindowEvent,
+    },
+    event_loop::{
+        ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW, HapticPattern, SourceToken,
+        Timer, TimerId,
+    },
+    gamepad::GamepadId,
+    hotkey::{Accelerator, HotkeyId},
+    icon::{BadIcon, RgbaIcon},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
+    monitor::{ColorCapabilities, MonitorEvent, MonitorHandle as RootMonitorHandle},
+    platform::pump_events::PumpStatus,
     platform_impl::platform::{
+        clipboard,
         dark_mode::try_theme,
-        dpi::{become_dpi_aware, dpi_to_scale_factor, enable_non_client_dpi_scaling},
+        direct_manipulation::{self, DirectManipulation},
+        dpi::{self, become_dpi_aware, dpi_to_scale_factor, enable_non_client_dpi_scaling},
+        drag_source,
         drop_handler::FileDropHandler,
-        event::{self, handle_extended_keys, process_key_params, vkey_to_winit_vkey},
+        event::{
+            self, handle_extended_keys, is_repeat, process_key_params, vkey_to_modifier_side,
+            vkey_to_winit_vkey, ModifiersStateSide,
+        },
+        icon::WinCursor,
+        ime, keyboard_hook, keymap,
         monitor::{self, MonitorHandle},
-        raw_input, util,
-        window_state::{CursorFlags, WindowFlags, WindowState},
-        wrap_device_id, WindowId, DEVICE_ID,
+        raw_input,
+        tsf::TextStore,
+        util, virtual_desktop,
+        window_state::{self, CursorFlags, WindowFlags, WindowState},
+        wrap_device_id, DeviceId, WindowId, DEVICE_ID,
     },
-    window::{Fullscreen, WindowId as RootWindowId},
+    window::{CursorFrame, Fullscreen, WindowId as RootWindowId},
 };
+use external_source::ExternalSources;
+use gamepad::Gamepads;
+use hid::HidReports;
+use hotkey::Hotkeys;
 use runner::{EventLoopRunner, EventLoopRunnerShared};
+use timer::Timers;
 
 type GetPointerFrameInfoHistory = unsafe extern "system" fn(
     pointerId: UINT,
@@ -70,6 +107,37 @@ type GetPointerTouchInfo =
 type GetPointerPenInfo =
     unsafe extern "system" fn(pointId: UINT, penInfo: *mut winuser::POINTER_PEN_INFO) -> BOOL;
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct POINTER_DEVICE_PROPERTY {
+    logicalMin: i32,
+    logicalMax: i32,
+    physicalMin: i32,
+    physicalMax: i32,
+    unit: u32,
+    unitExponent: u32,
+    usagePageId: u16,
+    usageId: u16,
+}
+
+type GetPointerDeviceProperties = unsafe extern "system" fn(
+    device: HANDLE,
+    propertyCount: *mut UINT,
+    pointerProperties: *mut POINTER_DEVICE_PROPERTY,
+) -> BOOL;
+
+type GetRawPointerDeviceData = unsafe extern "system" fn(
+    pointerId: UINT,
+    historyCount: UINT,
+    propertiesCount: UINT,
+    pProperties: *mut POINTER_DEVICE_PROPERTY,
+    pValues: *mut LONG,
+) -> BOOL;
+
+/// The usage of the serial of the transducers, in the page of the digitizers of HID.
+const HID_USAGE_PAGE_DIGITIZER: u16 = 0x0d;
+const HID_USAGE_DIGITIZER_TRANSDUCER_SERIAL: u16 = 0x5b;
+
 lazy_static! {
     static ref GET_POINTER_FRAME_INFO_HISTORY: Option<GetPointerFrameInfoHistory> =
         get_function!("user32.dll", GetPointerFrameInfoHistory);
@@ -81,12 +149,25 @@ lazy_static! {
         get_function!("user32.dll", GetPointerTouchInfo);
     static ref GET_POINTER_PEN_INFO: Option<GetPointerPenInfo> =
         get_function!("user32.dll", GetPointerPenInfo);
+    static ref GET_POINTER_DEVICE_PROPERTIES: Option<GetPointerDeviceProperties> =
+        get_function!("user32.dll", GetPointerDeviceProperties);
+    static ref GET_RAW_POINTER_DEVICE_DATA: Option<GetRawPointerDeviceData> =
+        get_function!("user32.dll", GetRawPointerDeviceData);
+}
+
+thread_local! {
+    /// The modifiers held on each of the keyboards, tracked from their raw input on the thread of
+    /// the event loop.
+    static KEYBOARD_MODIFIERS: RefCell<HashMap<u32, ModifiersStateSide>> =
+        RefCell::new(HashMap::new());
 }
 
 pub(crate) struct SubclassInput<T: 'static> {
     pub window_state: Arc<Mutex<WindowState>>,
     pub event_loop_runner: EventLoopRunnerShared<T>,
     pub file_drop_handler: Option<FileDropHandler>,
+    pub text_store: Option<TextStore>,
+    pub direct_manipulation: Option<DirectManipulation>,
     pub subclass_removed: Cell<bool>,
     pub recurse_depth: Cell<u32>,
 }
@@ -100,6 +181,14 @@ impl<T> SubclassInput<T> {
 struct ThreadMsgTargetSubclassInput<T: 'static> {
     event_loop_runner: EventLoopRunnerShared<T>,
     user_event_receiver: Receiver<T>,
+    external_sources: Rc<ExternalSources>,
+    timers: Rc<Timers>,
+    hotkeys: Rc<Hotkeys>,
+    gamepads: Rc<Gamepads>,
+    hid_reports: Rc<HidReports>,
+    /// The monitors and their color capabilities, compared against once the displays changed.
+    monitors: RefCell<VecDeque<MonitorHandle>>,
+    monitor_colors: RefCell<Vec<(MonitorHandle, ColorCapabilities)>>,
 }
 
 impl<T> ThreadMsgTargetSubclassInput<T> {
@@ -110,13 +199,19 @@ impl<T> ThreadMsgTargetSubclassInput<T> {
 
 pub struct EventLoop<T: 'static> {
     thread_msg_sender: Sender<T>,
+    external_sources: Rc<ExternalSources>,
     window_target: RootELW<T>,
+    cloak_event_hook: HWINEVENTHOOK,
 }
 
 pub struct EventLoopWindowTarget<T: 'static> {
     thread_id: DWORD,
     thread_msg_target: HWND,
     pub(crate) runner_shared: EventLoopRunnerShared<T>,
+    timers: Rc<Timers>,
+    hotkeys: Rc<Hotkeys>,
+    gamepads: Rc<Gamepads>,
+    hid_reports: Rc<HidReports>,
 }
 
 macro_rules! main_thread_check {
@@ -146,6 +241,12 @@ impl<T: 'static> EventLoop<T> {
         Self::new_dpi_unaware_any_thread()
     }
 
+    /// Message loops are per thread on Windows, so the secondary event loops don't need anything
+    /// more than an event loop on the thread.
+    pub fn new_secondary() -> Result<EventLoop<T>, ExternalError> {
+        Ok(Self::new_any_thread())
+    }
+
     pub fn new_dpi_unaware() -> EventLoop<T> {
         main_thread_check!("new_dpi_unaware_any_thread");
 
@@ -163,17 +264,50 @@ impl<T: 'static> EventLoop<T> {
 
         let runner_shared = Rc::new(EventLoopRunner::new(thread_msg_target, wait_thread_id));
 
-        let thread_msg_sender =
-            subclass_event_target_window(thread_msg_target, runner_shared.clone());
+        let external_sources = Rc::new(ExternalSources::new(thread_msg_target));
+        let timers = Rc::new(Timers::new(thread_msg_target));
+        let hotkeys = Rc::new(Hotkeys::new(thread_msg_target));
+        let gamepads = Rc::new(Gamepads::new(thread_msg_target));
+        let hid_reports = Rc::new(HidReports::new(thread_msg_target));
+
+        let thread_msg_sender = subclass_event_target_window(
+            thread_msg_target,
+            runner_shared.clone(),
+            external_sources.clone(),
+            timers.clone(),
+            hotkeys.clone(),
+            gamepads.clone(),
+            hid_reports.clone(),
+        );
         raw_input::register_all_mice_and_keyboards_for_raw_input(thread_msg_target);
 
+        // The windows are cloaked by DWM without any message, such as when the user switches to
+        // another virtual desktop.
+        let cloak_event_hook = unsafe {
+            winuser::SetWinEventHook(
+                winuser::EVENT_OBJECT_CLOAKED,
+                winuser::EVENT_OBJECT_UNCLOAKED,
+                ptr::null_mut(),
+                Some(cloak_event_hook),
+                processthreadsapi::GetCurrentProcessId(),
+                thread_id,
+                winuser::WINEVENT_OUTOFCONTEXT,
+            )
+        };
+
         EventLoop {
             thread_msg_sender,
+            external_sources,
+            cloak_event_hook,
             window_target: RootELW {
                 p: EventLoopWindowTarget {
                     thread_id,
                     thread_msg_target,
                     runner_shared,
+                    timers,
+                    hotkeys,
+                    gamepads,
+                    hid_reports,
                 },
                 _marker: PhantomData,
             },
@@ -188,11 +322,22 @@ impl<T: 'static> EventLoop<T> {
     where
         F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
-        self.run_return(event_handler);
-        ::std::process::exit(0);
+        let exit_code = self.run_return(event_handler);
+        ::std::process::exit(exit_code);
+    }
+
+    pub fn run_return<F>(&mut self, mut event_handler: F) -> i32
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        loop {
+            if let PumpStatus::Exit(code) = self.pump_events(None, &mut event_handler) {
+                return code;
+            }
+        }
     }
 
-    pub fn run_return<F>(&mut self, mut event_handler: F)
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut event_handler: F) -> PumpStatus
     where
         F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
@@ -209,32 +354,47 @@ impl<T: 'static> EventLoop<T> {
 
         let runner = &self.window_target.p.runner_shared;
 
+        let mut quit = false;
         unsafe {
-            let mut msg = mem::zeroed();
-
-            runner.poll();
-            'main: loop {
-                if 0 == winuser::GetMessageW(&mut msg, ptr::null_mut(), 0, 0) {
-                    break 'main;
-                }
-                winuser::TranslateMessage(&mut msg);
-                winuser::DispatchMessageW(&mut msg);
-
-                if let Err(payload) = runner.take_panic_error() {
-                    runner.reset_runner();
-                    panic::resume_unwind(payload);
-                }
+            // A loop starting over goes through the `Init` iteration first, without waiting.
+            if runner.uninitialized() {
+                quit = !dispatch_iteration(runner);
+            }
 
-                if runner.control_flow() == ControlFlow::Exit && !runner.handling_events() {
-                    break 'main;
+            if !quit && !matches!(runner.control_flow(), ControlFlow::ExitWithCode(_)) {
+                // The messages posted for the control flow, like the ones of the wait thread,
+                // wake us up before the timeout.
+                let timeout = timeout.map_or(winbase::INFINITE, dur2timeout);
+                if timeout != 0 {
+                    winuser::MsgWaitForMultipleObjectsEx(
+                        0,
+                        ptr::null(),
+                        timeout,
+                        winuser::QS_ALLINPUT,
+                        winuser::MWMO_INPUTAVAILABLE,
+                    );
                 }
+                quit = !dispatch_iteration(runner);
             }
         }
 
-        unsafe {
-            runner.loop_destroyed();
+        let exit_code = match runner.control_flow() {
+            ControlFlow::ExitWithCode(code) => Some(code),
+            // The loop quits with a `WM_QUIT`.
+            _ if quit => Some(0),
+            _ => None,
+        };
+        if let Some(code) = exit_code {
+            unsafe {
+                runner.loop_destroyed();
+            }
+            runner.reset_runner();
+            PumpStatus::Exit(code)
+        } else {
+            // The handler only borrows the state of the caller for the duration of this call.
+            runner.clear_event_handler();
+            PumpStatus::Continue
         }
-        runner.reset_runner();
     }
 
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
@@ -243,6 +403,24 @@ impl<T: 'static> EventLoop<T> {
             event_send: self.thread_msg_sender.clone(),
         }
     }
+
+    pub fn create_wakeup(&self) -> EventLoopWakeup {
+        EventLoopWakeup {
+            target_window: self.window_target.p.thread_msg_target,
+        }
+    }
+
+    pub fn insert_source(
+        &mut self,
+        handle: HANDLE,
+        token: SourceToken,
+    ) -> Result<(), ExternalError> {
+        self.external_sources.insert(handle, token)
+    }
+
+    pub fn remove_source(&mut self, token: SourceToken) -> Result<(), ExternalError> {
+        self.external_sources.remove(token)
+    }
 }
 
 impl<T> EventLoopWindowTarget<T> {
@@ -263,6 +441,110 @@ impl<T> EventLoopWindowTarget<T> {
         let monitor = monitor::primary_monitor();
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        clipboard::set_clipboard(self.thread_msg_target, kind, provider)
+    }
+
+    pub fn create_custom_cursor(
+        &self,
+        image: RgbaIcon,
+        hotspot: PhysicalPosition<u32>,
+    ) -> Result<WinCursor, BadIcon> {
+        WinCursor::new(&image, hotspot)
+    }
+
+    pub fn create_animated_cursor(&self, frames: Vec<CursorFrame>) -> Result<WinCursor, BadIcon> {
+        WinCursor::new_animated(&frames)
+    }
+
+    pub fn set_timer(&self, id: TimerId, timer: Timer) -> Result<(), ExternalError> {
+        self.timers.set(id, timer)
+    }
+
+    pub fn cancel_timer(&self, id: TimerId) -> bool {
+        self.timers.cancel(id)
+    }
+
+    pub fn set_precise_wait(&self, precise: bool) -> Result<(), ExternalError> {
+        self.runner_shared.set_precise_wait(precise);
+        Ok(())
+    }
+
+    pub fn set_gamepads_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        self.gamepads.set_enabled(enabled)
+    }
+
+    pub fn rumble_gamepad(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        self.gamepads.rumble(gamepad_id, strong, weak, duration)
+    }
+
+    pub fn perform_haptic_feedback(&self, _pattern: HapticPattern) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn input_devices(&self) -> Result<Vec<InputDevice>, ExternalError> {
+        Ok(raw_input::get_input_devices())
+    }
+
+    pub fn set_hid_reports_enabled(
+        &self,
+        device: DeviceId,
+        enabled: bool,
+    ) -> Result<(), ExternalError> {
+        self.hid_reports.set_enabled(device.0, enabled)
+    }
+
+    pub fn keyboard_layouts(&self) -> Result<Vec<KeyboardLayout>, ExternalError> {
+        Ok(keymap::keyboard_layouts())
+    }
+
+    pub fn current_keyboard_layout(&self) -> Result<KeyboardLayout, ExternalError> {
+        Ok(keymap::current_keyboard_layout())
+    }
+
+    pub fn scancode_to_text(
+        &self,
+        scancode: ScanCode,
+        modifiers: ModifiersState,
+    ) -> Result<Option<String>, ExternalError> {
+        Ok(keymap::scancode_to_text(scancode, modifiers))
+    }
+
+    pub fn key_repeat(&self) -> Result<Option<KeyRepeat>, ExternalError> {
+        keymap::key_repeat()
+            .map(Some)
+            .map_err(|err| ExternalError::Os(os_error!(err)))
+    }
+
+    pub fn lock_keys(&self) -> Result<LockKeys, ExternalError> {
+        Ok(keymap::lock_keys())
+    }
+
+    pub fn set_lock_keys(&self, lock_keys: LockKeys, on: bool) -> Result<(), ExternalError> {
+        keymap::set_lock_keys(lock_keys, on).map_err(|err| ExternalError::Os(os_error!(err)))
+    }
+
+    pub fn register_global_hotkey(
+        &self,
+        accelerator: Accelerator,
+    ) -> Result<HotkeyId, ExternalError> {
+        self.hotkeys.register(accelerator)
+    }
+
+    pub fn unregister_global_hotkey(&self, id: HotkeyId) -> bool {
+        self.hotkeys.unregister(id)
+    }
 }
 
 /// Returns the id of the main thread.
@@ -324,6 +606,29 @@ fn get_wait_thread_id() -> DWORD {
     }
 }
 
+/// Goes through one iteration of the event loop, dispatching the messages until the runner is
+/// idle again.
+///
+/// Returns `false` if `WM_QUIT` was received.
+unsafe fn dispatch_iteration<T: 'static>(runner: &EventLoopRunner<T>) -> bool {
+    let mut msg = mem::zeroed();
+
+    runner.poll();
+    while runner.handling_events() {
+        if 0 == winuser::GetMessageW(&mut msg, ptr::null_mut(), 0, 0) {
+            return false;
+        }
+        winuser::TranslateMessage(&mut msg);
+        winuser::DispatchMessageW(&mut msg);
+
+        if let Err(payload) = runner.take_panic_error() {
+            runner.reset_runner();
+            panic::resume_unwind(payload);
+        }
+    }
+    true
+}
+
 fn wait_thread(parent_thread_id: DWORD, msg_window_id: HWND) {
     unsafe {
         let mut msg: winuser::MSG;
@@ -336,6 +641,9 @@ fn wait_thread(parent_thread_id: DWORD, msg_window_id: HWND) {
             cur_thread_id as LPARAM,
         );
 
+        // The high resolution timer of the precise waits, created on their first use.
+        let mut precise_timer: HANDLE = ptr::null_mut();
+        let mut precise = false;
         let mut wait_until_opt = None;
         'main: loop {
             // Zeroing out the message ensures that the `WaitUntilInstantBox` doesn't get
@@ -359,13 +667,47 @@ fn wait_thread(parent_thread_id: DWORD, msg_window_id: HWND) {
 
             if msg.message == *WAIT_UNTIL_MSG_ID {
                 wait_until_opt = Some(*WaitUntilInstantBox::from_raw(msg.lParam as *mut _));
+                precise = msg.wParam != 0;
+                if precise && precise_timer.is_null() {
+                    // The high resolution timers are only available since Windows 10 1803, the
+                    // waits keep the resolution of the system timer on the older versions.
+                    precise_timer = synchapi::CreateWaitableTimerExW(
+                        ptr::null_mut(),
+                        ptr::null(),
+                        CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+                        winnt::TIMER_ALL_ACCESS,
+                    );
+                }
             } else if msg.message == *CANCEL_WAIT_UNTIL_MSG_ID {
                 wait_until_opt = None;
             }
 
             if let Some(wait_until) = wait_until_opt {
                 let now = Instant::now();
-                if now < wait_until {
+                if now < wait_until && precise && !precise_timer.is_null() {
+                    // The due time is relative when negative, in intervals of 100 nanoseconds.
+                    let mut due_time: LARGE_INTEGER = mem::zeroed();
+                    *due_time.QuadPart_mut() = -(((wait_until - now).as_nanos() / 100) as i64);
+                    synchapi::SetWaitableTimer(
+                        precise_timer,
+                        &due_time,
+                        0,
+                        None,
+                        ptr::null_mut(),
+                        FALSE,
+                    );
+                    let resume_reason = winuser::MsgWaitForMultipleObjectsEx(
+                        1,
+                        &precise_timer,
+                        winbase::INFINITE,
+                        winuser::QS_ALLEVENTS,
+                        winuser::MWMO_INPUTAVAILABLE,
+                    );
+                    if resume_reason == winbase::WAIT_OBJECT_0 {
+                        winuser::PostMessageW(msg_window_id, *PROCESS_NEW_EVENTS_MSG_ID, 0, 0);
+                        wait_until_opt = None;
+                    }
+                } else if now < wait_until {
                     // MsgWaitForMultipleObjects tends to overshoot just a little bit. We subtract
                     // 1 millisecond from the requested time and spinlock for the remainder to
                     // compensate for that.
@@ -386,6 +728,10 @@ fn wait_thread(parent_thread_id: DWORD, msg_window_id: HWND) {
                 }
             }
         }
+
+        if !precise_timer.is_null() {
+            handleapi::CloseHandle(precise_timer);
+        }
     }
 }
 
@@ -421,6 +767,9 @@ fn dur2timeout(dur: Duration) -> DWORD {
 impl<T> Drop for EventLoop<T> {
     fn drop(&mut self) {
         unsafe {
+            if !self.cloak_event_hook.is_null() {
+                winuser::UnhookWinEvent(self.cloak_event_hook);
+            }
             winuser::DestroyWindow(self.window_target.p.thread_msg_target);
         }
     }
@@ -512,6 +861,20 @@ impl<T: 'static> EventLoopProxy<T> {
 
 type WaitUntilInstantBox = Box<Instant>;
 
+pub struct EventLoopWakeup {
+    target_window: HWND,
+}
+unsafe impl Send for EventLoopWakeup {}
+unsafe impl Sync for EventLoopWakeup {}
+
+impl EventLoopWakeup {
+    pub fn wake_up(&self) {
+        unsafe {
+            winuser::PostMessageW(self.target_window, *WAKE_UP_MSG_ID, 0, 0);
+        }
+    }
+}
+
 lazy_static! {
     // Message sent by the `EventLoopProxy` when we want to wake up the thread.
     // WPARAM and LPARAM are unused.
@@ -520,6 +883,27 @@ lazy_static! {
             winuser::RegisterWindowMessageA("Winit::WakeupMsg\0".as_ptr() as LPCSTR)
         }
     };
+    // Message sent by the `EventLoopWakeup` when we want to wake up the thread, without any
+    // event. WPARAM and LPARAM are unused.
+    static ref WAKE_UP_MSG_ID: u32 = {
+        unsafe {
+            winuser::RegisterWindowMessageA("Winit::WakeUpOnly\0".as_ptr() as LPCSTR)
+        }
+    };
+    // Message sent from the thread pool once an external source is signaled.
+    // WPARAM contains the `SourceToken` of the source, and LPARAM is unused.
+    static ref SOURCE_READY_MSG_ID: u32 = {
+        unsafe {
+            winuser::RegisterWindowMessageA("Winit::SourceReady\0".as_ptr() as LPCSTR)
+        }
+    };
+    // Message sent from the thread polling the gamepads once some of them changed.
+    // WPARAM and LPARAM are unused.
+    static ref GAMEPADS_CHANGED_MSG_ID: u32 = {
+        unsafe {
+            winuser::RegisterWindowMessageA("Winit::GamepadsChanged\0".as_ptr() as LPCSTR)
+        }
+    };
     // Message sent when we want to execute a closure in the thread.
     // WPARAM contains a Box<Box<dyn FnMut()>> that must be retrieved with `Box::from_raw`,
     // and LPARAM is unused.
@@ -551,6 +935,13 @@ lazy_static! {
             winuser::RegisterWindowMessageA("Winit::CancelWaitUntil\0".as_ptr() as *const i8)
         }
     };
+    // Message sent to the windows of the thread once they're cloaked or uncloaked.
+    // WPARAM and LPARAM are unused.
+    static ref CLOAK_CHANGED_MSG_ID: u32 = {
+        unsafe {
+            winuser::RegisterWindowMessageA("Winit::CloakChanged\0".as_ptr() as LPCSTR)
+        }
+    };
     // Message sent by a `Window` when it wants to be destroyed by the main thread.
     // WPARAM and LPARAM are unused.
     pub static ref DESTROY_MSG_ID: u32 = {
@@ -563,6 +954,36 @@ lazy_static! {
     pub static ref SET_RETAIN_STATE_ON_SIZE_MSG_ID: u32 = unsafe {
         winuser::RegisterWindowMessageA("Winit::SetRetainMaximized\0".as_ptr() as LPCSTR)
     };
+    // Message sent by a `Window` once the IME state in its `WindowState` changed, for its text
+    // store to read.
+    // WPARAM and LPARAM are unused.
+    pub static ref IME_STATE_MSG_ID: u32 = unsafe {
+        winuser::RegisterWindowMessageA("Winit::ImeState\0".as_ptr() as LPCSTR)
+    };
+    // Message sent by a `Window` once the clipboard was read.
+    // WPARAM is unused, and LPARAM contains a Box<Option<ClipboardMimedContent>> that must be
+    // retrieved with `Box::from_raw`.
+    pub static ref CLIPBOARD_DATA_MSG_ID: u32 = unsafe {
+        winuser::RegisterWindowMessageA("Winit::ClipboardData\0".as_ptr() as LPCSTR)
+    };
+    // Message sent to a `Window` once the drag it started moves over a drop target accepting
+    // the data, or the target changes the effect.
+    // WPARAM contains the drop effect chosen by the drop target, and LPARAM is unused.
+    pub static ref DRAG_ENTERED_MSG_ID: u32 = unsafe {
+        winuser::RegisterWindowMessageA("Winit::DragEntered\0".as_ptr() as LPCSTR)
+    };
+    // Message sent to a `Window` once the cursor dragging the data from it moves.
+    // WPARAM is unused, and LPARAM contains the position of the cursor in the window, packed the
+    // same way as for `WM_MOUSEMOVE`.
+    pub static ref DRAG_MOVED_MSG_ID: u32 = unsafe {
+        winuser::RegisterWindowMessageA("Winit::DragMoved\0".as_ptr() as LPCSTR)
+    };
+    // Message sent by a `Window` once the drag it started is over.
+    // WPARAM contains the drop effect chosen by the drop target, and LPARAM is non-zero if the
+    // data was dropped outside of the windows of the application.
+    pub static ref DRAG_FINISHED_MSG_ID: u32 = unsafe {
+        winuser::RegisterWindowMessageA("Winit::DragFinished\0".as_ptr() as LPCSTR)
+    };
     static ref THREAD_EVENT_TARGET_WINDOW_CLASS: Vec<u16> = unsafe {
         use std::ffi::OsStr;
         use std::os::windows::ffi::OsStrExt;
@@ -624,6 +1045,11 @@ fn create_event_target_window() -> HWND {
 fn subclass_event_target_window<T>(
     window: HWND,
     event_loop_runner: EventLoopRunnerShared<T>,
+    external_sources: Rc<ExternalSources>,
+    timers: Rc<Timers>,
+    hotkeys: Rc<Hotkeys>,
+    gamepads: Rc<Gamepads>,
+    hid_reports: Rc<HidReports>,
 ) -> Sender<T> {
     unsafe {
         let (tx, rx) = mpsc::channel();
@@ -631,6 +1057,13 @@ fn subclass_event_target_window<T>(
         let subclass_input = ThreadMsgTargetSubclassInput {
             event_loop_runner,
             user_event_receiver: rx,
+            external_sources,
+            timers,
+            hotkeys,
+            gamepads,
+            hid_reports,
+            monitors: RefCell::new(monitor::available_monitors()),
+            monitor_colors: RefCell::new(monitor::available_color_capabilities()),
         };
         let input_ptr = Box::into_raw(Box::new(subclass_input));
         let subclass_result = commctrl::SetWindowSubclass(
@@ -658,14 +1091,14 @@ fn remove_event_target_window_subclass<T: 'static>(window: HWND) {
 
 /// Capture mouse input, allowing `window` to receive mouse events when the cursor is outside of
 /// the window.
-unsafe fn capture_mouse(window: HWND, window_state: &mut WindowState) {
+pub(crate) unsafe fn capture_mouse(window: HWND, window_state: &mut WindowState) {
     window_state.mouse.capture_count += 1;
     winuser::SetCapture(window);
 }
 
 /// Release mouse input, stopping windows on this thread from receiving mouse input when the cursor
 /// is outside the window.
-unsafe fn release_mouse(mut window_state: parking_lot::MutexGuard<'_, WindowState>) {
+pub(crate) unsafe fn release_mouse(mut window_state: parking_lot::MutexGuard<'_, WindowState>) {
     window_state.mouse.capture_count = window_state.mouse.capture_count.saturating_sub(1);
     if window_state.mouse.capture_count == 0 {
         // ReleaseCapture() causes a WM_CAPTURECHANGED where we lock the window_state.
@@ -674,6 +1107,9 @@ unsafe fn release_mouse(mut window_state: parking_lot::MutexGuard<'_, WindowStat
     }
 }
 
+// Not exposed by `winapi`, the flag of the high resolution timers since Windows 10 1803.
+const CREATE_WAITABLE_TIMER_HIGH_RESOLUTION: DWORD = 0x0000_0002;
+
 const WINDOW_SUBCLASS_ID: UINT_PTR = 0;
 const THREAD_EVENT_TARGET_SUBCLASS_ID: UINT_PTR = 1;
 pub(crate) fn subclass_window<T>(window: HWND, subclass_input: SubclassInput<T>) {
@@ -708,6 +1144,93 @@ fn normalize_pointer_pressure(pressure: u32) -> Option<Force> {
     }
 }
 
+/// The information of the pointer when it's a pen.
+unsafe fn get_pointer_pen_info(pointer_id: u32) -> Option<winuser::POINTER_PEN_INFO> {
+    let GetPointerPenInfo = (*GET_POINTER_PEN_INFO)?;
+    let mut pen_info = mem::MaybeUninit::uninit();
+    match GetPointerPenInfo(pointer_id, pen_info.as_mut_ptr()) {
+        0 => None,
+        _ => Some(pen_info.assume_init()),
+    }
+}
+
+/// The serial of the transducer of the pen, when its digitizer tells it over HID.
+unsafe fn get_pen_serial(pointer_info: &winuser::POINTER_INFO) -> Option<u64> {
+    let GetPointerDeviceProperties = (*GET_POINTER_DEVICE_PROPERTIES)?;
+    let GetRawPointerDeviceData = (*GET_RAW_POINTER_DEVICE_DATA)?;
+    let device = pointer_info.sourceDevice;
+    let mut count = 0;
+    if GetPointerDeviceProperties(device, &mut count, ptr::null_mut()) == 0 {
+        return None;
+    }
+    let mut properties = Vec::with_capacity(count as usize);
+    if GetPointerDeviceProperties(device, &mut count, properties.as_mut_ptr()) == 0 {
+        return None;
+    }
+    properties.set_len(count as usize);
+    let mut property = *properties.iter().find(|property| {
+        property.usagePageId == HID_USAGE_PAGE_DIGITIZER
+            && property.usageId == HID_USAGE_DIGITIZER_TRANSDUCER_SERIAL
+    })?;
+    let mut value = 0;
+    match GetRawPointerDeviceData(pointer_info.pointerId, 1, 1, &mut property, &mut value) {
+        0 => None,
+        _ => Some(value as u32 as u64),
+    }
+}
+
+/// The instant of the time of a message or an input, in milliseconds since the system started as
+/// per `GetTickCount`, which wraps around.
+fn instant_from_tick_count(time: DWORD) -> Instant {
+    let now = Instant::now();
+    let elapsed = unsafe { sysinfoapi::GetTickCount() }.wrapping_sub(time);
+    now.checked_sub(Duration::from_millis(elapsed as u64))
+        .unwrap_or(now)
+}
+
+fn pen_event(
+    pen_info: &winuser::POINTER_PEN_INFO,
+    phase: PenPhase,
+    location: PhysicalPosition<f64>,
+    time: Instant,
+    serial: Option<u64>,
+) -> PenEvent {
+    let mask = pen_info.penMask;
+    let flags = pen_info.penFlags;
+    let pressure = match mask & winuser::PEN_MASK_PRESSURE {
+        0 => 0.,
+        _ => pen_info.pressure.min(1024) as f64 / 1024.,
+    };
+    let tilt = match mask & (winuser::PEN_MASK_TILT_X | winuser::PEN_MASK_TILT_Y) {
+        0 => None,
+        _ => Some((pen_info.tiltX as f64, pen_info.tiltY as f64)),
+    };
+    let twist = match mask & winuser::PEN_MASK_ROTATION {
+        0 => None,
+        _ => Some(pen_info.rotation as f64),
+    };
+    // The pens which are turned over to erase are inverted before the eraser touches.
+    let eraser = flags & (winuser::PEN_FLAG_ERASER | winuser::PEN_FLAG_INVERTED) != 0;
+    PenEvent {
+        device_id: DEVICE_ID,
+        phase,
+        location,
+        time,
+        pressure,
+        tilt,
+        twist,
+        eraser,
+        barrel_button: flags & winuser::PEN_FLAG_BARREL != 0,
+        tool_id: pen_info.pointerInfo.pointerId as u64,
+        tool: if eraser {
+            PenTool::Eraser
+        } else {
+            PenTool::Pen
+        },
+        serial,
+    }
+}
+
 /// Flush redraw events for Winit's windows.
 ///
 /// Winit's API guarantees that all redraw events will be clustered together and dispatched all at
@@ -762,11 +1285,11 @@ unsafe fn process_control_flow<T: 'static>(runner: &EventLoopRunner<T>) {
             winuser::PostThreadMessageW(
                 runner.wait_thread_id(),
                 *WAIT_UNTIL_MSG_ID,
-                0,
+                runner.precise_wait() as WPARAM,
                 Box::into_raw(WaitUntilInstantBox::new(until)) as LPARAM,
             );
         }
-        ControlFlow::Exit => (),
+        ControlFlow::ExitWithCode(_) => (),
     }
 }
 
@@ -791,6 +1314,193 @@ fn update_modifiers<T>(window: HWND, subclass_input: &SubclassInput<T>) {
     }
 }
 
+fn update_recommended_bounds<T>(window: HWND, subclass_input: &SubclassInput<T>) {
+    use crate::event::WindowEvent::RecommendedBounds;
+
+    let bounds = match monitor::recommended_bounds(window) {
+        Some(bounds) => bounds,
+        None => return,
+    };
+    let mut window_state = subclass_input.window_state.lock();
+    if window_state.recommended_bounds != Some(bounds) {
+        window_state.recommended_bounds = Some(bounds);
+
+        // Drop lock
+        drop(window_state);
+
+        unsafe {
+            subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window)),
+                event: RecommendedBounds(bounds),
+            });
+        }
+    }
+}
+
+fn update_tiled_edges<T>(window: HWND, subclass_input: &SubclassInput<T>) {
+    use crate::event::WindowEvent::TiledStateChanged;
+
+    let edges = monitor::snapped_edges(window);
+    let mut window_state = subclass_input.window_state.lock();
+    if window_state.tiled_edges != edges {
+        window_state.tiled_edges = edges;
+
+        // Drop lock
+        drop(window_state);
+
+        unsafe {
+            subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window)),
+                event: TiledStateChanged(edges),
+            });
+        }
+    }
+}
+
+fn update_refresh_rate<T>(window: HWND, subclass_input: &SubclassInput<T>) {
+    use crate::event::WindowEvent::RefreshRateChanged;
+
+    let refresh_rate = match monitor::current_monitor(window).refresh_rate_millihertz() {
+        Some(refresh_rate) => refresh_rate,
+        None => return,
+    };
+    let mut window_state = subclass_input.window_state.lock();
+    let previous = window_state.refresh_rate.replace(refresh_rate);
+
+    // Drop lock
+    drop(window_state);
+
+    // The refresh rate of the monitor the window is created on isn't a change.
+    if previous.map_or(false, |previous| previous != refresh_rate) {
+        unsafe {
+            subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window)),
+                event: RefreshRateChanged(refresh_rate),
+            });
+        }
+    }
+}
+
+fn update_workspace<T>(window: HWND, subclass_input: &SubclassInput<T>) {
+    use crate::event::WindowEvent::WorkspaceChanged;
+
+    let workspace = match virtual_desktop::desktop_index(window) {
+        Some(workspace) => workspace,
+        None => return,
+    };
+    let mut window_state = subclass_input.window_state.lock();
+    let previous = window_state.workspace.replace(workspace);
+
+    // Drop lock
+    drop(window_state);
+
+    // The virtual desktop the window is first shown on isn't a change.
+    if previous.map_or(false, |previous| previous != workspace) {
+        unsafe {
+            subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window)),
+                event: WorkspaceChanged(Some(workspace)),
+            });
+        }
+    }
+}
+
+fn update_occlusion<T>(window: HWND, subclass_input: &SubclassInput<T>) {
+    use crate::event::WindowEvent::Occluded;
+
+    let occluded = util::is_occluded(window);
+    let mut window_state = subclass_input.window_state.lock();
+    if window_state.occluded != occluded {
+        window_state.occluded = occluded;
+
+        // Drop lock
+        drop(window_state);
+
+        unsafe {
+            subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window)),
+                event: Occluded(occluded),
+            });
+        }
+    }
+}
+
+/// Forwards the cloaking of the windows of the thread to them, which changes without any other
+/// message.
+unsafe extern "system" fn cloak_event_hook(
+    _hook: HWINEVENTHOOK,
+    _event: DWORD,
+    window: HWND,
+    object: LONG,
+    _child: LONG,
+    _thread: DWORD,
+    _time: DWORD,
+) {
+    if object == winuser::OBJID_WINDOW {
+        winuser::PostMessageW(window, *CLOAK_CHANGED_MSG_ID, 0, 0);
+    }
+}
+
+/// Emit the `PinchGesture` and the `RotationGesture` of the touches on the window, when the
+/// touch is one of them.
+unsafe fn send_touch_gesture<T>(
+    window: HWND,
+    subclass_input: &SubclassInput<T>,
+    id: u64,
+    location: PhysicalPosition<f64>,
+    phase: TouchPhase,
+) {
+    let gesture = subclass_input
+        .window_state
+        .lock()
+        .touch_gesture
+        .touch(id, location, phase);
+    if let Some((scale_delta, rotation, phase)) = gesture {
+        subclass_input.send_event(Event::WindowEvent {
+            window_id: RootWindowId(WindowId(window)),
+            event: WindowEvent::PinchGesture {
+                device_id: DEVICE_ID,
+                delta: scale_delta,
+                phase,
+            },
+        });
+        subclass_input.send_event(Event::WindowEvent {
+            window_id: RootWindowId(WindowId(window)),
+            event: WindowEvent::RotationGesture {
+                device_id: DEVICE_ID,
+                delta: rotation,
+                phase,
+            },
+        });
+    }
+}
+
+/// Emit a `DeviceModifiersChanged` event for each of the keyboards holding modifiers, along with
+/// the focus of the window, or an empty one for each of them as the window loses the focus.
+unsafe fn send_device_modifiers<T>(window: HWND, subclass_input: &SubclassInput<T>, focused: bool) {
+    let devices: Vec<(u32, ModifiersState)> = KEYBOARD_MODIFIERS.with(|keyboard_modifiers| {
+        keyboard_modifiers
+            .borrow()
+            .iter()
+            .filter(|(_, side)| !side.is_empty())
+            .map(|(&device, side)| (device, ModifiersState::from(side.filter_out_altgr())))
+            .collect()
+    });
+    for (device, modifiers) in devices {
+        subclass_input.send_event(Event::WindowEvent {
+            window_id: RootWindowId(WindowId(window)),
+            event: WindowEvent::DeviceModifiersChanged {
+                device_id: wrap_device_id(device),
+                modifiers: if focused {
+                    modifiers
+                } else {
+                    ModifiersState::empty()
+                },
+            },
+        });
+    }
+}
+
 /// Any window whose callback is configured to this function will have its events propagated
 /// through the events loop of the thread the window was created in.
 //
@@ -876,6 +1586,58 @@ unsafe fn public_window_callback_inner<T: 'static>(
             commctrl::DefSubclassProc(window, msg, wparam, lparam)
         }
 
+        winuser::WM_NCCALCSIZE => {
+            let style = winuser::GetWindowLongW(window, winuser::GWL_STYLE) as DWORD;
+            if wparam == 0
+                || !util::has_flag(style, winuser::WS_CAPTION)
+                || !util::is_titlebar_extended(window)
+            {
+                return commctrl::DefSubclassProc(window, msg, wparam, lparam);
+            }
+
+            // The client area is extended over the title bar, up to the top of the window, the
+            // other sides of the frame being kept for the resizing.
+            let params = lparam as *mut winuser::NCCALCSIZE_PARAMS;
+            let top = (*params).rgrc[0].top;
+            let result = commctrl::DefSubclassProc(window, msg, wparam, lparam);
+            (*params).rgrc[0].top = top;
+            // The frame of the maximized windows is out of the monitor.
+            if winuser::IsZoomed(window) != 0 {
+                (*params).rgrc[0].top += dpi::frame_thickness(window);
+            }
+            result
+        }
+
+        winuser::WM_NCHITTEST => {
+            let window_flags = subclass_input.window_state.lock().window_flags();
+            if !window_flags.contains(WindowFlags::TITLEBAR_EXTENDED | WindowFlags::DECORATIONS) {
+                return commctrl::DefSubclassProc(window, msg, wparam, lparam);
+            }
+
+            // The caption buttons drawn by the DWM are hit first.
+            let mut result = 0;
+            if window_flags.contains(WindowFlags::CAPTION_BUTTONS)
+                && dwmapi::DwmDefWindowProc(window, msg, wparam, lparam, &mut result) != 0
+            {
+                return result;
+            }
+
+            // The top of the frame is within the client area, where the window is still resized.
+            let hit = commctrl::DefSubclassProc(window, msg, wparam, lparam);
+            if hit == winuser::HTCLIENT as LRESULT
+                && window_flags.contains(WindowFlags::RESIZABLE)
+                && winuser::IsZoomed(window) == 0
+            {
+                let mut rect: RECT = mem::zeroed();
+                winuser::GetWindowRect(window, &mut rect);
+                let y = windowsx::GET_Y_LPARAM(lparam);
+                if y < rect.top + dpi::frame_thickness(window) {
+                    return winuser::HTTOP as LRESULT;
+                }
+            }
+            hit
+        }
+
         winuser::WM_CLOSE => {
             use crate::event::WindowEvent::CloseRequested;
             subclass_input.send_event(Event::WindowEvent {
@@ -888,6 +1650,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
         winuser::WM_DESTROY => {
             use crate::event::WindowEvent::Destroyed;
             ole2::RevokeDragDrop(window);
+            winuser::RemoveClipboardFormatListener(window);
+            wtsapi32::WTSUnRegisterSessionNotification(window);
+            util::set_titlebar_extended(window, false);
+            let _ = keyboard_hook::set_shortcuts_inhibited(window, false);
             subclass_input.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: Destroyed,
@@ -896,6 +1662,54 @@ unsafe fn public_window_callback_inner<T: 'static>(
             0
         }
 
+        winuser::WM_RENDERFORMAT => {
+            clipboard::render_format(window, wparam as UINT);
+            0
+        }
+
+        winuser::WM_RENDERALLFORMATS => {
+            clipboard::render_all_formats(window);
+            0
+        }
+
+        winuser::WM_DESTROYCLIPBOARD => {
+            clipboard::clear_provider(window);
+            0
+        }
+
+        winuser::WM_CLIPBOARDUPDATE => {
+            // Only the changes made by other applications are reported.
+            if !clipboard::is_owned_by_current_process() {
+                subclass_input.send_event(Event::WindowEvent {
+                    window_id: RootWindowId(WindowId(window)),
+                    event: WindowEvent::ClipboardChanged {
+                        kind: ClipboardKind::Clipboard,
+                    },
+                });
+            }
+            0
+        }
+
+        winuser::WM_WTSSESSION_CHANGE => {
+            use crate::event::WindowEvent::{SurfaceLost, SurfaceRestored};
+
+            // The adapter drawing the windows changes along with the client of the session.
+            let event = match wparam {
+                winuser::WTS_CONSOLE_DISCONNECT | winuser::WTS_REMOTE_DISCONNECT => {
+                    Some(SurfaceLost)
+                }
+                winuser::WTS_CONSOLE_CONNECT | winuser::WTS_REMOTE_CONNECT => Some(SurfaceRestored),
+                _ => None,
+            };
+            if let Some(event) = event {
+                subclass_input.send_event(Event::WindowEvent {
+                    window_id: RootWindowId(WindowId(window)),
+                    event,
+                });
+            }
+            0
+        }
+
         winuser::WM_NCDESTROY => {
             remove_window_subclass::<T>(window);
             subclass_input.subclass_removed.set(true);
@@ -957,6 +1771,15 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             });
                         }
                     }
+                    // The window keeps covering the monitors it spans.
+                    Fullscreen::Span(ref monitors) => {
+                        if let Some((position, size)) = monitor::span_rect(monitors) {
+                            window_pos.x = position.x;
+                            window_pos.y = position.y;
+                            window_pos.cx = size.width as i32;
+                            window_pos.cy = size.height as i32;
+                        }
+                    }
                     Fullscreen::Exclusive(ref video_mode) => {
                         let old_monitor = video_mode.video_mode.monitor.hmonitor();
                         if let Ok(old_monitor_info) = monitor::get_monitor_info(old_monitor) {
@@ -986,11 +1809,21 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     event: Moved(physical_position),
                 });
             }
+            // The window may have moved to another monitor, or changed its decorations.
+            update_recommended_bounds(window, subclass_input);
+            update_tiled_edges(window, subclass_input);
+            update_refresh_rate(window, subclass_input);
 
             // This is necessary for us to still get sent WM_SIZE.
             commctrl::DefSubclassProc(window, msg, wparam, lparam)
         }
 
+        // The top-level windows are told once the mode of a monitor changed.
+        winuser::WM_DISPLAYCHANGE => {
+            update_refresh_rate(window, subclass_input);
+            commctrl::DefSubclassProc(window, msg, wparam, lparam)
+        }
+
         winuser::WM_SIZE => {
             use crate::event::WindowEvent::Resized;
             let w = LOWORD(lparam as DWORD) as u32;
@@ -1015,12 +1848,70 @@ unsafe fn public_window_callback_inner<T: 'static>(
             }
 
             subclass_input.send_event(event);
+            // The window may have been minimized or restored.
+            update_occlusion(window, subclass_input);
             0
         }
 
+        winuser::WM_IME_COMPOSITION => {
+            if lparam as DWORD & ime::GCS_COMPSTR != 0 {
+                if let Some(preedit) = ime::composition_preedit(window) {
+                    subclass_input.send_event(Event::WindowEvent {
+                        window_id: RootWindowId(WindowId(window)),
+                        event: WindowEvent::ImePreedit(preedit),
+                    });
+                }
+            }
+            // The default procedure still shows the composition window and sends the result.
+            commctrl::DefSubclassProc(window, msg, wparam, lparam)
+        }
+
+        winuser::WM_IME_NOTIFY => {
+            // The input method opens and moves the candidate window while handling the message.
+            let result = commctrl::DefSubclassProc(window, msg, wparam, lparam);
+            let area = match wparam as DWORD {
+                ime::IMN_OPENCANDIDATE | ime::IMN_CHANGECANDIDATE => {
+                    match ime::candidate_area(window) {
+                        Some(area) => Some(area),
+                        None => return result,
+                    }
+                }
+                ime::IMN_CLOSECANDIDATE => None,
+                _ => return result,
+            };
+            subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window)),
+                event: WindowEvent::ImeCandidateArea(area),
+            });
+            result
+        }
+
+        winuser::WM_IME_REQUEST if wparam as DWORD == ime::IMR_RECONVERTSTRING => {
+            let window_state = subclass_input.window_state.lock();
+            match window_state.surrounding_text {
+                Some(ref surrounding_text) => surrounding_text.reconvert_string(lparam),
+                None => {
+                    drop(window_state);
+                    commctrl::DefSubclassProc(window, msg, wparam, lparam)
+                }
+            }
+        }
+
+        winuser::WM_IME_ENDCOMPOSITION => {
+            subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window)),
+                event: WindowEvent::ImePreedit(Default::default()),
+            });
+            commctrl::DefSubclassProc(window, msg, wparam, lparam)
+        }
+
         winuser::WM_CHAR | winuser::WM_SYSCHAR => {
             use crate::event::WindowEvent::ReceivedCharacter;
             use std::char;
+
+            if is_repeat(lparam) && !subclass_input.window_state.lock().key_repeat {
+                return 0;
+            }
             let is_high_surrogate = 0xD800 <= wparam && wparam <= 0xDBFF;
             let is_low_surrogate = 0xDC00 <= wparam && wparam <= 0xDFFF;
 
@@ -1074,22 +1965,41 @@ unsafe fn public_window_callback_inner<T: 'static>(
         }
 
         winuser::WM_MOUSEMOVE => {
-            use crate::event::WindowEvent::{CursorEntered, CursorMoved};
-            let mouse_was_outside_window = {
+            use crate::event::WindowEvent::{CursorEntered, CursorLeft, CursorMoved};
+
+            let x = windowsx::GET_X_LPARAM(lparam);
+            let y = windowsx::GET_Y_LPARAM(lparam);
+            let position = PhysicalPosition::new(x as f64, y as f64);
+            // The captured mouse moves outside of the client area as well.
+            let mut client_rect: RECT = mem::zeroed();
+            winuser::GetClientRect(window, &mut client_rect);
+            let in_client_area =
+                x >= 0 && y >= 0 && x < client_rect.right && y < client_rect.bottom;
+
+            let mouse_was_in_window = {
                 let mut w = subclass_input.window_state.lock();
 
-                let was_outside_window = !w.mouse.cursor_flags().contains(CursorFlags::IN_WINDOW);
+                let was_in_window = w.mouse.cursor_flags().contains(CursorFlags::IN_WINDOW);
                 w.mouse
-                    .set_cursor_flags(window, |f| f.set(CursorFlags::IN_WINDOW, true))
+                    .set_cursor_flags(window, |f| f.set(CursorFlags::IN_WINDOW, in_client_area))
                     .ok();
-                was_outside_window
+                was_in_window
             };
 
-            if mouse_was_outside_window {
+            if mouse_was_in_window && !in_client_area {
+                subclass_input.send_event(Event::WindowEvent {
+                    window_id: RootWindowId(WindowId(window)),
+                    event: CursorLeft {
+                        device_id: DEVICE_ID,
+                        position,
+                    },
+                });
+            } else if !mouse_was_in_window && in_client_area {
                 subclass_input.send_event(Event::WindowEvent {
                     window_id: RootWindowId(WindowId(window)),
                     event: CursorEntered {
                         device_id: DEVICE_ID,
+                        position,
                     },
                 });
 
@@ -1102,9 +2012,6 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 });
             }
 
-            let x = windowsx::GET_X_LPARAM(lparam) as f64;
-            let y = windowsx::GET_Y_LPARAM(lparam) as f64;
-            let position = PhysicalPosition::new(x, y);
             let cursor_moved;
             {
                 // handle spurious WM_MOUSEMOVE messages
@@ -1132,29 +2039,39 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
         winuser::WM_MOUSELEAVE => {
             use crate::event::WindowEvent::CursorLeft;
-            {
+            let mouse_was_in_window = {
                 let mut w = subclass_input.window_state.lock();
+                let was_in_window = w.mouse.cursor_flags().contains(CursorFlags::IN_WINDOW);
                 w.mouse
                     .set_cursor_flags(window, |f| f.set(CursorFlags::IN_WINDOW, false))
                     .ok();
-            }
+                was_in_window
+            };
 
-            subclass_input.send_event(Event::WindowEvent {
-                window_id: RootWindowId(WindowId(window)),
-                event: CursorLeft {
-                    device_id: DEVICE_ID,
-                },
-            });
+            // The captured mouse may have left already.
+            if mouse_was_in_window {
+                // The message has no position, the cursor is wherever it went.
+                let mut position = POINT { x: 0, y: 0 };
+                winuser::GetCursorPos(&mut position);
+                winuser::ScreenToClient(window, &mut position);
+                subclass_input.send_event(Event::WindowEvent {
+                    window_id: RootWindowId(WindowId(window)),
+                    event: CursorLeft {
+                        device_id: DEVICE_ID,
+                        position: PhysicalPosition::new(position.x as f64, position.y as f64),
+                    },
+                });
+            }
 
             0
         }
 
         winuser::WM_MOUSEWHEEL => {
-            use crate::event::MouseScrollDelta::LineDelta;
+            use crate::event::MouseScrollDelta::Value120;
 
+            // The value is in 120ths of a notch, `WHEEL_DELTA`.
             let value = (wparam >> 16) as i16;
             let value = value as i32;
-            let value = value as f32 / winuser::WHEEL_DELTA as f32;
 
             update_modifiers(window, subclass_input);
 
@@ -1162,8 +2079,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 window_id: RootWindowId(WindowId(window)),
                 event: WindowEvent::MouseWheel {
                     device_id: DEVICE_ID,
-                    delta: LineDelta(0.0, value),
+                    delta: Value120(0, value),
                     phase: TouchPhase::Moved,
+                    source: util::wheel_source(),
                     modifiers: event::get_key_mods(),
                 },
             });
@@ -1172,11 +2090,11 @@ unsafe fn public_window_callback_inner<T: 'static>(
         }
 
         winuser::WM_MOUSEHWHEEL => {
-            use crate::event::MouseScrollDelta::LineDelta;
+            use crate::event::MouseScrollDelta::Value120;
 
+            // The value is in 120ths of a notch, `WHEEL_DELTA`.
             let value = (wparam >> 16) as i16;
             let value = value as i32;
-            let value = value as f32 / winuser::WHEEL_DELTA as f32;
 
             update_modifiers(window, subclass_input);
 
@@ -1184,8 +2102,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 window_id: RootWindowId(WindowId(window)),
                 event: WindowEvent::MouseWheel {
                     device_id: DEVICE_ID,
-                    delta: LineDelta(value, 0.0),
+                    delta: Value120(value, 0),
                     phase: TouchPhase::Moved,
+                    source: util::wheel_source(),
                     modifiers: event::get_key_mods(),
                 },
             });
@@ -1198,6 +2117,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
             if msg == winuser::WM_SYSKEYDOWN && wparam as i32 == winuser::VK_F4 {
                 commctrl::DefSubclassProc(window, msg, wparam, lparam)
             } else {
+                let repeat = is_repeat(lparam);
+                if repeat && !subclass_input.window_state.lock().key_repeat {
+                    return 0;
+                }
                 if let Some((scancode, vkey)) = process_key_params(wparam, lparam) {
                     update_modifiers(window, subclass_input);
 
@@ -1208,6 +2131,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             device_id: DEVICE_ID,
                             input: KeyboardInput {
                                 state: Pressed,
+                                repeat,
                                 scancode,
                                 virtual_keycode: vkey,
                                 modifiers: event::get_key_mods(),
@@ -1240,6 +2164,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                         device_id: DEVICE_ID,
                         input: KeyboardInput {
                             state: Released,
+                            repeat: false,
                             scancode,
                             virtual_keycode: vkey,
                             modifiers: event::get_key_mods(),
@@ -1377,9 +2302,15 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
         winuser::WM_XBUTTONDOWN => {
             use crate::event::{
-                ElementState::Pressed, MouseButton::Other, WindowEvent::MouseInput,
+                ElementState::Pressed,
+                MouseButton::{Back, Forward, Other},
+                WindowEvent::MouseInput,
+            };
+            let xbutton = match winuser::GET_XBUTTON_WPARAM(wparam) {
+                winuser::XBUTTON1 => Back,
+                winuser::XBUTTON2 => Forward,
+                xbutton => Other(xbutton),
             };
-            let xbutton = winuser::GET_XBUTTON_WPARAM(wparam);
 
             capture_mouse(window, &mut *subclass_input.window_state.lock());
 
@@ -1390,7 +2321,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Pressed,
-                    button: Other(xbutton),
+                    button: xbutton,
                     modifiers: event::get_key_mods(),
                 },
             });
@@ -1399,9 +2330,15 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
         winuser::WM_XBUTTONUP => {
             use crate::event::{
-                ElementState::Released, MouseButton::Other, WindowEvent::MouseInput,
+                ElementState::Released,
+                MouseButton::{Back, Forward, Other},
+                WindowEvent::MouseInput,
+            };
+            let xbutton = match winuser::GET_XBUTTON_WPARAM(wparam) {
+                winuser::XBUTTON1 => Back,
+                winuser::XBUTTON2 => Forward,
+                xbutton => Other(xbutton),
             };
-            let xbutton = winuser::GET_XBUTTON_WPARAM(wparam);
 
             release_mouse(subclass_input.window_state.lock());
 
@@ -1412,7 +2349,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Released,
-                    button: Other(xbutton),
+                    button: xbutton,
                     modifiers: event::get_key_mods(),
                 },
             });
@@ -1425,7 +2362,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
             // can happen if `SetCapture` is called on our window when it already has the mouse
             // capture.
             if lparam != window as isize {
-                subclass_input.window_state.lock().mouse.capture_count = 0;
+                let mut window_state = subclass_input.window_state.lock();
+                window_state.mouse.capture_count = 0;
+                window_state.mouse.pointer_captured = false;
             }
             0
         }
@@ -1442,6 +2381,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 mem::size_of::<winuser::TOUCHINPUT>() as INT,
             ) > 0
             {
+                let time = instant_from_tick_count(winuser::GetMessageTime() as DWORD);
                 for input in &inputs {
                     let mut location = POINT {
                         x: input.x / 100,
@@ -1455,24 +2395,28 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     let x = location.x as f64 + (input.x % 100) as f64 / 100f64;
                     let y = location.y as f64 + (input.y % 100) as f64 / 100f64;
                     let location = PhysicalPosition::new(x, y);
+                    let phase = if input.dwFlags & winuser::TOUCHEVENTF_DOWN != 0 {
+                        TouchPhase::Started
+                    } else if input.dwFlags & winuser::TOUCHEVENTF_UP != 0 {
+                        TouchPhase::Ended
+                    } else if input.dwFlags & winuser::TOUCHEVENTF_MOVE != 0 {
+                        TouchPhase::Moved
+                    } else {
+                        continue;
+                    };
                     subclass_input.send_event(Event::WindowEvent {
                         window_id: RootWindowId(WindowId(window)),
                         event: WindowEvent::Touch(Touch {
-                            phase: if input.dwFlags & winuser::TOUCHEVENTF_DOWN != 0 {
-                                TouchPhase::Started
-                            } else if input.dwFlags & winuser::TOUCHEVENTF_UP != 0 {
-                                TouchPhase::Ended
-                            } else if input.dwFlags & winuser::TOUCHEVENTF_MOVE != 0 {
-                                TouchPhase::Moved
-                            } else {
-                                continue;
-                            },
+                            phase,
                             location,
+                            time,
                             force: None, // WM_TOUCH doesn't support pressure information
                             id: input.dwID as u64,
                             device_id: DEVICE_ID,
+                            palm: input.dwFlags & winuser::TOUCHEVENTF_PALM != 0,
                         }),
                     });
+                    send_touch_gesture(window, subclass_input, input.dwID as u64, location, phase);
                 }
             }
             winuser::CloseTouchInputHandle(htouch);
@@ -1558,6 +2502,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                         continue;
                     }
 
+                    let mut pen_info = None;
                     let force = match pointer_info.pointerType {
                         winuser::PT_TOUCH => {
                             let mut touch_info = mem::MaybeUninit::uninit();
@@ -1574,18 +2519,9 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             })
                         }
                         winuser::PT_PEN => {
-                            let mut pen_info = mem::MaybeUninit::uninit();
-                            GET_POINTER_PEN_INFO.and_then(|GetPointerPenInfo| {
-                                match GetPointerPenInfo(
-                                    pointer_info.pointerId,
-                                    pen_info.as_mut_ptr(),
-                                ) {
-                                    0 => None,
-                                    _ => {
-                                        normalize_pointer_pressure(pen_info.assume_init().pressure)
-                                    }
-                                }
-                            })
+                            pen_info = get_pointer_pen_info(pointer_info.pointerId);
+                            pen_info
+                                .and_then(|pen_info| normalize_pointer_pressure(pen_info.pressure))
                         }
                         _ => None,
                     };
@@ -1593,25 +2529,70 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     let x = location.x as f64 + x.fract();
                     let y = location.y as f64 + y.fract();
                     let location = PhysicalPosition::new(x, y);
+                    let flags = pointer_info.pointerFlags;
+                    let phase = if flags & winuser::POINTER_FLAG_DOWN != 0 {
+                        TouchPhase::Started
+                    } else if flags & winuser::POINTER_FLAG_UP != 0 {
+                        // The pointers the system takes back, like the palms, are cancelled.
+                        if flags & winuser::POINTER_FLAG_CANCELED != 0 {
+                            TouchPhase::Cancelled
+                        } else {
+                            TouchPhase::Ended
+                        }
+                    } else if flags & winuser::POINTER_FLAG_UPDATE != 0 {
+                        TouchPhase::Moved
+                    } else {
+                        continue;
+                    };
+                    let mut window_state = subclass_input.window_state.lock();
+                    match phase {
+                        TouchPhase::Started | TouchPhase::Moved => {
+                            window_state
+                                .touches
+                                .insert(pointer_info.pointerId, location);
+                        }
+                        TouchPhase::Ended | TouchPhase::Cancelled => {
+                            window_state.touches.remove(&pointer_info.pointerId);
+                        }
+                    }
+                    drop(window_state);
+                    // The entries of the history are the moves coalesced into the message.
+                    let time = instant_from_tick_count(pointer_info.dwTime);
                     subclass_input.send_event(Event::WindowEvent {
                         window_id: RootWindowId(WindowId(window)),
                         event: WindowEvent::Touch(Touch {
-                            phase: if pointer_info.pointerFlags & winuser::POINTER_FLAG_DOWN != 0 {
-                                TouchPhase::Started
-                            } else if pointer_info.pointerFlags & winuser::POINTER_FLAG_UP != 0 {
-                                TouchPhase::Ended
-                            } else if pointer_info.pointerFlags & winuser::POINTER_FLAG_UPDATE != 0
-                            {
-                                TouchPhase::Moved
-                            } else {
-                                continue;
-                            },
+                            phase,
                             location,
+                            time,
                             force,
                             id: pointer_info.pointerId as u64,
                             device_id: DEVICE_ID,
+                            palm: false,
                         }),
                     });
+                    if pointer_info.pointerType == winuser::PT_TOUCH {
+                        let id = pointer_info.pointerId as u64;
+                        send_touch_gesture(window, subclass_input, id, location, phase);
+                    }
+                    if let Some(pen_info) = pen_info {
+                        let phase = match phase {
+                            TouchPhase::Started => PenPhase::Down,
+                            TouchPhase::Ended | TouchPhase::Cancelled => PenPhase::Up,
+                            _ => PenPhase::Moved,
+                        };
+                        let serial = subclass_input
+                            .window_state
+                            .lock()
+                            .pen_serials
+                            .get(&pointer_info.pointerId)
+                            .cloned()
+                            .flatten();
+                        let pen_event = pen_event(&pen_info, phase, location, time, serial);
+                        subclass_input.send_event(Event::WindowEvent {
+                            window_id: RootWindowId(WindowId(window)),
+                            event: WindowEvent::Pen(pen_event),
+                        });
+                    }
                 }
 
                 SkipPointerFrameMessages(pointer_id);
@@ -1619,6 +2600,80 @@ unsafe fn public_window_callback_inner<T: 'static>(
             0
         }
 
+        winuser::WM_POINTERENTER | winuser::WM_POINTERLEAVE => {
+            // The pens coming close and leaving, whose position is in screen coordinates.
+            let pointer_id = LOWORD(wparam as DWORD) as u32;
+            if let Some(pen_info) = get_pointer_pen_info(pointer_id) {
+                let mut location = pen_info.pointerInfo.ptPixelLocation;
+                if winuser::ScreenToClient(window, &mut location) != 0 {
+                    let mut window_state = subclass_input.window_state.lock();
+                    let (phase, serial) = match msg {
+                        winuser::WM_POINTERENTER => {
+                            let serial = get_pen_serial(&pen_info.pointerInfo);
+                            window_state.pen_serials.insert(pointer_id, serial);
+                            (PenPhase::Entered, serial)
+                        }
+                        _ => {
+                            let serial = window_state.pen_serials.remove(&pointer_id);
+                            (PenPhase::Left, serial.flatten())
+                        }
+                    };
+                    drop(window_state);
+                    let location = PhysicalPosition::new(location.x as f64, location.y as f64);
+                    let time = instant_from_tick_count(winuser::GetMessageTime() as DWORD);
+                    let pen_event = pen_event(&pen_info, phase, location, time, serial);
+                    subclass_input.send_event(Event::WindowEvent {
+                        window_id: RootWindowId(WindowId(window)),
+                        event: WindowEvent::Pen(pen_event),
+                    });
+                }
+            }
+            commctrl::DefSubclassProc(window, msg, wparam, lparam)
+        }
+
+        winuser::WM_POINTERCAPTURECHANGED => {
+            // The window gets no more messages of the pointer, like when the system takes it for
+            // a gesture of its own.
+            let pointer_id = LOWORD(wparam as DWORD) as u32;
+            let location = subclass_input
+                .window_state
+                .lock()
+                .touches
+                .remove(&pointer_id);
+            if let Some(location) = location {
+                let id = pointer_id as u64;
+                subclass_input.send_event(Event::WindowEvent {
+                    window_id: RootWindowId(WindowId(window)),
+                    event: WindowEvent::Touch(Touch {
+                        phase: TouchPhase::Cancelled,
+                        location,
+                        time: instant_from_tick_count(winuser::GetMessageTime() as DWORD),
+                        force: None,
+                        id,
+                        device_id: DEVICE_ID,
+                        palm: false,
+                    }),
+                });
+                send_touch_gesture(window, subclass_input, id, location, TouchPhase::Cancelled);
+            }
+            commctrl::DefSubclassProc(window, msg, wparam, lparam)
+        }
+
+        direct_manipulation::DM_POINTERHITTEST => {
+            // The contacts of the touchpads scroll through the viewport of DirectManipulation.
+            if let Some(direct_manipulation) = subclass_input.direct_manipulation.as_ref() {
+                direct_manipulation.hit_test(LOWORD(wparam as DWORD) as u32);
+            }
+            commctrl::DefSubclassProc(window, msg, wparam, lparam)
+        }
+
+        winuser::WM_TIMER if wparam == direct_manipulation::UPDATE_TIMER_ID => {
+            if let Some(direct_manipulation) = subclass_input.direct_manipulation.as_ref() {
+                direct_manipulation.update();
+            }
+            0
+        }
+
         winuser::WM_SETFOCUS => {
             use crate::event::{ElementState::Released, WindowEvent::Focused};
             for windows_keycode in event::get_pressed_keys() {
@@ -1637,6 +2692,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             scancode,
                             virtual_keycode,
                             state: Released,
+                            repeat: false,
                             modifiers: event::get_key_mods(),
                         },
                         is_synthetic: true,
@@ -1644,6 +2700,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 })
             }
 
+            send_device_modifiers(window, subclass_input, true);
+
             subclass_input.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: Focused(true),
@@ -1655,7 +2713,6 @@ unsafe fn public_window_callback_inner<T: 'static>(
         winuser::WM_KILLFOCUS => {
             use crate::event::{
                 ElementState::Released,
-                ModifiersState,
                 WindowEvent::{Focused, ModifiersChanged},
             };
             for windows_keycode in event::get_pressed_keys() {
@@ -1672,6 +2729,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             scancode,
                             virtual_keycode,
                             state: Released,
+                            repeat: false,
                             modifiers: event::get_key_mods(),
                         },
                         is_synthetic: true,
@@ -1684,6 +2742,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 window_id: RootWindowId(WindowId(window)),
                 event: ModifiersChanged(ModifiersState::empty()),
             });
+            send_device_modifiers(window, subclass_input, false);
 
             subclass_input.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
@@ -1694,22 +2753,25 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
         winuser::WM_SETCURSOR => {
             let set_cursor_to = {
-                let window_state = subclass_input.window_state.lock();
+                let mut window_state = subclass_input.window_state.lock();
                 // The return value for the preceding `WM_NCHITTEST` message is conveniently
                 // provided through the low-order word of lParam. We use that here since
                 // `WM_MOUSEMOVE` seems to come after `WM_SETCURSOR` for a given cursor movement.
                 let in_client_area = LOWORD(lparam as DWORD) == winuser::HTCLIENT as WORD;
                 if in_client_area {
-                    Some(window_state.mouse.cursor)
+                    let scale_factor = window_state.scale_factor;
+                    Some((
+                        window_state.mouse.cursor,
+                        window_state.mouse.shown_cursor(scale_factor),
+                    ))
                 } else {
                     None
                 }
             };
 
             match set_cursor_to {
-                Some(cursor) => {
-                    let cursor = winuser::LoadCursorW(ptr::null_mut(), cursor.to_windows_cursor());
-                    winuser::SetCursor(cursor);
+                Some((cursor, shown_cursor)) => {
+                    window_state::set_client_cursor(cursor, shown_cursor);
                     0
                 }
                 None => winuser::DefWindowProcW(window, msg, wparam, lparam),
@@ -1748,6 +2810,36 @@ unsafe fn public_window_callback_inner<T: 'static>(
             0
         }
 
+        winuser::WM_SIZING => {
+            let aspect_ratio = subclass_input.window_state.lock().aspect_ratio;
+            let ratio = match aspect_ratio {
+                Some(ratio) => ratio,
+                None => return winuser::DefWindowProcW(window, msg, wparam, lparam),
+            };
+
+            // The dragged rectangle is the one of the window, which has the frame around the
+            // client area.
+            let rect = &mut *(lparam as *mut RECT);
+            let frame = util::adjust_size(window, PhysicalSize::new(0, 0));
+            let width = (rect.right - rect.left - frame.width as i32).max(0) as u32;
+            let height = (rect.bottom - rect.top - frame.height as i32).max(0) as u32;
+
+            // The dragged edge is kept, the width being kept when a corner is dragged.
+            match wparam as u32 {
+                winuser::WMSZ_TOP | winuser::WMSZ_BOTTOM => {
+                    rect.right = rect.left + (ratio.width_for(height) + frame.width) as i32;
+                }
+                winuser::WMSZ_TOPLEFT | winuser::WMSZ_TOPRIGHT => {
+                    rect.top = rect.bottom - (ratio.height_for(width) + frame.height) as i32;
+                }
+                _ => {
+                    rect.bottom = rect.top + (ratio.height_for(width) + frame.height) as i32;
+                }
+            }
+
+            1
+        }
+
         // Only sent on Windows 8.1 or newer. On Windows 7 and older user has to log out to change
         // DPI, therefore all applications are closed while DPI is changing.
         winuser::WM_DPICHANGED => {
@@ -1842,6 +2934,13 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 dragging_window = window_state
                     .window_flags()
                     .contains(WindowFlags::MARKER_IN_SIZE_MOVE);
+                // The caption buttons are drawn over a frame of the size of the new DPI.
+                if window_state
+                    .window_flags()
+                    .contains(WindowFlags::CAPTION_BUTTONS)
+                {
+                    window_state.window_flags().extend_frame(window);
+                }
                 // Unset maximized if we're changing the window's size.
                 if new_physical_inner_size != old_physical_inner_size {
                     WindowState::set_window_flags(window_state, window, |f| {
@@ -1965,6 +3064,15 @@ unsafe fn public_window_callback_inner<T: 'static>(
             0
         }
 
+        winuser::WM_INPUTLANGCHANGE => {
+            subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window)),
+                event: WindowEvent::KeyboardLayoutChanged(keymap::layout(lparam as HKL)),
+            });
+
+            commctrl::DefSubclassProc(window, msg, wparam, lparam)
+        }
+
         winuser::WM_SETTINGCHANGE => {
             use crate::event::WindowEvent::ThemeChanged;
 
@@ -1984,6 +3092,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 }
             }
 
+            if wparam == winuser::SPI_SETWORKAREA as WPARAM {
+                update_recommended_bounds(window, subclass_input);
+            }
+
             commctrl::DefSubclassProc(window, msg, wparam, lparam)
         }
 
@@ -1991,12 +3103,68 @@ unsafe fn public_window_callback_inner<T: 'static>(
             if msg == *DESTROY_MSG_ID {
                 winuser::DestroyWindow(window);
                 0
+            } else if msg == *CLOAK_CHANGED_MSG_ID {
+                update_occlusion(window, subclass_input);
+                // The windows are cloaked as they move to another virtual desktop.
+                update_workspace(window, subclass_input);
+                0
             } else if msg == *SET_RETAIN_STATE_ON_SIZE_MSG_ID {
                 let mut window_state = subclass_input.window_state.lock();
                 window_state.set_window_flags_in_place(|f| {
                     f.set(WindowFlags::MARKER_RETAIN_STATE_ON_SIZE, wparam != 0)
                 });
                 0
+            } else if msg == *IME_STATE_MSG_ID {
+                if let Some(ref text_store) = subclass_input.text_store {
+                    let window_state = subclass_input.window_state.lock();
+                    let surrounding_text = window_state.surrounding_text.clone();
+                    let cursor_area = window_state.ime_cursor_area;
+                    let purpose = window_state.ime_purpose;
+                    drop(window_state);
+                    text_store.set_ime_state(surrounding_text.as_ref(), cursor_area, purpose);
+                }
+                0
+            } else if msg == *CLIPBOARD_DATA_MSG_ID {
+                let content = Box::from_raw(lparam as *mut Option<ClipboardMimedContent>);
+                subclass_input.send_event(Event::WindowEvent {
+                    window_id: RootWindowId(WindowId(window)),
+                    event: WindowEvent::ClipboardData {
+                        kind: ClipboardKind::Clipboard,
+                        content: *content,
+                    },
+                });
+                0
+            } else if msg == *DRAG_ENTERED_MSG_ID {
+                if let Some(action) = drag_source::effect_action(wparam as DWORD) {
+                    subclass_input.send_event(Event::WindowEvent {
+                        window_id: RootWindowId(WindowId(window)),
+                        event: WindowEvent::DragEntered { action },
+                    });
+                }
+                0
+            } else if msg == *DRAG_MOVED_MSG_ID {
+                let x = windowsx::GET_X_LPARAM(lparam) as f64;
+                let y = windowsx::GET_Y_LPARAM(lparam) as f64;
+                subclass_input.send_event(Event::WindowEvent {
+                    window_id: RootWindowId(WindowId(window)),
+                    event: WindowEvent::DragMoved {
+                        position: PhysicalPosition::new(x, y),
+                    },
+                });
+                0
+            } else if msg == *DRAG_FINISHED_MSG_ID {
+                let event = match drag_source::effect_action(wparam as DWORD) {
+                    Some(action) => WindowEvent::DragFinished {
+                        action,
+                        outside: lparam != 0,
+                    },
+                    None => WindowEvent::DragCancelled,
+                };
+                subclass_input.send_event(Event::WindowEvent {
+                    window_id: RootWindowId(WindowId(window)),
+                    event,
+                });
+                0
             } else {
                 commctrl::DefSubclassProc(window, msg, wparam, lparam)
             }
@@ -2072,17 +3240,76 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
             commctrl::DefSubclassProc(window, msg, wparam, lparam)
         }
 
+        // The clipboard set through `EventLoopWindowTarget` is owned by this window.
+        winuser::WM_RENDERFORMAT => {
+            clipboard::render_format(window, wparam as UINT);
+            0
+        }
+
+        winuser::WM_RENDERALLFORMATS => {
+            clipboard::render_all_formats(window);
+            0
+        }
+
+        winuser::WM_DESTROYCLIPBOARD => {
+            clipboard::clear_provider(window);
+            0
+        }
+
+        winuser::WM_DISPLAYCHANGE => {
+            let monitors = monitor::available_monitors();
+            let previous = subclass_input.monitors.replace(monitors.clone());
+            let missing_from = |list: &VecDeque<MonitorHandle>,
+                                monitors: &VecDeque<MonitorHandle>| {
+                monitors
+                    .iter()
+                    .filter(|monitor| !list.contains(monitor))
+                    .map(|monitor| RootMonitorHandle {
+                        inner: monitor.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let added = missing_from(&previous, &monitors);
+            let removed = missing_from(&monitors, &previous);
+            if !added.is_empty() || !removed.is_empty() {
+                subclass_input.send_event(Event::MonitorsChanged { added, removed });
+            }
+
+            let monitor_colors = monitor::available_color_capabilities();
+            let previous = subclass_input
+                .monitor_colors
+                .replace(monitor_colors.clone());
+            for (monitor, capabilities) in monitor_colors {
+                let changed = previous
+                    .iter()
+                    .any(|(handle, previous)| *handle == monitor && *previous != capabilities);
+                if changed {
+                    subclass_input.send_event(Event::MonitorEvent {
+                        monitor: RootMonitorHandle { inner: monitor },
+                        event: MonitorEvent::ColorChanged(capabilities),
+                    });
+                }
+            }
+
+            commctrl::DefSubclassProc(window, msg, wparam, lparam)
+        }
+
         winuser::WM_INPUT_DEVICE_CHANGE => {
             let event = match wparam as _ {
-                winuser::GIDC_ARRIVAL => DeviceEvent::Added,
-                winuser::GIDC_REMOVAL => DeviceEvent::Removed,
+                // The devices which can't be queried anymore were already removed again.
+                winuser::GIDC_ARRIVAL => {
+                    raw_input::get_input_device(lparam as _).map(DeviceEvent::Added)
+                }
+                winuser::GIDC_REMOVAL => Some(DeviceEvent::Removed),
                 _ => unreachable!(),
             };
 
-            subclass_input.send_event(Event::DeviceEvent {
-                device_id: wrap_device_id(lparam as _),
-                event,
-            });
+            if let Some(event) = event {
+                subclass_input.send_event(Event::DeviceEvent {
+                    device_id: wrap_device_id(lparam as _),
+                    event,
+                });
+            }
 
             0
         }
@@ -2091,9 +3318,23 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
             use crate::event::{
                 DeviceEvent::{Button, Key, Motion, MouseMotion, MouseWheel},
                 ElementState::{Pressed, Released},
-                MouseScrollDelta::LineDelta,
+                MouseScrollDelta::Value120,
             };
 
+            if !subclass_input.hid_reports.is_empty() {
+                if let Some((device, reports)) = raw_input::get_raw_input_hid_reports(lparam as _) {
+                    if subclass_input.hid_reports.is_enabled(device) {
+                        let device_id = wrap_device_id(device as _);
+                        for data in reports {
+                            subclass_input.send_event(Event::DeviceEvent {
+                                device_id,
+                                event: DeviceEvent::HidReport { data },
+                            });
+                        }
+                    }
+                }
+            }
+
             if let Some(data) = raw_input::get_raw_input_data(lparam as _) {
                 let device_id = wrap_device_id(data.header.hDevice as _);
 
@@ -2127,12 +3368,11 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
                     }
 
                     if util::has_flag(mouse.usButtonFlags, winuser::RI_MOUSE_WHEEL) {
-                        let delta =
-                            mouse.usButtonData as SHORT as f32 / winuser::WHEEL_DELTA as f32;
+                        let delta = mouse.usButtonData as SHORT as i32;
                         subclass_input.send_event(Event::DeviceEvent {
                             device_id,
                             event: MouseWheel {
-                                delta: LineDelta(0.0, delta),
+                                delta: Value120(0, delta),
                             },
                         });
                     }
@@ -2171,12 +3411,41 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
                         {
                             let virtual_keycode = vkey_to_winit_vkey(vkey);
 
+                            if let Some(modifier) = vkey_to_modifier_side(vkey) {
+                                let device = data.header.hDevice as u32;
+                                let modifiers = KEYBOARD_MODIFIERS.with(|keyboard_modifiers| {
+                                    let mut keyboard_modifiers = keyboard_modifiers.borrow_mut();
+                                    let side = keyboard_modifiers.entry(device).or_default();
+                                    let old = ModifiersState::from(side.filter_out_altgr());
+                                    side.set(modifier, pressed);
+                                    let new = ModifiersState::from(side.filter_out_altgr());
+                                    if old != new {
+                                        Some(new)
+                                    } else {
+                                        None
+                                    }
+                                });
+                                // The raw input comes to the event loop, so the modifiers go to
+                                // the window with the focus.
+                                let focus = winuser::GetFocus();
+                                if let (Some(modifiers), false) = (modifiers, focus.is_null()) {
+                                    subclass_input.send_event(Event::WindowEvent {
+                                        window_id: RootWindowId(WindowId(focus)),
+                                        event: WindowEvent::DeviceModifiersChanged {
+                                            device_id,
+                                            modifiers,
+                                        },
+                                    });
+                                }
+                            }
+
                             #[allow(deprecated)]
                             subclass_input.send_event(Event::DeviceEvent {
                                 device_id,
                                 event: Key(KeyboardInput {
                                     scancode,
                                     state,
+                                    repeat: false,
                                     virtual_keycode,
                                     modifiers: event::get_key_mods(),
                                 }),
@@ -2195,6 +3464,38 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
             }
             0
         }
+        // Receiving the message is enough to go through an iteration of the event loop.
+        _ if msg == *WAKE_UP_MSG_ID => 0,
+        winuser::WM_TIMER => {
+            let id = TimerId(wparam as usize);
+            if subclass_input.timers.handle_timer(id) {
+                subclass_input.send_event(Event::Timer(id));
+            }
+            0
+        }
+        winuser::WM_HOTKEY => {
+            // The hotkey may have been unregistered since the message was posted.
+            let id = HotkeyId(wparam);
+            if subclass_input.hotkeys.contains(id) {
+                subclass_input.send_event(Event::GlobalHotkey(id));
+            }
+            0
+        }
+        _ if msg == *SOURCE_READY_MSG_ID => {
+            let token = SourceToken(wparam as usize);
+            // The source may have been removed since it was signaled.
+            if subclass_input.external_sources.contains(token) {
+                subclass_input.send_event(Event::ExternalSource(token));
+                subclass_input.external_sources.rearm(token);
+            }
+            0
+        }
+        _ if msg == *GAMEPADS_CHANGED_MSG_ID => {
+            for (gamepad_id, event) in subclass_input.gamepads.take_events() {
+                subclass_input.send_event(Event::GamepadEvent { gamepad_id, event });
+            }
+            0
+        }
         _ if msg == *EXEC_MSG_ID => {
             let mut function: ThreadExecFn = Box::from_raw(wparam as usize as *mut _);
             function();
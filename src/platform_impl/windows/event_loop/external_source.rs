@@ -0,0 +1,151 @@
+use std::{cell::RefCell, collections::HashMap, io, ptr};
+
+use winapi::{
+    shared::{
+        minwindef::{BOOL, WPARAM},
+        ntdef::{BOOLEAN, PVOID},
+        windef::HWND,
+    },
+    um::{
+        handleapi::INVALID_HANDLE_VALUE,
+        threadpoollegacyapiset, winbase,
+        winnt::{HANDLE, WT_EXECUTEONLYONCE},
+        winuser,
+    },
+};
+
+use crate::{error::ExternalError, event_loop::SourceToken};
+
+use super::SOURCE_READY_MSG_ID;
+
+/// The external sources registered by the user, which are shared with the thread event target.
+pub(crate) struct ExternalSources {
+    target_window: HWND,
+    sources: RefCell<HashMap<SourceToken, ExternalSource>>,
+}
+
+struct ExternalSource {
+    handle: HANDLE,
+    /// The wait on the handle, which is registered again once the source is reported.
+    wait: HANDLE,
+    /// The context of the wait, which must outlive it.
+    context: Box<SourceContext>,
+}
+
+struct SourceContext {
+    target_window: HWND,
+    token: SourceToken,
+}
+
+impl ExternalSources {
+    pub fn new(target_window: HWND) -> Self {
+        Self {
+            target_window,
+            sources: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn insert(&self, handle: HANDLE, token: SourceToken) -> Result<(), ExternalError> {
+        let mut sources = self.sources.borrow_mut();
+        if sources.contains_key(&token) {
+            return Err(ExternalError::Os(os_error!(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "The token of the source is already in use",
+            ))));
+        }
+
+        let context = Box::new(SourceContext {
+            target_window: self.target_window,
+            token,
+        });
+        let wait = unsafe { register_wait(handle, &context) }
+            .map_err(|err| ExternalError::Os(os_error!(err)))?;
+        sources.insert(
+            token,
+            ExternalSource {
+                handle,
+                wait,
+                context,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove(&self, token: SourceToken) -> Result<(), ExternalError> {
+        let source = self.sources.borrow_mut().remove(&token).ok_or_else(|| {
+            ExternalError::Os(os_error!(io::Error::new(
+                io::ErrorKind::NotFound,
+                "There's no source with this token",
+            )))
+        })?;
+        unsafe { unregister_wait(source.wait) };
+        Ok(())
+    }
+
+    /// Whether the source reported by `SOURCE_READY_MSG_ID` is still registered.
+    pub fn contains(&self, token: SourceToken) -> bool {
+        self.sources.borrow().contains_key(&token)
+    }
+
+    /// Waits for the source again once it's reported, so it's reported for as long as it stays
+    /// signaled.
+    pub fn rearm(&self, token: SourceToken) {
+        let mut sources = self.sources.borrow_mut();
+        let source = match sources.get_mut(&token) {
+            Some(source) => source,
+            None => return,
+        };
+        unsafe {
+            unregister_wait(source.wait);
+            match register_wait(source.handle, &source.context) {
+                Ok(wait) => source.wait = wait,
+                Err(err) => {
+                    warn!("Failed to wait for the external source again: {}", err);
+                    sources.remove(&token);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ExternalSources {
+    fn drop(&mut self) {
+        for (_, source) in self.sources.get_mut().drain() {
+            unsafe { unregister_wait(source.wait) };
+        }
+    }
+}
+
+/// Waits for the handle once on the thread pool, posting `SOURCE_READY_MSG_ID` when it's
+/// signaled.
+unsafe fn register_wait(handle: HANDLE, context: &SourceContext) -> Result<HANDLE, io::Error> {
+    let mut wait = ptr::null_mut();
+    let result: BOOL = winbase::RegisterWaitForSingleObject(
+        &mut wait,
+        handle,
+        Some(source_signaled),
+        context as *const SourceContext as PVOID,
+        winbase::INFINITE,
+        WT_EXECUTEONLYONCE,
+    );
+    if result == 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(wait)
+    }
+}
+
+/// Cancels the wait, blocking until its callback is over, so the context can be freed.
+unsafe fn unregister_wait(wait: HANDLE) {
+    threadpoollegacyapiset::UnregisterWaitEx(wait, INVALID_HANDLE_VALUE);
+}
+
+unsafe extern "system" fn source_signaled(context: PVOID, _timed_out: BOOLEAN) {
+    let context = &*(context as *const SourceContext);
+    winuser::PostMessageW(
+        context.target_window,
+        *SOURCE_READY_MSG_ID,
+        context.token.0 as WPARAM,
+        0,
+    );
+}
@@ -0,0 +1,430 @@
+use std::{
+    cell::{Cell, RefCell},
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use winapi::{
+    shared::{
+        minwindef::{BYTE, DWORD, WORD},
+        ntdef::SHORT,
+        windef::HWND,
+        winerror::ERROR_SUCCESS,
+    },
+    um::winuser,
+};
+
+use crate::{
+    error::{ExternalError, NotSupportedError},
+    event::ElementState,
+    gamepad::{GamepadAxis, GamepadButton, GamepadEvent, GamepadId},
+};
+
+use super::GAMEPADS_CHANGED_MSG_ID;
+
+/// The interval of the polls of the connected controllers.
+const POLL_INTERVAL: Duration = Duration::from_millis(8);
+
+/// The interval of the polls of the empty slots, which are much slower to poll.
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The number of controllers XInput handles.
+const SLOT_COUNT: usize = 4;
+
+/// The threshold past which the triggers are pressed, as `XINPUT_GAMEPAD_TRIGGER_THRESHOLD`.
+const TRIGGER_THRESHOLD: BYTE = 30;
+
+// Not exposed by the features of `winapi` used here.
+const XINPUT_GAMEPAD_DPAD_UP: WORD = 0x0001;
+const XINPUT_GAMEPAD_DPAD_DOWN: WORD = 0x0002;
+const XINPUT_GAMEPAD_DPAD_LEFT: WORD = 0x0004;
+const XINPUT_GAMEPAD_DPAD_RIGHT: WORD = 0x0008;
+const XINPUT_GAMEPAD_START: WORD = 0x0010;
+const XINPUT_GAMEPAD_BACK: WORD = 0x0020;
+const XINPUT_GAMEPAD_LEFT_THUMB: WORD = 0x0040;
+const XINPUT_GAMEPAD_RIGHT_THUMB: WORD = 0x0080;
+const XINPUT_GAMEPAD_LEFT_SHOULDER: WORD = 0x0100;
+const XINPUT_GAMEPAD_RIGHT_SHOULDER: WORD = 0x0200;
+const XINPUT_GAMEPAD_A: WORD = 0x1000;
+const XINPUT_GAMEPAD_B: WORD = 0x2000;
+const XINPUT_GAMEPAD_X: WORD = 0x4000;
+const XINPUT_GAMEPAD_Y: WORD = 0x8000;
+
+const BUTTONS: [(WORD, GamepadButton); 14] = [
+    (XINPUT_GAMEPAD_DPAD_UP, GamepadButton::DPadUp),
+    (XINPUT_GAMEPAD_DPAD_DOWN, GamepadButton::DPadDown),
+    (XINPUT_GAMEPAD_DPAD_LEFT, GamepadButton::DPadLeft),
+    (XINPUT_GAMEPAD_DPAD_RIGHT, GamepadButton::DPadRight),
+    (XINPUT_GAMEPAD_START, GamepadButton::Start),
+    (XINPUT_GAMEPAD_BACK, GamepadButton::Select),
+    (XINPUT_GAMEPAD_LEFT_THUMB, GamepadButton::LeftStick),
+    (XINPUT_GAMEPAD_RIGHT_THUMB, GamepadButton::RightStick),
+    (XINPUT_GAMEPAD_LEFT_SHOULDER, GamepadButton::LeftBumper),
+    (XINPUT_GAMEPAD_RIGHT_SHOULDER, GamepadButton::RightBumper),
+    (XINPUT_GAMEPAD_A, GamepadButton::South),
+    (XINPUT_GAMEPAD_B, GamepadButton::East),
+    (XINPUT_GAMEPAD_X, GamepadButton::West),
+    (XINPUT_GAMEPAD_Y, GamepadButton::North),
+];
+
+#[allow(non_snake_case)]
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq)]
+struct XINPUT_GAMEPAD {
+    wButtons: WORD,
+    bLeftTrigger: BYTE,
+    bRightTrigger: BYTE,
+    sThumbLX: SHORT,
+    sThumbLY: SHORT,
+    sThumbRX: SHORT,
+    sThumbRY: SHORT,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct XINPUT_STATE {
+    dwPacketNumber: DWORD,
+    Gamepad: XINPUT_GAMEPAD,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct XINPUT_VIBRATION {
+    wLeftMotorSpeed: WORD,
+    wRightMotorSpeed: WORD,
+}
+
+type XInputGetState = unsafe extern "system" fn(DWORD, *mut XINPUT_STATE) -> DWORD;
+type XInputSetState = unsafe extern "system" fn(DWORD, *mut XINPUT_VIBRATION) -> DWORD;
+
+lazy_static! {
+    // The version of Windows 7 is used when the one of Windows 8 isn't there.
+    static ref XINPUT_GET_STATE: Option<XInputGetState> =
+        get_function!("xinput1_4.dll", XInputGetState)
+            .or_else(|| get_function!("xinput9_1_0.dll", XInputGetState));
+    static ref XINPUT_SET_STATE: Option<XInputSetState> =
+        get_function!("xinput1_4.dll", XInputSetState)
+            .or_else(|| get_function!("xinput9_1_0.dll", XInputSetState));
+}
+
+/// When the rumbles of the slots stop. The lock is held while the vibrations are set, so the
+/// thread doesn't stop a rumble which was just replaced.
+type Rumbles = Arc<Mutex<[Option<Instant>; SLOT_COUNT]>>;
+
+/// The XInput controllers, which are polled on a thread of their own while the gamepads are
+/// enabled. The thread only posts `GAMEPADS_CHANGED_MSG_ID` to the thread event target once the
+/// state of a controller changed, so the event loop isn't woken up for nothing.
+pub(crate) struct Gamepads {
+    target_window: HWND,
+    next_id: Cell<u32>,
+    poll: RefCell<Option<Poll>>,
+}
+
+struct Poll {
+    stop: Arc<AtomicBool>,
+    /// The states of the controllers which changed, or `None` when they were disconnected.
+    receiver: Receiver<(usize, Option<XINPUT_GAMEPAD>)>,
+    /// The connected controllers, with the last state which was sent.
+    slots: [Option<(GamepadId, XINPUT_GAMEPAD)>; SLOT_COUNT],
+    rumbles: Rumbles,
+}
+
+impl Drop for Poll {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Gamepads {
+    pub fn new(target_window: HWND) -> Self {
+        Self {
+            target_window,
+            next_id: Cell::new(0),
+            poll: RefCell::new(None),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), ExternalError> {
+        let mut poll = self.poll.borrow_mut();
+        if !enabled {
+            *poll = None;
+            return Ok(());
+        }
+        if poll.is_some() {
+            return Ok(());
+        }
+        let get_state = XINPUT_GET_STATE.ok_or_else(|| {
+            ExternalError::Os(os_error!(io::Error::new(
+                io::ErrorKind::NotFound,
+                "XInput isn't available",
+            )))
+        })?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel();
+        let rumbles = Rumbles::default();
+        let target_window = self.target_window as usize;
+        let thread_stop = stop.clone();
+        let thread_rumbles = rumbles.clone();
+        thread::Builder::new()
+            .name("winit gamepads".to_owned())
+            .spawn(move || {
+                poll_thread(
+                    target_window as HWND,
+                    get_state,
+                    thread_stop,
+                    sender,
+                    thread_rumbles,
+                )
+            })
+            .map_err(|err| ExternalError::Os(os_error!(err)))?;
+        *poll = Some(Poll {
+            stop,
+            receiver,
+            slots: [None; SLOT_COUNT],
+            rumbles,
+        });
+        Ok(())
+    }
+
+    pub fn rumble(
+        &self,
+        gamepad_id: GamepadId,
+        strong: f64,
+        weak: f64,
+        duration: Duration,
+    ) -> Result<(), ExternalError> {
+        let poll = self.poll.borrow();
+        let (poll, slot) = poll
+            .as_ref()
+            .and_then(|poll| {
+                let slot = poll
+                    .slots
+                    .iter()
+                    .position(|slot| matches!(slot, Some((id, _)) if *id == gamepad_id))?;
+                Some((poll, slot))
+            })
+            .ok_or_else(|| {
+                ExternalError::Os(os_error!(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "The gamepad isn't connected",
+                )))
+            })?;
+        let set_state = XINPUT_SET_STATE
+            .ok_or_else(|| ExternalError::NotSupported(NotSupportedError::new()))?;
+
+        let speed = |value: f64| (value.clamp(0., 1.) * WORD::max_value() as f64) as WORD;
+        let mut vibration = XINPUT_VIBRATION {
+            wLeftMotorSpeed: speed(strong),
+            wRightMotorSpeed: speed(weak),
+        };
+        let mut rumbles = poll.rumbles.lock().unwrap();
+        let result = unsafe { set_state(slot as DWORD, &mut vibration) };
+        if result != ERROR_SUCCESS {
+            return Err(ExternalError::Os(os_error!(io::Error::from_raw_os_error(
+                result as i32
+            ))));
+        }
+        rumbles[slot] = Some(Instant::now() + duration);
+        Ok(())
+    }
+
+    /// Takes the changes posted by the thread, giving the events of the controllers.
+    pub fn take_events(&self) -> Vec<(GamepadId, GamepadEvent)> {
+        let mut events = Vec::new();
+        let mut poll = self.poll.borrow_mut();
+        let poll = match poll.as_mut() {
+            Some(poll) => poll,
+            None => return events,
+        };
+        while let Ok((slot, state)) = poll.receiver.try_recv() {
+            match (state, poll.slots[slot]) {
+                (Some(state), Some((id, previous))) => {
+                    push_changes(id, &previous, &state, &mut events);
+                    poll.slots[slot] = Some((id, state));
+                }
+                (Some(state), None) => {
+                    let id = GamepadId(self.next_id.get());
+                    self.next_id.set(self.next_id.get().wrapping_add(1));
+                    events.push((
+                        id,
+                        GamepadEvent::Connected {
+                            name: format!("XInput Controller {}", slot + 1),
+                        },
+                    ));
+                    push_changes(id, &XINPUT_GAMEPAD::default(), &state, &mut events);
+                    poll.slots[slot] = Some((id, state));
+                }
+                (None, Some((id, _))) => {
+                    events.push((id, GamepadEvent::Disconnected));
+                    poll.slots[slot] = None;
+                }
+                (None, None) => (),
+            }
+        }
+        events
+    }
+}
+
+fn poll_thread(
+    target_window: HWND,
+    get_state: XInputGetState,
+    stop: Arc<AtomicBool>,
+    sender: Sender<(usize, Option<XINPUT_GAMEPAD>)>,
+    rumbles: Rumbles,
+) {
+    let mut packets: [Option<DWORD>; SLOT_COUNT] = [None; SLOT_COUNT];
+    let mut last_scan: Option<Instant> = None;
+    while !stop.load(Ordering::Relaxed) {
+        let scan = last_scan.map_or(true, |last_scan| last_scan.elapsed() >= SCAN_INTERVAL);
+        if scan {
+            last_scan = Some(Instant::now());
+        }
+        let mut changed = false;
+        for (slot, packet) in packets.iter_mut().enumerate() {
+            if packet.is_none() && !scan {
+                continue;
+            }
+            let mut state = XINPUT_STATE::default();
+            let result = unsafe { get_state(slot as DWORD, &mut state) };
+            let update = match (result, *packet) {
+                (ERROR_SUCCESS, Some(previous)) if previous == state.dwPacketNumber => continue,
+                (ERROR_SUCCESS, _) => {
+                    *packet = Some(state.dwPacketNumber);
+                    Some(state.Gamepad)
+                }
+                (_, Some(_)) => {
+                    *packet = None;
+                    None
+                }
+                (_, None) => continue,
+            };
+            if sender.send((slot, update)).is_err() {
+                return;
+            }
+            changed = true;
+        }
+        if changed {
+            unsafe { winuser::PostMessageW(target_window, *GAMEPADS_CHANGED_MSG_ID, 0, 0) };
+        }
+        stop_rumbles(&rumbles, Some(Instant::now()));
+        thread::sleep(POLL_INTERVAL);
+    }
+    // The controllers don't stop rumbling on their own once the gamepads are disabled.
+    stop_rumbles(&rumbles, None);
+}
+
+/// Stops the rumbles which end before `now`, or all of them.
+fn stop_rumbles(rumbles: &Rumbles, now: Option<Instant>) {
+    let set_state = match *XINPUT_SET_STATE {
+        Some(set_state) => set_state,
+        None => return,
+    };
+    let mut rumbles = rumbles.lock().unwrap();
+    for (slot, end) in rumbles.iter_mut().enumerate() {
+        let ended = match (*end, now) {
+            (Some(end), Some(now)) => end <= now,
+            (end, None) => end.is_some(),
+            (None, _) => false,
+        };
+        if ended {
+            *end = None;
+            let mut vibration = XINPUT_VIBRATION::default();
+            unsafe { set_state(slot as DWORD, &mut vibration) };
+        }
+    }
+}
+
+fn push_changes(
+    id: GamepadId,
+    previous: &XINPUT_GAMEPAD,
+    state: &XINPUT_GAMEPAD,
+    events: &mut Vec<(GamepadId, GamepadEvent)>,
+) {
+    let mut button = |button, pressed: bool| {
+        let state = if pressed {
+            ElementState::Pressed
+        } else {
+            ElementState::Released
+        };
+        events.push((id, GamepadEvent::Button { button, state }));
+    };
+    for &(mask, gamepad_button) in BUTTONS.iter() {
+        let pressed = state.wButtons & mask != 0;
+        if pressed != (previous.wButtons & mask != 0) {
+            button(gamepad_button, pressed);
+        }
+    }
+    for &(previous, trigger, gamepad_button) in &[
+        (
+            previous.bLeftTrigger,
+            state.bLeftTrigger,
+            GamepadButton::LeftTrigger,
+        ),
+        (
+            previous.bRightTrigger,
+            state.bRightTrigger,
+            GamepadButton::RightTrigger,
+        ),
+    ] {
+        let pressed = trigger > TRIGGER_THRESHOLD;
+        if pressed != (previous > TRIGGER_THRESHOLD) {
+            button(gamepad_button, pressed);
+        }
+    }
+
+    let axes = [
+        (
+            GamepadAxis::LeftStickX,
+            stick(previous.sThumbLX),
+            stick(state.sThumbLX),
+        ),
+        (
+            GamepadAxis::LeftStickY,
+            stick(previous.sThumbLY),
+            stick(state.sThumbLY),
+        ),
+        (
+            GamepadAxis::RightStickX,
+            stick(previous.sThumbRX),
+            stick(state.sThumbRX),
+        ),
+        (
+            GamepadAxis::RightStickY,
+            stick(previous.sThumbRY),
+            stick(state.sThumbRY),
+        ),
+        (
+            GamepadAxis::LeftTrigger,
+            trigger(previous.bLeftTrigger),
+            trigger(state.bLeftTrigger),
+        ),
+        (
+            GamepadAxis::RightTrigger,
+            trigger(previous.bRightTrigger),
+            trigger(state.bRightTrigger),
+        ),
+    ];
+    for &(axis, previous, value) in axes.iter() {
+        if value != previous {
+            events.push((id, GamepadEvent::Axis { axis, value }));
+        }
+    }
+}
+
+/// The sticks of XInput already point up.
+fn stick(value: SHORT) -> f64 {
+    (value as f64 / SHORT::max_value() as f64).max(-1.)
+}
+
+fn trigger(value: BYTE) -> f64 {
+    value as f64 / BYTE::max_value() as f64
+}
@@ -0,0 +1,68 @@
+use std::{cell::RefCell, io};
+
+use winapi::{
+    shared::{minwindef::USHORT, windef::HWND},
+    um::winnt::HANDLE,
+};
+
+use crate::{
+    error::{ExternalError, NotSupportedError},
+    platform_impl::platform::raw_input::{self, RawDeviceInfo},
+};
+
+/// The HID devices whose raw reports are delivered. Their usages are registered for the raw
+/// input of the thread event target, and the reports of the other devices of the same usages
+/// are left out.
+pub(crate) struct HidReports {
+    target_window: HWND,
+    /// The enabled devices, with their usage page and usage.
+    devices: RefCell<Vec<(HANDLE, (USHORT, USHORT))>>,
+}
+
+impl HidReports {
+    pub fn new(target_window: HWND) -> Self {
+        Self {
+            target_window,
+            devices: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn set_enabled(&self, device: u32, enabled: bool) -> Result<(), ExternalError> {
+        let device = device as usize as HANDLE;
+        let mut devices = self.devices.borrow_mut();
+        let index = devices.iter().position(|&(enabled, _)| enabled == device);
+        match (enabled, index) {
+            (true, None) => {
+                let usage = match raw_input::get_raw_input_device_info(device) {
+                    Some(RawDeviceInfo::Hid(hid)) => (hid.usUsagePage, hid.usUsage),
+                    _ => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+                };
+                let registered = devices.iter().any(|&(_, registered)| registered == usage);
+                if !registered && !raw_input::register_hid_usage(self.target_window, usage, true) {
+                    return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+                }
+                devices.push((device, usage));
+            }
+            (false, Some(index)) => {
+                let (_, usage) = devices.remove(index);
+                if !devices.iter().any(|&(_, registered)| registered == usage) {
+                    raw_input::register_hid_usage(self.target_window, usage, false);
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Whether the input of the raw input has to be checked for the reports.
+    pub fn is_empty(&self) -> bool {
+        self.devices.borrow().is_empty()
+    }
+
+    pub fn is_enabled(&self, device: HANDLE) -> bool {
+        self.devices
+            .borrow()
+            .iter()
+            .any(|&(enabled, _)| enabled == device)
+    }
+}
@@ -0,0 +1,88 @@
+use std::{cell::RefCell, collections::HashSet, io, os::raw::c_int};
+
+use winapi::{
+    shared::{minwindef::UINT, windef::HWND},
+    um::winuser,
+};
+
+use crate::{
+    error::ExternalError,
+    event::ModifiersState,
+    hotkey::{Accelerator, HotkeyId},
+    platform_impl::platform::event::vkey_to_winit_vkey,
+};
+
+/// The identifiers above are reserved to the shared libraries.
+const MAX_HOTKEY_ID: usize = 0xBFFF;
+
+/// The global hotkeys registered by the user, which are the hotkeys of the thread event target,
+/// posting `WM_HOTKEY` with the `HotkeyId` as their identifier.
+pub(crate) struct Hotkeys {
+    target_window: HWND,
+    ids: RefCell<HashSet<HotkeyId>>,
+}
+
+impl Hotkeys {
+    pub fn new(target_window: HWND) -> Self {
+        Self {
+            target_window,
+            ids: RefCell::new(HashSet::new()),
+        }
+    }
+
+    pub fn register(&self, accelerator: Accelerator) -> Result<HotkeyId, ExternalError> {
+        let vkey = (0..=0xFF)
+            .find(|&vkey| vkey_to_winit_vkey(vkey) == Some(accelerator.key))
+            .ok_or_else(|| {
+                ExternalError::Os(os_error!(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "The keyboard layout has no such key",
+                )))
+            })?;
+
+        let mut ids = self.ids.borrow_mut();
+        let id = (0..=MAX_HOTKEY_ID)
+            .map(HotkeyId)
+            .find(|id| !ids.contains(id))
+            .ok_or_else(|| {
+                ExternalError::Os(os_error!(io::Error::new(
+                    io::ErrorKind::Other,
+                    "Too many hotkeys are registered",
+                )))
+            })?;
+
+        let mut modifiers = winuser::MOD_NOREPEAT as UINT;
+        for &(modifier, modifier_flag) in &[
+            (ModifiersState::SHIFT, winuser::MOD_SHIFT as UINT),
+            (ModifiersState::CTRL, winuser::MOD_CONTROL as UINT),
+            (ModifiersState::ALT, winuser::MOD_ALT as UINT),
+            (ModifiersState::LOGO, winuser::MOD_WIN as UINT),
+        ] {
+            if accelerator.modifiers.contains(modifier) {
+                modifiers |= modifier_flag;
+            }
+        }
+
+        // Fails with `ERROR_HOTKEY_ALREADY_REGISTERED` when the combination is taken.
+        let registered = unsafe {
+            winuser::RegisterHotKey(self.target_window, id.0 as c_int, modifiers, vkey as UINT)
+        };
+        if registered == 0 {
+            return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+        }
+        ids.insert(id);
+        Ok(id)
+    }
+
+    pub fn unregister(&self, id: HotkeyId) -> bool {
+        let removed = self.ids.borrow_mut().remove(&id);
+        if removed {
+            unsafe { winuser::UnregisterHotKey(self.target_window, id.0 as c_int) };
+        }
+        removed
+    }
+
+    pub fn contains(&self, id: HotkeyId) -> bool {
+        self.ids.borrow().contains(&id)
+    }
+}
@@ -27,6 +27,7 @@ pub(crate) struct EventLoopRunner<T: 'static> {
     wait_thread_id: DWORD,
 
     control_flow: Cell<ControlFlow>,
+    precise_wait: Cell<bool>,
     runner_state: Cell<RunnerState>,
     last_events_cleared: Cell<Instant>,
 
@@ -69,6 +70,7 @@ impl<T> EventLoopRunner<T> {
             wait_thread_id,
             runner_state: Cell::new(RunnerState::Uninitialized),
             control_flow: Cell::new(ControlFlow::Poll),
+            precise_wait: Cell::new(false),
             panic_error: Cell::new(None),
             last_events_cleared: Cell::new(Instant::now()),
             event_handler: Cell::new(None),
@@ -105,6 +107,10 @@ impl<T> EventLoopRunner<T> {
         control_flow.set(ControlFlow::Poll);
         event_handler.set(None);
     }
+
+    pub(crate) fn clear_event_handler(&self) {
+        self.event_handler.set(None);
+    }
 }
 
 /// State retrieval functions.
@@ -117,6 +123,10 @@ impl<T> EventLoopRunner<T> {
         self.wait_thread_id
     }
 
+    pub fn uninitialized(&self) -> bool {
+        self.runner_state.get() == RunnerState::Uninitialized
+    }
+
     pub fn redrawing(&self) -> bool {
         self.runner_state.get() == RunnerState::HandlingRedrawEvents
     }
@@ -132,6 +142,14 @@ impl<T> EventLoopRunner<T> {
         self.control_flow.get()
     }
 
+    pub fn precise_wait(&self) -> bool {
+        self.precise_wait.get()
+    }
+
+    pub fn set_precise_wait(&self, precise: bool) {
+        self.precise_wait.set(precise);
+    }
+
     pub fn handling_events(&self) -> bool {
         self.runner_state.get() != RunnerState::Idle
     }
@@ -241,10 +259,10 @@ impl<T> EventLoopRunner<T> {
             let mut event_handler = self.event_handler.take()
                 .expect("either event handler is re-entrant (likely), or no event handler is registered (very unlikely)");
 
-            if control_flow != ControlFlow::Exit {
-                event_handler(event, &mut control_flow);
+            if let ControlFlow::ExitWithCode(code) = control_flow {
+                event_handler(event, &mut ControlFlow::ExitWithCode(code));
             } else {
-                event_handler(event, &mut ControlFlow::Exit);
+                event_handler(event, &mut control_flow);
             }
 
             assert!(self.event_handler.replace(Some(event_handler)).is_none());
@@ -372,10 +390,12 @@ impl<T> EventLoopRunner<T> {
         let start_cause = match (init, self.control_flow()) {
             (true, _) => StartCause::Init,
             (false, ControlFlow::Poll) => StartCause::Poll,
-            (false, ControlFlow::Exit) | (false, ControlFlow::Wait) => StartCause::WaitCancelled {
-                requested_resume: None,
-                start: self.last_events_cleared.get(),
-            },
+            (false, ControlFlow::ExitWithCode(_)) | (false, ControlFlow::Wait) => {
+                StartCause::WaitCancelled {
+                    requested_resume: None,
+                    start: self.last_events_cleared.get(),
+                }
+            }
             (false, ControlFlow::WaitUntil(requested_resume)) => {
                 if Instant::now() < requested_resume {
                     StartCause::WaitCancelled {
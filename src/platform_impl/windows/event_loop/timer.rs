@@ -0,0 +1,88 @@
+use std::{cell::RefCell, collections::HashMap, io, time::Instant};
+
+use winapi::{
+    shared::{basetsd::UINT_PTR, windef::HWND},
+    um::winuser,
+};
+
+use crate::{
+    error::ExternalError,
+    event_loop::{Timer, TimerId},
+};
+
+use super::dur2timeout;
+
+/// The timers set by the user, which are the timers of the thread event target, posting
+/// `WM_TIMER` with the `TimerId` as their identifier.
+pub(crate) struct Timers {
+    target_window: HWND,
+    /// The deadline of the timers firing once, or `None` for the repeating ones.
+    timers: RefCell<HashMap<TimerId, Option<Instant>>>,
+}
+
+impl Timers {
+    pub fn new(target_window: HWND) -> Self {
+        Self {
+            target_window,
+            timers: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn set(&self, id: TimerId, timer: Timer) -> Result<(), ExternalError> {
+        let (elapse, deadline) = match timer {
+            Timer::At(deadline) => (
+                dur2timeout(deadline.saturating_duration_since(Instant::now())),
+                Some(deadline),
+            ),
+            Timer::Every(interval) => (dur2timeout(interval), None),
+        };
+        // Setting the timer again replaces it.
+        unsafe { set_timer(self.target_window, id, elapse) }
+            .map_err(|err| ExternalError::Os(os_error!(err)))?;
+        self.timers.borrow_mut().insert(id, deadline);
+        Ok(())
+    }
+
+    pub fn cancel(&self, id: TimerId) -> bool {
+        let removed = self.timers.borrow_mut().remove(&id).is_some();
+        if removed {
+            unsafe { winuser::KillTimer(self.target_window, id.0 as UINT_PTR) };
+        }
+        removed
+    }
+
+    /// Handles the `WM_TIMER` of the timer `id`, returning whether the timer fired.
+    pub fn handle_timer(&self, id: TimerId) -> bool {
+        let mut timers = self.timers.borrow_mut();
+        match timers.get(&id) {
+            Some(None) => true,
+            Some(Some(deadline)) => {
+                // The timers are limited to less than 25 days, so the farther deadlines take
+                // several rounds.
+                let now = Instant::now();
+                if now < *deadline {
+                    let elapse = dur2timeout(*deadline - now);
+                    if unsafe { set_timer(self.target_window, id, elapse) }.is_ok() {
+                        return false;
+                    }
+                }
+                timers.remove(&id);
+                unsafe { winuser::KillTimer(self.target_window, id.0 as UINT_PTR) };
+                true
+            }
+            None => {
+                unsafe { winuser::KillTimer(self.target_window, id.0 as UINT_PTR) };
+                false
+            }
+        }
+    }
+}
+
+unsafe fn set_timer(target_window: HWND, id: TimerId, elapse: u32) -> Result<(), io::Error> {
+    let elapse = elapse.min(winuser::USER_TIMER_MAXIMUM);
+    if winuser::SetTimer(target_window, id.0 as UINT_PTR, elapse, None) == 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+//! The pinches and the rotations of two touches on the touch screens, which are recognized from
+//! the touches since the windows registered for them don't get `WM_GESTURE`.
+
+use crate::{
+    dpi::PhysicalPosition,
+    event::{GesturePhase, TouchPhase},
+};
+
+#[derive(Default)]
+pub struct TouchGesture {
+    /// The touches down on the window, the two first ones making the gesture.
+    touches: Vec<(u64, PhysicalPosition<f64>)>,
+    /// The distance between the touches when the gesture started, then the distance and the angle
+    /// between them at its previous event.
+    span: Option<(f64, f64, f64)>,
+}
+
+impl TouchGesture {
+    /// Updates the gesture with the touch, giving the change of the scale and the
+    /// counterclockwise rotation in degrees when the touch is one of the gesture.
+    pub fn touch(
+        &mut self,
+        id: u64,
+        location: PhysicalPosition<f64>,
+        phase: TouchPhase,
+    ) -> Option<(f64, f32, GesturePhase)> {
+        let index = self.touches.iter().position(|&(touch, _)| touch == id);
+        match (phase, index) {
+            (TouchPhase::Started, None) => {
+                self.touches.push((id, location));
+                if self.touches.len() != 2 {
+                    return None;
+                }
+                let (distance, angle) = self.distance_and_angle()?;
+                self.span = Some((distance, distance, angle));
+                Some((0., 0., GesturePhase::Started))
+            }
+            (TouchPhase::Moved, Some(index)) if index < 2 => {
+                self.touches[index].1 = location;
+                let (start_distance, last_distance, last_angle) = self.span?;
+                let (distance, angle) = self.distance_and_angle()?;
+                self.span = Some((start_distance, distance, angle));
+                // The y axis points down, which turns the angles clockwise.
+                let mut rotation = last_angle - angle;
+                if rotation > 180. {
+                    rotation -= 360.;
+                } else if rotation < -180. {
+                    rotation += 360.;
+                }
+                let scale_delta = (distance - last_distance) / start_distance;
+                Some((scale_delta, rotation as f32, GesturePhase::Updated))
+            }
+            (TouchPhase::Ended, Some(index)) | (TouchPhase::Cancelled, Some(index)) => {
+                self.touches.remove(index);
+                if index >= 2 {
+                    return None;
+                }
+                // The gesture doesn't go on with the touches left.
+                self.span.take()?;
+                let phase = match phase {
+                    TouchPhase::Cancelled => GesturePhase::Cancelled,
+                    _ => GesturePhase::Ended,
+                };
+                Some((0., 0., phase))
+            }
+            _ => None,
+        }
+    }
+
+    fn distance_and_angle(&self) -> Option<(f64, f64)> {
+        let (a, b) = match &self.touches[..] {
+            [(_, a), (_, b), ..] => (a, b),
+            _ => return None,
+        };
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let distance = dx.hypot(dy);
+        if distance == 0. {
+            return None;
+        }
+        Some((distance, dy.atan2(dx).to_degrees()))
+    }
+}
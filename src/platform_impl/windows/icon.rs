@@ -3,15 +3,20 @@ use std::{fmt, io, iter::once, mem, os::windows::ffi::OsStrExt, path::Path, ptr,
 use winapi::{
     ctypes::{c_int, wchar_t},
     shared::{
-        minwindef::{BYTE, LPARAM, WORD, WPARAM},
-        windef::{HICON, HWND},
+        minwindef::{BYTE, DWORD, FALSE, LPARAM, TRUE, WORD, WPARAM},
+        ntdef::LONG,
+        windef::{HCURSOR, HICON, HWND, RECT},
     },
     um::libloaderapi,
-    um::winuser,
+    um::{wingdi, winuser},
 };
 
-use crate::dpi::PhysicalSize;
+use crate::dpi::{PhysicalPosition, PhysicalSize};
 use crate::icon::*;
+use crate::window::{CursorFrame, CursorIcon};
+
+/// The frames of the animated cursors have icons of their own, instead of raw bitmaps.
+const AF_ICON: u32 = 0x1;
 
 impl Pixel {
     fn to_bgra(&mut self) {
@@ -132,6 +137,98 @@ impl WinIcon {
         rgba_icon.into_windows_icon()
     }
 
+    /// Draws the count in white over a red disc, for the overlay icon of a taskbar button. The
+    /// counts above 99 are drawn as `99+`.
+    pub fn new_badge(count: u64) -> Result<Self, BadIcon> {
+        const SIZE: c_int = 16;
+
+        let text = if count > 99 {
+            "99+".to_owned()
+        } else {
+            count.to_string()
+        };
+        let text: Vec<wchar_t> = text.encode_utf16().collect();
+        let face: Vec<wchar_t> = "Segoe UI".encode_utf16().chain(once(0)).collect();
+        unsafe {
+            let screen = winuser::GetDC(ptr::null_mut());
+            let dc = wingdi::CreateCompatibleDC(screen);
+            let color = wingdi::CreateCompatibleBitmap(screen, SIZE, SIZE);
+            let mask = wingdi::CreateBitmap(SIZE, SIZE, 1, 1, ptr::null());
+            winuser::ReleaseDC(ptr::null_mut(), screen);
+            let pen = wingdi::SelectObject(dc, wingdi::GetStockObject(wingdi::NULL_PEN as c_int));
+
+            // The mask is black where the icon is opaque.
+            let bitmap = wingdi::SelectObject(dc, mask as _);
+            wingdi::PatBlt(dc, 0, 0, SIZE, SIZE, wingdi::WHITENESS);
+            let brush =
+                wingdi::SelectObject(dc, wingdi::GetStockObject(wingdi::BLACK_BRUSH as c_int));
+            wingdi::Ellipse(dc, 0, 0, SIZE + 1, SIZE + 1);
+
+            wingdi::SelectObject(dc, color as _);
+            wingdi::PatBlt(dc, 0, 0, SIZE, SIZE, wingdi::BLACKNESS);
+            let disc = wingdi::CreateSolidBrush(wingdi::RGB(0xd0, 0x20, 0x20));
+            wingdi::SelectObject(dc, disc as _);
+            wingdi::Ellipse(dc, 0, 0, SIZE + 1, SIZE + 1);
+
+            let font = wingdi::CreateFontW(
+                -11,
+                0,
+                0,
+                0,
+                wingdi::FW_BOLD,
+                FALSE as DWORD,
+                FALSE as DWORD,
+                FALSE as DWORD,
+                wingdi::DEFAULT_CHARSET,
+                wingdi::OUT_DEFAULT_PRECIS,
+                wingdi::CLIP_DEFAULT_PRECIS,
+                wingdi::ANTIALIASED_QUALITY,
+                wingdi::DEFAULT_PITCH,
+                face.as_ptr(),
+            );
+            let previous_font = wingdi::SelectObject(dc, font as _);
+            wingdi::SetBkMode(dc, wingdi::TRANSPARENT as c_int);
+            wingdi::SetTextColor(dc, wingdi::RGB(0xff, 0xff, 0xff));
+            let mut rect = RECT {
+                left: 0,
+                top: 0,
+                right: SIZE,
+                bottom: SIZE,
+            };
+            winuser::DrawTextW(
+                dc,
+                text.as_ptr(),
+                text.len() as c_int,
+                &mut rect,
+                winuser::DT_CENTER | winuser::DT_VCENTER | winuser::DT_SINGLELINE,
+            );
+
+            wingdi::SelectObject(dc, previous_font);
+            wingdi::SelectObject(dc, brush);
+            wingdi::SelectObject(dc, bitmap);
+            wingdi::SelectObject(dc, pen);
+            wingdi::DeleteObject(font as _);
+            wingdi::DeleteObject(disc as _);
+            wingdi::DeleteDC(dc);
+
+            let mut icon_info = winuser::ICONINFO {
+                fIcon: TRUE,
+                xHotspot: 0,
+                yHotspot: 0,
+                hbmMask: mask,
+                hbmColor: color,
+            };
+            let handle = winuser::CreateIconIndirect(&mut icon_info);
+            wingdi::DeleteObject(mask as _);
+            wingdi::DeleteObject(color as _);
+            if !handle.is_null() {
+                Ok(WinIcon::from_handle(handle))
+            } else {
+                Err(BadIcon::OsError(io::Error::last_os_error()))
+            }
+        }
+    }
+
     pub fn set_for_window(&self, hwnd: HWND, icon_type: IconType) {
         unsafe {
             winuser::SendMessageW(
@@ -150,6 +247,217 @@ impl WinIcon {
     }
 }
 
+/// A cursor created from an image, destroyed along with its last clone.
+#[derive(Clone)]
+pub struct WinCursor {
+    inner: Arc<RaiiIcon>,
+}
+
+unsafe impl Send for WinCursor {}
+unsafe impl Sync for WinCursor {}
+
+impl WinCursor {
+    pub fn as_raw_handle(&self) -> HCURSOR {
+        self.inner.handle
+    }
+
+    pub fn new(image: &RgbaIcon, hotspot: PhysicalPosition<u32>) -> Result<Self, BadIcon> {
+        unsafe {
+            let mut info: wingdi::BITMAPINFO = mem::zeroed();
+            info.bmiHeader.biSize = mem::size_of::<wingdi::BITMAPINFOHEADER>() as DWORD;
+            info.bmiHeader.biWidth = image.width as LONG;
+            // A negative height makes the bitmap top-down, like the image.
+            info.bmiHeader.biHeight = -(image.height as LONG);
+            info.bmiHeader.biPlanes = 1;
+            info.bmiHeader.biBitCount = 32;
+            info.bmiHeader.biCompression = wingdi::BI_RGB;
+
+            let mut bits = ptr::null_mut();
+            let color = wingdi::CreateDIBSection(
+                ptr::null_mut(),
+                &info,
+                wingdi::DIB_RGB_COLORS,
+                &mut bits,
+                ptr::null_mut(),
+                0,
+            );
+            if color.is_null() || bits.is_null() {
+                return Err(BadIcon::OsError(io::Error::last_os_error()));
+            }
+            // The colors of the cursors use straight alpha, unlike the drag images.
+            let pixels = std::slice::from_raw_parts_mut(bits as *mut u8, image.rgba.len());
+            for (pixel, rgba) in pixels.chunks_exact_mut(4).zip(image.rgba.chunks_exact(4)) {
+                pixel.copy_from_slice(&[rgba[2], rgba[1], rgba[0], rgba[3]]);
+            }
+
+            // The mask is ignored in favor of the alpha of the colors, but it's still required.
+            // Its rows are aligned to 16 bits.
+            let mask_bits = vec![0u8; (image.width as usize + 15) / 16 * 2 * image.height as usize];
+            let mask = wingdi::CreateBitmap(
+                image.width as c_int,
+                image.height as c_int,
+                1,
+                1,
+                mask_bits.as_ptr() as *const _,
+            );
+
+            let mut icon_info = winuser::ICONINFO {
+                fIcon: FALSE,
+                xHotspot: hotspot.x,
+                yHotspot: hotspot.y,
+                hbmMask: mask,
+                hbmColor: color,
+            };
+            let handle = winuser::CreateIconIndirect(&mut icon_info);
+            let error = io::Error::last_os_error();
+            // The cursor has copies of the bitmaps.
+            wingdi::DeleteObject(mask as _);
+            wingdi::DeleteObject(color as _);
+            if !handle.is_null() {
+                Ok(Self {
+                    inner: Arc::new(RaiiIcon { handle }),
+                })
+            } else {
+                Err(BadIcon::OsError(error))
+            }
+        }
+    }
+
+    /// Creates an animated cursor, by loading the frames as the `.ani` file they'd make.
+    pub fn new_animated(frames: &[CursorFrame]) -> Result<Self, BadIcon> {
+        let mut frame_list = b"fram".to_vec();
+        for frame in frames {
+            push_chunk(&mut frame_list, b"icon", &cursor_file(frame));
+        }
+
+        // The delays are in jiffies, sixtieths of a second.
+        let rates: Vec<u8> = frames
+            .iter()
+            .flat_map(|frame| {
+                let jiffies = (frame.delay.as_millis() * 60 + 500) / 1000;
+                (jiffies.max(1).min(u32::MAX as u128) as u32).to_le_bytes()
+            })
+            .collect();
+
+        let count = frames.len() as u32;
+        // The size, the frames and the steps, then the size and the format which come from the
+        // icons, the default rate and the flags.
+        let header: Vec<u8> = [36, count, count, 0, 0, 0, 0, 1, AF_ICON]
+            .iter()
+            .flat_map(|value: &u32| value.to_le_bytes())
+            .collect();
+
+        let mut acon = b"ACON".to_vec();
+        push_chunk(&mut acon, b"anih", &header);
+        push_chunk(&mut acon, b"rate", &rates);
+        push_chunk(&mut acon, b"LIST", &frame_list);
+        let mut riff = Vec::new();
+        push_chunk(&mut riff, b"RIFF", &acon);
+
+        // The resources are in the format of the version 3.
+        let handle = unsafe {
+            winuser::CreateIconFromResourceEx(
+                riff.as_mut_ptr(),
+                riff.len() as DWORD,
+                FALSE,
+                0x00030000,
+                0,
+                0,
+                winuser::LR_DEFAULTCOLOR,
+            )
+        };
+        if !handle.is_null() {
+            Ok(Self {
+                inner: Arc::new(RaiiIcon { handle }),
+            })
+        } else {
+            Err(BadIcon::OsError(io::Error::last_os_error()))
+        }
+    }
+}
+
+impl WinCursor {
+    /// Loads the system cursor of the icon at the size in pixels, rather than the size of the
+    /// system cursors.
+    pub fn from_system(cursor: CursorIcon, size: u32) -> Option<Self> {
+        unsafe {
+            let shared = winuser::LoadCursorW(ptr::null_mut(), cursor.to_windows_cursor());
+            if shared.is_null() {
+                return None;
+            }
+            // Copying from the resource picks its image closest to the size, instead of
+            // stretching the shared cursor.
+            let handle = winuser::CopyImage(
+                shared as _,
+                winuser::IMAGE_CURSOR,
+                size as c_int,
+                size as c_int,
+                winuser::LR_COPYFROMRESOURCE,
+            );
+            if handle.is_null() {
+                return None;
+            }
+            Some(Self {
+                inner: Arc::new(RaiiIcon {
+                    handle: handle as HCURSOR,
+                }),
+            })
+        }
+    }
+}
+
+/// Appends a RIFF chunk, padded to an even size.
+fn push_chunk(data: &mut Vec<u8>, id: &[u8; 4], content: &[u8]) {
+    data.extend_from_slice(id);
+    data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    data.extend_from_slice(content);
+    if content.len() % 2 == 1 {
+        data.push(0);
+    }
+}
+
+/// The `.cur` file of the frame, with its image as a bitmap of 32 bits per pixel.
+fn cursor_file(frame: &CursorFrame) -> Vec<u8> {
+    let image = &frame.image;
+    let (width, height) = (image.width as usize, image.height as usize);
+    // The rows of the mask are aligned to 32 bits.
+    let mask_stride = (width + 31) / 32 * 4;
+    let image_size = 40 + width * height * 4 + mask_stride * height;
+
+    let mut data = Vec::with_capacity(22 + image_size);
+    // The directory of the one cursor image in the file.
+    for value in [0u16, 2, 1].iter() {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    // The sizes of 256 pixels are 0.
+    data.extend_from_slice(&[width as u8, height as u8, 0, 0]);
+    data.extend_from_slice(&(frame.hotspot.x as u16).to_le_bytes());
+    data.extend_from_slice(&(frame.hotspot.y as u16).to_le_bytes());
+    data.extend_from_slice(&(image_size as u32).to_le_bytes());
+    data.extend_from_slice(&22u32.to_le_bytes());
+
+    // The height of the bitmap counts both the colors and the mask.
+    let mut header: wingdi::BITMAPINFOHEADER = unsafe { mem::zeroed() };
+    header.biSize = mem::size_of::<wingdi::BITMAPINFOHEADER>() as DWORD;
+    header.biWidth = width as LONG;
+    header.biHeight = (height * 2) as LONG;
+    header.biPlanes = 1;
+    header.biBitCount = 32;
+    header.biCompression = wingdi::BI_RGB;
+    data.extend_from_slice(unsafe {
+        std::slice::from_raw_parts(&header as *const _ as *const u8, mem::size_of_val(&header))
+    });
+
+    // The rows are bottom-up, and the colors use straight alpha, the mask being ignored.
+    for row in image.rgba.chunks_exact(width * 4).rev() {
+        for rgba in row.chunks_exact(4) {
+            data.extend_from_slice(&[rgba[2], rgba[1], rgba[0], rgba[3]]);
+        }
+    }
+    data.resize(data.len() + mask_stride * height, 0);
+    data
+}
+
 impl Drop for RaiiIcon {
     fn drop(&mut self) {
         unsafe { winuser::DestroyIcon(self.handle) };
@@ -0,0 +1,316 @@
+//! The input methods of IMM32, which take the area and the purpose of the text being edited and
+//! give the composition string with the attributes of its clauses.
+
+use std::{mem, ops::Range, ptr};
+
+use winapi::{
+    ctypes::c_int,
+    shared::{
+        minwindef::{BOOL, DWORD, FALSE, LPARAM, LPVOID, LRESULT, TRUE},
+        windef::{HWND, POINT, RECT},
+        winerror::HRESULT,
+    },
+    um::{
+        imm::{
+            ImmGetContext, ImmReleaseContext, ImmSetCompositionWindow, CFS_EXCLUDE, CFS_POINT,
+            COMPOSITIONFORM, HIMC,
+        },
+        processthreadsapi, winuser,
+    },
+};
+
+use crate::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ImePreedit, ImePreeditSpan, ImeUnderline},
+    window::ImePurpose,
+};
+
+pub const GCS_COMPSTR: DWORD = 0x0008;
+const GCS_COMPATTR: DWORD = 0x0010;
+const GCS_CURSORPOS: DWORD = 0x0080;
+
+const ATTR_TARGET_CONVERTED: u8 = 0x01;
+const ATTR_CONVERTED: u8 = 0x02;
+const ATTR_TARGET_NOTCONVERTED: u8 = 0x03;
+
+const IACE_DEFAULT: DWORD = 0x0010;
+
+const IS_DEFAULT: c_int = 0;
+const IS_URL: c_int = 1;
+const IS_EMAIL_SMTPEMAILADDRESS: c_int = 5;
+const IS_PERSONALNAME_FULLNAME: c_int = 7;
+const IS_DIGITS: c_int = 28;
+const IS_NUMBER: c_int = 29;
+const IS_PASSWORD: c_int = 31;
+const IS_TELEPHONE_FULLTELEPHONENUMBER: c_int = 32;
+const IS_NUMERIC_PIN: c_int = 64;
+
+pub const IMR_RECONVERTSTRING: DWORD = 0x0004;
+
+pub const IMN_CHANGECANDIDATE: DWORD = 0x0003;
+pub const IMN_CLOSECANDIDATE: DWORD = 0x0004;
+pub const IMN_OPENCANDIDATE: DWORD = 0x0005;
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct CANDIDATEFORM {
+    dwIndex: DWORD,
+    dwStyle: DWORD,
+    ptCurrentPos: POINT,
+    rcArea: RECT,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct RECONVERTSTRING {
+    dwSize: DWORD,
+    dwVersion: DWORD,
+    dwStrLen: DWORD,
+    dwStrOffset: DWORD,
+    dwCompStrLen: DWORD,
+    dwCompStrOffset: DWORD,
+    dwTargetStrLen: DWORD,
+    dwTargetStrOffset: DWORD,
+}
+
+#[link(name = "imm32")]
+extern "system" {
+    fn ImmGetCompositionStringW(himc: HIMC, index: DWORD, buf: LPVOID, buf_len: DWORD) -> i32;
+    fn ImmSetCandidateWindow(himc: HIMC, candidate: *mut CANDIDATEFORM) -> BOOL;
+    fn ImmGetDefaultIMEWnd(window: HWND) -> HWND;
+    fn ImmAssociateContextEx(window: HWND, himc: HIMC, flags: DWORD) -> BOOL;
+}
+
+type SetInputScope = unsafe extern "system" fn(window: HWND, input_scope: c_int) -> HRESULT;
+
+lazy_static! {
+    static ref SET_INPUT_SCOPE: Option<SetInputScope> = get_function!("msctf.dll", SetInputScope);
+}
+
+/// Sets the input scope of the window, which the input methods and the touch keyboard follow,
+/// and turns the input method off for the passwords.
+pub unsafe fn set_purpose(window: HWND, purpose: ImePurpose) {
+    let input_scope = match purpose {
+        ImePurpose::Normal | ImePurpose::Terminal => IS_DEFAULT,
+        ImePurpose::Password => IS_PASSWORD,
+        ImePurpose::Pin => IS_NUMERIC_PIN,
+        ImePurpose::Digits => IS_DIGITS,
+        ImePurpose::Number => IS_NUMBER,
+        ImePurpose::Phone => IS_TELEPHONE_FULLTELEPHONENUMBER,
+        ImePurpose::Email => IS_EMAIL_SMTPEMAILADDRESS,
+        ImePurpose::Url => IS_URL,
+        ImePurpose::Name => IS_PERSONALNAME_FULLNAME,
+    };
+    if let Some(set_input_scope) = *SET_INPUT_SCOPE {
+        set_input_scope(window, input_scope);
+    }
+
+    // Associating no input context turns the input method off, and the default one back on.
+    let flags = match purpose {
+        ImePurpose::Password | ImePurpose::Pin => 0,
+        _ => IACE_DEFAULT,
+    };
+    ImmAssociateContextEx(window, ptr::null_mut(), flags);
+}
+
+/// Shows the composition at the area, and the candidate window next to it without covering it.
+pub unsafe fn set_cursor_area(window: HWND, area: RECT) {
+    let himc = ImmGetContext(window);
+    if himc.is_null() {
+        return;
+    }
+    let position = POINT {
+        x: area.left,
+        y: area.top,
+    };
+    let mut composition_form = COMPOSITIONFORM {
+        dwStyle: CFS_POINT,
+        ptCurrentPos: position,
+        rcArea: mem::zeroed(),
+    };
+    ImmSetCompositionWindow(himc, &mut composition_form);
+    let mut candidate_form = CANDIDATEFORM {
+        dwIndex: 0,
+        dwStyle: CFS_EXCLUDE,
+        ptCurrentPos: position,
+        rcArea: area,
+    };
+    ImmSetCandidateWindow(himc, &mut candidate_form);
+    ImmReleaseContext(window, himc);
+}
+
+/// The area of the visible candidate window of the window, in client area coordinates.
+pub unsafe fn candidate_area(window: HWND) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    let ime_window = ImmGetDefaultIMEWnd(window);
+    if ime_window.is_null() {
+        return None;
+    }
+    // The windows of the input methods belong to the thread of the window they're for.
+    let mut search = (ime_window, ptr::null_mut());
+    winuser::EnumThreadWindows(
+        processthreadsapi::GetCurrentThreadId(),
+        Some(find_candidate_window),
+        &mut search as *mut (HWND, HWND) as LPARAM,
+    );
+    let candidate_window = search.1;
+    if candidate_window.is_null() {
+        return None;
+    }
+
+    let mut rect: RECT = mem::zeroed();
+    winuser::GetWindowRect(candidate_window, &mut rect);
+    let mut origin = POINT {
+        x: rect.left,
+        y: rect.top,
+    };
+    winuser::ScreenToClient(window, &mut origin);
+    Some((
+        PhysicalPosition::new(origin.x, origin.y),
+        PhysicalSize::new(
+            (rect.right - rect.left) as u32,
+            (rect.bottom - rect.top) as u32,
+        ),
+    ))
+}
+
+unsafe extern "system" fn find_candidate_window(window: HWND, lparam: LPARAM) -> BOOL {
+    let search = &mut *(lparam as *mut (HWND, HWND));
+    if winuser::IsWindowVisible(window) == 0 {
+        return TRUE;
+    }
+    // The candidate windows are owned by the IME window, which is owned by the window.
+    let mut owner = winuser::GetWindow(window, winuser::GW_OWNER);
+    while !owner.is_null() {
+        if owner == search.0 {
+            search.1 = window;
+            return FALSE;
+        }
+        owner = winuser::GetWindow(owner, winuser::GW_OWNER);
+    }
+    TRUE
+}
+
+/// The composition string of the input context of the window, or `None` when the window has no
+/// input context.
+pub unsafe fn composition_preedit(window: HWND) -> Option<ImePreedit> {
+    let himc = ImmGetContext(window);
+    if himc.is_null() {
+        return None;
+    }
+    let preedit = context_preedit(himc);
+    ImmReleaseContext(window, himc);
+    Some(preedit)
+}
+
+unsafe fn context_preedit(himc: HIMC) -> ImePreedit {
+    let text = composition_data::<u16>(himc, GCS_COMPSTR);
+    let attributes = composition_data::<u8>(himc, GCS_COMPATTR);
+    let cursor = ImmGetCompositionStringW(himc, GCS_CURSORPOS, ptr::null_mut(), 0);
+
+    // The attributes and the cursor count the UTF-16 code units.
+    let mut preedit = ImePreedit::default();
+    let mut utf16_to_byte = Vec::with_capacity(text.len() + 1);
+    for c in std::char::decode_utf16(text.iter().cloned()) {
+        let c = c.unwrap_or(std::char::REPLACEMENT_CHARACTER);
+        for _ in 0..c.len_utf16() {
+            utf16_to_byte.push(preedit.text.len());
+        }
+        preedit.text.push(c);
+    }
+    utf16_to_byte.push(preedit.text.len());
+    let byte = |index: usize| utf16_to_byte[index.min(utf16_to_byte.len() - 1)];
+
+    if cursor >= 0 {
+        let cursor = byte(cursor as usize);
+        preedit.cursor = Some(cursor..cursor);
+    }
+
+    let mut start = 0;
+    while start < attributes.len() {
+        let attribute = attributes[start];
+        let end = attributes[start..]
+            .iter()
+            .position(|&a| a != attribute)
+            .map_or(attributes.len(), |len| start + len);
+        let (underline, highlighted) = match attribute {
+            ATTR_TARGET_CONVERTED => (ImeUnderline::Thick, true),
+            ATTR_CONVERTED => (ImeUnderline::Single, false),
+            ATTR_TARGET_NOTCONVERTED => (ImeUnderline::Dotted, true),
+            // The input which isn't converted yet, as `ATTR_INPUT`.
+            _ => (ImeUnderline::Dotted, false),
+        };
+        preedit.spans.push(ImePreeditSpan {
+            range: byte(start)..byte(end),
+            underline,
+            highlighted,
+        });
+        start = end;
+    }
+    preedit
+}
+
+/// The data of the composition string at the index, in units of `T`.
+unsafe fn composition_data<T: Copy + Default>(himc: HIMC, index: DWORD) -> Vec<T> {
+    let size = ImmGetCompositionStringW(himc, index, ptr::null_mut(), 0);
+    if size <= 0 {
+        return Vec::new();
+    }
+    let mut data = vec![T::default(); size as usize / mem::size_of::<T>()];
+    let size = ImmGetCompositionStringW(himc, index, data.as_mut_ptr() as LPVOID, size as DWORD);
+    data.truncate(size.max(0) as usize / mem::size_of::<T>());
+    data
+}
+
+/// The text around the cursor, in UTF-16 code units.
+#[derive(Clone)]
+pub struct SurroundingText {
+    pub text: Vec<u16>,
+    pub selection: Range<usize>,
+}
+
+impl SurroundingText {
+    pub fn new(text: &str, cursor: usize, anchor: usize) -> SurroundingText {
+        let utf16_offset = |offset: usize| {
+            text.char_indices()
+                .take_while(|&(index, _)| index < offset)
+                .map(|(_, c)| c.len_utf16())
+                .sum::<usize>()
+        };
+        let (cursor, anchor) = (utf16_offset(cursor), utf16_offset(anchor));
+        SurroundingText {
+            text: text.encode_utf16().collect(),
+            selection: cursor.min(anchor)..cursor.max(anchor),
+        }
+    }
+
+    /// Answers `IMR_RECONVERTSTRING`, giving the size of the `RECONVERTSTRING` with the text
+    /// when `lparam` is null, and writing it there otherwise. The selection is the text to
+    /// reconvert.
+    pub unsafe fn reconvert_string(&self, lparam: LPARAM) -> LRESULT {
+        let header = mem::size_of::<RECONVERTSTRING>();
+        let size = header + self.text.len() * mem::size_of::<u16>();
+        if lparam == 0 {
+            return size as LRESULT;
+        }
+        let reconvert_string = lparam as *mut RECONVERTSTRING;
+        if ((*reconvert_string).dwSize as usize) < size {
+            return 0;
+        }
+        // The offsets of the composition and the target are in bytes from the start of the text.
+        let offset = (self.selection.start * mem::size_of::<u16>()) as DWORD;
+        let len = self.selection.len() as DWORD;
+        *reconvert_string = RECONVERTSTRING {
+            dwSize: size as DWORD,
+            dwVersion: 0,
+            dwStrLen: self.text.len() as DWORD,
+            dwStrOffset: header as DWORD,
+            dwCompStrLen: len,
+            dwCompStrOffset: offset,
+            dwTargetStrLen: len,
+            dwTargetStrOffset: offset,
+        };
+        let text = (reconvert_string as *mut u8).add(header) as *mut u16;
+        ptr::copy_nonoverlapping(self.text.as_ptr(), text, self.text.len());
+        size as LRESULT
+    }
+}
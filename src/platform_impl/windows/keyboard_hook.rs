@@ -0,0 +1,133 @@
+//! The inhibition of the system shortcuts, which is a low-level keyboard hook handing the keys of
+//! the shortcuts to the focused window instead of the shell.
+
+use std::{cell::RefCell, io, ptr};
+
+use winapi::{
+    ctypes::c_int,
+    shared::{
+        minwindef::{LPARAM, LRESULT, UINT, WPARAM},
+        windef::{HHOOK, HWND},
+    },
+    um::{libloaderapi, winuser},
+};
+
+/// The hook with the windows inhibiting the shortcuts, which belong to the thread of the event
+/// loop as it's the one the hook is called on.
+struct ShortcutsHook {
+    hook: HHOOK,
+    windows: Vec<HWND>,
+}
+
+thread_local! {
+    static SHORTCUTS_HOOK: RefCell<ShortcutsHook> = RefCell::new(ShortcutsHook {
+        hook: ptr::null_mut(),
+        windows: Vec::new(),
+    });
+}
+
+/// Sets whether the window inhibits the shortcuts, installing the hook along with the first
+/// window and removing it along with the last one.
+pub fn set_shortcuts_inhibited(window: HWND, inhibited: bool) -> Result<(), io::Error> {
+    SHORTCUTS_HOOK.with(|shortcuts_hook| {
+        let mut shortcuts_hook = shortcuts_hook.borrow_mut();
+        let position = shortcuts_hook.windows.iter().position(|&w| w == window);
+        match (inhibited, position) {
+            (true, None) => {
+                if shortcuts_hook.hook.is_null() {
+                    let hook = unsafe {
+                        winuser::SetWindowsHookExW(
+                            winuser::WH_KEYBOARD_LL,
+                            Some(hook_proc),
+                            libloaderapi::GetModuleHandleW(ptr::null()),
+                            0,
+                        )
+                    };
+                    if hook.is_null() {
+                        return Err(io::Error::last_os_error());
+                    }
+                    shortcuts_hook.hook = hook;
+                }
+                shortcuts_hook.windows.push(window);
+            }
+            (false, Some(position)) => {
+                shortcuts_hook.windows.remove(position);
+                if shortcuts_hook.windows.is_empty() {
+                    unsafe { winuser::UnhookWindowsHookEx(shortcuts_hook.hook) };
+                    shortcuts_hook.hook = ptr::null_mut();
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    })
+}
+
+unsafe extern "system" fn hook_proc(code: c_int, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == winuser::HC_ACTION {
+        let key = &*(lparam as *const winuser::KBDLLHOOKSTRUCT);
+        if is_shortcut_key(key) {
+            if let Some(window) = inhibiting_window() {
+                // The window gets the key as if the shell didn't know of the shortcut.
+                winuser::PostMessageW(
+                    window,
+                    wparam as UINT,
+                    key.vkCode as WPARAM,
+                    key_lparam(key),
+                );
+                return 1;
+            }
+        }
+    }
+    winuser::CallNextHookEx(ptr::null_mut(), code, wparam, lparam)
+}
+
+/// The focused window if it inhibits the shortcuts, and the application is in the foreground.
+unsafe fn inhibiting_window() -> Option<HWND> {
+    let focus = winuser::GetFocus();
+    if focus.is_null()
+        || winuser::GetAncestor(focus, winuser::GA_ROOT) != winuser::GetForegroundWindow()
+    {
+        return None;
+    }
+    let inhibited = SHORTCUTS_HOOK.with(|shortcuts_hook| {
+        shortcuts_hook
+            .try_borrow()
+            .map(|shortcuts_hook| shortcuts_hook.windows.contains(&focus))
+            .unwrap_or(false)
+    });
+    if inhibited {
+        Some(focus)
+    } else {
+        None
+    }
+}
+
+/// Whether the shell would take the key for one of its shortcuts.
+fn is_shortcut_key(key: &winuser::KBDLLHOOKSTRUCT) -> bool {
+    let alt = key.flags & winuser::LLKHF_ALTDOWN != 0;
+    let ctrl = unsafe { winuser::GetAsyncKeyState(winuser::VK_CONTROL) } < 0;
+    match key.vkCode as c_int {
+        winuser::VK_LWIN | winuser::VK_RWIN | winuser::VK_SNAPSHOT => true,
+        winuser::VK_TAB => alt,
+        winuser::VK_ESCAPE => alt || ctrl,
+        // The browser, volume, media and launch keys.
+        winuser::VK_BROWSER_BACK..=winuser::VK_LAUNCH_APP2 => true,
+        _ => false,
+    }
+}
+
+/// The `lParam` of the keyboard messages of the key.
+fn key_lparam(key: &winuser::KBDLLHOOKSTRUCT) -> LPARAM {
+    let mut lparam = 1 | (key.scanCode & 0xFF) << 16;
+    if key.flags & winuser::LLKHF_EXTENDED != 0 {
+        lparam |= 1 << 24;
+    }
+    if key.flags & winuser::LLKHF_ALTDOWN != 0 {
+        lparam |= 1 << 29;
+    }
+    if key.flags & winuser::LLKHF_UP != 0 {
+        lparam |= 1 << 30 | 1 << 31;
+    }
+    lparam as LPARAM
+}
@@ -0,0 +1,195 @@
+//! The introspection of the keyboard layouts, which are the input locales of Windows.
+
+use std::{
+    io, mem,
+    os::raw::{c_int, c_void},
+    ptr,
+    time::Duration,
+};
+
+use winapi::{
+    shared::minwindef::{DWORD, HKL, WORD},
+    um::{winnls, winnt, winuser},
+};
+
+use crate::{
+    event::{ModifiersState, ScanCode},
+    keymap::{KeyRepeat, KeyboardLayout, LockKeys},
+};
+
+/// The virtual keys of the lock keys.
+const LOCK_KEYS: [(LockKeys, c_int); 3] = [
+    (LockKeys::CAPS_LOCK, winuser::VK_CAPITAL),
+    (LockKeys::NUM_LOCK, winuser::VK_NUMLOCK),
+    (LockKeys::SCROLL_LOCK, winuser::VK_SCROLL),
+];
+
+/// Tells `ToUnicodeEx` to keep the state of the keyboard, such as the pending dead keys, since
+/// Windows 10 version 1607.
+const TO_UNICODE_KEEP_STATE: u32 = 1 << 2;
+
+pub fn keyboard_layouts() -> Vec<KeyboardLayout> {
+    unsafe {
+        let count = winuser::GetKeyboardLayoutList(0, ptr::null_mut());
+        let mut hkls: Vec<HKL> = vec![ptr::null_mut(); count.max(0) as usize];
+        let count = winuser::GetKeyboardLayoutList(hkls.len() as c_int, hkls.as_mut_ptr());
+        hkls.truncate(count.max(0) as usize);
+        hkls.into_iter().map(layout).collect()
+    }
+}
+
+/// The layout of the calling thread.
+pub fn current_keyboard_layout() -> KeyboardLayout {
+    layout(unsafe { winuser::GetKeyboardLayout(0) })
+}
+
+/// The characters the key at `scancode` produces with the layout of the calling thread and
+/// `modifiers`.
+pub fn scancode_to_text(scancode: ScanCode, modifiers: ModifiersState) -> Option<String> {
+    unsafe {
+        let hkl = winuser::GetKeyboardLayout(0);
+        let vkey = winuser::MapVirtualKeyExW(scancode, winuser::MAPVK_VSC_TO_VK_EX, hkl);
+        if vkey == 0 {
+            return None;
+        }
+
+        let mut keyboard_state = [0u8; 256];
+        for &(modifier, vkeys) in &[
+            (
+                ModifiersState::SHIFT,
+                &[winuser::VK_SHIFT, winuser::VK_LSHIFT][..],
+            ),
+            (
+                ModifiersState::CTRL,
+                &[winuser::VK_CONTROL, winuser::VK_LCONTROL],
+            ),
+            (ModifiersState::ALT, &[winuser::VK_MENU, winuser::VK_LMENU]),
+            (ModifiersState::LOGO, &[winuser::VK_LWIN]),
+        ] {
+            if modifiers.contains(modifier) {
+                for &vkey in vkeys {
+                    keyboard_state[vkey as usize] = 0x80;
+                }
+            }
+        }
+
+        let mut text = [0u16; 8];
+        let len = winuser::ToUnicodeEx(
+            vkey,
+            scancode,
+            keyboard_state.as_ptr(),
+            text.as_mut_ptr(),
+            text.len() as c_int,
+            TO_UNICODE_KEEP_STATE,
+            hkl,
+        );
+        // The dead keys return -1 along with their spacing character.
+        let len = match len {
+            -1 => 1,
+            len if len > 0 => len as usize,
+            _ => return None,
+        };
+        Some(String::from_utf16_lossy(&text[..len.min(text.len())]))
+    }
+}
+
+/// The lock keys which are on, as of the latest keyboard message the calling thread received.
+pub fn lock_keys() -> LockKeys {
+    let mut lock_keys = LockKeys::empty();
+    for &(lock_key, vkey) in &LOCK_KEYS {
+        lock_keys.set(lock_key, is_toggled(vkey));
+    }
+    lock_keys
+}
+
+fn is_toggled(vkey: c_int) -> bool {
+    // The low-order bit is the toggle state of the key.
+    unsafe { winuser::GetKeyState(vkey) & 1 != 0 }
+}
+
+/// Turns the `lock_keys` on or off by synthesizing the presses of the ones which aren't already.
+pub fn set_lock_keys(lock_keys: LockKeys, on: bool) -> Result<(), io::Error> {
+    let mut inputs = Vec::new();
+    for &(lock_key, vkey) in &LOCK_KEYS {
+        if !lock_keys.contains(lock_key) || is_toggled(vkey) == on {
+            continue;
+        }
+        // Num Lock is an extended key, as Pause shares its scancode.
+        let extended = if vkey == winuser::VK_NUMLOCK {
+            winuser::KEYEVENTF_EXTENDEDKEY
+        } else {
+            0
+        };
+        for &flags in &[extended, extended | winuser::KEYEVENTF_KEYUP] {
+            let mut input: winuser::INPUT = unsafe { mem::zeroed() };
+            input.type_ = winuser::INPUT_KEYBOARD;
+            let ki = unsafe { input.u.ki_mut() };
+            ki.wVk = vkey as WORD;
+            ki.dwFlags = flags;
+            inputs.push(input);
+        }
+    }
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    let sent = unsafe {
+        winuser::SendInput(
+            inputs.len() as _,
+            inputs.as_mut_ptr(),
+            mem::size_of::<winuser::INPUT>() as _,
+        )
+    };
+    if sent as usize != inputs.len() {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// The repeat settings of the control panel, where the delay goes from 0 (250 ms) to 3 (1 s), and
+/// the speed from 0 (about 2.5 repeats per second) to 31 (about 30 repeats per second).
+pub fn key_repeat() -> Result<KeyRepeat, io::Error> {
+    let (mut delay, mut speed): (c_int, DWORD) = (0, 0);
+    unsafe {
+        if winuser::SystemParametersInfoW(
+            winuser::SPI_GETKEYBOARDDELAY,
+            0,
+            &mut delay as *mut _ as *mut c_void,
+            0,
+        ) == 0
+            || winuser::SystemParametersInfoW(
+                winuser::SPI_GETKEYBOARDSPEED,
+                0,
+                &mut speed as *mut _ as *mut c_void,
+                0,
+            ) == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    let rate = 2.5 + speed.min(31) as f64 * (30.0 - 2.5) / 31.0;
+    Ok(KeyRepeat {
+        delay: Duration::from_millis(250 * (delay.max(0).min(3) as u64 + 1)),
+        interval: Duration::from_millis((1000.0 / rate).round() as u64),
+    })
+}
+
+pub fn layout(hkl: HKL) -> KeyboardLayout {
+    let id = format!("{:08X}", hkl as usize as u32);
+    let language = (hkl as usize & 0xffff) as winnt::LANGID;
+    let mut name = [0u16; 128];
+    let len = unsafe {
+        winnls::GetLocaleInfoW(
+            winnt::MAKELCID(language, winnt::SORT_DEFAULT),
+            winnls::LOCALE_SLANGUAGE,
+            name.as_mut_ptr(),
+            name.len() as c_int,
+        )
+    };
+    let name = if len > 1 {
+        // The length includes the terminating null character.
+        String::from_utf16_lossy(&name[..len as usize - 1])
+    } else {
+        id.clone()
+    };
+    KeyboardLayout { id, name }
+}
@@ -3,13 +3,15 @@
 use winapi::{self, shared::windef::HMENU, shared::windef::HWND};
 
 pub use self::{
-    event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget},
+    child_surface::ChildSurface,
+    event_loop::{EventLoop, EventLoopProxy, EventLoopWakeup, EventLoopWindowTarget},
     icon::WinIcon,
     monitor::{MonitorHandle, VideoMode},
+    scancode::{key_to_scancode, scancode_to_key},
     window::Window,
 };
 
-pub use self::icon::WinIcon as PlatformIcon;
+pub use self::icon::{WinCursor as CustomCursor, WinIcon as PlatformIcon};
 
 use crate::event::DeviceId as RootDeviceId;
 use crate::icon::Icon;
@@ -97,13 +99,26 @@ impl WindowId {
 
 #[macro_use]
 mod util;
+mod backdrop;
+mod child_surface;
+mod clipboard;
 mod dark_mode;
+mod direct_manipulation;
 mod dpi;
+mod drag_source;
 mod drop_handler;
 mod event;
 mod event_loop;
+mod gesture;
 mod icon;
+mod ime;
+mod keyboard_hook;
+mod keymap;
 mod monitor;
 mod raw_input;
+mod scancode;
+mod tsf;
+mod virtual_desktop;
+mod vsync;
 mod window;
 mod window_state;
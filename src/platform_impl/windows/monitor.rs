@@ -1,24 +1,40 @@
 use winapi::{
     shared::{
-        minwindef::{BOOL, DWORD, LPARAM, TRUE, WORD},
-        windef::{HDC, HMONITOR, HWND, LPRECT, POINT},
+        dxgi::{self, IDXGIAdapter1, IDXGIFactory1, IDXGIOutput},
+        dxgi1_5::{IDXGIFactory5, DXGI_FEATURE_PRESENT_ALLOW_TEARING},
+        dxgi1_6::{IDXGIOutput6, DXGI_OUTPUT_DESC1},
+        dxgitype::DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020,
+        minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE, UINT, WORD},
+        windef::{HDC, HMONITOR, HWND, LPRECT, POINT, RECT},
+        winerror::{ERROR_SUCCESS, S_OK},
     },
-    um::{wingdi, winuser},
+    um::{dwmapi, unknwnbase::IUnknown, wingdi, winreg, winuser},
+    Interface,
 };
 
 use std::{
     collections::{BTreeSet, VecDeque},
-    io, mem, ptr,
+    ffi::OsStr,
+    io, mem,
+    os::windows::ffi::OsStrExt,
+    ptr,
 };
 
 use super::util;
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
-    monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
-    platform_impl::platform::{
-        dpi::{dpi_to_scale_factor, get_monitor_dpi},
-        window::Window,
+    monitor::{
+        union_rect, ColorCapabilities, ColorPrimaries, MonitorHandle as RootMonitorHandle,
+        MonitorIdentity, TransferFunction, VideoMode as RootVideoMode,
     },
+    platform_impl::{
+        edid::Edid,
+        platform::{
+            dpi::{dpi_to_scale_factor, get_monitor_dpi},
+            window::Window,
+        },
+    },
+    window::EdgeFlags,
 };
 
 #[derive(Clone)]
@@ -126,6 +142,175 @@ pub fn current_monitor(hwnd: HWND) -> MonitorHandle {
     MonitorHandle::new(hmonitor)
 }
 
+/// The color capabilities of all the monitors, enumerating the outputs of the DXGI adapters once.
+pub(crate) fn available_color_capabilities() -> Vec<(MonitorHandle, ColorCapabilities)> {
+    let mut capabilities = Vec::new();
+    unsafe {
+        for_each_output_desc(|desc| {
+            capabilities.push((MonitorHandle::new(desc.Monitor), color_capabilities(desc)));
+        });
+    }
+    capabilities
+}
+
+/// Calls `f` with the description of each output of each adapter, which is only there for
+/// the outputs supporting `IDXGIOutput6`, from Windows 10 1703 on.
+unsafe fn for_each_output_desc<F: FnMut(&DXGI_OUTPUT_DESC1)>(mut f: F) {
+    let mut factory: *mut IDXGIFactory1 = ptr::null_mut();
+    if dxgi::CreateDXGIFactory1(&IDXGIFactory1::uuidof(), &mut factory as *mut _ as *mut _) != S_OK
+    {
+        return;
+    }
+
+    let mut adapter_index = 0;
+    loop {
+        let mut adapter: *mut IDXGIAdapter1 = ptr::null_mut();
+        if (*factory).EnumAdapters1(adapter_index, &mut adapter) != S_OK {
+            break;
+        }
+        adapter_index += 1;
+
+        let mut output_index = 0;
+        loop {
+            let mut output: *mut IDXGIOutput = ptr::null_mut();
+            if (*adapter).EnumOutputs(output_index, &mut output) != S_OK {
+                break;
+            }
+            output_index += 1;
+
+            let mut output6: *mut IDXGIOutput6 = ptr::null_mut();
+            let status = (*(output as *mut IUnknown))
+                .QueryInterface(&IDXGIOutput6::uuidof(), &mut output6 as *mut _ as *mut _);
+            if status == S_OK {
+                let mut desc: DXGI_OUTPUT_DESC1 = mem::zeroed();
+                if (*output6).GetDesc1(&mut desc) == S_OK {
+                    f(&desc);
+                }
+                (*(output6 as *mut IUnknown)).Release();
+            }
+            (*(output as *mut IUnknown)).Release();
+        }
+        (*(adapter as *mut IUnknown)).Release();
+    }
+    (*(factory as *mut IUnknown)).Release();
+}
+
+/// Whether DXGI allows presenting with tearing, from Windows 10 1607 on.
+pub(crate) fn allows_tearing() -> bool {
+    unsafe {
+        let mut factory: *mut IDXGIFactory5 = ptr::null_mut();
+        let status =
+            dxgi::CreateDXGIFactory1(&IDXGIFactory5::uuidof(), &mut factory as *mut _ as *mut _);
+        if status != S_OK {
+            return false;
+        }
+
+        let mut allow_tearing: BOOL = FALSE;
+        let status = (*factory).CheckFeatureSupport(
+            DXGI_FEATURE_PRESENT_ALLOW_TEARING,
+            &mut allow_tearing as *mut BOOL as *mut _,
+            mem::size_of::<BOOL>() as UINT,
+        );
+        (*(factory as *mut IUnknown)).Release();
+        status == S_OK && allow_tearing != FALSE
+    }
+}
+
+fn color_capabilities(desc: &DXGI_OUTPUT_DESC1) -> ColorCapabilities {
+    // The HDR of the advanced color is the only color space with the PQ.
+    let hdr = desc.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020;
+    let point = |primary: [f32; 2]| (primary[0], primary[1]);
+    let primaries = ColorPrimaries::covered_by(
+        point(desc.RedPrimary),
+        point(desc.GreenPrimary),
+        point(desc.BluePrimary),
+    );
+    // The compositor takes the scRGB of the swap chains besides their PQ.
+    let transfer_functions = if hdr {
+        vec![
+            TransferFunction::Srgb,
+            TransferFunction::ExtendedLinear,
+            TransferFunction::Pq,
+        ]
+    } else {
+        vec![TransferFunction::Srgb]
+    };
+
+    ColorCapabilities {
+        hdr,
+        bit_depth: Some(desc.BitsPerColor as u8).filter(|&depth| depth != 0),
+        max_luminance: Some(desc.MaxLuminance).filter(|&luminance| luminance > 0.0),
+        headroom: None,
+        primaries,
+        transfer_functions,
+    }
+}
+
+/// Reads the EDID of the monitor from the parameters of its device in the registry.
+fn monitor_edid(hmonitor: HMONITOR) -> Option<Vec<u8>> {
+    let monitor_info = get_monitor_info(hmonitor).ok()?;
+    unsafe {
+        let mut device: wingdi::DISPLAY_DEVICEW = mem::zeroed();
+        device.cb = mem::size_of::<wingdi::DISPLAY_DEVICEW>() as DWORD;
+        let status = winuser::EnumDisplayDevicesW(
+            monitor_info.szDevice.as_ptr(),
+            0,
+            &mut device,
+            winuser::EDD_GET_DEVICE_INTERFACE_NAME,
+        );
+        if status == 0 {
+            return None;
+        }
+
+        // The interface is like `\\?\DISPLAY#DEL4093#5&1a2b3c&0&UID4353#{...}`, naming the
+        // hardware and the instance of the device.
+        let interface = util::wchar_to_string(&device.DeviceID);
+        let mut parts = interface.split('#').skip(1);
+        let (hardware_id, instance_id) = (parts.next()?, parts.next()?);
+        let key = widestring(&format!(
+            "SYSTEM\\CurrentControlSet\\Enum\\DISPLAY\\{}\\{}\\Device Parameters",
+            hardware_id, instance_id
+        ));
+        let value = widestring("EDID");
+
+        let mut len: DWORD = 0;
+        let status = winreg::RegGetValueW(
+            winreg::HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            value.as_ptr(),
+            winreg::RRF_RT_REG_BINARY,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut len,
+        );
+        if status != ERROR_SUCCESS as _ || len == 0 {
+            return None;
+        }
+        let mut edid = vec![0u8; len as usize];
+        let status = winreg::RegGetValueW(
+            winreg::HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            value.as_ptr(),
+            winreg::RRF_RT_REG_BINARY,
+            ptr::null_mut(),
+            edid.as_mut_ptr() as *mut _,
+            &mut len,
+        );
+        if status != ERROR_SUCCESS as _ {
+            return None;
+        }
+        edid.truncate(len as usize);
+        Some(edid)
+    }
+}
+
+fn widestring(src: &str) -> Vec<u16> {
+    OsStr::new(src)
+        .encode_wide()
+        .chain(Some(0).into_iter())
+        .collect()
+}
+
 impl Window {
     pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
         available_monitors()
@@ -137,6 +322,82 @@ impl Window {
     }
 }
 
+/// The largest inner size of the window within the work area of its monitor.
+/// The rectangle covered by the monitors of `Fullscreen::Span`, all of them when there's none.
+pub(crate) fn span_rect(
+    monitors: &[RootMonitorHandle],
+) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    if monitors.is_empty() {
+        let monitors = available_monitors()
+            .into_iter()
+            .map(|inner| RootMonitorHandle { inner });
+        union_rect(monitors)
+    } else {
+        union_rect(monitors.iter().cloned())
+    }
+}
+
+pub(crate) fn recommended_bounds(hwnd: HWND) -> Option<PhysicalSize<u32>> {
+    let hmonitor = unsafe { winuser::MonitorFromWindow(hwnd, winuser::MONITOR_DEFAULTTONEAREST) };
+    let work_area = get_monitor_info(hmonitor).ok()?.rcWork;
+    let frame = util::adjust_size(hwnd, PhysicalSize::new(0, 0));
+    let width = (work_area.right - work_area.left) as u32;
+    let height = (work_area.bottom - work_area.top) as u32;
+    Some(PhysicalSize::new(
+        width.saturating_sub(frame.width),
+        height.saturating_sub(frame.height),
+    ))
+}
+
+/// The edges of the work area of its monitor the window is snapped against. The snapped windows
+/// are neither maximized nor minimized, though they left their normal placement.
+pub(crate) fn snapped_edges(hwnd: HWND) -> EdgeFlags {
+    let mut edges = EdgeFlags::empty();
+    let mut rect: RECT = unsafe { mem::zeroed() };
+    unsafe {
+        if winuser::IsZoomed(hwnd) != 0 || winuser::IsIconic(hwnd) != 0 {
+            return edges;
+        }
+
+        let mut placement: winuser::WINDOWPLACEMENT = mem::zeroed();
+        placement.length = mem::size_of::<winuser::WINDOWPLACEMENT>() as _;
+        if winuser::GetWindowPlacement(hwnd, &mut placement) == 0
+            || winuser::GetWindowRect(hwnd, &mut rect) == 0
+        {
+            return edges;
+        }
+        // The normal placement is in the coordinates of the work area, only its size is compared.
+        let normal = placement.rcNormalPosition;
+        if normal.right - normal.left == rect.right - rect.left
+            && normal.bottom - normal.top == rect.bottom - rect.top
+        {
+            return edges;
+        }
+
+        // The visible frame leaves out the invisible resize borders.
+        let status = dwmapi::DwmGetWindowAttribute(
+            hwnd,
+            dwmapi::DWMWA_EXTENDED_FRAME_BOUNDS,
+            &mut rect as *mut RECT as *mut _,
+            mem::size_of::<RECT>() as DWORD,
+        );
+        if status != S_OK {
+            winuser::GetWindowRect(hwnd, &mut rect);
+        }
+    }
+
+    let hmonitor = unsafe { winuser::MonitorFromWindow(hwnd, winuser::MONITOR_DEFAULTTONEAREST) };
+    let work_area = match get_monitor_info(hmonitor) {
+        Ok(monitor_info) => monitor_info.rcWork,
+        Err(_) => return edges,
+    };
+    edges.set(EdgeFlags::TOP, rect.top == work_area.top);
+    edges.set(EdgeFlags::BOTTOM, rect.bottom == work_area.bottom);
+    edges.set(EdgeFlags::LEFT, rect.left == work_area.left);
+    edges.set(EdgeFlags::RIGHT, rect.right == work_area.right);
+    edges
+}
+
 pub(crate) fn get_monitor_info(hmonitor: HMONITOR) -> Result<winuser::MONITORINFOEXW, io::Error> {
     let mut monitor_info: winuser::MONITORINFOEXW = unsafe { mem::zeroed() };
     monitor_info.cbSize = mem::size_of::<winuser::MONITORINFOEXW>() as DWORD;
@@ -197,6 +458,64 @@ impl MonitorHandle {
         dpi_to_scale_factor(get_monitor_dpi(self.0).unwrap_or(96))
     }
 
+    pub fn color_capabilities(&self) -> ColorCapabilities {
+        let mut capabilities = ColorCapabilities::default();
+        unsafe {
+            for_each_output_desc(|desc| {
+                if desc.Monitor == self.0 {
+                    capabilities = color_capabilities(desc);
+                }
+            });
+        }
+        capabilities
+    }
+
+    #[inline]
+    pub fn supports_variable_refresh(&self) -> bool {
+        allows_tearing()
+    }
+
+    pub fn identity(&self) -> Option<MonitorIdentity> {
+        let edid = monitor_edid(self.0)?;
+        Edid::parse(&edid).map(|edid| edid.identity())
+    }
+
+    #[inline]
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        let work_area = get_monitor_info(self.0).unwrap().rcWork;
+        (
+            PhysicalPosition::new(work_area.left, work_area.top),
+            PhysicalSize::new(
+                (work_area.right - work_area.left) as u32,
+                (work_area.bottom - work_area.top) as u32,
+            ),
+        )
+    }
+
+    /// The refresh rate of the current mode, in millihertz.
+    pub(crate) fn refresh_rate_millihertz(&self) -> Option<u32> {
+        unsafe {
+            let monitor_info = get_monitor_info(self.0).ok()?;
+            let mut mode: wingdi::DEVMODEW = mem::zeroed();
+            mode.dmSize = mem::size_of_val(&mode) as WORD;
+            let device_name = monitor_info.szDevice.as_ptr();
+            if winuser::EnumDisplaySettingsExW(
+                device_name,
+                winuser::ENUM_CURRENT_SETTINGS,
+                &mut mode,
+                0,
+            ) == 0
+            {
+                return None;
+            }
+            // The frequencies of 0 and 1 stand for the default of the hardware.
+            if mode.dmDisplayFrequency <= 1 {
+                return None;
+            }
+            Some(mode.dmDisplayFrequency * 1000)
+        }
+    }
+
     #[inline]
     pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
         // EnumDisplaySettingsExW can return duplicate values (or some of the
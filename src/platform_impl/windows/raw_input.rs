@@ -1,29 +1,49 @@
 use std::{
+    ffi::OsStr,
     mem::{self, size_of},
-    ptr,
+    os::windows::ffi::OsStrExt,
+    ptr, slice,
 };
 
 use winapi::{
     ctypes::wchar_t,
     shared::{
-        hidusage::{HID_USAGE_GENERIC_KEYBOARD, HID_USAGE_GENERIC_MOUSE, HID_USAGE_PAGE_GENERIC},
-        minwindef::{TRUE, UINT, USHORT},
+        hidusage::{
+            HID_USAGE_DIGITIZER_PEN, HID_USAGE_DIGITIZER_TOUCH_PAD,
+            HID_USAGE_DIGITIZER_TOUCH_SCREEN, HID_USAGE_GENERIC_KEYBOARD, HID_USAGE_GENERIC_MOUSE,
+            HID_USAGE_PAGE_DIGITIZER, HID_USAGE_PAGE_GENERIC,
+        },
+        minwindef::{BOOLEAN, DWORD, FALSE, TRUE, UINT, ULONG, USHORT},
+        ntdef::PVOID,
         windef::HWND,
     },
     um::{
-        winnt::HANDLE,
+        fileapi::{CreateFileW, OPEN_EXISTING},
+        handleapi::{CloseHandle, INVALID_HANDLE_VALUE},
+        winnt::{FILE_SHARE_READ, FILE_SHARE_WRITE, HANDLE},
         winuser::{
             self, HRAWINPUT, RAWINPUT, RAWINPUTDEVICE, RAWINPUTDEVICELIST, RAWINPUTHEADER,
-            RIDEV_DEVNOTIFY, RIDEV_INPUTSINK, RIDI_DEVICEINFO, RIDI_DEVICENAME, RID_DEVICE_INFO,
-            RID_DEVICE_INFO_HID, RID_DEVICE_INFO_KEYBOARD, RID_DEVICE_INFO_MOUSE, RID_INPUT,
-            RIM_TYPEHID, RIM_TYPEKEYBOARD, RIM_TYPEMOUSE,
+            RIDEV_DEVNOTIFY, RIDEV_INPUTSINK, RIDEV_REMOVE, RIDI_DEVICEINFO, RIDI_DEVICENAME,
+            RID_DEVICE_INFO, RID_DEVICE_INFO_HID, RID_DEVICE_INFO_KEYBOARD, RID_DEVICE_INFO_MOUSE,
+            RID_HEADER, RID_INPUT, RIM_TYPEHID, RIM_TYPEKEYBOARD, RIM_TYPEMOUSE,
         },
     },
 };
 
-use crate::{event::ElementState, platform_impl::platform::util};
+use crate::{
+    event::{DeviceCapabilities, ElementState, InputDevice},
+    platform_impl::platform::{util, wrap_device_id},
+};
+
+#[allow(non_camel_case_types)]
+type HidD_GetProductString =
+    unsafe extern "system" fn(device: HANDLE, buffer: PVOID, buffer_length: ULONG) -> BOOLEAN;
+
+lazy_static! {
+    static ref HID_GET_PRODUCT_STRING: Option<HidD_GetProductString> =
+        get_function!("hid.dll", HidD_GetProductString);
+}
 
-#[allow(dead_code)]
 pub fn get_raw_input_device_list() -> Option<Vec<RAWINPUTDEVICELIST>> {
     let list_size = size_of::<RAWINPUTDEVICELIST>() as UINT;
 
@@ -72,7 +92,6 @@ impl From<RID_DEVICE_INFO> for RawDeviceInfo {
     }
 }
 
-#[allow(dead_code)]
 pub fn get_raw_input_device_info(handle: HANDLE) -> Option<RawDeviceInfo> {
     let mut info: RID_DEVICE_INFO = unsafe { mem::zeroed() };
     let info_size = size_of::<RID_DEVICE_INFO>() as UINT;
@@ -130,6 +149,110 @@ pub fn get_raw_input_device_name(handle: HANDLE) -> Option<String> {
     Some(util::wchar_to_string(&name))
 }
 
+/// The devices of the raw input, whose handles are their identifiers.
+pub fn get_input_devices() -> Vec<InputDevice> {
+    let list = get_raw_input_device_list().unwrap_or_default();
+    list.iter()
+        .filter_map(|device| get_input_device(device.hDevice))
+        .collect()
+}
+
+/// The device of the raw input, the HID devices other than the digitizers having no capabilities.
+pub fn get_input_device(handle: HANDLE) -> Option<InputDevice> {
+    let (capabilities, ids, fallback_name) = match get_raw_input_device_info(handle)? {
+        RawDeviceInfo::Keyboard(_) => (DeviceCapabilities::KEYBOARD, None, "Keyboard"),
+        RawDeviceInfo::Mouse(_) => (DeviceCapabilities::POINTER, None, "Mouse"),
+        RawDeviceInfo::Hid(hid) => {
+            let capabilities = match (hid.usUsagePage, hid.usUsage) {
+                (HID_USAGE_PAGE_DIGITIZER, HID_USAGE_DIGITIZER_PEN) => DeviceCapabilities::PEN,
+                (HID_USAGE_PAGE_DIGITIZER, HID_USAGE_DIGITIZER_TOUCH_SCREEN) => {
+                    DeviceCapabilities::TOUCH
+                }
+                (HID_USAGE_PAGE_DIGITIZER, HID_USAGE_DIGITIZER_TOUCH_PAD) => {
+                    DeviceCapabilities::POINTER
+                }
+                _ => DeviceCapabilities::empty(),
+            };
+            let ids = (hid.dwVendorId as u16, hid.dwProductId as u16);
+            (capabilities, Some(ids), "HID Device")
+        }
+    };
+    let path = get_raw_input_device_name(handle);
+    // The paths of the USB devices, such as `\\?\HID#VID_046D&PID_C08B&MI_00#...`, hold their
+    // identifiers.
+    let (vendor_id, product_id) = match ids {
+        Some((vendor_id, product_id)) => (Some(vendor_id), Some(product_id)),
+        None => {
+            let path = path.as_deref().unwrap_or_default();
+            (path_id(path, "VID_"), path_id(path, "PID_"))
+        }
+    };
+    let name = path
+        .as_deref()
+        .and_then(product_string)
+        .unwrap_or_else(|| fallback_name.to_owned());
+    Some(InputDevice {
+        id: wrap_device_id(handle as _),
+        name,
+        vendor_id,
+        product_id,
+        capabilities,
+        resolution: None,
+    })
+}
+
+fn path_id(path: &str, prefix: &str) -> Option<u16> {
+    let path = path.to_uppercase();
+    let start = path.find(prefix)? + prefix.len();
+    let id = path.get(start..start + 4)?;
+    u16::from_str_radix(id, 16).ok()
+}
+
+/// The name of the product the HID device gives, when the device can be opened.
+fn product_string(path: &str) -> Option<String> {
+    let get_product_string = (*HID_GET_PRODUCT_STRING)?;
+    let path: Vec<u16> = OsStr::new(path)
+        .encode_wide()
+        .chain(Some(0).into_iter())
+        .collect();
+    // The device is only opened to query it, which doesn't need any access.
+    let handle = unsafe {
+        CreateFileW(
+            path.as_ptr(),
+            0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            ptr::null_mut(),
+            OPEN_EXISTING,
+            0,
+            ptr::null_mut(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return None;
+    }
+    // The strings of HID are at most 126 characters long.
+    let mut buffer = [0 as wchar_t; 127];
+    let success = unsafe {
+        get_product_string(
+            handle,
+            buffer.as_mut_ptr() as _,
+            mem::size_of_val(&buffer) as ULONG,
+        )
+    };
+    unsafe { CloseHandle(handle) };
+    if success == FALSE as BOOLEAN {
+        return None;
+    }
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    let name = util::wchar_to_string(&buffer[..len]);
+    let name = name.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_owned())
+    }
+}
+
 pub fn register_raw_input_devices(devices: &[RAWINPUTDEVICE]) -> bool {
     let device_size = size_of::<RAWINPUTDEVICE>() as UINT;
 
@@ -140,6 +263,21 @@ pub fn register_raw_input_devices(devices: &[RAWINPUTDEVICE]) -> bool {
     success == TRUE
 }
 
+/// Registers the usage of the HID devices for the raw input of the window, or removes it.
+pub fn register_hid_usage(window_handle: HWND, usage: (USHORT, USHORT), register: bool) -> bool {
+    let (flags, target) = if register {
+        (RIDEV_INPUTSINK, window_handle)
+    } else {
+        (RIDEV_REMOVE, ptr::null_mut())
+    };
+    register_raw_input_devices(&[RAWINPUTDEVICE {
+        usUsagePage: usage.0,
+        usUsage: usage.1,
+        dwFlags: flags,
+        hwndTarget: target,
+    }])
+}
+
 pub fn register_all_mice_and_keyboards_for_raw_input(window_handle: HWND) -> bool {
     // RIDEV_DEVNOTIFY: receive hotplug events
     // RIDEV_INPUTSINK: receive events even if we're not in the foreground
@@ -163,6 +301,66 @@ pub fn register_all_mice_and_keyboards_for_raw_input(window_handle: HWND) -> boo
     register_raw_input_devices(&devices)
 }
 
+/// The reports of the raw input of a HID device, with the device, which don't fit in `RAWINPUT`.
+pub fn get_raw_input_hid_reports(handle: HRAWINPUT) -> Option<(HANDLE, Vec<Vec<u8>>)> {
+    let header_size = size_of::<RAWINPUTHEADER>() as UINT;
+    let mut header: RAWINPUTHEADER = unsafe { mem::zeroed() };
+    let mut size = header_size;
+    let status = unsafe {
+        winuser::GetRawInputData(
+            handle,
+            RID_HEADER,
+            &mut header as *mut _ as _,
+            &mut size,
+            header_size,
+        )
+    };
+    if status == UINT::max_value() || header.dwType != RIM_TYPEHID {
+        return None;
+    }
+
+    let mut size = 0;
+    let status = unsafe {
+        winuser::GetRawInputData(handle, RID_INPUT, ptr::null_mut(), &mut size, header_size)
+    };
+    if status != 0 {
+        return None;
+    }
+    // The buffer is aligned as `RAWINPUT`.
+    let mut buffer: Vec<u64> = vec![0; (size as usize + 7) / 8];
+    let status = unsafe {
+        winuser::GetRawInputData(
+            handle,
+            RID_INPUT,
+            buffer.as_mut_ptr() as _,
+            &mut size,
+            header_size,
+        )
+    };
+    if status == UINT::max_value() || status == 0 {
+        return None;
+    }
+    let data = unsafe { slice::from_raw_parts(buffer.as_ptr() as *const u8, status as usize) };
+
+    // The header is followed by `RAWHID`, whose reports all have the same size.
+    let hid = data.get(header_size as usize..)?;
+    let dword = |offset: usize| {
+        let bytes = hid.get(offset..offset + 4)?;
+        Some(DWORD::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    };
+    let (report_size, count) = (dword(0)?, dword(4)?);
+    if report_size == 0 {
+        return None;
+    }
+    let reports = hid
+        .get(8..)?
+        .chunks_exact(report_size)
+        .take(count)
+        .map(|report| report.to_vec())
+        .collect();
+    Some((header.hDevice, reports))
+}
+
 pub fn get_raw_input_data(handle: HRAWINPUT) -> Option<RAWINPUT> {
     let mut data: RAWINPUT = unsafe { mem::zeroed() };
     let mut data_size = size_of::<RAWINPUT>() as UINT;
@@ -0,0 +1,167 @@
+//! The physical keys of the scancodes, which are the set 1 scancodes, where the extended keys are
+//! prefixed with `0xE0`.
+
+use crate::event::{ScanCode, VirtualKeyCode};
+
+/// The keys at the position of the scancodes on a US keyboard.
+const SCANCODES: &[(ScanCode, VirtualKeyCode)] = &[
+    (0x01, VirtualKeyCode::Escape),
+    (0x02, VirtualKeyCode::Key1),
+    (0x03, VirtualKeyCode::Key2),
+    (0x04, VirtualKeyCode::Key3),
+    (0x05, VirtualKeyCode::Key4),
+    (0x06, VirtualKeyCode::Key5),
+    (0x07, VirtualKeyCode::Key6),
+    (0x08, VirtualKeyCode::Key7),
+    (0x09, VirtualKeyCode::Key8),
+    (0x0A, VirtualKeyCode::Key9),
+    (0x0B, VirtualKeyCode::Key0),
+    (0x0C, VirtualKeyCode::Minus),
+    (0x0D, VirtualKeyCode::Equals),
+    (0x0E, VirtualKeyCode::Back),
+    (0x0F, VirtualKeyCode::Tab),
+    (0x10, VirtualKeyCode::Q),
+    (0x11, VirtualKeyCode::W),
+    (0x12, VirtualKeyCode::E),
+    (0x13, VirtualKeyCode::R),
+    (0x14, VirtualKeyCode::T),
+    (0x15, VirtualKeyCode::Y),
+    (0x16, VirtualKeyCode::U),
+    (0x17, VirtualKeyCode::I),
+    (0x18, VirtualKeyCode::O),
+    (0x19, VirtualKeyCode::P),
+    (0x1A, VirtualKeyCode::LBracket),
+    (0x1B, VirtualKeyCode::RBracket),
+    (0x1C, VirtualKeyCode::Return),
+    (0x1D, VirtualKeyCode::LControl),
+    (0x1E, VirtualKeyCode::A),
+    (0x1F, VirtualKeyCode::S),
+    (0x20, VirtualKeyCode::D),
+    (0x21, VirtualKeyCode::F),
+    (0x22, VirtualKeyCode::G),
+    (0x23, VirtualKeyCode::H),
+    (0x24, VirtualKeyCode::J),
+    (0x25, VirtualKeyCode::K),
+    (0x26, VirtualKeyCode::L),
+    (0x27, VirtualKeyCode::Semicolon),
+    (0x28, VirtualKeyCode::Apostrophe),
+    (0x29, VirtualKeyCode::Grave),
+    (0x2A, VirtualKeyCode::LShift),
+    (0x2B, VirtualKeyCode::Backslash),
+    (0x2C, VirtualKeyCode::Z),
+    (0x2D, VirtualKeyCode::X),
+    (0x2E, VirtualKeyCode::C),
+    (0x2F, VirtualKeyCode::V),
+    (0x30, VirtualKeyCode::B),
+    (0x31, VirtualKeyCode::N),
+    (0x32, VirtualKeyCode::M),
+    (0x33, VirtualKeyCode::Comma),
+    (0x34, VirtualKeyCode::Period),
+    (0x35, VirtualKeyCode::Slash),
+    (0x36, VirtualKeyCode::RShift),
+    (0x37, VirtualKeyCode::NumpadMultiply),
+    (0x38, VirtualKeyCode::LAlt),
+    (0x39, VirtualKeyCode::Space),
+    (0x3A, VirtualKeyCode::Capital),
+    (0x3B, VirtualKeyCode::F1),
+    (0x3C, VirtualKeyCode::F2),
+    (0x3D, VirtualKeyCode::F3),
+    (0x3E, VirtualKeyCode::F4),
+    (0x3F, VirtualKeyCode::F5),
+    (0x40, VirtualKeyCode::F6),
+    (0x41, VirtualKeyCode::F7),
+    (0x42, VirtualKeyCode::F8),
+    (0x43, VirtualKeyCode::F9),
+    (0x44, VirtualKeyCode::F10),
+    (0x46, VirtualKeyCode::Scroll),
+    (0x47, VirtualKeyCode::Numpad7),
+    (0x48, VirtualKeyCode::Numpad8),
+    (0x49, VirtualKeyCode::Numpad9),
+    (0x4A, VirtualKeyCode::NumpadSubtract),
+    (0x4B, VirtualKeyCode::Numpad4),
+    (0x4C, VirtualKeyCode::Numpad5),
+    (0x4D, VirtualKeyCode::Numpad6),
+    (0x4E, VirtualKeyCode::NumpadAdd),
+    (0x4F, VirtualKeyCode::Numpad1),
+    (0x50, VirtualKeyCode::Numpad2),
+    (0x51, VirtualKeyCode::Numpad3),
+    (0x52, VirtualKeyCode::Numpad0),
+    (0x53, VirtualKeyCode::NumpadDecimal),
+    (0x56, VirtualKeyCode::OEM102),
+    (0x57, VirtualKeyCode::F11),
+    (0x58, VirtualKeyCode::F12),
+    (0x59, VirtualKeyCode::NumpadEquals),
+    (0x64, VirtualKeyCode::F13),
+    (0x65, VirtualKeyCode::F14),
+    (0x66, VirtualKeyCode::F15),
+    (0x67, VirtualKeyCode::F16),
+    (0x68, VirtualKeyCode::F17),
+    (0x69, VirtualKeyCode::F18),
+    (0x6A, VirtualKeyCode::F19),
+    (0x6B, VirtualKeyCode::F20),
+    (0x6C, VirtualKeyCode::F21),
+    (0x6D, VirtualKeyCode::F22),
+    (0x6E, VirtualKeyCode::F23),
+    (0x70, VirtualKeyCode::Kana),
+    (0x73, VirtualKeyCode::AbntC1),
+    (0x76, VirtualKeyCode::F24),
+    (0x79, VirtualKeyCode::Convert),
+    (0x7B, VirtualKeyCode::NoConvert),
+    (0x7D, VirtualKeyCode::Yen),
+    (0x7E, VirtualKeyCode::AbntC2),
+    (0xE010, VirtualKeyCode::PrevTrack),
+    (0xE019, VirtualKeyCode::NextTrack),
+    (0xE01C, VirtualKeyCode::NumpadEnter),
+    (0xE01D, VirtualKeyCode::RControl),
+    (0xE020, VirtualKeyCode::Mute),
+    (0xE021, VirtualKeyCode::Calculator),
+    (0xE022, VirtualKeyCode::PlayPause),
+    (0xE024, VirtualKeyCode::MediaStop),
+    (0xE02E, VirtualKeyCode::VolumeDown),
+    (0xE030, VirtualKeyCode::VolumeUp),
+    (0xE032, VirtualKeyCode::WebHome),
+    (0xE035, VirtualKeyCode::NumpadDivide),
+    (0xE037, VirtualKeyCode::Snapshot),
+    (0xE038, VirtualKeyCode::RAlt),
+    (0xE045, VirtualKeyCode::Numlock),
+    (0xE047, VirtualKeyCode::Home),
+    (0xE048, VirtualKeyCode::Up),
+    (0xE049, VirtualKeyCode::PageUp),
+    (0xE04B, VirtualKeyCode::Left),
+    (0xE04D, VirtualKeyCode::Right),
+    (0xE04F, VirtualKeyCode::End),
+    (0xE050, VirtualKeyCode::Down),
+    (0xE051, VirtualKeyCode::PageDown),
+    (0xE052, VirtualKeyCode::Insert),
+    (0xE053, VirtualKeyCode::Delete),
+    (0xE059, VirtualKeyCode::Pause),
+    (0xE05B, VirtualKeyCode::LWin),
+    (0xE05C, VirtualKeyCode::RWin),
+    (0xE05D, VirtualKeyCode::Apps),
+    (0xE05E, VirtualKeyCode::Power),
+    (0xE05F, VirtualKeyCode::Sleep),
+    (0xE063, VirtualKeyCode::Wake),
+    (0xE065, VirtualKeyCode::WebSearch),
+    (0xE066, VirtualKeyCode::WebFavorites),
+    (0xE067, VirtualKeyCode::WebRefresh),
+    (0xE068, VirtualKeyCode::WebStop),
+    (0xE069, VirtualKeyCode::WebForward),
+    (0xE06A, VirtualKeyCode::WebBack),
+    (0xE06B, VirtualKeyCode::MyComputer),
+    (0xE06C, VirtualKeyCode::Mail),
+    (0xE06D, VirtualKeyCode::MediaSelect),
+];
+
+pub fn scancode_to_key(scancode: ScanCode) -> Option<VirtualKeyCode> {
+    SCANCODES
+        .iter()
+        .find(|&&(code, _)| code == scancode)
+        .map(|&(_, key)| key)
+}
+
+pub fn key_to_scancode(key: VirtualKeyCode) -> Option<ScanCode> {
+    SCANCODES
+        .iter()
+        .find(|&&(_, code_key)| code_key == key)
+        .map(|&(scancode, _)| scancode)
+}
@@ -0,0 +1,1714 @@
+//! The input methods of the Text Services Framework, which edit a text store holding the
+//! surrounding text of the window along with the composition, instead of the composition string
+//! of IMM32.
+
+use std::{
+    cell::{Cell, RefCell},
+    mem,
+    ops::Range,
+    ptr, slice,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use winapi::{
+    ctypes::c_void,
+    shared::{
+        guiddef::{IsEqualIID, GUID, REFGUID, REFIID},
+        minwindef::{BOOL, DWORD, FALSE, TRUE, ULONG},
+        windef::{HWND, POINT, RECT},
+        winerror::{E_FAIL, E_INVALIDARG, E_NOINTERFACE, E_NOTIMPL, E_POINTER, S_OK},
+        wtypesbase::CLSCTX_INPROC_SERVER,
+    },
+    um::{
+        combaseapi,
+        objidl::{IDataObject, FORMATETC},
+        unknwnbase::{IUnknown, IUnknownVtbl},
+        winnt::{HRESULT, LONG, WCHAR},
+        winuser,
+    },
+    Interface,
+};
+
+use crate::{
+    event::{Event, ImePreedit, ImePreeditSpan, ImeUnderline, WindowEvent},
+    platform_impl::platform::{ime::SurroundingText, window, WindowId},
+    window::{ImePurpose, WindowId as RootWindowId},
+};
+
+type TfClientId = DWORD;
+type TfEditCookie = DWORD;
+type TsViewCookie = DWORD;
+type TsActiveSelEnd = i32;
+type TsRunType = i32;
+type TsLayoutCode = i32;
+
+const TS_LF_SYNC: DWORD = 0x1;
+const TS_LF_READ: DWORD = 0x2;
+const TS_LF_READWRITE: DWORD = 0x6;
+
+const TS_AS_TEXT_CHANGE: DWORD = 0x1;
+const TS_AS_SEL_CHANGE: DWORD = 0x2;
+const TS_AS_LAYOUT_CHANGE: DWORD = 0x4;
+
+const TS_SS_NOHIDDENTEXT: DWORD = 0x8;
+const TS_IAS_NOQUERY: DWORD = 0x1;
+const TS_IAS_QUERYONLY: DWORD = 0x2;
+const TS_DEFAULT_SELECTION: ULONG = !0;
+
+const TS_AE_END: TsActiveSelEnd = 2;
+const TS_RT_PLAIN: TsRunType = 0;
+const TS_LC_CHANGE: TsLayoutCode = 1;
+const TF_POPF_ALL: DWORD = 0x1;
+
+/// The only view of the text store, which is the client area of the window.
+const VIEW_COOKIE: TsViewCookie = 0;
+
+const TS_E_INVALIDPOS: HRESULT = 0x80040200u32 as HRESULT;
+const TS_E_NOLOCK: HRESULT = 0x80040201u32 as HRESULT;
+const TS_E_SYNCHRONOUS: HRESULT = 0x80040208u32 as HRESULT;
+const TS_S_ASYNC: HRESULT = 0x00040300;
+const CONNECT_E_NOCONNECTION: HRESULT = 0x80040200u32 as HRESULT;
+const CONNECT_E_ADVISELIMIT: HRESULT = 0x80040201u32 as HRESULT;
+
+const CLSID_TF_THREAD_MGR: GUID = GUID {
+    Data1: 0x529A9E6B,
+    Data2: 0x6587,
+    Data3: 0x4F23,
+    Data4: [0xAB, 0x9E, 0x9C, 0x7D, 0x68, 0x3E, 0x3C, 0x50],
+};
+const IID_ITF_THREAD_MGR: GUID = GUID {
+    Data1: 0xAA80E801,
+    Data2: 0x2021,
+    Data3: 0x11D2,
+    Data4: [0x93, 0xE0, 0x00, 0x60, 0xB0, 0x67, 0xB8, 0x6E],
+};
+const IID_ITEXT_STORE_ACP: GUID = GUID {
+    Data1: 0x28888FE3,
+    Data2: 0xC2A0,
+    Data3: 0x483A,
+    Data4: [0xA3, 0xEA, 0x8C, 0xB1, 0xCE, 0x51, 0xFF, 0x3D],
+};
+const IID_ITEXT_STORE_ACP_SINK: GUID = GUID {
+    Data1: 0x22D44C94,
+    Data2: 0xA419,
+    Data3: 0x4542,
+    Data4: [0xA2, 0x72, 0xAE, 0x26, 0x09, 0x3E, 0xCE, 0xCF],
+};
+const IID_ITF_CONTEXT_OWNER_COMPOSITION_SINK: GUID = GUID {
+    Data1: 0x5F20AA40,
+    Data2: 0xB57A,
+    Data3: 0x4F34,
+    Data4: [0x96, 0xAB, 0x35, 0x76, 0xF3, 0x77, 0xCC, 0x79],
+};
+const IID_ITF_RANGE_ACP: GUID = GUID {
+    Data1: 0x057A6296,
+    Data2: 0x029B,
+    Data3: 0x4154,
+    Data4: [0xB7, 0x9A, 0x0D, 0x46, 0x1D, 0x4E, 0xA9, 0x4C],
+};
+
+#[allow(non_snake_case, non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TS_STATUS {
+    dwDynamicFlags: DWORD,
+    dwStaticFlags: DWORD,
+}
+
+#[allow(non_snake_case, non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TS_SELECTIONSTYLE {
+    ase: TsActiveSelEnd,
+    fInterimChar: BOOL,
+}
+
+#[allow(non_snake_case, non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TS_SELECTION_ACP {
+    acpStart: LONG,
+    acpEnd: LONG,
+    style: TS_SELECTIONSTYLE,
+}
+
+#[allow(non_snake_case, non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TS_TEXTCHANGE {
+    acpStart: LONG,
+    acpOldEnd: LONG,
+    acpNewEnd: LONG,
+}
+
+#[allow(non_snake_case, non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct TS_RUNINFO {
+    uCount: ULONG,
+    type_: TsRunType,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct ITfThreadMgrVtbl {
+    parent: IUnknownVtbl,
+    Activate: unsafe extern "system" fn(This: *mut ITfThreadMgr, ptid: *mut TfClientId) -> HRESULT,
+    // `Deactivate`, which isn't used as the thread manager is kept until the thread exits.
+    _Deactivate: usize,
+    CreateDocumentMgr: unsafe extern "system" fn(
+        This: *mut ITfThreadMgr,
+        ppdim: *mut *mut ITfDocumentMgr,
+    ) -> HRESULT,
+    // `EnumDocumentMgrs`, `GetFocus` and `SetFocus`, which aren't used.
+    _EnumDocumentMgrs: usize,
+    _GetFocus: usize,
+    _SetFocus: usize,
+    AssociateFocus: unsafe extern "system" fn(
+        This: *mut ITfThreadMgr,
+        hwnd: HWND,
+        pdimNew: *mut ITfDocumentMgr,
+        ppdimPrev: *mut *mut ITfDocumentMgr,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct ITfThreadMgr {
+    lpVtbl: *const ITfThreadMgrVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct ITfDocumentMgrVtbl {
+    parent: IUnknownVtbl,
+    CreateContext: unsafe extern "system" fn(
+        This: *mut ITfDocumentMgr,
+        tidOwner: TfClientId,
+        dwFlags: DWORD,
+        punk: *mut IUnknown,
+        ppic: *mut *mut ITfContext,
+        pecTextStore: *mut TfEditCookie,
+    ) -> HRESULT,
+    Push: unsafe extern "system" fn(This: *mut ITfDocumentMgr, pic: *mut ITfContext) -> HRESULT,
+    Pop: unsafe extern "system" fn(This: *mut ITfDocumentMgr, dwFlags: DWORD) -> HRESULT,
+}
+
+#[repr(C)]
+struct ITfDocumentMgr {
+    lpVtbl: *const ITfDocumentMgrVtbl,
+}
+
+/// The context of the text store, which is only kept alive.
+#[repr(C)]
+struct ITfContext {
+    lpVtbl: *const IUnknownVtbl,
+}
+
+/// The range of a composition, which is only queried for its `ITfRangeACP`.
+#[repr(C)]
+struct ITfRange {
+    lpVtbl: *const IUnknownVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct ITfRangeACPVtbl {
+    parent: IUnknownVtbl,
+    // The 22 methods of `ITfRange`, which aren't used.
+    _ITfRange: [usize; 22],
+    GetExtent: unsafe extern "system" fn(
+        This: *mut ITfRangeACP,
+        pacpAnchor: *mut LONG,
+        pcch: *mut LONG,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct ITfRangeACP {
+    lpVtbl: *const ITfRangeACPVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct ITfCompositionViewVtbl {
+    parent: IUnknownVtbl,
+    // `GetOwnerClsid`, which isn't used.
+    _GetOwnerClsid: usize,
+    GetRange: unsafe extern "system" fn(
+        This: *mut ITfCompositionView,
+        ppRange: *mut *mut ITfRange,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct ITfCompositionView {
+    lpVtbl: *const ITfCompositionViewVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct ITextStoreACPSinkVtbl {
+    parent: IUnknownVtbl,
+    OnTextChange: unsafe extern "system" fn(
+        This: *mut ITextStoreACPSink,
+        dwFlags: DWORD,
+        pChange: *const TS_TEXTCHANGE,
+    ) -> HRESULT,
+    OnSelectionChange: unsafe extern "system" fn(This: *mut ITextStoreACPSink) -> HRESULT,
+    OnLayoutChange: unsafe extern "system" fn(
+        This: *mut ITextStoreACPSink,
+        lcode: TsLayoutCode,
+        vcView: TsViewCookie,
+    ) -> HRESULT,
+    // `OnStatusChange` and `OnAttrsChange`, which aren't used.
+    _OnStatusChange: usize,
+    _OnAttrsChange: usize,
+    OnLockGranted:
+        unsafe extern "system" fn(This: *mut ITextStoreACPSink, dwLockFlags: DWORD) -> HRESULT,
+}
+
+#[repr(C)]
+struct ITextStoreACPSink {
+    lpVtbl: *const ITextStoreACPSinkVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct ITextStoreACPVtbl {
+    parent: IUnknownVtbl,
+    AdviseSink: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        riid: REFIID,
+        punk: *mut IUnknown,
+        dwMask: DWORD,
+    ) -> HRESULT,
+    UnadviseSink:
+        unsafe extern "system" fn(This: *mut ITextStoreACP, punk: *mut IUnknown) -> HRESULT,
+    RequestLock: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        dwLockFlags: DWORD,
+        phrSession: *mut HRESULT,
+    ) -> HRESULT,
+    GetStatus: unsafe extern "system" fn(This: *mut ITextStoreACP, pdcs: *mut TS_STATUS) -> HRESULT,
+    QueryInsert: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        acpTestStart: LONG,
+        acpTestEnd: LONG,
+        cch: ULONG,
+        pacpResultStart: *mut LONG,
+        pacpResultEnd: *mut LONG,
+    ) -> HRESULT,
+    GetSelection: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        ulIndex: ULONG,
+        ulCount: ULONG,
+        pSelection: *mut TS_SELECTION_ACP,
+        pcFetched: *mut ULONG,
+    ) -> HRESULT,
+    SetSelection: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        ulCount: ULONG,
+        pSelection: *const TS_SELECTION_ACP,
+    ) -> HRESULT,
+    GetText: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        acpStart: LONG,
+        acpEnd: LONG,
+        pchPlain: *mut WCHAR,
+        cchPlainReq: ULONG,
+        pcchPlainRet: *mut ULONG,
+        prgRunInfo: *mut TS_RUNINFO,
+        cRunInfoReq: ULONG,
+        pcRunInfoRet: *mut ULONG,
+        pacpNext: *mut LONG,
+    ) -> HRESULT,
+    SetText: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        dwFlags: DWORD,
+        acpStart: LONG,
+        acpEnd: LONG,
+        pchText: *const WCHAR,
+        cch: ULONG,
+        pChange: *mut TS_TEXTCHANGE,
+    ) -> HRESULT,
+    GetFormattedText: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        acpStart: LONG,
+        acpEnd: LONG,
+        ppDataObject: *mut *mut IDataObject,
+    ) -> HRESULT,
+    GetEmbedded: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        acpPos: LONG,
+        rguidService: REFGUID,
+        riid: REFIID,
+        ppunk: *mut *mut IUnknown,
+    ) -> HRESULT,
+    QueryInsertEmbedded: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        pguidService: *const GUID,
+        pFormatEtc: *const FORMATETC,
+        pfInsertable: *mut BOOL,
+    ) -> HRESULT,
+    InsertEmbedded: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        dwFlags: DWORD,
+        acpStart: LONG,
+        acpEnd: LONG,
+        pDataObject: *mut IDataObject,
+        pChange: *mut TS_TEXTCHANGE,
+    ) -> HRESULT,
+    RequestSupportedAttrs: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        dwFlags: DWORD,
+        cFilterAttrs: ULONG,
+        paFilterAttrs: *const GUID,
+    ) -> HRESULT,
+    RequestAttrsAtPosition: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        acpPos: LONG,
+        cFilterAttrs: ULONG,
+        paFilterAttrs: *const GUID,
+        dwFlags: DWORD,
+    ) -> HRESULT,
+    RequestAttrsTransitioningAtPosition: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        acpPos: LONG,
+        cFilterAttrs: ULONG,
+        paFilterAttrs: *const GUID,
+        dwFlags: DWORD,
+    ) -> HRESULT,
+    FindNextAttrTransition: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        acpStart: LONG,
+        acpHalt: LONG,
+        cFilterAttrs: ULONG,
+        paFilterAttrs: *const GUID,
+        dwFlags: DWORD,
+        pacpNext: *mut LONG,
+        pfFound: *mut BOOL,
+        plFoundOffset: *mut LONG,
+    ) -> HRESULT,
+    RetrieveRequestedAttrs: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        ulCount: ULONG,
+        paAttrVals: *mut c_void,
+        pcFetched: *mut ULONG,
+    ) -> HRESULT,
+    GetEndACP: unsafe extern "system" fn(This: *mut ITextStoreACP, pacp: *mut LONG) -> HRESULT,
+    GetActiveView:
+        unsafe extern "system" fn(This: *mut ITextStoreACP, pvcView: *mut TsViewCookie) -> HRESULT,
+    GetACPFromPoint: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        vcView: TsViewCookie,
+        ptScreen: *const POINT,
+        dwFlags: DWORD,
+        pacp: *mut LONG,
+    ) -> HRESULT,
+    GetTextExt: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        vcView: TsViewCookie,
+        acpStart: LONG,
+        acpEnd: LONG,
+        prc: *mut RECT,
+        pfClipped: *mut BOOL,
+    ) -> HRESULT,
+    GetScreenExt: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        vcView: TsViewCookie,
+        prc: *mut RECT,
+    ) -> HRESULT,
+    GetWnd: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        vcView: TsViewCookie,
+        phwnd: *mut HWND,
+    ) -> HRESULT,
+    InsertTextAtSelection: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        dwFlags: DWORD,
+        pchText: *const WCHAR,
+        cch: ULONG,
+        pacpStart: *mut LONG,
+        pacpEnd: *mut LONG,
+        pChange: *mut TS_TEXTCHANGE,
+    ) -> HRESULT,
+    InsertEmbeddedAtSelection: unsafe extern "system" fn(
+        This: *mut ITextStoreACP,
+        dwFlags: DWORD,
+        pDataObject: *mut IDataObject,
+        pacpStart: *mut LONG,
+        pacpEnd: *mut LONG,
+        pChange: *mut TS_TEXTCHANGE,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct ITextStoreACP {
+    lpVtbl: *const ITextStoreACPVtbl,
+}
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct ITfContextOwnerCompositionSinkVtbl {
+    parent: IUnknownVtbl,
+    OnStartComposition: unsafe extern "system" fn(
+        This: *mut ITfContextOwnerCompositionSink,
+        pComposition: *mut ITfCompositionView,
+        pfOk: *mut BOOL,
+    ) -> HRESULT,
+    OnUpdateComposition: unsafe extern "system" fn(
+        This: *mut ITfContextOwnerCompositionSink,
+        pComposition: *mut ITfCompositionView,
+        pRangeNew: *mut ITfRange,
+    ) -> HRESULT,
+    OnEndComposition: unsafe extern "system" fn(
+        This: *mut ITfContextOwnerCompositionSink,
+        pComposition: *mut ITfCompositionView,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct ITfContextOwnerCompositionSink {
+    lpVtbl: *const ITfContextOwnerCompositionSinkVtbl,
+}
+
+thread_local! {
+    /// The thread manager of the thread with its client id, activated along with the first text
+    /// store and kept until the thread exits.
+    static THREAD_MGR: Cell<Option<(*mut ITfThreadMgr, TfClientId)>> = Cell::new(None);
+}
+
+/// The activated thread manager of the thread, or `None` if TSF isn't available.
+unsafe fn thread_mgr() -> Option<(*mut ITfThreadMgr, TfClientId)> {
+    THREAD_MGR.with(|thread_mgr_cell| {
+        if let Some(thread_mgr) = thread_mgr_cell.get() {
+            return Some(thread_mgr);
+        }
+
+        window::com_initialized();
+        let mut thread_mgr: *mut ITfThreadMgr = ptr::null_mut();
+        let hr = combaseapi::CoCreateInstance(
+            &CLSID_TF_THREAD_MGR,
+            ptr::null_mut(),
+            CLSCTX_INPROC_SERVER,
+            &IID_ITF_THREAD_MGR,
+            &mut thread_mgr as *mut _ as *mut _,
+        );
+        if hr != S_OK || thread_mgr.is_null() {
+            warn!("Failed to create the TSF thread manager: {:#x}", hr);
+            return None;
+        }
+        let mut client_id = 0;
+        let hr = ((*(*thread_mgr).lpVtbl).Activate)(thread_mgr, &mut client_id);
+        if hr != S_OK {
+            warn!("Failed to activate the TSF thread manager: {:#x}", hr);
+            (*(thread_mgr as *mut IUnknown)).Release();
+            return None;
+        }
+        thread_mgr_cell.set(Some((thread_mgr, client_id)));
+        Some((thread_mgr, client_id))
+    })
+}
+
+/// The text of the text store, which is the one of the application along with the composition
+/// and the edits of the input method not delivered yet.
+struct TextState {
+    text: Vec<u16>,
+    selection: Range<usize>,
+    active_end: TsActiveSelEnd,
+    /// The range of `text` the input method composes.
+    composition: Option<Range<usize>>,
+    /// The text the application has, as it was last set or updated with the delivered edits.
+    app_text: Vec<u16>,
+    app_selection: Range<usize>,
+    /// Whether the application sets the surrounding text, without which the text store only
+    /// keeps the composition.
+    has_surrounding_text: bool,
+    /// The area of the cursor, in client area coordinates.
+    cursor_area: Option<RECT>,
+    /// The preedit last sent to the application.
+    preedit: ImePreedit,
+}
+
+impl TextState {
+    /// Replaces the range of the text, moving the caret after the new text.
+    fn replace(&mut self, range: Range<usize>, text: &[u16]) -> TS_TEXTCHANGE {
+        let end = range.start + text.len();
+        let change = TS_TEXTCHANGE {
+            acpStart: range.start as LONG,
+            acpOldEnd: range.end as LONG,
+            acpNewEnd: end as LONG,
+        };
+        self.text.splice(range, text.iter().cloned());
+        self.selection = end..end;
+        self.active_end = TS_AE_END;
+        change
+    }
+
+    fn contains(&self, start: LONG, end: LONG) -> bool {
+        0 <= start && start <= end && end as usize <= self.text.len()
+    }
+
+    /// The events telling the application of the edits of the input method since the last
+    /// ones, along with the length of the text if the text store dropped it, as the application
+    /// doesn't keep it.
+    fn changes(&mut self) -> (Vec<WindowEvent<'static>>, Option<usize>) {
+        let len = self.text.len();
+        let composition = self.composition.as_ref().map(|composition| {
+            let start = composition.start.min(len);
+            start..composition.end.min(len).max(start)
+        });
+
+        // The application gets all of the text but the composition, which it draws as the
+        // preedit instead.
+        let text = match composition {
+            Some(ref composition) => [
+                &self.text[..composition.start],
+                &self.text[composition.end..],
+            ]
+            .concat(),
+            None => self.text.clone(),
+        };
+        let mut events = Vec::new();
+        if let Some((replaced, replacement)) =
+            replaced_range(&self.app_text, &text, &self.app_selection)
+        {
+            if self.preedit != ImePreedit::default() {
+                self.preedit = ImePreedit::default();
+                events.push(WindowEvent::ImePreedit(ImePreedit::default()));
+            }
+            if !replaced.is_empty() {
+                let bytes =
+                    |range: Range<usize>| String::from_utf16_lossy(&self.app_text[range]).len();
+                events.push(WindowEvent::ImeDeleteSurrounding {
+                    before_length: bytes(replaced.start..self.app_selection.start),
+                    after_length: bytes(self.app_selection.end..replaced.end),
+                });
+            }
+            events.extend(
+                std::char::decode_utf16(text[replacement.clone()].iter().cloned()).map(|c| {
+                    WindowEvent::ReceivedCharacter(c.unwrap_or(std::char::REPLACEMENT_CHARACTER))
+                }),
+            );
+            self.app_selection = replacement.end..replacement.end;
+            self.app_text = text;
+        }
+
+        let preedit = match composition {
+            Some(ref composition) => {
+                composition_preedit(&self.text, composition.clone(), &self.selection)
+            }
+            None => ImePreedit::default(),
+        };
+        if preedit != self.preedit {
+            self.preedit = preedit.clone();
+            events.push(WindowEvent::ImePreedit(preedit));
+        }
+
+        let cleared = if !self.has_surrounding_text && composition.is_none() && len != 0 {
+            self.text.clear();
+            self.app_text.clear();
+            self.selection = 0..0;
+            self.app_selection = 0..0;
+            Some(len)
+        } else {
+            None
+        };
+        (events, cleared)
+    }
+}
+
+/// The range of `old` to replace to get `new`, along with the range of its replacement in `new`.
+/// The range takes in the selection, which the deletions are counted from and which the
+/// replacement is inserted at, and doesn't cut the surrogate pairs.
+fn replaced_range(
+    old: &[u16],
+    new: &[u16],
+    selection: &Range<usize>,
+) -> Option<(Range<usize>, Range<usize>)> {
+    if old == new {
+        return None;
+    }
+    let is_low_surrogate = |unit: u16| (0xDC00..=0xDFFF).contains(&unit);
+
+    let mut start = old
+        .iter()
+        .zip(new)
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(selection.start);
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take((old.len() - selection.end).min(new.len() - start))
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut end = old.len() - suffix;
+    if start > 0 && start < old.len() && is_low_surrogate(old[start]) {
+        start -= 1;
+    }
+    if end < old.len() && is_low_surrogate(old[end]) {
+        end += 1;
+    }
+    let new_end = new.len() - (old.len() - end);
+    Some((start..end, start..new_end))
+}
+
+/// The preedit of the composition, as a single underlined span since the display attributes of
+/// the input methods aren't read.
+fn composition_preedit(
+    text: &[u16],
+    composition: Range<usize>,
+    selection: &Range<usize>,
+) -> ImePreedit {
+    let composed = &text[composition.clone()];
+    let byte = |index: usize| String::from_utf16_lossy(&composed[..index]).len();
+    let mut preedit = ImePreedit {
+        text: String::from_utf16_lossy(composed),
+        ..Default::default()
+    };
+    if composition.start <= selection.start && selection.end <= composition.end {
+        preedit.cursor = Some(
+            byte(selection.start - composition.start)..byte(selection.end - composition.start),
+        );
+    }
+    if !preedit.text.is_empty() {
+        preedit.spans.push(ImePreeditSpan {
+            range: 0..preedit.text.len(),
+            underline: ImeUnderline::Single,
+            highlighted: false,
+        });
+    }
+    preedit
+}
+
+/// The range of the composition, which is the new one if it's given.
+unsafe fn composition_range(
+    view: *mut ITfCompositionView,
+    range: *mut ITfRange,
+) -> Option<Range<usize>> {
+    let mut view_range = ptr::null_mut();
+    let range = if range.is_null() {
+        if view.is_null()
+            || ((*(*view).lpVtbl).GetRange)(view, &mut view_range) != S_OK
+            || view_range.is_null()
+        {
+            return None;
+        }
+        view_range
+    } else {
+        range
+    };
+
+    let mut range_acp: *mut ITfRangeACP = ptr::null_mut();
+    let hr = (*(range as *mut IUnknown)).QueryInterface(
+        &IID_ITF_RANGE_ACP,
+        &mut range_acp as *mut _ as *mut *mut c_void,
+    );
+    if !view_range.is_null() {
+        (*(view_range as *mut IUnknown)).Release();
+    }
+    if hr != S_OK || range_acp.is_null() {
+        return None;
+    }
+    let (mut start, mut len) = (0, 0);
+    let hr = ((*(*range_acp).lpVtbl).GetExtent)(range_acp, &mut start, &mut len);
+    (*(range_acp as *mut IUnknown)).Release();
+    if hr != S_OK || start < 0 || len < 0 {
+        return None;
+    }
+    Some(start as usize..(start + len) as usize)
+}
+
+/// Whether the interfaces belong to the same object, as told by their `IUnknown`.
+unsafe fn is_same_object(a: *mut IUnknown, b: *mut IUnknown) -> bool {
+    let identity = |object: *mut IUnknown| {
+        let mut unknown = ptr::null_mut();
+        if (*object).QueryInterface(&IUnknown::uuidof(), &mut unknown) != S_OK {
+            return ptr::null_mut();
+        }
+        (*(unknown as *mut IUnknown)).Release();
+        unknown
+    };
+    let a = identity(a);
+    !a.is_null() && a == identity(b)
+}
+
+/// The rectangle in screen coordinates of the one in client area coordinates.
+unsafe fn client_to_screen(window: HWND, rect: RECT) -> RECT {
+    let mut top_left = POINT {
+        x: rect.left,
+        y: rect.top,
+    };
+    let mut bottom_right = POINT {
+        x: rect.right,
+        y: rect.bottom,
+    };
+    winuser::ClientToScreen(window, &mut top_left);
+    winuser::ClientToScreen(window, &mut bottom_right);
+    RECT {
+        left: top_left.x,
+        top: top_left.y,
+        right: bottom_right.x,
+        bottom: bottom_right.y,
+    }
+}
+
+#[repr(C)]
+struct TextStoreData {
+    interface: ITextStoreACP,
+    composition_sink: ITfContextOwnerCompositionSink,
+    refcount: AtomicUsize,
+    window: HWND,
+    send_event: Box<dyn Fn(Event<'static, ()>)>,
+    sink: Cell<*mut ITextStoreACPSink>,
+    sink_mask: Cell<DWORD>,
+    /// The lock the sink holds, which is 0 when the text store is unlocked.
+    lock: Cell<DWORD>,
+    /// The asynchronous lock the sink asked for while holding one.
+    queued_lock: Cell<DWORD>,
+    state: RefCell<TextState>,
+}
+
+impl TextStoreData {
+    fn has_lock(&self, lock: DWORD) -> bool {
+        self.lock.get() & lock == lock
+    }
+
+    unsafe fn grant_lock(&self, lock: DWORD) -> HRESULT {
+        let sink = self.sink.get();
+        if sink.is_null() {
+            return E_FAIL;
+        }
+        self.lock.set(lock);
+        let hr = ((*(*sink).lpVtbl).OnLockGranted)(sink, lock);
+        self.lock.set(0);
+        hr
+    }
+
+    /// Tells the sink of the changes the application made, if it asked for them.
+    unsafe fn notify(&self, mask: DWORD, notify: impl FnOnce(*mut ITextStoreACPSink)) {
+        let sink = self.sink.get();
+        if !sink.is_null() && self.sink_mask.get() & mask != 0 {
+            notify(sink);
+        }
+    }
+
+    /// Sends the edits of the input method, once it released the text store.
+    unsafe fn send_changes(&self) {
+        let (events, cleared) = self.state.borrow_mut().changes();
+        if let Some(len) = cleared {
+            let change = TS_TEXTCHANGE {
+                acpStart: 0,
+                acpOldEnd: len as LONG,
+                acpNewEnd: 0,
+            };
+            self.notify(TS_AS_TEXT_CHANGE, |sink| {
+                ((*(*sink).lpVtbl).OnTextChange)(sink, 0, &change);
+            });
+            self.notify(TS_AS_SEL_CHANGE, |sink| {
+                ((*(*sink).lpVtbl).OnSelectionChange)(sink);
+            });
+        }
+        for event in events {
+            (self.send_event)(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(self.window)),
+                event,
+            });
+        }
+    }
+
+    unsafe fn set_composition(&self, composition: Option<Range<usize>>) {
+        self.state.borrow_mut().composition = composition;
+        // The input methods usually end the compositions in an edit session, but not always.
+        if self.lock.get() == 0 {
+            self.send_changes();
+        }
+    }
+
+    unsafe fn set_app_state(
+        &self,
+        surrounding_text: Option<&SurroundingText>,
+        cursor_area: Option<RECT>,
+    ) {
+        // The text can't change under the input method while it's reading or editing it.
+        if self.lock.get() != 0 {
+            return;
+        }
+        let mut state = self.state.borrow_mut();
+        let layout_changed = match (state.cursor_area, cursor_area) {
+            (Some(a), Some(b)) => {
+                (a.left, a.top, a.right, a.bottom) != (b.left, b.top, b.right, b.bottom)
+            }
+            (None, None) => false,
+            _ => true,
+        };
+        state.cursor_area = cursor_area;
+
+        // The text set during a composition doesn't have the composed text yet, so it's only
+        // taken once the composition ended.
+        let mut text_change = None;
+        let mut selection_changed = false;
+        if state.composition.is_none() {
+            let (text, selection) = match surrounding_text {
+                Some(surrounding_text) => (
+                    surrounding_text.text.clone(),
+                    surrounding_text.selection.clone(),
+                ),
+                None => (Vec::new(), 0..0),
+            };
+            state.has_surrounding_text = surrounding_text.is_some();
+            if text != state.text {
+                text_change = Some(TS_TEXTCHANGE {
+                    acpStart: 0,
+                    acpOldEnd: state.text.len() as LONG,
+                    acpNewEnd: text.len() as LONG,
+                });
+                state.text = text.clone();
+            }
+            if text_change.is_some() || selection != state.selection {
+                selection_changed = true;
+                state.selection = selection.clone();
+                state.active_end = TS_AE_END;
+            }
+            state.app_text = text;
+            state.app_selection = selection;
+        }
+        drop(state);
+
+        if let Some(change) = text_change {
+            self.notify(TS_AS_TEXT_CHANGE, |sink| {
+                ((*(*sink).lpVtbl).OnTextChange)(sink, 0, &change);
+            });
+        }
+        if selection_changed {
+            self.notify(TS_AS_SEL_CHANGE, |sink| {
+                ((*(*sink).lpVtbl).OnSelectionChange)(sink);
+            });
+        }
+        if layout_changed {
+            self.notify(TS_AS_LAYOUT_CHANGE, |sink| {
+                ((*(*sink).lpVtbl).OnLayoutChange)(sink, TS_LC_CHANGE, VIEW_COOKIE);
+            });
+        }
+    }
+}
+
+impl Drop for TextStoreData {
+    fn drop(&mut self) {
+        let sink = self.sink.get();
+        if !sink.is_null() {
+            unsafe { (*(sink as *mut IUnknown)).Release() };
+        }
+    }
+}
+
+/// The text store of a window, whose document manager gets the focus along with the window.
+pub struct TextStore {
+    data: *mut TextStoreData,
+    window: HWND,
+    document_mgr: *mut ITfDocumentMgr,
+    context: *mut ITfContext,
+    /// Whether the document manager is associated with the window, which it isn't while the
+    /// input method is off.
+    focus_associated: Cell<bool>,
+}
+
+#[allow(non_snake_case)]
+impl TextStore {
+    /// Creates the text store of the window, or returns `None` if TSF isn't available, in which
+    /// case the input methods go through IMM32.
+    pub unsafe fn new(
+        window: HWND,
+        send_event: Box<dyn Fn(Event<'static, ()>)>,
+    ) -> Option<TextStore> {
+        let (thread_mgr, client_id) = thread_mgr()?;
+        let mut document_mgr = ptr::null_mut();
+        let hr = ((*(*thread_mgr).lpVtbl).CreateDocumentMgr)(thread_mgr, &mut document_mgr);
+        if hr != S_OK || document_mgr.is_null() {
+            warn!("Failed to create the TSF document manager: {:#x}", hr);
+            return None;
+        }
+
+        let data = Box::new(TextStoreData {
+            interface: ITextStoreACP {
+                lpVtbl: &TEXT_STORE_VTBL as *const ITextStoreACPVtbl,
+            },
+            composition_sink: ITfContextOwnerCompositionSink {
+                lpVtbl: &COMPOSITION_SINK_VTBL as *const ITfContextOwnerCompositionSinkVtbl,
+            },
+            refcount: AtomicUsize::new(1),
+            window,
+            send_event,
+            sink: Cell::new(ptr::null_mut()),
+            sink_mask: Cell::new(0),
+            lock: Cell::new(0),
+            queued_lock: Cell::new(0),
+            state: RefCell::new(TextState {
+                text: Vec::new(),
+                selection: 0..0,
+                active_end: TS_AE_END,
+                composition: None,
+                app_text: Vec::new(),
+                app_selection: 0..0,
+                has_surrounding_text: false,
+                cursor_area: None,
+                preedit: ImePreedit::default(),
+            }),
+        });
+        // The text store releases what it got so far if it fails midway.
+        let mut text_store = TextStore {
+            data: Box::into_raw(data),
+            window,
+            document_mgr,
+            context: ptr::null_mut(),
+            focus_associated: Cell::new(false),
+        };
+
+        let mut edit_cookie = 0;
+        let hr = ((*(*document_mgr).lpVtbl).CreateContext)(
+            document_mgr,
+            client_id,
+            0,
+            text_store.data as *mut IUnknown,
+            &mut text_store.context,
+            &mut edit_cookie,
+        );
+        if hr != S_OK || text_store.context.is_null() {
+            warn!("Failed to create the TSF context: {:#x}", hr);
+            return None;
+        }
+        let hr = ((*(*document_mgr).lpVtbl).Push)(document_mgr, text_store.context);
+        if hr != S_OK {
+            warn!("Failed to push the TSF context: {:#x}", hr);
+            return None;
+        }
+        text_store.set_focus_associated(true);
+        Some(text_store)
+    }
+
+    /// Gives the text store the state of the input method the application set.
+    pub unsafe fn set_ime_state(
+        &self,
+        surrounding_text: Option<&SurroundingText>,
+        cursor_area: Option<RECT>,
+        purpose: ImePurpose,
+    ) {
+        // Like with IMM32, the input methods are off for the passwords.
+        self.set_focus_associated(!matches!(purpose, ImePurpose::Password | ImePurpose::Pin));
+        (*self.data).set_app_state(surrounding_text, cursor_area);
+    }
+
+    unsafe fn set_focus_associated(&self, associated: bool) {
+        if associated == self.focus_associated.get() {
+            return;
+        }
+        let (thread_mgr, _) = match thread_mgr() {
+            Some(thread_mgr) => thread_mgr,
+            None => return,
+        };
+        let document_mgr = if associated {
+            self.document_mgr
+        } else {
+            ptr::null_mut()
+        };
+        let mut previous = ptr::null_mut();
+        ((*(*thread_mgr).lpVtbl).AssociateFocus)(
+            thread_mgr,
+            self.window,
+            document_mgr,
+            &mut previous,
+        );
+        if !previous.is_null() {
+            (*(previous as *mut IUnknown)).Release();
+        }
+        self.focus_associated.set(associated);
+    }
+
+    // Implement IUnknown
+    unsafe extern "system" fn QueryInterface(
+        this: *mut IUnknown,
+        riid: REFIID,
+        ppvObject: *mut *mut c_void,
+    ) -> HRESULT {
+        if ppvObject.is_null() {
+            return E_POINTER;
+        }
+        let text_store = Self::from_interface(this);
+        let riid = &*riid;
+        let interface =
+            if IsEqualIID(riid, &IUnknown::uuidof()) || IsEqualIID(riid, &IID_ITEXT_STORE_ACP) {
+                &text_store.interface as *const _ as *mut c_void
+            } else if IsEqualIID(riid, &IID_ITF_CONTEXT_OWNER_COMPOSITION_SINK) {
+                &text_store.composition_sink as *const _ as *mut c_void
+            } else {
+                *ppvObject = ptr::null_mut();
+                return E_NOINTERFACE;
+            };
+        Self::AddRef(this);
+        *ppvObject = interface;
+        S_OK
+    }
+
+    unsafe extern "system" fn AddRef(this: *mut IUnknown) -> ULONG {
+        let text_store = Self::from_interface(this);
+        let count = text_store.refcount.fetch_add(1, Ordering::Release) + 1;
+        count as ULONG
+    }
+
+    unsafe extern "system" fn Release(this: *mut IUnknown) -> ULONG {
+        let text_store = Self::from_interface(this);
+        let count = text_store.refcount.fetch_sub(1, Ordering::Release) - 1;
+        if count == 0 {
+            // Destroy the underlying data
+            drop(Box::from_raw(
+                text_store as *const TextStoreData as *mut TextStoreData,
+            ));
+        }
+        count as ULONG
+    }
+
+    // Implement ITextStoreACP
+    unsafe extern "system" fn AdviseSink(
+        this: *mut ITextStoreACP,
+        riid: REFIID,
+        punk: *mut IUnknown,
+        dwMask: DWORD,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if punk.is_null() || !IsEqualIID(&*riid, &IID_ITEXT_STORE_ACP_SINK) {
+            return E_INVALIDARG;
+        }
+        let sink = text_store.sink.get();
+        if !sink.is_null() {
+            // Advising the same sink again only changes its mask.
+            if is_same_object(sink as *mut IUnknown, punk) {
+                text_store.sink_mask.set(dwMask);
+                return S_OK;
+            }
+            return CONNECT_E_ADVISELIMIT;
+        }
+
+        let mut sink: *mut ITextStoreACPSink = ptr::null_mut();
+        let hr = (*punk).QueryInterface(
+            &IID_ITEXT_STORE_ACP_SINK,
+            &mut sink as *mut _ as *mut *mut c_void,
+        );
+        if hr != S_OK || sink.is_null() {
+            return E_NOINTERFACE;
+        }
+        text_store.sink.set(sink);
+        text_store.sink_mask.set(dwMask);
+        S_OK
+    }
+
+    unsafe extern "system" fn UnadviseSink(
+        this: *mut ITextStoreACP,
+        punk: *mut IUnknown,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        let sink = text_store.sink.get();
+        if sink.is_null() || punk.is_null() || !is_same_object(sink as *mut IUnknown, punk) {
+            return CONNECT_E_NOCONNECTION;
+        }
+        text_store.sink.set(ptr::null_mut());
+        text_store.sink_mask.set(0);
+        (*(sink as *mut IUnknown)).Release();
+        S_OK
+    }
+
+    unsafe extern "system" fn RequestLock(
+        this: *mut ITextStoreACP,
+        dwLockFlags: DWORD,
+        phrSession: *mut HRESULT,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if text_store.sink.get().is_null() {
+            return E_FAIL;
+        }
+        if phrSession.is_null() {
+            return E_INVALIDARG;
+        }
+        let lock = dwLockFlags & TS_LF_READWRITE;
+        if text_store.lock.get() != 0 {
+            if dwLockFlags & TS_LF_SYNC != 0 {
+                *phrSession = TS_E_SYNCHRONOUS;
+            } else {
+                // The queued lock is granted once the current one is released, and is for
+                // writing if any of the requests is.
+                text_store
+                    .queued_lock
+                    .set(text_store.queued_lock.get() | lock);
+                *phrSession = TS_S_ASYNC;
+            }
+            return S_OK;
+        }
+
+        *phrSession = text_store.grant_lock(lock);
+        while text_store.queued_lock.get() != 0 {
+            let lock = text_store.queued_lock.replace(0);
+            text_store.grant_lock(lock);
+        }
+        text_store.send_changes();
+        S_OK
+    }
+
+    unsafe extern "system" fn GetStatus(
+        _this: *mut ITextStoreACP,
+        pdcs: *mut TS_STATUS,
+    ) -> HRESULT {
+        if pdcs.is_null() {
+            return E_INVALIDARG;
+        }
+        *pdcs = TS_STATUS {
+            dwDynamicFlags: 0,
+            dwStaticFlags: TS_SS_NOHIDDENTEXT,
+        };
+        S_OK
+    }
+
+    unsafe extern "system" fn QueryInsert(
+        this: *mut ITextStoreACP,
+        acpTestStart: LONG,
+        acpTestEnd: LONG,
+        _cch: ULONG,
+        pacpResultStart: *mut LONG,
+        pacpResultEnd: *mut LONG,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if pacpResultStart.is_null() || pacpResultEnd.is_null() {
+            return E_INVALIDARG;
+        }
+        if !text_store.state.borrow().contains(acpTestStart, acpTestEnd) {
+            return E_INVALIDARG;
+        }
+        // The text can be inserted anywhere.
+        *pacpResultStart = acpTestStart;
+        *pacpResultEnd = acpTestEnd;
+        S_OK
+    }
+
+    unsafe extern "system" fn GetSelection(
+        this: *mut ITextStoreACP,
+        ulIndex: ULONG,
+        ulCount: ULONG,
+        pSelection: *mut TS_SELECTION_ACP,
+        pcFetched: *mut ULONG,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if !text_store.has_lock(TS_LF_READ) {
+            return TS_E_NOLOCK;
+        }
+        if pcFetched.is_null() || (ulCount > 0 && pSelection.is_null()) {
+            return E_INVALIDARG;
+        }
+        *pcFetched = 0;
+        // There is only one selection.
+        if ulIndex != 0 && ulIndex != TS_DEFAULT_SELECTION {
+            return E_INVALIDARG;
+        }
+        if ulCount == 0 {
+            return S_OK;
+        }
+        let state = text_store.state.borrow();
+        *pSelection = TS_SELECTION_ACP {
+            acpStart: state.selection.start as LONG,
+            acpEnd: state.selection.end as LONG,
+            style: TS_SELECTIONSTYLE {
+                ase: state.active_end,
+                fInterimChar: FALSE,
+            },
+        };
+        *pcFetched = 1;
+        S_OK
+    }
+
+    unsafe extern "system" fn SetSelection(
+        this: *mut ITextStoreACP,
+        ulCount: ULONG,
+        pSelection: *const TS_SELECTION_ACP,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if !text_store.has_lock(TS_LF_READWRITE) {
+            return TS_E_NOLOCK;
+        }
+        if ulCount != 1 || pSelection.is_null() {
+            return E_INVALIDARG;
+        }
+        let selection = *pSelection;
+        let mut state = text_store.state.borrow_mut();
+        if !state.contains(selection.acpStart, selection.acpEnd) {
+            return TS_E_INVALIDPOS;
+        }
+        state.selection = selection.acpStart as usize..selection.acpEnd as usize;
+        state.active_end = selection.style.ase;
+        S_OK
+    }
+
+    unsafe extern "system" fn GetText(
+        this: *mut ITextStoreACP,
+        acpStart: LONG,
+        acpEnd: LONG,
+        pchPlain: *mut WCHAR,
+        cchPlainReq: ULONG,
+        pcchPlainRet: *mut ULONG,
+        prgRunInfo: *mut TS_RUNINFO,
+        cRunInfoReq: ULONG,
+        pcRunInfoRet: *mut ULONG,
+        pacpNext: *mut LONG,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if !text_store.has_lock(TS_LF_READ) {
+            return TS_E_NOLOCK;
+        }
+        if pcchPlainRet.is_null() || pcRunInfoRet.is_null() || pacpNext.is_null() {
+            return E_INVALIDARG;
+        }
+        let state = text_store.state.borrow();
+        // An end of -1 is the end of the text.
+        let acpEnd = if acpEnd == -1 {
+            state.text.len() as LONG
+        } else {
+            acpEnd
+        };
+        if !state.contains(acpStart, acpEnd) {
+            return TS_E_INVALIDPOS;
+        }
+
+        let start = acpStart as usize;
+        let len = (acpEnd as usize - start).min(cchPlainReq as usize);
+        if !pchPlain.is_null() {
+            ptr::copy_nonoverlapping(state.text[start..].as_ptr(), pchPlain, len);
+        }
+        *pcchPlainRet = len as ULONG;
+        *pcRunInfoRet = 0;
+        if len > 0 && cRunInfoReq > 0 && !prgRunInfo.is_null() {
+            *prgRunInfo = TS_RUNINFO {
+                uCount: len as ULONG,
+                type_: TS_RT_PLAIN,
+            };
+            *pcRunInfoRet = 1;
+        }
+        *pacpNext = (start + len) as LONG;
+        S_OK
+    }
+
+    unsafe extern "system" fn SetText(
+        this: *mut ITextStoreACP,
+        _dwFlags: DWORD,
+        acpStart: LONG,
+        acpEnd: LONG,
+        pchText: *const WCHAR,
+        cch: ULONG,
+        pChange: *mut TS_TEXTCHANGE,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if !text_store.has_lock(TS_LF_READWRITE) {
+            return TS_E_NOLOCK;
+        }
+        if pChange.is_null() || (cch > 0 && pchText.is_null()) {
+            return E_INVALIDARG;
+        }
+        let mut state = text_store.state.borrow_mut();
+        if !state.contains(acpStart, acpEnd) {
+            return TS_E_INVALIDPOS;
+        }
+        let text = if cch > 0 {
+            slice::from_raw_parts(pchText, cch as usize)
+        } else {
+            &[]
+        };
+        *pChange = state.replace(acpStart as usize..acpEnd as usize, text);
+        S_OK
+    }
+
+    unsafe extern "system" fn GetFormattedText(
+        _this: *mut ITextStoreACP,
+        _acpStart: LONG,
+        _acpEnd: LONG,
+        _ppDataObject: *mut *mut IDataObject,
+    ) -> HRESULT {
+        E_NOTIMPL
+    }
+
+    unsafe extern "system" fn GetEmbedded(
+        _this: *mut ITextStoreACP,
+        _acpPos: LONG,
+        _rguidService: REFGUID,
+        _riid: REFIID,
+        _ppunk: *mut *mut IUnknown,
+    ) -> HRESULT {
+        E_NOTIMPL
+    }
+
+    unsafe extern "system" fn QueryInsertEmbedded(
+        _this: *mut ITextStoreACP,
+        _pguidService: *const GUID,
+        _pFormatEtc: *const FORMATETC,
+        pfInsertable: *mut BOOL,
+    ) -> HRESULT {
+        if pfInsertable.is_null() {
+            return E_INVALIDARG;
+        }
+        // The text store only holds text.
+        *pfInsertable = FALSE;
+        S_OK
+    }
+
+    unsafe extern "system" fn InsertEmbedded(
+        _this: *mut ITextStoreACP,
+        _dwFlags: DWORD,
+        _acpStart: LONG,
+        _acpEnd: LONG,
+        _pDataObject: *mut IDataObject,
+        _pChange: *mut TS_TEXTCHANGE,
+    ) -> HRESULT {
+        E_NOTIMPL
+    }
+
+    // The text has no attributes.
+    unsafe extern "system" fn RequestSupportedAttrs(
+        _this: *mut ITextStoreACP,
+        _dwFlags: DWORD,
+        _cFilterAttrs: ULONG,
+        _paFilterAttrs: *const GUID,
+    ) -> HRESULT {
+        S_OK
+    }
+
+    unsafe extern "system" fn RequestAttrsAtPosition(
+        _this: *mut ITextStoreACP,
+        _acpPos: LONG,
+        _cFilterAttrs: ULONG,
+        _paFilterAttrs: *const GUID,
+        _dwFlags: DWORD,
+    ) -> HRESULT {
+        S_OK
+    }
+
+    unsafe extern "system" fn FindNextAttrTransition(
+        _this: *mut ITextStoreACP,
+        _acpStart: LONG,
+        acpHalt: LONG,
+        _cFilterAttrs: ULONG,
+        _paFilterAttrs: *const GUID,
+        _dwFlags: DWORD,
+        pacpNext: *mut LONG,
+        pfFound: *mut BOOL,
+        plFoundOffset: *mut LONG,
+    ) -> HRESULT {
+        if pacpNext.is_null() || pfFound.is_null() || plFoundOffset.is_null() {
+            return E_INVALIDARG;
+        }
+        *pacpNext = acpHalt;
+        *pfFound = FALSE;
+        *plFoundOffset = 0;
+        S_OK
+    }
+
+    unsafe extern "system" fn RetrieveRequestedAttrs(
+        _this: *mut ITextStoreACP,
+        _ulCount: ULONG,
+        _paAttrVals: *mut c_void,
+        pcFetched: *mut ULONG,
+    ) -> HRESULT {
+        if pcFetched.is_null() {
+            return E_INVALIDARG;
+        }
+        *pcFetched = 0;
+        S_OK
+    }
+
+    unsafe extern "system" fn GetEndACP(this: *mut ITextStoreACP, pacp: *mut LONG) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if !text_store.has_lock(TS_LF_READ) {
+            return TS_E_NOLOCK;
+        }
+        if pacp.is_null() {
+            return E_INVALIDARG;
+        }
+        *pacp = text_store.state.borrow().text.len() as LONG;
+        S_OK
+    }
+
+    unsafe extern "system" fn GetActiveView(
+        _this: *mut ITextStoreACP,
+        pvcView: *mut TsViewCookie,
+    ) -> HRESULT {
+        if pvcView.is_null() {
+            return E_INVALIDARG;
+        }
+        *pvcView = VIEW_COOKIE;
+        S_OK
+    }
+
+    unsafe extern "system" fn GetACPFromPoint(
+        _this: *mut ITextStoreACP,
+        _vcView: TsViewCookie,
+        _ptScreen: *const POINT,
+        _dwFlags: DWORD,
+        _pacp: *mut LONG,
+    ) -> HRESULT {
+        // The application doesn't tell where the characters are drawn.
+        E_NOTIMPL
+    }
+
+    unsafe extern "system" fn GetTextExt(
+        this: *mut ITextStoreACP,
+        _vcView: TsViewCookie,
+        _acpStart: LONG,
+        _acpEnd: LONG,
+        prc: *mut RECT,
+        pfClipped: *mut BOOL,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if !text_store.has_lock(TS_LF_READ) {
+            return TS_E_NOLOCK;
+        }
+        if prc.is_null() || pfClipped.is_null() {
+            return E_INVALIDARG;
+        }
+        // All of the text is at the cursor area, as the application only tells where that is.
+        let cursor_area = text_store.state.borrow().cursor_area.unwrap_or(RECT {
+            left: 0,
+            top: 0,
+            right: 0,
+            bottom: 0,
+        });
+        *prc = client_to_screen(text_store.window, cursor_area);
+        *pfClipped = FALSE;
+        S_OK
+    }
+
+    unsafe extern "system" fn GetScreenExt(
+        this: *mut ITextStoreACP,
+        _vcView: TsViewCookie,
+        prc: *mut RECT,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if prc.is_null() {
+            return E_INVALIDARG;
+        }
+        let mut client_rect: RECT = mem::zeroed();
+        winuser::GetClientRect(text_store.window, &mut client_rect);
+        *prc = client_to_screen(text_store.window, client_rect);
+        S_OK
+    }
+
+    unsafe extern "system" fn GetWnd(
+        this: *mut ITextStoreACP,
+        _vcView: TsViewCookie,
+        phwnd: *mut HWND,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if phwnd.is_null() {
+            return E_INVALIDARG;
+        }
+        *phwnd = text_store.window;
+        S_OK
+    }
+
+    unsafe extern "system" fn InsertTextAtSelection(
+        this: *mut ITextStoreACP,
+        dwFlags: DWORD,
+        pchText: *const WCHAR,
+        cch: ULONG,
+        pacpStart: *mut LONG,
+        pacpEnd: *mut LONG,
+        pChange: *mut TS_TEXTCHANGE,
+    ) -> HRESULT {
+        let text_store = Self::from_interface(this);
+        if dwFlags & TS_IAS_QUERYONLY != 0 {
+            if !text_store.has_lock(TS_LF_READ) {
+                return TS_E_NOLOCK;
+            }
+            let selection = text_store.state.borrow().selection.clone();
+            if !pacpStart.is_null() {
+                *pacpStart = selection.start as LONG;
+            }
+            if !pacpEnd.is_null() {
+                *pacpEnd = selection.end as LONG;
+            }
+            return S_OK;
+        }
+
+        if !text_store.has_lock(TS_LF_READWRITE) {
+            return TS_E_NOLOCK;
+        }
+        if cch > 0 && pchText.is_null() {
+            return E_INVALIDARG;
+        }
+        let text = if cch > 0 {
+            slice::from_raw_parts(pchText, cch as usize)
+        } else {
+            &[]
+        };
+        let mut state = text_store.state.borrow_mut();
+        let selection = state.selection.clone();
+        let change = state.replace(selection, text);
+        if dwFlags & TS_IAS_NOQUERY == 0 {
+            if !pacpStart.is_null() {
+                *pacpStart = change.acpStart;
+            }
+            if !pacpEnd.is_null() {
+                *pacpEnd = change.acpNewEnd;
+            }
+        }
+        if !pChange.is_null() {
+            *pChange = change;
+        }
+        S_OK
+    }
+
+    unsafe extern "system" fn InsertEmbeddedAtSelection(
+        _this: *mut ITextStoreACP,
+        _dwFlags: DWORD,
+        _pDataObject: *mut IDataObject,
+        _pacpStart: *mut LONG,
+        _pacpEnd: *mut LONG,
+        _pChange: *mut TS_TEXTCHANGE,
+    ) -> HRESULT {
+        E_NOTIMPL
+    }
+
+    unsafe fn from_interface<'a, InterfaceT>(this: *mut InterfaceT) -> &'a TextStoreData {
+        &*(this as *const TextStoreData)
+    }
+}
+
+impl Drop for TextStore {
+    fn drop(&mut self) {
+        unsafe {
+            self.set_focus_associated(false);
+            ((*(*self.document_mgr).lpVtbl).Pop)(self.document_mgr, TF_POPF_ALL);
+            if !self.context.is_null() {
+                (*(self.context as *mut IUnknown)).Release();
+            }
+            (*(self.document_mgr as *mut IUnknown)).Release();
+            TextStore::Release(self.data as *mut IUnknown);
+        }
+    }
+}
+
+/// The `ITfContextOwnerCompositionSink` of the text store, which follows the composition.
+struct CompositionSink;
+
+#[allow(non_snake_case)]
+impl CompositionSink {
+    // Implement IUnknown
+    unsafe extern "system" fn QueryInterface(
+        this: *mut IUnknown,
+        riid: REFIID,
+        ppvObject: *mut *mut c_void,
+    ) -> HRESULT {
+        TextStore::QueryInterface(Self::text_store_interface(this), riid, ppvObject)
+    }
+
+    unsafe extern "system" fn AddRef(this: *mut IUnknown) -> ULONG {
+        TextStore::AddRef(Self::text_store_interface(this))
+    }
+
+    unsafe extern "system" fn Release(this: *mut IUnknown) -> ULONG {
+        TextStore::Release(Self::text_store_interface(this))
+    }
+
+    // Implement ITfContextOwnerCompositionSink
+    unsafe extern "system" fn OnStartComposition(
+        this: *mut ITfContextOwnerCompositionSink,
+        pComposition: *mut ITfCompositionView,
+        pfOk: *mut BOOL,
+    ) -> HRESULT {
+        if pfOk.is_null() {
+            return E_INVALIDARG;
+        }
+        *pfOk = TRUE;
+        let text_store = TextStore::from_interface(Self::text_store_interface(this));
+        text_store.set_composition(Some(Self::range_or_caret(
+            text_store,
+            composition_range(pComposition, ptr::null_mut()),
+        )));
+        S_OK
+    }
+
+    unsafe extern "system" fn OnUpdateComposition(
+        this: *mut ITfContextOwnerCompositionSink,
+        pComposition: *mut ITfCompositionView,
+        pRangeNew: *mut ITfRange,
+    ) -> HRESULT {
+        let text_store = TextStore::from_interface(Self::text_store_interface(this));
+        text_store.set_composition(Some(Self::range_or_caret(
+            text_store,
+            composition_range(pComposition, pRangeNew),
+        )));
+        S_OK
+    }
+
+    unsafe extern "system" fn OnEndComposition(
+        this: *mut ITfContextOwnerCompositionSink,
+        _pComposition: *mut ITfCompositionView,
+    ) -> HRESULT {
+        let text_store = TextStore::from_interface(Self::text_store_interface(this));
+        text_store.set_composition(None);
+        S_OK
+    }
+
+    /// The range of the composition, or the caret if it can't be read.
+    fn range_or_caret(text_store: &TextStoreData, range: Option<Range<usize>>) -> Range<usize> {
+        range.unwrap_or_else(|| {
+            let caret = text_store.state.borrow().selection.end;
+            caret..caret
+        })
+    }
+
+    /// The `ITextStoreACP` of the text store, which the sink follows in its data.
+    unsafe fn text_store_interface<InterfaceT>(this: *mut InterfaceT) -> *mut IUnknown {
+        (this as *mut u8).sub(mem::size_of::<ITextStoreACP>()) as *mut IUnknown
+    }
+}
+
+static TEXT_STORE_VTBL: ITextStoreACPVtbl = ITextStoreACPVtbl {
+    parent: IUnknownVtbl {
+        QueryInterface: TextStore::QueryInterface,
+        AddRef: TextStore::AddRef,
+        Release: TextStore::Release,
+    },
+    AdviseSink: TextStore::AdviseSink,
+    UnadviseSink: TextStore::UnadviseSink,
+    RequestLock: TextStore::RequestLock,
+    GetStatus: TextStore::GetStatus,
+    QueryInsert: TextStore::QueryInsert,
+    GetSelection: TextStore::GetSelection,
+    SetSelection: TextStore::SetSelection,
+    GetText: TextStore::GetText,
+    SetText: TextStore::SetText,
+    GetFormattedText: TextStore::GetFormattedText,
+    GetEmbedded: TextStore::GetEmbedded,
+    QueryInsertEmbedded: TextStore::QueryInsertEmbedded,
+    InsertEmbedded: TextStore::InsertEmbedded,
+    RequestSupportedAttrs: TextStore::RequestSupportedAttrs,
+    RequestAttrsAtPosition: TextStore::RequestAttrsAtPosition,
+    RequestAttrsTransitioningAtPosition: TextStore::RequestAttrsAtPosition,
+    FindNextAttrTransition: TextStore::FindNextAttrTransition,
+    RetrieveRequestedAttrs: TextStore::RetrieveRequestedAttrs,
+    GetEndACP: TextStore::GetEndACP,
+    GetActiveView: TextStore::GetActiveView,
+    GetACPFromPoint: TextStore::GetACPFromPoint,
+    GetTextExt: TextStore::GetTextExt,
+    GetScreenExt: TextStore::GetScreenExt,
+    GetWnd: TextStore::GetWnd,
+    InsertTextAtSelection: TextStore::InsertTextAtSelection,
+    InsertEmbeddedAtSelection: TextStore::InsertEmbeddedAtSelection,
+};
+
+static COMPOSITION_SINK_VTBL: ITfContextOwnerCompositionSinkVtbl =
+    ITfContextOwnerCompositionSinkVtbl {
+        parent: IUnknownVtbl {
+            QueryInterface: CompositionSink::QueryInterface,
+            AddRef: CompositionSink::AddRef,
+            Release: CompositionSink::Release,
+        },
+        OnStartComposition: CompositionSink::OnStartComposition,
+        OnUpdateComposition: CompositionSink::OnUpdateComposition,
+        OnEndComposition: CompositionSink::OnEndComposition,
+    };
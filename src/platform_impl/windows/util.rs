@@ -6,18 +6,21 @@ use std::{
     sync::atomic::{AtomicBool, Ordering},
 };
 
-use crate::{dpi::PhysicalSize, window::CursorIcon};
+use crate::{dpi::PhysicalSize, event::AxisSource, window::CursorIcon};
 use winapi::{
-    ctypes::wchar_t,
+    ctypes::{c_int, wchar_t},
     shared::{
+        basetsd::SIZE_T,
         minwindef::{BOOL, DWORD, UINT},
         windef::{DPI_AWARENESS_CONTEXT, HMONITOR, HWND, LPRECT, RECT},
+        winerror::S_OK,
     },
     um::{
+        dwmapi,
         libloaderapi::{GetProcAddress, LoadLibraryA},
         shellscalingapi::{MONITOR_DPI_TYPE, PROCESS_DPI_AWARENESS},
         winbase::lstrlenW,
-        winnt::{HRESULT, LONG, LPCSTR},
+        winnt::{HRESULT, LONG, LPCSTR, PVOID},
         winuser,
     },
 };
@@ -139,18 +142,45 @@ pub fn adjust_window_rect_with_styles(
             *r = rect;
 
             let b_menu = !winuser::GetMenu(hwnd).is_null() as BOOL;
-            if let (Some(get_dpi_for_window), Some(adjust_window_rect_ex_for_dpi)) =
+            let status = if let (Some(get_dpi_for_window), Some(adjust_window_rect_ex_for_dpi)) =
                 (*GET_DPI_FOR_WINDOW, *ADJUST_WINDOW_RECT_EX_FOR_DPI)
             {
                 let dpi = get_dpi_for_window(hwnd);
                 adjust_window_rect_ex_for_dpi(r, style as _, b_menu, style_ex as _, dpi)
             } else {
                 winuser::AdjustWindowRectEx(r, style as _, b_menu, style_ex as _)
+            };
+
+            // The client area extended over the title bar starts at the top of the window.
+            if has_flag(style, winuser::WS_CAPTION) && is_titlebar_extended(hwnd) {
+                r.top = rect.top;
             }
+            status
         })
     }
 }
 
+lazy_static! {
+    static ref TITLEBAR_EXTENDED_PROP: Vec<u16> =
+        "WinitTitlebarExtended\0".encode_utf16().collect();
+}
+
+/// Marks the window as having its client area extended over its title bar, which is read by
+/// `WM_NCCALCSIZE` and when adjusting the window rects.
+pub fn set_titlebar_extended(hwnd: HWND, extended: bool) {
+    unsafe {
+        if extended {
+            winuser::SetPropW(hwnd, TITLEBAR_EXTENDED_PROP.as_ptr(), 1 as _);
+        } else {
+            winuser::RemovePropW(hwnd, TITLEBAR_EXTENDED_PROP.as_ptr());
+        }
+    }
+}
+
+pub fn is_titlebar_extended(hwnd: HWND) -> bool {
+    unsafe { !winuser::GetPropW(hwnd, TITLEBAR_EXTENDED_PROP.as_ptr()).is_null() }
+}
+
 pub fn set_cursor_hidden(hidden: bool) {
     static HIDDEN: AtomicBool = AtomicBool::new(false);
     let changed = HIDDEN.swap(hidden, Ordering::SeqCst) ^ hidden;
@@ -196,6 +226,39 @@ pub fn is_focused(window: HWND) -> bool {
     window == unsafe { winuser::GetActiveWindow() }
 }
 
+/// Whether the window can't be seen, as it's minimized, or cloaked by DWM, such as when it's on
+/// another virtual desktop.
+pub fn is_occluded(window: HWND) -> bool {
+    unsafe {
+        let mut cloaked: DWORD = 0;
+        let status = dwmapi::DwmGetWindowAttribute(
+            window,
+            dwmapi::DWMWA_CLOAKED,
+            &mut cloaked as *mut DWORD as *mut c_void,
+            mem::size_of::<DWORD>() as DWORD,
+        );
+        winuser::IsIconic(window) != 0 || (status == S_OK && cloaked != 0)
+    }
+}
+
+/// The kind of device the wheel message being handled came from.
+pub fn wheel_source() -> Option<AxisSource> {
+    let get_current_input_message_source = (*GET_CURRENT_INPUT_MESSAGE_SOURCE)?;
+    let mut source = INPUT_MESSAGE_SOURCE {
+        deviceType: 0,
+        originId: 0,
+    };
+    if unsafe { get_current_input_message_source(&mut source) } == 0 {
+        return None;
+    }
+    match source.deviceType {
+        IMDT_MOUSE => Some(AxisSource::Wheel),
+        // The precision touchpads scroll with wheel messages too.
+        IMDT_TOUCHPAD => Some(AxisSource::Finger),
+        _ => None,
+    }
+}
+
 impl CursorIcon {
     pub(crate) fn to_windows_cursor(self) -> *const wchar_t {
         match self {
@@ -249,6 +312,16 @@ pub(super) fn get_function_impl(library: &str, function: &str) -> Option<*const
     Some(function_ptr as _)
 }
 
+#[allow(non_snake_case)]
+#[repr(C)]
+pub struct INPUT_MESSAGE_SOURCE {
+    deviceType: DWORD,
+    originId: DWORD,
+}
+
+const IMDT_MOUSE: DWORD = 0x0002;
+const IMDT_TOUCHPAD: DWORD = 0x0010;
+
 macro_rules! get_function {
     ($lib:expr, $func:ident) => {
         crate::platform_impl::platform::util::get_function_impl(
@@ -259,6 +332,14 @@ macro_rules! get_function {
     };
 }
 
+#[allow(non_snake_case)]
+#[repr(C)]
+pub struct WINDOWCOMPOSITIONATTRIBDATA {
+    Attrib: DWORD,
+    pvData: PVOID,
+    cbData: SIZE_T,
+}
+
 pub type SetProcessDPIAware = unsafe extern "system" fn() -> BOOL;
 pub type SetProcessDpiAwareness =
     unsafe extern "system" fn(value: PROCESS_DPI_AWARENESS) -> HRESULT;
@@ -272,6 +353,11 @@ pub type GetDpiForMonitor = unsafe extern "system" fn(
     dpi_y: *mut UINT,
 ) -> HRESULT;
 pub type EnableNonClientDpiScaling = unsafe extern "system" fn(hwnd: HWND) -> BOOL;
+pub type GetCurrentInputMessageSource =
+    unsafe extern "system" fn(source: *mut INPUT_MESSAGE_SOURCE) -> BOOL;
+pub type SetWindowCompositionAttribute =
+    unsafe extern "system" fn(hwnd: HWND, data: *mut WINDOWCOMPOSITIONATTRIBDATA) -> BOOL;
+pub type GetSystemMetricsForDpi = unsafe extern "system" fn(index: c_int, dpi: UINT) -> c_int;
 pub type AdjustWindowRectExForDpi = unsafe extern "system" fn(
     rect: LPRECT,
     dwStyle: DWORD,
@@ -289,10 +375,32 @@ lazy_static! {
         get_function!("shcore.dll", GetDpiForMonitor);
     pub static ref ENABLE_NON_CLIENT_DPI_SCALING: Option<EnableNonClientDpiScaling> =
         get_function!("user32.dll", EnableNonClientDpiScaling);
+    pub static ref GET_CURRENT_INPUT_MESSAGE_SOURCE: Option<GetCurrentInputMessageSource> =
+        get_function!("user32.dll", GetCurrentInputMessageSource);
+    pub static ref GET_SYSTEM_METRICS_FOR_DPI: Option<GetSystemMetricsForDpi> =
+        get_function!("user32.dll", GetSystemMetricsForDpi);
     pub static ref SET_PROCESS_DPI_AWARENESS_CONTEXT: Option<SetProcessDpiAwarenessContext> =
         get_function!("user32.dll", SetProcessDpiAwarenessContext);
     pub static ref SET_PROCESS_DPI_AWARENESS: Option<SetProcessDpiAwareness> =
         get_function!("shcore.dll", SetProcessDpiAwareness);
     pub static ref SET_PROCESS_DPI_AWARE: Option<SetProcessDPIAware> =
         get_function!("user32.dll", SetProcessDPIAware);
+    pub static ref SET_WINDOW_COMPOSITION_ATTRIBUTE: Option<SetWindowCompositionAttribute> =
+        get_function!("user32.dll", SetWindowCompositionAttribute);
+}
+
+/// Sets the attribute of the window with the undocumented `SetWindowCompositionAttribute`,
+/// returning whether it succeeded.
+pub unsafe fn set_window_composition_attribute<T>(hwnd: HWND, attrib: DWORD, data: &mut T) -> bool {
+    match *SET_WINDOW_COMPOSITION_ATTRIBUTE {
+        Some(set_window_composition_attribute) => {
+            let mut data = WINDOWCOMPOSITIONATTRIBDATA {
+                Attrib: attrib,
+                pvData: data as *mut T as _,
+                cbData: mem::size_of::<T>() as _,
+            };
+            set_window_composition_attribute(hwnd, &mut data) != 0
+        }
+        None => false,
+    }
 }
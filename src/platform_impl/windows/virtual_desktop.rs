@@ -0,0 +1,173 @@
+//! The virtual desktops of the windows through `IVirtualDesktopManager`, which only knows the
+//! identifiers of the desktops. Their order is the one of the list Explorer keeps in the
+//! registry, which is missing until the user adds a second desktop.
+
+use std::{cell::Cell, ffi::OsStr, mem, os::windows::ffi::OsStrExt, ptr};
+
+use winapi::{
+    shared::{
+        guiddef::{IsEqualGUID, GUID, REFGUID},
+        minwindef::DWORD,
+        windef::HWND,
+        winerror::{ERROR_SUCCESS, S_OK},
+    },
+    um::{combaseapi, unknwnbase::IUnknownVtbl, winnt::HRESULT, winreg},
+};
+
+use crate::platform_impl::platform::window;
+
+const CLSID_VIRTUAL_DESKTOP_MANAGER: GUID = GUID {
+    Data1: 0xAA509086,
+    Data2: 0x5CA9,
+    Data3: 0x4C25,
+    Data4: [0x8F, 0x95, 0x58, 0x9D, 0x3C, 0x07, 0xB4, 0x8A],
+};
+const IID_IVIRTUAL_DESKTOP_MANAGER: GUID = GUID {
+    Data1: 0xA5CD92FF,
+    Data2: 0x29BE,
+    Data3: 0x454C,
+    Data4: [0x8D, 0x04, 0xD8, 0x28, 0x79, 0xFB, 0x3F, 0x1B],
+};
+
+const VIRTUAL_DESKTOPS_KEY: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\VirtualDesktops";
+const VIRTUAL_DESKTOP_IDS_VALUE: &str = "VirtualDesktopIDs";
+
+#[allow(non_snake_case)]
+#[repr(C)]
+struct IVirtualDesktopManagerVtbl {
+    parent: IUnknownVtbl,
+    // `IsWindowOnCurrentVirtualDesktop`, which isn't used.
+    _IsWindowOnCurrentVirtualDesktop: usize,
+    GetWindowDesktopId: unsafe extern "system" fn(
+        This: *mut IVirtualDesktopManager,
+        topLevelWindow: HWND,
+        desktopId: *mut GUID,
+    ) -> HRESULT,
+    MoveWindowToDesktop: unsafe extern "system" fn(
+        This: *mut IVirtualDesktopManager,
+        topLevelWindow: HWND,
+        desktopId: REFGUID,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct IVirtualDesktopManager {
+    lpVtbl: *const IVirtualDesktopManagerVtbl,
+}
+
+thread_local! {
+    static VIRTUAL_DESKTOP_MANAGER: Cell<*mut IVirtualDesktopManager> = Cell::new(ptr::null_mut());
+}
+
+/// The index of the virtual desktop of the window, if it's known.
+pub fn desktop_index(window: HWND) -> Option<u32> {
+    let id = unsafe {
+        with_manager(|manager| {
+            let mut id: GUID = mem::zeroed();
+            let hr = ((*(*manager).lpVtbl).GetWindowDesktopId)(manager, window, &mut id);
+            // The windows which were never shown aren't on a desktop yet.
+            if hr == S_OK && !IsEqualGUID(&id, &mem::zeroed()) {
+                Some(id)
+            } else {
+                None
+            }
+        })??
+    };
+
+    let ids = desktop_ids();
+    if ids.is_empty() {
+        // There's only ever been a single desktop.
+        return Some(0);
+    }
+    ids.iter()
+        .position(|desktop| IsEqualGUID(desktop, &id))
+        .map(|index| index as u32)
+}
+
+/// Moves the window to the virtual desktop of the index, returning whether it moved.
+pub fn move_to_desktop(window: HWND, index: u32) -> bool {
+    let id = match desktop_ids().get(index as usize) {
+        Some(id) => *id,
+        None => return false,
+    };
+    unsafe {
+        with_manager(|manager| {
+            ((*(*manager).lpVtbl).MoveWindowToDesktop)(manager, window, &id) == S_OK
+        })
+        .unwrap_or(false)
+    }
+}
+
+/// The identifiers of the virtual desktops, in their order.
+fn desktop_ids() -> Vec<GUID> {
+    let key: Vec<u16> = OsStr::new(VIRTUAL_DESKTOPS_KEY)
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    let value: Vec<u16> = OsStr::new(VIRTUAL_DESKTOP_IDS_VALUE)
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+
+    unsafe {
+        let mut len: DWORD = 0;
+        let status = winreg::RegGetValueW(
+            winreg::HKEY_CURRENT_USER,
+            key.as_ptr(),
+            value.as_ptr(),
+            winreg::RRF_RT_REG_BINARY,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut len,
+        );
+        if status != ERROR_SUCCESS as _ || len == 0 {
+            return Vec::new();
+        }
+        let mut ids = vec![0u8; len as usize];
+        let status = winreg::RegGetValueW(
+            winreg::HKEY_CURRENT_USER,
+            key.as_ptr(),
+            value.as_ptr(),
+            winreg::RRF_RT_REG_BINARY,
+            ptr::null_mut(),
+            ids.as_mut_ptr() as *mut _,
+            &mut len,
+        );
+        if status != ERROR_SUCCESS as _ {
+            return Vec::new();
+        }
+        ids.truncate(len as usize);
+
+        // The identifiers are stored back to back, in the layout of `GUID`.
+        ids.chunks_exact(mem::size_of::<GUID>())
+            .map(|id| ptr::read_unaligned(id.as_ptr() as *const GUID))
+            .collect()
+    }
+}
+
+unsafe fn with_manager<R, F: FnOnce(*mut IVirtualDesktopManager) -> R>(f: F) -> Option<R> {
+    window::com_initialized();
+
+    VIRTUAL_DESKTOP_MANAGER.with(|manager_ptr| {
+        let mut manager = manager_ptr.get();
+
+        if manager.is_null() {
+            let hr = combaseapi::CoCreateInstance(
+                &CLSID_VIRTUAL_DESKTOP_MANAGER,
+                ptr::null_mut(),
+                combaseapi::CLSCTX_ALL,
+                &IID_IVIRTUAL_DESKTOP_MANAGER,
+                &mut manager as *mut _ as *mut _,
+            );
+            if hr != S_OK || manager.is_null() {
+                // The virtual desktops are only there since Windows 10.
+                debug!("Failed to create the virtual desktop manager: {:#x}", hr);
+                return None;
+            }
+            manager_ptr.set(manager);
+        }
+
+        Some(f(manager))
+    })
+}
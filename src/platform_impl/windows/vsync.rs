@@ -0,0 +1,55 @@
+//! The redraws synchronized with the composition of the desktop, which are held until a thread
+//! waiting on `DwmFlush` releases them after the next composition.
+
+use std::{mem, ptr, thread};
+
+use parking_lot::{Condvar, Mutex};
+use winapi::{
+    shared::windef::HWND,
+    um::{dwmapi, winuser},
+};
+
+lazy_static! {
+    /// The windows whose redraws are held, as `HWND`s aren't `Send`.
+    static ref HELD_REDRAWS: Mutex<Vec<usize>> = {
+        thread::spawn(vsync_thread);
+        Mutex::new(Vec::new())
+    };
+    static ref REDRAWS_HELD: Condvar = Condvar::new();
+}
+
+/// Holds the redraw of the window until the next composition of the desktop.
+pub fn hold_redraw(window: HWND) {
+    let mut held_redraws = HELD_REDRAWS.lock();
+    if !held_redraws.contains(&(window as usize)) {
+        held_redraws.push(window as usize);
+    }
+    REDRAWS_HELD.notify_one();
+}
+
+fn vsync_thread() {
+    loop {
+        {
+            let mut held_redraws = HELD_REDRAWS.lock();
+            while held_redraws.is_empty() {
+                REDRAWS_HELD.wait(&mut held_redraws);
+            }
+        }
+
+        // This fails right away while the composition is off, the redraws being released as
+        // soon as they're requested.
+        unsafe { dwmapi::DwmFlush() };
+
+        let held_redraws = mem::take(&mut *HELD_REDRAWS.lock());
+        for window in held_redraws {
+            unsafe {
+                winuser::RedrawWindow(
+                    window as HWND,
+                    ptr::null(),
+                    ptr::null_mut(),
+                    winuser::RDW_INTERNALPAINT,
+                );
+            }
+        }
+    }
+}
@@ -14,39 +14,54 @@ use std::{
 use winapi::{
     ctypes::c_int,
     shared::{
-        minwindef::{HINSTANCE, LPARAM, UINT, WPARAM},
+        minwindef::{BOOL, DWORD, HINSTANCE, LPARAM, TRUE, UINT, WPARAM},
         windef::{HWND, POINT, POINTS, RECT},
+        winerror::{DRAGDROP_S_CANCEL, DRAGDROP_S_DROP},
     },
     um::{
-        combaseapi, dwmapi,
-        imm::{CFS_POINT, COMPOSITIONFORM},
-        libloaderapi,
+        combaseapi, dwmapi, libloaderapi,
         objbase::COINIT_APARTMENTTHREADED,
+        objidl::IDataObject,
         ole2,
-        oleidl::LPDROPTARGET,
-        shobjidl_core::{CLSID_TaskbarList, ITaskbarList2},
-        wingdi::{CreateRectRgn, DeleteObject},
+        oleidl::{IDropSource, DROPEFFECT_NONE, LPDROPTARGET},
+        shobjidl_core::{
+            CLSID_TaskbarList, ITaskbarList3, TBPF_ERROR, TBPF_INDETERMINATE, TBPF_NOPROGRESS,
+            TBPF_NORMAL, TBPF_PAUSED,
+        },
+        uxtheme,
+        wingdi::{self, CreateRectRgn, DeleteObject},
         winnt::{LPCWSTR, SHORT},
-        winuser,
+        winuser, wtsapi32,
     },
 };
 
 use crate::{
+    clipboard::{ClipboardConsumer, ClipboardKind, ClipboardProvider, MimePicker},
+    dnd::{DndActions, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
-    error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    error::{ExternalError, HandleError, NotSupportedError, OsError as RootOsError},
     icon::Icon,
     monitor::MonitorHandle as RootMonitorHandle,
     platform_impl::platform::{
+        backdrop, clipboard,
         dark_mode::try_theme,
-        dpi::{dpi_to_scale_factor, hwnd_dpi},
+        direct_manipulation::DirectManipulation,
+        dpi::{dpi_to_scale_factor, hwnd_dpi, system_cursor_size},
+        drag_source::{self, DragDataObject, DragSource},
         drop_handler::FileDropHandler,
-        event_loop::{self, EventLoopWindowTarget, DESTROY_MSG_ID},
-        icon::{self, IconType},
-        monitor, util,
-        window_state::{CursorFlags, SavedWindow, WindowFlags, WindowState},
+        event_loop::{self, EventLoopWindowTarget, DESTROY_MSG_ID, IME_STATE_MSG_ID},
+        icon::{self, IconType, WinCursor, WinIcon},
+        ime::{self, SurroundingText},
+        keyboard_hook, monitor,
+        tsf::TextStore,
+        util, virtual_desktop, vsync,
+        window_state::{self, CursorFlags, SavedWindow, WindowFlags, WindowState},
         Parent, PlatformSpecificWindowBuilderAttributes, WindowId,
     },
-    window::{CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes},
+    window::{
+        Backdrop, CursorIcon, CursorTheme, Fullscreen, ImePurpose, Placement, ProgressState, Ratio,
+        Theme, TitlebarStyle, UserAttentionType, WindowAttributes,
+    },
 };
 
 /// The Win32 implementation of the main `Window` object.
@@ -111,15 +126,50 @@ impl Window {
                     None
                 };
 
+                // The input methods go through IMM32 when TSF isn't available.
+                let text_store_runner = event_loop.runner_shared.clone();
+                let text_store = TextStore::new(
+                    win.window.0,
+                    Box::new(move |event| {
+                        if let Ok(e) = event.map_nonuser_event() {
+                            text_store_runner.send_event(e)
+                        }
+                    }),
+                );
+
+                // The touchpads scroll with the wheel messages when DirectManipulation isn't
+                // available.
+                let direct_manipulation_runner = event_loop.runner_shared.clone();
+                let direct_manipulation = DirectManipulation::new(
+                    win.window.0,
+                    Box::new(move |event| {
+                        if let Ok(e) = event.map_nonuser_event() {
+                            direct_manipulation_runner.send_event(e)
+                        }
+                    }),
+                );
+
                 let subclass_input = event_loop::SubclassInput {
                     window_state: win.window_state.clone(),
                     event_loop_runner: event_loop.runner_shared.clone(),
                     file_drop_handler,
+                    text_store,
+                    direct_manipulation,
                     subclass_removed: Cell::new(false),
                     recurse_depth: Cell::new(0),
                 };
 
                 event_loop::subclass_window(win.window.0, subclass_input);
+
+                // Get notified about the clipboard changes, see `WM_CLIPBOARDUPDATE`.
+                winuser::AddClipboardFormatListener(win.window.0);
+
+                // Get notified about the session changes, see `WM_WTSSESSION_CHANGE`.
+                wtsapi32::WTSRegisterSessionNotification(
+                    win.window.0,
+                    wtsapi32::NOTIFY_FOR_THIS_SESSION,
+                );
+
                 win
             })
         }
@@ -148,6 +198,10 @@ impl Window {
 
     #[inline]
     pub fn request_redraw(&self) {
+        if self.window_state.lock().vsync_redraws {
+            vsync::hold_redraw(self.window.0);
+            return;
+        }
         unsafe {
             winuser::RedrawWindow(
                 self.window.0,
@@ -158,6 +212,58 @@ impl Window {
         }
     }
 
+    #[inline]
+    pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+        self.window_state.lock().vsync_redraws = vsync_redraws;
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {}
+
+    #[cfg(feature = "software-present")]
+    pub fn present_pixels(
+        &self,
+        pixels: &[u32],
+        size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        let (width, height) = (size.width as c_int, size.height as c_int);
+        let mut info: wingdi::BITMAPINFO = unsafe { mem::zeroed() };
+        info.bmiHeader.biSize = mem::size_of::<wingdi::BITMAPINFOHEADER>() as DWORD;
+        info.bmiHeader.biWidth = width;
+        // The rows are from the top.
+        info.bmiHeader.biHeight = -height;
+        info.bmiHeader.biPlanes = 1;
+        info.bmiHeader.biBitCount = 32;
+        info.bmiHeader.biCompression = wingdi::BI_RGB;
+
+        unsafe {
+            let hdc = winuser::GetDC(self.window.0);
+            if hdc.is_null() {
+                return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+            }
+            let lines = wingdi::StretchDIBits(
+                hdc,
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                pixels.as_ptr() as *const _,
+                &info,
+                wingdi::DIB_RGB_COLORS,
+                wingdi::SRCCOPY,
+            );
+            winuser::ReleaseDC(self.window.0, hdc);
+            if lines == 0 {
+                return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         util::get_window_rect(self.window.0)
@@ -243,6 +349,11 @@ impl Window {
         util::set_inner_size_physical(self.window.0, width, height);
     }
 
+    #[inline]
+    pub fn recommended_bounds(&self) -> Option<PhysicalSize<u32>> {
+        monitor::recommended_bounds(self.window.0)
+    }
+
     #[inline]
     pub fn set_min_inner_size(&self, size: Option<Size>) {
         self.window_state.lock().min_size = size;
@@ -259,6 +370,11 @@ impl Window {
         self.set_inner_size(size.into());
     }
 
+    #[inline]
+    pub fn set_aspect_ratio(&self, ratio: Option<Ratio>) {
+        self.window_state.lock().aspect_ratio = ratio;
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
         let window = self.window.clone();
@@ -283,21 +399,77 @@ impl Window {
     }
 
     #[inline]
-    pub fn raw_window_handle(&self) -> RawWindowHandle {
+    pub fn window_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        self.check_destroyed()?;
         let handle = WindowsHandle {
             hwnd: self.window.0 as *mut _,
             hinstance: self.hinstance() as *mut _,
             ..WindowsHandle::empty()
         };
-        RawWindowHandle::Windows(handle)
+        Ok(RawWindowHandle::Windows(handle))
+    }
+
+    #[inline]
+    pub fn display_handle(&self) -> Result<RawWindowHandle, HandleError> {
+        self.check_destroyed()?;
+        let handle = WindowsHandle {
+            hinstance: self.hinstance() as *mut _,
+            ..WindowsHandle::empty()
+        };
+        Ok(RawWindowHandle::Windows(handle))
+    }
+
+    /// Fails once the window was destroyed by the system, as along with its parent, while it's
+    /// still alive.
+    fn check_destroyed(&self) -> Result<(), HandleError> {
+        if unsafe { winuser::IsWindow(self.window.0) } == 0 {
+            Err(HandleError::Destroyed)
+        } else {
+            Ok(())
+        }
     }
 
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        self.window_state.lock().mouse.cursor = cursor;
+        let mut window_state = self.window_state.lock();
+        window_state.mouse.cursor = cursor;
+        window_state.mouse.custom_cursor = None;
+        let scale_factor = window_state.scale_factor;
+        let shown_cursor = window_state.mouse.shown_cursor(scale_factor);
+        drop(window_state);
+        self.thread_executor.execute_in_thread(move || unsafe {
+            window_state::set_client_cursor(cursor, shown_cursor);
+        });
+    }
+
+    #[inline]
+    pub fn set_cursor_theme(&self, _name: &str, size: u32) -> Result<(), ExternalError> {
+        let mut window_state = self.window_state.lock();
+        window_state.mouse.cursor_size = size;
+        let cursor = window_state.mouse.cursor;
+        let scale_factor = window_state.scale_factor;
+        let shown_cursor = window_state.mouse.shown_cursor(scale_factor);
+        drop(window_state);
         self.thread_executor.execute_in_thread(move || unsafe {
-            let cursor = winuser::LoadCursorW(ptr::null_mut(), cursor.to_windows_cursor());
-            winuser::SetCursor(cursor);
+            window_state::set_client_cursor(cursor, shown_cursor);
+        });
+        Ok(())
+    }
+
+    #[inline]
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        let size = match self.window_state.lock().mouse.cursor_size {
+            0 => system_cursor_size(),
+            size => size,
+        };
+        Some(CursorTheme { name: None, size })
+    }
+
+    #[inline]
+    pub fn set_cursor(&self, cursor: WinCursor) {
+        self.window_state.lock().mouse.custom_cursor = Some(cursor.clone());
+        self.thread_executor.execute_in_thread(move || unsafe {
+            winuser::SetCursor(cursor.as_raw_handle());
         });
     }
 
@@ -318,6 +490,95 @@ impl Window {
         rx.recv().unwrap()
     }
 
+    #[inline]
+    pub fn set_cursor_grab_area(
+        &self,
+        area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+        let scale_factor = self.scale_factor();
+        let area = area.map(|(position, size)| {
+            let (x, y): (i32, i32) = position.to_physical::<i32>(scale_factor).into();
+            let (width, height): (i32, i32) = size.to_physical::<i32>(scale_factor).into();
+            RECT {
+                left: x,
+                top: y,
+                right: x + width,
+                bottom: y + height,
+            }
+        });
+        let (tx, rx) = channel();
+
+        self.thread_executor.execute_in_thread(move || {
+            let mut window_state = window_state.lock();
+            window_state.mouse.grab_area = area;
+            let result = window_state
+                .mouse
+                .set_cursor_flags(window.0, |_| ())
+                .map_err(|e| ExternalError::Os(os_error!(e)));
+            let _ = tx.send(result);
+        });
+        rx.recv().unwrap()
+    }
+
+    #[inline]
+    pub fn set_pointer_lock(&self, lock: bool) -> Result<(), ExternalError> {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+        let (tx, rx) = channel();
+
+        self.thread_executor.execute_in_thread(move || {
+            let mut window_state = window_state.lock();
+            if lock
+                && !window_state
+                    .mouse
+                    .cursor_flags()
+                    .contains(CursorFlags::LOCKED)
+            {
+                let mut position = POINT { x: 0, y: 0 };
+                unsafe {
+                    winuser::GetCursorPos(&mut position);
+                    winuser::ScreenToClient(window.0, &mut position);
+                }
+                window_state.mouse.lock_position = position;
+            }
+            let result = window_state
+                .mouse
+                .set_cursor_flags(window.0, |f| f.set(CursorFlags::LOCKED, lock))
+                .map_err(|e| ExternalError::Os(os_error!(e)));
+            let _ = tx.send(result);
+        });
+        rx.recv().unwrap()
+    }
+
+    #[inline]
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+        self.thread_executor.execute_in_thread(move || unsafe {
+            let mut window_state = window_state.lock();
+            if !window_state.mouse.pointer_captured {
+                window_state.mouse.pointer_captured = true;
+                event_loop::capture_mouse(window.0, &mut window_state);
+            }
+        });
+        Ok(())
+    }
+
+    #[inline]
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
+        let window_state = Arc::clone(&self.window_state);
+        self.thread_executor.execute_in_thread(move || unsafe {
+            let mut window_state = window_state.lock();
+            if window_state.mouse.pointer_captured {
+                window_state.mouse.pointer_captured = false;
+                event_loop::release_mouse(window_state);
+            }
+        });
+        Ok(())
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         let window = self.window.clone();
@@ -342,10 +603,13 @@ impl Window {
 
     #[inline]
     pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
-        let scale_factor = self.scale_factor();
-        let (x, y) = position.to_physical::<i32>(scale_factor).into();
-
-        let mut point = POINT { x, y };
+        // The position is in the pixel it falls into, since rounding it could put the cursor
+        // out of the window at the fractional scale factors.
+        let position = position.to_physical::<f64>(self.scale_factor());
+        let mut point = POINT {
+            x: position.x.floor() as i32,
+            y: position.y.floor() as i32,
+        };
         unsafe {
             if winuser::ClientToScreen(self.window.0, &mut point) == 0 {
                 return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
@@ -444,6 +708,7 @@ impl Window {
                     &Some(Fullscreen::Borderless(_)),
                     &Some(Fullscreen::Exclusive(ref video_mode)),
                 )
+                | (&Some(Fullscreen::Span(_)), &Some(Fullscreen::Exclusive(ref video_mode)))
                 | (&Some(Fullscreen::Exclusive(_)), &Some(Fullscreen::Exclusive(ref video_mode))) =>
                 {
                     let monitor = video_mode.monitor();
@@ -475,7 +740,8 @@ impl Window {
                     assert_eq!(res, winuser::DISP_CHANGE_SUCCESSFUL);
                 }
                 (&Some(Fullscreen::Exclusive(_)), &None)
-                | (&Some(Fullscreen::Exclusive(_)), &Some(Fullscreen::Borderless(_))) => {
+                | (&Some(Fullscreen::Exclusive(_)), &Some(Fullscreen::Borderless(_)))
+                | (&Some(Fullscreen::Exclusive(_)), &Some(Fullscreen::Span(_))) => {
                     let res = unsafe {
                         winuser::ChangeDisplaySettingsExW(
                             std::ptr::null_mut(),
@@ -516,7 +782,10 @@ impl Window {
                 );
                 f.set(
                     WindowFlags::MARKER_BORDERLESS_FULLSCREEN,
-                    matches!(fullscreen, Some(Fullscreen::Borderless(_))),
+                    matches!(
+                        fullscreen,
+                        Some(Fullscreen::Borderless(_)) | Some(Fullscreen::Span(_))
+                    ),
                 );
             });
 
@@ -532,16 +801,26 @@ impl Window {
 
                     window_state.lock().saved_window = Some(SavedWindow { placement });
 
-                    let monitor = match &fullscreen {
-                        Fullscreen::Exclusive(video_mode) => video_mode.monitor(),
-                        Fullscreen::Borderless(Some(monitor)) => monitor.clone(),
-                        Fullscreen::Borderless(None) => RootMonitorHandle {
-                            inner: monitor::current_monitor(window.0),
+                    let (position, size) = match &fullscreen {
+                        Fullscreen::Exclusive(video_mode) => {
+                            let monitor = video_mode.monitor();
+                            (monitor.position(), monitor.size())
+                        }
+                        Fullscreen::Borderless(Some(monitor)) => {
+                            (monitor.position(), monitor.size())
+                        }
+                        Fullscreen::Borderless(None) => {
+                            let monitor = monitor::current_monitor(window.0);
+                            (monitor.position(), monitor.size())
+                        }
+                        Fullscreen::Span(monitors) => match monitor::span_rect(monitors) {
+                            Some(rect) => rect,
+                            None => return,
                         },
                     };
 
-                    let position: (i32, i32) = monitor.position().into();
-                    let size: (u32, u32) = monitor.size().into();
+                    let position: (i32, i32) = position.into();
+                    let size: (u32, u32) = size.into();
 
                     unsafe {
                         winuser::SetWindowPos(
@@ -586,6 +865,60 @@ impl Window {
         });
     }
 
+    #[inline]
+    pub fn set_shadow(&self, shadow: bool) {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+
+        self.thread_executor.execute_in_thread(move || {
+            WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+                f.set(WindowFlags::SHADOW, shadow)
+            });
+        });
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+
+        self.thread_executor.execute_in_thread(move || {
+            // The opaque windows are kept from being layered, which is slower to draw.
+            let layered = opacity < 1.0;
+            WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+                f.set(WindowFlags::LAYERED, layered)
+            });
+            if layered {
+                unsafe {
+                    let alpha = (opacity * 255.0).round() as u8;
+                    winuser::SetLayeredWindowAttributes(window.0, 0, alpha, winuser::LWA_ALPHA);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_backdrop(&self, backdrop: Backdrop) -> Result<(), ExternalError> {
+        let window = self.window.clone();
+        let window_state = Arc::clone(&self.window_state);
+        let (tx, rx) = channel();
+
+        self.thread_executor.execute_in_thread(move || {
+            let result = match backdrop::set_backdrop(window.0, backdrop) {
+                Some(system_backdrop) => {
+                    WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+                        f.set(WindowFlags::SYSTEM_BACKDROP, system_backdrop)
+                    });
+                    Ok(())
+                }
+                None => Err(ExternalError::NotSupported(NotSupportedError::new())),
+            };
+            let _ = tx.send(result);
+        });
+        rx.recv().unwrap()
+    }
+
     #[inline]
     pub fn set_always_on_top(&self, always_on_top: bool) {
         let window = self.window.clone();
@@ -605,6 +938,12 @@ impl Window {
         })
     }
 
+    #[inline]
+    pub fn is_vrr_active(&self) -> bool {
+        // The variable refresh rate is only used for the fullscreen swap chains.
+        self.fullscreen().is_some() && monitor::allows_tearing()
+    }
+
     #[inline]
     pub fn set_window_icon(&self, window_icon: Option<Icon>) {
         if let Some(ref window_icon) = window_icon {
@@ -629,25 +968,145 @@ impl Window {
         self.window_state.lock().taskbar_icon = taskbar_icon;
     }
 
-    pub(crate) fn set_ime_position_physical(&self, x: i32, y: i32) {
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+        let (x, y) = position.to_physical::<i32>(self.scale_factor()).into();
+        let (width, height) = size.to_physical::<i32>(self.scale_factor()).into();
+        let area = RECT {
+            left: x,
+            top: y,
+            right: x + width,
+            bottom: y + height,
+        };
         if unsafe { winuser::GetSystemMetrics(winuser::SM_IMMENABLED) } != 0 {
-            let mut composition_form = COMPOSITIONFORM {
-                dwStyle: CFS_POINT,
-                ptCurrentPos: POINT { x, y },
-                rcArea: unsafe { mem::zeroed() },
-            };
-            unsafe {
-                let himc = winapi::um::imm::ImmGetContext(self.window.0);
-                winapi::um::imm::ImmSetCompositionWindow(himc, &mut composition_form);
-                winapi::um::imm::ImmReleaseContext(self.window.0, himc);
+            unsafe { ime::set_cursor_area(self.window.0, area) };
+        }
+        self.window_state.lock().ime_cursor_area = Some(area);
+        self.ime_state_changed();
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        self.window_state.lock().surrounding_text =
+            Some(SurroundingText::new(&text, cursor, anchor));
+        self.ime_state_changed();
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        unsafe { ime::set_purpose(self.window.0, purpose) };
+        self.window_state.lock().ime_purpose = purpose;
+        self.ime_state_changed();
+    }
+
+    /// Tells the text store of the window to read the IME state again, on the thread of the
+    /// window as TSF is.
+    fn ime_state_changed(&self) {
+        unsafe { winuser::PostMessageW(self.window.0, *IME_STATE_MSG_ID, 0, 0) };
+    }
+
+    #[inline]
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        self.window_state.lock().key_repeat = key_repeat;
+    }
+
+    #[inline]
+    pub fn set_system_shortcuts_inhibited(&self, inhibited: bool) -> Result<(), ExternalError> {
+        let window = self.window.clone();
+        let (tx, rx) = channel();
+
+        // The keyboard hook is called on the thread which installed it.
+        self.thread_executor.execute_in_thread(move || {
+            let result = keyboard_hook::set_shortcuts_inhibited(window.0, inhibited)
+                .map_err(|e| ExternalError::Os(os_error!(e)));
+            let _ = tx.send(result);
+        });
+        rx.recv().unwrap()
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        clipboard::set_clipboard(self.window.0, kind, provider)
+    }
+
+    #[inline]
+    pub fn read_clipboard(
+        &self,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Option<Box<dyn ClipboardConsumer>>,
+    ) -> Result<(), ExternalError> {
+        let content = clipboard::read_clipboard(self.window.0, kind, picker)?;
+        if let Some(consumer) = consumer {
+            crate::clipboard::consume_content(consumer, content);
+            return Ok(());
+        }
+
+        // The content is delivered through the event loop, like on the other platforms.
+        let content = Box::into_raw(Box::new(content));
+        unsafe {
+            if winuser::PostMessageW(
+                self.window.0,
+                *event_loop::CLIPBOARD_DATA_MSG_ID,
+                0,
+                content as LPARAM,
+            ) == 0
+            {
+                drop(Box::from_raw(content));
+                return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
             }
         }
+
+        Ok(())
     }
 
     #[inline]
-    pub fn set_ime_position(&self, spot: Position) {
-        let (x, y) = spot.to_physical::<i32>(self.scale_factor()).into();
-        self.set_ime_position_physical(x, y);
+    pub fn start_drag(&self, data: DragData, actions: DndActions) -> Result<(), ExternalError> {
+        let data_object = DragDataObject::new(self.window.0, data.provider);
+        let drag_source = DragSource::new(self.window.0);
+        if let Some(icon) = data.icon.as_ref() {
+            unsafe { drag_source::set_drag_icon(data_object.data as *mut IDataObject, icon) };
+        }
+
+        // The drag runs a modal loop, which only returns once the data is dropped or the drag is
+        // cancelled.
+        let mut effect = 0;
+        let result = unsafe {
+            ole2::DoDragDrop(
+                data_object.data as *mut IDataObject,
+                drag_source.data as *mut IDropSource,
+                drag_source::actions_effects(actions),
+                &mut effect,
+            )
+        };
+        match result {
+            DRAGDROP_S_DROP => (),
+            DRAGDROP_S_CANCEL => effect = DROPEFFECT_NONE,
+            err => {
+                return Err(ExternalError::Os(os_error!(io::Error::from_raw_os_error(
+                    err
+                ))))
+            }
+        }
+
+        let outside = !data_object.dropped_inside();
+        unsafe {
+            if winuser::PostMessageW(
+                self.window.0,
+                *event_loop::DRAG_FINISHED_MSG_ID,
+                effect as WPARAM,
+                outside as LPARAM,
+            ) == 0
+            {
+                return Err(ExternalError::Os(os_error!(io::Error::last_os_error())));
+            }
+        }
+
+        Ok(())
     }
 
     #[inline]
@@ -681,6 +1140,66 @@ impl Window {
         });
     }
 
+    #[inline]
+    pub fn tab_group(&self) -> Option<String> {
+        None
+    }
+
+    #[inline]
+    pub fn set_tab_group(&self, _group: &str) {}
+
+    #[inline]
+    pub fn merge_into_tab_group(&self, _window: &Window) {}
+
+    #[inline]
+    pub fn select_next_tab(&self) {}
+
+    #[inline]
+    pub fn workspace(&self) -> Option<u32> {
+        virtual_desktop::desktop_index(self.window.0)
+    }
+
+    #[inline]
+    pub fn move_to_workspace(&self, workspace: u32) {
+        let window = self.window.clone();
+        self.thread_executor.execute_in_thread(move || {
+            if !virtual_desktop::move_to_desktop(window.0, workspace) {
+                warn!(
+                    "Failed to move the window to the virtual desktop {}",
+                    workspace
+                );
+            }
+        });
+    }
+
+    #[inline]
+    pub fn set_visible_on_all_workspaces(&self, _visible: bool) {}
+
+    #[inline]
+    pub fn set_progress(&self, state: ProgressState) {
+        let window = self.window.clone();
+        self.thread_executor
+            .execute_in_thread(move || unsafe { taskbar_set_progress(window.0, state) });
+    }
+
+    #[inline]
+    pub fn set_badge(&self, count: Option<u64>) {
+        let window = self.window.clone();
+        self.thread_executor
+            .execute_in_thread(move || unsafe { taskbar_set_badge(window.0, count) });
+    }
+
+    #[inline]
+    pub fn set_modal(&self, modal: bool) -> Result<(), ExternalError> {
+        let owner = unsafe { winuser::GetWindow(self.window.0, winuser::GW_OWNER) };
+        if owner.is_null() {
+            return Ok(());
+        }
+        self.window_state.lock().modal = modal;
+        unsafe { winuser::EnableWindow(owner, !modal as BOOL) };
+        Ok(())
+    }
+
     #[inline]
     pub fn theme(&self) -> Theme {
         self.window_state.lock().current_theme
@@ -705,6 +1224,12 @@ impl Drop for Window {
     #[inline]
     fn drop(&mut self) {
         unsafe {
+            // The owner is enabled again before the window is gone, so it gets the activation.
+            if self.window_state.lock().modal {
+                let owner = winuser::GetWindow(self.window.0, winuser::GW_OWNER);
+                winuser::EnableWindow(owner, TRUE);
+            }
+
             // The window must be destroyed from the same thread that created it, so we send a
             // custom message to be handled by our callback to do the actual work.
             winuser::PostMessageW(self.window.0, *DESTROY_MSG_ID, 0, 0);
@@ -745,8 +1270,18 @@ unsafe fn init<T: 'static>(
         pl_attribs.no_redirection_bitmap,
     );
     window_flags.set(WindowFlags::TRANSPARENT, attributes.transparent);
+    window_flags.set(WindowFlags::SHADOW, attributes.shadow);
     // WindowFlags::VISIBLE and MAXIMIZED are set down below after the window has been configured.
     window_flags.set(WindowFlags::RESIZABLE, attributes.resizable);
+    let titlebar_style = attributes.titlebar_style;
+    window_flags.set(
+        WindowFlags::TITLEBAR_EXTENDED,
+        titlebar_style == TitlebarStyle::Hidden || titlebar_style == TitlebarStyle::ExtendContent,
+    );
+    window_flags.set(
+        WindowFlags::CAPTION_BUTTONS,
+        titlebar_style == TitlebarStyle::ExtendContent,
+    );
 
     let parent = match pl_attribs.parent {
         Parent::ChildOf(parent) => {
@@ -760,10 +1295,16 @@ unsafe fn init<T: 'static>(
             window_flags.set(WindowFlags::POPUP, true);
             Some(parent)
         }
-        Parent::None => {
-            window_flags.set(WindowFlags::ON_TASKBAR, true);
-            None
-        }
+        Parent::None => match attributes.owner {
+            Some(owner) => {
+                window_flags.set(WindowFlags::POPUP, true);
+                Some((owner.0).0)
+            }
+            None => {
+                window_flags.set(WindowFlags::ON_TASKBAR, true);
+                None
+            }
+        },
     };
 
     // creating the real window this time, by using the functions in `extra_functions`
@@ -823,6 +1364,21 @@ unsafe fn init<T: 'static>(
     // window for the first time).
     let current_theme = try_theme(real_window.0, pl_attribs.preferred_theme);
 
+    // The title and the icon are left out of the title bar, its buttons kept.
+    if titlebar_style != TitlebarStyle::Visible {
+        let no_caption = uxtheme::WTNCA_NODRAWCAPTION | uxtheme::WTNCA_NODRAWICON;
+        let options = uxtheme::WTA_OPTIONS {
+            dwFlags: no_caption,
+            dwMask: no_caption,
+        };
+        uxtheme::SetWindowThemeAttribute(
+            real_window.0,
+            uxtheme::WTA_NONCLIENT,
+            &options as *const _ as _,
+            mem::size_of_val(&options) as DWORD,
+        );
+    }
+
     let window_state = {
         let window_state = WindowState::new(
             &attributes,
@@ -846,6 +1402,12 @@ unsafe fn init<T: 'static>(
         .inner_size
         .unwrap_or_else(|| PhysicalSize::new(800, 600).into());
     win.set_inner_size(dimensions);
+    if let Some(placement) = attributes.placement {
+        if attributes.position.is_none() && attributes.fullscreen.is_none() && !attributes.maximized
+        {
+            place_window(&win, placement, attributes.owner.map(|owner| (owner.0).0));
+        }
+    }
     if attributes.maximized {
         // Need to set MAXIMIZED after setting `inner_size` as
         // `Window::set_inner_size` changes MAXIMIZED to false.
@@ -865,6 +1427,57 @@ unsafe fn init<T: 'static>(
     Ok(win)
 }
 
+/// Places the window within the work area of the monitor of its owner, or of the cursor.
+unsafe fn place_window(win: &Window, placement: Placement, owner: Option<HWND>) {
+    let bounds = |hmonitor| {
+        let work = monitor::get_monitor_info(hmonitor).ok()?.rcWork;
+        Some((
+            PhysicalPosition::new(work.left, work.top),
+            PhysicalSize::new(
+                (work.right - work.left) as u32,
+                (work.bottom - work.top) as u32,
+            ),
+        ))
+    };
+    let center_of = |(position, size): (PhysicalPosition<i32>, PhysicalSize<u32>)| {
+        PhysicalPosition::new(
+            position.x + size.width as i32 / 2,
+            position.y + size.height as i32 / 2,
+        )
+    };
+
+    let mut owner_rect: RECT = mem::zeroed();
+    let owner = owner.filter(|&owner| winuser::GetWindowRect(owner, &mut owner_rect) != 0);
+    let (hmonitor, center) = match (placement, owner) {
+        (Placement::CenterOnParent, Some(owner)) => {
+            let center = PhysicalPosition::new(
+                (owner_rect.left + owner_rect.right) / 2,
+                (owner_rect.top + owner_rect.bottom) / 2,
+            );
+            let hmonitor = winuser::MonitorFromWindow(owner, winuser::MONITOR_DEFAULTTONEAREST);
+            (hmonitor, Some(center))
+        }
+        (Placement::AtCursor, _) => {
+            let mut cursor = POINT { x: 0, y: 0 };
+            winuser::GetCursorPos(&mut cursor);
+            let hmonitor = winuser::MonitorFromPoint(cursor, winuser::MONITOR_DEFAULTTONEAREST);
+            (hmonitor, Some(PhysicalPosition::new(cursor.x, cursor.y)))
+        }
+        // The windows created at `CW_USEDEFAULT` are already cascaded.
+        (Placement::Cascade, _) => return,
+        _ => {
+            let hmonitor =
+                winuser::MonitorFromWindow(win.window.0, winuser::MONITOR_DEFAULTTONEAREST);
+            (hmonitor, None)
+        }
+    };
+    if let Some(bounds) = bounds(hmonitor) {
+        let center = center.unwrap_or_else(|| center_of(bounds));
+        let position = Placement::center_on(center, win.outer_size(), bounds);
+        win.set_outer_position(position.into());
+    }
+}
+
 unsafe fn register_window_class(
     window_icon: &Option<Icon>,
     taskbar_icon: &Option<Icon>,
@@ -922,7 +1535,7 @@ thread_local! {
         }
     };
 
-    static TASKBAR_LIST: Cell<*mut ITaskbarList2> = Cell::new(ptr::null_mut());
+    static TASKBAR_LIST: Cell<*mut ITaskbarList3> = Cell::new(ptr::null_mut());
 }
 
 pub fn com_initialized() {
@@ -938,6 +1551,58 @@ pub fn com_initialized() {
 // heuristics to determine how the window should be treated, which means
 // that it could still consider the window as fullscreen. :(
 unsafe fn taskbar_mark_fullscreen(handle: HWND, fullscreen: bool) {
+    with_taskbar_list(|task_bar_list| {
+        task_bar_list.MarkFullscreenWindow(handle, if fullscreen { 1 } else { 0 });
+    })
+}
+
+// The badge is the overlay icon of the taskbar button of the window.
+unsafe fn taskbar_set_badge(handle: HWND, count: Option<u64>) {
+    let icon = count.and_then(|count| WinIcon::new_badge(count).ok());
+    let description = count
+        .map(|count| {
+            OsStr::new(&count.to_string())
+                .encode_wide()
+                .chain(Some(0).into_iter())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    with_taskbar_list(|task_bar_list| {
+        task_bar_list.SetOverlayIcon(
+            handle,
+            icon.as_ref()
+                .map_or(ptr::null_mut(), WinIcon::as_raw_handle),
+            if description.is_empty() {
+                ptr::null()
+            } else {
+                description.as_ptr()
+            },
+        );
+    })
+}
+
+// The progress is shown on the taskbar button of the window, its value being given over
+// `PROGRESS_TOTAL`.
+unsafe fn taskbar_set_progress(handle: HWND, state: ProgressState) {
+    const PROGRESS_TOTAL: u64 = 10_000;
+
+    let (flags, progress) = match state {
+        ProgressState::None => (TBPF_NOPROGRESS, None),
+        ProgressState::Indeterminate => (TBPF_INDETERMINATE, None),
+        ProgressState::Normal(progress) => (TBPF_NORMAL, Some(progress)),
+        ProgressState::Paused(progress) => (TBPF_PAUSED, Some(progress)),
+        ProgressState::Error(progress) => (TBPF_ERROR, Some(progress)),
+    };
+    with_taskbar_list(|task_bar_list| {
+        if let Some(progress) = progress {
+            let completed = (progress.clamp(0., 1.) * PROGRESS_TOTAL as f64) as u64;
+            task_bar_list.SetProgressValue(handle, completed, PROGRESS_TOTAL);
+        }
+        task_bar_list.SetProgressState(handle, flags);
+    })
+}
+
+unsafe fn with_taskbar_list<F: FnOnce(&ITaskbarList3)>(f: F) {
     com_initialized();
 
     TASKBAR_LIST.with(|task_bar_list_ptr| {
@@ -950,7 +1615,7 @@ unsafe fn taskbar_mark_fullscreen(handle: HWND, fullscreen: bool) {
                 &CLSID_TaskbarList,
                 ptr::null_mut(),
                 combaseapi::CLSCTX_ALL,
-                &ITaskbarList2::uuidof(),
+                &ITaskbarList3::uuidof(),
                 &mut task_bar_list as *mut _ as *mut _,
             );
 
@@ -961,8 +1626,7 @@ unsafe fn taskbar_mark_fullscreen(handle: HWND, fullscreen: bool) {
             task_bar_list_ptr.set(task_bar_list)
         }
 
-        task_bar_list = task_bar_list_ptr.get();
-        (*task_bar_list).MarkFullscreenWindow(handle, if fullscreen { 1 } else { 0 });
+        f(&*task_bar_list_ptr.get())
     })
 }
 
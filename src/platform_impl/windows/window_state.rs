@@ -1,20 +1,32 @@
 use crate::{
-    dpi::{PhysicalPosition, Size},
+    dpi::{PhysicalPosition, PhysicalSize, Size},
     event::ModifiersState,
     icon::Icon,
-    platform_impl::platform::{event_loop, util},
-    window::{CursorIcon, Fullscreen, Theme, WindowAttributes},
+    platform_impl::platform::{
+        dpi, event_loop, gesture::TouchGesture, icon::WinCursor, ime::SurroundingText, util,
+    },
+    window::{CursorIcon, EdgeFlags, Fullscreen, ImePurpose, Ratio, Theme, WindowAttributes},
 };
 use parking_lot::MutexGuard;
-use std::{io, ptr};
+use std::{collections::HashMap, io, ptr};
 use winapi::{
     shared::{
         minwindef::DWORD,
-        windef::{HWND, RECT},
+        windef::{HWND, POINT, RECT},
     },
-    um::winuser,
+    um::{dwmapi, uxtheme::MARGINS, winuser},
 };
 
+/// Shows the cursor over the client area, which is the shown cursor of the mouse properties or
+/// the cursor icon at the size of the system cursors.
+pub unsafe fn set_client_cursor(cursor: CursorIcon, shown_cursor: Option<WinCursor>) {
+    let cursor = match shown_cursor {
+        Some(shown_cursor) => shown_cursor.as_raw_handle(),
+        None => winuser::LoadCursorW(ptr::null_mut(), cursor.to_windows_cursor()),
+    };
+    winuser::SetCursor(cursor);
+}
+
 /// Contains information about states and the window that the callback is going to use.
 pub struct WindowState {
     pub mouse: MouseProperties,
@@ -22,6 +34,20 @@ pub struct WindowState {
     /// Used by `WM_GETMINMAXINFO`.
     pub min_size: Option<Size>,
     pub max_size: Option<Size>,
+    /// Used by `WM_SIZING`.
+    pub aspect_ratio: Option<Ratio>,
+    /// The bounds last sent with `RecommendedBounds`.
+    pub recommended_bounds: Option<PhysicalSize<u32>>,
+    /// Whether the window was last sent as occluded.
+    pub occluded: bool,
+    /// The edges last sent with `TiledStateChanged`.
+    pub tiled_edges: EdgeFlags,
+    /// The refresh rate of the monitor the window was last on, in millihertz.
+    pub refresh_rate: Option<u32>,
+    /// The index of the virtual desktop the window was last on.
+    pub workspace: Option<u32>,
+    /// Whether the requested redraws wait for the next composition of the desktop.
+    pub vsync_redraws: bool,
 
     pub window_icon: Option<Icon>,
     pub taskbar_icon: Option<Icon>,
@@ -35,6 +61,22 @@ pub struct WindowState {
     pub preferred_theme: Option<Theme>,
     pub high_surrogate: Option<u16>,
     pub window_flags: WindowFlags,
+    pub key_repeat: bool,
+    /// The text around the cursor, for the reconversions of the input methods.
+    pub surrounding_text: Option<SurroundingText>,
+    /// The area of the cursor in client area coordinates, for the text store of TSF.
+    pub ime_cursor_area: Option<RECT>,
+    /// The purpose of the edited text, for the text store of TSF.
+    pub ime_purpose: ImePurpose,
+    /// The pinch and the rotation of the touches on the window.
+    pub touch_gesture: TouchGesture,
+    /// The serials of the pens over the window by their pointer, read as they enter it.
+    pub pen_serials: HashMap<u32, Option<u64>>,
+    /// The pointers down on the window by their id, with their last position, which are
+    /// cancelled if the window loses them.
+    pub touches: HashMap<u32, PhysicalPosition<f64>>,
+    /// Whether the owner window is disabled for the window.
+    pub modal: bool,
 }
 
 #[derive(Clone)]
@@ -45,9 +87,22 @@ pub struct SavedWindow {
 #[derive(Clone)]
 pub struct MouseProperties {
     pub cursor: CursorIcon,
+    /// Shown instead of the cursor icon until the icon is set again.
+    pub custom_cursor: Option<WinCursor>,
     pub capture_count: u32,
+    /// Whether the mouse is captured by `Window::capture_pointer`, which holds one of the captures.
+    pub pointer_captured: bool,
     cursor_flags: CursorFlags,
     pub last_position: Option<PhysicalPosition<f64>>,
+    /// Where the cursor is clipped to while it's locked, in client area coordinates.
+    pub lock_position: POINT,
+    /// The area the grabbed cursor is clipped to instead of the client area, in client area
+    /// coordinates.
+    pub grab_area: Option<RECT>,
+    /// The size of the cursor icons in logical pixels, or 0 for the size of the system cursors.
+    pub cursor_size: u32,
+    /// The cursor icon loaded at the size, along with its size in pixels.
+    sized_cursor: Option<(CursorIcon, u32, WinCursor)>,
 }
 
 bitflags! {
@@ -55,6 +110,7 @@ bitflags! {
         const GRABBED   = 1 << 0;
         const HIDDEN    = 1 << 1;
         const IN_WINDOW = 1 << 2;
+        const LOCKED    = 1 << 3;
     }
 }
 bitflags! {
@@ -69,6 +125,13 @@ bitflags! {
         const CHILD          = 1 << 7;
         const MAXIMIZED      = 1 << 8;
         const POPUP          = 1 << 14;
+        const SHADOW         = 1 << 15;
+        const LAYERED        = 1 << 16;
+        const SYSTEM_BACKDROP = 1 << 17;
+        /// The client area is extended over the title bar.
+        const TITLEBAR_EXTENDED = 1 << 18;
+        /// The caption buttons are drawn by the DWM over the extended client area.
+        const CAPTION_BUTTONS = 1 << 19;
 
         /// Marker flag for fullscreen. Should always match `WindowState::fullscreen`, but is
         /// included here to make masking easier.
@@ -103,13 +166,26 @@ impl WindowState {
         WindowState {
             mouse: MouseProperties {
                 cursor: CursorIcon::default(),
+                custom_cursor: None,
+                cursor_size: 0,
+                sized_cursor: None,
                 capture_count: 0,
+                pointer_captured: false,
                 cursor_flags: CursorFlags::empty(),
                 last_position: None,
+                lock_position: POINT { x: 0, y: 0 },
+                grab_area: None,
             },
 
             min_size: attributes.min_inner_size,
             max_size: attributes.max_inner_size,
+            aspect_ratio: None,
+            recommended_bounds: None,
+            occluded: false,
+            tiled_edges: EdgeFlags::empty(),
+            refresh_rate: None,
+            workspace: None,
+            vsync_redraws: false,
 
             window_icon: attributes.window_icon.clone(),
             taskbar_icon,
@@ -123,6 +199,14 @@ impl WindowState {
             preferred_theme,
             high_surrogate: None,
             window_flags: WindowFlags::empty(),
+            key_repeat: attributes.key_repeat,
+            surrounding_text: None,
+            ime_cursor_area: None,
+            ime_purpose: ImePurpose::Normal,
+            touch_gesture: TouchGesture::default(),
+            pen_serials: HashMap::new(),
+            touches: HashMap::new(),
+            modal: false,
         }
     }
 
@@ -151,6 +235,30 @@ impl WindowState {
 }
 
 impl MouseProperties {
+    /// The cursor shown over the client area instead of the cursor icon at the size of the
+    /// system cursors, which is the custom cursor or the icon at the size of the window.
+    pub fn shown_cursor(&mut self, scale_factor: f64) -> Option<WinCursor> {
+        if let Some(ref cursor) = self.custom_cursor {
+            return Some(cursor.clone());
+        }
+        if self.cursor_size == 0 {
+            return None;
+        }
+        let size = (self.cursor_size as f64 * scale_factor).round() as u32;
+        match self.sized_cursor {
+            Some((cursor, cursor_size, ref sized_cursor))
+                if cursor == self.cursor && cursor_size == size =>
+            {
+                Some(sized_cursor.clone())
+            }
+            _ => {
+                let sized_cursor = WinCursor::from_system(self.cursor, size)?;
+                self.sized_cursor = Some((self.cursor, size, sized_cursor.clone()));
+                Some(sized_cursor)
+            }
+        }
+    }
+
     pub fn cursor_flags(&self) -> CursorFlags {
         self.cursor_flags
     }
@@ -161,7 +269,10 @@ impl MouseProperties {
     {
         let old_flags = self.cursor_flags;
         f(&mut self.cursor_flags);
-        match self.cursor_flags.refresh_os_cursor(window) {
+        match self
+            .cursor_flags
+            .refresh_os_cursor(window, self.lock_position, self.grab_area)
+        {
             Ok(()) => (),
             Err(e) => {
                 self.cursor_flags = old_flags;
@@ -211,6 +322,9 @@ impl WindowFlags {
         if self.contains(WindowFlags::NO_BACK_BUFFER) {
             style_ex |= WS_EX_NOREDIRECTIONBITMAP;
         }
+        if self.contains(WindowFlags::LAYERED) {
+            style_ex |= WS_EX_LAYERED;
+        }
         if self.contains(WindowFlags::CHILD) {
             style |= WS_CHILD; // This is incompatible with WS_POPUP if that gets added eventually.
         }
@@ -236,6 +350,51 @@ impl WindowFlags {
         (style, style_ex)
     }
 
+    /// Whether the DWM frame is extended into the client area, which gives a shadow to the
+    /// undecorated windows. Not done for the transparent windows, whose frame would show.
+    fn has_frame_shadow(self) -> bool {
+        self.contains(WindowFlags::SHADOW)
+            && !self.contains(WindowFlags::DECORATIONS)
+            && !self.contains(WindowFlags::TRANSPARENT)
+    }
+
+    /// The margins the DWM frame is extended into the client area with. The system backdrops are
+    /// only drawn behind the frame, which is then extended into the whole client area.
+    fn frame_margins(self, window: HWND) -> MARGINS {
+        let inset = if self.contains(WindowFlags::SYSTEM_BACKDROP) {
+            -1
+        } else if self.has_frame_shadow() {
+            1
+        } else {
+            0
+        };
+        let mut margins = MARGINS {
+            cxLeftWidth: inset,
+            cxRightWidth: inset,
+            cyTopHeight: inset,
+            cyBottomHeight: inset,
+        };
+        let fullscreen =
+            WindowFlags::MARKER_EXCLUSIVE_FULLSCREEN | WindowFlags::MARKER_BORDERLESS_FULLSCREEN;
+        if inset >= 0
+            && self.contains(WindowFlags::DECORATIONS | WindowFlags::CAPTION_BUTTONS)
+            && !self.intersects(fullscreen)
+        {
+            margins.cyTopHeight = unsafe {
+                dpi::frame_thickness(window) + dpi::system_metric(window, winuser::SM_CYCAPTION)
+            };
+        }
+        margins
+    }
+
+    /// Extends the DWM frame into the client area, which is done again once the DPI changes.
+    pub fn extend_frame(self, window: HWND) {
+        let margins = self.mask().frame_margins(window);
+        unsafe {
+            dwmapi::DwmExtendFrameIntoClientArea(window, &margins);
+        }
+    }
+
     /// Adjust the window client rectangle to the return value, if present.
     fn apply_diff(mut self, window: HWND, mut new: WindowFlags) {
         self = self.mask();
@@ -290,6 +449,23 @@ impl WindowFlags {
             }
         }
 
+        let margins_of = |flags: WindowFlags| {
+            let margins = flags.frame_margins(window);
+            (
+                margins.cxLeftWidth,
+                margins.cxRightWidth,
+                margins.cyTopHeight,
+                margins.cyBottomHeight,
+            )
+        };
+        if margins_of(self) != margins_of(new) {
+            new.extend_frame(window);
+        }
+        if diff.contains(WindowFlags::TITLEBAR_EXTENDED) {
+            // Applied with the frame refreshed below.
+            util::set_titlebar_extended(window, new.contains(WindowFlags::TITLEBAR_EXTENDED));
+        }
+
         // Minimize operations should execute after maximize for proper window animations
         if diff.contains(WindowFlags::MINIMIZED) {
             unsafe {
@@ -338,13 +514,38 @@ impl WindowFlags {
 }
 
 impl CursorFlags {
-    fn refresh_os_cursor(self, window: HWND) -> Result<(), io::Error> {
+    fn refresh_os_cursor(
+        self,
+        window: HWND,
+        lock_position: POINT,
+        grab_area: Option<RECT>,
+    ) -> Result<(), io::Error> {
         let client_rect = util::get_client_rect(window)?;
 
         if util::is_focused(window) {
-            let cursor_clip = match self.contains(CursorFlags::GRABBED) {
-                true => Some(client_rect),
-                false => None,
+            // The locked cursor is clipped to a single pixel, so it stays in place while the raw
+            // input keeps giving the motion of the mouse.
+            let cursor_clip = if self.contains(CursorFlags::LOCKED) {
+                let x = client_rect.left + lock_position.x;
+                let y = client_rect.top + lock_position.y;
+                Some(RECT {
+                    left: x,
+                    top: y,
+                    right: x + 1,
+                    bottom: y + 1,
+                })
+            } else if self.contains(CursorFlags::GRABBED) {
+                Some(match grab_area {
+                    Some(area) => RECT {
+                        left: client_rect.left + area.left,
+                        top: client_rect.top + area.top,
+                        right: client_rect.left + area.right,
+                        bottom: client_rect.top + area.bottom,
+                    },
+                    None => client_rect,
+                })
+            } else {
+                None
             };
 
             let rect_to_tuple = |rect: RECT| (rect.left, rect.top, rect.right, rect.bottom);
@@ -366,7 +567,7 @@ impl CursorFlags {
 
         let cursor_in_client = self.contains(CursorFlags::IN_WINDOW);
         if cursor_in_client {
-            util::set_cursor_hidden(self.contains(CursorFlags::HIDDEN));
+            util::set_cursor_hidden(self.intersects(CursorFlags::HIDDEN | CursorFlags::LOCKED));
         } else {
             util::set_cursor_hidden(false);
         }
@@ -0,0 +1,384 @@
+//! The resampling of the moves of the touches and the pens, which holds them until the end of the
+//! events of every iteration of the event loop and sends their position at the last refresh of
+//! the display, which the frames drawn then start at. The refreshes are timed by the
+//! presentations of the windows.
+
+use std::time::Duration;
+
+use instant::Instant;
+
+use crate::{
+    dpi::PhysicalPosition,
+    event::{DeviceId, PenEvent, PenPhase, Touch, TouchPhase, WindowEvent},
+    event_loop::MotionResampling,
+    window::WindowId,
+};
+
+/// What becomes of an event going through the resampler.
+pub(crate) enum Resampled {
+    /// The event is a move, held until the moves are resampled.
+    Held,
+    /// The event goes on, after the last move of its touch or its pen when it was held.
+    Passed(Option<(WindowId, WindowEvent<'static>)>),
+}
+
+pub(crate) struct Resampler {
+    resampling: MotionResampling,
+    motions: Vec<Motion>,
+    /// The refreshes of the windows which were presented.
+    refreshes: Vec<Refresh>,
+}
+
+/// The last presentation of a window, and the refresh period of its monitor.
+struct Refresh {
+    window_id: WindowId,
+    presented: Instant,
+    period: Duration,
+}
+
+impl Refresh {
+    /// The last refresh at or before `now`. The presentations may be predicted, and so be later.
+    fn before(&self, now: Instant) -> Instant {
+        let period = self.period.as_secs_f64();
+        if now >= self.presented {
+            let refreshes = ((now - self.presented).as_secs_f64() / period).floor();
+            self.presented + self.period.mul_f64(refreshes)
+        } else {
+            let refreshes = ((self.presented - now).as_secs_f64() / period).ceil();
+            self.presented - self.period.mul_f64(refreshes)
+        }
+    }
+}
+
+/// A touch or a pen, with its two last samples.
+struct Motion {
+    window_id: WindowId,
+    id: MotionId,
+    previous: Option<(Instant, PhysicalPosition<f64>)>,
+    latest: Sample,
+    /// Whether the latest sample is a move which wasn't sent yet.
+    pending: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MotionId {
+    Touch(DeviceId, u64),
+    Pen(DeviceId, u64),
+}
+
+#[derive(Clone, Copy)]
+enum Sample {
+    Touch(Touch),
+    Pen(PenEvent),
+}
+
+impl Sample {
+    fn id(&self) -> MotionId {
+        match self {
+            Sample::Touch(touch) => MotionId::Touch(touch.device_id, touch.id),
+            Sample::Pen(pen) => MotionId::Pen(pen.device_id, pen.tool_id),
+        }
+    }
+
+    fn time(&self) -> Instant {
+        match self {
+            Sample::Touch(touch) => touch.time,
+            Sample::Pen(pen) => pen.time,
+        }
+    }
+
+    fn location(&self) -> PhysicalPosition<f64> {
+        match self {
+            Sample::Touch(touch) => touch.location,
+            Sample::Pen(pen) => pen.location,
+        }
+    }
+
+    fn event(self) -> WindowEvent<'static> {
+        match self {
+            Sample::Touch(touch) => WindowEvent::Touch(touch),
+            Sample::Pen(pen) => WindowEvent::Pen(pen),
+        }
+    }
+
+    fn resampled(self, time: Instant, location: PhysicalPosition<f64>) -> WindowEvent<'static> {
+        match self {
+            Sample::Touch(touch) => WindowEvent::Touch(Touch {
+                time,
+                location,
+                ..touch
+            }),
+            Sample::Pen(pen) => WindowEvent::Pen(PenEvent {
+                time,
+                location,
+                ..pen
+            }),
+        }
+    }
+}
+
+impl Resampler {
+    pub fn new(resampling: MotionResampling) -> Self {
+        Self {
+            resampling,
+            motions: Vec::new(),
+            refreshes: Vec::new(),
+        }
+    }
+
+    /// Takes the event as it reaches the event loop, holding it when it's a move. The moves are
+    /// timed by the platform, as they may be queued a while before they reach the event loop.
+    /// The presentations of the windows time their refreshes.
+    pub fn push(&mut self, window_id: WindowId, event: &WindowEvent<'_>) -> Resampled {
+        // Whether the sample moves, and whether its touch or its pen is over.
+        let (sample, moved, over) = match *event {
+            WindowEvent::Touch(touch) => {
+                let over = matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled);
+                (Sample::Touch(touch), touch.phase == TouchPhase::Moved, over)
+            }
+            WindowEvent::Pen(pen) => {
+                let over = pen.phase == PenPhase::Left;
+                (Sample::Pen(pen), pen.phase == PenPhase::Moved, over)
+            }
+            WindowEvent::Presented {
+                time,
+                refresh: Some(period),
+                ..
+            } if period > Duration::from_secs(0) => {
+                self.refreshes
+                    .retain(|refresh| refresh.window_id != window_id);
+                self.refreshes.push(Refresh {
+                    window_id,
+                    presented: time,
+                    period,
+                });
+                return Resampled::Passed(None);
+            }
+            WindowEvent::Destroyed => {
+                self.motions.retain(|motion| motion.window_id != window_id);
+                self.refreshes
+                    .retain(|refresh| refresh.window_id != window_id);
+                return Resampled::Passed(None);
+            }
+            _ => return Resampled::Passed(None),
+        };
+
+        let id = sample.id();
+        let index = self
+            .motions
+            .iter()
+            .position(|motion| motion.window_id == window_id && motion.id == id);
+        let motion = match index {
+            Some(index) => &mut self.motions[index],
+            None => {
+                if !over {
+                    self.motions.push(Motion {
+                        window_id,
+                        id,
+                        previous: None,
+                        latest: sample,
+                        pending: moved,
+                    });
+                }
+                return if moved {
+                    Resampled::Held
+                } else {
+                    Resampled::Passed(None)
+                };
+            }
+        };
+
+        // The last move goes before the other events, without being resampled.
+        let flushed = match (moved, motion.pending) {
+            (false, true) => Some((window_id, motion.latest.event())),
+            _ => None,
+        };
+        motion.previous = Some((motion.latest.time(), motion.latest.location()));
+        motion.latest = sample;
+        motion.pending = moved;
+
+        if over {
+            self.motions.remove(index.unwrap());
+        }
+        if moved {
+            Resampled::Held
+        } else {
+            Resampled::Passed(flushed)
+        }
+    }
+
+    /// Sends the held moves, at their position `latency` before the last refresh of their window
+    /// at `now`, or before `now` for the windows which weren't presented.
+    pub fn resample(&mut self, now: Instant) -> Vec<(WindowId, WindowEvent<'static>)> {
+        let latency = self.resampling.latency.as_secs_f64();
+        let max_prediction = self.resampling.max_prediction.as_secs_f64();
+        let refreshes = &self.refreshes;
+        let mut events = Vec::new();
+        for motion in self.motions.iter_mut().filter(|motion| motion.pending) {
+            motion.pending = false;
+            let now = refreshes
+                .iter()
+                .find(|refresh| refresh.window_id == motion.window_id)
+                .map_or(now, |refresh| refresh.before(now));
+            let sample = motion.latest;
+            let (time, location) = (sample.time(), sample.location());
+            let event = match motion.previous {
+                Some((previous_time, previous_location)) if time > previous_time => {
+                    let interval = (time - previous_time).as_secs_f64();
+                    // The time of the resampling, from the latest sample.
+                    let offset = (now - time).as_secs_f64() - latency;
+                    let offset = if offset > 0. {
+                        // The prediction doesn't go further than half of the last interval.
+                        offset.min(max_prediction).min(interval / 2.)
+                    } else {
+                        offset.max(-interval)
+                    };
+                    let ratio = 1. + offset / interval;
+                    let location = PhysicalPosition::new(
+                        previous_location.x + (location.x - previous_location.x) * ratio,
+                        previous_location.y + (location.y - previous_location.y) * ratio,
+                    );
+                    let time = if offset > 0. {
+                        time + Duration::from_secs_f64(offset)
+                    } else {
+                        time - Duration::from_secs_f64(-offset)
+                    };
+                    sample.resampled(time, location)
+                }
+                _ => sample.event(),
+            };
+            events.push((motion.window_id, event));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::PresentationFlags;
+
+    fn touch(phase: TouchPhase, x: f64, time: Instant) -> WindowEvent<'static> {
+        WindowEvent::Touch(Touch {
+            device_id: unsafe { DeviceId::dummy() },
+            phase,
+            location: PhysicalPosition::new(x, 0.),
+            time,
+            force: None,
+            id: 0,
+            palm: false,
+        })
+    }
+
+    /// A touch moving by 10 pixels every 10 milliseconds, last at 20 pixels.
+    fn moving_touch(resampling: MotionResampling, start: Instant) -> Resampler {
+        let window_id = unsafe { WindowId::dummy() };
+        let mut resampler = Resampler::new(resampling);
+        let samples = [
+            (TouchPhase::Started, 0.),
+            (TouchPhase::Moved, 10.),
+            (TouchPhase::Moved, 20.),
+        ];
+        for &(phase, x) in samples.iter() {
+            let time = start + Duration::from_millis(x as u64);
+            resampler.push(window_id, &touch(phase, x, time));
+        }
+        resampler
+    }
+
+    /// The position and the time of the single move resampled.
+    fn resampled(resampler: &mut Resampler, now: Instant) -> (f64, Instant) {
+        match resampler.resample(now).as_slice() {
+            [(_, WindowEvent::Touch(touch))] => (touch.location.x, touch.time),
+            _ => panic!("expected a single resampled move"),
+        }
+    }
+
+    fn assert_resampled((x, time): (f64, Instant), expected_x: f64, expected_time: Instant) {
+        assert!((x - expected_x).abs() < 1e-6, "{} != {}", x, expected_x);
+        let error = if time > expected_time {
+            time - expected_time
+        } else {
+            expected_time - time
+        };
+        assert!(error < Duration::from_micros(1), "{:?} off", error);
+    }
+
+    #[test]
+    fn moves_are_interpolated() {
+        let resampling = MotionResampling::default();
+        let start = Instant::now();
+        let mut resampler = moving_touch(resampling, start);
+        // Resampled 5 milliseconds before the time of the resampling, between the two last moves.
+        let now = start + Duration::from_millis(22);
+        let expected_time = start + Duration::from_millis(17);
+        assert_resampled(resampled(&mut resampler, now), 17., expected_time);
+        // The held moves are only sent once.
+        assert!(resampler.resample(now).is_empty());
+    }
+
+    #[test]
+    fn moves_are_extrapolated() {
+        let resampling = MotionResampling {
+            latency: Duration::from_millis(5),
+            max_prediction: Duration::from_millis(2),
+        };
+        let start = Instant::now();
+        let mut resampler = moving_touch(resampling, start);
+        let now = start + Duration::from_millis(30);
+        let expected_time = start + Duration::from_millis(22);
+        assert_resampled(resampled(&mut resampler, now), 22., expected_time);
+
+        // The prediction doesn't go further than half of the last interval.
+        let resampling = MotionResampling {
+            max_prediction: Duration::from_millis(8),
+            ..resampling
+        };
+        let mut resampler = moving_touch(resampling, start);
+        let now = start + Duration::from_millis(50);
+        let expected_time = start + Duration::from_millis(25);
+        assert_resampled(resampled(&mut resampler, now), 25., expected_time);
+    }
+
+    #[test]
+    fn moves_are_resampled_at_the_refresh() {
+        let window_id = unsafe { WindowId::dummy() };
+        let start = Instant::now();
+        let refresh = Duration::from_millis(16);
+        let presented = |time: Instant| WindowEvent::Presented {
+            time,
+            refresh: Some(refresh),
+            flags: PresentationFlags::VSYNC,
+        };
+        // Resampled 5 milliseconds before the refresh at 24 milliseconds, two refreshes after
+        // the presentation.
+        let mut resampler = moving_touch(MotionResampling::default(), start);
+        resampler.push(window_id, &presented(start - Duration::from_millis(8)));
+        let now = start + Duration::from_millis(30);
+        let expected_time = start + Duration::from_millis(19);
+        assert_resampled(resampled(&mut resampler, now), 19., expected_time);
+
+        // The same refresh, from a presentation predicted later.
+        let mut resampler = moving_touch(MotionResampling::default(), start);
+        resampler.push(window_id, &presented(start + Duration::from_millis(40)));
+        assert_resampled(resampled(&mut resampler, now), 19., expected_time);
+    }
+
+    #[test]
+    fn moves_are_flushed_before_the_other_events() {
+        let window_id = unsafe { WindowId::dummy() };
+        let start = Instant::now();
+        let mut resampler = moving_touch(MotionResampling::default(), start);
+        let time = start + Duration::from_millis(25);
+        match resampler.push(window_id, &touch(TouchPhase::Ended, 20., time)) {
+            Resampled::Passed(Some((_, WindowEvent::Touch(touch)))) => {
+                // The last move as it was sent by the platform.
+                assert_eq!(touch.phase, TouchPhase::Moved);
+                assert_eq!(touch.time, start + Duration::from_millis(20));
+            }
+            _ => panic!("expected the last move to be flushed"),
+        }
+        let now = start + Duration::from_millis(30);
+        assert!(resampler.resample(now).is_empty());
+    }
+}
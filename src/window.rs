@@ -1,10 +1,18 @@
 //! The `Window` struct and associated types.
-use std::fmt;
+use std::{fmt, marker::PhantomData, sync::Arc, time::Duration};
+
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
 
 use crate::{
+    clipboard::{
+        CallbackProvider, ClipboardConsumer, ClipboardKind, ClipboardMimedContent,
+        ClipboardProvider, MimePicker, MimeType,
+    },
+    dnd::{DndActions, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
-    error::{ExternalError, NotSupportedError, OsError},
+    error::{ExternalError, HandleError, NotSupportedError, OsError},
     event_loop::EventLoopWindowTarget,
+    icon::RgbaIcon,
     monitor::{MonitorHandle, VideoMode},
     platform_impl,
 };
@@ -141,6 +149,13 @@ pub struct WindowAttributes {
     /// [`Window::set_outer_position`]: crate::window::Window::set_outer_position
     pub position: Option<Position>,
 
+    /// How the window is placed when it's given no position.
+    ///
+    /// The default is `None`, which leaves the placement to the system.
+    ///
+    /// See [`WindowBuilder::with_placement`].
+    pub placement: Option<Placement>,
+
     /// Whether the window is resizable or not.
     ///
     /// The default is `true`.
@@ -177,6 +192,16 @@ pub struct WindowAttributes {
     /// The default is `true`.
     pub decorations: bool,
 
+    /// How the title bar of the decorations is drawn.
+    ///
+    /// The default is [`TitlebarStyle::Visible`].
+    pub titlebar_style: TitlebarStyle,
+
+    /// Whether the window should have a drop shadow.
+    ///
+    /// The default is `true`.
+    pub shadow: bool,
+
     /// Whether the window should always be on top of other windows.
     ///
     /// The default is `false`.
@@ -186,6 +211,16 @@ pub struct WindowAttributes {
     ///
     /// The default is `None`.
     pub window_icon: Option<Icon>,
+
+    /// Whether the keys held down send repeated presses to the window.
+    ///
+    /// The default is `true`.
+    pub key_repeat: bool,
+
+    /// The window owning the window, which is kept above it.
+    ///
+    /// The default is `None`.
+    pub owner: Option<WindowId>,
 }
 
 impl Default for WindowAttributes {
@@ -196,6 +231,7 @@ impl Default for WindowAttributes {
             min_inner_size: None,
             max_inner_size: None,
             position: None,
+            placement: None,
             resizable: true,
             title: "winit window".to_owned(),
             maximized: false,
@@ -203,8 +239,12 @@ impl Default for WindowAttributes {
             visible: true,
             transparent: false,
             decorations: true,
+            titlebar_style: TitlebarStyle::Visible,
+            shadow: true,
             always_on_top: false,
             window_icon: None,
+            key_repeat: true,
+            owner: None,
         }
     }
 }
@@ -260,6 +300,25 @@ impl WindowBuilder {
         self
     }
 
+    /// Sets how the window is placed, when it's given no position with
+    /// [`WindowBuilder::with_position`]. The window is kept within the work area of its monitor,
+    /// which leaves out the panels and the docks of the desktop.
+    ///
+    /// The windows created fullscreen or maximized aren't placed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** [`Placement::Cascade`] is the placement of the system.
+    /// - **X11:** The window is placed with its inner size, as the size of its frame is only
+    ///   known once it's shown.
+    /// - **macOS:** [`Placement::CenterOnMonitor`] is the placement of the system.
+    /// - **iOS / Android / Web / Wayland:** Unsupported, the system places the windows.
+    #[inline]
+    pub fn with_placement(mut self, placement: Placement) -> Self {
+        self.window.placement = Some(placement);
+        self
+    }
+
     /// Sets whether the window is resizable or not.
     ///
     /// See [`Window::set_resizable`] for details.
@@ -333,6 +392,39 @@ impl WindowBuilder {
         self
     }
 
+    /// Sets how the title bar of the decorations is drawn, which can leave the title bar to the
+    /// content of the window while the window is still resized and moved by the system. Ignored
+    /// for the windows without decorations.
+    ///
+    /// The content drawn under the title bar is moved with [`Window::drag_window`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** [`TitlebarStyle::ExtendContent`] is the full size content view of the window.
+    /// - **Windows:** The client area is extended over the title bar, with the caption buttons of
+    ///   [`TitlebarStyle::ExtendContent`] drawn by the DWM in the part left transparent. The title
+    ///   bar of [`TitlebarStyle::Transparent`] keeps its color.
+    /// - **Wayland:** The decorations drawn by winit leave out the title for
+    ///   [`TitlebarStyle::Transparent`]. The window has none for [`TitlebarStyle::Hidden`] and
+    ///   [`TitlebarStyle::ExtendContent`], so it isn't resized by the user.
+    /// - **iOS / Android / Web / X11:** Unsupported.
+    #[inline]
+    pub fn with_titlebar_style(mut self, titlebar_style: TitlebarStyle) -> Self {
+        self.window.titlebar_style = titlebar_style;
+        self
+    }
+
+    /// Sets whether the window should have a drop shadow.
+    ///
+    /// See [`Window::set_shadow`] for details.
+    ///
+    /// [`Window::set_shadow`]: crate::window::Window::set_shadow
+    #[inline]
+    pub fn with_shadow(mut self, shadow: bool) -> Self {
+        self.window.shadow = shadow;
+        self
+    }
+
     /// Sets whether or not the window will always be on top of other windows.
     ///
     /// See [`Window::set_always_on_top`] for details.
@@ -355,6 +447,37 @@ impl WindowBuilder {
         self
     }
 
+    /// Sets whether the keys held down send repeated presses to the window.
+    ///
+    /// See [`Window::set_key_repeat`] for details.
+    ///
+    /// [`Window::set_key_repeat`]: crate::window::Window::set_key_repeat
+    #[inline]
+    pub fn with_key_repeat(mut self, key_repeat: bool) -> Self {
+        self.window.key_repeat = key_repeat;
+        self
+    }
+
+    /// Sets the window owning the window. The owned window is kept above its owner, and is
+    /// minimized and closed along with it. It can then be made modal with [`Window::set_modal`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The owner becomes the owner window of the `HWND`, as with
+    ///   `WindowBuilderExtWindows::with_owner_window`, which takes precedence along with
+    ///   `WindowBuilderExtWindows::with_parent_window`.
+    /// - **X11:** Sets `WM_TRANSIENT_FOR` to the owner.
+    /// - **macOS:** The window becomes a child window of the owner.
+    /// - **Wayland:** Unsupported, the building fails.
+    /// - **iOS / Android / Web:** Unsupported.
+    ///
+    /// [`Window::set_modal`]: crate::window::Window::set_modal
+    #[inline]
+    pub fn with_owner(mut self, owner: &Window) -> Self {
+        self.window.owner = Some(owner.id());
+        self
+    }
+
     /// Builds the window.
     ///
     /// Possible causes of error include denied permission, incompatible system, and lack of memory.
@@ -443,6 +566,95 @@ impl Window {
     pub fn request_redraw(&self) {
         self.window.request_redraw()
     }
+
+    /// Sets whether the redraws requested with [`Window::request_redraw`] are synchronized with
+    /// the refresh of the monitor of the window.
+    ///
+    /// When turned on, the requests are held until the monitor is about to refresh, then
+    /// coalesced into a single `RedrawRequested`. The animations may then keep requesting redraws
+    /// with [`ControlFlow::Wait`](crate::event_loop::ControlFlow::Wait) instead of spinning with
+    /// [`ControlFlow::Poll`](crate::event_loop::ControlFlow::Poll). The redraws asked by the
+    /// system, such as on resizes, are never held.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Synchronized with the composition of the desktop by the DWM, the redraws
+    ///   being held by a thread waiting on `DwmFlush`.
+    /// - **X11:** Only with the Present extension.
+    /// - **Wayland:** Synchronized with the frame callbacks, so a frame is expected to be
+    ///   committed on every redraw. The redraws stop while the compositor doesn't call back,
+    ///   such as when the window is hidden.
+    /// - **macOS:** Synchronized with a `CVDisplayLink` of the displays.
+    /// - **Web:** Unsupported, as [`ControlFlow::Poll`](crate::event_loop::ControlFlow::Poll)
+    ///   already runs on the animation frames.
+    /// - **iOS / Android:** Unsupported.
+    #[inline]
+    pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+        self.window.set_vsync_redraws(vsync_redraws)
+    }
+
+    /// Notifies that the window is about to present a frame, right before the buffers are
+    /// swapped or the frame is otherwise submitted.
+    ///
+    /// The backend then does its work for the frame at the point it's presented rather than
+    /// before `RedrawRequested`, such as requesting when the next frame is to be drawn, which
+    /// keeps the resizes smooth. Once called, it's expected to be called for every frame.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requests the frame callback with the frame, which the redraws synchronized
+    ///   with [`Window::set_vsync_redraws`] then wait for. Ignored outside the thread of the
+    ///   event loop, the callback being requested before the redraws instead.
+    /// - **macOS / iOS:** Times the presentation of the frame, which is then sent as
+    ///   [`WindowEvent::Presented`](crate::event::WindowEvent::Presented).
+    /// - **Windows / X11 / Android / Web:** Unsupported.
+    #[inline]
+    pub fn pre_present_notify(&self) {
+        self.window.pre_present_notify()
+    }
+
+    /// Presents the pixels in the window, for the applications drawing in software rather than
+    /// with a graphics API.
+    ///
+    /// The pixels are the rows of `size.width` pixels from the top, each pixel being
+    /// `0x00RRGGBB`. They're put at the top-left corner of the window and are clipped to it, so
+    /// `size` is usually [`Window::inner_size`]. The windows drawn with a graphics API mustn't be
+    /// presented to as well.
+    ///
+    /// Only available with the `software-present` feature.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if there aren't `size.width * size.height` pixels.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Drawn with `StretchDIBits`.
+    /// - **X11:** Put through the shared memory of the MIT-SHM extension, or sent through the
+    ///   connection when the server can't attach it. Returns [`ExternalError::NotSupported`] if
+    ///   the visual of the window doesn't have 8 bits per channel.
+    /// - **Wayland:** Attached to the surface from a shared memory buffer, the surface taking the
+    ///   size of the pixels. The pixels are dropped while the compositor still reads the two
+    ///   previous buffers.
+    /// - **macOS:** Set as the `CGImage` contents of the layer of the view.
+    /// - **iOS / Android / Web:** Always returns [`ExternalError::NotSupported`].
+    #[cfg(feature = "software-present")]
+    #[inline]
+    pub fn present_pixels(
+        &self,
+        pixels: &[u32],
+        size: PhysicalSize<u32>,
+    ) -> Result<(), ExternalError> {
+        assert_eq!(
+            pixels.len(),
+            size.width as usize * size.height as usize,
+            "the pixels don't have the size"
+        );
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+        self.window.present_pixels(pixels, size)
+    }
 }
 
 /// Position and size functions.
@@ -549,6 +761,32 @@ impl Window {
         self.window.outer_size()
     }
 
+    /// Returns the largest inner size the window is recommended to take, which is the area of its
+    /// monitor left for the windows by the panels and the docks, without the decorations. The
+    /// windows can choose their initial size against it on the small screens.
+    ///
+    /// Returns `None` if the bounds are unknown. [`WindowEvent::RecommendedBounds`] is sent when
+    /// they change.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The work area of the monitor of the window. The event is sent when the
+    ///   window moves, or the work area changes.
+    /// - **X11:** The part of `_NET_WORKAREA` over the monitor of the window, or the monitor
+    ///   itself if the window manager doesn't set it. The event is sent when the window is
+    ///   configured.
+    /// - **macOS:** The visible frame of the screen of the window. The event is sent when the
+    ///   window is resized or changes screens.
+    /// - **Wayland:** Unsupported, always `None`, as the bounds of `xdg_toplevel` need a newer
+    ///   `xdg_wm_base` than the one in use.
+    /// - **iOS / Android / Web:** Unsupported, always `None`.
+    ///
+    /// [`WindowEvent::RecommendedBounds`]: crate::event::WindowEvent::RecommendedBounds
+    #[inline]
+    pub fn recommended_bounds(&self) -> Option<PhysicalSize<u32>> {
+        self.window.recommended_bounds()
+    }
+
     /// Sets a minimum dimension size for the window.
     ///
     /// ## Platform-specific
@@ -568,6 +806,25 @@ impl Window {
     pub fn set_max_inner_size<S: Into<Size>>(&self, max_size: Option<S>) {
         self.window.set_max_inner_size(max_size.map(|s| s.into()))
     }
+
+    /// Sets the ratio of the width to the height the inner size keeps while the user resizes the
+    /// window, such as `16:9` for a video player. `None` lets the user resize it freely.
+    ///
+    /// Only the resizes of the user keep the ratio: the sizes requested with
+    /// [`Window::set_inner_size`], or chosen when the window is maximized, may not.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Adjusts the size dragged with `WM_SIZING`, keeping the dragged edge.
+    /// - **X11:** Sets the aspect of `WM_NORMAL_HINTS`, which the window manager enforces.
+    /// - **Wayland:** The sizes configured while the window is resized are shrunk to the
+    ///   largest ones of the ratio.
+    /// - **macOS:** Sets `contentAspectRatio`.
+    /// - **iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_aspect_ratio(&self, ratio: Option<Ratio>) {
+        self.window.set_aspect_ratio(ratio)
+    }
 }
 
 /// Misc. attribute functions.
@@ -694,6 +951,64 @@ impl Window {
         self.window.set_decorations(decorations)
     }
 
+    /// Turn the drop shadow of the window on or off, which the custom-shaped and transparent
+    /// windows may not want around their rectangle.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Sets `hasShadow`, as with `WindowExtMacOS::set_has_shadow`.
+    /// - **Windows:** The decorated windows always have the shadow of their frame. The shadow is
+    ///   only drawn around an undecorated window if it isn't transparent, by extending the DWM
+    ///   frame into it.
+    /// - **X11:** Sets `_COMPTON_SHADOW`, which the compositors of the Compton lineage, such as
+    ///   picom, follow. Whether the other window managers draw a shadow is up to them.
+    /// - **iOS / Android / Web / Wayland:** Unsupported.
+    #[inline]
+    pub fn set_shadow(&self, shadow: bool) {
+        self.window.set_shadow(shadow)
+    }
+
+    /// Sets the opacity of the whole window, along with its decorations, from `0.0` for fully
+    /// transparent to `1.0` for opaque. The values out of this range are clamped.
+    ///
+    /// Unlike [`WindowBuilder::with_transparent`], this doesn't need the content to be rendered
+    /// with an alpha channel.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Makes the window layered with `SetLayeredWindowAttributes`, which is
+    ///   turned off again once the window is opaque.
+    /// - **X11:** Sets `_NET_WM_WINDOW_OPACITY`, which is only followed with a compositor
+    ///   running.
+    /// - **macOS:** Sets `alphaValue`.
+    /// - **iOS / Android / Web / Wayland:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        self.window.set_opacity(opacity.clamp(0.0, 1.0))
+    }
+
+    /// Sets the effect shown behind the transparent parts of the window, which needs the window
+    /// to be made transparent with [`WindowBuilder::with_transparent`]. The effects the system
+    /// doesn't have fall back to [`Backdrop::Blur`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** [`Backdrop::Mica`] and [`Backdrop::Acrylic`] are the system backdrops of
+    ///   DWM from Windows 11 on, the window being drawn over them as its frame. Before, the
+    ///   acrylic and the blur are the accents of the window, from Windows 10 on. Returns an
+    ///   [`ExternalError::NotSupported`] on the earlier versions.
+    /// - **macOS:** An `NSVisualEffectView` is placed behind the content view, with the material
+    ///   of the sidebars for [`Backdrop::Vibrancy`].
+    /// - **X11:** Sets `_KDE_NET_WM_BLUR_BEHIND_REGION`, which is followed by KWin. Whether the
+    ///   other compositors, such as picom, blur the window is up to their configuration.
+    /// - **Wayland:** Requires the `org_kde_kwin_blur_manager` protocol, all the effects being
+    ///   the blur.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_backdrop(&self, backdrop: Backdrop) -> Result<(), ExternalError> {
+        self.window.set_backdrop(backdrop)
+    }
+
     /// Change whether or not the window will always be on top of other windows.
     ///
     /// ## Platform-specific
@@ -723,12 +1038,111 @@ impl Window {
 
     /// Sets location of IME candidate box in client area coordinates relative to the top left.
     ///
+    /// This is the same as [`Window::set_ime_cursor_area`] with an empty area.
+    ///
     /// ## Platform-specific
     ///
     /// - **iOS / Android / Web:** Unsupported.
     #[inline]
     pub fn set_ime_position<P: Into<Position>>(&self, position: P) {
-        self.window.set_ime_position(position.into())
+        self.window
+            .set_ime_cursor_area(position.into(), PhysicalSize::new(0, 0).into())
+    }
+
+    /// Sets the area of the text being edited, in client area coordinates relative to the top
+    /// left, which the candidate window of the input method is placed next to without covering it.
+    ///
+    /// The area is usually the cursor, or the text being composed. Where the candidate window
+    /// actually appeared is reported through [`WindowEvent::ImeCandidateArea`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** XIM only knows of a spot, which is set to the bottom left of the area so the
+    ///   candidate window appears below it.
+    /// - **iOS / Android / Web:** Unsupported.
+    ///
+    /// [`WindowEvent::ImeCandidateArea`]: crate::event::WindowEvent::ImeCandidateArea
+    #[inline]
+    pub fn set_ime_cursor_area<P: Into<Position>, S: Into<Size>>(&self, position: P, size: S) {
+        self.window
+            .set_ime_cursor_area(position.into(), size.into())
+    }
+
+    /// Sets the text around the cursor, which the input method reads to reconvert the text and
+    /// to adapt its candidates to the context.
+    ///
+    /// `cursor` and `anchor` are the byte offsets in `text` of the cursor and of the other end of
+    /// the selection, which are the same when nothing is selected. The text should be set again
+    /// whenever it or the cursor changes. The input method asks to delete some of it through
+    /// [`WindowEvent::ImeDeleteSurrounding`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The text is trimmed around the cursor to the 4000 bytes `text-input-v3`
+    ///   allows.
+    /// - **Windows:** The input methods of the Text Services Framework read and edit the text,
+    ///   and the ones of IMM32 only reconvert the selection.
+    /// - **X11 / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`WindowEvent::ImeDeleteSurrounding`]: crate::event::WindowEvent::ImeDeleteSurrounding
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        self.window.set_ime_surrounding_text(text, cursor, anchor)
+    }
+
+    /// Sets the purpose of the text being edited, which the input methods and the virtual
+    /// keyboards adapt to, like by not remembering the passwords in their history.
+    ///
+    /// The windows start with [`ImePurpose::Normal`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Sets the input scope of the window, and turns the input method off for
+    ///   the passwords.
+    /// - **macOS:** Only [`ImePurpose::Password`] is supported, which turns on the secure
+    ///   keyboard entry and restricts the input sources to the Roman ones.
+    /// - **X11 / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        self.window.set_ime_purpose(purpose)
+    }
+
+    /// Sets whether the keys held down send repeated presses to the window.
+    ///
+    /// When turned off, the presses which would have [`KeyboardInput::repeat`] set and the
+    /// characters they produce are dropped, which games usually want.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Web:** Unsupported.
+    ///
+    /// [`KeyboardInput::repeat`]: crate::event::KeyboardInput::repeat
+    #[inline]
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        self.window.set_key_repeat(key_repeat)
+    }
+
+    /// Sets whether the window receives the keyboard shortcuts of the system while it has focus.
+    ///
+    /// When inhibited, the keys of the shortcuts such as <kbd>Alt</kbd> + <kbd>Tab</kbd>, the
+    /// <kbd>Super</kbd> key and the media keys are sent to the window instead of the system,
+    /// which virtual machine viewers, remote desktop clients and kiosk applications want. The
+    /// shortcuts come back once the window loses focus.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Uses a low-level keyboard hook. <kbd>Ctrl</kbd> + <kbd>Alt</kbd> +
+    ///   <kbd>Delete</kbd> always reaches the system.
+    /// - **macOS:** Uses an event tap, which needs the application to be trusted for
+    ///   accessibility. Returns an [`ExternalError::Os`] otherwise.
+    /// - **X11:** Grabs the keyboard while the window has focus.
+    /// - **Wayland:** The compositor may refuse to inhibit the shortcuts, or ask the user first.
+    ///   Returns an [`ExternalError::NotSupported`] if the compositor doesn't support
+    ///   `zwp_keyboard_shortcuts_inhibit_manager_v1`.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_system_shortcuts_inhibited(&self, inhibited: bool) -> Result<(), ExternalError> {
+        self.window.set_system_shortcuts_inhibited(inhibited)
     }
 
     /// Brings the window to the front and sets input focus. Has no effect if the window is
@@ -762,12 +1176,157 @@ impl Window {
     pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
         self.window.request_user_attention(request_type)
     }
+
+    /// Sets whether the window is modal for its owner, set with [`WindowBuilder::with_owner`].
+    /// The input of the owner is blocked while the window is modal.
+    ///
+    /// This has no effect on the windows without an owner.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Disables the owner window until the window stops being modal or is dropped.
+    /// - **X11:** Sets `_NET_WM_STATE_MODAL`, the window manager blocking the owner.
+    /// - **macOS:** The window is shown as a sheet of its owner.
+    /// - **iOS / Android / Web / Wayland:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowBuilder::with_owner`]: crate::window::WindowBuilder::with_owner
+    #[inline]
+    pub fn set_modal(&self, modal: bool) -> Result<(), ExternalError> {
+        self.window.set_modal(modal)
+    }
+
+    /// Shows the progress of a long operation on the taskbar button or the dock icon.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Uses `ITaskbarList3`, showing the progress on the taskbar button of the
+    ///   window.
+    /// - **Linux:** Uses the `com.canonical.Unity.LauncherEntry` D-Bus API, through `libdbus`.
+    ///   The progress is shared by the windows, on the desktop entry the application was
+    ///   launched from, or the one named after its executable. `Indeterminate` hides the
+    ///   progress, and `Error` asks for attention.
+    /// - **macOS:** Draws a progress bar on the dock tile of the application, which the windows
+    ///   share. `Paused` and `Error` are shown as `Normal`.
+    /// - **iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_progress(&self, state: ProgressState) {
+        self.window.set_progress(state)
+    }
+
+    /// Shows the count, such as of the unread messages, as a badge on the taskbar button or the
+    /// dock icon. `None` removes the badge.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Shows the count as the overlay icon of the taskbar button of the window,
+    ///   the counts above 99 being shown as `99+`.
+    /// - **Linux:** Uses the `com.canonical.Unity.LauncherEntry` D-Bus API, like
+    ///   [`Window::set_progress`]. The count is shared by the windows.
+    /// - **macOS:** Sets the badge label of the dock tile of the application, which the windows
+    ///   share.
+    /// - **iOS:** Sets the badge number of the application icon, which the user has to allow
+    ///   with the authorization of the notifications. `Some(0)` removes the badge.
+    /// - **Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_badge(&self, count: Option<u64>) {
+        self.window.set_badge(count)
+    }
+}
+
+/// Tab functions.
+///
+/// The windows of the same tab group are shown as the tabs of a single window, which the user
+/// switches between. The user may also drag a tab out of its group, which sends
+/// [`WindowEvent::TabDetached`] to its window.
+///
+/// ## Platform-specific
+///
+/// - **macOS:** Uses the native tabs of `NSWindow`, whose tab groups are the windows sharing the
+///   same `tabbingIdentifier`. The tab bar is shown once a group has several windows.
+/// - **Windows / Linux / iOS / Android / Web:** The windows have no tabs. The functions have no
+///   effect, and the windows are never in a tab group.
+///
+/// [`WindowEvent::TabDetached`]: crate::event::WindowEvent::TabDetached
+impl Window {
+    /// Returns the identifier of the tab group of the window, which the windows opened as its
+    /// tabs share.
+    #[inline]
+    pub fn tab_group(&self) -> Option<String> {
+        self.window.tab_group()
+    }
+
+    /// Sets the identifier of the tab group of the window. The windows of the same identifier
+    /// may be opened as tabs of each other, following the preference of the user.
+    #[inline]
+    pub fn set_tab_group(&self, group: &str) {
+        self.window.set_tab_group(group)
+    }
+
+    /// Adds the window as a tab of the tab group of `window`, right after it, taking the
+    /// identifier of its group.
+    #[inline]
+    pub fn merge_into_tab_group(&self, window: &Window) {
+        self.window.merge_into_tab_group(&window.window)
+    }
+
+    /// Selects the tab following the one of the window in its group, going back to the first one
+    /// after the last one.
+    #[inline]
+    pub fn select_next_tab(&self) {
+        self.window.select_next_tab()
+    }
+}
+
+/// Workspace functions.
+///
+/// The workspaces, also known as virtual desktops, each show their own set of windows, the user
+/// switching between them. They are numbered from 0, in their order on the system. A window may
+/// also be visible on all the workspaces, which sends [`WindowEvent::WorkspaceChanged`] as for
+/// the moves to another workspace.
+///
+/// ## Platform-specific
+///
+/// - **X11:** Uses `_NET_WM_DESKTOP`, with the window managers following the EWMH.
+/// - **Windows:** Uses `IVirtualDesktopManager`, the order of the virtual desktops being the one
+///   of the registry of Explorer. The windows can't be made visible on all the virtual desktops.
+/// - **macOS:** The windows may only be made visible on all the Spaces, through their collection
+///   behavior. The Spaces aren't exposed otherwise, so the windows are never on a workspace and
+///   can't be moved to one.
+/// - **Wayland / iOS / Android / Web:** The windows are never on a workspace, and the functions
+///   have no effect.
+///
+/// [`WindowEvent::WorkspaceChanged`]: crate::event::WindowEvent::WorkspaceChanged
+impl Window {
+    /// Returns the index of the workspace the window is on.
+    ///
+    /// Returns `None` if the window is visible on all the workspaces, or if its workspace is
+    /// unknown, as for the windows which were never shown.
+    #[inline]
+    pub fn workspace(&self) -> Option<u32> {
+        self.window.workspace()
+    }
+
+    /// Moves the window to the workspace of the given index, keeping the current workspace
+    /// shown. The window stops being visible on all the workspaces.
+    #[inline]
+    pub fn move_to_workspace(&self, workspace: u32) {
+        self.window.move_to_workspace(workspace)
+    }
+
+    /// Sets whether the window is visible on all the workspaces. The window is kept on the
+    /// current workspace once it isn't anymore.
+    #[inline]
+    pub fn set_visible_on_all_workspaces(&self, visible: bool) {
+        self.window.set_visible_on_all_workspaces(visible)
+    }
 }
 
 /// Cursor functions.
 impl Window {
     /// Modifies the cursor icon of the window.
     ///
+    /// This replaces the custom cursor set with [`Window::set_cursor`].
+    ///
     /// ## Platform-specific
     ///
     /// - **iOS / Android:** Unsupported.
@@ -776,11 +1335,65 @@ impl Window {
         self.window.set_cursor_icon(cursor);
     }
 
+    /// Sets a custom cursor for the window, until [`Window::set_cursor_icon`] sets a system
+    /// cursor back.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland / macOS:** The image isn't scaled for the scale factor, its pixels are
+    ///   logical pixels.
+    /// - **iOS / Android:** Unsupported.
+    #[inline]
+    pub fn set_cursor(&self, cursor: CustomCursor) {
+        self.window.set_cursor(cursor.inner);
+    }
+
+    /// Loads the cursor icons of the window from the theme `name`, with a size of `size` logical
+    /// pixels drawn at the scale factor of the window.
+    ///
+    /// An empty name keeps the theme of the system and a size of zero keeps its size, so
+    /// `set_cursor_theme("", 0)` goes back to the cursors of the system.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Replaces `XCURSOR_THEME`, `XCURSOR_SIZE` and the `Xcursor` resources for the
+    ///   window. The icons missing from the theme come from the one of the system.
+    /// - **Wayland:** Replaces the theme of the seats, `XCURSOR_THEME` and `XCURSOR_SIZE`, for the
+    ///   window. The cursors are drawn at the scale of the outputs they're on.
+    /// - **Windows:** The name is ignored, the system cursors are drawn at the size for the DPI
+    ///   of the window.
+    /// - **macOS / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_cursor_theme(&self, name: &str, size: u32) -> Result<(), ExternalError> {
+        self.window.set_cursor_theme(name, size)
+    }
+
+    /// The theme the cursor icons of the window are loaded from, set with
+    /// [`Window::set_cursor_theme`] or the one of the system.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The theme has no name.
+    /// - **macOS / iOS / Android / Web:** Always returns `None`.
+    #[inline]
+    pub fn cursor_theme(&self) -> Option<CursorTheme> {
+        self.window.cursor_theme()
+    }
+
     /// Changes the position of the cursor in window coordinates.
     ///
+    /// The physical position is the pixel the position falls into, for the cursor to stay inside
+    /// the window at the fractional scale factors.
+    ///
     /// ## Platform-specific
     ///
-    /// - **iOS / Android / Web / Wayland:** Always returns an [`ExternalError::NotSupported`].
+    /// - **Wayland:** Only while the pointer is locked with [`Window::set_pointer_lock`], and
+    ///   otherwise returns an [`ExternalError::NotSupported`]. The position is a hint of where the
+    ///   compositor puts the cursor once the pointer is unlocked, which sends a
+    ///   [`WindowEvent::CursorMoved`] with the position when it's applied to the window.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::CursorMoved`]: crate::event::WindowEvent::CursorMoved
     #[inline]
     pub fn set_cursor_position<P: Into<Position>>(&self, position: P) -> Result<(), ExternalError> {
         self.window.set_cursor_position(position.into())
@@ -800,6 +1413,86 @@ impl Window {
         self.window.set_cursor_grab(grab)
     }
 
+    /// Sets the area of the window the grabbed cursor is confined to, or the whole window with
+    /// `None`, which is the default.
+    ///
+    /// The area is in window coordinates, like the position of [`Window::set_cursor_position`],
+    /// and applies to the current grab of [`Window::set_cursor_grab`] as well as to the later
+    /// ones, for the cursor to stay in one of the viewports of the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the version 5 of the XFixes extension, whose pointer barriers
+    ///   surround the area.
+    /// - **Wayland:** Requires the `zwp_pointer_constraints_v1` protocol.
+    /// - **macOS / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_cursor_grab_area(
+        &self,
+        area: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        self.window.set_cursor_grab_area(area)
+    }
+
+    /// Locks the cursor in place and hides it, for the relative motion of the pointer to control
+    /// something like a camera.
+    ///
+    /// While the cursor is locked, the motion of the pointer keeps being sent as
+    /// [`DeviceEvent::MouseMotion`] deltas, even when the cursor would hit the edges of the window
+    /// or of the screen, and the cursor is back where it was once unlocked.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the `zwp_pointer_constraints_v1` protocol, and the deltas the
+    ///   `zwp_relative_pointer_manager_v1` one.
+    /// - **X11:** The cursor is grabbed, and put back where it was locked as it moves.
+    /// - **Windows:** The cursor is clipped to where it was locked.
+    /// - **macOS:** The cursor is dissociated from the mouse, like [`Window::set_cursor_grab`].
+    /// - **Web:** The lock is requested along with the next input of the user, as the browsers
+    ///   only allow it then, and they lift it when the user presses Escape.
+    /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`DeviceEvent::MouseMotion`]: crate::event::DeviceEvent::MouseMotion
+    #[inline]
+    pub fn set_pointer_lock(&self, lock: bool) -> Result<(), ExternalError> {
+        self.window.set_pointer_lock(lock)
+    }
+
+    /// Captures the pointer, for the window to keep getting its motion and its buttons while it's
+    /// outside of the window, until [`Window::release_pointer`].
+    ///
+    /// The pointer is captured without this while a button pressed inside the window is held, so
+    /// that the drags get their motion and their release wherever they end. The window still gets
+    /// a [`WindowEvent::CursorLeft`] when the captured pointer leaves it, then
+    /// [`WindowEvent::CursorMoved`] events with positions outside of it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** The pointer over the windows of the other applications is only captured
+    ///   while a button is held.
+    /// - **Wayland / macOS / Web / iOS / Android:** Always returns an
+    ///   [`ExternalError::NotSupported`], only the presses of the buttons capture the pointer.
+    ///
+    /// [`WindowEvent::CursorLeft`]: crate::event::WindowEvent::CursorLeft
+    /// [`WindowEvent::CursorMoved`]: crate::event::WindowEvent::CursorMoved
+    #[inline]
+    pub fn capture_pointer(&self) -> Result<(), ExternalError> {
+        self.window.capture_pointer()
+    }
+
+    /// Releases the pointer captured by [`Window::capture_pointer`].
+    ///
+    /// The buttons held keep it captured until they're released.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland / macOS / Web / iOS / Android:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn release_pointer(&self) -> Result<(), ExternalError> {
+        self.window.release_pointer()
+    }
+
     /// Modifies the cursor's visibility.
     ///
     /// If `false`, this will hide the cursor. If `true`, this will show the cursor.
@@ -834,6 +1527,177 @@ impl Window {
     }
 }
 
+/// Clipboard functions.
+impl Window {
+    /// Sets the content of the clipboard of the given `kind`.
+    ///
+    /// The content is served to other applications for as long as the event loop is running,
+    /// even when this window is closed, or until some other application takes over the
+    /// clipboard. This is the same as `EventLoopWindowTarget::set_clipboard`, and is provided
+    /// for convenience.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The clipboard is set asynchronously, errors are only logged. Requires a
+    ///   recent input event on the window's seat, like a key press. [`ClipboardKind::Primary`]
+    ///   requires the `zwp_primary_selection_device_manager_v1` protocol.
+    /// - **macOS / Windows:** The content is handed over to the system clipboard once some
+    ///   application requests it. [`ClipboardKind::Primary`] returns an
+    ///   [`ExternalError::NotSupported`].
+    /// - **Web:** Requires a secure context. Only text, HTML, PNG images and the custom
+    ///   formats prefixed with `web ` are supported. The clipboard is set asynchronously, errors
+    ///   are only logged. [`ClipboardKind::Primary`] returns an [`ExternalError::NotSupported`].
+    /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`ClipboardKind::Primary`]: crate::clipboard::ClipboardKind::Primary
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        content: ClipboardMimedContent,
+    ) -> Result<(), ExternalError> {
+        self.window.set_clipboard(kind, Arc::new(content))
+    }
+
+    /// Sets the content of the clipboard of the given `kind` to the data of the `provider`.
+    ///
+    /// The provider is asked to write the data each time some application requests it, so the
+    /// data doesn't have to be kept in memory. Otherwise this is the same as
+    /// [`Window::set_clipboard`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11 / macOS:** The data is collected into memory for each request.
+    /// - **Windows:** The data is collected into memory for each request, and for each of the
+    ///   offered kinds once the window owning the clipboard is destroyed.
+    /// - **Web:** The data of each supported kind is collected into memory right away.
+    /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_clipboard_stream(
+        &self,
+        kind: ClipboardKind,
+        provider: Arc<dyn ClipboardProvider>,
+    ) -> Result<(), ExternalError> {
+        self.window.set_clipboard(kind, provider)
+    }
+
+    /// Sets the content of the clipboard of the given `kind`, which is only rendered by
+    /// `render` once some application requests the data of one of the `mime_types`.
+    ///
+    /// This avoids serializing large content which is never pasted. `render` is called with the
+    /// requested kind of data each time it's requested, possibly on a different thread.
+    /// Otherwise this is the same as [`Window::set_clipboard`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** `render` is also called for each of the `mime_types` once the window
+    ///   owning the clipboard is destroyed.
+    /// - **Web:** `render` is called for each of the `mime_types` right away.
+    /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_clipboard_provider<F>(
+        &self,
+        kind: ClipboardKind,
+        mime_types: Vec<MimeType>,
+        render: F,
+    ) -> Result<(), ExternalError>
+    where
+        F: FnMut(MimeType) -> ClipboardMimedContent + Send + 'static,
+    {
+        let provider = CallbackProvider::new(mime_types, render);
+        self.window.set_clipboard(kind, Arc::new(provider))
+    }
+
+    /// Requests the content of the clipboard of the given `kind`.
+    ///
+    /// Once the kinds of data offered by the clipboard owner are known, `picker` is called to
+    /// choose which one to read. The content is then delivered to this window through a
+    /// [`WindowEvent::ClipboardData`], since the data is transferred asynchronously on some
+    /// platforms.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** [`ClipboardKind::Primary`] requires the
+    ///   `zwp_primary_selection_device_manager_v1` protocol.
+    /// - **macOS / Windows:** The content is read right away, and `picker` is called before
+    ///   this function returns. [`ClipboardKind::Primary`] returns an
+    ///   [`ExternalError::NotSupported`].
+    /// - **Web:** Requires a secure context, and the browser may ask the user for the permission
+    ///   first. [`ClipboardKind::Primary`] returns an [`ExternalError::NotSupported`].
+    /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::ClipboardData`]: crate::event::WindowEvent::ClipboardData
+    /// [`ClipboardKind::Primary`]: crate::clipboard::ClipboardKind::Primary
+    #[inline]
+    pub fn read_clipboard(
+        &self,
+        kind: ClipboardKind,
+        picker: MimePicker,
+    ) -> Result<(), ExternalError> {
+        self.window.read_clipboard(kind, picker, None)
+    }
+
+    /// Requests the content of the clipboard of the given `kind`, and passes the data to the
+    /// `consumer` as it arrives instead of delivering a [`WindowEvent::ClipboardData`].
+    ///
+    /// Otherwise this is the same as [`Window::read_clipboard`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The consumer reads from the pipe of the clipboard owner on a separate
+    ///   thread.
+    /// - **X11:** The data is collected into memory before being passed to the consumer.
+    /// - **macOS / Windows:** The data is collected into memory before being passed to the
+    ///   consumer, which is called before this function returns.
+    /// - **Web:** The data is collected into memory before being passed to the consumer.
+    /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::ClipboardData`]: crate::event::WindowEvent::ClipboardData
+    #[inline]
+    pub fn read_clipboard_stream(
+        &self,
+        kind: ClipboardKind,
+        picker: MimePicker,
+        consumer: Box<dyn ClipboardConsumer>,
+    ) -> Result<(), ExternalError> {
+        self.window.read_clipboard(kind, picker, Some(consumer))
+    }
+}
+
+/// Drag and drop functions.
+impl Window {
+    /// Starts dragging the `data` from this window with the mouse button which is currently
+    /// pressed, allowing the drop target to choose any of the `allowed_actions`.
+    ///
+    /// The progress of the drag is delivered to this window through [`WindowEvent::DragEntered`]
+    /// and [`WindowEvent::DragMoved`], and its outcome through a [`WindowEvent::DragFinished`]
+    /// or a [`WindowEvent::DragCancelled`]. There's no guarantee that this will work unless a mouse
+    /// button was pressed immediately before this function is called.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** Must be called while handling a mouse event.
+    /// - **Wayland:** [`DndAction::Link`] isn't supported. Errors are only logged, since the drag
+    ///   is started asynchronously.
+    /// - **Windows:** This function blocks until the drag is over, while the system keeps
+    ///   dispatching the messages of the windows.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::DragEntered`]: crate::event::WindowEvent::DragEntered
+    /// [`WindowEvent::DragMoved`]: crate::event::WindowEvent::DragMoved
+    /// [`WindowEvent::DragFinished`]: crate::event::WindowEvent::DragFinished
+    /// [`WindowEvent::DragCancelled`]: crate::event::WindowEvent::DragCancelled
+    /// [`DndAction::Link`]: crate::dnd::DndAction::Link
+    #[inline]
+    pub fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: DndActions,
+    ) -> Result<(), ExternalError> {
+        self.window.start_drag(data, allowed_actions)
+    }
+}
+
 /// Monitor info functions.
 impl Window {
     /// Returns the monitor on which the window currently resides.
@@ -877,17 +1741,187 @@ impl Window {
     pub fn primary_monitor(&self) -> Option<MonitorHandle> {
         self.window.primary_monitor()
     }
+
+    /// Returns whether the window is currently presented with a variable refresh rate, so that
+    /// its frames are shown as soon as they're presented rather than on the next refresh.
+    ///
+    /// The monitor of the window has to [support it](MonitorHandle::supports_variable_refresh),
+    /// and most systems only enable it for the fullscreen windows.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The window also needs the `_VARIABLE_REFRESH` property, which Mesa sets on the
+    ///   windows it presents to.
+    /// - **Wayland:** Follows the adaptive sync policy of the output given by KDE's
+    ///   `kde_output_device_v2` protocol.
+    /// - **iOS / Android / Web:** Always returns `false`.
+    #[inline]
+    pub fn is_vrr_active(&self) -> bool {
+        self.window.is_vrr_active()
+    }
+}
+
+/// Native handle functions.
+///
+/// The handles borrow the window, so it can't be dropped while they're used. The surfaces the
+/// graphics APIs create from them still outlive them, and have to be destroyed along with the
+/// window, or once its surface is gone.
+impl Window {
+    /// Returns the native handle of the window, for the graphics APIs to create their surfaces.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / X11:** Returns [`HandleError::Destroyed`] once the window was destroyed by
+    ///   the system, as along with its parent.
+    /// - **Android:** Returns [`HandleError::Unavailable`] before [`Event::Resumed`] and after
+    ///   [`Event::Suspended`], while there's no native window.
+    ///
+    /// [`Event::Resumed`]: crate::event::Event::Resumed
+    /// [`Event::Suspended`]: crate::event::Event::Suspended
+    #[inline]
+    pub fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        self.window.window_handle().map(|raw| WindowHandle {
+            raw,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the native handle of the display of the window, which is the handle of the
+    /// window with only the fields of the display set.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows:** Holds the `HINSTANCE` of the window, and returns
+    ///   [`HandleError::Destroyed`] once the window was destroyed by the system.
+    /// - **X11 / Wayland:** Holds the connection to the server.
+    /// - **macOS / iOS / Android / Web:** The handle is empty, as there's no display.
+    #[inline]
+    pub fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        self.window.display_handle().map(|raw| DisplayHandle {
+            raw,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// The native handle of a window or of a [`ChildSurface`], borrowed from it.
+///
+/// Implements [`HasRawWindowHandle`] for the graphics crates taking the raw handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowHandle<'a> {
+    raw: RawWindowHandle,
+    _marker: PhantomData<&'a Window>,
+}
+
+impl WindowHandle<'_> {
+    /// Returns the raw handle, which is only valid while the window is borrowed.
+    #[inline]
+    pub fn raw(&self) -> RawWindowHandle {
+        self.raw
+    }
 }
 
-unsafe impl raw_window_handle::HasRawWindowHandle for Window {
-    /// Returns a `raw_window_handle::RawWindowHandle` for the Window
+unsafe impl HasRawWindowHandle for WindowHandle<'_> {
+    #[inline]
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        self.raw
+    }
+}
+
+/// The native handle of the display of a window, borrowed from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DisplayHandle<'a> {
+    raw: RawWindowHandle,
+    _marker: PhantomData<&'a Window>,
+}
+
+impl DisplayHandle<'_> {
+    /// Returns the raw handle, which is only valid while the window is borrowed.
+    #[inline]
+    pub fn raw(&self) -> RawWindowHandle {
+        self.raw
+    }
+}
+
+/// A surface positioned within a window, with its own native handle.
+///
+/// The graphics APIs present to it independently of the window, as the hardware video decoders
+/// of the media players do while the interface is drawn on the window. It's drawn above the
+/// window, and the input over it goes to the window. It's destroyed once dropped, or along with
+/// the window.
+///
+/// ## Platform-specific
+///
+/// - **Windows:** A child `HWND` of the window.
+/// - **X11:** A child window of the window, with its visual.
+/// - **Wayland:** A `wl_subsurface` of the window, which is committed without waiting for the
+///   commits of the window. It's only shown once a buffer is attached to it.
+/// - **macOS:** A subview of the view of the window.
+/// - **iOS / Android / Web:** Unsupported.
+pub struct ChildSurface {
+    pub(crate) surface: platform_impl::ChildSurface,
+}
+
+impl fmt::Debug for ChildSurface {
+    fn fmt(&self, fmtr: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmtr.pad("ChildSurface { .. }")
+    }
+}
+
+impl ChildSurface {
+    /// Creates a surface within the window, with its top-left corner at the position relative to
+    /// the top-left corner of the client area of the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Returns [`ExternalError::NotSupported`] if the compositor has no
+    ///   `wl_subcompositor`.
+    /// - **iOS / Android / Web:** Always returns [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn new<T: 'static>(
+        event_loop: &EventLoopWindowTarget<T>,
+        window: &Window,
+        position: Position,
+        size: Size,
+    ) -> Result<ChildSurface, ExternalError> {
+        platform_impl::ChildSurface::new(&event_loop.p, &window.window, position, size)
+            .map(|surface| ChildSurface { surface })
+    }
+
+    /// Moves the top-left corner of the surface to the position relative to the top-left corner
+    /// of the client area of the window.
     ///
     /// ## Platform-specific
     ///
-    /// - **Android:** Only available after receiving the Resumed event and before Suspended. *If you*
-    /// *try to get the handle outside of that period, this function will panic*!
-    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
-        self.window.raw_window_handle()
+    /// - **Wayland:** Applied once the window commits its next frame.
+    #[inline]
+    pub fn set_position(&self, position: Position) {
+        self.surface.set_position(position)
+    }
+
+    /// Resizes the surface.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Applied once the surface commits its next frame, with `wp_viewporter`.
+    ///   Without it, the size of the surface is the one of the buffers attached to it.
+    #[inline]
+    pub fn set_size(&self, size: Size) {
+        self.surface.set_size(size)
+    }
+
+    /// Returns the native handle of the surface, for the graphics APIs to create their
+    /// surfaces. The display handle is the one of the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / X11:** Returns [`HandleError::Destroyed`] once the window was destroyed.
+    #[inline]
+    pub fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        self.surface.window_handle().map(|raw| WindowHandle {
+            raw,
+            _marker: PhantomData,
+        })
     }
 }
 
@@ -956,6 +1990,73 @@ impl Default for CursorIcon {
     }
 }
 
+/// A cursor made of an image, created with [`EventLoopWindowTarget::create_custom_cursor`] or
+/// [`EventLoopWindowTarget::create_animated_cursor`] and shown with [`Window::set_cursor`].
+///
+/// The cursor can be shared by many windows, cloning it is cheap.
+#[derive(Clone)]
+pub struct CustomCursor {
+    pub(crate) inner: platform_impl::CustomCursor,
+}
+
+impl fmt::Debug for CustomCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("CustomCursor { .. }")
+    }
+}
+
+/// A frame of an animated cursor, shown for its delay before the next frame.
+#[derive(Debug, Clone)]
+// Only read by the backends with custom cursors
+#[cfg_attr(
+    not(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_arch = "wasm32",
+        x11_platform,
+        wayland_platform
+    )),
+    allow(dead_code)
+)]
+pub struct CursorFrame {
+    pub(crate) image: RgbaIcon,
+    pub(crate) hotspot: PhysicalPosition<u32>,
+    pub(crate) delay: Duration,
+}
+
+impl CursorFrame {
+    /// Creates a frame from an image of `width` by `height` pixels in RGBA order, with the
+    /// `hotspot` being the pixel of the image pointing at the position of the cursor.
+    ///
+    /// The hotspot is clamped to the image, and the delay is at least a millisecond.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot: PhysicalPosition<u32>,
+        delay: Duration,
+    ) -> Result<Self, BadIcon> {
+        Ok(CursorFrame {
+            image: RgbaIcon::from_rgba(rgba, width, height)?,
+            hotspot: PhysicalPosition::new(
+                hotspot.x.min(width.saturating_sub(1)),
+                hotspot.y.min(height.saturating_sub(1)),
+            ),
+            delay: delay.max(Duration::from_millis(1)),
+        })
+    }
+}
+
+/// The theme the cursor icons are loaded from, and their size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorTheme {
+    /// The name of the theme, or `None` when the cursors of the platform don't come from themes
+    /// picked by name.
+    pub name: Option<String>,
+    /// The size of the cursors in logical pixels.
+    pub size: u32,
+}
+
 /// Fullscreen modes.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Fullscreen {
@@ -963,6 +2064,22 @@ pub enum Fullscreen {
 
     /// Providing `None` to `Borderless` will fullscreen on the current monitor.
     Borderless(Option<MonitorHandle>),
+
+    /// Covers all the given monitors with a borderless window, for video walls and the setups of
+    /// several monitors. An empty list spans all the monitors.
+    ///
+    /// The window covers the smallest rectangle containing the monitors, so the parts of it
+    /// between the monitors which aren't adjacent aren't shown.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The window is undecorated and moved over the monitors, without the fullscreen
+    ///   state, so the window manager may keep it from covering its panels.
+    /// - **Wayland / macOS / iOS / Web:** Unsupported, as the windows are fullscreen on a single
+    ///   monitor. The window is fullscreen on the first monitor instead, or on the current one
+    ///   when the list is empty.
+    /// - **Android:** Unsupported.
+    Span(Vec<MonitorHandle>),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -991,3 +2108,256 @@ impl Default for UserAttentionType {
         UserAttentionType::Informational
     }
 }
+
+/// How the title bar of the decorations is drawn, given in [`WindowBuilder::with_titlebar_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TitlebarStyle {
+    /// The title bar of the system.
+    Visible,
+    /// The title bar without the title, its buttons kept, taking the background of the window.
+    Transparent,
+    /// No title bar, the content of the window extended over its place.
+    Hidden,
+    /// The content of the window extended under the title bar, only its buttons drawn over it.
+    ExtendContent,
+}
+
+impl Default for TitlebarStyle {
+    fn default() -> Self {
+        TitlebarStyle::Visible
+    }
+}
+
+/// The progress shown on the taskbar button or the dock icon, given with
+/// [`Window::set_progress`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProgressState {
+    /// No progress is shown.
+    None,
+    /// The operation is ongoing, with an unknown progress.
+    Indeterminate,
+    /// The progress of the operation, from `0.0` to `1.0`.
+    Normal(f64),
+    /// The progress of the paused operation.
+    Paused(f64),
+    /// The progress of the failed operation.
+    Error(f64),
+}
+
+/// The ratio of the width to the height of the inner size, such as `16:9`, given with
+/// [`Window::set_aspect_ratio`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    width: u32,
+    height: u32,
+}
+
+impl Ratio {
+    /// Creates the ratio of `width` to `height`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `width` or `height` is zero.
+    pub fn new(width: u32, height: u32) -> Self {
+        assert!(
+            width != 0 && height != 0,
+            "the terms of an aspect ratio must be non-zero"
+        );
+        Ratio { width, height }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// The arithmetic of the aspect ratios, for the backends enforcing them themselves.
+#[cfg(any(target_os = "windows", wayland_platform, test))]
+mod aspect_ratio {
+    use super::Ratio;
+
+    impl Ratio {
+        /// The height of the ratio for `width`.
+        pub(crate) fn height_for(&self, width: u32) -> u32 {
+            (width as f64 * self.height as f64 / self.width as f64).round() as u32
+        }
+
+        /// The width of the ratio for `height`.
+        pub(crate) fn width_for(&self, height: u32) -> u32 {
+            (height as f64 * self.width as f64 / self.height as f64).round() as u32
+        }
+
+        /// The largest size of the ratio within `width` and `height`.
+        #[cfg(any(wayland_platform, test))]
+        pub(crate) fn fit(&self, width: u32, height: u32) -> (u32, u32) {
+            let fitted_height = self.height_for(width);
+            if fitted_height <= height {
+                (width, fitted_height)
+            } else {
+                (self.width_for(height), height)
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn ratio_rounds_to_nearest() {
+            let ratio = Ratio::new(16, 9);
+            // 562.5 and 888.9 are rounded to the nearest pixel.
+            assert_eq!(ratio.height_for(1000), 563);
+            assert_eq!(ratio.width_for(500), 889);
+            assert_eq!(ratio.height_for(1920), 1080);
+            assert_eq!(ratio.width_for(1080), 1920);
+
+            let ratio = Ratio::new(4, 3);
+            assert_eq!(ratio.height_for(1001), 751);
+            assert_eq!(ratio.width_for(1), 1);
+        }
+
+        #[test]
+        fn ratio_fits_within_size() {
+            let ratio = Ratio::new(16, 9);
+            // Limited by the width.
+            assert_eq!(ratio.fit(1000, 1000), (1000, 563));
+            // Limited by the height.
+            assert_eq!(ratio.fit(1000, 500), (889, 500));
+            // Exactly of the ratio.
+            assert_eq!(ratio.fit(1920, 1080), (1920, 1080));
+        }
+    }
+}
+
+/// The purpose of the text being edited, given with [`Window::set_ime_purpose`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImePurpose {
+    /// Any text.
+    Normal,
+    /// A password, which is hidden and not remembered.
+    Password,
+    /// A numeric password, which is hidden and not remembered.
+    Pin,
+    /// Only digits.
+    Digits,
+    /// A number, with its sign and decimal separator.
+    Number,
+    /// A phone number.
+    Phone,
+    /// An email address.
+    Email,
+    /// An URL.
+    Url,
+    /// The name of a person.
+    Name,
+    /// The input of a terminal, which gets the keys as they are.
+    Terminal,
+}
+
+bitflags! {
+    /// A set of the edges of a window, such as the ones it's tiled on, given in
+    /// [`WindowEvent::TiledStateChanged`].
+    ///
+    /// [`WindowEvent::TiledStateChanged`]: crate::event::WindowEvent::TiledStateChanged
+    #[derive(Default)]
+    pub struct EdgeFlags: u32 {
+        /// The top edge.
+        const TOP = 1 << 0;
+        /// The bottom edge.
+        const BOTTOM = 1 << 1;
+        /// The left edge.
+        const LEFT = 1 << 2;
+        /// The right edge.
+        const RIGHT = 1 << 3;
+    }
+}
+
+/// How a new window is placed, given in [`WindowBuilder::with_placement`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Placement {
+    /// The window is centered on the monitor it appears on.
+    CenterOnMonitor,
+    /// The window is centered on its owner, set with [`WindowBuilder::with_owner`]. The windows
+    /// without an owner are centered on their monitor.
+    CenterOnParent,
+    /// The window is centered on the cursor, on the monitor of the cursor.
+    AtCursor,
+    /// The window is placed below and to the right of the window last cascaded, starting over at
+    /// the top left of the work area once it doesn't fit anymore.
+    Cascade,
+}
+
+/// The arithmetic of the placements, for the backends placing the windows themselves.
+#[cfg(any(target_os = "windows", x11_platform))]
+mod placement {
+    use super::*;
+
+    impl Placement {
+        /// The position of the window of outer `size` centered on `center`, moved to be kept within
+        /// the work area `bounds`. The windows larger than the work area are kept at its top left.
+        /// They are all in physical pixels of the screen.
+        pub(crate) fn center_on(
+            center: PhysicalPosition<i32>,
+            size: PhysicalSize<u32>,
+            bounds: (PhysicalPosition<i32>, PhysicalSize<u32>),
+        ) -> PhysicalPosition<i32> {
+            let keep = |center: i32, length: u32, start: i32, bounds_length: u32| {
+                let position = center - length as i32 / 2;
+                position
+                    .min(start + bounds_length as i32 - length as i32)
+                    .max(start)
+            };
+            PhysicalPosition::new(
+                keep(center.x, size.width, bounds.0.x, bounds.1.width),
+                keep(center.y, size.height, bounds.0.y, bounds.1.height),
+            )
+        }
+
+        /// The position of the window of outer `size` cascaded by `step` from the window last
+        /// cascaded at `last`, starting over at the top left of the work area `bounds` once it
+        /// doesn't fit. They are all in physical pixels of the screen.
+        #[cfg(x11_platform)]
+        pub(crate) fn cascade_from(
+            last: Option<PhysicalPosition<i32>>,
+            step: i32,
+            size: PhysicalSize<u32>,
+            bounds: (PhysicalPosition<i32>, PhysicalSize<u32>),
+        ) -> PhysicalPosition<i32> {
+            let (start, bounds_size) = bounds;
+            let position = match last {
+                Some(last) => PhysicalPosition::new(last.x + step, last.y + step),
+                None => return start,
+            };
+            let fits = position.x >= start.x
+                && position.y >= start.y
+                && position.x + size.width as i32 <= start.x + bounds_size.width as i32
+                && position.y + size.height as i32 <= start.y + bounds_size.height as i32;
+            if fits {
+                position
+            } else {
+                start
+            }
+        }
+    }
+}
+
+/// The effect shown behind the transparent parts of a window, given in [`Window::set_backdrop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backdrop {
+    /// No effect, what's behind the window shows through as is.
+    None,
+    /// What's behind the window is blurred.
+    Blur,
+    /// The blur tinted with the color of the theme and grained, as the acrylic of Windows.
+    Acrylic,
+    /// The desktop wallpaper tinted with the color of the theme, as the mica of Windows 11.
+    Mica,
+    /// The blur mixed with the color of the theme, as the vibrancy of macOS.
+    Vibrancy,
+}
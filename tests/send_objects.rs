@@ -18,6 +18,13 @@ fn window_send() {
     needs_send::<winit::window::Window>();
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn child_surface_send() {
+    // ensures that `winit::ChildSurface` implements `Send`
+    needs_send::<winit::window::ChildSurface>();
+}
+
 #[test]
 fn ids_send() {
     // ensures that the various `..Id` types implement `Send`
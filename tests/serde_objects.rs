@@ -4,9 +4,11 @@ use serde::{Deserialize, Serialize};
 use winit::{
     dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
     event::{
-        ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
-        VirtualKeyCode,
+        AxisSource, ElementState, GesturePhase, KeyboardInput, ModifiersState, MouseButton,
+        MouseScrollDelta, PenPhase, PenTool, TouchPhase, VirtualKeyCode,
     },
+    gamepad::{GamepadAxis, GamepadButton, GamepadEvent, GamepadId},
+    monitor::{ColorCapabilities, ColorPrimaries, MonitorEvent, MonitorIdentity, TransferFunction},
     window::CursorIcon,
 };
 
@@ -22,13 +24,34 @@ fn window_serde() {
 fn events_serde() {
     needs_serde::<KeyboardInput>();
     needs_serde::<TouchPhase>();
+    needs_serde::<GesturePhase>();
+    needs_serde::<PenPhase>();
+    needs_serde::<PenTool>();
     needs_serde::<ElementState>();
     needs_serde::<MouseButton>();
     needs_serde::<MouseScrollDelta>();
+    needs_serde::<AxisSource>();
     needs_serde::<VirtualKeyCode>();
     needs_serde::<ModifiersState>();
 }
 
+#[test]
+fn gamepad_serde() {
+    needs_serde::<GamepadId>();
+    needs_serde::<GamepadEvent>();
+    needs_serde::<GamepadButton>();
+    needs_serde::<GamepadAxis>();
+}
+
+#[test]
+fn monitor_serde() {
+    needs_serde::<MonitorEvent>();
+    needs_serde::<ColorCapabilities>();
+    needs_serde::<ColorPrimaries>();
+    needs_serde::<TransferFunction>();
+    needs_serde::<MonitorIdentity>();
+}
+
 #[test]
 fn dpi_serde() {
     needs_serde::<LogicalPosition<f64>>();
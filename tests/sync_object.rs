@@ -7,3 +7,10 @@ fn window_sync() {
     // ensures that `winit::Window` implements `Sync`
     needs_sync::<winit::window::Window>();
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn child_surface_sync() {
+    // ensures that `winit::ChildSurface` implements `Sync`
+    needs_sync::<winit::window::ChildSurface>();
+}